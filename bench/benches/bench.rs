@@ -17,6 +17,7 @@ criterion_group!(benches,
 	lightning::routing::router::benches::generate_mpp_routes_with_nonlinear_probabilistic_scorer,
 	lightning::routing::router::benches::generate_large_mpp_routes_with_nonlinear_probabilistic_scorer,
 	lightning::sign::benches::bench_get_secure_random_bytes,
+	lightning::util::message_signing::benches::bench_verify_message_signatures,
 	lightning::ln::channelmanager::bench::bench_sends,
 	lightning_persister::fs_store::bench::bench_sends,
 	lightning_rapid_gossip_sync::bench::bench_reading_full_graph_from_file,
@@ -78,6 +78,8 @@ echo -e "\n\nChecking and testing lightning with features"
 cargo test -p lightning --verbose --color always --features dnssec
 cargo check -p lightning --verbose --color always --features dnssec
 cargo doc -p lightning --document-private-items --features dnssec
+cargo check -p lightning --verbose --color always --features channel_state_replication
+cargo test -p lightning --verbose --color always --features serde anchor_channel_reserves
 
 echo -e "\n\nChecking and testing Block Sync Clients with features"
 
@@ -27,6 +27,9 @@ GEN_TEST base32
 GEN_TEST fromstr_to_netaddress
 GEN_TEST feature_flags
 GEN_TEST lsps_message
+GEN_TEST lossy_read
+GEN_TEST recover_pk
+GEN_TEST sigrec_decode
 
 GEN_TEST msg_accept_channel msg_targets::
 GEN_TEST msg_announcement_signatures msg_targets::
@@ -70,6 +70,7 @@ use lightning::types::payment::{PaymentHash, PaymentPreimage, PaymentSecret};
 use lightning::util::config::UserConfig;
 use lightning::util::hash_tables::*;
 use lightning::util::logger::Logger;
+use lightning::util::message_signing::MessageSigningKeyId;
 use lightning::util::ser::{LengthReadable, ReadableArgs, Writeable, Writer};
 use lightning::util::test_channel_signer::{EnforcementState, TestChannelSigner};
 
@@ -102,9 +103,9 @@ impl FeeEstimator for FuzzEstimator {
 		// always return a HighPriority feerate here which is >= the maximum Normal feerate and a
 		// Background feerate which is <= the minimum Normal feerate.
 		match conf_target {
-			ConfirmationTarget::MaximumFeeEstimate | ConfirmationTarget::UrgentOnChainSweep => {
-				MAX_FEE
-			},
+			ConfirmationTarget::MaximumFeeEstimate
+			| ConfirmationTarget::UrgentOnChainSweep
+			| ConfirmationTarget::MaximumExogenousFeeReserve => MAX_FEE,
 			ConfirmationTarget::ChannelCloseMinimum
 			| ConfirmationTarget::AnchorChannelFee
 			| ConfirmationTarget::MinAllowedAnchorChannelRemoteFee
@@ -369,6 +370,16 @@ impl NodeSigner for KeyProvider {
 	fn sign_message(&self, msg: &[u8]) -> Result<String, ()> {
 		Ok(lightning::util::message_signing::sign(msg, &self.node_secret))
 	}
+
+	fn get_message_signing_pubkey(&self, _key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		unreachable!()
+	}
+
+	fn sign_message_with_derived_key(
+		&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		unreachable!()
+	}
 }
 
 impl SignerProvider for KeyProvider {
@@ -1607,11 +1618,11 @@ pub fn do_test<Out: Output>(data: &[u8], underlying_out: Out, anchors: bool) {
 				if fee_est_a.ret_val.fetch_add(250, atomic::Ordering::AcqRel) + 250 > max_feerate {
 					fee_est_a.ret_val.store(max_feerate, atomic::Ordering::Release);
 				}
-				nodes[0].maybe_update_chan_fees();
+				nodes[0].process_stale_feerate_updates();
 			},
 			0x81 => {
 				fee_est_a.ret_val.store(253, atomic::Ordering::Release);
-				nodes[0].maybe_update_chan_fees();
+				nodes[0].process_stale_feerate_updates();
 			},
 
 			0x84 => {
@@ -1622,11 +1633,11 @@ pub fn do_test<Out: Output>(data: &[u8], underlying_out: Out, anchors: bool) {
 				if fee_est_b.ret_val.fetch_add(250, atomic::Ordering::AcqRel) + 250 > max_feerate {
 					fee_est_b.ret_val.store(max_feerate, atomic::Ordering::Release);
 				}
-				nodes[1].maybe_update_chan_fees();
+				nodes[1].process_stale_feerate_updates();
 			},
 			0x85 => {
 				fee_est_b.ret_val.store(253, atomic::Ordering::Release);
-				nodes[1].maybe_update_chan_fees();
+				nodes[1].process_stale_feerate_updates();
 			},
 
 			0x88 => {
@@ -1637,11 +1648,11 @@ pub fn do_test<Out: Output>(data: &[u8], underlying_out: Out, anchors: bool) {
 				if fee_est_c.ret_val.fetch_add(250, atomic::Ordering::AcqRel) + 250 > max_feerate {
 					fee_est_c.ret_val.store(max_feerate, atomic::Ordering::Release);
 				}
-				nodes[2].maybe_update_chan_fees();
+				nodes[2].process_stale_feerate_updates();
 			},
 			0x89 => {
 				fee_est_c.ret_val.store(253, atomic::Ordering::Release);
-				nodes[2].maybe_update_chan_fees();
+				nodes[2].process_stale_feerate_updates();
 			},
 
 			0xa0 => {
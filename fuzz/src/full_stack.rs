@@ -65,6 +65,7 @@ use lightning::util::config::{ChannelConfig, UserConfig};
 use lightning::util::errors::APIError;
 use lightning::util::hash_tables::*;
 use lightning::util::logger::Logger;
+use lightning::util::message_signing::MessageSigningKeyId;
 use lightning::util::ser::{Readable, Writeable};
 use lightning::util::test_channel_signer::{EnforcementState, TestChannelSigner};
 
@@ -440,6 +441,16 @@ impl NodeSigner for KeyProvider {
 		Ok(lightning::util::message_signing::sign(msg, &self.node_secret))
 	}
 
+	fn get_message_signing_pubkey(&self, _key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		unreachable!()
+	}
+
+	fn sign_message_with_derived_key(
+		&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		unreachable!()
+	}
+
 	fn get_peer_storage_key(&self) -> PeerStorageKey {
 		PeerStorageKey { inner: [42; 32] }
 	}
@@ -535,6 +546,11 @@ pub fn do_test(mut data: &[u8], logger: &Arc<dyn Logger>) {
 	} else {
 		return;
 	};
+	if let Err(errors) = config.validate() {
+		if errors.iter().any(|error| error.is_fatal()) {
+			return;
+		}
+	}
 
 	let input = Arc::new(InputData {
 		data: data.to_vec(),
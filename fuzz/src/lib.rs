@@ -33,6 +33,7 @@ pub mod full_stack;
 pub mod indexedmap;
 pub mod invoice_deser;
 pub mod invoice_request_deser;
+pub mod lossy_read;
 pub mod lsps_message;
 pub mod offer_deser;
 pub mod onion_hop_data;
@@ -40,8 +41,10 @@ pub mod onion_message;
 pub mod peer_crypt;
 pub mod process_network_graph;
 pub mod process_onion_failure;
+pub mod recover_pk;
 pub mod refund_deser;
 pub mod router;
+pub mod sigrec_decode;
 pub mod zbase32;
 
 pub mod msg_targets;
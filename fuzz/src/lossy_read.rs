@@ -0,0 +1,121 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Test that `read_lossy` of `NetworkGraph` and `ProbabilisticScorer` recovers consistent objects
+//! from real framed data which is truncated and has bits flipped as directed by the fuzz input.
+
+use bitcoin::network::Network;
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+use lightning::routing::gossip::{NetworkGraph, NodeId};
+use lightning::routing::router::{Path, RouteHop};
+use lightning::routing::scoring::{
+	ProbabilisticScorer, ProbabilisticScoringDecayParameters, ScoreUpdate,
+};
+use lightning::types::features::{ChannelFeatures, NodeFeatures};
+
+use crate::utils::test_logger;
+
+use core::time::Duration;
+
+const NUM_NODES: u8 = 8;
+
+fn node_pubkey(idx: u8) -> PublicKey {
+	let secp_ctx = Secp256k1::signing_only();
+	PublicKey::from_secret_key(&secp_ctx, &SecretKey::from_slice(&[idx + 1; 32]).unwrap())
+}
+
+#[inline]
+pub fn do_test<Out: test_logger::Output>(data: &[u8], out: Out) {
+	let logger = test_logger::TestLogger::new("".to_owned(), out);
+	let network_graph = NetworkGraph::new(Network::Bitcoin, &logger);
+	for idx in 1..NUM_NODES {
+		let node_id_1 = NodeId::from_pubkey(&node_pubkey(idx - 1));
+		let node_id_2 = NodeId::from_pubkey(&node_pubkey(idx));
+		let features = ChannelFeatures::empty();
+		network_graph
+			.add_channel_from_partial_announcement(
+				idx as u64,
+				Some(100_000),
+				0,
+				features,
+				node_id_1,
+				node_id_2,
+			)
+			.unwrap();
+	}
+
+	if data.is_empty() {
+		return;
+	}
+	let read_scorer = data[0] & 1 == 1;
+	let decay_params = ProbabilisticScoringDecayParameters::default();
+	let mut framed = Vec::new();
+	if read_scorer {
+		let mut scorer = ProbabilisticScorer::new(decay_params, &network_graph, &logger);
+		let hops = (1..NUM_NODES)
+			.map(|idx| RouteHop {
+				pubkey: node_pubkey(idx),
+				node_features: NodeFeatures::empty(),
+				short_channel_id: idx as u64,
+				channel_features: ChannelFeatures::empty(),
+				fee_msat: 1_000,
+				cltv_expiry_delta: 0,
+				maybe_announced_channel: true,
+			})
+			.collect();
+		let path = Path { hops, blinded_tail: None };
+		scorer.payment_path_failed(&path, NUM_NODES as u64 - 1, Duration::from_secs(1));
+		scorer.write_framed(&mut framed).unwrap();
+	} else {
+		network_graph.write_framed(&mut framed).unwrap();
+	}
+
+	// Each full chunk of the remaining input flips a bit of the framed data, while a trailing
+	// partial chunk truncates it.
+	for chunk in data[1..].chunks(3) {
+		if chunk.len() == 3 {
+			let pos = u16::from_be_bytes([chunk[0], chunk[1]]) as usize % framed.len();
+			framed[pos] ^= 1 << (chunk[2] % 8);
+		} else {
+			let len = chunk.iter().fold(0, |len, byte| (len << 8) | *byte as usize);
+			framed.truncate(len % (framed.len() + 1));
+		}
+	}
+	let undamaged = data.len() == 1;
+
+	let mut reader = &framed[..];
+	if read_scorer {
+		let result =
+			ProbabilisticScorer::read_lossy(&mut reader, decay_params, &network_graph, &logger);
+		if let Ok((scorer, report)) = result {
+			assert!(report.damage.is_none() || reader.is_empty());
+			assert!(!undamaged || report.is_complete());
+			scorer.write_framed(&mut Vec::new()).unwrap();
+		} else {
+			assert!(!undamaged);
+		}
+	} else if let Ok((graph, report)) = NetworkGraph::read_lossy(&mut reader, &logger) {
+		assert!(report.damage.is_none() || reader.is_empty());
+		assert!(!undamaged || (report.is_complete() && graph == network_graph));
+		// Writing the recovered graph checks the consistency of its nodes and channels.
+		graph.write_framed(&mut Vec::new()).unwrap();
+	} else {
+		assert!(!undamaged);
+	}
+}
+
+pub fn lossy_read_test<Out: test_logger::Output>(data: &[u8], out: Out) {
+	do_test(data, out);
+}
+
+#[no_mangle]
+pub extern "C" fn lossy_read_run(data: *const u8, datalen: usize) {
+	do_test(unsafe { std::slice::from_raw_parts(data, datalen) }, test_logger::DevNull {});
+}
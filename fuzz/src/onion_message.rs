@@ -29,6 +29,7 @@ use lightning::sign::{
 };
 use lightning::types::features::InitFeatures;
 use lightning::util::logger::Logger;
+use lightning::util::message_signing::MessageSigningKeyId;
 use lightning::util::ser::{LengthReadable, Writeable, Writer};
 use lightning::util::test_channel_signer::TestChannelSigner;
 
@@ -281,6 +282,16 @@ impl NodeSigner for KeyProvider {
 		Ok(lightning::util::message_signing::sign(msg, &self.node_secret))
 	}
 
+	fn get_message_signing_pubkey(&self, _key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		unreachable!()
+	}
+
+	fn sign_message_with_derived_key(
+		&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		unreachable!()
+	}
+
 	fn get_peer_storage_key(&self) -> PeerStorageKey {
 		unreachable!()
 	}
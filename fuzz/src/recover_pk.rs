@@ -0,0 +1,43 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+use bitcoin::secp256k1::{Secp256k1, VerifyOnly};
+use lightning::util::message_signing;
+
+use crate::utils::test_logger;
+
+thread_local! {
+	static SECP_CTX: Secp256k1<VerifyOnly> = Secp256k1::verification_only();
+}
+
+#[inline]
+pub fn do_test(data: &[u8]) {
+	// The first byte determines the length of the message, the remainder is the signature.
+	if data.is_empty() {
+		return;
+	}
+	let msg_len = core::cmp::min(data[0] as usize, data.len() - 1);
+	let (msg, sig) = data[1..].split_at(msg_len);
+	if let Ok(sig) = std::str::from_utf8(sig) {
+		let res =
+			SECP_CTX.with(|secp_ctx| message_signing::recover_pk_with_secp_ctx(secp_ctx, msg, sig));
+		if sig.len() > message_signing::MAX_SIGNATURE_LENGTH {
+			assert!(res.is_err());
+		}
+	}
+}
+
+pub fn recover_pk_test<Out: test_logger::Output>(data: &[u8], _out: Out) {
+	do_test(data);
+}
+
+#[no_mangle]
+pub extern "C" fn recover_pk_run(data: *const u8, datalen: usize) {
+	do_test(unsafe { std::slice::from_raw_parts(data, datalen) });
+}
@@ -254,6 +254,7 @@ pub fn do_test<Out: test_logger::Output>(data: &[u8], out: Out) {
 							channel_shutdown_state: Some(ChannelShutdownState::NotShuttingDown),
 							pending_inbound_htlcs: Vec::new(),
 							pending_outbound_htlcs: Vec::new(),
+							exogenous_fee_reserve_recommended_sat: None,
 						});
 					}
 					Some(&$first_hops_vec[..])
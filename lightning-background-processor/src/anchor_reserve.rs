@@ -0,0 +1,297 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Periodic re-evaluation of the anchor channel reserve by the background processor.
+
+use bitcoin::{Amount, OutPoint};
+
+use lightning::events::bump_transaction::Utxo;
+#[cfg(feature = "std")]
+use lightning::util::anchor_channel_reserves::{
+	get_pending_reserve_releases, get_reserve_per_channel, AnchorChannelReserveCheck,
+	AnchorReserveReport,
+};
+use lightning::util::anchor_channel_reserves::{
+	AnchorChannelReserveContext, PendingReserveRelease,
+};
+#[cfg(feature = "std")]
+use lightning::util::facade::{DynChainMonitor, DynChannelManager};
+#[cfg(feature = "std")]
+use lightning::util::logger::{Level, Logger};
+
+#[cfg(feature = "std")]
+use core::ops::Deref;
+use core::sync::atomic::{AtomicBool, Ordering};
+use core::time::Duration;
+
+use alloc::sync::Arc;
+
+#[cfg(not(feature = "std"))]
+use alloc::boxed::Box;
+#[cfg(not(feature = "std"))]
+use alloc::vec::Vec;
+
+/// The status of the anchor channel reserve, as determined by an [`AnchorReserveMonitor`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorReserveStatus {
+	/// The number of anchor channels relying on the reserve.
+	pub num_anchor_channels: u64,
+	/// The number of anchor channels the reserve is sufficient to support.
+	pub num_supportable_channels: u64,
+	/// The reserve required per anchor channel.
+	pub reserve_per_channel: Amount,
+	/// The total value of the UTXOs provided for the reserve.
+	pub total_utxo_value: Amount,
+	/// The closed anchor channels which are included in [`Self::num_anchor_channels`] until the
+	/// given heights, after which they no longer rely on the reserve.
+	pub pending_releases: Vec<PendingReserveRelease>,
+	/// The UTXOs spent by claims whose confirmation was reorged out, which are excluded from the
+	/// reserve until the claims confirm again. See
+	/// [`AnchorReserveMonitor::with_reorged_claim_utxos`].
+	pub reorged_claim_utxos: Vec<OutPoint>,
+}
+
+impl AnchorReserveStatus {
+	/// Returns whether the reserve is sufficient to support all anchor channels relying on it.
+	pub fn is_sufficient(&self) -> bool {
+		self.num_supportable_channels >= self.num_anchor_channels
+	}
+}
+
+impl_writeable_tlv_based!(AnchorReserveStatus, {
+	(0, num_anchor_channels, required),
+	(2, num_supportable_channels, required),
+	(4, reserve_per_channel, required),
+	(6, total_utxo_value, required),
+	(8, pending_releases, required_vec),
+	(10, reorged_claim_utxos, required_vec),
+});
+
+/// A handle to request an [`AnchorReserveReport`] to be logged by an [`AnchorReserveMonitor`],
+/// obtained via [`AnchorReserveMonitor::report_trigger`].
+///
+/// [`AnchorReserveReport`]: lightning::util::anchor_channel_reserves::AnchorReserveReport
+#[derive(Clone)]
+pub struct AnchorReserveReportTrigger {
+	report_requested: Arc<AtomicBool>,
+}
+
+impl AnchorReserveReportTrigger {
+	/// Requests the background processor to log an [`AnchorReserveReport`] at the
+	/// [`Level::Info`] level once it next wakes up, independently of the check interval of the
+	/// [`AnchorReserveMonitor`].
+	///
+	/// Multiple requests made before the report is logged result in a single report.
+	///
+	/// [`AnchorReserveReport`]: lightning::util::anchor_channel_reserves::AnchorReserveReport
+	/// [`Level::Info`]: lightning::util::logger::Level::Info
+	pub fn request_report(&self) {
+		self.report_requested.store(true, Ordering::Release);
+	}
+}
+
+/// Periodically re-evaluates whether the anchor channel reserve is sufficient to support the
+/// anchor channels of a [`ChannelManager`] and [`ChainMonitor`], when provided to the
+/// `BackgroundProcessor` or [`process_events_async`].
+///
+/// Each check logs the resulting [`AnchorReserveStatus`] and calls the status callback if
+/// [`AnchorReserveStatus::is_sufficient`] changed since the previous check. The reserve is assumed
+/// to be sufficient on startup, i.e., the callback is only called on the first check if it is not.
+///
+/// Optionally, the negotiation of anchor channels by the [`ChannelManager`] can be disabled and
+/// re-enabled depending on the reserve, see [`Self::with_anchor_negotiation_thresholds`].
+///
+/// Counting the anchor channels requires the `std` feature, without which this can't be created.
+///
+/// Note that the background processor wakes up at most every few seconds, which bounds how often
+/// the reserve is checked regardless of the configured interval.
+///
+/// [`ChannelManager`]: lightning::ln::channelmanager::ChannelManager
+/// [`ChainMonitor`]: lightning::chain::chainmonitor::ChainMonitor
+/// [`process_events_async`]: crate::process_events_async
+#[cfg_attr(not(feature = "std"), allow(dead_code))]
+pub struct AnchorReserveMonitor {
+	context: AnchorChannelReserveContext,
+	utxo_source: Box<dyn Fn() -> Result<Vec<Utxo>, ()> + Send>,
+	status_callback: Box<dyn Fn(AnchorReserveStatus) + Send>,
+	pub(crate) check_interval: Duration,
+	is_sufficient: bool,
+	anchor_negotiation_thresholds: Option<(u64, u64)>,
+	reorged_claim_utxo_source: Option<Box<dyn Fn() -> Vec<OutPoint> + Send>>,
+	report_requested: Arc<AtomicBool>,
+}
+
+impl AnchorReserveMonitor {
+	/// Creates a new monitor checking the reserve provided by the UTXOs returned by `utxo_source`
+	/// every `check_interval`, calling `status_callback` when its status changes.
+	///
+	/// `utxo_source` should return the confirmed UTXOs earmarked for the anchor channel reserve.
+	/// If it fails, the check is skipped until the next interval.
+	#[cfg(feature = "std")]
+	pub fn new(
+		context: AnchorChannelReserveContext,
+		utxo_source: impl Fn() -> Result<Vec<Utxo>, ()> + Send + 'static,
+		status_callback: impl Fn(AnchorReserveStatus) + Send + 'static, check_interval: Duration,
+	) -> Self {
+		Self {
+			context,
+			utxo_source: Box::new(utxo_source),
+			status_callback: Box::new(status_callback),
+			check_interval,
+			is_sufficient: true,
+			anchor_negotiation_thresholds: None,
+			reorged_claim_utxo_source: None,
+			report_requested: Arc::new(AtomicBool::new(false)),
+		}
+	}
+
+	/// Excludes the UTXOs returned by `reorged_claim_utxo_source` from the reserve, reporting them
+	/// via [`AnchorReserveStatus::reorged_claim_utxos`] instead.
+	///
+	/// This should return the UTXOs spent by claims whose confirmation was reorged out, e.g., via
+	/// [`BumpTransactionEventHandler::reorged_claim_utxos`]. Until the claims confirm again, the
+	/// wallet may list these UTXOs as available, even though they are committed to a rebroadcast
+	/// claim.
+	///
+	/// [`BumpTransactionEventHandler::reorged_claim_utxos`]: lightning::events::bump_transaction::BumpTransactionEventHandler::reorged_claim_utxos
+	pub fn with_reorged_claim_utxos(
+		mut self, reorged_claim_utxo_source: impl Fn() -> Vec<OutPoint> + Send + 'static,
+	) -> Self {
+		self.reorged_claim_utxo_source = Some(Box::new(reorged_claim_utxo_source));
+		self
+	}
+
+	/// Disables the negotiation of anchor channels via
+	/// [`ChannelManager::set_anchor_negotiation_enabled`] once the reserve supports fewer than
+	/// `disable_below` anchor channels in addition to the existing ones, and re-enables it once it
+	/// supports more than `enable_above` additional channels.
+	///
+	/// Keeping `enable_above` above `disable_below` avoids toggling the negotiation on every check
+	/// while the reserve hovers around a single threshold. If `enable_above` is below
+	/// `disable_below`, it is raised to `disable_below`.
+	///
+	/// Negotiation is only changed if it is enabled while below `disable_below` or disabled while
+	/// above `enable_above`, and is left unchanged if a check is skipped.
+	///
+	/// [`ChannelManager::set_anchor_negotiation_enabled`]: lightning::ln::channelmanager::ChannelManager::set_anchor_negotiation_enabled
+	pub fn with_anchor_negotiation_thresholds(
+		mut self, disable_below: u64, enable_above: u64,
+	) -> Self {
+		self.anchor_negotiation_thresholds =
+			Some((disable_below, core::cmp::max(disable_below, enable_above)));
+		self
+	}
+
+	/// Returns a handle to request a detailed report on the reserve to be logged on demand, e.g.,
+	/// when an operator investigates why anchor channels are rejected.
+	pub fn report_trigger(&self) -> AnchorReserveReportTrigger {
+		AnchorReserveReportTrigger { report_requested: Arc::clone(&self.report_requested) }
+	}
+
+	#[cfg(feature = "std")]
+	fn list_reserve_utxos(&self) -> Result<(Vec<Utxo>, Vec<OutPoint>), ()> {
+		let mut utxos = (self.utxo_source)()?;
+		let reorged_claim_utxos =
+			self.reorged_claim_utxo_source.as_ref().map_or_else(Vec::new, |source| source());
+		utxos.retain(|utxo| !reorged_claim_utxos.contains(&utxo.outpoint));
+		Ok((utxos, reorged_claim_utxos))
+	}
+
+	/// Logs an [`AnchorReserveReport`] if one was requested via an [`AnchorReserveReportTrigger`].
+	///
+	/// [`AnchorReserveReport`]: lightning::util::anchor_channel_reserves::AnchorReserveReport
+	#[cfg(feature = "std")]
+	pub(crate) fn report_if_requested<L: Deref>(
+		&self, channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+		logger: &L,
+	) where
+		L::Target: Logger,
+	{
+		if !self.report_requested.swap(false, Ordering::AcqRel) {
+			return;
+		}
+		let utxos = match self.list_reserve_utxos() {
+			Ok((utxos, _)) => utxos,
+			Err(()) => {
+				log_warn!(logger, "Failed to list anchor reserve UTXOs, skipping reserve report");
+				return;
+			},
+		};
+		let report =
+			AnchorReserveReport::new(&self.context, &utxos, channel_manager, chain_monitor);
+		log_info!(logger, "Anchor reserve report:\n{}", report);
+	}
+
+	#[cfg(feature = "std")]
+	pub(crate) fn check<L: Deref>(
+		&mut self, channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+		logger: &L,
+	) where
+		L::Target: Logger,
+	{
+		let (utxos, reorged_claim_utxos) = match self.list_reserve_utxos() {
+			Ok(utxos) => utxos,
+			Err(()) => {
+				log_warn!(logger, "Failed to list anchor reserve UTXOs, skipping reserve check");
+				return;
+			},
+		};
+		let reserve_check =
+			AnchorChannelReserveCheck::new(&self.context, &utxos, channel_manager, chain_monitor);
+		let status = AnchorReserveStatus {
+			num_anchor_channels: reserve_check.num_anchor_channels(),
+			num_supportable_channels: reserve_check.num_supportable_channels(),
+			reserve_per_channel: get_reserve_per_channel(&self.context),
+			total_utxo_value: utxos
+				.iter()
+				.try_fold(Amount::ZERO, |total, utxo| total.checked_add(utxo.output.value))
+				.unwrap_or(Amount::MAX),
+			pending_releases: get_pending_reserve_releases(chain_monitor),
+			reorged_claim_utxos,
+		};
+		let is_sufficient = status.is_sufficient();
+		let level = if is_sufficient { Level::Info } else { Level::Warn };
+		log_given_level!(
+			logger,
+			level,
+			"Anchor reserve status: num_anchor_channels={} num_supportable_channels={} reserve_per_channel_sat={} total_utxo_value_sat={} pending_releases={} reorged_claim_utxos={} sufficient={}",
+			status.num_anchor_channels,
+			status.num_supportable_channels,
+			status.reserve_per_channel.to_sat(),
+			status.total_utxo_value.to_sat(),
+			status.pending_releases.len(),
+			status.reorged_claim_utxos.len(),
+			is_sufficient
+		);
+		if let Some((disable_below, enable_above)) = self.anchor_negotiation_thresholds {
+			let num_additional_channels =
+				status.num_supportable_channels.saturating_sub(status.num_anchor_channels);
+			let is_enabled = channel_manager.is_anchor_negotiation_enabled();
+			if is_enabled && num_additional_channels < disable_below {
+				log_warn!(
+					logger,
+					"Anchor reserve supports only {} additional anchor channels, disabling anchor channel negotiation",
+					num_additional_channels
+				);
+				channel_manager.set_anchor_negotiation_enabled(false);
+			} else if !is_enabled && num_additional_channels > enable_above {
+				log_info!(
+					logger,
+					"Anchor reserve supports {} additional anchor channels, re-enabling anchor channel negotiation",
+					num_additional_channels
+				);
+				channel_manager.set_anchor_negotiation_enabled(true);
+			}
+		}
+		if is_sufficient != self.is_sufficient {
+			self.is_sufficient = is_sufficient;
+			(self.status_callback)(status);
+		}
+	}
+}
@@ -26,8 +26,10 @@ extern crate alloc;
 extern crate lightning;
 extern crate lightning_rapid_gossip_sync;
 
+mod anchor_reserve;
 mod fwd_batch;
 
+pub use anchor_reserve::{AnchorReserveMonitor, AnchorReserveReportTrigger, AnchorReserveStatus};
 use fwd_batch::BatchDelay;
 
 use lightning::chain;
@@ -55,11 +57,8 @@ use lightning::sign::EntropySource;
 use lightning::sign::OutputSpender;
 use lightning::util::logger::Logger;
 use lightning::util::persist::{
-	KVStore, KVStoreSync, KVStoreSyncWrapper, CHANNEL_MANAGER_PERSISTENCE_KEY,
-	CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE, CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
-	NETWORK_GRAPH_PERSISTENCE_KEY, NETWORK_GRAPH_PERSISTENCE_PRIMARY_NAMESPACE,
-	NETWORK_GRAPH_PERSISTENCE_SECONDARY_NAMESPACE, SCORER_PERSISTENCE_KEY,
-	SCORER_PERSISTENCE_PRIMARY_NAMESPACE, SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+	KVStore, KVStoreNamespace, KVStoreSync, KVStoreSyncWrapper, CHANNEL_MANAGER_PERSISTENCE_KEY,
+	NETWORK_GRAPH_PERSISTENCE_KEY, SCORER_PERSISTENCE_KEY,
 };
 use lightning::util::sweep::OutputSweeper;
 #[cfg(feature = "std")]
@@ -96,6 +95,12 @@ use alloc::boxed::Box;
 ///   and [`PeerManager::timer_tick_occurred`] at the appropriate intervals.
 /// * Calling [`NetworkGraph::remove_stale_channels_and_tracking`] (if a [`GossipSync`] with a
 ///   [`NetworkGraph`] is provided to [`BackgroundProcessor::start`]).
+/// * Calling [`P2PGossipSync::timer_tick_occurred`] (if a [`GossipSync::P2P`] is provided to
+///   [`BackgroundProcessor::start`]).
+/// * Calling [`ChainMonitor::check_anchor_reserve_utxos`] to detect anchor reserve UTXOs spent
+///   externally (if an [`AnchorReserveProvider`] is registered with the [`ChainMonitor`]).
+/// * Re-evaluating whether the anchor channel reserve is sufficient (if an
+///   [`AnchorReserveMonitor`] is provided to [`BackgroundProcessor::start`]).
 ///
 /// It will also call [`PeerManager::process_events`] periodically though this shouldn't be relied
 /// upon as doing so may result in high latency.
@@ -107,6 +112,7 @@ use alloc::boxed::Box;
 /// However, as long as [`ChannelMonitor`] backups are sound, no funds besides those used for
 /// unilateral chain closure fees are at risk.
 ///
+/// [`AnchorReserveProvider`]: lightning::util::anchor_channel_reserves::AnchorReserveProvider
 /// [`ChannelManager`]: lightning::ln::channelmanager::ChannelManager
 /// [`ChannelManager::timer_tick_occurred`]: lightning::ln::channelmanager::ChannelManager::timer_tick_occurred
 /// [`ChannelMonitor`]: lightning::chain::channelmonitor::ChannelMonitor
@@ -227,6 +233,12 @@ where
 			GossipSync::None => None,
 		}
 	}
+
+	fn timer_tick_occurred(&self) {
+		if let GossipSync::P2P(gossip_sync) = self {
+			gossip_sync.timer_tick_occurred();
+		}
+	}
 }
 
 /// This is not exported to bindings users as the bindings concretize everything and have constructors for us
@@ -712,6 +724,7 @@ use futures_util::{dummy_waker, Joiner, OptionalSelector, Selector, SelectorOutp
 ///			Some(background_sweeper),
 ///			background_logger,
 ///			Some(background_scorer),
+///			None,
 ///			sleeper,
 ///			mobile_interruptable_platform,
 ///			|| Some(SystemTime::now().duration_since(SystemTime::UNIX_EPOCH).unwrap())
@@ -760,7 +773,8 @@ pub async fn process_events_async<
 	kv_store: K, event_handler: EventHandler, chain_monitor: M, channel_manager: CM,
 	onion_messenger: Option<OM>, gossip_sync: GossipSync<PGS, RGS, G, UL, L>, peer_manager: PM,
 	liquidity_manager: Option<LM>, sweeper: Option<OS>, logger: L, scorer: Option<S>,
-	sleeper: Sleeper, mobile_interruptable_platform: bool, fetch_time: FetchTime,
+	anchor_reserve_monitor: Option<AnchorReserveMonitor>, sleeper: Sleeper,
+	mobile_interruptable_platform: bool, fetch_time: FetchTime,
 ) -> Result<(), lightning::io::Error>
 where
 	UL::Target: 'static + UtxoLookup,
@@ -796,8 +810,8 @@ where
 						log_trace!(logger, "Persisting scorer after update");
 						if let Err(e) = kv_store
 							.write(
-								SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
-								SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+								KVStoreNamespace::SCORER.primary_namespace,
+								KVStoreNamespace::SCORER.secondary_namespace,
 								SCORER_PERSISTENCE_KEY,
 								scorer.encode(),
 							)
@@ -821,6 +835,15 @@ where
 	channel_manager.get_cm().timer_tick_occurred();
 	log_trace!(logger, "Rebroadcasting monitor's pending claims on startup");
 	chain_monitor.rebroadcast_pending_claims();
+	#[cfg(feature = "std")]
+	let mut anchor_reserve_monitor = anchor_reserve_monitor.map(|mut monitor| {
+		log_trace!(logger, "Checking anchor reserve on startup");
+		monitor.check(channel_manager.get_cm(), &*chain_monitor, &logger);
+		let last_check_call = sleeper(monitor.check_interval);
+		(monitor, last_check_call)
+	});
+	#[cfg(not(feature = "std"))]
+	let _ = anchor_reserve_monitor;
 
 	let mut last_freshness_call = sleeper(FRESHNESS_TIMER);
 	let mut last_onion_message_handler_call = sleeper(ONION_MESSAGE_HANDLER_TIMER);
@@ -836,10 +859,14 @@ where
 
 	loop {
 		channel_manager.get_cm().process_pending_events_async(async_event_handler).await;
+		chain_monitor.check_anchor_reserve_utxos();
 		chain_monitor.process_pending_events_async(async_event_handler).await;
 		if let Some(om) = &onion_messenger {
 			om.get_om().process_pending_events_async(async_event_handler).await
 		}
+		if let Some(network_graph) = gossip_sync.network_graph() {
+			network_graph.process_pending_events_async(async_event_handler).await
+		}
 
 		// Note that the PeerManager::process_events may block on ChannelManager's locks,
 		// hence it comes last here. When the ChannelManager finishes whatever it's doing,
@@ -919,6 +946,7 @@ where
 			Some(false) => {
 				log_trace!(logger, "Calling ChannelManager's timer_tick_occurred");
 				channel_manager.get_cm().timer_tick_occurred();
+				gossip_sync.timer_tick_occurred();
 			},
 			Some(true) => break,
 			None => {},
@@ -932,8 +960,8 @@ where
 			let fut = async {
 				kv_store
 					.write(
-						CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
-						CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+						KVStoreNamespace::CHANNEL_MANAGER.primary_namespace,
+						KVStoreNamespace::CHANNEL_MANAGER.secondary_namespace,
 						CHANNEL_MANAGER_PERSISTENCE_KEY,
 						channel_manager.get_cm().encode(),
 					)
@@ -995,8 +1023,8 @@ where
 				let fut = async {
 					if let Err(e) = kv_store
 						.write(
-							NETWORK_GRAPH_PERSISTENCE_PRIMARY_NAMESPACE,
-							NETWORK_GRAPH_PERSISTENCE_SECONDARY_NAMESPACE,
+							KVStoreNamespace::NETWORK_GRAPH.primary_namespace,
+							KVStoreNamespace::NETWORK_GRAPH.secondary_namespace,
 							NETWORK_GRAPH_PERSISTENCE_KEY,
 							network_graph.encode(),
 						)
@@ -1037,8 +1065,8 @@ where
 					let fut = async {
 						if let Err(e) = kv_store
 							.write(
-								SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
-								SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+								KVStoreNamespace::SCORER.primary_namespace,
+								KVStoreNamespace::SCORER.secondary_namespace,
 								SCORER_PERSISTENCE_KEY,
 								scorer.encode(),
 							)
@@ -1134,6 +1162,20 @@ where
 			Some(true) => break,
 			None => {},
 		}
+
+		#[cfg(feature = "std")]
+		if let Some((ref mut monitor, ref mut last_check_call)) = anchor_reserve_monitor {
+			let check_interval = monitor.check_interval;
+			match check_and_reset_sleeper(last_check_call, || sleeper(check_interval)) {
+				Some(false) => {
+					log_trace!(logger, "Checking anchor reserve");
+					monitor.check(channel_manager.get_cm(), &*chain_monitor, &logger);
+				},
+				Some(true) => break,
+				None => {},
+			}
+			monitor.report_if_requested(channel_manager.get_cm(), &*chain_monitor, &logger);
+		}
 	}
 	log_trace!(logger, "Terminating background processor.");
 
@@ -1142,8 +1184,8 @@ where
 	// ChannelMonitor update(s) persisted without a corresponding ChannelManager update.
 	kv_store
 		.write(
-			CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MANAGER.primary_namespace,
+			KVStoreNamespace::CHANNEL_MANAGER.secondary_namespace,
 			CHANNEL_MANAGER_PERSISTENCE_KEY,
 			channel_manager.get_cm().encode(),
 		)
@@ -1151,8 +1193,8 @@ where
 	if let Some(ref scorer) = scorer {
 		kv_store
 			.write(
-				SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
-				SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+				KVStoreNamespace::SCORER.primary_namespace,
+				KVStoreNamespace::SCORER.secondary_namespace,
 				SCORER_PERSISTENCE_KEY,
 				scorer.encode(),
 			)
@@ -1161,8 +1203,8 @@ where
 	if let Some(network_graph) = gossip_sync.network_graph() {
 		kv_store
 			.write(
-				NETWORK_GRAPH_PERSISTENCE_PRIMARY_NAMESPACE,
-				NETWORK_GRAPH_PERSISTENCE_SECONDARY_NAMESPACE,
+				KVStoreNamespace::NETWORK_GRAPH.primary_namespace,
+				KVStoreNamespace::NETWORK_GRAPH.secondary_namespace,
 				NETWORK_GRAPH_PERSISTENCE_KEY,
 				network_graph.encode(),
 			)
@@ -1223,7 +1265,8 @@ pub async fn process_events_async_with_kv_store_sync<
 	kv_store: K, event_handler: EventHandler, chain_monitor: M, channel_manager: CM,
 	onion_messenger: Option<OM>, gossip_sync: GossipSync<PGS, RGS, G, UL, L>, peer_manager: PM,
 	liquidity_manager: Option<LM>, sweeper: Option<OS>, logger: L, scorer: Option<S>,
-	sleeper: Sleeper, mobile_interruptable_platform: bool, fetch_time: FetchTime,
+	anchor_reserve_monitor: Option<AnchorReserveMonitor>, sleeper: Sleeper,
+	mobile_interruptable_platform: bool, fetch_time: FetchTime,
 ) -> Result<(), lightning::io::Error>
 where
 	UL::Target: 'static + UtxoLookup,
@@ -1254,6 +1297,7 @@ where
 		sweeper,
 		logger,
 		scorer,
+		anchor_reserve_monitor,
 		sleeper,
 		mobile_interruptable_platform,
 		fetch_time,
@@ -1298,6 +1342,11 @@ impl BackgroundProcessor {
 	/// to indicate that the [`BackgroundProcessor`] should not prune the [`NetworkGraph`] instance
 	/// until the [`RapidGossipSync`] instance completes its first sync.
 	///
+	/// # Anchor Channel Reserve
+	///
+	/// If an [`AnchorReserveMonitor`] is given via `anchor_reserve_monitor`, the anchor channel
+	/// reserve is re-evaluated on startup and at the interval the monitor was created with.
+	///
 	/// [top-level documentation]: BackgroundProcessor
 	/// [`join`]: Self::join
 	/// [`stop`]: Self::stop
@@ -1338,6 +1387,7 @@ impl BackgroundProcessor {
 		kv_store: K, event_handler: EH, chain_monitor: M, channel_manager: CM,
 		onion_messenger: Option<OM>, gossip_sync: GossipSync<PGS, RGS, G, UL, L>, peer_manager: PM,
 		liquidity_manager: Option<LM>, sweeper: Option<OS>, logger: L, scorer: Option<S>,
+		anchor_reserve_monitor: Option<AnchorReserveMonitor>,
 	) -> Self
 	where
 		UL::Target: 'static + UtxoLookup,
@@ -1371,8 +1421,8 @@ impl BackgroundProcessor {
 					if update_scorer(scorer, &event, duration_since_epoch) {
 						log_trace!(logger, "Persisting scorer after update");
 						if let Err(e) = kv_store.write(
-							SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
-							SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+							KVStoreNamespace::SCORER.primary_namespace,
+							KVStoreNamespace::SCORER.secondary_namespace,
 							SCORER_PERSISTENCE_KEY,
 							scorer.encode(),
 						) {
@@ -1388,6 +1438,11 @@ impl BackgroundProcessor {
 			channel_manager.get_cm().timer_tick_occurred();
 			log_trace!(logger, "Rebroadcasting monitor's pending claims on startup");
 			chain_monitor.rebroadcast_pending_claims();
+			let mut anchor_reserve_monitor = anchor_reserve_monitor;
+			if let Some(ref mut monitor) = anchor_reserve_monitor {
+				log_trace!(logger, "Checking anchor reserve on startup");
+				monitor.check(channel_manager.get_cm(), &*chain_monitor, &logger);
+			}
 
 			let mut last_freshness_call = Instant::now();
 			let mut last_onion_message_handler_call = Instant::now();
@@ -1396,6 +1451,7 @@ impl BackgroundProcessor {
 			let mut last_scorer_persist_call = Instant::now();
 			let mut last_rebroadcast_call = Instant::now();
 			let mut last_sweeper_call = Instant::now();
+			let mut last_anchor_reserve_call = Instant::now();
 			let mut have_pruned = false;
 			let mut have_decayed_scorer = false;
 
@@ -1404,10 +1460,14 @@ impl BackgroundProcessor {
 
 			loop {
 				channel_manager.get_cm().process_pending_events(&event_handler);
+				chain_monitor.check_anchor_reserve_utxos();
 				chain_monitor.process_pending_events(&event_handler);
 				if let Some(om) = &onion_messenger {
 					om.get_om().process_pending_events(&event_handler)
 				};
+				if let Some(network_graph) = gossip_sync.network_graph() {
+					network_graph.process_pending_events(&event_handler)
+				};
 
 				// Note that the PeerManager::process_events may block on ChannelManager's locks,
 				// hence it comes last here. When the ChannelManager finishes whatever it's doing,
@@ -1466,13 +1526,14 @@ impl BackgroundProcessor {
 				if last_freshness_call.elapsed() > FRESHNESS_TIMER {
 					log_trace!(logger, "Calling ChannelManager's timer_tick_occurred");
 					channel_manager.get_cm().timer_tick_occurred();
+					gossip_sync.timer_tick_occurred();
 					last_freshness_call = Instant::now();
 				}
 				if channel_manager.get_cm().get_and_clear_needs_persistence() {
 					log_trace!(logger, "Persisting ChannelManager...");
 					(kv_store.write(
-						CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
-						CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+						KVStoreNamespace::CHANNEL_MANAGER.primary_namespace,
+						KVStoreNamespace::CHANNEL_MANAGER.secondary_namespace,
 						CHANNEL_MANAGER_PERSISTENCE_KEY,
 						channel_manager.get_cm().encode(),
 					))?;
@@ -1503,8 +1564,8 @@ impl BackgroundProcessor {
 							duration_since_epoch.as_secs(),
 						);
 						if let Err(e) = kv_store.write(
-							NETWORK_GRAPH_PERSISTENCE_PRIMARY_NAMESPACE,
-							NETWORK_GRAPH_PERSISTENCE_SECONDARY_NAMESPACE,
+							KVStoreNamespace::NETWORK_GRAPH.primary_namespace,
+							KVStoreNamespace::NETWORK_GRAPH.secondary_namespace,
 							NETWORK_GRAPH_PERSISTENCE_KEY,
 							network_graph.encode(),
 						) {
@@ -1532,8 +1593,8 @@ impl BackgroundProcessor {
 						log_trace!(logger, "Calling time_passed and persisting scorer");
 						scorer.write_lock().time_passed(duration_since_epoch);
 						if let Err(e) = kv_store.write(
-							SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
-							SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+							KVStoreNamespace::SCORER.primary_namespace,
+							KVStoreNamespace::SCORER.secondary_namespace,
 							SCORER_PERSISTENCE_KEY,
 							scorer.encode(),
 						) {
@@ -1566,29 +1627,37 @@ impl BackgroundProcessor {
 					chain_monitor.rebroadcast_pending_claims();
 					last_rebroadcast_call = Instant::now();
 				}
+				if let Some(ref mut monitor) = anchor_reserve_monitor {
+					if last_anchor_reserve_call.elapsed() > monitor.check_interval {
+						log_trace!(logger, "Checking anchor reserve");
+						monitor.check(channel_manager.get_cm(), &*chain_monitor, &logger);
+						last_anchor_reserve_call = Instant::now();
+					}
+					monitor.report_if_requested(channel_manager.get_cm(), &*chain_monitor, &logger);
+				}
 			}
 
 			// After we exit, ensure we persist the ChannelManager one final time - this avoids
 			// some races where users quit while channel updates were in-flight, with
 			// ChannelMonitor update(s) persisted without a corresponding ChannelManager update.
 			kv_store.write(
-				CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
-				CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+				KVStoreNamespace::CHANNEL_MANAGER.primary_namespace,
+				KVStoreNamespace::CHANNEL_MANAGER.secondary_namespace,
 				CHANNEL_MANAGER_PERSISTENCE_KEY,
 				channel_manager.get_cm().encode(),
 			)?;
 			if let Some(ref scorer) = scorer {
 				kv_store.write(
-					SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
-					SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
+					KVStoreNamespace::SCORER.primary_namespace,
+					KVStoreNamespace::SCORER.secondary_namespace,
 					SCORER_PERSISTENCE_KEY,
 					scorer.encode(),
 				)?;
 			}
 			if let Some(network_graph) = gossip_sync.network_graph() {
 				kv_store.write(
-					NETWORK_GRAPH_PERSISTENCE_PRIMARY_NAMESPACE,
-					NETWORK_GRAPH_PERSISTENCE_SECONDARY_NAMESPACE,
+					KVStoreNamespace::NETWORK_GRAPH.primary_namespace,
+					KVStoreNamespace::NETWORK_GRAPH.secondary_namespace,
 					NETWORK_GRAPH_PERSISTENCE_KEY,
 					network_graph.encode(),
 				)?;
@@ -1648,7 +1717,9 @@ impl Drop for BackgroundProcessor {
 
 #[cfg(all(feature = "std", test))]
 mod tests {
-	use super::{BackgroundProcessor, GossipSync, FRESHNESS_TIMER};
+	use super::{
+		AnchorReserveMonitor, AnchorReserveStatus, BackgroundProcessor, GossipSync, FRESHNESS_TIMER,
+	};
 	use bitcoin::constants::{genesis_block, ChainHash};
 	use bitcoin::hashes::Hash;
 	use bitcoin::locktime::absolute::LockTime;
@@ -1656,11 +1727,12 @@ mod tests {
 	use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
 	use bitcoin::transaction::Version;
 	use bitcoin::transaction::{Transaction, TxOut};
-	use bitcoin::{Amount, ScriptBuf, Txid};
+	use bitcoin::{Amount, ScriptBuf, Txid, WPubkeyHash};
 	use core::sync::atomic::{AtomicBool, Ordering};
 	use lightning::chain::channelmonitor::ANTI_REORG_DELAY;
 	use lightning::chain::transaction::OutPoint;
 	use lightning::chain::{chainmonitor, BestBlock, Confirm, Filter};
+	use lightning::events::bump_transaction::Utxo;
 	use lightning::events::{Event, PathFailure, ReplayEvent};
 	use lightning::ln::channelmanager;
 	use lightning::ln::channelmanager::{
@@ -1679,6 +1751,9 @@ mod tests {
 	use lightning::sign::{ChangeDestinationSourceSync, InMemorySigner, KeysManager, NodeSigner};
 	use lightning::types::features::{ChannelFeatures, NodeFeatures};
 	use lightning::types::payment::PaymentHash;
+	use lightning::util::anchor_channel_reserves::{
+		get_reserve_per_channel, AnchorChannelReserveContext, PendingReserveRelease,
+	};
 	use lightning::util::config::UserConfig;
 	use lightning::util::persist::{
 		KVStoreSync, KVStoreSyncWrapper, CHANNEL_MANAGER_PERSISTENCE_KEY,
@@ -1688,7 +1763,7 @@ mod tests {
 		SCORER_PERSISTENCE_KEY, SCORER_PERSISTENCE_PRIMARY_NAMESPACE,
 		SCORER_PERSISTENCE_SECONDARY_NAMESPACE,
 	};
-	use lightning::util::ser::Writeable;
+	use lightning::util::ser::{Readable, Writeable};
 	use lightning::util::sweep::{OutputSpendStatus, OutputSweeperSync, PRUNE_DELAY_BLOCKS};
 	use lightning::util::test_utils;
 	use lightning::{get_event, get_event_msg};
@@ -2458,6 +2533,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		macro_rules! check_persisted_data {
@@ -2553,6 +2629,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 		loop {
 			let log_entries = nodes[0].logger.lines.lock().unwrap();
@@ -2597,6 +2674,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 		match bg_processor.join() {
 			Ok(_) => panic!("Expected error persisting manager"),
@@ -2631,6 +2709,7 @@ mod tests {
 			Some(nodes[0].sweeper.sweeper_async()),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 			move |dur: Duration| {
 				Box::pin(async move {
 					tokio::time::sleep(dur).await;
@@ -2669,6 +2748,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		match bg_processor.stop() {
@@ -2700,6 +2780,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		match bg_processor.stop() {
@@ -2748,6 +2829,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		// Open a channel and check that the FundingGenerationReady event was handled.
@@ -2812,6 +2894,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		// Force close the channel and check that the SpendableOutputs event was handled.
@@ -2976,6 +3059,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		begin_open_channel!(nodes[0], nodes[1], channel_value);
@@ -3007,6 +3091,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		loop {
@@ -3105,6 +3190,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		do_test_not_pruning_network_graph_until_graph_sync_completion!(
@@ -3140,6 +3226,7 @@ mod tests {
 			Some(nodes[0].sweeper.sweeper_async()),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 			move |dur: Duration| {
 				let mut exit_receiver = exit_receiver.clone();
 				Box::pin(async move {
@@ -3306,6 +3393,7 @@ mod tests {
 			Some(Arc::clone(&nodes[0].sweeper)),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 		);
 
 		do_test_payment_path_scoring!(nodes, receiver.recv_timeout(EVENT_DEADLINE));
@@ -3355,6 +3443,7 @@ mod tests {
 			Some(nodes[0].sweeper.sweeper_async()),
 			Arc::clone(&nodes[0].logger),
 			Some(Arc::clone(&nodes[0].scorer)),
+			None,
 			move |dur: Duration| {
 				let mut exit_receiver = exit_receiver.clone();
 				Box::pin(async move {
@@ -3384,4 +3473,358 @@ mod tests {
 		r1.unwrap().unwrap();
 		r2.unwrap()
 	}
+
+	#[test]
+	fn test_anchor_reserve_status_serialization() {
+		let status = AnchorReserveStatus {
+			num_anchor_channels: 3,
+			num_supportable_channels: 2,
+			reserve_per_channel: Amount::from_sat(40_000),
+			total_utxo_value: Amount::from_sat(90_000),
+			pending_releases: vec![PendingReserveRelease {
+				channel_id: ChannelId([42; 32]),
+				release_height: 1000,
+			}],
+			reorged_claim_utxos: vec![bitcoin::OutPoint { txid: Txid::all_zeros(), vout: 1 }],
+		};
+		let encoded = status.encode();
+		let decoded: AnchorReserveStatus = Readable::read(&mut &encoded[..]).unwrap();
+		assert_eq!(decoded, status);
+
+		// Unknown odd TLV records written by newer versions are ignored.
+		let mut encoded_with_odd_type = encoded.clone();
+		encoded_with_odd_type[0] += 3;
+		encoded_with_odd_type.extend_from_slice(&[13, 1, 42]);
+		let decoded: AnchorReserveStatus =
+			Readable::read(&mut &encoded_with_odd_type[..]).unwrap();
+		assert_eq!(decoded, status);
+	}
+
+	#[test]
+	fn test_anchor_reserve_monitor() {
+		// Test that the status callback is called once the anchor reserve becomes insufficient for
+		// the anchor channels relying on it, and not again while it remains insufficient.
+		let (_, nodes) = create_nodes(2, "test_anchor_reserve_monitor");
+
+		let mut anchors_config = UserConfig::default();
+		anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		nodes[0]
+			.node
+			.create_channel(
+				nodes[1].node.get_our_node_id(),
+				100_000,
+				0,
+				42,
+				None,
+				Some(anchors_config),
+			)
+			.unwrap();
+
+		let utxo = Utxo::new_p2wpkh(
+			bitcoin::OutPoint { txid: Txid::all_zeros(), vout: 0 },
+			Amount::ONE_BTC,
+			&WPubkeyHash::all_zeros(),
+		);
+		let reserve_available = Arc::new(AtomicBool::new(true));
+		let utxo_source = {
+			let reserve_available = Arc::clone(&reserve_available);
+			move || {
+				if reserve_available.load(Ordering::Acquire) {
+					Ok(vec![utxo.clone()])
+				} else {
+					Ok(Vec::new())
+				}
+			}
+		};
+		let (sender, receiver) = std::sync::mpsc::channel();
+		let anchor_reserve_monitor = AnchorReserveMonitor::new(
+			AnchorChannelReserveContext::default(),
+			utxo_source,
+			move |status| sender.send(status).unwrap(),
+			Duration::from_millis(100),
+		);
+
+		let data_dir = nodes[0].kv_store.get_data_dir();
+		let persister = Arc::new(Persister::new(data_dir));
+		let event_handler = |_: _| Ok(());
+		let bg_processor = BackgroundProcessor::start(
+			persister,
+			event_handler,
+			Arc::clone(&nodes[0].chain_monitor),
+			Arc::clone(&nodes[0].node),
+			Some(Arc::clone(&nodes[0].messenger)),
+			nodes[0].no_gossip_sync(),
+			Arc::clone(&nodes[0].peer_manager),
+			Some(Arc::clone(&nodes[0].liquidity_manager)),
+			Some(Arc::clone(&nodes[0].sweeper)),
+			Arc::clone(&nodes[0].logger),
+			Some(Arc::clone(&nodes[0].scorer)),
+			Some(anchor_reserve_monitor),
+		);
+
+		// The reserve is sufficient for the single anchor channel, so the callback isn't called.
+		assert!(receiver.recv_timeout(2 * FRESHNESS_TIMER).is_err());
+
+		reserve_available.store(false, Ordering::Release);
+		let status = receiver
+			.recv_timeout(EVENT_DEADLINE)
+			.expect("AnchorReserveStatus not reported within deadline");
+		assert!(!status.is_sufficient());
+		assert_eq!(status.num_anchor_channels, 1);
+		assert_eq!(status.num_supportable_channels, 0);
+		assert_eq!(status.total_utxo_value, Amount::ZERO);
+		assert!(status.pending_releases.is_empty());
+		assert!(status.reorged_claim_utxos.is_empty());
+
+		// Further checks while the reserve remains insufficient don't call the callback again.
+		assert!(receiver.recv_timeout(2 * FRESHNESS_TIMER).is_err());
+
+		assert!(bg_processor.stop().is_ok());
+
+		let log_entries = nodes[0].logger.lines.lock().unwrap();
+		let expected_log =
+			"Anchor reserve status: num_anchor_channels=1 num_supportable_channels=0";
+		assert!(log_entries.keys().any(|(module, line)| {
+			*module == "lightning_background_processor::anchor_reserve"
+				&& line.starts_with(expected_log)
+		}));
+	}
+
+	#[test]
+	fn test_anchor_reserve_monitor_excludes_reorged_claim_utxos() {
+		// Test that UTXOs spent by claims whose confirmation was reorged out don't count towards
+		// the reserve, even while the wallet lists them as available again.
+		let (_, nodes) =
+			create_nodes(2, "test_anchor_reserve_monitor_excludes_reorged_claim_utxos");
+
+		let mut anchors_config = UserConfig::default();
+		anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		nodes[0]
+			.node
+			.create_channel(
+				nodes[1].node.get_our_node_id(),
+				100_000,
+				0,
+				42,
+				None,
+				Some(anchors_config),
+			)
+			.unwrap();
+
+		let outpoint = bitcoin::OutPoint { txid: Txid::all_zeros(), vout: 0 };
+		let utxo = Utxo::new_p2wpkh(outpoint, Amount::ONE_BTC, &WPubkeyHash::all_zeros());
+		let claim_reorged = Arc::new(AtomicBool::new(false));
+		let reorged_claim_utxo_source = {
+			let claim_reorged = Arc::clone(&claim_reorged);
+			move || {
+				if claim_reorged.load(Ordering::Acquire) {
+					vec![outpoint]
+				} else {
+					Vec::new()
+				}
+			}
+		};
+		let (sender, receiver) = std::sync::mpsc::channel();
+		let anchor_reserve_monitor = AnchorReserveMonitor::new(
+			AnchorChannelReserveContext::default(),
+			move || Ok(vec![utxo.clone()]),
+			move |status| sender.send(status).unwrap(),
+			Duration::from_millis(100),
+		)
+		.with_reorged_claim_utxos(reorged_claim_utxo_source);
+
+		let data_dir = nodes[0].kv_store.get_data_dir();
+		let persister = Arc::new(Persister::new(data_dir));
+		let event_handler = |_: _| Ok(());
+		let bg_processor = BackgroundProcessor::start(
+			persister,
+			event_handler,
+			Arc::clone(&nodes[0].chain_monitor),
+			Arc::clone(&nodes[0].node),
+			Some(Arc::clone(&nodes[0].messenger)),
+			nodes[0].no_gossip_sync(),
+			Arc::clone(&nodes[0].peer_manager),
+			Some(Arc::clone(&nodes[0].liquidity_manager)),
+			Some(Arc::clone(&nodes[0].sweeper)),
+			Arc::clone(&nodes[0].logger),
+			Some(Arc::clone(&nodes[0].scorer)),
+			Some(anchor_reserve_monitor),
+		);
+
+		assert!(receiver.recv_timeout(2 * FRESHNESS_TIMER).is_err());
+
+		claim_reorged.store(true, Ordering::Release);
+		let status = receiver
+			.recv_timeout(EVENT_DEADLINE)
+			.expect("AnchorReserveStatus not reported within deadline");
+		assert!(!status.is_sufficient());
+		assert_eq!(status.total_utxo_value, Amount::ZERO);
+		assert_eq!(status.reorged_claim_utxos, vec![outpoint]);
+
+		claim_reorged.store(false, Ordering::Release);
+		let status = receiver
+			.recv_timeout(EVENT_DEADLINE)
+			.expect("AnchorReserveStatus not reported within deadline");
+		assert!(status.is_sufficient());
+		assert_eq!(status.total_utxo_value, Amount::ONE_BTC);
+		assert!(status.reorged_claim_utxos.is_empty());
+
+		assert!(bg_processor.stop().is_ok());
+	}
+
+	#[test]
+	fn test_anchor_reserve_monitor_toggles_anchor_negotiation() {
+		// Test that the negotiation of anchor channels is disabled once the reserve doesn't support
+		// additional anchor channels, and re-enabled once it does again.
+		let (_, nodes) = create_nodes(1, "test_anchor_reserve_monitor_toggles_anchor_negotiation");
+
+		let utxo = Utxo::new_p2wpkh(
+			bitcoin::OutPoint { txid: Txid::all_zeros(), vout: 0 },
+			Amount::ONE_BTC,
+			&WPubkeyHash::all_zeros(),
+		);
+		let reserve_available = Arc::new(AtomicBool::new(true));
+		let utxo_source = {
+			let reserve_available = Arc::clone(&reserve_available);
+			move || {
+				if reserve_available.load(Ordering::Acquire) {
+					Ok(vec![utxo.clone()])
+				} else {
+					Ok(Vec::new())
+				}
+			}
+		};
+		let anchor_reserve_monitor = AnchorReserveMonitor::new(
+			AnchorChannelReserveContext::default(),
+			utxo_source,
+			|_| {},
+			Duration::from_millis(100),
+		)
+		.with_anchor_negotiation_thresholds(1, 2);
+
+		let data_dir = nodes[0].kv_store.get_data_dir();
+		let persister = Arc::new(Persister::new(data_dir));
+		let event_handler = |_: _| Ok(());
+		let bg_processor = BackgroundProcessor::start(
+			persister,
+			event_handler,
+			Arc::clone(&nodes[0].chain_monitor),
+			Arc::clone(&nodes[0].node),
+			Some(Arc::clone(&nodes[0].messenger)),
+			nodes[0].no_gossip_sync(),
+			Arc::clone(&nodes[0].peer_manager),
+			Some(Arc::clone(&nodes[0].liquidity_manager)),
+			Some(Arc::clone(&nodes[0].sweeper)),
+			Arc::clone(&nodes[0].logger),
+			Some(Arc::clone(&nodes[0].scorer)),
+			Some(anchor_reserve_monitor),
+		);
+
+		let wait_for_anchor_negotiation = |enabled| {
+			let start = std::time::Instant::now();
+			while nodes[0].node.is_anchor_negotiation_enabled() != enabled {
+				assert!(start.elapsed() < EVENT_DEADLINE, "Anchor negotiation not toggled");
+				std::thread::sleep(Duration::from_millis(10));
+			}
+		};
+
+		// The reserve supports additional anchor channels, so negotiation remains enabled.
+		assert!(nodes[0].node.is_anchor_negotiation_enabled());
+		std::thread::sleep(2 * FRESHNESS_TIMER);
+		assert!(nodes[0].node.is_anchor_negotiation_enabled());
+
+		reserve_available.store(false, Ordering::Release);
+		wait_for_anchor_negotiation(false);
+
+		reserve_available.store(true, Ordering::Release);
+		wait_for_anchor_negotiation(true);
+
+		assert!(bg_processor.stop().is_ok());
+	}
+	#[test]
+	fn test_anchor_reserve_monitor_logs_requested_report() {
+		// Test that a report on the reserve is only logged once requested via the trigger,
+		// independently of the check interval.
+		let (_, nodes) = create_nodes(2, "test_anchor_reserve_monitor_logs_requested_report");
+
+		let mut anchors_config = UserConfig::default();
+		anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		nodes[0]
+			.node
+			.create_channel(
+				nodes[1].node.get_our_node_id(),
+				100_000,
+				0,
+				42,
+				None,
+				Some(anchors_config),
+			)
+			.unwrap();
+		let channel_id = nodes[0].node.list_channels()[0].channel_id;
+
+		let utxo = Utxo::new_p2wpkh(
+			bitcoin::OutPoint { txid: Txid::all_zeros(), vout: 0 },
+			Amount::ONE_BTC,
+			&WPubkeyHash::all_zeros(),
+		);
+		let anchor_reserve_monitor = AnchorReserveMonitor::new(
+			AnchorChannelReserveContext::default(),
+			move || Ok(vec![utxo.clone()]),
+			|_| {},
+			Duration::from_secs(3600),
+		);
+		let report_trigger = anchor_reserve_monitor.report_trigger();
+
+		let data_dir = nodes[0].kv_store.get_data_dir();
+		let persister = Arc::new(Persister::new(data_dir));
+		let event_handler = |_: _| Ok(());
+		let bg_processor = BackgroundProcessor::start(
+			persister,
+			event_handler,
+			Arc::clone(&nodes[0].chain_monitor),
+			Arc::clone(&nodes[0].node),
+			Some(Arc::clone(&nodes[0].messenger)),
+			nodes[0].no_gossip_sync(),
+			Arc::clone(&nodes[0].peer_manager),
+			Some(Arc::clone(&nodes[0].liquidity_manager)),
+			Some(Arc::clone(&nodes[0].sweeper)),
+			Arc::clone(&nodes[0].logger),
+			Some(Arc::clone(&nodes[0].scorer)),
+			Some(anchor_reserve_monitor),
+		);
+
+		let find_report = || {
+			let log_entries = nodes[0].logger.lines.lock().unwrap();
+			log_entries
+				.keys()
+				.find(|(module, line)| {
+					*module == "lightning_background_processor::anchor_reserve"
+						&& line.starts_with("Anchor reserve report:")
+				})
+				.map(|(_, line)| line.clone())
+		};
+
+		std::thread::sleep(2 * FRESHNESS_TIMER);
+		assert!(find_report().is_none());
+
+		report_trigger.request_report();
+		let start = std::time::Instant::now();
+		let report = loop {
+			if let Some(report) = find_report() {
+				break report;
+			}
+			assert!(start.elapsed() < EVENT_DEADLINE, "Anchor reserve report not logged");
+			std::thread::sleep(Duration::from_millis(10));
+		};
+		let reserve_per_channel = get_reserve_per_channel(&AnchorChannelReserveContext::default());
+		assert!(report.contains("\nnum_anchor_channels=1\n"));
+		assert!(report.contains("\nutxos class=p2wpkh count=1 value_sat=100000000\n"));
+		assert!(report.contains(&format!(
+			"\nchannel channel_id={} reserve_sat={}\n",
+			channel_id,
+			reserve_per_channel.to_sat()
+		)));
+
+		assert!(bg_processor.stop().is_ok());
+	}
 }
@@ -0,0 +1,156 @@
+//! Utilities for checking the BOLT 11 encoding of invoices against other implementations.
+//!
+//! [`encode_invoice`] produces the canonical encoding of an invoice described by a
+//! [`SignedRawBolt11Invoice`], while [`dump_invoice`] produces a normalized, line-based dump of an
+//! encoded invoice suitable for diffing against the output of other implementations.
+
+use core::fmt::Write;
+
+use bitcoin::hashes::Hash;
+
+use crate::prelude::*;
+use crate::ser::Base32Iterable;
+use crate::{Bolt11ParseError, Fallback, RawTaggedField, SignedRawBolt11Invoice, TaggedField};
+
+/// Returns the canonical BOLT 11 encoding of the given invoice.
+///
+/// A [`SignedRawBolt11Invoice`] may be constructed from a [`RawBolt11Invoice`] describing each
+/// tagged field using [`RawBolt11Invoice::sign`].
+///
+/// [`RawBolt11Invoice`]: crate::RawBolt11Invoice
+/// [`RawBolt11Invoice::sign`]: crate::RawBolt11Invoice::sign
+pub fn encode_invoice(invoice: &SignedRawBolt11Invoice) -> String {
+	invoice.to_string()
+}
+
+/// Parses the given BOLT 11 invoice and returns a normalized dump of its structure.
+///
+/// The dump consists of `key=value` lines in encoding order, starting with the human readable
+/// part (`hrp`, `currency`, `amount_pico_btc`) and `timestamp`. Each tagged field is then given
+/// as a `field` line containing its tag, data length and data in bech32 characters, followed by
+/// lines decoding its contents if the field has known semantics:
+/// - `payment_hash`, `payment_secret`, `description_hash`, `payee_pub_key`, `payment_metadata`
+///   and `description` as lowercase hex of the encoded bytes,
+/// - `expiry_time` and `min_final_cltv_expiry_delta` as decimal integers,
+/// - `fallback` as `version:program` with the program in lowercase hex,
+/// - one `route_hop` per hop as `pubkey:short_channel_id:fee_base_msat:fee_proportional_millionths`
+///   followed by `:cltv_expiry_delta`,
+/// - `features` as the comma-separated list of set feature bits in ascending order.
+///
+/// Finally, the `signature`, `recovery_id`, the `recovered_payee_pub_key` (or `invalid`), the
+/// `signable_hash` and whether the input used the `canonical` encoding are given.
+///
+/// Tagged field data is always given in its canonical encoding, so non-minimal encodings (e.g.,
+/// of features) are only reflected by `canonical=false` and the resulting signable hash. Two
+/// implementations producing the same dump for an invoice therefore agree on its encoding.
+pub fn dump_invoice(encoded: &str) -> Result<String, Bolt11ParseError> {
+	let invoice = encoded.parse::<SignedRawBolt11Invoice>()?;
+	let raw_invoice = invoice.raw_invoice();
+
+	let mut dump = String::new();
+	writeln!(dump, "hrp={}", raw_invoice.hrp).unwrap();
+	writeln!(dump, "currency={}", raw_invoice.hrp.currency).unwrap();
+	match raw_invoice.amount_pico_btc() {
+		Some(amount) => writeln!(dump, "amount_pico_btc={}", amount).unwrap(),
+		None => writeln!(dump, "amount_pico_btc=none").unwrap(),
+	}
+	writeln!(dump, "timestamp={}", raw_invoice.data.timestamp.as_unix_timestamp()).unwrap();
+
+	for field in raw_invoice.data.tagged_fields.iter() {
+		let mut fes = field.fe_iter();
+		let tag = fes.next().expect("tagged fields always include a tag");
+		let data = fes.skip(2).map(|fe| fe.to_char()).collect::<String>();
+		writeln!(dump, "field={} data_len={} data={}", tag.to_char(), data.len(), data).unwrap();
+		if let RawTaggedField::KnownSemantics(field) = field {
+			dump_tagged_field(&mut dump, field);
+		}
+	}
+
+	let (recovery_id, signature) = invoice.signature().serialize_compact();
+	writeln!(dump, "signature={}", to_hex(&signature)).unwrap();
+	writeln!(dump, "recovery_id={}", recovery_id.to_i32()).unwrap();
+	match invoice.recover_payee_pub_key() {
+		Ok(pubkey) => {
+			writeln!(dump, "recovered_payee_pub_key={}", to_hex(&pubkey.serialize())).unwrap()
+		},
+		Err(_) => writeln!(dump, "recovered_payee_pub_key=invalid").unwrap(),
+	}
+	writeln!(dump, "signable_hash={}", to_hex(invoice.signable_hash())).unwrap();
+	writeln!(dump, "canonical={}", invoice.to_string() == encoded.to_lowercase()).unwrap();
+
+	Ok(dump)
+}
+
+fn dump_tagged_field(dump: &mut String, field: &TaggedField) {
+	match field {
+		TaggedField::PaymentHash(hash) => {
+			writeln!(dump, "payment_hash={}", to_hex(hash.0.as_byte_array())).unwrap()
+		},
+		TaggedField::Description(description) => {
+			let description = description.as_inner().0.as_bytes();
+			writeln!(dump, "description={}", to_hex(description)).unwrap()
+		},
+		TaggedField::PayeePubKey(pubkey) => {
+			writeln!(dump, "payee_pub_key={}", to_hex(&pubkey.serialize())).unwrap()
+		},
+		TaggedField::DescriptionHash(hash) => {
+			writeln!(dump, "description_hash={}", to_hex(hash.0.as_byte_array())).unwrap()
+		},
+		TaggedField::ExpiryTime(expiry_time) => {
+			writeln!(dump, "expiry_time={}", expiry_time.as_seconds()).unwrap()
+		},
+		TaggedField::MinFinalCltvExpiryDelta(delta) => {
+			writeln!(dump, "min_final_cltv_expiry_delta={}", delta.0).unwrap()
+		},
+		TaggedField::Fallback(fallback) => {
+			let (version, program): (u8, &[u8]) = match fallback {
+				Fallback::SegWitProgram { version, program } => {
+					(version.to_num(), program.as_slice())
+				},
+				Fallback::PubKeyHash(pkh) => (17, &pkh.as_byte_array()[..]),
+				Fallback::ScriptHash(sh) => (18, &sh.as_byte_array()[..]),
+			};
+			writeln!(dump, "fallback={}:{}", version, to_hex(program)).unwrap()
+		},
+		TaggedField::PrivateRoute(route) => {
+			for hop in route.0 .0.iter() {
+				writeln!(
+					dump,
+					"route_hop={}:{}:{}:{}:{}",
+					to_hex(&hop.src_node_id.serialize()),
+					hop.short_channel_id,
+					hop.fees.base_msat,
+					hop.fees.proportional_millionths,
+					hop.cltv_expiry_delta,
+				)
+				.unwrap()
+			}
+		},
+		TaggedField::PaymentSecret(secret) => {
+			writeln!(dump, "payment_secret={}", to_hex(&secret.0)).unwrap()
+		},
+		TaggedField::PaymentMetadata(metadata) => {
+			writeln!(dump, "payment_metadata={}", to_hex(metadata)).unwrap()
+		},
+		TaggedField::Features(features) => {
+			let bits = features
+				.le_flags()
+				.iter()
+				.enumerate()
+				.flat_map(|(idx, byte)| {
+					(0..8).filter(move |bit| byte & (1 << bit) != 0).map(move |bit| idx * 8 + bit)
+				})
+				.map(|bit| bit.to_string())
+				.collect::<Vec<_>>();
+			writeln!(dump, "features={}", bits.join(",")).unwrap()
+		},
+	}
+}
+
+fn to_hex(bytes: &[u8]) -> String {
+	let mut hex = String::with_capacity(bytes.len() * 2);
+	for byte in bytes {
+		write!(hex, "{:02x}", byte).unwrap();
+	}
+	hex
+}
@@ -60,6 +60,7 @@ pub use lightning_types::payment::PaymentSecret;
 pub use lightning_types::routing::{RouteHint, RouteHintHop, RoutingFees};
 use lightning_types::string::UntrustedString;
 
+pub mod conformance;
 mod de;
 mod ser;
 mod tb;
@@ -1818,6 +1819,9 @@ pub enum CreationError {
 
 	/// The provided `min_final_cltv_expiry_delta` was less than rust-lightning's minimum.
 	MinFinalCltvExpiryDeltaTooShort,
+
+	/// A route hint required for the invoice is no longer valid by the time the invoice expires.
+	RouteHintExpired,
 }
 
 impl Display for CreationError {
@@ -1830,6 +1834,8 @@ impl Display for CreationError {
 			CreationError::MissingRouteHints => f.write_str("The invoice required route hints and they weren't provided"),
 			CreationError::MinFinalCltvExpiryDeltaTooShort => f.write_str(
 				"The supplied final CLTV expiry delta was less than LDK's `MIN_FINAL_CLTV_EXPIRY_DELTA`"),
+			CreationError::RouteHintExpired => f.write_str(
+				"A required route hint is no longer valid by the time the invoice expires"),
 		}
 	}
 }
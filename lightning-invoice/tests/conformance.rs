@@ -0,0 +1,351 @@
+//! Golden vectors for checking BOLT 11 encoding conformance across implementations. Each vector
+//! pairs a structured description of an invoice with its canonical encoding and the normalized
+//! dump produced by [`dump_invoice`].
+
+extern crate bech32;
+extern crate lightning_invoice;
+
+use bech32::Fe32;
+use bitcoin::hashes::{sha256, Hash};
+use bitcoin::hex::FromHex;
+use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+use bitcoin::{PubkeyHash, ScriptHash, WitnessVersion};
+use lightning_invoice::conformance::{dump_invoice, encode_invoice};
+use lightning_invoice::*;
+use lightning_types::features::Bolt11InvoiceFeatures;
+use std::time::Duration;
+
+const TIMESTAMP: u64 = 1496314658;
+
+fn pubkey(byte: u8) -> PublicKey {
+	PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&[byte; 32]).unwrap())
+}
+
+fn payment_hash() -> RawTaggedField {
+	let hash = sha256::Hash::from_slice(
+		&<Vec<u8>>::from_hex("0001020304050607080900010203040506070809000102030405060708090102")
+			.unwrap(),
+	)
+	.unwrap();
+	TaggedField::PaymentHash(Sha256(hash)).into()
+}
+
+fn payment_secret() -> RawTaggedField {
+	TaggedField::PaymentSecret(PaymentSecret([0x11; 32])).into()
+}
+
+fn description(description: &str) -> RawTaggedField {
+	TaggedField::Description(Description::new(description.to_owned()).unwrap()).into()
+}
+
+fn route_hop(
+	node_byte: u8, short_channel_id: u64, base_msat: u32, proportional_millionths: u32,
+	cltv_expiry_delta: u16,
+) -> RouteHintHop {
+	RouteHintHop {
+		src_node_id: pubkey(node_byte),
+		short_channel_id,
+		fees: RoutingFees { base_msat, proportional_millionths },
+		cltv_expiry_delta,
+		htlc_minimum_msat: None,
+		htlc_maximum_msat: None,
+	}
+}
+
+fn private_route(hops: Vec<RouteHintHop>) -> RawTaggedField {
+	TaggedField::PrivateRoute(PrivateRoute::new(RouteHint(hops)).unwrap()).into()
+}
+
+fn features(le_flags: Vec<u8>) -> RawTaggedField {
+	TaggedField::Features(Bolt11InvoiceFeatures::from_le_bytes(le_flags)).into()
+}
+
+fn sign(
+	raw_invoice: RawBolt11Invoice, signature: &str, recovery_id: i32,
+) -> SignedRawBolt11Invoice {
+	raw_invoice
+		.sign(|_| {
+			RecoverableSignature::from_compact(
+				&<Vec<u8>>::from_hex(signature).unwrap(),
+				RecoveryId::from_i32(recovery_id).unwrap(),
+			)
+		})
+		.unwrap()
+}
+
+/// Returns the golden vectors as tuples of an invoice's structured description, its canonical
+/// encoding and its normalized dump.
+fn get_golden_vectors() -> Vec<(SignedRawBolt11Invoice, &'static str, &'static str)> {
+	vec![
+		// Huge fees and maximal values for all integers in a route hint.
+		(
+			sign(
+				RawBolt11Invoice {
+					hrp: RawHrp {
+						currency: Currency::Bitcoin,
+						raw_amount: Some(20),
+						si_prefix: Some(SiPrefix::Milli),
+					},
+					data: RawDataPart {
+						timestamp: PositiveTimestamp::from_unix_timestamp(TIMESTAMP).unwrap(),
+						tagged_fields: vec![
+							payment_hash(),
+							payment_secret(),
+							description("huge fee"),
+							private_route(vec![route_hop(
+								1,
+								u64::max_value(),
+								u32::max_value(),
+								u32::max_value(),
+								u16::max_value(),
+							)]),
+							features(vec![0x00, 0x41]),
+						],
+					},
+				},
+				"e3d9b1e16ddb54e755cde42892960b8e9f9c0b0c9b9eb3f4a8884ce7015d7ed30d880e1caf545d85df7894476ab3c6d2e016d3e051ce312bafae902a9ce40881",
+				0,
+			),
+			"lnbc20m1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygsdqddp6kwefqvejk2rzjqvdcf32k0vfxgsyet5ldt246q4jaw8scx3sysx0lnstlt6w4m5rclllllllllllllllllllllllllllllu9qrsgqu0vmrctdmd2ww4wdus5f99st360eczcvnw0t8a9g3pxwwq2a0mfsmzqwrjh4ghv9maufg3m2k0rd9cqk60s9rn339wh6ayp2nnjq3qgq5f0fmr",
+			concat!(
+				"hrp=lnbc20m\n",
+				"currency=bc\n",
+				"amount_pico_btc=20000000000\n",
+				"timestamp=1496314658\n",
+				"field=p data_len=52 data=qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq\n",
+				"payment_hash=0001020304050607080900010203040506070809000102030405060708090102\n",
+				"field=s data_len=52 data=zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs\n",
+				"payment_secret=1111111111111111111111111111111111111111111111111111111111111111\n",
+				"field=d data_len=13 data=dp6kwefqvejk2\n",
+				"description=6875676520666565\n",
+				"field=r data_len=82 data=qvdcf32k0vfxgsyet5ldt246q4jaw8scx3sysx0lnstlt6w4m5rclllllllllllllllllllllllllllllu\n",
+				"route_hop=031b84c5567b126440995d3ed5aaba0565d71e1834604819ff9c17f5e9d5dd078f:18446744073709551615:4294967295:4294967295:65535\n",
+				"field=9 data_len=3 data=sgq\n",
+				"features=8,14\n",
+				"signature=e3d9b1e16ddb54e755cde42892960b8e9f9c0b0c9b9eb3f4a8884ce7015d7ed30d880e1caf545d85df7894476ab3c6d2e016d3e051ce312bafae902a9ce40881\n",
+				"recovery_id=0\n",
+				"recovered_payee_pub_key=02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619\n",
+				"signable_hash=dd52fd1febabd59df1e8302999e7be76f759daf403e7a6ab4fe41409af5dfad4\n",
+				"canonical=true\n",
+			),
+		),
+		// The maximum number of hops in a single route hint, followed by another route hint.
+		(
+			sign(
+				RawBolt11Invoice {
+					hrp: RawHrp {
+						currency: Currency::BitcoinTestnet,
+						raw_amount: None,
+						si_prefix: None,
+					},
+					data: RawDataPart {
+						timestamp: PositiveTimestamp::from_unix_timestamp(TIMESTAMP).unwrap(),
+						tagged_fields: vec![
+							payment_hash(),
+							payment_secret(),
+							TaggedField::DescriptionHash(Sha256(sha256::Hash::hash(
+								b"max routing hints",
+							)))
+							.into(),
+							TaggedField::MinFinalCltvExpiryDelta(MinFinalCltvExpiryDelta(144))
+								.into(),
+							private_route(
+								(0..12u64)
+									.map(|i| {
+										route_hop(
+											i as u8 + 2,
+											(700_000 << 40) | (i << 16) | i,
+											1000 + i as u32,
+											100 * i as u32,
+											40 + i as u16,
+										)
+									})
+									.collect(),
+							),
+							private_route(vec![route_hop(14, 1, 0, 0, 0)]),
+							features(vec![0x00, 0x41]),
+						],
+					},
+				},
+				"18b4478564d404dfcf31f35f6482fca43324be9f278819af68169b0e8425f7273b1a4e560b458603073d444086b26648e7b922f5467426a53c78ee2acd2aeac7",
+				0,
+			),
+			"lntb1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygshp5awm4336sqehd7dxvnwd96hx6acepk0fyse5yt7rxswxa42grfwfqcqzysr75qfx5kmx3xcgr9j5m62htnkgq4fx5tk02mq9vjs3nwnz9rfe9f5rkvz4wvqqqqqqqqqqqqqlgqqqqqqqq9qp9x8lxq6qng5payu33xv38epn6eraxeq79xl56gnput0daev07xdc24esqqqqpqqqsqqqrayqqqqryqq5sx3380xk54tfe29rpgag6wyy97tcsu8r6tylyuqcwlddcwgww2kctp2hxqqqqqgqqyqqqq04qqqqqeqqz5qmzczsydkkvapka6q6rcmfu03uuygyt5rvunney5mgyd5say8us7u92ucqqqqpsqqcqqqp7kqqqqykqq2cr7qr2rr2k20zwmaferler5c0s8lur6gm73q8wvyv8l20n0xsz3c9q4tnqqqqqgqqyqqqq8mqqqqqeqqpvq2vfczmkedtrju0aexl0x8kqds6kpueyn4hwnewc83tky4vkup0k7z4wvqqqqpgqq5qqqqldqqqqraqq95plny0egng7r9220lytn0mzurtc7q2lfsrhvt2stcswd3zjvz3kvxc24esqqqqxqqrqqqqracqqqqjcqqhqy44n9zesezl4sw0zgpv8g7regz9akd3yrhyu9e7xr8a2z2efyzt8p2hxqqqqquqqwqqqq0hsqqqzhsqz7qlhdguaq45xud9yggyf0c6exuvrv9za6wtnuwvz269kp7zr8tw7dc92ucqqqqyqqzqqqqplqqqqqvsqqvqz25kxxzmyk49l2qsse83985ut6j2fcuhz9pe4qrmzshptah3392zq4tnqqqqqjqqfqqqq8ugqqqpcgqp3qv8slwdzgjknrgmfacpt02lmhv9l5wqjhx3eakfngmgr6e75ztghwz4wvqqqqzsqpgqqqqljqqqq86qqxgpz7xe3paxqv5e3hsxhnwjxvxaesgkk047y5xc2rzfwrlgv6ga2drg24esqqqqtqq9sqqqr7vqqqpzvqqesrzjq2vu925962ep5chnj6g84qp2trjjrkhat0w6ej7hy7rwagvfh3xujqqqqqqqqqqqqyqqqqqqqqqqqqqqqq9qrsgqrz6y0pty6szdlne37d0kfqhu5sejf05ly7ypntmgz6dsapp97unnkxjw2c95tpsrqu75gsyxkfny3eaeyt65vapx55783m32e54w43cql7cd4a",
+			concat!(
+				"hrp=lntb\n",
+				"currency=tb\n",
+				"amount_pico_btc=none\n",
+				"timestamp=1496314658\n",
+				"field=p data_len=52 data=qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq\n",
+				"payment_hash=0001020304050607080900010203040506070809000102030405060708090102\n",
+				"field=s data_len=52 data=zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs\n",
+				"payment_secret=1111111111111111111111111111111111111111111111111111111111111111\n",
+				"field=h data_len=52 data=awm4336sqehd7dxvnwd96hx6acepk0fyse5yt7rxswxa42grfwfq\n",
+				"description_hash=ebb758c750066edf34cc9b9a5d5cdaee321b3d24866845f866838ddaa9034b92\n",
+				"field=c data_len=2 data=ys\n",
+				"min_final_cltv_expiry_delta=144\n",
+				"field=r data_len=980 data=qfx5kmx3xcgr9j5m62htnkgq4fx5tk02mq9vjs3nwnz9rfe9f5rkvz4wvqqqqqqqqqqqqqlgqqqqqqqq9qp9x8lxq6qng5payu33xv38epn6eraxeq79xl56gnput0daev07xdc24esqqqqpqqqsqqqrayqqqqryqq5sx3380xk54tfe29rpgag6wyy97tcsu8r6tylyuqcwlddcwgww2kctp2hxqqqqqgqqyqqqq04qqqqqeqqz5qmzczsydkkvapka6q6rcmfu03uuygyt5rvunney5mgyd5say8us7u92ucqqqqpsqqcqqqp7kqqqqykqq2cr7qr2rr2k20zwmaferler5c0s8lur6gm73q8wvyv8l20n0xsz3c9q4tnqqqqqgqqyqqqq8mqqqqqeqqpvq2vfczmkedtrju0aexl0x8kqds6kpueyn4hwnewc83tky4vkup0k7z4wvqqqqpgqq5qqqqldqqqqraqq95plny0egng7r9220lytn0mzurtc7q2lfsrhvt2stcswd3zjvz3kvxc24esqqqqxqqrqqqqracqqqqjcqqhqy44n9zesezl4sw0zgpv8g7regz9akd3yrhyu9e7xr8a2z2efyzt8p2hxqqqqquqqwqqqq0hsqqqzhsqz7qlhdguaq45xud9yggyf0c6exuvrv9za6wtnuwvz269kp7zr8tw7dc92ucqqqqyqqzqqqqplqqqqqvsqqvqz25kxxzmyk49l2qsse83985ut6j2fcuhz9pe4qrmzshptah3392zq4tnqqqqqjqqfqqqq8ugqqqpcgqp3qv8slwdzgjknrgmfacpt02lmhv9l5wqjhx3eakfngmgr6e75ztghwz4wvqqqqzsqpgqqqqljqqqq86qqxgpz7xe3paxqv5e3hsxhnwjxvxaesgkk047y5xc2rzfwrlgv6ga2drg24esqqqqtqq9sqqqr7vqqqpzvqqes\n",
+				"route_hop=024d4b6cd1361032ca9bd2aeb9d900aa4d45d9ead80ac9423374c451a7254d0766:769658139443200000:1000:0:40\n",
+				"route_hop=02531fe6068134503d2723133227c867ac8fa6c83c537e9a44c3c5bdbdcb1fe337:769658139443265537:1001:100:41\n",
+				"route_hop=03462779ad4aad39514614751a71085f2f10e1c7a593e4e030efb5b8721ce55b0b:769658139443331074:1002:200:42\n",
+				"route_hop=0362c0a046dacce86ddd0343c6d3c7c79c2208ba0d9c9cf24a6d046d21d21f90f7:769658139443396611:1003:300:43\n",
+				"route_hop=03f006a18d5653c4edf5391ff23a61f03ff83d237e880ee61187fa9f379a028e0a:769658139443462148:1004:400:44\n",
+				"route_hop=02989c0b76cb563971fdc9bef31ec06c3560f3249d6ee9e5d83c57625596e05f6f:769658139443527685:1005:500:45\n",
+				"route_hop=03f991f944d1e1954a7fc8b9bf62e0d78f015f4c07762d505e20e6c45260a3661b:769658139443593222:1006:600:46\n",
+				"route_hop=0256b328b30c8bf5839e24058747879408bdb36241dc9c2e7c619faa12b2920967:769658139443658759:1007:700:47\n",
+				"route_hop=03f76a39d05686e34a4420897e359371836145dd3973e3982568b60f8433adde6e:769658139443724296:1008:800:48\n",
+				"route_hop=02552c630b64b54bf50210c9e253d38bd4949c72e22873500f6285c2bede312a84:769658139443789833:1009:900:49\n",
+				"route_hop=030f0fb9a244ad31a369ee02b7abfbbb0bfa3812b9a39ed93346d03d67d412d177:769658139443855370:1010:1000:50\n",
+				"route_hop=022f1b310f4c065331bc0d79ba4661bb9822d67d7c4a1b0a1892e1fd0cd23aa68d:769658139443920907:1011:1100:51\n",
+				"field=r data_len=82 data=q2vu925962ep5chnj6g84qp2trjjrkhat0w6ej7hy7rwagvfh3xujqqqqqqqqqqqqyqqqqqqqqqqqqqqqq\n",
+				"route_hop=0299c2aa85d2b21a62f396907a802a58e521dafd5bddaccbd72786eea189bc4dc9:1:0:0:0\n",
+				"field=9 data_len=3 data=sgq\n",
+				"features=8,14\n",
+				"signature=18b4478564d404dfcf31f35f6482fca43324be9f278819af68169b0e8425f7273b1a4e560b458603073d444086b26648e7b922f5467426a53c78ee2acd2aeac7\n",
+				"recovery_id=0\n",
+				"recovered_payee_pub_key=02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619\n",
+				"signable_hash=2a2e201c545fad81f6207bb0368a3b46fcb2531c9be4d19999c295aa50e9dd2d\n",
+				"canonical=true\n",
+			),
+		),
+		// All feature bits, all fallback address types, a non-ASCII description and an unknown
+		// field.
+		(
+			sign(
+				RawBolt11Invoice {
+					hrp: RawHrp {
+						currency: Currency::Regtest,
+						raw_amount: Some(10),
+						si_prefix: Some(SiPrefix::Pico),
+					},
+					data: RawDataPart {
+						timestamp: PositiveTimestamp::from_unix_timestamp(TIMESTAMP).unwrap(),
+						tagged_fields: vec![
+							payment_hash(),
+							payment_secret(),
+							description("☕ café"),
+							TaggedField::PayeePubKey(PayeePubKey(pubkey(0x41))).into(),
+							TaggedField::ExpiryTime(ExpiryTime::from_duration(
+								Duration::from_secs(604800),
+							))
+							.into(),
+							TaggedField::Fallback(Fallback::SegWitProgram {
+								version: WitnessVersion::V0,
+								program: (0..20).collect(),
+							})
+							.into(),
+							TaggedField::Fallback(Fallback::SegWitProgram {
+								version: WitnessVersion::V1,
+								program: (0..32).collect(),
+							})
+							.into(),
+							TaggedField::Fallback(Fallback::PubKeyHash(
+								PubkeyHash::from_slice(&[0xaa; 20]).unwrap(),
+							))
+							.into(),
+							TaggedField::Fallback(Fallback::ScriptHash(
+								ScriptHash::from_slice(&[0xbb; 20]).unwrap(),
+							))
+							.into(),
+							TaggedField::PaymentMetadata(vec![0x01, 0xfa, 0xfa, 0xf0]).into(),
+							features(vec![0xff; 32]),
+							RawTaggedField::UnknownSemantics(
+								"lqvw4hxkmn0wahq".chars().map(|c| Fe32::from_char(c).unwrap()).collect(),
+							),
+						],
+					},
+				},
+				"3d88a01ee0cbc19c0a789b8554e3a84b0e1b50bae1cd361a69b003069d5e311c135d10606e24a2b17714cd3fe5378132e868bc9593237fd6409e1a0d6cdc7f61",
+				1,
+			),
+			"lnbcrt10p1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygsdq0u2vf2grrv9nv82gnp4qthvwfzadd7jejes8q9lhc4rvjxd022zv5l44g6qah82ru5rdpnpjxqyjw5qfppqqqqsyqcyq5rqwzqfpg9scrgwpugpzysnfp4pqqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0sfpp342424242424242424242424242424242fppjhwamhwamhwamhwamhwamhwamhwamhwammq8q8a04uq9p5pllllllllllllllllllllllllllllllllllllllllllllllllllllqvw4hxkmn0wahq8ky2q8hqe0qeczncnwz4fcagfv8pk596u8xnvxnfkqpsd827xywpxhgsvphzfg43wu2v60l9x7qn96rghj2exgml6eqfuxsddnw87cgpkmp2le",
+			concat!(
+				"hrp=lnbcrt10p\n",
+				"currency=bcrt\n",
+				"amount_pico_btc=10\n",
+				"timestamp=1496314658\n",
+				"field=p data_len=52 data=qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq\n",
+				"payment_hash=0001020304050607080900010203040506070809000102030405060708090102\n",
+				"field=s data_len=52 data=zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs\n",
+				"payment_secret=1111111111111111111111111111111111111111111111111111111111111111\n",
+				"field=d data_len=15 data=u2vf2grrv9nv82g\n",
+				"description=e2989520636166c3a9\n",
+				"field=n data_len=53 data=qthvwfzadd7jejes8q9lhc4rvjxd022zv5l44g6qah82ru5rdpnpj\n",
+				"payee_pub_key=02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619\n",
+				"field=x data_len=4 data=jw5q\n",
+				"expiry_time=604800\n",
+				"field=f data_len=33 data=qqqqsyqcyq5rqwzqfpg9scrgwpugpzysn\n",
+				"fallback=0:000102030405060708090a0b0c0d0e0f10111213\n",
+				"field=f data_len=53 data=pqqqsyqcyq5rqwzqfpg9scrgwpugpzysnzs23v9ccrydpk8qarc0s\n",
+				"fallback=1:000102030405060708090a0b0c0d0e0f101112131415161718191a1b1c1d1e1f\n",
+				"field=f data_len=33 data=342424242424242424242424242424242\n",
+				"fallback=17:aaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaaa\n",
+				"field=f data_len=33 data=jhwamhwamhwamhwamhwamhwamhwamhwam\n",
+				"fallback=18:bbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbbb\n",
+				"field=m data_len=7 data=q8a04uq\n",
+				"payment_metadata=01fafaf0\n",
+				"field=9 data_len=52 data=plllllllllllllllllllllllllllllllllllllllllllllllllll\n",
+				"features=0,1,2,3,4,5,6,7,8,9,10,11,12,13,14,15,16,17,18,19,20,21,22,23,24,25,26,27,28,29,30,31,32,33,34,35,36,37,38,39,40,41,42,43,44,45,46,47,48,49,50,51,52,53,54,55,56,57,58,59,60,61,62,63,64,65,66,67,68,69,70,71,72,73,74,75,76,77,78,79,80,81,82,83,84,85,86,87,88,89,90,91,92,93,94,95,96,97,98,99,100,101,102,103,104,105,106,107,108,109,110,111,112,113,114,115,116,117,118,119,120,121,122,123,124,125,126,127,128,129,130,131,132,133,134,135,136,137,138,139,140,141,142,143,144,145,146,147,148,149,150,151,152,153,154,155,156,157,158,159,160,161,162,163,164,165,166,167,168,169,170,171,172,173,174,175,176,177,178,179,180,181,182,183,184,185,186,187,188,189,190,191,192,193,194,195,196,197,198,199,200,201,202,203,204,205,206,207,208,209,210,211,212,213,214,215,216,217,218,219,220,221,222,223,224,225,226,227,228,229,230,231,232,233,234,235,236,237,238,239,240,241,242,243,244,245,246,247,248,249,250,251,252,253,254,255\n",
+				"field=l data_len=12 data=w4hxkmn0wahq\n",
+				"signature=3d88a01ee0cbc19c0a789b8554e3a84b0e1b50bae1cd361a69b003069d5e311c135d10606e24a2b17714cd3fe5378132e868bc9593237fd6409e1a0d6cdc7f61\n",
+				"recovery_id=1\n",
+				"recovered_payee_pub_key=02eec7245d6b7d2ccb30380bfbe2a3648cd7a942653f5aa340edcea1f283686619\n",
+				"signable_hash=c94adc8e45dffa244681c597f52ff3bdbfa8c3e5df490d027d82bd1ea759ecb3\n",
+				"canonical=true\n",
+			),
+		),
+	]
+}
+
+#[test]
+fn encodes_golden_vectors() {
+	for (invoice, encoded, _) in get_golden_vectors() {
+		assert!(invoice.check_signature());
+		assert_eq!(encode_invoice(&invoice), encoded);
+	}
+}
+
+#[test]
+fn dumps_golden_vectors() {
+	for (invoice, encoded, dump) in get_golden_vectors() {
+		assert_eq!(dump_invoice(encoded).unwrap(), dump);
+		assert_eq!(encoded.parse::<SignedRawBolt11Invoice>().unwrap(), invoice);
+
+		// The dump is independent of the case of the encoding.
+		assert_eq!(dump_invoice(&encoded.to_uppercase()).unwrap(), dump);
+	}
+}
+
+#[test]
+fn dumps_non_canonical_encoding() {
+	// Both the expiry time and features are encoded with leading zeros. The dump gives their
+	// canonical encoding, for which the signature recovers to a different payee.
+	let encoded = "lnbc1u1pvjluezpp5qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypqsp5zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygsdq4dehkuttrv9hx7mnfvdskcxq9qqrss9q9qqsgqksw9vcrrvf90hhvsx35dg37mdlskdksm57qksn0dgd3jw7mcxagqhdg09aqal86fkfym2k076v7lejaakqymydua4277dyemlt6ka6qp9dx0pw";
+	let dump = concat!(
+		"hrp=lnbc1u\n",
+		"currency=bc\n",
+		"amount_pico_btc=1000000\n",
+		"timestamp=1496314658\n",
+		"field=p data_len=52 data=qqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqqqsyqcyq5rqwzqfqypq\n",
+		"payment_hash=0001020304050607080900010203040506070809000102030405060708090102\n",
+		"field=s data_len=52 data=zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zyg3zygs\n",
+		"payment_secret=1111111111111111111111111111111111111111111111111111111111111111\n",
+		"field=d data_len=21 data=dehkuttrv9hx7mnfvdskc\n",
+		"description=6e6f6e2d63616e6f6e6963616c\n",
+		"field=x data_len=3 data=rss\n",
+		"expiry_time=3600\n",
+		"field=9 data_len=3 data=sgq\n",
+		"features=8,14\n",
+		"signature=b41c566063624afbdd903468d447db6fe166da1ba781684ded4363277b7837500bb50f2f41df9f49b249b559fed33dfccbbdb009b2379daabde6933bfaf56ee8\n",
+		"recovery_id=1\n",
+		"recovered_payee_pub_key=025aa1197435cca3a3a9b30b3d9a1e988221106b79c6748c715ac55928ee387c55\n",
+		"signable_hash=9759b30d4ddc1b030484a696532571f854624c6d438713b77662daf3b5e6d79a\n",
+		"canonical=false\n",
+	);
+	assert_eq!(dump_invoice(encoded).unwrap(), dump);
+
+	let invoice = encoded.parse::<SignedRawBolt11Invoice>().unwrap();
+	assert_ne!(encode_invoice(&invoice), encoded);
+	assert_ne!(invoice.recover_payee_pub_key().unwrap().0, pubkey(0x41));
+}
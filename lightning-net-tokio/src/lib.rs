@@ -769,6 +769,9 @@ mod tests {
 			Some(vec![ChainHash::using_genesis_block(Network::Testnet)])
 		}
 		fn message_received(&self) {}
+		fn peer_has_pending_htlcs(&self, _their_node_id: PublicKey) -> bool {
+			false
+		}
 	}
 	impl BaseMessageHandler for MsgHandler {
 		fn peer_disconnected(&self, their_node_id: PublicKey) {
@@ -92,6 +92,8 @@ use lightning::util::logger::Logger;
 /// Core functionality of this crate
 mod processing;
 
+pub use processing::{ChunkResult, ChunkedGraphUpdate, GraphSyncProgress};
+
 /// All-encompassing standard error type that processing can return
 #[derive(Debug)]
 pub enum GraphSyncError {
@@ -191,6 +193,29 @@ where
 		self.update_network_graph_from_byte_stream_no_std(&mut read_cursor, current_time_unix)
 	}
 
+	/// Starts an update of the network graph from binary data which is provided in chunks via
+	/// [`ChunkedGraphUpdate::apply_chunk`], e.g., while it is being downloaded.
+	///
+	/// This allows the update to be applied incrementally and resumed if fetching the data is
+	/// interrupted, see [`ChunkedGraphUpdate`] for details.
+	#[cfg(feature = "std")]
+	pub fn start_chunked_update(&self) -> ChunkedGraphUpdate<'_, NG, L> {
+		ChunkedGraphUpdate::new(self, processing::current_time_unix())
+	}
+
+	/// Starts an update of the network graph from binary data which is provided in chunks via
+	/// [`ChunkedGraphUpdate::apply_chunk`], e.g., while it is being downloaded.
+	///
+	/// This allows the update to be applied incrementally and resumed if fetching the data is
+	/// interrupted, see [`ChunkedGraphUpdate`] for details.
+	///
+	/// `current_time_unix`: `Option<u64>` optional current timestamp to verify data age
+	pub fn start_chunked_update_no_std(
+		&self, current_time_unix: Option<u64>,
+	) -> ChunkedGraphUpdate<'_, NG, L> {
+		ChunkedGraphUpdate::new(self, current_time_unix)
+	}
+
 	/// Gets a reference to the underlying [`NetworkGraph`] which was provided in
 	/// [`RapidGossipSync::new`].
 	///
@@ -37,6 +37,234 @@ const MAX_INITIAL_NODE_ID_VECTOR_CAPACITY: u32 = 50_000;
 /// suggestion.
 const STALE_RGS_UPDATE_AGE_LIMIT_SECS: u64 = 60 * 60 * 24 * 14;
 
+/// The section of the rapid gossip sync data which is read next.
+enum DataSection {
+	Header,
+	NodeIds { remaining: u32 },
+	ChannelAnnouncementCount,
+	ChannelAnnouncements { remaining: u32 },
+	ChannelUpdateCount,
+	ChannelUpdateDefaults,
+	ChannelUpdates { remaining: u32 },
+	Complete,
+}
+
+struct DataHeader {
+	version: u8,
+	chain_hash: ChainHash,
+	latest_seen_timestamp: u32,
+	backdated_timestamp: u32,
+	default_node_features: Vec<NodeFeatures>,
+	node_id_count: u32,
+}
+
+/// Default values for non-incremental channel updates.
+struct ChannelUpdateDefaults {
+	cltv_expiry_delta: u16,
+	htlc_minimum_msat: u64,
+	fee_base_msat: u32,
+	fee_proportional_millionths: u32,
+	htlc_maximum_msat: u64,
+}
+
+/// The state of processing rapid gossip sync data, which allows the data to be read one record at
+/// a time via [`RapidGossipSync::process_next_record`].
+struct ProcessingState {
+	current_time_unix: Option<u64>,
+	next_section: DataSection,
+	header: Option<DataHeader>,
+	/// The node ids referenced by index in channel announcements.
+	node_ids: Vec<NodeId>,
+	node_modifications: Vec<UnsignedNodeAnnouncement>,
+	previous_scid: u64,
+	previous_channel_direction: Option<bool>,
+	channel_update_defaults: Option<ChannelUpdateDefaults>,
+	channel_announcement_count: Option<u32>,
+	channel_announcements_applied: u32,
+	channel_update_count: Option<u32>,
+	channel_updates_applied: u32,
+}
+
+impl ProcessingState {
+	fn new(current_time_unix: Option<u64>) -> Self {
+		Self {
+			current_time_unix,
+			next_section: DataSection::Header,
+			header: None,
+			node_ids: Vec::new(),
+			node_modifications: Vec::new(),
+			previous_scid: 0,
+			previous_channel_direction: None,
+			channel_update_defaults: None,
+			channel_announcement_count: None,
+			channel_announcements_applied: 0,
+			channel_update_count: None,
+			channel_updates_applied: 0,
+		}
+	}
+
+	fn is_complete(&self) -> bool {
+		matches!(self.next_section, DataSection::Complete)
+	}
+
+	fn latest_seen_timestamp(&self) -> u32 {
+		self.header.as_ref().expect("The header is read first").latest_seen_timestamp
+	}
+}
+
+#[cfg(feature = "std")]
+pub(crate) fn current_time_unix() -> Option<u64> {
+	#[allow(unused_mut, unused_assignments)]
+	let mut current_time_unix = None;
+	#[cfg(not(test))]
+	{
+		// Note that many tests rely on being able to set arbitrarily old timestamps, thus we
+		// disable this check during tests!
+		current_time_unix = Some(
+			SystemTime::now().duration_since(UNIX_EPOCH).expect("Time must be > 1970").as_secs(),
+		);
+	}
+	current_time_unix
+}
+
+/// Returns whether the given error was caused by running out of data while reading a record.
+fn is_incomplete_read(error: &GraphSyncError) -> bool {
+	matches!(
+		error,
+		GraphSyncError::DecodeError(DecodeError::ShortRead)
+			| GraphSyncError::DecodeError(DecodeError::Io(io::ErrorKind::UnexpectedEof))
+	)
+}
+
+/// The progress of applying rapid gossip sync data via a [`ChunkedGraphUpdate`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GraphSyncProgress {
+	/// The number of bytes of the data which were passed to [`ChunkedGraphUpdate::apply_chunk`]
+	/// so far.
+	///
+	/// If fetching the data was interrupted, it should be resumed from this byte offset, e.g., via
+	/// an HTTP range request.
+	pub bytes_received: u64,
+	/// The number of channel announcements applied so far, including ones which were already
+	/// known.
+	pub channel_announcements_applied: u32,
+	/// The total number of channel announcements in the data, if known yet.
+	pub channel_announcements_expected: Option<u32>,
+	/// The number of channel updates applied so far, including ones which were ignored.
+	pub channel_updates_applied: u32,
+	/// The total number of channel updates in the data, if known yet.
+	pub channel_updates_expected: Option<u32>,
+}
+
+/// The result of [`ChunkedGraphUpdate::apply_chunk`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChunkResult {
+	/// All records contained in the data received so far were applied, but more data is needed to
+	/// complete the update.
+	InProgress(GraphSyncProgress),
+	/// The update is complete. Contains the last sync timestamp to be used the next time rapid
+	/// sync data is queried.
+	///
+	/// Any data following the end of the update is ignored.
+	Complete(u32),
+}
+
+/// Applies rapid gossip sync data to the network graph as it is received in chunks of arbitrary
+/// size, e.g., while it is being downloaded, rather than all at once.
+///
+/// Each chunk is applied as far as possible, with incomplete records at its end being buffered
+/// until the next chunk arrives. Thus, if fetching the data is interrupted, it can be resumed by
+/// fetching the remaining data starting at [`GraphSyncProgress::bytes_received`] and passing it
+/// to the same [`ChunkedGraphUpdate`].
+///
+/// Note that the state needed to resume is only held in memory, so an update interrupted by a
+/// restart has to be started over.
+///
+/// Created via [`RapidGossipSync::start_chunked_update`] or
+/// [`RapidGossipSync::start_chunked_update_no_std`].
+pub struct ChunkedGraphUpdate<'a, NG: Deref<Target = NetworkGraph<L>>, L: Deref>
+where
+	L::Target: Logger,
+{
+	rapid_sync: &'a RapidGossipSync<NG, L>,
+	state: ProcessingState,
+	/// Data received but not yet applied, i.e., an incomplete record.
+	pending_data: Vec<u8>,
+	bytes_received: u64,
+	failed: bool,
+}
+
+impl<'a, NG: Deref<Target = NetworkGraph<L>>, L: Deref> ChunkedGraphUpdate<'a, NG, L>
+where
+	L::Target: Logger,
+{
+	pub(crate) fn new(
+		rapid_sync: &'a RapidGossipSync<NG, L>, current_time_unix: Option<u64>,
+	) -> Self {
+		log_trace!(rapid_sync.logger, "Processing RGS data in chunks...");
+		Self {
+			rapid_sync,
+			state: ProcessingState::new(current_time_unix),
+			pending_data: Vec::new(),
+			bytes_received: 0,
+			failed: false,
+		}
+	}
+
+	/// Applies the next chunk of the rapid gossip sync data, which directly follows the data
+	/// passed in previous calls.
+	///
+	/// Once an error is returned, the update is aborted and all subsequent calls will fail.
+	pub fn apply_chunk(&mut self, chunk: &[u8]) -> Result<ChunkResult, GraphSyncError> {
+		if self.failed {
+			return Err(LightningError {
+				err: "Rapid Gossip Sync data failed to apply previously".to_owned(),
+				action: ErrorAction::IgnoreError,
+			}
+			.into());
+		}
+		if self.state.is_complete() {
+			return Ok(ChunkResult::Complete(self.state.latest_seen_timestamp()));
+		}
+
+		self.pending_data.extend_from_slice(chunk);
+		self.bytes_received += chunk.len() as u64;
+
+		let mut consumed_bytes = 0;
+		while !self.state.is_complete() {
+			let mut read_cursor = &self.pending_data[consumed_bytes..];
+			match self.rapid_sync.process_next_record(&mut self.state, &mut read_cursor) {
+				Ok(()) => consumed_bytes = self.pending_data.len() - read_cursor.len(),
+				// The record is incomplete and will be read again once more data is available.
+				Err(e) if is_incomplete_read(&e) => break,
+				Err(e) => {
+					self.failed = true;
+					return Err(e);
+				},
+			}
+		}
+		self.pending_data.drain(..consumed_bytes);
+
+		if self.state.is_complete() {
+			self.pending_data = Vec::new();
+			Ok(ChunkResult::Complete(self.state.latest_seen_timestamp()))
+		} else {
+			Ok(ChunkResult::InProgress(self.progress()))
+		}
+	}
+
+	/// Returns the progress of the update so far.
+	pub fn progress(&self) -> GraphSyncProgress {
+		GraphSyncProgress {
+			bytes_received: self.bytes_received,
+			channel_announcements_applied: self.state.channel_announcements_applied,
+			channel_announcements_expected: self.state.channel_announcement_count,
+			channel_updates_applied: self.state.channel_updates_applied,
+			channel_updates_expected: self.state.channel_update_count,
+		}
+	}
+}
+
 impl<NG: Deref<Target = NetworkGraph<L>>, L: Deref> RapidGossipSync<NG, L>
 where
 	L::Target: Logger,
@@ -45,26 +273,221 @@ where
 	pub(crate) fn update_network_graph_from_byte_stream<R: io::Read>(
 		&self, read_cursor: &mut R,
 	) -> Result<u32, GraphSyncError> {
-		#[allow(unused_mut, unused_assignments)]
-		let mut current_time_unix = None;
-		#[cfg(not(test))]
-		{
-			// Note that many tests rely on being able to set arbitrarily old timestamps, thus we
-			// disable this check during tests!
-			current_time_unix = Some(
-				SystemTime::now()
-					.duration_since(UNIX_EPOCH)
-					.expect("Time must be > 1970")
-					.as_secs(),
-			);
-		}
-		self.update_network_graph_from_byte_stream_no_std(read_cursor, current_time_unix)
+		self.update_network_graph_from_byte_stream_no_std(read_cursor, current_time_unix())
 	}
 
 	pub(crate) fn update_network_graph_from_byte_stream_no_std<R: io::Read>(
 		&self, read_cursor: &mut R, current_time_unix: Option<u64>,
 	) -> Result<u32, GraphSyncError> {
 		log_trace!(self.logger, "Processing RGS data...");
+		let mut state = ProcessingState::new(current_time_unix);
+		while !state.is_complete() {
+			self.process_next_record(&mut state, read_cursor)?;
+		}
+		Ok(state.latest_seen_timestamp())
+	}
+
+	/// Reads the next record of the rapid gossip sync data from `read_cursor` and applies it.
+	///
+	/// The record is only applied, and `state` only advanced, once it was read in full. Thus, if
+	/// reading fails due to running out of data, it can be retried once more data is available.
+	fn process_next_record<R: io::Read>(
+		&self, state: &mut ProcessingState, read_cursor: &mut R,
+	) -> Result<(), GraphSyncError> {
+		let network_graph = &self.network_graph;
+		match state.next_section {
+			DataSection::Header => {
+				let header = self.read_header(read_cursor, state.current_time_unix)?;
+				state.node_ids = Vec::with_capacity(core::cmp::min(
+					header.node_id_count,
+					MAX_INITIAL_NODE_ID_VECTOR_CAPACITY,
+				) as usize);
+				state.next_section = DataSection::NodeIds { remaining: header.node_id_count };
+				state.header = Some(header);
+			},
+			DataSection::NodeIds { remaining: 0 } => {
+				state.next_section = DataSection::ChannelAnnouncementCount;
+			},
+			DataSection::NodeIds { remaining } => {
+				let header = state.header.as_ref().expect("The header is read first");
+				let node_id = if header.version == 2 {
+					let (node_id, node_modification) =
+						self.read_node_details(read_cursor, header)?;
+					if let Some(node_modification) = node_modification {
+						state.node_modifications.push(node_modification);
+					}
+					node_id
+				} else {
+					Readable::read(read_cursor)?
+				};
+				state.node_ids.push(node_id);
+				state.next_section = DataSection::NodeIds { remaining: remaining - 1 };
+			},
+			DataSection::ChannelAnnouncementCount => {
+				let announcement_count: u32 = Readable::read(read_cursor)?;
+				state.channel_announcement_count = Some(announcement_count);
+				state.next_section =
+					DataSection::ChannelAnnouncements { remaining: announcement_count };
+			},
+			DataSection::ChannelAnnouncements { remaining: 0 } => {
+				for modification in state.node_modifications.drain(..) {
+					match network_graph.update_node_from_unsigned_announcement(&modification) {
+						Ok(_) => {},
+						Err(LightningError {
+							action: ErrorAction::IgnoreDuplicateGossip, ..
+						}) => {},
+						Err(LightningError { action: ErrorAction::IgnoreAndLog(level), err }) => {
+							log_given_level!(
+								self.logger,
+								level,
+								"Failed to apply node announcement: {:?}",
+								err
+							);
+						},
+						Err(LightningError { action: ErrorAction::IgnoreError, err }) => {
+							log_gossip!(
+								self.logger,
+								"Failed to apply node announcement: {:?}",
+								err
+							);
+						},
+						Err(e) => return Err(e.into()),
+					}
+				}
+
+				// updates start at a new scid
+				state.previous_scid = 0;
+				state.next_section = DataSection::ChannelUpdateCount;
+			},
+			DataSection::ChannelAnnouncements { remaining } => {
+				let header = state.header.as_ref().expect("The header is read first");
+				let features = Readable::read(read_cursor)?;
+
+				// handle SCID
+				let scid_delta: BigSize = Readable::read(read_cursor)?;
+				let short_channel_id = state
+					.previous_scid
+					.checked_add(scid_delta.0)
+					.ok_or(DecodeError::InvalidValue)?;
+
+				let node_id_1_index: BigSize = Readable::read(read_cursor)?;
+				let mut node_id_2_index: BigSize = Readable::read(read_cursor)?;
+				let has_additional_data = (node_id_2_index.0 & (1 << 63)) > 0;
+				// ensure 63rd bit isn't set
+				node_id_2_index.0 &= !(1 << 63);
+
+				if max(node_id_1_index.0, node_id_2_index.0) >= header.node_id_count as u64 {
+					return Err(DecodeError::InvalidValue.into());
+				};
+				let node_id_1 = state.node_ids[node_id_1_index.0 as usize];
+				let node_id_2 = state.node_ids[node_id_2_index.0 as usize];
+
+				let mut funding_sats: Option<u64> = None;
+				let mut ignored_additional_data_len = 0;
+
+				if header.version >= 2 && has_additional_data {
+					// forwards compatibility
+					let additional_data: Vec<u8> = Readable::read(read_cursor)?;
+					let mut cursor = &additional_data[..];
+					let funding_sats_read: BigSize = Readable::read(&mut cursor)?;
+					funding_sats = Some(funding_sats_read.0);
+					ignored_additional_data_len = cursor.len();
+				}
+
+				state.previous_scid = short_channel_id;
+				state.channel_announcements_applied += 1;
+				state.next_section = DataSection::ChannelAnnouncements { remaining: remaining - 1 };
+
+				log_gossip!(
+					self.logger,
+					"Adding channel {} from RGS announcement at {}",
+					short_channel_id,
+					header.latest_seen_timestamp
+				);
+				if ignored_additional_data_len > 0 {
+					log_gossip!(
+						self.logger,
+						"Ignoring {} bytes of additional data in channel announcement",
+						ignored_additional_data_len
+					);
+				}
+
+				let announcement_result = network_graph.add_channel_from_partial_announcement(
+					short_channel_id,
+					funding_sats,
+					header.backdated_timestamp as u64,
+					features,
+					node_id_1,
+					node_id_2,
+				);
+				if let Err(lightning_error) = announcement_result {
+					if let ErrorAction::IgnoreDuplicateGossip = lightning_error.action {
+						// everything is fine, just a duplicate channel announcement
+					} else {
+						log_warn!(
+							self.logger,
+							"Failed to process channel announcement: {:?}",
+							lightning_error
+						);
+						return Err(lightning_error.into());
+					}
+				}
+			},
+			DataSection::ChannelUpdateCount => {
+				let header = state.header.as_ref().expect("The header is read first");
+				let update_count: u32 = Readable::read(read_cursor)?;
+				log_debug!(self.logger, "Processing RGS update from {} with {} nodes, {} channel announcements and {} channel updates.",
+					header.latest_seen_timestamp, header.node_id_count, state.channel_announcement_count.unwrap_or(0), update_count);
+				state.channel_update_count = Some(update_count);
+				state.next_section = if update_count == 0 {
+					DataSection::Complete
+				} else {
+					DataSection::ChannelUpdateDefaults
+				};
+			},
+			DataSection::ChannelUpdateDefaults => {
+				// obtain default values for non-incremental updates
+				let cltv_expiry_delta: u16 = Readable::read(read_cursor)?;
+				let htlc_minimum_msat: u64 = Readable::read(read_cursor)?;
+				let fee_base_msat: u32 = Readable::read(read_cursor)?;
+				let fee_proportional_millionths: u32 = Readable::read(read_cursor)?;
+				let htlc_maximum_msat: u64 = Readable::read(read_cursor)?;
+				state.channel_update_defaults = Some(ChannelUpdateDefaults {
+					cltv_expiry_delta,
+					htlc_minimum_msat,
+					fee_base_msat,
+					fee_proportional_millionths,
+					htlc_maximum_msat,
+				});
+				state.next_section = DataSection::ChannelUpdates {
+					remaining: state.channel_update_count.unwrap_or(0),
+				};
+			},
+			DataSection::ChannelUpdates { remaining: 0 } => {
+				let latest_seen_timestamp = state.latest_seen_timestamp();
+				self.network_graph.set_last_rapid_gossip_sync_timestamp(latest_seen_timestamp);
+
+				if let Some(time) = state.current_time_unix {
+					self.network_graph.remove_stale_channels_and_tracking_with_time(time)
+				}
+
+				self.is_initial_sync_complete.store(true, Ordering::Release);
+				log_trace!(self.logger, "Done processing RGS data from {}", latest_seen_timestamp);
+				state.next_section = DataSection::Complete;
+			},
+			DataSection::ChannelUpdates { remaining } => {
+				self.process_channel_update(state, read_cursor)?;
+				state.channel_updates_applied += 1;
+				state.next_section = DataSection::ChannelUpdates { remaining: remaining - 1 };
+			},
+			DataSection::Complete => {},
+		}
+		Ok(())
+	}
+
+	fn read_header<R: io::Read>(
+		&self, read_cursor: &mut R, current_time_unix: Option<u64>,
+	) -> Result<DataHeader, GraphSyncError> {
 		let mut protocol_prefix = [0u8; 3];
 
 		read_cursor.read_exact(&mut protocol_prefix)?;
@@ -116,361 +539,264 @@ where
 		};
 
 		let node_id_count: u32 = Readable::read(read_cursor)?;
-		let mut node_ids: Vec<NodeId> = Vec::with_capacity(core::cmp::min(
-			node_id_count,
-			MAX_INITIAL_NODE_ID_VECTOR_CAPACITY,
-		) as usize);
 
-		let network_graph = &self.network_graph;
-		let mut node_modifications: Vec<UnsignedNodeAnnouncement> = Vec::new();
+		Ok(DataHeader {
+			version,
+			chain_hash,
+			latest_seen_timestamp,
+			backdated_timestamp,
+			default_node_features,
+			node_id_count,
+		})
+	}
 
-		if parse_node_details {
-			let read_only_network_graph = network_graph.read_only();
-			for _ in 0..node_id_count {
-				let mut pubkey_bytes = [0u8; 33];
-				read_cursor.read_exact(&mut pubkey_bytes)?;
-
-				/*
-				We encode additional information in the pubkey parity byte with the following mapping:
-
-				7: expect extra data after the pubkey
-				6: use this as a reminder without altering anything
-				5-3: index of new features among default (1-6). If index is 7 (all 3 bits are set, it's
-				outside the present default range). 0 means no feature changes.
-				2: addresses have changed
-
-				1: used for all keys
-				0: used for odd keys
-				*/
-				let node_detail_flag = pubkey_bytes.first().ok_or(DecodeError::ShortRead)?;
-
-				let has_address_details = (node_detail_flag & (1 << 2)) > 0;
-				let feature_detail_marker = (node_detail_flag & (0b111 << 3)) >> 3;
-				let is_reminder = (node_detail_flag & (1 << 6)) > 0;
-				let has_additional_data = (node_detail_flag & (1 << 7)) > 0;
-
-				// extract the relevant bits for pubkey parity
-				let key_parity = node_detail_flag & 0b_0000_0011;
-				pubkey_bytes[0] = key_parity;
-
-				let current_node_id = NodeId::from_slice(&pubkey_bytes)?;
-				node_ids.push(current_node_id);
-
-				if is_reminder || has_address_details || feature_detail_marker > 0 {
-					let mut synthetic_node_announcement = UnsignedNodeAnnouncement {
-						features: NodeFeatures::empty(),
-						timestamp: backdated_timestamp,
-						node_id: current_node_id,
-						rgb: [0, 0, 0],
-						alias: NodeAlias([0u8; 32]),
-						addresses: Vec::new(),
-						excess_address_data: Vec::new(),
-						excess_data: Vec::new(),
-					};
-
-					read_only_network_graph
-						.nodes()
-						.get(&current_node_id)
-						.and_then(|node| node.announcement_info.as_ref())
-						.map(|info| {
-							synthetic_node_announcement.features = info.features().clone();
-							synthetic_node_announcement.rgb.clone_from(&info.rgb());
-							synthetic_node_announcement.alias = info.alias().clone();
-							synthetic_node_announcement.addresses = info.addresses().to_vec();
-						});
-
-					if has_address_details {
-						let address_count: u8 = Readable::read(read_cursor)?;
-						let mut node_addresses: Vec<SocketAddress> = Vec::new();
-						for address_index in 0..address_count {
-							let current_byte_count: u8 = Readable::read(read_cursor)?;
-							let mut address_reader =
-								FixedLengthReader::new(read_cursor, current_byte_count as u64);
-							if let Ok(current_address) = Readable::read(&mut address_reader) {
-								node_addresses.push(current_address);
-								if address_reader.bytes_remain() {
-									return Err(DecodeError::ShortRead.into());
-								}
-							} else {
-								// Do not crash to allow future socket address forwards compatibility
-								log_gossip!(
-									self.logger,
-									"Failure to parse address at index {} for node ID {}",
-									address_index,
-									current_node_id
-								);
-								address_reader.eat_remaining()?;
-							}
-						}
-						synthetic_node_announcement.addresses = node_addresses;
-					}
+	/// Reads a node id along with the details encoded alongside it in version 2 of the format,
+	/// returning the node announcement to apply, if any.
+	fn read_node_details<R: io::Read>(
+		&self, read_cursor: &mut R, header: &DataHeader,
+	) -> Result<(NodeId, Option<UnsignedNodeAnnouncement>), GraphSyncError> {
+		let mut pubkey_bytes = [0u8; 33];
+		read_cursor.read_exact(&mut pubkey_bytes)?;
+
+		/*
+		We encode additional information in the pubkey parity byte with the following mapping:
+
+		7: expect extra data after the pubkey
+		6: use this as a reminder without altering anything
+		5-3: index of new features among default (1-6). If index is 7 (all 3 bits are set, it's
+		outside the present default range). 0 means no feature changes.
+		2: addresses have changed
+
+		1: used for all keys
+		0: used for odd keys
+		*/
+		let node_detail_flag = pubkey_bytes.first().ok_or(DecodeError::ShortRead)?;
+
+		let has_address_details = (node_detail_flag & (1 << 2)) > 0;
+		let feature_detail_marker = (node_detail_flag & (0b111 << 3)) >> 3;
+		let is_reminder = (node_detail_flag & (1 << 6)) > 0;
+		let has_additional_data = (node_detail_flag & (1 << 7)) > 0;
+
+		// extract the relevant bits for pubkey parity
+		let key_parity = node_detail_flag & 0b_0000_0011;
+		pubkey_bytes[0] = key_parity;
+
+		let current_node_id = NodeId::from_slice(&pubkey_bytes)?;
+
+		let mut node_modification = None;
+		// Logging is deferred until the record was read in full, as it may be read again otherwise.
+		let mut unparseable_address_indices = Vec::new();
+		if is_reminder || has_address_details || feature_detail_marker > 0 {
+			let mut synthetic_node_announcement = UnsignedNodeAnnouncement {
+				features: NodeFeatures::empty(),
+				timestamp: header.backdated_timestamp,
+				node_id: current_node_id,
+				rgb: [0, 0, 0],
+				alias: NodeAlias([0u8; 32]),
+				addresses: Vec::new(),
+				excess_address_data: Vec::new(),
+				excess_data: Vec::new(),
+			};
 
-					if feature_detail_marker > 0 {
-						if feature_detail_marker < 7 {
-							let feature_index = (feature_detail_marker - 1) as usize;
-							synthetic_node_announcement.features = default_node_features
-								.get(feature_index)
-								.ok_or(DecodeError::InvalidValue)?
-								.clone();
-						} else {
-							let node_features: NodeFeatures = Readable::read(read_cursor)?;
-							synthetic_node_announcement.features = node_features;
+			self.network_graph
+				.read_only()
+				.nodes()
+				.get(&current_node_id)
+				.and_then(|node| node.announcement_info.as_ref())
+				.map(|info| {
+					synthetic_node_announcement.features = info.features().clone();
+					synthetic_node_announcement.rgb.clone_from(&info.rgb());
+					synthetic_node_announcement.alias = info.alias().clone();
+					synthetic_node_announcement.addresses = info.addresses().to_vec();
+				});
+
+			if has_address_details {
+				let address_count: u8 = Readable::read(read_cursor)?;
+				let mut node_addresses: Vec<SocketAddress> = Vec::new();
+				for address_index in 0..address_count {
+					let current_byte_count: u8 = Readable::read(read_cursor)?;
+					let mut address_reader =
+						FixedLengthReader::new(read_cursor, current_byte_count as u64);
+					if let Ok(current_address) = Readable::read(&mut address_reader) {
+						node_addresses.push(current_address);
+						if address_reader.bytes_remain() {
+							return Err(DecodeError::ShortRead.into());
 						}
+					} else {
+						// Do not crash to allow future socket address forwards compatibility
+						unparseable_address_indices.push(address_index);
+						address_reader.eat_remaining()?;
 					}
-
-					node_modifications.push(synthetic_node_announcement);
 				}
+				synthetic_node_announcement.addresses = node_addresses;
+			}
 
-				if has_additional_data {
-					let additional_data: Vec<u8> = Readable::read(read_cursor)?;
-					log_gossip!(
-						self.logger,
-						"Ignoring {} bytes of additional data in node announcement",
-						additional_data.len()
-					);
+			if feature_detail_marker > 0 {
+				if feature_detail_marker < 7 {
+					let feature_index = (feature_detail_marker - 1) as usize;
+					synthetic_node_announcement.features = header
+						.default_node_features
+						.get(feature_index)
+						.ok_or(DecodeError::InvalidValue)?
+						.clone();
+				} else {
+					let node_features: NodeFeatures = Readable::read(read_cursor)?;
+					synthetic_node_announcement.features = node_features;
 				}
 			}
-		} else {
-			for _ in 0..node_id_count {
-				let current_node_id = Readable::read(read_cursor)?;
-				node_ids.push(current_node_id);
-			}
+
+			node_modification = Some(synthetic_node_announcement);
 		}
 
-		let mut previous_scid: u64 = 0;
-		let announcement_count: u32 = Readable::read(read_cursor)?;
-		for _ in 0..announcement_count {
-			let features = Readable::read(read_cursor)?;
-
-			// handle SCID
-			let scid_delta: BigSize = Readable::read(read_cursor)?;
-			let short_channel_id =
-				previous_scid.checked_add(scid_delta.0).ok_or(DecodeError::InvalidValue)?;
-			previous_scid = short_channel_id;
-
-			let node_id_1_index: BigSize = Readable::read(read_cursor)?;
-			let mut node_id_2_index: BigSize = Readable::read(read_cursor)?;
-			let has_additional_data = (node_id_2_index.0 & (1 << 63)) > 0;
-			// ensure 63rd bit isn't set
-			node_id_2_index.0 &= !(1 << 63);
-
-			if max(node_id_1_index.0, node_id_2_index.0) >= node_id_count as u64 {
-				return Err(DecodeError::InvalidValue.into());
-			};
-			let node_id_1 = node_ids[node_id_1_index.0 as usize];
-			let node_id_2 = node_ids[node_id_2_index.0 as usize];
+		let mut additional_data_len = None;
+		if has_additional_data {
+			let additional_data: Vec<u8> = Readable::read(read_cursor)?;
+			additional_data_len = Some(additional_data.len());
+		}
 
+		for address_index in unparseable_address_indices {
 			log_gossip!(
 				self.logger,
-				"Adding channel {} from RGS announcement at {}",
-				short_channel_id,
-				latest_seen_timestamp
-			);
-
-			let mut funding_sats: Option<u64> = None;
-
-			if version >= 2 && has_additional_data {
-				// forwards compatibility
-				let additional_data: Vec<u8> = Readable::read(read_cursor)?;
-				let mut cursor = &additional_data[..];
-				let funding_sats_read: BigSize = Readable::read(&mut cursor)?;
-				funding_sats = Some(funding_sats_read.0);
-				if !cursor.is_empty() {
-					log_gossip!(
-						self.logger,
-						"Ignoring {} bytes of additional data in channel announcement",
-						cursor.len()
-					);
-				}
-			}
-
-			let announcement_result = network_graph.add_channel_from_partial_announcement(
-				short_channel_id,
-				funding_sats,
-				backdated_timestamp as u64,
-				features,
-				node_id_1,
-				node_id_2,
+				"Failure to parse address at index {} for node ID {}",
+				address_index,
+				current_node_id
 			);
-			if let Err(lightning_error) = announcement_result {
-				if let ErrorAction::IgnoreDuplicateGossip = lightning_error.action {
-					// everything is fine, just a duplicate channel announcement
-				} else {
-					log_warn!(
-						self.logger,
-						"Failed to process channel announcement: {:?}",
-						lightning_error
-					);
-					return Err(lightning_error.into());
-				}
-			}
 		}
-
-		for modification in node_modifications {
-			match network_graph.update_node_from_unsigned_announcement(&modification) {
-				Ok(_) => {},
-				Err(LightningError { action: ErrorAction::IgnoreDuplicateGossip, .. }) => {},
-				Err(LightningError { action: ErrorAction::IgnoreAndLog(level), err }) => {
-					log_given_level!(
-						self.logger,
-						level,
-						"Failed to apply node announcement: {:?}",
-						err
-					);
-				},
-				Err(LightningError { action: ErrorAction::IgnoreError, err }) => {
-					log_gossip!(self.logger, "Failed to apply node announcement: {:?}", err);
-				},
-				Err(e) => return Err(e.into()),
-			}
-		}
-
-		// updates start at a new scid
-		previous_scid = 0;
-
-		let update_count: u32 = Readable::read(read_cursor)?;
-		log_debug!(self.logger, "Processing RGS update from {} with {} nodes, {} channel announcements and {} channel updates.",
-			latest_seen_timestamp, node_id_count, announcement_count, update_count);
-		if update_count == 0 {
-			return Ok(latest_seen_timestamp);
+		if let Some(additional_data_len) = additional_data_len {
+			log_gossip!(
+				self.logger,
+				"Ignoring {} bytes of additional data in node announcement",
+				additional_data_len
+			);
 		}
 
-		// obtain default values for non-incremental updates
-		let default_cltv_expiry_delta: u16 = Readable::read(read_cursor)?;
-		let default_htlc_minimum_msat: u64 = Readable::read(read_cursor)?;
-		let default_fee_base_msat: u32 = Readable::read(read_cursor)?;
-		let default_fee_proportional_millionths: u32 = Readable::read(read_cursor)?;
-		let default_htlc_maximum_msat: u64 = Readable::read(read_cursor)?;
+		Ok((current_node_id, node_modification))
+	}
 
-		let mut previous_channel_direction = None;
+	fn process_channel_update<R: io::Read>(
+		&self, state: &mut ProcessingState, read_cursor: &mut R,
+	) -> Result<(), GraphSyncError> {
+		let network_graph = &self.network_graph;
+		let header = state.header.as_ref().expect("The header is read first");
+		let defaults =
+			state.channel_update_defaults.as_ref().expect("The defaults are read before updates");
 
-		for _ in 0..update_count {
-			let scid_delta: BigSize = Readable::read(read_cursor)?;
-			let short_channel_id =
-				previous_scid.checked_add(scid_delta.0).ok_or(DecodeError::InvalidValue)?;
-			previous_scid = short_channel_id;
+		let scid_delta: BigSize = Readable::read(read_cursor)?;
+		let short_channel_id =
+			state.previous_scid.checked_add(scid_delta.0).ok_or(DecodeError::InvalidValue)?;
 
-			let channel_flags: u8 = Readable::read(read_cursor)?;
+		let channel_flags: u8 = Readable::read(read_cursor)?;
 
-			if version >= 2 {
-				let direction = (channel_flags & 1) == 1;
-				let is_same_direction_update = Some(direction) == previous_channel_direction;
-				previous_channel_direction = Some(direction);
+		let mut channel_direction = state.previous_channel_direction;
+		if header.version >= 2 {
+			let direction = (channel_flags & 1) == 1;
+			let is_same_direction_update = Some(direction) == state.previous_channel_direction;
+			channel_direction = Some(direction);
 
-				if scid_delta.0 == 0 && is_same_direction_update {
-					// this is additional data for forwards compatibility
-					let additional_data: Vec<u8> = Readable::read(read_cursor)?;
-					log_gossip!(
-						self.logger,
-						"Ignoring {} bytes of additional data in channel update",
-						additional_data.len()
-					);
-					continue;
-				}
+			if scid_delta.0 == 0 && is_same_direction_update {
+				// this is additional data for forwards compatibility
+				let additional_data: Vec<u8> = Readable::read(read_cursor)?;
+				state.previous_scid = short_channel_id;
+				state.previous_channel_direction = channel_direction;
+				log_gossip!(
+					self.logger,
+					"Ignoring {} bytes of additional data in channel update",
+					additional_data.len()
+				);
+				return Ok(());
 			}
+		}
 
-			// flags are always sent in full, and hence always need updating
-			let standard_channel_flags = channel_flags & 0b_0000_0011;
-
-			let mut synthetic_update = UnsignedChannelUpdate {
-				chain_hash,
-				short_channel_id,
-				timestamp: backdated_timestamp,
-				message_flags: 1, // Only must_be_one
-				channel_flags: standard_channel_flags,
-				cltv_expiry_delta: default_cltv_expiry_delta,
-				htlc_minimum_msat: default_htlc_minimum_msat,
-				htlc_maximum_msat: default_htlc_maximum_msat,
-				fee_base_msat: default_fee_base_msat,
-				fee_proportional_millionths: default_fee_proportional_millionths,
-				excess_data: Vec::new(),
-			};
-
-			let mut skip_update_for_unknown_channel = false;
-
-			if (channel_flags & 0b_1000_0000) != 0 {
-				// incremental update, field flags will indicate mutated values
-				let read_only_network_graph = network_graph.read_only();
-				if let Some(directional_info) = read_only_network_graph
-					.channels()
-					.get(&short_channel_id)
-					.and_then(|channel| channel.get_directional_info(channel_flags))
-				{
-					synthetic_update.cltv_expiry_delta = directional_info.cltv_expiry_delta;
-					synthetic_update.htlc_minimum_msat = directional_info.htlc_minimum_msat;
-					synthetic_update.htlc_maximum_msat = directional_info.htlc_maximum_msat;
-					synthetic_update.fee_base_msat = directional_info.fees.base_msat;
-					synthetic_update.fee_proportional_millionths =
-						directional_info.fees.proportional_millionths;
-				} else {
-					log_trace!(self.logger,
-						"Skipping application of channel update for chan {} with flags {} as original data is missing.",
-						short_channel_id, channel_flags);
-					skip_update_for_unknown_channel = true;
-				}
-			};
+		// flags are always sent in full, and hence always need updating
+		let standard_channel_flags = channel_flags & 0b_0000_0011;
+
+		let mut synthetic_update = UnsignedChannelUpdate {
+			chain_hash: header.chain_hash,
+			short_channel_id,
+			timestamp: header.backdated_timestamp,
+			message_flags: 1, // Only must_be_one
+			channel_flags: standard_channel_flags,
+			cltv_expiry_delta: defaults.cltv_expiry_delta,
+			htlc_minimum_msat: defaults.htlc_minimum_msat,
+			htlc_maximum_msat: defaults.htlc_maximum_msat,
+			fee_base_msat: defaults.fee_base_msat,
+			fee_proportional_millionths: defaults.fee_proportional_millionths,
+			excess_data: Vec::new(),
+		};
 
-			if channel_flags & 0b_0100_0000 > 0 {
-				let cltv_expiry_delta: u16 = Readable::read(read_cursor)?;
-				synthetic_update.cltv_expiry_delta = cltv_expiry_delta;
-			}
+		let mut skip_update_for_unknown_channel = false;
 
-			if channel_flags & 0b_0010_0000 > 0 {
-				let htlc_minimum_msat: u64 = Readable::read(read_cursor)?;
-				synthetic_update.htlc_minimum_msat = htlc_minimum_msat;
+		if (channel_flags & 0b_1000_0000) != 0 {
+			// incremental update, field flags will indicate mutated values
+			let read_only_network_graph = network_graph.read_only();
+			if let Some(directional_info) = read_only_network_graph
+				.channels()
+				.get(&short_channel_id)
+				.and_then(|channel| channel.get_directional_info(channel_flags))
+			{
+				synthetic_update.cltv_expiry_delta = directional_info.cltv_expiry_delta;
+				synthetic_update.htlc_minimum_msat = directional_info.htlc_minimum_msat;
+				synthetic_update.htlc_maximum_msat = directional_info.htlc_maximum_msat;
+				synthetic_update.fee_base_msat = directional_info.fees.base_msat;
+				synthetic_update.fee_proportional_millionths =
+					directional_info.fees.proportional_millionths;
+			} else {
+				skip_update_for_unknown_channel = true;
 			}
+		};
 
-			if channel_flags & 0b_0001_0000 > 0 {
-				let fee_base_msat: u32 = Readable::read(read_cursor)?;
-				synthetic_update.fee_base_msat = fee_base_msat;
-			}
+		if channel_flags & 0b_0100_0000 > 0 {
+			let cltv_expiry_delta: u16 = Readable::read(read_cursor)?;
+			synthetic_update.cltv_expiry_delta = cltv_expiry_delta;
+		}
 
-			if channel_flags & 0b_0000_1000 > 0 {
-				let fee_proportional_millionths: u32 = Readable::read(read_cursor)?;
-				synthetic_update.fee_proportional_millionths = fee_proportional_millionths;
-			}
+		if channel_flags & 0b_0010_0000 > 0 {
+			let htlc_minimum_msat: u64 = Readable::read(read_cursor)?;
+			synthetic_update.htlc_minimum_msat = htlc_minimum_msat;
+		}
 
-			if channel_flags & 0b_0000_0100 > 0 {
-				let htlc_maximum_msat: u64 = Readable::read(read_cursor)?;
-				synthetic_update.htlc_maximum_msat = htlc_maximum_msat;
-			}
+		if channel_flags & 0b_0001_0000 > 0 {
+			let fee_base_msat: u32 = Readable::read(read_cursor)?;
+			synthetic_update.fee_base_msat = fee_base_msat;
+		}
 
-			if skip_update_for_unknown_channel {
-				continue;
-			}
+		if channel_flags & 0b_0000_1000 > 0 {
+			let fee_proportional_millionths: u32 = Readable::read(read_cursor)?;
+			synthetic_update.fee_proportional_millionths = fee_proportional_millionths;
+		}
 
-			log_gossip!(
-				self.logger,
-				"Updating channel {} with flags {} from RGS announcement at {}",
-				short_channel_id,
-				channel_flags,
-				latest_seen_timestamp
-			);
-			match network_graph.update_channel_unsigned(&synthetic_update) {
-				Ok(_) => {},
-				Err(LightningError { action: ErrorAction::IgnoreDuplicateGossip, .. }) => {},
-				Err(LightningError { action: ErrorAction::IgnoreAndLog(level), err }) => {
-					log_given_level!(
-						self.logger,
-						level,
-						"Failed to apply channel update: {:?}",
-						err
-					);
-				},
-				Err(LightningError { action: ErrorAction::IgnoreError, .. }) => {},
-				Err(e) => return Err(e.into()),
-			}
+		if channel_flags & 0b_0000_0100 > 0 {
+			let htlc_maximum_msat: u64 = Readable::read(read_cursor)?;
+			synthetic_update.htlc_maximum_msat = htlc_maximum_msat;
 		}
 
-		self.network_graph.set_last_rapid_gossip_sync_timestamp(latest_seen_timestamp);
+		state.previous_scid = short_channel_id;
+		state.previous_channel_direction = channel_direction;
 
-		if let Some(time) = current_time_unix {
-			self.network_graph.remove_stale_channels_and_tracking_with_time(time)
+		if skip_update_for_unknown_channel {
+			log_trace!(self.logger,
+				"Skipping application of channel update for chan {} with flags {} as original data is missing.",
+				short_channel_id, channel_flags);
+			return Ok(());
 		}
 
-		self.is_initial_sync_complete.store(true, Ordering::Release);
-		log_trace!(self.logger, "Done processing RGS data from {}", latest_seen_timestamp);
-		Ok(latest_seen_timestamp)
+		log_gossip!(
+			self.logger,
+			"Updating channel {} with flags {} from RGS announcement at {}",
+			short_channel_id,
+			channel_flags,
+			header.latest_seen_timestamp
+		);
+		match network_graph.update_channel_unsigned(&synthetic_update) {
+			Ok(_) => {},
+			Err(LightningError { action: ErrorAction::IgnoreDuplicateGossip, .. }) => {},
+			Err(LightningError { action: ErrorAction::IgnoreAndLog(level), err }) => {
+				log_given_level!(self.logger, level, "Failed to apply channel update: {:?}", err);
+			},
+			Err(LightningError { action: ErrorAction::IgnoreError, .. }) => {},
+			Err(e) => return Err(e.into()),
+		}
+		Ok(())
 	}
 }
 
@@ -485,7 +811,7 @@ mod tests {
 	use lightning::util::test_utils::TestLogger;
 
 	use crate::processing::STALE_RGS_UPDATE_AGE_LIMIT_SECS;
-	use crate::{GraphSyncError, RapidGossipSync};
+	use crate::{ChunkResult, GraphSyncError, GraphSyncProgress, RapidGossipSync};
 
 	const VALID_RGS_BINARY: [u8; 300] = [
 		76, 68, 75, 1, 111, 226, 140, 10, 182, 241, 179, 114, 193, 166, 162, 70, 174, 99, 247, 79,
@@ -505,6 +831,20 @@ mod tests {
 	];
 	const VALID_BINARY_TIMESTAMP: u64 = 1642291930;
 
+	const VALID_V2_RGS_BINARY: [u8; 260] = [
+		76, 68, 75, 2, 111, 226, 140, 10, 182, 241, 179, 114, 193, 166, 162, 70, 174, 99, 247, 79,
+		147, 30, 131, 101, 225, 90, 8, 156, 104, 214, 25, 0, 0, 0, 0, 0, 102, 97, 206, 240, 0, 0,
+		0, 0, 2, 63, 27, 132, 197, 86, 123, 18, 100, 64, 153, 93, 62, 213, 170, 186, 5, 101, 215,
+		30, 24, 52, 96, 72, 25, 255, 156, 23, 245, 233, 213, 221, 7, 143, 5, 38, 4, 1, 1, 1, 1, 1,
+		1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 0, 2, 3,
+		0, 4, 7, 1, 127, 0, 0, 1, 37, 163, 14, 5, 10, 103, 111, 111, 103, 108, 101, 46, 99, 111,
+		109, 1, 187, 19, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 5, 57, 13, 3, 1, 2, 3,
+		4, 5, 6, 7, 8, 9, 10, 11, 12, 0, 2, 23, 48, 62, 77, 75, 108, 209, 54, 16, 50, 202, 155,
+		210, 174, 185, 217, 0, 170, 77, 69, 217, 234, 216, 10, 201, 66, 51, 116, 196, 81, 167, 37,
+		77, 7, 102, 0, 0, 2, 25, 48, 0, 0, 0, 1, 0, 0, 1, 0, 1, 0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0,
+		0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 1, 0, 0, 1,
+	];
+
 	#[test]
 	#[cfg(feature = "std")]
 	fn network_graph_fails_to_update_from_clipped_input() {
@@ -542,23 +882,8 @@ mod tests {
 		let logger = TestLogger::new();
 		let network_graph = NetworkGraph::new(Network::Bitcoin, &logger);
 
-		let example_input = [
-			76, 68, 75, 2, 111, 226, 140, 10, 182, 241, 179, 114, 193, 166, 162, 70, 174, 99, 247,
-			79, 147, 30, 131, 101, 225, 90, 8, 156, 104, 214, 25, 0, 0, 0, 0, 0, 102, 97, 206, 240,
-			0, 0, 0, 0, 2, 63, 27, 132, 197, 86, 123, 18, 100, 64, 153, 93, 62, 213, 170, 186, 5,
-			101, 215, 30, 24, 52, 96, 72, 25, 255, 156, 23, 245, 233, 213, 221, 7, 143, 5, 38, 4,
-			1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-			1, 1, 1, 0, 2, 3, 0, 4, 7, 1, 127, 0, 0, 1, 37, 163, 14, 5, 10, 103, 111, 111, 103,
-			108, 101, 46, 99, 111, 109, 1, 187, 19, 2, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1, 1,
-			1, 5, 57, 13, 3, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 0, 2, 23, 48, 62, 77, 75, 108,
-			209, 54, 16, 50, 202, 155, 210, 174, 185, 217, 0, 170, 77, 69, 217, 234, 216, 10, 201,
-			66, 51, 116, 196, 81, 167, 37, 77, 7, 102, 0, 0, 2, 25, 48, 0, 0, 0, 1, 0, 0, 1, 0, 1,
-			0, 0, 0, 2, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 6, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0,
-			0, 1, 0, 0, 1,
-		];
-
 		let rapid_sync = RapidGossipSync::new(&network_graph, &logger);
-		let update_result = rapid_sync.update_network_graph_no_std(&example_input[..], None);
+		let update_result = rapid_sync.update_network_graph_no_std(&VALID_V2_RGS_BINARY, None);
 		assert!(update_result.is_ok());
 
 		let read_only_graph = network_graph.read_only();
@@ -1085,4 +1410,123 @@ mod tests {
 			panic!("Unexpected update result: {:?}", update_result)
 		}
 	}
+
+	/// Applies `input` to a new network graph, split into chunks at each of the given offsets, and
+	/// checks that the result is identical to applying it all at once.
+	fn check_chunked_update(input: &[u8], split_offsets: &[usize]) {
+		let logger = TestLogger::new();
+		let expected_graph = NetworkGraph::new(Network::Bitcoin, &logger);
+		let rapid_sync = RapidGossipSync::new(&expected_graph, &logger);
+		let expected_timestamp = rapid_sync.update_network_graph_no_std(input, None).unwrap();
+
+		let network_graph = NetworkGraph::new(Network::Bitcoin, &logger);
+		let rapid_sync = RapidGossipSync::new(&network_graph, &logger);
+		let mut chunked_update = rapid_sync.start_chunked_update_no_std(None);
+		let mut offset = 0;
+		for split_offset in split_offsets {
+			match chunked_update.apply_chunk(&input[offset..*split_offset]).unwrap() {
+				ChunkResult::InProgress(progress) => {
+					assert_eq!(progress.bytes_received, *split_offset as u64);
+					offset = progress.bytes_received as usize;
+				},
+				ChunkResult::Complete(_) => panic!("Completed before all data was applied"),
+			}
+		}
+		let result = chunked_update.apply_chunk(&input[offset..]).unwrap();
+		assert_eq!(result, ChunkResult::Complete(expected_timestamp));
+		assert!(rapid_sync.is_initial_sync_complete());
+		assert!(network_graph == expected_graph);
+		assert_eq!(
+			network_graph.get_last_rapid_gossip_sync_timestamp(),
+			expected_graph.get_last_rapid_gossip_sync_timestamp()
+		);
+	}
+
+	#[test]
+	fn chunked_update_matches_full_update_at_any_split() {
+		for input in [&VALID_RGS_BINARY[..], &VALID_V2_RGS_BINARY[..]] {
+			// Splitting at every offset covers splits within the header, within the node id
+			// dictionary and its v2 address details, within BigSize SCID deltas and within
+			// channel updates.
+			for split_offset in 0..input.len() {
+				check_chunked_update(input, &[split_offset]);
+			}
+			for split_offset in 0..input.len() - 7 {
+				check_chunked_update(input, &[split_offset, split_offset + 1, split_offset + 7]);
+			}
+			let byte_offsets: Vec<usize> = (1..input.len()).collect();
+			check_chunked_update(input, &byte_offsets);
+		}
+	}
+
+	#[test]
+	fn chunked_update_reports_progress() {
+		let logger = TestLogger::new();
+		let network_graph = NetworkGraph::new(Network::Bitcoin, &logger);
+		let rapid_sync = RapidGossipSync::new(&network_graph, &logger);
+		let mut chunked_update = rapid_sync.start_chunked_update_no_std(None);
+
+		// Nothing is known about the data until the header and node ids were read.
+		let result = chunked_update.apply_chunk(&VALID_RGS_BINARY[..100]).unwrap();
+		assert_eq!(
+			result,
+			ChunkResult::InProgress(GraphSyncProgress {
+				bytes_received: 100,
+				channel_announcements_applied: 0,
+				channel_announcements_expected: None,
+				channel_updates_applied: 0,
+				channel_updates_expected: None,
+			})
+		);
+		assert!(!rapid_sync.is_initial_sync_complete());
+
+		// Once we're missing only the last byte, all records but the last channel update apply.
+		let last_offset = VALID_RGS_BINARY.len() - 1;
+		let result = chunked_update.apply_chunk(&VALID_RGS_BINARY[100..last_offset]).unwrap();
+		assert_eq!(
+			result,
+			ChunkResult::InProgress(GraphSyncProgress {
+				bytes_received: last_offset as u64,
+				channel_announcements_applied: 2,
+				channel_announcements_expected: Some(2),
+				channel_updates_applied: 3,
+				channel_updates_expected: Some(4),
+			})
+		);
+		assert_eq!(network_graph.read_only().channels().len(), 2);
+		assert!(!rapid_sync.is_initial_sync_complete());
+
+		let result = chunked_update.apply_chunk(&VALID_RGS_BINARY[last_offset..]).unwrap();
+		assert_eq!(result, ChunkResult::Complete(VALID_BINARY_TIMESTAMP as u32));
+		assert!(rapid_sync.is_initial_sync_complete());
+
+		// Any trailing data is ignored.
+		let result = chunked_update.apply_chunk(&[0; 10]).unwrap();
+		assert_eq!(result, ChunkResult::Complete(VALID_BINARY_TIMESTAMP as u32));
+	}
+
+	#[test]
+	fn chunked_update_fails_permanently() {
+		let logger = TestLogger::new();
+		// Set to testnet so that the VALID_RGS_BINARY chain hash of mainnet does not match.
+		let network_graph = NetworkGraph::new(Network::Testnet, &logger);
+		let rapid_sync = RapidGossipSync::new(&network_graph, &logger);
+		let mut chunked_update = rapid_sync.start_chunked_update_no_std(Some(0));
+
+		// The chain hash isn't checked until it was received in full.
+		assert!(chunked_update.apply_chunk(&VALID_RGS_BINARY[..10]).is_ok());
+		let result = chunked_update.apply_chunk(&VALID_RGS_BINARY[10..]);
+		if let Err(GraphSyncError::LightningError(err)) = result {
+			assert_eq!(
+				err.err,
+				"Rapid Gossip Sync data's chain hash does not match the network graph's"
+			);
+		} else {
+			panic!("Unexpected update result: {:?}", result)
+		}
+
+		// Once failed, the update can't be continued.
+		assert!(chunked_update.apply_chunk(&VALID_RGS_BINARY[..]).is_err());
+		assert_eq!(network_graph.read_only().channels().len(), 0);
+	}
 }
@@ -0,0 +1,120 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! A curated set of re-exports of the types and functions most applications built on LDK need.
+//!
+//! LDK's module layout follows its internal architecture, so the items an application interacts
+//! with are spread across many modules, some of which are reorganized between releases. This
+//! module re-exports the stable, application-facing subset of them in one place, so that
+//! applications can import them without depending on where they are defined.
+//!
+//! # Stability
+//!
+//! Within a major version, items are only ever added to this module, never moved or removed.
+//! Items may still be deprecated, or their signatures changed, as with any other public API, but
+//! the path under which they are imported from here remains valid.
+//!
+//! # Examples
+//!
+//! Configuring new channels and handling events:
+//!
+//! ```
+//! use lightning::api::{ClosureReason, Event, UserConfig};
+//!
+//! let mut config = UserConfig::default();
+//! config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+//! config.channel_handshake_config.announce_for_forwarding = false;
+//!
+//! fn handle_event(event: Event) {
+//! 	match event {
+//! 		Event::ChannelClosed { reason: ClosureReason::HolderForceClosed { .. }, .. } => {},
+//! 		Event::PaymentClaimable { payment_hash, .. } => {
+//! 			println!("Received a payment with hash {}", payment_hash);
+//! 		},
+//! 		_ => {},
+//! 	}
+//! }
+//! ```
+//!
+//! Building the parameters to route a payment:
+//!
+//! ```
+//! use lightning::api::{PaymentParameters, RouteParameters};
+//! # use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+//! # let secp_ctx = Secp256k1::new();
+//! # let payee_secret = SecretKey::from_slice(&[42; 32]).unwrap();
+//! # let payee_pubkey = PublicKey::from_secret_key(&secp_ctx, &payee_secret);
+//!
+//! let payment_params = PaymentParameters::from_node_id(payee_pubkey, 40);
+//! let route_params = RouteParameters::from_payment_params_and_value(payment_params, 10_000);
+//! assert_eq!(route_params.final_value_msat, 10_000);
+//! ```
+//!
+//! Sizing the anchor channel reserve of a wallet:
+//!
+//! ```
+//! use lightning::api::{get_reserve_per_channel, AnchorChannelReserveContext};
+//!
+//! let context = AnchorChannelReserveContext::default();
+//! let reserve_per_channel = get_reserve_per_channel(&context);
+//! assert!(reserve_per_channel.to_sat() > 0);
+//! ```
+//!
+//! Signing and verifying messages with the node key:
+//!
+//! ```
+//! use lightning::api::{sign_message, verify_message};
+//! # use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+//! # let secp_ctx = Secp256k1::new();
+//! # let node_secret = SecretKey::from_slice(&[42; 32]).unwrap();
+//! # let node_id = PublicKey::from_secret_key(&secp_ctx, &node_secret);
+//!
+//! let signature = sign_message(b"Hello, world!", &node_secret);
+//! assert!(verify_message(b"Hello, world!", &signature, &node_id));
+//! ```
+
+pub use crate::events::{
+	ClosureReason, Event, EventHandler, EventsProvider, PaymentFailureReason, ReplayEvent,
+};
+
+pub use crate::ln::channel_state::{ChannelDetails, ChannelShutdownState};
+pub use crate::ln::channelmanager::{
+	AChannelManager, Bolt11InvoiceParameters, ChainParameters, ChannelManager,
+	ChannelManagerReadArgs, PaymentId, RecipientOnionFields, Retry, RetryableSendFailure,
+	SimpleArcChannelManager, SimpleRefChannelManager,
+};
+pub use crate::ln::types::ChannelId;
+pub use crate::types::payment::{PaymentHash, PaymentPreimage, PaymentSecret};
+
+pub use crate::routing::router::{PaymentParameters, RouteParameters, RouteParametersConfig};
+
+pub use crate::util::config::{
+	ChannelConfig, ChannelConfigUpdate, ChannelHandshakeConfig, ChannelHandshakeLimits,
+	ConfigError, MaxDustHTLCExposure, UserConfig,
+};
+pub use crate::util::errors::APIError;
+
+pub use crate::bolt11_invoice::{Bolt11Invoice, Bolt11InvoiceDescription, Description};
+
+#[cfg(feature = "std")]
+pub use crate::util::anchor_channel_reserves::{
+	can_support_additional_anchor_channel, can_support_additional_anchor_channel_multi,
+	can_support_channel_batch,
+};
+pub use crate::util::anchor_channel_reserves::{
+	can_support_channel_batch_with_num_anchor_channels, get_reserve_per_channel,
+	get_supportable_anchor_channels, get_supportable_anchor_channels_multi,
+	AnchorChannelReserveCheck, AnchorChannelReserveContext, MultiWalletSupportableAnchorChannels,
+};
+
+pub use crate::util::message_signing::{
+	recover_node_id as recover_message_node_id, recover_pk as recover_message_pubkey,
+	sign as sign_message, verify as verify_message, verify_against as verify_message_against,
+	NodeIdParseError, NodeIdRef,
+};
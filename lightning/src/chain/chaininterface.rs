@@ -147,6 +147,22 @@ pub enum ConfirmationTarget {
 	/// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
 	/// [`SpendableOutputDescriptor`]: crate::sign::SpendableOutputDescriptor
 	OutputSpendingFee,
+	/// An upper bound on the feerate we may have to pay to resolve our anchor channels on-chain at
+	/// any point in the future, used to size the UTXO reserve providing their fees exogenously.
+	///
+	/// Unlike all other targets, this is never used to pick the feerate of a transaction we
+	/// broadcast, so it may be well above the current feerates without causing us to overpay. It is
+	/// only used by [`AnchorChannelReserveContext::from_fee_estimator`].
+	///
+	/// [`FeeEstimator`] implementors should map this to a high percentile of feerates over a long
+	/// horizon, e.g., the 99th percentile of median block feerates over the past months or years,
+	/// as tracked by a [`FeeRateHistory`], rather than to a current estimate. If no such history is
+	/// available, returning the same value as for [`Self::MaximumFeeEstimate`] is a conservative
+	/// approximation, though the reserve may then fall short once feerates rise.
+	///
+	/// [`AnchorChannelReserveContext::from_fee_estimator`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext::from_fee_estimator
+	/// [`FeeRateHistory`]: crate::util::fee_rate_history::FeeRateHistory
+	MaximumExogenousFeeReserve,
 }
 
 /// A trait which should be implemented to provide feerate information on a number of time
@@ -201,6 +217,8 @@ where
 	}
 
 	pub fn bounded_sat_per_1000_weight(&self, confirmation_target: ConfirmationTarget) -> u32 {
+		// Reserve sizing estimates may be far above the current feerates and must never be paid.
+		debug_assert_ne!(confirmation_target, ConfirmationTarget::MaximumExogenousFeeReserve);
 		cmp::max(self.0.get_est_sat_per_1000_weight(confirmation_target), FEERATE_FLOOR_SATS_PER_KW)
 	}
 }
@@ -244,4 +262,13 @@ mod tests {
 			sat_per_kw
 		);
 	}
+
+	#[test]
+	#[cfg(debug_assertions)]
+	#[should_panic]
+	fn test_fee_estimator_reserve_target_not_used_for_transactions() {
+		let test_fee_estimator = &TestFeeEstimator { sat_per_kw: FEERATE_FLOOR_SATS_PER_KW };
+		let fee_estimator = LowerBoundedFeeEstimator::new(test_fee_estimator);
+		fee_estimator.bounded_sat_per_1000_weight(ConfirmationTarget::MaximumExogenousFeeReserve);
+	}
 }
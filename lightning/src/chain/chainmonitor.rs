@@ -29,8 +29,8 @@ use bitcoin::hash_types::{BlockHash, Txid};
 use crate::chain;
 use crate::chain::chaininterface::{BroadcasterInterface, FeeEstimator};
 use crate::chain::channelmonitor::{
-	Balance, ChannelMonitor, ChannelMonitorUpdate, MonitorEvent, TransactionOutputs,
-	WithChannelMonitor,
+	Balance, ChannelMonitor, ChannelMonitorUpdate, EmergencyBroadcastToken, MonitorEvent,
+	TransactionOutputs, WithChannelMonitor,
 };
 use crate::chain::transaction::{OutPoint, TransactionData};
 use crate::chain::{ChannelMonitorUpdateStatus, Filter, WatchedOutput};
@@ -42,15 +42,21 @@ use crate::ln::types::ChannelId;
 use crate::prelude::*;
 use crate::sign::ecdsa::EcdsaChannelSigner;
 use crate::sign::{EntropySource, PeerStorageKey};
-use crate::sync::{Mutex, MutexGuard, RwLock, RwLockReadGuard};
-use crate::types::features::{InitFeatures, NodeFeatures};
+use crate::sync::{Arc, Mutex, MutexGuard, RwLock, RwLockReadGuard};
+use crate::types::features::{ChannelTypeFeatures, InitFeatures, NodeFeatures};
+use crate::util::anchor_channel_reserves::{
+	is_anchor_channel_type, AnchorChannelReserveContext, AnchorReserveProvider,
+	AnchorReserveUtxoTracker, ReserveAuditDecision, ReserveAuditEntry,
+};
+use crate::util::deadlines::DeadlineKind;
 use crate::util::errors::APIError;
 use crate::util::logger::{Logger, WithContext};
 use crate::util::persist::MonitorName;
 use crate::util::wakers::{Future, Notifier};
 use bitcoin::secp256k1::PublicKey;
+use bitcoin::Amount;
 use core::ops::Deref;
-use core::sync::atomic::{AtomicUsize, Ordering};
+use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 /// `Persist` defines behavior for persisting channel monitors: this could mean
 /// writing once to disk, and/or uploading to one or more backup services.
@@ -202,12 +208,95 @@ struct MonitorHolder<ChannelSigner: EcdsaChannelSigner> {
 	/// [`ChannelMonitorUpdate`] which was already applied. While this isn't an issue for the
 	/// LDK-provided update-based [`Persist`], it is somewhat surprising for users so we avoid it.
 	pending_monitor_updates: Mutex<Vec<u64>>,
+	/// Whether the [`ChannelMonitor`] has any claimable balances, refreshed whenever the monitor is
+	/// updated or processes chain data so that [`ChainMonitor::list_monitor_summaries`] does not
+	/// need to compute the balances of each monitor.
+	has_claimable_balances: AtomicBool,
+	/// Whether [`ChannelMonitor::needs_exogenous_fee_reserve`], refreshed along with
+	/// [`Self::has_claimable_balances`].
+	needs_exogenous_fee_reserve: AtomicBool,
+	/// The [`ChannelMonitor::reserve_release_height`], refreshed along with
+	/// [`Self::has_claimable_balances`].
+	reserve_release_height: Mutex<Option<u32>>,
+	/// Whether the [`ChannelMonitor`] has seen a spend of the funding output, refreshed along with
+	/// [`Self::has_claimable_balances`].
+	funding_spend_seen: AtomicBool,
+	/// The [`ChannelMonitor::get_pending_claim_external_inputs_value`], refreshed along with
+	/// [`Self::has_claimable_balances`] and whenever a claim fee bump is provided.
+	pending_claim_external_inputs_value: Mutex<Amount>,
 }
 
 impl<ChannelSigner: EcdsaChannelSigner> MonitorHolder<ChannelSigner> {
+	fn new(monitor: ChannelMonitor<ChannelSigner>, pending_monitor_updates: Vec<u64>) -> Self {
+		let has_claimable_balances = AtomicBool::new(monitor.has_claimable_balances());
+		let needs_exogenous_fee_reserve = AtomicBool::new(monitor.needs_exogenous_fee_reserve());
+		let reserve_release_height = Mutex::new(monitor.reserve_release_height());
+		let funding_spend_seen = AtomicBool::new(monitor.funding_spend_seen());
+		let pending_claim_external_inputs_value =
+			Mutex::new(monitor.get_pending_claim_external_inputs_value());
+		MonitorHolder {
+			monitor,
+			pending_monitor_updates: Mutex::new(pending_monitor_updates),
+			has_claimable_balances,
+			needs_exogenous_fee_reserve,
+			reserve_release_height,
+			funding_spend_seen,
+			pending_claim_external_inputs_value,
+		}
+	}
+
 	fn has_pending_updates(&self, pending_monitor_updates_lock: &MutexGuard<Vec<u64>>) -> bool {
 		!pending_monitor_updates_lock.is_empty()
 	}
+
+	fn summary(&self, channel_id: ChannelId) -> MonitorSummary {
+		MonitorSummary {
+			channel_id,
+			channel_type_features: self.monitor.channel_type_features(),
+			has_claimable_balances: self.has_claimable_balances.load(Ordering::Acquire),
+			needs_exogenous_fee_reserve: self.needs_exogenous_fee_reserve.load(Ordering::Acquire),
+			reserve_release_height: *self.reserve_release_height.lock().unwrap(),
+			funding_spend_seen: self.funding_spend_seen.load(Ordering::Acquire),
+			pending_claim_external_inputs_value: *self
+				.pending_claim_external_inputs_value
+				.lock()
+				.unwrap(),
+		}
+	}
+
+	fn refresh_balance_flags(&self) {
+		let has_claimable_balances = self.monitor.has_claimable_balances();
+		self.has_claimable_balances.store(has_claimable_balances, Ordering::Release);
+		let needs_exogenous_fee_reserve = self.monitor.needs_exogenous_fee_reserve();
+		self.needs_exogenous_fee_reserve.store(needs_exogenous_fee_reserve, Ordering::Release);
+		*self.reserve_release_height.lock().unwrap() = self.monitor.reserve_release_height();
+		self.funding_spend_seen.store(self.monitor.funding_spend_seen(), Ordering::Release);
+		*self.pending_claim_external_inputs_value.lock().unwrap() =
+			self.monitor.get_pending_claim_external_inputs_value();
+	}
+}
+
+/// A summary of a [`ChannelMonitor`], as returned by [`ChainMonitor::list_monitor_summaries`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MonitorSummary {
+	/// The channel ID of the channel monitored by the [`ChannelMonitor`].
+	pub channel_id: ChannelId,
+	/// The channel type of the channel, as returned by [`ChannelMonitor::channel_type_features`].
+	pub channel_type_features: ChannelTypeFeatures,
+	/// Whether [`ChannelMonitor::get_claimable_balances`] returns any balances.
+	pub has_claimable_balances: bool,
+	/// Whether resolving the channel on-chain may still require fees paid from outside of the
+	/// channel, as returned by [`ChannelMonitor::needs_exogenous_fee_reserve`].
+	pub needs_exogenous_fee_reserve: bool,
+	/// The block height at which resolving the channel on-chain will no longer require fees paid
+	/// from outside of the channel, as returned by [`ChannelMonitor::reserve_release_height`].
+	pub reserve_release_height: Option<u32>,
+	/// Whether a spend of the funding output has been seen on-chain, i.e., whether the channel is
+	/// being resolved on-chain.
+	pub funding_spend_seen: bool,
+	/// The total value of the external inputs attached to the most recent transactions of pending
+	/// claims, as provided via [`ChannelMonitor::provide_claim_fee_bump`].
+	pub pending_claim_external_inputs_value: Amount,
 }
 
 /// A read-only reference to a current ChannelMonitor.
@@ -279,6 +368,19 @@ pub struct ChainMonitor<
 	pending_send_only_events: Mutex<Vec<MessageSendEvent>>,
 
 	our_peerstorage_encryption_key: PeerStorageKey,
+
+	/// Tracks the UTXOs of the [`AnchorReserveProvider`] registered via
+	/// [`Self::register_anchor_reserve_provider`], if any.
+	anchor_reserve_tracker: Mutex<Option<AnchorReserveUtxoTracker>>,
+	/// [`Event`]s generated by the [`ChainMonitor`] itself rather than one of its
+	/// [`ChannelMonitor`]s.
+	pending_events: Mutex<Vec<Event>>,
+	/// Whether [`Event::AnchorChannelResolutionStarted`]s are passed to the event handler, see
+	/// [`Self::set_anchor_resolution_events_enabled`].
+	anchor_resolution_events_enabled: AtomicBool,
+	/// Whether [`Self::execute_emergency_broadcast`] may broadcast commitment transactions, see
+	/// [`Self::set_emergency_broadcast_enabled`].
+	emergency_broadcast_enabled: AtomicBool,
 }
 
 impl<
@@ -382,6 +484,7 @@ where
 		let logger = WithChannelMonitor::from(&self.logger, &monitor, None);
 
 		let mut txn_outputs = process(monitor, txdata);
+		monitor_state.refresh_balance_flags();
 
 		let get_partition_key = |channel_id: &ChannelId| {
 			let channel_id_bytes = channel_id.0;
@@ -492,6 +595,10 @@ where
 			event_notifier: Notifier::new(),
 			pending_send_only_events: Mutex::new(Vec::new()),
 			our_peerstorage_encryption_key,
+			anchor_reserve_tracker: Mutex::new(None),
+			pending_events: Mutex::new(Vec::new()),
+			anchor_resolution_events_enabled: AtomicBool::new(false),
+			emergency_broadcast_enabled: AtomicBool::new(false),
 		}
 	}
 
@@ -535,6 +642,31 @@ where
 		}
 	}
 
+	/// Calls `f` with the [`ChannelMonitor`] for the given channel, returning its result, or an
+	/// `Err` if no such [`ChannelMonitor`] is currently being monitored for.
+	///
+	/// Unlike [`ChainMonitor::get_monitor`], the monitor is only borrowed for the duration of `f`,
+	/// which should not block as our monitor set is locked while it runs.
+	pub fn with_monitor<R, F: FnOnce(&ChannelMonitor<ChannelSigner>) -> R>(
+		&self, channel_id: ChannelId, f: F,
+	) -> Result<R, ()> {
+		let monitors = self.monitors.read().unwrap();
+		monitors.get(&channel_id).map(|holder| f(&holder.monitor)).ok_or(())
+	}
+
+	/// Calls `f` with the channel ID and [`ChannelMonitor`] of each channel being monitored.
+	///
+	/// All monitors are visited under a single lock of our monitor set, so `f` sees a consistent
+	/// snapshot of it, unlike calling [`ChainMonitor::get_monitor`] for each entry of
+	/// [`ChainMonitor::list_monitors`], where monitors may be removed in between. As the monitor set
+	/// is locked while `f` runs, it should not block.
+	pub fn with_monitors<F: FnMut(ChannelId, &ChannelMonitor<ChannelSigner>)>(&self, mut f: F) {
+		let monitors = self.monitors.read().unwrap();
+		for (channel_id, holder) in monitors.iter() {
+			f(*channel_id, &holder.monitor);
+		}
+	}
+
 	/// Lists the funding outpoint and channel ID of each [`ChannelMonitor`] being monitored.
 	///
 	/// Note that [`ChannelMonitor`]s are not removed when a channel is closed as they are always
@@ -543,6 +675,63 @@ where
 		self.monitors.read().unwrap().keys().copied().collect()
 	}
 
+	/// Gets the time-sensitive deadlines tracked by each [`ChannelMonitor`] being monitored, see
+	/// [`ChannelMonitor::get_htlc_deadlines`].
+	pub fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+		let mut deadlines = Vec::new();
+		self.with_monitors(|_, monitor| deadlines.append(&mut monitor.get_htlc_deadlines()));
+		deadlines
+	}
+
+	/// Lists a [`MonitorSummary`] for each [`ChannelMonitor`] being monitored.
+	///
+	/// Unlike calling [`ChannelMonitor::get_claimable_balances`] on each monitor, this uses state
+	/// cached as monitors are updated and process chain data, and is therefore cheap enough to be
+	/// called when deciding whether to accept new channels.
+	///
+	/// This allocates a summary for every monitor, including those of channels which have long been
+	/// resolved. Use [`ChainMonitor::for_each_monitor_summary`] or
+	/// [`ChainMonitor::count_monitors_matching`] to avoid this for large numbers of monitors.
+	pub fn list_monitor_summaries(&self) -> Vec<MonitorSummary> {
+		let monitors = self.monitors.read().unwrap();
+		monitors.iter().map(|(channel_id, holder)| holder.summary(*channel_id)).collect()
+	}
+
+	/// Calls `f` with a [`MonitorSummary`] of each [`ChannelMonitor`] being monitored, as listed by
+	/// [`ChainMonitor::list_monitor_summaries`], without collecting them first.
+	///
+	/// If `include_resolved` is unset, monitors without any claimable balances are skipped. These
+	/// are the monitors of channels which have been fully resolved on-chain, but which have not yet
+	/// been archived via [`ChainMonitor::archive_fully_resolved_channel_monitors`].
+	///
+	/// All summaries are visited under a single lock of our monitor set, which is held while `f`
+	/// runs, so it should not block.
+	pub fn for_each_monitor_summary<F: FnMut(&MonitorSummary)>(
+		&self, include_resolved: bool, mut f: F,
+	) {
+		let monitors = self.monitors.read().unwrap();
+		for (channel_id, holder) in monitors.iter() {
+			if include_resolved || holder.has_claimable_balances.load(Ordering::Acquire) {
+				f(&holder.summary(*channel_id));
+			}
+		}
+	}
+
+	/// Counts the [`ChannelMonitor`]s whose [`MonitorSummary`] matches `predicate`, skipping
+	/// monitors without any claimable balances unless `include_resolved` is set, as
+	/// [`ChainMonitor::for_each_monitor_summary`] does.
+	pub fn count_monitors_matching<F: FnMut(&MonitorSummary) -> bool>(
+		&self, include_resolved: bool, mut predicate: F,
+	) -> usize {
+		let mut count = 0;
+		self.for_each_monitor_summary(include_resolved, |summary| {
+			if predicate(summary) {
+				count += 1;
+			}
+		});
+		count
+	}
+
 	#[cfg(not(c_bindings))]
 	/// Lists the pending updates for each [`ChannelMonitor`] (by `ChannelId` being monitored).
 	/// Each `Vec<u64>` contains `update_id`s from [`ChannelMonitor::get_latest_update_id`] for updates
@@ -682,6 +871,18 @@ where
 	>(
 		&self, handler: H,
 	) {
+		let pending_events = core::mem::take(&mut *self.pending_events.lock().unwrap());
+		let mut failed_events = Vec::new();
+		for event in pending_events {
+			if handler(event.clone()).await.is_err() {
+				failed_events.push(event);
+			}
+		}
+		if !failed_events.is_empty() {
+			self.pending_events.lock().unwrap().extend(failed_events);
+			self.event_notifier.notify();
+		}
+
 		// Sadly we can't hold the monitors read lock through an async call. Thus we have to do a
 		// crazy dance to process a monitor's events then only remove them once we've done so.
 		let mons_to_process = self.monitors.read().unwrap().keys().cloned().collect::<Vec<_>>();
@@ -691,7 +892,7 @@ where
 				self.monitors.read().unwrap().get(&channel_id).map(|m| &m.monitor),
 				self.logger,
 				ev,
-				handler(ev).await
+				if self.is_disabled_event(&ev) { Ok(()) } else { handler(ev).await }
 			) {
 				Ok(()) => {},
 				Err(ReplayEvent()) => {
@@ -729,6 +930,216 @@ where
 		}
 	}
 
+	/// Provides the fee bump recorded in `entry` by a [`BumpTransactionEventHandler`] to the
+	/// [`ChannelMonitor`] of the channel, to be accounted for in its
+	/// [`ChannelMonitor::get_onchain_resolution_stats`] once the claim is resolved. See
+	/// [`ChannelMonitor::provide_claim_fee_bump`] for details.
+	///
+	/// This is intended to be called by a [`ReserveAuditLogger`] registered via
+	/// [`BumpTransactionEventHandler::with_audit_logger`]. Entries not recording a fee bump, entries
+	/// recorded with LDK versions prior to 0.3, and entries for unknown channels or resolved claims
+	/// are ignored. Note that the fees of HTLC claims aggregated across channels are accounted for
+	/// in full for each channel.
+	///
+	/// [`BumpTransactionEventHandler`]: crate::events::bump_transaction::BumpTransactionEventHandler
+	/// [`BumpTransactionEventHandler::with_audit_logger`]: crate::events::bump_transaction::BumpTransactionEventHandler::with_audit_logger
+	/// [`ReserveAuditLogger`]: crate::util::anchor_channel_reserves::ReserveAuditLogger
+	pub fn provide_claim_fee_bump(&self, entry: &ReserveAuditEntry) {
+		let (claim_id, txid, feerate_sat_per_1000_weight) = match entry.decision {
+			ReserveAuditDecision::CommitmentFeeBumped {
+				claim_id: Some(claim_id),
+				anchor_txid,
+				target_feerate_sat_per_1000_weight,
+				..
+			} => (claim_id, anchor_txid, target_feerate_sat_per_1000_weight),
+			ReserveAuditDecision::HtlcFeeBumped {
+				claim_id: Some(claim_id),
+				htlc_txid,
+				target_feerate_sat_per_1000_weight,
+				..
+			} => (claim_id, htlc_txid, target_feerate_sat_per_1000_weight),
+			_ => return,
+		};
+		let monitors = self.monitors.read().unwrap();
+		if let Some(monitor_holder) = monitors.get(&entry.channel_id) {
+			let pending = monitor_holder.monitor.provide_claim_fee_bump(
+				claim_id,
+				txid,
+				entry.fees_paid,
+				entry.utxos_value,
+				feerate_sat_per_1000_weight,
+			);
+			if pending {
+				monitor_holder.refresh_balance_flags();
+			}
+		}
+	}
+
+	/// Registers an [`AnchorReserveProvider`] whose UTXOs are re-validated whenever
+	/// [`Self::anchor_reserve_utxos_changed`] is called and at least every `check_interval_blocks`
+	/// blocks, replacing any previously registered one.
+	///
+	/// If any UTXOs seen on the previous check are no longer returned by the provider and were not
+	/// selected as inputs to LDK's own claims, an [`Event::AnchorReserveUtxosSpent`] is generated.
+	/// The first check only records the provider's UTXOs.
+	///
+	/// The checks are performed by [`Self::check_anchor_reserve_utxos`], which is called by the
+	/// background processor. This is not persisted and needs to be set again after a restart.
+	pub fn register_anchor_reserve_provider(
+		&self, provider: Arc<dyn AnchorReserveProvider + Send + Sync>,
+		context: AnchorChannelReserveContext, check_interval_blocks: u32,
+	) {
+		let tracker = AnchorReserveUtxoTracker::new(provider, context, check_interval_blocks);
+		*self.anchor_reserve_tracker.lock().unwrap() = Some(tracker);
+		self.event_notifier.notify();
+	}
+
+	/// Sets whether an [`Event::AnchorChannelResolutionStarted`] is provided to the event handler
+	/// when one of our anchor channels starts being resolved on-chain, e.g., to lock the UTXOs of
+	/// the anchor channel reserve allocated to it immediately.
+	///
+	/// While disabled, which is the default, these events are dropped when processing events
+	/// rather than being provided to the event handler. Each [`ChannelMonitor`] only generates the
+	/// event once, so this should be enabled before processing events for the first time. This is
+	/// not persisted and needs to be set again after a restart.
+	pub fn set_anchor_resolution_events_enabled(&self, enabled: bool) {
+		self.anchor_resolution_events_enabled.store(enabled, Ordering::Release);
+	}
+
+	/// Returns whether the given event should be dropped rather than provided to the event
+	/// handler, see [`Self::set_anchor_resolution_events_enabled`].
+	fn is_disabled_event(&self, event: &Event) -> bool {
+		matches!(event, Event::AnchorChannelResolutionStarted { .. })
+			&& !self.anchor_resolution_events_enabled.load(Ordering::Acquire)
+	}
+
+	/// Sets whether [`Self::execute_emergency_broadcast`] may broadcast our latest commitment
+	/// transaction for a channel.
+	///
+	/// While disabled, which is the default, emergency broadcasts are refused, such that they have
+	/// to be enabled deliberately, e.g., from an operator's console. This is not persisted and needs
+	/// to be set again after a restart.
+	pub fn set_emergency_broadcast_enabled(&self, enabled: bool) {
+		self.emergency_broadcast_enabled.store(enabled, Ordering::Release);
+	}
+
+	/// Broadcasts our latest commitment transaction for the channel a `token` was prepared for
+	/// via [`ChannelMonitor::prepare_emergency_broadcast`], force-closing the channel.
+	///
+	/// This may be used while the [`ChannelManager`] is still running. Once broadcast, the
+	/// [`ChannelMonitor`] refuses any further updates, and the [`ChannelManager`] closes the
+	/// channel with [`ClosureReason::HolderForceClosed`] when it next processes events from this
+	/// [`ChainMonitor`], generating an [`Event::ChannelClosed`]. For anchor channels, an
+	/// [`Event::BumpTransaction`] is generated to provide the fees for the commitment transaction.
+	///
+	/// Returns an [`APIError::APIMisuseError`] if emergency broadcasts are disabled, see
+	/// [`Self::set_emergency_broadcast_enabled`], if no [`ChannelMonitor`] for the channel is
+	/// registered, or if the channel advanced to a new commitment transaction since the token was
+	/// prepared.
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	/// [`ClosureReason::HolderForceClosed`]: crate::events::ClosureReason::HolderForceClosed
+	pub fn execute_emergency_broadcast(
+		&self, token: EmergencyBroadcastToken,
+	) -> Result<(), APIError> {
+		let channel_id = token.summary().channel_id;
+		if !self.emergency_broadcast_enabled.load(Ordering::Acquire) {
+			log_error!(
+				self.logger,
+				"Refusing emergency broadcast for channel {} as emergency broadcasts are disabled",
+				channel_id
+			);
+			return Err(APIError::APIMisuseError {
+				err: "Emergency broadcasts are disabled".to_owned(),
+			});
+		}
+		let monitors = self.monitors.read().unwrap();
+		let monitor = match monitors.get(&channel_id) {
+			Some(monitor_holder) => &monitor_holder.monitor,
+			None => {
+				return Err(APIError::APIMisuseError {
+					err: format!("No ChannelMonitor matching channel ID {} found", channel_id),
+				});
+			},
+		};
+		let logger = WithChannelMonitor::from(&self.logger, monitor, None);
+		let broadcast = monitor.execute_emergency_broadcast(
+			&token,
+			&self.broadcaster,
+			&self.fee_estimator,
+			&self.logger,
+		);
+		if broadcast.is_err() {
+			log_error!(
+				logger,
+				"Refusing emergency broadcast for channel {} as it advanced to a new commitment transaction",
+				channel_id
+			);
+			return Err(APIError::APIMisuseError {
+				err: format!(
+					"Channel {} advanced to a new commitment transaction since the emergency broadcast was prepared",
+					channel_id
+				),
+			});
+		}
+		// The monitor now refuses any further updates, which has to survive a restart.
+		self.persister.update_persisted_channel(monitor.persistence_key(), None, monitor);
+		self.event_notifier.notify();
+		Ok(())
+	}
+
+	/// Indicates that the set of UTXOs of the registered [`AnchorReserveProvider`] may have
+	/// changed, e.g., after the wallet synced, such that they are re-validated on the next call to
+	/// [`Self::check_anchor_reserve_utxos`].
+	pub fn anchor_reserve_utxos_changed(&self) {
+		if let Some(tracker) = self.anchor_reserve_tracker.lock().unwrap().as_mut() {
+			tracker.utxos_changed();
+			self.event_notifier.notify();
+		}
+	}
+
+	/// Re-validates the UTXOs of the [`AnchorReserveProvider`] registered via
+	/// [`Self::register_anchor_reserve_provider`] if they changed or enough blocks have passed since
+	/// the last check, generating an [`Event::AnchorReserveUtxosSpent`] if any of them were spent
+	/// externally.
+	///
+	/// Anchor channels are counted as relying on the reserve as long as their [`ChannelMonitor`]
+	/// has claimable balances. This is cheap to call if no check is due, and is called on every
+	/// iteration of the background processor.
+	pub fn check_anchor_reserve_utxos(&self) {
+		let mut tracker_lock = self.anchor_reserve_tracker.lock().unwrap();
+		let tracker = match tracker_lock.as_mut() {
+			Some(tracker) => tracker,
+			None => return,
+		};
+		let best_block_height = self.highest_chain_height.load(Ordering::Acquire) as u32;
+		if !tracker.is_check_due(best_block_height) {
+			return;
+		}
+		// Only monitors with claimable balances may require fees, so resolved ones can be skipped.
+		let num_anchor_channels = self.count_monitors_matching(false, |summary| {
+			summary.needs_exogenous_fee_reserve
+				&& is_anchor_channel_type(&summary.channel_type_features)
+		}) as u64;
+		match tracker.check(best_block_height, num_anchor_channels) {
+			Ok(Some((spent_outpoints, num_affected_channels))) => {
+				log_error!(
+					self.logger,
+					"{} anchor reserve UTXOs were spent externally, leaving {} anchor channels without sufficient reserve",
+					spent_outpoints.len(),
+					num_affected_channels
+				);
+				self.pending_events.lock().unwrap().push(Event::AnchorReserveUtxosSpent {
+					spent_outpoints,
+					num_affected_channels,
+				});
+				self.event_notifier.notify();
+			},
+			Ok(None) => {},
+			Err(()) => log_error!(self.logger, "Failed to list the anchor reserve UTXOs"),
+		}
+	}
+
 	/// Triggers rebroadcasts of pending claims from force-closed channels after a transaction
 	/// signature generation failure.
 	///
@@ -945,6 +1356,7 @@ where
 				&*self.fee_estimator,
 				&self.logger,
 			);
+			monitor_state.refresh_balance_flags();
 		}
 	}
 }
@@ -998,6 +1410,7 @@ where
 				&*self.fee_estimator,
 				&self.logger,
 			);
+			monitor_state.refresh_balance_flags();
 		}
 	}
 
@@ -1101,10 +1514,7 @@ where
 		if let Some(ref chain_source) = self.chain_source {
 			monitor.load_outputs_to_watch(chain_source, &self.logger);
 		}
-		entry.insert(MonitorHolder {
-			monitor,
-			pending_monitor_updates: Mutex::new(pending_monitor_updates),
-		});
+		entry.insert(MonitorHolder::new(monitor, pending_monitor_updates));
 		Ok(persist_res)
 	}
 
@@ -1150,6 +1560,7 @@ where
 					&self.fee_estimator,
 					&self.logger,
 				);
+				monitor_state.refresh_balance_flags();
 
 				let update_id = update.update_id;
 				let persist_res = if update_res.is_err() {
@@ -1284,8 +1695,25 @@ where
 	where
 		H::Target: EventHandler,
 	{
+		let pending_events = core::mem::take(&mut *self.pending_events.lock().unwrap());
+		let mut failed_events = Vec::new();
+		for event in pending_events {
+			if handler.handle_event(event.clone()).is_err() {
+				failed_events.push(event);
+			}
+		}
+		if !failed_events.is_empty() {
+			self.pending_events.lock().unwrap().extend(failed_events);
+			self.event_notifier.notify();
+		}
+		let filtered_handler = &|event: Event| {
+			if self.is_disabled_event(&event) {
+				return Ok(());
+			}
+			handler.handle_event(event)
+		};
 		for monitor_state in self.monitors.read().unwrap().values() {
-			match monitor_state.monitor.process_pending_events(&handler, &self.logger) {
+			match monitor_state.monitor.process_pending_events(&filtered_handler, &self.logger) {
 				Ok(()) => {},
 				Err(ReplayEvent()) => {
 					self.event_notifier.notify();
@@ -1302,10 +1730,26 @@ mod tests {
 	use crate::events::{ClosureReason, Event};
 	use crate::ln::functional_test_utils::*;
 	use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, MessageSendEvent};
-	use crate::{check_added_monitors, check_closed_event};
+	use crate::{check_added_monitors, check_closed_event, check_spends};
 	use crate::{expect_payment_path_successful, get_event_msg};
 	use crate::{get_htlc_update_msgs, get_revoke_commit_msgs};
 
+	use crate::chain::chaininterface::ConfirmationTarget;
+	use crate::chain::chainmonitor::{MonitorHolder, MonitorSummary};
+	use crate::events::bump_transaction::sync::{BumpTransactionEventHandlerSync, WalletSync};
+	use crate::events::bump_transaction::HTLCDeadlineFeerateSchedule;
+	use crate::ln::types::ChannelId;
+	use crate::sync::Arc;
+	#[cfg(feature = "std")]
+	use crate::util::anchor_channel_reserves::AnchorChannelReserveCheck;
+	use crate::util::anchor_channel_reserves::AnchorChannelReserveContext;
+	use crate::util::config::UserConfig;
+	use crate::util::test_utils::{TestLogger, TestWalletSource};
+	use core::sync::atomic::{AtomicBool, Ordering};
+
+	use bitcoin::hashes::Hash;
+	use bitcoin::{Amount, OutPoint, Txid};
+
 	const CHAINSYNC_MONITOR_PARTITION_FACTOR: u32 = 5;
 
 	#[test]
@@ -1532,6 +1976,241 @@ mod tests {
 		);
 	}
 
+	fn check_monitor_summaries(node: &Node, expected_anchor_channels: usize) {
+		// Compare each summary with the state of its monitor, and count the anchor channels which
+		// may require fees using both the summaries and the monitors.
+		let chain_monitor = &node.chain_monitor.chain_monitor;
+		let summaries = chain_monitor.list_monitor_summaries();
+		assert_eq!(summaries.len(), chain_monitor.list_monitors().len());
+		for summary in summaries.iter() {
+			chain_monitor
+				.with_monitor(summary.channel_id, |monitor| {
+					assert_eq!(summary.channel_type_features, monitor.channel_type_features());
+					let has_claimable_balances = !monitor.get_claimable_balances().is_empty();
+					assert_eq!(summary.has_claimable_balances, has_claimable_balances);
+					let needs_exogenous_fee_reserve = monitor.needs_exogenous_fee_reserve();
+					assert_eq!(summary.needs_exogenous_fee_reserve, needs_exogenous_fee_reserve);
+					assert!(has_claimable_balances || !needs_exogenous_fee_reserve);
+					assert_eq!(summary.reserve_release_height, monitor.reserve_release_height());
+					assert_eq!(summary.funding_spend_seen, monitor.funding_spend_seen());
+					let external_inputs_value = monitor.get_pending_claim_external_inputs_value();
+					assert_eq!(summary.pending_claim_external_inputs_value, external_inputs_value);
+				})
+				.unwrap();
+		}
+
+		let num_anchor_channels = summaries
+			.iter()
+			.filter(|summary| {
+				summary.channel_type_features.supports_anchors_zero_fee_htlc_tx()
+					&& summary.needs_exogenous_fee_reserve
+			})
+			.count();
+		let mut num_anchor_channels_from_monitors = 0;
+		chain_monitor.with_monitors(|_, monitor| {
+			if monitor.channel_type_features().supports_anchors_zero_fee_htlc_tx()
+				&& monitor.needs_exogenous_fee_reserve()
+			{
+				num_anchor_channels_from_monitors += 1;
+			}
+		});
+		assert_eq!(num_anchor_channels, num_anchor_channels_from_monitors);
+		assert_eq!(num_anchor_channels, expected_anchor_channels);
+	}
+
+	#[test]
+	fn test_list_monitor_summaries() {
+		// Test that the cached balance state in `MonitorSummary`s follows the monitors' balances as
+		// a channel is closed and its funds are resolved on-chain.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut anchors_config = test_default_channel_config();
+		anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		anchors_config.manually_accept_inbound_channels = true;
+		let configs = [Some(anchors_config.clone()), Some(anchors_config)];
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &configs);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+		let node_a_id = nodes[0].node.get_our_node_id();
+		let node_b_id = nodes[1].node.get_our_node_id();
+
+		let (_, _, channel_id, funding_tx) = create_announced_chan_between_nodes(&nodes, 0, 1);
+		create_announced_chan_between_nodes(&nodes, 0, 1);
+		send_payment(&nodes[0], &[&nodes[1]], 10_000_000);
+		check_monitor_summaries(&nodes[0], 2);
+		check_monitor_summaries(&nodes[1], 2);
+
+		let closing_tx = close_channel(&nodes[0], &nodes[1], &channel_id, funding_tx, false).2;
+		let reason_a = ClosureReason::LocallyInitiatedCooperativeClosure;
+		check_closed_event!(nodes[0], 1, reason_a, [node_b_id], 100000);
+		let reason_b = ClosureReason::CounterpartyInitiatedCooperativeClosure;
+		check_closed_event!(nodes[1], 1, reason_b, [node_a_id], 100000);
+
+		// The closed channel may require fees to force-close it until the closing transaction
+		// appears on-chain. Its balances are then claimable until the closing transaction is final,
+		// but no longer require any fees.
+		check_monitor_summaries(&nodes[0], 2);
+		check_monitor_summaries(&nodes[1], 2);
+		mine_transaction(&nodes[0], &closing_tx);
+		mine_transaction(&nodes[1], &closing_tx);
+		connect_blocks(&nodes[0], ANTI_REORG_DELAY - 2);
+		connect_blocks(&nodes[1], ANTI_REORG_DELAY - 2);
+		check_monitor_summaries(&nodes[0], 1);
+		check_monitor_summaries(&nodes[1], 1);
+		for node in nodes.iter() {
+			let summaries = node.chain_monitor.chain_monitor.list_monitor_summaries();
+			let summary = summaries.iter().find(|s| s.channel_id == channel_id).unwrap();
+			assert!(summary.has_claimable_balances);
+		}
+
+		connect_blocks(&nodes[0], 1);
+		connect_blocks(&nodes[1], 1);
+		check_monitor_summaries(&nodes[0], 1);
+		check_monitor_summaries(&nodes[1], 1);
+
+		for node in nodes.iter() {
+			let events = node.chain_monitor.chain_monitor.get_and_clear_pending_events();
+			assert_eq!(events.len(), 1);
+			assert!(matches!(events[0], Event::SpendableOutputs { .. }));
+		}
+	}
+
+	fn anchors_config() -> UserConfig {
+		let mut config = test_default_channel_config();
+		config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		config.manually_accept_inbound_channels = true;
+		config
+	}
+
+	fn register_reserve_wallet(
+		node: &Node, check_interval_blocks: u32,
+	) -> Arc<WalletSync<Arc<TestWalletSource>, Arc<TestLogger>>> {
+		let logger = Arc::new(TestLogger::new());
+		let wallet = Arc::new(WalletSync::new(Arc::clone(&node.wallet_source), logger));
+		node.chain_monitor.chain_monitor.register_anchor_reserve_provider(
+			wallet.clone(),
+			AnchorChannelReserveContext::default(),
+			check_interval_blocks,
+		);
+		wallet
+	}
+
+	fn spent_reserve_utxos(node: &Node) -> Vec<(Vec<OutPoint>, u64)> {
+		node.chain_monitor.chain_monitor.check_anchor_reserve_utxos();
+		let events = node.chain_monitor.chain_monitor.get_and_clear_pending_events();
+		events
+			.into_iter()
+			.filter_map(|event| match event {
+				Event::AnchorReserveUtxosSpent { spent_outpoints, num_affected_channels } => {
+					Some((spent_outpoints, num_affected_channels))
+				},
+				_ => None,
+			})
+			.collect()
+	}
+
+	#[test]
+	fn test_anchor_reserve_utxos_spent_externally() {
+		// Test that reserve UTXOs disappearing from the wallet are reported once the wallet signals
+		// a change or enough blocks have passed.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let config = anchors_config();
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(config.clone()), Some(config)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+		let coinbase_tx = provide_anchor_reserves(&nodes);
+		let reserve_outpoint = OutPoint { txid: coinbase_tx.compute_txid(), vout: 0 };
+		let small_outpoint = OutPoint { txid: Txid::all_zeros(), vout: 0 };
+		nodes[0].wallet_source.add_utxo(small_outpoint, Amount::from_sat(10_000));
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+
+		// Nothing is checked until a provider is registered, and the first check only records the
+		// provider's UTXOs.
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+		register_reserve_wallet(&nodes[0], 6);
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+
+		// Spending a UTXO isn't noticed until the wallet signals a change...
+		nodes[0].wallet_source.remove_utxo(small_outpoint);
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+		nodes[0].chain_monitor.chain_monitor.anchor_reserve_utxos_changed();
+		// ...after which it's reported, though the remaining reserve still supports the channel.
+		assert_eq!(spent_reserve_utxos(&nodes[0]), vec![(vec![small_outpoint], 0)]);
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+
+		// Without a signal from the wallet, the UTXOs are re-validated every 6 blocks.
+		nodes[0].wallet_source.remove_utxo(reserve_outpoint);
+		connect_blocks(&nodes[0], 5);
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+		connect_blocks(&nodes[0], 1);
+		assert_eq!(spent_reserve_utxos(&nodes[0]), vec![(vec![reserve_outpoint], 1)]);
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+	}
+
+	#[test]
+	fn test_anchor_reserve_utxos_spent_by_claim() {
+		// Test that reserve UTXOs spent by LDK's own anchor CPFP transaction are not reported as
+		// spent externally.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let config = anchors_config();
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(config.clone()), Some(config)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+
+		let coinbase_tx = provide_anchor_reserves(&nodes);
+		let (_, _, chan_id, funding_tx) =
+			create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+
+		let wallet = register_reserve_wallet(&nodes[0], 6);
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+
+		// Force close such that the commitment transaction needs to be bumped with a wallet UTXO.
+		nodes[0]
+			.fee_estimator
+			.target_override
+			.lock()
+			.unwrap()
+			.insert(ConfirmationTarget::OutputSpendingFee, 500);
+		let message = "Channel force-closed".to_owned();
+		nodes[0]
+			.node
+			.force_close_broadcasting_latest_txn(&chan_id, &node_b_id, message.clone())
+			.unwrap();
+		check_added_monitors(&nodes[0], 1);
+		check_closed_broadcast(&nodes[0], 1, true);
+		let reason =
+			ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+		check_closed_event!(nodes[0], 1, reason, [node_b_id], 1_000_000);
+
+		let bump_tx_handler = BumpTransactionEventHandlerSync::new(
+			nodes[0].tx_broadcaster,
+			wallet,
+			nodes[0].keys_manager,
+			nodes[0].logger,
+			HTLCDeadlineFeerateSchedule::default(),
+		);
+		let mut events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		match events.pop().unwrap() {
+			Event::BumpTransaction(event) => bump_tx_handler.handle_event(&event),
+			_ => panic!("Unexpected event"),
+		}
+		let txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+		assert_eq!(txn.len(), 2);
+		check_spends!(txn[0], funding_tx);
+		check_spends!(txn[1], coinbase_tx, txn[0]);
+
+		// Once the anchor transaction confirms, the wallet no longer lists the UTXO it spent, but
+		// as it was used for our own claim, it isn't reported.
+		mine_transactions(&nodes[0], &[&txn[0], &txn[1]]);
+		nodes[0].chain_monitor.chain_monitor.anchor_reserve_utxos_changed();
+		assert!(spent_reserve_utxos(&nodes[0]).is_empty());
+	}
+
 	#[test]
 	#[cfg(feature = "std")]
 	fn update_during_chainsync_poisons_channel() {
@@ -1557,4 +2236,164 @@ mod tests {
 		})
 		.is_err());
 	}
+
+	#[test]
+	fn test_with_monitors_concurrent_removal() {
+		// Test that `with_monitors` always sees a consistent snapshot of the monitor set, and that
+		// `with_monitor` fails cleanly for removed monitors, while monitors are being removed from
+		// another thread.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+		let mut channel_ids = Vec::new();
+		for _ in 0..4 {
+			channel_ids.push(create_announced_chan_between_nodes(&nodes, 0, 1).2);
+		}
+
+		let done = Arc::new(AtomicBool::new(false));
+		let mut threads = Vec::new();
+		for _ in 0..4 {
+			// We really want std::thread::scope, but its not stable until 1.63. Until then, we get unsafe.
+			let node_ref = NodePtr::from_node(&nodes[0]);
+			let done = Arc::clone(&done);
+			let channel_ids = channel_ids.clone();
+			threads.push(std::thread::spawn(move || {
+				let _ = &node_ref;
+				let node = unsafe { &*node_ref.0 };
+				let chain_monitor = &node.chain_monitor.chain_monitor;
+				let mut num_monitors = channel_ids.len();
+				while !done.load(Ordering::Acquire) {
+					let mut visited = Vec::new();
+					chain_monitor.with_monitors(|channel_id, monitor| {
+						assert_eq!(channel_id, monitor.channel_id());
+						visited.push(channel_id);
+					});
+
+					// Monitors are removed in order, so any snapshot must contain exactly the ones
+					// which were not yet removed.
+					assert!(visited.len() <= num_monitors);
+					num_monitors = visited.len();
+					let (removed, remaining) =
+						channel_ids.split_at(channel_ids.len() - num_monitors);
+					assert!(remaining.iter().all(|channel_id| visited.contains(channel_id)));
+					for channel_id in removed {
+						assert!(chain_monitor.with_monitor(*channel_id, |_| ()).is_err());
+					}
+				}
+			}));
+		}
+
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+		let mut removed_monitors = Vec::new();
+		for channel_id in channel_ids.iter() {
+			std::thread::sleep(std::time::Duration::from_millis(10));
+			removed_monitors.push(chain_monitor.remove_monitor(channel_id));
+			assert!(chain_monitor.with_monitor(*channel_id, |_| ()).is_err());
+		}
+		done.store(true, Ordering::Release);
+		for thread in threads {
+			thread.join().unwrap();
+		}
+
+		// Restore the monitors so that the consistency checks when dropping the nodes pass.
+		for (channel_id, monitor) in channel_ids.into_iter().zip(removed_monitors) {
+			assert_eq!(
+				chain_monitor.watch_channel(channel_id, monitor),
+				Ok(ChannelMonitorUpdateStatus::Completed)
+			);
+			let balances = chain_monitor
+				.with_monitor(channel_id, |monitor| monitor.get_claimable_balances().len());
+			assert_eq!(balances, Ok(1));
+		}
+	}
+
+	#[test]
+	fn test_monitor_summary_iteration_matches_list() {
+		// Test that iterating over and counting monitor summaries matches listing them, also for
+		// large numbers of monitors of which some are resolved.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let configs = [Some(anchors_config()), Some(anchors_config())];
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &configs);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let channel_id = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+
+		// Insert dummy copies of the channel's monitor, marking every third one as resolved.
+		const NUM_DUMMY_MONITORS: u32 = 3000;
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+		let monitor = chain_monitor.with_monitor(channel_id, |monitor| monitor.clone()).unwrap();
+		let dummy_channel_ids: Vec<ChannelId> = (0..NUM_DUMMY_MONITORS)
+			.map(|i| {
+				let mut channel_id = [0xff; 32];
+				channel_id[..4].copy_from_slice(&i.to_be_bytes());
+				ChannelId(channel_id)
+			})
+			.collect();
+		{
+			let mut monitors = chain_monitor.monitors.write().unwrap();
+			for (i, dummy_channel_id) in dummy_channel_ids.iter().enumerate() {
+				let holder = MonitorHolder::new(monitor.clone(), Vec::new());
+				if i % 3 == 0 {
+					holder.has_claimable_balances.store(false, Ordering::Release);
+					holder.needs_exogenous_fee_reserve.store(false, Ordering::Release);
+				}
+				monitors.insert(*dummy_channel_id, holder);
+			}
+		}
+		let num_unresolved = 1 + 2 * NUM_DUMMY_MONITORS as usize / 3;
+
+		let mut listed = chain_monitor.list_monitor_summaries();
+		listed.sort_unstable_by_key(|summary| summary.channel_id.0);
+		assert_eq!(listed.len(), 1 + NUM_DUMMY_MONITORS as usize);
+		let mut visited = Vec::new();
+		chain_monitor.for_each_monitor_summary(true, |summary| visited.push(summary.clone()));
+		visited.sort_unstable_by_key(|summary| summary.channel_id.0);
+		assert_eq!(visited, listed);
+
+		// Resolved monitors are skipped unless requested.
+		let mut visited = Vec::new();
+		chain_monitor.for_each_monitor_summary(false, |summary| visited.push(summary.clone()));
+		visited.sort_unstable_by_key(|summary| summary.channel_id.0);
+		listed.retain(|summary| summary.has_claimable_balances);
+		assert_eq!(visited, listed);
+		assert_eq!(visited.len(), num_unresolved);
+
+		let requires_anchor_fees = |summary: &MonitorSummary| {
+			summary.channel_type_features.supports_anchors_zero_fee_htlc_tx()
+				&& summary.needs_exogenous_fee_reserve
+		};
+		assert_eq!(
+			listed.iter().filter(|summary| requires_anchor_fees(*summary)).count(),
+			num_unresolved
+		);
+		assert_eq!(
+			chain_monitor.count_monitors_matching(true, requires_anchor_fees),
+			num_unresolved
+		);
+		assert_eq!(
+			chain_monitor.count_monitors_matching(false, requires_anchor_fees),
+			num_unresolved
+		);
+		assert_eq!(
+			chain_monitor.count_monitors_matching(true, |_| true),
+			1 + NUM_DUMMY_MONITORS as usize
+		);
+
+		// The reserve check counts the same anchor channels.
+		#[cfg(feature = "std")]
+		{
+			let context = AnchorChannelReserveContext::default();
+			let reserve_check =
+				AnchorChannelReserveCheck::new(&context, &[], nodes[0].node, chain_monitor);
+			assert_eq!(reserve_check.num_anchor_channels(), num_unresolved as u64);
+		}
+
+		// Remove the dummy monitors so that the consistency checks when dropping the nodes pass.
+		let mut monitors = chain_monitor.monitors.write().unwrap();
+		for dummy_channel_id in dummy_channel_ids.iter() {
+			monitors.remove(dummy_channel_id);
+		}
+	}
 }
@@ -43,14 +43,14 @@ use crate::chain::package::{
 };
 use crate::chain::transaction::{OutPoint, TransactionData};
 use crate::chain::Filter;
-use crate::chain::{BestBlock, WatchedOutput};
+use crate::chain::{BestBlock, ClaimId, WatchedOutput};
 use crate::events::bump_transaction::{AnchorDescriptor, BumpTransactionEvent};
 use crate::events::{ClosureReason, Event, EventHandler, ReplayEvent};
 use crate::ln::chan_utils::{
 	self, ChannelTransactionParameters, CommitmentTransaction, CounterpartyCommitmentSecrets,
 	HTLCClaim, HTLCOutputInCommitment, HolderCommitmentTransaction,
 };
-use crate::ln::channel::INITIAL_COMMITMENT_NUMBER;
+use crate::ln::channel::{ANCHOR_OUTPUT_VALUE_SATOSHI, INITIAL_COMMITMENT_NUMBER};
 use crate::ln::channel_keys::{
 	DelayedPaymentBasepoint, DelayedPaymentKey, HtlcBasepoint, HtlcKey, RevocationBasepoint,
 	RevocationKey,
@@ -65,7 +65,9 @@ use crate::sign::{
 };
 use crate::types::features::ChannelTypeFeatures;
 use crate::types::payment::{PaymentHash, PaymentPreimage};
+use crate::util::anchor_channel_reserves::is_anchor_channel_type;
 use crate::util::byte_utils;
+use crate::util::deadlines::DeadlineKind;
 use crate::util::logger::{Logger, Record};
 use crate::util::persist::MonitorName;
 use crate::util::ser::{
@@ -78,6 +80,7 @@ use crate::prelude::*;
 
 use crate::io::{self, Error};
 use crate::sync::{LockTestExt, Mutex};
+use core::cell::RefCell;
 use core::ops::Deref;
 use core::{cmp, mem};
 
@@ -926,8 +929,268 @@ impl Balance {
 			Balance::MaybePreimageClaimableHTLC { .. } => 0,
 		}
 	}
+
+	/// Returns whether this balance is ours unconditionally, or which action by us or our
+	/// counterparty it depends on.
+	///
+	/// See [`BalanceCertainty`] for the action and timing required for each classification.
+	pub fn outcome_certainty(&self) -> BalanceCertainty {
+		match self {
+			Balance::ClaimableOnChannelClose { .. }
+			| Balance::ClaimableAwaitingConfirmations { .. } => {
+				BalanceCertainty::ClaimableByUsUnconditionally
+			},
+			Balance::ContentiousClaimable { .. }
+			| Balance::CounterpartyRevokedOutputClaimable { .. } => {
+				BalanceCertainty::ClaimableByUsBeforeTimeout
+			},
+			Balance::MaybePreimageClaimableHTLC { .. } => {
+				BalanceCertainty::ClaimableByUsIfPreimageKnown
+			},
+			Balance::MaybeTimeoutClaimableHTLC { .. } => {
+				BalanceCertainty::CounterpartyCanClaimBeforeTimeout
+			},
+		}
+	}
+
+	/// Returns whether resolving this balance may still require fees paid from outside of the
+	/// channel, as used by [`ChannelMonitor::needs_exogenous_fee_reserve`].
+	pub(crate) fn needs_exogenous_fee_reserve(&self) -> bool {
+		match self {
+			// The commitment transaction has yet to confirm and may have to be bumped.
+			Balance::ClaimableOnChannelClose { .. } => true,
+			// HTLC claims may have to be bumped to confirm before the HTLCs expire.
+			Balance::ContentiousClaimable { .. } => true,
+			Balance::MaybeTimeoutClaimableHTLC { .. } => true,
+			Balance::MaybePreimageClaimableHTLC { .. } => true,
+			// The transaction resolving the balance has already confirmed.
+			Balance::ClaimableAwaitingConfirmations { .. } => false,
+			Balance::CounterpartyRevokedOutputClaimable { .. } => false,
+		}
+	}
+}
+
+/// Whether a [`Balance`] is ours unconditionally, or which action by us or our counterparty it
+/// depends on, as returned by [`Balance::outcome_certainty`].
+///
+/// Only [`BalanceCertainty::ClaimableByUsUnconditionally`] balances are guaranteed to become
+/// spendable. Balances of the other classifications may be lost if we fail to act in time or
+/// our counterparty acts first.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(test, derive(PartialOrd, Ord))]
+pub enum BalanceCertainty {
+	/// The balance is ours and our counterparty cannot take it from us.
+	///
+	/// For [`Balance::ClaimableOnChannelClose`], the channel has to be closed first, which may
+	/// require a force-close. For [`Balance::ClaimableAwaitingConfirmations`], we only have to
+	/// wait for the [`Balance::ClaimableAwaitingConfirmations::confirmation_height`].
+	///
+	/// Note that [`Balance::ClaimableOnChannelClose`] includes inbound HTLCs we know the preimage
+	/// for, which become [`BalanceCertainty::ClaimableByUsBeforeTimeout`] balances once the
+	/// channel is force-closed.
+	ClaimableByUsUnconditionally,
+	/// The balance is ours if our claim confirms before a timeout, after which our counterparty
+	/// may be able to claim it instead.
+	///
+	/// For [`Balance::ContentiousClaimable`], we know the preimage and have to confirm our claim
+	/// before the [`Balance::ContentiousClaimable::timeout_height`]. For
+	/// [`Balance::CounterpartyRevokedOutputClaimable`], we have to confirm our claim before the
+	/// counterparty's timelock on the revoked output expires, which is handled automatically as
+	/// long as the [`ChannelMonitor`] is kept up-to-date with the chain.
+	ClaimableByUsBeforeTimeout,
+	/// The balance is only ours if we learn the preimage for an inbound HTLC, e.g., from the
+	/// downstream node we forwarded the HTLC to, and claim it before the
+	/// [`Balance::MaybePreimageClaimableHTLC::expiry_height`]. Otherwise, our counterparty will
+	/// claim it once the HTLC expires.
+	ClaimableByUsIfPreimageKnown,
+	/// The balance is only ours if our counterparty does not claim an outbound HTLC with its
+	/// preimage before the [`Balance::MaybeTimeoutClaimableHTLC::claimable_height`], after which
+	/// we can claim it back with an HTLC-timeout claim. As our counterparty is likely to know the
+	/// preimage, it is likely to claim the balance first.
+	CounterpartyCanClaimBeforeTimeout,
+}
+
+/// A [`Balance`] along with the block heights relevant to claiming it.
+///
+/// See [`ChannelMonitor::get_detailed_claimable_balances`] for more details.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DetailedBalance {
+	/// The balance itself, as would be returned by [`ChannelMonitor::get_claimable_balances`].
+	pub balance: Balance,
+	/// The absolute block height at which our counterparty may be able to claim this balance if we
+	/// have not yet done so (or, for [`Balance::ClaimableOnChannelClose`], the earliest height at
+	/// which an inbound HTLC we know the preimage for expires).
+	///
+	/// This is:
+	///  * the HTLC expiry for [`Balance::ContentiousClaimable`] and
+	///    [`Balance::MaybePreimageClaimableHTLC`] balances, as well as for
+	///    [`Balance::ClaimableAwaitingConfirmations`] balances resulting from a preimage claim of
+	///    an HTLC, in case the claim is reorganized out of the chain,
+	///  * the height at which the CSV (or HTLC) timelock on the output matures for
+	///    [`Balance::CounterpartyRevokedOutputClaimable`] balances, if known.
+	///
+	/// `None` if there is no such deadline or it is not known.
+	pub deadline_height: Option<u32>,
+	/// The number of blocks which still have to be connected before the balance is considered
+	/// spendable and an [`Event::SpendableOutputs`] is generated for it.
+	///
+	/// This is only set for [`Balance::ClaimableAwaitingConfirmations`] balances, and is zero once
+	/// the [`Balance::ClaimableAwaitingConfirmations::confirmation_height`] has been reached.
+	pub confirmations_remaining: Option<u32>,
+}
+
+/// A [`Balance`] along with the estimated block heights at which it can be claimed and at which
+/// it becomes spendable by our on-chain wallet.
+///
+/// See [`ChannelMonitor::get_resolution_timeline`] for more details.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ResolutionTimelineEntry {
+	/// The balance itself, as would be returned by [`ChannelMonitor::get_claimable_balances`].
+	pub balance: Balance,
+	/// The earliest block height at which the balance can be claimed, i.e., at which we can
+	/// force-close the channel or broadcast a claim for the balance, or, for
+	/// [`Balance::ClaimableAwaitingConfirmations`], at which an [`Event::SpendableOutputs`] is
+	/// generated for it.
+	///
+	/// For balances which depend on learning a preimage, this assumes we learn it immediately.
+	pub claimable_height: u32,
+	/// The estimated block height at which the balance becomes spendable by our on-chain wallet.
+	///
+	/// This assumes each claim confirms in the block after it is broadcast, and includes the
+	/// [`ANTI_REORG_DELAY`] confirmations a sweep of the output, e.g., by an [`OutputSweeper`],
+	/// requires after the [`Event::SpendableOutputs`] is generated. Balances of a cooperative
+	/// close are paid to our wallet directly and are thus spendable at their
+	/// [`Self::claimable_height`].
+	///
+	/// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
+	pub spendable_height: u32,
+	/// Whether claiming the balance may require fees paid from outside of the channel, e.g., to
+	/// bump our commitment or HTLC transactions via their anchor outputs.
+	pub needs_exogenous_fees: bool,
+	/// The outpoint of the output which will be provided via [`Event::SpendableOutputs`] for this
+	/// balance, as given by [`SpendableOutputDescriptor::spendable_outpoint`], allowing it to be
+	/// looked up via [`OutputSweeper::tracked_spendable_output`] once it is handed over.
+	///
+	/// This is only known for [`Balance::ClaimableAwaitingConfirmations`] balances whose output
+	/// has been confirmed with its full value, and is thus `None` for HTLC balances claimed by
+	/// HTLC transactions which pay their fees from the HTLC's value.
+	///
+	/// [`OutputSweeper::tracked_spendable_output`]: crate::util::sweep::OutputSweeper::tracked_spendable_output
+	pub spendable_outpoint: Option<OutPoint>,
+}
+
+/// Whether the anchor output of our broadcast commitment transaction is being spent to bump the
+/// commitment transaction's fee, or why it is left unclaimed.
+///
+/// See [`ChannelMonitor::get_holder_anchor_output`] for more details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AnchorClaimStatus {
+	/// Our commitment transaction pays less than the target feerate, so a
+	/// [`BumpTransactionEvent::ChannelClose`] is being generated to spend the anchor output and
+	/// bump the commitment transaction's fee.
+	Claiming,
+	/// Our commitment transaction already pays at least the target feerate, so there is no need to
+	/// spend the anchor output to bump its fee.
+	FeerateSufficient {
+		/// The feerate, in satoshis per 1000 weight units, our commitment transaction pays.
+		commitment_feerate_sat_per_1000_weight: u32,
+	},
+	/// Spending the anchor output at the target feerate would cost more in fees than the anchor
+	/// output is worth, and our commitment transaction already pays at least the feerate set via
+	/// [`ChannelMonitor::set_min_feerate_to_skip_uneconomical_anchor_claim`].
+	Uneconomical {
+		/// The feerate, in satoshis per 1000 weight units, our commitment transaction pays.
+		commitment_feerate_sat_per_1000_weight: u32,
+		/// The fee, in satoshis, required to spend the anchor output at the target feerate.
+		claim_fee_satoshis: u64,
+	},
+	/// No HTLCs are at risk and the fees required to claim our commitment transaction at the
+	/// target feerate exceed the fraction of our balance set via
+	/// [`ChannelMonitor::set_max_force_close_fee_proportional_millionths`], so we wait for
+	/// feerates to drop, only spending the anchor output if our commitment transaction pays less
+	/// than the lowest feerate required to enter the mempool.
+	Deferred {
+		/// The feerate, in satoshis per 1000 weight units, our commitment transaction pays.
+		commitment_feerate_sat_per_1000_weight: u32,
+		/// The lowest feerate, in satoshis per 1000 weight units, we target instead, as given by
+		/// [`ConfirmationTarget::MinAllowedAnchorChannelRemoteFee`].
+		target_feerate_sat_per_1000_weight: u32,
+		/// The estimated fee, in satoshis, we would pay to claim our commitment transaction at the
+		/// target feerate, as estimated by [`estimate_force_close_cost`].
+		///
+		/// [`estimate_force_close_cost`]: crate::util::anchor_channel_reserves::estimate_force_close_cost
+		claim_fee_satoshis: u64,
+		/// Our balance in the commitment transaction, in satoshis.
+		balance_satoshis: u64,
+	},
+	/// Our commitment transaction has confirmed, so its fee can no longer be bumped.
+	CommitmentConfirmed,
+}
+
+/// The anchor output of our broadcast commitment transaction, along with whether we're spending
+/// it, as returned by [`ChannelMonitor::get_holder_anchor_output`].
+///
+/// The value of the anchor output is never included in a [`Balance`], as it is only spent to
+/// bump the fee of our commitment transaction, if at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HolderAnchorOutput {
+	/// The txid of our commitment transaction containing the anchor output.
+	pub commitment_txid: Txid,
+	/// The value of the anchor output, in satoshis.
+	pub amount_satoshis: u64,
+	/// Whether we're spending the anchor output, or why it is left unclaimed.
+	pub claim_status: AnchorClaimStatus,
+}
+
+/// Statistics on the claims we made on-chain to resolve a closed channel, as returned by
+/// [`ChannelMonitor::get_onchain_resolution_stats`].
+///
+/// Only claim transactions which confirmed and reached [`ANTI_REORG_DELAY`] confirmations are
+/// accounted for. The fees of transactions spending our anchor outputs and of HTLC transactions
+/// with anchor outputs are paid by external inputs, and are thus only known once provided via
+/// [`ChannelMonitor::provide_claim_fee_bump`]. The fee of the commitment transaction itself is
+/// never included.
+///
+/// A set of historical statistics can be used to calibrate the [`AnchorChannelReserveContext`]
+/// via [`suggest_reserve_context`].
+///
+/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+/// [`suggest_reserve_context`]: crate::util::anchor_channel_reserves::suggest_reserve_context
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct OnchainResolutionStats {
+	/// The total fees paid across our confirmed claim transactions, including those spending our
+	/// anchor outputs.
+	pub total_claim_fees: Amount,
+	/// The total value of the external inputs spent by our confirmed claim transactions, before
+	/// deducting any change returned to the wallet.
+	pub total_external_inputs_value: Amount,
+	/// The number of our claim transactions which confirmed.
+	pub num_confirmed_claims: u32,
+	/// The number of HTLC outputs spent by our confirmed claim transactions.
+	pub num_htlc_outputs_claimed: u32,
+	/// The highest feerate, in satoshis per 1000 weight units, paid by any of our confirmed claim
+	/// transactions, or targeted for fee bumps provided via
+	/// [`ChannelMonitor::provide_claim_fee_bump`].
+	pub max_claim_feerate_sat_per_1000_weight: u32,
+	/// The highest number of claims we had pending at the same time.
+	pub max_pending_claims: u32,
+	/// The portion of [`Self::total_claim_fees`] paid out of external inputs, i.e., by the
+	/// transactions spending our anchor outputs and by HTLC transactions with anchor outputs.
+	///
+	/// Note that this is only tracked for claims resolved since LDK 0.2.
+	pub total_exogenous_fees: Amount,
 }
 
+impl_writeable_tlv_based!(OnchainResolutionStats, {
+	(0, total_claim_fees, required),
+	(2, total_external_inputs_value, required),
+	(4, num_confirmed_claims, required),
+	(6, num_htlc_outputs_claimed, required),
+	(8, max_claim_feerate_sat_per_1000_weight, required),
+	(10, max_pending_claims, required),
+	(12, total_exogenous_fees, (default_value, Amount::ZERO)),
+});
+
 /// An HTLC which has been irrevocably resolved on-chain, and has reached ANTI_REORG_DELAY.
 #[derive(Clone, PartialEq, Eq)]
 struct IrrevocablyResolvedHTLC {
@@ -980,6 +1243,87 @@ impl Readable for IrrevocablyResolvedHTLC {
 	}
 }
 
+/// A summary of what an emergency broadcast of our latest commitment transaction would put on
+/// chain, as provided by [`ChannelMonitor::prepare_emergency_broadcast`].
+///
+/// Its [`Display`] implementation describes the risks involved in human-readable form, e.g., to
+/// present them to an operator before the broadcast is executed.
+///
+/// [`Display`]: core::fmt::Display
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EmergencyBroadcastSummary {
+	/// The channel whose commitment transaction would be broadcast.
+	pub channel_id: ChannelId,
+	/// The `node_id` of the counterparty of the channel.
+	pub counterparty_node_id: PublicKey,
+	/// The backwards-counting number of the commitment transaction which would be broadcast, as
+	/// in [`CommitmentTransaction::commitment_number`].
+	pub commitment_number: u64,
+	/// The txid of the commitment transaction which would be broadcast.
+	pub commitment_txid: Txid,
+	/// The non-dust HTLCs on the commitment transaction, which would have to be resolved on chain.
+	pub htlcs: Vec<HTLCOutputInCommitment>,
+	/// Our balance on the commitment transaction, which would only become spendable once the
+	/// channel's `to_self_delay` expires.
+	pub to_self_value_satoshis: u64,
+	/// The fee rate the commitment transaction commits to, in satoshis per 1000 weight units.
+	pub feerate_per_kw: u32,
+	/// Whether fees have to be provided at broadcast time by spending an anchor output of the
+	/// commitment transaction, as indicated by [`Event::BumpTransaction`], rather than being
+	/// committed to.
+	pub requires_exogenous_fees: bool,
+}
+
+impl core::fmt::Display for EmergencyBroadcastSummary {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		let htlc_value_msat: u64 = self.htlcs.iter().map(|htlc| htlc.amount_msat).sum();
+		write!(
+			f,
+			"Broadcasting commitment transaction {} (commitment number {}) of channel {} with {} puts {} HTLCs worth {} msat on chain and locks our balance of {} sat until the channel's to_self_delay expires. ",
+			self.commitment_txid,
+			self.commitment_number,
+			self.channel_id,
+			self.counterparty_node_id,
+			self.htlcs.len(),
+			htlc_value_msat,
+			self.to_self_value_satoshis,
+		)?;
+		if self.requires_exogenous_fees {
+			write!(f, "Fees have to be provided from our on-chain wallet via the anchor output. ")?;
+		} else {
+			write!(
+				f,
+				"The commitment transaction pays a fixed fee rate of {} sat/kW, which may not confirm in time. ",
+				self.feerate_per_kw,
+			)?;
+		}
+		write!(
+			f,
+			"If the counterparty has a revocation secret for this commitment transaction, it can claim the entire channel balance."
+		)
+	}
+}
+
+/// Authorizes a single emergency broadcast of our latest commitment transaction through
+/// [`ChainMonitor::execute_emergency_broadcast`], as returned by
+/// [`ChannelMonitor::prepare_emergency_broadcast`].
+///
+/// A token is only valid for the commitment transaction it was prepared for, so a new one has to
+/// be prepared once the channel advances to a new commitment transaction.
+///
+/// [`ChainMonitor::execute_emergency_broadcast`]: crate::chain::chainmonitor::ChainMonitor::execute_emergency_broadcast
+#[derive(Debug)]
+pub struct EmergencyBroadcastToken {
+	summary: EmergencyBroadcastSummary,
+}
+
+impl EmergencyBroadcastToken {
+	/// Returns the summary of what the broadcast would put on chain.
+	pub fn summary(&self) -> &EmergencyBroadcastSummary {
+		&self.summary
+	}
+}
+
 /// A ChannelMonitor handles chain events (blocks connected and disconnected) and generates
 /// on-chain transactions to ensure no loss of funds occurs.
 ///
@@ -1267,6 +1611,10 @@ pub(crate) struct ChannelMonitorImpl<Signer: EcdsaChannelSigner> {
 	/// The first block height at which we had no remaining claimable balances.
 	balances_empty_height: Option<u32>,
 
+	/// Set once we've generated an [`Event::AnchorChannelResolutionStarted`], such that it is only
+	/// generated once per channel.
+	resolution_started_event_generated: bool,
+
 	/// In-memory only HTLC ids used to track upstream HTLCs that have been failed backwards due to
 	/// a downstream channel force-close remaining unconfirmed by the time the upstream timeout
 	/// expires. This is used to tell us we already generated an event to fail this HTLC back
@@ -1280,6 +1628,37 @@ pub(crate) struct ChannelMonitorImpl<Signer: EcdsaChannelSigner> {
 	// commitment transactions, their ordering with respect to each other must remain the same.
 	current_holder_htlc_data: CommitmentHTLCData,
 	prev_holder_htlc_data: Option<CommitmentHTLCData>,
+
+	/// In-memory only cache of our claimable balances, see [`ClaimableBalancesCache`].
+	claimable_balances_cache: ClaimableBalancesCache,
+}
+
+/// An in-memory cache of the claimable balances of a [`ChannelMonitor`], as computed by
+/// [`ChannelMonitorImpl::compute_claimable_balances_with_deadlines`].
+///
+/// The balances are keyed by the latest update ID and best block they were computed at, and are
+/// additionally invalidated whenever transactions are confirmed or unconfirmed, or claims are
+/// queued, without either changing. The cache is not serialized and is ignored when comparing
+/// monitors.
+#[derive(Default)]
+struct ClaimableBalancesCache(RefCell<Option<((u64, BlockHash), Vec<(Balance, Option<u32>)>)>>);
+
+impl ClaimableBalancesCache {
+	fn invalidate(&self) {
+		*self.0.borrow_mut() = None;
+	}
+}
+
+impl Clone for ClaimableBalancesCache {
+	fn clone(&self) -> Self {
+		Self(RefCell::new(self.0.borrow().clone()))
+	}
+}
+
+impl PartialEq for ClaimableBalancesCache {
+	fn eq(&self, _: &Self) -> bool {
+		true
+	}
 }
 
 // Macro helper to access holder commitment HTLC data (including both non-dust and dust) while
@@ -1555,6 +1934,7 @@ impl<Signer: EcdsaChannelSigner> Writeable for ChannelMonitorImpl<Signer> {
 			(29, self.initial_counterparty_commitment_tx, option),
 			(31, self.funding.channel_parameters, required),
 			(32, self.pending_funding, optional_vec),
+			(33, self.resolution_started_event_generated, required),
 		});
 
 		Ok(())
@@ -1774,12 +2154,15 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 			initial_counterparty_commitment_info: None,
 			initial_counterparty_commitment_tx: None,
 			balances_empty_height: None,
+			resolution_started_event_generated: false,
 
 			failed_back_htlc_ids: new_hash_set(),
 
 			// There are never any HTLCs in the initial commitment transaction
 			current_holder_htlc_data: CommitmentHTLCData::new(),
 			prev_holder_htlc_data: None,
+
+			claimable_balances_cache: ClaimableBalancesCache::default(),
 		})
 	}
 
@@ -2124,6 +2507,10 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 	/// close channel with their commitment transaction after a substantial amount of time. Best
 	/// may be to contact the other node operator out-of-band to coordinate other options available
 	/// to you.
+	///
+	/// [`Self::prepare_emergency_broadcast`] provides a safer, two-step alternative which
+	/// summarizes the risks of the broadcast first and refuses to broadcast a commitment
+	/// transaction which is no longer the latest one.
 	#[rustfmt::skip]
 	pub fn broadcast_latest_holder_commitment_txn<B: Deref, F: Deref, L: Deref>(
 		&self, broadcaster: &B, fee_estimator: &F, logger: &L
@@ -2139,6 +2526,62 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 		inner.queue_latest_holder_commitment_txn_for_broadcast(broadcaster, &fee_estimator, &logger);
 	}
 
+	/// Prepares a broadcast of our latest commitment transaction, e.g., because our counterparty has
+	/// become unresponsive, returning a token to perform it through
+	/// [`ChainMonitor::execute_emergency_broadcast`].
+	///
+	/// The token includes an [`EmergencyBroadcastSummary`] of the transactions and HTLCs that would
+	/// go on chain, which should be reviewed before executing the broadcast. Preparing a broadcast
+	/// has no effect on the channel.
+	///
+	/// [`ChainMonitor::execute_emergency_broadcast`]: crate::chain::chainmonitor::ChainMonitor::execute_emergency_broadcast
+	pub fn prepare_emergency_broadcast(&self) -> EmergencyBroadcastToken {
+		let inner = self.inner.lock().unwrap();
+		let commitment_tx = &inner.funding.current_holder_commitment_tx;
+		let summary = EmergencyBroadcastSummary {
+			channel_id: inner.channel_id(),
+			counterparty_node_id: inner.counterparty_node_id,
+			commitment_number: commitment_tx.commitment_number(),
+			commitment_txid: commitment_tx.trust().txid(),
+			htlcs: commitment_tx.nondust_htlcs().clone(),
+			to_self_value_satoshis: commitment_tx.to_broadcaster_value_sat(),
+			feerate_per_kw: commitment_tx.feerate_per_kw(),
+			requires_exogenous_fees: is_anchor_channel_type(inner.channel_type_features()),
+		};
+		EmergencyBroadcastToken { summary }
+	}
+
+	/// Broadcasts our latest commitment transaction for an [`EmergencyBroadcastToken`], failing if
+	/// it was prepared for a different commitment transaction.
+	pub(crate) fn execute_emergency_broadcast<B: Deref, F: Deref, L: Deref>(
+		&self, token: &EmergencyBroadcastToken, broadcaster: &B, fee_estimator: &F, logger: &L,
+	) -> Result<(), ()>
+	where
+		B::Target: BroadcasterInterface,
+		F::Target: FeeEstimator,
+		L::Target: Logger,
+	{
+		let mut inner = self.inner.lock().unwrap();
+		let fee_estimator = LowerBoundedFeeEstimator::new(&**fee_estimator);
+		let logger = WithChannelMonitor::from_impl(logger, &*inner, None);
+		let commitment_number = inner.funding.current_holder_commitment_tx.commitment_number();
+		if commitment_number != token.summary.commitment_number {
+			return Err(());
+		}
+		log_error!(logger, "Executing emergency broadcast: {}", token.summary);
+		let reason = ClosureReason::HolderForceClosed {
+			broadcasted_latest_txn: Some(true),
+			message: "Emergency broadcast of the latest commitment transaction".to_owned(),
+		};
+		inner.queue_holder_commitment_txn_for_broadcast(
+			reason,
+			broadcaster,
+			&fee_estimator,
+			&logger,
+		);
+		Ok(())
+	}
+
 	/// Unsafe test-only version of `broadcast_latest_holder_commitment_txn` used by our test framework
 	/// to bypass HolderCommitmentTransaction state update lockdown after signature and generate
 	/// revoked commitment transaction.
@@ -2485,7 +2928,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 	fn get_htlc_balance(&self, htlc: &HTLCOutputInCommitment, source: Option<&HTLCSource>,
 		holder_commitment: bool, counterparty_revoked_commitment: bool,
 		confirmed_txid: Option<Txid>
-	) -> Option<Balance> {
+	) -> Option<(Balance, Option<u32>)> {
 		let htlc_commitment_tx_output_idx = htlc.transaction_output_index?;
 
 		let mut htlc_spend_txid_opt = None;
@@ -2569,11 +3012,11 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 
 		if let Some(conf_thresh) = holder_delayed_output_pending {
 			debug_assert!(holder_commitment);
-			return Some(Balance::ClaimableAwaitingConfirmations {
+			return Some((Balance::ClaimableAwaitingConfirmations {
 				amount_satoshis: htlc.amount_msat / 1000,
 				confirmation_height: conf_thresh,
 				source: BalanceSource::Htlc,
-			});
+			}, None));
 		} else if htlc_resolved && !htlc_output_spend_pending {
 			// Funding transaction spends should be fully confirmed by the time any
 			// HTLC transactions are resolved, unless we're talking about a holder
@@ -2608,32 +3051,33 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 					"HTLCUpdate OnchainEvents should never appear for preimage claims");
 				debug_assert!(!htlc.offered || htlc_spend_pending.is_none() || !htlc_spend_pending.unwrap().1,
 					"We don't (currently) generate preimage claims against revoked outputs, where did you get one?!");
-				return Some(Balance::CounterpartyRevokedOutputClaimable {
+				let deadline = self.onchain_tx_handler.get_claim_deadline(&htlc_output_to_spend);
+				return Some((Balance::CounterpartyRevokedOutputClaimable {
 					amount_satoshis: htlc.amount_msat / 1000,
-				});
+				}, deadline));
 			}
 		} else if htlc.offered == holder_commitment {
 			// If the payment was outbound, check if there's an HTLCUpdate
 			// indicating we have spent this HTLC with a timeout, claiming it back
 			// and awaiting confirmations on it.
 			if let Some(conf_thresh) = holder_timeout_spend_pending {
-				return Some(Balance::ClaimableAwaitingConfirmations {
+				return Some((Balance::ClaimableAwaitingConfirmations {
 					amount_satoshis: htlc.amount_msat / 1000,
 					confirmation_height: conf_thresh,
 					source: BalanceSource::Htlc,
-				});
+				}, None));
 			} else {
 				let outbound_payment = match source {
 					None => panic!("Outbound HTLCs should have a source"),
 					Some(&HTLCSource::PreviousHopData(_)) => false,
 					Some(&HTLCSource::OutboundRoute { .. }) => true,
 				};
-				return Some(Balance::MaybeTimeoutClaimableHTLC {
+				return Some((Balance::MaybeTimeoutClaimableHTLC {
 					amount_satoshis: htlc.amount_msat / 1000,
 					claimable_height: htlc.cltv_expiry,
 					payment_hash: htlc.payment_hash,
 					outbound_payment,
-				});
+				}, None));
 			}
 		} else if let Some((payment_preimage, _)) = self.payment_preimages.get(&htlc.payment_hash) {
 			// Otherwise (the payment was inbound), only expose it as claimable if
@@ -2643,49 +3087,80 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 			// to show it as ContentiousClaimable until ANTI_REORG_DELAY.
 			debug_assert!(holder_timeout_spend_pending.is_none());
 			if let Some((conf_thresh, true)) = htlc_spend_pending {
-				return Some(Balance::ClaimableAwaitingConfirmations {
+				// If the preimage claim is reorganized out of the chain, we have to get it
+				// confirmed again before our counterparty can claim the HTLC via timeout.
+				return Some((Balance::ClaimableAwaitingConfirmations {
 					amount_satoshis: htlc.amount_msat / 1000,
 					confirmation_height: conf_thresh,
 					source: BalanceSource::Htlc,
-				});
+				}, Some(htlc.cltv_expiry)));
 			} else {
-				return Some(Balance::ContentiousClaimable {
+				return Some((Balance::ContentiousClaimable {
 					amount_satoshis: htlc.amount_msat / 1000,
 					timeout_height: htlc.cltv_expiry,
 					payment_hash: htlc.payment_hash,
 					payment_preimage: *payment_preimage,
-				});
+				}, Some(htlc.cltv_expiry)));
 			}
 		} else if !htlc_resolved {
-			return Some(Balance::MaybePreimageClaimableHTLC {
+			return Some((Balance::MaybePreimageClaimableHTLC {
 				amount_satoshis: htlc.amount_msat / 1000,
 				expiry_height: htlc.cltv_expiry,
 				payment_hash: htlc.payment_hash,
-			});
+			}, Some(htlc.cltv_expiry)));
 		}
 		None
 	}
-}
 
-impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
-	/// Gets the balances in this channel which are either claimable by us if we were to
-	/// force-close the channel now or which are claimable on-chain (possibly awaiting
-	/// confirmation).
-	///
-	/// Any balances in the channel which are available on-chain (excluding on-chain fees) are
-	/// included here until an [`Event::SpendableOutputs`] event has been generated for the
-	/// balance, or until our counterparty has claimed the balance and accrued several
-	/// confirmations on the claim transaction.
-	///
-	/// Note that for `ChannelMonitors` which track a channel which went on-chain with versions of
-	/// LDK prior to 0.0.111, not all or excess balances may be included.
+	/// Returns whether a spend of the funding output has been seen on-chain, even if it has not
+	/// yet reached [`ANTI_REORG_DELAY`] confirmations.
+	fn funding_spend_seen(&self) -> bool {
+		self.funding_spend_confirmed.is_some()
+			|| self
+				.onchain_events_awaiting_threshold_conf
+				.iter()
+				.any(|event| matches!(event.event, OnchainEvent::FundingSpendConfirmation { .. }))
+	}
+
+	/// Helper for [`ChannelMonitor::reserve_release_height`].
+	fn reserve_release_height(&self) -> Option<u32> {
+		if !self.funding_spend_seen() {
+			return None;
+		}
+		let balances = self.get_claimable_balances_with_deadlines();
+		if balances.iter().any(|(balance, _)| balance.needs_exogenous_fee_reserve()) {
+			return None;
+		}
+		let threshold_height =
+			self.onchain_tx_handler.get_externally_funded_claims_threshold_height();
+		Some(threshold_height.unwrap_or(self.best_block.height))
+	}
+
+	/// Helper for [`ChannelMonitor::get_claimable_balances`] and
+	/// [`ChannelMonitor::get_detailed_claimable_balances`], returning each [`Balance`] along with
+	/// its [`DetailedBalance::deadline_height`].
 	///
-	/// See [`Balance`] for additional details on the types of claimable balances which
-	/// may be returned here and their meanings.
+	/// The balances are cached until a monitor update or block is processed, see
+	/// [`ClaimableBalancesCache`].
+	fn get_claimable_balances_with_deadlines(&self) -> Vec<(Balance, Option<u32>)> {
+		let key = (self.latest_update_id, self.best_block.block_hash);
+		let mut cache = self.claimable_balances_cache.0.borrow_mut();
+		if let Some((cached_key, balances)) = &*cache {
+			if *cached_key == key {
+				return balances.clone();
+			}
+		}
+		let balances = self.compute_claimable_balances_with_deadlines();
+		*cache = Some((key, balances.clone()));
+		balances
+	}
+
+	/// Computes the balances returned by [`Self::get_claimable_balances_with_deadlines`] from
+	/// scratch, bypassing the cache.
 	#[rustfmt::skip]
-	pub fn get_claimable_balances(&self) -> Vec<Balance> {
+	fn compute_claimable_balances_with_deadlines(&self) -> Vec<(Balance, Option<u32>)> {
 		let mut res = Vec::new();
-		let us = self.inner.lock().unwrap();
+		let us = self;
 
 		let mut confirmed_txid = us.funding_spend_confirmed;
 		let mut confirmed_counterparty_output = us.confirmed_commitment_tx_counterparty_output;
@@ -2732,11 +3207,11 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 							Some(descriptor.output.value)
 						} else { None }
 					}) {
-						res.push(Balance::ClaimableAwaitingConfirmations {
+						res.push((Balance::ClaimableAwaitingConfirmations {
 							amount_satoshis: value.to_sat(),
 							confirmation_height: conf_thresh,
 							source: BalanceSource::CounterpartyForceClosed,
-						});
+						}, None));
 					} else {
 						// If a counterparty commitment transaction is awaiting confirmation, we
 						// should either have a StaticPaymentOutput MaturingOutput event awaiting
@@ -2756,11 +3231,11 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 						if let OnchainEvent::MaturingOutput {
 							descriptor: SpendableOutputDescriptor::StaticOutput { output, .. }
 						} = &event.event {
-							res.push(Balance::ClaimableAwaitingConfirmations {
+							res.push((Balance::ClaimableAwaitingConfirmations {
 								amount_satoshis: output.value.to_sat(),
 								confirmation_height: event.confirmation_threshold(),
 								source: BalanceSource::CounterpartyForceClosed,
-							});
+							}, None));
 							if let Some(confirmed_to_self_idx) = confirmed_counterparty_output.map(|(idx, _)| idx) {
 								if event.transaction.as_ref().map(|tx|
 									tx.input.iter().any(|inp| inp.previous_output.vout == confirmed_to_self_idx)
@@ -2773,12 +3248,13 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 
 					if spent_counterparty_output {
 					} else if let Some((confirmed_to_self_idx, amt)) = confirmed_counterparty_output {
+						let to_self_outpoint = BitcoinOutPoint::new(txid, confirmed_to_self_idx);
 						let output_spendable = us.onchain_tx_handler
-							.is_output_spend_pending(&BitcoinOutPoint::new(txid, confirmed_to_self_idx));
+							.is_output_spend_pending(&to_self_outpoint);
 						if output_spendable {
-							res.push(Balance::CounterpartyRevokedOutputClaimable {
+							res.push((Balance::CounterpartyRevokedOutputClaimable {
 								amount_satoshis: amt.to_sat(),
-							});
+							}, us.onchain_tx_handler.get_claim_deadline(&to_self_outpoint)));
 						}
 					} else {
 						// Counterparty output is missing, either it was broadcasted on a
@@ -2789,22 +3265,22 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 			} else if txid == us.funding.current_holder_commitment_tx.trust().txid() {
 				walk_htlcs!(true, false, holder_commitment_htlcs!(us, CURRENT_WITH_SOURCES));
 				if let Some(conf_thresh) = pending_commitment_tx_conf_thresh {
-					res.push(Balance::ClaimableAwaitingConfirmations {
+					res.push((Balance::ClaimableAwaitingConfirmations {
 						amount_satoshis: us.funding.current_holder_commitment_tx.to_broadcaster_value_sat(),
 						confirmation_height: conf_thresh,
 						source: BalanceSource::HolderForceClosed,
-					});
+					}, None));
 				}
 				found_commitment_tx = true;
 			} else if let Some(prev_holder_commitment_tx) = &us.funding.prev_holder_commitment_tx {
 				if txid == prev_holder_commitment_tx.trust().txid() {
 					walk_htlcs!(true, false, holder_commitment_htlcs!(us, PREV_WITH_SOURCES).unwrap());
 					if let Some(conf_thresh) = pending_commitment_tx_conf_thresh {
-						res.push(Balance::ClaimableAwaitingConfirmations {
+						res.push((Balance::ClaimableAwaitingConfirmations {
 							amount_satoshis: prev_holder_commitment_tx.to_broadcaster_value_sat(),
 							confirmation_height: conf_thresh,
 							source: BalanceSource::HolderForceClosed,
-						});
+						}, None));
 					}
 					found_commitment_tx = true;
 				}
@@ -2814,11 +3290,11 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 					// We blindly assume this is a cooperative close transaction here, and that
 					// neither us nor our counterparty misbehaved. At worst we've under-estimated
 					// the amount we can claim as we'll punish a misbehaving counterparty.
-					res.push(Balance::ClaimableAwaitingConfirmations {
+					res.push((Balance::ClaimableAwaitingConfirmations {
 						amount_satoshis: us.funding.current_holder_commitment_tx.to_broadcaster_value_sat(),
 						confirmation_height: conf_thresh,
 						source: BalanceSource::CoopClose,
-					});
+					}, None));
 				}
 			}
 		} else {
@@ -2827,6 +3303,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 			let mut outbound_forwarded_htlc_rounded_msat = 0;
 			let mut inbound_claiming_htlc_rounded_msat = 0;
 			let mut inbound_htlc_rounded_msat = 0;
+			let mut earliest_claiming_htlc_expiry: Option<u32> = None;
 			for (htlc, source) in holder_commitment_htlcs!(us, CURRENT_WITH_SOURCES) {
 				let rounded_value_msat = if htlc.transaction_output_index.is_none() {
 					htlc.amount_msat
@@ -2843,33 +3320,36 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 						outbound_forwarded_htlc_rounded_msat += rounded_value_msat;
 					}
 					if htlc.transaction_output_index.is_some() {
-						res.push(Balance::MaybeTimeoutClaimableHTLC {
+						res.push((Balance::MaybeTimeoutClaimableHTLC {
 							amount_satoshis: htlc.amount_msat / 1000,
 							claimable_height: htlc.cltv_expiry,
 							payment_hash: htlc.payment_hash,
 							outbound_payment,
-						});
+						}, None));
 					}
 				} else if us.payment_preimages.contains_key(&htlc.payment_hash) {
 					inbound_claiming_htlc_rounded_msat += rounded_value_msat;
 					if htlc.transaction_output_index.is_some() {
 						claimable_inbound_htlc_value_sat += htlc.amount_msat / 1000;
+						earliest_claiming_htlc_expiry = Some(cmp::min(
+							earliest_claiming_htlc_expiry.unwrap_or(u32::MAX), htlc.cltv_expiry
+						));
 					}
 				} else {
 					inbound_htlc_rounded_msat += rounded_value_msat;
 					if htlc.transaction_output_index.is_some() {
 						// As long as the HTLC is still in our latest commitment state, treat
 						// it as potentially claimable, even if it has long-since expired.
-						res.push(Balance::MaybePreimageClaimableHTLC {
+						res.push((Balance::MaybePreimageClaimableHTLC {
 							amount_satoshis: htlc.amount_msat / 1000,
 							expiry_height: htlc.cltv_expiry,
 							payment_hash: htlc.payment_hash,
-						});
+						}, Some(htlc.cltv_expiry)));
 					}
 				}
 			}
 			let to_self_value_sat = us.funding.current_holder_commitment_tx.to_broadcaster_value_sat();
-			res.push(Balance::ClaimableOnChannelClose {
+			res.push((Balance::ClaimableOnChannelClose {
 				amount_satoshis: to_self_value_sat + claimable_inbound_htlc_value_sat,
 				// In addition to `commit_tx_fee_sat`, this can also include dust HTLCs, any elided anchors,
 				// and the total msat amount rounded down from non-dust HTLCs
@@ -2882,12 +3362,385 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
 				outbound_forwarded_htlc_rounded_msat,
 				inbound_claiming_htlc_rounded_msat,
 				inbound_htlc_rounded_msat,
-			});
+			}, earliest_claiming_htlc_expiry));
 		}
 
 		res
 	}
 
+	/// Helper for [`ChannelMonitor::get_resolution_timeline`].
+	fn get_resolution_timeline(&self, current_height: u32) -> Vec<ResolutionTimelineEntry> {
+		let funding_spend_txid = self.funding_spend_confirmed.or_else(|| {
+			self.onchain_events_awaiting_threshold_conf.iter().find_map(|event| match event.event {
+				OnchainEvent::FundingSpendConfirmation { .. } => Some(event.txid),
+				_ => None,
+			})
+		});
+		// Claims from a counterparty commitment transaction pay to our wallet and fees from the
+		// claimed outputs. Otherwise, we assume our own commitment transaction is (or will be)
+		// confirmed, whose outputs are subject to our CSV delay and whose HTLC transactions have
+		// to be bumped via anchor outputs, if any.
+		let counterparty_commitment = funding_spend_txid.map_or(false, |txid| {
+			self.funding.counterparty_claimable_outpoints.contains_key(&txid)
+		});
+		let maturity_delay = if counterparty_commitment {
+			ANTI_REORG_DELAY
+		} else {
+			cmp::max(self.on_holder_tx_csv as u32, ANTI_REORG_DELAY)
+		};
+		let anchors = self.channel_type_features().supports_anchors_zero_fee_htlc_tx();
+
+		let mut maturing_outputs = self
+			.onchain_events_awaiting_threshold_conf
+			.iter()
+			.filter_map(|event| match event.event {
+				OnchainEvent::MaturingOutput { ref descriptor } => {
+					Some((event.confirmation_threshold(), descriptor))
+				},
+				_ => None,
+			})
+			.collect::<Vec<_>>();
+
+		let mut timeline = Vec::new();
+		for (balance, _) in self.get_claimable_balances_with_deadlines() {
+			let (claimable_height, spendable_height, spendable_outpoint) = match balance {
+				Balance::ClaimableAwaitingConfirmations {
+					amount_satoshis,
+					confirmation_height,
+					ref source,
+				} => {
+					let spendable_outpoint = maturing_outputs
+						.iter()
+						.position(|(height, descriptor)| {
+							*height == confirmation_height
+								&& descriptor.output().value.to_sat() == amount_satoshis
+						})
+						.map(|idx| maturing_outputs.swap_remove(idx).1.spendable_outpoint());
+					let spendable_height = if *source == BalanceSource::CoopClose {
+						confirmation_height
+					} else {
+						confirmation_height + ANTI_REORG_DELAY
+					};
+					(confirmation_height, spendable_height, spendable_outpoint)
+				},
+				Balance::MaybeTimeoutClaimableHTLC { claimable_height, .. } => {
+					let claimable_height = cmp::max(claimable_height, current_height);
+					(claimable_height, claimable_height + maturity_delay + ANTI_REORG_DELAY, None)
+				},
+				Balance::CounterpartyRevokedOutputClaimable { .. } => {
+					// Our claims of revoked outputs are not subject to any CSV delay.
+					(current_height, current_height + 2 * ANTI_REORG_DELAY, None)
+				},
+				Balance::ClaimableOnChannelClose { .. }
+				| Balance::ContentiousClaimable { .. }
+				| Balance::MaybePreimageClaimableHTLC { .. } => {
+					(current_height, current_height + maturity_delay + ANTI_REORG_DELAY, None)
+				},
+			};
+			let needs_exogenous_fees =
+				anchors && !counterparty_commitment && balance.needs_exogenous_fee_reserve();
+			timeline.push(ResolutionTimelineEntry {
+				balance,
+				claimable_height,
+				spendable_height,
+				needs_exogenous_fees,
+				spendable_outpoint,
+			});
+		}
+		timeline.sort_by_key(|entry| (entry.spendable_height, entry.claimable_height));
+		timeline
+	}
+}
+
+impl<Signer: EcdsaChannelSigner> ChannelMonitor<Signer> {
+	/// Gets the balances in this channel which are either claimable by us if we were to
+	/// force-close the channel now or which are claimable on-chain (possibly awaiting
+	/// confirmation).
+	///
+	/// Any balances in the channel which are available on-chain (excluding on-chain fees) are
+	/// included here until an [`Event::SpendableOutputs`] event has been generated for the
+	/// balance, or until our counterparty has claimed the balance and accrued several
+	/// confirmations on the claim transaction.
+	///
+	/// Note that for `ChannelMonitors` which track a channel which went on-chain with versions of
+	/// LDK prior to 0.0.111, not all or excess balances may be included.
+	///
+	/// See [`Balance`] for additional details on the types of claimable balances which
+	/// may be returned here and their meanings. The fees already paid out of external inputs to
+	/// claim them are returned by [`Self::get_exogenous_fees_paid`].
+	///
+	/// The balances are cached until the next monitor update or block is processed, see
+	/// [`Self::recompute_claimable_balances`].
+	pub fn get_claimable_balances(&self) -> Vec<Balance> {
+		let us = self.inner.lock().unwrap();
+		us.get_claimable_balances_with_deadlines().into_iter().map(|(balance, _)| balance).collect()
+	}
+
+	/// Recomputes the balances returned by [`Self::get_claimable_balances`] from scratch.
+	///
+	/// The balances are otherwise cached until the next monitor update or block is processed, such
+	/// that repeated calls to [`Self::get_claimable_balances`] in between are cheap. This refreshes
+	/// the cache and should never be necessary, but may be used to rule out stale balances.
+	pub fn recompute_claimable_balances(&self) -> Vec<Balance> {
+		let us = self.inner.lock().unwrap();
+		us.claimable_balances_cache.invalidate();
+		us.get_claimable_balances_with_deadlines().into_iter().map(|(balance, _)| balance).collect()
+	}
+
+	/// Gets the same balances as [`Self::get_claimable_balances`], along with the block height
+	/// by which we have to act to claim each balance and the number of confirmations it still
+	/// requires.
+	///
+	/// As both are derived from the current chain state, they are updated as blocks are
+	/// connected or disconnected and are preserved across serialization of the monitor.
+	///
+	/// See [`DetailedBalance`] for more details.
+	pub fn get_detailed_claimable_balances(&self) -> Vec<DetailedBalance> {
+		let us = self.inner.lock().unwrap();
+		let best_height = us.best_block.height;
+		us.get_claimable_balances_with_deadlines()
+			.into_iter()
+			.map(|(balance, deadline_height)| {
+				let confirmations_remaining = match balance {
+					Balance::ClaimableAwaitingConfirmations { confirmation_height, .. } => {
+						Some(confirmation_height.saturating_sub(best_height))
+					},
+					_ => None,
+				};
+				DetailedBalance { balance, deadline_height, confirmations_remaining }
+			})
+			.collect()
+	}
+
+	/// Gets the same balances as [`Self::get_claimable_balances`] as a timeline, along with the
+	/// estimated heights at which each can be claimed and at which it becomes spendable by our
+	/// on-chain wallet, sorted by the latter.
+	///
+	/// The heights account for the CSV delays on our outputs, the expiries of HTLCs and the
+	/// confirmations required before funds are handed over via [`Event::SpendableOutputs`]. Until
+	/// the channel is closed, they are estimated as if we force-closed it at `current_height`.
+	///
+	/// See [`ResolutionTimelineEntry`] for more details.
+	pub fn get_resolution_timeline(&self, current_height: u32) -> Vec<ResolutionTimelineEntry> {
+		self.inner.lock().unwrap().get_resolution_timeline(current_height)
+	}
+
+	/// Gets the time-sensitive deadlines tracked by this monitor, as the height at which each is
+	/// due, its kind, the channel ID and the amount at stake.
+	///
+	/// This includes the height at which we'll force-close the channel to resolve an HTLC on-chain,
+	/// as well as the heights at which our counterparty may claim HTLC outputs of a confirmed
+	/// commitment transaction which we have yet to claim with the preimage.
+	///
+	/// See [`get_htlc_deadlines`] to get the deadlines across all monitors and channels.
+	///
+	/// [`get_htlc_deadlines`]: crate::util::deadlines::get_htlc_deadlines
+	pub fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+		let us = self.inner.lock().unwrap();
+		let mut deadlines = Vec::new();
+		if let Some((height, htlc, _)) = us.next_htlc_force_close() {
+			let amount = Amount::from_sat(htlc.amount_msat / 1000);
+			deadlines.push((height, DeadlineKind::ChannelForceClose, us.channel_id, amount));
+		}
+		for (balance, _) in us.get_claimable_balances_with_deadlines() {
+			if let Balance::ContentiousClaimable { amount_satoshis, timeout_height, .. } = balance {
+				let amount = Amount::from_sat(amount_satoshis);
+				let kind = DeadlineKind::CounterpartyClaimTimeout;
+				deadlines.push((timeout_height, kind, us.channel_id, amount));
+			}
+		}
+		deadlines
+	}
+
+	/// Gets the anchor output of our commitment transaction, if we've broadcast one for a channel
+	/// with anchor outputs, along with whether we're spending it to bump the commitment
+	/// transaction's fee.
+	///
+	/// This allows explaining the value of the anchor output missing from
+	/// [`Self::get_claimable_balances`] once our commitment transaction confirms, or when we chose
+	/// not to spend the anchor output as doing so would cost more than it is worth.
+	///
+	/// Until our commitment transaction confirms, the claim status is only known once a claim for
+	/// it has been generated, i.e., upon force-closing, connecting a block, or calling
+	/// [`Self::rebroadcast_pending_claims`]. As the claim status is not persisted, it may be
+	/// unavailable after a restart until then.
+	pub fn get_holder_anchor_output(&self) -> Option<HolderAnchorOutput> {
+		self.inner.lock().unwrap().get_holder_anchor_output()
+	}
+
+	/// Sets the minimum feerate, in satoshis per 1000 weight units, our commitment transaction
+	/// must already pay for us to skip spending its anchor output when doing so at the target
+	/// feerate would cost more in fees than the anchor output is worth.
+	///
+	/// If our commitment transaction pays less than this feerate, we will always spend the anchor
+	/// output to bump its fee, as it may otherwise never confirm. If `None`, we always spend the
+	/// anchor output of a commitment transaction paying less than the target feerate, which is
+	/// the default.
+	///
+	/// The setting is persisted along with the rest of the monitor the next time it is written.
+	pub fn set_min_feerate_to_skip_uneconomical_anchor_claim(
+		&self, feerate_sat_per_1000_weight: Option<u32>,
+	) {
+		let mut inner = self.inner.lock().unwrap();
+		inner
+			.onchain_tx_handler
+			.set_min_feerate_to_skip_uneconomical_anchor_claim(feerate_sat_per_1000_weight);
+	}
+
+	/// Sets the maximum fees, in millionths of our balance, we are willing to pay to claim our
+	/// commitment transaction after force-closing an anchor channel.
+	///
+	/// If no HTLCs are pending on any commitment transaction of the channel, and the fees required
+	/// to spend our anchor output at the target feerate, as estimated by
+	/// [`estimate_force_close_cost`], exceed this fraction of our balance, we prefer to wait for
+	/// feerates to drop. Our commitment transaction is then only bumped to the lowest feerate
+	/// required to enter the mempool, as given by
+	/// [`ConfirmationTarget::MinAllowedAnchorChannelRemoteFee`], and an
+	/// [`Event::ForceCloseClaimDeferred`] is generated. The claim is re-evaluated whenever it
+	/// would otherwise be bumped, and never deferred while HTLCs are at risk.
+	///
+	/// If `None`, we always bump our commitment transaction to the target feerate, which is the
+	/// default.
+	///
+	/// The setting is persisted along with the rest of the monitor the next time it is written.
+	///
+	/// [`estimate_force_close_cost`]: crate::util::anchor_channel_reserves::estimate_force_close_cost
+	pub fn set_max_force_close_fee_proportional_millionths(
+		&self, max_fee_proportional_millionths: Option<u32>,
+	) {
+		let mut inner = self.inner.lock().unwrap();
+		inner
+			.onchain_tx_handler
+			.set_max_force_close_fee_proportional_millionths(max_fee_proportional_millionths);
+	}
+
+	/// Returns statistics on the claims we made on-chain to resolve the channel after it was
+	/// closed, such as the fees paid across our claim transactions.
+	///
+	/// The statistics are persisted along with the rest of the monitor.
+	pub fn get_onchain_resolution_stats(&self) -> OnchainResolutionStats {
+		self.inner.lock().unwrap().onchain_tx_handler.get_onchain_resolution_stats()
+	}
+
+	/// Gets the total fees paid out of external inputs by our claim transactions which are
+	/// currently confirmed, i.e., by the transactions spending our anchor outputs and by HTLC
+	/// transactions with anchor outputs, as provided via [`Self::provide_claim_fee_bump`].
+	///
+	/// Unlike [`OnchainResolutionStats::total_exogenous_fees`], this includes claim transactions
+	/// which have not yet reached [`ANTI_REORG_DELAY`] confirmations, and is thus recomputed as
+	/// such transactions are reorged out. Fee bumps for claim transactions which already
+	/// confirmed are only accounted for if provided before the claim is resolved.
+	pub fn get_exogenous_fees_paid(&self) -> Amount {
+		self.inner.lock().unwrap().onchain_tx_handler.get_exogenous_fees_paid()
+	}
+
+	/// Provides a transaction paying the fees of the pending claim `claim_id` using external
+	/// inputs, as built in response to a [`BumpTransactionEvent`], to be accounted for in
+	/// [`Self::get_onchain_resolution_stats`] once the claim is resolved.
+	///
+	/// `txid` is the txid of the transaction spending our anchor output for
+	/// [`BumpTransactionEvent::ChannelClose`], or of the HTLC transaction for
+	/// [`BumpTransactionEvent::HTLCResolution`]. `fees_paid` and `external_inputs_value` are the
+	/// fees paid by, and the total value of, the external inputs attached. Only the most recent
+	/// transaction of a claim is accounted for if our commitment transaction confirms, while for
+	/// other claims, only the transaction which actually confirmed is accounted for.
+	///
+	/// Returns whether the claim is still pending, as fee bumps for resolved claims are ignored.
+	/// The fee bump is persisted along with the rest of the monitor the next time it is written.
+	///
+	/// See [`ChainMonitor::provide_claim_fee_bump`] to provide fee bumps as recorded by a
+	/// [`ReserveAuditLogger`].
+	///
+	/// [`ChainMonitor::provide_claim_fee_bump`]: crate::chain::chainmonitor::ChainMonitor::provide_claim_fee_bump
+	/// [`ReserveAuditLogger`]: crate::util::anchor_channel_reserves::ReserveAuditLogger
+	pub fn provide_claim_fee_bump(
+		&self, claim_id: ClaimId, txid: Txid, fees_paid: Amount, external_inputs_value: Amount,
+		feerate_sat_per_1000_weight: u32,
+	) -> bool {
+		let mut inner = self.inner.lock().unwrap();
+		inner.onchain_tx_handler.provide_claim_fee_bump(
+			claim_id,
+			txid,
+			fees_paid.to_sat(),
+			external_inputs_value.to_sat(),
+			feerate_sat_per_1000_weight,
+		)
+	}
+
+	/// Returns the total value of the external inputs attached to the most recent transactions of
+	/// our pending claims, i.e., of the wallet funds currently committed to resolving the channel,
+	/// as provided via [`Self::provide_claim_fee_bump`]. Claims are no longer pending once their
+	/// transactions reach [`ANTI_REORG_DELAY`] confirmations.
+	pub(crate) fn get_pending_claim_external_inputs_value(&self) -> Amount {
+		self.inner.lock().unwrap().onchain_tx_handler.get_pending_claim_external_inputs_value()
+	}
+
+	/// Returns whether a spend of the funding output has been seen on-chain, i.e., whether the
+	/// channel is being resolved on-chain, even if the spend has not yet reached
+	/// [`ANTI_REORG_DELAY`] confirmations.
+	pub(crate) fn funding_spend_seen(&self) -> bool {
+		self.inner.lock().unwrap().funding_spend_seen()
+	}
+
+	/// Returns whether [`Self::get_claimable_balances`] would return any balances.
+	///
+	/// Until a spend of the funding output is seen on-chain, the channel's balance is always
+	/// claimable on channel close, allowing us to skip computing the balances.
+	pub(crate) fn has_claimable_balances(&self) -> bool {
+		let us = self.inner.lock().unwrap();
+		if !us.funding_spend_seen() {
+			return true;
+		}
+		!us.get_claimable_balances_with_deadlines().is_empty()
+	}
+
+	/// Returns whether resolving the channel on-chain may still require fees paid from outside of
+	/// the channel, e.g., to bump a commitment or HTLC transaction via its anchor output, and thus
+	/// whether the channel still needs to be covered by the anchor channel reserve.
+	///
+	/// This is the case until a spend of the funding output appears on-chain, as the channel may
+	/// still have to be force-closed, and afterwards as long as any HTLCs remain unresolved.
+	/// Balances which are only [`Balance::ClaimableAwaitingConfirmations`], e.g., of a confirmed
+	/// commitment or closing transaction, or [`Balance::CounterpartyRevokedOutputClaimable`], whose
+	/// claims pay their fees from the claimed outputs, don't require any further fees. However, the
+	/// confirmed transactions of claims which may require external funds, e.g., our commitment
+	/// transaction, may still be reorged until they reach [`ANTI_REORG_DELAY`] confirmations and
+	/// thus require the reserve until then, see [`Self::reserve_release_height`].
+	pub fn needs_exogenous_fee_reserve(&self) -> bool {
+		let us = self.inner.lock().unwrap();
+		us.reserve_release_height().map_or(true, |height| height > us.best_block.height)
+	}
+
+	/// Returns the block height at which resolving the channel on-chain will no longer require
+	/// fees paid from outside of the channel, allowing wallets to display when the anchor channel
+	/// reserve for a closed channel is released.
+	///
+	/// Returns `None` as long as the funding output is unspent or any balance may still require
+	/// such fees, see [`Self::needs_exogenous_fee_reserve`]. Otherwise, returns the height at which
+	/// the confirmed transactions of all claims which may require external funds reach
+	/// [`ANTI_REORG_DELAY`] confirmations. A height at or below the current best block height indicates that the
+	/// reserve has already been released.
+	///
+	/// The returned height changes as blocks are connected and disconnected, e.g., it reverts to
+	/// `None` if a claim is reorged out of the chain.
+	pub fn reserve_release_height(&self) -> Option<u32> {
+		self.inner.lock().unwrap().reserve_release_height()
+	}
+
+	/// Gets the amounts, in millisatoshis, of the non-dust inbound HTLCs pending in our latest
+	/// holder commitment transaction, i.e. the HTLCs we would have to resolve on-chain if we were
+	/// to force-close the channel now.
+	pub(crate) fn get_pending_inbound_htlc_amounts_msat(&self) -> Vec<u64> {
+		let us = self.inner.lock().unwrap();
+		us.funding
+			.current_holder_commitment_tx
+			.nondust_htlcs()
+			.iter()
+			.filter(|htlc| !htlc.offered)
+			.map(|htlc| htlc.amount_msat)
+			.collect()
+	}
+
 	/// Gets the set of outbound HTLCs which can be (or have been) resolved by this
 	/// `ChannelMonitor`. This is used to determine if an HTLC was removed from the channel prior
 	/// to the `ChannelManager` having been persisted.
@@ -3513,6 +4366,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 		    F::Target: FeeEstimator,
 		    L::Target: Logger,
 	{
+		self.claimable_balances_cache.invalidate();
 		self.payment_preimages.entry(payment_hash.clone())
 			.and_modify(|(_, payment_infos)| {
 				if let Some(payment_info) = payment_info {
@@ -3606,6 +4460,36 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 		}
 	}
 
+	/// Generates an [`Event::AnchorChannelResolutionStarted`] for the commitment transaction with
+	/// the given `commitment_txid`, unless the channel has no anchor outputs or we already did so.
+	fn maybe_generate_resolution_started_event(
+		&mut self, commitment_txid: Txid, holder_force_closed: bool,
+	) {
+		if self.resolution_started_event_generated
+			|| !is_anchor_channel_type(self.channel_type_features())
+		{
+			return;
+		}
+		let num_pending_htlc_outputs = if holder_force_closed {
+			let holder_commitment_tx = core::iter::once(&self.funding.current_holder_commitment_tx)
+				.chain(self.funding.prev_holder_commitment_tx.as_ref())
+				.find(|commitment_tx| commitment_tx.trust().txid() == commitment_txid);
+			holder_commitment_tx.map_or(0, |commitment_tx| commitment_tx.nondust_htlcs().len())
+		} else {
+			self.funding.counterparty_claimable_outpoints.get(&commitment_txid).map_or(0, |htlcs| {
+				htlcs.iter().filter(|(htlc, _)| htlc.transaction_output_index.is_some()).count()
+			})
+		};
+		self.pending_events.push(Event::AnchorChannelResolutionStarted {
+			channel_id: self.channel_id,
+			counterparty_node_id: self.counterparty_node_id,
+			commitment_txid,
+			num_pending_htlc_outputs: num_pending_htlc_outputs as u32,
+			holder_force_closed,
+		});
+		self.resolution_started_event_generated = true;
+	}
+
 	#[rustfmt::skip]
 	fn generate_claimable_outpoints_and_watch_outputs(&mut self, reason: ClosureReason) -> (Vec<PackageTemplate>, Vec<TransactionOutputs>) {
 		let holder_commitment_tx = &self.funding.current_holder_commitment_tx;
@@ -3626,6 +4510,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 			channel_id: self.channel_id,
 		};
 		self.pending_monitor_events.push(event);
+		self.maybe_generate_resolution_started_event(holder_commitment_tx.trust().txid(), true);
 
 		// Although we aren't signing the transaction directly here, the transaction will be signed
 		// in the claim that is queued to OnchainTxHandler. We set holder_tx_signed here to reject
@@ -3664,11 +4549,29 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 			broadcasted_latest_txn: Some(true),
 			message: "ChannelMonitor-initiated commitment transaction broadcast".to_owned(),
 		};
+		self.queue_holder_commitment_txn_for_broadcast(reason, broadcaster, fee_estimator, logger);
+	}
+
+	fn queue_holder_commitment_txn_for_broadcast<B: Deref, F: Deref, L: Deref>(
+		&mut self, reason: ClosureReason, broadcaster: &B,
+		fee_estimator: &LowerBoundedFeeEstimator<F>, logger: &WithChannelMonitor<L>,
+	) where
+		B::Target: BroadcasterInterface,
+		F::Target: FeeEstimator,
+		L::Target: Logger,
+	{
+		self.claimable_balances_cache.invalidate();
 		let (claimable_outpoints, _) = self.generate_claimable_outpoints_and_watch_outputs(reason);
 		let conf_target = self.closure_conf_target();
 		self.onchain_tx_handler.update_claims_view_from_requests(
-			claimable_outpoints, self.best_block.height, self.best_block.height, broadcaster,
-			conf_target, &self.destination_script, fee_estimator, logger,
+			claimable_outpoints,
+			self.best_block.height,
+			self.best_block.height,
+			broadcaster,
+			conf_target,
+			&self.destination_script,
+			fee_estimator,
+			logger,
 		);
 	}
 
@@ -4096,7 +4999,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 					}));
 				},
 				ClaimEvent::BumpHTLC {
-					target_feerate_sat_per_1000_weight, htlcs, tx_lock_time,
+					target_feerate_sat_per_1000_weight, htlcs, tx_lock_time, blocks_until_expiry,
 				} => {
 					let channel_id = self.channel_id;
 					let counterparty_node_id = self.counterparty_node_id;
@@ -4107,10 +5010,25 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 						target_feerate_sat_per_1000_weight,
 						htlc_descriptors: htlcs,
 						tx_lock_time,
+						blocks_until_expiry,
 					}));
 				}
 			}
 		}
+		if let Some((commitment_txid, status)) = self.onchain_tx_handler.take_pending_claim_deferral() {
+			if let AnchorClaimStatus::Deferred {
+				target_feerate_sat_per_1000_weight, claim_fee_satoshis, balance_satoshis, ..
+			} = status {
+				ret.push(Event::ForceCloseClaimDeferred {
+					channel_id: self.channel_id,
+					counterparty_node_id: self.counterparty_node_id,
+					commitment_txid,
+					target_feerate_sat_per_1000_weight,
+					claim_fee_satoshis,
+					balance_satoshis,
+				});
+			}
+		}
 		ret
 	}
 
@@ -4894,6 +5812,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 
 							claimable_outpoints.append(&mut new_outpoints);
 							balance_spendable_csv = Some(self.on_holder_tx_csv);
+							self.maybe_generate_resolution_started_event(txid, true);
 						} else {
 							let mut new_watch_outputs = Vec::new();
 							for (idx, outp) in tx.output.iter().enumerate() {
@@ -4904,6 +5823,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 							let (mut new_outpoints, counterparty_output_idx_sats) =
 								self.check_spend_counterparty_transaction(&tx, height, &block_hash, &logger);
 							commitment_tx_to_counterparty_output = counterparty_output_idx_sats;
+							self.maybe_generate_resolution_started_event(txid, false);
 
 							claimable_outpoints.append(&mut new_outpoints);
 						}
@@ -4983,6 +5903,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 		F::Target: FeeEstimator,
 		L::Target: Logger,
 	{
+		self.claimable_balances_cache.invalidate();
 		log_trace!(logger, "Processing {} matched transactions for block at height {}.", txn_matched.len(), conf_height);
 		debug_assert!(self.best_block.height >= conf_height);
 
@@ -5171,6 +6092,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 		F::Target: FeeEstimator,
 		L::Target: Logger,
 	{
+		self.claimable_balances_cache.invalidate();
 		log_trace!(logger, "Block {} at height {} disconnected", header.block_hash(), height);
 
 		//We may discard:
@@ -5199,6 +6121,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 		F::Target: FeeEstimator,
 		L::Target: Logger,
 	{
+		self.claimable_balances_cache.invalidate();
 		let mut removed_height = None;
 		for entry in self.onchain_events_awaiting_threshold_conf.iter() {
 			if entry.txid == *txid {
@@ -5281,6 +6204,20 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 	fn should_broadcast_holder_commitment_txn<L: Deref>(
 		&self, logger: &WithChannelMonitor<L>
 	) -> bool where L::Target: Logger {
+		if let Some((force_close_height, htlc, htlc_outbound)) = self.next_htlc_force_close() {
+			if force_close_height <= self.best_block.height {
+				log_info!(logger, "Force-closing channel due to {} HTLC timeout, HTLC expiry is {}", if htlc_outbound { "outbound" } else { "inbound "}, htlc.cltv_expiry);
+				return true;
+			}
+		}
+		false
+	}
+
+	/// Returns the earliest height at which we'll broadcast our holder commitment transaction to
+	/// resolve an HTLC on-chain, along with the HTLC and whether it is outbound, or `None` if no
+	/// HTLC requires doing so.
+	#[rustfmt::skip]
+	fn next_htlc_force_close(&self) -> Option<(u32, &HTLCOutputInCommitment, bool)> {
 		// There's no need to broadcast our commitment transaction if we've seen one confirmed (even
 		// with 1 confirmation) as it'll be rejected as duplicate/conflicting.
 		if self.funding_spend_confirmed.is_some() ||
@@ -5289,7 +6226,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 				_ => false,
 			}).is_some()
 		{
-			return false;
+			return None;
 		}
 		// We need to consider all HTLCs which are:
 		//  * in any unrevoked counterparty commitment transaction, as they could broadcast said
@@ -5301,10 +6238,10 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 		// to the source, and if we don't fail the channel we will have to ensure that the next
 		// updates that peer sends us are update_fails, failing the channel if not. It's probably
 		// easier to just fail the channel as this case should be rare enough anyway.
-		let height = self.best_block.height;
+		let mut next_force_close: Option<(u32, &HTLCOutputInCommitment, bool)> = None;
 		macro_rules! scan_commitment {
 			($htlcs: expr, $holder_tx: expr) => {
-				for ref htlc in $htlcs {
+				for htlc in $htlcs {
 					// For inbound HTLCs which we know the preimage for, we have to ensure we hit the
 					// chain with enough room to claim the HTLC without our counterparty being able to
 					// time out the HTLC first.
@@ -5317,10 +6254,15 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 					// we give ourselves a few blocks of headroom after expiration before going
 					// on-chain for an expired HTLC.
 					let htlc_outbound = $holder_tx == htlc.offered;
-					if ( htlc_outbound && htlc.cltv_expiry + LATENCY_GRACE_PERIOD_BLOCKS <= height) ||
-					   (!htlc_outbound && htlc.cltv_expiry <= height + CLTV_CLAIM_BUFFER && self.payment_preimages.contains_key(&htlc.payment_hash)) {
-						log_info!(logger, "Force-closing channel due to {} HTLC timeout, HTLC expiry is {}", if htlc_outbound { "outbound" } else { "inbound "}, htlc.cltv_expiry);
-						return true;
+					let force_close_height = if htlc_outbound {
+						htlc.cltv_expiry + LATENCY_GRACE_PERIOD_BLOCKS
+					} else if self.payment_preimages.contains_key(&htlc.payment_hash) {
+						htlc.cltv_expiry.saturating_sub(CLTV_CLAIM_BUFFER)
+					} else {
+						continue;
+					};
+					if next_force_close.map_or(true, |(height, _, _)| force_close_height < height) {
+						next_force_close = Some((force_close_height, htlc, htlc_outbound));
 					}
 				}
 			}
@@ -5339,7 +6281,7 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 			}
 		}
 
-		false
+		next_force_close
 	}
 
 	/// Check if any transaction broadcasted is resolving HTLC output by a success or timeout on a holder
@@ -5616,6 +6558,38 @@ impl<Signer: EcdsaChannelSigner> ChannelMonitorImpl<Signer> {
 	fn channel_type_features(&self) -> &ChannelTypeFeatures {
 		&self.funding.channel_parameters.channel_type_features
 	}
+
+	fn get_holder_anchor_output(&self) -> Option<HolderAnchorOutput> {
+		if !self.channel_type_features().supports_anchors_zero_fee_htlc_tx() {
+			return None;
+		}
+		let confirmed_txid = self.funding_spend_confirmed.or_else(|| {
+			self.onchain_events_awaiting_threshold_conf.iter().find_map(|event| match event.event {
+				OnchainEvent::FundingSpendConfirmation { .. } => Some(event.txid),
+				_ => None,
+			})
+		});
+		if let Some(txid) = confirmed_txid {
+			let current_holder_txid = self.funding.current_holder_commitment_tx.trust().txid();
+			let prev_holder_txid =
+				self.funding.prev_holder_commitment_tx.as_ref().map(|tx| tx.trust().txid());
+			if txid != current_holder_txid && Some(txid) != prev_holder_txid {
+				return None;
+			}
+			return Some(HolderAnchorOutput {
+				commitment_txid: txid,
+				amount_satoshis: ANCHOR_OUTPUT_VALUE_SATOSHI,
+				claim_status: AnchorClaimStatus::CommitmentConfirmed,
+			});
+		}
+		self.onchain_tx_handler.holder_anchor_claim_status().map(
+			|(commitment_txid, claim_status)| HolderAnchorOutput {
+				commitment_txid,
+				amount_satoshis: ANCHOR_OUTPUT_VALUE_SATOSHI,
+				claim_status,
+			},
+		)
+	}
 }
 
 impl<Signer: EcdsaChannelSigner, T: Deref, F: Deref, L: Deref> chain::Listen
@@ -5870,6 +6844,7 @@ impl<'a, 'b, ES: EntropySource, SP: SignerProvider> ReadableArgs<(&'a ES, &'b SP
 		let mut first_negotiated_funding_txo = RequiredWrapper(None);
 		let mut channel_parameters = None;
 		let mut pending_funding = None;
+		let mut resolution_started_event_generated = None;
 		read_tlv_fields!(reader, {
 			(1, funding_spend_confirmed, option),
 			(3, htlcs_resolved_on_chain, optional_vec),
@@ -5888,7 +6863,12 @@ impl<'a, 'b, ES: EntropySource, SP: SignerProvider> ReadableArgs<(&'a ES, &'b SP
 			(29, initial_counterparty_commitment_tx, option),
 			(31, channel_parameters, (option: ReadableArgs, None)),
 			(32, pending_funding, optional_vec),
+			(33, resolution_started_event_generated, option),
 		});
+		// Monitors written prior to 0.2 may already be resolving the channel on-chain, in which case
+		// we don't want to generate an `AnchorChannelResolutionStarted` event for it after the fact.
+		let resolution_started_event_generated = resolution_started_event_generated
+			.unwrap_or(funding_spend_seen.unwrap() || holder_tx_signed);
 		if let Some(payment_preimages_with_info) = payment_preimages_with_info {
 			if payment_preimages_with_info.len() != payment_preimages.len() {
 				return Err(DecodeError::InvalidValue);
@@ -6053,10 +7033,13 @@ impl<'a, 'b, ES: EntropySource, SP: SignerProvider> ReadableArgs<(&'a ES, &'b SP
 			initial_counterparty_commitment_info,
 			initial_counterparty_commitment_tx,
 			balances_empty_height,
+			resolution_started_event_generated,
 			failed_back_htlc_ids: new_hash_set(),
 
 			current_holder_htlc_data,
 			prev_holder_htlc_data,
+
+			claimable_balances_cache: ClaimableBalancesCache::default(),
 		})))
 	}
 }
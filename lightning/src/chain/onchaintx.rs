@@ -22,18 +22,23 @@ use bitcoin::secp256k1;
 use bitcoin::secp256k1::{ecdsa::Signature, Secp256k1};
 use bitcoin::transaction::OutPoint as BitcoinOutPoint;
 use bitcoin::transaction::Transaction;
+use bitcoin::FeeRate;
 
 use crate::chain::chaininterface::{compute_feerate_sat_per_1000_weight, ConfirmationTarget};
 use crate::chain::chaininterface::{BroadcasterInterface, FeeEstimator, LowerBoundedFeeEstimator};
-use crate::chain::channelmonitor::ANTI_REORG_DELAY;
+use crate::chain::channelmonitor::{AnchorClaimStatus, OnchainResolutionStats, ANTI_REORG_DELAY};
 use crate::chain::package::{PackageSolvingData, PackageTemplate};
 use crate::chain::transaction::MaybeSignedTransaction;
 use crate::chain::ClaimId;
+use crate::events::bump_transaction::{BASE_INPUT_WEIGHT, EMPTY_SCRIPT_SIG_WEIGHT};
 use crate::ln::chan_utils::{
 	self, ChannelTransactionParameters, HTLCOutputInCommitment, HolderCommitmentTransaction,
+	ANCHOR_INPUT_WITNESS_WEIGHT,
 };
+use crate::ln::channel::ANCHOR_OUTPUT_VALUE_SATOSHI;
 use crate::ln::msgs::DecodeError;
 use crate::sign::{ecdsa::EcdsaChannelSigner, EntropySource, HTLCDescriptor, SignerProvider};
+use crate::util::anchor_channel_reserves::{estimate_force_close_cost, ForceCloseTransactionType};
 use crate::util::logger::Logger;
 use crate::util::ser::{
 	MaybeReadable, Readable, ReadableArgs, UpgradableRequired, Writeable, Writer,
@@ -127,6 +132,27 @@ impl_writeable_tlv_based_enum_upgradable!(OnchainEvent,
 	},
 );
 
+/// A transaction paying the fees of a pending claim, tracked until the claim is resolved to account
+/// for the transaction which eventually confirmed in the [`OnchainResolutionStats`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct ClaimAttempt {
+	claim_id: ClaimId,
+	/// The txid of the transaction, or of the transaction spending our anchor output for claims of
+	/// our commitment transaction.
+	txid: Txid,
+	fee_satoshis: u64,
+	external_inputs_value_satoshis: u64,
+	feerate_sat_per_1000_weight: u32,
+}
+
+impl_writeable_tlv_based!(ClaimAttempt, {
+	(0, claim_id, required),
+	(2, txid, required),
+	(4, fee_satoshis, required),
+	(6, external_inputs_value_satoshis, required),
+	(8, feerate_sat_per_1000_weight, required),
+});
+
 impl Readable for Option<Vec<Option<(usize, Signature)>>> {
 	#[rustfmt::skip]
 	fn read<R: io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
@@ -192,6 +218,7 @@ pub(crate) enum ClaimEvent {
 		target_feerate_sat_per_1000_weight: u32,
 		htlcs: Vec<HTLCDescriptor>,
 		tx_lock_time: LockTime,
+		blocks_until_expiry: u32,
 	},
 }
 
@@ -276,13 +303,40 @@ pub struct OnchainTxHandler<ChannelSigner: EcdsaChannelSigner> {
 
 	onchain_events_awaiting_threshold_conf: Vec<OnchainEventEntry>,
 
+	// The minimum feerate our commitment transaction must already pay for us to skip spending its
+	// anchor output when doing so costs more than the anchor output is worth. If `None`, we always
+	// spend the anchor output of a commitment transaction paying less than the target feerate.
+	min_feerate_to_skip_uneconomical_anchor_claim: Option<u32>,
+
+	// The maximum fees, in millionths of our balance, we are willing to pay to claim our
+	// commitment transaction when no HTLCs are at risk. If `None`, we always bump our commitment
+	// transaction to the target feerate.
+	max_force_close_fee_proportional_millionths: Option<u32>,
+
+	// Whether the claim of our commitment transaction was deferred since the last call to
+	// `take_pending_claim_deferral`. Not persisted, as the claim is regenerated after restarts.
+	claim_deferral_pending: bool,
+
+	// The txid of our commitment transaction and whether we're spending its anchor output, as
+	// determined by the last claim generated for it. Not persisted, as it is regenerated along
+	// with the claim.
+	holder_anchor_claim_status: Option<(Txid, AnchorClaimStatus)>,
+
+	// The transactions paying the fees of our pending claims, whether built by us or funded
+	// externally, as provided via `provide_claim_fee_bump`. Folded into `resolution_stats` once
+	// the claim is resolved.
+	claim_attempts: Vec<ClaimAttempt>,
+
+	resolution_stats: OnchainResolutionStats,
+
 	pub(super) secp_ctx: Secp256k1<secp256k1::All>,
 }
 
 impl<ChannelSigner: EcdsaChannelSigner> PartialEq for OnchainTxHandler<ChannelSigner> {
 	#[rustfmt::skip]
 	fn eq(&self, other: &Self) -> bool {
-		// `signer`, `secp_ctx`, and `pending_claim_events` are excluded on purpose.
+		// `signer`, `secp_ctx`, `pending_claim_events`, `claim_deferral_pending`, and
+		// `holder_anchor_claim_status` are excluded on purpose.
 		self.channel_value_satoshis == other.channel_value_satoshis &&
 			self.channel_keys_id == other.channel_keys_id &&
 			self.destination_script == other.destination_script &&
@@ -292,7 +346,11 @@ impl<ChannelSigner: EcdsaChannelSigner> PartialEq for OnchainTxHandler<ChannelSi
 			self.pending_claim_requests == other.pending_claim_requests &&
 			self.claimable_outpoints == other.claimable_outpoints &&
 			self.locktimed_packages == other.locktimed_packages &&
-			self.onchain_events_awaiting_threshold_conf == other.onchain_events_awaiting_threshold_conf
+			self.onchain_events_awaiting_threshold_conf == other.onchain_events_awaiting_threshold_conf &&
+			self.min_feerate_to_skip_uneconomical_anchor_claim == other.min_feerate_to_skip_uneconomical_anchor_claim &&
+			self.max_force_close_fee_proportional_millionths == other.max_force_close_fee_proportional_millionths &&
+			self.claim_attempts == other.claim_attempts &&
+			self.resolution_stats == other.resolution_stats
 	}
 }
 
@@ -343,7 +401,12 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 			entry.write(writer)?;
 		}
 
-		write_tlv_fields!(writer, {});
+		write_tlv_fields!(writer, {
+			(1, self.min_feerate_to_skip_uneconomical_anchor_claim, option),
+			(3, self.max_force_close_fee_proportional_millionths, option),
+			(5, self.claim_attempts, optional_vec),
+			(7, self.resolution_stats, required),
+		});
 		Ok(())
 	}
 }
@@ -419,7 +482,16 @@ impl<'a, 'b, ES: EntropySource, SP: SignerProvider> ReadableArgs<(&'a ES, &'b SP
 			}
 		}
 
-		read_tlv_fields!(reader, {});
+		let mut min_feerate_to_skip_uneconomical_anchor_claim = None;
+		let mut max_force_close_fee_proportional_millionths = None;
+		let mut claim_attempts = Some(Vec::new());
+		let mut resolution_stats = None;
+		read_tlv_fields!(reader, {
+			(1, min_feerate_to_skip_uneconomical_anchor_claim, option),
+			(3, max_force_close_fee_proportional_millionths, option),
+			(5, claim_attempts, optional_vec),
+			(7, resolution_stats, option),
+		});
 
 		let mut secp_ctx = Secp256k1::new();
 		secp_ctx.seeded_randomize(&entropy_source.get_secure_random_bytes());
@@ -436,6 +508,12 @@ impl<'a, 'b, ES: EntropySource, SP: SignerProvider> ReadableArgs<(&'a ES, &'b SP
 			locktimed_packages,
 			pending_claim_requests,
 			onchain_events_awaiting_threshold_conf,
+			min_feerate_to_skip_uneconomical_anchor_claim,
+			max_force_close_fee_proportional_millionths,
+			claim_deferral_pending: false,
+			holder_anchor_claim_status: None,
+			claim_attempts: claim_attempts.unwrap(),
+			resolution_stats: resolution_stats.unwrap_or_default(),
 			pending_claim_events: Vec::new(),
 			secp_ctx,
 		})
@@ -460,6 +538,12 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 			claimable_outpoints: new_hash_map(),
 			locktimed_packages: BTreeMap::new(),
 			onchain_events_awaiting_threshold_conf: Vec::new(),
+			min_feerate_to_skip_uneconomical_anchor_claim: None,
+			max_force_close_fee_proportional_millionths: None,
+			claim_deferral_pending: false,
+			holder_anchor_claim_status: None,
+			claim_attempts: Vec::new(),
+			resolution_stats: OnchainResolutionStats::default(),
 			pending_claim_events: Vec::new(),
 			secp_ctx,
 		}
@@ -473,6 +557,196 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 		&self.holder_commitment
 	}
 
+	pub(crate) fn set_min_feerate_to_skip_uneconomical_anchor_claim(
+		&mut self, feerate_sat_per_1000_weight: Option<u32>,
+	) {
+		self.min_feerate_to_skip_uneconomical_anchor_claim = feerate_sat_per_1000_weight;
+	}
+
+	pub(crate) fn set_max_force_close_fee_proportional_millionths(
+		&mut self, max_fee_proportional_millionths: Option<u32>,
+	) {
+		self.max_force_close_fee_proportional_millionths = max_fee_proportional_millionths;
+	}
+
+	pub(crate) fn holder_anchor_claim_status(&self) -> Option<(Txid, AnchorClaimStatus)> {
+		self.holder_anchor_claim_status
+	}
+
+	/// Returns the status of the claim of our commitment transaction if it was newly deferred since
+	/// the last call.
+	pub(crate) fn take_pending_claim_deferral(&mut self) -> Option<(Txid, AnchorClaimStatus)> {
+		if !replace(&mut self.claim_deferral_pending, false) {
+			return None;
+		}
+		self.holder_anchor_claim_status
+			.filter(|(_, status)| matches!(status, AnchorClaimStatus::Deferred { .. }))
+	}
+
+	pub(crate) fn get_onchain_resolution_stats(&self) -> OnchainResolutionStats {
+		self.resolution_stats.clone()
+	}
+
+	/// Tracks a transaction paying the fees of the pending claim `claim_id`, which was funded by
+	/// external inputs, e.g., as a result of a [`ClaimEvent`]. Returns whether the claim is still
+	/// pending.
+	pub(crate) fn provide_claim_fee_bump(
+		&mut self, claim_id: ClaimId, txid: Txid, fee_satoshis: u64,
+		external_inputs_value_satoshis: u64, feerate_sat_per_1000_weight: u32,
+	) -> bool {
+		if !self.pending_claim_requests.contains_key(&claim_id) {
+			return false;
+		}
+		self.track_claim_attempt(ClaimAttempt {
+			claim_id,
+			txid,
+			fee_satoshis,
+			external_inputs_value_satoshis,
+			feerate_sat_per_1000_weight,
+		});
+		true
+	}
+
+	fn track_claim_attempt(&mut self, attempt: ClaimAttempt) {
+		self.claim_attempts.retain(|existing| existing.txid != attempt.txid);
+		self.claim_attempts.push(attempt);
+	}
+
+	/// Tracks a claim transaction built by us, paying its fees out of the outputs it claims.
+	fn track_claim_tx(&mut self, claim_id: ClaimId, request: &PackageTemplate, tx: &Transaction) {
+		// Our commitment transaction is not a claim transaction in itself. The fees of any
+		// transaction spending its anchor output are provided via `provide_claim_fee_bump`.
+		let is_commitment_claim = request
+			.inputs()
+			.any(|input| matches!(input, PackageSolvingData::HolderFundingOutput(_)));
+		if is_commitment_claim {
+			return;
+		}
+		let output_value = tx.output.iter().map(|output| output.value.to_sat()).sum::<u64>();
+		let fee_satoshis = request.package_amount().saturating_sub(output_value);
+		self.track_claim_attempt(ClaimAttempt {
+			claim_id,
+			txid: tx.compute_txid(),
+			fee_satoshis,
+			external_inputs_value_satoshis: 0,
+			feerate_sat_per_1000_weight: compute_feerate_sat_per_1000_weight(
+				fee_satoshis,
+				tx.weight().to_wu(),
+			),
+		});
+	}
+
+	/// Returns the attempt of the claim `claim_id` which confirmed in `txid` out of `attempts`.
+	fn confirmed_claim_attempt<'a, I: Iterator<Item = &'a ClaimAttempt> + Clone>(
+		attempts: I, claim_id: ClaimId, txid: Txid,
+	) -> Option<&'a ClaimAttempt> {
+		let attempts = attempts.filter(move |attempt| attempt.claim_id == claim_id);
+		// Claims of our commitment transaction are resolved by the commitment transaction itself,
+		// as we don't track the spend of its anchor output. As any later transaction spending the
+		// anchor output replaces the earlier ones, we assume the most recent one confirmed.
+		let is_own_commitment = txid.to_byte_array() == claim_id.0;
+		attempts.clone().find(|attempt| attempt.txid == txid).or_else(|| {
+			if is_own_commitment {
+				attempts.last()
+			} else {
+				None
+			}
+		})
+	}
+
+	/// Returns the fees paid by the external inputs of our claim transactions which confirmed,
+	/// including those which didn't reach [`ANTI_REORG_DELAY`] confirmations yet.
+	///
+	/// As the claims awaiting [`ANTI_REORG_DELAY`] confirmations are dropped upon reorgs, the fees
+	/// of claim transactions which are no longer confirmed are not included.
+	pub(crate) fn get_exogenous_fees_paid(&self) -> Amount {
+		let confirmed_exogenous_fees = self
+			.onchain_events_awaiting_threshold_conf
+			.iter()
+			.filter_map(|entry| match entry.event {
+				OnchainEvent::Claim { claim_id } => {
+					Self::confirmed_claim_attempt(self.claim_attempts.iter(), claim_id, entry.txid)
+				},
+				OnchainEvent::ContentiousOutpoint { .. } => None,
+			})
+			.filter(|attempt| attempt.external_inputs_value_satoshis > 0)
+			.map(|attempt| Amount::from_sat(attempt.fee_satoshis))
+			.sum::<Amount>();
+		self.resolution_stats.total_exogenous_fees + confirmed_exogenous_fees
+	}
+
+	/// Returns the total value of the external inputs attached to the most recent transaction of
+	/// each claim which has not yet been resolved, as provided via [`Self::provide_claim_fee_bump`].
+	pub(crate) fn get_pending_claim_external_inputs_value(&self) -> Amount {
+		// Each attempt of a claim replaces the previous ones, which thus no longer spend their inputs.
+		let mut latest_attempts = new_hash_map();
+		for attempt in self.claim_attempts.iter() {
+			latest_attempts.insert(attempt.claim_id, attempt.external_inputs_value_satoshis);
+		}
+		Amount::from_sat(latest_attempts.values().sum())
+	}
+
+	/// Returns the height at which the confirmed transactions of all claims which require external
+	/// funding reach [`ANTI_REORG_DELAY`] confirmations, if any such claims are still awaiting it.
+	///
+	/// Until then, the transactions may be reorged out of the chain and have to be bumped again.
+	pub(crate) fn get_externally_funded_claims_threshold_height(&self) -> Option<u32> {
+		self.onchain_events_awaiting_threshold_conf
+			.iter()
+			.filter(|entry| match entry.event {
+				OnchainEvent::Claim { claim_id } => self
+					.pending_claim_requests
+					.get(&claim_id)
+					.map_or(false, |request| request.requires_external_funding()),
+				OnchainEvent::ContentiousOutpoint { ref package } => {
+					package.requires_external_funding()
+				},
+			})
+			.map(|entry| entry.confirmation_threshold())
+			.max()
+	}
+
+	/// Accounts for the attempt of the claim `claim_id` which confirmed in `txid` in our
+	/// [`OnchainResolutionStats`], dropping all attempts of the claim.
+	fn resolve_claim_attempts(&mut self, claim_id: ClaimId, txid: Txid, request: &PackageTemplate) {
+		let mut attempts = Vec::new();
+		self.claim_attempts.retain(|attempt| {
+			if attempt.claim_id == claim_id {
+				attempts.push(attempt.clone());
+				false
+			} else {
+				true
+			}
+		});
+		let confirmed_attempt = Self::confirmed_claim_attempt(attempts.iter(), claim_id, txid);
+		if let Some(attempt) = confirmed_attempt {
+			let num_htlc_outputs = request
+				.inputs()
+				.filter(|input| match input {
+					PackageSolvingData::RevokedHTLCOutput(_)
+					| PackageSolvingData::CounterpartyOfferedHTLCOutput(_)
+					| PackageSolvingData::CounterpartyReceivedHTLCOutput(_)
+					| PackageSolvingData::HolderHTLCOutput(_) => true,
+					PackageSolvingData::RevokedOutput(_)
+					| PackageSolvingData::HolderFundingOutput(_) => false,
+				})
+				.count();
+			let stats = &mut self.resolution_stats;
+			stats.total_claim_fees += Amount::from_sat(attempt.fee_satoshis);
+			stats.total_external_inputs_value +=
+				Amount::from_sat(attempt.external_inputs_value_satoshis);
+			if attempt.external_inputs_value_satoshis > 0 {
+				stats.total_exogenous_fees += Amount::from_sat(attempt.fee_satoshis);
+			}
+			stats.num_confirmed_claims += 1;
+			stats.num_htlc_outputs_claimed += num_htlc_outputs as u32;
+			stats.max_claim_feerate_sat_per_1000_weight = cmp::max(
+				stats.max_claim_feerate_sat_per_1000_weight,
+				attempt.feerate_sat_per_1000_weight,
+			);
+		}
+	}
+
 	pub(crate) fn get_and_clear_pending_claim_events(&mut self) -> Vec<(ClaimId, ClaimEvent)> {
 		let mut events = Vec::new();
 		swap(&mut events, &mut self.pending_claim_events);
@@ -516,6 +790,7 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 							if tx.is_fully_signed() {
 								let log_start = if feerate_was_bumped { "Broadcasting RBF-bumped" } else { "Rebroadcasting" };
 								log_info!(logger, "{} onchain {}", log_start, log_tx!(tx.0));
+								self.track_claim_tx(claim_id, &request, &tx.0);
 								broadcaster.broadcast_transactions(&[&tx.0]);
 							} else {
 								log_info!(logger, "Waiting for signature of unsigned onchain transaction {}", tx.0.compute_txid());
@@ -615,6 +890,8 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 						target_feerate_sat_per_1000_weight,
 						htlcs,
 						tx_lock_time: LockTime::from_consensus(cached_request.package_locktime(cur_height)),
+						blocks_until_expiry: cached_request.get_htlc_deadline_height()
+							.map_or(0, |deadline_height| deadline_height.saturating_sub(cur_height)),
 					}),
 				));
 			}
@@ -678,12 +955,81 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 						log_debug!(logger, "Pre-signed commitment {} already has feerate {} sat/kW above required {} sat/kW",
 							tx.compute_txid(), commitment_tx_feerate_sat_per_1000_weight,
 							package_target_feerate_sat_per_1000_weight);
+						self.holder_anchor_claim_status = Some((tx.compute_txid(), AnchorClaimStatus::FeerateSufficient {
+							commitment_feerate_sat_per_1000_weight: commitment_tx_feerate_sat_per_1000_weight,
+						}));
 						// The commitment transaction already meets the required feerate and doesn't
 						// need a CPFP. We still want to return something other than the event to
 						// register the claim.
 						return Some((new_timer, 0, OnchainClaim::Tx(MaybeSignedTransaction(tx))));
 					}
 
+					// If claiming our commitment transaction would burn too much of our balance while
+					// no HTLCs are at risk, we rather wait for feerates to drop than to bump it
+					// aggressively, only making sure it can enter the mempool.
+					let mut claim_status = AnchorClaimStatus::Claiming;
+					let mut package_target_feerate_sat_per_1000_weight = package_target_feerate_sat_per_1000_weight;
+					if let Some(max_fee_proportional_millionths) = self.max_force_close_fee_proportional_millionths {
+						let channel_type = &output.channel_parameters.as_ref()
+							.unwrap_or(self.channel_parameters()).channel_type_features;
+						let cost_estimate = estimate_force_close_cost(
+							channel_type, commitment_tx_feerate_sat_per_1000_weight, 0, 0,
+							FeeRate::from_sat_per_kwu(package_target_feerate_sat_per_1000_weight as u64), false,
+						);
+						// The fee committed to in the commitment transaction is already deducted from
+						// our balance, if we pay it at all.
+						let claim_fee_satoshis = cost_estimate.transactions.iter()
+							.filter(|cost| cost.transaction_type != ForceCloseTransactionType::Commitment)
+							.map(|cost| cost.fee.to_sat())
+							.fold(0u64, |total, fee| total.saturating_add(fee));
+						let balance_satoshis = holder_commitment.to_broadcaster_value_sat();
+						if conf_target != ConfirmationTarget::UrgentOnChainSweep
+							&& holder_commitment.nondust_htlcs().is_empty()
+							&& claim_fee_satoshis as u128 * 1_000_000
+								> balance_satoshis as u128 * max_fee_proportional_millionths as u128
+						{
+							let lowest_feerate_sat_per_1000_weight = fee_estimator
+								.bounded_sat_per_1000_weight(ConfirmationTarget::MinAllowedAnchorChannelRemoteFee);
+							log_info!(logger, "Deferring claim of commitment {} as claiming it at {} sat/kW costs {} sats of our {} sat balance, targeting {} sat/kW instead",
+								tx.compute_txid(), package_target_feerate_sat_per_1000_weight,
+								claim_fee_satoshis, balance_satoshis, lowest_feerate_sat_per_1000_weight);
+							claim_status = AnchorClaimStatus::Deferred {
+								commitment_feerate_sat_per_1000_weight: commitment_tx_feerate_sat_per_1000_weight,
+								target_feerate_sat_per_1000_weight: lowest_feerate_sat_per_1000_weight,
+								claim_fee_satoshis,
+								balance_satoshis,
+							};
+							package_target_feerate_sat_per_1000_weight = lowest_feerate_sat_per_1000_weight;
+							if commitment_tx_feerate_sat_per_1000_weight >= lowest_feerate_sat_per_1000_weight {
+								self.set_holder_anchor_claim_status(tx.compute_txid(), claim_status);
+								return Some((new_timer, 0, OnchainClaim::Tx(MaybeSignedTransaction(tx))));
+							}
+						}
+					}
+
+					// Spending the anchor output may cost more than it's worth at high feerates. If
+					// the commitment transaction already pays a feerate we're content with, we rather
+					// wait for it to confirm than to pay for the CPFP.
+					let anchor_input_weight = BASE_INPUT_WEIGHT + EMPTY_SCRIPT_SIG_WEIGHT + ANCHOR_INPUT_WITNESS_WEIGHT;
+					let claim_fee_satoshis = anchor_input_weight
+						* package_target_feerate_sat_per_1000_weight as u64 / 1000;
+					if let (Some(min_feerate), AnchorClaimStatus::Claiming) =
+						(self.min_feerate_to_skip_uneconomical_anchor_claim, claim_status)
+					{
+						if claim_fee_satoshis > ANCHOR_OUTPUT_VALUE_SATOSHI
+							&& commitment_tx_feerate_sat_per_1000_weight >= min_feerate
+						{
+							log_debug!(logger, "Skipping anchor spend for commitment {} with feerate {} sat/kW, as claiming it at {} sat/kW costs {} sats",
+								tx.compute_txid(), commitment_tx_feerate_sat_per_1000_weight,
+								package_target_feerate_sat_per_1000_weight, claim_fee_satoshis);
+							self.holder_anchor_claim_status = Some((tx.compute_txid(), AnchorClaimStatus::Uneconomical {
+								commitment_feerate_sat_per_1000_weight: commitment_tx_feerate_sat_per_1000_weight,
+								claim_fee_satoshis,
+							}));
+							return Some((new_timer, 0, OnchainClaim::Tx(MaybeSignedTransaction(tx))));
+						}
+					}
+
 					// We'll locate an anchor output we can spend within the commitment transaction.
 					let channel_parameters = output.channel_parameters.as_ref()
 						.unwrap_or(self.channel_parameters());
@@ -691,20 +1037,19 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 					match chan_utils::get_keyed_anchor_output(&tx, funding_pubkey) {
 						// An anchor output was found, so we should yield a funding event externally.
 						Some((idx, _)) => {
+							let commitment_txid = tx.compute_txid();
 							// TODO: Use a lower confirmation target when both our and the
 							// counterparty's latest commitment don't have any HTLCs present.
-							Some((
-								new_timer,
-								package_target_feerate_sat_per_1000_weight as u64,
-								OnchainClaim::Event(ClaimEvent::BumpCommitment {
-									package_target_feerate_sat_per_1000_weight,
-									commitment_tx: tx,
-									pending_nondust_htlcs: holder_commitment.nondust_htlcs().to_vec(),
-									commitment_tx_fee_satoshis: fee_sat,
-									anchor_output_idx: idx,
-									channel_parameters: channel_parameters.clone(),
-								}),
-							))
+							let claim = OnchainClaim::Event(ClaimEvent::BumpCommitment {
+								package_target_feerate_sat_per_1000_weight,
+								commitment_tx: tx,
+								pending_nondust_htlcs: holder_commitment.nondust_htlcs().to_vec(),
+								commitment_tx_fee_satoshis: fee_sat,
+								anchor_output_idx: idx,
+								channel_parameters: channel_parameters.clone(),
+							});
+							self.set_holder_anchor_claim_status(commitment_txid, claim_status);
+							Some((new_timer, package_target_feerate_sat_per_1000_weight as u64, claim))
 						},
 						// An anchor output was not found. There's nothing we can do other than
 						// attempt to broadcast the transaction with its current fee rate and hope
@@ -722,6 +1067,17 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 		None
 	}
 
+	fn set_holder_anchor_claim_status(&mut self, commitment_txid: Txid, status: AnchorClaimStatus) {
+		if let AnchorClaimStatus::Deferred { .. } = status {
+			let already_deferred = match self.holder_anchor_claim_status {
+				Some((txid, AnchorClaimStatus::Deferred { .. })) => txid == commitment_txid,
+				_ => false,
+			};
+			self.claim_deferral_pending |= !already_deferred;
+		}
+		self.holder_anchor_claim_status = Some((commitment_txid, status));
+	}
+
 	#[rustfmt::skip]
 	pub fn abandon_claim(&mut self, outpoint: &BitcoinOutPoint) {
 		let claim_id = self.claimable_outpoints.get(outpoint).map(|(claim_id, _)| *claim_id)
@@ -736,6 +1092,7 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 					self.claimable_outpoints.remove(outpoint);
 				}
 			}
+			self.claim_attempts.retain(|attempt| attempt.claim_id != claim_id);
 		} else {
 			self.locktimed_packages.values_mut().for_each(|claims|
 				claims.retain(|claim| !claim.outpoints().contains(&outpoint)));
@@ -852,13 +1209,15 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 				// `OnchainClaim`.
 				let claim_id = match claim {
 					OnchainClaim::Tx(tx) => {
+						let claim_id = ClaimId(tx.0.compute_txid().to_byte_array());
 						if tx.is_fully_signed() {
 							log_info!(logger, "Broadcasting onchain {}", log_tx!(tx.0));
+							self.track_claim_tx(claim_id, &req, &tx.0);
 							broadcaster.broadcast_transactions(&[&tx.0]);
 						} else {
 							log_info!(logger, "Waiting for signature of unsigned onchain transaction {}", tx.0.compute_txid());
 						}
-						ClaimId(tx.0.compute_txid().to_byte_array())
+						claim_id
 					},
 					OnchainClaim::Event(claim_event) => {
 						log_info!(logger, "Yielding onchain event to spend inputs {:?}", req.outpoints());
@@ -895,6 +1254,9 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 					self.claimable_outpoints.insert(k.clone(), (claim_id, creation_height));
 				}
 				self.pending_claim_requests.insert(claim_id, req);
+				self.resolution_stats.max_pending_claims = cmp::max(
+					self.resolution_stats.max_pending_claims, self.pending_claim_requests.len() as u32,
+				);
 			}
 		}
 	}
@@ -1045,6 +1407,7 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 								assert!(num_existing == 0 || num_existing == 1);
 							}
 							self.pending_claim_events.retain(|(id, _)| *id != claim_id);
+							self.resolve_claim_attempts(claim_id, entry.txid, &request);
 						}
 					},
 					OnchainEvent::ContentiousOutpoint { package } => {
@@ -1080,6 +1443,7 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 					OnchainClaim::Tx(bump_tx) => {
 						if bump_tx.is_fully_signed() {
 							log_info!(logger, "Broadcasting RBF-bumped onchain {}", log_tx!(bump_tx.0));
+							self.track_claim_tx(*claim_id, request, &bump_tx.0);
 							broadcaster.broadcast_transactions(&[&bump_tx.0]);
 						} else {
 							log_info!(logger, "Waiting for signature of RBF-bumped unsigned onchain transaction {}",
@@ -1185,6 +1549,7 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 					OnchainClaim::Tx(bump_tx) => {
 						if bump_tx.is_fully_signed() {
 							log_info!(logger, "Broadcasting onchain {}", log_tx!(bump_tx.0));
+							self.track_claim_tx(*_claim_id, request, &bump_tx.0);
 							broadcaster.broadcast_transactions(&[&bump_tx.0]);
 						} else {
 							log_info!(logger, "Waiting for signature of unsigned onchain transaction {}", bump_tx.0.compute_txid());
@@ -1216,6 +1581,7 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 			} else { true });
 		for req in remove_request {
 			self.pending_claim_requests.remove(&req);
+			self.claim_attempts.retain(|attempt| attempt.claim_id != req);
 		}
 	}
 
@@ -1223,6 +1589,17 @@ impl<ChannelSigner: EcdsaChannelSigner> OnchainTxHandler<ChannelSigner> {
 		self.claimable_outpoints.get(outpoint).is_some()
 	}
 
+	/// Returns the height at which our counterparty may be able to claim the given outpoint, if we
+	/// have a pending claim for it and know the height.
+	///
+	/// Note that as claims may be aggregated, this is the earliest such height across all outputs
+	/// in the claim spending `outpoint`.
+	pub(crate) fn get_claim_deadline(&self, outpoint: &BitcoinOutPoint) -> Option<u32> {
+		let (claim_id, _) = self.claimable_outpoints.get(outpoint)?;
+		let package = self.pending_claim_requests.get(claim_id)?;
+		Some(package.counterparty_spendable_height()).filter(|height| *height != 0)
+	}
+
 	#[rustfmt::skip]
 	pub(crate) fn get_relevant_txids(&self) -> Vec<(Txid, u32, Option<BlockHash>)> {
 		let mut txids: Vec<(Txid, u32, Option<BlockHash>)> = self.onchain_events_awaiting_threshold_conf
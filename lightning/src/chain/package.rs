@@ -1205,6 +1205,9 @@ impl PackageTemplate {
 	pub(crate) fn set_timer(&mut self, new_timer: u32) {
 		self.height_timer = new_timer;
 	}
+	pub(crate) fn counterparty_spendable_height(&self) -> u32 {
+		self.counterparty_spendable_height
+	}
 	pub(crate) fn outpoints(&self) -> Vec<&BitcoinOutPoint> {
 		self.inputs.iter().map(|(o, _)| o).collect()
 	}
@@ -1447,6 +1450,26 @@ impl PackageTemplate {
 		height_timer
 	}
 
+	/// Gets the height by which the HTLC outputs claimed by this package should be claimed, i.e.,
+	/// the same deadline [`Self::get_height_timer`] bumps towards, or `None` if it claims no HTLC
+	/// outputs.
+	pub(crate) fn get_htlc_deadline_height(&self) -> Option<u32> {
+		let htlc_deadline_height = |input: &PackageSolvingData| match input {
+			PackageSolvingData::CounterpartyOfferedHTLCOutput(outp) => Some(outp.htlc.cltv_expiry),
+			PackageSolvingData::HolderHTLCOutput(outp) if outp.preimage.is_some() => {
+				Some(self.counterparty_spendable_height)
+			},
+			PackageSolvingData::CounterpartyReceivedHTLCOutput(outp) => {
+				Some(outp.htlc.cltv_expiry + MIN_CLTV_EXPIRY_DELTA as u32)
+			},
+			PackageSolvingData::HolderHTLCOutput(outp) => {
+				Some(outp.cltv_expiry + MIN_CLTV_EXPIRY_DELTA as u32)
+			},
+			_ => None,
+		};
+		self.inputs.iter().filter_map(|(_, input)| htlc_deadline_height(input)).min()
+	}
+
 	/// Returns value in satoshis to be included as package outgoing output amount and feerate
 	/// which was used to generate the value. Will not return less than `dust_limit_sats` for the
 	/// value.
@@ -17,34 +17,48 @@ use alloc::collections::BTreeMap;
 use core::ops::Deref;
 
 use crate::chain::chaininterface::{fee_for_weight, BroadcasterInterface};
+use crate::chain::channelmonitor::ANTI_REORG_DELAY;
 use crate::chain::ClaimId;
-use crate::io_extras::sink;
+use crate::events::{Event, EventHandler, EventsProvider};
+use crate::io;
 use crate::ln::chan_utils;
 use crate::ln::chan_utils::{
 	shared_anchor_script_pubkey, HTLCOutputInCommitment, ANCHOR_INPUT_WITNESS_WEIGHT,
 	HTLC_SUCCESS_INPUT_ANCHOR_WITNESS_WEIGHT, HTLC_TIMEOUT_INPUT_ANCHOR_WITNESS_WEIGHT,
 };
 use crate::ln::channel::ANCHOR_OUTPUT_VALUE_SATOSHI;
+use crate::ln::msgs::DecodeError;
 use crate::ln::types::ChannelId;
 use crate::prelude::*;
 use crate::sign::ecdsa::EcdsaChannelSigner;
 use crate::sign::{
-	ChannelDerivationParameters, HTLCDescriptor, SignerProvider, P2WPKH_WITNESS_WEIGHT,
+	ChannelDerivationParameters, HTLCDescriptor, SignerProvider, P2TR_KEY_PATH_WITNESS_WEIGHT,
+	P2WPKH_WITNESS_WEIGHT,
+};
+use crate::sync::{Arc, Mutex};
+use crate::util::anchor_channel_reserves::{
+	change_output_weight, ReserveAuditDecision, ReserveAuditEntry, ReserveAuditLogger,
 };
-use crate::sync::Mutex;
 use crate::util::async_poll::{AsyncResult, MaybeSend, MaybeSync};
 use crate::util::logger::Logger;
+use crate::util::persist::{KVStoreNamespace, KVStoreSync};
+use crate::util::ser::{Readable, Writeable, Writer};
 
 use bitcoin::amount::Amount;
-use bitcoin::consensus::Encodable;
+use bitcoin::consensus::encode;
 use bitcoin::constants::WITNESS_SCALE_FACTOR;
+use bitcoin::hashes::sha256::Hash as Sha256;
+use bitcoin::hashes::{Hash, HashEngine};
+use bitcoin::hex::{DisplayHex, FromHex};
+use bitcoin::key::TweakedPublicKey;
 use bitcoin::locktime::absolute::LockTime;
 use bitcoin::secp256k1;
 use bitcoin::secp256k1::ecdsa::Signature;
 use bitcoin::secp256k1::{PublicKey, Secp256k1};
 use bitcoin::transaction::Version;
 use bitcoin::{
-	OutPoint, Psbt, PubkeyHash, ScriptBuf, Sequence, Transaction, TxIn, TxOut, WPubkeyHash, Witness,
+	OutPoint, Psbt, PubkeyHash, ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, WPubkeyHash,
+	Weight, Witness,
 };
 
 pub(crate) const EMPTY_SCRIPT_SIG_WEIGHT: u64 =
@@ -54,6 +68,29 @@ const BASE_INPUT_SIZE: u64 = 32 /* txid */ + 4 /* vout */ + 4 /* sequence */;
 
 pub(crate) const BASE_INPUT_WEIGHT: u64 = BASE_INPUT_SIZE * WITNESS_SCALE_FACTOR as u64;
 
+const P2PKH_SCRIPT_SIG_SIZE: u64 = 1 /* script_sig length */ +
+	1 /* OP_PUSH73 */ +
+	73 /* sig including sighash flag */ +
+	1 /* OP_PUSH33 */ +
+	33 /* pubkey */;
+
+const P2PKH_SATISFACTION_WEIGHT: u64 =
+	P2PKH_SCRIPT_SIG_SIZE * WITNESS_SCALE_FACTOR as u64 + 1 /* empty witness */;
+
+const NESTED_P2WPKH_SCRIPT_SIG_SIZE: u64 = 1 /* script_sig length */ +
+	1 /* OP_PUSH22 */ +
+	1 /* OP_0 */ +
+	1 /* OP_PUSH20 */ +
+	20 /* pubkey_hash */;
+
+const NESTED_P2WPKH_SATISFACTION_WEIGHT: u64 =
+	NESTED_P2WPKH_SCRIPT_SIG_SIZE * WITNESS_SCALE_FACTOR as u64 + P2WPKH_WITNESS_WEIGHT;
+
+const P2WPKH_SATISFACTION_WEIGHT: u64 = EMPTY_SCRIPT_SIG_WEIGHT + P2WPKH_WITNESS_WEIGHT;
+
+const P2TR_KEY_PATH_SATISFACTION_WEIGHT: u64 =
+	EMPTY_SCRIPT_SIG_WEIGHT + P2TR_KEY_PATH_WITNESS_WEIGHT;
+
 /// A descriptor used to sign for a commitment transaction's anchor output.
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct AnchorDescriptor {
@@ -228,9 +265,54 @@ pub enum BumpTransactionEvent {
 		htlc_descriptors: Vec<HTLCDescriptor>,
 		/// The locktime required for the resulting HTLC transaction.
 		tx_lock_time: LockTime,
+		/// The number of blocks, as of the height at which this event was generated, left until the
+		/// earliest deadline by which the HTLCs must be claimed, after which our counterparty may
+		/// claim them or, for HTLCs we forwarded, we may no longer be able to claim their inbound
+		/// counterparts in time.
+		///
+		/// This is used by [`BumpTransactionEventHandler`] to escalate the feerate of the HTLC
+		/// transaction as the deadline approaches, see [`HTLCDeadlineFeerateSchedule`].
+		blocks_until_expiry: u32,
 	},
 }
 
+impl BumpTransactionEvent {
+	fn claim_type(&self) -> BumpTransactionClaimType {
+		match self {
+			BumpTransactionEvent::ChannelClose { .. } => BumpTransactionClaimType::AnchorCPFP,
+			BumpTransactionEvent::HTLCResolution { .. } => BumpTransactionClaimType::HTLCResolution,
+		}
+	}
+
+	/// Returns the total value and earliest expiry of the HTLCs that are at risk if the claim does
+	/// not confirm in time.
+	fn htlcs_at_risk(&self) -> (u64, Option<u32>) {
+		let htlcs: Vec<&HTLCOutputInCommitment> = match self {
+			BumpTransactionEvent::ChannelClose { pending_htlcs, .. } => {
+				pending_htlcs.iter().collect()
+			},
+			BumpTransactionEvent::HTLCResolution { htlc_descriptors, .. } => {
+				htlc_descriptors.iter().map(|descriptor| &descriptor.htlc).collect()
+			},
+		};
+		let amount_msat = htlcs.iter().map(|htlc| htlc.amount_msat).sum();
+		let deadline_height = htlcs.iter().map(|htlc| htlc.cltv_expiry).min();
+		(amount_msat, deadline_height)
+	}
+}
+
+/// The type of claim that failed to be fee-bumped, as surfaced in
+/// [`Event::BumpTransactionFailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BumpTransactionClaimType {
+	/// A Child-Pays-For-Parent transaction spending the anchor output of a commitment transaction,
+	/// originating from a [`BumpTransactionEvent::ChannelClose`].
+	AnchorCPFP,
+	/// An HTLC transaction resolving HTLCs on a confirmed commitment transaction, originating from
+	/// a [`BumpTransactionEvent::HTLCResolution`].
+	HTLCResolution,
+}
+
 /// An input that must be included in a transaction when performing coin selection through
 /// [`CoinSelectionSource::select_confirmed_utxos`]. It is guaranteed to be a SegWit input, so it
 /// must have an empty [`TxIn::script_sig`] when spent.
@@ -249,6 +331,7 @@ pub struct Input {
 /// An unspent transaction output that is available to spend resulting from a successful
 /// [`CoinSelection`] attempt.
 #[derive(Clone, Debug, Hash, PartialOrd, Ord, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Utxo {
 	/// The unique identifier of the output.
 	pub outpoint: OutPoint,
@@ -257,30 +340,34 @@ pub struct Utxo {
 	/// The upper-bound weight consumed by the input's full [`TxIn::script_sig`] and [`TxIn::witness`], each
 	/// with their lengths included, required to satisfy the output's script. The weight consumed by
 	/// the input's `script_sig` must account for [`WITNESS_SCALE_FACTOR`].
-	pub satisfaction_weight: u64,
+	///
+	/// Note that this is a [`Weight`] rather than a virtual size. A size in vbytes must be converted
+	/// via [`Weight::from_vb`] rather than [`Weight::from_wu`].
+	#[cfg_attr(
+		feature = "serde",
+		serde(rename = "satisfaction_weight_wu", with = "crate::util::serde_helpers::weight_wu")
+	)]
+	pub satisfaction_weight: Weight,
 }
 
 impl Utxo {
+	/// Returns the [`Self::satisfaction_weight`] in weight units.
+	#[deprecated(note = "Use `satisfaction_weight` directly, which is now a `Weight`")]
+	pub fn satisfaction_weight_wu(&self) -> u64 {
+		self.satisfaction_weight.to_wu()
+	}
+
 	/// Returns a `Utxo` with the `satisfaction_weight` estimate for a legacy P2PKH output.
 	pub fn new_p2pkh(outpoint: OutPoint, value: Amount, pubkey_hash: &PubkeyHash) -> Self {
-		let script_sig_size = 1 /* script_sig length */ +
-			1 /* OP_PUSH73 */ +
-			73 /* sig including sighash flag */ +
-			1 /* OP_PUSH33 */ +
-			33 /* pubkey */;
 		Self {
 			outpoint,
 			output: TxOut { value, script_pubkey: ScriptBuf::new_p2pkh(pubkey_hash) },
-			satisfaction_weight: script_sig_size * WITNESS_SCALE_FACTOR as u64 + 1, /* empty witness */
+			satisfaction_weight: Weight::from_wu(P2PKH_SATISFACTION_WEIGHT),
 		}
 	}
 
 	/// Returns a `Utxo` with the `satisfaction_weight` estimate for a P2WPKH nested in P2SH output.
 	pub fn new_nested_p2wpkh(outpoint: OutPoint, value: Amount, pubkey_hash: &WPubkeyHash) -> Self {
-		let script_sig_size = 1 /* script_sig length */ +
-			1 /* OP_0 */ +
-			1 /* OP_PUSH20 */ +
-			20 /* pubkey_hash */;
 		Self {
 			outpoint,
 			output: TxOut {
@@ -289,24 +376,105 @@ impl Utxo {
 					&ScriptBuf::new_p2wpkh(pubkey_hash).script_hash(),
 				),
 			},
-			satisfaction_weight: script_sig_size * WITNESS_SCALE_FACTOR as u64
-				+ P2WPKH_WITNESS_WEIGHT,
+			satisfaction_weight: Weight::from_wu(NESTED_P2WPKH_SATISFACTION_WEIGHT),
 		}
 	}
 
 	/// Returns a `Utxo` with the `satisfaction_weight` estimate for a SegWit v0 P2WPKH output.
-	pub fn new_v0_p2wpkh(outpoint: OutPoint, value: Amount, pubkey_hash: &WPubkeyHash) -> Self {
+	pub fn new_p2wpkh(outpoint: OutPoint, value: Amount, pubkey_hash: &WPubkeyHash) -> Self {
 		Self {
 			outpoint,
 			output: TxOut { value, script_pubkey: ScriptBuf::new_p2wpkh(pubkey_hash) },
-			satisfaction_weight: EMPTY_SCRIPT_SIG_WEIGHT + P2WPKH_WITNESS_WEIGHT,
+			satisfaction_weight: Weight::from_wu(P2WPKH_SATISFACTION_WEIGHT),
+		}
+	}
+
+	/// Returns a `Utxo` with the `satisfaction_weight` estimate for a SegWit v0 P2WPKH output.
+	#[deprecated(note = "Use `new_p2wpkh` instead")]
+	pub fn new_v0_p2wpkh(outpoint: OutPoint, value: Amount, pubkey_hash: &WPubkeyHash) -> Self {
+		Self::new_p2wpkh(outpoint, value, pubkey_hash)
+	}
+
+	/// Returns a `Utxo` with the `satisfaction_weight` estimate for a P2TR output spent via its key
+	/// path.
+	pub fn new_p2tr(outpoint: OutPoint, value: Amount, output_key: TweakedPublicKey) -> Self {
+		Self {
+			outpoint,
+			output: TxOut { value, script_pubkey: ScriptBuf::new_p2tr_tweaked(output_key) },
+			satisfaction_weight: Weight::from_wu(P2TR_KEY_PATH_SATISFACTION_WEIGHT),
 		}
 	}
+
+	/// Returns a `Utxo` spending `output`, with the `satisfaction_weight` estimate for the type of
+	/// its `script_pubkey`, e.g., as listed by a wallet's `listunspent`.
+	///
+	/// P2PKH, P2WPKH and P2TR outputs are supported, the latter assumed to be spent via their key
+	/// path. Returns an error for any other type, including P2SH, as the script it commits to is
+	/// unknown. P2WPKH outputs nested in P2SH may be constructed via [`Utxo::new_nested_p2wpkh`]
+	/// instead.
+	pub fn try_from_txout(outpoint: OutPoint, output: TxOut) -> Result<Self, ()> {
+		let satisfaction_weight = if output.script_pubkey.is_p2pkh() {
+			P2PKH_SATISFACTION_WEIGHT
+		} else if output.script_pubkey.is_p2wpkh() {
+			P2WPKH_SATISFACTION_WEIGHT
+		} else if output.script_pubkey.is_p2tr() {
+			P2TR_KEY_PATH_SATISFACTION_WEIGHT
+		} else {
+			return Err(());
+		};
+		Ok(Self { outpoint, output, satisfaction_weight: Weight::from_wu(satisfaction_weight) })
+	}
+}
+
+impl_writeable_tlv_based!(Utxo, {
+	(0, outpoint, required),
+	(2, output, required),
+	(4, satisfaction_weight, required),
+});
+
+const UTXO_SNAPSHOT_SERIALIZATION_VERSION: u8 = 1;
+const MIN_UTXO_SNAPSHOT_SERIALIZATION_VERSION: u8 = 1;
+
+struct UtxoSnapshot<'a>(&'a [Utxo]);
+
+impl<'a> Writeable for UtxoSnapshot<'a> {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), io::Error> {
+		write_ver_prefix!(
+			writer,
+			UTXO_SNAPSHOT_SERIALIZATION_VERSION,
+			MIN_UTXO_SNAPSHOT_SERIALIZATION_VERSION
+		);
+		write_tlv_fields!(writer, {
+			(0, *self.0, required_vec),
+		});
+		Ok(())
+	}
+}
+
+/// Serializes a snapshot of a wallet's UTXOs, e.g., to provide them to LDK from a wallet running
+/// in a separate process, prefixed by a version byte.
+///
+/// The snapshot can be read via [`deserialize_utxo_snapshot`], including by later versions.
+pub fn serialize_utxo_snapshot(utxos: &[Utxo]) -> Vec<u8> {
+	UtxoSnapshot(utxos).encode()
+}
+
+/// Reads a snapshot of a wallet's UTXOs written via [`serialize_utxo_snapshot`].
+///
+/// Fails with [`DecodeError::UnknownVersion`] if the snapshot was written by a later version that
+/// this version can't read.
+pub fn deserialize_utxo_snapshot(mut snapshot: &[u8]) -> Result<Vec<Utxo>, DecodeError> {
+	let reader = &mut snapshot;
+	let _ver = read_ver_prefix!(reader, UTXO_SNAPSHOT_SERIALIZATION_VERSION);
+	_init_and_read_len_prefixed_tlv_fields!(reader, {
+		(0, utxos, required_vec),
+	});
+	Ok(utxos)
 }
 
 /// The result of a successful coin selection attempt for a transaction requiring additional UTXOs
 /// to cover its fees.
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct CoinSelection {
 	/// The set of UTXOs (with at least 1 confirmation) to spend and use within a transaction
 	/// requiring additional fees.
@@ -315,9 +483,19 @@ pub struct CoinSelection {
 	/// should always have a value above dust for its given `script_pubkey`. It should not be
 	/// spent until the transaction it belongs to confirms to ensure mempool descendant limits are
 	/// not met. This implies no other party should be able to spend it except us.
+	///
+	/// Its fee should be accounted for based on its actual `script_pubkey`, e.g., via
+	/// [`change_output_weight`], as the output is included in the transaction as-is.
+	///
+	/// [`change_output_weight`]: crate::util::anchor_channel_reserves::change_output_weight
 	pub change_output: Option<TxOut>,
 }
 
+impl_writeable_tlv_based!(CoinSelection, {
+	(0, confirmed_utxos, required_vec),
+	(2, change_output, option),
+});
+
 /// An abstraction over a bitcoin wallet that can perform coin selection over a set of UTXOs and can
 /// sign for them. The coin selection method aims to mimic Bitcoin Core's `fundrawtransaction` RPC,
 /// which most wallets should be able to satisfy. Otherwise, consider implementing [`WalletSource`],
@@ -411,6 +589,11 @@ where
 		Self { source, logger, locked_utxos: Mutex::new(new_hash_map()) }
 	}
 
+	/// Returns the outpoints of the UTXOs which were selected as inputs to a claim.
+	pub(crate) fn list_locked_outpoints(&self) -> Vec<OutPoint> {
+		self.locked_utxos.lock().unwrap().keys().copied().collect()
+	}
+
 	/// Performs coin selection on the set of UTXOs obtained from
 	/// [`WalletSource::list_confirmed_utxos`]. Its algorithm can be described as "smallest
 	/// above-dust-after-spend first", with a slight twist: we may skip UTXOs that are above dust at
@@ -444,7 +627,7 @@ where
 					}
 					let fee_to_spend_utxo = Amount::from_sat(fee_for_weight(
 						target_feerate_sat_per_1000_weight,
-						BASE_INPUT_WEIGHT + utxo.satisfaction_weight,
+						BASE_INPUT_WEIGHT + utxo.satisfaction_weight.to_wu(),
 					));
 					let should_spend = if tolerate_high_network_feerates {
 						utxo.output.value > fee_to_spend_utxo
@@ -496,8 +679,7 @@ where
 		let change_script = self.source.get_change_script().await?;
 		let change_output_fee = fee_for_weight(
 			target_feerate_sat_per_1000_weight,
-			(8 /* value */ + change_script.consensus_encode(&mut sink()).unwrap() as u64)
-				* WITNESS_SCALE_FACTOR as u64,
+			change_output_weight(&change_script).to_wu(),
 		);
 		let change_output_amount =
 			Amount::from_sat(remaining_amount.to_sat().saturating_sub(change_output_fee));
@@ -576,6 +758,379 @@ where
 	}
 }
 
+/// The number of consecutive failed attempts to bump a claim after which an
+/// [`Event::BumpTransactionFailed`] is first generated.
+const FAILURES_BEFORE_FAILED_EVENT: u32 = 3;
+
+/// The number of additional failed attempts after which an [`Event::BumpTransactionFailed`] is
+/// repeated for the first time. The interval is halved upon every repetition, down to
+/// [`MIN_FAILED_EVENT_INTERVAL`], to surface the failure with increasing urgency.
+const INITIAL_FAILED_EVENT_INTERVAL: u32 = 16;
+
+/// The minimum number of failed attempts between two [`Event::BumpTransactionFailed`]s for the
+/// same claim.
+const MIN_FAILED_EVENT_INTERVAL: u32 = 2;
+
+/// Tracks the consecutive failed attempts to bump the fee of a claim.
+struct FailedClaim {
+	consecutive_failures: u32,
+	next_event_at_failures: u32,
+	event_interval: u32,
+}
+
+impl FailedClaim {
+	fn new() -> Self {
+		Self {
+			consecutive_failures: 0,
+			next_event_at_failures: FAILURES_BEFORE_FAILED_EVENT,
+			event_interval: INITIAL_FAILED_EVENT_INTERVAL,
+		}
+	}
+
+	/// Records a failed attempt, returning whether an [`Event::BumpTransactionFailed`] should be
+	/// generated.
+	fn record_failure(&mut self) -> bool {
+		self.consecutive_failures = self.consecutive_failures.saturating_add(1);
+		if self.consecutive_failures < self.next_event_at_failures {
+			return false;
+		}
+		self.next_event_at_failures = self.consecutive_failures.saturating_add(self.event_interval);
+		self.event_interval = core::cmp::max(self.event_interval / 2, MIN_FAILED_EVENT_INTERVAL);
+		true
+	}
+}
+
+/// A successful fee bump of a claim, as recorded to a [`ReserveAuditLogger`].
+struct FeeBump {
+	/// The txid of the transaction paying the fees.
+	txid: Txid,
+	/// The fee rate targeted for the transaction, or the package for commitment transactions.
+	target_feerate_sat_per_1000_weight: u32,
+	/// The fees paid by the wallet inputs.
+	fees_paid: Amount,
+	/// The weight of the signed transaction paying the fees.
+	tx_weight: Weight,
+	wallet_utxos: Vec<OutPoint>,
+	wallet_utxos_value: Amount,
+	/// Whether the fees were paid by the UTXOs of a sponsor rather than our own.
+	sponsored: bool,
+}
+
+impl FeeBump {
+	fn new(
+		txid: Txid, target_feerate_sat_per_1000_weight: u32, fees_paid: Amount, tx_weight: Weight,
+		wallet_utxos: &[Utxo], sponsored: bool,
+	) -> Self {
+		Self {
+			txid,
+			target_feerate_sat_per_1000_weight,
+			fees_paid,
+			tx_weight,
+			wallet_utxos: wallet_utxos.iter().map(|utxo| utxo.outpoint).collect(),
+			wallet_utxos_value: wallet_utxos.iter().map(|utxo| utxo.output.value).sum(),
+			sponsored,
+		}
+	}
+}
+
+/// The claims for which a [`CoinSelectionSource`] registered via
+/// [`BumpTransactionEventHandler::register_fee_sponsor`] provides the UTXOs to bump their fees.
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum FeeSponsorScope {
+	/// The claims of all channels with the counterparty with the given node id.
+	Counterparty(PublicKey),
+	/// The claims of the channel with the given `channel_id`.
+	Channel(ChannelId),
+}
+
+/// The wallet inputs and change output which [`BumpTransactionEventHandler`] is about to spend to
+/// bump the fee of a claim, as passed to a [`ClaimNotifier`] before broadcasting the transaction.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingClaimSpend {
+	/// The identifier of the claim, as provided to [`CoinSelectionSource::select_confirmed_utxos`].
+	/// For HTLC claims aggregated by [`BumpTransactionEventHandler::handle_events_aggregated`],
+	/// this identifies the set of aggregated claims.
+	pub claim_id: ClaimId,
+	/// The outpoints of the wallet UTXOs spent by the transaction.
+	pub wallet_utxos: Vec<OutPoint>,
+	/// The change output paying back to the wallet, if any.
+	pub change_output: Option<TxOut>,
+	/// The feerate targeted by the transaction, or by the package of the commitment and anchor
+	/// transactions for [`BumpTransactionEvent::ChannelClose`].
+	pub target_feerate_sat_per_1000_weight: u32,
+	/// The consensus-serialized transaction, without any signatures.
+	pub unsigned_tx: Vec<u8>,
+}
+
+/// A hook allowing wallets to learn which of their UTXOs [`BumpTransactionEventHandler`] spends to
+/// bump the fee of a claim before the transaction is broadcast, rather than by scanning the mempool
+/// afterwards, which races with their own coin selection.
+///
+/// Configured via [`BumpTransactionEventHandler::with_claim_notifier`]. It is only invoked for
+/// claims bumped with our own [`CoinSelectionSource`], not for those of a fee sponsor registered
+/// via [`BumpTransactionEventHandler::register_fee_sponsor`].
+pub trait ClaimNotifier {
+	/// Called once the transaction bumping the fee of a claim is signed, immediately before it is
+	/// handed to the [`BroadcasterInterface`].
+	///
+	/// Implementations may lock the [`PendingClaimSpend::wallet_utxos`] such that the wallet
+	/// doesn't spend them otherwise. Returning `Err` vetoes this attempt, such that the
+	/// transaction is not broadcast. The claim is not abandoned though, but retried upon the next
+	/// [`BumpTransactionEvent`] for it, with the failure counted towards an
+	/// [`Event::BumpTransactionFailed`] as any other failed attempt.
+	fn claim_spend_pending(&self, pending_spend: &PendingClaimSpend) -> Result<(), ()>;
+}
+
+/// A claim whose transaction awaits the signatures of our wallet inputs by an external signer, as
+/// listed by [`BumpTransactionEventHandler::pending_signatures`] once configured via
+/// [`BumpTransactionEventHandler::with_external_signing`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingSignature {
+	/// The identifier of the claim, to be passed to
+	/// [`BumpTransactionEventHandler::provide_signed_psbt`] along with the signed [`Self::psbt`].
+	/// For HTLC claims aggregated by [`BumpTransactionEventHandler::handle_events_aggregated`],
+	/// this identifies the set of aggregated claims.
+	pub claim_id: ClaimId,
+	/// The PSBT whose wallet inputs need to be signed, as otherwise passed to
+	/// [`CoinSelectionSource::sign_psbt`]. Its leading anchor or HTLC inputs are only signed once
+	/// the signed PSBT is provided.
+	pub psbt: Psbt,
+	/// The feerate targeted by the transaction, or by the package of the commitment and anchor
+	/// transactions for [`BumpTransactionEvent::ChannelClose`].
+	pub target_feerate_sat_per_1000_weight: u32,
+}
+
+/// An error returned by [`BumpTransactionEventHandler::provide_signed_psbt`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ProvideSignedPsbtError {
+	/// No PSBT awaits signatures for the claim, as it was never handed out, was already completed,
+	/// or was discarded since, e.g., because it timed out or the claim was invalidated.
+	UnknownClaim,
+	/// The unsigned transaction of the PSBT differs from the one handed out, or not all of its
+	/// wallet inputs are finalized. The claim keeps awaiting a correctly signed PSBT.
+	InvalidPsbt,
+	/// Signing our anchor or HTLC inputs failed, or the [`ClaimNotifier`] vetoed the broadcast. As
+	/// with any other failed attempt, the claim is retried upon the next [`BumpTransactionEvent`]
+	/// for it.
+	ClaimFailed,
+}
+
+/// The inputs of a claim which we sign once its wallet inputs were signed externally.
+enum ExternallySignedClaim {
+	ChannelClose { commitment_tx: Transaction, anchor_descriptor: AnchorDescriptor },
+	HTLCResolution { htlc_descriptors: Vec<HTLCDescriptor> },
+}
+
+/// A claim handed out as a [`PendingSignature`], along with what is needed to complete it.
+struct AwaitingSignature {
+	claim_id: ClaimId,
+	psbt: Psbt,
+	claim: ExternallySignedClaim,
+	target_feerate_sat_per_1000_weight: u32,
+	/// The fees paid by the wallet inputs.
+	fees_paid: Amount,
+	wallet_utxos: Vec<Utxo>,
+	pending_spend: Option<PendingClaimSpend>,
+	/// The events of the claims bumped by the transaction, whose results are tracked once it
+	/// completes.
+	events: Vec<BumpTransactionEvent>,
+	/// The number of [`BumpTransactionEvent`]s handled for the claim while awaiting signatures.
+	blocks_awaited: u32,
+}
+
+/// The outcome of a successful attempt to bump the fee of a claim.
+enum ClaimOutcome {
+	/// The transaction was signed and broadcast.
+	Broadcast(FeeBump),
+	/// The transaction awaits the signatures of an external signer.
+	AwaitingSignature(AwaitingSignature),
+}
+
+/// The last transaction broadcast for a claim, as persisted via
+/// [`BumpTransactionEventHandler::with_claim_persistence`].
+#[derive(Clone)]
+struct BroadcastClaim {
+	/// The txid of the commitment transaction whose fee the transaction bumps, if any.
+	parent_txid: Option<Txid>,
+	tx: Transaction,
+	/// The fee rate targeted for the transaction, or the package for commitment transactions.
+	target_feerate_sat_per_1000_weight: u32,
+	wallet_utxos: Vec<OutPoint>,
+	/// Whether the `wallet_utxos` were provided by a fee sponsor rather than our own wallet.
+	sponsored: bool,
+	/// The height at which the claim was resolved, i.e., at which its parent transaction or any
+	/// of its inputs confirmed, if any.
+	confirmation_height: Option<u32>,
+	/// Whether a resolution of the claim was reverted by a reorg, and it didn't resolve since.
+	reorged: bool,
+}
+
+impl BroadcastClaim {
+	/// Returns whether any of `txdata` confirms the parent transaction of the claim or spends any
+	/// of its inputs.
+	fn is_resolved_by(&self, txdata: &[&Transaction]) -> bool {
+		let parent_confirmed = self
+			.parent_txid
+			.map_or(false, |parent_txid| txdata.iter().any(|tx| tx.compute_txid() == parent_txid));
+		let claim_inputs = &self.tx.input;
+		let inputs_spent = txdata.iter().flat_map(|tx| tx.input.iter()).any(|input| {
+			claim_inputs
+				.iter()
+				.any(|claim_input| claim_input.previous_output == input.previous_output)
+		});
+		parent_confirmed || inputs_spent
+	}
+
+	/// Describes the spend of the `wallet_utxos` by the claim, as passed to a [`ClaimNotifier`].
+	fn pending_spend(&self, claim_id: ClaimId) -> PendingClaimSpend {
+		// Anchor transactions only have a change output, while HTLC transactions have an output
+		// for each of their leading HTLC inputs followed by the change output. Coin selection
+		// burns the change to an `OP_RETURN` output if it isn't worth keeping.
+		let num_claim_outputs = match self.parent_txid {
+			Some(_) => 0,
+			None => self.tx.input.len().saturating_sub(self.wallet_utxos.len()),
+		};
+		let mut unsigned_tx = self.tx.clone();
+		for input in unsigned_tx.input.iter_mut() {
+			input.script_sig = ScriptBuf::new();
+			input.witness = Witness::new();
+		}
+		PendingClaimSpend {
+			claim_id,
+			wallet_utxos: self.wallet_utxos.clone(),
+			change_output: self
+				.tx
+				.output
+				.get(num_claim_outputs)
+				.filter(|output| !output.script_pubkey.is_op_return())
+				.cloned(),
+			target_feerate_sat_per_1000_weight: self.target_feerate_sat_per_1000_weight,
+			unsigned_tx: encode::serialize(&unsigned_tx),
+		}
+	}
+}
+
+const BROADCAST_CLAIM_SERIALIZATION_VERSION: u8 = 1;
+const MIN_BROADCAST_CLAIM_SERIALIZATION_VERSION: u8 = 1;
+
+impl Writeable for BroadcastClaim {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), io::Error> {
+		write_ver_prefix!(
+			writer,
+			BROADCAST_CLAIM_SERIALIZATION_VERSION,
+			MIN_BROADCAST_CLAIM_SERIALIZATION_VERSION
+		);
+		write_tlv_fields!(writer, {
+			(0, self.parent_txid, option),
+			(1, self.confirmation_height, option),
+			(2, self.tx, required),
+			(3, self.sponsored, (default_value, false)),
+			(4, self.target_feerate_sat_per_1000_weight, required),
+			(5, self.reorged, (default_value, false)),
+			(6, self.wallet_utxos, required_vec),
+		});
+		Ok(())
+	}
+}
+
+impl Readable for BroadcastClaim {
+	fn read<R: io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+		let _ver = read_ver_prefix!(reader, BROADCAST_CLAIM_SERIALIZATION_VERSION);
+		_init_and_read_len_prefixed_tlv_fields!(reader, {
+			(0, parent_txid, option),
+			(1, confirmation_height, option),
+			(2, tx, required),
+			(3, sponsored, (default_value, false)),
+			(4, target_feerate_sat_per_1000_weight, required),
+			(5, reorged, (default_value, false)),
+			(6, wallet_utxos, required_vec),
+		});
+		Ok(Self {
+			parent_txid,
+			tx: tx.0.unwrap(),
+			target_feerate_sat_per_1000_weight: target_feerate_sat_per_1000_weight.0.unwrap(),
+			wallet_utxos,
+			sponsored: sponsored.0.unwrap(),
+			confirmation_height,
+			reorged: reorged.0.unwrap(),
+		})
+	}
+}
+
+/// The schedule with which [`BumpTransactionEventHandler`] escalates the feerate of HTLC
+/// transactions as the deadline by which their HTLCs must be claimed approaches.
+///
+/// Once a [`BumpTransactionEvent::HTLCResolution`] has no more than
+/// [`Self::escalation_start_blocks`] left until its deadline, its target feerate is scaled
+/// linearly towards [`Self::max_feerate_sat_per_1000_weight`], which is targeted once no more than
+/// [`Self::max_feerate_blocks`] are left. A target feerate above the maximum is never lowered.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HTLCDeadlineFeerateSchedule {
+	/// The number of blocks left until the deadline at which the feerate starts being scaled
+	/// towards [`Self::max_feerate_sat_per_1000_weight`].
+	///
+	/// Default value: 36 blocks.
+	pub escalation_start_blocks: u32,
+	/// The number of blocks left until the deadline at which
+	/// [`Self::max_feerate_sat_per_1000_weight`] is targeted.
+	///
+	/// Default value: 6 blocks.
+	pub max_feerate_blocks: u32,
+	/// The feerate, in satoshis per 1000 weight units, targeted as the deadline is reached.
+	///
+	/// This is usually set to the [`AnchorChannelReserveContext::upper_bound_fee_rate`] used to
+	/// size the reserves available to fee bump claims.
+	///
+	/// Default value: 12,500 sat/kW, i.e., 50 sat/vB.
+	///
+	/// [`AnchorChannelReserveContext::upper_bound_fee_rate`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext::upper_bound_fee_rate
+	pub max_feerate_sat_per_1000_weight: u32,
+}
+
+impl HTLCDeadlineFeerateSchedule {
+	/// Returns a schedule which never escalates feerates, always targeting the feerate of each
+	/// [`BumpTransactionEvent::HTLCResolution`].
+	pub fn disabled() -> Self {
+		Self {
+			escalation_start_blocks: 0,
+			max_feerate_blocks: 0,
+			max_feerate_sat_per_1000_weight: 0,
+		}
+	}
+
+	/// Gets the feerate to target for an HTLC claim with a `target_feerate_sat_per_1000_weight`
+	/// and `blocks_until_expiry` left until its deadline.
+	fn feerate_for_deadline(
+		&self, target_feerate_sat_per_1000_weight: u32, blocks_until_expiry: u32,
+	) -> u32 {
+		let max_feerate = self.max_feerate_sat_per_1000_weight;
+		if max_feerate <= target_feerate_sat_per_1000_weight
+			|| blocks_until_expiry > self.escalation_start_blocks
+		{
+			return target_feerate_sat_per_1000_weight;
+		}
+		if blocks_until_expiry <= self.max_feerate_blocks {
+			return max_feerate;
+		}
+		let blocks_elapsed = (self.escalation_start_blocks - blocks_until_expiry) as u64;
+		let escalation_blocks = (self.escalation_start_blocks - self.max_feerate_blocks) as u64;
+		let feerate_increase = (max_feerate - target_feerate_sat_per_1000_weight) as u64
+			* blocks_elapsed
+			/ escalation_blocks;
+		target_feerate_sat_per_1000_weight + feerate_increase as u32
+	}
+}
+
+impl Default for HTLCDeadlineFeerateSchedule {
+	fn default() -> Self {
+		Self {
+			escalation_start_blocks: 36,
+			max_feerate_blocks: 6,
+			max_feerate_sat_per_1000_weight: 12_500,
+		}
+	}
+}
+
 /// A handler for [`Event::BumpTransaction`] events that sources confirmed UTXOs from a
 /// [`CoinSelectionSource`] to fee bump transactions via Child-Pays-For-Parent (CPFP) or
 /// Replace-By-Fee (RBF).
@@ -595,6 +1150,20 @@ where
 	signer_provider: SP,
 	logger: L,
 	secp: Secp256k1<secp256k1::All>,
+	failed_claims: Mutex<HashMap<ClaimId, FailedClaim>>,
+	pending_events: Mutex<Vec<Event>>,
+	audit_logger: Option<Arc<dyn ReserveAuditLogger + Send + Sync>>,
+	htlc_deadline_schedule: HTLCDeadlineFeerateSchedule,
+	/// The feerate of the last attempt to bump each HTLC claim, which later attempts never go below.
+	htlc_claim_feerates: Mutex<HashMap<ClaimId, u32>>,
+	fee_sponsors: Mutex<HashMap<FeeSponsorScope, Arc<dyn CoinSelectionSource + Send + Sync>>>,
+	claim_notifier: Option<Arc<dyn ClaimNotifier + Send + Sync>>,
+	/// The number of blocks after which a PSBT handed to an external signer times out, if enabled.
+	external_signing_timeout_blocks: Option<u32>,
+	awaiting_signatures: Mutex<HashMap<ClaimId, AwaitingSignature>>,
+	claim_store: Option<Arc<dyn KVStoreSync + Send + Sync>>,
+	/// The last transaction broadcast for each claim, if persisted to the `claim_store`.
+	broadcast_claims: Mutex<HashMap<ClaimId, BroadcastClaim>>,
 }
 
 impl<B: Deref, C: Deref, SP: Deref, L: Deref> BumpTransactionEventHandler<B, C, SP, L>
@@ -606,106 +1175,788 @@ where
 {
 	/// Returns a new instance capable of handling [`Event::BumpTransaction`] events.
 	///
+	/// The feerate of HTLC transactions is escalated as their deadline approaches according to
+	/// `htlc_deadline_schedule`.
+	///
 	/// [`Event::BumpTransaction`]: crate::events::Event::BumpTransaction
-	pub fn new(broadcaster: B, utxo_source: C, signer_provider: SP, logger: L) -> Self {
-		Self { broadcaster, utxo_source, signer_provider, logger, secp: Secp256k1::new() }
+	pub fn new(
+		broadcaster: B, utxo_source: C, signer_provider: SP, logger: L,
+		htlc_deadline_schedule: HTLCDeadlineFeerateSchedule,
+	) -> Self {
+		Self {
+			broadcaster,
+			utxo_source,
+			signer_provider,
+			logger,
+			secp: Secp256k1::new(),
+			failed_claims: Mutex::new(new_hash_map()),
+			pending_events: Mutex::new(Vec::new()),
+			audit_logger: None,
+			htlc_deadline_schedule,
+			htlc_claim_feerates: Mutex::new(new_hash_map()),
+			fee_sponsors: Mutex::new(new_hash_map()),
+			claim_notifier: None,
+			external_signing_timeout_blocks: None,
+			awaiting_signatures: Mutex::new(new_hash_map()),
+			claim_store: None,
+			broadcast_claims: Mutex::new(new_hash_map()),
+		}
 	}
 
-	/// Updates a transaction with the result of a successful coin selection attempt.
-	fn process_coin_selection(&self, tx: &mut Transaction, coin_selection: &CoinSelection) {
-		for utxo in coin_selection.confirmed_utxos.iter() {
-			tx.input.push(TxIn {
-				previous_output: utxo.outpoint,
-				script_sig: ScriptBuf::new(),
-				sequence: Sequence::ZERO,
-				witness: Witness::new(),
-			});
-		}
-		if let Some(change_output) = coin_selection.change_output.clone() {
-			tx.output.push(change_output);
-		} else if tx.output.is_empty() {
-			// We weren't provided a change output, likely because the input set was a perfect
-			// match, but we still need to have at least one output in the transaction for it to be
-			// considered standard. We choose to go with an empty OP_RETURN as it is the cheapest
-			// way to include a dummy output.
-			if tx.input.len() <= 1 {
-				// Transactions have to be at least 65 bytes in non-witness data, which we can run
-				// under if we have too few witness inputs.
-				log_debug!(self.logger, "Including large OP_RETURN output since an output is needed and a change output was not provided and the transaction is small");
-				debug_assert!(!tx.input.is_empty());
-				tx.output.push(TxOut {
-					value: Amount::ZERO,
-					// Minimum transaction size is 60 bytes, so we need a 5-byte script to get a
-					// 65 byte transaction. We do that as OP_RETURN <3 0 bytes, plus 1 byte len>.
-					script_pubkey: ScriptBuf::new_op_return(&[0, 0, 0]),
-				});
-				debug_assert_eq!(tx.base_size(), 65);
-			} else {
-				log_debug!(self.logger, "Including dummy OP_RETURN output since an output is needed and a change output was not provided");
-				tx.output.push(TxOut {
-					value: Amount::ZERO,
-					script_pubkey: ScriptBuf::new_op_return(&[]),
-				});
-			}
-		}
+	/// Records the fees paid for each successfully bumped claim to `audit_logger`.
+	pub fn with_audit_logger(
+		self, audit_logger: Arc<dyn ReserveAuditLogger + Send + Sync>,
+	) -> Self {
+		Self { audit_logger: Some(audit_logger), ..self }
 	}
 
-	/// Handles a [`BumpTransactionEvent::ChannelClose`] event variant by producing a fully-signed
-	/// transaction spending an anchor output of the commitment transaction to bump its fee and
-	/// broadcasts them to the network as a package.
-	async fn handle_channel_close(
-		&self, claim_id: ClaimId, package_target_feerate_sat_per_1000_weight: u32,
-		commitment_tx: &Transaction, commitment_tx_fee_sat: u64,
-		anchor_descriptor: &AnchorDescriptor,
-	) -> Result<(), ()> {
-		// Our commitment transaction already has fees allocated to it, so we should take them into
-		// account. We do so by pretending the commitment transaction's fee and weight are part of
-		// the anchor input.
-		let mut anchor_utxo = anchor_descriptor.previous_utxo();
-		let commitment_tx_fee_sat = Amount::from_sat(commitment_tx_fee_sat);
-		anchor_utxo.value += commitment_tx_fee_sat;
-		let starting_package_and_fixed_input_satisfaction_weight =
-			commitment_tx.weight().to_wu() + ANCHOR_INPUT_WITNESS_WEIGHT + EMPTY_SCRIPT_SIG_WEIGHT;
-		let mut package_and_fixed_input_satisfaction_weight =
-			starting_package_and_fixed_input_satisfaction_weight;
+	/// Notifies `claim_notifier` of the wallet UTXOs spent by each claim before its transaction is
+	/// broadcast, allowing it to veto the broadcast. See [`ClaimNotifier`] for details.
+	pub fn with_claim_notifier(self, claim_notifier: Arc<dyn ClaimNotifier + Send + Sync>) -> Self {
+		Self { claim_notifier: Some(claim_notifier), ..self }
+	}
 
-		loop {
-			let must_spend = vec![Input {
-				outpoint: anchor_descriptor.outpoint,
-				previous_utxo: anchor_utxo.clone(),
-				satisfaction_weight: package_and_fixed_input_satisfaction_weight,
-			}];
-			let must_spend_amount =
-				must_spend.iter().map(|input| input.previous_utxo.value).sum::<Amount>();
+	/// Hands the wallet inputs of claims bumped with our own [`CoinSelectionSource`] to an external
+	/// signer, such as a hardware wallet, rather than having them signed via
+	/// [`CoinSelectionSource::sign_psbt`].
+	///
+	/// Instead of being broadcast, such claims are listed by [`Self::pending_signatures`] until
+	/// their signed PSBT is passed to [`Self::provide_signed_psbt`], which signs our anchor or HTLC
+	/// inputs and broadcasts the transaction. Claims bumped by a fee sponsor registered via
+	/// [`Self::register_fee_sponsor`] are still signed by the sponsor.
+	///
+	/// As [`ChannelMonitor`]s regenerate the [`BumpTransactionEvent`]s of unresolved claims upon
+	/// every block, each event for a claim awaiting signatures counts as a block passing. Once an
+	/// event targets a higher feerate than the PSBT, or `timeout_blocks` events were handled while
+	/// awaiting signatures, the PSBT is discarded and the claim is regenerated at the event's
+	/// feerate. A timeout counts as a failed attempt towards an [`Event::BumpTransactionFailed`].
+	///
+	/// Claims whose parent transaction confirmed or whose inputs were spent in the meantime are
+	/// only discarded via [`Self::transactions_confirmed`].
+	///
+	/// [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+	pub fn with_external_signing(self, timeout_blocks: u32) -> Self {
+		Self { external_signing_timeout_blocks: Some(timeout_blocks), ..self }
+	}
 
-			log_debug!(self.logger, "Performing coin selection for commitment package (commitment and anchor transaction) targeting {} sat/kW",
-				package_target_feerate_sat_per_1000_weight);
-			let coin_selection: CoinSelection = self
-				.utxo_source
-				.select_confirmed_utxos(
-					claim_id,
-					must_spend,
-					&[],
-					package_target_feerate_sat_per_1000_weight,
+	/// Persists the last transaction broadcast for each claim, along with its target feerate and
+	/// the wallet UTXOs it spends, to `kv_store` under
+	/// [`KVStoreNamespace::BUMP_TRANSACTION_CLAIMS`], reloading any claims persisted previously.
+	///
+	/// Later attempts to bump a claim never target a lower feerate than its last broadcast
+	/// transaction, such that they replace it via RBF rather than conflicting with it, even
+	/// across restarts. A claim is resolved via [`Self::transactions_confirmed`] once the
+	/// commitment transaction whose fee it bumps or any of its inputs, e.g., by the claim itself,
+	/// is confirmed, and pruned via [`Self::best_block_updated`] once its resolution is
+	/// [`ANTI_REORG_DELAY`] blocks deep. Until then, [`Self::blocks_disconnected`] reverts its
+	/// resolution if the resolving block is reorged out, rebroadcasting its transaction.
+	///
+	/// Fails if the persisted claims can't be read or decoded.
+	pub fn with_claim_persistence(
+		self, kv_store: Arc<dyn KVStoreSync + Send + Sync>,
+	) -> Result<Self, io::Error> {
+		let namespace = KVStoreNamespace::BUMP_TRANSACTION_CLAIMS;
+		let mut broadcast_claims = new_hash_map();
+		let mut htlc_claim_feerates = new_hash_map();
+		for key in kv_store.list(namespace.primary_namespace, namespace.secondary_namespace)? {
+			let claim_id = <[u8; 32]>::from_hex(&key).map(ClaimId).map_err(|_| {
+				io::Error::new(io::ErrorKind::InvalidData, "Invalid bump transaction claim key")
+			})?;
+			let data =
+				kv_store.read(namespace.primary_namespace, namespace.secondary_namespace, &key)?;
+			let claim = BroadcastClaim::read(&mut &data[..]).map_err(|_| {
+				io::Error::new(
+					io::ErrorKind::InvalidData,
+					"Failed to decode bump transaction claim",
 				)
-				.await?;
+			})?;
+			log_info!(
+				self.logger,
+				"Reloaded claim {} with transaction {} at {} sat/kW spending wallet UTXOs {}",
+				key,
+				claim.tx.compute_txid(),
+				claim.target_feerate_sat_per_1000_weight,
+				log_iter!(claim.wallet_utxos.iter())
+			);
+			if claim.parent_txid.is_none() {
+				htlc_claim_feerates.insert(claim_id, claim.target_feerate_sat_per_1000_weight);
+			}
+			broadcast_claims.insert(claim_id, claim);
+		}
+		Ok(Self {
+			htlc_claim_feerates: Mutex::new(htlc_claim_feerates),
+			claim_store: Some(kv_store),
+			broadcast_claims: Mutex::new(broadcast_claims),
+			..self
+		})
+	}
 
-			let mut anchor_tx = Transaction {
-				version: Version::TWO,
-				lock_time: LockTime::ZERO, // TODO: Use next best height.
-				input: vec![anchor_descriptor.unsigned_tx_input()],
-				output: vec![],
-			};
+	/// Persists the transaction broadcast for a claim, if configured via
+	/// [`Self::with_claim_persistence`].
+	fn persist_broadcast_claim(
+		&self, claim_id: ClaimId, parent_txid: Option<Txid>, tx: &Transaction,
+		target_feerate_sat_per_1000_weight: u32, wallet_utxos: &[Utxo], sponsored: bool,
+	) {
+		if self.claim_store.is_none() {
+			return;
+		}
+		let claim = BroadcastClaim {
+			parent_txid,
+			tx: tx.clone(),
+			target_feerate_sat_per_1000_weight,
+			wallet_utxos: wallet_utxos.iter().map(|utxo| utxo.outpoint).collect(),
+			sponsored,
+			confirmation_height: None,
+			reorged: false,
+		};
+		self.write_broadcast_claim(claim_id, &claim);
+		self.broadcast_claims.lock().unwrap().insert(claim_id, claim);
+	}
 
-			let input_satisfaction_weight: u64 =
-				coin_selection.confirmed_utxos.iter().map(|utxo| utxo.satisfaction_weight).sum();
-			let total_satisfaction_weight =
-				ANCHOR_INPUT_WITNESS_WEIGHT + EMPTY_SCRIPT_SIG_WEIGHT + input_satisfaction_weight;
-			let total_input_amount = must_spend_amount
-				+ coin_selection.confirmed_utxos.iter().map(|utxo| utxo.output.value).sum();
+	/// Writes a claim to the store configured via [`Self::with_claim_persistence`], if any.
+	fn write_broadcast_claim(&self, claim_id: ClaimId, claim: &BroadcastClaim) {
+		let claim_store = match &self.claim_store {
+			Some(claim_store) => claim_store,
+			None => return,
+		};
+		let namespace = KVStoreNamespace::BUMP_TRANSACTION_CLAIMS;
+		let key = claim_id.0.to_lower_hex_string();
+		let res = claim_store.write(
+			namespace.primary_namespace,
+			namespace.secondary_namespace,
+			&key,
+			claim.encode(),
+		);
+		if let Err(e) = res {
+			log_error!(self.logger, "Failed to persist claim {}: {}", key, e);
+		}
+	}
 
-			self.process_coin_selection(&mut anchor_tx, &coin_selection);
-			let anchor_txid = anchor_tx.compute_txid();
+	/// Returns the feerate targeted by the last transaction broadcast for a claim, if persisted.
+	fn broadcast_claim_feerate(&self, claim_id: &ClaimId) -> u32 {
+		let broadcast_claims = self.broadcast_claims.lock().unwrap();
+		broadcast_claims.get(claim_id).map_or(0, |claim| claim.target_feerate_sat_per_1000_weight)
+	}
+
+	/// Returns the claims awaiting the signatures of an external signer, to be provided via
+	/// [`Self::provide_signed_psbt`]. See [`Self::with_external_signing`] for details.
+	pub fn pending_signatures(&self) -> Vec<PendingSignature> {
+		let awaiting_signatures = self.awaiting_signatures.lock().unwrap();
+		let mut pending_signatures = awaiting_signatures
+			.values()
+			.map(|awaiting| PendingSignature {
+				claim_id: awaiting.claim_id,
+				psbt: awaiting.psbt.clone(),
+				target_feerate_sat_per_1000_weight: awaiting.target_feerate_sat_per_1000_weight,
+			})
+			.collect::<Vec<_>>();
+		pending_signatures.sort_unstable_by_key(|pending| pending.claim_id.0);
+		pending_signatures
+	}
+
+	/// Completes a claim listed by [`Self::pending_signatures`] once an external signer signed the
+	/// wallet inputs of its PSBT, signing our anchor or HTLC inputs and broadcasting the
+	/// transaction.
+	///
+	/// The wallet inputs of `psbt` must be finalized, i.e., have their `final_script_witness` or
+	/// `final_script_sig` set, while its unsigned transaction must be left unmodified.
+	pub fn provide_signed_psbt(
+		&self, claim_id: ClaimId, psbt: Psbt,
+	) -> Result<(), ProvideSignedPsbtError> {
+		let mut awaiting_signatures = self.awaiting_signatures.lock().unwrap();
+		let awaiting =
+			awaiting_signatures.get(&claim_id).ok_or(ProvideSignedPsbtError::UnknownClaim)?;
+		let claim_inputs = match &awaiting.claim {
+			ExternallySignedClaim::ChannelClose { .. } => 1,
+			ExternallySignedClaim::HTLCResolution { htlc_descriptors } => htlc_descriptors.len(),
+		};
+		let wallet_inputs_finalized = psbt.inputs.len() == psbt.unsigned_tx.input.len()
+			&& psbt.inputs.iter().skip(claim_inputs).all(|input| {
+				input.final_script_witness.is_some() || input.final_script_sig.is_some()
+			});
+		if psbt.unsigned_tx != awaiting.psbt.unsigned_tx || !wallet_inputs_finalized {
+			log_error!(
+				self.logger,
+				"Rejecting invalid PSBT provided for claim {}",
+				log_bytes!(claim_id.0)
+			);
+			return Err(ProvideSignedPsbtError::InvalidPsbt);
+		}
+		let awaiting = awaiting_signatures.remove(&claim_id).unwrap();
+		drop(awaiting_signatures);
+
+		let mut tx = psbt.unsigned_tx;
+		for (input, psbt_input) in tx.input.iter_mut().zip(psbt.inputs).skip(claim_inputs) {
+			if let Some(script_sig) = psbt_input.final_script_sig {
+				input.script_sig = script_sig;
+			}
+			if let Some(witness) = psbt_input.final_script_witness {
+				input.witness = witness;
+			}
+		}
+		let res = self.complete_external_signature(&awaiting, tx).map_err(|e| {
+			log_error!(
+				self.logger,
+				"Failed completing externally signed claim {}: {}",
+				log_bytes!(claim_id.0),
+				e
+			);
+			e
+		});
+		for event in awaiting.events.iter() {
+			let res = res.as_ref().map(|fee_bump| self.audit_fee_bump(event, fee_bump));
+			self.track_claim_result(event, res.map_err(|e| *e));
+		}
+		res.map(|_| ()).map_err(|_| ProvideSignedPsbtError::ClaimFailed)
+	}
+
+	/// Discards the PSBTs awaiting signatures whose claims were invalidated by the confirmation of
+	/// any of `txdata`, returning their claim ids.
+	///
+	/// A claim is invalidated once any of its inputs is spent otherwise, e.g., by the counterparty
+	/// claiming an HTLC output, or, for a [`BumpTransactionEvent::ChannelClose`], once the
+	/// commitment transaction confirmed such that its fee no longer needs to be bumped. This
+	/// should be called with the transactions of each connected block while
+	/// [`Self::with_external_signing`] or [`Self::with_claim_persistence`] is used.
+	///
+	/// Claims persisted via [`Self::with_claim_persistence`] are marked as resolved at `height`
+	/// likewise, including once their own transaction confirmed, but aren't included in the
+	/// returned claim ids. They are only pruned via [`Self::best_block_updated`] once `height` is
+	/// [`ANTI_REORG_DELAY`] blocks deep.
+	pub fn transactions_confirmed(&self, txdata: &[&Transaction], height: u32) -> Vec<ClaimId> {
+		self.resolve_broadcast_claims(txdata, height);
+		let mut invalidated_claims = Vec::new();
+		self.awaiting_signatures.lock().unwrap().retain(|claim_id, awaiting| {
+			let parent_confirmed = match &awaiting.claim {
+				ExternallySignedClaim::ChannelClose { commitment_tx, .. } => {
+					let commitment_txid = commitment_tx.compute_txid();
+					txdata.iter().any(|tx| tx.compute_txid() == commitment_txid)
+				},
+				ExternallySignedClaim::HTLCResolution { .. } => false,
+			};
+			let claim_inputs = &awaiting.psbt.unsigned_tx.input;
+			let inputs_spent = txdata.iter().flat_map(|tx| tx.input.iter()).any(|input| {
+				claim_inputs
+					.iter()
+					.any(|claim_input| claim_input.previous_output == input.previous_output)
+			});
+			if parent_confirmed || inputs_spent {
+				log_info!(
+					self.logger,
+					"Discarding PSBT awaiting signatures for invalidated claim {}",
+					log_bytes!(claim_id.0)
+				);
+				invalidated_claims.push(*claim_id);
+				return false;
+			}
+			true
+		});
+		invalidated_claims
+	}
+
+	/// Marks the persisted claims whose parent transaction confirmed or whose inputs were spent by
+	/// any of `txdata` as resolved at `height`.
+	fn resolve_broadcast_claims(&self, txdata: &[&Transaction], height: u32) {
+		let mut broadcast_claims = self.broadcast_claims.lock().unwrap();
+		for (claim_id, claim) in broadcast_claims.iter_mut() {
+			if claim.confirmation_height.is_some() || !claim.is_resolved_by(txdata) {
+				continue;
+			}
+			log_info!(
+				self.logger,
+				"Claim {} resolved at height {}",
+				claim_id.0.to_lower_hex_string(),
+				height
+			);
+			claim.confirmation_height = Some(height);
+			claim.reorged = false;
+			self.write_broadcast_claim(*claim_id, claim);
+		}
+	}
+
+	/// Prunes the persisted claims whose resolution is at least [`ANTI_REORG_DELAY`] blocks deep
+	/// given the new best block `height`, such that they can no longer be reorged out.
+	///
+	/// This should be called upon each new best block while [`Self::with_claim_persistence`] is
+	/// used.
+	pub fn best_block_updated(&self, height: u32) {
+		let claim_store = match &self.claim_store {
+			Some(claim_store) => claim_store,
+			None => return,
+		};
+		let namespace = KVStoreNamespace::BUMP_TRANSACTION_CLAIMS;
+		let mut pruned_claims = Vec::new();
+		self.broadcast_claims.lock().unwrap().retain(|claim_id, claim| {
+			let confirmation_height = match claim.confirmation_height {
+				Some(confirmation_height) => confirmation_height,
+				None => return true,
+			};
+			if height < confirmation_height + ANTI_REORG_DELAY - 1 {
+				return true;
+			}
+			let key = claim_id.0.to_lower_hex_string();
+			log_info!(self.logger, "Pruning resolved claim {}", key);
+			let res = claim_store.remove(
+				namespace.primary_namespace,
+				namespace.secondary_namespace,
+				&key,
+				false,
+			);
+			if let Err(e) = res {
+				log_error!(self.logger, "Failed to remove persisted claim {}: {}", key, e);
+			}
+			pruned_claims.push(*claim_id);
+			false
+		});
+		let mut htlc_claim_feerates = self.htlc_claim_feerates.lock().unwrap();
+		for claim_id in pruned_claims {
+			htlc_claim_feerates.remove(&claim_id);
+		}
+	}
+
+	/// Reverts the resolution of the persisted claims resolved in blocks above
+	/// `new_best_height`, which were disconnected by a reorg.
+	///
+	/// As their wallet UTXOs may be unspent again, they are marked as such until the claim
+	/// resolves again, see [`Self::reorged_claim_utxos`]. Any [`ClaimNotifier`] is notified of
+	/// their spend anew, such that the wallet doesn't spend them otherwise in the meantime, and
+	/// their transactions are rebroadcast, as the bumped claims may still need to confirm.
+	///
+	/// This should be called whenever blocks are disconnected while
+	/// [`Self::with_claim_persistence`] is used.
+	pub fn blocks_disconnected(&self, new_best_height: u32) {
+		let mut reorged_claims = Vec::new();
+		{
+			let mut broadcast_claims = self.broadcast_claims.lock().unwrap();
+			for (claim_id, claim) in broadcast_claims.iter_mut() {
+				match claim.confirmation_height {
+					Some(confirmation_height) if confirmation_height > new_best_height => {},
+					_ => continue,
+				}
+				log_info!(
+					self.logger,
+					"Resolution of claim {} was reorged out, rebroadcasting {}",
+					claim_id.0.to_lower_hex_string(),
+					claim.tx.compute_txid()
+				);
+				claim.confirmation_height = None;
+				claim.reorged = true;
+				self.write_broadcast_claim(*claim_id, claim);
+				reorged_claims.push((*claim_id, claim.clone()));
+			}
+		}
+		for (claim_id, claim) in reorged_claims {
+			if !claim.sponsored {
+				if let Err(e) = self.notify_claim_spend(Some(claim.pending_spend(claim_id))) {
+					log_error!(
+						self.logger,
+						"Failed to renotify spend of reorged claim {}: {}",
+						claim_id.0.to_lower_hex_string(),
+						e
+					);
+				}
+			}
+			self.broadcaster.broadcast_transactions(&[&claim.tx]);
+		}
+	}
+
+	/// Returns the outpoints of the wallet UTXOs spent by persisted claims whose resolution was
+	/// reorged out via [`Self::blocks_disconnected`] and which didn't resolve again since.
+	///
+	/// Until their claims confirm again, these UTXOs may either be spent by the rebroadcast claim
+	/// or be available again, so they should neither be counted towards any reserve nor be spent
+	/// otherwise.
+	pub fn reorged_claim_utxos(&self) -> Vec<OutPoint> {
+		let broadcast_claims = self.broadcast_claims.lock().unwrap();
+		let mut utxos = broadcast_claims
+			.values()
+			.filter(|claim| claim.reorged)
+			.flat_map(|claim| claim.wallet_utxos.iter().copied())
+			.collect::<Vec<_>>();
+		utxos.sort_unstable();
+		utxos
+	}
+
+	/// Returns whether the claim awaits signatures for a PSBT targeting at least
+	/// `target_feerate_sat_per_1000_weight`, in which case no new PSBT should be generated for it.
+	///
+	/// Otherwise, any stale PSBT of the claim is discarded, with a timeout counting as a failed
+	/// attempt.
+	fn awaiting_signature(
+		&self, claim_id: ClaimId, target_feerate_sat_per_1000_weight: u32,
+	) -> bool {
+		let timeout_blocks = match self.external_signing_timeout_blocks {
+			Some(timeout_blocks) => timeout_blocks,
+			None => return false,
+		};
+		let mut awaiting_signatures = self.awaiting_signatures.lock().unwrap();
+		let awaiting = match awaiting_signatures.get_mut(&claim_id) {
+			Some(awaiting) => awaiting,
+			None => return false,
+		};
+		awaiting.blocks_awaited = awaiting.blocks_awaited.saturating_add(1);
+		let timed_out = awaiting.blocks_awaited >= timeout_blocks;
+		if !timed_out
+			&& awaiting.target_feerate_sat_per_1000_weight >= target_feerate_sat_per_1000_weight
+		{
+			return true;
+		}
+		let awaiting = awaiting_signatures.remove(&claim_id).unwrap();
+		drop(awaiting_signatures);
+		if timed_out {
+			log_warn!(
+				self.logger,
+				"External signer failed to sign claim {} within {} blocks, regenerating it",
+				log_bytes!(claim_id.0),
+				timeout_blocks
+			);
+			for event in awaiting.events.iter() {
+				self.track_claim_result(event, Err("External signer failed to sign in time"));
+			}
+		} else {
+			log_info!(
+				self.logger,
+				"Regenerating claim {} awaiting signatures at a higher feerate of {} sat/kW",
+				log_bytes!(claim_id.0),
+				target_feerate_sat_per_1000_weight
+			);
+		}
+		false
+	}
+
+	/// Signs our inputs of a claim whose wallet inputs were signed by an external signer and
+	/// broadcasts its transaction.
+	fn complete_external_signature(
+		&self, awaiting: &AwaitingSignature, mut tx: Transaction,
+	) -> Result<FeeBump, &'static str> {
+		match &awaiting.claim {
+			ExternallySignedClaim::ChannelClose { commitment_tx, anchor_descriptor } => {
+				let derivation_parameters = &anchor_descriptor.channel_derivation_parameters;
+				let signer =
+					self.signer_provider.derive_channel_signer(derivation_parameters.keys_id);
+				let anchor_sig = signer
+					.sign_holder_keyed_anchor_input(
+						&derivation_parameters.transaction_parameters,
+						&tx,
+						0,
+						&self.secp,
+					)
+					.map_err(|_| "Failed to sign anchor input")?;
+				tx.input[0].witness = anchor_descriptor.tx_input_witness(&anchor_sig);
+				self.notify_claim_spend(awaiting.pending_spend.clone())?;
+				log_info!(
+					self.logger,
+					"Broadcasting externally signed anchor transaction {} to bump channel close with txid {}",
+					tx.compute_txid(),
+					commitment_tx.compute_txid()
+				);
+				self.broadcaster.broadcast_transactions(&[commitment_tx, &tx]);
+				self.persist_broadcast_claim(
+					awaiting.claim_id,
+					Some(commitment_tx.compute_txid()),
+					&tx,
+					awaiting.target_feerate_sat_per_1000_weight,
+					&awaiting.wallet_utxos,
+					false,
+				);
+			},
+			ExternallySignedClaim::HTLCResolution { htlc_descriptors } => {
+				let htlc_witnesses = self.sign_htlc_inputs(&tx, htlc_descriptors)?;
+				for (input, witness) in tx.input.iter_mut().zip(htlc_witnesses) {
+					input.witness = witness;
+				}
+				self.notify_claim_spend(awaiting.pending_spend.clone())?;
+				log_info!(self.logger, "Broadcasting externally signed {}", log_tx!(tx));
+				self.broadcaster.broadcast_transactions(&[&tx]);
+				self.persist_broadcast_claim(
+					awaiting.claim_id,
+					None,
+					&tx,
+					awaiting.target_feerate_sat_per_1000_weight,
+					&awaiting.wallet_utxos,
+					false,
+				);
+			},
+		}
+		Ok(FeeBump::new(
+			tx.compute_txid(),
+			awaiting.target_feerate_sat_per_1000_weight,
+			awaiting.fees_paid,
+			tx.weight(),
+			&awaiting.wallet_utxos,
+			false,
+		))
+	}
+
+	/// Stores a claim handed out to an external signer, replacing any stale PSBT for it.
+	fn await_signature(&self, mut awaiting: AwaitingSignature, events: Vec<BumpTransactionEvent>) {
+		log_info!(
+			self.logger,
+			"Handing PSBT for claim {} to the external signer",
+			log_bytes!(awaiting.claim_id.0)
+		);
+		awaiting.events = events;
+		self.awaiting_signatures.lock().unwrap().insert(awaiting.claim_id, awaiting);
+	}
+
+	/// Registers `sponsor` to provide the UTXOs to bump the fees of the claims within `scope`,
+	/// replacing any sponsor previously registered for it. This allows, e.g., an LSP to sponsor
+	/// the force-closes of clients without on-chain funds.
+	///
+	/// A sponsor registered for a [`FeeSponsorScope::Channel`] takes precedence over one for the
+	/// channel's [`FeeSponsorScope::Counterparty`]. If a sponsor fails to select UTXOs, the claim
+	/// is bumped with our own [`CoinSelectionSource`] instead. Sponsored HTLC claims are never
+	/// aggregated with others by [`Self::handle_events_aggregated`].
+	///
+	/// As sponsors aren't trusted, they are only asked to sign their inputs via
+	/// [`CoinSelectionSource::sign_psbt`] once our anchor or HTLC inputs are signed, and the
+	/// returned transaction is rejected if the sponsor modified anything but its own inputs'
+	/// witnesses.
+	pub fn register_fee_sponsor(
+		&self, scope: FeeSponsorScope, sponsor: Arc<dyn CoinSelectionSource + Send + Sync>,
+	) {
+		self.fee_sponsors.lock().unwrap().insert(scope, sponsor);
+	}
+
+	/// Removes the sponsor registered for `scope` via [`Self::register_fee_sponsor`], returning
+	/// whether one was registered.
+	pub fn remove_fee_sponsor(&self, scope: &FeeSponsorScope) -> bool {
+		self.fee_sponsors.lock().unwrap().remove(scope).is_some()
+	}
+
+	/// Returns the sponsor providing the UTXOs to bump the claims of the given channel, if any.
+	fn fee_sponsor(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+	) -> Option<Arc<dyn CoinSelectionSource + Send + Sync>> {
+		let fee_sponsors = self.fee_sponsors.lock().unwrap();
+		fee_sponsors
+			.get(&FeeSponsorScope::Channel(*channel_id))
+			.or_else(|| fee_sponsors.get(&FeeSponsorScope::Counterparty(*counterparty_node_id)))
+			.cloned()
+	}
+
+	/// Performs coin selection via `sponsor`, falling back to our own [`CoinSelectionSource`] if
+	/// there is none or it fails. Returns whether the UTXOs were selected by the sponsor.
+	async fn select_claim_utxos(
+		&self, sponsor: Option<&(dyn CoinSelectionSource + Send + Sync)>, claim_id: ClaimId,
+		must_spend: Vec<Input>, must_pay_to: &[TxOut], target_feerate_sat_per_1000_weight: u32,
+	) -> Result<(CoinSelection, bool), &'static str> {
+		if let Some(sponsor) = sponsor {
+			let coin_selection = sponsor
+				.select_confirmed_utxos(
+					claim_id,
+					must_spend.clone(),
+					must_pay_to,
+					target_feerate_sat_per_1000_weight,
+				)
+				.await;
+			match coin_selection {
+				Ok(coin_selection) => return Ok((coin_selection, true)),
+				Err(()) => log_warn!(
+					self.logger,
+					"Sponsor failed to select UTXOs for claim {}, falling back to our own",
+					log_bytes!(claim_id.0)
+				),
+			}
+		}
+		let coin_selection = self
+			.utxo_source
+			.select_confirmed_utxos(
+				claim_id,
+				must_spend,
+				must_pay_to,
+				target_feerate_sat_per_1000_weight,
+			)
+			.await
+			.map_err(|_| "Coin selection failed")?;
+		Ok((coin_selection, false))
+	}
+
+	/// Has `sponsor` sign the inputs it contributed to `psbt`, after we signed the leading inputs
+	/// with `local_witnesses`, failing if the sponsor modified the transaction otherwise.
+	async fn sign_sponsored_psbt(
+		&self, sponsor: &(dyn CoinSelectionSource + Send + Sync), mut psbt: Psbt,
+		local_witnesses: Vec<Witness>,
+	) -> Result<Transaction, &'static str> {
+		let txid = psbt.unsigned_tx.compute_txid();
+		for (input, witness) in psbt.inputs.iter_mut().zip(local_witnesses.iter()) {
+			input.final_script_witness = Some(witness.clone());
+		}
+		let mut tx =
+			sponsor.sign_psbt(psbt).await.map_err(|_| "Sponsor failed to sign transaction")?;
+		if tx.compute_txid() != txid {
+			return Err("Sponsor modified the transaction");
+		}
+		for (input, witness) in tx.input.iter_mut().zip(local_witnesses) {
+			input.witness = witness;
+		}
+		Ok(tx)
+	}
+
+	/// Describes the spend of the UTXOs selected via `coin_selection` by the unsigned `tx`, if a
+	/// [`ClaimNotifier`] needs to be notified of it.
+	fn pending_claim_spend(
+		&self, claim_id: ClaimId, coin_selection: &CoinSelection, sponsored: bool,
+		target_feerate_sat_per_1000_weight: u32, tx: &Transaction,
+	) -> Option<PendingClaimSpend> {
+		if self.claim_notifier.is_none() || sponsored {
+			return None;
+		}
+		Some(PendingClaimSpend {
+			claim_id,
+			wallet_utxos: coin_selection.confirmed_utxos.iter().map(|utxo| utxo.outpoint).collect(),
+			change_output: coin_selection.change_output.clone(),
+			target_feerate_sat_per_1000_weight,
+			unsigned_tx: encode::serialize(tx),
+		})
+	}
+
+	/// Notifies the [`ClaimNotifier`] of a pending spend, failing if it vetoes the broadcast.
+	fn notify_claim_spend(
+		&self, pending_spend: Option<PendingClaimSpend>,
+	) -> Result<(), &'static str> {
+		match (&self.claim_notifier, pending_spend) {
+			(Some(claim_notifier), Some(pending_spend)) => claim_notifier
+				.claim_spend_pending(&pending_spend)
+				.map_err(|()| "Claim notifier vetoed the broadcast"),
+			_ => Ok(()),
+		}
+	}
+
+	/// Signs the leading HTLC inputs of `htlc_tx`, returning their witnesses.
+	fn sign_htlc_inputs(
+		&self, htlc_tx: &Transaction, htlc_descriptors: &[HTLCDescriptor],
+	) -> Result<Vec<Witness>, &'static str> {
+		let mut signers = BTreeMap::new();
+		let mut htlc_witnesses = Vec::with_capacity(htlc_descriptors.len());
+		for (idx, htlc_descriptor) in htlc_descriptors.iter().enumerate() {
+			let keys_id = htlc_descriptor.channel_derivation_parameters.keys_id;
+			let signer = signers
+				.entry(keys_id)
+				.or_insert_with(|| self.signer_provider.derive_channel_signer(keys_id));
+			let htlc_sig = signer
+				.sign_holder_htlc_transaction(htlc_tx, idx, htlc_descriptor, &self.secp)
+				.map_err(|_| "Failed to sign HTLC input")?;
+			let witness_script = htlc_descriptor.witness_script(&self.secp);
+			htlc_witnesses.push(htlc_descriptor.tx_input_witness(&htlc_sig, &witness_script));
+		}
+		Ok(htlc_witnesses)
+	}
+
+	/// Updates a transaction with the result of a successful coin selection attempt.
+	fn process_coin_selection(&self, tx: &mut Transaction, coin_selection: &CoinSelection) {
+		for utxo in coin_selection.confirmed_utxos.iter() {
+			tx.input.push(TxIn {
+				previous_output: utxo.outpoint,
+				script_sig: ScriptBuf::new(),
+				sequence: Sequence::ZERO,
+				witness: Witness::new(),
+			});
+		}
+		if let Some(change_output) = coin_selection.change_output.clone() {
+			tx.output.push(change_output);
+		} else if tx.output.is_empty() {
+			// We weren't provided a change output, likely because the input set was a perfect
+			// match, but we still need to have at least one output in the transaction for it to be
+			// considered standard. We choose to go with an empty OP_RETURN as it is the cheapest
+			// way to include a dummy output.
+			if tx.input.len() <= 1 {
+				// Transactions have to be at least 65 bytes in non-witness data, which we can run
+				// under if we have too few witness inputs.
+				log_debug!(self.logger, "Including large OP_RETURN output since an output is needed and a change output was not provided and the transaction is small");
+				debug_assert!(!tx.input.is_empty());
+				tx.output.push(TxOut {
+					value: Amount::ZERO,
+					// Minimum transaction size is 60 bytes, so we need a 5-byte script to get a
+					// 65 byte transaction. We do that as OP_RETURN <3 0 bytes, plus 1 byte len>.
+					script_pubkey: ScriptBuf::new_op_return(&[0, 0, 0]),
+				});
+				debug_assert_eq!(tx.base_size(), 65);
+			} else {
+				log_debug!(self.logger, "Including dummy OP_RETURN output since an output is needed and a change output was not provided");
+				tx.output.push(TxOut {
+					value: Amount::ZERO,
+					script_pubkey: ScriptBuf::new_op_return(&[]),
+				});
+			}
+		}
+	}
+
+	/// Handles a [`BumpTransactionEvent::ChannelClose`] event variant by producing a fully-signed
+	/// transaction spending an anchor output of the commitment transaction to bump its fee and
+	/// broadcasts them to the network as a package.
+	///
+	/// The target feerate is raised to that of any persisted previous attempt for the same claim,
+	/// such that the resulting transaction may replace any previously broadcast one.
+	async fn handle_channel_close(
+		&self, claim_id: ClaimId, package_target_feerate_sat_per_1000_weight: u32,
+		commitment_tx: &Transaction, commitment_tx_fee_sat: u64,
+		anchor_descriptor: &AnchorDescriptor,
+		sponsor: Option<&(dyn CoinSelectionSource + Send + Sync)>,
+	) -> Result<ClaimOutcome, &'static str> {
+		let package_target_feerate_sat_per_1000_weight = core::cmp::max(
+			package_target_feerate_sat_per_1000_weight,
+			self.broadcast_claim_feerate(&claim_id),
+		);
+		// Our commitment transaction already has fees allocated to it, so we should take them into
+		// account. We do so by pretending the commitment transaction's fee and weight are part of
+		// the anchor input.
+		let mut anchor_utxo = anchor_descriptor.previous_utxo();
+		let commitment_tx_fee_sat = Amount::from_sat(commitment_tx_fee_sat);
+		anchor_utxo.value += commitment_tx_fee_sat;
+		let starting_package_and_fixed_input_satisfaction_weight =
+			commitment_tx.weight().to_wu() + ANCHOR_INPUT_WITNESS_WEIGHT + EMPTY_SCRIPT_SIG_WEIGHT;
+		let mut package_and_fixed_input_satisfaction_weight =
+			starting_package_and_fixed_input_satisfaction_weight;
+
+		loop {
+			let must_spend = vec![Input {
+				outpoint: anchor_descriptor.outpoint,
+				previous_utxo: anchor_utxo.clone(),
+				satisfaction_weight: package_and_fixed_input_satisfaction_weight,
+			}];
+			let must_spend_amount =
+				must_spend.iter().map(|input| input.previous_utxo.value).sum::<Amount>();
+
+			log_debug!(self.logger, "Performing coin selection for commitment package (commitment and anchor transaction) targeting {} sat/kW",
+				package_target_feerate_sat_per_1000_weight);
+			let (coin_selection, sponsored) = self
+				.select_claim_utxos(
+					sponsor,
+					claim_id,
+					must_spend,
+					&[],
+					package_target_feerate_sat_per_1000_weight,
+				)
+				.await?;
+
+			let mut anchor_tx = Transaction {
+				version: Version::TWO,
+				lock_time: LockTime::ZERO, // TODO: Use next best height.
+				input: vec![anchor_descriptor.unsigned_tx_input()],
+				output: vec![],
+			};
+
+			let input_satisfaction_weight: u64 = coin_selection
+				.confirmed_utxos
+				.iter()
+				.map(|utxo| utxo.satisfaction_weight.to_wu())
+				.sum();
+			let total_satisfaction_weight =
+				ANCHOR_INPUT_WITNESS_WEIGHT + EMPTY_SCRIPT_SIG_WEIGHT + input_satisfaction_weight;
+			let total_input_amount = must_spend_amount
+				+ coin_selection.confirmed_utxos.iter().map(|utxo| utxo.output.value).sum();
+
+			self.process_coin_selection(&mut anchor_tx, &coin_selection);
+			let anchor_txid = anchor_tx.compute_txid();
+			let pending_spend = self.pending_claim_spend(
+				claim_id,
+				&coin_selection,
+				sponsored,
+				package_target_feerate_sat_per_1000_weight,
+				&anchor_tx,
+			);
+			let wallet_utxos = coin_selection.confirmed_utxos.clone();
 
 			// construct psbt
 			let mut anchor_psbt = Psbt::from_unsigned_tx(anchor_tx).unwrap();
@@ -754,20 +2005,62 @@ where
 			}
 
 			log_debug!(self.logger, "Signing anchor transaction {}", anchor_txid);
-			anchor_tx = self.utxo_source.sign_psbt(anchor_psbt).await?;
-
 			let signer = self
 				.signer_provider
 				.derive_channel_signer(anchor_descriptor.channel_derivation_parameters.keys_id);
 			let channel_parameters =
 				&anchor_descriptor.channel_derivation_parameters.transaction_parameters;
-			let anchor_sig = signer.sign_holder_keyed_anchor_input(
-				channel_parameters,
-				&anchor_tx,
-				0,
-				&self.secp,
-			)?;
-			anchor_tx.input[0].witness = anchor_descriptor.tx_input_witness(&anchor_sig);
+			match sponsor.filter(|_| sponsored) {
+				Some(sponsor) => {
+					// The anchor signature commits to the sponsor's inputs, which are only signed
+					// afterwards.
+					let anchor_sig = signer
+						.sign_holder_keyed_anchor_input(
+							channel_parameters,
+							&anchor_psbt.unsigned_tx,
+							0,
+							&self.secp,
+						)
+						.map_err(|_| "Failed to sign anchor input")?;
+					let anchor_witness = anchor_descriptor.tx_input_witness(&anchor_sig);
+					anchor_tx = self
+						.sign_sponsored_psbt(sponsor, anchor_psbt, vec![anchor_witness])
+						.await?;
+				},
+				None if self.external_signing_timeout_blocks.is_some() => {
+					return Ok(ClaimOutcome::AwaitingSignature(AwaitingSignature {
+						claim_id,
+						psbt: anchor_psbt,
+						claim: ExternallySignedClaim::ChannelClose {
+							commitment_tx: commitment_tx.clone(),
+							anchor_descriptor: anchor_descriptor.clone(),
+						},
+						target_feerate_sat_per_1000_weight:
+							package_target_feerate_sat_per_1000_weight,
+						fees_paid: package_fee - commitment_tx_fee_sat,
+						wallet_utxos,
+						pending_spend,
+						events: Vec::new(),
+						blocks_awaited: 0,
+					}));
+				},
+				None => {
+					anchor_tx = self
+						.utxo_source
+						.sign_psbt(anchor_psbt)
+						.await
+						.map_err(|_| "Failed to sign anchor transaction")?;
+					let anchor_sig = signer
+						.sign_holder_keyed_anchor_input(
+							channel_parameters,
+							&anchor_tx,
+							0,
+							&self.secp,
+						)
+						.map_err(|_| "Failed to sign anchor input")?;
+					anchor_tx.input[0].witness = anchor_descriptor.tx_input_witness(&anchor_sig);
+				},
+			}
 
 			#[cfg(debug_assertions)]
 			{
@@ -788,6 +2081,7 @@ where
 				assert!(package_fee >= expected_package_fee);
 			}
 
+			self.notify_claim_spend(pending_spend)?;
 			log_info!(
 				self.logger,
 				"Broadcasting anchor transaction {} to bump channel close with txid {}",
@@ -795,16 +2089,41 @@ where
 				commitment_tx.compute_txid()
 			);
 			self.broadcaster.broadcast_transactions(&[&commitment_tx, &anchor_tx]);
-			return Ok(());
-		}
+			self.persist_broadcast_claim(
+				claim_id,
+				Some(commitment_tx.compute_txid()),
+				&anchor_tx,
+				package_target_feerate_sat_per_1000_weight,
+				&wallet_utxos,
+				sponsored,
+			);
+			return Ok(ClaimOutcome::Broadcast(FeeBump::new(
+				anchor_txid,
+				package_target_feerate_sat_per_1000_weight,
+				package_fee - commitment_tx_fee_sat,
+				anchor_tx.weight(),
+				&wallet_utxos,
+				sponsored,
+			)));
+		}
 	}
 
 	/// Handles a [`BumpTransactionEvent::HTLCResolution`] event variant by producing a
 	/// fully-signed, fee-bumped HTLC transaction that is broadcast to the network.
+	///
+	/// The target feerate is raised to that of any previous attempt for the same claim, such that
+	/// the resulting transaction may replace any previously broadcast one.
 	async fn handle_htlc_resolution(
 		&self, claim_id: ClaimId, target_feerate_sat_per_1000_weight: u32,
 		htlc_descriptors: &[HTLCDescriptor], tx_lock_time: LockTime,
-	) -> Result<(), ()> {
+		sponsor: Option<&(dyn CoinSelectionSource + Send + Sync)>,
+	) -> Result<ClaimOutcome, &'static str> {
+		let target_feerate_sat_per_1000_weight = {
+			let mut htlc_claim_feerates = self.htlc_claim_feerates.lock().unwrap();
+			let feerate = htlc_claim_feerates.entry(claim_id).or_insert(0);
+			*feerate = core::cmp::max(*feerate, target_feerate_sat_per_1000_weight);
+			*feerate
+		};
 		let mut htlc_tx = Transaction {
 			version: Version::TWO,
 			lock_time: tx_lock_time,
@@ -838,13 +2157,12 @@ where
 		#[cfg(debug_assertions)]
 		let must_spend_satisfaction_weight =
 			must_spend.iter().map(|input| input.satisfaction_weight).sum::<u64>();
-		#[cfg(debug_assertions)]
 		let must_spend_amount =
 			must_spend.iter().map(|input| input.previous_utxo.value.to_sat()).sum::<u64>();
 
-		let coin_selection: CoinSelection = self
-			.utxo_source
-			.select_confirmed_utxos(
+		let (coin_selection, sponsored) = self
+			.select_claim_utxos(
+				sponsor,
 				claim_id,
 				must_spend,
 				&htlc_tx.output,
@@ -853,17 +2171,26 @@ where
 			.await?;
 
 		#[cfg(debug_assertions)]
-		let input_satisfaction_weight: u64 =
-			coin_selection.confirmed_utxos.iter().map(|utxo| utxo.satisfaction_weight).sum();
+		let input_satisfaction_weight: u64 = coin_selection
+			.confirmed_utxos
+			.iter()
+			.map(|utxo| utxo.satisfaction_weight.to_wu())
+			.sum();
 		#[cfg(debug_assertions)]
 		let total_satisfaction_weight = must_spend_satisfaction_weight + input_satisfaction_weight;
-		#[cfg(debug_assertions)]
 		let input_value: u64 =
 			coin_selection.confirmed_utxos.iter().map(|utxo| utxo.output.value.to_sat()).sum();
-		#[cfg(debug_assertions)]
 		let total_input_amount = must_spend_amount + input_value;
 
 		self.process_coin_selection(&mut htlc_tx, &coin_selection);
+		let wallet_utxos = coin_selection.confirmed_utxos.clone();
+		let pending_spend = self.pending_claim_spend(
+			claim_id,
+			&coin_selection,
+			sponsored,
+			target_feerate_sat_per_1000_weight,
+			&htlc_tx,
+		);
 
 		// construct psbt
 		let mut htlc_psbt = Psbt::from_unsigned_tx(htlc_tx).unwrap();
@@ -894,21 +2221,55 @@ where
 			"Signing HTLC transaction {}",
 			htlc_psbt.unsigned_tx.compute_txid()
 		);
-		htlc_tx = self.utxo_source.sign_psbt(htlc_psbt).await?;
+		let sponsored_psbt = match sponsor.filter(|_| sponsored) {
+			Some(sponsor) => {
+				// The HTLC signatures commit to the sponsor's inputs, which are only signed
+				// afterwards.
+				htlc_tx = htlc_psbt.unsigned_tx.clone();
+				Some((sponsor, htlc_psbt))
+			},
+			None if self.external_signing_timeout_blocks.is_some() => {
+				let output_value: u64 =
+					htlc_psbt.unsigned_tx.output.iter().map(|output| output.value.to_sat()).sum();
+				return Ok(ClaimOutcome::AwaitingSignature(AwaitingSignature {
+					claim_id,
+					psbt: htlc_psbt,
+					claim: ExternallySignedClaim::HTLCResolution {
+						htlc_descriptors: htlc_descriptors.to_vec(),
+					},
+					target_feerate_sat_per_1000_weight,
+					fees_paid: Amount::from_sat(total_input_amount - output_value),
+					wallet_utxos,
+					pending_spend,
+					events: Vec::new(),
+					blocks_awaited: 0,
+				}));
+			},
+			None => {
+				htlc_tx = self
+					.utxo_source
+					.sign_psbt(htlc_psbt)
+					.await
+					.map_err(|_| "Failed to sign HTLC transaction")?;
+				None
+			},
+		};
 
-		let mut signers = BTreeMap::new();
-		for (idx, htlc_descriptor) in htlc_descriptors.iter().enumerate() {
-			let keys_id = htlc_descriptor.channel_derivation_parameters.keys_id;
-			let signer = signers
-				.entry(keys_id)
-				.or_insert_with(|| self.signer_provider.derive_channel_signer(keys_id));
-			let htlc_sig =
-				signer.sign_holder_htlc_transaction(&htlc_tx, idx, htlc_descriptor, &self.secp)?;
-			let witness_script = htlc_descriptor.witness_script(&self.secp);
-			htlc_tx.input[idx].witness =
-				htlc_descriptor.tx_input_witness(&htlc_sig, &witness_script);
+		let htlc_witnesses = self.sign_htlc_inputs(&htlc_tx, htlc_descriptors)?;
+		match sponsored_psbt {
+			Some((sponsor, htlc_psbt)) => {
+				htlc_tx = self.sign_sponsored_psbt(sponsor, htlc_psbt, htlc_witnesses).await?;
+			},
+			None => {
+				for (input, witness) in htlc_tx.input.iter_mut().zip(htlc_witnesses) {
+					input.witness = witness;
+				}
+			},
 		}
 
+		let signed_tx_fee = total_input_amount
+			- htlc_tx.output.iter().map(|output| output.value.to_sat()).sum::<u64>();
+
 		#[cfg(debug_assertions)]
 		{
 			let signed_tx_weight = htlc_tx.weight().to_wu();
@@ -920,21 +2281,48 @@ where
 
 			let expected_signed_tx_fee =
 				fee_for_weight(target_feerate_sat_per_1000_weight, signed_tx_weight);
-			let signed_tx_fee = total_input_amount
-				- htlc_tx.output.iter().map(|output| output.value.to_sat()).sum::<u64>();
 			// Our feerate should always be at least what we were seeking. It may overshoot if
 			// the coin selector burned funds to an OP_RETURN without a change output.
 			assert!(signed_tx_fee >= expected_signed_tx_fee);
 		}
 
+		self.notify_claim_spend(pending_spend)?;
 		log_info!(self.logger, "Broadcasting {}", log_tx!(htlc_tx));
 		self.broadcaster.broadcast_transactions(&[&htlc_tx]);
-		Ok(())
+		self.persist_broadcast_claim(
+			claim_id,
+			None,
+			&htlc_tx,
+			target_feerate_sat_per_1000_weight,
+			&wallet_utxos,
+			sponsored,
+		);
+		Ok(ClaimOutcome::Broadcast(FeeBump::new(
+			htlc_tx.compute_txid(),
+			target_feerate_sat_per_1000_weight,
+			Amount::from_sat(signed_tx_fee),
+			htlc_tx.weight(),
+			&wallet_utxos,
+			sponsored,
+		)))
 	}
 
 	/// Handles all variants of [`BumpTransactionEvent`].
+	///
+	/// If a claim repeatedly fails to be fee-bumped, an [`Event::BumpTransactionFailed`] is queued,
+	/// which can be retrieved via [`EventsProvider::process_pending_events`].
+	///
+	/// If configured via [`Self::with_external_signing`], claims bumped with our own
+	/// [`CoinSelectionSource`] await signatures via [`Self::provide_signed_psbt`] rather than
+	/// being broadcast.
 	pub async fn handle_event(&self, event: &BumpTransactionEvent) {
-		match event {
+		let sponsor = match event {
+			BumpTransactionEvent::ChannelClose { channel_id, counterparty_node_id, .. }
+			| BumpTransactionEvent::HTLCResolution { channel_id, counterparty_node_id, .. } => {
+				self.fee_sponsor(channel_id, counterparty_node_id)
+			},
+		};
+		let res = match event {
 			BumpTransactionEvent::ChannelClose {
 				claim_id,
 				package_target_feerate_sat_per_1000_weight,
@@ -949,50 +2337,317 @@ where
 					log_bytes!(claim_id.0),
 					commitment_tx.compute_txid()
 				);
+				if self.awaiting_signature(*claim_id, *package_target_feerate_sat_per_1000_weight) {
+					return;
+				}
 				self.handle_channel_close(
 					*claim_id,
 					*package_target_feerate_sat_per_1000_weight,
 					commitment_tx,
 					*commitment_tx_fee_satoshis,
 					anchor_descriptor,
+					sponsor.as_deref(),
 				)
 				.await
-				.unwrap_or_else(|_| {
+				.map_err(|e| {
 					log_error!(
 						self.logger,
-						"Failed bumping commitment transaction fee for {}",
-						commitment_tx.compute_txid()
+						"Failed bumping commitment transaction fee for {}: {}",
+						commitment_tx.compute_txid(),
+						e
 					);
-				});
+					e
+				})
 			},
 			BumpTransactionEvent::HTLCResolution {
 				claim_id,
 				target_feerate_sat_per_1000_weight,
 				htlc_descriptors,
 				tx_lock_time,
+				blocks_until_expiry,
 				..
 			} => {
 				log_info!(
 					self.logger,
-					"Handling HTLC bump (claim_id = {}, htlcs_to_claim = {})",
+					"Handling HTLC bump (claim_id = {}, htlcs_to_claim = {}, blocks_until_expiry = {})",
 					log_bytes!(claim_id.0),
-					log_iter!(htlc_descriptors.iter().map(|d| d.outpoint()))
+					log_iter!(htlc_descriptors.iter().map(|d| d.outpoint())),
+					blocks_until_expiry
 				);
+				let target_feerate_sat_per_1000_weight =
+					self.htlc_deadline_schedule.feerate_for_deadline(
+						*target_feerate_sat_per_1000_weight,
+						*blocks_until_expiry,
+					);
+				if self.awaiting_signature(*claim_id, target_feerate_sat_per_1000_weight) {
+					return;
+				}
 				self.handle_htlc_resolution(
 					*claim_id,
-					*target_feerate_sat_per_1000_weight,
+					target_feerate_sat_per_1000_weight,
 					htlc_descriptors,
 					*tx_lock_time,
+					sponsor.as_deref(),
 				)
 				.await
-				.unwrap_or_else(|_| {
+				.map_err(|e| {
 					log_error!(
 						self.logger,
-						"Failed bumping HTLC transaction fee for commitment {}",
-						htlc_descriptors[0].commitment_txid
+						"Failed bumping HTLC transaction fee for commitment {}: {}",
+						htlc_descriptors[0].commitment_txid,
+						e
 					);
+					e
+				})
+			},
+		};
+		match res {
+			Ok(ClaimOutcome::Broadcast(fee_bump)) => {
+				self.track_claim_result(event, Ok(self.audit_fee_bump(event, &fee_bump)))
+			},
+			Ok(ClaimOutcome::AwaitingSignature(awaiting)) => {
+				self.await_signature(awaiting, vec![event.clone()])
+			},
+			Err(e) => self.track_claim_result(event, Err(e)),
+		}
+	}
+
+	/// Handles a set of [`BumpTransactionEvent`]s, aggregating HTLC claims across channels where
+	/// possible.
+	///
+	/// [`BumpTransactionEvent::HTLCResolution`]s sharing the same `tx_lock_time`, which may
+	/// originate from different channels, are resolved in a single transaction spending one set of
+	/// wallet inputs and producing one change output, targeting the highest feerate among them
+	/// after escalating each according to the [`HTLCDeadlineFeerateSchedule`].
+	/// Since all HTLC signatures use `SIGHASH_SINGLE|SIGHASH_ANYONECANPAY`, each HTLC input remains
+	/// valid alongside the others. A failure to bump an aggregated transaction is attributed to each
+	/// of the claims it includes. All other events, including claims sponsored via
+	/// [`Self::register_fee_sponsor`], are handled as in [`Self::handle_event`].
+	///
+	/// Note that each [`ChannelMonitor`] continues to track its claims individually, so later
+	/// events for the same HTLCs may replace the aggregated transaction with a per-channel one.
+	///
+	/// [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+	pub async fn handle_events_aggregated(&self, events: &[BumpTransactionEvent]) {
+		let mut htlc_batches: Vec<(LockTime, Vec<&BumpTransactionEvent>)> = Vec::new();
+		for event in events {
+			match event {
+				BumpTransactionEvent::ChannelClose { .. } => self.handle_event(event).await,
+				// Sponsored claims are bumped with the sponsor's UTXOs, so they can't share a
+				// transaction with other claims.
+				BumpTransactionEvent::HTLCResolution {
+					channel_id, counterparty_node_id, ..
+				} if self.fee_sponsor(channel_id, counterparty_node_id).is_some() => {
+					self.handle_event(event).await
+				},
+				BumpTransactionEvent::HTLCResolution { tx_lock_time, .. } => {
+					match htlc_batches.iter_mut().find(|(lock_time, _)| lock_time == tx_lock_time) {
+						Some((_, batch)) => batch.push(event),
+						None => htlc_batches.push((*tx_lock_time, vec![event])),
+					}
+				},
+			}
+		}
+
+		for (tx_lock_time, batch) in htlc_batches {
+			if batch.len() == 1 {
+				self.handle_event(batch[0]).await;
+				continue;
+			}
+			let mut claim_ids = Vec::with_capacity(batch.len());
+			let mut batch_feerate_sat_per_1000_weight = 0;
+			let mut batch_htlc_descriptors = Vec::new();
+			for event in batch.iter() {
+				if let BumpTransactionEvent::HTLCResolution {
+					claim_id,
+					target_feerate_sat_per_1000_weight,
+					htlc_descriptors,
+					blocks_until_expiry,
+					..
+				} = event
+				{
+					claim_ids.push(*claim_id);
+					let target_feerate_sat_per_1000_weight =
+						self.htlc_deadline_schedule.feerate_for_deadline(
+							*target_feerate_sat_per_1000_weight,
+							*blocks_until_expiry,
+						);
+					batch_feerate_sat_per_1000_weight = core::cmp::max(
+						batch_feerate_sat_per_1000_weight,
+						target_feerate_sat_per_1000_weight,
+					);
+					batch_htlc_descriptors.extend_from_slice(htlc_descriptors);
+				}
+			}
+			// Commit to the set of aggregated claims such that coin selection can reuse the same
+			// UTXOs when the same set of claims is aggregated again.
+			claim_ids.sort_unstable_by_key(|claim_id| claim_id.0);
+			let mut engine = Sha256::engine();
+			for claim_id in claim_ids.iter() {
+				engine.input(&claim_id.0);
+			}
+			let batch_claim_id = ClaimId(Sha256::from_engine(engine).to_byte_array());
+			log_info!(
+				self.logger,
+				"Handling aggregated HTLC bump (claim_id = {}, claims = {}, htlcs_to_claim = {})",
+				log_bytes!(batch_claim_id.0),
+				log_iter!(claim_ids.iter().map(|claim_id| log_bytes!(claim_id.0))),
+				log_iter!(batch_htlc_descriptors.iter().map(|d| d.outpoint()))
+			);
+			if self.awaiting_signature(batch_claim_id, batch_feerate_sat_per_1000_weight) {
+				continue;
+			}
+			let res = self
+				.handle_htlc_resolution(
+					batch_claim_id,
+					batch_feerate_sat_per_1000_weight,
+					&batch_htlc_descriptors,
+					tx_lock_time,
+					None,
+				)
+				.await
+				.map_err(|e| {
+					log_error!(
+						self.logger,
+						"Failed bumping aggregated HTLC transaction fee for {} claims: {}",
+						claim_ids.len(),
+						e
+					);
+					e
 				});
+			let fee_bump = match res {
+				Ok(ClaimOutcome::Broadcast(fee_bump)) => Ok(fee_bump),
+				Ok(ClaimOutcome::AwaitingSignature(awaiting)) => {
+					self.await_signature(awaiting, batch.into_iter().cloned().collect());
+					continue;
+				},
+				Err(e) => Err(e),
+			};
+			for event in batch {
+				let res = fee_bump.as_ref().map(|fee_bump| self.audit_fee_bump(event, fee_bump));
+				self.track_claim_result(event, res.map_err(|e| *e));
+			}
+		}
+	}
+
+	/// Records the fees paid by a successful fee bump of the claim of `event` to the
+	/// [`ReserveAuditLogger`], if any.
+	///
+	/// Fee bumps paid for by a sponsor are not recorded, as they don't draw on our reserve.
+	fn audit_fee_bump(&self, event: &BumpTransactionEvent, fee_bump: &FeeBump) {
+		let audit_logger = match &self.audit_logger {
+			Some(audit_logger) => audit_logger,
+			None => return,
+		};
+		if fee_bump.sponsored {
+			return;
+		}
+		let (channel_id, decision) = match event {
+			BumpTransactionEvent::ChannelClose {
+				channel_id,
+				claim_id,
+				commitment_tx,
+				commitment_tx_fee_satoshis,
+				..
+			} => {
+				let decision = ReserveAuditDecision::CommitmentFeeBumped {
+					commitment_txid: commitment_tx.compute_txid(),
+					anchor_txid: fee_bump.txid,
+					commitment_tx_fee: Amount::from_sat(*commitment_tx_fee_satoshis),
+					target_feerate_sat_per_1000_weight: fee_bump.target_feerate_sat_per_1000_weight,
+					claim_id: Some(*claim_id),
+					anchor_tx_weight: Some(fee_bump.tx_weight),
+				};
+				(*channel_id, decision)
 			},
+			BumpTransactionEvent::HTLCResolution { channel_id, claim_id, .. } => {
+				let decision = ReserveAuditDecision::HtlcFeeBumped {
+					htlc_txid: fee_bump.txid,
+					target_feerate_sat_per_1000_weight: fee_bump.target_feerate_sat_per_1000_weight,
+					claim_id: Some(*claim_id),
+					htlc_tx_weight: Some(fee_bump.tx_weight),
+				};
+				(*channel_id, decision)
+			},
+		};
+		audit_logger.log_entry(ReserveAuditEntry::new(
+			channel_id,
+			decision,
+			None,
+			fee_bump.wallet_utxos.clone(),
+			fee_bump.wallet_utxos_value,
+			fee_bump.fees_paid,
+		));
+	}
+
+	/// Tracks consecutive failures per claim, queueing an [`Event::BumpTransactionFailed`] when
+	/// needed.
+	fn track_claim_result(&self, event: &BumpTransactionEvent, res: Result<(), &'static str>) {
+		let (channel_id, counterparty_node_id, claim_id) = match event {
+			BumpTransactionEvent::ChannelClose {
+				channel_id,
+				counterparty_node_id,
+				claim_id,
+				..
+			}
+			| BumpTransactionEvent::HTLCResolution {
+				channel_id,
+				counterparty_node_id,
+				claim_id,
+				..
+			} => (*channel_id, *counterparty_node_id, *claim_id),
+		};
+		let mut failed_claims = self.failed_claims.lock().unwrap();
+		let err = match res {
+			Ok(()) => {
+				failed_claims.remove(&claim_id);
+				return;
+			},
+			Err(err) => err,
+		};
+		let failed_claim = failed_claims.entry(claim_id).or_insert_with(FailedClaim::new);
+		if failed_claim.record_failure() {
+			let (amount_at_risk_msat, deadline_height) = event.htlcs_at_risk();
+			log_error!(
+				self.logger,
+				"Failed bumping claim {} for channel {} {} consecutive times",
+				log_bytes!(claim_id.0),
+				channel_id,
+				failed_claim.consecutive_failures
+			);
+			self.pending_events.lock().unwrap().push(Event::BumpTransactionFailed {
+				channel_id,
+				counterparty_node_id,
+				claim_id,
+				claim_type: event.claim_type(),
+				amount_at_risk_msat,
+				deadline_height,
+				consecutive_failures: failed_claim.consecutive_failures,
+				error: err.to_string(),
+			});
+		}
+	}
+}
+
+impl<B: Deref, C: Deref, SP: Deref, L: Deref> EventsProvider
+	for BumpTransactionEventHandler<B, C, SP, L>
+where
+	B::Target: BroadcasterInterface,
+	C::Target: CoinSelectionSource,
+	SP::Target: SignerProvider,
+	L::Target: Logger,
+{
+	/// Processes any [`Event::BumpTransactionFailed`]s generated since the last call.
+	///
+	/// Events are not replayed if the handler fails to handle them, as they'll be regenerated upon
+	/// further failed attempts to bump the claim.
+	fn process_pending_events<H: Deref>(&self, handler: H)
+	where
+		H::Target: EventHandler,
+	{
+		let pending_events = core::mem::take(&mut *self.pending_events.lock().unwrap());
+		for event in pending_events {
+			let _ = handler.handle_event(event);
 		}
 	}
 }
@@ -1002,18 +2657,30 @@ mod tests {
 	use super::*;
 
 	use crate::events::bump_transaction::sync::{
-		BumpTransactionEventHandlerSync, CoinSelectionSourceSync,
+		BumpTransactionEventHandlerSync, CoinSelectionSourceSync, WalletSourceSync, WalletSync,
 	};
 	use crate::io::Cursor;
 	use crate::ln::chan_utils::ChannelTransactionParameters;
 	use crate::sign::KeysManager;
 	use crate::types::features::ChannelTypeFeatures;
+	use crate::types::payment::PaymentHash;
+	use crate::util::anchor_channel_reserves::{
+		htlc_timeout_transaction_weight, AnchorChannelReserveContext, ReserveAuditLog,
+		WalletInputType,
+	};
 	use crate::util::ser::Readable;
 	use crate::util::test_utils::{TestBroadcaster, TestLogger};
 
 	use bitcoin::hashes::Hash;
 	use bitcoin::hex::FromHex;
+	use bitcoin::script::{Builder, PushBytesBuf};
+	use bitcoin::secp256k1::SecretKey;
 	use bitcoin::{Network, ScriptBuf, Transaction, Txid};
+	use core::cell::RefCell;
+	use core::future::Future;
+	use core::pin::Pin;
+	use core::sync::atomic::{AtomicBool, Ordering};
+	use core::task;
 
 	struct TestCoinSelectionSource {
 		// (commitment + anchor value, commitment + input weight, target feerate, result)
@@ -1050,8 +2717,7 @@ mod tests {
 		}
 	}
 
-	#[test]
-	fn test_op_return_under_funds() {
+	fn op_return_under_funds_claim() -> (TestCoinSelectionSource, BumpTransactionEvent) {
 		// Test what happens if we have to select coins but the anchor output value itself suffices
 		// to pay the required fee.
 		//
@@ -1075,7 +2741,6 @@ mod tests {
 				.weight()
 				.to_wu();
 
-		let broadcaster = TestBroadcaster::new(Network::Testnet);
 		let source = TestCoinSelectionSource {
 			expected_selects: Mutex::new(vec![
 				(
@@ -1095,22 +2760,20 @@ mod tests {
 								value: Amount::from_sat(200),
 								script_pubkey: ScriptBuf::new(),
 							},
-							satisfaction_weight: 5, // Just the script_sig and witness lengths
+							// Just the script_sig and witness lengths
+							satisfaction_weight: Weight::from_wu(5),
 						}],
 						change_output: None,
 					},
 				),
 			]),
 		};
-		let signer = KeysManager::new(&[42; 32], 42, 42);
-		let logger = TestLogger::new();
-		let handler = BumpTransactionEventHandlerSync::new(&broadcaster, &source, &signer, &logger);
 
 		let mut transaction_parameters = ChannelTransactionParameters::test_dummy(42_000_000);
 		transaction_parameters.channel_type_features =
 			ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
 
-		handler.handle_event(&BumpTransactionEvent::ChannelClose {
+		let event = BumpTransactionEvent::ChannelClose {
 			channel_id: ChannelId([42; 32]),
 			counterparty_node_id: PublicKey::from_slice(&[2; 33]).unwrap(),
 			claim_id: ClaimId([42; 32]),
@@ -1126,6 +2789,975 @@ mod tests {
 				outpoint: OutPoint { txid: Txid::from_byte_array([42; 32]), vout: 0 },
 			},
 			pending_htlcs: Vec::new(),
+		};
+		(source, event)
+	}
+
+	#[test]
+	fn test_op_return_under_funds() {
+		let (source, event) = op_return_under_funds_claim();
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let schedule = HTLCDeadlineFeerateSchedule::default();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &source, &signer, &logger, schedule);
+		handler.handle_event(&event);
+	}
+
+	#[test]
+	fn test_audit_log_records_anchor_fee_bump() {
+		let (source, event) = op_return_under_funds_claim();
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let audit_log = Arc::new(ReserveAuditLog::new(10));
+		let schedule = HTLCDeadlineFeerateSchedule::default();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &source, &signer, &logger, schedule)
+				.with_audit_logger(
+					Arc::clone(&audit_log) as Arc<dyn ReserveAuditLogger + Send + Sync>
+				);
+		handler.handle_event(&event);
+
+		let txn = broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+		assert_eq!(txn.len(), 2);
+		let entries = audit_log.entries();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(entries[0].channel_id, ChannelId([42; 32]));
+		assert_eq!(
+			entries[0].decision,
+			ReserveAuditDecision::CommitmentFeeBumped {
+				commitment_txid: txn[0].compute_txid(),
+				anchor_txid: txn[1].compute_txid(),
+				commitment_tx_fee: Amount::from_sat(930),
+				target_feerate_sat_per_1000_weight: 868,
+				claim_id: Some(ClaimId([42; 32])),
+				anchor_tx_weight: Some(txn[1].weight()),
+			}
+		);
+		assert_eq!(entries[0].reserve_per_channel, None);
+		let wallet_outpoint = OutPoint { txid: Txid::from_byte_array([44; 32]), vout: 0 };
+		assert_eq!(entries[0].utxos_considered, vec![wallet_outpoint]);
+		assert_eq!(entries[0].utxos_value, Amount::from_sat(200));
+		// The anchor transaction burns the anchor output and the wallet input to an OP_RETURN.
+		assert_eq!(entries[0].fees_paid, Amount::from_sat(330 + 200));
+	}
+
+	struct TestFeeSponsor {
+		source: TestCoinSelectionSource,
+		fail_selection: bool,
+		modify_tx: bool,
+	}
+	impl CoinSelectionSourceSync for TestFeeSponsor {
+		fn select_confirmed_utxos(
+			&self, claim_id: ClaimId, must_spend: Vec<Input>, must_pay_to: &[TxOut],
+			target_feerate_sat_per_1000_weight: u32,
+		) -> Result<CoinSelection, ()> {
+			if self.fail_selection {
+				return Err(());
+			}
+			self.source.select_confirmed_utxos(
+				claim_id,
+				must_spend,
+				must_pay_to,
+				target_feerate_sat_per_1000_weight,
+			)
+		}
+		fn sign_psbt(&self, psbt: Psbt) -> Result<Transaction, ()> {
+			// The anchor input must already be signed by the time the sponsor is asked to sign.
+			assert!(psbt.inputs[0].final_script_witness.is_some());
+			let mut tx = self.source.sign_psbt(psbt)?;
+			if self.modify_tx {
+				tx.output.push(TxOut { value: Amount::ZERO, script_pubkey: ScriptBuf::new() });
+			}
+			Ok(tx)
+		}
+	}
+
+	fn sponsored_claim_handler_txn(
+		local_source: TestCoinSelectionSource, sponsor: TestFeeSponsor, event: BumpTransactionEvent,
+	) -> Vec<Transaction> {
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let schedule = HTLCDeadlineFeerateSchedule::default();
+		let handler = BumpTransactionEventHandlerSync::new(
+			&broadcaster,
+			&local_source,
+			&signer,
+			&logger,
+			schedule,
+		);
+		let counterparty_node_id = PublicKey::from_slice(&[2; 33]).unwrap();
+		handler.register_fee_sponsor(
+			FeeSponsorScope::Counterparty(counterparty_node_id),
+			Arc::new(sponsor) as Arc<dyn CoinSelectionSourceSync + Send + Sync>,
+		);
+		handler.handle_event(&event);
+		broadcaster.txn_broadcasted.lock().unwrap().split_off(0)
+	}
+
+	#[test]
+	fn test_fee_sponsor_provides_anchor_utxos() {
+		// The sponsor's UTXOs are used to bump the commitment and our wallet is never consulted.
+		let (source, event) = op_return_under_funds_claim();
+		let local_source = TestCoinSelectionSource { expected_selects: Mutex::new(Vec::new()) };
+		let sponsor = TestFeeSponsor { source, fail_selection: false, modify_tx: false };
+		let txn = sponsored_claim_handler_txn(local_source, sponsor, event);
+		assert_eq!(txn.len(), 2);
+		let anchor_tx = &txn[1];
+		assert_eq!(anchor_tx.input.len(), 2);
+		assert_eq!(anchor_tx.input[0].previous_output.txid, Txid::from_byte_array([42; 32]));
+		assert_eq!(anchor_tx.input[1].previous_output.txid, Txid::from_byte_array([44; 32]));
+		// Our anchor witness is kept, not whatever the sponsor placed there.
+		assert_ne!(anchor_tx.input[0].witness, Witness::from_slice(&[vec![42; 162]]));
+	}
+
+	#[test]
+	fn test_fee_sponsor_selection_failure_falls_back_to_wallet() {
+		let (source, event) = op_return_under_funds_claim();
+		let sponsor = TestFeeSponsor {
+			source: TestCoinSelectionSource { expected_selects: Mutex::new(Vec::new()) },
+			fail_selection: true,
+			modify_tx: false,
+		};
+		let txn = sponsored_claim_handler_txn(source, sponsor, event);
+		assert_eq!(txn.len(), 2);
+	}
+
+	#[test]
+	fn test_fee_sponsor_modifying_transaction_is_rejected() {
+		let (source, event) = op_return_under_funds_claim();
+		let local_source = TestCoinSelectionSource { expected_selects: Mutex::new(Vec::new()) };
+		let sponsor = TestFeeSponsor { source, fail_selection: false, modify_tx: true };
+		let txn = sponsored_claim_handler_txn(local_source, sponsor, event);
+		assert!(txn.is_empty());
+	}
+
+	/// Returns `Poll::Pending` the first time it's polled, simulating an await point in an async
+	/// wallet backend.
+	struct YieldOnce(bool);
+	impl Future for YieldOnce {
+		type Output = ();
+		fn poll(mut self: Pin<&mut Self>, cx: &mut task::Context<'_>) -> task::Poll<()> {
+			if self.0 {
+				task::Poll::Ready(())
+			} else {
+				self.0 = true;
+				cx.waker().wake_by_ref();
+				task::Poll::Pending
+			}
+		}
+	}
+
+	struct YieldingCoinSelectionSource(TestCoinSelectionSource);
+	impl CoinSelectionSource for YieldingCoinSelectionSource {
+		fn select_confirmed_utxos<'a>(
+			&'a self, claim_id: ClaimId, must_spend: Vec<Input>, must_pay_to: &'a [TxOut],
+			target_feerate_sat_per_1000_weight: u32,
+		) -> AsyncResult<'a, CoinSelection> {
+			Box::pin(async move {
+				YieldOnce(false).await;
+				CoinSelectionSourceSync::select_confirmed_utxos(
+					&self.0,
+					claim_id,
+					must_spend,
+					must_pay_to,
+					target_feerate_sat_per_1000_weight,
+				)
+			})
+		}
+		fn sign_psbt<'a>(&'a self, psbt: Psbt) -> AsyncResult<'a, Transaction> {
+			Box::pin(async move {
+				YieldOnce(false).await;
+				CoinSelectionSourceSync::sign_psbt(&self.0, psbt)
+			})
+		}
+	}
+
+	#[test]
+	fn test_async_coin_selection_with_await_points() {
+		// Ensure claims are still broadcast if the coin selection source is asynchronous and
+		// suspends while selecting coins and signing.
+		let (source, event) = op_return_under_funds_claim();
+		let source = YieldingCoinSelectionSource(source);
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let schedule = HTLCDeadlineFeerateSchedule::default();
+		let handler =
+			BumpTransactionEventHandler::new(&broadcaster, &source, &signer, &logger, schedule);
+
+		let mut fut = Box::pin(handler.handle_event(&event));
+		let waker = dummy_waker();
+		let mut ctx = task::Context::from_waker(&waker);
+		let mut pending_polls = 0;
+		while let task::Poll::Pending = fut.as_mut().poll(&mut ctx) {
+			pending_polls += 1;
+		}
+		// Coin selection is performed twice and signing once, each yielding once.
+		assert_eq!(pending_polls, 3);
+
+		let txn = broadcaster.txn_broadcast();
+		assert_eq!(txn.len(), 2);
+		if let BumpTransactionEvent::ChannelClose { commitment_tx, .. } = &event {
+			assert_eq!(&txn[0], commitment_tx);
+			assert_eq!(txn[1].input[0].previous_output.txid, commitment_tx.compute_txid());
+		}
+		assert!(handler.failed_claims.lock().unwrap().is_empty());
+	}
+	struct FailingCoinSelectionSource;
+	impl CoinSelectionSourceSync for FailingCoinSelectionSource {
+		fn select_confirmed_utxos(
+			&self, _claim_id: ClaimId, _must_spend: Vec<Input>, _must_pay_to: &[TxOut],
+			_target_feerate_sat_per_1000_weight: u32,
+		) -> Result<CoinSelection, ()> {
+			Err(())
+		}
+		fn sign_psbt(&self, _psbt: Psbt) -> Result<Transaction, ()> {
+			unreachable!()
+		}
+	}
+
+	#[test]
+	fn test_bump_transaction_failed_event() {
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let source = FailingCoinSelectionSource;
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let schedule = HTLCDeadlineFeerateSchedule::default();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &source, &signer, &logger, schedule);
+
+		let mut transaction_parameters = ChannelTransactionParameters::test_dummy(42_000_000);
+		transaction_parameters.channel_type_features =
+			ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		let pending_htlc = |amount_msat, cltv_expiry| HTLCOutputInCommitment {
+			offered: false,
+			amount_msat,
+			cltv_expiry,
+			payment_hash: PaymentHash([1; 32]),
+			transaction_output_index: None,
+		};
+		let event = BumpTransactionEvent::ChannelClose {
+			channel_id: ChannelId([42; 32]),
+			counterparty_node_id: PublicKey::from_slice(&[2; 33]).unwrap(),
+			claim_id: ClaimId([42; 32]),
+			package_target_feerate_sat_per_1000_weight: 868,
+			commitment_tx_fee_satoshis: 930,
+			commitment_tx: Transaction {
+				version: Version::TWO,
+				lock_time: LockTime::ZERO,
+				input: vec![],
+				output: vec![],
+			},
+			anchor_descriptor: AnchorDescriptor {
+				channel_derivation_parameters: ChannelDerivationParameters {
+					value_satoshis: 42_000_000,
+					keys_id: [42; 32],
+					transaction_parameters,
+				},
+				outpoint: OutPoint { txid: Txid::from_byte_array([42; 32]), vout: 0 },
+			},
+			pending_htlcs: vec![pending_htlc(10_000, 800), pending_htlc(20_000, 700)],
+		};
+
+		let collect_events = |handler: &BumpTransactionEventHandlerSync<_, _, _, _>| {
+			let events = RefCell::new(Vec::new());
+			handler.process_pending_events(&|e| Ok(events.borrow_mut().push(e)));
+			events.into_inner()
+		};
+
+		// No event is generated before the failure threshold is reached.
+		for _ in 1..FAILURES_BEFORE_FAILED_EVENT {
+			handler.handle_event(&event);
+		}
+		assert!(collect_events(&handler).is_empty());
+
+		handler.handle_event(&event);
+		let events = collect_events(&handler);
+		assert_eq!(events.len(), 1);
+		match &events[0] {
+			Event::BumpTransactionFailed {
+				channel_id,
+				claim_id,
+				claim_type,
+				amount_at_risk_msat,
+				deadline_height,
+				consecutive_failures,
+				error,
+				..
+			} => {
+				assert_eq!(*channel_id, ChannelId([42; 32]));
+				assert_eq!(*claim_id, ClaimId([42; 32]));
+				assert_eq!(*claim_type, BumpTransactionClaimType::AnchorCPFP);
+				assert_eq!(*amount_at_risk_msat, 30_000);
+				assert_eq!(*deadline_height, Some(700));
+				assert_eq!(*consecutive_failures, FAILURES_BEFORE_FAILED_EVENT);
+				assert_eq!(error, "Coin selection failed");
+			},
+			_ => panic!("Unexpected event"),
+		}
+
+		// The event is rate limited, with the interval between events decreasing over time.
+		let mut event_failures = Vec::new();
+		for failures in FAILURES_BEFORE_FAILED_EVENT + 1..=40 {
+			handler.handle_event(&event);
+			if !collect_events(&handler).is_empty() {
+				event_failures.push(failures);
+			}
+		}
+		assert_eq!(event_failures, vec![19, 27, 31, 33, 35, 37, 39]);
+	}
+
+	struct RecordingCoinSelectionSource {
+		// (claim ID, must spend outpoints, target feerate)
+		selects: Mutex<Vec<(ClaimId, Vec<OutPoint>, u32)>>,
+	}
+	impl CoinSelectionSource for RecordingCoinSelectionSource {
+		fn select_confirmed_utxos<'a>(
+			&'a self, claim_id: ClaimId, must_spend: Vec<Input>, _must_pay_to: &'a [TxOut],
+			target_feerate_sat_per_1000_weight: u32,
+		) -> AsyncResult<'a, CoinSelection> {
+			let outpoints = must_spend.iter().map(|input| input.outpoint).collect();
+			let select = (claim_id, outpoints, target_feerate_sat_per_1000_weight);
+			self.selects.lock().unwrap().push(select);
+			Box::pin(async move { Err(()) })
+		}
+		fn sign_psbt<'a>(&'a self, _psbt: Psbt) -> AsyncResult<'a, Transaction> {
+			unreachable!()
+		}
+	}
+
+	fn htlc_resolution_event(
+		channel: u8, target_feerate_sat_per_1000_weight: u32, tx_lock_time: u32,
+		blocks_until_expiry: u32,
+	) -> BumpTransactionEvent {
+		let mut transaction_parameters = ChannelTransactionParameters::test_dummy(42_000_000);
+		transaction_parameters.channel_type_features =
+			ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		let secp = Secp256k1::new();
+		let per_commitment_secret = SecretKey::from_slice(&[channel; 32]).unwrap();
+		let htlc_descriptor = HTLCDescriptor {
+			channel_derivation_parameters: ChannelDerivationParameters {
+				value_satoshis: 42_000_000,
+				keys_id: [channel; 32],
+				transaction_parameters,
+			},
+			commitment_txid: Txid::from_byte_array([channel; 32]),
+			per_commitment_number: 0,
+			per_commitment_point: PublicKey::from_secret_key(&secp, &per_commitment_secret),
+			feerate_per_kw: 0,
+			htlc: HTLCOutputInCommitment {
+				offered: true,
+				amount_msat: 10_000_000,
+				cltv_expiry: tx_lock_time,
+				payment_hash: PaymentHash([channel; 32]),
+				transaction_output_index: Some(0),
+			},
+			preimage: None,
+			counterparty_sig: Signature::from_compact(&[1; 64]).unwrap(),
+		};
+		BumpTransactionEvent::HTLCResolution {
+			channel_id: ChannelId([channel; 32]),
+			counterparty_node_id: PublicKey::from_slice(&[2; 33]).unwrap(),
+			claim_id: ClaimId([channel; 32]),
+			target_feerate_sat_per_1000_weight,
+			htlc_descriptors: vec![htlc_descriptor],
+			tx_lock_time: LockTime::from_consensus(tx_lock_time),
+			blocks_until_expiry,
+		}
+	}
+
+	#[test]
+	fn test_htlc_resolution_aggregated_across_channels() {
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let source = RecordingCoinSelectionSource { selects: Mutex::new(Vec::new()) };
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let schedule = HTLCDeadlineFeerateSchedule::default();
+		let handler =
+			BumpTransactionEventHandler::new(&broadcaster, &source, &signer, &logger, schedule);
+
+		// HTLC claims from channels 1 and 2 share a locktime and are aggregated, while the claim
+		// from channel 3 must be handled on its own.
+		let events = vec![
+			htlc_resolution_event(1, 1000, 500, 100),
+			htlc_resolution_event(2, 2000, 500, 100),
+			htlc_resolution_event(3, 3000, 600, 100),
+		];
+		let mut fut = Box::pin(handler.handle_events_aggregated(&events));
+		let waker = dummy_waker();
+		let mut ctx = task::Context::from_waker(&waker);
+		assert!(fut.as_mut().poll(&mut ctx).is_ready());
+
+		let selects = source.selects.lock().unwrap();
+		assert_eq!(selects.len(), 2);
+		let (batch_claim_id, batch_outpoints, batch_feerate) = &selects[0];
+		assert_ne!(*batch_claim_id, ClaimId([1; 32]));
+		assert_ne!(*batch_claim_id, ClaimId([2; 32]));
+		assert_eq!(
+			*batch_outpoints,
+			vec![
+				OutPoint { txid: Txid::from_byte_array([1; 32]), vout: 0 },
+				OutPoint { txid: Txid::from_byte_array([2; 32]), vout: 0 },
+			]
+		);
+		assert_eq!(*batch_feerate, 2000);
+		assert_eq!(
+			selects[1],
+			(
+				ClaimId([3; 32]),
+				vec![OutPoint { txid: Txid::from_byte_array([3; 32]), vout: 0 }],
+				3000
+			)
+		);
+
+		// The failure of the aggregated claim is attributed to each of the original claims.
+		let failed_claims = handler.failed_claims.lock().unwrap();
+		assert_eq!(failed_claims.len(), 3);
+		for channel in 1..=3 {
+			assert_eq!(failed_claims.get(&ClaimId([channel; 32])).unwrap().consecutive_failures, 1);
+		}
+	}
+	#[test]
+	fn test_htlc_resolution_feerate_escalates_towards_deadline() {
+		// As the deadline of an HTLC claim approaches, its feerate is scaled towards the maximum of
+		// the schedule, never going below that of a previous attempt.
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let source = RecordingCoinSelectionSource { selects: Mutex::new(Vec::new()) };
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let logger = TestLogger::new();
+		let schedule = HTLCDeadlineFeerateSchedule {
+			escalation_start_blocks: 10,
+			max_feerate_blocks: 2,
+			max_feerate_sat_per_1000_weight: 5000,
+		};
+		let handler =
+			BumpTransactionEventHandler::new(&broadcaster, &source, &signer, &logger, schedule);
+		let waker = dummy_waker();
+		let mut ctx = task::Context::from_waker(&waker);
+
+		// The target feerate of the events briefly rises at 12 blocks left, after which the
+		// following attempts stick to it until the escalation exceeds it.
+		for blocks_until_expiry in (0..=14).rev() {
+			let target_feerate = if blocks_until_expiry == 12 { 1200 } else { 1000 };
+			let event = htlc_resolution_event(1, target_feerate, 500, blocks_until_expiry);
+			assert!(Box::pin(handler.handle_event(&event)).as_mut().poll(&mut ctx).is_ready());
+		}
+		// A target feerate above the maximum of the schedule is not lowered.
+		let event = htlc_resolution_event(1, 6000, 500, 0);
+		assert!(Box::pin(handler.handle_event(&event)).as_mut().poll(&mut ctx).is_ready());
+
+		let selects = source.selects.lock().unwrap();
+		let feerates: Vec<u32> = selects.iter().map(|(_, _, feerate)| *feerate).collect();
+		assert_eq!(
+			feerates,
+			vec![
+				1000, 1000, 1200, 1200, 1200, 1500, 2000, 2500, 3000, 3500, 4000, 4500, 5000, 5000,
+				5000, 6000
+			]
+		);
+		assert!(feerates.windows(2).all(|feerates| feerates[0] <= feerates[1]));
+	}
+
+	struct ChangeScriptWalletSource {
+		utxos: Vec<Utxo>,
+		change_script: ScriptBuf,
+	}
+	impl WalletSourceSync for ChangeScriptWalletSource {
+		fn list_confirmed_utxos(&self) -> Result<Vec<Utxo>, ()> {
+			Ok(self.utxos.clone())
+		}
+		fn get_change_script(&self) -> Result<ScriptBuf, ()> {
+			Ok(self.change_script.clone())
+		}
+		fn sign_psbt(&self, psbt: Psbt) -> Result<Transaction, ()> {
+			let mut tx = psbt.unsigned_tx;
+			for input in tx.input.iter_mut() {
+				if self.utxos.iter().any(|utxo| utxo.outpoint == input.previous_output) {
+					// A P2WPKH witness with a low-R signature.
+					input.witness = Witness::from_slice(&[vec![42; 72], vec![42; 33]]);
+				}
+			}
+			Ok(tx)
+		}
+	}
+
+	fn do_test_htlc_claim_fee_with_change_script(taproot_change: bool) {
+		// The fee paid for an HTLC claim accounts for the change output the wallet actually
+		// provides, and is recorded with the claim such that it can be reconciled exactly.
+		let secp_ctx = Secp256k1::new();
+		let pubkey =
+			PublicKey::from_secret_key(&secp_ctx, &SecretKey::from_slice(&[42; 32]).unwrap());
+		let pubkey_hash = WPubkeyHash::hash(&pubkey.serialize());
+		let change_script = if taproot_change {
+			let output_key =
+				TweakedPublicKey::dangerous_assume_tweaked(pubkey.x_only_public_key().0);
+			ScriptBuf::new_p2tr_tweaked(output_key)
+		} else {
+			ScriptBuf::new_p2wpkh(&pubkey_hash)
+		};
+		let wallet_outpoint = OutPoint { txid: Txid::from_byte_array([44; 32]), vout: 0 };
+		let wallet_utxo_value = Amount::from_sat(1_000_000);
+		let source = ChangeScriptWalletSource {
+			utxos: vec![Utxo::new_p2wpkh(wallet_outpoint, wallet_utxo_value, &pubkey_hash)],
+			change_script,
+		};
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let logger = TestLogger::new();
+		let wallet = WalletSync::new(&source, &logger);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let audit_log = Arc::new(ReserveAuditLog::new(10));
+		let schedule = HTLCDeadlineFeerateSchedule::disabled();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &wallet, &signer, &logger, schedule)
+				.with_audit_logger(
+					Arc::clone(&audit_log) as Arc<dyn ReserveAuditLogger + Send + Sync>
+				);
+		let feerate = 2000;
+		handler.handle_event(&htlc_resolution_event(1, feerate, 0, 100));
+
+		let txn = broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+		assert_eq!(txn.len(), 1);
+		let htlc_tx = &txn[0];
+		assert_eq!(htlc_tx.output.len(), 2);
+		assert_eq!(htlc_tx.output[1].script_pubkey, source.change_script);
+		let input_value = Amount::from_sat(10_000) + wallet_utxo_value;
+		let actual_fee = input_value - htlc_tx.output.iter().map(|output| output.value).sum();
+		assert!(actual_fee.to_sat() >= fee_for_weight(feerate, htlc_tx.weight().to_wu()));
+
+		let entries = audit_log.entries();
+		assert_eq!(entries.len(), 1);
+		assert_eq!(
+			entries[0].decision,
+			ReserveAuditDecision::HtlcFeeBumped {
+				htlc_txid: htlc_tx.compute_txid(),
+				target_feerate_sat_per_1000_weight: feerate,
+				claim_id: Some(ClaimId([1; 32])),
+				htlc_tx_weight: Some(htlc_tx.weight()),
+			}
+		);
+		assert_eq!(entries[0].utxos_considered, vec![wallet_outpoint]);
+		assert_eq!(entries[0].fees_paid, actual_fee);
+
+		// The fee estimated for the wallet's change output type matches the fee paid, except for
+		// the wallet input, whose satisfaction weight assumes a 73-byte signature rather than the
+		// 72-byte one of the estimate.
+		let context =
+			AnchorChannelReserveContext { taproot_wallet: taproot_change, ..Default::default() };
+		let estimated_weight = htlc_timeout_transaction_weight(&context, WalletInputType::P2wpkh);
+		let estimated_fee = fee_for_weight(feerate, estimated_weight.to_wu());
+		assert_eq!(actual_fee.to_sat(), estimated_fee + fee_for_weight(feerate, 1));
+	}
+
+	#[test]
+	fn test_htlc_claim_fee_with_change_script() {
+		do_test_htlc_claim_fee_with_change_script(false);
+		do_test_htlc_claim_fee_with_change_script(true);
+	}
+
+	struct TestClaimNotifier {
+		broadcaster: Arc<TestBroadcaster>,
+		pending_spends: Mutex<Vec<PendingClaimSpend>>,
+		veto: AtomicBool,
+	}
+	impl ClaimNotifier for TestClaimNotifier {
+		fn claim_spend_pending(&self, pending_spend: &PendingClaimSpend) -> Result<(), ()> {
+			// The wallet is notified before the transaction is broadcast.
+			assert!(self.broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+			self.pending_spends.lock().unwrap().push(pending_spend.clone());
+			if self.veto.load(Ordering::Acquire) {
+				Err(())
+			} else {
+				Ok(())
+			}
+		}
+	}
+
+	fn claim_notifier_test_wallet_source() -> ChangeScriptWalletSource {
+		let secp_ctx = Secp256k1::new();
+		let pubkey =
+			PublicKey::from_secret_key(&secp_ctx, &SecretKey::from_slice(&[42; 32]).unwrap());
+		let pubkey_hash = WPubkeyHash::hash(&pubkey.serialize());
+		let wallet_outpoint = OutPoint { txid: Txid::from_byte_array([44; 32]), vout: 0 };
+		ChangeScriptWalletSource {
+			utxos: vec![Utxo::new_p2wpkh(
+				wallet_outpoint,
+				Amount::from_sat(1_000_000),
+				&pubkey_hash,
+			)],
+			change_script: ScriptBuf::new_p2wpkh(&pubkey_hash),
+		}
+	}
+
+	#[test]
+	fn test_claim_notifier_locks_before_broadcast() {
+		let source = claim_notifier_test_wallet_source();
+		let broadcaster = Arc::new(TestBroadcaster::new(Network::Testnet));
+		let logger = TestLogger::new();
+		let wallet = WalletSync::new(&source, &logger);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let notifier = Arc::new(TestClaimNotifier {
+			broadcaster: Arc::clone(&broadcaster),
+			pending_spends: Mutex::new(Vec::new()),
+			veto: AtomicBool::new(false),
+		});
+		let schedule = HTLCDeadlineFeerateSchedule::disabled();
+		let handler = BumpTransactionEventHandlerSync::new(
+			Arc::clone(&broadcaster),
+			&wallet,
+			&signer,
+			&logger,
+			schedule,
+		)
+		.with_claim_notifier(Arc::clone(&notifier) as Arc<dyn ClaimNotifier + Send + Sync>);
+		handler.handle_event(&htlc_resolution_event(1, 2000, 0, 100));
+
+		let txn = broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+		assert_eq!(txn.len(), 1);
+		let htlc_tx = &txn[0];
+		let pending_spends = notifier.pending_spends.lock().unwrap().split_off(0);
+		assert_eq!(pending_spends.len(), 1);
+		let pending_spend = &pending_spends[0];
+		assert_eq!(pending_spend.claim_id, ClaimId([1; 32]));
+		assert_eq!(pending_spend.wallet_utxos, vec![source.utxos[0].outpoint]);
+		assert_eq!(pending_spend.change_output.as_ref(), Some(&htlc_tx.output[1]));
+		assert_eq!(pending_spend.target_feerate_sat_per_1000_weight, 2000);
+		let unsigned_tx: Transaction = encode::deserialize(&pending_spend.unsigned_tx).unwrap();
+		assert_eq!(unsigned_tx.compute_txid(), htlc_tx.compute_txid());
+		assert!(unsigned_tx.input.iter().all(|input| input.witness.is_empty()));
+	}
+
+	#[test]
+	fn test_claim_notifier_veto_is_retried() {
+		let source = claim_notifier_test_wallet_source();
+		let broadcaster = Arc::new(TestBroadcaster::new(Network::Testnet));
+		let logger = TestLogger::new();
+		let wallet = WalletSync::new(&source, &logger);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let notifier = Arc::new(TestClaimNotifier {
+			broadcaster: Arc::clone(&broadcaster),
+			pending_spends: Mutex::new(Vec::new()),
+			veto: AtomicBool::new(true),
 		});
+		let schedule = HTLCDeadlineFeerateSchedule::disabled();
+		let handler = BumpTransactionEventHandlerSync::new(
+			Arc::clone(&broadcaster),
+			&wallet,
+			&signer,
+			&logger,
+			schedule,
+		)
+		.with_claim_notifier(Arc::clone(&notifier) as Arc<dyn ClaimNotifier + Send + Sync>);
+		let event = htlc_resolution_event(1, 2000, 0, 100);
+
+		// Vetoed attempts are not broadcast, but count as failures of the claim.
+		for _ in 0..FAILURES_BEFORE_FAILED_EVENT {
+			handler.handle_event(&event);
+		}
+		assert!(broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+		assert_eq!(notifier.pending_spends.lock().unwrap().len(), 3);
+		let events = RefCell::new(Vec::new());
+		handler.process_pending_events(&|e| Ok(events.borrow_mut().push(e)));
+		match &events.into_inner()[..] {
+			[Event::BumpTransactionFailed { claim_id, error, .. }] => {
+				assert_eq!(*claim_id, ClaimId([1; 32]));
+				assert_eq!(error, "Claim notifier vetoed the broadcast");
+			},
+			_ => panic!("Unexpected events"),
+		}
+
+		// Once the notifier allows it, the claim is retried with the same wallet UTXOs.
+		notifier.veto.store(false, Ordering::Release);
+		handler.handle_event(&event);
+		let txn = broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+		assert_eq!(txn.len(), 1);
+		let pending_spends = notifier.pending_spends.lock().unwrap().split_off(0);
+		assert_eq!(pending_spends.len(), 4);
+		assert!(pending_spends.windows(2).all(|spends| spends[0] == spends[1]));
+		assert_eq!(pending_spends[3].wallet_utxos, vec![source.utxos[0].outpoint]);
+	}
+
+	fn sign_wallet_inputs_externally(mut psbt: Psbt) -> Psbt {
+		// Only the wallet input following the HTLC input is signed by the external signer.
+		for input in psbt.inputs.iter_mut().skip(1) {
+			input.final_script_witness = Some(Witness::from_slice(&[vec![42; 72], vec![42; 33]]));
+		}
+		psbt
+	}
+
+	#[test]
+	fn test_external_signer_completes_claim_after_delay() {
+		let source = claim_notifier_test_wallet_source();
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let logger = TestLogger::new();
+		let wallet = WalletSync::new(&source, &logger);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let schedule = HTLCDeadlineFeerateSchedule::disabled();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &wallet, &signer, &logger, schedule)
+				.with_external_signing(6);
+		let event = htlc_resolution_event(1, 2000, 0, 100);
+		handler.handle_event(&event);
+
+		// Rather than being broadcast, the claim awaits the signatures of the external signer.
+		assert!(broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+		let pending_signatures = handler.pending_signatures();
+		assert_eq!(pending_signatures.len(), 1);
+		let pending = pending_signatures[0].clone();
+		assert_eq!(pending.claim_id, ClaimId([1; 32]));
+		assert_eq!(pending.target_feerate_sat_per_1000_weight, 2000);
+		assert_eq!(pending.psbt.unsigned_tx.input[1].previous_output, source.utxos[0].outpoint);
+
+		// While the signer takes its time, the events regenerated upon every block leave the PSBT
+		// handed out untouched.
+		for _ in 0..5 {
+			handler.handle_event(&event);
+		}
+		assert!(broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+		assert_eq!(handler.pending_signatures(), vec![pending.clone()]);
+
+		// A PSBT whose wallet inputs aren't finalized is rejected.
+		assert_eq!(
+			handler.provide_signed_psbt(pending.claim_id, pending.psbt.clone()),
+			Err(ProvideSignedPsbtError::InvalidPsbt)
+		);
+		let signed_psbt = sign_wallet_inputs_externally(pending.psbt.clone());
+		assert_eq!(handler.provide_signed_psbt(pending.claim_id, signed_psbt.clone()), Ok(()));
+
+		let txn = broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+		assert_eq!(txn.len(), 1);
+		let htlc_tx = &txn[0];
+		assert_eq!(htlc_tx.compute_txid(), pending.psbt.unsigned_tx.compute_txid());
+		assert!(!htlc_tx.input[0].witness.is_empty());
+		assert_eq!(
+			Some(&htlc_tx.input[1].witness),
+			signed_psbt.inputs[1].final_script_witness.as_ref()
+		);
+		assert!(handler.pending_signatures().is_empty());
+		assert_eq!(
+			handler.provide_signed_psbt(pending.claim_id, signed_psbt),
+			Err(ProvideSignedPsbtError::UnknownClaim)
+		);
+	}
+
+	#[test]
+	fn test_external_signing_discards_stale_claims() {
+		let source = claim_notifier_test_wallet_source();
+		let broadcaster = TestBroadcaster::new(Network::Testnet);
+		let logger = TestLogger::new();
+		let wallet = WalletSync::new(&source, &logger);
+		let signer = KeysManager::new(&[42; 32], 42, 42);
+		let schedule = HTLCDeadlineFeerateSchedule::disabled();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &wallet, &signer, &logger, schedule)
+				.with_external_signing(6);
+		handler.handle_event(&htlc_resolution_event(1, 2000, 0, 100));
+		let stale_psbt = handler.pending_signatures()[0].psbt.clone();
+
+		// Once the claim targets a higher feerate, it is regenerated and the stale PSBT rejected.
+		handler.handle_event(&htlc_resolution_event(1, 3000, 0, 99));
+		let pending_signatures = handler.pending_signatures();
+		assert_eq!(pending_signatures.len(), 1);
+		assert_eq!(pending_signatures[0].target_feerate_sat_per_1000_weight, 3000);
+		assert_eq!(
+			handler
+				.provide_signed_psbt(ClaimId([1; 32]), sign_wallet_inputs_externally(stale_psbt)),
+			Err(ProvideSignedPsbtError::InvalidPsbt)
+		);
+
+		// Once the counterparty spends the HTLC output, the claim is invalidated.
+		let htlc_outpoint = pending_signatures[0].psbt.unsigned_tx.input[0].previous_output;
+		let counterparty_claim = Transaction {
+			version: Version::TWO,
+			lock_time: LockTime::ZERO,
+			input: vec![TxIn {
+				previous_output: htlc_outpoint,
+				script_sig: ScriptBuf::new(),
+				sequence: Sequence::ZERO,
+				witness: Witness::new(),
+			}],
+			output: vec![],
+		};
+		assert_eq!(
+			handler.transactions_confirmed(&[&counterparty_claim], 100),
+			vec![ClaimId([1; 32])]
+		);
+		assert!(handler.pending_signatures().is_empty());
+		let psbt = sign_wallet_inputs_externally(pending_signatures[0].psbt.clone());
+		assert_eq!(
+			handler.provide_signed_psbt(ClaimId([1; 32]), psbt),
+			Err(ProvideSignedPsbtError::UnknownClaim)
+		);
+		assert!(broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+
+		// Once the commitment transaction confirms, it no longer needs its fee bumped.
+		let (source, event) = op_return_under_funds_claim();
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &source, &signer, &logger, schedule)
+				.with_external_signing(6);
+		handler.handle_event(&event);
+		assert_eq!(handler.pending_signatures().len(), 1);
+		let commitment_tx = match &event {
+			BumpTransactionEvent::ChannelClose { commitment_tx, .. } => commitment_tx,
+			_ => unreachable!(),
+		};
+		assert_eq!(handler.transactions_confirmed(&[commitment_tx], 100), vec![ClaimId([42; 32])]);
+		assert!(handler.pending_signatures().is_empty());
+
+		// A signer failing to sign in time counts as a failed attempt, after which the claim is
+		// regenerated.
+		let handler =
+			BumpTransactionEventHandlerSync::new(&broadcaster, &wallet, &signer, &logger, schedule)
+				.with_external_signing(1);
+		let event = htlc_resolution_event(1, 2000, 0, 100);
+		for _ in 0..=FAILURES_BEFORE_FAILED_EVENT {
+			handler.handle_event(&event);
+		}
+		assert_eq!(handler.pending_signatures().len(), 1);
+		let events = RefCell::new(Vec::new());
+		handler.process_pending_events(&|e| Ok(events.borrow_mut().push(e)));
+		match &events.into_inner()[..] {
+			[Event::BumpTransactionFailed { claim_id, error, consecutive_failures, .. }] => {
+				assert_eq!(*claim_id, ClaimId([1; 32]));
+				assert_eq!(*consecutive_failures, FAILURES_BEFORE_FAILED_EVENT);
+				assert_eq!(error, "External signer failed to sign in time");
+			},
+			_ => panic!("Unexpected events"),
+		}
+		assert!(broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+	}
+
+	#[test]
+	fn test_utxo_constructors() {
+		let outpoint = OutPoint { txid: Txid::from_byte_array([42; 32]), vout: 0 };
+		let value = Amount::ONE_BTC;
+		let secp_ctx = Secp256k1::new();
+		let pubkey =
+			PublicKey::from_secret_key(&secp_ctx, &SecretKey::from_slice(&[42; 32]).unwrap());
+		let pubkey_hash = WPubkeyHash::hash(&pubkey.serialize());
+		let output_key = TweakedPublicKey::dangerous_assume_tweaked(pubkey.x_only_public_key().0);
+
+		let push = |len| PushBytesBuf::try_from(vec![0; len]).unwrap();
+		let input_with = |script_sig, witness: &[Vec<u8>]| TxIn {
+			previous_output: outpoint,
+			script_sig,
+			sequence: Sequence::ZERO,
+			witness: Witness::from_slice(witness),
+		};
+		let nested_script_sig = Builder::new().push_slice(push(22)).into_script();
+		let p2pkh_script_sig =
+			Builder::new().push_slice(push(73)).push_slice(push(33)).into_script();
+		let max_ecdsa_witness = [vec![0; 73], vec![0; 33]];
+		let cases = [
+			(
+				Utxo::new_p2pkh(outpoint, value, &PubkeyHash::hash(&pubkey.serialize())),
+				input_with(p2pkh_script_sig, &[]),
+			),
+			(
+				Utxo::new_nested_p2wpkh(outpoint, value, &pubkey_hash),
+				input_with(nested_script_sig, &max_ecdsa_witness),
+			),
+			(
+				Utxo::new_p2wpkh(outpoint, value, &pubkey_hash),
+				input_with(ScriptBuf::new(), &max_ecdsa_witness),
+			),
+			(
+				Utxo::new_p2tr(outpoint, value, output_key),
+				input_with(ScriptBuf::new(), &[vec![0; 64]]),
+			),
+		];
+		for (utxo, max_input) in cases {
+			// The satisfaction weight matches that of the largest input satisfying the output.
+			assert_eq!(
+				utxo.satisfaction_weight,
+				max_input.segwit_weight() - Weight::from_wu(BASE_INPUT_WEIGHT)
+			);
+			assert_eq!(utxo.output.value, value);
+			assert_eq!(utxo.outpoint, outpoint);
+
+			// The satisfaction weight is inferred from the script type of the output, except for
+			// P2SH outputs, whose redeem script is unknown.
+			let res = Utxo::try_from_txout(outpoint, utxo.output.clone());
+			if utxo.output.script_pubkey.is_p2sh() {
+				assert_eq!(res, Err(()));
+			} else {
+				assert_eq!(res, Ok(utxo));
+			}
+		}
+
+		let unsupported_scripts = [
+			ScriptBuf::new_p2wsh(&ScriptBuf::new().wscript_hash()),
+			ScriptBuf::new_op_return(&[]),
+			ScriptBuf::new(),
+		];
+		for script_pubkey in unsupported_scripts {
+			let output = TxOut { value, script_pubkey };
+			assert_eq!(Utxo::try_from_txout(outpoint, output), Err(()));
+		}
+	}
+
+	#[test]
+	fn test_utxo_serialization() {
+		let pubkey_hash = WPubkeyHash::from_byte_array([42; 20]);
+		let utxos = vec![
+			Utxo::new_p2wpkh(
+				OutPoint { txid: Txid::from_byte_array([42; 32]), vout: 0 },
+				Amount::ONE_BTC,
+				&pubkey_hash,
+			),
+			Utxo::new_nested_p2wpkh(
+				OutPoint { txid: Txid::from_byte_array([43; 32]), vout: 1 },
+				Amount::from_sat(50_000),
+				&pubkey_hash,
+			),
+		];
+		for utxo in utxos.iter() {
+			let encoded = utxo.encode();
+			assert_eq!(Utxo::read(&mut &encoded[..]).unwrap(), *utxo);
+
+			// Unknown odd TLV records written by newer versions are ignored.
+			let mut encoded_with_odd_type = encoded.clone();
+			encoded_with_odd_type[0] += 3;
+			encoded_with_odd_type.extend_from_slice(&[7, 1, 42]);
+			assert_eq!(Utxo::read(&mut &encoded_with_odd_type[..]).unwrap(), *utxo);
+
+			// Unknown even TLV records are required to be understood.
+			let mut encoded_with_even_type = encoded.clone();
+			encoded_with_even_type[0] += 3;
+			encoded_with_even_type.extend_from_slice(&[6, 1, 42]);
+			assert_eq!(
+				Utxo::read(&mut &encoded_with_even_type[..]),
+				Err(DecodeError::UnknownRequiredFeature)
+			);
+		}
+
+		let coin_selections = [
+			CoinSelection { confirmed_utxos: Vec::new(), change_output: None },
+			CoinSelection {
+				confirmed_utxos: utxos.clone(),
+				change_output: Some(TxOut {
+					value: Amount::from_sat(1_000),
+					script_pubkey: ScriptBuf::new_p2wpkh(&pubkey_hash),
+				}),
+			},
+		];
+		for coin_selection in coin_selections {
+			let encoded = coin_selection.encode();
+			assert_eq!(CoinSelection::read(&mut &encoded[..]).unwrap(), coin_selection);
+		}
+
+		// Snapshots round-trip regardless of their size, and are rejected once written by a
+		// version this one can't read.
+		for snapshot_utxos in [&utxos[..0], &utxos[..]] {
+			let snapshot = serialize_utxo_snapshot(snapshot_utxos);
+			assert_eq!(snapshot[0], UTXO_SNAPSHOT_SERIALIZATION_VERSION);
+			assert_eq!(deserialize_utxo_snapshot(&snapshot).unwrap(), snapshot_utxos);
+		}
+		let mut snapshot = serialize_utxo_snapshot(&utxos);
+		snapshot[1] = UTXO_SNAPSHOT_SERIALIZATION_VERSION + 1;
+		assert_eq!(deserialize_utxo_snapshot(&snapshot), Err(DecodeError::UnknownVersion));
 	}
 }
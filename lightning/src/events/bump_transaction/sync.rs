@@ -15,17 +15,23 @@ use core::task;
 
 use crate::chain::chaininterface::BroadcasterInterface;
 use crate::chain::ClaimId;
+use crate::events::{EventHandler, EventsProvider};
+use crate::io;
 use crate::prelude::*;
 use crate::sign::SignerProvider;
+use crate::sync::Arc;
+use crate::util::anchor_channel_reserves::{AnchorReserveProvider, ReserveAuditLogger};
 use crate::util::async_poll::{dummy_waker, AsyncResult, MaybeSend, MaybeSync};
 use crate::util::logger::Logger;
+use crate::util::persist::KVStoreSync;
 
-use bitcoin::{Psbt, ScriptBuf, Transaction, TxOut};
+use bitcoin::{OutPoint, Psbt, ScriptBuf, Transaction, TxOut};
 
 use super::BumpTransactionEvent;
 use super::{
-	BumpTransactionEventHandler, CoinSelection, CoinSelectionSource, Input, Utxo, Wallet,
-	WalletSource,
+	BumpTransactionEventHandler, ClaimNotifier, CoinSelection, CoinSelectionSource,
+	FeeSponsorScope, HTLCDeadlineFeerateSchedule, Input, PendingSignature, ProvideSignedPsbtError,
+	Utxo, Wallet, WalletSource,
 };
 
 /// A synchronous version of the [`WalletSource`] trait.
@@ -135,6 +141,21 @@ where
 	}
 }
 
+impl<W: Deref + MaybeSync + MaybeSend, L: Deref + MaybeSync + MaybeSend> AnchorReserveProvider
+	for WalletSync<W, L>
+where
+	W::Target: WalletSourceSync + MaybeSend,
+	L::Target: Logger + MaybeSend,
+{
+	fn list_reserve_utxos(&self) -> Result<Vec<Utxo>, ()> {
+		self.wallet.source.0.list_confirmed_utxos()
+	}
+
+	fn list_claim_input_outpoints(&self) -> Vec<OutPoint> {
+		self.wallet.list_locked_outpoints()
+	}
+}
+
 /// A synchronous version of the [`CoinSelectionSource`] trait.
 pub trait CoinSelectionSourceSync {
 	/// A synchronous version of [`CoinSelectionSource::select_confirmed_utxos`].
@@ -206,16 +227,122 @@ where
 	L::Target: Logger,
 {
 	/// Constructs a new instance of [`BumpTransactionEventHandlerSync`].
-	pub fn new(broadcaster: B, utxo_source: C, signer_provider: SP, logger: L) -> Self {
+	///
+	/// See [`BumpTransactionEventHandler::new`] for details.
+	pub fn new(
+		broadcaster: B, utxo_source: C, signer_provider: SP, logger: L,
+		htlc_deadline_schedule: HTLCDeadlineFeerateSchedule,
+	) -> Self {
 		let bump_transaction_event_handler = BumpTransactionEventHandler::new(
 			broadcaster,
 			CoinSelectionSourceSyncWrapper(utxo_source),
 			signer_provider,
 			logger,
+			htlc_deadline_schedule,
 		);
 		Self { bump_transaction_event_handler }
 	}
 
+	/// Records the fees paid for each successfully bumped claim to `audit_logger`.
+	pub fn with_audit_logger(
+		self, audit_logger: Arc<dyn ReserveAuditLogger + Send + Sync>,
+	) -> Self {
+		Self {
+			bump_transaction_event_handler: self
+				.bump_transaction_event_handler
+				.with_audit_logger(audit_logger),
+		}
+	}
+
+	/// Notifies `claim_notifier` of the wallet UTXOs spent by each claim before its transaction is
+	/// broadcast. See [`BumpTransactionEventHandler::with_claim_notifier`] for details.
+	pub fn with_claim_notifier(self, claim_notifier: Arc<dyn ClaimNotifier + Send + Sync>) -> Self {
+		Self {
+			bump_transaction_event_handler: self
+				.bump_transaction_event_handler
+				.with_claim_notifier(claim_notifier),
+		}
+	}
+
+	/// Hands the wallet inputs of claims to an external signer rather than signing them via
+	/// [`CoinSelectionSourceSync::sign_psbt`]. See
+	/// [`BumpTransactionEventHandler::with_external_signing`] for details.
+	pub fn with_external_signing(self, timeout_blocks: u32) -> Self {
+		Self {
+			bump_transaction_event_handler: self
+				.bump_transaction_event_handler
+				.with_external_signing(timeout_blocks),
+		}
+	}
+
+	/// Persists the last transaction broadcast for each claim to `kv_store`, reloading any claims
+	/// persisted previously. See [`BumpTransactionEventHandler::with_claim_persistence`] for
+	/// details.
+	pub fn with_claim_persistence(
+		self, kv_store: Arc<dyn KVStoreSync + Send + Sync>,
+	) -> Result<Self, io::Error> {
+		Ok(Self {
+			bump_transaction_event_handler: self
+				.bump_transaction_event_handler
+				.with_claim_persistence(kv_store)?,
+		})
+	}
+
+	/// Returns the claims awaiting the signatures of an external signer. See
+	/// [`BumpTransactionEventHandler::pending_signatures`] for details.
+	pub fn pending_signatures(&self) -> Vec<PendingSignature> {
+		self.bump_transaction_event_handler.pending_signatures()
+	}
+
+	/// Completes a claim once an external signer signed the wallet inputs of its PSBT. See
+	/// [`BumpTransactionEventHandler::provide_signed_psbt`] for details.
+	pub fn provide_signed_psbt(
+		&self, claim_id: ClaimId, psbt: Psbt,
+	) -> Result<(), ProvideSignedPsbtError> {
+		self.bump_transaction_event_handler.provide_signed_psbt(claim_id, psbt)
+	}
+
+	/// Discards the PSBTs awaiting signatures and resolves the persisted claims which were
+	/// invalidated by the confirmation of any of `txdata` at `height`. See
+	/// [`BumpTransactionEventHandler::transactions_confirmed`] for details.
+	pub fn transactions_confirmed(&self, txdata: &[&Transaction], height: u32) -> Vec<ClaimId> {
+		self.bump_transaction_event_handler.transactions_confirmed(txdata, height)
+	}
+
+	/// Prunes the persisted claims resolved deep enough below `height`. See
+	/// [`BumpTransactionEventHandler::best_block_updated`] for details.
+	pub fn best_block_updated(&self, height: u32) {
+		self.bump_transaction_event_handler.best_block_updated(height)
+	}
+
+	/// Reverts the resolution of the persisted claims resolved above `new_best_height`. See
+	/// [`BumpTransactionEventHandler::blocks_disconnected`] for details.
+	pub fn blocks_disconnected(&self, new_best_height: u32) {
+		self.bump_transaction_event_handler.blocks_disconnected(new_best_height)
+	}
+
+	/// Returns the wallet UTXOs of persisted claims whose resolution was reorged out. See
+	/// [`BumpTransactionEventHandler::reorged_claim_utxos`] for details.
+	pub fn reorged_claim_utxos(&self) -> Vec<OutPoint> {
+		self.bump_transaction_event_handler.reorged_claim_utxos()
+	}
+
+	/// Registers `sponsor` to provide the UTXOs to bump the fees of the claims within `scope`.
+	///
+	/// See [`BumpTransactionEventHandler::register_fee_sponsor`] for details.
+	pub fn register_fee_sponsor(
+		&self, scope: FeeSponsorScope, sponsor: Arc<dyn CoinSelectionSourceSync + Send + Sync>,
+	) {
+		let sponsor = Arc::new(CoinSelectionSourceSyncWrapper(sponsor));
+		self.bump_transaction_event_handler.register_fee_sponsor(scope, sponsor);
+	}
+
+	/// Removes the sponsor registered for `scope` via [`Self::register_fee_sponsor`], returning
+	/// whether one was registered.
+	pub fn remove_fee_sponsor(&self, scope: &FeeSponsorScope) -> bool {
+		self.bump_transaction_event_handler.remove_fee_sponsor(scope)
+	}
+
 	/// Handles all variants of [`BumpTransactionEvent`].
 	pub fn handle_event(&self, event: &BumpTransactionEvent) {
 		let mut fut = Box::pin(self.bump_transaction_event_handler.handle_event(event));
@@ -229,4 +356,36 @@ where
 			},
 		}
 	}
+
+	/// Handles a set of [`BumpTransactionEvent`]s, aggregating HTLC claims across channels where
+	/// possible. See [`BumpTransactionEventHandler::handle_events_aggregated`] for details.
+	pub fn handle_events_aggregated(&self, events: &[BumpTransactionEvent]) {
+		let mut fut =
+			Box::pin(self.bump_transaction_event_handler.handle_events_aggregated(events));
+		let mut waker = dummy_waker();
+		let mut ctx = task::Context::from_waker(&mut waker);
+		match fut.as_mut().poll(&mut ctx) {
+			task::Poll::Ready(result) => result,
+			task::Poll::Pending => {
+				// In a sync context, we can't wait for the future to complete.
+				unreachable!("BumpTransactionEventHandlerSync::handle_events_aggregated should not be pending in a sync context");
+			},
+		}
+	}
+}
+
+impl<B: Deref, C: Deref, SP: Deref, L: Deref> EventsProvider
+	for BumpTransactionEventHandlerSync<B, C, SP, L>
+where
+	B::Target: BroadcasterInterface,
+	C::Target: CoinSelectionSourceSync,
+	SP::Target: SignerProvider,
+	L::Target: Logger,
+{
+	fn process_pending_events<H: Deref>(&self, handler: H)
+	where
+		H::Target: EventHandler,
+	{
+		self.bump_transaction_event_handler.process_pending_events(handler);
+	}
 }
@@ -23,6 +23,8 @@ use crate::blinded_path::payment::{
 	Bolt12OfferContext, Bolt12RefundContext, PaymentContext, PaymentContextRef,
 };
 use crate::chain::transaction;
+use crate::chain::ClaimId;
+use crate::events::bump_transaction::BumpTransactionClaimType;
 use crate::ln::channel::FUNDING_CONF_DEADLINE_BLOCKS;
 use crate::ln::channelmanager::{InterceptId, PaymentId, RecipientOnionFields};
 use crate::ln::types::ChannelId;
@@ -30,7 +32,7 @@ use crate::ln::{msgs, LocalHTLCFailureReason};
 use crate::offers::invoice::Bolt12Invoice;
 use crate::offers::static_invoice::StaticInvoice;
 use crate::onion_message::messenger::Responder;
-use crate::routing::gossip::NetworkUpdate;
+use crate::routing::gossip::{NetworkUpdate, NodeAlias};
 use crate::routing::router::{BlindedTail, Path, RouteHop, RouteParameters};
 use crate::sign::SpendableOutputDescriptor;
 use crate::types::features::ChannelTypeFeatures;
@@ -48,7 +50,7 @@ use bitcoin::hashes::sha256::Hash as Sha256;
 use bitcoin::hashes::Hash;
 use bitcoin::script::ScriptBuf;
 use bitcoin::secp256k1::PublicKey;
-use bitcoin::{OutPoint, Transaction};
+use bitcoin::{Amount, BlockHash, OutPoint, Transaction, TxOut, Txid};
 use core::ops::Deref;
 
 #[allow(unused_imports)]
@@ -74,6 +76,14 @@ pub enum FundingInfo {
 		/// The outpoint of the funding
 		outpoint: transaction::OutPoint,
 	},
+	/// The inputs and outputs we contributed to an interactively constructed funding transaction
+	/// for a dual-funded channel or a splice, whose negotiation was aborted.
+	Contribution {
+		/// The outpoints of the inputs we contributed, which may be unlocked and spent elsewhere.
+		inputs: Vec<OutPoint>,
+		/// The outputs we contributed, e.g., a change output.
+		outputs: Vec<TxOut>,
+	},
 }
 
 impl_writeable_tlv_based_enum!(FundingInfo,
@@ -82,6 +92,10 @@ impl_writeable_tlv_based_enum!(FundingInfo,
 	},
 	(1, OutPoint) => {
 		(1, outpoint, required)
+	},
+	(2, Contribution) => {
+		(0, inputs, optional_vec),
+		(2, outputs, optional_vec),
 	}
 );
 
@@ -510,6 +524,103 @@ impl_writeable_tlv_based_enum_upgradable!(ClosureReason,
 	(25, LocallyCoopClosedUnfundedChannel) => {},
 );
 
+/// The outcome of closing a channel as part of a batch, as reported in
+/// [`Event::BatchedChannelCloseCompleted`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BatchedChannelCloseOutcome {
+	/// The channel was closed cooperatively.
+	CooperativelyClosed,
+	/// The channel was force-closed, either by us once the batch timed out or for any other
+	/// reason. The [`ClosureReason`] is provided in the corresponding [`Event::ChannelClosed`].
+	ForceClosed,
+	/// The channel had not closed by the time the batch timed out, as it still had HTLCs pending.
+	/// Its cooperative close continues as usual, generating an [`Event::ChannelClosed`] once
+	/// completed.
+	Pending,
+}
+
+impl_writeable_tlv_based_enum!(BatchedChannelCloseOutcome,
+	(0, CooperativelyClosed) => {},
+	(2, ForceClosed) => {},
+	(4, Pending) => {},
+);
+
+/// The outcome of closing a single channel of a batch, as reported in
+/// [`Event::BatchedChannelCloseCompleted`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BatchedChannelCloseResult {
+	/// The `channel_id` of the channel.
+	pub channel_id: ChannelId,
+	/// How the channel was closed, if at all.
+	pub outcome: BatchedChannelCloseOutcome,
+}
+
+impl_writeable_tlv_based!(BatchedChannelCloseResult, {
+	(0, channel_id, required),
+	(2, outcome, required),
+});
+
+/// A band of a channel's local balance, delimited by the [`LiquidityAlertThresholds`] configured
+/// for the channel, as reported in [`Event::ChannelLiquidityThresholdCrossed`].
+///
+/// Bands are ordered from the lowest to the highest local balance.
+///
+/// [`LiquidityAlertThresholds`]: crate::util::config::LiquidityAlertThresholds
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum LiquidityBand {
+	/// The local balance is below [`LiquidityAlertThresholds::low_percent`] of the channel's
+	/// capacity, i.e., the channel's outbound liquidity is depleted.
+	///
+	/// [`LiquidityAlertThresholds::low_percent`]: crate::util::config::LiquidityAlertThresholds::low_percent
+	Low,
+	/// The local balance is between the configured thresholds.
+	Normal,
+	/// The local balance is above [`LiquidityAlertThresholds::high_percent`] of the channel's
+	/// capacity, i.e., the channel's inbound liquidity is depleted.
+	///
+	/// [`LiquidityAlertThresholds::high_percent`]: crate::util::config::LiquidityAlertThresholds::high_percent
+	High,
+}
+
+impl_writeable_tlv_based_enum!(LiquidityBand,
+	(0, Low) => {},
+	(2, Normal) => {},
+	(4, High) => {},
+);
+
+/// The direction in which a channel's local balance crossed a threshold, as reported in
+/// [`Event::ChannelLiquidityThresholdCrossed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LiquidityThresholdDirection {
+	/// The local balance decreased, e.g., as payments were sent or forwarded over the channel.
+	Decreasing,
+	/// The local balance increased, e.g., as payments were received over the channel.
+	Increasing,
+}
+
+impl_writeable_tlv_based_enum!(LiquidityThresholdDirection,
+	(0, Decreasing) => {},
+	(2, Increasing) => {},
+);
+
+/// The reason the parts of an incomplete multi-part payment were failed back, as reported in
+/// [`Event::PartialPaymentFailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PartialPaymentFailureReason {
+	/// The remaining parts did not arrive within [`UserConfig::mpp_timeout_secs`] of the first
+	/// part.
+	///
+	/// [`UserConfig::mpp_timeout_secs`]: crate::util::config::UserConfig::mpp_timeout_secs
+	TimedOutAwaitingParts,
+	/// The parts received so far were about to expire before the remaining parts arrived.
+	PartsExpiring,
+}
+
+impl_writeable_tlv_based_enum!(PartialPaymentFailureReason,
+	(0, TimedOutAwaitingParts) => {},
+	(2, PartsExpiring) => {},
+);
+
 /// The type of HTLC handling performed in [`Event::HTLCHandlingFailed`].
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum HTLCHandlingFailureType {
@@ -1401,6 +1512,12 @@ pub enum Event {
 	///   to both parties (i.e., `splice_locked` messages were exchanged), or,
 	/// - in case of a 0conf channel, when both parties have confirmed the channel establishment.
 	///
+	/// As a splice changes the channel's funding outpoint and capacity, state derived from them,
+	/// e.g., the anchor channel reserve per [`get_reserve_per_channel_for_capacity`], should be
+	/// recalculated upon each such event.
+	///
+	/// [`get_reserve_per_channel_for_capacity`]: crate::util::anchor_channel_reserves::get_reserve_per_channel_for_capacity
+	///
 	/// # Failure Behavior and Persistence
 	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
 	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
@@ -1423,6 +1540,11 @@ pub enum Event {
 		/// Will be `None` if the channel's funding transaction reached an acceptable depth prior to
 		/// version 0.2.
 		funding_txo: Option<OutPoint>,
+		/// The value, in satoshis, of the output at [`Event::ChannelReady::funding_txo`], i.e., the
+		/// capacity of the channel once the funding transaction is ready.
+		///
+		/// Will be `None` for events generated prior to version 0.2.
+		channel_value_satoshis: Option<u64>,
 		/// The features that this channel will operate with.
 		channel_type: ChannelTypeFeatures,
 	},
@@ -1484,13 +1606,18 @@ pub enum Event {
 	/// Used to indicate to the user that they can abandon the funding transaction and recycle the
 	/// inputs for another purpose.
 	///
+	/// This is also generated with [`FundingInfo::Contribution`] when the negotiation of an
+	/// interactively constructed funding transaction for a dual-funded channel or a splice is
+	/// aborted, in which case the channel is not necessarily closed.
+	///
 	/// This event is not guaranteed to be generated for channels that are closed due to a restart.
 	///
 	/// # Failure Behavior and Persistence
 	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
 	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
 	DiscardFunding {
-		/// The channel_id of the channel which has been closed.
+		/// The channel_id of the channel which has been closed, or whose funding negotiation was
+		/// aborted.
 		channel_id: ChannelId,
 		/// The full transaction received from the user
 		funding_info: FundingInfo,
@@ -1558,6 +1685,19 @@ pub enum Event {
 		is_announced: bool,
 		/// Channel parameters given by the counterparty.
 		params: msgs::ChannelParameters,
+		/// If [`Self::OpenChannelRequest::channel_type`] uses anchor outputs, the amount that needs to
+		/// be maintained as a reserve in on-chain funds to handle a unilateral close of the channel.
+		///
+		/// This is estimated using [`get_reserve_per_channel`] with the context registered through
+		/// [`ChannelManager::set_anchor_channel_reserve_context`], or the default
+		/// [`AnchorChannelReserveContext`] otherwise. The reserve can be checked before accepting the
+		/// channel via [`ChannelManager::accept_inbound_channel_with_anchor_reserve`].
+		///
+		/// [`get_reserve_per_channel`]: crate::util::anchor_channel_reserves::get_reserve_per_channel
+		/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+		/// [`ChannelManager::set_anchor_channel_reserve_context`]: crate::ln::channelmanager::ChannelManager::set_anchor_channel_reserve_context
+		/// [`ChannelManager::accept_inbound_channel_with_anchor_reserve`]: crate::ln::channelmanager::ChannelManager::accept_inbound_channel_with_anchor_reserve
+		estimated_reserve_required: Option<Amount>,
 	},
 	/// Indicates that the HTLC was accepted, but could not be processed when or after attempting to
 	/// forward it.
@@ -1588,6 +1728,354 @@ pub enum Event {
 	///
 	/// [`ChannelHandshakeConfig::negotiate_anchors_zero_fee_htlc_tx`]: crate::util::config::ChannelHandshakeConfig::negotiate_anchors_zero_fee_htlc_tx
 	BumpTransaction(BumpTransactionEvent),
+	/// Indicates that LDK's [`BumpTransactionEventHandler`] repeatedly failed to bump the fee of a
+	/// claim originating from an [`Event::BumpTransaction`], most likely because the
+	/// [`CoinSelectionSource`] could not provide sufficient confirmed UTXOs.
+	///
+	/// If the claim is not resolved in time, the HTLCs pending on the channel may time out and the
+	/// funds allocated to them may be lost. Users should make additional confirmed funds available
+	/// to their wallet as soon as possible.
+	///
+	/// This event is only generated after several consecutive failures for the same claim and is
+	/// deduplicated per claim. While the claim keeps failing, it is repeated with an increasing
+	/// frequency as time passes and the deadline approaches. Once the claim succeeds, failures are
+	/// reset.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event won't be replayed after failures-to-handle (i.e., the event handler returning
+	/// `Err(ReplayEvent ())`) and won't be persisted across restarts, but will be regenerated upon
+	/// further failed attempts to bump the claim.
+	///
+	/// [`BumpTransactionEventHandler`]: crate::events::bump_transaction::BumpTransactionEventHandler
+	/// [`CoinSelectionSource`]: crate::events::bump_transaction::CoinSelectionSource
+	BumpTransactionFailed {
+		/// The `channel_id` of the channel which has been closed.
+		channel_id: ChannelId,
+		/// Counterparty in the closed channel.
+		counterparty_node_id: PublicKey,
+		/// The unique identifier of the claim that could not be fee-bumped.
+		claim_id: ClaimId,
+		/// The type of claim that could not be fee-bumped.
+		claim_type: BumpTransactionClaimType,
+		/// The total value of the HTLCs pending resolution that are at risk if the claim does not
+		/// confirm in time.
+		amount_at_risk_msat: u64,
+		/// The earliest block height at which one of the HTLCs at risk expires, if any.
+		deadline_height: Option<u32>,
+		/// The number of consecutive failed attempts to bump the fee of the claim.
+		consecutive_failures: u32,
+		/// A description of the latest failure.
+		error: String,
+	},
+	/// Indicates that a [`RetryingPersister`] was unable to persist updates to a channel's
+	/// [`ChannelMonitor`] within [`RetryingPersisterConfig::failure_deadline_ticks`] and has given
+	/// up retrying.
+	///
+	/// The channel can no longer make progress. Any further updates to its [`ChannelMonitor`] will
+	/// fail with [`ChannelMonitorUpdateStatus::UnrecoverableError`], so the underlying persistence
+	/// issue should be resolved and the node restarted as soon as possible.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`), but won't be persisted across restarts.
+	///
+	/// [`RetryingPersister`]: crate::util::persist::RetryingPersister
+	/// [`RetryingPersisterConfig::failure_deadline_ticks`]: crate::util::persist::RetryingPersisterConfig::failure_deadline_ticks
+	/// [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+	/// [`ChannelMonitorUpdateStatus::UnrecoverableError`]: crate::chain::ChannelMonitorUpdateStatus::UnrecoverableError
+	MonitorPersistenceFailed {
+		/// The `channel_id` of the channel whose [`ChannelMonitor`] could not be persisted.
+		///
+		/// [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+		channel_id: ChannelId,
+		/// The counterparty in the channel.
+		counterparty_node_id: PublicKey,
+		/// The `update_id`s of the [`ChannelMonitorUpdate`]s which were never persisted.
+		///
+		/// [`ChannelMonitorUpdate`]: crate::chain::channelmonitor::ChannelMonitorUpdate
+		pending_update_ids: Vec<u64>,
+	},
+	/// Indicates that our counterparty sent a `channel_reestablish` proving that our state for the
+	/// channel is stale, i.e., that we have lost data, e.g., by restarting from an old backup.
+	///
+	/// This event will only be generated if [`UserConfig::manually_acknowledge_data_loss`] is set.
+	/// Until [`ChannelManager::acknowledge_data_loss`] is called, the channel is left untouched and
+	/// the peer is disconnected with a warning upon every reconnection. Before acknowledging, the
+	/// operator should check whether a more recent backup of the channel's state is available, as
+	/// a malicious peer may attempt to trick us into closing the channel without broadcasting our
+	/// latest commitment transaction.
+	///
+	/// Once acknowledged, the channel is force-closed without broadcasting our (stale) commitment
+	/// transaction and the peer is asked to broadcast its own instead, after which our funds are
+	/// recovered via [`Event::SpendableOutputs`].
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`), but won't be persisted across restarts. It will be
+	/// regenerated upon the next `channel_reestablish` from the peer after a restart.
+	///
+	/// [`UserConfig::manually_acknowledge_data_loss`]: crate::util::config::UserConfig::manually_acknowledge_data_loss
+	/// [`ChannelManager::acknowledge_data_loss`]: crate::ln::channelmanager::ChannelManager::acknowledge_data_loss
+	LocalDataLossDetected {
+		/// The `channel_id` of the channel for which we have lost data.
+		channel_id: ChannelId,
+		/// The counterparty in the channel.
+		counterparty_node_id: PublicKey,
+		/// The number of our current commitment transaction, according to our state.
+		our_commitment_number: u64,
+		/// The minimum number of our current commitment transaction, as proven by our counterparty
+		/// by revealing the secret we released upon revoking the previous one. This is always
+		/// greater than `our_commitment_number`.
+		their_claimed_number: u64,
+	},
+	/// Indicates that all channels of a batch initiated via
+	/// [`ChannelManager::close_channels_with_peer`] have either closed or remained open past the
+	/// batch's timeout.
+	///
+	/// This is generated in addition to the [`Event::ChannelClosed`] events for the individual
+	/// channels, after all of them.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
+	///
+	/// [`ChannelManager::close_channels_with_peer`]: crate::ln::channelmanager::ChannelManager::close_channels_with_peer
+	BatchedChannelCloseCompleted {
+		/// The counterparty of all channels in the batch.
+		counterparty_node_id: PublicKey,
+		/// The outcome for each channel in the batch.
+		results: Vec<BatchedChannelCloseResult>,
+	},
+	/// Indicates that UTXOs earmarked for the anchor channel reserve were spent by something other
+	/// than LDK's own claims, e.g., by accidentally using them to fund an unrelated transaction.
+	///
+	/// This event is only generated if an [`AnchorReserveProvider`] was registered via
+	/// [`ChainMonitor::register_anchor_reserve_provider`]. Without a sufficient reserve, force-closes
+	/// of anchor channels may fail to confirm in time, so the reserve should be replenished as soon
+	/// as possible.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`), but won't be persisted across restarts.
+	///
+	/// [`AnchorReserveProvider`]: crate::util::anchor_channel_reserves::AnchorReserveProvider
+	/// [`ChainMonitor::register_anchor_reserve_provider`]: crate::chain::chainmonitor::ChainMonitor::register_anchor_reserve_provider
+	AnchorReserveUtxosSpent {
+		/// The outpoints of the reserve UTXOs which were spent externally since the last check.
+		spent_outpoints: Vec<OutPoint>,
+		/// The number of anchor channels which the remaining reserve can no longer support at
+		/// [`AnchorChannelReserveContext::upper_bound_fee_rate`].
+		///
+		/// This may be zero if the remaining reserve is still sufficient.
+		///
+		/// [`AnchorChannelReserveContext::upper_bound_fee_rate`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext::upper_bound_fee_rate
+		num_affected_channels: u64,
+	},
+	/// Indicates that we deferred fee-bumping our commitment transaction after force-closing an
+	/// anchor channel, as no HTLCs are at risk and claiming it at the target feerate would cost
+	/// more than the fraction of our balance set via
+	/// [`ChannelMonitor::set_max_force_close_fee_proportional_millionths`].
+	///
+	/// Our commitment transaction is only bumped to the lowest feerate required to enter the
+	/// mempool until claiming it at the target feerate becomes economical again, so our balance
+	/// may remain unavailable for a while.
+	///
+	/// This event is generated once each time a claim becomes deferred.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`), but won't be persisted across restarts. It is generated
+	/// again after restarts if the claim remains deferred.
+	///
+	/// [`ChannelMonitor::set_max_force_close_fee_proportional_millionths`]: crate::chain::channelmonitor::ChannelMonitor::set_max_force_close_fee_proportional_millionths
+	ForceCloseClaimDeferred {
+		/// The `channel_id` of the channel which has been closed.
+		channel_id: ChannelId,
+		/// Counterparty in the closed channel.
+		counterparty_node_id: PublicKey,
+		/// The txid of our commitment transaction whose claim was deferred.
+		commitment_txid: Txid,
+		/// The lowest feerate, in satoshis per 1000 weight units, our commitment transaction is
+		/// bumped to instead.
+		target_feerate_sat_per_1000_weight: u32,
+		/// The estimated fee, in satoshis, we would have paid to claim our commitment transaction
+		/// at the target feerate.
+		claim_fee_satoshis: u64,
+		/// Our balance in the commitment transaction, in satoshis.
+		balance_satoshis: u64,
+	},
+	/// Indicates that an anchor channel started being resolved on-chain, i.e., that we broadcast
+	/// our commitment transaction or that a commitment transaction confirmed on-chain.
+	///
+	/// From this point on, resolving the channel may require the anchor channel reserve to bump
+	/// the fees of the commitment and HTLC transactions, so it can be used to lock the UTXOs of the
+	/// reserve allocated to the channel immediately rather than once the first
+	/// [`Event::BumpTransaction`] is generated.
+	///
+	/// This event is generated exactly once per channel, for the first commitment transaction we
+	/// broadcast or see confirmed, and only for channels with anchor outputs. It is only provided
+	/// if enabled via [`ChainMonitor::set_anchor_resolution_events_enabled`].
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
+	///
+	/// [`ChainMonitor::set_anchor_resolution_events_enabled`]: crate::chain::chainmonitor::ChainMonitor::set_anchor_resolution_events_enabled
+	AnchorChannelResolutionStarted {
+		/// The `channel_id` of the channel which is being resolved.
+		channel_id: ChannelId,
+		/// Counterparty in the channel.
+		counterparty_node_id: PublicKey,
+		/// The txid of the commitment transaction which is being resolved.
+		///
+		/// Note that a different commitment transaction may eventually confirm if this one was
+		/// broadcast by us.
+		commitment_txid: Txid,
+		/// The number of HTLC outputs in the commitment transaction, which may have to be claimed
+		/// on-chain.
+		num_pending_htlc_outputs: u32,
+		/// Whether the commitment transaction is ours, i.e., whether we force-closed the channel,
+		/// rather than our counterparty.
+		holder_force_closed: bool,
+	},
+	/// Indicates that no new best block has been seen for longer than
+	/// [`UserConfig::chain_sync_stale_threshold_secs`], which may indicate that our chain source
+	/// stopped working.
+	///
+	/// With a stale view of the chain, HTLC timeouts may not be acted on in time, so the chain
+	/// source should be checked. If [`UserConfig::fail_htlc_forwards_while_chain_sync_stale`] is
+	/// set, HTLC forwards are failed until a new best block is seen.
+	///
+	/// This event is generated once each time our view of the chain becomes stale, and only with
+	/// the `std` feature enabled.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`), but won't be persisted across restarts. It is generated
+	/// again after restarts if no new best block is seen in time.
+	///
+	/// [`UserConfig::chain_sync_stale_threshold_secs`]: crate::util::config::UserConfig::chain_sync_stale_threshold_secs
+	/// [`UserConfig::fail_htlc_forwards_while_chain_sync_stale`]: crate::util::config::UserConfig::fail_htlc_forwards_while_chain_sync_stale
+	ChainSyncStale {
+		/// The hash of the best block we have seen.
+		last_block_hash: BlockHash,
+		/// The number of seconds since the best block was seen, or since startup if no new best
+		/// block has been seen since.
+		last_update_age_secs: u64,
+	},
+	/// Indicates that a node watched via [`NetworkGraph::watch_node_announcements`] announced a new
+	/// set of addresses in a `node_announcement`.
+	///
+	/// This is typically used to keep the addresses used to reconnect to channel partners up to
+	/// date without waiting for the next query of the [`NetworkGraph`]. Only a single event is
+	/// generated per announcement timestamp, and none is generated if only other fields of the
+	/// announcement changed.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`), but won't be persisted across restarts.
+	///
+	/// [`NetworkGraph::watch_node_announcements`]: crate::routing::gossip::NetworkGraph::watch_node_announcements
+	/// [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+	ChannelPartnerAnnouncementChanged {
+		/// The node id of the node which announced new addresses.
+		node_id: PublicKey,
+		/// The addresses the node announced, replacing any previously announced addresses.
+		addresses: Vec<msgs::SocketAddress>,
+		/// The alias the node announced alongside its addresses.
+		alias: NodeAlias,
+	},
+	/// Indicates that the local balance of a channel moved into a different [`LiquidityBand`],
+	/// crossing one of the thresholds configured in
+	/// [`ChannelConfig::liquidity_alert_thresholds`].
+	///
+	/// The local balance is evaluated whenever an update to the channel's commitment transactions
+	/// completes, e.g., once an HTLC has been resolved, rather than periodically. To avoid an
+	/// event for every HTLC while the balance oscillates around a threshold, a band is only left
+	/// once the balance moved back past its threshold by
+	/// [`LiquidityAlertThresholds::hysteresis_percent`].
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
+	///
+	/// [`ChannelConfig::liquidity_alert_thresholds`]: crate::util::config::ChannelConfig::liquidity_alert_thresholds
+	/// [`LiquidityAlertThresholds::hysteresis_percent`]: crate::util::config::LiquidityAlertThresholds::hysteresis_percent
+	ChannelLiquidityThresholdCrossed {
+		/// The `channel_id` of the channel.
+		channel_id: ChannelId,
+		/// The `user_channel_id` of the channel, as passed to [`ChannelManager::create_channel`]
+		/// for outbound channels, or to [`ChannelManager::accept_inbound_channel`] for inbound
+		/// channels.
+		///
+		/// [`ChannelManager::create_channel`]: crate::ln::channelmanager::ChannelManager::create_channel
+		/// [`ChannelManager::accept_inbound_channel`]: crate::ln::channelmanager::ChannelManager::accept_inbound_channel
+		user_channel_id: u128,
+		/// The node id of the channel counterparty.
+		counterparty_node_id: PublicKey,
+		/// Whether the local balance decreased or increased into the new band.
+		direction: LiquidityThresholdDirection,
+		/// The band the local balance moved into.
+		band: LiquidityBand,
+		/// Our balance in the channel, excluding pending HTLCs and the commitment transaction fee.
+		local_balance_msat: u64,
+		/// The capacity of the channel.
+		channel_value_satoshis: u64,
+	},
+	/// Indicates that we received the first part of a multi-part payment to one of our invoices,
+	/// which does not cover the payment's total value yet.
+	///
+	/// Once the remaining parts arrive, [`Event::PaymentClaimable`] is generated as usual.
+	/// Otherwise, the parts received so far are failed back around the `deadline` and
+	/// [`Event::PartialPaymentFailed`] is generated.
+	///
+	/// This event will only be generated if [`UserConfig::notify_partial_mpp_payments`] is set.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
+	///
+	/// [`UserConfig::notify_partial_mpp_payments`]: crate::util::config::UserConfig::notify_partial_mpp_payments
+	PaymentPartiallyReceived {
+		/// The hash for which the preimage should be handed to the ChannelManager.
+		payment_hash: PaymentHash,
+		/// The value, in thousandths of a satoshi, of the part received.
+		received_msat: u64,
+		/// The total value, in thousandths of a satoshi, the sender intends to pay, i.e., the value
+		/// the parts have to add up to for the payment to become claimable.
+		expected_msat: u64,
+		/// The time, in seconds since the UNIX epoch, after which the parts received so far are
+		/// failed back if the remaining parts haven't arrived, as determined by
+		/// [`UserConfig::mpp_timeout_secs`].
+		///
+		/// This is only an estimate, as the timeout is enforced in calls to
+		/// [`ChannelManager::expire_stale_inbound_payments`]. Without the `std` feature, it is
+		/// based on the highest block timestamp seen instead of the current time.
+		///
+		/// [`UserConfig::mpp_timeout_secs`]: crate::util::config::UserConfig::mpp_timeout_secs
+		/// [`ChannelManager::expire_stale_inbound_payments`]: crate::ln::channelmanager::ChannelManager::expire_stale_inbound_payments
+		deadline: u64,
+	},
+	/// Indicates that the parts of a multi-part payment received so far were failed back before
+	/// the payment became claimable, following an [`Event::PaymentPartiallyReceived`].
+	///
+	/// This event will only be generated if [`UserConfig::notify_partial_mpp_payments`] is set.
+	///
+	/// # Failure Behavior and Persistence
+	/// This event will eventually be replayed after failures-to-handle (i.e., the event handler
+	/// returning `Err(ReplayEvent ())`) and will be persisted across restarts.
+	///
+	/// [`UserConfig::notify_partial_mpp_payments`]: crate::util::config::UserConfig::notify_partial_mpp_payments
+	PartialPaymentFailed {
+		/// The hash of the payment whose parts were failed back.
+		payment_hash: PaymentHash,
+		/// The total value, in thousandths of a satoshi, of the parts failed back.
+		received_msat: u64,
+		/// The total value, in thousandths of a satoshi, the sender intended to pay.
+		expected_msat: u64,
+		/// Why the parts were failed back.
+		reason: PartialPaymentFailureReason,
+	},
 	/// We received an onion message that is intended to be forwarded to a peer
 	/// that is currently offline. This event will only be generated if the
 	/// `OnionMessenger` was initialized with
@@ -2047,11 +2535,118 @@ impl Writeable for Event {
 				}
 				write_tlv_fields!(writer, {}); // Write a length field for forwards compat
 			},
+			&Event::BumpTransactionFailed { .. } => {
+				49u8.write(writer)?;
+				// Never write BumpTransactionFailed events as they'll be regenerated upon further
+				// failed attempts to bump the claim.
+			},
+			&Event::MonitorPersistenceFailed { .. } => {
+				51u8.write(writer)?;
+				// We never write out MonitorPersistenceFailed events as the buffered updates they
+				// refer to are lost on restart.
+			},
+			&Event::LocalDataLossDetected { .. } => {
+				53u8.write(writer)?;
+				// We never write out LocalDataLossDetected events as they'll be regenerated upon
+				// the next channel_reestablish from the peer.
+			},
+			&Event::BatchedChannelCloseCompleted { ref counterparty_node_id, ref results } => {
+				55u8.write(writer)?;
+				write_tlv_fields!(writer, {
+					(0, counterparty_node_id, required),
+					(2, results, required_vec),
+				});
+			},
+			&Event::AnchorReserveUtxosSpent { .. } => {
+				57u8.write(writer)?;
+				// We never write out AnchorReserveUtxosSpent events as the reserve UTXOs are tracked
+				// from scratch upon restart.
+			},
+			&Event::ChannelPartnerAnnouncementChanged { .. } => {
+				59u8.write(writer)?;
+				// We never write out ChannelPartnerAnnouncementChanged events as the addresses are
+				// available from the network graph upon restart.
+			},
+			&Event::ChannelLiquidityThresholdCrossed {
+				ref channel_id,
+				ref user_channel_id,
+				ref counterparty_node_id,
+				ref direction,
+				ref band,
+				ref local_balance_msat,
+				ref channel_value_satoshis,
+			} => {
+				61u8.write(writer)?;
+				write_tlv_fields!(writer, {
+					(0, channel_id, required),
+					(2, user_channel_id, required),
+					(4, counterparty_node_id, required),
+					(6, direction, required),
+					(8, band, required),
+					(10, local_balance_msat, required),
+					(12, channel_value_satoshis, required),
+				});
+			},
+			&Event::PaymentPartiallyReceived {
+				ref payment_hash,
+				ref received_msat,
+				ref expected_msat,
+				ref deadline,
+			} => {
+				63u8.write(writer)?;
+				write_tlv_fields!(writer, {
+					(0, payment_hash, required),
+					(2, received_msat, required),
+					(4, expected_msat, required),
+					(6, deadline, required),
+				});
+			},
+			&Event::PartialPaymentFailed {
+				ref payment_hash,
+				ref received_msat,
+				ref expected_msat,
+				ref reason,
+			} => {
+				65u8.write(writer)?;
+				write_tlv_fields!(writer, {
+					(0, payment_hash, required),
+					(2, received_msat, required),
+					(4, expected_msat, required),
+					(6, reason, required),
+				});
+			},
+			&Event::ForceCloseClaimDeferred { .. } => {
+				67u8.write(writer)?;
+				// We never write out ForceCloseClaimDeferred events as they'll be regenerated along
+				// with the claim upon restart.
+			},
+			&Event::AnchorChannelResolutionStarted {
+				ref channel_id,
+				ref counterparty_node_id,
+				ref commitment_txid,
+				ref num_pending_htlc_outputs,
+				ref holder_force_closed,
+			} => {
+				69u8.write(writer)?;
+				write_tlv_fields!(writer, {
+					(0, channel_id, required),
+					(2, counterparty_node_id, required),
+					(4, commitment_txid, required),
+					(6, num_pending_htlc_outputs, required),
+					(8, holder_force_closed, required),
+				});
+			},
+			&Event::ChainSyncStale { .. } => {
+				71u8.write(writer)?;
+				// We never write out ChainSyncStale events as they'll be regenerated upon restart if
+				// our view of the chain remains stale.
+			},
 			&Event::ChannelReady {
 				ref channel_id,
 				ref user_channel_id,
 				ref counterparty_node_id,
 				ref funding_txo,
+				ref channel_value_satoshis,
 				ref channel_type,
 			} => {
 				29u8.write(writer)?;
@@ -2059,6 +2654,7 @@ impl Writeable for Event {
 					(0, channel_id, required),
 					(1, funding_txo, option),
 					(2, user_channel_id, required),
+					(3, channel_value_satoshis, option),
 					(4, counterparty_node_id, required),
 					(6, channel_type, required),
 				});
@@ -2585,11 +3181,13 @@ impl MaybeReadable for Event {
 					let mut user_channel_id: u128 = 0;
 					let mut counterparty_node_id = RequiredWrapper(None);
 					let mut funding_txo = None;
+					let mut channel_value_satoshis = None;
 					let mut channel_type = RequiredWrapper(None);
 					read_tlv_fields!(reader, {
 						(0, channel_id, required),
 						(1, funding_txo, option),
 						(2, user_channel_id, required),
+						(3, channel_value_satoshis, option),
 						(4, counterparty_node_id, required),
 						(6, channel_type, required),
 					});
@@ -2599,6 +3197,7 @@ impl MaybeReadable for Event {
 						user_channel_id,
 						counterparty_node_id: counterparty_node_id.0.unwrap(),
 						funding_txo,
+						channel_value_satoshis,
 						channel_type: channel_type.0.unwrap(),
 					}))
 				};
@@ -2716,6 +3315,110 @@ impl MaybeReadable for Event {
 			// Note that we do not write a length-prefixed TLV for StaticInvoiceRequested events.
 			#[cfg(async_payments)]
 			47u8 => Ok(None),
+			// Note that we do not write a length-prefixed TLV for BumpTransactionFailed events.
+			49u8 => Ok(None),
+			// Note that we do not write a length-prefixed TLV for MonitorPersistenceFailed events.
+			51u8 => Ok(None),
+			// Note that we do not write a length-prefixed TLV for LocalDataLossDetected events.
+			53u8 => Ok(None),
+			55u8 => {
+				let mut f = || {
+					_init_and_read_len_prefixed_tlv_fields!(reader, {
+						(0, counterparty_node_id, required),
+						(2, results, required_vec),
+					});
+					Ok(Some(Event::BatchedChannelCloseCompleted {
+						counterparty_node_id: counterparty_node_id.0.unwrap(),
+						results,
+					}))
+				};
+				f()
+			},
+			// Note that we do not write a length-prefixed TLV for AnchorReserveUtxosSpent events.
+			57u8 => Ok(None),
+			// Note that we do not write a length-prefixed TLV for ChannelPartnerAnnouncementChanged
+			// events.
+			59u8 => Ok(None),
+			61u8 => {
+				let mut f = || {
+					_init_and_read_len_prefixed_tlv_fields!(reader, {
+						(0, channel_id, required),
+						(2, user_channel_id, required),
+						(4, counterparty_node_id, required),
+						(6, direction, required),
+						(8, band, required),
+						(10, local_balance_msat, required),
+						(12, channel_value_satoshis, required),
+					});
+					Ok(Some(Event::ChannelLiquidityThresholdCrossed {
+						channel_id: channel_id.0.unwrap(),
+						user_channel_id: user_channel_id.0.unwrap(),
+						counterparty_node_id: counterparty_node_id.0.unwrap(),
+						direction: direction.0.unwrap(),
+						band: band.0.unwrap(),
+						local_balance_msat: local_balance_msat.0.unwrap(),
+						channel_value_satoshis: channel_value_satoshis.0.unwrap(),
+					}))
+				};
+				f()
+			},
+			63u8 => {
+				let mut f = || {
+					_init_and_read_len_prefixed_tlv_fields!(reader, {
+						(0, payment_hash, required),
+						(2, received_msat, required),
+						(4, expected_msat, required),
+						(6, deadline, required),
+					});
+					Ok(Some(Event::PaymentPartiallyReceived {
+						payment_hash: payment_hash.0.unwrap(),
+						received_msat: received_msat.0.unwrap(),
+						expected_msat: expected_msat.0.unwrap(),
+						deadline: deadline.0.unwrap(),
+					}))
+				};
+				f()
+			},
+			65u8 => {
+				let mut f = || {
+					_init_and_read_len_prefixed_tlv_fields!(reader, {
+						(0, payment_hash, required),
+						(2, received_msat, required),
+						(4, expected_msat, required),
+						(6, reason, required),
+					});
+					Ok(Some(Event::PartialPaymentFailed {
+						payment_hash: payment_hash.0.unwrap(),
+						received_msat: received_msat.0.unwrap(),
+						expected_msat: expected_msat.0.unwrap(),
+						reason: reason.0.unwrap(),
+					}))
+				};
+				f()
+			},
+			// Note that we do not write a length-prefixed TLV for ForceCloseClaimDeferred events.
+			67u8 => Ok(None),
+			69u8 => {
+				let mut f = || {
+					_init_and_read_len_prefixed_tlv_fields!(reader, {
+						(0, channel_id, required),
+						(2, counterparty_node_id, required),
+						(4, commitment_txid, required),
+						(6, num_pending_htlc_outputs, required),
+						(8, holder_force_closed, required),
+					});
+					Ok(Some(Event::AnchorChannelResolutionStarted {
+						channel_id: channel_id.0.unwrap(),
+						counterparty_node_id: counterparty_node_id.0.unwrap(),
+						commitment_txid: commitment_txid.0.unwrap(),
+						num_pending_htlc_outputs: num_pending_htlc_outputs.0.unwrap(),
+						holder_force_closed: holder_force_closed.0.unwrap(),
+					}))
+				};
+				f()
+			},
+			// Note that we do not write a length-prefixed TLV for ChainSyncStale events.
+			71u8 => Ok(None),
 			// Versions prior to 0.0.100 did not ignore odd types, instead returning InvalidValue.
 			// Version 0.0.100 failed to properly ignore odd types, possibly resulting in corrupt
 			// reads.
@@ -70,6 +70,7 @@ extern crate parking_lot;
 #[macro_use]
 pub mod util;
 
+pub mod api;
 pub mod blinded_path;
 pub mod chain;
 pub mod events;
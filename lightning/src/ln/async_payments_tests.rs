@@ -15,7 +15,7 @@ use crate::events::{
 	Event, HTLCHandlingFailureType, PaidBolt12Invoice, PaymentFailureReason, PaymentPurpose,
 };
 use crate::ln::blinded_payment_tests::{fail_blinded_htlc_backwards, get_blinded_route_parameters};
-use crate::ln::channelmanager::{PaymentId, RecipientOnionFields};
+use crate::ln::channelmanager::{PaymentId, RecentPaymentDetails, RecipientOnionFields};
 use crate::ln::functional_test_utils::*;
 use crate::ln::msgs;
 use crate::ln::msgs::{
@@ -23,9 +23,7 @@ use crate::ln::msgs::{
 };
 use crate::ln::offers_tests;
 use crate::ln::onion_utils::LocalHTLCFailureReason;
-use crate::ln::outbound_payment::{
-	PendingOutboundPayment, Retry, TEST_ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY,
-};
+use crate::ln::outbound_payment::{PendingOutboundPayment, Retry};
 use crate::offers::async_receive_offer_cache::{
 	TEST_MAX_CACHED_OFFERS_TARGET, TEST_MAX_UPDATE_ATTEMPTS,
 	TEST_MIN_OFFER_PATHS_RELATIVE_EXPIRY_SECS, TEST_OFFER_REFRESH_THRESHOLD,
@@ -687,10 +685,20 @@ fn expired_static_invoice_fail() {
 #[cfg_attr(feature = "std", ignore)]
 #[test]
 fn timeout_unreleased_payment() {
+	do_timeout_unreleased_payment(None);
+	do_timeout_unreleased_payment(Some(60 * 60));
+}
+
+fn do_timeout_unreleased_payment(hold_timeout_secs: Option<u64>) {
 	// If a server holds a pending HTLC for too long, payment is considered expired.
 	let chanmon_cfgs = create_chanmon_cfgs(3);
 	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
-	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+	let mut sender_cfg = test_default_channel_config();
+	if let Some(hold_timeout_secs) = hold_timeout_secs {
+		sender_cfg.async_payment_hold_timeout_secs = hold_timeout_secs;
+	}
+	let hold_timeout = Duration::from_secs(sender_cfg.async_payment_hold_timeout_secs);
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[Some(sender_cfg), None, None]);
 	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
 	create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
 	create_unannounced_chan_between_nodes_with_value(&nodes, 1, 2, 1_000_000, 0);
@@ -733,12 +741,18 @@ fn timeout_unreleased_payment() {
 
 	// We handle the static invoice to held the pending HTLC
 	sender.onion_messenger.handle_onion_message(server.node.get_our_node_id(), &static_invoice_om);
+	let recent_payments = sender.node.list_recent_payments();
+	assert_eq!(recent_payments.len(), 1);
+	match recent_payments[0] {
+		RecentPaymentDetails::AwaitingRelease { payment_id: held_payment_id, .. } => {
+			assert_eq!(held_payment_id, payment_id);
+		},
+		_ => panic!(),
+	}
 
 	// We advance enough time to expire the payment.
 	// We add 2 hours as is the margin added to remove stale payments in non-std implementation.
-	let timeout_time_expiry = TEST_ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY
-		+ Duration::from_secs(7200)
-		+ Duration::from_secs(1);
+	let timeout_time_expiry = hold_timeout + Duration::from_secs(7200) + Duration::from_secs(1);
 	advance_time_by(timeout_time_expiry, sender);
 	sender.node.timer_tick_occurred();
 	let events = sender.node.get_and_clear_pending_events();
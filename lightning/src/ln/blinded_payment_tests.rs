@@ -38,6 +38,7 @@ use crate::prelude::*;
 use crate::routing::router::{BlindedTail, Path, Payee, PaymentParameters, RouteHop, RouteParameters, TrampolineHop};
 use crate::sign::{NodeSigner, PeerStorageKey, ReceiveAuthKey, Recipient};
 use crate::util::config::UserConfig;
+use crate::util::message_signing::MessageSigningKeyId;
 use crate::util::ser::{WithoutLength, Writeable};
 use crate::util::test_utils;
 use lightning_invoice::RawBolt11Invoice;
@@ -1635,6 +1636,10 @@ fn route_blinding_spec_test_vector() {
 		fn sign_gossip_message(&self, _msg: UnsignedGossipMessage) -> Result<Signature, ()> { unreachable!() }
 
 		fn sign_message(&self, msg: &[u8]) -> Result<String, ()> { Ok(crate::util::message_signing::sign(msg, &self.node_secret)) }
+		fn get_message_signing_pubkey(&self, _key_id: MessageSigningKeyId) -> Result<PublicKey, ()> { unreachable!() }
+		fn sign_message_with_derived_key(
+			&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+		) -> Result<String, ()> { unreachable!() }
 	}
 	let logger = test_utils::TestLogger::with_id("".to_owned());
 
@@ -1947,6 +1952,10 @@ fn test_trampoline_inbound_payment_decoding() {
 		) -> Result<schnorr::Signature, ()> { unreachable!() }
 		fn sign_gossip_message(&self, _msg: UnsignedGossipMessage) -> Result<Signature, ()> { unreachable!() }
 		fn sign_message(&self, msg: &[u8]) -> Result<String, ()> { Ok(crate::util::message_signing::sign(msg, &self.node_secret)) }
+		fn get_message_signing_pubkey(&self, _key_id: MessageSigningKeyId) -> Result<PublicKey, ()> { unreachable!() }
+		fn sign_message_with_derived_key(
+			&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+		) -> Result<String, ()> { unreachable!() }
 	}
 	let logger = test_utils::TestLogger::with_id("".to_owned());
 
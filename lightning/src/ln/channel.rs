@@ -14,7 +14,7 @@ use bitcoin::constants::ChainHash;
 use bitcoin::script::{Builder, Script, ScriptBuf, WScriptHash};
 use bitcoin::sighash::EcdsaSighashType;
 use bitcoin::transaction::{Transaction, TxIn, TxOut};
-use bitcoin::Weight;
+use bitcoin::{FeeRate, Weight};
 
 use bitcoin::hash_types::{BlockHash, Txid};
 use bitcoin::hashes::sha256::Hash as Sha256;
@@ -38,7 +38,7 @@ use crate::chain::channelmonitor::{
 use crate::chain::transaction::{OutPoint, TransactionData};
 use crate::chain::BestBlock;
 use crate::events::bump_transaction::BASE_INPUT_WEIGHT;
-use crate::events::{ClosureReason, Event};
+use crate::events::{ClosureReason, Event, LiquidityBand, LiquidityThresholdDirection};
 use crate::ln::chan_utils;
 #[cfg(splicing)]
 use crate::ln::chan_utils::FUNDING_TRANSACTION_WITNESS_WEIGHT;
@@ -49,8 +49,9 @@ use crate::ln::chan_utils::{
 	CounterpartyCommitmentSecrets, HTLCOutputInCommitment, HolderCommitmentTransaction,
 };
 use crate::ln::channel_state::{
-	ChannelShutdownState, CounterpartyForwardingInfo, InboundHTLCDetails, InboundHTLCStateDetails,
-	OutboundHTLCDetails, OutboundHTLCStateDetails,
+	ChannelReceivableAmount, ChannelShutdownState, CooperativeCloseFeeEstimate,
+	CounterpartyForwardingInfo, DustExposureProjection, HoldingCellSize, InboundHTLCDetails,
+	InboundHTLCStateDetails, OutboundHTLCDetails, OutboundHTLCStateDetails, ReceiveConstraint,
 };
 use crate::ln::channelmanager::{
 	self, FundingConfirmedMessage, HTLCFailureMsg, HTLCSource, OpenChannelMessage,
@@ -78,6 +79,7 @@ use crate::sign::tx_builder::{SpecTxBuilder, TxBuilder};
 use crate::sign::{ChannelSigner, EntropySource, NodeSigner, Recipient, SignerProvider};
 use crate::types::features::{ChannelTypeFeatures, InitFeatures};
 use crate::types::payment::{PaymentHash, PaymentPreimage};
+use crate::util::anchor_channel_reserves::{estimate_force_close_cost, ForceCloseCostEstimate};
 use crate::util::config::{
 	ChannelConfig, ChannelHandshakeConfig, ChannelHandshakeLimits, LegacyChannelConfig,
 	MaxDustHTLCExposure, UserConfig,
@@ -1075,7 +1077,7 @@ macro_rules! secp_check {
 /// our counterparty or not. However, we don't want to announce updates right away to avoid
 /// spamming the network with updates if the connection is flapping. Instead, we "stage" updates to
 /// our channel_update message and track the current state here.
-/// See implementation at [`super::channelmanager::ChannelManager::timer_tick_occurred`].
+/// See implementation at [`super::channelmanager::ChannelManager::process_channel_update_status`].
 #[derive(Clone, Copy, PartialEq)]
 pub(super) enum ChannelUpdateStatus {
 	/// We've announced the channel as enabled and are connected to our peer.
@@ -1108,6 +1110,15 @@ pub enum AnnouncementSigsState {
 	PeerReceived,
 }
 
+/// Returns the height at which an outbound HTLC with the given `cltv_expiry` which is still in our
+/// holding cell is failed back rather than forwarded.
+///
+/// This mirrors the check in ChannelManager::decode_update_add_htlc_onion, refusing to forward an
+/// HTLC when our counterparty should almost certainly just fail it for expiring ~now.
+fn holding_cell_htlc_fail_back_height(cltv_expiry: u32) -> u32 {
+	cltv_expiry.saturating_sub(LATENCY_GRACE_PERIOD_BLOCKS)
+}
+
 /// An enum indicating whether the local or remote side offered a given HTLC.
 enum HTLCInitiator {
 	LocalOffered,
@@ -1741,6 +1752,15 @@ where
 		}
 	}
 
+	pub fn interactive_tx_constructor(&self) -> Option<&InteractiveTxConstructor> {
+		match &self.phase {
+			ChannelPhase::UnfundedV2(chan) => chan.interactive_tx_constructor.as_ref(),
+			#[cfg(splicing)]
+			ChannelPhase::Funded(chan) => chan.interactive_tx_constructor(),
+			_ => None,
+		}
+	}
+
 	pub fn interactive_tx_constructor_mut(&mut self) -> Option<&mut InteractiveTxConstructor> {
 		match &mut self.phase {
 			ChannelPhase::UnfundedV2(chan) => chan.interactive_tx_constructor.as_mut(),
@@ -1750,6 +1770,25 @@ where
 		}
 	}
 
+	/// Aborts the interactive funding transaction negotiation in progress, if any, returning the
+	/// outpoints of the inputs and the outputs we contributed to it.
+	///
+	/// Returns `None` if no negotiation was in progress, including if we already exchanged
+	/// consecutive `tx_complete` messages.
+	pub fn abort_interactive_tx_negotiation(
+		&mut self,
+	) -> Option<(Vec<bitcoin::OutPoint>, Vec<TxOut>)> {
+		match &mut self.phase {
+			ChannelPhase::UnfundedV2(chan) => chan
+				.interactive_tx_constructor
+				.take()
+				.map(|interactive_tx_constructor| interactive_tx_constructor.into_contribution()),
+			#[cfg(splicing)]
+			ChannelPhase::Funded(chan) => chan.abort_splice_negotiation(),
+			_ => None,
+		}
+	}
+
 	#[rustfmt::skip]
 	pub fn funding_signed<L: Deref>(
 		&mut self, msg: &msgs::FundingSigned, best_block: BestBlock, signer_provider: &SP, logger: &L
@@ -2028,7 +2067,7 @@ impl UnfundedChannelContext {
 	/// Determines whether we should force-close and purge this unfunded channel from memory due to it
 	/// having reached the unfunded channel age limit.
 	///
-	/// This should be called on every [`super::channelmanager::ChannelManager::timer_tick_occurred`].
+	/// This should be called on every [`super::channelmanager::ChannelManager::expire_stale_channels`].
 	pub fn should_expire_unfunded_channel(&mut self) -> bool {
 		self.unfunded_channel_age_ticks += 1;
 		self.unfunded_channel_age_ticks >= UNFUNDED_CHANNEL_AGE_LIMIT_TICKS
@@ -2219,6 +2258,11 @@ impl FundingScope {
 		&self.get_counterparty_pubkeys().funding_pubkey
 	}
 
+	/// Returns our and our counterparty's funding public keys, respectively.
+	pub(crate) fn get_funding_pubkeys(&self) -> (PublicKey, PublicKey) {
+		(self.get_holder_pubkeys().funding_pubkey, *self.counterparty_funding_pubkey())
+	}
+
 	/// Gets the channel's type
 	pub fn get_channel_type(&self) -> &ChannelTypeFeatures {
 		&self.channel_transaction_parameters.channel_type_features
@@ -2616,6 +2660,10 @@ where
 	pub(crate) closing_fee_limits: Option<(u64, u64)>,
 	#[cfg(not(any(test, feature = "_test_utils")))]
 	closing_fee_limits: Option<(u64, u64)>,
+	/// An upper bound on the absolute fee, in satoshis, we are willing to pay on the closing
+	/// transaction if we are the funder, overriding the limit derived from our
+	/// `ChannelConfig::force_close_avoidance_max_fee_satoshis`. This is not persisted.
+	max_closing_fee_satoshis: Option<u64>,
 
 	/// If we remove an HTLC (or fee update), commit, and receive our counterparty's
 	/// `revoke_and_ack`, we remove all knowledge of said HTLC (or fee update). However, the latest
@@ -2698,6 +2746,15 @@ where
 	/// See-also <https://github.com/lightningnetwork/lnd/issues/4006>
 	pub workaround_lnd_bug_4006: Option<msgs::ChannelReady>,
 
+	/// Set to our and our counterparty's view of our current commitment number if our
+	/// counterparty proved in a `channel_reestablish` that our state is stale while
+	/// [`UserConfig::manually_acknowledge_data_loss`] is set. While set, we refuse to reestablish
+	/// the channel and never broadcast our (revoked) commitment transaction upon closing.
+	///
+	/// Note that this field is not persisted, as we'll detect the data loss again upon the next
+	/// `channel_reestablish` after a restart.
+	local_data_loss: Option<(u64, u64)>,
+
 	/// An option set when we wish to track how many ticks have elapsed while waiting for a response
 	/// from our counterparty after entering specific states. If the peer has yet to respond after
 	/// reaching `DISCONNECT_PEER_AWAITING_RESPONSE_TICKS`, a reconnection should be attempted to
@@ -2743,6 +2800,10 @@ where
 	/// Some if we initiated to shut down the channel.
 	local_initiated_shutdown: Option<()>,
 
+	/// The band our balance was last determined to be in, if
+	/// [`ChannelConfig::liquidity_alert_thresholds`] are set.
+	liquidity_band: Option<LiquidityBand>,
+
 	/// The unique identifier used to re-derive the private key material for the channel through
 	/// [`SignerProvider::derive_channel_signer`].
 	#[cfg(not(any(test, feature = "_test_utils")))]
@@ -3282,6 +3343,7 @@ where
 			pending_counterparty_closing_signed: None,
 			expecting_peer_commitment_signed: false,
 			closing_fee_limits: None,
+			max_closing_fee_satoshis: None,
 			target_closing_feerate_sats_per_kw: None,
 
 			channel_creation_height: current_chain_height,
@@ -3315,6 +3377,7 @@ where
 			announcement_sigs: None,
 
 			workaround_lnd_bug_4006: None,
+			local_data_loss: None,
 			sent_message_awaiting_response: None,
 
 			latest_inbound_scid_alias: None,
@@ -3328,6 +3391,7 @@ where
 			channel_keys_id,
 
 			local_initiated_shutdown: None,
+			liquidity_band: None,
 
 			blocked_monitor_updates: Vec::new(),
 
@@ -3392,15 +3456,9 @@ where
 		);
 
 		let value_to_self_msat = channel_value_satoshis * 1000 - push_msat;
-		let commit_tx_fee_sat = SpecTxBuilder {}.commit_tx_fee_sat(commitment_feerate, MIN_AFFORDABLE_HTLC_COUNT, &channel_type);
-		// Subtract any non-HTLC outputs from the local balance
-		let (local_balance_before_fee_msat, _) = SpecTxBuilder {}.subtract_non_htlc_outputs(
-			true,
-			value_to_self_msat,
-			push_msat,
-			&channel_type,
-		);
-		if local_balance_before_fee_msat / 1000 < commit_tx_fee_sat {
+		if let Err(commit_tx_fee_sat) = check_outbound_commit_tx_fee_affordable(
+			channel_value_satoshis, push_msat, commitment_feerate, &channel_type,
+		) {
 			return Err(APIError::APIMisuseError{ err: format!("Funding amount ({}) can't even pay fee for initial commitment transaction fee of {}.", value_to_self_msat / 1000, commit_tx_fee_sat) });
 		}
 
@@ -3521,6 +3579,7 @@ where
 			pending_counterparty_closing_signed: None,
 			expecting_peer_commitment_signed: false,
 			closing_fee_limits: None,
+			max_closing_fee_satoshis: None,
 			target_closing_feerate_sats_per_kw: None,
 
 			channel_creation_height: current_chain_height,
@@ -3556,6 +3615,7 @@ where
 			announcement_sigs: None,
 
 			workaround_lnd_bug_4006: None,
+			local_data_loss: None,
 			sent_message_awaiting_response: None,
 
 			latest_inbound_scid_alias: None,
@@ -3570,6 +3630,7 @@ where
 
 			blocked_monitor_updates: Vec::new(),
 			local_initiated_shutdown: None,
+			liquidity_band: None,
 			is_manual_broadcast: false,
 
 			is_holder_quiescence_initiator: None,
@@ -3598,6 +3659,23 @@ where
 		self.config.announce_for_forwarding
 	}
 
+	/// Returns whether the real SCID and funding outpoint of this channel may be included in
+	/// messages sent to our counterparty, see
+	/// [`ChannelConfig::reveal_private_channel_identifiers`].
+	pub fn may_reveal_channel_identifiers(&self) -> bool {
+		self.should_announce() || self.config.options.reveal_private_channel_identifiers
+	}
+
+	/// Formats the given funding transaction id for inclusion in a message sent to our
+	/// counterparty, omitting it if [`Self::may_reveal_channel_identifiers`] is unset.
+	fn funding_txid_for_message(&self, funding_txid: &Txid) -> String {
+		if self.may_reveal_channel_identifiers() {
+			funding_txid.to_string()
+		} else {
+			"(omitted)".to_owned()
+		}
+	}
+
 	/// Gets the fee we'd want to charge for adding an HTLC output to this Channel
 	/// Allowed in any state (including after shutdown)
 	pub fn get_outbound_forwarding_fee_base_msat(&self) -> u32 {
@@ -3767,6 +3845,12 @@ where
 		self.user_id
 	}
 
+	/// Gets the `channel_keys_id` from which this channel's signer (and thus its shutdown and
+	/// destination scripts) was derived.
+	pub fn get_channel_keys_id(&self) -> [u8; 32] {
+		self.channel_keys_id
+	}
+
 	/// Allowed in any state (including after shutdown)
 	pub fn latest_inbound_scid_alias(&self) -> Option<u64> {
 		self.latest_inbound_scid_alias
@@ -3955,6 +4039,17 @@ where
 		self.counterparty_htlc_minimum_msat
 	}
 
+	/// Allowed in any state (including after shutdown)
+	pub fn get_holder_max_accepted_htlcs(&self) -> u16 {
+		self.holder_max_accepted_htlcs
+	}
+
+	/// Allowed in any state (including after shutdown), but will return zero before
+	/// TheirInitSent
+	pub fn get_counterparty_max_accepted_htlcs(&self) -> u16 {
+		self.counterparty_max_accepted_htlcs
+	}
+
 	/// Allowed in any state (including after shutdown), but will return none before TheirInitSent
 	pub fn get_counterparty_htlc_maximum_msat(&self, funding: &FundingScope) -> Option<u64> {
 		funding.get_htlc_maximum_msat(self.counterparty_max_htlc_value_in_flight_msat)
@@ -4381,6 +4476,35 @@ where
 			}
 		}
 
+		if let Some(multiplier_percent) = self.config.options.dust_classification_feerate_multiplier_percent {
+			// Unlike the dust buffer feerate above, which only covers a modest feerate increase,
+			// classify HTLCs as dust at a user-configured multiple of the current feerate.
+			let mut feerate_per_kw = self.feerate_per_kw;
+			if let Some((feerate, _)) = self.pending_update_fee {
+				feerate_per_kw = cmp::max(feerate_per_kw, feerate);
+			}
+			let projected_feerate = (feerate_per_kw as u64 * multiplier_percent as u64 / 100)
+				.try_into().unwrap_or(u32::MAX);
+			let (projected_htlc_success_tx_fee_sat, projected_htlc_timeout_tx_fee_sat) =
+				second_stage_tx_fees_sat(funding.get_channel_type(), projected_feerate);
+			let (on_holder_tx_projected_dust_exposure_msat, on_counterparty_tx_projected_dust_exposure_msat) =
+				self.get_dust_exposure_msat_at_feerate(funding, projected_feerate);
+			if msg.amount_msat / 1000 < projected_htlc_timeout_tx_fee_sat + self.counterparty_dust_limit_satoshis
+				&& on_counterparty_tx_projected_dust_exposure_msat > max_dust_htlc_exposure_msat
+			{
+				log_info!(logger, "Cannot accept value that would put our exposure to dust HTLCs at {} over the limit {} on counterparty commitment tx at projected feerate {} sat/kW",
+					on_counterparty_tx_projected_dust_exposure_msat, max_dust_htlc_exposure_msat, projected_feerate);
+				return Err(LocalHTLCFailureReason::DustLimitCounterparty)
+			}
+			if msg.amount_msat / 1000 < projected_htlc_success_tx_fee_sat + self.holder_dust_limit_satoshis
+				&& on_holder_tx_projected_dust_exposure_msat > max_dust_htlc_exposure_msat
+			{
+				log_info!(logger, "Cannot accept value that would put our exposure to dust HTLCs at {} over the limit {} on holder commitment tx at projected feerate {} sat/kW",
+					on_holder_tx_projected_dust_exposure_msat, max_dust_htlc_exposure_msat, projected_feerate);
+				return Err(LocalHTLCFailureReason::DustLimitHolder)
+			}
+		}
+
 		if !funding.is_outbound() {
 			let removed_outbound_total_msat: u64 = self.pending_outbound_htlcs
 				.iter()
@@ -4812,6 +4936,75 @@ where
 		}
 	}
 
+	/// Returns the total value of pending HTLCs which would be dust on the holder's and the
+	/// counterparty's commitment transactions, respectively, if the channel's feerate were
+	/// `feerate_per_kw`. HTLCs are classified the same way as in [`Self::get_pending_htlc_stats`],
+	/// but excess commitment transaction fees are not included.
+	fn get_dust_exposure_msat_at_feerate(
+		&self, funding: &FundingScope, feerate_per_kw: u32,
+	) -> (u64, u64) {
+		let (htlc_success_tx_fee_sat, htlc_timeout_tx_fee_sat) =
+			second_stage_tx_fees_sat(funding.get_channel_type(), feerate_per_kw);
+		let holder_dust_limit_success_sat =
+			htlc_success_tx_fee_sat + self.holder_dust_limit_satoshis;
+		let holder_dust_limit_timeout_sat =
+			htlc_timeout_tx_fee_sat + self.holder_dust_limit_satoshis;
+		let counterparty_dust_limit_success_sat =
+			htlc_success_tx_fee_sat + self.counterparty_dust_limit_satoshis;
+		let counterparty_dust_limit_timeout_sat =
+			htlc_timeout_tx_fee_sat + self.counterparty_dust_limit_satoshis;
+
+		let mut on_holder_tx_dust_exposure_msat = 0;
+		let mut on_counterparty_tx_dust_exposure_msat = 0;
+		for htlc in self.pending_inbound_htlcs.iter() {
+			if htlc.amount_msat / 1000 < holder_dust_limit_success_sat {
+				on_holder_tx_dust_exposure_msat += htlc.amount_msat;
+			}
+			if htlc.amount_msat / 1000 < counterparty_dust_limit_timeout_sat {
+				on_counterparty_tx_dust_exposure_msat += htlc.amount_msat;
+			}
+		}
+		let holding_cell_outbound_amounts =
+			self.holding_cell_htlc_updates.iter().filter_map(|update| match update {
+				HTLCUpdateAwaitingACK::AddHTLC { amount_msat, .. } => Some(*amount_msat),
+				_ => None,
+			});
+		let outbound_amounts = self.pending_outbound_htlcs.iter().map(|htlc| htlc.amount_msat);
+		for amount_msat in outbound_amounts.chain(holding_cell_outbound_amounts) {
+			if amount_msat / 1000 < holder_dust_limit_timeout_sat {
+				on_holder_tx_dust_exposure_msat += amount_msat;
+			}
+			if amount_msat / 1000 < counterparty_dust_limit_success_sat {
+				on_counterparty_tx_dust_exposure_msat += amount_msat;
+			}
+		}
+		(on_holder_tx_dust_exposure_msat, on_counterparty_tx_dust_exposure_msat)
+	}
+
+	/// Returns the feerate of our next commitment transaction along with the number of inbound
+	/// and outbound HTLCs which are not dust on it, i.e., which would need to be resolved with
+	/// HTLC transactions if it were broadcast.
+	fn get_holder_commitment_nondust_htlc_counts(
+		&self, funding: &FundingScope,
+	) -> (u32, usize, usize) {
+		let feerate_per_kw = self.get_commitment_feerate(funding, true);
+		let channel_type = funding.get_channel_type();
+		let dust_limit_sat = self.holder_dust_limit_satoshis;
+		let num_inbound_htlcs = self
+			.pending_inbound_htlcs
+			.iter()
+			.filter(|htlc| htlc.state.included_in_commitment(true))
+			.filter(|htlc| !htlc.is_dust(true, feerate_per_kw, dust_limit_sat, channel_type))
+			.count();
+		let num_outbound_htlcs = self
+			.pending_outbound_htlcs
+			.iter()
+			.filter(|htlc| htlc.state.included_in_commitment(true))
+			.filter(|htlc| !htlc.is_dust(true, feerate_per_kw, dust_limit_sat, channel_type))
+			.count();
+		(feerate_per_kw, num_inbound_htlcs, num_outbound_htlcs)
+	}
+
 	/// Returns information on all pending inbound HTLCs.
 	#[rustfmt::skip]
 	pub fn get_pending_inbound_htlc_details(&self, funding: &FundingScope) -> Vec<InboundHTLCDetails> {
@@ -5056,6 +5249,139 @@ where
 		}
 	}
 
+	/// Computes the maximum amount we can receive in a single HTLC and in total, along with the
+	/// respective limiting constraints. Mirrors the checks in `validate_update_add_htlc`.
+	fn get_receivable_amount_for_scope<F: Deref>(
+		&self, funding: &FundingScope, fee_estimator: &LowerBoundedFeeEstimator<F>,
+	) -> (u64, ReceiveConstraint, u64, ReceiveConstraint)
+	where
+		F::Target: FeeEstimator,
+	{
+		if !self.is_live() {
+			return (
+				0,
+				ReceiveConstraint::ChannelNotUsable,
+				0,
+				ReceiveConstraint::ChannelNotUsable,
+			);
+		}
+
+		let dust_exposure_limiting_feerate =
+			self.get_dust_exposure_limiting_feerate(&fee_estimator, funding.get_channel_type());
+		let htlc_stats = self.get_pending_htlc_stats(funding, None, dust_exposure_limiting_feerate);
+		if htlc_stats.pending_inbound_htlcs >= self.holder_max_accepted_htlcs as usize {
+			return (
+				0,
+				ReceiveConstraint::MaxAcceptedHtlcs,
+				0,
+				ReceiveConstraint::MaxAcceptedHtlcs,
+			);
+		}
+
+		let (local_balance_before_fee_msat, remote_balance_before_fee_msat) = SpecTxBuilder {}
+			.subtract_non_htlc_outputs(
+				funding.is_outbound(),
+				funding.value_to_self_msat,
+				(funding.get_value_satoshis() * 1000)
+					.saturating_sub(funding.value_to_self_msat)
+					.saturating_sub(htlc_stats.pending_inbound_htlcs_value_msat),
+				funding.get_channel_type(),
+			);
+
+		// HTLCs below the dust limit don't add an output to the commitment transaction, and thus
+		// don't increase its fee.
+		let (htlc_success_tx_fee_sat, htlc_timeout_tx_fee_sat) =
+			second_stage_tx_fees_sat(funding.get_channel_type(), self.feerate_per_kw);
+		let real_dust_limit_timeout_msat =
+			(htlc_timeout_tx_fee_sat + self.counterparty_dust_limit_satoshis) * 1000;
+		let htlc_above_dust =
+			HTLCCandidate::new(real_dust_limit_timeout_msat, HTLCInitiator::RemoteOffered);
+		let htlc_dust =
+			HTLCCandidate::new(real_dust_limit_timeout_msat - 1, HTLCInitiator::RemoteOffered);
+
+		let holder_selected_chan_reserve_msat =
+			funding.holder_selected_channel_reserve_satoshis * 1000;
+		let mut constraint = ReceiveConstraint::CounterpartyBalance;
+		let mut receivable_msat = if funding.is_outbound() {
+			let receivable_msat =
+				remote_balance_before_fee_msat.saturating_sub(holder_selected_chan_reserve_msat);
+			// As the funder, we pay the fee for the HTLC's output and have to keep the reserve our
+			// counterparty requires.
+			let counterparty_selected_chan_reserve_msat =
+				funding.counterparty_selected_channel_reserve_satoshis.unwrap_or(0) * 1000;
+			let real_dust_limit_success_msat =
+				(htlc_success_tx_fee_sat + self.holder_dust_limit_satoshis) * 1000;
+			let htlc_above_dust = HTLCCandidate::new(
+				cmp::max(real_dust_limit_success_msat, real_dust_limit_timeout_msat),
+				HTLCInitiator::RemoteOffered,
+			);
+			let local_commit_tx_fee_msat =
+				self.next_local_commit_tx_fee_msat(funding, htlc_above_dust, None);
+			if local_balance_before_fee_msat
+				< counterparty_selected_chan_reserve_msat + local_commit_tx_fee_msat
+				&& receivable_msat >= real_dust_limit_timeout_msat
+			{
+				constraint = ReceiveConstraint::HolderCommitmentFee;
+				real_dust_limit_timeout_msat - 1
+			} else {
+				receivable_msat
+			}
+		} else {
+			let remote_commit_tx_fee_msat =
+				self.next_remote_commit_tx_fee_msat(funding, Some(htlc_above_dust), None);
+			let receivable_msat = remote_balance_before_fee_msat
+				.saturating_sub(remote_commit_tx_fee_msat)
+				.saturating_sub(holder_selected_chan_reserve_msat);
+			if receivable_msat >= real_dust_limit_timeout_msat {
+				receivable_msat
+			} else {
+				let remote_commit_tx_fee_msat =
+					self.next_remote_commit_tx_fee_msat(funding, Some(htlc_dust), None);
+				cmp::min(
+					real_dust_limit_timeout_msat - 1,
+					remote_balance_before_fee_msat
+						.saturating_sub(remote_commit_tx_fee_msat)
+						.saturating_sub(holder_selected_chan_reserve_msat),
+				)
+			}
+		};
+
+		let remaining_in_flight_msat = self
+			.holder_max_htlc_value_in_flight_msat
+			.saturating_sub(htlc_stats.pending_inbound_htlcs_value_msat);
+		if remaining_in_flight_msat < receivable_msat {
+			receivable_msat = remaining_in_flight_msat;
+			constraint = ReceiveConstraint::MaxHtlcValueInFlight;
+		}
+
+		let (mut max_single_htlc_msat, mut single_htlc_constraint) = (receivable_msat, constraint);
+		let (mut max_total_msat, mut total_constraint) = (receivable_msat, constraint);
+		if let Some(htlc_maximum_msat) = self.get_holder_htlc_maximum_msat(funding) {
+			if htlc_maximum_msat < max_single_htlc_msat {
+				max_single_htlc_msat = htlc_maximum_msat;
+				single_htlc_constraint = ReceiveConstraint::MaxHtlcValue;
+			}
+			let remaining_htlcs =
+				self.holder_max_accepted_htlcs as u64 - htlc_stats.pending_inbound_htlcs as u64;
+			let max_slots_msat = htlc_maximum_msat.saturating_mul(remaining_htlcs);
+			if max_slots_msat < max_total_msat {
+				max_total_msat = max_slots_msat;
+				total_constraint = ReceiveConstraint::MaxAcceptedHtlcs;
+			}
+		}
+
+		if max_single_htlc_msat != 0 && max_single_htlc_msat < self.holder_htlc_minimum_msat {
+			max_single_htlc_msat = 0;
+			single_htlc_constraint = ReceiveConstraint::MinHtlcValue;
+		}
+		if max_total_msat != 0 && max_total_msat < self.holder_htlc_minimum_msat {
+			max_total_msat = 0;
+			total_constraint = ReceiveConstraint::MinHtlcValue;
+		}
+
+		(max_single_htlc_msat, single_htlc_constraint, max_total_msat, total_constraint)
+	}
+
 	/// Get the commitment tx fee for the local's (i.e. our) next commitment transaction based on the
 	/// number of pending HTLCs that are on track to be in our next commitment tx.
 	///
@@ -5304,6 +5630,14 @@ where
 		self.is_batch_funding.is_some()
 	}
 
+	/// Returns our and our counterparty's view of our current commitment number if our
+	/// counterparty proved that our state is stale, see [`Event::LocalDataLossDetected`].
+	///
+	/// [`Event::LocalDataLossDetected`]: crate::events::Event::LocalDataLossDetected
+	pub fn local_data_loss(&self) -> Option<(u64, u64)> {
+		self.local_data_loss
+	}
+
 	/// Returns the transaction ID if there is a pending batch funding transaction that is yet to be
 	/// broadcast.
 	pub fn unbroadcasted_batch_funding_txid(&self, funding: &FundingScope) -> Option<Txid> {
@@ -5321,7 +5655,9 @@ where
 		// be delayed in being processed! See the docs for `ChannelManagerReadArgs` for more.
 		assert!(!matches!(self.channel_state, ChannelState::ShutdownComplete));
 
-		let broadcast = self.is_funding_broadcast();
+		// If we've fallen behind, our latest commitment transaction has been revoked and
+		// broadcasting it would allow our counterparty to claim all of the channel's funds.
+		let broadcast = self.is_funding_broadcast() && self.local_data_loss.is_none();
 
 		// We go ahead and "free" any holding cell HTLCs or HTLCs we haven't yet committed to and
 		// return them to fail the payment.
@@ -6205,6 +6541,57 @@ where
 		self.context.force_shutdown(&self.funding, closure_reason)
 	}
 
+	#[cfg(splicing)]
+	fn interactive_tx_constructor(&self) -> Option<&InteractiveTxConstructor> {
+		self.pending_splice
+			.as_ref()
+			.and_then(|pending_splice| pending_splice.funding_negotiation.as_ref())
+			.and_then(|funding_negotiation| {
+				if let FundingNegotiation::ConstructingTransaction(_, interactive_tx_constructor) =
+					funding_negotiation
+				{
+					Some(interactive_tx_constructor)
+				} else {
+					None
+				}
+			})
+	}
+
+	/// Drops the pending splice if its funding transaction is still being negotiated, returning
+	/// the outpoints of the inputs and the outputs we contributed to it.
+	#[cfg(splicing)]
+	fn abort_splice_negotiation(&mut self) -> Option<(Vec<bitcoin::OutPoint>, Vec<TxOut>)> {
+		let is_negotiating = matches!(
+			self.pending_splice.as_ref()?.funding_negotiation,
+			Some(FundingNegotiation::AwaitingAck(_))
+				| Some(FundingNegotiation::ConstructingTransaction(..))
+		);
+		if !is_negotiating {
+			return None;
+		}
+		let funding_negotiation = self
+			.pending_splice
+			.take()
+			.and_then(|pending_splice| pending_splice.funding_negotiation);
+		match funding_negotiation {
+			Some(FundingNegotiation::AwaitingAck(funding_negotiation_context)) => {
+				let inputs = funding_negotiation_context
+					.our_funding_inputs
+					.iter()
+					.map(|(txin, _)| txin.previous_output)
+					.collect();
+				Some((inputs, Vec::new()))
+			},
+			Some(FundingNegotiation::ConstructingTransaction(_, interactive_tx_constructor)) => {
+				Some(interactive_tx_constructor.into_contribution())
+			},
+			_ => {
+				debug_assert!(false);
+				None
+			},
+		}
+	}
+
 	#[cfg(splicing)]
 	fn interactive_tx_constructor_mut(&mut self) -> Option<&mut InteractiveTxConstructor> {
 		self.pending_splice
@@ -6262,7 +6649,7 @@ where
 	}
 
 	#[inline]
-	fn get_closing_scriptpubkey(&self) -> ScriptBuf {
+	pub fn get_closing_scriptpubkey(&self) -> ScriptBuf {
 		// The shutdown scriptpubkey is set on channel opening when option_upfront_shutdown_script
 		// is signaled. Otherwise, it is set when sending a shutdown message. Calling this method
 		// outside of those situations will fail.
@@ -7159,7 +7546,7 @@ where
 				btree_map::Entry::Occupied(_) => {
 					return Err(ChannelError::close(format!(
 						"Peer sent batched commitment_signed with duplicate funding_txid {}",
-						funding_txid
+						self.context.funding_txid_for_message(&funding_txid)
 					)));
 				},
 			}
@@ -7175,7 +7562,7 @@ where
 			let msg = messages.get(&funding_txid).ok_or_else(|| {
 				ChannelError::close(format!(
 					"Peer did not send a commitment_signed for pending splice transaction: {}",
-					funding_txid
+					self.context.funding_txid_for_message(&funding_txid)
 				))
 			})?;
 			let (commitment_tx, htlcs_included) = self.context.validate_commitment_signed(
@@ -7395,6 +7782,9 @@ where
 
 	/// Frees any pending commitment updates in the holding cell, generating the relevant messages
 	/// for our counterparty.
+	///
+	/// At most [`ChannelConfig::max_holding_cell_updates_per_commitment`] HTLC updates are freed, if
+	/// set, with the remaining ones left in the holding cell.
 	fn free_holding_cell_htlcs<F: Deref, L: Deref>(
 		&mut self, fee_estimator: &LowerBoundedFeeEstimator<F>, logger: &L,
 	) -> (Option<ChannelMonitorUpdate>, Vec<(HTLCSource, PaymentHash)>)
@@ -7428,6 +7818,16 @@ where
 
 			let mut htlc_updates = Vec::new();
 			mem::swap(&mut htlc_updates, &mut self.context.holding_cell_htlc_updates);
+			// If configured, only release a bounded prefix of the holding cell, leaving the rest to
+			// be freed in order once our counterparty has revoked the resulting commitment.
+			let max_updates = self.context.config().max_holding_cell_updates_per_commitment;
+			let mut remaining_htlc_updates = match max_updates {
+				Some(max_updates) => {
+					let max_updates = cmp::max(max_updates, 1) as usize;
+					htlc_updates.split_off(cmp::min(max_updates, htlc_updates.len()))
+				},
+				None => Vec::new(),
+			};
 			let mut update_add_count = 0;
 			let mut update_fulfill_count = 0;
 			let mut update_fail_count = 0;
@@ -7546,6 +7946,16 @@ where
 					}
 				}
 			}
+			if !remaining_htlc_updates.is_empty() {
+				log_debug!(
+					logger,
+					"Leaving {} HTLC updates in the holding cell of channel {} for a later commitment update",
+					remaining_htlc_updates.len(),
+					&self.context.channel_id()
+				);
+				remaining_htlc_updates.append(&mut self.context.holding_cell_htlc_updates);
+				self.context.holding_cell_htlc_updates = remaining_htlc_updates;
+			}
 			let update_fee =
 				self.context.holding_cell_update_fee.take().and_then(|feerate| {
 					self.send_update_fee(feerate, false, fee_estimator, logger)
@@ -7556,7 +7966,15 @@ where
 				&& update_fail_count == 0
 				&& update_fee.is_none()
 			{
-				return (None, htlcs_to_fail);
+				if self.context.holding_cell_htlc_updates.is_empty() {
+					return (None, htlcs_to_fail);
+				}
+				// None of the released updates resulted in a commitment update, so move on to the
+				// next batch rather than leaving it stuck until the next attempt to free it.
+				let (monitor_update_opt, mut additional_htlcs_to_fail) =
+					self.free_holding_cell_htlcs(fee_estimator, logger);
+				htlcs_to_fail.append(&mut additional_htlcs_to_fail);
+				return (monitor_update_opt, htlcs_to_fail);
 			}
 
 			let mut additional_update = self.build_commitment_no_status_check(logger);
@@ -8709,6 +9127,12 @@ where
 				return Err(ChannelError::close("Peer sent a garbage channel_reestablish with secret key not matching the commitment height provided".to_owned()));
 			}
 			if msg.next_remote_commitment_number > our_commitment_transaction {
+				if user_config.manually_acknowledge_data_loss {
+					log_error!(logger, "We have fallen behind on channel {} - our counterparty proved our latest commitment transaction is at least {} but we only have {}. Awaiting acknowledgement of the data loss before closing the channel.",
+						&self.context.channel_id(), msg.next_remote_commitment_number, our_commitment_transaction);
+					self.context.local_data_loss = Some((our_commitment_transaction, msg.next_remote_commitment_number));
+					return Err(ChannelError::WarnAndDisconnect("Refusing to reestablish channel with stale local state until data loss is acknowledged".to_owned()));
+				}
 				macro_rules! log_and_panic {
 					($err_msg: expr) => {
 						log_error!(logger, $err_msg);
@@ -8872,7 +9296,8 @@ where
 							channel_id: self.context.channel_id(),
 							data: format!(
 								"next_funding_txid {} does match our latest interactive funding txid {}",
-								next_funding_txid, our_next_funding_txid,
+								self.context.funding_txid_for_message(&next_funding_txid),
+								self.context.funding_txid_for_message(&our_next_funding_txid),
 							).into_bytes() }))
 					}
 				} else {
@@ -9005,16 +9430,27 @@ where
 			Some(&self.get_closing_scriptpubkey()),
 			Some(self.context.counterparty_shutdown_scriptpubkey.as_ref().unwrap()),
 		);
-		let proposed_total_fee_satoshis = proposed_feerate as u64 * tx_weight / 1000;
+		let mut proposed_total_fee_satoshis = proposed_feerate as u64 * tx_weight / 1000;
 		let proposed_max_total_fee_satoshis = if self.funding.is_outbound() {
 			// We always add force_close_avoidance_max_fee_satoshis to our normal
 			// feerate-calculated fee, but allow the max to be overridden if we're using a
 			// target feerate-calculated fee.
-			cmp::max(
+			let max_total_fee_satoshis = cmp::max(
 				normal_feerate as u64 * tx_weight / 1000
 					+ self.context.config.options.force_close_avoidance_max_fee_satoshis,
 				proposed_max_feerate as u64 * tx_weight / 1000,
-			)
+			);
+			// An explicit fee cap takes precedence over all of the above, lowering our minimum as
+			// well if needed.
+			match self.context.max_closing_fee_satoshis {
+				Some(max_fee_satoshis) => {
+					let max_total_fee_satoshis = cmp::min(max_total_fee_satoshis, max_fee_satoshis);
+					proposed_total_fee_satoshis =
+						cmp::min(proposed_total_fee_satoshis, max_total_fee_satoshis);
+					max_total_fee_satoshis
+				},
+				None => max_total_fee_satoshis,
+			}
 		} else {
 			self.funding.get_value_satoshis() - (self.funding.value_to_self_msat + 999) / 1000
 		};
@@ -9032,6 +9468,19 @@ where
 		self.context.closing_negotiation_ready()
 	}
 
+	/// Returns true if any HTLCs are pending in either direction, which need to be resolved before
+	/// the closing_signed negotiation can commence.
+	pub fn has_pending_htlcs(&self) -> bool {
+		!self.context.pending_inbound_htlcs.is_empty()
+			|| !self.context.pending_outbound_htlcs.is_empty()
+	}
+
+	/// Sets an upper bound on the absolute fee we are willing to pay on the closing transaction if
+	/// we are the funder. Has no effect once the closing_signed negotiation has commenced.
+	pub fn set_max_closing_fee_satoshis(&mut self, max_fee_satoshis: u64) {
+		self.context.max_closing_fee_satoshis = Some(max_fee_satoshis);
+	}
+
 	/// Checks if the closing_signed negotiation is making appropriate progress, possibly returning
 	/// an Err if no progress is being made and the channel should be force-closed instead.
 	/// Should be called on a one-minute timer.
@@ -9103,7 +9552,7 @@ where
 	/// within our expected timeframe.
 	///
 	/// This should be called for peers with an active socket on every
-	/// [`super::channelmanager::ChannelManager::timer_tick_occurred`].
+	/// [`super::channelmanager::ChannelManager::check_peer_timeouts`].
 	#[allow(clippy::assertions_on_constants)]
 	#[rustfmt::skip]
 	pub fn should_disconnect_peer_awaiting_response(&mut self) -> bool {
@@ -9599,6 +10048,49 @@ where
 		})
 	}
 
+	/// Determines whether accepting the inbound HTLC `msg` would exceed
+	/// [`ChannelConfig::max_inbound_htlcs_for_reserve`].
+	///
+	/// Only the inbound HTLCs preceding `msg` which are not being removed count towards the limit,
+	/// less `num_preceding_htlcs_failing` of them which are about to be failed back.
+	pub fn check_max_inbound_htlcs_for_reserve<L: Deref>(
+		&self, msg: &msgs::UpdateAddHTLC, num_preceding_htlcs_failing: usize, logger: &L,
+	) -> Result<(), LocalHTLCFailureReason>
+	where
+		L::Target: Logger,
+	{
+		let max_inbound_htlcs = match self.context.config().max_inbound_htlcs_for_reserve {
+			Some(max_inbound_htlcs) => max_inbound_htlcs as usize,
+			None => return Ok(()),
+		};
+		let is_in_holding_cell = |htlc_id: u64| {
+			self.context.holding_cell_htlc_updates.iter().any(|update| match update {
+				HTLCUpdateAwaitingACK::ClaimHTLC { htlc_id: id, .. }
+				| HTLCUpdateAwaitingACK::FailHTLC { htlc_id: id, .. }
+				| HTLCUpdateAwaitingACK::FailMalformedHTLC { htlc_id: id, .. } => *id == htlc_id,
+				HTLCUpdateAwaitingACK::AddHTLC { .. } => false,
+			})
+		};
+		let num_accepted_htlcs = self
+			.context
+			.pending_inbound_htlcs
+			.iter()
+			.filter(|htlc| htlc.htlc_id < msg.htlc_id)
+			.filter(|htlc| !matches!(htlc.state, InboundHTLCState::LocalRemoved(_)))
+			.filter(|htlc| !is_in_holding_cell(htlc.htlc_id))
+			.count()
+			.saturating_sub(num_preceding_htlcs_failing);
+		if num_accepted_htlcs >= max_inbound_htlcs {
+			log_info!(
+				logger,
+				"Cannot accept inbound HTLC as {} inbound HTLCs are already in-flight, the maximum given our reserve",
+				num_accepted_htlcs
+			);
+			return Err(LocalHTLCFailureReason::InboundHTLCLimitForReserve);
+		}
+		Ok(())
+	}
+
 	/// When this function is called, the HTLC is already irrevocably committed to the channel;
 	/// this function determines whether to fail the HTLC, or forward / claim it.
 	#[rustfmt::skip]
@@ -9622,6 +10114,172 @@ where
 			.try_for_each(|funding| self.context.can_accept_incoming_htlc(funding, msg, dust_exposure_limiting_feerate, &logger))
 	}
 
+	/// Estimates the worst-case on-chain fees paid to resolve the channel if we broadcast our
+	/// commitment transaction, assuming fees are provided at `fee_rate` by spending P2WPKH wallet
+	/// inputs for anchor channels. See [`estimate_force_close_cost`].
+	pub fn estimate_force_close_cost(&self, fee_rate: FeeRate) -> ForceCloseCostEstimate {
+		let (feerate_per_kw, num_inbound_htlcs, num_outbound_htlcs) =
+			self.context.get_holder_commitment_nondust_htlc_counts(&self.funding);
+		estimate_force_close_cost(
+			self.funding.get_channel_type(),
+			feerate_per_kw,
+			num_inbound_htlcs,
+			num_outbound_htlcs,
+			fee_rate,
+			false,
+		)
+	}
+
+	/// Estimates the fee and weight of a cooperative closing transaction at `target_feerate`,
+	/// based on the current balances and the shutdown scripts known so far.
+	pub fn estimate_cooperative_close_fee(
+		&self, target_feerate: FeeRate,
+	) -> CooperativeCloseFeeEstimate {
+		// Until a shutdown script is known, assume the largest standard one, i.e., P2WSH or P2TR.
+		let unknown_script = ScriptBuf::from_bytes(vec![0; 34]);
+		let holder_script = self
+			.context
+			.shutdown_scriptpubkey
+			.clone()
+			.map_or(unknown_script.clone(), |script| script.into_inner());
+		let counterparty_script =
+			self.context.counterparty_shutdown_scriptpubkey.clone().unwrap_or(unknown_script);
+
+		let dust_limit_satoshis = self.context.holder_dust_limit_satoshis;
+		let value_to_holder_satoshis = self.funding.value_to_self_msat / 1000;
+		let value_to_counterparty_satoshis =
+			self.funding.get_value_satoshis().saturating_sub(value_to_holder_satoshis);
+		let (funder_value_satoshis, fundee_value_satoshis) = if self.funding.is_outbound() {
+			(value_to_holder_satoshis, value_to_counterparty_satoshis)
+		} else {
+			(value_to_counterparty_satoshis, value_to_holder_satoshis)
+		};
+		// As in `build_closing_transaction`, the funder pays the whole fee, so the fundee's output
+		// is only omitted if its balance is dust to begin with.
+		let includes_fundee_output = fundee_value_satoshis > dust_limit_satoshis;
+		let estimate = |includes_funder_output: bool, fee: Option<Amount>| {
+			let (includes_holder_output, includes_counterparty_output) =
+				if self.funding.is_outbound() {
+					(includes_funder_output, includes_fundee_output)
+				} else {
+					(includes_fundee_output, includes_funder_output)
+				};
+			let weight = Weight::from_wu(self.get_closing_transaction_weight(
+				Some(holder_script.as_script()).filter(|_| includes_holder_output),
+				Some(counterparty_script.as_script()).filter(|_| includes_counterparty_output),
+			));
+			let fee = fee.unwrap_or_else(|| target_feerate.fee_wu(weight).unwrap_or(Amount::MAX));
+			CooperativeCloseFeeEstimate {
+				fee,
+				weight,
+				includes_holder_output,
+				includes_counterparty_output,
+			}
+		};
+
+		let estimate_with_funder_output = estimate(true, None);
+		if funder_value_satoshis.saturating_sub(estimate_with_funder_output.fee.to_sat())
+			> dust_limit_satoshis
+		{
+			return estimate_with_funder_output;
+		}
+		// The funder's output would be dust after paying the fee, so all of it goes to fees.
+		estimate(false, Some(Amount::from_sat(funder_value_satoshis)))
+	}
+
+	/// Returns our dust exposure on both commitment transactions if the channel's feerate were
+	/// `feerate_per_kw`, along with the maximum dust exposure we currently allow.
+	pub fn project_dust_exposure<F: Deref>(
+		&self, feerate_per_kw: u32, fee_estimator: &LowerBoundedFeeEstimator<F>,
+	) -> DustExposureProjection
+	where
+		F::Target: FeeEstimator,
+	{
+		let dust_exposure_limiting_feerate = self
+			.context
+			.get_dust_exposure_limiting_feerate(fee_estimator, self.funding.get_channel_type());
+		let (holder_commitment_dust_exposure_msat, counterparty_commitment_dust_exposure_msat) =
+			self.context.get_dust_exposure_msat_at_feerate(&self.funding, feerate_per_kw);
+		DustExposureProjection {
+			feerate_sat_per_1000_weight: feerate_per_kw,
+			holder_commitment_dust_exposure_msat,
+			counterparty_commitment_dust_exposure_msat,
+			max_dust_htlc_exposure_msat: self
+				.context
+				.get_max_dust_htlc_exposure_msat(dust_exposure_limiting_feerate),
+		}
+	}
+
+	/// Returns the number of updates queued in the holding cell, by type.
+	pub fn holding_cell_size(&self) -> HoldingCellSize {
+		let mut holding_cell_size = HoldingCellSize {
+			channel_id: self.context.channel_id(),
+			counterparty_node_id: self.context.get_counterparty_node_id(),
+			num_pending_adds: 0,
+			num_pending_fulfills: 0,
+			num_pending_fails: 0,
+			has_pending_fee_update: self.context.holding_cell_update_fee.is_some(),
+		};
+		for update in self.context.holding_cell_htlc_updates.iter() {
+			match update {
+				HTLCUpdateAwaitingACK::AddHTLC { .. } => holding_cell_size.num_pending_adds += 1,
+				HTLCUpdateAwaitingACK::ClaimHTLC { .. } => {
+					holding_cell_size.num_pending_fulfills += 1
+				},
+				HTLCUpdateAwaitingACK::FailHTLC { .. }
+				| HTLCUpdateAwaitingACK::FailMalformedHTLC { .. } => holding_cell_size.num_pending_fails += 1,
+			}
+		}
+		holding_cell_size
+	}
+
+	/// Determines the [`LiquidityBand`] our balance is in given the channel's
+	/// [`ChannelConfig::liquidity_alert_thresholds`], returning the direction in which our balance
+	/// moved along with the new band if it moved into a different band since the last call.
+	///
+	/// The first call after the thresholds were set only determines the initial band.
+	pub fn check_liquidity_band(&mut self) -> Option<(LiquidityThresholdDirection, LiquidityBand)> {
+		let thresholds = match self.context.config().liquidity_alert_thresholds {
+			Some(thresholds) => thresholds,
+			None => {
+				self.context.liquidity_band = None;
+				return None;
+			},
+		};
+		let band = thresholds.band_for_balance(
+			self.context.liquidity_band,
+			self.funding.value_to_self_msat,
+			self.funding.get_value_satoshis() * 1000,
+		);
+		let prev_band = self.context.liquidity_band.replace(band)?;
+		if band < prev_band {
+			Some((LiquidityThresholdDirection::Decreasing, band))
+		} else if band > prev_band {
+			Some((LiquidityThresholdDirection::Increasing, band))
+		} else {
+			None
+		}
+	}
+
+	/// Returns the height at which each outbound HTLC in our holding cell will be failed back rather
+	/// than forwarded, along with its value in msat.
+	pub fn get_holding_cell_htlc_deadlines(&self) -> Vec<(u32, u64)> {
+		let mut deadlines = Vec::new();
+		for htlc_update in self.context.holding_cell_htlc_updates.iter() {
+			if let &HTLCUpdateAwaitingACK::AddHTLC { amount_msat, cltv_expiry, .. } = htlc_update {
+				deadlines.push((holding_cell_htlc_fail_back_height(cltv_expiry), amount_msat));
+			}
+		}
+		deadlines
+	}
+
+	/// Returns the number and total value, in msat, of the HTLCs added by our counterparty which are
+	/// pending in the channel.
+	pub fn get_accepted_htlc_stats(&self) -> (u64, u64) {
+		let htlcs = &self.context.pending_inbound_htlcs;
+		(htlcs.len() as u64, htlcs.iter().map(|htlc| htlc.amount_msat).sum())
+	}
+
 	pub fn get_cur_holder_commitment_transaction_number(&self) -> u64 {
 		self.holder_commitment_point.transaction_number() + 1
 	}
@@ -10133,14 +10791,10 @@ where
 		L::Target: Logger
 	{
 		let mut timed_out_htlcs = Vec::new();
-		// This mirrors the check in ChannelManager::decode_update_add_htlc_onion, refusing to
-		// forward an HTLC when our counterparty should almost certainly just fail it for expiring
-		// ~now.
-		let unforwarded_htlc_cltv_limit = height + LATENCY_GRACE_PERIOD_BLOCKS;
 		self.context.holding_cell_htlc_updates.retain(|htlc_update| {
 			match htlc_update {
 				&HTLCUpdateAwaitingACK::AddHTLC { ref payment_hash, ref source, ref cltv_expiry, .. } => {
-					if *cltv_expiry <= unforwarded_htlc_cltv_limit {
+					if height >= holding_cell_htlc_fail_back_height(*cltv_expiry) {
 						timed_out_htlcs.push((source.clone(), payment_hash.clone()));
 						false
 					} else { true }
@@ -11151,6 +11805,37 @@ where
 			.expect("At least one FundingScope is always provided")
 	}
 
+	/// Gets how much we can currently receive over this channel, accounting for the same limits
+	/// we apply when accepting an inbound HTLC across all funding scopes.
+	pub(super) fn get_receivable_amount<F: Deref>(
+		&self, fee_estimator: &LowerBoundedFeeEstimator<F>,
+	) -> ChannelReceivableAmount
+	where
+		F::Target: FeeEstimator,
+	{
+		let mut receivable = ChannelReceivableAmount {
+			channel_id: self.context.channel_id(),
+			counterparty_node_id: self.context.get_counterparty_node_id(),
+			max_single_htlc_msat: u64::MAX,
+			single_htlc_constraint: ReceiveConstraint::CounterpartyBalance,
+			max_total_msat: u64::MAX,
+			total_constraint: ReceiveConstraint::CounterpartyBalance,
+		};
+		for funding in core::iter::once(&self.funding).chain(self.pending_funding.iter()) {
+			let (max_single_htlc_msat, single_htlc_constraint, max_total_msat, total_constraint) =
+				self.context.get_receivable_amount_for_scope(funding, fee_estimator);
+			if max_single_htlc_msat < receivable.max_single_htlc_msat {
+				receivable.max_single_htlc_msat = max_single_htlc_msat;
+				receivable.single_htlc_constraint = single_htlc_constraint;
+			}
+			if max_total_msat < receivable.max_total_msat {
+				receivable.max_total_msat = max_total_msat;
+				receivable.total_constraint = total_constraint;
+			}
+		}
+		receivable
+	}
+
 	#[rustfmt::skip]
 	fn build_commitment_no_status_check<L: Deref>(&mut self, logger: &L) -> ChannelMonitorUpdate where L::Target: Logger {
 		log_trace!(logger, "Updating HTLC state for a newly-sent commitment_signed...");
@@ -12758,6 +13443,25 @@ where
 
 // Unfunded channel utilities
 
+/// Checks that the funder of an outbound channel can afford the fee of the initial commitment
+/// transaction at `feerate_per_kw`, returning the fee in satoshis if it cannot.
+pub(super) fn check_outbound_commit_tx_fee_affordable(
+	channel_value_satoshis: u64, push_msat: u64, feerate_per_kw: u32,
+	channel_type: &ChannelTypeFeatures,
+) -> Result<(), u64> {
+	let value_to_self_msat = channel_value_satoshis * 1000 - push_msat;
+	let commit_tx_fee_sat =
+		SpecTxBuilder {}.commit_tx_fee_sat(feerate_per_kw, MIN_AFFORDABLE_HTLC_COUNT, channel_type);
+	// Subtract any non-HTLC outputs from the local balance
+	let (local_balance_before_fee_msat, _) =
+		SpecTxBuilder {}.subtract_non_htlc_outputs(true, value_to_self_msat, push_msat, channel_type);
+	if local_balance_before_fee_msat / 1000 < commit_tx_fee_sat {
+		Err(commit_tx_fee_sat)
+	} else {
+		Ok(())
+	}
+}
+
 pub(super) fn get_initial_channel_type(
 	config: &UserConfig, their_features: &InitFeatures,
 ) -> ChannelTypeFeatures {
@@ -13281,6 +13985,7 @@ where
 			(59, self.funding.minimum_depth_override, option), // Added in 0.2
 			(60, self.context.historical_scids, optional_vec), // Added in 0.2
 			(61, fulfill_attribution_data, optional_vec), // Added in 0.2
+			(63, self.context.liquidity_band, option),
 		});
 
 		Ok(())
@@ -13620,6 +14325,7 @@ where
 		let mut is_batch_funding: Option<()> = None;
 
 		let mut local_initiated_shutdown: Option<()> = None;
+		let mut liquidity_band: Option<LiquidityBand> = None;
 
 		let mut pending_outbound_blinding_points_opt: Option<Vec<Option<PublicKey>>> = None;
 		let mut holding_cell_blinding_points_opt: Option<Vec<Option<PublicKey>>> = None;
@@ -13683,6 +14389,7 @@ where
 			(59, minimum_depth_override, option), // Added in 0.2
 			(60, historical_scids, optional_vec), // Added in 0.2
 			(61, fulfill_attribution_data, optional_vec), // Added in 0.2
+			(63, liquidity_band, option),
 		});
 
 		let holder_signer = signer_provider.derive_channel_signer(channel_keys_id);
@@ -13976,6 +14683,7 @@ where
 				pending_counterparty_closing_signed: None,
 				expecting_peer_commitment_signed: false,
 				closing_fee_limits: None,
+				max_closing_fee_satoshis: None,
 				target_closing_feerate_sats_per_kw,
 
 				channel_creation_height,
@@ -14007,6 +14715,7 @@ where
 				announcement_sigs,
 
 				workaround_lnd_bug_4006: None,
+				local_data_loss: None,
 				sent_message_awaiting_response: None,
 
 				latest_inbound_scid_alias,
@@ -14023,6 +14732,7 @@ where
 				channel_keys_id,
 
 				local_initiated_shutdown,
+				liquidity_band,
 
 				blocked_monitor_updates: blocked_monitor_updates.unwrap(),
 				is_manual_broadcast: is_manual_broadcast.unwrap_or(false),
@@ -1,14 +1,29 @@
-use crate::events::Event;
-use crate::ln::channelmanager::{MAX_UNFUNDED_CHANNEL_PEERS, MAX_UNFUNDED_CHANS_PER_PEER};
+use crate::events::bump_transaction::Utxo;
+use crate::events::{ClosureReason, Event};
+use crate::ln::channel_state::ChannelDetails;
+use crate::ln::channelmanager::{
+	InboundChannelAcceptor, InboundChannelDecision, InboundChannelProposal,
+	ZeroConfInsufficientReserveAction, MAX_CHANNEL_REJECTION_REASON_LEN,
+	MAX_UNFUNDED_CHANNEL_PEERS, MAX_UNFUNDED_CHANS_PER_PEER,
+};
 use crate::ln::msgs::{
 	AcceptChannel, BaseMessageHandler, ChannelMessageHandler, ErrorAction, MessageSendEvent,
 };
 use crate::ln::types::ChannelId;
 use crate::ln::{functional_test_utils::*, msgs};
 use crate::sign::EntropySource;
+use crate::sync::Arc;
+use crate::types::string::UntrustedString;
+use crate::util::anchor_channel_reserves::{
+	get_reserve_per_channel, insufficient_reserve_error_message, AnchorChannelReserveCheck,
+	AnchorChannelReserveContext, AnchorChannelReservePolicies, AnchorChannelReservePolicy,
+	AnchorReserveChannelAcceptor, ReserveAuditDecision, ReserveAuditLog, ReserveAuditLogger,
+};
 use crate::util::config::{ChannelConfigOverrides, ChannelHandshakeConfigUpdate, UserConfig};
 use crate::util::errors::APIError;
+use bitcoin::hashes::Hash;
 use bitcoin::secp256k1::{PublicKey, SecretKey};
+use bitcoin::{Amount, FeeRate, OutPoint, Txid, WPubkeyHash};
 use lightning_types::features::ChannelTypeFeatures;
 
 #[test]
@@ -187,6 +202,518 @@ fn test_inbound_zero_fee_commitments_manual_acceptance() {
 	do_test_manual_inbound_accept_with_override(zero_fee_cfg, None);
 }
 
+#[test]
+fn test_inbound_anchors_acceptance_with_anchor_reserve() {
+	// Test that `OpenChannelRequest` provides the reserve estimate for inbound anchor channels using
+	// the registered context, and that accepting such channels can be gated on the reserve.
+	let mut manual_accept_cfg = test_default_channel_config();
+	manual_accept_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	manual_accept_cfg.manually_accept_inbound_channels = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(
+		2,
+		&node_cfgs,
+		&[Some(manual_accept_cfg.clone()), Some(manual_accept_cfg)],
+	);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+
+	let context = AnchorChannelReserveContext {
+		expected_accepted_htlcs: 2,
+		..AnchorChannelReserveContext::default()
+	};
+	nodes[1].node.set_anchor_channel_reserve_context(context.clone());
+
+	nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	let temporary_channel_id = match events[0] {
+		Event::OpenChannelRequest {
+			temporary_channel_id,
+			ref channel_type,
+			estimated_reserve_required,
+			..
+		} => {
+			assert!(channel_type.supports_anchors_zero_fee_htlc_tx());
+			let reserve = get_reserve_per_channel(&context);
+			assert_eq!(estimated_reserve_required, Some(reserve));
+			assert!(reserve < get_reserve_per_channel(&AnchorChannelReserveContext::default()));
+			temporary_channel_id
+		},
+		_ => panic!("Unexpected event"),
+	};
+
+	// Without any on-chain funds, the channel is not accepted and the request remains pending.
+	let insufficient_reserve =
+		AnchorChannelReserveCheck::with_num_anchor_channels(&context, &[], 0);
+	let err =
+		format!("Insufficient anchor channel reserve to accept channel {temporary_channel_id}");
+	assert_eq!(
+		nodes[1].node.accept_inbound_channel_with_anchor_reserve(
+			&temporary_channel_id,
+			&node_a,
+			23,
+			None,
+			Some(&insufficient_reserve),
+		),
+		Err(APIError::ChannelUnavailable { err })
+	);
+	assert!(nodes[1].node.get_and_clear_pending_msg_events().is_empty());
+
+	// Once sufficient funds are available, the channel is accepted.
+	let utxo = Utxo::new_p2wpkh(
+		OutPoint { txid: Txid::all_zeros(), vout: 0 },
+		Amount::ONE_BTC,
+		&WPubkeyHash::all_zeros(),
+	);
+	let sufficient_reserve =
+		AnchorChannelReserveCheck::with_num_anchor_channels(&context, &[utxo], 0);
+	nodes[1]
+		.node
+		.accept_inbound_channel_with_anchor_reserve(
+			&temporary_channel_id,
+			&node_a,
+			23,
+			None,
+			Some(&sufficient_reserve),
+		)
+		.unwrap();
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+}
+
+#[test]
+fn test_0conf_anchor_channel_with_insufficient_reserve() {
+	do_test_0conf_anchor_channel_with_insufficient_reserve(
+		ZeroConfInsufficientReserveAction::RequireConfirmations,
+	);
+	do_test_0conf_anchor_channel_with_insufficient_reserve(
+		ZeroConfInsufficientReserveAction::Reject,
+	);
+}
+
+fn do_test_0conf_anchor_channel_with_insufficient_reserve(
+	insufficient_reserve_action: ZeroConfInsufficientReserveAction,
+) {
+	// Test that accepting a zero-conf anchor channel while the reserve is insufficient either
+	// falls back to requiring confirmations or rejects the channel, depending on the configured
+	// action, and that zero-conf is granted once the reserve suffices.
+	let mut manual_accept_cfg = test_default_channel_config();
+	manual_accept_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	manual_accept_cfg.manually_accept_inbound_channels = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(
+		2,
+		&node_cfgs,
+		&[Some(manual_accept_cfg.clone()), Some(manual_accept_cfg)],
+	);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+
+	let context = AnchorChannelReserveContext::default();
+	let insufficient_reserve =
+		AnchorChannelReserveCheck::with_num_anchor_channels(&context, &[], 0);
+
+	nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let mut open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	let temporary_channel_id = match events[0] {
+		Event::OpenChannelRequest { temporary_channel_id, ref channel_type, .. } => {
+			assert!(channel_type.supports_anchors_zero_fee_htlc_tx());
+			temporary_channel_id
+		},
+		_ => panic!("Unexpected event"),
+	};
+
+	let res = nodes[1].node.accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve(
+		&temporary_channel_id,
+		&node_a,
+		23,
+		None,
+		Some(&insufficient_reserve),
+		insufficient_reserve_action,
+	);
+	match insufficient_reserve_action {
+		ZeroConfInsufficientReserveAction::RequireConfirmations => {
+			// The channel is accepted, but only once the funding transaction confirms.
+			assert_eq!(res, Ok(false));
+			let accept_channel_msg =
+				get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+			assert_ne!(accept_channel_msg.common_fields.minimum_depth, 0);
+		},
+		ZeroConfInsufficientReserveAction::Reject => {
+			// The channel is rejected with an error indicating the insufficient reserve.
+			let err = format!(
+				"Insufficient anchor channel reserve to accept zero-conf channel {temporary_channel_id}"
+			);
+			assert_eq!(res, Err(APIError::InsufficientAnchorReserve { err: err.clone() }));
+			let msg_events = nodes[1].node.get_and_clear_pending_msg_events();
+			assert_eq!(msg_events.len(), 1);
+			match &msg_events[0] {
+				MessageSendEvent::HandleError {
+					node_id,
+					action: ErrorAction::SendErrorMessage { msg },
+				} => {
+					assert_eq!(*node_id, node_a);
+					assert_eq!(msg.channel_id, temporary_channel_id);
+					assert_eq!(msg.data, err);
+				},
+				_ => panic!("Unexpected event"),
+			}
+			assert!(nodes[1]
+				.node
+				.accept_inbound_channel(&temporary_channel_id, &node_a, 23, None)
+				.is_err());
+		},
+	}
+
+	// Once sufficient funds are available, the channel is accepted as zero-conf.
+	let utxo = Utxo::new_p2wpkh(
+		OutPoint { txid: Txid::all_zeros(), vout: 0 },
+		Amount::ONE_BTC,
+		&WPubkeyHash::all_zeros(),
+	);
+	let sufficient_reserve =
+		AnchorChannelReserveCheck::with_num_anchor_channels(&context, &[utxo], 0);
+	open_channel_msg.common_fields.temporary_channel_id =
+		ChannelId::temporary_from_entropy_source(&nodes[0].keys_manager);
+	let temporary_channel_id = open_channel_msg.common_fields.temporary_channel_id;
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	assert!(matches!(events[0], Event::OpenChannelRequest { .. }));
+	let res = nodes[1].node.accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve(
+		&temporary_channel_id,
+		&node_a,
+		23,
+		None,
+		Some(&sufficient_reserve),
+		insufficient_reserve_action,
+	);
+	assert_eq!(res, Ok(true));
+	let accept_channel_msg = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+	assert_eq!(accept_channel_msg.common_fields.minimum_depth, 0);
+}
+
+#[test]
+fn test_inbound_channel_acceptor_with_anchor_reserve() {
+	// Test that an `AnchorReserveChannelAcceptor` rejects inbound anchor channels without manual
+	// acceptance while the wallet cannot provide the reserve, and accepts them once it can.
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs =
+		create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+
+	let audit_log = Arc::new(ReserveAuditLog::new(10));
+	let acceptor = AnchorReserveChannelAcceptor::new(
+		AnchorChannelReserveContext::default(),
+		Arc::clone(&nodes[1].wallet_source),
+	)
+	.with_audit_logger(Arc::clone(&audit_log) as Arc<dyn ReserveAuditLogger + Send + Sync>);
+	nodes[1].node.set_inbound_channel_acceptor(Some(Arc::new(acceptor)));
+
+	nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let mut open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	assert!(open_channel_msg
+		.common_fields
+		.channel_type
+		.as_ref()
+		.unwrap()
+		.supports_anchors_zero_fee_htlc_tx());
+
+	// Without any on-chain funds, the channel is rejected.
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	let msg_events = nodes[1].node.get_and_clear_pending_msg_events();
+	assert_eq!(msg_events.len(), 1);
+	match &msg_events[0] {
+		MessageSendEvent::HandleError { node_id, action } => {
+			assert_eq!(*node_id, node_a);
+			match action {
+				ErrorAction::SendErrorMessage { msg } => {
+					let reserve = get_reserve_per_channel(&AnchorChannelReserveContext::default());
+					let expected_err =
+						format!("insufficient on-chain reserves: need {} sat", reserve.to_sat());
+					assert_eq!(msg.data, expected_err);
+				},
+				_ => panic!("Unexpected error action"),
+			}
+		},
+		_ => panic!("Unexpected event"),
+	}
+
+	// Once the wallet can provide the reserve, the channel is accepted without an
+	// `OpenChannelRequest`.
+	let denied_channel_id = open_channel_msg.common_fields.temporary_channel_id;
+	let reserve_outpoint = OutPoint { txid: Txid::all_zeros(), vout: 0 };
+	nodes[1].wallet_source.add_utxo(reserve_outpoint, Amount::ONE_BTC);
+	open_channel_msg.common_fields.temporary_channel_id =
+		ChannelId::temporary_from_entropy_source(&nodes[0].keys_manager);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+
+	// Both decisions were recorded to the audit log along with the reserve they were based on.
+	let reserve_per_channel = get_reserve_per_channel(&AnchorChannelReserveContext::default());
+	let entries = audit_log.entries();
+	assert_eq!(entries.len(), 2);
+	assert_eq!(entries[0].channel_id, denied_channel_id);
+	assert_eq!(
+		entries[0].decision,
+		ReserveAuditDecision::ChannelDenied { num_anchor_channels: 0, num_supportable_channels: 0 }
+	);
+	assert_eq!(entries[0].reserve_per_channel, Some(reserve_per_channel));
+	assert!(entries[0].utxos_considered.is_empty());
+	assert_eq!(entries[1].channel_id, open_channel_msg.common_fields.temporary_channel_id);
+	assert!(matches!(
+		entries[1].decision,
+		ReserveAuditDecision::ChannelAllowed { num_anchor_channels: 0, .. }
+	));
+	assert_eq!(entries[1].utxos_considered, vec![reserve_outpoint]);
+	assert_eq!(entries[1].utxos_value, Amount::ONE_BTC);
+	assert_eq!(entries[1].fees_paid, Amount::ZERO);
+}
+
+#[test]
+fn test_inbound_channel_acceptor_with_reserve_policies() {
+	// Test that an `AnchorReserveChannelAcceptor` accepts inbound anchor channels from trusted
+	// counterparties without any reserve, and that their policy can be changed at runtime.
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs =
+		create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+
+	let mut policies = AnchorChannelReservePolicies::default();
+	policies.set_counterparty_policy(node_a, AnchorChannelReservePolicy::None);
+	let acceptor = Arc::new(
+		AnchorReserveChannelAcceptor::new(
+			AnchorChannelReserveContext::default(),
+			Arc::clone(&nodes[1].wallet_source),
+		)
+		.with_reserve_policies(policies),
+	);
+	nodes[1].node.set_inbound_channel_acceptor(Some(Arc::clone(&acceptor) as _));
+
+	// Without any on-chain funds, the channel with the trusted counterparty is still accepted.
+	nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let mut open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+
+	// Once the counterparty consumes the full reserve per channel, further channels are rejected.
+	acceptor.set_counterparty_reserve_policy(node_a, AnchorChannelReservePolicy::Full);
+	assert_eq!(acceptor.reserve_policies().policy_for(&node_a), AnchorChannelReservePolicy::Full);
+	open_channel_msg.common_fields.temporary_channel_id =
+		ChannelId::temporary_from_entropy_source(&nodes[0].keys_manager);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	let msg_events = nodes[1].node.get_and_clear_pending_msg_events();
+	assert_eq!(msg_events.len(), 1);
+	match &msg_events[0] {
+		MessageSendEvent::HandleError { node_id, action } => {
+			assert_eq!(*node_id, node_a);
+			match action {
+				ErrorAction::SendErrorMessage { msg } => {
+					// Both channels are now counted towards the required reserve.
+					let reserve = get_reserve_per_channel(&AnchorChannelReserveContext::default());
+					let expected_err = format!(
+						"insufficient on-chain reserves: need {} sat",
+						reserve.to_sat() * 2
+					);
+					assert_eq!(msg.data, expected_err);
+				},
+				_ => panic!("Unexpected error action"),
+			}
+		},
+		_ => panic!("Unexpected event"),
+	}
+}
+
+struct RejectingChannelAcceptor(String);
+
+impl InboundChannelAcceptor for RejectingChannelAcceptor {
+	fn decide_inbound_channel(
+		&self, _proposal: &InboundChannelProposal, _existing_channels: &[ChannelDetails],
+	) -> InboundChannelDecision {
+		InboundChannelDecision::Reject(self.0.clone())
+	}
+}
+
+fn expect_channel_rejection(
+	opener: &Node, rejecter: &Node, temporary_channel_id: ChannelId, expected_reason: &str,
+) {
+	// The rejecting node sends the error to its counterparty, which closes its channel with the
+	// sent reason.
+	let opener_id = opener.node.get_our_node_id();
+	let rejecter_id = rejecter.node.get_our_node_id();
+	let msg_events = rejecter.node.get_and_clear_pending_msg_events();
+	assert_eq!(msg_events.len(), 1);
+	let err_msg = match &msg_events[0] {
+		MessageSendEvent::HandleError {
+			node_id,
+			action: ErrorAction::SendErrorMessage { msg },
+		} => {
+			assert_eq!(*node_id, opener_id);
+			assert_eq!(msg.channel_id, temporary_channel_id);
+			msg.clone()
+		},
+		_ => panic!("Unexpected event"),
+	};
+	assert_eq!(err_msg.data, expected_reason);
+	opener.node.handle_error(rejecter_id, &err_msg);
+	let peer_msg = UntrustedString(expected_reason.to_owned());
+	let reason = ClosureReason::CounterpartyForceClosed { peer_msg };
+	check_closed_event(opener, 1, reason, false, &[rejecter_id], 100_000);
+}
+
+#[test]
+fn test_channel_rejection_reason_sent_to_counterparty() {
+	// Test that the reason for rejecting an inbound channel, whether by declining an
+	// `OpenChannelRequest` or by an acceptor, reaches the counterparty sanitized and length-capped.
+	let mut manual_accept_cfg = test_default_channel_config();
+	manual_accept_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	manual_accept_cfg.manually_accept_inbound_channels = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(
+		3,
+		&node_cfgs,
+		&[Some(manual_accept_cfg.clone()), Some(manual_accept_cfg), None],
+	);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+	let node_c = nodes[2].node.get_our_node_id();
+
+	// Declining an `OpenChannelRequest` for lack of reserve sends the reserve shortfall.
+	nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	let temporary_channel_id = match events[0] {
+		Event::OpenChannelRequest { temporary_channel_id, .. } => temporary_channel_id,
+		_ => panic!("Unexpected event"),
+	};
+	let context = AnchorChannelReserveContext::default();
+	let reserve_check = AnchorChannelReserveCheck::with_num_anchor_channels(&context, &[], 1);
+	assert!(!reserve_check.can_support_additional_anchor_channel());
+	let reason = insufficient_reserve_error_message(
+		&context,
+		reserve_check.num_anchor_channels(),
+		reserve_check.num_supportable_channels(),
+	);
+	let shortfall = get_reserve_per_channel(&context) * 2;
+	assert_eq!(reason, format!("insufficient on-chain reserves: need {} sat", shortfall.to_sat()));
+	nodes[1]
+		.node
+		.force_close_broadcasting_latest_txn(&temporary_channel_id, &node_a, reason.clone())
+		.unwrap();
+	expect_channel_rejection(&nodes[0], &nodes[1], temporary_channel_id, &reason);
+
+	// Reasons given by an acceptor have non-printable characters replaced and are truncated.
+	let mut acceptor_reason = "Rejected\n\u{7}channel \u{1F4A5}: ".to_owned();
+	acceptor_reason.push_str(&"x".repeat(MAX_CHANNEL_REJECTION_REASON_LEN));
+	let mut expected_reason = "Rejected??channel ?: ".to_owned();
+	expected_reason.push_str(&"x".repeat(MAX_CHANNEL_REJECTION_REASON_LEN - expected_reason.len()));
+	let acceptor = RejectingChannelAcceptor(acceptor_reason);
+	nodes[2].node.set_inbound_channel_acceptor(Some(Arc::new(acceptor)));
+
+	nodes[0].node.create_channel(node_c, 100_000, 0, 42, None, None).unwrap();
+	let open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_c);
+	nodes[2].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[2].node.get_and_clear_pending_events().is_empty());
+	let temporary_channel_id = open_channel_msg.common_fields.temporary_channel_id;
+	expect_channel_rejection(&nodes[0], &nodes[2], temporary_channel_id, &expected_reason);
+}
+
+#[test]
+fn test_inbound_channel_acceptor_defer_to_manual() {
+	// Test that an acceptor deferring its decision results in an `OpenChannelRequest` even though
+	// `manually_accept_inbound_channels` is not set, and that channels without anchor outputs are
+	// accepted immediately.
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(
+		3,
+		&node_cfgs,
+		&[Some(anchors_cfg.clone()), Some(anchors_cfg), Some(test_default_channel_config())],
+	);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+	let node_c = nodes[2].node.get_our_node_id();
+
+	let acceptor = AnchorReserveChannelAcceptor::new(
+		AnchorChannelReserveContext::default(),
+		Arc::clone(&nodes[1].wallet_source),
+	)
+	.with_deferral_on_insufficient_reserve();
+	nodes[1].node.set_inbound_channel_acceptor(Some(Arc::new(acceptor)));
+
+	nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_msg_events().is_empty());
+
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match events[0] {
+		Event::OpenChannelRequest {
+			temporary_channel_id,
+			ref channel_type,
+			estimated_reserve_required,
+			..
+		} => {
+			assert!(channel_type.supports_anchors_zero_fee_htlc_tx());
+			assert!(estimated_reserve_required.is_some());
+			nodes[1].node.accept_inbound_channel(&temporary_channel_id, &node_a, 23, None).unwrap();
+		},
+		_ => panic!("Unexpected event"),
+	}
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+
+	// Channels without anchor outputs don't rely on the reserve and are accepted immediately.
+	nodes[2].node.create_channel(node_b, 100_000, 0, 42, None, None).unwrap();
+	let open_channel_msg = get_event_msg!(nodes[2], MessageSendEvent::SendOpenChannel, node_b);
+	nodes[1].node.handle_open_channel(node_c, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_c);
+}
+
 fn do_test_manual_inbound_accept_with_override(
 	start_cfg: UserConfig, config_overrides: Option<ChannelConfigOverrides>,
 ) -> AcceptChannel {
@@ -252,6 +779,100 @@ fn test_anchors_zero_fee_htlc_tx_downgrade() {
 	do_test_channel_type_downgrade(initiator_cfg, receiver_cfg, start_type, vec![end_type]);
 }
 
+#[test]
+fn test_anchor_negotiation_toggle() {
+	// Tests that disabling the negotiation of anchor channels at runtime applies to channels
+	// subsequently opened and accepted, but not to existing ones, and that it can be re-enabled.
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_cfg.manually_accept_inbound_channels = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(
+		2,
+		&node_cfgs,
+		&[Some(anchors_cfg.clone()), Some(anchors_cfg.clone())],
+	);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a = nodes[0].node.get_our_node_id();
+	let node_b = nodes[1].node.get_our_node_id();
+	let anchors_type = ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+	let static_remote_key_type = ChannelTypeFeatures::only_static_remote_key();
+
+	let open_channel = |override_config: Option<UserConfig>| {
+		nodes[0].node.create_channel(node_b, 100_000, 0, 42, None, override_config).unwrap();
+		get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b)
+	};
+	let accept_channel = |open_channel_msg: &msgs::OpenChannel| {
+		nodes[1].node.handle_open_channel(node_a, open_channel_msg);
+		let events = nodes[1].node.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		match &events[0] {
+			Event::OpenChannelRequest { temporary_channel_id, channel_type, .. } => {
+				nodes[1]
+					.node
+					.accept_inbound_channel(temporary_channel_id, &node_a, 23, None)
+					.unwrap();
+				channel_type.clone()
+			},
+			_ => panic!("Unexpected event"),
+		}
+	};
+
+	// Anchor channels are negotiated by default.
+	assert!(nodes[0].node.is_anchor_negotiation_enabled());
+	let open_channel_msg = open_channel(None);
+	let anchor_channel_id = open_channel_msg.common_fields.temporary_channel_id;
+	assert_eq!(open_channel_msg.common_fields.channel_type.as_ref().unwrap(), &anchors_type);
+	assert_eq!(accept_channel(&open_channel_msg), anchors_type);
+	let accept_channel_msg = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+	nodes[0].node.handle_accept_channel(node_b, &accept_channel_msg);
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	assert!(matches!(events[0], Event::FundingGenerationReady { .. }));
+
+	// Once disabled, new outbound channels are proposed without anchors, even if the override
+	// config negotiates them, while the existing channel keeps its type.
+	nodes[0].node.set_anchor_negotiation_enabled(false);
+	assert!(!nodes[0].node.is_anchor_negotiation_enabled());
+	let open_channel_msg = open_channel(None);
+	assert_eq!(
+		open_channel_msg.common_fields.channel_type.as_ref().unwrap(),
+		&static_remote_key_type
+	);
+	assert_eq!(accept_channel(&open_channel_msg), static_remote_key_type);
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+	let open_channel_msg = open_channel(Some(anchors_cfg.clone()));
+	assert_eq!(
+		open_channel_msg.common_fields.channel_type.as_ref().unwrap(),
+		&static_remote_key_type
+	);
+	let anchor_channel =
+		nodes[0].node.list_channels().into_iter().find(|c| c.channel_id == anchor_channel_id);
+	assert_eq!(anchor_channel.unwrap().channel_type, Some(anchors_type.clone()));
+
+	// Once re-enabled, anchor channels are negotiated again.
+	nodes[0].node.set_anchor_negotiation_enabled(true);
+	let open_channel_msg = open_channel(None);
+	assert_eq!(open_channel_msg.common_fields.channel_type.as_ref().unwrap(), &anchors_type);
+
+	// If the acceptor disabled negotiation, inbound anchor channels are rejected without an
+	// `OpenChannelRequest`, upon which the initiator retries without anchors.
+	nodes[1].node.set_anchor_negotiation_enabled(false);
+	nodes[1].node.handle_open_channel(node_a, &open_channel_msg);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	let error_msg = get_err_msg(&nodes[1], &node_a);
+	nodes[0].node.handle_error(node_b, &error_msg);
+	let open_channel_msg = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b);
+	assert_eq!(
+		open_channel_msg.common_fields.channel_type.as_ref().unwrap(),
+		&static_remote_key_type
+	);
+	assert_eq!(accept_channel(&open_channel_msg), static_remote_key_type);
+	get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a);
+}
+
 #[test]
 fn test_scid_privacy_downgrade() {
 	// Tests downgrade from `anchors_zero_fee_commitments` with `option_scid_alias` when the
@@ -417,3 +1038,48 @@ fn test_no_channel_downgrade() {
 		panic!();
 	}
 }
+
+#[test]
+fn test_inbound_channel_acceptor_logs_reserve_config_warnings() {
+	// Test that registering an `AnchorReserveChannelAcceptor` whose context contradicts the
+	// `UserConfig` logs a warning for each contradiction.
+	let mut cfg = test_default_channel_config();
+	cfg.channel_handshake_config.our_max_accepted_htlcs = 50;
+	cfg.manually_accept_inbound_channels = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, Some(cfg)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let context = AnchorChannelReserveContext {
+		expected_accepted_htlcs: 10,
+		upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+		..Default::default()
+	};
+	let acceptor = AnchorReserveChannelAcceptor::new(context, Arc::clone(&nodes[1].wallet_source));
+	nodes[1].node.set_inbound_channel_acceptor(Some(Arc::new(acceptor)));
+	nodes[1].logger.assert_log_contains(
+		"lightning::ln::channelmanager",
+		"Anchor channel reserve context contradicts our configuration",
+		2,
+	);
+	nodes[1].logger.assert_log_contains(
+		"lightning::ln::channelmanager",
+		"ExpectedAcceptedHtlcsBelowMaxAccepted",
+		1,
+	);
+	nodes[1].logger.assert_log_contains(
+		"lightning::ln::channelmanager",
+		"InboundChannelAcceptorBypassed",
+		1,
+	);
+
+	// Registering no acceptor does not check anything.
+	nodes[0].node.set_inbound_channel_acceptor(None);
+	nodes[0].logger.assert_log_contains(
+		"lightning::ln::channelmanager",
+		"Anchor channel reserve context contradicts our configuration",
+		0,
+	);
+}
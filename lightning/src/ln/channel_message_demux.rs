@@ -0,0 +1,641 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! A [`ChannelMessageHandler`] which multiplexes several underlying handlers, e.g., several
+//! [`ChannelManager`]s, behind a single [`PeerManager`].
+//!
+//! This allows running several isolated nodes with distinct seeds and channel sets in one process
+//! while sharing peer connections, the [`NetworkGraph`] and the chain source between them.
+//!
+//! [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+//! [`PeerManager`]: crate::ln::peer_handler::PeerManager
+//! [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+
+use bitcoin::constants::ChainHash;
+use bitcoin::secp256k1::PublicKey;
+
+use crate::chain::transaction::OutPoint;
+use crate::ln::msgs::{self, BaseMessageHandler, ChannelMessageHandler, MessageSendEvent};
+use crate::ln::types::ChannelId;
+use crate::types::features::{InitFeatures, NodeFeatures};
+
+use crate::prelude::*;
+use crate::sync::Mutex;
+use core::ops::Deref;
+
+/// A [`ChannelMessageHandler`] which routes channel messages received from peers to the one of
+/// several underlying handlers which owns the channel the message is for.
+///
+/// Channel ownership is learned from the messages the underlying handlers send: a handler owns
+/// any channel for which it sends a message, e.g., an `open_channel` message using a temporary
+/// channel id, or the `channel_reestablish` message it sends for each of its channels upon
+/// reconnection. New inbound channels, as well as messages for channels no handler has claimed,
+/// are routed to the default handler given in [`Self::new`]. Messages which aren't specific to a
+/// channel, such as connection and disconnection of peers, are passed to all handlers.
+///
+/// Peers only ever see the node id of the [`PeerManager`]. If the underlying handlers are
+/// [`ChannelManager`]s using a [`NodeSigner`] with a different node id, their channels must not
+/// be announced, as the channel announcement would not match the node id the counterparty sees.
+/// Payments to such a [`ChannelManager`] must be routed via route hints through its peers. To run
+/// nodes with announced channels and distinct node ids, use a [`PeerManager`] per node id instead,
+/// which may still share the [`NetworkGraph`] and chain source.
+///
+/// Note that onion messages are encrypted to the node id of the [`PeerManager`] and thus cannot be
+/// demultiplexed. A single [`OnionMessageHandler`] using the [`PeerManager`]'s node id should be
+/// used instead. Each [`ChannelManager`] continues to provide its own [`Event`]s, which should be
+/// processed independently.
+///
+/// [`PeerManager`]: crate::ln::peer_handler::PeerManager
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+/// [`NodeSigner`]: crate::sign::NodeSigner
+/// [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+/// [`OnionMessageHandler`]: crate::ln::msgs::OnionMessageHandler
+/// [`Event`]: crate::events::Event
+pub struct ChannelMessageDemultiplexer<CM: Deref>
+where
+	CM::Target: ChannelMessageHandler,
+{
+	handlers: Vec<CM>,
+	default_handler: usize,
+	/// Maps channel ids (or temporary channel ids) with a given peer to the index of the handler in
+	/// `handlers` which owns the channel.
+	channel_owners: Mutex<HashMap<(PublicKey, ChannelId), usize>>,
+	/// Messages retrieved from `handlers` which have yet to be returned to the `PeerManager`.
+	pending_msg_events: Mutex<Vec<MessageSendEvent>>,
+}
+
+impl<CM: Deref> ChannelMessageDemultiplexer<CM>
+where
+	CM::Target: ChannelMessageHandler,
+{
+	/// Creates a new demultiplexer over the given handlers. New inbound channels are routed to the
+	/// handler at index `default_handler`.
+	///
+	/// # Panics
+	///
+	/// Panics if `handlers` is empty or `default_handler` is out of bounds.
+	pub fn new(handlers: Vec<CM>, default_handler: usize) -> Self {
+		assert!(default_handler < handlers.len());
+		Self {
+			handlers,
+			default_handler,
+			channel_owners: Mutex::new(new_hash_map()),
+			pending_msg_events: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Gets the underlying handlers, as provided in [`Self::new`].
+	pub fn handlers(&self) -> &[CM] {
+		&self.handlers
+	}
+
+	/// Gets the index of the handler which owns the channel with the given (temporary) channel id
+	/// with the given peer, if known.
+	pub fn channel_owner(
+		&self, their_node_id: &PublicKey, channel_id: &ChannelId,
+	) -> Option<usize> {
+		self.channel_owners.lock().unwrap().get(&(*their_node_id, *channel_id)).copied()
+	}
+
+	fn handler_for_channel(&self, their_node_id: &PublicKey, channel_id: &ChannelId) -> &CM {
+		let owner = self.channel_owner(their_node_id, channel_id);
+		&self.handlers[owner.unwrap_or(self.default_handler)]
+	}
+
+	/// Retrieves the pending messages of all handlers, learning the ownership of any channels they
+	/// refer to.
+	fn collect_pending_msg_events(&self) {
+		let mut events_by_handler = Vec::with_capacity(self.handlers.len());
+		for handler in self.handlers.iter() {
+			events_by_handler.push(handler.get_and_clear_pending_msg_events());
+		}
+
+		let mut pending_msg_events = self.pending_msg_events.lock().unwrap();
+		let mut channel_owners = self.channel_owners.lock().unwrap();
+		for (handler_idx, events) in events_by_handler.into_iter().enumerate() {
+			for event in events.iter() {
+				let mut record_owner = |node_id: &PublicKey, channel_id: ChannelId| {
+					channel_owners.insert((*node_id, channel_id), handler_idx);
+				};
+				match event {
+					MessageSendEvent::SendOpenChannel { node_id, msg } => {
+						record_owner(node_id, msg.common_fields.temporary_channel_id)
+					},
+					MessageSendEvent::SendOpenChannelV2 { node_id, msg } => {
+						record_owner(node_id, msg.common_fields.temporary_channel_id)
+					},
+					MessageSendEvent::SendAcceptChannel { node_id, msg } => {
+						record_owner(node_id, msg.common_fields.temporary_channel_id)
+					},
+					MessageSendEvent::SendAcceptChannelV2 { node_id, msg } => {
+						record_owner(node_id, msg.common_fields.temporary_channel_id)
+					},
+					MessageSendEvent::SendFundingCreated { node_id, msg } => {
+						// The counterparty's `funding_signed` will use the final channel id.
+						record_owner(node_id, msg.temporary_channel_id);
+						let funding_outpoint =
+							OutPoint { txid: msg.funding_txid, index: msg.funding_output_index };
+						record_owner(
+							node_id,
+							ChannelId::v1_from_funding_outpoint(funding_outpoint),
+						);
+					},
+					MessageSendEvent::SendFundingSigned { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendStfu { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendSpliceInit { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendSpliceAck { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendSpliceLocked { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxAddInput { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxAddOutput { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxRemoveInput { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxRemoveOutput { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxComplete { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxSignatures { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxInitRbf { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxAckRbf { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendTxAbort { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendChannelReady { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendAnnouncementSignatures { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::UpdateHTLCs { node_id, channel_id, .. } => {
+						record_owner(node_id, *channel_id)
+					},
+					MessageSendEvent::SendRevokeAndACK { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendClosingSigned { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendClosingComplete { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendClosingSig { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendShutdown { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					MessageSendEvent::SendChannelReestablish { node_id, msg } => {
+						record_owner(node_id, msg.channel_id)
+					},
+					_ => {},
+				}
+			}
+			pending_msg_events.extend(events);
+		}
+	}
+}
+
+impl<CM: Deref> BaseMessageHandler for ChannelMessageDemultiplexer<CM>
+where
+	CM::Target: ChannelMessageHandler,
+{
+	fn get_and_clear_pending_msg_events(&self) -> Vec<MessageSendEvent> {
+		self.collect_pending_msg_events();
+		core::mem::take(&mut *self.pending_msg_events.lock().unwrap())
+	}
+
+	fn peer_disconnected(&self, their_node_id: PublicKey) {
+		for handler in self.handlers.iter() {
+			handler.peer_disconnected(their_node_id);
+		}
+		// Ownership of any remaining channels is learned again from the `channel_reestablish`
+		// messages sent upon reconnection.
+		self.channel_owners.lock().unwrap().retain(|(node_id, _), _| *node_id != their_node_id);
+	}
+
+	fn provided_node_features(&self) -> NodeFeatures {
+		let mut features = NodeFeatures::empty();
+		for handler in self.handlers.iter() {
+			features = features | handler.provided_node_features();
+		}
+		features
+	}
+
+	fn provided_init_features(&self, their_node_id: PublicKey) -> InitFeatures {
+		let mut features = InitFeatures::empty();
+		for handler in self.handlers.iter() {
+			features = features | handler.provided_init_features(their_node_id);
+		}
+		features
+	}
+
+	fn peer_connected(
+		&self, their_node_id: PublicKey, msg: &msgs::Init, inbound: bool,
+	) -> Result<(), ()> {
+		for (idx, handler) in self.handlers.iter().enumerate() {
+			if handler.peer_connected(their_node_id, msg, inbound).is_err() {
+				for connected_handler in self.handlers[..idx].iter() {
+					connected_handler.peer_disconnected(their_node_id);
+				}
+				return Err(());
+			}
+		}
+		// Learn the ownership of existing channels from the `channel_reestablish` messages the
+		// handlers queued before the peer sends us its own.
+		self.collect_pending_msg_events();
+		Ok(())
+	}
+}
+
+impl<CM: Deref> ChannelMessageHandler for ChannelMessageDemultiplexer<CM>
+where
+	CM::Target: ChannelMessageHandler,
+{
+	fn handle_open_channel(&self, their_node_id: PublicKey, msg: &msgs::OpenChannel) {
+		let channel_id = msg.common_fields.temporary_channel_id;
+		self.handler_for_channel(&their_node_id, &channel_id)
+			.handle_open_channel(their_node_id, msg);
+	}
+	fn handle_open_channel_v2(&self, their_node_id: PublicKey, msg: &msgs::OpenChannelV2) {
+		let channel_id = msg.common_fields.temporary_channel_id;
+		self.handler_for_channel(&their_node_id, &channel_id)
+			.handle_open_channel_v2(their_node_id, msg);
+	}
+	fn handle_accept_channel(&self, their_node_id: PublicKey, msg: &msgs::AcceptChannel) {
+		let channel_id = msg.common_fields.temporary_channel_id;
+		self.handler_for_channel(&their_node_id, &channel_id)
+			.handle_accept_channel(their_node_id, msg);
+	}
+	fn handle_accept_channel_v2(&self, their_node_id: PublicKey, msg: &msgs::AcceptChannelV2) {
+		let channel_id = msg.common_fields.temporary_channel_id;
+		self.handler_for_channel(&their_node_id, &channel_id)
+			.handle_accept_channel_v2(their_node_id, msg);
+	}
+	fn handle_funding_created(&self, their_node_id: PublicKey, msg: &msgs::FundingCreated) {
+		self.handler_for_channel(&their_node_id, &msg.temporary_channel_id)
+			.handle_funding_created(their_node_id, msg);
+	}
+	fn handle_funding_signed(&self, their_node_id: PublicKey, msg: &msgs::FundingSigned) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_funding_signed(their_node_id, msg);
+	}
+	fn handle_channel_ready(&self, their_node_id: PublicKey, msg: &msgs::ChannelReady) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_channel_ready(their_node_id, msg);
+	}
+
+	fn handle_peer_storage(&self, their_node_id: PublicKey, msg: msgs::PeerStorage) {
+		self.handlers[self.default_handler].handle_peer_storage(their_node_id, msg);
+	}
+	fn handle_peer_storage_retrieval(
+		&self, their_node_id: PublicKey, msg: msgs::PeerStorageRetrieval,
+	) {
+		self.handlers[self.default_handler].handle_peer_storage_retrieval(their_node_id, msg);
+	}
+
+	fn handle_shutdown(&self, their_node_id: PublicKey, msg: &msgs::Shutdown) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_shutdown(their_node_id, msg);
+	}
+	fn handle_closing_signed(&self, their_node_id: PublicKey, msg: &msgs::ClosingSigned) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_closing_signed(their_node_id, msg);
+	}
+	#[cfg(simple_close)]
+	fn handle_closing_complete(&self, their_node_id: PublicKey, msg: msgs::ClosingComplete) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_closing_complete(their_node_id, msg);
+	}
+	#[cfg(simple_close)]
+	fn handle_closing_sig(&self, their_node_id: PublicKey, msg: msgs::ClosingSig) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_closing_sig(their_node_id, msg);
+	}
+
+	fn handle_stfu(&self, their_node_id: PublicKey, msg: &msgs::Stfu) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id).handle_stfu(their_node_id, msg);
+	}
+
+	#[cfg(splicing)]
+	fn handle_splice_init(&self, their_node_id: PublicKey, msg: &msgs::SpliceInit) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_splice_init(their_node_id, msg);
+	}
+	#[cfg(splicing)]
+	fn handle_splice_ack(&self, their_node_id: PublicKey, msg: &msgs::SpliceAck) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_splice_ack(their_node_id, msg);
+	}
+	#[cfg(splicing)]
+	fn handle_splice_locked(&self, their_node_id: PublicKey, msg: &msgs::SpliceLocked) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_splice_locked(their_node_id, msg);
+	}
+
+	fn handle_tx_add_input(&self, their_node_id: PublicKey, msg: &msgs::TxAddInput) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_add_input(their_node_id, msg);
+	}
+	fn handle_tx_add_output(&self, their_node_id: PublicKey, msg: &msgs::TxAddOutput) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_add_output(their_node_id, msg);
+	}
+	fn handle_tx_remove_input(&self, their_node_id: PublicKey, msg: &msgs::TxRemoveInput) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_remove_input(their_node_id, msg);
+	}
+	fn handle_tx_remove_output(&self, their_node_id: PublicKey, msg: &msgs::TxRemoveOutput) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_remove_output(their_node_id, msg);
+	}
+	fn handle_tx_complete(&self, their_node_id: PublicKey, msg: &msgs::TxComplete) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_complete(their_node_id, msg);
+	}
+	fn handle_tx_signatures(&self, their_node_id: PublicKey, msg: &msgs::TxSignatures) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_signatures(their_node_id, msg);
+	}
+	fn handle_tx_init_rbf(&self, their_node_id: PublicKey, msg: &msgs::TxInitRbf) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_init_rbf(their_node_id, msg);
+	}
+	fn handle_tx_ack_rbf(&self, their_node_id: PublicKey, msg: &msgs::TxAckRbf) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_ack_rbf(their_node_id, msg);
+	}
+	fn handle_tx_abort(&self, their_node_id: PublicKey, msg: &msgs::TxAbort) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_tx_abort(their_node_id, msg);
+	}
+
+	fn handle_update_add_htlc(&self, their_node_id: PublicKey, msg: &msgs::UpdateAddHTLC) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_update_add_htlc(their_node_id, msg);
+	}
+	fn handle_update_fulfill_htlc(&self, their_node_id: PublicKey, msg: msgs::UpdateFulfillHTLC) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_update_fulfill_htlc(their_node_id, msg);
+	}
+	fn handle_update_fail_htlc(&self, their_node_id: PublicKey, msg: &msgs::UpdateFailHTLC) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_update_fail_htlc(their_node_id, msg);
+	}
+	fn handle_update_fail_malformed_htlc(
+		&self, their_node_id: PublicKey, msg: &msgs::UpdateFailMalformedHTLC,
+	) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_update_fail_malformed_htlc(their_node_id, msg);
+	}
+	fn handle_commitment_signed(&self, their_node_id: PublicKey, msg: &msgs::CommitmentSigned) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_commitment_signed(their_node_id, msg);
+	}
+	fn handle_commitment_signed_batch(
+		&self, their_node_id: PublicKey, channel_id: ChannelId, batch: Vec<msgs::CommitmentSigned>,
+	) {
+		self.handler_for_channel(&their_node_id, &channel_id).handle_commitment_signed_batch(
+			their_node_id,
+			channel_id,
+			batch,
+		);
+	}
+	fn handle_revoke_and_ack(&self, their_node_id: PublicKey, msg: &msgs::RevokeAndACK) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_revoke_and_ack(their_node_id, msg);
+	}
+
+	fn handle_update_fee(&self, their_node_id: PublicKey, msg: &msgs::UpdateFee) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_update_fee(their_node_id, msg);
+	}
+
+	fn handle_announcement_signatures(
+		&self, their_node_id: PublicKey, msg: &msgs::AnnouncementSignatures,
+	) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_announcement_signatures(their_node_id, msg);
+	}
+
+	fn handle_channel_reestablish(&self, their_node_id: PublicKey, msg: &msgs::ChannelReestablish) {
+		self.handler_for_channel(&their_node_id, &msg.channel_id)
+			.handle_channel_reestablish(their_node_id, msg);
+	}
+
+	fn handle_channel_update(&self, their_node_id: PublicKey, msg: &msgs::ChannelUpdate) {
+		// `channel_update`s are identified by short channel id only. Handlers ignore those for
+		// channels they don't have, so pass them to all handlers.
+		for handler in self.handlers.iter() {
+			handler.handle_channel_update(their_node_id, msg);
+		}
+	}
+
+	fn handle_error(&self, their_node_id: PublicKey, msg: &msgs::ErrorMessage) {
+		// An all-zero channel id refers to all channels with the peer.
+		if msg.channel_id.is_zero() {
+			for handler in self.handlers.iter() {
+				handler.handle_error(their_node_id, msg);
+			}
+		} else {
+			self.handler_for_channel(&their_node_id, &msg.channel_id)
+				.handle_error(their_node_id, msg);
+		}
+	}
+
+	fn get_chain_hashes(&self) -> Option<Vec<ChainHash>> {
+		let mut chain_hashes: Option<Vec<ChainHash>> = None;
+		for handler in self.handlers.iter() {
+			if let Some(handler_chain_hashes) = handler.get_chain_hashes() {
+				let chain_hashes = chain_hashes.get_or_insert_with(Vec::new);
+				for chain_hash in handler_chain_hashes {
+					if !chain_hashes.contains(&chain_hash) {
+						chain_hashes.push(chain_hash);
+					}
+				}
+			}
+		}
+		chain_hashes
+	}
+
+	fn message_received(&self) {
+		for handler in self.handlers.iter() {
+			handler.message_received();
+		}
+	}
+
+	fn peer_has_pending_htlcs(&self, their_node_id: PublicKey) -> bool {
+		self.handlers.iter().any(|handler| handler.peer_has_pending_htlcs(their_node_id))
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::ChannelMessageDemultiplexer;
+	use crate::ln::functional_test_utils::*;
+	use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, Init, MessageSendEvent};
+
+	#[test]
+	fn test_demultiplexes_channels_between_managers() {
+		// Nodes 0 and 1 run behind a single demultiplexer and share node 2 as their peer. Node 0
+		// opens a channel to node 2 while node 2 concurrently opens a channel to the
+		// demultiplexer, which is accepted by the default handler, node 1.
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		let node_0_id = nodes[0].node.get_our_node_id();
+		let node_1_id = nodes[1].node.get_our_node_id();
+		let node_2_id = nodes[2].node.get_our_node_id();
+
+		let demux = ChannelMessageDemultiplexer::new(vec![nodes[0].node, nodes[1].node], 1);
+
+		let inbound_temp_id =
+			nodes[2].node.create_channel(node_1_id, 100_000, 0, 42, None, None).unwrap();
+		let open_channel = get_event_msg!(nodes[2], MessageSendEvent::SendOpenChannel, node_1_id);
+		let outbound_temp_id =
+			nodes[0].node.create_channel(node_2_id, 100_000, 0, 42, None, None).unwrap();
+		demux.handle_open_channel(node_2_id, &open_channel);
+
+		let events = demux.get_and_clear_pending_msg_events();
+		assert_eq!(events.len(), 2);
+		assert_eq!(demux.channel_owner(&node_2_id, &outbound_temp_id), Some(0));
+		assert_eq!(demux.channel_owner(&node_2_id, &inbound_temp_id), Some(1));
+		match &events[0] {
+			MessageSendEvent::SendOpenChannel { node_id, msg } => {
+				assert_eq!(*node_id, node_2_id);
+				nodes[2].node.handle_open_channel(node_0_id, msg);
+			},
+			_ => panic!("Unexpected event {:?}", events[0]),
+		}
+		match &events[1] {
+			MessageSendEvent::SendAcceptChannel { node_id, msg } => {
+				assert_eq!(*node_id, node_2_id);
+				nodes[2].node.handle_accept_channel(node_1_id, msg);
+			},
+			_ => panic!("Unexpected event {:?}", events[1]),
+		}
+
+		let accept_channel =
+			get_event_msg!(nodes[2], MessageSendEvent::SendAcceptChannel, node_0_id);
+		demux.handle_accept_channel(node_2_id, &accept_channel);
+
+		let (temp_id, funding_tx, _) =
+			create_funding_transaction(&nodes[0], &node_2_id, 100_000, 42);
+		nodes[0].node.funding_transaction_generated(temp_id, node_2_id, funding_tx).unwrap();
+		let (temp_id, funding_tx, _) =
+			create_funding_transaction(&nodes[2], &node_1_id, 100_000, 42);
+		nodes[2].node.funding_transaction_generated(temp_id, node_1_id, funding_tx).unwrap();
+		let funding_created =
+			get_event_msg!(nodes[2], MessageSendEvent::SendFundingCreated, node_1_id);
+		demux.handle_funding_created(node_2_id, &funding_created);
+		check_added_monitors!(nodes[1], 1);
+		expect_channel_pending_event(&nodes[1], &node_2_id);
+
+		let events = demux.get_and_clear_pending_msg_events();
+		assert_eq!(events.len(), 2);
+		match &events[0] {
+			MessageSendEvent::SendFundingCreated { node_id, msg } => {
+				assert_eq!(*node_id, node_2_id);
+				nodes[2].node.handle_funding_created(node_0_id, msg);
+				check_added_monitors!(nodes[2], 1);
+				expect_channel_pending_event(&nodes[2], &node_0_id);
+			},
+			_ => panic!("Unexpected event {:?}", events[0]),
+		}
+		match &events[1] {
+			MessageSendEvent::SendFundingSigned { node_id, msg } => {
+				assert_eq!(*node_id, node_2_id);
+				nodes[2].node.handle_funding_signed(node_1_id, msg);
+				check_added_monitors!(nodes[2], 1);
+				expect_channel_pending_event(&nodes[2], &node_1_id);
+			},
+			_ => panic!("Unexpected event {:?}", events[1]),
+		}
+
+		// Node 2's `funding_signed` uses the final channel id, which the demultiplexer derived from
+		// node 0's `funding_created`.
+		let funding_signed =
+			get_event_msg!(nodes[2], MessageSendEvent::SendFundingSigned, node_0_id);
+		demux.handle_funding_signed(node_2_id, &funding_signed);
+		check_added_monitors!(nodes[0], 1);
+		expect_channel_pending_event(&nodes[0], &node_2_id);
+		assert!(demux.get_and_clear_pending_msg_events().is_empty());
+
+		let node_0_channels = nodes[0].node.list_channels();
+		let node_1_channels = nodes[1].node.list_channels();
+		assert_eq!(node_0_channels.len(), 1);
+		assert_eq!(node_1_channels.len(), 1);
+		assert_eq!(nodes[2].node.list_channels().len(), 2);
+		let node_0_channel_id = node_0_channels[0].channel_id;
+		let node_1_channel_id = node_1_channels[0].channel_id;
+		assert_eq!(demux.channel_owner(&node_2_id, &node_0_channel_id), Some(0));
+		assert_eq!(demux.channel_owner(&node_2_id, &node_1_channel_id), Some(1));
+
+		// Upon reconnection, channel ownership is learned again before the peer's
+		// `channel_reestablish` messages are handled.
+		demux.peer_disconnected(node_2_id);
+		nodes[2].node.peer_disconnected(node_0_id);
+		nodes[2].node.peer_disconnected(node_1_id);
+		assert_eq!(demux.channel_owner(&node_2_id, &node_0_channel_id), None);
+
+		let init = Init {
+			features: nodes[2].node.init_features(),
+			networks: None,
+			remote_network_address: None,
+		};
+		demux.peer_connected(node_2_id, &init, true).unwrap();
+		assert_eq!(demux.channel_owner(&node_2_id, &node_0_channel_id), Some(0));
+		assert_eq!(demux.channel_owner(&node_2_id, &node_1_channel_id), Some(1));
+
+		let init = Init {
+			features: demux.provided_init_features(node_2_id),
+			networks: None,
+			remote_network_address: None,
+		};
+		nodes[2].node.peer_connected(node_0_id, &init, false).unwrap();
+		nodes[2].node.peer_connected(node_1_id, &init, false).unwrap();
+		for event in nodes[2].node.get_and_clear_pending_msg_events() {
+			match event {
+				MessageSendEvent::SendChannelReestablish { msg, .. } => {
+					demux.handle_channel_reestablish(node_2_id, &msg);
+				},
+				_ => panic!("Unexpected event {:?}", event),
+			}
+		}
+		for event in demux.get_and_clear_pending_msg_events() {
+			if let MessageSendEvent::HandleError { .. } = event {
+				panic!("Unexpected error {:?}", event);
+			}
+		}
+		assert_eq!(nodes[0].node.list_channels().len(), 1);
+		assert_eq!(nodes[1].node.list_channels().len(), 1);
+	}
+}
@@ -0,0 +1,264 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Signed statements attesting that a node has a channel with a given peer, without announcing the
+//! channel to the network.
+//!
+//! A [`ChannelReceipt`] commits to a channel's id, funding outpoint, capacity and funding keys at a
+//! point in time, signed by the node's key using the [`message_signing`] scheme with a dedicated
+//! prefix. It can be handed to third parties, e.g., auditors, which can check it against the
+//! on-chain funding output via [`ChannelReceipt::verify`].
+//!
+//! [`message_signing`]: crate::util::message_signing
+
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::TxOut;
+
+use crate::chain::transaction::OutPoint;
+use crate::ln::chan_utils::make_funding_redeemscript;
+use crate::ln::types::ChannelId;
+use crate::sign::NodeSigner;
+use crate::util::message_signing;
+use crate::util::ser::Writeable;
+
+use crate::prelude::*;
+use core::ops::Deref;
+
+/// The prefix prepended to the serialized [`UnsignedChannelReceipt`] before signing it, to
+/// distinguish receipts from other messages signed by the node.
+static CHANNEL_RECEIPT_PREFIX: &[u8] = b"Lightning Channel Receipt:";
+
+/// The contents of a [`ChannelReceipt`], i.e., the statement signed by [`Self::node_id`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnsignedChannelReceipt {
+	/// The node id of the node which signed the receipt.
+	pub node_id: PublicKey,
+	/// The node id of the node's counterparty in the channel.
+	pub counterparty_node_id: PublicKey,
+	/// The channel's id.
+	pub channel_id: ChannelId,
+	/// The channel's funding outpoint.
+	pub funding_outpoint: OutPoint,
+	/// The channel's capacity, i.e., the value of its funding output.
+	pub channel_value_satoshis: u64,
+	/// The time, in seconds since the UNIX epoch, at which the receipt was produced.
+	pub timestamp: u64,
+	/// The funding public key of [`Self::node_id`].
+	pub funding_pubkey: PublicKey,
+	/// The funding public key of [`Self::counterparty_node_id`].
+	pub counterparty_funding_pubkey: PublicKey,
+}
+
+impl_writeable_tlv_based!(UnsignedChannelReceipt, {
+	(0, node_id, required),
+	(2, counterparty_node_id, required),
+	(4, channel_id, required),
+	(6, funding_outpoint, required),
+	(8, channel_value_satoshis, required),
+	(10, timestamp, required),
+	(12, funding_pubkey, required),
+	(14, counterparty_funding_pubkey, required),
+});
+
+impl UnsignedChannelReceipt {
+	fn signed_message(&self) -> Vec<u8> {
+		[CHANNEL_RECEIPT_PREFIX, &self.encode()[..]].concat()
+	}
+}
+
+/// A signed statement that a node had a channel with a given counterparty, funding outpoint and
+/// capacity at a point in time, as produced by [`ChannelManager::sign_channel_receipt`].
+///
+/// Unlike a channel announcement, a receipt is not gossiped and may be produced for unannounced
+/// channels. It is serialized via its [`Writeable`] implementation.
+///
+/// [`ChannelManager::sign_channel_receipt`]: crate::ln::channelmanager::ChannelManager::sign_channel_receipt
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelReceipt {
+	/// The signed statement.
+	pub contents: UnsignedChannelReceipt,
+	/// The zbase32-encoded recoverable signature of [`UnsignedChannelReceipt::node_id`] over the
+	/// serialized [`Self::contents`], as produced by [`NodeSigner::sign_message`].
+	pub signature: String,
+}
+
+impl_writeable_tlv_based!(ChannelReceipt, {
+	(0, contents, required),
+	(2, signature, required),
+});
+
+/// An error returned by [`ChannelReceipt::verify`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChannelReceiptError {
+	/// The signature is malformed or was not created by [`UnsignedChannelReceipt::node_id`].
+	InvalidSignature,
+	/// The receipt was not issued for a channel with the expected counterparty.
+	CounterpartyMismatch,
+	/// The script of the funding output does not match the receipt's funding keys.
+	FundingScriptMismatch,
+	/// The value of the funding output does not match the receipt's channel capacity.
+	ChannelValueMismatch,
+}
+
+impl ChannelReceipt {
+	/// Signs the given `contents` with the node key of the given [`NodeSigner`].
+	///
+	/// Returns `Err` if the signer is unavailable or [`UnsignedChannelReceipt::node_id`] is not
+	/// the signer's node id.
+	pub fn sign<NS: Deref>(contents: UnsignedChannelReceipt, node_signer: NS) -> Result<Self, ()>
+	where
+		NS::Target: NodeSigner,
+	{
+		let signature = node_signer.sign_message(&contents.signed_message())?;
+		if !message_signing::verify(&contents.signed_message(), &signature, &contents.node_id) {
+			return Err(());
+		}
+		Ok(Self { contents, signature })
+	}
+
+	/// Verifies that the receipt was signed by [`UnsignedChannelReceipt::node_id`] for a channel
+	/// with the given `counterparty_node_id`, and that the given `funding_output`, i.e., the
+	/// output spent by [`UnsignedChannelReceipt::funding_outpoint`] as looked up on-chain,
+	/// matches the receipt's funding keys and capacity.
+	///
+	/// Note that this does not check whether the funding output has been spent, i.e., whether the
+	/// channel has since been closed.
+	pub fn verify(
+		&self, counterparty_node_id: &PublicKey, funding_output: &TxOut,
+	) -> Result<(), ChannelReceiptError> {
+		let contents = &self.contents;
+		if !message_signing::verify(&contents.signed_message(), &self.signature, &contents.node_id)
+		{
+			return Err(ChannelReceiptError::InvalidSignature);
+		}
+		if contents.counterparty_node_id != *counterparty_node_id {
+			return Err(ChannelReceiptError::CounterpartyMismatch);
+		}
+		let funding_redeemscript = make_funding_redeemscript(
+			&contents.funding_pubkey,
+			&contents.counterparty_funding_pubkey,
+		);
+		if funding_output.script_pubkey != funding_redeemscript.to_p2wsh() {
+			return Err(ChannelReceiptError::FundingScriptMismatch);
+		}
+		if funding_output.value.to_sat() != contents.channel_value_satoshis {
+			return Err(ChannelReceiptError::ChannelValueMismatch);
+		}
+		Ok(())
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ChannelReceipt, ChannelReceiptError};
+	use crate::ln::functional_test_utils::*;
+	use crate::util::ser::{Readable, Writeable};
+
+	use bitcoin::{Amount, TxOut};
+
+	#[test]
+	fn test_channel_receipt_round_trip() {
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		let node_id_0 = nodes[0].node.get_our_node_id();
+		let node_id_1 = nodes[1].node.get_our_node_id();
+		let node_id_2 = nodes[2].node.get_our_node_id();
+
+		let (_, _, channel_id, funding_tx) =
+			create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+
+		let receipt = nodes[0].node.sign_channel_receipt(&channel_id, &node_id_1).unwrap();
+		assert_eq!(receipt.contents.node_id, node_id_0);
+		assert_eq!(receipt.contents.counterparty_node_id, node_id_1);
+		assert_eq!(receipt.contents.channel_id, channel_id);
+		assert_eq!(receipt.contents.channel_value_satoshis, 100_000);
+		let funding_outpoint = receipt.contents.funding_outpoint;
+		assert_eq!(funding_outpoint.txid, funding_tx.compute_txid());
+		let funding_output = &funding_tx.output[funding_outpoint.index as usize];
+
+		let decoded: ChannelReceipt = Readable::read(&mut &receipt.encode()[..]).unwrap();
+		assert_eq!(decoded, receipt);
+		assert_eq!(decoded.verify(&node_id_1, funding_output), Ok(()));
+
+		// The counterparty can produce a receipt for the same channel.
+		let counterparty_receipt =
+			nodes[1].node.sign_channel_receipt(&channel_id, &node_id_0).unwrap();
+		assert_eq!(counterparty_receipt.contents.funding_outpoint, funding_outpoint);
+		assert_eq!(counterparty_receipt.verify(&node_id_0, funding_output), Ok(()));
+
+		// The receipt is only valid for the claimed counterparty and capacity.
+		assert_eq!(
+			receipt.verify(&node_id_2, funding_output),
+			Err(ChannelReceiptError::CounterpartyMismatch)
+		);
+		let mut larger_funding_output = funding_output.clone();
+		larger_funding_output.value = Amount::from_sat(200_000);
+		assert_eq!(
+			receipt.verify(&node_id_1, &larger_funding_output),
+			Err(ChannelReceiptError::ChannelValueMismatch)
+		);
+
+		// Tampering with the contents invalidates the signature.
+		let mut tampered_receipt = receipt.clone();
+		tampered_receipt.contents.channel_value_satoshis = 200_000;
+		assert_eq!(
+			tampered_receipt.verify(&node_id_1, &larger_funding_output),
+			Err(ChannelReceiptError::InvalidSignature)
+		);
+		let mut tampered_receipt = receipt.clone();
+		tampered_receipt.contents.node_id = node_id_2;
+		assert_eq!(
+			tampered_receipt.verify(&node_id_1, funding_output),
+			Err(ChannelReceiptError::InvalidSignature)
+		);
+
+		// No receipt can be produced for unknown channels.
+		assert!(nodes[0].node.sign_channel_receipt(&channel_id, &node_id_2).is_err());
+	}
+
+	#[test]
+	fn test_channel_receipt_funding_script_mismatch() {
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		let node_id_1 = nodes[1].node.get_our_node_id();
+
+		let (_, _, channel_id, _) =
+			create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+		let (_, _, _, other_funding_tx) =
+			create_announced_chan_between_nodes_with_value(&nodes, 1, 2, 100_000, 0);
+		let receipt = nodes[0].node.sign_channel_receipt(&channel_id, &node_id_1).unwrap();
+
+		// The funding output of another channel with the same capacity doesn't match the
+		// receipt's funding keys.
+		let other_funding_output = other_funding_tx
+			.output
+			.iter()
+			.find(|output| output.value == Amount::from_sat(100_000))
+			.unwrap();
+		assert_eq!(
+			receipt.verify(&node_id_1, other_funding_output),
+			Err(ChannelReceiptError::FundingScriptMismatch)
+		);
+
+		// Neither does a non-channel output paying to one of the funding keys.
+		let funding_pubkey = bitcoin::PublicKey::new(receipt.contents.funding_pubkey);
+		let wpkh_output = TxOut {
+			value: Amount::from_sat(100_000),
+			script_pubkey: bitcoin::ScriptBuf::new_p2wpkh(&funding_pubkey.wpubkey_hash().unwrap()),
+		};
+		assert_eq!(
+			receipt.verify(&node_id_1, &wpkh_output),
+			Err(ChannelReceiptError::FundingScriptMismatch)
+		);
+	}
+}
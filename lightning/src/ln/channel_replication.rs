@@ -0,0 +1,477 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Streaming of channel state changes from a primary [`ChannelManager`] to a hot standby.
+//!
+//! Once a [`ReplicationSink`] is registered via [`ChannelManager::set_replication_sink`], the
+//! primary emits a [`ChannelStateDelta`] for each channel whose serialized state changed after any
+//! operation which requires the [`ChannelManager`] to be persisted. Deltas are ordered by their
+//! [`ChannelStateDelta::epoch`] and [`ChannelStateDelta::sequence`], and are applied on the standby
+//! via [`ChannelManager::apply_channel_state_delta`].
+//!
+//! Epochs fence off old primaries: each node taking over as primary must do so with an epoch
+//! greater than any epoch used before, e.g., as allocated by a lease in a consistent store, and
+//! the [`ReplicationSink`] must reject deltas with an epoch lower than the highest one it has seen
+//! by returning [`ReplicationError::Fenced`]. A fenced [`ChannelManager`] stops replicating, drops
+//! all outbound messages and refuses new peer connections, see
+//! [`ChannelManager::is_replication_fenced`].
+//!
+//! Only the state of funded channels is replicated. [`ChannelMonitor`]s must be replicated
+//! separately, and other [`ChannelManager`] state, e.g., pending HTLC forwards or payments, is
+//! only captured by full snapshots. Thus, before taking over, a standby should be serialized and
+//! read again together with its up-to-date [`ChannelMonitor`]s, such that any channel which is
+//! stale compared to its monitor is force-closed on startup.
+//!
+//! This module is only available with the `channel_state_replication` feature.
+//!
+//! [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+//! [`ChannelManager::set_replication_sink`]: crate::ln::channelmanager::ChannelManager::set_replication_sink
+//! [`ChannelManager::apply_channel_state_delta`]: crate::ln::channelmanager::ChannelManager::apply_channel_state_delta
+//! [`ChannelManager::is_replication_fenced`]: crate::ln::channelmanager::ChannelManager::is_replication_fenced
+//! [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+
+use bitcoin::hashes::sha256::Hash as Sha256;
+use bitcoin::hashes::Hash;
+use bitcoin::secp256k1::PublicKey;
+
+use crate::ln::types::ChannelId;
+
+use crate::prelude::*;
+use crate::sync::Arc;
+
+/// A change to the state of a single channel, as emitted by a primary [`ChannelManager`] to its
+/// [`ReplicationSink`].
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelStateDelta {
+	/// The epoch of the primary which emitted the delta.
+	pub epoch: u64,
+	/// The position of the delta among all deltas emitted by the primary in [`Self::epoch`].
+	///
+	/// Sequences start at zero for each epoch and increase by one for each delta, across all
+	/// channels.
+	pub sequence: u64,
+	/// The id of the channel.
+	pub channel_id: ChannelId,
+	/// The node id of the channel's counterparty.
+	pub counterparty_node_id: PublicKey,
+	/// The full serialized state of the channel, as written as part of the [`ChannelManager`], or
+	/// `None` if the channel was removed, e.g., because it was closed.
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	pub serialized_channel: Option<Vec<u8>>,
+}
+
+impl_writeable_tlv_based!(ChannelStateDelta, {
+	(0, epoch, required),
+	(2, sequence, required),
+	(4, channel_id, required),
+	(6, counterparty_node_id, required),
+	(8, serialized_channel, option),
+});
+
+/// An error returned when replicating or applying a [`ChannelStateDelta`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReplicationError {
+	/// The delta's epoch is lower than the most recent epoch, i.e., another node has taken over as
+	/// primary in the meantime.
+	Fenced {
+		/// The most recent epoch known to the party returning the error.
+		current_epoch: u64,
+	},
+	/// The delta was not the next one expected, i.e., a previous delta was skipped or the delta was
+	/// already applied.
+	OutOfOrder {
+		/// The sequence of the delta which was expected next.
+		expected_sequence: u64,
+	},
+	/// The delta's serialized channel could not be read or does not match its channel id or
+	/// counterparty.
+	InvalidChannelState,
+	/// The [`ReplicationSink`] is temporarily unable to replicate the delta.
+	///
+	/// The delta will be emitted again with the same sequence on the next channel state change.
+	Unavailable,
+}
+
+/// A destination for the [`ChannelStateDelta`]s of a primary [`ChannelManager`], registered via
+/// [`ChannelManager::set_replication_sink`].
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+/// [`ChannelManager::set_replication_sink`]: crate::ln::channelmanager::ChannelManager::set_replication_sink
+pub trait ReplicationSink {
+	/// Durably replicates the given delta to the standby before returning `Ok`.
+	///
+	/// Deltas are provided in order. Any delta with an epoch lower than the highest epoch seen by
+	/// the sink, e.g., via deltas of another primary, must be rejected with
+	/// [`ReplicationError::Fenced`], after which the [`ChannelManager`] stops operating.
+	///
+	/// This is called while the [`ChannelManager`] is processing an operation, thus it must not
+	/// call back into the [`ChannelManager`].
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	fn replicate_channel_state(&self, delta: ChannelStateDelta) -> Result<(), ReplicationError>;
+}
+
+/// The replication state of a [`ChannelManager`], as a primary with a [`ReplicationSink`] or as a
+/// standby applying [`ChannelStateDelta`]s.
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+pub(crate) struct ChannelReplicationState {
+	sink: Option<Arc<dyn ReplicationSink + Send + Sync>>,
+	/// The highest epoch we've used as a primary or seen as a standby.
+	epoch: u64,
+	/// The sequence of the next delta to emit as a primary or to apply as a standby.
+	next_sequence: u64,
+	/// The counterparty and hash of the serialized state of each channel as last replicated by us
+	/// as a primary.
+	replicated_channels: HashMap<ChannelId, (PublicKey, Sha256)>,
+	fenced: bool,
+}
+
+impl ChannelReplicationState {
+	pub(crate) fn new() -> Self {
+		Self {
+			sink: None,
+			epoch: 0,
+			next_sequence: 0,
+			replicated_channels: new_hash_map(),
+			fenced: false,
+		}
+	}
+
+	pub(crate) fn is_fenced(&self) -> bool {
+		self.fenced
+	}
+
+	pub(crate) fn is_replicating(&self) -> bool {
+		self.sink.is_some() && !self.fenced
+	}
+
+	/// Starts replicating as a primary in the given epoch, or stops replicating if `sink` is
+	/// `None`.
+	pub(crate) fn set_sink(
+		&mut self, sink: Option<Arc<dyn ReplicationSink + Send + Sync>>, epoch: u64,
+	) -> Result<(), ReplicationError> {
+		if sink.is_some() {
+			if epoch <= self.epoch {
+				return Err(ReplicationError::Fenced { current_epoch: self.epoch });
+			}
+			self.epoch = epoch;
+			self.next_sequence = 0;
+			self.replicated_channels.clear();
+			self.fenced = false;
+		}
+		self.sink = sink;
+		Ok(())
+	}
+
+	/// Replicates the given serialized funded channels, emitting deltas for each channel which
+	/// changed since it was last replicated as well as for each channel which was removed.
+	///
+	/// Stops at the first error, leaving the remaining changes to be replicated on the next call.
+	pub(crate) fn replicate_channels(
+		&mut self, channels: Vec<(ChannelId, PublicKey, Vec<u8>)>,
+	) -> Result<(), ReplicationError> {
+		let sink = match &self.sink {
+			Some(sink) if !self.fenced => Arc::clone(sink),
+			_ => return Ok(()),
+		};
+
+		let current_channel_ids: HashSet<ChannelId> =
+			channels.iter().map(|(channel_id, _, _)| *channel_id).collect();
+		let removed_channels: Vec<(ChannelId, PublicKey)> = self
+			.replicated_channels
+			.iter()
+			.filter(|(channel_id, _)| !current_channel_ids.contains(channel_id))
+			.map(|(channel_id, (counterparty_node_id, _))| (*channel_id, *counterparty_node_id))
+			.collect();
+		let updated_channels = channels.into_iter().filter_map(|(channel_id, cp_id, channel)| {
+			let hash = Sha256::hash(&channel);
+			match self.replicated_channels.get(&channel_id) {
+				Some((_, replicated_hash)) if *replicated_hash == hash => None,
+				_ => Some((channel_id, cp_id, Some((channel, hash)))),
+			}
+		});
+		let changes: Vec<_> = updated_channels
+			.chain(
+				removed_channels.into_iter().map(|(channel_id, cp_id)| (channel_id, cp_id, None)),
+			)
+			.collect();
+
+		for (channel_id, counterparty_node_id, channel) in changes {
+			let (serialized_channel, hash) = match channel {
+				Some((channel, hash)) => (Some(channel), Some(hash)),
+				None => (None, None),
+			};
+			let delta = ChannelStateDelta {
+				epoch: self.epoch,
+				sequence: self.next_sequence,
+				channel_id,
+				counterparty_node_id,
+				serialized_channel,
+			};
+			match sink.replicate_channel_state(delta) {
+				Ok(()) => {
+					self.next_sequence += 1;
+					match hash {
+						Some(hash) => {
+							self.replicated_channels
+								.insert(channel_id, (counterparty_node_id, hash));
+						},
+						None => {
+							self.replicated_channels.remove(&channel_id);
+						},
+					}
+				},
+				Err(e) => {
+					if let ReplicationError::Fenced { .. } = e {
+						self.fenced = true;
+						self.sink = None;
+					}
+					return Err(e);
+				},
+			}
+		}
+		Ok(())
+	}
+
+	/// Checks whether the given delta is the next one to be applied as a standby.
+	pub(crate) fn check_delta(&self, delta: &ChannelStateDelta) -> Result<(), ReplicationError> {
+		if delta.epoch < self.epoch || (self.sink.is_some() && delta.epoch == self.epoch) {
+			return Err(ReplicationError::Fenced { current_epoch: self.epoch });
+		}
+		let expected_sequence = if delta.epoch > self.epoch { 0 } else { self.next_sequence };
+		if delta.sequence != expected_sequence {
+			return Err(ReplicationError::OutOfOrder { expected_sequence });
+		}
+		Ok(())
+	}
+
+	/// Records that the given delta, as checked by [`Self::check_delta`], was applied.
+	///
+	/// If we were replicating as a primary, a delta from a later epoch means another node took
+	/// over, thus we are fenced.
+	pub(crate) fn delta_applied(&mut self, delta: &ChannelStateDelta) {
+		if delta.epoch > self.epoch && self.sink.is_some() {
+			self.fenced = true;
+			self.sink = None;
+		}
+		self.epoch = delta.epoch;
+		self.next_sequence = delta.sequence + 1;
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{ChannelStateDelta, ReplicationError, ReplicationSink};
+	use crate::events::ClosureReason;
+	use crate::ln::functional_test_utils::*;
+	use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, Init};
+	use crate::sync::{Arc, Mutex};
+	use crate::util::ser::{Readable, Writeable};
+
+	/// A sink shared between all primaries, as, e.g., backed by a consistent store.
+	struct TestReplicationSink {
+		deltas: Mutex<Vec<ChannelStateDelta>>,
+		highest_epoch: Mutex<u64>,
+	}
+
+	impl TestReplicationSink {
+		fn new() -> Self {
+			Self { deltas: Mutex::new(Vec::new()), highest_epoch: Mutex::new(0) }
+		}
+
+		fn take_deltas(&self) -> Vec<ChannelStateDelta> {
+			core::mem::take(&mut *self.deltas.lock().unwrap())
+		}
+	}
+
+	impl ReplicationSink for TestReplicationSink {
+		fn replicate_channel_state(
+			&self, delta: ChannelStateDelta,
+		) -> Result<(), ReplicationError> {
+			let mut highest_epoch = self.highest_epoch.lock().unwrap();
+			if delta.epoch < *highest_epoch {
+				return Err(ReplicationError::Fenced { current_epoch: *highest_epoch });
+			}
+			*highest_epoch = delta.epoch;
+			self.deltas.lock().unwrap().push(delta);
+			Ok(())
+		}
+	}
+
+	#[test]
+	fn test_channel_state_replication_ordering() {
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		// The standby shares the primary's keys but isn't connected to any peers.
+		let standby_chanmgrs = create_node_chanmgrs(1, &node_cfgs, &[None]);
+		let standby = &standby_chanmgrs[0];
+
+		let sink = Arc::new(TestReplicationSink::new());
+		nodes[0].node.set_replication_sink(Some(sink.clone()), 1).unwrap();
+		assert!(sink.take_deltas().is_empty());
+
+		let (_, _, channel_id, _) = create_announced_chan_between_nodes(&nodes, 0, 1);
+		route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+
+		let deltas = sink.take_deltas();
+		assert!(deltas.len() > 1);
+		for (sequence, delta) in deltas.iter().enumerate() {
+			assert_eq!(delta.epoch, 1);
+			assert_eq!(delta.sequence, sequence as u64);
+			assert_eq!(delta.channel_id, channel_id);
+			assert_eq!(delta.counterparty_node_id, nodes[1].node.get_our_node_id());
+			assert!(delta.serialized_channel.is_some());
+			let decoded: ChannelStateDelta = Readable::read(&mut &delta.encode()[..]).unwrap();
+			assert_eq!(&decoded, delta);
+		}
+
+		// Deltas must be applied in order, without gaps or repetitions.
+		assert_eq!(
+			standby.apply_channel_state_delta(&deltas[1]),
+			Err(ReplicationError::OutOfOrder { expected_sequence: 0 })
+		);
+		standby.apply_channel_state_delta(&deltas[0]).unwrap();
+		assert_eq!(
+			standby.apply_channel_state_delta(&deltas[0]),
+			Err(ReplicationError::OutOfOrder { expected_sequence: 1 })
+		);
+		for delta in deltas.iter().skip(1) {
+			standby.apply_channel_state_delta(delta).unwrap();
+		}
+
+		// The standby now has the primary's view of the channel, including the pending HTLC.
+		let primary_channels = nodes[0].node.list_channels();
+		let standby_channels = standby.list_channels();
+		assert_eq!(primary_channels.len(), 1);
+		assert_eq!(standby_channels.len(), 1);
+		assert_eq!(standby_channels[0].channel_id, channel_id);
+		assert_eq!(standby_channels[0].short_channel_id, primary_channels[0].short_channel_id);
+		assert_eq!(
+			standby_channels[0].outbound_capacity_msat,
+			primary_channels[0].outbound_capacity_msat
+		);
+		assert_eq!(
+			standby_channels[0].pending_outbound_htlcs,
+			primary_channels[0].pending_outbound_htlcs
+		);
+		assert!(!standby_channels[0].is_usable);
+
+		// A delta with a mismatched counterparty is rejected without being applied.
+		let mut invalid_delta = deltas.last().unwrap().clone();
+		invalid_delta.sequence += 1;
+		invalid_delta.counterparty_node_id = nodes[0].node.get_our_node_id();
+		assert_eq!(
+			standby.apply_channel_state_delta(&invalid_delta),
+			Err(ReplicationError::InvalidChannelState)
+		);
+
+		// Closing the channel replicates its removal.
+		let err = "Channel force-closed".to_string();
+		let node_id_1 = nodes[1].node.get_our_node_id();
+		nodes[0].node.force_close_broadcasting_latest_txn(&channel_id, &node_id_1, err).unwrap();
+		check_added_monitors(&nodes[0], 1);
+		check_closed_broadcast(&nodes[0], 1, true);
+		let reason = ClosureReason::HolderForceClosed {
+			broadcasted_latest_txn: Some(true),
+			message: "Channel force-closed".to_string(),
+		};
+		check_closed_event(&nodes[0], 1, reason, false, &[node_id_1], 100000);
+
+		let deltas = sink.take_deltas();
+		let removal = deltas.last().unwrap();
+		assert_eq!(removal.channel_id, channel_id);
+		assert!(removal.serialized_channel.is_none());
+		for delta in deltas.iter() {
+			standby.apply_channel_state_delta(delta).unwrap();
+		}
+		assert!(standby.list_channels().is_empty());
+		assert!(standby.get_and_clear_pending_events().is_empty());
+	}
+
+	#[test]
+	fn test_channel_state_replication_fencing() {
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let standby_chanmgrs = create_node_chanmgrs(1, &node_cfgs, &[None]);
+		let standby = &standby_chanmgrs[0];
+		let node_id_1 = nodes[1].node.get_our_node_id();
+
+		let sink = Arc::new(TestReplicationSink::new());
+		nodes[0].node.set_replication_sink(Some(sink.clone()), 1).unwrap();
+		let (_, _, channel_id, _) = create_announced_chan_between_nodes(&nodes, 0, 1);
+		let old_primary_deltas = sink.take_deltas();
+		for delta in old_primary_deltas.iter() {
+			standby.apply_channel_state_delta(delta).unwrap();
+		}
+
+		// The standby can't take over in an epoch which was already used.
+		assert_eq!(
+			standby.set_replication_sink(Some(sink.clone()), 1),
+			Err(ReplicationError::Fenced { current_epoch: 1 })
+		);
+
+		// Once the standby takes over in a later epoch, it replicates all of its channels anew.
+		standby.set_replication_sink(Some(sink.clone()), 2).unwrap();
+		let new_primary_deltas = sink.take_deltas();
+		assert_eq!(new_primary_deltas.len(), 1);
+		assert_eq!(new_primary_deltas[0].epoch, 2);
+		assert_eq!(new_primary_deltas[0].sequence, 0);
+		assert_eq!(new_primary_deltas[0].channel_id, channel_id);
+		assert!(!standby.is_replication_fenced());
+
+		// Deltas of the old primary are rejected by the new primary.
+		let mut stale_delta = old_primary_deltas.last().unwrap().clone();
+		stale_delta.sequence += 1;
+		assert_eq!(
+			standby.apply_channel_state_delta(&stale_delta),
+			Err(ReplicationError::Fenced { current_epoch: 2 })
+		);
+
+		// The old primary is fenced as soon as it tries to replicate a state change, and doesn't
+		// release the corresponding messages to its peer.
+		assert!(!nodes[0].node.is_replication_fenced());
+		nodes[0].node.close_channel(&channel_id, &node_id_1).unwrap();
+		assert!(nodes[0].node.is_replication_fenced());
+		assert!(sink.take_deltas().is_empty());
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+		// It also refuses to reconnect to peers and can't resume in its old epoch.
+		nodes[0].node.peer_disconnected(node_id_1);
+		nodes[1].node.peer_disconnected(nodes[0].node.get_our_node_id());
+		let init_msg = Init {
+			features: nodes[1].node.init_features(),
+			networks: None,
+			remote_network_address: None,
+		};
+		assert!(nodes[0].node.peer_connected(node_id_1, &init_msg, true).is_err());
+		assert_eq!(
+			nodes[0].node.set_replication_sink(Some(sink.clone()), 1),
+			Err(ReplicationError::Fenced { current_epoch: 1 })
+		);
+		assert!(nodes[0].node.is_replication_fenced());
+		assert!(sink.take_deltas().is_empty());
+
+		// A node acting as a standby of the new primary learns it was superseded from its deltas.
+		let standby_chanmgrs = create_node_chanmgrs(1, &node_cfgs, &[None]);
+		let new_standby = &standby_chanmgrs[0];
+		new_standby.set_replication_sink(Some(Arc::new(TestReplicationSink::new())), 1).unwrap();
+		new_standby.apply_channel_state_delta(&new_primary_deltas[0]).unwrap();
+		assert!(new_standby.is_replication_fenced());
+		assert_eq!(new_standby.list_channels().len(), 1);
+		assert!(new_standby.get_and_clear_pending_events().is_empty());
+	}
+}
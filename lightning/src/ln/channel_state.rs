@@ -12,6 +12,7 @@
 use alloc::vec::Vec;
 
 use bitcoin::secp256k1::PublicKey;
+use bitcoin::{Amount, Weight};
 
 use crate::chain::chaininterface::{FeeEstimator, LowerBoundedFeeEstimator};
 use crate::chain::transaction::OutPoint;
@@ -20,6 +21,7 @@ use crate::ln::types::ChannelId;
 use crate::sign::SignerProvider;
 use crate::types::features::{ChannelTypeFeatures, InitFeatures};
 use crate::types::payment::PaymentHash;
+use crate::util::anchor_channel_reserves::{get_reserve_for_channel, AnchorChannelReserveContext};
 use crate::util::config::ChannelConfig;
 
 use core::ops::Deref;
@@ -450,6 +452,20 @@ pub struct ChannelDetails {
 	///
 	/// This field is empty for objects serialized with LDK versions prior to 0.0.122.
 	pub pending_outbound_htlcs: Vec<OutboundHTLCDetails>,
+	/// The amount, in satoshis, recommended to be kept on-chain to pay the fees of this channel's
+	/// transactions should it be force-closed, as calculated by [`get_reserve_per_channel`].
+	///
+	/// The calculation uses the [`AnchorChannelReserveContext`] set via
+	/// [`ChannelManager::set_anchor_channel_reserve_context`], with its expected numbers of HTLCs
+	/// capped at the limits negotiated for this channel.
+	///
+	/// `None` if this is not an anchor channel, if the channel type has not been negotiated yet,
+	/// or for objects serialized with LDK versions prior to 0.3.
+	///
+	/// [`get_reserve_per_channel`]: crate::util::anchor_channel_reserves::get_reserve_per_channel
+	/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+	/// [`ChannelManager::set_anchor_channel_reserve_context`]: crate::ln::channelmanager::ChannelManager::set_anchor_channel_reserve_context
+	pub exogenous_fee_reserve_recommended_sat: Option<u64>,
 }
 
 impl ChannelDetails {
@@ -478,6 +494,7 @@ impl ChannelDetails {
 	pub(super) fn from_channel<SP: Deref, F: Deref>(
 		channel: &Channel<SP>, best_block_height: u32, latest_features: InitFeatures,
 		fee_estimator: &LowerBoundedFeeEstimator<F>,
+		anchor_reserve_context: &AnchorChannelReserveContext,
 	) -> Self
 	where
 		SP::Target: SignerProvider,
@@ -488,6 +505,19 @@ impl ChannelDetails {
 		let balance = channel.get_available_balances(fee_estimator);
 		let (to_remote_reserve_satoshis, to_self_reserve_satoshis) =
 			funding.get_holder_counterparty_selected_channel_reserve_satoshis();
+		// Note that accept_channel (or open_channel) is always the first message, so
+		// `have_received_message` indicates that type negotiation has completed.
+		let exogenous_fee_reserve_recommended_sat = if context.have_received_message() {
+			get_reserve_for_channel(
+				anchor_reserve_context,
+				funding.get_channel_type(),
+				context.get_holder_max_accepted_htlcs(),
+				context.get_counterparty_max_accepted_htlcs(),
+			)
+			.map(|reserve| reserve.to_sat())
+		} else {
+			None
+		};
 		#[allow(deprecated)] // TODO: Remove once balance_msat is removed.
 		ChannelDetails {
 			channel_id: context.channel_id(),
@@ -544,6 +574,7 @@ impl ChannelDetails {
 			channel_shutdown_state: Some(context.shutdown_state()),
 			pending_inbound_htlcs: context.get_pending_inbound_htlc_details(funding),
 			pending_outbound_htlcs: context.get_pending_outbound_htlc_details(funding),
+			exogenous_fee_reserve_recommended_sat,
 		}
 	}
 }
@@ -583,6 +614,7 @@ impl_writeable_tlv_based!(ChannelDetails, {
 	(41, channel_shutdown_state, option),
 	(43, pending_inbound_htlcs, optional_vec),
 	(45, pending_outbound_htlcs, optional_vec),
+	(47, exogenous_fee_reserve_recommended_sat, option),
 	(_unused, user_channel_id, (static_value,
 		_user_channel_id_low.unwrap_or(0) as u128 | ((_user_channel_id_high.unwrap_or(0) as u128) << 64)
 	)),
@@ -618,6 +650,232 @@ impl_writeable_tlv_based_enum!(ChannelShutdownState,
 	(8, ShutdownComplete) => {},
 );
 
+/// The progress of an interactive funding transaction negotiation for a dual-funded channel or a
+/// splice, as returned by [`ChannelManager::interactive_tx_negotiation_progress`].
+///
+/// Counts include the shared funding input and output, which are added by the initiator.
+///
+/// [`ChannelManager::interactive_tx_negotiation_progress`]: crate::ln::channelmanager::ChannelManager::interactive_tx_negotiation_progress
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InteractiveTxNegotiationProgress {
+	/// Whether we initiated the negotiation.
+	pub is_initiator: bool,
+	/// The number of inputs we have added to the transaction so far.
+	pub holder_inputs_added: usize,
+	/// The number of outputs we have added to the transaction so far.
+	pub holder_outputs_added: usize,
+	/// The number of inputs our counterparty has added to the transaction so far.
+	pub counterparty_inputs_added: usize,
+	/// The number of outputs our counterparty has added to the transaction so far.
+	pub counterparty_outputs_added: usize,
+	/// The number of inputs we intend to contribute but have not yet added to the transaction.
+	pub holder_inputs_pending: usize,
+	/// The number of outputs we intend to contribute but have not yet added to the transaction.
+	pub holder_outputs_pending: usize,
+	/// The total value, in satoshis, of the inputs we contribute from our wallet, whether or not
+	/// they have been added to the transaction yet.
+	pub holder_contributed_input_satoshis: u64,
+	/// The feerate of the funding transaction set by the initiator.
+	pub feerate_sat_per_1000_weight: u32,
+	/// The number of calls to [`ChannelManager::timer_tick_occurred`] since we last received a
+	/// negotiation message from our counterparty, or since the negotiation started if we have not
+	/// received one yet.
+	///
+	/// Once this reaches [`UserConfig::interactive_tx_negotiation_timeout_ticks`], the negotiation
+	/// is aborted.
+	///
+	/// [`ChannelManager::timer_tick_occurred`]: crate::ln::channelmanager::ChannelManager::timer_tick_occurred
+	/// [`UserConfig::interactive_tx_negotiation_timeout_ticks`]: crate::util::config::UserConfig::interactive_tx_negotiation_timeout_ticks
+	pub ticks_since_counterparty_message: u16,
+}
+
+/// A channel whose opening we initiated or accepted but which has not yet been funded, as
+/// returned by [`ChannelManager::list_pending_channel_negotiations`].
+///
+/// [`ChannelManager::list_pending_channel_negotiations`]: crate::ln::channelmanager::ChannelManager::list_pending_channel_negotiations
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PendingChannelNegotiation {
+	/// The temporary channel ID used while negotiating the channel.
+	pub temporary_channel_id: ChannelId,
+	/// The node ID of our counterparty.
+	pub counterparty_node_id: PublicKey,
+	/// Whether we initiated the channel opening.
+	pub is_outbound: bool,
+	/// The channel type proposed in the `open_channel` message. This may still change if the
+	/// counterparty rejects it and we fall back to a different channel type.
+	pub channel_type: ChannelTypeFeatures,
+	/// Whether the negotiation was in progress when the [`ChannelManager`] was last persisted and
+	/// has not been observed since it was reloaded.
+	///
+	/// Such negotiations cannot complete anymore, but are retained until the counterparty
+	/// reconnects or [`UserConfig::pending_channel_negotiation_timeout_ticks`] elapse, as the
+	/// counterparty may still consider them in progress until then.
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	/// [`UserConfig::pending_channel_negotiation_timeout_ticks`]: crate::util::config::UserConfig::pending_channel_negotiation_timeout_ticks
+	pub is_restored: bool,
+}
+
+impl_writeable_tlv_based!(PendingChannelNegotiation, {
+	(0, temporary_channel_id, required),
+	(2, counterparty_node_id, required),
+	(4, is_outbound, required),
+	(6, channel_type, required),
+	(_unused, is_restored, (static_value, true)),
+});
+
+/// Our exposure to dust HTLCs in a channel if its feerate were at a given, hypothetical value, as
+/// returned by [`ChannelManager::project_dust_exposure`].
+///
+/// HTLCs are classified as dust on each commitment transaction using that commitment's dust limit
+/// plus the fee of the second-stage HTLC transaction claiming it at the hypothetical feerate.
+/// Unlike the exposure limit checks applied to HTLCs, the totals do not include any excess fees
+/// of the commitment transactions themselves.
+///
+/// Note that the fees of second-stage HTLC transactions are zero for anchor channels, so the
+/// projection does not depend on the feerate for them.
+///
+/// [`ChannelManager::project_dust_exposure`]: crate::ln::channelmanager::ChannelManager::project_dust_exposure
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DustExposureProjection {
+	/// The hypothetical feerate the exposure was projected at.
+	pub feerate_sat_per_1000_weight: u32,
+	/// The total value, in millisatoshis, of pending HTLCs which would be dust on our commitment
+	/// transaction.
+	pub holder_commitment_dust_exposure_msat: u64,
+	/// The total value, in millisatoshis, of pending HTLCs which would be dust on our
+	/// counterparty's commitment transaction.
+	pub counterparty_commitment_dust_exposure_msat: u64,
+	/// The maximum dust exposure we currently allow on either commitment transaction, as
+	/// configured via [`ChannelConfig::max_dust_htlc_exposure`].
+	pub max_dust_htlc_exposure_msat: u64,
+}
+
+/// The expected fee and weight of a cooperative closing transaction of a channel at a given
+/// feerate, as returned by [`ChannelManager::estimate_cooperative_close_fee`].
+///
+/// The closing transaction is built from the channel's current balances, i.e., as if all pending
+/// HTLCs were failed, and the shutdown scripts negotiated so far. Until a shutdown script is known
+/// for either side, a 34-byte script (e.g., P2WSH or P2TR) is assumed for it.
+///
+/// [`ChannelManager::estimate_cooperative_close_fee`]: crate::ln::channelmanager::ChannelManager::estimate_cooperative_close_fee
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CooperativeCloseFeeEstimate {
+	/// The total fee paid by the closing transaction.
+	///
+	/// If the funder's output would be dust after paying the fee, it is omitted and its whole
+	/// balance is paid as fee instead.
+	pub fee: Amount,
+	/// The expected weight of the closing transaction, which can be used to derive the fee at
+	/// other feerates.
+	///
+	/// Note that whether an output is omitted as dust depends on the feerate, so this may change
+	/// at other feerates if either balance is small.
+	pub weight: Weight,
+	/// Whether the closing transaction includes an output paying our balance.
+	pub includes_holder_output: bool,
+	/// Whether the closing transaction includes an output paying our counterparty's balance.
+	pub includes_counterparty_output: bool,
+}
+
+/// The number of updates queued in the holding cell of a channel, as returned by
+/// [`ChannelManager::list_holding_cell_sizes`].
+///
+/// Updates are queued in the holding cell while they cannot be sent to our counterparty
+/// immediately, e.g., while it is disconnected or we're waiting on a `revoke_and_ack`. See
+/// [`ChannelConfig::max_holding_cell_updates_per_commitment`] to limit how many of them are
+/// released at once.
+///
+/// [`ChannelManager::list_holding_cell_sizes`]: crate::ln::channelmanager::ChannelManager::list_holding_cell_sizes
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HoldingCellSize {
+	/// The channel's id.
+	pub channel_id: ChannelId,
+	/// The node id of our counterparty in the channel.
+	pub counterparty_node_id: PublicKey,
+	/// The number of queued outbound HTLC additions.
+	pub num_pending_adds: usize,
+	/// The number of queued fulfills of inbound HTLCs.
+	pub num_pending_fulfills: usize,
+	/// The number of queued fails of inbound HTLCs, including malformed HTLC fails.
+	pub num_pending_fails: usize,
+	/// Whether a fee update is queued.
+	pub has_pending_fee_update: bool,
+}
+
+/// The constraint limiting how much we can receive over a channel, as reported in
+/// [`ChannelReceivableAmount`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReceiveConstraint {
+	/// The channel is not currently usable, e.g., as it is shutting down or our counterparty is
+	/// disconnected.
+	ChannelNotUsable,
+	/// Our counterparty's balance, less the channel reserve we require them to keep and, if they
+	/// are the channel funder, the commitment transaction fee for the additional HTLC.
+	CounterpartyBalance,
+	/// We are the channel funder and our balance does not cover the commitment transaction fee
+	/// for an additional non-dust HTLC while keeping the channel reserve our counterparty requires,
+	/// so we can only receive dust HTLCs.
+	HolderCommitmentFee,
+	/// The maximum total value of pending inbound HTLCs we accept.
+	MaxHtlcValueInFlight,
+	/// The maximum number of pending inbound HTLCs we accept.
+	MaxAcceptedHtlcs,
+	/// The maximum value of a single HTLC, as advertised in our `channel_update`.
+	MaxHtlcValue,
+	/// The minimum value of an HTLC we accept exceeds what we can otherwise receive.
+	MinHtlcValue,
+}
+
+/// How much we can currently receive over a channel, as returned in a [`ReceivableSummary`].
+///
+/// Unlike [`ChannelDetails::inbound_capacity_msat`], this accounts for the same limits we apply
+/// when accepting an inbound HTLC, i.e., the reserve our counterparty has to keep, the commitment
+/// transaction fee for the HTLC, and our limits on inbound HTLCs.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelReceivableAmount {
+	/// The channel's id.
+	pub channel_id: ChannelId,
+	/// The node id of our counterparty in the channel.
+	pub counterparty_node_id: PublicKey,
+	/// The maximum amount, in millisatoshis, we can receive in a single HTLC.
+	pub max_single_htlc_msat: u64,
+	/// The constraint limiting [`Self::max_single_htlc_msat`].
+	pub single_htlc_constraint: ReceiveConstraint,
+	/// The maximum amount, in millisatoshis, we can receive across multiple HTLCs, e.g., as parts
+	/// of a multi-path payment.
+	///
+	/// This assumes the commitment transaction fee of a single additional HTLC, and may thus
+	/// overstate the receivable amount slightly if our counterparty pays the fee for many HTLCs.
+	pub max_total_msat: u64,
+	/// The constraint limiting [`Self::max_total_msat`].
+	pub total_constraint: ReceiveConstraint,
+}
+
+/// How much we can currently receive over all our channels, as returned by
+/// [`ChannelManager::get_receivable_summary`].
+///
+/// [`ChannelManager::get_receivable_summary`]: crate::ln::channelmanager::ChannelManager::get_receivable_summary
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReceivableSummary {
+	/// How much we can receive over each of our funded channels.
+	pub channels: Vec<ChannelReceivableAmount>,
+	/// The maximum amount, in millisatoshis, we can receive in a single HTLC over any channel,
+	/// i.e., the largest payment we can receive without multi-path payments.
+	pub max_single_htlc_msat: u64,
+	/// The maximum amount, in millisatoshis, we can receive in total over all channels using
+	/// multi-path payments.
+	pub max_total_msat: u64,
+}
+
+impl ReceivableSummary {
+	/// Returns whether a payment of the given amount can be received given the current channel
+	/// balances and limits, using multi-path payments if needed.
+	pub fn can_receive(&self, amount_msat: u64) -> bool {
+		amount_msat <= self.max_total_msat
+	}
+}
+
 #[cfg(test)]
 mod tests {
 	use bitcoin::{hashes::Hash as _, secp256k1::PublicKey};
@@ -698,6 +956,7 @@ mod tests {
 				skimmed_fee_msat: Some(42),
 				is_dust: false,
 			}],
+			exogenous_fee_reserve_recommended_sat: Some(45_000),
 		};
 		let mut buffer = Vec::new();
 		channel_details.write(&mut buffer).unwrap();
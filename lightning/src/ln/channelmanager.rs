@@ -17,11 +17,13 @@
 //! on-chain transactions (it only monitors the chain to watch for any force-closes that might
 //! imply it needs to fail HTLCs/payments/channels it manages).
 
+use bitcoin::absolute::LockTime;
 use bitcoin::block::Header;
 use bitcoin::constants::ChainHash;
 use bitcoin::key::constants::SECRET_KEY_SIZE;
 use bitcoin::network::Network;
-use bitcoin::transaction::Transaction;
+use bitcoin::psbt::Psbt;
+use bitcoin::transaction::{Transaction, Version};
 
 use bitcoin::hash_types::{BlockHash, Txid};
 use bitcoin::hashes::hmac::Hmac;
@@ -30,9 +32,9 @@ use bitcoin::hashes::{Hash, HashEngine, HmacEngine};
 
 use bitcoin::secp256k1::Secp256k1;
 use bitcoin::secp256k1::{PublicKey, SecretKey};
-use bitcoin::{secp256k1, Sequence};
 #[cfg(splicing)]
-use bitcoin::{ScriptBuf, TxIn, Weight};
+use bitcoin::Weight;
+use bitcoin::{secp256k1, FeeRate, ScriptBuf, Sequence, TxIn, TxOut, Witness};
 
 use crate::blinded_path::message::MessageForwardNode;
 use crate::blinded_path::message::{AsyncPaymentsContext, OffersContext};
@@ -50,10 +52,13 @@ use crate::chain::channelmonitor::{
 	LATENCY_GRACE_PERIOD_BLOCKS, MAX_BLOCKS_FOR_CONF,
 };
 use crate::chain::transaction::{OutPoint, TransactionData};
-use crate::chain::{BestBlock, ChannelMonitorUpdateStatus, Confirm, Watch};
+use crate::chain::{BestBlock, ChannelMonitorUpdateStatus, ClaimId, Confirm, Watch, WatchedOutput};
+use crate::events::bump_transaction::sync::CoinSelectionSourceSync;
+use crate::events::bump_transaction::{Input, EMPTY_SCRIPT_SIG_WEIGHT};
 use crate::events::{
-	self, ClosureReason, Event, EventHandler, EventsProvider, HTLCHandlingFailureType,
-	InboundChannelFunds, PaymentFailureReason, ReplayEvent,
+	self, BatchedChannelCloseOutcome, BatchedChannelCloseResult, ClosureReason, Event,
+	EventHandler, EventsProvider, HTLCHandlingFailureType, InboundChannelFunds,
+	PaymentFailureReason, ReplayEvent,
 };
 use crate::events::{FundingInfo, PaidBolt12Invoice};
 use crate::ln::chan_utils::selected_commitment_sat_per_1000_weight;
@@ -64,9 +69,18 @@ use crate::ln::channel::{
 	InboundV1Channel, OutboundV1Channel, PendingV2Channel, ReconnectionMsg, ShutdownResult,
 	UpdateFulfillCommitFetch, WithChannelContext,
 };
-use crate::ln::channel_state::ChannelDetails;
+use crate::ln::channel_receipt::{ChannelReceipt, UnsignedChannelReceipt};
+#[cfg(any(test, feature = "channel_state_replication"))]
+use crate::ln::channel_replication::{
+	ChannelReplicationState, ChannelStateDelta, ReplicationError, ReplicationSink,
+};
+use crate::ln::channel_state::{
+	ChannelDetails, CooperativeCloseFeeEstimate, DustExposureProjection, HoldingCellSize,
+	InteractiveTxNegotiationProgress, PendingChannelNegotiation, ReceivableSummary,
+};
 use crate::ln::inbound_payment;
 use crate::ln::interactivetxs::{HandleTxCompleteResult, InteractiveTxMessageSendResult};
+use crate::ln::invoice_utils::{select_jit_channel_hint, JitChannelHint};
 use crate::ln::msgs;
 use crate::ln::msgs::{
 	BaseMessageHandler, ChannelMessageHandler, CommitmentUpdate, DecodeError, LightningError,
@@ -86,8 +100,8 @@ use crate::ln::our_peer_storage::EncryptedOurPeerStorage;
 #[cfg(test)]
 use crate::ln::outbound_payment;
 use crate::ln::outbound_payment::{
-	OutboundPayments, PendingOutboundPayment, RetryableInvoiceRequest, SendAlongPathArgs,
-	StaleExpiration,
+	check_payment_feasibility, OutboundPayments, PendingOutboundPayment, RetryableInvoiceRequest,
+	SendAlongPathArgs, StaleExpiration,
 };
 use crate::ln::types::ChannelId;
 use crate::offers::async_receive_offer_cache::AsyncReceiveOfferCache;
@@ -111,12 +125,15 @@ use crate::onion_message::messenger::{
 	MessageRouter, MessageSendInstructions, Responder, ResponseInstruction,
 };
 use crate::onion_message::offers::{OffersMessage, OffersMessageHandler};
+use crate::routing::gossip::RoutingFees;
 use crate::routing::router::{
-	BlindedTail, FixedRouter, InFlightHtlcs, Path, Payee, PaymentParameters, Route,
+	BlindedTail, FixedRouter, InFlightHtlcs, Path, Payee, PaymentParameters, Route, RouteHint,
 	RouteParameters, RouteParametersConfig, Router,
 };
 use crate::sign::ecdsa::EcdsaChannelSigner;
-use crate::sign::{EntropySource, NodeSigner, Recipient, SignerProvider};
+use crate::sign::{
+	EntropySource, NodeSigner, OutputSpender, Recipient, SignerProvider, SpendableOutputDescriptor,
+};
 #[cfg(any(feature = "_test_utils", test))]
 use crate::types::features::Bolt11InvoiceFeatures;
 use crate::types::features::{
@@ -124,9 +141,15 @@ use crate::types::features::{
 };
 use crate::types::payment::{PaymentHash, PaymentPreimage, PaymentSecret};
 use crate::types::string::UntrustedString;
+use crate::util::anchor_channel_reserves::{
+	get_reserve_per_channel, is_anchor_channel_type, validate_against_config,
+	AnchorChannelReserveCheck, AnchorChannelReserveContext, ForceCloseCostEstimate,
+};
 use crate::util::config::{ChannelConfig, ChannelConfigOverrides, ChannelConfigUpdate, UserConfig};
+use crate::util::deadlines::DeadlineKind;
 use crate::util::errors::APIError;
 use crate::util::logger::{Level, Logger, WithContext};
+use crate::util::message_signing::{verify_close_authorization, CloseAuthorization};
 use crate::util::scid_utils::fake_scid;
 use crate::util::ser::{
 	BigSize, FixedLengthReader, LengthReadable, MaybeReadable, Readable, ReadableArgs, VecWriter,
@@ -134,6 +157,9 @@ use crate::util::ser::{
 };
 use crate::util::wakers::{Future, Notifier};
 
+#[cfg(feature = "std")]
+use crate::util::time::Instant;
+
 #[cfg(all(test, async_payments))]
 use crate::blinded_path::payment::BlindedPaymentPath;
 #[cfg(async_payments)]
@@ -195,8 +221,8 @@ use core::{cmp, mem};
 #[cfg(any(test, feature = "_externalize_tests"))]
 pub(crate) use crate::ln::outbound_payment::PaymentSendFailure;
 pub use crate::ln::outbound_payment::{
-	Bolt11PaymentError, Bolt12PaymentError, ProbeSendFailure, RecipientOnionFields, Retry,
-	RetryableSendFailure,
+	Bolt11PaymentError, Bolt12PaymentError, InfeasiblePaymentReason, ProbeSendFailure,
+	RecipientOnionFields, Retry, RetryableSendFailure,
 };
 use crate::ln::script::ShutdownScript;
 
@@ -785,12 +811,20 @@ impl HTLCSource {
 /// This enum is used to specify which error data to send to peers when failing back an HTLC
 /// using [`ChannelManager::fail_htlc_backwards_with_reason`].
 ///
+/// Only failure codes which may be sent by the final node of a payment are covered. Any fields
+/// required by a failure code are populated automatically from the HTLC being failed. A
+/// [`LocalHTLCFailureReason`] may be converted into a `FailureCode` with [`TryFrom`], which will
+/// fail if the failure is not legal for a final node to send.
+///
 /// For more info on failure codes, see <https://github.com/lightning/bolts/blob/master/04-onion-routing.md#failure-messages>.
-#[derive(Clone, Copy)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum FailureCode {
 	/// We had a temporary error processing the payment. Useful if no other error codes fit
 	/// and you want to indicate that the payer may want to retry.
 	TemporaryNodeFailure,
+	/// We had a permanent error processing the payment, indicating that the payer should not
+	/// retry through us.
+	PermanentNodeFailure,
 	/// We have a required feature which was not in this onion. For example, you may require
 	/// some additional metadata that was not provided with this payment.
 	RequiredNodeFeatureMissing,
@@ -805,17 +839,66 @@ pub enum FailureCode {
 	/// If available, the tuple data may include the type number and byte offset in the
 	/// decrypted byte stream where the failure occurred.
 	InvalidOnionPayload(Option<(u64, u16)>),
+	/// The CLTV expiry of the HTLC does not match the one in the onion. The HTLC's CLTV expiry is
+	/// included in the failure.
+	FinalIncorrectCltvExpiry,
+	/// The amount of the HTLC does not match the one in the onion. The HTLC's amount is included
+	/// in the failure.
+	FinalIncorrectHtlcAmount,
+	/// The full amount of a multi-part payment was not received in time.
+	MppTimeout,
 }
 
 impl Into<LocalHTLCFailureReason> for FailureCode {
 	fn into(self) -> LocalHTLCFailureReason {
 		match self {
 			FailureCode::TemporaryNodeFailure => LocalHTLCFailureReason::TemporaryNodeFailure,
+			FailureCode::PermanentNodeFailure => LocalHTLCFailureReason::PermanentNodeFailure,
 			FailureCode::RequiredNodeFeatureMissing => LocalHTLCFailureReason::RequiredNodeFeature,
 			FailureCode::IncorrectOrUnknownPaymentDetails => {
 				LocalHTLCFailureReason::IncorrectPaymentDetails
 			},
 			FailureCode::InvalidOnionPayload(_) => LocalHTLCFailureReason::InvalidOnionPayload,
+			FailureCode::FinalIncorrectCltvExpiry => {
+				LocalHTLCFailureReason::FinalIncorrectCLTVExpiry
+			},
+			FailureCode::FinalIncorrectHtlcAmount => {
+				LocalHTLCFailureReason::FinalIncorrectHTLCAmount
+			},
+			FailureCode::MppTimeout => LocalHTLCFailureReason::MPPTimeout,
+		}
+	}
+}
+
+impl TryFrom<LocalHTLCFailureReason> for FailureCode {
+	type Error = ();
+
+	/// Converts a [`LocalHTLCFailureReason`] into a [`FailureCode`], failing if the reason is not
+	/// one a final node may send.
+	fn try_from(reason: LocalHTLCFailureReason) -> Result<Self, ()> {
+		match reason {
+			LocalHTLCFailureReason::TemporaryNodeFailure => Ok(FailureCode::TemporaryNodeFailure),
+			LocalHTLCFailureReason::PermanentNodeFailure => Ok(FailureCode::PermanentNodeFailure),
+			LocalHTLCFailureReason::RequiredNodeFeature => {
+				Ok(FailureCode::RequiredNodeFeatureMissing)
+			},
+			LocalHTLCFailureReason::InvalidOnionPayload => {
+				Ok(FailureCode::InvalidOnionPayload(None))
+			},
+			LocalHTLCFailureReason::FinalIncorrectCLTVExpiry => {
+				Ok(FailureCode::FinalIncorrectCltvExpiry)
+			},
+			LocalHTLCFailureReason::FinalIncorrectHTLCAmount => {
+				Ok(FailureCode::FinalIncorrectHtlcAmount)
+			},
+			LocalHTLCFailureReason::MPPTimeout => Ok(FailureCode::MppTimeout),
+			// Several of our local failure reasons are sent as `incorrect_or_unknown_payment_details`.
+			_ if reason.failure_code()
+				== LocalHTLCFailureReason::IncorrectPaymentDetails.failure_code() =>
+			{
+				Ok(FailureCode::IncorrectOrUnknownPaymentDetails)
+			},
+			_ => Err(()),
 		}
 	}
 }
@@ -1507,6 +1590,159 @@ pub(super) struct InboundChannelRequest {
 /// accepted. An unaccepted channel that exceeds this limit will be abandoned.
 const UNACCEPTED_INBOUND_CHANNEL_AGE_LIMIT_TICKS: i32 = 2;
 
+/// The progress of a batch of channel closures initiated via
+/// [`ChannelManager::close_channels_with_peer`].
+struct BatchedCloseState {
+	counterparty_node_id: PublicKey,
+	/// The channels in the batch, along with their outcome once resolved.
+	channels: Vec<(ChannelId, Option<BatchedChannelCloseOutcome>)>,
+	/// The number of ticks remaining before the batch times out.
+	ticks_remaining: u16,
+}
+
+impl BatchedCloseState {
+	fn is_resolved(&self) -> bool {
+		self.channels.iter().all(|(_, outcome)| outcome.is_some())
+	}
+
+	/// Returns the [`Event::BatchedChannelCloseCompleted`] for the batch, reporting channels which
+	/// have not been resolved yet as [`BatchedChannelCloseOutcome::Pending`].
+	fn completed_event(&self) -> Event {
+		let results = self
+			.channels
+			.iter()
+			.map(|(channel_id, outcome)| BatchedChannelCloseResult {
+				channel_id: *channel_id,
+				outcome: outcome.unwrap_or(BatchedChannelCloseOutcome::Pending),
+			})
+			.collect();
+		Event::BatchedChannelCloseCompleted {
+			counterparty_node_id: self.counterparty_node_id,
+			results,
+		}
+	}
+}
+
+/// The parameters of an inbound channel open request, as provided to an
+/// [`InboundChannelAcceptor`].
+///
+/// The fields mirror those of [`Event::OpenChannelRequest`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InboundChannelProposal {
+	/// The temporary channel ID of the channel requested to be opened.
+	pub temporary_channel_id: ChannelId,
+	/// The node_id of the counterparty requesting to open the channel.
+	pub counterparty_node_id: PublicKey,
+	/// The channel value of the requested channel.
+	pub funding_satoshis: u64,
+	/// The kind of funding for the requested channel.
+	pub channel_negotiation_type: InboundChannelFunds,
+	/// The features that this channel will operate with.
+	pub channel_type: ChannelTypeFeatures,
+	/// Whether the counterparty wishes to announce the channel to the network.
+	pub is_announced: bool,
+	/// The channel parameters proposed by the counterparty.
+	pub params: msgs::ChannelParameters,
+}
+
+/// The decision of an [`InboundChannelAcceptor`] on an inbound channel open request.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InboundChannelDecision {
+	/// Accept the channel immediately, including channels with anchor outputs, which are otherwise
+	/// only accepted manually.
+	///
+	/// Zero-conf channels are still rejected as they require
+	/// [`ChannelManager::accept_inbound_channel_from_trusted_peer_0conf`].
+	Accept,
+	/// Reject the channel, sending an error message with the given reason to the counterparty.
+	///
+	/// The reason is sanitized before it is sent, as BOLT 1 asks nodes not to display error data
+	/// verbatim unless it consists solely of printable ASCII characters: any other characters are
+	/// replaced with `?` and the reason is truncated to [`MAX_CHANNEL_REJECTION_REASON_LEN`]
+	/// bytes. See [`insufficient_reserve_error_message`] for a reason describing a lack of anchor
+	/// channel reserve.
+	///
+	/// [`insufficient_reserve_error_message`]: crate::util::anchor_channel_reserves::insufficient_reserve_error_message
+	Reject(String),
+	/// Defer the decision by generating an [`Event::OpenChannelRequest`], as if
+	/// [`UserConfig::manually_accept_inbound_channels`] was set.
+	DeferToManual,
+}
+
+/// The maximum length, in bytes, of the reason sent to our counterparty when rejecting an inbound
+/// channel, see [`InboundChannelDecision::Reject`].
+pub const MAX_CHANNEL_REJECTION_REASON_LEN: usize = 256;
+
+/// Sanitizes the reason for rejecting an inbound channel before sending it to our counterparty,
+/// replacing characters which aren't printable ASCII and capping its length to
+/// [`MAX_CHANNEL_REJECTION_REASON_LEN`].
+fn sanitize_channel_rejection_reason(reason: &str) -> String {
+	reason
+		.chars()
+		.map(|c| if (' '..='~').contains(&c) { c } else { '?' })
+		.take(MAX_CHANNEL_REJECTION_REASON_LEN)
+		.collect()
+}
+
+/// The action taken by
+/// [`ChannelManager::accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve`] if the
+/// anchor channel reserve is insufficient to support an additional anchor channel.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ZeroConfInsufficientReserveAction {
+	/// Accept the channel, but wait for the funding transaction to reach the usual minimum depth
+	/// before using it, as with [`ChannelManager::accept_inbound_channel`].
+	///
+	/// Channels whose type requires zero confirmations are still rejected.
+	RequireConfirmations,
+	/// Reject the channel, sending an error message to the counterparty.
+	Reject,
+}
+
+/// A hook consulted by the [`ChannelManager`] on each inbound channel open request when
+/// [`UserConfig::manually_accept_inbound_channels`] is not set, allowing policies such as
+/// reserve checks to be applied without handling every [`Event::OpenChannelRequest`].
+///
+/// Registered via [`ChannelManager::set_inbound_channel_acceptor`]. See
+/// [`AnchorReserveChannelAcceptor`] for an implementation gating anchor channels on the available
+/// anchor channel reserve.
+///
+/// [`AnchorReserveChannelAcceptor`]: crate::util::anchor_channel_reserves::AnchorReserveChannelAcceptor
+pub trait InboundChannelAcceptor {
+	/// Decides whether to accept the channel described by `proposal`, given the channels currently
+	/// open with any counterparty, as returned by [`ChannelManager::list_channels`].
+	///
+	/// This is called while handling the counterparty's `open_channel` message and thus should not
+	/// block.
+	fn decide_inbound_channel(
+		&self, proposal: &InboundChannelProposal, existing_channels: &[ChannelDetails],
+	) -> InboundChannelDecision;
+
+	/// Returns the [`AnchorChannelReserveContext`] the acceptor enforces on inbound anchor
+	/// channels, if any.
+	///
+	/// If provided, it is checked against the [`ChannelManager`]'s [`UserConfig`] when the
+	/// acceptor is registered, logging any [`ReserveConfigWarning`]s.
+	///
+	/// [`ReserveConfigWarning`]: crate::util::anchor_channel_reserves::ReserveConfigWarning
+	fn anchor_channel_reserve_context(&self) -> Option<&AnchorChannelReserveContext> {
+		None
+	}
+}
+
+/// Requires cooperative closes of channels whose value exceeds a threshold to be authorized by a
+/// signature of a given key, e.g., one kept offline, as configured via
+/// [`ChannelManager::set_close_authorizer`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CloseAuthorizer {
+	/// The public key which must sign a [`CloseAuthorization`] via
+	/// [`sign_close_authorization`].
+	///
+	/// [`sign_close_authorization`]: crate::util::message_signing::sign_close_authorization
+	pub authorizer_pubkey: PublicKey,
+	/// Cooperatively closing channels with a value above this threshold requires authorization.
+	pub threshold_satoshis: u64,
+}
+
 /// The number of blocks of historical feerate estimates we keep around and consider when deciding
 /// to force-close a channel for having too-low fees. Also the number of blocks we have to see
 /// after startup before we consider force-closing channels for having too-low fees.
@@ -2505,6 +2741,55 @@ pub struct ChannelManager<
 	L::Target: Logger,
 {
 	default_configuration: UserConfig,
+	/// The context used to estimate the reserve required for inbound anchor channels, as provided
+	/// in [`Event::OpenChannelRequest::estimated_reserve_required`]. This is not persisted.
+	anchor_channel_reserve_context: Mutex<AnchorChannelReserveContext>,
+	/// The hook consulted on inbound channel open requests when
+	/// [`UserConfig::manually_accept_inbound_channels`] is not set. This is not persisted.
+	inbound_channel_acceptor: Mutex<Option<Arc<dyn InboundChannelAcceptor + Send + Sync>>>,
+	/// Whether channel types with anchor outputs are negotiated for new channels, see
+	/// [`Self::set_anchor_negotiation_enabled`]. This is not persisted.
+	anchor_negotiation_enabled: AtomicBool,
+	/// The [`chain::Filter`] with which funding outputs are registered as soon as they are known,
+	/// i.e., before the [`ChannelMonitor`] is handed to the [`chain::Watch`]. This is not persisted.
+	chain_filter: Mutex<Option<Arc<dyn chain::Filter + Send + Sync>>>,
+	/// The state of replicating our channels to a hot standby, or of applying the channel state
+	/// replicated by a primary. This is not persisted.
+	///
+	/// This lock is taken after `per_peer_state` but before any `peer_state`.
+	#[cfg(any(test, feature = "channel_state_replication"))]
+	channel_replication: Mutex<ChannelReplicationState>,
+	/// A bounded history of the balances of each of our channels, recorded if
+	/// [`UserConfig::channel_balance_history_limit`] is non-zero.
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	channel_balance_history: Mutex<HashMap<ChannelId, VecDeque<ChannelBalanceSample>>>,
+	/// The high watermarks of the HTLCs accepted in each of our channels, as returned by
+	/// [`Self::get_inflight_htlc_stats`].
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	accepted_htlc_watermarks: Mutex<HashMap<ChannelId, AcceptedHtlcWatermark>>,
+	/// Cooperatively closed channels whose closing transaction we broadcast but which has not yet
+	/// been confirmed [`ANTI_REORG_DELAY`] blocks deep, allowing its fee to be bumped via
+	/// [`Self::bump_closing_transaction_fee`].
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	pending_cooperative_closes: Mutex<HashMap<ChannelId, PendingCooperativeClose>>,
+	/// Channel negotiations which were in progress when we were last persisted and whose
+	/// counterparty has not reconnected since, see [`Self::list_pending_channel_negotiations`].
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	restored_channel_negotiations: Mutex<Vec<RestoredChannelNegotiation>>,
+	/// The key required to authorize cooperative closes of large channels, see
+	/// [`Self::set_close_authorizer`]. This is not persisted.
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	close_authorizer: Mutex<Option<CloseAuthorizer>>,
+	/// The highest nonce of any [`CloseAuthorization`] used so far, such that authorizations can't
+	/// be replayed, even across restarts.
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	last_close_authorization_nonce: Mutex<u64>,
 	chain_hash: ChainHash,
 	fee_estimator: LowerBoundedFeeEstimator<F>,
 	chain_monitor: M,
@@ -2557,6 +2842,12 @@ pub struct ChannelManager<
 	///
 	/// See `ChannelManager` struct-level documentation for lock order requirements.
 	pending_intercepted_htlcs: Mutex<HashMap<InterceptId, PendingAddHTLCInfo>>,
+	/// The number of [`Self::expire_stale_intercepted_htlcs`] calls each HTLC in
+	/// [`Self::pending_intercepted_htlcs`] has been held for. This is not persisted, so the count
+	/// restarts on reload.
+	///
+	/// This is only ever locked after [`Self::pending_intercepted_htlcs`].
+	intercepted_htlc_timer_ticks: Mutex<HashMap<InterceptId, u16>>,
 
 	/// SCID/SCID Alias -> pending `update_add_htlc`s to decode.
 	///
@@ -2624,6 +2915,14 @@ pub struct ChannelManager<
 	/// very far in the past, and can only ever be up to two hours in the future.
 	highest_seen_timestamp: AtomicUsize,
 
+	/// The time at which we last saw a new best block, or at which we started if we have not seen
+	/// one since, used by [`Self::check_chain_sync_staleness`].
+	#[cfg(feature = "std")]
+	last_best_block_update: Mutex<Instant>,
+	/// Whether no new best block has been seen for longer than
+	/// [`UserConfig::chain_sync_stale_threshold_secs`], reset as soon as one is seen.
+	chain_sync_stale: AtomicBool,
+
 	/// The bulk of our storage. Currently the `per_peer_state` stores our channels on a per-peer
 	/// basis, as well as the peer's latest features.
 	///
@@ -2666,6 +2965,12 @@ pub struct ChannelManager<
 
 	/// A simple atomic flag to ensure only one task at a time can be processing events asynchronously.
 	pending_events_processor: AtomicBool,
+	/// The highest number of events pending at once, as returned by
+	/// [`Self::get_pending_event_stats`]. This is not persisted.
+	pending_events_watermark: AtomicUsize,
+	/// The number of informational events dropped as more than
+	/// [`UserConfig::pending_events_soft_limit`] events were pending. This is not persisted.
+	dropped_pending_events: AtomicUsize,
 
 	/// A simple atomic flag to ensure only one task at a time can be processing HTLC forwards via
 	/// [`Self::process_pending_htlc_forwards`].
@@ -2696,6 +3001,20 @@ pub struct ChannelManager<
 	/// This information does not need to be persisted as funding nodes can forget
 	/// unfunded channels upon disconnection.
 	funding_batch_states: Mutex<BTreeMap<Txid, Vec<(ChannelId, PublicKey, bool)>>>,
+	/// The PSBTs of funding transactions provided via
+	/// [`Self::funding_transaction_generated_unsigned`] which are not yet fully signed, by the
+	/// temporary channel id of the channel they fund, along with the counterparty's node id.
+	///
+	/// This information does not need to be persisted as funding nodes can forget
+	/// unfunded channels upon disconnection.
+	pending_funding_psbts: Mutex<HashMap<ChannelId, (PublicKey, Psbt)>>,
+	/// Tracks the progress of batches of channel closures initiated via
+	/// [`Self::close_channels_with_peer`].
+	///
+	/// This is not persisted, i.e., batches in progress are forgotten upon restart.
+	///
+	/// This is a leaf lock which may be taken while holding any other lock.
+	batched_close_states: Mutex<Vec<BatchedCloseState>>,
 
 	background_events_processed_since_startup: AtomicBool,
 
@@ -2775,9 +3094,18 @@ enum NotifyOption {
 /// We allow callers to either always notify by constructing with `notify_on_drop` or choose to
 /// notify or not based on whether relevant changes have been made, providing a closure to
 /// `optionally_notify` which returns a `NotifyOption`.
+/// Replicates the state of the channels of a [`ChannelManager`] to its [`ReplicationSink`], if
+/// any, whenever a [`PersistenceNotifierGuard`] indicates the need to persist.
+#[cfg(any(test, feature = "channel_state_replication"))]
+trait ReplicateChannelStates {
+	fn replicate_channel_states(&self) -> Result<(), ReplicationError>;
+}
+
 struct PersistenceNotifierGuard<'a, F: FnOnce() -> NotifyOption> {
 	event_persist_notifier: &'a Notifier,
 	needs_persist_flag: &'a AtomicBool,
+	#[cfg(any(test, feature = "channel_state_replication"))]
+	channel_state_replicator: &'a dyn ReplicateChannelStates,
 	// Always `Some` once initialized, but tracked as an `Option` to obtain the closure by value in
 	// [`PersistenceNotifierGuard::drop`].
 	should_persist: Option<F>,
@@ -2808,6 +3136,8 @@ impl<'a> PersistenceNotifierGuard<'a, fn() -> NotifyOption> {
 		PersistenceNotifierGuard {
 			event_persist_notifier: &cm.get_cm().event_persist_notifier,
 			needs_persist_flag: &cm.get_cm().needs_persist_flag,
+			#[cfg(any(test, feature = "channel_state_replication"))]
+			channel_state_replicator: cm.get_cm(),
 			should_persist: Some(move || {
 				// Pick the "most" action between `persist_check` and the background events
 				// processing and return that.
@@ -2839,6 +3169,8 @@ impl<'a> PersistenceNotifierGuard<'a, fn() -> NotifyOption> {
 		PersistenceNotifierGuard {
 			event_persist_notifier: &cm.get_cm().event_persist_notifier,
 			needs_persist_flag: &cm.get_cm().needs_persist_flag,
+			#[cfg(any(test, feature = "channel_state_replication"))]
+			channel_state_replicator: cm.get_cm(),
 			should_persist: Some(persist_check),
 			_read_guard: read_guard,
 		}
@@ -2856,6 +3188,10 @@ impl<'a, F: FnOnce() -> NotifyOption> Drop for PersistenceNotifierGuard<'a, F> {
 		};
 		match should_persist() {
 			NotifyOption::DoPersist => {
+				// Replicate any channel state changes before they can be persisted or any messages
+				// resulting from them can be sent. Failures are logged by the `ChannelManager`.
+				#[cfg(any(test, feature = "channel_state_replication"))]
+				let _ = self.channel_state_replicator.replicate_channel_states();
 				self.needs_persist_flag.store(true, Ordering::Release);
 				self.event_persist_notifier.notify()
 			},
@@ -2865,6 +3201,60 @@ impl<'a, F: FnOnce() -> NotifyOption> Drop for PersistenceNotifierGuard<'a, F> {
 	}
 }
 
+#[cfg(any(test, feature = "channel_state_replication"))]
+impl<
+		M: Deref,
+		T: Deref,
+		ES: Deref,
+		NS: Deref,
+		SP: Deref,
+		F: Deref,
+		R: Deref,
+		MR: Deref,
+		L: Deref,
+	> ReplicateChannelStates for ChannelManager<M, T, ES, NS, SP, F, R, MR, L>
+where
+	M::Target: chain::Watch<<SP::Target as SignerProvider>::EcdsaSigner>,
+	T::Target: BroadcasterInterface,
+	ES::Target: EntropySource,
+	NS::Target: NodeSigner,
+	SP::Target: SignerProvider,
+	F::Target: FeeEstimator,
+	R::Target: Router,
+	MR::Target: MessageRouter,
+	L::Target: Logger,
+{
+	fn replicate_channel_states(&self) -> Result<(), ReplicationError> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let mut replication = self.channel_replication.lock().unwrap();
+		if !replication.is_replicating() {
+			return Ok(());
+		}
+
+		let mut channels = Vec::new();
+		for (counterparty_node_id, peer_state_mutex) in per_peer_state.iter() {
+			let peer_state = peer_state_mutex.lock().unwrap();
+			for channel in peer_state
+				.channel_by_id
+				.values()
+				.filter_map(Channel::as_funded)
+				.filter(|channel| channel.context.is_funding_broadcast())
+			{
+				let channel_id = channel.context.channel_id();
+				channels.push((channel_id, *counterparty_node_id, channel.encode()));
+			}
+		}
+		// Keep holding the replication lock while calling the sink, such that deltas are emitted
+		// in the order in which the channel states were read.
+		mem::drop(per_peer_state);
+
+		replication.replicate_channels(channels).map_err(|e| {
+			log_error!(self.logger, "Failed to replicate channel state: {:?}", e);
+			e
+		})
+	}
+}
+
 /// The amount of time in blocks we require our counterparty wait to claim their money (ie time
 /// between when we, or our watchtower, must check for them having broadcast a theft transaction).
 ///
@@ -2934,17 +3324,58 @@ const _CHECK_CLTV_EXPIRY_OFFCHAIN: () = assert!(
 		>= 2 * LATENCY_GRACE_PERIOD_BLOCKS - 1 + _ASSUMED_COUNTERPARTY_CLTV_CLAIM_BUFFER
 );
 
-/// The number of ticks of [`ChannelManager::timer_tick_occurred`] until expiry of incomplete MPPs
+/// The number of ticks of [`ChannelManager::expire_stale_inbound_payments`] until expiry of
+/// incomplete MPPs with the default [`UserConfig::mpp_timeout_secs`]
+#[cfg(any(test, feature = "_externalize_tests"))]
 pub(crate) const MPP_TIMEOUT_TICKS: u8 = 3;
 
-/// The number of ticks of [`ChannelManager::timer_tick_occurred`] where a peer is disconnected
-/// until we mark the channel disabled and gossip the update.
+/// The minimum [`UserConfig::mpp_timeout_secs`], as BOLT 4 recommends waiting at least 60 seconds
+/// for the remaining parts of a multi-part payment.
+pub const MIN_MPP_TIMEOUT_SECS: u64 = 60;
+
+/// The maximum [`UserConfig::mpp_timeout_secs`], bounding how long the HTLCs of an incomplete
+/// multi-part payment, and thus the liquidity of the channels they were received over, are held.
+pub const MAX_MPP_TIMEOUT_SECS: u64 = 60 * 60;
+
+fn mpp_timeout_secs(config: &UserConfig) -> u64 {
+	cmp::min(cmp::max(config.mpp_timeout_secs, MIN_MPP_TIMEOUT_SECS), MAX_MPP_TIMEOUT_SECS)
+}
+
+/// Returns the number of ticks of [`ChannelManager::expire_stale_inbound_payments`] until expiry
+/// of incomplete MPPs. As the first tick may happen right after the first part was received, an
+/// additional tick ensures at least [`UserConfig::mpp_timeout_secs`] pass if the ticks are a
+/// minute apart.
+fn mpp_timeout_ticks(config: &UserConfig) -> u8 {
+	((mpp_timeout_secs(config) + 59) / 60 + 1) as u8
+}
+
+/// Returns the height at which a claimable or intercepted HTLC whose onward HTLC would expire at
+/// `cltv_expiry` is failed back.
+///
+/// If height is approaching the number of blocks we think it takes us to get our commitment
+/// transaction confirmed before the HTLC expires, plus the number of blocks we generally consider
+/// it to take to do a commitment update, we just give up on it and fail the HTLC.
+fn inbound_htlc_fail_back_height(cltv_expiry: u32) -> u32 {
+	cltv_expiry.saturating_sub(HTLC_FAIL_BACK_BUFFER)
+}
+
+/// The number of ticks of [`ChannelManager::process_channel_update_status`] where a peer is
+/// disconnected until we mark the channel disabled and gossip the update.
 pub(crate) const DISABLE_GOSSIP_TICKS: u8 = 10;
 
-/// The number of ticks of [`ChannelManager::timer_tick_occurred`] where a peer is connected until
-/// we mark the channel enabled and gossip the update.
+/// The number of ticks of [`ChannelManager::process_channel_update_status`] where a peer is
+/// connected until we mark the channel enabled and gossip the update.
 pub(crate) const ENABLE_GOSSIP_TICKS: u8 = 5;
 
+/// The number of ticks of [`ChannelManager::expire_stale_intercepted_htlcs`] an intercepted HTLC
+/// may be held without being forwarded or failed before we fail it back (around six hours).
+pub(crate) const INTERCEPTED_HTLC_TIMEOUT_TICKS: u16 = 6 * 60;
+
+/// The number of ticks of [`ChannelManager::expire_stale_channels`] after which channels of a
+/// batch initiated via [`ChannelManager::close_channels_with_peer`] which have not closed yet are
+/// force-closed, unless they still have HTLCs pending (around an hour).
+pub(crate) const BATCHED_CLOSE_TIMEOUT_TICKS: u16 = 60;
+
 /// The maximum number of unfunded channels we can have per-peer before we start rejecting new
 /// (inbound) ones. The number of peers with unfunded channels is limited separately in
 /// [`MAX_UNFUNDED_CHANNEL_PEERS`].
@@ -2965,6 +3396,228 @@ const MAX_PEER_STORAGE_SIZE: usize = 1024;
 /// many peers we reject new (inbound) connections.
 const MAX_NO_CHANNEL_PEERS: usize = 250;
 
+/// A reason why [`ChannelManager::create_channel`] would fail or a newly opened channel would be
+/// at risk, as returned by [`ChannelManager::preflight_open_channel`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OpenChannelBlocker {
+	/// We are not currently connected to the counterparty.
+	PeerNotConnected,
+	/// The requested channel type would not be proposed to the counterparty, either because they
+	/// do not support it or because our configuration does not allow it.
+	ChannelTypeUnavailable {
+		/// The channel type that was requested.
+		requested: ChannelTypeFeatures,
+		/// The channel type that would be proposed instead.
+		proposed: ChannelTypeFeatures,
+	},
+	/// The channel value is below the minimum value we allow for a channel.
+	FundingTooLow {
+		/// The minimum channel value, in satoshis.
+		min_channel_value_satoshis: u64,
+	},
+	/// The channel value is above the maximum value allowed, e.g. because the counterparty does not
+	/// support large channels.
+	FundingTooHigh {
+		/// The maximum channel value, in satoshis.
+		max_channel_value_satoshis: u64,
+	},
+	/// The amount to push to the counterparty exceeds the channel value.
+	PushAmountTooHigh {
+		/// The maximum amount that may be pushed, in millisatoshis.
+		max_push_msat: u64,
+	},
+	/// Our balance in the channel can't pay the fee of the initial commitment transaction at the
+	/// feerate currently returned by our [`FeeEstimator`].
+	InsufficientFundsForFee {
+		/// The feerate the initial commitment transaction would use, in satoshis per 1000 weight.
+		feerate_sat_per_1000_weight: u32,
+		/// The fee of the initial commitment transaction, in satoshis.
+		commit_tx_fee_sat: u64,
+	},
+	/// Our configuration negotiates anchor outputs, but the counterparty does not support them,
+	/// thus the channel would fall back to a channel type without anchor outputs.
+	///
+	/// This is only reported if no channel type was explicitly requested, in which case a mismatch
+	/// is reported as [`Self::ChannelTypeUnavailable`] instead.
+	PeerLacksAnchorSupport {
+		/// The channel type that would be proposed instead.
+		proposed: ChannelTypeFeatures,
+	},
+	/// The channel would use anchor outputs, but the provided [`AnchorChannelReserveCheck`] is not
+	/// sufficient to support an additional anchor channel.
+	InsufficientAnchorReserve {
+		/// The number of anchor channels already relying on the reserve.
+		num_anchor_channels: u64,
+		/// The number of anchor channels the reserve is sufficient to support.
+		num_supportable_channels: u64,
+	},
+	/// The feerate of the funding transaction is below the minimum feerate returned by our
+	/// [`FeeEstimator`] for [`ConfirmationTarget::MinAllowedAnchorChannelRemoteFee`], which is
+	/// expected to track the mempool minimum, and thus may not be relayed.
+	FundingFeerateTooLow {
+		/// The feerate of the funding transaction, in satoshis per 1000 weight.
+		funding_feerate_sat_per_1000_weight: u32,
+		/// The minimum feerate, in satoshis per 1000 weight.
+		min_feerate_sat_per_1000_weight: u32,
+	},
+}
+
+/// Statistics about the state held for inbound payments and intercepted HTLCs, as returned by
+/// [`ChannelManager::inbound_state_stats`].
+///
+/// Note that no state is held for payments which have not yet been received, as payment secrets
+/// (see [`ChannelManager::create_inbound_payment`]) and intercept SCIDs (see
+/// [`ChannelManager::get_intercept_scid`]) are derived statelessly.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InboundStateStats {
+	/// The number of payments which have been received and are awaiting being claimed or failed.
+	pub claimable_payments: usize,
+	/// The total number of HTLCs across all [`Self::claimable_payments`].
+	pub claimable_htlcs: usize,
+	/// The number of payments which are being claimed, awaiting a [`ChannelMonitorUpdate`] to
+	/// complete.
+	pub claiming_payments: usize,
+	/// The number of intercepted HTLCs awaiting being forwarded or failed.
+	pub intercepted_htlcs: usize,
+	/// The number of distinct intercept SCIDs across all [`Self::intercepted_htlcs`].
+	pub intercept_scids: usize,
+	/// An approximation of the memory, in bytes, used to hold the above.
+	pub approximate_memory_bytes: usize,
+}
+
+/// A sample of the balance of one of our channels, as returned by
+/// [`ChannelManager::get_channel_balance_history`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChannelBalanceSample {
+	/// The time at which the balance was recorded, as a duration since the Unix epoch.
+	pub timestamp: Duration,
+	/// The available outbound capacity of the channel, as in
+	/// [`ChannelDetails::outbound_capacity_msat`].
+	pub outbound_capacity_msat: u64,
+	/// The available inbound capacity of the channel, as in
+	/// [`ChannelDetails::inbound_capacity_msat`].
+	pub inbound_capacity_msat: u64,
+}
+
+impl_writeable_tlv_based!(ChannelBalanceSample, {
+	(0, timestamp, required),
+	(2, outbound_capacity_msat, required),
+	(4, inbound_capacity_msat, required),
+});
+
+/// Statistics on the HTLCs accepted in one of our channels, i.e., the HTLCs added by our
+/// counterparty, as returned by [`ChannelManager::get_inflight_htlc_stats`].
+///
+/// These can be used to size the anchor channel reserve based on observed rather than expected
+/// HTLCs via [`AnchorChannelReserveContext::from_observed_stats`].
+///
+/// [`AnchorChannelReserveContext::from_observed_stats`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext::from_observed_stats
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct InflightHtlcStats {
+	/// The channel ID of the channel.
+	pub channel_id: ChannelId,
+	/// The node ID of our counterparty in the channel.
+	pub counterparty_node_id: PublicKey,
+	/// The number of accepted HTLCs currently pending in the channel.
+	pub accepted_htlcs: u64,
+	/// The total value of the accepted HTLCs currently pending in the channel.
+	pub accepted_htlcs_value_msat: u64,
+	/// The highest number of accepted HTLCs pending in the channel at once since the channel was
+	/// opened or [`ChannelManager::reset_inflight_htlc_watermarks`] was last called.
+	pub max_accepted_htlcs: u64,
+	/// The highest total value of accepted HTLCs pending in the channel at once since the channel
+	/// was opened or [`ChannelManager::reset_inflight_htlc_watermarks`] was last called.
+	///
+	/// Note that this is tracked independently of [`Self::max_accepted_htlcs`], i.e., both may have
+	/// been reached at different times.
+	pub max_accepted_htlcs_value_msat: u64,
+}
+
+/// Statistics on the events pending handling by the [`ChannelManager`], as returned by
+/// [`ChannelManager::get_pending_event_stats`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingEventStats {
+	/// The number of events currently pending handling.
+	pub pending_events: usize,
+	/// The highest number of events pending at once since the [`ChannelManager`] was started.
+	///
+	/// Note that this is only sampled whenever events are processed,
+	/// [`ChannelManager::timer_tick_occurred`] is called or the stats are queried.
+	pub max_pending_events: usize,
+	/// The number of informational events dropped since the [`ChannelManager`] was started, see
+	/// [`UserConfig::drop_informational_events_on_overflow`].
+	pub dropped_events: usize,
+}
+
+/// Returns whether the given event only informs the user and may be dropped if too many events are
+/// pending, see [`UserConfig::drop_informational_events_on_overflow`].
+fn is_informational_event(event: &Event) -> bool {
+	match event {
+		Event::PaymentForwarded { .. }
+		| Event::PaymentPathSuccessful { .. }
+		| Event::ProbeSuccessful { .. }
+		| Event::ProbeFailed { .. }
+		| Event::ChannelPending { .. }
+		| Event::ChannelReady { .. }
+		| Event::HTLCHandlingFailed { .. }
+		| Event::ChannelPartnerAnnouncementChanged { .. }
+		| Event::ChannelLiquidityThresholdCrossed { .. } => true,
+		_ => false,
+	}
+}
+
+/// The high watermarks of the HTLCs accepted in a channel, see [`InflightHtlcStats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+struct AcceptedHtlcWatermark {
+	max_accepted_htlcs: u64,
+	max_accepted_htlcs_value_msat: u64,
+}
+
+impl_writeable_tlv_based!(AcceptedHtlcWatermark, {
+	(0, max_accepted_htlcs, required),
+	(2, max_accepted_htlcs_value_msat, required),
+});
+
+/// A cooperatively closed channel whose closing transaction has yet to confirm
+/// [`ANTI_REORG_DELAY`] blocks deep.
+struct PendingCooperativeClose {
+	counterparty_node_id: PublicKey,
+	funding_txo: OutPoint,
+	channel_value_satoshis: u64,
+	channel_keys_id: [u8; 32],
+	/// The script our share of the channel is paid to in the closing transaction(s).
+	holder_script: ScriptBuf,
+	/// All closing transactions we consider valid for this channel, in the order they were
+	/// signed. Only one is ever signed for legacy `closing_signed` negotiation.
+	closing_transactions: Vec<Transaction>,
+	/// The transaction spending the funding output and the height and block at which it
+	/// confirmed, if any.
+	confirmation: Option<(Txid, u32, BlockHash)>,
+}
+
+impl_writeable_tlv_based!(PendingCooperativeClose, {
+	(0, counterparty_node_id, required),
+	(2, funding_txo, required),
+	(4, channel_value_satoshis, required),
+	(6, channel_keys_id, required),
+	(8, holder_script, required),
+	(10, closing_transactions, required_vec),
+	(12, confirmation, option),
+});
+
+/// A channel negotiation which was in progress when the [`ChannelManager`] was last persisted.
+struct RestoredChannelNegotiation {
+	negotiation: PendingChannelNegotiation,
+	/// The number of calls to [`ChannelManager::timer_tick_occurred`] since the negotiation was
+	/// first restored.
+	timer_ticks: u16,
+}
+
+impl_writeable_tlv_based!(RestoredChannelNegotiation, {
+	(0, negotiation, required),
+	(2, timer_ticks, required),
+});
+
 /// Used by [`ChannelManager::list_recent_payments`] to express the status of recent payments.
 /// These include payments that have yet to find a successful path, or have unresolved HTLCs.
 #[derive(Debug, PartialEq)]
@@ -2975,6 +3628,23 @@ pub enum RecentPaymentDetails {
 		/// payment and ensure idempotency in LDK.
 		payment_id: PaymentId,
 	},
+	/// When a payment to an often-offline recipient is being held until the recipient comes online
+	/// and releases it, and thus no HTLCs have been sent yet.
+	///
+	/// Once released, the payment transitions to [`RecentPaymentDetails::Pending`]. If not released
+	/// by `expiry_time`, the payment fails with [`PaymentFailureReason::PaymentExpired`].
+	///
+	/// [`PaymentFailureReason::PaymentExpired`]: crate::events::PaymentFailureReason::PaymentExpired
+	AwaitingRelease {
+		/// A user-provided identifier in [`ChannelManager::pay_for_offer`] used to uniquely identify a
+		/// payment and ensure idempotency in LDK.
+		payment_id: PaymentId,
+		/// Hash of the payment that will be sent once released.
+		payment_hash: PaymentHash,
+		/// The time, as a duration since the Unix epoch, after which the payment will be failed if
+		/// it has not been released, as configured by [`UserConfig::async_payment_hold_timeout_secs`].
+		expiry_time: Duration,
+	},
 	/// When a payment is still being sent and awaiting successful delivery.
 	Pending {
 		/// A user-provided identifier in [`send_payment`] or [`pay_for_offer`] used to uniquely
@@ -3329,6 +3999,7 @@ macro_rules! emit_initial_channel_ready_event {
 						.funding
 						.get_funding_txo()
 						.map(|outpoint| outpoint.into_bitcoin_outpoint()),
+					channel_value_satoshis: Some($channel.funding.get_value_satoshis()),
 					channel_type: $channel.funding.get_channel_type().clone(),
 				},
 				None,
@@ -3611,6 +4282,7 @@ macro_rules! process_events_body {
 				}
 			}
 
+			$self.enforce_pending_events_limit();
 			let pending_events = $self.pending_events.lock().unwrap().clone();
 			if !pending_events.is_empty() {
 				result = NotifyOption::DoPersist;
@@ -3704,15 +4376,34 @@ where
 	/// from after [`params.best_block.block_hash`]. See [`chain::Listen`] and [`chain::Confirm`] for
 	/// more details.
 	///
+	/// The provided `config` is checked with [`UserConfig::validate`], with any issues found being
+	/// logged at the warning level.
+	///
+	/// # Panics
+	///
+	/// Panics if `config` contains a [fatal] [`ConfigError`], i.e., one which would prevent any
+	/// channel from being opened.
+	///
 	/// [`block_connected`]: chain::Listen::block_connected
 	/// [`block_disconnected`]: chain::Listen::block_disconnected
 	/// [`params.best_block.block_hash`]: chain::BestBlock::block_hash
+	/// [fatal]: crate::util::config::ConfigError::is_fatal
+	/// [`ConfigError`]: crate::util::config::ConfigError
 	#[rustfmt::skip]
 	pub fn new(
 		fee_est: F, chain_monitor: M, tx_broadcaster: T, router: R, message_router: MR, logger: L,
 		entropy_source: ES, node_signer: NS, signer_provider: SP, config: UserConfig,
 		params: ChainParameters, current_timestamp: u32,
 	) -> Self {
+		if let Err(errors) = config.validate() {
+			for error in errors.iter() {
+				log_warn!(logger, "Invalid configuration: {}", error);
+			}
+			if let Some(error) = errors.iter().find(|error| error.is_fatal()) {
+				panic!("Invalid configuration: {}", error);
+			}
+		}
+
 		let mut secp_ctx = Secp256k1::new();
 		secp_ctx.seeded_randomize(&entropy_source.get_secure_random_bytes());
 
@@ -3727,6 +4418,20 @@ where
 
 		ChannelManager {
 			default_configuration: config.clone(),
+			anchor_channel_reserve_context: Mutex::new(AnchorChannelReserveContext::for_network(
+				params.network,
+			)),
+			inbound_channel_acceptor: Mutex::new(None),
+			anchor_negotiation_enabled: AtomicBool::new(true),
+			chain_filter: Mutex::new(None),
+			#[cfg(any(test, feature = "channel_state_replication"))]
+			channel_replication: Mutex::new(ChannelReplicationState::new()),
+			channel_balance_history: Mutex::new(new_hash_map()),
+			accepted_htlc_watermarks: Mutex::new(new_hash_map()),
+			pending_cooperative_closes: Mutex::new(new_hash_map()),
+			restored_channel_negotiations: Mutex::new(Vec::new()),
+			close_authorizer: Mutex::new(None),
+			last_close_authorization_nonce: Mutex::new(0),
 			chain_hash: ChainHash::using_genesis_block(params.network),
 			fee_estimator: LowerBoundedFeeEstimator::new(fee_est),
 			chain_monitor,
@@ -3742,6 +4447,7 @@ where
 			decode_update_add_htlcs: Mutex::new(new_hash_map()),
 			claimable_payments: Mutex::new(ClaimablePayments { claimable_payments: new_hash_map(), pending_claiming_payments: new_hash_map() }),
 			pending_intercepted_htlcs: Mutex::new(new_hash_map()),
+			intercepted_htlc_timer_ticks: Mutex::new(new_hash_map()),
 			short_to_chan_info: FairRwLock::new(new_hash_map()),
 
 			our_network_pubkey,
@@ -3754,6 +4460,9 @@ where
 			inbound_payment_id_secret: entropy_source.get_secure_random_bytes(),
 
 			highest_seen_timestamp: AtomicUsize::new(current_timestamp as usize),
+			#[cfg(feature = "std")]
+			last_best_block_update: Mutex::new(Instant::now()),
+			chain_sync_stale: AtomicBool::new(false),
 
 			per_peer_state: FairRwLock::new(new_hash_map()),
 
@@ -3762,6 +4471,8 @@ where
 
 			pending_events: Mutex::new(VecDeque::new()),
 			pending_events_processor: AtomicBool::new(false),
+			pending_events_watermark: AtomicUsize::new(0),
+			dropped_pending_events: AtomicUsize::new(0),
 			pending_htlc_forwards_processor: AtomicBool::new(false),
 			pending_background_events: Mutex::new(Vec::new()),
 			total_consistency_lock: RwLock::new(()),
@@ -3769,6 +4480,8 @@ where
 			event_persist_notifier: Notifier::new(),
 			needs_persist_flag: AtomicBool::new(false),
 			funding_batch_states: Mutex::new(BTreeMap::new()),
+			pending_funding_psbts: Mutex::new(new_hash_map()),
+			batched_close_states: Mutex::new(Vec::new()),
 
 			pending_broadcast_messages: Mutex::new(Vec::new()),
 
@@ -3853,6 +4566,11 @@ where
 	/// one derived from the funding transaction's TXID. If the counterparty rejects the channel
 	/// immediately, this temporary ID will appear in [`Event::ChannelClosed::channel_id`].
 	///
+	/// To fund the channel from [`SpendableOutputDescriptor`]s, e.g., those of a channel closed
+	/// previously, call [`ChannelManager::funding_transaction_generated_from_spendable_outputs`]
+	/// upon [`Event::FundingGenerationReady`] instead.
+	///
+	/// [`Event::FundingGenerationReady`]: events::Event::FundingGenerationReady
 	/// [`Event::FundingGenerationReady::user_channel_id`]: events::Event::FundingGenerationReady::user_channel_id
 	/// [`Event::FundingGenerationReady::temporary_channel_id`]: events::Event::FundingGenerationReady::temporary_channel_id
 	/// [`Event::ChannelClosed::channel_id`]: events::Event::ChannelClosed::channel_id
@@ -3882,9 +4600,9 @@ where
 		let mut channel = {
 			let outbound_scid_alias = self.create_and_insert_outbound_scid_alias();
 			let their_features = &peer_state.latest_features;
-			let config = if override_config.is_some() { override_config.as_ref().unwrap() } else { &self.default_configuration };
+			let config = self.config_for_anchor_negotiation(override_config.as_ref().unwrap_or(&self.default_configuration));
 			match OutboundV1Channel::new(&self.fee_estimator, &self.entropy_source, &self.signer_provider, their_network_key,
-				their_features, channel_value_satoshis, push_msat, user_channel_id, config,
+				their_features, channel_value_satoshis, push_msat, user_channel_id, &config,
 				self.best_block.read().unwrap().height, outbound_scid_alias, temporary_channel_id, &*self.logger)
 			{
 				Ok(res) => res,
@@ -3918,6 +4636,116 @@ where
 		Ok(temporary_channel_id)
 	}
 
+	/// Checks whether a channel with the given parameters could currently be opened with
+	/// [`ChannelManager::create_channel`], returning all [`OpenChannelBlocker`]s found rather than
+	/// only the first.
+	///
+	/// The counterparty's features are taken from their `init` message. If we are not connected to
+	/// them, the remaining checks assume they support everything we do.
+	///
+	/// If `channel_type` is provided, it is compared against the channel type we would propose to
+	/// the counterparty. Otherwise, it is checked whether the counterparty supports anchor outputs
+	/// if our configuration negotiates them. If `anchor_reserve` is provided, e.g. built from the
+	/// UTXOs of our on-chain wallet via [`AnchorChannelReserveCheck::with_num_anchor_channels`], it
+	/// is checked whenever the proposed channel type uses anchor outputs. If
+	/// `funding_feerate_sat_per_1000_weight` is provided, it is checked against the minimum
+	/// feerate returned by our [`FeeEstimator`].
+	///
+	/// No messages are sent to the counterparty. Note that passing these checks does not guarantee
+	/// the counterparty will accept the channel.
+	pub fn preflight_open_channel(
+		&self, counterparty_node_id: PublicKey, channel_value_satoshis: u64, push_msat: u64,
+		channel_type: Option<&ChannelTypeFeatures>, override_config: Option<&UserConfig>,
+		anchor_reserve: Option<&AnchorChannelReserveCheck>,
+		funding_feerate_sat_per_1000_weight: Option<u32>,
+	) -> Result<(), Vec<OpenChannelBlocker>> {
+		let config = &self.config_for_anchor_negotiation(
+			override_config.unwrap_or(&self.default_configuration),
+		);
+		let mut blockers = Vec::new();
+
+		let their_features = self.get_peer_features(&counterparty_node_id).unwrap_or_else(|| {
+			blockers.push(OpenChannelBlocker::PeerNotConnected);
+			provided_init_features(config)
+		});
+
+		let proposed_channel_type = channel::get_initial_channel_type(config, &their_features);
+		let is_anchor_channel = proposed_channel_type.supports_anchors_zero_fee_htlc_tx()
+			|| proposed_channel_type.supports_anchor_zero_fee_commitments();
+		if let Some(requested) = channel_type {
+			if *requested != proposed_channel_type {
+				blockers.push(OpenChannelBlocker::ChannelTypeUnavailable {
+					requested: requested.clone(),
+					proposed: proposed_channel_type.clone(),
+				});
+			}
+		} else if config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx
+			&& !is_anchor_channel
+		{
+			blockers.push(OpenChannelBlocker::PeerLacksAnchorSupport {
+				proposed: proposed_channel_type.clone(),
+			});
+		}
+
+		let min_channel_value_satoshis = 1000;
+		let max_channel_value_satoshis = if their_features.supports_wumbo() {
+			channel::TOTAL_BITCOIN_SUPPLY_SATOSHIS - 1
+		} else {
+			channel::MAX_FUNDING_SATOSHIS_NO_WUMBO
+		};
+		if channel_value_satoshis < min_channel_value_satoshis {
+			blockers.push(OpenChannelBlocker::FundingTooLow { min_channel_value_satoshis });
+		} else if channel_value_satoshis > max_channel_value_satoshis {
+			blockers.push(OpenChannelBlocker::FundingTooHigh { max_channel_value_satoshis });
+		} else if push_msat > channel_value_satoshis * 1000 {
+			let max_push_msat = channel_value_satoshis * 1000;
+			blockers.push(OpenChannelBlocker::PushAmountTooHigh { max_push_msat });
+		} else {
+			let feerate_sat_per_1000_weight = selected_commitment_sat_per_1000_weight(
+				&self.fee_estimator,
+				&proposed_channel_type,
+			);
+			if let Err(commit_tx_fee_sat) = channel::check_outbound_commit_tx_fee_affordable(
+				channel_value_satoshis,
+				push_msat,
+				feerate_sat_per_1000_weight,
+				&proposed_channel_type,
+			) {
+				blockers.push(OpenChannelBlocker::InsufficientFundsForFee {
+					feerate_sat_per_1000_weight,
+					commit_tx_fee_sat,
+				});
+			}
+		}
+
+		if let Some(reserve) = anchor_reserve.filter(|_| is_anchor_channel) {
+			if !reserve.can_support_additional_anchor_channel() {
+				blockers.push(OpenChannelBlocker::InsufficientAnchorReserve {
+					num_anchor_channels: reserve.num_anchor_channels(),
+					num_supportable_channels: reserve.num_supportable_channels(),
+				});
+			}
+		}
+
+		if let Some(funding_feerate_sat_per_1000_weight) = funding_feerate_sat_per_1000_weight {
+			let min_feerate_sat_per_1000_weight = self
+				.fee_estimator
+				.bounded_sat_per_1000_weight(ConfirmationTarget::MinAllowedAnchorChannelRemoteFee);
+			if funding_feerate_sat_per_1000_weight < min_feerate_sat_per_1000_weight {
+				blockers.push(OpenChannelBlocker::FundingFeerateTooLow {
+					funding_feerate_sat_per_1000_weight,
+					min_feerate_sat_per_1000_weight,
+				});
+			}
+		}
+
+		if blockers.is_empty() {
+			Ok(())
+		} else {
+			Err(blockers)
+		}
+	}
+
 	fn list_funded_channels_with_filter<Fn: FnMut(&(&ChannelId, &Channel<SP>)) -> bool + Copy>(
 		&self, f: Fn,
 	) -> Vec<ChannelDetails> {
@@ -3929,6 +4757,8 @@ where
 		let mut res = Vec::with_capacity(self.short_to_chan_info.read().unwrap().len());
 		{
 			let best_block_height = self.best_block.read().unwrap().height;
+			let anchor_reserve_context =
+				self.anchor_channel_reserve_context.lock().unwrap().clone();
 			let per_peer_state = self.per_peer_state.read().unwrap();
 			for (_cp_id, peer_state_mutex) in per_peer_state.iter() {
 				let mut peer_state_lock = peer_state_mutex.lock().unwrap();
@@ -3942,6 +4772,7 @@ where
 						best_block_height,
 						peer_state.latest_features.clone(),
 						&self.fee_estimator,
+						&anchor_reserve_context,
 					)
 				}));
 			}
@@ -3960,6 +4791,8 @@ where
 		let mut res = Vec::with_capacity(self.short_to_chan_info.read().unwrap().len());
 		{
 			let best_block_height = self.best_block.read().unwrap().height;
+			let anchor_reserve_context =
+				self.anchor_channel_reserve_context.lock().unwrap().clone();
 			let per_peer_state = self.per_peer_state.read().unwrap();
 			for (_cp_id, peer_state_mutex) in per_peer_state.iter() {
 				let mut peer_state_lock = peer_state_mutex.lock().unwrap();
@@ -3970,6 +4803,7 @@ where
 						best_block_height,
 						peer_state.latest_features.clone(),
 						&self.fee_estimator,
+						&anchor_reserve_context,
 					);
 					res.push(details);
 				}
@@ -3978,6 +4812,81 @@ where
 		res
 	}
 
+	/// Gets the list of channels whose opening we initiated or accepted but which have not yet been
+	/// funded, in random order.
+	///
+	/// Negotiations which were in progress when the [`ChannelManager`] was last persisted are
+	/// included as well, see [`PendingChannelNegotiation::is_restored`]. While they cannot complete
+	/// anymore, they are retained until the counterparty reconnects or
+	/// [`UserConfig::pending_channel_negotiation_timeout_ticks`] elapse, so that the set of
+	/// negotiations is consistent across restarts.
+	///
+	/// Inbound channel requests which have not been accepted yet are not included, see
+	/// [`Event::OpenChannelRequest`].
+	pub fn list_pending_channel_negotiations(&self) -> Vec<PendingChannelNegotiation> {
+		let mut res = Vec::new();
+		{
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			for (counterparty_node_id, peer_state_mutex) in per_peer_state.iter() {
+				let peer_state = peer_state_mutex.lock().unwrap();
+				let unfunded_channels =
+					peer_state.channel_by_id.values().filter(|chan| !chan.is_funded());
+				res.extend(unfunded_channels.map(|channel| PendingChannelNegotiation {
+					temporary_channel_id: channel.context().channel_id(),
+					counterparty_node_id: *counterparty_node_id,
+					is_outbound: channel.context().is_outbound(),
+					channel_type: channel.funding().get_channel_type().clone(),
+					is_restored: false,
+				}));
+			}
+		}
+		let restored_negotiations = self.restored_channel_negotiations.lock().unwrap();
+		res.extend(restored_negotiations.iter().map(|restored| restored.negotiation.clone()));
+		res
+	}
+
+	/// Gets the IDs of the anchor channels which rely on the anchor channel reserve, in random
+	/// order.
+	///
+	/// Unlike combining [`Self::list_channels`] and [`Self::list_pending_channel_negotiations`],
+	/// each channel is listed exactly once in every state before its funding transaction confirms,
+	/// i.e., while awaiting the counterparty's acceptance, awaiting `funding_signed`, awaiting the
+	/// broadcast of the funding transaction, or awaiting its confirmation. Channels whose type
+	/// hasn't been negotiated yet are listed if we proposed an anchor channel type. Unfunded
+	/// channels are listed by their temporary channel ID.
+	///
+	/// Inbound channel requests which have not been accepted yet are not included, nor are closed
+	/// channels which may still rely on the reserve as their [`ChannelMonitor`] may require fees
+	/// to resolve on-chain.
+	pub fn list_channels_requiring_anchor_reserves(&self) -> Vec<ChannelId> {
+		let mut res = Vec::new();
+		{
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			for (_cp_id, peer_state_mutex) in per_peer_state.iter() {
+				let peer_state = peer_state_mutex.lock().unwrap();
+				let anchor_channels = peer_state
+					.channel_by_id
+					.values()
+					.filter(|channel| is_anchor_channel_type(channel.funding().get_channel_type()));
+				res.extend(anchor_channels.map(|channel| channel.context().channel_id()));
+			}
+		}
+		let restored_negotiations = self.restored_channel_negotiations.lock().unwrap();
+		res.extend(
+			restored_negotiations
+				.iter()
+				.filter(|restored| is_anchor_channel_type(&restored.negotiation.channel_type))
+				.map(|restored| restored.negotiation.temporary_channel_id),
+		);
+		res
+	}
+
+	/// Returns the number of anchor channels which rely on the anchor channel reserve. See
+	/// [`Self::list_channels_requiring_anchor_reserves`] for details.
+	pub fn count_channels_requiring_anchor_reserves(&self) -> u64 {
+		self.list_channels_requiring_anchor_reserves().len() as u64
+	}
+
 	/// Gets the list of usable channels, in random order. Useful as an argument to
 	/// [`Router::find_route`] to ensure non-announced channels are used.
 	///
@@ -3996,6 +4905,7 @@ where
 		&self, counterparty_node_id: &PublicKey,
 	) -> Vec<ChannelDetails> {
 		let best_block_height = self.best_block.read().unwrap().height;
+		let anchor_reserve_context = self.anchor_channel_reserve_context.lock().unwrap().clone();
 		let per_peer_state = self.per_peer_state.read().unwrap();
 
 		if let Some(peer_state_mutex) = per_peer_state.get(counterparty_node_id) {
@@ -4008,6 +4918,7 @@ where
 					best_block_height,
 					features.clone(),
 					&self.fee_estimator,
+					&anchor_reserve_context,
 				)
 			};
 			let chan_by_id = peer_state.channel_by_id.iter();
@@ -4016,27 +4927,621 @@ where
 		vec![]
 	}
 
-	/// Returns in an undefined order recent payments that -- if not fulfilled -- have yet to find a
-	/// successful path, or have unresolved HTLCs.
+	/// Returns the [`InitFeatures`] from the most recent `init` message of the peer with the given
+	/// `counterparty_node_id`, or `None` if we are not currently connected to them.
+	pub fn get_peer_features(&self, counterparty_node_id: &PublicKey) -> Option<InitFeatures> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		per_peer_state.get(counterparty_node_id).and_then(|peer_state_mutex| {
+			let peer_state = peer_state_mutex.lock().unwrap();
+			peer_state.is_connected.then(|| peer_state.latest_features.clone())
+		})
+	}
+
+	/// Returns whether the peer with the given `counterparty_node_id` supports any anchor channel
+	/// type, i.e., `option_anchors_zero_fee_htlc_tx` or `option_zero_fee_commitments`, or `None` if
+	/// we are not currently connected to them.
 	///
-	/// This can be useful for payments that may have been prepared, but ultimately not sent, as a
-	/// result of a crash. If such a payment exists, is not listed here, and an
-	/// [`Event::PaymentSent`] has not been received, you may consider resending the payment.
+	/// This may be used to determine whether a channel opened to the peer will rely on an anchor
+	/// channel reserve before checking it, see [`AnchorChannelReserveCheck`]. Note that whether an
+	/// anchor channel is proposed also depends on our own configuration.
+	pub fn peer_supports_anchor_channels(&self, counterparty_node_id: &PublicKey) -> Option<bool> {
+		self.get_peer_features(counterparty_node_id).map(|features| {
+			features.supports_anchors_zero_fee_htlc_tx()
+				|| features.supports_anchor_zero_fee_commitments()
+		})
+	}
+
+	/// Gets the funding outputs of our channels whose funding transaction is known but has not yet
+	/// confirmed, including zero-conf channels which are already usable.
 	///
-	/// [`Event::PaymentSent`]: events::Event::PaymentSent
-	#[rustfmt::skip]
-	pub fn list_recent_payments(&self) -> Vec<RecentPaymentDetails> {
-		self.pending_outbound_payments.pending_outbound_payments.lock().unwrap().iter()
-			.filter_map(|(payment_id, pending_outbound_payment)| match pending_outbound_payment {
-				PendingOutboundPayment::AwaitingInvoice { .. }
-					| PendingOutboundPayment::AwaitingOffer { .. }
-					// InvoiceReceived is an intermediate state and doesn't need to be exposed
-					| PendingOutboundPayment::InvoiceReceived { .. } =>
-				{
-					Some(RecentPaymentDetails::AwaitingInvoice { payment_id: *payment_id })
-				},
-				PendingOutboundPayment::StaticInvoiceReceived { .. } => {
-					Some(RecentPaymentDetails::AwaitingInvoice { payment_id: *payment_id })
+	/// These are registered with the [`chain::Filter`] set via [`Self::set_chain_filter`] as soon
+	/// as they are known, and may be used to register them again, e.g., after the block source
+	/// reconnects. Channels which are closed or abandoned before their funding transaction confirms
+	/// are no longer returned. Note that [`chain::Filter`] does not support unregistering outputs.
+	pub fn list_pending_funding_outputs(&self) -> Vec<WatchedOutput> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let mut res = Vec::new();
+		for (_, peer_state_mutex) in per_peer_state.iter() {
+			let peer_state = peer_state_mutex.lock().unwrap();
+			for (_, channel) in peer_state.channel_by_id.iter() {
+				res.extend(Self::pending_funding_output(channel.funding()));
+			}
+		}
+		res
+	}
+
+	/// Produces a [`ChannelReceipt`], i.e., a statement signed by our node key that we have a channel
+	/// with the given `channel_id` and `counterparty_node_id`, including its funding outpoint and
+	/// capacity, as of the current time.
+	///
+	/// Unlike a channel announcement, the receipt is not gossiped and may be produced for
+	/// unannounced channels, e.g., to prove their existence to an auditor, who can check it via
+	/// [`ChannelReceipt::verify`].
+	pub fn sign_channel_receipt(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+	) -> Result<ChannelReceipt, APIError> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+			let err = format!(
+				"Can't find a peer matching the passed counterparty node_id {}",
+				counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		let peer_state = peer_state_mutex.lock().unwrap();
+		let chan = peer_state.channel_by_id.get(channel_id).and_then(Channel::as_funded);
+		let chan = chan.ok_or_else(|| {
+			let err = format!(
+				"Funded channel with id {} not found for the passed counterparty node_id {}",
+				channel_id, counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		let funding_outpoint =
+			chan.funding.get_funding_txo().expect("Funded channels have a funding outpoint");
+		let (funding_pubkey, counterparty_funding_pubkey) = chan.funding.get_funding_pubkeys();
+		let contents = UnsignedChannelReceipt {
+			node_id: self.get_our_node_id(),
+			counterparty_node_id: *counterparty_node_id,
+			channel_id: *channel_id,
+			funding_outpoint,
+			channel_value_satoshis: chan.funding.get_value_satoshis(),
+			timestamp: self.duration_since_epoch().as_secs(),
+			funding_pubkey,
+			counterparty_funding_pubkey,
+		};
+		ChannelReceipt::sign(contents, &*self.node_signer).map_err(|()| APIError::APIMisuseError {
+			err: "Failed to sign the channel receipt with the node key".to_owned(),
+		})
+	}
+
+	/// Projects our exposure to dust HTLCs in the channel with the given `channel_id` and
+	/// `counterparty_node_id` if its feerate were `feerate_sat_per_1000_weight`.
+	///
+	/// This allows checking how many of the currently pending HTLCs would become dust, and thus
+	/// be lost to fees if the channel were force-closed, after a feerate increase, e.g., to pick
+	/// a value for [`ChannelConfig::dust_classification_feerate_multiplier_percent`].
+	pub fn project_dust_exposure(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		feerate_sat_per_1000_weight: u32,
+	) -> Result<DustExposureProjection, APIError> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+			let err = format!(
+				"Can't find a peer matching the passed counterparty node_id {}",
+				counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		let peer_state = peer_state_mutex.lock().unwrap();
+		let chan = peer_state.channel_by_id.get(channel_id).and_then(Channel::as_funded);
+		let chan = chan.ok_or_else(|| {
+			let err = format!(
+				"Funded channel with id {} not found for the passed counterparty node_id {}",
+				channel_id, counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		Ok(chan.project_dust_exposure(feerate_sat_per_1000_weight, &self.fee_estimator))
+	}
+
+	/// Estimates the worst-case on-chain fees we would pay to resolve the channel with the given
+	/// `channel_id` and `counterparty_node_id` if we force-closed it now, given its current
+	/// pending HTLCs.
+	///
+	/// For anchor channels, fees are provided when broadcasting, so the estimate assumes the
+	/// commitment and HTLC transactions are confirmed at `fee_rate` by spending P2WPKH wallet
+	/// inputs, and includes the anchor spend bumping the commitment transaction. For other
+	/// channels, the fees already committed to in the commitment and HTLC transactions are
+	/// reported and `fee_rate` is ignored, see [`ForceCloseCostEstimate::fees_committed`].
+	///
+	/// This can be compared against the fee of a cooperative close to decide whether to wait for
+	/// the counterparty rather than force-closing. See [`estimate_force_close_cost`] to estimate
+	/// the cost for a Taproot wallet.
+	///
+	/// [`estimate_force_close_cost`]: crate::util::anchor_channel_reserves::estimate_force_close_cost
+	pub fn estimate_force_close_cost(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey, fee_rate: FeeRate,
+	) -> Result<ForceCloseCostEstimate, APIError> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+			let err = format!(
+				"Can't find a peer matching the passed counterparty node_id {}",
+				counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		let peer_state = peer_state_mutex.lock().unwrap();
+		let chan = peer_state.channel_by_id.get(channel_id).and_then(Channel::as_funded);
+		let chan = chan.ok_or_else(|| {
+			let err = format!(
+				"Funded channel with id {} not found for the passed counterparty node_id {}",
+				channel_id, counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		Ok(chan.estimate_force_close_cost(fee_rate))
+	}
+
+	/// Estimates the fee and weight of a cooperative closing transaction for the channel with the
+	/// given `channel_id` and `counterparty_node_id` at `target_feerate`, e.g., to show the cost of
+	/// closing the channel before calling [`Self::close_channel_with_feerate_and_script`].
+	///
+	/// The estimate is based on the actual shutdown scripts negotiated so far, including our
+	/// counterparty's upfront shutdown script if any, and omits outputs which would be dust. See
+	/// [`CooperativeCloseFeeEstimate`] for details.
+	///
+	/// Note that the fee eventually agreed upon with our counterparty may differ, see
+	/// [`ChannelConfig::force_close_avoidance_max_fee_satoshis`].
+	pub fn estimate_cooperative_close_fee(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey, target_feerate: FeeRate,
+	) -> Result<CooperativeCloseFeeEstimate, APIError> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+			let err = format!(
+				"Can't find a peer matching the passed counterparty node_id {}",
+				counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		let peer_state = peer_state_mutex.lock().unwrap();
+		let chan = peer_state.channel_by_id.get(channel_id).and_then(Channel::as_funded);
+		let chan = chan.ok_or_else(|| {
+			let err = format!(
+				"Funded channel with id {} not found for the passed counterparty node_id {}",
+				channel_id, counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		Ok(chan.estimate_cooperative_close_fee(target_feerate))
+	}
+
+	/// Lists the number of updates queued in the holding cell of each of our funded channels.
+	///
+	/// This can be used to monitor the backlog of updates which will be sent to our counterparties
+	/// once possible, e.g., after a long disconnection. Channels with an empty holding cell are
+	/// included as well.
+	pub fn list_holding_cell_sizes(&self) -> Vec<HoldingCellSize> {
+		let mut res = Vec::new();
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		for (_, peer_state_mutex) in per_peer_state.iter() {
+			let peer_state = peer_state_mutex.lock().unwrap();
+			for chan in peer_state.channel_by_id.values().filter_map(Channel::as_funded) {
+				res.push(chan.holding_cell_size());
+			}
+		}
+		res
+	}
+
+	/// Gets how much we can currently receive over each of our funded channels and in total.
+	///
+	/// Unlike [`ChannelDetails::inbound_capacity_msat`], this applies the same limits we check
+	/// when accepting inbound HTLCs, i.e., the reserve our counterparty has to keep, commitment
+	/// transaction fees, and our limits on the number and value of inbound HTLCs, reporting which
+	/// constraint limits each channel. This can be used to warn before handing out an invoice we
+	/// will likely be unable to receive, as [`Self::create_bolt11_invoice`] does in its logs.
+	///
+	/// Channels which are not currently usable, e.g., as our counterparty is disconnected, are
+	/// included with a receivable amount of zero.
+	pub fn get_receivable_summary(&self) -> ReceivableSummary {
+		let mut channels = Vec::new();
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		for (_, peer_state_mutex) in per_peer_state.iter() {
+			let peer_state = peer_state_mutex.lock().unwrap();
+			for chan in peer_state.channel_by_id.values().filter_map(Channel::as_funded) {
+				channels.push(chan.get_receivable_amount(&self.fee_estimator));
+			}
+		}
+		let max_single_htlc_msat =
+			channels.iter().map(|channel| channel.max_single_htlc_msat).max().unwrap_or(0);
+		let max_total_msat = channels
+			.iter()
+			.fold(0u64, |total, channel| total.saturating_add(channel.max_total_msat));
+		ReceivableSummary { channels, max_single_htlc_msat, max_total_msat }
+	}
+
+	/// Gets the progress of the interactive funding transaction negotiation for a dual-funded
+	/// channel or a splice with the given `channel_id` and `counterparty_node_id`.
+	///
+	/// Returns `None` if no such channel exists or no negotiation is in progress, including once
+	/// consecutive `tx_complete` messages have been exchanged.
+	pub fn interactive_tx_negotiation_progress(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+	) -> Option<InteractiveTxNegotiationProgress> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state = per_peer_state.get(counterparty_node_id)?.lock().unwrap();
+		peer_state.channel_by_id.get(channel_id)?.interactive_tx_constructor()?.progress()
+	}
+
+	/// Aborts the interactive funding transaction negotiation in progress for `chan`, if any,
+	/// generating an [`Event::DiscardFunding`] for the inputs and outputs we contributed to it.
+	///
+	/// Returns whether a negotiation was in progress.
+	fn abort_interactive_tx_negotiation(&self, chan: &mut Channel<SP>) -> bool {
+		match chan.abort_interactive_tx_negotiation() {
+			Some((inputs, outputs)) => {
+				if !inputs.is_empty() || !outputs.is_empty() {
+					let event = events::Event::DiscardFunding {
+						channel_id: chan.context().channel_id(),
+						funding_info: FundingInfo::Contribution { inputs, outputs },
+					};
+					self.pending_events.lock().unwrap().push_back((event, None));
+				}
+				true
+			},
+			None => false,
+		}
+	}
+
+	/// Gets the recorded history of the balance of the channel with the given `channel_id`, from
+	/// oldest to newest.
+	///
+	/// Samples are only recorded if [`UserConfig::channel_balance_history_limit`] is non-zero. In
+	/// that case, a sample is recorded whenever the channel's balance changes as HTLCs are added or
+	/// resolved, retaining up to the configured number of the most recent samples. The history is
+	/// persisted with the [`ChannelManager`] and dropped once the channel is closed.
+	pub fn get_channel_balance_history(&self, channel_id: &ChannelId) -> Vec<ChannelBalanceSample> {
+		let channel_balance_history = self.channel_balance_history.lock().unwrap();
+		channel_balance_history
+			.get(channel_id)
+			.map(|samples| samples.iter().cloned().collect())
+			.unwrap_or_else(Vec::new)
+	}
+
+	/// Records the current balance of the given channel in [`Self::channel_balance_history`] if it
+	/// changed since the last sample, dropping the oldest sample once the configured limit is
+	/// reached.
+	fn record_channel_balance(&self, chan: &FundedChannel<SP>) {
+		let limit = self.default_configuration.channel_balance_history_limit as usize;
+		if limit == 0 {
+			return;
+		}
+		let balances = chan.get_available_balances(&self.fee_estimator);
+		let mut channel_balance_history = self.channel_balance_history.lock().unwrap();
+		let samples =
+			channel_balance_history.entry(chan.context.channel_id()).or_insert_with(VecDeque::new);
+		let unchanged = samples.back().map_or(false, |sample| {
+			sample.outbound_capacity_msat == balances.outbound_capacity_msat
+				&& sample.inbound_capacity_msat == balances.inbound_capacity_msat
+		});
+		if unchanged {
+			return;
+		}
+		while samples.len() >= limit {
+			samples.pop_front();
+		}
+		samples.push_back(ChannelBalanceSample {
+			timestamp: self.duration_since_epoch(),
+			outbound_capacity_msat: balances.outbound_capacity_msat,
+			inbound_capacity_msat: balances.inbound_capacity_msat,
+		});
+	}
+
+	/// Gets [`InflightHtlcStats`] for each of our funded channels.
+	///
+	/// The high watermarks are updated whenever HTLCs are added to or removed from a channel and are
+	/// persisted with the [`ChannelManager`] until the channel is closed or they are reset via
+	/// [`Self::reset_inflight_htlc_watermarks`].
+	pub fn get_inflight_htlc_stats(&self) -> Vec<InflightHtlcStats> {
+		let mut stats = Vec::new();
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		for (counterparty_node_id, peer_state_mutex) in per_peer_state.iter() {
+			let peer_state_lock = peer_state_mutex.lock().unwrap();
+			let peer_state = &*peer_state_lock;
+			for chan in peer_state.channel_by_id.values().filter_map(Channel::as_funded) {
+				let channel_id = chan.context.channel_id();
+				let (accepted_htlcs, accepted_htlcs_value_msat) = chan.get_accepted_htlc_stats();
+				let watermarks = self.accepted_htlc_watermarks.lock().unwrap();
+				let watermark = watermarks.get(&channel_id).copied().unwrap_or_default();
+				stats.push(InflightHtlcStats {
+					channel_id,
+					counterparty_node_id: *counterparty_node_id,
+					accepted_htlcs,
+					accepted_htlcs_value_msat,
+					max_accepted_htlcs: cmp::max(watermark.max_accepted_htlcs, accepted_htlcs),
+					max_accepted_htlcs_value_msat: cmp::max(
+						watermark.max_accepted_htlcs_value_msat,
+						accepted_htlcs_value_msat,
+					),
+				});
+			}
+		}
+		stats
+	}
+
+	/// Resets the high watermarks returned by [`Self::get_inflight_htlc_stats`] to the HTLCs
+	/// currently accepted in each channel.
+	pub fn reset_inflight_htlc_watermarks(&self) {
+		self.accepted_htlc_watermarks.lock().unwrap().clear();
+	}
+
+	/// Gets [`PendingEventStats`] on the events pending handling.
+	///
+	/// Operators may want to alert once [`PendingEventStats::pending_events`] approaches
+	/// [`UserConfig::pending_events_soft_limit`], as it indicates the event handler is falling
+	/// behind.
+	pub fn get_pending_event_stats(&self) -> PendingEventStats {
+		let pending_events = self.pending_events.lock().unwrap().len();
+		let watermark = self.pending_events_watermark.fetch_max(pending_events, Ordering::AcqRel);
+		PendingEventStats {
+			pending_events,
+			max_pending_events: cmp::max(watermark, pending_events),
+			dropped_events: self.dropped_pending_events.load(Ordering::Acquire),
+		}
+	}
+
+	/// Raises the high watermark of pending events and logs a warning if more than
+	/// [`UserConfig::pending_events_soft_limit`] events are pending, dropping the oldest
+	/// informational events beyond the limit if [`UserConfig::drop_informational_events_on_overflow`]
+	/// is set. Returns whether any events were dropped.
+	///
+	/// As events may not be removed while they are being processed, this must only be called while
+	/// holding `pending_events_processor`.
+	fn enforce_pending_events_limit(&self) -> bool {
+		let mut pending_events = self.pending_events.lock().unwrap();
+		let num_pending_events = pending_events.len();
+		self.pending_events_watermark.fetch_max(num_pending_events, Ordering::AcqRel);
+		let limit = self.default_configuration.pending_events_soft_limit as usize;
+		if limit == 0 || num_pending_events <= limit {
+			return false;
+		}
+		let mut num_dropped_events = 0;
+		if self.default_configuration.drop_informational_events_on_overflow {
+			let mut num_excess_events = num_pending_events - limit;
+			pending_events.retain(|(event, action)| {
+				let droppable = action.is_none() && is_informational_event(event);
+				if num_excess_events > 0 && droppable {
+					num_excess_events -= 1;
+					return false;
+				}
+				true
+			});
+			num_dropped_events = num_pending_events - pending_events.len();
+			self.dropped_pending_events.fetch_add(num_dropped_events, Ordering::AcqRel);
+		}
+		log_warn!(
+			self.logger,
+			"{} events are pending handling, exceeding the limit of {}. Dropped {} informational events.",
+			num_pending_events,
+			limit,
+			num_dropped_events
+		);
+		num_dropped_events > 0
+	}
+
+	/// Raises the high watermarks of the HTLCs accepted in the given channel to the HTLCs currently
+	/// pending in it.
+	fn record_accepted_htlcs(&self, chan: &FundedChannel<SP>) {
+		let (accepted_htlcs, accepted_htlcs_value_msat) = chan.get_accepted_htlc_stats();
+		if accepted_htlcs == 0 {
+			return;
+		}
+		let mut watermarks = self.accepted_htlc_watermarks.lock().unwrap();
+		let watermark = watermarks.entry(chan.context.channel_id()).or_default();
+		watermark.max_accepted_htlcs = cmp::max(watermark.max_accepted_htlcs, accepted_htlcs);
+		watermark.max_accepted_htlcs_value_msat =
+			cmp::max(watermark.max_accepted_htlcs_value_msat, accepted_htlcs_value_msat);
+	}
+
+	/// Generates an [`Event::ChannelLiquidityThresholdCrossed`] if our balance in the given channel
+	/// moved into a different [`events::LiquidityBand`] since it was last checked.
+	fn check_liquidity_thresholds(&self, chan: &mut FundedChannel<SP>) {
+		if let Some((direction, band)) = chan.check_liquidity_band() {
+			let event = Event::ChannelLiquidityThresholdCrossed {
+				channel_id: chan.context.channel_id(),
+				user_channel_id: chan.context.get_user_id(),
+				counterparty_node_id: chan.context.get_counterparty_node_id(),
+				direction,
+				band,
+				local_balance_msat: chan.funding.get_value_to_self_msat(),
+				channel_value_satoshis: chan.funding.get_value_satoshis(),
+			};
+			self.pending_events.lock().unwrap().push_back((event, None));
+		}
+	}
+
+	/// Records the given closing transaction for a channel we're cooperatively closing in
+	/// [`Self::pending_cooperative_closes`], allowing its fee to be bumped later.
+	fn track_cooperative_close(&self, chan: &FundedChannel<SP>, closing_tx: &Transaction) {
+		let funding_txo = match chan.funding.get_funding_txo() {
+			Some(funding_txo) => funding_txo,
+			None => {
+				debug_assert!(false);
+				return;
+			},
+		};
+		let mut pending_cooperative_closes = self.pending_cooperative_closes.lock().unwrap();
+		let close =
+			pending_cooperative_closes.entry(chan.context.channel_id()).or_insert_with(|| {
+				PendingCooperativeClose {
+					counterparty_node_id: chan.context.get_counterparty_node_id(),
+					funding_txo,
+					channel_value_satoshis: chan.funding.get_value_satoshis(),
+					channel_keys_id: chan.context.get_channel_keys_id(),
+					holder_script: chan.get_closing_scriptpubkey(),
+					closing_transactions: Vec::new(),
+					confirmation: None,
+				}
+			});
+		if !close.closing_transactions.contains(closing_tx) {
+			close.closing_transactions.push(closing_tx.clone());
+		}
+	}
+
+	/// Attempts to get the cooperative closing transaction of the channel with the given
+	/// `channel_id` confirmed even though it pays less than `feerate_sat_per_1000_weight`, e.g.
+	/// because feerates spiked after the closing fee was negotiated.
+	///
+	/// Once `closing_signed` negotiation has completed, exactly one closing transaction has been
+	/// signed by both parties and the protocol provides no way to re-negotiate its fee, even if
+	/// the peer is still connected. Replacing our closing transaction unilaterally, as
+	/// `option_simple_close` allows, is not yet supported. Thus, the fee is bumped using
+	/// child-pays-for-parent: the closing transaction is re-broadcast and an
+	/// [`Event::SpendableOutputs`] is generated for our output right away, rather than only once
+	/// the closing transaction has confirmed. Passing it to an [`OutputSweeper`] will have the
+	/// sweeper spend it, bumping the closing transaction along with it. Note that the sweeper
+	/// picks the feerate of its spend via [`ConfirmationTarget::OutputSpendingFee`], which should
+	/// thus be at least `feerate_sat_per_1000_weight` for the bump to be effective.
+	///
+	/// Closing transactions are tracked, and may thus be bumped, until they have been confirmed
+	/// [`ANTI_REORG_DELAY`] blocks deep.
+	///
+	/// Returns an [`APIError::ChannelUnavailable`] if the channel is still open (including while
+	/// the closing fee is being negotiated) or we didn't broadcast a cooperative closing
+	/// transaction for it, and an [`APIError::APIMisuseError`] if the closing transaction has
+	/// already confirmed, already pays at least `feerate_sat_per_1000_weight`, or doesn't pay
+	/// anything to us (leaving us no output to spend).
+	///
+	/// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
+	pub fn bump_closing_transaction_fee(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		feerate_sat_per_1000_weight: u32,
+	) -> Result<(), APIError> {
+		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+
+		{
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			if let Some(peer_state_mutex) = per_peer_state.get(counterparty_node_id) {
+				if peer_state_mutex.lock().unwrap().channel_by_id.contains_key(channel_id) {
+					return Err(APIError::ChannelUnavailable {
+						err: format!(
+							"Channel {} has not completed cooperative close negotiation",
+							channel_id
+						),
+					});
+				}
+			}
+		}
+
+		let pending_cooperative_closes = self.pending_cooperative_closes.lock().unwrap();
+		let close = match pending_cooperative_closes.get(channel_id) {
+			Some(close) if close.counterparty_node_id == *counterparty_node_id => close,
+			_ => {
+				return Err(APIError::ChannelUnavailable {
+					err: format!(
+						"No pending cooperative closing transaction for channel {} with counterparty {}",
+						channel_id, counterparty_node_id
+					),
+				})
+			},
+		};
+		if let Some((txid, height, _)) = close.confirmation {
+			return Err(APIError::APIMisuseError {
+				err: format!(
+					"The funding output of channel {} was already spent by {} at height {}",
+					channel_id, txid, height
+				),
+			});
+		}
+		let closing_tx = match close.closing_transactions.last() {
+			Some(closing_tx) => closing_tx,
+			None => {
+				debug_assert!(false);
+				return Err(APIError::ChannelUnavailable {
+					err: format!("No closing transaction for channel {}", channel_id),
+				});
+			},
+		};
+
+		let output_value: u64 = closing_tx.output.iter().map(|output| output.value.to_sat()).sum();
+		let fee_satoshis = close.channel_value_satoshis.saturating_sub(output_value);
+		let closing_feerate = fee_satoshis * 1000 / closing_tx.weight().to_wu();
+		if closing_feerate >= feerate_sat_per_1000_weight as u64 {
+			return Err(APIError::APIMisuseError {
+				err: format!(
+					"The closing transaction of channel {} already pays {} sat/kW",
+					channel_id, closing_feerate
+				),
+			});
+		}
+
+		let holder_output = closing_tx
+			.output
+			.iter()
+			.enumerate()
+			.find(|(_, output)| output.script_pubkey == close.holder_script);
+		let (idx, output) = match holder_output {
+			Some(holder_output) => holder_output,
+			None => {
+				return Err(APIError::APIMisuseError {
+					err: format!(
+						"The closing transaction of channel {} has no output paying to us to bump its fee with",
+						channel_id
+					),
+				})
+			},
+		};
+
+		let logger =
+			WithContext::from(&self.logger, Some(*counterparty_node_id), Some(*channel_id), None);
+		log_info!(
+			logger,
+			"Bumping closing transaction {} paying {} sat/kW towards {} sat/kW",
+			closing_tx.compute_txid(),
+			closing_feerate,
+			feerate_sat_per_1000_weight
+		);
+		let closing_txs = close.closing_transactions.iter().collect::<Vec<_>>();
+		self.tx_broadcaster.broadcast_transactions(&closing_txs);
+
+		let descriptor = SpendableOutputDescriptor::StaticOutput {
+			outpoint: bitcoin::OutPoint::new(closing_tx.compute_txid(), idx as u32),
+			output: output.clone(),
+			channel_keys_id: Some(close.channel_keys_id),
+		};
+		mem::drop(pending_cooperative_closes);
+
+		let event =
+			Event::SpendableOutputs { outputs: vec![descriptor], channel_id: Some(*channel_id) };
+		self.pending_events.lock().unwrap().push_back((event, None));
+		Ok(())
+	}
+
+	/// Returns in an undefined order recent payments that -- if not fulfilled -- have yet to find a
+	/// successful path, or have unresolved HTLCs.
+	///
+	/// This can be useful for payments that may have been prepared, but ultimately not sent, as a
+	/// result of a crash. If such a payment exists, is not listed here, and an
+	/// [`Event::PaymentSent`] has not been received, you may consider resending the payment.
+	///
+	/// [`Event::PaymentSent`]: events::Event::PaymentSent
+	#[rustfmt::skip]
+	pub fn list_recent_payments(&self) -> Vec<RecentPaymentDetails> {
+		self.pending_outbound_payments.pending_outbound_payments.lock().unwrap().iter()
+			.filter_map(|(payment_id, pending_outbound_payment)| match pending_outbound_payment {
+				PendingOutboundPayment::AwaitingInvoice { .. }
+					| PendingOutboundPayment::AwaitingOffer { .. }
+					// InvoiceReceived is an intermediate state and doesn't need to be exposed
+					| PendingOutboundPayment::InvoiceReceived { .. } =>
+				{
+					Some(RecentPaymentDetails::AwaitingInvoice { payment_id: *payment_id })
+				},
+				PendingOutboundPayment::StaticInvoiceReceived { payment_hash, expiry_time, .. } => {
+					Some(RecentPaymentDetails::AwaitingRelease {
+						payment_id: *payment_id,
+						payment_hash: *payment_hash,
+						expiry_time: *expiry_time,
+					})
 				},
 				PendingOutboundPayment::Retryable { payment_hash, total_msat, .. } => {
 					Some(RecentPaymentDetails::Pending {
@@ -4056,8 +5561,59 @@ where
 			.collect()
 	}
 
+	/// Checks that cooperatively closing the channel with the given ID and value is authorized as
+	/// required by the [`CloseAuthorizer`] set via [`Self::set_close_authorizer`], if any.
+	///
+	/// If authorization is required, the nonce of the given `authorization` is used up, such that
+	/// it can't be replayed even if initiating the close fails afterwards.
+	fn check_close_authorization(
+		&self, channel_id: &ChannelId, channel_value_satoshis: u64,
+		authorization: Option<(&CloseAuthorization, &str)>,
+	) -> Result<(), APIError> {
+		let authorizer = match *self.close_authorizer.lock().unwrap() {
+			Some(authorizer) if channel_value_satoshis > authorizer.threshold_satoshis => {
+				authorizer
+			},
+			_ => return Ok(()),
+		};
+		let (authorization, signature) =
+			authorization.ok_or_else(|| APIError::AuthorizationRequired {
+				err: format!(
+					"Closing channel {} with a value of {} sat requires authorization",
+					channel_id, channel_value_satoshis
+				),
+			})?;
+		if authorization.channel_id != *channel_id {
+			return Err(APIError::AuthorizationRequired {
+				err: format!(
+					"Authorization for channel {} can't close channel {}",
+					authorization.channel_id, channel_id
+				),
+			});
+		}
+		if !verify_close_authorization(authorization, signature, &authorizer.authorizer_pubkey) {
+			return Err(APIError::AuthorizationRequired {
+				err: format!(
+					"Authorization for channel {} was not signed by the authorizer",
+					channel_id
+				),
+			});
+		}
+		let mut last_nonce = self.last_close_authorization_nonce.lock().unwrap();
+		if authorization.nonce <= *last_nonce {
+			return Err(APIError::AuthorizationRequired {
+				err: format!(
+					"Authorization nonce {} must be greater than the last used nonce {}",
+					authorization.nonce, *last_nonce
+				),
+			});
+		}
+		*last_nonce = authorization.nonce;
+		Ok(())
+	}
+
 	#[rustfmt::skip]
-	fn close_channel_internal(&self, chan_id: &ChannelId, counterparty_node_id: &PublicKey, target_feerate_sats_per_1000_weight: Option<u32>, override_shutdown_script: Option<ShutdownScript>) -> Result<(), APIError> {
+	fn close_channel_internal(&self, chan_id: &ChannelId, counterparty_node_id: &PublicKey, target_feerate_sats_per_1000_weight: Option<u32>, override_shutdown_script: Option<ShutdownScript>, max_closing_fee_satoshis: Option<u64>, authorization: Option<(&CloseAuthorization, &str)>) -> Result<(), APIError> {
 		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
 
 		let mut failed_htlcs: Vec<(HTLCSource, PaymentHash)> = Vec::new();
@@ -4075,11 +5631,15 @@ where
 			match peer_state.channel_by_id.entry(*chan_id) {
 				hash_map::Entry::Occupied(mut chan_entry) => {
 					if let Some(chan) = chan_entry.get_mut().as_funded_mut() {
+						self.check_close_authorization(chan_id, chan.funding.get_value_satoshis(), authorization)?;
 						let funding_txo_opt = chan.funding.get_funding_txo();
 						let their_features = &peer_state.latest_features;
 						let (shutdown_msg, mut monitor_update_opt, htlcs) =
 							chan.get_shutdown(&self.signer_provider, their_features, target_feerate_sats_per_1000_weight, override_shutdown_script)?;
 						failed_htlcs = htlcs;
+						if let Some(max_fee_satoshis) = max_closing_fee_satoshis {
+							chan.set_max_closing_fee_satoshis(max_fee_satoshis);
+						}
 
 						// We can send the `shutdown` message before updating the `ChannelMonitor`
 						// here as we don't need the monitor update to complete until we send a
@@ -4148,6 +5708,10 @@ where
 	/// [`SignerProvider::get_shutdown_scriptpubkey`]. A force-closure may be needed to close the
 	/// channel.
 	///
+	/// Raises [`APIError::AuthorizationRequired`] if the channel's value exceeds the threshold set
+	/// via [`Self::set_close_authorizer`], in which case it must be closed via
+	/// [`Self::close_channel_with_authorization`].
+	///
 	/// [`ChannelConfig::force_close_avoidance_max_fee_satoshis`]: crate::util::config::ChannelConfig::force_close_avoidance_max_fee_satoshis
 	/// [`ChannelCloseMinimum`]: crate::chain::chaininterface::ConfirmationTarget::ChannelCloseMinimum
 	/// [`NonAnchorChannelFee`]: crate::chain::chaininterface::ConfirmationTarget::NonAnchorChannelFee
@@ -4155,7 +5719,7 @@ where
 	pub fn close_channel(
 		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
 	) -> Result<(), APIError> {
-		self.close_channel_internal(channel_id, counterparty_node_id, None, None)
+		self.close_channel_internal(channel_id, counterparty_node_id, None, None, None, None)
 	}
 
 	/// Begins the process of closing a channel. After this call (plus some timeout), no new HTLCs
@@ -4184,6 +5748,10 @@ where
 	/// [`SignerProvider::get_shutdown_scriptpubkey`]. A force-closure may be needed to close the
 	/// channel.
 	///
+	/// Raises [`APIError::AuthorizationRequired`] if the channel's value exceeds the threshold set
+	/// via [`Self::set_close_authorizer`], in which case it must be closed via
+	/// [`Self::close_channel_with_authorization`].
+	///
 	/// [`ChannelConfig::force_close_avoidance_max_fee_satoshis`]: crate::util::config::ChannelConfig::force_close_avoidance_max_fee_satoshis
 	/// [`NonAnchorChannelFee`]: crate::chain::chaininterface::ConfirmationTarget::NonAnchorChannelFee
 	/// [`SendShutdown`]: MessageSendEvent::SendShutdown
@@ -4196,9 +5764,197 @@ where
 			counterparty_node_id,
 			target_feerate_sats_per_1000_weight,
 			shutdown_script,
+			None,
+			None,
+		)
+	}
+
+	/// Begins the process of closing a channel as [`Self::close_channel_with_feerate_and_script`]
+	/// does, providing the `authorization` required to close channels whose value exceeds the
+	/// threshold set via [`Self::set_close_authorizer`], along with its zbase32-encoded
+	/// `signature` by the authorizer, as created via [`sign_close_authorization`].
+	///
+	/// We will not pay more than the authorization's [`CloseAuthorization::max_fee_satoshis`] on
+	/// the closing transaction if we initiated the channel, overriding
+	/// [`ChannelConfig::force_close_avoidance_max_fee_satoshis`] as well as the target feerate. If
+	/// our counterparty insists on a higher fee, the channel will be force-closed.
+	///
+	/// Returns [`APIError::AuthorizationRequired`] if authorization is required but the
+	/// authorization is for a different channel, is not signed by the authorizer, or its nonce is
+	/// not greater than that of any authorization used before. Once a valid authorization is
+	/// provided, its nonce is used up, even if the channel then fails to close.
+	///
+	/// [`ChannelConfig::force_close_avoidance_max_fee_satoshis`]: crate::util::config::ChannelConfig::force_close_avoidance_max_fee_satoshis
+	/// [`sign_close_authorization`]: crate::util::message_signing::sign_close_authorization
+	pub fn close_channel_with_authorization(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		target_feerate_sats_per_1000_weight: Option<u32>, shutdown_script: Option<ShutdownScript>,
+		authorization: &CloseAuthorization, signature: &str,
+	) -> Result<(), APIError> {
+		self.close_channel_internal(
+			channel_id,
+			counterparty_node_id,
+			target_feerate_sats_per_1000_weight,
+			shutdown_script,
+			Some(authorization.max_fee_satoshis),
+			Some((authorization, signature)),
 		)
 	}
 
+	/// Begins the process of cooperatively closing all channels with the given counterparty, or
+	/// only those in `channel_ids` if set, tracking their progress as a batch.
+	///
+	/// Shutdown is initiated on each channel as by [`Self::close_channel_with_feerate_and_script`]
+	/// with the given `target_feerate_sats_per_1000_weight`. If `max_fee_per_channel_satoshis` is
+	/// set, we will not pay more than it on the closing transaction of any channel we initiated,
+	/// overriding [`ChannelConfig::force_close_avoidance_max_fee_satoshis`] as well as the target
+	/// feerate. If our counterparty insists on a higher fee, the channel will be force-closed.
+	///
+	/// Once all channels in the batch have closed, an [`Event::BatchedChannelCloseCompleted`] is
+	/// generated with the outcome for each of them, in addition to the usual
+	/// [`Event::ChannelClosed`] events. Channels which have not closed after around an hour worth of
+	/// [`Self::expire_stale_channels`] calls are force-closed, broadcasting our latest commitment
+	/// transaction, unless they still have HTLCs pending. Those are reported as
+	/// [`BatchedChannelCloseOutcome::Pending`] once the batch times out and continue to close
+	/// cooperatively. This includes channels on which shutdown could not be initiated, e.g., as
+	/// our counterparty is disconnected.
+	///
+	/// All channels are checked to exist before initiating shutdown on any of them. Raises
+	/// [`APIError::ChannelUnavailable`] if any of the `channel_ids` is not found or if there are no
+	/// channels to close with the counterparty, and [`APIError::AuthorizationRequired`] if any of
+	/// them requires authorization, see [`Self::set_close_authorizer`].
+	///
+	/// The progress of the batch is not persisted. If we restart before it completes, no
+	/// [`Event::BatchedChannelCloseCompleted`] will be generated for it.
+	///
+	/// [`ChannelConfig::force_close_avoidance_max_fee_satoshis`]: crate::util::config::ChannelConfig::force_close_avoidance_max_fee_satoshis
+	pub fn close_channels_with_peer(
+		&self, counterparty_node_id: &PublicKey, channel_ids: Option<Vec<ChannelId>>,
+		target_feerate_sats_per_1000_weight: Option<u32>,
+		max_fee_per_channel_satoshis: Option<u64>,
+	) -> Result<(), APIError> {
+		let channel_ids = {
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+				APIError::ChannelUnavailable {
+					err: format!(
+						"Can't find a peer matching the passed counterparty node_id {}",
+						counterparty_node_id
+					),
+				}
+			})?;
+			let peer_state = peer_state_mutex.lock().unwrap();
+			let channel_ids: Vec<ChannelId> = match channel_ids {
+				Some(mut channel_ids) => {
+					let mut seen_channel_ids = new_hash_set();
+					channel_ids.retain(|channel_id| seen_channel_ids.insert(*channel_id));
+					let unknown_channel_id = channel_ids
+						.iter()
+						.find(|channel_id| !peer_state.channel_by_id.contains_key(channel_id));
+					if let Some(channel_id) = unknown_channel_id {
+						return Err(APIError::ChannelUnavailable {
+							err: format!(
+								"Channel with id {} not found for the passed counterparty node_id {}",
+								channel_id, counterparty_node_id,
+							),
+						});
+					}
+					channel_ids
+				},
+				None => peer_state.channel_by_id.keys().copied().collect(),
+			};
+			if channel_ids.is_empty() {
+				return Err(APIError::ChannelUnavailable {
+					err: format!("No channels to close with counterparty {}", counterparty_node_id),
+				});
+			}
+			// Channels requiring authorization would fail to close and then be force-closed once
+			// the batch times out, so none are closed.
+			if let Some(authorizer) = *self.close_authorizer.lock().unwrap() {
+				let requires_authorization = channel_ids.iter().find(|channel_id| {
+					peer_state
+						.channel_by_id
+						.get(channel_id)
+						.and_then(|chan| chan.as_funded())
+						.map_or(false, |chan| {
+							chan.funding.get_value_satoshis() > authorizer.threshold_satoshis
+						})
+				});
+				if let Some(channel_id) = requires_authorization {
+					return Err(APIError::AuthorizationRequired {
+						err: format!(
+							"Closing channel {} requires authorization and can't be batched",
+							channel_id
+						),
+					});
+				}
+			}
+
+			// Register the batch while holding the peer's lock, as unfunded channels are closed
+			// immediately below and no channel may close before then.
+			self.batched_close_states.lock().unwrap().push(BatchedCloseState {
+				counterparty_node_id: *counterparty_node_id,
+				channels: channel_ids.iter().map(|channel_id| (*channel_id, None)).collect(),
+				ticks_remaining: BATCHED_CLOSE_TIMEOUT_TICKS,
+			});
+			channel_ids
+		};
+
+		for channel_id in channel_ids.iter() {
+			let res = self.close_channel_internal(
+				channel_id,
+				counterparty_node_id,
+				target_feerate_sats_per_1000_weight,
+				None,
+				max_fee_per_channel_satoshis,
+				None,
+			);
+			if let Err(e) = res {
+				let logger = WithContext::from(
+					&self.logger,
+					Some(*counterparty_node_id),
+					Some(*channel_id),
+					None,
+				);
+				log_info!(
+					logger,
+					"Failed to initiate shutdown of channel {} in batch: {:?}",
+					channel_id,
+					e
+				);
+			}
+		}
+		Ok(())
+	}
+
+	/// Records the `outcome` of the closure of the given channel in any batch initiated via
+	/// [`Self::close_channels_with_peer`] it is part of, returning the events for the batches that
+	/// have thereby been resolved.
+	fn resolve_batched_close(
+		&self, counterparty_node_id: &PublicKey, channel_id: &ChannelId,
+		outcome: BatchedChannelCloseOutcome,
+	) -> Vec<Event> {
+		let mut batched_close_states = self.batched_close_states.lock().unwrap();
+		let mut events = Vec::new();
+		batched_close_states.retain_mut(|batch| {
+			if batch.counterparty_node_id != *counterparty_node_id {
+				return true;
+			}
+			for (batch_channel_id, batch_outcome) in batch.channels.iter_mut() {
+				if batch_channel_id == channel_id && batch_outcome.is_none() {
+					*batch_outcome = Some(outcome);
+				}
+			}
+			if batch.is_resolved() {
+				events.push(batch.completed_event());
+				false
+			} else {
+				true
+			}
+		});
+		events
+	}
+
 	/// Applies a [`ChannelMonitorUpdate`] which may or may not be for a channel which is closed.
 	#[rustfmt::skip]
 	fn apply_post_close_monitor_update(
@@ -4250,6 +6006,8 @@ where
 
 		log_debug!(logger, "Finishing closure of channel due to {} with {} HTLCs to fail",
 			shutdown_res.closure_reason, shutdown_res.dropped_outbound_htlcs.len());
+		self.channel_balance_history.lock().unwrap().remove(&shutdown_res.channel_id);
+		self.accepted_htlc_watermarks.lock().unwrap().remove(&shutdown_res.channel_id);
 		for htlc_source in shutdown_res.dropped_outbound_htlcs.drain(..) {
 			let (source, payment_hash, counterparty_node_id, channel_id) = htlc_source;
 			let failure_reason = LocalHTLCFailureReason::ChannelClosed;
@@ -4295,6 +6053,18 @@ where
 			);
 		}
 
+		let batched_close_outcome = match shutdown_res.closure_reason {
+			ClosureReason::LegacyCooperativeClosure
+			| ClosureReason::CounterpartyInitiatedCooperativeClosure
+			| ClosureReason::LocallyInitiatedCooperativeClosure
+			| ClosureReason::CounterpartyCoopClosedUnfundedChannel
+			| ClosureReason::LocallyCoopClosedUnfundedChannel => BatchedChannelCloseOutcome::CooperativelyClosed,
+			_ => BatchedChannelCloseOutcome::ForceClosed,
+		};
+		let batched_close_events = self.resolve_batched_close(
+			&shutdown_res.counterparty_node_id, &shutdown_res.channel_id, batched_close_outcome
+		);
+
 		{
 			let mut pending_events = self.pending_events.lock().unwrap();
 			pending_events.push_back((events::Event::ChannelClosed {
@@ -4320,6 +6090,9 @@ where
 					channel_id: shutdown_res.channel_id, funding_info
 				}, None));
 			}
+			for event in batched_close_events {
+				pending_events.push_back((event, None));
+			}
 		}
 		for (err, counterparty_node_id) in shutdown_results.drain(..) {
 			let _ = handle_error!(self, err, counterparty_node_id);
@@ -4360,11 +6133,12 @@ where
 		} else if peer_state.inbound_channel_request_by_id.remove(channel_id).is_some() {
 			log_error!(logger, "Force-closing inbound channel request {}", &channel_id);
 			if !is_from_counterparty {
+				let data = sanitize_channel_rejection_reason(&message);
 				peer_state.pending_msg_events.push(
 					MessageSendEvent::HandleError {
 						node_id: *peer_node_id,
 						action: msgs::ErrorAction::SendErrorMessage {
-							msg: msgs::ErrorMessage { channel_id: *channel_id, data: message }
+							msg: msgs::ErrorMessage { channel_id: *channel_id, data }
 						},
 					}
 				);
@@ -4399,6 +6173,9 @@ where
 	/// The provided `error_message` is sent to connected peers for closing
 	/// channels and should be a human-readable description of what went wrong.
 	///
+	/// This may also be used to decline a pending [`Event::OpenChannelRequest`], in which case
+	/// `error_message` is sanitized as for [`InboundChannelDecision::Reject`].
+	///
 	/// Fails if `channel_id` is unknown to the manager, or if the `counterparty_node_id`
 	/// isn't the counterparty of the corresponding channel.
 	pub fn force_close_broadcasting_latest_txn(
@@ -4422,6 +6199,57 @@ where
 		}
 	}
 
+	/// Acknowledges that our state for a channel is stale, as reported by
+	/// [`Event::LocalDataLossDetected`], and proceeds with recovering the channel's funds.
+	///
+	/// The channel is force-closed without broadcasting our (revoked) commitment transaction and
+	/// our counterparty is asked to broadcast its latest commitment transaction instead, either
+	/// immediately or upon reconnection. Once it confirms, our funds are made available via
+	/// [`Event::SpendableOutputs`].
+	///
+	/// Only call this after ensuring no more recent backup of the channel's state is available.
+	///
+	/// Fails if `channel_id` is unknown to the manager, if the `counterparty_node_id` isn't the
+	/// counterparty of the corresponding channel, or if no data loss was detected for the channel.
+	pub fn acknowledge_data_loss(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+	) -> Result<(), APIError> {
+		{
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+				APIError::ChannelUnavailable {
+					err: format!(
+						"Can't find a peer matching the passed counterparty node_id {}",
+						counterparty_node_id
+					),
+				}
+			})?;
+			let peer_state = peer_state_mutex.lock().unwrap();
+			let has_local_data_loss = peer_state
+				.channel_by_id
+				.get(channel_id)
+				.ok_or_else(|| APIError::ChannelUnavailable {
+					err: format!(
+						"Channel with id {} not found for the passed counterparty node_id {}",
+						channel_id, counterparty_node_id
+					),
+				})?
+				.context()
+				.local_data_loss()
+				.is_some();
+			if !has_local_data_loss {
+				return Err(APIError::APIMisuseError {
+					err: format!("No data loss was detected for channel {}", channel_id),
+				});
+			}
+		}
+		self.force_close_sending_error(
+			channel_id,
+			counterparty_node_id,
+			"Local data loss acknowledged".to_owned(),
+		)
+	}
+
 	/// Initiate a splice, to change the channel capacity of an existing funded channel.
 	/// After completion of splicing, the funding transaction will be replaced by a new one, spending the old funding transaction,
 	/// with optional extra inputs (splice-in) and/or extra outputs (splice-out or change).
@@ -4579,6 +6407,11 @@ where
 	fn can_forward_htlc(
 		&self, msg: &msgs::UpdateAddHTLC, next_packet_details: &NextPacketDetails
 	) -> Result<(), LocalHTLCFailureReason> {
+		if self.default_configuration.fail_htlc_forwards_while_chain_sync_stale && self.chain_sync_stale.load(Ordering::Acquire) {
+			// Our view of the chain is stale, so we may not be able to act on the HTLC's timeout in
+			// time.
+			return Err(LocalHTLCFailureReason::TemporaryNodeFailure);
+		}
 		let outgoing_scid = match next_packet_details.outgoing_connector {
 			HopConnector::ShortChannelId(scid) => scid,
 			HopConnector::Trampoline(_) => {
@@ -4767,7 +6600,12 @@ where
 	fn get_channel_update_for_unicast(&self, chan: &FundedChannel<SP>) -> Result<msgs::ChannelUpdate, LightningError> {
 		let logger = WithChannelContext::from(&self.logger, &chan.context, None);
 		log_trace!(logger, "Attempting to generate channel update for channel {}", chan.context.channel_id());
-		let short_channel_id = match chan.funding.get_short_channel_id().or(chan.context.latest_inbound_scid_alias()) {
+		// For unannounced channels, prefer the alias our counterparty knows the channel by over the
+		// real SCID, which would link the channel to its funding transaction.
+		let real_scid = chan.funding.get_short_channel_id().filter(|_| {
+			chan.context.may_reveal_channel_identifiers() || chan.context.latest_inbound_scid_alias().is_none()
+		});
+		let short_channel_id = match real_scid.or(chan.context.latest_inbound_scid_alias()) {
 			None => return Err(LightningError{err: "Channel not yet established".to_owned(), action: msgs::ErrorAction::IgnoreError}),
 			Some(id) => id,
 		};
@@ -5031,6 +6869,11 @@ where
 	/// particular payment, use [`Self::send_payment_with_route`] or match the [`PaymentId`] passed to
 	/// [`Router::find_route_with_id`].
 	///
+	/// Unless [`UserConfig::fail_fast_infeasible_payments`] is disabled, payments which cannot
+	/// possibly succeed fail with [`RetryableSendFailure::PaymentInfeasible`] before attempting to
+	/// find a route, see [`Self::estimate_payment_feasibility`]. In that case, the [`PaymentId`] is
+	/// not marked as pending and may be reused.
+	///
 	/// [`Event::PaymentSent`]: events::Event::PaymentSent
 	/// [`Event::PaymentFailed`]: events::Event::PaymentFailed
 	/// [`UpdateHTLCs`]: MessageSendEvent::UpdateHTLCs
@@ -5042,6 +6885,25 @@ where
 	) -> Result<(), RetryableSendFailure> {
 		let best_block_height = self.best_block.read().unwrap().height;
 		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+		let first_hops = self.list_usable_channels();
+		if self.default_configuration.fail_fast_infeasible_payments {
+			check_payment_feasibility(
+				&route_params,
+				&first_hops,
+				&self.router,
+				self.duration_since_epoch(),
+			)
+			.map_err(|reason| {
+				log_error!(
+					self.logger,
+					"Payment with id {} and hash {} is infeasible: {:?}",
+					payment_id,
+					payment_hash,
+					reason
+				);
+				RetryableSendFailure::PaymentInfeasible(reason)
+			})?;
+		}
 		self.pending_outbound_payments.send_payment(
 			payment_hash,
 			recipient_onion,
@@ -5049,7 +6911,7 @@ where
 			retry_strategy,
 			route_params,
 			&self.router,
-			self.list_usable_channels(),
+			first_hops,
 			|| self.compute_inflight_htlcs(),
 			&self.entropy_source,
 			&self.node_signer,
@@ -5060,6 +6922,32 @@ where
 		)
 	}
 
+	/// Checks whether a payment with the given [`RouteParameters`] can possibly succeed, without
+	/// attempting to find a route.
+	///
+	/// This is a cheap check of:
+	/// - the [`PaymentParameters::expiry_time`], if any,
+	/// - whether the total outbound capacity of our usable channels covers
+	///   [`RouteParameters::final_value_msat`] (routing fees are not included, as they may be
+	///   anywhere up to [`RouteParameters::max_total_routing_fee_msat`]),
+	/// - whether the destination is reachable, i.e., it is one of our peers, route hints or blinded
+	///   paths were provided, or the [`Router`] knows of any of its channels.
+	///
+	/// Passing these checks does not guarantee a route will be found. Performed by
+	/// [`Self::send_payment`] unless [`UserConfig::fail_fast_infeasible_payments`] is disabled.
+	///
+	/// [`PaymentParameters::expiry_time`]: crate::routing::router::PaymentParameters::expiry_time
+	pub fn estimate_payment_feasibility(
+		&self, route_params: &RouteParameters,
+	) -> Result<(), InfeasiblePaymentReason> {
+		check_payment_feasibility(
+			route_params,
+			&self.list_usable_channels(),
+			&self.router,
+			self.duration_since_epoch(),
+		)
+	}
+
 	#[cfg(any(test, feature = "_externalize_tests"))]
 	pub(super) fn test_send_payment_internal(
 		&self, route: &Route, payment_hash: PaymentHash, recipient_onion: RecipientOnionFields,
@@ -5287,6 +7175,7 @@ where
 				features,
 				best_block_height,
 				self.duration_since_epoch(),
+				Duration::from_secs(self.default_configuration.async_payment_hold_timeout_secs),
 				&*self.entropy_source,
 				&self.pending_events,
 			);
@@ -5659,6 +7548,7 @@ where
 				if is_manual_broadcast {
 					chan.context.set_manual_broadcast();
 				}
+				self.register_funding_output_with_filter(&chan.funding);
 
 				e.insert(Channel::from(chan));
 				Ok(())
@@ -5720,6 +7610,228 @@ where
 		self.batch_funding_transaction_generated(temporary_chan, funding_transaction)
 	}
 
+	/// Call this upon [`Event::FundingGenerationReady`] to fund the channel with a transaction
+	/// spending the given [`SpendableOutputDescriptor`]s, e.g., the outputs of a channel we
+	/// recently closed, instead of one built by an on-chain wallet.
+	///
+	/// Each descriptor is provided along with the height of the block in which its output was
+	/// confirmed. If the relative locktime of any of the outputs has not yet expired, i.e., its
+	/// [`SpendableOutputDescriptor::maturity_height`] is beyond the next block, an
+	/// [`APIError::APIMisuseError`] including the maturity height is returned and the channel is
+	/// left untouched.
+	///
+	/// The descriptor inputs are signed by the given `output_spender`, which must be the one which
+	/// generated the descriptors, e.g., the [`KeysManager`]. Any value in excess of the channel
+	/// value and fees at the given feerate is sent to `change_destination_script`. See
+	/// [`Self::funding_transaction_generated_from_spendable_outputs_and_wallet`] if the descriptors
+	/// are insufficient to fund the channel on their own.
+	///
+	/// The resulting transaction is handed to [`Self::funding_transaction_generated`], so the same
+	/// errors apply and the funding flow proceeds as usual. Once this returns `Ok`, the outputs
+	/// should no longer be swept, e.g., by an [`OutputSweeper`]. If the channel is closed before the
+	/// funding transaction is broadcast, an [`Event::DiscardFunding`] is generated and the outputs
+	/// are spendable again.
+	///
+	/// [`Event::FundingGenerationReady`]: crate::events::Event::FundingGenerationReady
+	/// [`Event::DiscardFunding`]: crate::events::Event::DiscardFunding
+	/// [`KeysManager`]: crate::sign::KeysManager
+	/// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
+	pub fn funding_transaction_generated_from_spendable_outputs<O: Deref>(
+		&self, temporary_channel_id: ChannelId, counterparty_node_id: PublicKey,
+		descriptors: &[(SpendableOutputDescriptor, u32)], output_spender: O,
+		change_destination_script: ScriptBuf, feerate_sat_per_1000_weight: u32,
+	) -> Result<(), APIError>
+	where
+		O::Target: OutputSpender,
+	{
+		let (descriptors, funding_output, lock_time) = self.spendable_outputs_funding_output(
+			&temporary_channel_id,
+			&counterparty_node_id,
+			descriptors,
+		)?;
+		let funding_transaction = output_spender
+			.spend_spendable_outputs(
+				&descriptors,
+				vec![funding_output],
+				change_destination_script,
+				feerate_sat_per_1000_weight,
+				Some(lock_time),
+				&self.secp_ctx,
+			)
+			.map_err(|()| APIError::APIMisuseError {
+				err: "Failed to build a funding transaction from the spendable outputs".to_owned(),
+			})?;
+		self.funding_transaction_generated(
+			temporary_channel_id,
+			counterparty_node_id,
+			funding_transaction,
+		)
+	}
+
+	/// Call this upon [`Event::FundingGenerationReady`] to fund the channel with a transaction
+	/// spending the given [`SpendableOutputDescriptor`]s along with any additional UTXOs selected
+	/// from the given `coin_selection_source`.
+	///
+	/// Works like [`Self::funding_transaction_generated_from_spendable_outputs`], except that the
+	/// descriptors are passed to [`CoinSelectionSourceSync::select_confirmed_utxos`] as inputs
+	/// which must be spent, leaving it to the `coin_selection_source` to cover any shortfall and
+	/// to provide the change output. The `coin_selection_source` then signs its own inputs via
+	/// [`CoinSelectionSourceSync::sign_psbt`] after the descriptor inputs have been signed by the
+	/// `output_spender`.
+	///
+	/// [`Event::FundingGenerationReady`]: crate::events::Event::FundingGenerationReady
+	pub fn funding_transaction_generated_from_spendable_outputs_and_wallet<O: Deref, C: Deref>(
+		&self, temporary_channel_id: ChannelId, counterparty_node_id: PublicKey,
+		descriptors: &[(SpendableOutputDescriptor, u32)], output_spender: O,
+		coin_selection_source: C, feerate_sat_per_1000_weight: u32,
+	) -> Result<(), APIError>
+	where
+		O::Target: OutputSpender,
+		C::Target: CoinSelectionSourceSync,
+	{
+		let (descriptors, funding_output, lock_time) = self.spendable_outputs_funding_output(
+			&temporary_channel_id,
+			&counterparty_node_id,
+			descriptors,
+		)?;
+
+		let must_spend = descriptors
+			.iter()
+			.map(|descriptor| Input {
+				outpoint: descriptor.spendable_outpoint().into_bitcoin_outpoint(),
+				previous_utxo: descriptor.output().clone(),
+				satisfaction_weight: EMPTY_SCRIPT_SIG_WEIGHT + descriptor.max_witness_weight(),
+			})
+			.collect();
+		let claim_id =
+			ClaimId(Sha256::hash(funding_output.script_pubkey.as_bytes()).to_byte_array());
+		let coin_selection = coin_selection_source
+			.select_confirmed_utxos(
+				claim_id,
+				must_spend,
+				&[funding_output.clone()],
+				feerate_sat_per_1000_weight,
+			)
+			.map_err(|()| APIError::APIMisuseError { err: "Coin selection failed".to_owned() })?;
+
+		let descriptor_inputs = descriptors.iter().map(|descriptor| TxIn {
+			previous_output: descriptor.spendable_outpoint().into_bitcoin_outpoint(),
+			script_sig: ScriptBuf::new(),
+			sequence: descriptor.spending_sequence(),
+			witness: Witness::new(),
+		});
+		let utxo_inputs = coin_selection.confirmed_utxos.iter().map(|utxo| TxIn {
+			previous_output: utxo.outpoint,
+			script_sig: ScriptBuf::new(),
+			sequence: Sequence::ZERO,
+			witness: Witness::new(),
+		});
+		let unsigned_tx = Transaction {
+			version: Version::TWO,
+			lock_time,
+			input: descriptor_inputs.chain(utxo_inputs).collect(),
+			output: core::iter::once(funding_output).chain(coin_selection.change_output).collect(),
+		};
+
+		let mut psbt = Psbt::from_unsigned_tx(unsigned_tx).expect("Inputs are unsigned");
+		for (idx, descriptor) in descriptors.iter().enumerate() {
+			psbt.inputs[idx] = descriptor.to_psbt_input(&self.secp_ctx);
+		}
+		for (idx, utxo) in coin_selection.confirmed_utxos.into_iter().enumerate() {
+			// Offset to skip the descriptor inputs
+			let index = idx + descriptors.len();
+			if utxo.output.script_pubkey.is_witness_program() {
+				psbt.inputs[index].witness_utxo = Some(utxo.output);
+			}
+		}
+
+		let psbt = output_spender
+			.sign_spendable_outputs_psbt(&descriptors, psbt, &self.secp_ctx)
+			.map_err(|()| APIError::APIMisuseError {
+			err: "Failed to sign the spendable outputs".to_owned(),
+		})?;
+		// The wallet isn't required to retain the witnesses of inputs it doesn't sign for, so we
+		// restore the descriptor witnesses after it's done.
+		let descriptor_witnesses: Vec<Option<Witness>> = psbt.inputs[..descriptors.len()]
+			.iter()
+			.map(|input| input.final_script_witness.clone())
+			.collect();
+		let mut funding_transaction = coin_selection_source.sign_psbt(psbt).map_err(|()| {
+			APIError::APIMisuseError { err: "Failed to sign the funding transaction".to_owned() }
+		})?;
+		for (input, witness) in funding_transaction.input.iter_mut().zip(descriptor_witnesses) {
+			if let Some(witness) = witness {
+				input.witness = witness;
+			}
+		}
+
+		self.funding_transaction_generated(
+			temporary_channel_id,
+			counterparty_node_id,
+			funding_transaction,
+		)
+	}
+
+	/// Checks that the given spendable outputs may be spent in the next block and returns them
+	/// along with the funding output of the given unfunded outbound channel and the locktime to
+	/// use for its funding transaction.
+	fn spendable_outputs_funding_output<'a>(
+		&self, temporary_channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		descriptors: &'a [(SpendableOutputDescriptor, u32)],
+	) -> Result<(Vec<&'a SpendableOutputDescriptor>, TxOut, LockTime), APIError> {
+		let best_block_height = self.best_block.read().unwrap().height;
+		for (descriptor, confirmation_height) in descriptors {
+			let maturity_height = descriptor.maturity_height(*confirmation_height);
+			if maturity_height > best_block_height + 1 {
+				let err = format!(
+					"Spendable output {} is not mature until block height {}",
+					descriptor.spendable_outpoint(),
+					maturity_height
+				);
+				return Err(APIError::APIMisuseError { err });
+			}
+		}
+
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state_mutex = per_peer_state.get(counterparty_node_id).ok_or_else(|| {
+			let err = format!(
+				"Can't find a peer matching the passed counterparty node_id {counterparty_node_id}"
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		let mut peer_state_lock = peer_state_mutex.lock().unwrap();
+		let peer_state = &mut *peer_state_lock;
+		let chan = peer_state.channel_by_id.get_mut(temporary_channel_id).ok_or_else(|| {
+			let err = format!(
+				"Channel with id {} not found for the passed counterparty node_id {}",
+				temporary_channel_id, counterparty_node_id
+			);
+			APIError::ChannelUnavailable { err }
+		})?;
+		// The funding output is only known once the counterparty's `accept_channel` was received.
+		let funding =
+			chan.as_unfunded_outbound_v1_mut().map(|chan| &chan.funding).filter(|funding| {
+				funding.channel_transaction_parameters.counterparty_parameters.is_some()
+			});
+		let funding_output = match funding {
+			Some(funding) => TxOut {
+				value: bitcoin::Amount::from_sat(funding.get_value_satoshis()),
+				script_pubkey: funding.get_funding_redeemscript().to_p2wsh(),
+			},
+			None => {
+				let err = format!(
+					"Channel with id {} for the passed counterparty node_id {} is not an unfunded, outbound V1 channel awaiting funding",
+					temporary_channel_id, counterparty_node_id
+				);
+				return Err(APIError::APIMisuseError { err });
+			},
+		};
+
+		let descriptors = descriptors.iter().map(|(descriptor, _)| descriptor).collect();
+		let lock_time = LockTime::from_height(best_block_height).unwrap_or(LockTime::ZERO);
+		Ok((descriptors, funding_output, lock_time))
+	}
+
 	/// **Unsafe**: This method does not validate the spent output. It is the caller's
 	/// responsibility to ensure the spent outputs are SegWit, as well as making sure the funding
 	/// transaction has a final absolute locktime, i.e., its locktime is lower than the next block height.
@@ -5757,6 +7869,154 @@ where
 		self.batch_funding_transaction_generated_intern(temporary_chans, funding_type)
 	}
 
+	/// Call this upon [`Event::FundingGenerationReady`] with an unsigned funding transaction, e.g.,
+	/// if its inputs have to be signed by several external signers in turn, such as the holders of
+	/// a multisig. The signatures are then provided via [`Self::funding_psbt_updated`].
+	///
+	/// The transaction must spend only SegWit outputs, such that its txid is known before it is
+	/// signed, and contain exactly one output matching the parameters in
+	/// [`Event::FundingGenerationReady`]. Returns [`APIError::APIMisuseError`] if it doesn't, if it
+	/// has a script_sig or witness on any input, or if an unsigned funding transaction was already
+	/// provided for the channel, leaving the channel untouched.
+	///
+	/// Returns [`APIError::ChannelUnavailable`] if the channel is not an unfunded outbound channel
+	/// with the given counterparty, e.g., as it was closed as indicated by [`Event::ChannelClosed`].
+	///
+	/// The channel remains pending until the transaction is fully signed, so it is closed as usual
+	/// if that doesn't happen before our counterparty would give up on it. To abort funding the
+	/// channel beforehand, force-close it via [`Self::force_close_broadcasting_latest_txn`].
+	///
+	/// [`Event::FundingGenerationReady`]: crate::events::Event::FundingGenerationReady
+	/// [`Event::ChannelClosed`]: crate::events::Event::ChannelClosed
+	pub fn funding_transaction_generated_unsigned(
+		&self, temporary_channel_id: ChannelId, counterparty_node_id: PublicKey,
+		unsigned_transaction: Transaction,
+	) -> Result<(), APIError> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state_mutex = per_peer_state.get(&counterparty_node_id).ok_or_else(|| {
+			APIError::ChannelUnavailable {
+				err: format!("Can't find a peer matching the passed counterparty node_id {counterparty_node_id}"),
+			}
+		})?;
+		let mut peer_state = peer_state_mutex.lock().unwrap();
+		let chan = peer_state
+			.channel_by_id
+			.get_mut(&temporary_channel_id)
+			.and_then(Channel::as_unfunded_outbound_v1_mut)
+			.ok_or_else(|| APIError::ChannelUnavailable {
+				err: format!(
+					"Channel with id {} for the passed counterparty node_id {} is not an unfunded, outbound V1 channel",
+					temporary_channel_id, counterparty_node_id
+				),
+			})?;
+
+		let expected_spk = chan.funding.get_funding_redeemscript().to_p2wsh();
+		let funding_value = chan.funding.get_value_satoshis();
+		let matching_outputs = unsigned_transaction
+			.output
+			.iter()
+			.filter(|output| {
+				output.script_pubkey == expected_spk && output.value.to_sat() == funding_value
+			})
+			.count();
+		if matching_outputs != 1 {
+			return Err(APIError::APIMisuseError {
+				err: "Exactly one output must match the script_pubkey and value in the FundingGenerationReady event".to_owned(),
+			});
+		}
+		let psbt =
+			Psbt::from_unsigned_tx(unsigned_transaction).map_err(|_| APIError::APIMisuseError {
+				err: "Funding transaction must not be signed yet".to_owned(),
+			})?;
+
+		match self.pending_funding_psbts.lock().unwrap().entry(temporary_channel_id) {
+			hash_map::Entry::Occupied(_) => Err(APIError::APIMisuseError {
+				err: format!(
+					"An unsigned funding transaction was already provided for channel {}",
+					temporary_channel_id
+				),
+			}),
+			hash_map::Entry::Vacant(entry) => {
+				entry.insert((counterparty_node_id, psbt));
+				Ok(())
+			},
+		}
+	}
+
+	/// Call this with the [`Psbt`] of a funding transaction provided via
+	/// [`Self::funding_transaction_generated_unsigned`] each time it was updated by a signer.
+	///
+	/// The given PSBT is combined with the ones provided before, so each signer may be handed
+	/// either the latest PSBT or one of its own. Once all inputs have a final script witness,
+	/// the signed funding transaction is handed to [`Self::funding_transaction_generated`] and
+	/// `true` is returned, while `false` is returned if signatures are still missing.
+	///
+	/// Returns [`APIError::APIMisuseError`] if the PSBT's unsigned transaction differs from the
+	/// one provided before, e.g., as it changed the funding output, or if it can't be combined with
+	/// the ones provided before, leaving the channel untouched. Once fully signed, the errors of
+	/// [`Self::funding_transaction_generated`] apply.
+	///
+	/// Returns [`APIError::ChannelUnavailable`] if no unsigned funding transaction is pending for
+	/// the channel, e.g., as the channel was closed after not being funded in a timely manner as
+	/// indicated by [`Event::ChannelClosed`], in which case the funding transaction must not be
+	/// broadcast.
+	///
+	/// [`Event::ChannelClosed`]: crate::events::Event::ChannelClosed
+	pub fn funding_psbt_updated(
+		&self, temporary_channel_id: ChannelId, psbt: Psbt,
+	) -> Result<bool, APIError> {
+		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+
+		let no_pending_psbt_err = || APIError::ChannelUnavailable {
+			err: format!(
+				"No unsigned funding transaction is pending for channel {}",
+				temporary_channel_id
+			),
+		};
+		let counterparty_node_id = self
+			.pending_funding_psbts
+			.lock()
+			.unwrap()
+			.get(&temporary_channel_id)
+			.map(|(counterparty_node_id, _)| *counterparty_node_id)
+			.ok_or_else(no_pending_psbt_err)?;
+
+		let funding_transaction = {
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			let peer_state_lock =
+				per_peer_state.get(&counterparty_node_id).map(|m| m.lock().unwrap());
+			let mut pending_funding_psbts = self.pending_funding_psbts.lock().unwrap();
+			let is_pending = peer_state_lock.map_or(false, |peer_state| {
+				peer_state.channel_by_id.contains_key(&temporary_channel_id)
+			});
+			if !is_pending {
+				pending_funding_psbts.remove(&temporary_channel_id);
+				return Err(no_pending_psbt_err());
+			}
+			let (_, pending_psbt) = pending_funding_psbts
+				.get_mut(&temporary_channel_id)
+				.ok_or_else(no_pending_psbt_err)?;
+			if psbt.unsigned_tx != pending_psbt.unsigned_tx {
+				return Err(APIError::APIMisuseError {
+					err: "The updated PSBT must not change the funding transaction".to_owned(),
+				});
+			}
+			pending_psbt.combine(psbt).map_err(|e| APIError::APIMisuseError {
+				err: format!("Failed to combine the updated PSBT: {}", e),
+			})?;
+			if pending_psbt.inputs.iter().any(|input| input.final_script_witness.is_none()) {
+				return Ok(false);
+			}
+			let (_, signed_psbt) = pending_funding_psbts.remove(&temporary_channel_id).unwrap();
+			signed_psbt.extract_tx_unchecked_fee_rate()
+		};
+
+		let temporary_chans = &[(&temporary_channel_id, &counterparty_node_id)];
+		let funding_type = FundingType::Checked(funding_transaction);
+		self.batch_funding_transaction_generated_intern(temporary_chans, funding_type)?;
+		Ok(true)
+	}
+
 	/// Call this upon creation of a batch funding transaction for the given channels.
 	///
 	/// Return values are identical to [`Self::funding_transaction_generated`], respective to
@@ -5912,13 +8172,15 @@ where
 	/// `counterparty_node_id` is provided.
 	///
 	/// Returns [`APIMisuseError`] when a [`cltv_expiry_delta`] update is to be applied with a value
-	/// below [`MIN_CLTV_EXPIRY_DELTA`].
+	/// below [`MIN_CLTV_EXPIRY_DELTA`], or when [`liquidity_alert_thresholds`] are not
+	/// [valid](crate::util::config::LiquidityAlertThresholds::is_valid).
 	///
 	/// If an error is returned, none of the updates should be considered applied.
 	///
 	/// [`forwarding_fee_proportional_millionths`]: ChannelConfig::forwarding_fee_proportional_millionths
 	/// [`forwarding_fee_base_msat`]: ChannelConfig::forwarding_fee_base_msat
 	/// [`cltv_expiry_delta`]: ChannelConfig::cltv_expiry_delta
+	/// [`liquidity_alert_thresholds`]: ChannelConfig::liquidity_alert_thresholds
 	/// [`BroadcastChannelUpdate`]: MessageSendEvent::BroadcastChannelUpdate
 	/// [`ChannelUpdate`]: msgs::ChannelUpdate
 	/// [`ChannelUnavailable`]: APIError::ChannelUnavailable
@@ -5932,6 +8194,13 @@ where
 				err: format!("The chosen CLTV expiry delta is below the minimum of {}", MIN_CLTV_EXPIRY_DELTA),
 			});
 		}
+		if let Some(Some(thresholds)) = config_update.liquidity_alert_thresholds {
+			if !thresholds.is_valid() {
+				return Err(APIError::APIMisuseError {
+					err: format!("The chosen liquidity alert thresholds {:?} do not delimit three bands", thresholds),
+				});
+			}
+		}
 
 		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
 		let per_peer_state = self.per_peer_state.read().unwrap();
@@ -5990,13 +8259,15 @@ where
 	/// `counterparty_node_id` is provided.
 	///
 	/// Returns [`APIMisuseError`] when a [`cltv_expiry_delta`] update is to be applied with a value
-	/// below [`MIN_CLTV_EXPIRY_DELTA`].
+	/// below [`MIN_CLTV_EXPIRY_DELTA`], or when [`liquidity_alert_thresholds`] are not
+	/// [valid](crate::util::config::LiquidityAlertThresholds::is_valid).
 	///
 	/// If an error is returned, none of the updates should be considered applied.
 	///
 	/// [`forwarding_fee_proportional_millionths`]: ChannelConfig::forwarding_fee_proportional_millionths
 	/// [`forwarding_fee_base_msat`]: ChannelConfig::forwarding_fee_base_msat
 	/// [`cltv_expiry_delta`]: ChannelConfig::cltv_expiry_delta
+	/// [`liquidity_alert_thresholds`]: ChannelConfig::liquidity_alert_thresholds
 	/// [`BroadcastChannelUpdate`]: MessageSendEvent::BroadcastChannelUpdate
 	/// [`ChannelUpdate`]: msgs::ChannelUpdate
 	/// [`ChannelUnavailable`]: APIError::ChannelUnavailable
@@ -6007,6 +8278,30 @@ where
 		self.update_partial_channel_config(counterparty_node_id, channel_ids, &(*config).into())
 	}
 
+	/// Sets the maximum number of inbound HTLCs we accept to have in-flight on the given channel,
+	/// failing back any additional ones, or removes the limit if `None`. See
+	/// [`ChannelConfig::max_inbound_htlcs_for_reserve`] for details.
+	///
+	/// The limit is typically computed from the current on-chain reserve via
+	/// [`max_supportable_accepted_htlcs`] and updated whenever the reserve changes. HTLCs which
+	/// were already accepted are not affected by lowering it.
+	///
+	/// Returns [`ChannelUnavailable`] when the channel is not found or an incorrect
+	/// `counterparty_node_id` is provided.
+	///
+	/// [`max_supportable_accepted_htlcs`]: crate::util::anchor_channel_reserves::max_supportable_accepted_htlcs
+	/// [`ChannelUnavailable`]: APIError::ChannelUnavailable
+	pub fn set_max_inbound_htlcs_for_reserve(
+		&self, channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		max_inbound_htlcs: Option<u16>,
+	) -> Result<(), APIError> {
+		let config_update = ChannelConfigUpdate {
+			max_inbound_htlcs_for_reserve: Some(max_inbound_htlcs),
+			..ChannelConfigUpdate::default()
+		};
+		self.update_partial_channel_config(counterparty_node_id, &[*channel_id], &config_update)
+	}
+
 	/// Attempts to forward an intercepted HTLC over the provided channel id and with the provided
 	/// amount to forward. Should only be called in response to an [`HTLCIntercepted`] event.
 	///
@@ -6137,7 +8432,11 @@ where
 	#[rustfmt::skip]
 	pub fn fail_intercepted_htlc(&self, intercept_id: InterceptId) -> Result<(), APIError> {
 		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+		self.fail_intercepted_htlc_internal(intercept_id)
+	}
 
+	#[rustfmt::skip]
+	fn fail_intercepted_htlc_internal(&self, intercept_id: InterceptId) -> Result<(), APIError> {
 		let payment = self.pending_intercepted_htlcs.lock().unwrap().remove(&intercept_id)
 			.ok_or_else(|| APIError::APIMisuseError {
 				err: format!("Payment with intercept id {} not found", log_bytes!(intercept_id.0))
@@ -6165,6 +8464,129 @@ where
 		Ok(())
 	}
 
+	/// Fails back all intercepted HTLCs which were received for the given intercept SCID, e.g.
+	/// because the just-in-time channel it was handed out for will no longer be opened.
+	///
+	/// Returns the number of HTLCs which were failed back.
+	///
+	/// See [`Self::fail_intercepted_htlc`] for failing back an individual intercepted HTLC.
+	pub fn release_intercept_scid(&self, intercept_scid: u64) -> usize {
+		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+
+		let intercept_ids: Vec<InterceptId> = self
+			.pending_intercepted_htlcs
+			.lock()
+			.unwrap()
+			.iter()
+			.filter(|(_, htlc)| match htlc.forward_info.routing {
+				PendingHTLCRouting::Forward { short_channel_id, .. } => {
+					short_channel_id == intercept_scid
+				},
+				_ => false,
+			})
+			.map(|(intercept_id, _)| *intercept_id)
+			.collect();
+		intercept_ids
+			.into_iter()
+			.filter(|intercept_id| self.fail_intercepted_htlc_internal(*intercept_id).is_ok())
+			.count()
+	}
+
+	/// Fails back any received or intercepted HTLCs which are too close to expiry to be safely
+	/// claimed or forwarded, respectively.
+	///
+	/// This is done automatically as blocks are connected, but may be called to purge such HTLCs
+	/// on demand, e.g. prior to persisting the [`ChannelManager`].
+	///
+	/// Returns the number of HTLCs which were failed back.
+	pub fn purge_expired_inbound_payments(&self) -> usize {
+		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+
+		let height = self.best_block.read().unwrap().height;
+		let expired_htlcs = self.get_expired_inbound_htlcs(height);
+		let num_expired_htlcs = expired_htlcs.len();
+		for (source, payment_hash, reason, destination) in expired_htlcs {
+			self.fail_htlc_backwards_internal(&source, &payment_hash, &reason, destination);
+		}
+		num_expired_htlcs
+	}
+
+	/// Gets statistics about the state held for inbound payments and intercepted HTLCs.
+	pub fn inbound_state_stats(&self) -> InboundStateStats {
+		let (claimable_payments, claimable_htlcs, claiming_payments) = {
+			let claimable_payments = self.claimable_payments.lock().unwrap();
+			(
+				claimable_payments.claimable_payments.len(),
+				claimable_payments.claimable_payments.values().map(|p| p.htlcs.len()).sum(),
+				claimable_payments.pending_claiming_payments.len(),
+			)
+		};
+		let (intercepted_htlcs, intercept_scids) = {
+			let intercepted_htlcs = self.pending_intercepted_htlcs.lock().unwrap();
+			let mut intercept_scids: Vec<u64> = intercepted_htlcs
+				.values()
+				.filter_map(|htlc| match htlc.forward_info.routing {
+					PendingHTLCRouting::Forward { short_channel_id, .. } => Some(short_channel_id),
+					_ => None,
+				})
+				.collect();
+			intercept_scids.sort_unstable();
+			intercept_scids.dedup();
+			(intercepted_htlcs.len(), intercept_scids.len())
+		};
+		let approximate_memory_bytes = claimable_payments
+			* mem::size_of::<(PaymentHash, ClaimablePayment)>()
+			+ claimable_htlcs * mem::size_of::<ClaimableHTLC>()
+			+ claiming_payments * mem::size_of::<(PaymentHash, ClaimingPayment)>()
+			+ intercepted_htlcs * mem::size_of::<(InterceptId, PendingAddHTLCInfo)>();
+		InboundStateStats {
+			claimable_payments,
+			claimable_htlcs,
+			claiming_payments,
+			intercepted_htlcs,
+			intercept_scids,
+			approximate_memory_bytes,
+		}
+	}
+
+	/// Returns the number of HTLCs received over the channel with the given `channel_id` which are
+	/// pending as parts of inbound payments, i.e., which await their remaining parts or the user
+	/// claiming them. HTLCs of payments which are already being claimed are not counted.
+	fn num_pending_inbound_payment_parts(&self, channel_id: &ChannelId) -> usize {
+		let num_queued_parts = self
+			.forward_htlcs
+			.lock()
+			.unwrap()
+			.values()
+			.flatten()
+			.filter(|forward| match forward {
+				HTLCForwardInfo::AddHTLC(PendingAddHTLCInfo {
+					prev_channel_id,
+					forward_info: PendingHTLCInfo { routing, .. },
+					..
+				}) => {
+					prev_channel_id == channel_id
+						&& matches!(
+							routing,
+							PendingHTLCRouting::Receive { .. }
+								| PendingHTLCRouting::ReceiveKeysend { .. }
+						)
+				},
+				_ => false,
+			})
+			.count();
+		let num_claimable_parts = self
+			.claimable_payments
+			.lock()
+			.unwrap()
+			.claimable_payments
+			.values()
+			.flat_map(|payment| payment.htlcs.iter())
+			.filter(|htlc| htlc.prev_hop.channel_id == *channel_id)
+			.count();
+		num_queued_parts + num_claimable_parts
+	}
+
 	pub(crate) fn process_pending_update_add_htlcs(&self) -> bool {
 		let mut should_persist = false;
 		let mut decode_update_add_htlcs = new_hash_map();
@@ -6221,6 +8643,8 @@ where
 
 			let mut htlc_forwards = Vec::new();
 			let mut htlc_fails = Vec::new();
+			// Only counted once we decode an HTLC we receive over the channel, if at all.
+			let mut num_pending_inbound_payment_parts = None;
 			for update_add_htlc in &update_add_htlcs {
 				let (next_hop, next_packet_details_opt) =
 					match decode_incoming_update_add_htlc_onion(
@@ -6255,7 +8679,16 @@ where
 							&chan.context,
 							Some(update_add_htlc.payment_hash),
 						);
-						chan.can_accept_incoming_htlc(update_add_htlc, &self.fee_estimator, &logger)
+						chan.can_accept_incoming_htlc(
+							update_add_htlc,
+							&self.fee_estimator,
+							&logger,
+						)?;
+						chan.check_max_inbound_htlcs_for_reserve(
+							update_add_htlc,
+							htlc_fails.len(),
+							&logger,
+						)
 					},
 				) {
 					Some(Ok(_)) => {},
@@ -6302,7 +8735,39 @@ where
 					incoming_accept_underpaying_htlcs,
 					next_packet_details_opt.map(|d| d.next_packet_pubkey),
 				) {
-					Ok(info) => htlc_forwards.push((info, update_add_htlc.htlc_id)),
+					Ok(info) => {
+						let is_receive = matches!(
+							info.routing,
+							PendingHTLCRouting::Receive { .. }
+								| PendingHTLCRouting::ReceiveKeysend { .. }
+						);
+						let max_parts =
+							self.default_configuration.max_inbound_payment_parts_for_reserve;
+						if let (true, Some(max_parts)) = (is_receive, max_parts) {
+							let num_parts =
+								num_pending_inbound_payment_parts.get_or_insert_with(|| {
+									self.num_pending_inbound_payment_parts(&incoming_channel_id)
+								});
+							if *num_parts >= max_parts as usize {
+								let reason =
+									LocalHTLCFailureReason::InboundPaymentPartLimitForReserve;
+								let htlc_fail = self.htlc_failure_from_update_add_err(
+									&update_add_htlc,
+									&incoming_counterparty_node_id,
+									reason,
+									is_intro_node_blinded_forward,
+									&shared_secret,
+								);
+								let failure_type = HTLCHandlingFailureType::Receive {
+									payment_hash: update_add_htlc.payment_hash,
+								};
+								htlc_fails.push((htlc_fail, failure_type, reason.into()));
+								continue;
+							}
+							*num_parts += 1;
+						}
+						htlc_forwards.push((info, update_add_htlc.htlc_id))
+					},
 					Err(inbound_err) => {
 						let failure_type =
 							get_htlc_failure_type(outgoing_scid_opt, update_add_htlc.payment_hash);
@@ -7186,6 +9651,18 @@ where
 								// Nothing to do - we haven't reached the total
 								// payment value yet, wait until we receive more
 								// MPP parts.
+								if claimable_payment.htlcs.is_empty() && !is_keysend
+									&& self.default_configuration.notify_partial_mpp_payments
+								{
+									let deadline = self.duration_since_epoch().as_secs()
+										+ mpp_timeout_secs(&self.default_configuration);
+									new_events.push_back((events::Event::PaymentPartiallyReceived {
+										payment_hash,
+										received_msat: claimable_htlc.value,
+										expected_msat: claimable_htlc.total_msat,
+										deadline,
+									}, None));
+								}
 								claimable_payment.htlcs.push(claimable_htlc);
 								#[allow(unused_assignments)] {
 									committed_to_claimable = true;
@@ -7387,13 +9864,18 @@ where
 		NotifyOption::DoPersist
 	}
 
-	#[cfg(any(test, fuzzing, feature = "_externalize_tests"))]
-	/// In chanmon_consistency we want to sometimes do the channel fee updates done in
-	/// timer_tick_occurred, but we can't generate the disabled channel updates as it considers
-	/// these a fuzz failure (as they usually indicate a channel force-close, which is exactly what
-	/// it wants to detect). Thus, we have a variant exposed here for its benefit.
-	#[rustfmt::skip]
-	pub fn maybe_update_chan_fees(&self) {
+	/// Updates the feerate of our outbound channels if our fee estimates have changed
+	/// significantly since the channel's feerate was last set, sending an `update_fee` to the
+	/// counterparty.
+	///
+	/// This should be called at least once every few minutes, and more often when fee estimates
+	/// are volatile, as a commitment transaction with a stale feerate may fail to confirm in a
+	/// timely manner if we need to broadcast it. It is called as a part of
+	/// [`Self::timer_tick_occurred`].
+	///
+	/// Note that this may cause reentrancy through [`chain::Watch::update_channel`] calls or feerate
+	/// estimate fetches.
+	pub fn process_stale_feerate_updates(&self) {
 		PersistenceNotifierGuard::optionally_notify(self, || {
 			let mut should_persist = NotifyOption::SkipPersistNoEvents;
 			let mut feerate_cache = new_hash_map();
@@ -7402,17 +9884,19 @@ where
 			for (_cp_id, peer_state_mutex) in per_peer_state.iter() {
 				let mut peer_state_lock = peer_state_mutex.lock().unwrap();
 				let peer_state = &mut *peer_state_lock;
-				for (chan_id, chan) in peer_state.channel_by_id.iter_mut()
+				for (chan_id, chan) in peer_state
+					.channel_by_id
+					.iter_mut()
 					.filter_map(|(chan_id, chan)| chan.as_funded_mut().map(|chan| (chan_id, chan)))
 				{
 					let channel_type = chan.funding.get_channel_type();
-					let new_feerate = feerate_cache.get(channel_type).copied().or_else(|| {
-						let feerate = selected_commitment_sat_per_1000_weight(&self.fee_estimator, &channel_type);
-						feerate_cache.insert(channel_type.clone(), feerate);
-						Some(feerate)
-					}).unwrap();
-					let chan_needs_persist = self.update_channel_fee(chan_id, chan, new_feerate);
-					if chan_needs_persist == NotifyOption::DoPersist { should_persist = NotifyOption::DoPersist; }
+					let feerate = *feerate_cache.entry(channel_type.clone()).or_insert_with(|| {
+						selected_commitment_sat_per_1000_weight(&self.fee_estimator, &channel_type)
+					});
+					let chan_needs_persist = self.update_channel_fee(chan_id, chan, feerate);
+					if chan_needs_persist == NotifyOption::DoPersist {
+						should_persist = NotifyOption::DoPersist;
+					}
 				}
 			}
 
@@ -7420,35 +9904,20 @@ where
 		});
 	}
 
-	/// Performs actions which should happen on startup and roughly once per minute thereafter.
-	///
-	/// This currently includes:
-	///  * Increasing or decreasing the on-chain feerate estimates for our outbound channels,
-	///  * Broadcasting [`ChannelUpdate`] messages if we've been disconnected from our peer for more
-	///    than a minute, informing the network that they should no longer attempt to route over
-	///    the channel.
-	///  * Expiring a channel's previous [`ChannelConfig`] if necessary to only allow forwarding HTLCs
-	///    with the current [`ChannelConfig`].
-	///  * Removing peers which have disconnected but and no longer have any channels.
-	///  * Force-closing and removing channels which have not completed establishment in a timely manner.
-	///  * Forgetting about stale outbound payments, either those that have already been fulfilled
-	///    or those awaiting an invoice that hasn't been delivered in the necessary amount of time.
-	///    The latter is determined using the system clock in `std` and the highest seen block time
-	///    minus two hours in non-`std`.
-	///
-	/// Note that this may cause reentrancy through [`chain::Watch::update_channel`] calls or feerate
-	/// estimate fetches.
+	/// Checks for counterparties which have stopped responding, aborting stalled interactive
+	/// funding transaction negotiations, force-closing channels whose cooperative close
+	/// negotiation has not made progress, and disconnecting peers which have not responded to a
+	/// message we're awaiting a response to.
 	///
-	/// [`ChannelUpdate`]: msgs::ChannelUpdate
-	/// [`ChannelConfig`]: crate::util::config::ChannelConfig
-	pub fn timer_tick_occurred(&self) {
+	/// The timeouts involved are counted in calls to this method, e.g., interactive funding
+	/// transaction negotiations are aborted after
+	/// [`UserConfig::interactive_tx_negotiation_timeout_ticks`] calls, thus it should be called
+	/// roughly once per minute. Calling it less often delays the handling of unresponsive peers,
+	/// calling it much more often may disconnect peers which are merely slow.
+	pub fn check_peer_timeouts(&self) {
 		PersistenceNotifierGuard::optionally_notify(self, || {
 			let mut should_persist = NotifyOption::SkipPersistNoEvents;
-
 			let mut handle_errors: Vec<(Result<(), _>, _)> = Vec::new();
-			let mut timed_out_mpp_htlcs = Vec::new();
-			let mut pending_peers_awaiting_removal = Vec::new();
-			let mut feerate_cache = new_hash_map();
 
 			{
 				let per_peer_state = self.per_peer_state.read().unwrap();
@@ -7457,105 +9926,237 @@ where
 					let peer_state = &mut *peer_state_lock;
 					let pending_msg_events = &mut peer_state.pending_msg_events;
 					let counterparty_node_id = *counterparty_node_id;
+					let timeout_ticks =
+						self.default_configuration.interactive_tx_negotiation_timeout_ticks;
 					peer_state.channel_by_id.retain(|chan_id, chan| {
-						match chan.as_funded_mut() {
-							Some(funded_chan) => {
-								let channel_type = funded_chan.funding.get_channel_type();
-								let new_feerate = feerate_cache.get(channel_type).copied().or_else(|| {
-									let feerate = selected_commitment_sat_per_1000_weight(&self.fee_estimator, &channel_type);
-									feerate_cache.insert(channel_type.clone(), feerate);
-									Some(feerate)
-								}).unwrap();
-								let chan_needs_persist = self.update_channel_fee(chan_id, funded_chan, new_feerate);
-								if chan_needs_persist == NotifyOption::DoPersist { should_persist = NotifyOption::DoPersist; }
-
-								if let Err(e) = funded_chan.timer_check_closing_negotiation_progress() {
-									let (needs_close, err) = convert_channel_err!(self, peer_state, e, funded_chan, FUNDED_CHANNEL);
-									handle_errors.push((Err(err), counterparty_node_id));
-									if needs_close { return false; }
-								}
+						let negotiation_stalled =
+							chan.interactive_tx_constructor_mut().map_or(false, |constructor| {
+								constructor.should_abort_stalled_negotiation(timeout_ticks)
+							});
+						if negotiation_stalled && self.abort_interactive_tx_negotiation(chan) {
+							let logger = WithChannelContext::from(&self.logger, chan.context(), None);
+							log_info!(logger, "Aborting interactive funding transaction negotiation for channel {} as our counterparty stopped responding",
+								chan_id);
+							pending_msg_events.push(MessageSendEvent::SendTxAbort {
+								node_id: counterparty_node_id,
+								msg: msgs::TxAbort {
+									channel_id: *chan_id,
+									data: "Timed out awaiting interactive transaction negotiation message".to_owned().into_bytes(),
+								},
+							});
+							should_persist = NotifyOption::DoPersist;
+						}
 
-								match funded_chan.channel_update_status() {
-									ChannelUpdateStatus::Enabled if !funded_chan.context.is_live() => funded_chan.set_channel_update_status(ChannelUpdateStatus::DisabledStaged(0)),
-									ChannelUpdateStatus::Disabled if funded_chan.context.is_live() => funded_chan.set_channel_update_status(ChannelUpdateStatus::EnabledStaged(0)),
-									ChannelUpdateStatus::DisabledStaged(_) if funded_chan.context.is_live()
-										=> funded_chan.set_channel_update_status(ChannelUpdateStatus::Enabled),
-									ChannelUpdateStatus::EnabledStaged(_) if !funded_chan.context.is_live()
-										=> funded_chan.set_channel_update_status(ChannelUpdateStatus::Disabled),
-									ChannelUpdateStatus::DisabledStaged(mut n) if !funded_chan.context.is_live() => {
-										n += 1;
-										if n >= DISABLE_GOSSIP_TICKS {
-											funded_chan.set_channel_update_status(ChannelUpdateStatus::Disabled);
-											if let Ok(update) = self.get_channel_update_for_broadcast(&funded_chan) {
-												let mut pending_broadcast_messages = self.pending_broadcast_messages.lock().unwrap();
-												pending_broadcast_messages.push(MessageSendEvent::BroadcastChannelUpdate {
-													msg: update
-												});
-											}
-											should_persist = NotifyOption::DoPersist;
-										} else {
-											funded_chan.set_channel_update_status(ChannelUpdateStatus::DisabledStaged(n));
-										}
-									},
-									ChannelUpdateStatus::EnabledStaged(mut n) if funded_chan.context.is_live() => {
-										n += 1;
-										if n >= ENABLE_GOSSIP_TICKS {
-											funded_chan.set_channel_update_status(ChannelUpdateStatus::Enabled);
-											if let Ok(update) = self.get_channel_update_for_broadcast(&funded_chan) {
-												let mut pending_broadcast_messages = self.pending_broadcast_messages.lock().unwrap();
-												pending_broadcast_messages.push(MessageSendEvent::BroadcastChannelUpdate {
-													msg: update
-												});
-											}
-											should_persist = NotifyOption::DoPersist;
-										} else {
-											funded_chan.set_channel_update_status(ChannelUpdateStatus::EnabledStaged(n));
-										}
+						let funded_chan = match chan.as_funded_mut() {
+							Some(funded_chan) => funded_chan,
+							None => return true,
+						};
+
+						if let Err(e) = funded_chan.timer_check_closing_negotiation_progress() {
+							let (needs_close, err) =
+								convert_channel_err!(self, peer_state, e, funded_chan, FUNDED_CHANNEL);
+							handle_errors.push((Err(err), counterparty_node_id));
+							if needs_close {
+								return false;
+							}
+						}
+
+						if peer_state.is_connected && funded_chan.should_disconnect_peer_awaiting_response()
+						{
+							let logger = WithChannelContext::from(&self.logger, &funded_chan.context, None);
+							log_debug!(logger, "Disconnecting peer {} due to not making any progress on channel {}",
+									counterparty_node_id, chan_id);
+							pending_msg_events.push(MessageSendEvent::HandleError {
+								node_id: counterparty_node_id,
+								action: msgs::ErrorAction::DisconnectPeerWithWarning {
+									msg: msgs::WarningMessage {
+										channel_id: *chan_id,
+										data: "Disconnecting due to timeout awaiting response".to_owned(),
 									},
-									_ => {},
-								}
+								},
+							});
+						}
 
-								funded_chan.context.maybe_expire_prev_config();
+						true
+					});
+				}
+			}
 
-								if peer_state.is_connected {
-									if funded_chan.should_disconnect_peer_awaiting_response() {
-										let logger = WithChannelContext::from(&self.logger, &funded_chan.context, None);
-										log_debug!(logger, "Disconnecting peer {} due to not making any progress on channel {}",
-												counterparty_node_id, chan_id);
-										pending_msg_events.push(MessageSendEvent::HandleError {
-											node_id: counterparty_node_id,
-											action: msgs::ErrorAction::DisconnectPeerWithWarning {
-												msg: msgs::WarningMessage {
-													channel_id: *chan_id,
-													data: "Disconnecting due to timeout awaiting response".to_owned(),
-												},
-											},
-										});
-									}
-								}
+			if !handle_errors.is_empty() {
+				should_persist = NotifyOption::DoPersist;
+			}
+			for (err, counterparty_node_id) in handle_errors {
+				let _ = handle_error!(self, err, counterparty_node_id);
+			}
+
+			should_persist
+		});
+	}
+
+	/// Broadcasts [`ChannelUpdate`] messages disabling channels whose peer has been disconnected
+	/// for ten calls, informing the network that it should no longer attempt to route over them,
+	/// or re-enabling them once the peer has been connected again for five calls. Also expires a channel's previous [`ChannelConfig`] a few
+	/// calls after it was updated, after which we only allow forwarding HTLCs with the current
+	/// [`ChannelConfig`].
+	///
+	/// This should be called roughly once per minute. Calling it less often leaves the network
+	/// attempting to route payments over channels which are unusable for longer, leading to payment
+	/// failures and a worse reputation for our node.
+	///
+	/// [`ChannelUpdate`]: msgs::ChannelUpdate
+	/// [`ChannelConfig`]: crate::util::config::ChannelConfig
+	pub fn process_channel_update_status(&self) {
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let mut should_persist = NotifyOption::SkipPersistNoEvents;
+
+			let per_peer_state = self.per_peer_state.read().unwrap();
+			for (_cp_id, peer_state_mutex) in per_peer_state.iter() {
+				let mut peer_state_lock = peer_state_mutex.lock().unwrap();
+				let peer_state = &mut *peer_state_lock;
+				for chan in peer_state.channel_by_id.values_mut() {
+					chan.context_mut().maybe_expire_prev_config();
+					let chan = match chan.as_funded_mut() {
+						Some(chan) => chan,
+						None => continue,
+					};
+
+					let is_live = chan.context.is_live();
+					let broadcast_update = match chan.channel_update_status() {
+						ChannelUpdateStatus::Enabled if !is_live => {
+							chan.set_channel_update_status(ChannelUpdateStatus::DisabledStaged(0));
+							false
+						},
+						ChannelUpdateStatus::Disabled if is_live => {
+							chan.set_channel_update_status(ChannelUpdateStatus::EnabledStaged(0));
+							false
+						},
+						ChannelUpdateStatus::DisabledStaged(_) if is_live => {
+							chan.set_channel_update_status(ChannelUpdateStatus::Enabled);
+							false
+						},
+						ChannelUpdateStatus::EnabledStaged(_) if !is_live => {
+							chan.set_channel_update_status(ChannelUpdateStatus::Disabled);
+							false
+						},
+						ChannelUpdateStatus::DisabledStaged(n) if !is_live => {
+							if n + 1 >= DISABLE_GOSSIP_TICKS {
+								chan.set_channel_update_status(ChannelUpdateStatus::Disabled);
+								true
+							} else {
+								let status = ChannelUpdateStatus::DisabledStaged(n + 1);
+								chan.set_channel_update_status(status);
+								false
+							}
+						},
+						ChannelUpdateStatus::EnabledStaged(n) if is_live => {
+							if n + 1 >= ENABLE_GOSSIP_TICKS {
+								chan.set_channel_update_status(ChannelUpdateStatus::Enabled);
+								true
+							} else {
+								let status = ChannelUpdateStatus::EnabledStaged(n + 1);
+								chan.set_channel_update_status(status);
+								false
+							}
+						},
+						_ => false,
+					};
+
+					if broadcast_update {
+						if let Ok(update) = self.get_channel_update_for_broadcast(&chan) {
+							let mut pending_broadcast_messages =
+								self.pending_broadcast_messages.lock().unwrap();
+							pending_broadcast_messages
+								.push(MessageSendEvent::BroadcastChannelUpdate { msg: update });
+						}
+						should_persist = NotifyOption::DoPersist;
+					}
+				}
+			}
+
+			should_persist
+		});
+	}
+
+	/// Force-closes and removes channels which have not completed establishment in a timely
+	/// manner, abandons inbound channel requests which have not been accepted in a timely manner,
+	/// and force-closes channels of batched cooperative closures initiated via
+	/// [`Self::close_channels_with_peer`] which timed out.
+	///
+	/// The timeouts involved are counted in calls to this method, thus it should be called roughly
+	/// once per minute. Calling it less often leaves resources reserved for channels which will
+	/// never be funded or accepted for longer, calling it much more often may close channels
+	/// which are merely slow to complete establishment.
+	pub fn expire_stale_channels(&self) {
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let mut should_persist = NotifyOption::SkipPersistNoEvents;
+			let mut handle_errors: Vec<(Result<(), _>, _)> = Vec::new();
+
+			// Channels of timed-out batched closures are force-closed below, unless they still have
+			// HTLCs pending.
+			let mut timed_out_batched_close_channels = new_hash_set();
+			for batch in self.batched_close_states.lock().unwrap().iter_mut() {
+				batch.ticks_remaining = batch.ticks_remaining.saturating_sub(1);
+				if batch.ticks_remaining == 0 {
+					let unresolved_channels = batch
+						.channels
+						.iter()
+						.filter(|(_, outcome)| outcome.is_none())
+						.map(|(channel_id, _)| (batch.counterparty_node_id, *channel_id));
+					timed_out_batched_close_channels.extend(unresolved_channels);
+				}
+			}
 
+			{
+				let per_peer_state = self.per_peer_state.read().unwrap();
+				for (counterparty_node_id, peer_state_mutex) in per_peer_state.iter() {
+					let mut peer_state_lock = peer_state_mutex.lock().unwrap();
+					let peer_state = &mut *peer_state_lock;
+					let counterparty_node_id = *counterparty_node_id;
+					peer_state.channel_by_id.retain(|chan_id, chan| match chan.as_funded_mut() {
+						Some(funded_chan) => {
+							if timed_out_batched_close_channels.contains(&(counterparty_node_id, *chan_id))
+								&& !funded_chan.has_pending_htlcs()
+							{
+								let logger = WithChannelContext::from(&self.logger, &funded_chan.context, None);
+								log_info!(logger, "Force-closing channel {} as its batched cooperative close timed out", chan_id);
+								let message = "Batched cooperative close timed out".to_owned();
+								let reason = ClosureReason::HolderForceClosed {
+									broadcasted_latest_txn: Some(true),
+									message: message.clone(),
+								};
+								let err = ChannelError::Close((message, reason));
+								let (_, e) =
+									convert_channel_err!(self, peer_state, err, funded_chan, FUNDED_CHANNEL);
+								handle_errors.push((Err(e), counterparty_node_id));
+								return false;
+							}
+							true
+						},
+						None => {
+							let unfunded_context =
+								chan.unfunded_context_mut().expect("channel should be unfunded");
+							if unfunded_context.should_expire_unfunded_channel() {
+								let context = chan.context();
+								let logger = WithChannelContext::from(&self.logger, context, None);
+								log_error!(logger,
+									"Force-closing pending channel with ID {} for not establishing in a timely manner",
+									context.channel_id());
+								let reason = ClosureReason::FundingTimedOut;
+								let msg = "Force-closing pending channel due to timeout awaiting establishment handshake".to_owned();
+								let err = ChannelError::Close((msg, reason));
+								let (_, e) = convert_channel_err!(self, peer_state, err, chan);
+								handle_errors.push((Err(e), counterparty_node_id));
+								false
+							} else {
 								true
-							},
-							None => {
-								chan.context_mut().maybe_expire_prev_config();
-								let unfunded_context = chan.unfunded_context_mut().expect("channel should be unfunded");
-								if unfunded_context.should_expire_unfunded_channel() {
-									let context = chan.context();
-									let logger = WithChannelContext::from(&self.logger, context, None);
-									log_error!(logger,
-										"Force-closing pending channel with ID {} for not establishing in a timely manner",
-										context.channel_id());
-									let reason = ClosureReason::FundingTimedOut;
-									let msg = "Force-closing pending channel due to timeout awaiting establishment handshake".to_owned();
-									let err = ChannelError::Close((msg, reason));
-									let (_, e) = convert_channel_err!(self, peer_state, err, chan);
-									handle_errors.push((Err(e), counterparty_node_id));
-									false
-								} else {
-									true
-								}
-							},
-						}
+							}
+						},
+					});
+					// Forget the funding transactions of channels which were closed before being
+					// fully signed.
+					self.pending_funding_psbts.lock().unwrap().retain(|chan_id, (node_id, _)| {
+						*node_id != counterparty_node_id
+							|| peer_state.channel_by_id.contains_key(chan_id)
 					});
 
 					for (chan_id, req) in peer_state.inbound_channel_request_by_id.iter_mut() {
@@ -7585,20 +10186,63 @@ where
 					peer_state
 						.inbound_channel_request_by_id
 						.retain(|_, req| req.ticks_remaining > 0);
+				}
+			}
 
-					if peer_state.ok_to_remove(true) {
-						pending_peers_awaiting_removal.push(counterparty_node_id);
+			if !handle_errors.is_empty() {
+				should_persist = NotifyOption::DoPersist;
+			}
+			for (err, counterparty_node_id) in handle_errors {
+				let _ = handle_error!(self, err, counterparty_node_id);
+			}
+
+			// Any batched closures which timed out and weren't resolved by the force-closures above
+			// still have channels with HTLCs pending, which we report as such.
+			let mut timed_out_batched_close_events = Vec::new();
+			self.batched_close_states.lock().unwrap().retain(|batch| {
+				if batch.ticks_remaining == 0 {
+					timed_out_batched_close_events.push(batch.completed_event());
+					false
+				} else {
+					true
+				}
+			});
+			if !timed_out_batched_close_events.is_empty() {
+				let mut pending_events = self.pending_events.lock().unwrap();
+				for event in timed_out_batched_close_events {
+					pending_events.push_back((event, None));
+				}
+				should_persist = NotifyOption::DoPersist;
+			}
+
+			should_persist
+		});
+	}
+
+	/// Removes peers which have disconnected and with which we no longer have any channels.
+	///
+	/// There is no minimum frequency at which this needs to be called for safety, though the
+	/// memory used for such peers is only freed once it is called. It is called as a part of
+	/// [`Self::timer_tick_occurred`].
+	pub fn prune_disconnected_peers(&self) {
+		// When a peer disconnects but still has channels, the peer's `peer_state` entry in the
+		// `per_peer_state` is not removed by the `peer_disconnected` function. If the channels
+		// of to that peer is later closed while still being disconnected (i.e. force closed),
+		// we therefore need to remove the peer from `peer_state` separately.
+		// To avoid having to take the `per_peer_state` `write` lock once the channels are
+		// closed, we instead remove such peers awaiting removal here on a timer, to limit the
+		// negative effects on parallelism as much as possible.
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let mut pending_peers_awaiting_removal = Vec::new();
+			{
+				let per_peer_state = self.per_peer_state.read().unwrap();
+				for (counterparty_node_id, peer_state_mutex) in per_peer_state.iter() {
+					if peer_state_mutex.lock().unwrap().ok_to_remove(true) {
+						pending_peers_awaiting_removal.push(*counterparty_node_id);
 					}
 				}
 			}
 
-			// When a peer disconnects but still has channels, the peer's `peer_state` entry in the
-			// `per_peer_state` is not removed by the `peer_disconnected` function. If the channels
-			// of to that peer is later closed while still being disconnected (i.e. force closed),
-			// we therefore need to remove the peer from `peer_state` separately.
-			// To avoid having to take the `per_peer_state` `write` lock once the channels are
-			// closed, we instead remove such peers awaiting removal here on a timer, to limit the
-			// negative effects on parallelism as much as possible.
 			if pending_peers_awaiting_removal.len() > 0 {
 				let mut per_peer_state = self.per_peer_state.write().unwrap();
 				for counterparty_node_id in pending_peers_awaiting_removal {
@@ -7620,6 +10264,22 @@ where
 				}
 			}
 
+			NotifyOption::SkipPersistNoEvents
+		});
+	}
+
+	/// Fails back the HTLCs of inbound multi-part payments which have not received all of their
+	/// parts within [`UserConfig::mpp_timeout_secs`], counted in calls to this method.
+	///
+	/// This should be called roughly once per minute. Calling it less often holds the HTLCs of
+	/// incomplete payments, and thus the liquidity of the channels they were received over, for
+	/// longer, calling it much more often may fail payments whose parts are merely slow to arrive.
+	/// Incomplete payments are still failed back once their HTLCs approach expiry regardless.
+	pub fn expire_stale_inbound_payments(&self) {
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let mpp_timeout_ticks = mpp_timeout_ticks(&self.default_configuration);
+			let mut timed_out_mpp_htlcs = Vec::new();
+			let mut timed_out_payments = Vec::new();
 			self.claimable_payments.lock().unwrap().claimable_payments.retain(
 				|payment_hash, payment| {
 					if payment.htlcs.is_empty() {
@@ -7638,8 +10298,11 @@ where
 							return true;
 						} else if payment.htlcs.iter_mut().any(|htlc| {
 							htlc.timer_ticks += 1;
-							return htlc.timer_ticks >= MPP_TIMEOUT_TICKS;
+							return htlc.timer_ticks >= mpp_timeout_ticks;
 						}) {
+							let received_msat = payment.htlcs.iter().map(|h| h.value).sum();
+							let expected_msat = payment.htlcs[0].total_msat;
+							timed_out_payments.push((*payment_hash, received_msat, expected_msat));
 							let htlcs = payment
 								.htlcs
 								.drain(..)
@@ -7652,6 +10315,24 @@ where
 				},
 			);
 
+			let should_persist = if timed_out_mpp_htlcs.is_empty() {
+				NotifyOption::SkipPersistNoEvents
+			} else {
+				NotifyOption::DoPersist
+			};
+			if self.default_configuration.notify_partial_mpp_payments {
+				let mut pending_events = self.pending_events.lock().unwrap();
+				for (payment_hash, received_msat, expected_msat) in timed_out_payments {
+					let reason = events::PartialPaymentFailureReason::TimedOutAwaitingParts;
+					let event = events::Event::PartialPaymentFailed {
+						payment_hash,
+						received_msat,
+						expected_msat,
+						reason,
+					};
+					pending_events.push_back((event, None));
+				}
+			}
 			for htlc_source in timed_out_mpp_htlcs.drain(..) {
 				let source = HTLCSource::PreviousHopData(htlc_source.0.clone());
 				let failure_reason = LocalHTLCFailureReason::MPPTimeout;
@@ -7660,10 +10341,74 @@ where
 				self.fail_htlc_backwards_internal(&source, &htlc_source.1, &reason, receiver);
 			}
 
-			for (err, counterparty_node_id) in handle_errors {
-				let _ = handle_error!(self, err, counterparty_node_id);
+			should_persist
+		});
+	}
+
+	/// Fails back intercepted HTLCs which have been held for 360 calls without being forwarded or
+	/// failed.
+	///
+	/// This should be called roughly once per minute, such that intercepted HTLCs are failed back
+	/// after around six hours. Calling it less often holds the liquidity of the channels the HTLCs
+	/// were received over for longer. Intercepted HTLCs are still failed back once they approach
+	/// expiry regardless.
+	pub fn expire_stale_intercepted_htlcs(&self) {
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let mut should_persist = NotifyOption::SkipPersistNoEvents;
+			let mut timed_out_intercepts = Vec::new();
+			{
+				let intercepted_htlcs = self.pending_intercepted_htlcs.lock().unwrap();
+				let mut timer_ticks = self.intercepted_htlc_timer_ticks.lock().unwrap();
+				timer_ticks.retain(|intercept_id, _| intercepted_htlcs.contains_key(intercept_id));
+				for intercept_id in intercepted_htlcs.keys() {
+					let ticks = timer_ticks.entry(*intercept_id).or_insert(0);
+					*ticks += 1;
+					if *ticks >= INTERCEPTED_HTLC_TIMEOUT_TICKS {
+						timed_out_intercepts.push(*intercept_id);
+					}
+				}
+			}
+			for intercept_id in timed_out_intercepts {
+				if self.fail_intercepted_htlc_internal(intercept_id).is_ok() {
+					should_persist = NotifyOption::DoPersist;
+				}
+			}
+
+			should_persist
+		});
+	}
+
+	/// Forgets about channel negotiations restored from before we were reloaded once
+	/// [`UserConfig::pending_channel_negotiation_timeout_ticks`] elapsed.
+	fn expire_restored_channel_negotiations(&self) {
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let timeout_ticks =
+				self.default_configuration.pending_channel_negotiation_timeout_ticks;
+			let mut restored_negotiations = self.restored_channel_negotiations.lock().unwrap();
+			let num_restored_negotiations = restored_negotiations.len();
+			restored_negotiations.retain_mut(|restored| {
+				restored.timer_ticks = restored.timer_ticks.saturating_add(1);
+				restored.timer_ticks < timeout_ticks
+			});
+			if restored_negotiations.len() != num_restored_negotiations {
+				NotifyOption::DoPersist
+			} else {
+				NotifyOption::SkipPersistNoEvents
 			}
+		});
+	}
 
+	/// Forgets about stale outbound payments, either those that have already been fulfilled or
+	/// those awaiting an invoice that hasn't been delivered in the necessary amount of time. The
+	/// latter is determined using the system clock in `std` and the highest seen block time minus
+	/// two hours in non-`std`.
+	///
+	/// Fulfilled payments are only forgotten after a few calls to this method, during which
+	/// [`Self::send_payment`] with the same [`PaymentId`] is rejected. Thus, this must not be
+	/// called much more often than roughly once per minute to retain our idempotency guarantees.
+	/// Calling it less often only delays freeing the memory used for such payments.
+	pub fn remove_stale_outbound_payments(&self) {
+		PersistenceNotifierGuard::optionally_notify(self, || {
 			#[cfg(feature = "std")]
 			let duration_since_epoch = std::time::SystemTime::now()
 				.duration_since(std::time::SystemTime::UNIX_EPOCH)
@@ -7676,17 +10421,127 @@ where
 			self.pending_outbound_payments
 				.remove_stale_payments(duration_since_epoch, &self.pending_events);
 
+			NotifyOption::SkipPersistNoEvents
+		});
+	}
+
+	/// Checks whether no new best block has been seen for longer than
+	/// [`UserConfig::chain_sync_stale_threshold_secs`], e.g., because our chain source stopped
+	/// working, generating an [`Event::ChainSyncStale`] once each time this becomes the case.
+	///
+	/// While our view of the chain is stale, HTLC forwards are failed if
+	/// [`UserConfig::fail_htlc_forwards_while_chain_sync_stale`] is set. Forwarding resumes as soon
+	/// as a new best block is seen via [`chain::Listen`] or [`chain::Confirm`].
+	///
+	/// As this requires a local time source, it does nothing without the `std` feature.
+	pub fn check_chain_sync_staleness(&self) {
+		#[cfg(feature = "std")]
+		PersistenceNotifierGuard::optionally_notify(self, || {
+			let threshold =
+				Duration::from_secs(self.default_configuration.chain_sync_stale_threshold_secs);
+			let last_update = *self.last_best_block_update.lock().unwrap();
+			let last_update_age = Instant::now().duration_since(last_update);
+			if last_update_age <= threshold {
+				return NotifyOption::SkipPersistNoEvents;
+			}
+			if self.chain_sync_stale.swap(true, Ordering::AcqRel) {
+				return NotifyOption::SkipPersistNoEvents;
+			}
+
+			let last_block_hash = self.best_block.read().unwrap().block_hash;
+			log_warn!(
+				self.logger,
+				"No new best block seen for {} seconds, our view of the chain is stale at block {}",
+				last_update_age.as_secs(),
+				last_block_hash
+			);
+			let event = Event::ChainSyncStale {
+				last_block_hash,
+				last_update_age_secs: last_update_age.as_secs(),
+			};
+			self.pending_events.lock().unwrap().push_back((event, None));
+			NotifyOption::SkipPersistHandleEvents
+		});
+	}
+
+	/// Performs actions which should happen on startup and roughly once per minute thereafter.
+	///
+	/// This calls each of the following, which may instead be called individually at their own
+	/// frequencies, e.g., from different threads, as documented on each of them:
+	///  * [`Self::process_stale_feerate_updates`], increasing or decreasing the on-chain feerate
+	///    estimates for our outbound channels,
+	///  * [`Self::check_peer_timeouts`], aborting interactive funding transaction negotiations for
+	///    which our counterparty has not sent a message within
+	///    [`UserConfig::interactive_tx_negotiation_timeout_ticks`] and disconnecting unresponsive
+	///    peers,
+	///  * [`Self::process_channel_update_status`], broadcasting [`ChannelUpdate`] messages if we've
+	///    been disconnected from our peer for more than a few minutes, informing the network that
+	///    they should no longer attempt to route over the channel, and expiring a channel's
+	///    previous [`ChannelConfig`] if necessary to only allow forwarding HTLCs with the current
+	///    [`ChannelConfig`],
+	///  * [`Self::expire_stale_channels`], force-closing and removing channels which have not
+	///    completed establishment in a timely manner,
+	///  * [`Self::prune_disconnected_peers`], removing peers which have disconnected and no
+	///    longer have any channels,
+	///  * [`Self::expire_stale_inbound_payments`], failing back incomplete multi-part payments,
+	///  * [`Self::expire_stale_intercepted_htlcs`], failing back intercepted HTLCs which have not
+	///    been forwarded or failed in a timely manner,
+	///  * [`Self::remove_stale_outbound_payments`], forgetting about stale outbound payments,
+	///  * [`Self::check_chain_sync_staleness`], generating an [`Event::ChainSyncStale`] if no new
+	///    best block has been seen for too long.
+	///
+	/// Each of these may be called concurrently with any other [`ChannelManager`] method. Note
+	/// that the timeouts they implement are counted in calls to each method individually, rather
+	/// than in calls to this method.
+	///
+	/// Additionally, unless events are currently being processed, the pending events are checked
+	/// against [`UserConfig::pending_events_soft_limit`] in case the event handler stalls.
+	///
+	/// Note that this may cause reentrancy through [`chain::Watch::update_channel`] calls or feerate
+	/// estimate fetches.
+	///
+	/// [`ChannelUpdate`]: msgs::ChannelUpdate
+	/// [`ChannelConfig`]: crate::util::config::ChannelConfig
+	pub fn timer_tick_occurred(&self) {
+		self.process_stale_feerate_updates();
+		self.check_peer_timeouts();
+		self.process_channel_update_status();
+		self.expire_stale_channels();
+		self.expire_restored_channel_negotiations();
+		self.prune_disconnected_peers();
+		self.expire_stale_inbound_payments();
+		self.expire_stale_intercepted_htlcs();
+		self.remove_stale_outbound_payments();
+		self.check_chain_sync_staleness();
+
+		PersistenceNotifierGuard::optionally_notify(self, || {
 			#[cfg(async_payments)]
 			self.check_refresh_async_receive_offer_cache(true);
 
+			// Check the event queue against its limit here as well in case the event handler
+			// stalls, unless events are currently being processed.
+			let mut dropped_events = false;
+			if self
+				.pending_events_processor
+				.compare_exchange(false, true, Ordering::Acquire, Ordering::Relaxed)
+				.is_ok()
+			{
+				dropped_events = self.enforce_pending_events_limit();
+				self.pending_events_processor.store(false, Ordering::Release);
+			}
+
 			// Technically we don't need to do this here, but if we have holding cell entries in a
 			// channel that need freeing, it's better to do that here and block a background task
 			// than block the message queueing pipeline.
-			if self.check_free_holding_cells() {
-				should_persist = NotifyOption::DoPersist;
+			if self.check_free_holding_cells() || dropped_events {
+				NotifyOption::DoPersist
+			} else if !self.pending_events.lock().unwrap().is_empty() {
+				// An event processing attempt may have been skipped while we held
+				// `pending_events_processor` above, so make sure it is retried.
+				NotifyOption::SkipPersistHandleEvents
+			} else {
+				NotifyOption::SkipPersistNoEvents
 			}
-
-			should_persist
 		});
 	}
 
@@ -7734,12 +10589,10 @@ where
 		&self, failure_code: FailureCode, htlc: &ClaimableHTLC,
 	) -> HTLCFailReason {
 		match failure_code {
-			FailureCode::TemporaryNodeFailure => {
-				HTLCFailReason::from_failure_code(failure_code.into())
-			},
-			FailureCode::RequiredNodeFeatureMissing => {
-				HTLCFailReason::from_failure_code(failure_code.into())
-			},
+			FailureCode::TemporaryNodeFailure
+			| FailureCode::PermanentNodeFailure
+			| FailureCode::RequiredNodeFeatureMissing
+			| FailureCode::MppTimeout => HTLCFailReason::from_failure_code(failure_code.into()),
 			FailureCode::IncorrectOrUnknownPaymentDetails => {
 				let mut htlc_msat_height_data = htlc.value.to_be_bytes().to_vec();
 				htlc_msat_height_data
@@ -7753,6 +10606,14 @@ where
 				};
 				HTLCFailReason::reason(failure_code.into(), fail_data)
 			},
+			FailureCode::FinalIncorrectCltvExpiry => {
+				let cltv_expiry_data = htlc.cltv_expiry.to_be_bytes().to_vec();
+				HTLCFailReason::reason(failure_code.into(), cltv_expiry_data)
+			},
+			FailureCode::FinalIncorrectHtlcAmount => {
+				let htlc_msat_data = htlc.value.to_be_bytes().to_vec();
+				HTLCFailReason::reason(failure_code.into(), htlc_msat_data)
+			},
 		}
 	}
 
@@ -8982,6 +11843,351 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 		)
 	}
 
+	/// Accepts a request to open a channel after a [`Event::OpenChannelRequest`], as with
+	/// [`ChannelManager::accept_inbound_channel`], but first checks that the provided
+	/// `anchor_reserve` is sufficient to support an additional anchor channel if the requested
+	/// channel uses anchor outputs.
+	///
+	/// If the reserve is insufficient, an [`APIError::ChannelUnavailable`] is returned and the
+	/// request remains pending, allowing it to be rejected or accepted regardless by passing `None`
+	/// as `anchor_reserve`.
+	///
+	/// [`Event::OpenChannelRequest`]: events::Event::OpenChannelRequest
+	pub fn accept_inbound_channel_with_anchor_reserve(
+		&self, temporary_channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		user_channel_id: u128, config_overrides: Option<ChannelConfigOverrides>,
+		anchor_reserve: Option<&AnchorChannelReserveCheck>,
+	) -> Result<(), APIError> {
+		let channel_type =
+			self.pending_inbound_channel_type(temporary_channel_id, counterparty_node_id);
+		if let Some(channel_type) = channel_type {
+			if Self::lacks_anchor_reserve(&channel_type, anchor_reserve) {
+				return Err(APIError::ChannelUnavailable {
+					err: format!(
+						"Insufficient anchor channel reserve to accept channel {}",
+						temporary_channel_id
+					),
+				});
+			}
+		}
+		self.accept_inbound_channel(
+			temporary_channel_id,
+			counterparty_node_id,
+			user_channel_id,
+			config_overrides,
+		)
+	}
+
+	/// Returns the channel type of the pending inbound channel request with the given
+	/// `temporary_channel_id`, if any.
+	fn pending_inbound_channel_type(
+		&self, temporary_channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+	) -> Option<ChannelTypeFeatures> {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		let peer_state = per_peer_state.get(counterparty_node_id)?.lock().unwrap();
+		let request = peer_state.inbound_channel_request_by_id.get(temporary_channel_id)?;
+		let common_fields = match &request.open_channel_msg {
+			OpenChannelMessage::V1(msg) => &msg.common_fields,
+			OpenChannelMessage::V2(msg) => &msg.common_fields,
+		};
+		channel::channel_type_from_open_channel(common_fields, &self.channel_type_features()).ok()
+	}
+
+	/// Returns whether `channel_type` uses anchor outputs and `anchor_reserve` indicates that an
+	/// additional anchor channel can't be supported.
+	fn lacks_anchor_reserve(
+		channel_type: &ChannelTypeFeatures, anchor_reserve: Option<&AnchorChannelReserveCheck>,
+	) -> bool {
+		let is_anchor_channel = channel_type.supports_anchors_zero_fee_htlc_tx()
+			|| channel_type.supports_anchor_zero_fee_commitments();
+		is_anchor_channel
+			&& anchor_reserve
+				.map_or(false, |reserve| !reserve.can_support_additional_anchor_channel())
+	}
+
+	/// Sets the [`AnchorChannelReserveContext`] used to estimate the reserve required for inbound
+	/// anchor channels, as provided in [`Event::OpenChannelRequest::estimated_reserve_required`],
+	/// and for existing anchor channels, as provided in
+	/// [`ChannelDetails::exogenous_fee_reserve_recommended_sat`].
+	///
+	/// If not set, [`AnchorChannelReserveContext::for_network`] is used for the network the
+	/// [`ChannelManager`] operates on. This is not persisted and needs to be set again after
+	/// reloading the [`ChannelManager`], e.g., after reading it from
+	/// [`ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY`].
+	///
+	/// [`Event::OpenChannelRequest::estimated_reserve_required`]: events::Event::OpenChannelRequest::estimated_reserve_required
+	/// [`ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY`]: crate::util::persist::ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY
+	pub fn set_anchor_channel_reserve_context(&self, context: AnchorChannelReserveContext) {
+		*self.anchor_channel_reserve_context.lock().unwrap() = context;
+	}
+
+	/// Sets the [`InboundChannelAcceptor`] consulted on inbound channel open requests when
+	/// [`UserConfig::manually_accept_inbound_channels`] is not set, or removes it if `None`.
+	///
+	/// Without an acceptor, inbound channels are accepted immediately unless they use anchor
+	/// outputs or require zero confirmations. This is not persisted and needs to be set again after
+	/// reloading the [`ChannelManager`].
+	///
+	/// If the acceptor provides an [`InboundChannelAcceptor::anchor_channel_reserve_context`], it
+	/// is checked via [`validate_against_config`] and any contradictions with our configuration
+	/// are logged as warnings.
+	pub fn set_inbound_channel_acceptor(
+		&self, acceptor: Option<Arc<dyn InboundChannelAcceptor + Send + Sync>>,
+	) {
+		if let Some(context) = acceptor.as_ref().and_then(|a| a.anchor_channel_reserve_context()) {
+			let res = validate_against_config(
+				context,
+				&self.default_configuration,
+				&self.fee_estimator.0,
+				true,
+			);
+			for warning in res.err().unwrap_or_default() {
+				log_warn!(
+					self.logger,
+					"Anchor channel reserve context contradicts our configuration: {:?}",
+					warning
+				);
+			}
+		}
+		*self.inbound_channel_acceptor.lock().unwrap() = acceptor;
+	}
+
+	/// Sets the [`CloseAuthorizer`] whose signature is required to cooperatively close channels
+	/// with a value above its threshold, or removes it if `None`.
+	///
+	/// While set, such channels can only be closed via [`Self::close_channel_with_authorization`],
+	/// e.g., such that a service managing the node on our behalf requires an offline approval for
+	/// closing large channels. Force-closes are not affected.
+	///
+	/// The authorizer is not persisted and thus needs to be set again on startup. The nonce of the
+	/// last used authorization is persisted, such that authorizations can't be replayed after a
+	/// restart.
+	pub fn set_close_authorizer(&self, authorizer: Option<CloseAuthorizer>) {
+		*self.close_authorizer.lock().unwrap() = authorizer;
+	}
+
+	/// Enables or disables negotiating channel types with anchor outputs, i.e.,
+	/// `option_anchors_zero_fee_htlc_tx` and `option_zero_fee_commitments`, for channels which are
+	/// subsequently created via [`Self::create_channel`] or accepted from our peers.
+	///
+	/// While disabled, our outbound channels are proposed without anchor outputs, as if
+	/// [`ChannelHandshakeConfig::negotiate_anchors_zero_fee_htlc_tx`] was unset in both the default
+	/// and any override config, and inbound channels with anchor outputs are rejected, allowing
+	/// the counterparty to retry without them. This is useful to stop opening anchor channels once
+	/// the on-chain reserve required to close them can no longer be afforded, without rebuilding
+	/// our configuration or restarting. As we keep advertising support for anchor outputs to our
+	/// peers, their proposals of anchor channels are still rejected rather than avoided.
+	///
+	/// Existing channels are not affected. Negotiation is enabled on startup, and this is not
+	/// persisted, i.e., it needs to be disabled again after reloading the [`ChannelManager`].
+	///
+	/// [`ChannelHandshakeConfig::negotiate_anchors_zero_fee_htlc_tx`]: crate::util::config::ChannelHandshakeConfig::negotiate_anchors_zero_fee_htlc_tx
+	pub fn set_anchor_negotiation_enabled(&self, enabled: bool) {
+		self.anchor_negotiation_enabled.store(enabled, Ordering::Release);
+	}
+
+	/// Returns whether channel types with anchor outputs are negotiated for new channels, see
+	/// [`Self::set_anchor_negotiation_enabled`].
+	pub fn is_anchor_negotiation_enabled(&self) -> bool {
+		self.anchor_negotiation_enabled.load(Ordering::Acquire)
+	}
+
+	/// Returns `config` with the negotiation of anchor outputs unset if it was disabled via
+	/// [`Self::set_anchor_negotiation_enabled`].
+	fn config_for_anchor_negotiation(&self, config: &UserConfig) -> UserConfig {
+		let mut config = config.clone();
+		if !self.is_anchor_negotiation_enabled() {
+			config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = false;
+			#[cfg(test)]
+			{
+				config.channel_handshake_config.negotiate_anchor_zero_fee_commitments = false;
+			}
+		}
+		config
+	}
+
+	/// Returns the channel type features we support for inbound channels, i.e., those of
+	/// [`Self::channel_type_features`] without anchor outputs if their negotiation was disabled via
+	/// [`Self::set_anchor_negotiation_enabled`].
+	fn inbound_channel_type_features(&self) -> ChannelTypeFeatures {
+		let mut features = self.channel_type_features();
+		if !self.is_anchor_negotiation_enabled() {
+			features.clear_anchors_zero_fee_htlc_tx();
+			features.clear_anchor_zero_fee_commitments();
+		}
+		features
+	}
+
+	/// Sets the [`chain::Filter`] with which the funding output of a channel is registered as soon
+	/// as it is known, or removes it if `None`.
+	///
+	/// This is the case once the funding transaction is provided via
+	/// [`Self::funding_transaction_generated`] for outbound channels and once the counterparty's
+	/// `funding_created` message is received for inbound channels. Otherwise, the funding output is
+	/// only registered once the [`ChannelMonitor`] is handed to the [`chain::Watch`], which may be
+	/// too late for block sources relying on compact block filters.
+	///
+	/// This is not persisted and needs to be set again after reloading the [`ChannelManager`],
+	/// after which the outputs returned by [`Self::list_pending_funding_outputs`] should be
+	/// registered again.
+	pub fn set_chain_filter(&self, filter: Option<Arc<dyn chain::Filter + Send + Sync>>) {
+		*self.chain_filter.lock().unwrap() = filter;
+	}
+
+	/// Registers the funding output of the given funding with the [`chain::Filter`] set via
+	/// [`Self::set_chain_filter`], if any.
+	fn register_funding_output_with_filter(&self, funding: &channel::FundingScope) {
+		let filter = self.chain_filter.lock().unwrap().clone();
+		if let (Some(filter), Some(output)) = (filter, Self::pending_funding_output(funding)) {
+			filter.register_tx(&output.outpoint.txid, &output.script_pubkey);
+			filter.register_output(output);
+		}
+	}
+
+	fn pending_funding_output(funding: &channel::FundingScope) -> Option<WatchedOutput> {
+		if funding.get_funding_tx_confirmation_height().is_some() {
+			return None;
+		}
+		funding.get_funding_txo().map(|outpoint| WatchedOutput {
+			block_hash: None,
+			outpoint,
+			script_pubkey: funding.get_funding_redeemscript().to_p2wsh(),
+		})
+	}
+
+	/// Starts replicating the state of our channels to the given [`ReplicationSink`] as the
+	/// primary in the given `epoch`, or stops replicating if `sink` is `None`.
+	///
+	/// The `epoch` must be greater than any epoch used by a primary before, including any epoch
+	/// of the deltas applied via [`Self::apply_channel_state_delta`], as otherwise
+	/// [`ReplicationError::Fenced`] is returned. Upon registration, all funded channels are
+	/// replicated, after which a [`ChannelStateDelta`] is emitted for each channel whose state
+	/// changed whenever the [`ChannelManager`] needs to be persisted. Note that this requires
+	/// serializing all funded channels at that point.
+	///
+	/// If the sink returns [`ReplicationError::Fenced`], we stop replicating and consider ourselves
+	/// fenced, see [`Self::is_replication_fenced`].
+	///
+	/// This is not persisted and needs to be set again, with a new epoch, after reloading the
+	/// [`ChannelManager`]. See the [`channel_replication`] module for more details.
+	///
+	/// [`channel_replication`]: crate::ln::channel_replication
+	#[cfg(any(test, feature = "channel_state_replication"))]
+	pub fn set_replication_sink(
+		&self, sink: Option<Arc<dyn ReplicationSink + Send + Sync>>, epoch: u64,
+	) -> Result<(), ReplicationError> {
+		let _consistency_lock = self.total_consistency_lock.read().unwrap();
+		self.channel_replication.lock().unwrap().set_sink(sink, epoch)?;
+		self.replicate_channel_states()
+	}
+
+	/// Applies a [`ChannelStateDelta`] emitted by the [`ReplicationSink`] of a primary, replacing
+	/// or removing our copy of the channel.
+	///
+	/// Deltas must be applied in the order they were emitted, as otherwise
+	/// [`ReplicationError::OutOfOrder`] is returned. Deltas of an epoch lower than one we've
+	/// already seen, or of our own epoch if we're the primary, are rejected with
+	/// [`ReplicationError::Fenced`]. If we're the primary and a delta of a later epoch is applied,
+	/// another node took over and we are fenced, see [`Self::is_replication_fenced`].
+	///
+	/// This is intended to be called on a standby which isn't connected to any peers. Note that
+	/// channels are not checked against their [`ChannelMonitor`]s here, thus the standby should be
+	/// serialized and read again with its up-to-date [`ChannelMonitor`]s before taking over.
+	#[cfg(any(test, feature = "channel_state_replication"))]
+	pub fn apply_channel_state_delta(
+		&self, delta: &ChannelStateDelta,
+	) -> Result<(), ReplicationError> {
+		let _persistence_guard = PersistenceNotifierGuard::notify_on_drop(self);
+
+		let mut per_peer_state = self.per_peer_state.write().unwrap();
+		let mut replication = self.channel_replication.lock().unwrap();
+		replication.check_delta(delta)?;
+
+		let channel = match &delta.serialized_channel {
+			Some(serialized_channel) => {
+				let channel: FundedChannel<SP> = FundedChannel::read(
+					&mut &serialized_channel[..],
+					(
+						&self.entropy_source,
+						&self.signer_provider,
+						&provided_channel_type_features(&self.default_configuration),
+					),
+				)
+				.map_err(|_| ReplicationError::InvalidChannelState)?;
+				if channel.context.channel_id() != delta.channel_id
+					|| channel.context.get_counterparty_node_id() != delta.counterparty_node_id
+				{
+					return Err(ReplicationError::InvalidChannelState);
+				}
+				Some(channel)
+			},
+			None => None,
+		};
+
+		let peer_state_mutex =
+			per_peer_state.entry(delta.counterparty_node_id).or_insert_with(|| {
+				Mutex::new(PeerState {
+					channel_by_id: new_hash_map(),
+					inbound_channel_request_by_id: new_hash_map(),
+					latest_features: InitFeatures::empty(),
+					pending_msg_events: Vec::new(),
+					in_flight_monitor_updates: BTreeMap::new(),
+					monitor_update_blocked_actions: BTreeMap::new(),
+					actions_blocking_raa_monitor_updates: BTreeMap::new(),
+					closed_channel_monitor_update_ids: BTreeMap::new(),
+					is_connected: false,
+					peer_storage: Vec::new(),
+				})
+			});
+		let mut peer_state = peer_state_mutex.lock().unwrap();
+		let mut short_to_chan_info = self.short_to_chan_info.write().unwrap();
+		let mut outbound_scid_aliases = self.outbound_scid_aliases.lock().unwrap();
+
+		let removed_channel = peer_state.channel_by_id.remove(&delta.channel_id);
+		if let Some(removed_channel) = removed_channel.as_ref().and_then(Channel::as_funded) {
+			if let Some(short_channel_id) = removed_channel.funding.get_short_channel_id() {
+				short_to_chan_info.remove(&short_channel_id);
+			}
+			for short_channel_id in removed_channel.context.historical_scids() {
+				short_to_chan_info.remove(short_channel_id);
+			}
+			let outbound_scid_alias = removed_channel.context.outbound_scid_alias();
+			short_to_chan_info.remove(&outbound_scid_alias);
+			outbound_scid_aliases.remove(&outbound_scid_alias);
+		}
+
+		if let Some(channel) = channel {
+			let cp_id = channel.context.get_counterparty_node_id();
+			let chan_id = channel.context.channel_id();
+			if let Some(short_channel_id) = channel.funding.get_short_channel_id() {
+				short_to_chan_info.insert(short_channel_id, (cp_id, chan_id));
+			}
+			for short_channel_id in channel.context.historical_scids() {
+				short_to_chan_info.insert(*short_channel_id, (cp_id, chan_id));
+			}
+			let outbound_scid_alias = channel.context.outbound_scid_alias();
+			outbound_scid_aliases.insert(outbound_scid_alias);
+			if channel.context.is_usable() {
+				short_to_chan_info.insert(outbound_scid_alias, (cp_id, chan_id));
+			}
+			peer_state.channel_by_id.insert(chan_id, Channel::from(channel));
+		}
+
+		replication.delta_applied(delta);
+		Ok(())
+	}
+
+	/// Returns whether another node took over as the primary, as learned via the
+	/// [`ReplicationSink`] registered via [`Self::set_replication_sink`] or via
+	/// [`Self::apply_channel_state_delta`].
+	///
+	/// Once fenced, we no longer replicate our channels, drop any outbound messages, and refuse new
+	/// peer connections. A fenced [`ChannelManager`] should be shut down.
+	#[cfg(any(test, feature = "channel_state_replication"))]
+	pub fn is_replication_fenced(&self) -> bool {
+		self.channel_replication.lock().unwrap().is_fenced()
+	}
+
 	/// Accepts a request to open a channel after a [`events::Event::OpenChannelRequest`], treating
 	/// it as confirmed immediately.
 	///
@@ -9013,6 +12219,78 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 		)
 	}
 
+	/// Accepts a request to open a channel after a [`Event::OpenChannelRequest`] as zero-conf, as
+	/// with [`ChannelManager::accept_inbound_channel_from_trusted_peer_0conf`], but first checks
+	/// that the provided `anchor_reserve` is sufficient to support an additional anchor channel if
+	/// the requested channel uses anchor outputs.
+	///
+	/// As we can't wait for the funding transaction to confirm before relying on the channel,
+	/// accepting a zero-conf anchor channel without the reserve to bump its commitment transaction
+	/// risks funds immediately. If the reserve is insufficient, `insufficient_reserve_action`
+	/// determines whether the channel is accepted with the usual confirmation requirements
+	/// instead, or rejected. Channels whose type requires zero confirmations are always rejected
+	/// in that case. Upon rejection, an error is sent to the counterparty and
+	/// [`APIError::InsufficientAnchorReserve`] is returned, allowing the refusal to be reported,
+	/// e.g., by asking the user to deposit more on-chain funds.
+	///
+	/// Returns whether the channel was accepted as zero-conf.
+	///
+	/// [`Event::OpenChannelRequest`]: events::Event::OpenChannelRequest
+	pub fn accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve(
+		&self, temporary_channel_id: &ChannelId, counterparty_node_id: &PublicKey,
+		user_channel_id: u128, config_overrides: Option<ChannelConfigOverrides>,
+		anchor_reserve: Option<&AnchorChannelReserveCheck>,
+		insufficient_reserve_action: ZeroConfInsufficientReserveAction,
+	) -> Result<bool, APIError> {
+		let channel_type =
+			self.pending_inbound_channel_type(temporary_channel_id, counterparty_node_id);
+		let channel_type = match channel_type {
+			Some(channel_type) if Self::lacks_anchor_reserve(&channel_type, anchor_reserve) => {
+				channel_type
+			},
+			_ => {
+				self.accept_inbound_channel_from_trusted_peer_0conf(
+					temporary_channel_id,
+					counterparty_node_id,
+					user_channel_id,
+					config_overrides,
+				)?;
+				return Ok(true);
+			},
+		};
+
+		let logger = WithContext::from(
+			&self.logger,
+			Some(*counterparty_node_id),
+			Some(*temporary_channel_id),
+			None,
+		);
+		if insufficient_reserve_action == ZeroConfInsufficientReserveAction::RequireConfirmations
+			&& !channel_type.requires_zero_conf()
+		{
+			log_info!(
+				logger,
+				"Insufficient anchor channel reserve to accept channel {} as zero-conf, requiring confirmations instead",
+				temporary_channel_id
+			);
+			self.accept_inbound_channel(
+				temporary_channel_id,
+				counterparty_node_id,
+				user_channel_id,
+				config_overrides,
+			)?;
+			return Ok(false);
+		}
+
+		let err = format!(
+			"Insufficient anchor channel reserve to accept zero-conf channel {}",
+			temporary_channel_id
+		);
+		log_info!(logger, "{}", err);
+		self.force_close_sending_error(temporary_channel_id, counterparty_node_id, err.clone())?;
+		Err(APIError::InsufficientAnchorReserve { err })
+	}
+
 	/// TODO(dual_funding): Allow contributions, pass intended amount and inputs
 	#[rustfmt::skip]
 	fn do_accept_inbound_channel(
@@ -9055,7 +12333,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 					OpenChannelMessage::V1(open_channel_msg) => {
 						InboundV1Channel::new(
 							&self.fee_estimator, &self.entropy_source, &self.signer_provider, *counterparty_node_id,
-							&self.channel_type_features(), &peer_state.latest_features, &open_channel_msg,
+							&self.inbound_channel_type_features(), &peer_state.latest_features, &open_channel_msg,
 							user_channel_id, &config, best_block_height, &self.logger, accept_0conf
 						).map_err(|err| MsgHandleErrInternal::from_chan_no_close(err, *temporary_channel_id)
 						).map(|mut channel| {
@@ -9073,7 +12351,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 						PendingV2Channel::new_inbound(
 							&self.fee_estimator, &self.entropy_source, &self.signer_provider,
 							self.get_our_node_id(), *counterparty_node_id,
-							&self.channel_type_features(), &peer_state.latest_features,
+							&self.inbound_channel_type_features(), &peer_state.latest_features,
 							&open_channel_msg,
 							user_channel_id, &config, best_block_height,
 							&self.logger,
@@ -9250,6 +12528,43 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 				 common_fields.temporary_channel_id));
 		}
 
+		// We can get the channel type at this point already as we'll need it immediately in both the
+		// manual and the automatic acceptance cases.
+		let channel_type = channel::channel_type_from_open_channel(
+			common_fields, &self.inbound_channel_type_features()
+		).map_err(|e| MsgHandleErrInternal::from_chan_no_close(e, common_fields.temporary_channel_id))?;
+		let channel_negotiation_type = match msg {
+			OpenChannelMessageRef::V1(msg) => InboundChannelFunds::PushMsat(msg.push_msat),
+			OpenChannelMessageRef::V2(_) => InboundChannelFunds::DualFunded,
+		};
+		let is_announced = (common_fields.channel_flags & 1) == 1;
+
+		// Consult the registered acceptor, if any, before taking any peer locks as it is handed the
+		// list of our channels.
+		let mut manually_accept = self.default_configuration.manually_accept_inbound_channels;
+		let mut accepted_by_acceptor = false;
+		let acceptor = self.inbound_channel_acceptor.lock().unwrap().clone();
+		if let Some(acceptor) = acceptor.filter(|_| !manually_accept) {
+			let proposal = InboundChannelProposal {
+				temporary_channel_id: common_fields.temporary_channel_id,
+				counterparty_node_id: *counterparty_node_id,
+				funding_satoshis: common_fields.funding_satoshis,
+				channel_negotiation_type: channel_negotiation_type.clone(),
+				channel_type: channel_type.clone(),
+				is_announced,
+				params: common_fields.channel_parameters(),
+			};
+			match acceptor.decide_inbound_channel(&proposal, &self.list_channels()) {
+				InboundChannelDecision::Accept => accepted_by_acceptor = true,
+				InboundChannelDecision::Reject(reason) => {
+					let reason = sanitize_channel_rejection_reason(&reason);
+					return Err(MsgHandleErrInternal::send_err_msg_no_close(reason,
+						common_fields.temporary_channel_id));
+				},
+				InboundChannelDecision::DeferToManual => manually_accept = true,
+			}
+		}
+
 		// Get the number of peers with channels, but without funded ones. We don't care too much
 		// about peers that never open a channel, so we filter by peers that have at least one
 		// channel, and then limit the number of those with unfunded channels.
@@ -9272,7 +12587,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 		// channels per-peer we can accept channels from a peer with existing ones.
 		if peer_state.total_channel_count() == 0 &&
 			channeled_peers_without_funding >= MAX_UNFUNDED_CHANNEL_PEERS &&
-			!self.default_configuration.manually_accept_inbound_channels
+			!manually_accept
 		{
 			return Err(MsgHandleErrInternal::send_err_msg_no_close(
 				"Have too many peers with unfunded channels, not accepting new ones".to_owned(),
@@ -9294,27 +12609,21 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 				common_fields.temporary_channel_id));
 		}
 
-		// We can get the channel type at this point already as we'll need it immediately in both the
-		// manual and the automatic acceptance cases.
-		let channel_type = channel::channel_type_from_open_channel(
-			common_fields, &self.channel_type_features()
-		).map_err(|e| MsgHandleErrInternal::from_chan_no_close(e, common_fields.temporary_channel_id))?;
-
 		// If we're doing manual acceptance checks on the channel, then defer creation until we're sure we want to accept.
-		if self.default_configuration.manually_accept_inbound_channels {
+		if manually_accept {
+			let estimated_reserve_required = (channel_type.supports_anchors_zero_fee_htlc_tx()
+				|| channel_type.supports_anchor_zero_fee_commitments())
+				.then(|| get_reserve_per_channel(&self.anchor_channel_reserve_context.lock().unwrap()));
 			let mut pending_events = self.pending_events.lock().unwrap();
-			let is_announced = (common_fields.channel_flags & 1) == 1;
 			pending_events.push_back((events::Event::OpenChannelRequest {
 				temporary_channel_id: common_fields.temporary_channel_id,
 				counterparty_node_id: *counterparty_node_id,
 				funding_satoshis: common_fields.funding_satoshis,
-				channel_negotiation_type: match msg {
-					OpenChannelMessageRef::V1(msg) => InboundChannelFunds::PushMsat(msg.push_msat),
-					OpenChannelMessageRef::V2(_) => InboundChannelFunds::DualFunded,
-				},
+				channel_negotiation_type,
 				channel_type,
 				is_announced,
 				params: common_fields.channel_parameters(),
+				estimated_reserve_required,
 			}, None));
 			peer_state.inbound_channel_request_by_id.insert(channel_id, InboundChannelRequest {
 				open_channel_msg: match msg {
@@ -9334,7 +12643,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 		if channel_type.requires_zero_conf() {
 			return Err(MsgHandleErrInternal::send_err_msg_no_close("No zero confirmation channels accepted".to_owned(), common_fields.temporary_channel_id));
 		}
-		if channel_type.requires_anchors_zero_fee_htlc_tx() || channel_type.requires_anchor_zero_fee_commitments() {
+		if !accepted_by_acceptor && (channel_type.requires_anchors_zero_fee_htlc_tx() || channel_type.requires_anchor_zero_fee_commitments()) {
 			return Err(MsgHandleErrInternal::send_err_msg_no_close("No channels with anchor outputs accepted".to_owned(), common_fields.temporary_channel_id));
 		}
 
@@ -9342,7 +12651,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 			OpenChannelMessageRef::V1(msg) => {
 				let mut channel = InboundV1Channel::new(
 					&self.fee_estimator, &self.entropy_source, &self.signer_provider, *counterparty_node_id,
-					&self.channel_type_features(), &peer_state.latest_features, msg, user_channel_id,
+					&self.inbound_channel_type_features(), &peer_state.latest_features, msg, user_channel_id,
 					&self.default_configuration, best_block_height, &self.logger, /*is_0conf=*/false
 				).map_err(|e| MsgHandleErrInternal::from_chan_no_close(e, msg.common_fields.temporary_channel_id))?;
 				let logger = WithChannelContext::from(&self.logger, &channel.context, None);
@@ -9357,7 +12666,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 			OpenChannelMessageRef::V2(msg) => {
 				let channel = PendingV2Channel::new_inbound(
 					&self.fee_estimator, &self.entropy_source, &self.signer_provider,
-					self.get_our_node_id(), *counterparty_node_id, &self.channel_type_features(),
+					self.get_our_node_id(), *counterparty_node_id, &self.inbound_channel_type_features(),
 					&peer_state.latest_features, msg, user_channel_id,
 					&self.default_configuration, best_block_height, &self.logger,
 				).map_err(|e| MsgHandleErrInternal::from_chan_no_close(e, msg.common_fields.temporary_channel_id))?;
@@ -9477,6 +12786,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 				fail_chan!("Already had channel with the new channel_id");
 			},
 			hash_map::Entry::Vacant(e) => {
+				self.register_funding_output_with_filter(&chan.funding);
 				let monitor_res = self.chain_monitor.watch_channel(monitor.channel_id(), monitor);
 				if let Ok(persist_state) = monitor_res {
 					// There's no problem signing a counterparty's funding transaction if our monitor
@@ -9641,6 +12951,9 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 						return Err(MsgHandleErrInternal::from_chan_no_close(err, channel_id))
 					},
 				};
+				if let MessageSendEvent::SendTxAbort { .. } = msg_send_event {
+					self.abort_interactive_tx_negotiation(channel);
+				}
 				peer_state.pending_msg_events.push(msg_send_event);
 				Ok(())
 			},
@@ -9746,6 +13059,9 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 					},
 				};
 				if let Some(msg_send_event) = msg_send_event_opt {
+					if let MessageSendEvent::SendTxAbort { .. } = msg_send_event {
+						self.abort_interactive_tx_negotiation(chan_entry.get_mut());
+					}
 					peer_state.pending_msg_events.push(msg_send_event);
 				};
 				if negotiation_complete {
@@ -9858,31 +13174,18 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 		let peer_state = &mut *peer_state_lock;
 		match peer_state.channel_by_id.entry(msg.channel_id) {
 			hash_map::Entry::Occupied(mut chan_entry) => {
-				let tx_constructor = match chan_entry.get_mut().as_unfunded_v2_mut() {
-					Some(chan) => &mut chan.interactive_tx_constructor,
-					None => if chan_entry.get().is_funded() {
-						// TODO(splicing)/TODO(RBF): We'll also be doing interactive tx construction
-						// for a "Channel::Funded" when we want to bump the fee on an interactively
-						// constructed funding tx or during splicing. For now we send an error as we would
-						// never ack an RBF attempt or a splice for now:
-						try_channel_entry!(self, peer_state, Err(ChannelError::Warn(
-							"Got an unexpected tx_abort message: After initial funding transaction is signed, \
-							splicing and RBF attempts of interactive funding transactions are not supported yet so \
-							we don't have any negotiation in progress".into(),
-						)), chan_entry)
-					} else {
-						try_channel_entry!(self, peer_state, Err(ChannelError::Warn(
-							"Got an unexpected tx_abort message: This is an unfunded channel created with V1 channel \
-							establishment".into(),
-						)), chan_entry)
-					},
-				};
+				if chan_entry.get().is_unfunded_v1() {
+					try_channel_entry!(self, peer_state, Err(ChannelError::Warn(
+						"Got an unexpected tx_abort message: This is an unfunded channel created with V1 channel \
+						establishment".into(),
+					)), chan_entry)
+				}
 				// This checks for and resets the interactive negotiation state by `take()`ing it from the channel.
 				// The existence of the `tx_constructor` indicates that we have not moved into the signing
 				// phase for this interactively constructed transaction and hence we have not exchanged
 				// `tx_signatures`. Either way, we never close the channel upon receiving a `tx_abort`:
 				//   https://github.com/lightning/bolts/blob/247e83d/02-peer-protocol.md?plain=1#L574-L576
-				if tx_constructor.take().is_some() {
+				if self.abort_interactive_tx_negotiation(chan_entry.get_mut()) {
 					let msg = msgs::TxAbort {
 						channel_id: msg.channel_id,
 						data: "Acknowledged tx_abort".to_string().into_bytes(),
@@ -9897,6 +13200,16 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 						node_id: *counterparty_node_id,
 						msg,
 					});
+				} else if chan_entry.get().is_funded() && !cfg!(splicing) {
+					// TODO(RBF): We'll also be doing interactive tx construction for a "Channel::Funded"
+					// when we want to bump the fee on an interactively constructed funding tx. Without
+					// splicing, we send an error as we would never ack an RBF attempt or a splice. With
+					// splicing, this may be the counterparty acknowledging a `tx_abort` we sent.
+					try_channel_entry!(self, peer_state, Err(ChannelError::Warn(
+						"Got an unexpected tx_abort message: After initial funding transaction is signed, \
+						splicing and RBF attempts of interactive funding transactions are not supported yet so \
+						we don't have any negotiation in progress".into(),
+					)), chan_entry)
 				}
 				Ok(())
 			},
@@ -10093,6 +13406,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 							// also implies there are no pending HTLCs left on the channel, so we can
 							// fully delete it from tracking (the channel monitor is still around to
 							// watch for old state broadcasts)!
+							self.track_cooperative_close(chan, &tx);
 							let err = convert_channel_err!(self, peer_state, close_res, chan, COOP_CLOSED);
 							chan_entry.remove();
 							Some((tx, Err(err)))
@@ -10324,6 +13638,9 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 							try_channel_entry!(self, peer_state, Err(err), chan_entry)
 						}
 					} else if let Some(monitor_update) = monitor_update_opt {
+						self.record_channel_balance(chan);
+						self.record_accepted_htlcs(chan);
+						self.check_liquidity_thresholds(chan);
 						handle_new_monitor_update!(self, funding_txo.unwrap(), monitor_update, peer_state_lock,
 							peer_state, per_peer_state, chan);
 					}
@@ -10600,6 +13917,9 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 						} else { false };
 						let (htlcs_to_fail, monitor_update_opt) = try_channel_entry!(self, peer_state,
 							chan.revoke_and_ack(&msg, &self.fee_estimator, &&logger, mon_update_blocked), chan_entry);
+						self.record_channel_balance(chan);
+						self.record_accepted_htlcs(chan);
+						self.check_liquidity_thresholds(chan);
 						if let Some(monitor_update) = monitor_update_opt {
 							let funding_txo = funding_txo_opt
 								.expect("Funding outpoint must have been set for RAA handling to succeed");
@@ -10809,9 +14129,21 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 						// disconnect, so Channel's reestablish will never hand us any holding cell
 						// freed HTLCs to fail backwards. If in the future we no longer drop pending
 						// add-HTLCs on disconnect, we may be handed HTLCs to fail backwards here.
-						let responses = try_channel_entry!(self, peer_state, chan.channel_reestablish(
+						let had_local_data_loss = chan.context.local_data_loss().is_some();
+						let res = chan.channel_reestablish(
 							msg, &&logger, &self.node_signer, self.chain_hash,
-							&self.default_configuration, &*self.best_block.read().unwrap()), chan_entry);
+							&self.default_configuration, &*self.best_block.read().unwrap());
+						if let (false, Some((our_commitment_number, their_claimed_number))) =
+							(had_local_data_loss, chan.context.local_data_loss())
+						{
+							self.pending_events.lock().unwrap().push_back((events::Event::LocalDataLossDetected {
+								channel_id: msg.channel_id,
+								counterparty_node_id: *counterparty_node_id,
+								our_commitment_number,
+								their_claimed_number,
+							}, None));
+						}
+						let responses = try_channel_entry!(self, peer_state, res, chan_entry);
 						let mut channel_update = None;
 						if let Some(msg) = responses.shutdown_msg {
 							peer_state.pending_msg_events.push(MessageSendEvent::SendShutdown {
@@ -11019,6 +14351,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 								funding_txo: Some(
 									splice_promotion.funding_txo.into_bitcoin_outpoint(),
 								),
+								channel_value_satoshis: Some(chan.funding.get_value_satoshis()),
 								channel_type: chan.funding.get_channel_type().clone(),
 							},
 							None,
@@ -11306,6 +14639,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 						send_channel_ready!(self, pending_msg_events, funded_chan, msg);
 					}
 					if let Some(broadcast_tx) = msgs.signed_closing_tx {
+						self.track_cooperative_close(funded_chan, &broadcast_tx);
 						log_info!(logger, "Broadcasting closing tx {}", log_tx!(broadcast_tx));
 						self.tx_broadcaster.broadcast_transactions(&[&broadcast_tx]);
 					}
@@ -11395,6 +14729,7 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 									if let Some((tx, shutdown_res)) = tx_shutdown_result_opt {
 										// We're done with this channel. We got a closing_signed and sent back
 										// a closing_signed with a closing transaction to broadcast.
+										self.track_cooperative_close(funded_chan, &tx);
 										let err = convert_channel_err!(self, peer_state, shutdown_res, funded_chan, COOP_CLOSED);
 										handle_errors.push((*cp_id, Err(err)));
 
@@ -11543,10 +14878,41 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 	/// storing any additional state. It achieves this by including a [`PaymentSecret`] in the
 	/// invoice which it uses to verify that the invoice has not expired and the payment amount is
 	/// sufficient, reproducing the [`PaymentPreimage`] if applicable.
-	#[rustfmt::skip]
 	pub fn create_bolt11_invoice(
 		&self, params: Bolt11InvoiceParameters,
 	) -> Result<Bolt11Invoice, SignOrCreationError<()>> {
+		self.create_bolt11_invoice_internal(params, |_| Ok(Vec::new()))
+	}
+
+	/// Creates a BOLT11 invoice like [`Self::create_bolt11_invoice`], additionally including a
+	/// route hint for a just-in-time channel through the intercept SCID of the given
+	/// [`JitChannelHint`].
+	///
+	/// If `hint` is no longer valid by the time the invoice expires or doesn't accept the invoice
+	/// amount, `refresh_hint` is called to request a fresh hint, e.g., by requesting a new
+	/// intercept SCID from the LSP. If no suitable hint is available, the invoice is not created
+	/// and [`CreationError::RouteHintExpired`] or [`CreationError::InvalidAmount`] is returned.
+	pub fn create_bolt11_invoice_with_jit_channel_hint<F>(
+		&self, params: Bolt11InvoiceParameters, hint: JitChannelHint, refresh_hint: F,
+	) -> Result<Bolt11Invoice, SignOrCreationError<()>>
+	where
+		F: FnOnce(&JitChannelHint) -> Option<JitChannelHint>,
+	{
+		let amount_msats = params.amount_msats;
+		self.create_bolt11_invoice_internal(params, |invoice_expires_at| {
+			let hint =
+				select_jit_channel_hint(hint, refresh_hint, invoice_expires_at, amount_msats)?;
+			Ok(vec![hint.route_hint()])
+		})
+	}
+
+	#[rustfmt::skip]
+	fn create_bolt11_invoice_internal<F>(
+		&self, params: Bolt11InvoiceParameters, extra_route_hints: F,
+	) -> Result<Bolt11Invoice, SignOrCreationError<()>>
+	where
+		F: FnOnce(Duration) -> Result<Vec<RouteHint>, CreationError>,
+	{
 		let Bolt11InvoiceParameters {
 			amount_msats, description, invoice_expiry_delta_secs, min_final_cltv_expiry_delta,
 			payment_hash,
@@ -11575,6 +14941,12 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 			}
 		}
 
+		let invoice_expires_at = duration_since_epoch.saturating_add(Duration::from_secs(
+			invoice_expiry_delta_secs.unwrap_or(DEFAULT_EXPIRY_TIME as u32).into()
+		));
+		let extra_route_hints = extra_route_hints(invoice_expires_at)
+			.map_err(|e| SignOrCreationError::CreationError(e))?;
+
 		let (payment_hash, payment_secret) = match payment_hash {
 			Some(payment_hash) => {
 				let payment_secret = self
@@ -11597,6 +14969,13 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 		};
 
 		log_trace!(self.logger, "Creating invoice with payment hash {}", &payment_hash);
+		if let Some(amount_msats) = amount_msats {
+			let receivable_summary = self.get_receivable_summary();
+			if !receivable_summary.can_receive(amount_msats) {
+				log_warn!(self.logger, "Creating invoice for {} msat with payment hash {}, exceeding the {} msat we can currently receive",
+					amount_msats, &payment_hash, receivable_summary.max_total_msat);
+			}
+		}
 
 		let invoice = Bolt11InvoiceBuilder::new(currency);
 		let invoice = match description {
@@ -11623,6 +15002,10 @@ This indicates a bug inside LDK. Please report this error at https://github.com/
 			invoice = invoice.amount_milli_satoshis(amount_msats);
 		}
 
+		for hint in extra_route_hints {
+			invoice = invoice.private_route(hint);
+		}
+
 		let channels = self.list_channels();
 		let route_hints = super::invoice_utils::sort_and_filter_channels(channels, amount_msats, &self.logger);
 		for hint in route_hints {
@@ -12381,7 +15764,6 @@ where
 		self.flow.blinded_paths_for_async_recipient(recipient_id, relative_expiry, peers)
 	}
 
-	#[cfg(any(test, async_payments))]
 	pub(super) fn duration_since_epoch(&self) -> Duration {
 		#[cfg(not(feature = "std"))]
 		let now = Duration::from_secs(self.highest_seen_timestamp.load(Ordering::Acquire) as u64);
@@ -12490,6 +15872,30 @@ where
 		}
 	}
 
+	/// Gets a fake short channel id for use in receiving intercepted payments, as with
+	/// [`Self::get_intercept_scid`], annotated with the forwarding parameters and the time until
+	/// which we'll accept HTLCs over it.
+	///
+	/// This allows intercept SCIDs generated by us to be handled the same way as those provided by
+	/// an LSP, e.g., when handed to a client creating invoices via
+	/// [`Self::create_bolt11_invoice_with_jit_channel_hint`].
+	///
+	/// Note that the `valid_until` time is not enforced by us and should be enforced when handling
+	/// [`Event::HTLCIntercepted`].
+	pub fn get_intercept_scid_hint(
+		&self, fees: RoutingFees, cltv_expiry_delta: u16, valid_until: Duration,
+	) -> JitChannelHint {
+		JitChannelHint {
+			intercept_scid: self.get_intercept_scid(),
+			lsp_node_id: self.get_our_node_id(),
+			fees,
+			cltv_expiry_delta,
+			htlc_minimum_msat: None,
+			htlc_maximum_msat: None,
+			valid_until,
+		}
+	}
+
 	/// Gets inflight HTLC information by processing pending outbound payments that are in
 	/// our channels. May be used during pathfinding to account for in-use channel liquidity.
 	pub fn compute_inflight_htlcs(&self) -> InFlightHtlcs {
@@ -12814,6 +16220,11 @@ where
 	#[rustfmt::skip]
 	fn peer_connected(&self, counterparty_node_id: PublicKey, init_msg: &msgs::Init, inbound: bool) -> Result<(), ()> {
 		let logger = WithContext::from(&self.logger, Some(counterparty_node_id), None, None);
+		#[cfg(any(test, feature = "channel_state_replication"))]
+		if self.is_replication_fenced() {
+			log_debug!(logger, "Refusing connection to peer {} as another node took over as the primary", log_pubkey!(counterparty_node_id));
+			return Err(());
+		}
 		if !init_msg.features.supports_static_remote_key() {
 			log_debug!(logger, "Peer {} does not support static remote key, disconnecting", log_pubkey!(counterparty_node_id));
 			return Err(());
@@ -12869,6 +16280,17 @@ where
 				}
 			}
 
+			// Our counterparty forgets about unfunded channels upon disconnecting, so any negotiations
+			// with it from before we were reloaded have certainly been abandoned now.
+			let mut restored_negotiations = self.restored_channel_negotiations.lock().unwrap();
+			let num_restored_negotiations = restored_negotiations.len();
+			restored_negotiations.retain(|restored| {
+				restored.negotiation.counterparty_node_id != counterparty_node_id
+			});
+			let dropped_restored_negotiations =
+				restored_negotiations.len() != num_restored_negotiations;
+			mem::drop(restored_negotiations);
+
 			log_debug!(logger, "Generating channel_reestablish events for {}", log_pubkey!(counterparty_node_id));
 
 			let per_peer_state = self.per_peer_state.read().unwrap();
@@ -12909,6 +16331,9 @@ where
 				}
 			}
 
+			if dropped_restored_negotiations {
+				return NotifyOption::DoPersist;
+			}
 			return NotifyOption::SkipPersistHandleEvents;
 			//TODO: Also re-broadcast announcement_signatures
 		});
@@ -12982,6 +16407,12 @@ where
 
 			result
 		});
+		// Any state changes leading to the messages were replicated as the guard was dropped above,
+		// thus we're only fenced at this point if the messages must not be sent.
+		#[cfg(any(test, feature = "channel_state_replication"))]
+		if self.is_replication_fenced() {
+			return Vec::new();
+		}
 		events.into_inner()
 	}
 }
@@ -13072,6 +16503,12 @@ where
 			*best_block = BestBlock::new(header.prev_blockhash, new_height)
 		}
 
+		for close in self.pending_cooperative_closes.lock().unwrap().values_mut() {
+			if close.confirmation.map_or(false, |(_, conf_height, _)| conf_height > new_height) {
+				close.confirmation = None;
+			}
+		}
+
 		self.do_chain_event(Some(new_height), |channel| {
 			channel.best_block_updated(
 				new_height,
@@ -13122,6 +16559,19 @@ where
 		self.do_chain_event(Some(height), |channel| channel.transactions_confirmed(&block_hash, height, txdata, self.chain_hash, &self.node_signer, &self.default_configuration, &&WithChannelContext::from(&self.logger, &channel.context, None))
 			.map(|(a, b)| (a, Vec::new(), b)));
 
+		{
+			let mut pending_cooperative_closes = self.pending_cooperative_closes.lock().unwrap();
+			for close in pending_cooperative_closes.values_mut().filter(|close| close.confirmation.is_none()) {
+				let funding_outpoint = close.funding_txo.into_bitcoin_outpoint();
+				for (_, tx) in txdata.iter() {
+					if tx.input.iter().any(|input| input.previous_output == funding_outpoint) {
+						close.confirmation = Some((tx.compute_txid(), height, block_hash));
+						break;
+					}
+				}
+			}
+		}
+
 		let last_best_block_height = self.best_block.read().unwrap().height;
 		if height < last_best_block_height {
 			let timestamp = self.highest_seen_timestamp.load(Ordering::Acquire);
@@ -13143,6 +16593,10 @@ where
 				self, || -> NotifyOption { NotifyOption::DoPersist });
 		*self.best_block.write().unwrap() = BestBlock::new(block_hash, height);
 
+		self.pending_cooperative_closes.lock().unwrap().retain(|_, close| {
+			close.confirmation.map_or(true, |(_, conf_height, _)| conf_height + ANTI_REORG_DELAY - 1 > height)
+		});
+
 		let mut min_anchor_feerate = None;
 		let mut min_non_anchor_feerate = None;
 		if self.background_events_processed_since_startup.load(Ordering::Relaxed) {
@@ -13206,6 +16660,13 @@ where
 		}
 		max_time!(self.highest_seen_timestamp);
 
+		#[cfg(feature = "std")] {
+			*self.last_best_block_update.lock().unwrap() = Instant::now();
+		}
+		if self.chain_sync_stale.swap(false, Ordering::AcqRel) {
+			log_info!(self.logger, "Chain sync resumed with new best block {} at height {}", block_hash, height);
+		}
+
 		self.flow.best_block_updated(header, height);
 	}
 
@@ -13220,6 +16681,11 @@ where
 				}
 			}
 		}
+		for close in self.pending_cooperative_closes.lock().unwrap().values() {
+			if let Some((txid, conf_height, block_hash)) = close.confirmation {
+				res.push((txid, conf_height, Some(block_hash)));
+			}
+		}
 		res
 	}
 
@@ -13233,6 +16699,11 @@ where
 			let logger = WithChannelContext::from(&self.logger, &channel.context, None);
 			channel.transaction_unconfirmed(txid, &&logger).map(|()| (None, Vec::new(), None))
 		});
+		for close in self.pending_cooperative_closes.lock().unwrap().values_mut() {
+			if close.confirmation.map_or(false, |(conf_txid, _, _)| conf_txid == *txid) {
+				close.confirmation = None;
+			}
+		}
 	}
 }
 
@@ -13343,6 +16814,7 @@ where
 												user_channel_id: funded_channel.context.get_user_id(),
 												counterparty_node_id,
 												funding_txo: Some(funding_txo.into_bitcoin_outpoint()),
+												channel_value_satoshis: Some(funded_channel.funding.get_value_satoshis()),
 												channel_type: funded_channel.funding.get_channel_type().clone(),
 											}, None));
 										}
@@ -13444,53 +16916,7 @@ where
 		}
 
 		if let Some(height) = height_opt {
-			self.claimable_payments.lock().unwrap().claimable_payments.retain(|payment_hash, payment| {
-				payment.htlcs.retain(|htlc| {
-					// If height is approaching the number of blocks we think it takes us to get
-					// our commitment transaction confirmed before the HTLC expires, plus the
-					// number of blocks we generally consider it to take to do a commitment update,
-					// just give up on it and fail the HTLC.
-					if height >= htlc.cltv_expiry - HTLC_FAIL_BACK_BUFFER {
-						let reason = LocalHTLCFailureReason::PaymentClaimBuffer;
-						timed_out_htlcs.push((HTLCSource::PreviousHopData(htlc.prev_hop.clone()), payment_hash.clone(),
-							HTLCFailReason::reason(reason, invalid_payment_err_data(htlc.value, height)),
-							HTLCHandlingFailureType::Receive { payment_hash: payment_hash.clone() }));
-						false
-					} else { true }
-				});
-				!payment.htlcs.is_empty() // Only retain this entry if htlcs has at least one entry.
-			});
-
-			let mut intercepted_htlcs = self.pending_intercepted_htlcs.lock().unwrap();
-			intercepted_htlcs.retain(|_, htlc| {
-				if height >= htlc.forward_info.outgoing_cltv_value - HTLC_FAIL_BACK_BUFFER {
-					let prev_hop_data = HTLCSource::PreviousHopData(HTLCPreviousHopData {
-						short_channel_id: htlc.prev_short_channel_id,
-						user_channel_id: Some(htlc.prev_user_channel_id),
-						htlc_id: htlc.prev_htlc_id,
-						incoming_packet_shared_secret: htlc.forward_info.incoming_shared_secret,
-						phantom_shared_secret: None,
-						counterparty_node_id: htlc.prev_counterparty_node_id,
-						outpoint: htlc.prev_funding_outpoint,
-						channel_id: htlc.prev_channel_id,
-						blinded_failure: htlc.forward_info.routing.blinded_failure(),
-						cltv_expiry: htlc.forward_info.routing.incoming_cltv_expiry(),
-					});
-
-					let requested_forward_scid /* intercept scid */ = match htlc.forward_info.routing {
-						PendingHTLCRouting::Forward { short_channel_id, .. } => short_channel_id,
-						_ => unreachable!(),
-					};
-					timed_out_htlcs.push((prev_hop_data, htlc.forward_info.payment_hash,
-							HTLCFailReason::from_failure_code(LocalHTLCFailureReason::ForwardExpiryBuffer),
-							HTLCHandlingFailureType::InvalidForward { requested_forward_scid }));
-					let logger = WithContext::from(
-						&self.logger, None, Some(htlc.prev_channel_id), Some(htlc.forward_info.payment_hash)
-					);
-					log_trace!(logger, "Timing out intercepted HTLC with requested forward scid {}", requested_forward_scid);
-					false
-				} else { true }
-			});
+			timed_out_htlcs.extend(self.get_expired_inbound_htlcs(height));
 		}
 
 		for (failure, counterparty_node_id) in failed_channels {
@@ -13502,6 +16928,125 @@ where
 		}
 	}
 
+	/// Removes any claimable or intercepted HTLCs which are too close to expiry at the given
+	/// `height` to be safely claimed or forwarded, returning them to be failed backwards.
+	#[rustfmt::skip]
+	fn get_expired_inbound_htlcs(&self, height: u32) -> Vec<(HTLCSource, PaymentHash, HTLCFailReason, HTLCHandlingFailureType)> {
+		let mut timed_out_htlcs = Vec::new();
+		let mut expired_partial_payments = Vec::new();
+		self.claimable_payments.lock().unwrap().claimable_payments.retain(|payment_hash, payment| {
+			let expected_msat = payment.htlcs.first().map_or(0, |htlc| htlc.total_msat);
+			let is_partial_mpp = payment.htlcs.first().map_or(false, |htlc| {
+				matches!(htlc.onion_payload, OnionPayload::Invoice { .. })
+			}) && payment.htlcs.iter().map(|htlc| htlc.sender_intended_value).sum::<u64>() < expected_msat;
+			let mut expired_msat = 0;
+			payment.htlcs.retain(|htlc| {
+				if height >= inbound_htlc_fail_back_height(htlc.cltv_expiry) {
+					let reason = LocalHTLCFailureReason::PaymentClaimBuffer;
+					timed_out_htlcs.push((HTLCSource::PreviousHopData(htlc.prev_hop.clone()), payment_hash.clone(),
+						HTLCFailReason::reason(reason, invalid_payment_err_data(htlc.value, height)),
+						HTLCHandlingFailureType::Receive { payment_hash: payment_hash.clone() }));
+					expired_msat += htlc.value;
+					false
+				} else { true }
+			});
+			if is_partial_mpp && payment.htlcs.is_empty() {
+				expired_partial_payments.push((*payment_hash, expired_msat, expected_msat));
+			}
+			!payment.htlcs.is_empty() // Only retain this entry if htlcs has at least one entry.
+		});
+		if self.default_configuration.notify_partial_mpp_payments {
+			let mut pending_events = self.pending_events.lock().unwrap();
+			for (payment_hash, received_msat, expected_msat) in expired_partial_payments {
+				let reason = events::PartialPaymentFailureReason::PartsExpiring;
+				let event = events::Event::PartialPaymentFailed { payment_hash, received_msat, expected_msat, reason };
+				pending_events.push_back((event, None));
+			}
+		}
+
+		let mut intercepted_htlcs = self.pending_intercepted_htlcs.lock().unwrap();
+		intercepted_htlcs.retain(|_, htlc| {
+			if height >= inbound_htlc_fail_back_height(htlc.forward_info.outgoing_cltv_value) {
+				let prev_hop_data = HTLCSource::PreviousHopData(HTLCPreviousHopData {
+					short_channel_id: htlc.prev_short_channel_id,
+					user_channel_id: Some(htlc.prev_user_channel_id),
+					htlc_id: htlc.prev_htlc_id,
+					incoming_packet_shared_secret: htlc.forward_info.incoming_shared_secret,
+					phantom_shared_secret: None,
+					counterparty_node_id: htlc.prev_counterparty_node_id,
+					outpoint: htlc.prev_funding_outpoint,
+					channel_id: htlc.prev_channel_id,
+					blinded_failure: htlc.forward_info.routing.blinded_failure(),
+					cltv_expiry: htlc.forward_info.routing.incoming_cltv_expiry(),
+				});
+
+				let requested_forward_scid /* intercept scid */ = match htlc.forward_info.routing {
+					PendingHTLCRouting::Forward { short_channel_id, .. } => short_channel_id,
+					_ => unreachable!(),
+				};
+				timed_out_htlcs.push((prev_hop_data, htlc.forward_info.payment_hash,
+						HTLCFailReason::from_failure_code(LocalHTLCFailureReason::ForwardExpiryBuffer),
+						HTLCHandlingFailureType::InvalidForward { requested_forward_scid }));
+				let logger = WithContext::from(
+					&self.logger, None, Some(htlc.prev_channel_id), Some(htlc.forward_info.payment_hash)
+				);
+				log_trace!(logger, "Timing out intercepted HTLC with requested forward scid {}", requested_forward_scid);
+				false
+			} else { true }
+		});
+
+		timed_out_htlcs
+	}
+
+	/// Gets the time-sensitive deadlines tracked by the [`ChannelManager`], as the height at which
+	/// each is due, its kind, the channel ID and the amount at stake.
+	///
+	/// This includes the heights by which claimable HTLCs have to be claimed, as well as the heights
+	/// at which HTLCs we have yet to forward, as they are intercepted or held in a holding cell,
+	/// are failed back. Intercepted HTLCs are reported with the channel they were received over.
+	///
+	/// See [`get_htlc_deadlines`] to get the deadlines across all channels and monitors.
+	///
+	/// [`get_htlc_deadlines`]: crate::util::deadlines::get_htlc_deadlines
+	pub fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, bitcoin::Amount)> {
+		let mut deadlines = Vec::new();
+		{
+			let claimable_payments = self.claimable_payments.lock().unwrap();
+			for payment in claimable_payments.claimable_payments.values() {
+				for htlc in payment.htlcs.iter() {
+					deadlines.push((
+						inbound_htlc_fail_back_height(htlc.cltv_expiry),
+						DeadlineKind::InboundHtlcClaim,
+						htlc.prev_hop.channel_id,
+						bitcoin::Amount::from_sat(htlc.value / 1000),
+					));
+				}
+			}
+		}
+		for htlc in self.pending_intercepted_htlcs.lock().unwrap().values() {
+			deadlines.push((
+				inbound_htlc_fail_back_height(htlc.forward_info.outgoing_cltv_value),
+				DeadlineKind::OutboundHtlcFailBack,
+				htlc.prev_channel_id,
+				bitcoin::Amount::from_sat(htlc.forward_info.outgoing_amt_msat / 1000),
+			));
+		}
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		for (_, peer_state_mutex) in per_peer_state.iter() {
+			let peer_state_lock = peer_state_mutex.lock().unwrap();
+			let peer_state = &*peer_state_lock;
+			for chan in peer_state.channel_by_id.values().filter_map(Channel::as_funded) {
+				let channel_id = chan.context.channel_id();
+				for (height, amount_msat) in chan.get_holding_cell_htlc_deadlines() {
+					let amount = bitcoin::Amount::from_sat(amount_msat / 1000);
+					let kind = DeadlineKind::OutboundHtlcFailBack;
+					deadlines.push((height, kind, channel_id, amount));
+				}
+			}
+		}
+		deadlines
+	}
+
 	/// Gets a [`Future`] that completes when this [`ChannelManager`] may need to be persisted or
 	/// may have events that need processing.
 	///
@@ -14147,6 +17692,18 @@ where
 			}
 		}
 	}
+
+	fn peer_has_pending_htlcs(&self, counterparty_node_id: PublicKey) -> bool {
+		let per_peer_state = self.per_peer_state.read().unwrap();
+		per_peer_state.get(&counterparty_node_id).map_or(false, |peer_state_mutex| {
+			let peer_state = peer_state_mutex.lock().unwrap();
+			peer_state
+				.channel_by_id
+				.values()
+				.filter_map(Channel::as_funded)
+				.any(|chan| chan.has_pending_htlcs())
+		})
+	}
 }
 
 impl<
@@ -15293,6 +18850,51 @@ where
 			}
 		}
 
+		let mut channel_balance_history: Option<HashMap<ChannelId, Vec<ChannelBalanceSample>>> =
+			None;
+		for (channel_id, samples) in self.channel_balance_history.lock().unwrap().iter() {
+			channel_balance_history
+				.get_or_insert_with(|| new_hash_map())
+				.insert(*channel_id, samples.iter().cloned().collect());
+		}
+
+		let accepted_htlc_watermarks = self.accepted_htlc_watermarks.lock().unwrap();
+		let accepted_htlc_watermarks_opt =
+			if accepted_htlc_watermarks.is_empty() { None } else { Some(&*accepted_htlc_watermarks) };
+
+		let pending_cooperative_closes = self.pending_cooperative_closes.lock().unwrap();
+		let pending_cooperative_closes_opt =
+			if pending_cooperative_closes.is_empty() { None } else { Some(&*pending_cooperative_closes) };
+
+		let last_close_authorization_nonce = *self.last_close_authorization_nonce.lock().unwrap();
+		let last_close_authorization_nonce_opt =
+			if last_close_authorization_nonce == 0 { None } else { Some(last_close_authorization_nonce) };
+
+		// Unfunded channels are not persisted, so we only write down that they were being
+		// negotiated, along with any negotiations we restored ourselves.
+		let mut channel_negotiations = Vec::new();
+		for ((counterparty_node_id, _), peer_state) in per_peer_state.iter().zip(peer_states.iter()) {
+			for channel in peer_state.channel_by_id.values().filter(|chan| !chan.is_funded()) {
+				channel_negotiations.push(RestoredChannelNegotiation {
+					negotiation: PendingChannelNegotiation {
+						temporary_channel_id: channel.context().channel_id(),
+						counterparty_node_id: *counterparty_node_id,
+						is_outbound: channel.context().is_outbound(),
+						channel_type: channel.funding().get_channel_type().clone(),
+						is_restored: false,
+					},
+					timer_ticks: 0,
+				});
+			}
+		}
+		let restored_negotiations = self.restored_channel_negotiations.lock().unwrap();
+		for restored in restored_negotiations.iter() {
+			channel_negotiations.push(RestoredChannelNegotiation {
+				negotiation: restored.negotiation.clone(),
+				timer_ticks: restored.timer_ticks,
+			});
+		}
+
 		write_tlv_fields!(writer, {
 			(1, pending_outbound_payments_no_retry, required),
 			(2, pending_intercepted_htlcs, option),
@@ -15311,6 +18913,11 @@ where
 			(17, in_flight_monitor_updates, option),
 			(19, peer_storage_dir, optional_vec),
 			(21, self.flow.writeable_async_receive_offer_cache(), required),
+			(23, channel_balance_history, option),
+			(25, pending_cooperative_closes_opt, option),
+			(27, accepted_htlc_watermarks_opt, option),
+			(29, channel_negotiations, optional_vec),
+			(31, last_close_authorization_nonce_opt, option),
 		});
 
 		Ok(())
@@ -15996,6 +19603,13 @@ where
 		let mut inbound_payment_id_secret = None;
 		let mut peer_storage_dir: Option<Vec<(PublicKey, Vec<u8>)>> = None;
 		let mut async_receive_offer_cache: AsyncReceiveOfferCache = AsyncReceiveOfferCache::new();
+		let mut channel_balance_history: Option<HashMap<ChannelId, Vec<ChannelBalanceSample>>> =
+			None;
+		let mut pending_cooperative_closes: Option<HashMap<ChannelId, PendingCooperativeClose>> =
+			None;
+		let mut accepted_htlc_watermarks: Option<HashMap<ChannelId, AcceptedHtlcWatermark>> = None;
+		let mut restored_channel_negotiations: Option<Vec<RestoredChannelNegotiation>> = None;
+		let mut last_close_authorization_nonce: Option<u64> = None;
 		read_tlv_fields!(reader, {
 			(1, pending_outbound_payments_no_retry, option),
 			(2, pending_intercepted_htlcs, option),
@@ -16014,6 +19628,11 @@ where
 			(17, in_flight_monitor_updates, option),
 			(19, peer_storage_dir, optional_vec),
 			(21, async_receive_offer_cache, (default_value, async_receive_offer_cache)),
+			(23, channel_balance_history, option),
+			(25, pending_cooperative_closes, option),
+			(27, accepted_htlc_watermarks, option),
+			(29, restored_channel_negotiations, optional_vec),
+			(31, last_close_authorization_nonce, option),
 		});
 		let mut decode_update_add_htlcs = decode_update_add_htlcs.unwrap_or_else(|| new_hash_map());
 		let peer_storage_dir: Vec<(PublicKey, Vec<u8>)> = peer_storage_dir.unwrap_or_else(Vec::new);
@@ -16829,6 +20448,19 @@ where
 		)
 		.with_async_payments_offers_cache(async_receive_offer_cache);
 
+		// Only retain as many samples as currently configured, dropping the oldest ones.
+		let channel_balance_history_limit =
+			args.default_config.channel_balance_history_limit as usize;
+		let mut channel_balance_history_by_id = new_hash_map();
+		for (channel_id, samples) in channel_balance_history.unwrap_or_else(|| new_hash_map()) {
+			if channel_balance_history_limit == 0 {
+				break;
+			}
+			let skip = samples.len().saturating_sub(channel_balance_history_limit);
+			let samples: VecDeque<_> = samples.into_iter().skip(skip).collect();
+			channel_balance_history_by_id.insert(channel_id, samples);
+		}
+
 		let channel_manager = ChannelManager {
 			chain_hash,
 			fee_estimator: bounded_fee_estimator,
@@ -16842,6 +20474,7 @@ where
 			inbound_payment_key: expanded_inbound_key,
 			pending_outbound_payments: pending_outbounds,
 			pending_intercepted_htlcs: Mutex::new(pending_intercepted_htlcs.unwrap()),
+			intercepted_htlc_timer_ticks: Mutex::new(new_hash_map()),
 
 			forward_htlcs: Mutex::new(forward_htlcs),
 			decode_update_add_htlcs: Mutex::new(decode_update_add_htlcs),
@@ -16860,6 +20493,9 @@ where
 			secp_ctx,
 
 			highest_seen_timestamp: AtomicUsize::new(highest_seen_timestamp as usize),
+			#[cfg(feature = "std")]
+			last_best_block_update: Mutex::new(Instant::now()),
+			chain_sync_stale: AtomicBool::new(false),
 
 			per_peer_state: FairRwLock::new(per_peer_state),
 
@@ -16868,6 +20504,8 @@ where
 
 			pending_events: Mutex::new(pending_events_read),
 			pending_events_processor: AtomicBool::new(false),
+			pending_events_watermark: AtomicUsize::new(0),
+			dropped_pending_events: AtomicUsize::new(0),
 			pending_htlc_forwards_processor: AtomicBool::new(false),
 			pending_background_events: Mutex::new(pending_background_events),
 			total_consistency_lock: RwLock::new(()),
@@ -16877,6 +20515,8 @@ where
 			needs_persist_flag: AtomicBool::new(false),
 
 			funding_batch_states: Mutex::new(BTreeMap::new()),
+			pending_funding_psbts: Mutex::new(new_hash_map()),
+			batched_close_states: Mutex::new(Vec::new()),
 
 			pending_broadcast_messages: Mutex::new(Vec::new()),
 
@@ -16888,6 +20528,28 @@ where
 
 			logger: args.logger,
 			default_configuration: args.default_config,
+			anchor_channel_reserve_context: Mutex::new(
+				Network::from_chain_hash(chain_hash)
+					.map(AnchorChannelReserveContext::for_network)
+					.unwrap_or_default(),
+			),
+			inbound_channel_acceptor: Mutex::new(None),
+			anchor_negotiation_enabled: AtomicBool::new(true),
+			chain_filter: Mutex::new(None),
+			#[cfg(any(test, feature = "channel_state_replication"))]
+			channel_replication: Mutex::new(ChannelReplicationState::new()),
+			channel_balance_history: Mutex::new(channel_balance_history_by_id),
+			accepted_htlc_watermarks: Mutex::new(
+				accepted_htlc_watermarks.unwrap_or_else(|| new_hash_map()),
+			),
+			pending_cooperative_closes: Mutex::new(
+				pending_cooperative_closes.unwrap_or_else(|| new_hash_map()),
+			),
+			restored_channel_negotiations: Mutex::new(
+				restored_channel_negotiations.unwrap_or_else(Vec::new),
+			),
+			close_authorizer: Mutex::new(None),
+			last_close_authorization_nonce: Mutex::new(last_close_authorization_nonce.unwrap_or(0)),
 
 			#[cfg(feature = "_test_utils")]
 			testing_dnssec_proof_offer_resolution_override: Mutex::new(new_hash_map()),
@@ -17184,7 +20846,7 @@ mod tests {
 	use crate::events::{ClosureReason, Event, HTLCHandlingFailureType};
 	use crate::ln::channelmanager::{
 		create_recv_pending_htlc_info, inbound_payment, HTLCForwardInfo, InterceptId, PaymentId,
-		RecipientOnionFields,
+		PendingEventStats, RecipientOnionFields,
 	};
 	use crate::ln::functional_test_utils::*;
 	use crate::ln::msgs::{self, BaseMessageHandler, ChannelMessageHandler, MessageSendEvent};
@@ -17195,6 +20857,8 @@ mod tests {
 	use crate::prelude::*;
 	use crate::routing::router::{find_route, PaymentParameters, RouteParameters};
 	use crate::sign::EntropySource;
+	use crate::sync::Arc;
+	use crate::types::features::ChannelTypeFeatures;
 	use crate::types::payment::{PaymentHash, PaymentPreimage, PaymentSecret};
 	use crate::util::config::{ChannelConfig, ChannelConfigUpdate};
 	use crate::util::errors::APIError;
@@ -17202,7 +20866,7 @@ mod tests {
 	use crate::util::test_utils;
 	use bitcoin::secp256k1::ecdh::SharedSecret;
 	use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
-	use core::sync::atomic::Ordering;
+	use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 
 	#[test]
 	#[rustfmt::skip]
@@ -18418,6 +22082,316 @@ mod tests {
 		assert!(deserialized_fwd_htlcs.is_empty());
 		core::mem::drop(deserialized_fwd_htlcs);
 	}
+
+	#[test]
+	fn test_channel_balance_history() {
+		// Check that balance samples are only recorded if enabled, retain at most the configured
+		// number of samples, are persisted and are dropped once the channel is closed.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut config = test_default_channel_config();
+		config.channel_balance_history_limit = 2;
+		let persister;
+		let new_chain_monitor;
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(config.clone()), None]);
+		let nodes_0_deserialized;
+		let mut nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+
+		let chan_id = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+		assert!(nodes[0].node.get_channel_balance_history(&chan_id).is_empty());
+
+		send_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+		send_payment(&nodes[0], &[&nodes[1]], 2_000_000);
+
+		let history = nodes[0].node.get_channel_balance_history(&chan_id);
+		assert_eq!(history.len(), 2);
+		assert!(history[0].timestamp <= history[1].timestamp);
+		let channel = &nodes[0].node.list_channels()[0];
+		assert_eq!(history[1].outbound_capacity_msat, channel.outbound_capacity_msat);
+		assert_eq!(history[1].inbound_capacity_msat, channel.inbound_capacity_msat);
+		// Samples are only recorded when the balance changes.
+		assert_ne!(history[0].outbound_capacity_msat, history[1].outbound_capacity_msat);
+		assert!(nodes[1].node.get_channel_balance_history(&chan_id).is_empty());
+
+		let node_a_id = nodes[0].node.get_our_node_id();
+		nodes[1].node.peer_disconnected(node_a_id);
+		let chan_0_monitor_serialized = get_monitor!(nodes[0], chan_id).encode();
+		reload_node!(
+			nodes[0],
+			config,
+			&nodes[0].node.encode(),
+			&[&chan_0_monitor_serialized],
+			persister,
+			new_chain_monitor,
+			nodes_0_deserialized
+		);
+		assert_eq!(nodes[0].node.get_channel_balance_history(&chan_id), history);
+		reconnect_nodes(ReconnectArgs::new(&nodes[0], &nodes[1]));
+
+		let message = "Channel force-closed".to_owned();
+		nodes[0]
+			.node
+			.force_close_broadcasting_latest_txn(&chan_id, &node_b_id, message.clone())
+			.unwrap();
+		check_closed_broadcast!(nodes[0], true);
+		check_added_monitors!(nodes[0], 1);
+		let reason =
+			ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+		check_closed_event!(nodes[0], 1, reason, [node_b_id], 100000);
+		assert!(nodes[0].node.get_channel_balance_history(&chan_id).is_empty());
+	}
+
+	#[test]
+	fn test_pending_events_soft_limit() {
+		// Check that once more events than the configured limit are pending, the oldest
+		// informational events are dropped, while action-required events are always kept.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut config = test_default_channel_config();
+		config.pending_events_soft_limit = 3;
+		config.drop_informational_events_on_overflow = true;
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(config), None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+
+		let channel_ready = |channel: u8| Event::ChannelReady {
+			channel_id: ChannelId([channel; 32]),
+			user_channel_id: channel as u128,
+			counterparty_node_id: node_b_id,
+			funding_txo: None,
+			channel_value_satoshis: None,
+			channel_type: ChannelTypeFeatures::empty(),
+		};
+		let spendable_outputs =
+			|| Event::SpendableOutputs { outputs: Vec::new(), channel_id: None };
+		let push_events = |events: Vec<Event>| {
+			let mut pending_events = nodes[0].node.pending_events.lock().unwrap();
+			pending_events.extend(events.into_iter().map(|event| (event, None)));
+		};
+
+		push_events(vec![
+			channel_ready(1),
+			spendable_outputs(),
+			channel_ready(2),
+			channel_ready(3),
+			spendable_outputs(),
+			channel_ready(4),
+		]);
+		let stats = nodes[0].node.get_pending_event_stats();
+		assert_eq!(
+			stats,
+			PendingEventStats { pending_events: 6, max_pending_events: 6, dropped_events: 0 }
+		);
+
+		nodes[0].node.timer_tick_occurred();
+		let stats = nodes[0].node.get_pending_event_stats();
+		assert_eq!(
+			stats,
+			PendingEventStats { pending_events: 3, max_pending_events: 6, dropped_events: 3 }
+		);
+		let pending_events = nodes[0].node.pending_events.lock().unwrap().clone();
+		let pending_events: Vec<Event> = pending_events.into_iter().map(|(ev, _)| ev).collect();
+		assert_eq!(
+			pending_events,
+			vec![spendable_outputs(), spendable_outputs(), channel_ready(4)]
+		);
+
+		// Action-required events are kept even if they exceed the limit.
+		push_events(vec![spendable_outputs(), spendable_outputs(), spendable_outputs()]);
+		nodes[0].node.timer_tick_occurred();
+		let stats = nodes[0].node.get_pending_event_stats();
+		assert_eq!(
+			stats,
+			PendingEventStats { pending_events: 5, max_pending_events: 6, dropped_events: 4 }
+		);
+
+		let events = nodes[0].node.get_and_clear_pending_events();
+		assert_eq!(events, vec![spendable_outputs(); 5]);
+		let stats = nodes[0].node.get_pending_event_stats();
+		assert_eq!(
+			stats,
+			PendingEventStats { pending_events: 0, max_pending_events: 6, dropped_events: 4 }
+		);
+	}
+
+	#[test]
+	fn test_inflight_htlc_stats() {
+		// Check that the high watermarks of accepted HTLCs follow the HTLCs pending in a channel,
+		// are persisted and can be reset.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let persister;
+		let new_chain_monitor;
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes_1_deserialized;
+		let mut nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_a_id = nodes[0].node.get_our_node_id();
+
+		let chan_id = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+		let stats = nodes[1].node.get_inflight_htlc_stats();
+		assert_eq!(stats.len(), 1);
+		assert_eq!(stats[0].channel_id, chan_id);
+		assert_eq!(stats[0].counterparty_node_id, node_a_id);
+		assert_eq!(stats[0].accepted_htlcs, 0);
+		assert_eq!(stats[0].max_accepted_htlcs, 0);
+
+		let (preimage_1, ..) = route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+		let (preimage_2, ..) = route_payment(&nodes[0], &[&nodes[1]], 2_000_000);
+		let stats = nodes[1].node.get_inflight_htlc_stats()[0];
+		assert_eq!(stats.accepted_htlcs, 2);
+		assert_eq!(stats.accepted_htlcs_value_msat, 3_000_000);
+		assert_eq!(stats.max_accepted_htlcs, 2);
+		assert_eq!(stats.max_accepted_htlcs_value_msat, 3_000_000);
+		// Only HTLCs added by the counterparty are counted.
+		assert_eq!(nodes[0].node.get_inflight_htlc_stats()[0].max_accepted_htlcs, 0);
+
+		claim_payment(&nodes[0], &[&nodes[1]], preimage_2);
+		let stats = nodes[1].node.get_inflight_htlc_stats()[0];
+		assert_eq!(stats.accepted_htlcs, 1);
+		assert_eq!(stats.accepted_htlcs_value_msat, 1_000_000);
+		assert_eq!(stats.max_accepted_htlcs, 2);
+		assert_eq!(stats.max_accepted_htlcs_value_msat, 3_000_000);
+
+		nodes[0].node.peer_disconnected(nodes[1].node.get_our_node_id());
+		let chan_1_monitor_serialized = get_monitor!(nodes[1], chan_id).encode();
+		reload_node!(
+			nodes[1],
+			&nodes[1].node.encode(),
+			&[&chan_1_monitor_serialized],
+			persister,
+			new_chain_monitor,
+			nodes_1_deserialized
+		);
+		assert_eq!(nodes[1].node.get_inflight_htlc_stats(), vec![stats]);
+		reconnect_nodes(ReconnectArgs::new(&nodes[0], &nodes[1]));
+
+		nodes[1].node.reset_inflight_htlc_watermarks();
+		let stats = nodes[1].node.get_inflight_htlc_stats()[0];
+		assert_eq!(stats.max_accepted_htlcs, 1);
+		assert_eq!(stats.max_accepted_htlcs_value_msat, 1_000_000);
+
+		claim_payment(&nodes[0], &[&nodes[1]], preimage_1);
+		let stats = nodes[1].node.get_inflight_htlc_stats()[0];
+		assert_eq!(stats.accepted_htlcs, 0);
+		assert_eq!(stats.accepted_htlcs_value_msat, 0);
+		assert_eq!(stats.max_accepted_htlcs, 1);
+		assert_eq!(stats.max_accepted_htlcs_value_msat, 1_000_000);
+	}
+	/// Runs `scenario` while a second thread repeatedly calls each of the given `subtasks` on each of
+	/// the given `ChannelManager`s.
+	fn run_with_timer_subtasks_hammered<F: FnOnce()>(
+		managers: &[&TestChannelManager], subtasks: &[fn(&TestChannelManager)], scenario: F,
+	) {
+		// Until we have std::thread::scoped we have to unsafe { turn off the borrow checker }. The
+		// thread is always joined below, before the `ChannelManager`s may be dropped.
+		let managers: Vec<&'static TestChannelManager<'static, 'static>> =
+			managers.iter().map(|manager| unsafe { std::mem::transmute(*manager) }).collect();
+		let stop = Arc::new(AtomicBool::new(false));
+		let calls = Arc::new(AtomicUsize::new(0));
+		let (thread_stop, thread_calls) = (Arc::clone(&stop), Arc::clone(&calls));
+		let subtasks = subtasks.to_vec();
+		let thrd = std::thread::spawn(move || {
+			while !thread_stop.load(Ordering::Acquire) {
+				for manager in managers.iter() {
+					for subtask in subtasks.iter() {
+						subtask(manager);
+					}
+				}
+				thread_calls.fetch_add(1, Ordering::AcqRel);
+			}
+		});
+		while calls.load(Ordering::Acquire) == 0 {
+			std::thread::yield_now();
+		}
+
+		let res = std::panic::catch_unwind(std::panic::AssertUnwindSafe(scenario));
+		stop.store(true, Ordering::Release);
+		thrd.join().unwrap();
+		if let Err(e) = res {
+			std::panic::resume_unwind(e);
+		}
+	}
+
+	#[test]
+	fn test_timer_subtasks_concurrent_with_payments() {
+		// Check that the timer sub-tasks which don't count any timeouts while a payment is in flight
+		// can be called from a second thread at an arbitrary frequency while payments are forwarded,
+		// claimed and failed, without affecting them.
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		create_announced_chan_between_nodes(&nodes, 0, 1);
+		create_announced_chan_between_nodes(&nodes, 1, 2);
+
+		let managers = [nodes[0].node, nodes[1].node, nodes[2].node];
+		let subtasks: [fn(&TestChannelManager); 7] = [
+			|cm| cm.process_stale_feerate_updates(),
+			|cm| cm.process_channel_update_status(),
+			|cm| cm.expire_stale_channels(),
+			|cm| cm.prune_disconnected_peers(),
+			|cm| cm.expire_stale_inbound_payments(),
+			|cm| cm.expire_stale_intercepted_htlcs(),
+			|cm| cm.remove_stale_outbound_payments(),
+		];
+		run_with_timer_subtasks_hammered(&managers, &subtasks, || {
+			for _ in 0..3 {
+				let (payment_preimage, ..) =
+					route_payment(&nodes[0], &[&nodes[1], &nodes[2]], 1_000_000);
+				claim_payment(&nodes[0], &[&nodes[1], &nodes[2]], payment_preimage);
+			}
+			let (_, payment_hash, ..) =
+				route_payment(&nodes[0], &[&nodes[1], &nodes[2]], 1_000_000);
+			fail_payment(&nodes[0], &[&nodes[1], &nodes[2]], payment_hash);
+		});
+
+		// The channels remain usable after the sub-tasks stopped running.
+		for chan in nodes[1].node.list_channels() {
+			assert!(chan.is_usable);
+		}
+		send_payment(&nodes[0], &[&nodes[1], &nodes[2]], 1_000_000);
+	}
+
+	#[test]
+	fn test_timer_subtasks_concurrent_with_channel_establishment() {
+		// Check that checking for peer timeouts and the other sub-tasks which don't count any
+		// timeouts while a channel is established can be called from a second thread at an
+		// arbitrary frequency while channels are opened and their configs updated.
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+
+		let managers = [nodes[0].node, nodes[1].node, nodes[2].node];
+		let subtasks: [fn(&TestChannelManager); 6] = [
+			|cm| cm.process_stale_feerate_updates(),
+			|cm| cm.check_peer_timeouts(),
+			|cm| cm.prune_disconnected_peers(),
+			|cm| cm.expire_stale_inbound_payments(),
+			|cm| cm.expire_stale_intercepted_htlcs(),
+			|cm| cm.remove_stale_outbound_payments(),
+		];
+		run_with_timer_subtasks_hammered(&managers, &subtasks, || {
+			let (_, _, chan_id, _) = create_announced_chan_between_nodes(&nodes, 0, 1);
+			create_announced_chan_between_nodes(&nodes, 0, 1);
+			create_announced_chan_between_nodes(&nodes, 1, 2);
+
+			let mut config = nodes[0].node.get_current_default_configuration().channel_config;
+			config.forwarding_fee_base_msat += 10;
+			nodes[0].node.update_channel_config(&node_b_id, &[chan_id], &config).unwrap();
+			let events = nodes[0].node.get_and_clear_pending_msg_events();
+			assert_eq!(events.len(), 1);
+			assert!(matches!(events[0], MessageSendEvent::BroadcastChannelUpdate { .. }));
+		});
+
+		// No peer was disconnected due to the sub-tasks running at a high frequency.
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+		assert!(nodes[1].node.get_and_clear_pending_msg_events().is_empty());
+		assert_eq!(nodes[0].node.list_usable_channels().len(), 2);
+		send_payment(&nodes[0], &[&nodes[1], &nodes[2]], 1_000_000);
+	}
 }
 
 #[cfg(ldk_bench)]
@@ -16,7 +16,7 @@ use crate::chain::{BestBlock, ChannelMonitorUpdateStatus, Confirm, Listen, Watch
 use crate::events::bump_transaction::sync::{
 	BumpTransactionEventHandlerSync, WalletSourceSync, WalletSync,
 };
-use crate::events::bump_transaction::BumpTransactionEvent;
+use crate::events::bump_transaction::{BumpTransactionEvent, HTLCDeadlineFeerateSchedule};
 use crate::events::{
 	ClaimedHTLC, ClosureReason, Event, HTLCHandlingFailureType, PaidBolt12Invoice, PathFailure,
 	PaymentFailureReason, PaymentPurpose,
@@ -286,9 +286,9 @@ pub fn connect_block<'a, 'b, 'c, 'd>(node: &'a Node<'b, 'c, 'd>, block: &Block)
 
 fn call_claimable_balances<'a, 'b, 'c, 'd>(node: &'a Node<'b, 'c, 'd>) {
 	// Ensure `get_claimable_balances`' self-tests never panic
-	for channel_id in node.chain_monitor.chain_monitor.list_monitors() {
-		node.chain_monitor.chain_monitor.get_monitor(channel_id).unwrap().get_claimable_balances();
-	}
+	node.chain_monitor.chain_monitor.with_monitors(|_, monitor| {
+		monitor.get_claimable_balances();
+	});
 }
 
 fn do_connect_block_with_consistency_checks<'a, 'b, 'c, 'd>(
@@ -819,9 +819,8 @@ impl<'a, 'b, 'c> Drop for Node<'a, 'b, 'c> {
 			let feeest = test_utils::TestFeeEstimator::new(253);
 			let mut deserialized_monitors = Vec::new();
 			{
-				for channel_id in self.chain_monitor.chain_monitor.list_monitors() {
+				self.chain_monitor.chain_monitor.with_monitors(|_, mon| {
 					let mut w = test_utils::TestVecWriter(Vec::new());
-					let mon = self.chain_monitor.chain_monitor.get_monitor(channel_id).unwrap();
 					mon.write(&mut w).unwrap();
 					let (_, deserialized_monitor) =
 						<(BlockHash, ChannelMonitor<TestChannelSigner>)>::read(
@@ -830,7 +829,7 @@ impl<'a, 'b, 'c> Drop for Node<'a, 'b, 'c> {
 						)
 						.unwrap();
 					deserialized_monitors.push(deserialized_monitor);
-				}
+				});
 			}
 
 			let broadcaster = test_utils::TestBroadcaster {
@@ -4380,6 +4379,7 @@ pub fn create_network<'a, 'b: 'a, 'c: 'b>(
 				wallet,
 				&cfgs[i].keys_manager,
 				cfgs[i].logger,
+				HTLCDeadlineFeerateSchedule::disabled(),
 			),
 		})
 	}
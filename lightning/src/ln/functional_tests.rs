@@ -12,7 +12,7 @@
 //! claim outputs on-chain.
 
 use crate::chain;
-use crate::chain::chaininterface::LowerBoundedFeeEstimator;
+use crate::chain::chaininterface::{ConfirmationTarget, LowerBoundedFeeEstimator};
 use crate::chain::channelmonitor;
 use crate::chain::channelmonitor::{
 	Balance, ChannelMonitorUpdateStep, ANTI_REORG_DELAY, CLTV_CLAIM_BUFFER,
@@ -21,8 +21,8 @@ use crate::chain::channelmonitor::{
 use crate::chain::transaction::OutPoint;
 use crate::chain::{ChannelMonitorUpdateStatus, Confirm, Listen, Watch};
 use crate::events::{
-	ClosureReason, Event, FundingInfo, HTLCHandlingFailureType, PathFailure, PaymentFailureReason,
-	PaymentPurpose,
+	ClosureReason, Event, FundingInfo, HTLCHandlingFailureType, LiquidityBand,
+	LiquidityThresholdDirection, PathFailure, PaymentFailureReason, PaymentPurpose,
 };
 use crate::ln::chan_utils::{
 	commitment_tx_base_weight, second_stage_tx_fees_sat, COMMITMENT_TX_WEIGHT_PER_HTLC,
@@ -30,9 +30,10 @@ use crate::ln::chan_utils::{
 };
 use crate::ln::channel::{
 	get_holder_selected_channel_reserve_satoshis, Channel, ChannelError, InboundV1Channel,
-	OutboundV1Channel, COINBASE_MATURITY, DISCONNECT_PEER_AWAITING_RESPONSE_TICKS,
-	MIN_CHAN_DUST_LIMIT_SATOSHIS,
+	OutboundV1Channel, ANCHOR_OUTPUT_VALUE_SATOSHI, COINBASE_MATURITY,
+	DISCONNECT_PEER_AWAITING_RESPONSE_TICKS, MIN_CHAN_DUST_LIMIT_SATOSHIS,
 };
+use crate::ln::channel_state::ReceiveConstraint;
 use crate::ln::channelmanager::{
 	self, PaymentId, RAACommitmentOrder, RecipientOnionFields, BREAKDOWN_TIMEOUT,
 	DISABLE_GOSSIP_TICKS, ENABLE_GOSSIP_TICKS, MIN_CLTV_EXPIRY_DELTA,
@@ -49,13 +50,14 @@ use crate::routing::gossip::{NetworkGraph, NetworkUpdate};
 use crate::routing::router::{
 	get_route, Path, PaymentParameters, Route, RouteHop, RouteParameters,
 };
-use crate::sign::{EntropySource, OutputSpender, SignerProvider};
+use crate::sign::{EntropySource, OutputSpender, SignerProvider, SpendableOutputDescriptor};
 use crate::types::features::{ChannelFeatures, ChannelTypeFeatures, NodeFeatures};
 use crate::types::payment::{PaymentHash, PaymentSecret};
 use crate::types::string::UntrustedString;
+use crate::util::anchor_channel_reserves::{get_reserve_per_channel, AnchorChannelReserveContext};
 use crate::util::config::{
-	ChannelConfigOverrides, ChannelConfigUpdate, ChannelHandshakeConfigUpdate, MaxDustHTLCExposure,
-	UserConfig,
+	ChannelConfigOverrides, ChannelConfigUpdate, ChannelHandshakeConfigUpdate,
+	LiquidityAlertThresholds, MaxDustHTLCExposure, UserConfig,
 };
 use crate::util::errors::APIError;
 use crate::util::ser::{ReadableArgs, Writeable};
@@ -350,6 +352,314 @@ pub fn test_funding_exceeds_no_wumbo_limit() {
 	}
 }
 
+#[xtest(feature = "_externalize_tests")]
+pub fn test_preflight_open_channel() {
+	// Test that `preflight_open_channel` reports all the reasons a channel can't be opened at once.
+	use crate::ln::channel::MAX_FUNDING_SATOSHIS_NO_WUMBO;
+	use crate::ln::channelmanager::OpenChannelBlocker;
+	use crate::util::anchor_channel_reserves::{
+		AnchorChannelReserveCheck, AnchorChannelReserveContext,
+	};
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let mut node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	let mut features = channelmanager::provided_init_features(&anchors_cfg);
+	features.clear_wumbo();
+	*node_cfgs[1].override_init_features.borrow_mut() = Some(features);
+	let node_chanmgrs = create_node_chanmgrs(
+		3,
+		&node_cfgs,
+		&[Some(anchors_cfg.clone()), Some(anchors_cfg.clone()), Some(anchors_cfg)],
+	);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(node_b_id, 100_000, 0, None, None, None, None),
+		Ok(())
+	);
+
+	// Without the peer's support for wumbo channels, we're limited in the channel value.
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_b_id,
+			MAX_FUNDING_SATOSHIS_NO_WUMBO + 1,
+			0,
+			None,
+			None,
+			None,
+			None
+		),
+		Err(vec![OpenChannelBlocker::FundingTooHigh {
+			max_channel_value_satoshis: MAX_FUNDING_SATOSHIS_NO_WUMBO
+		}])
+	);
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_b_id,
+			100_000,
+			100_000_001,
+			None,
+			None,
+			None,
+			None
+		),
+		Err(vec![OpenChannelBlocker::PushAmountTooHigh { max_push_msat: 100_000_000 }])
+	);
+
+	// A disconnected peer, a channel type we won't propose and a lack of anchor reserves are all
+	// reported together.
+	nodes[0].node.peer_disconnected(node_c_id);
+	nodes[2].node.peer_disconnected(nodes[0].node.get_our_node_id());
+	let requested = ChannelTypeFeatures::only_static_remote_key();
+	let mut proposed = ChannelTypeFeatures::only_static_remote_key();
+	proposed.set_anchors_zero_fee_htlc_tx_required();
+	let reserve_check = AnchorChannelReserveCheck::with_num_anchor_channels(
+		&AnchorChannelReserveContext::default(),
+		&[],
+		0,
+	);
+	assert!(!reserve_check.can_support_additional_anchor_channel());
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_c_id,
+			500,
+			0,
+			Some(&requested),
+			None,
+			Some(&reserve_check),
+			None
+		),
+		Err(vec![
+			OpenChannelBlocker::PeerNotConnected,
+			OpenChannelBlocker::ChannelTypeUnavailable { requested, proposed },
+			OpenChannelBlocker::FundingTooLow { min_channel_value_satoshis: 1000 },
+			OpenChannelBlocker::InsufficientAnchorReserve {
+				num_anchor_channels: 0,
+				num_supportable_channels: 0
+			},
+		])
+	);
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_preflight_open_channel_anchors() {
+	// Test that `preflight_open_channel` reports a peer without support for anchor outputs, an
+	// insufficient anchor reserve and a funding feerate below the mempool minimum.
+	use crate::events::bump_transaction::Utxo;
+	use crate::ln::channelmanager::OpenChannelBlocker;
+	use crate::util::anchor_channel_reserves::{
+		get_reserve_per_channel, AnchorChannelReserveCheck, AnchorChannelReserveContext,
+	};
+	use bitcoin::hashes::Hash;
+	use bitcoin::{Txid, WPubkeyHash};
+
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	let node_chanmgrs =
+		create_node_chanmgrs(3, &node_cfgs, &[Some(anchors_cfg.clone()), None, Some(anchors_cfg)]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	// nodes[1] doesn't support anchor outputs, thus we'd fall back to a legacy channel.
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(node_b_id, 100_000, 0, None, None, None, None),
+		Err(vec![OpenChannelBlocker::PeerLacksAnchorSupport {
+			proposed: ChannelTypeFeatures::only_static_remote_key()
+		}])
+	);
+	// If a legacy channel is explicitly requested, there's nothing to report.
+	let legacy_type = ChannelTypeFeatures::only_static_remote_key();
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_b_id,
+			100_000,
+			0,
+			Some(&legacy_type),
+			None,
+			None,
+			None
+		),
+		Ok(())
+	);
+
+	// Two UTXOs covering the reserve of a channel each support two anchor channels.
+	let context = AnchorChannelReserveContext::default();
+	let utxos = (0..2)
+		.map(|vout| {
+			Utxo::new_p2wpkh(
+				BitcoinOutPoint { txid: Txid::all_zeros(), vout },
+				get_reserve_per_channel(&context),
+				&WPubkeyHash::all_zeros(),
+			)
+		})
+		.collect::<Vec<_>>();
+	let sufficient_reserve =
+		AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 1);
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_c_id,
+			100_000,
+			0,
+			None,
+			None,
+			Some(&sufficient_reserve),
+			None
+		),
+		Ok(())
+	);
+	let insufficient_reserve =
+		AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 2);
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_c_id,
+			100_000,
+			0,
+			None,
+			None,
+			Some(&insufficient_reserve),
+			None
+		),
+		Err(vec![OpenChannelBlocker::InsufficientAnchorReserve {
+			num_anchor_channels: 2,
+			num_supportable_channels: 2
+		}])
+	);
+
+	// The reserve isn't relevant for a channel without anchor outputs.
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(
+			node_c_id,
+			100_000,
+			0,
+			Some(&legacy_type),
+			None,
+			Some(&insufficient_reserve),
+			None
+		),
+		Err(vec![OpenChannelBlocker::ChannelTypeUnavailable {
+			requested: legacy_type,
+			proposed: ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies()
+		}])
+	);
+
+	let min_feerate_target = ConfirmationTarget::MinAllowedAnchorChannelRemoteFee;
+	let mut target_override = chanmon_cfgs[0].fee_estimator.target_override.lock().unwrap();
+	target_override.insert(min_feerate_target, 1000);
+	drop(target_override);
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(node_c_id, 100_000, 0, None, None, None, Some(1000)),
+		Ok(())
+	);
+	assert_eq!(
+		nodes[0].node.preflight_open_channel(node_c_id, 100_000, 0, None, None, None, Some(999)),
+		Err(vec![OpenChannelBlocker::FundingFeerateTooLow {
+			funding_feerate_sat_per_1000_weight: 999,
+			min_feerate_sat_per_1000_weight: 1000
+		}])
+	);
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_get_peer_features() {
+	// Test that the features of a connected peer are those of its latest `init` message and that
+	// they are no longer reported once it disconnects.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, Some(anchors_cfg)]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	// nodes[1] doesn't support anchor outputs while nodes[2] does.
+	let node_b_features = nodes[1].init_features(node_a_id);
+	assert!(!node_b_features.supports_anchors_zero_fee_htlc_tx());
+	assert_eq!(nodes[0].node.get_peer_features(&node_b_id), Some(node_b_features));
+	assert_eq!(nodes[0].node.peer_supports_anchor_channels(&node_b_id), Some(false));
+	let node_c_features = nodes[2].init_features(node_a_id);
+	assert!(node_c_features.supports_anchors_zero_fee_htlc_tx());
+	assert_eq!(nodes[0].node.get_peer_features(&node_c_id), Some(node_c_features.clone()));
+	assert_eq!(nodes[0].node.peer_supports_anchor_channels(&node_c_id), Some(true));
+
+	// Peers we never connected to are unknown.
+	let unknown_node_id = PublicKey::from_slice(&[2; 33]).unwrap();
+	assert_eq!(nodes[0].node.get_peer_features(&unknown_node_id), None);
+	assert_eq!(nodes[0].node.peer_supports_anchor_channels(&unknown_node_id), None);
+
+	// Once disconnected, the features of a peer are no longer reported.
+	nodes[0].node.peer_disconnected(node_c_id);
+	nodes[2].node.peer_disconnected(node_a_id);
+	assert_eq!(nodes[0].node.get_peer_features(&node_c_id), None);
+	assert_eq!(nodes[0].node.peer_supports_anchor_channels(&node_c_id), None);
+
+	// Upon reconnection, the features of the new `init` message are reported.
+	let mut features = node_c_features;
+	features.clear_anchors_zero_fee_htlc_tx();
+	*nodes[2].override_init_features.borrow_mut() = Some(features.clone());
+	connect_nodes(&nodes[0], &nodes[2]);
+	assert_eq!(nodes[0].node.get_peer_features(&node_c_id), Some(features));
+	assert_eq!(nodes[0].node.peer_supports_anchor_channels(&node_c_id), Some(false));
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_channel_details_exogenous_fee_reserve() {
+	// Test that `ChannelDetails` reports the recommended reserve for anchor channels only, using the
+	// registered `AnchorChannelReserveContext` capped at the negotiated HTLC limits.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	let node_chanmgrs =
+		create_node_chanmgrs(3, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg), None]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	// More HTLCs are expected than the 50 accepted by default.
+	let context = AnchorChannelReserveContext {
+		expected_accepted_htlcs: 100,
+		..AnchorChannelReserveContext::default()
+	};
+	nodes[0].node.set_anchor_channel_reserve_context(context.clone());
+
+	let anchor_chan_id = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	let legacy_chan_id = create_announced_chan_between_nodes(&nodes, 0, 2).2;
+
+	let channels = nodes[0].node.list_channels();
+	assert_eq!(channels.len(), 2);
+	let anchor_chan = channels.iter().find(|chan| chan.channel_id == anchor_chan_id).unwrap();
+	assert!(anchor_chan.channel_type.as_ref().unwrap().supports_anchors_zero_fee_htlc_tx());
+	let capped_context =
+		AnchorChannelReserveContext { expected_accepted_htlcs: 50, ..context.clone() };
+	let expected_reserve = get_reserve_per_channel(&capped_context).to_sat();
+	assert!(expected_reserve < get_reserve_per_channel(&context).to_sat());
+	assert_eq!(anchor_chan.exogenous_fee_reserve_recommended_sat, Some(expected_reserve));
+
+	let legacy_chan = channels.iter().find(|chan| chan.channel_id == legacy_chan_id).unwrap();
+	assert!(!legacy_chan.channel_type.as_ref().unwrap().supports_anchors_zero_fee_htlc_tx());
+	assert_eq!(legacy_chan.exogenous_fee_reserve_recommended_sat, None);
+
+	// The reserve follows the registered context.
+	nodes[0].node.set_anchor_channel_reserve_context(AnchorChannelReserveContext::default());
+	let reserve = get_reserve_per_channel(&AnchorChannelReserveContext::default()).to_sat();
+	let anchor_chan = nodes[0].node.list_channels_with_counterparty(&node_b_id).pop().unwrap();
+	assert_eq!(anchor_chan.exogenous_fee_reserve_recommended_sat, Some(reserve));
+	let legacy_chan = nodes[0].node.list_channels_with_counterparty(&node_c_id).pop().unwrap();
+	assert_eq!(legacy_chan.exogenous_fee_reserve_recommended_sat, None);
+}
+
 fn do_test_sanity_on_in_flight_opens(steps: u8) {
 	// Previously, we had issues deserializing channels when we hadn't connected the first block
 	// after creation. To catch that and similar issues, we lean on the Node::drop impl to test
@@ -2951,6 +3261,165 @@ pub fn test_peer_disconnected_before_funding_broadcasted() {
 	check_closed_event!(&nodes[1], 1, ClosureReason::DisconnectedPeer, false, [node_a_id], 1000000);
 }
 
+#[xtest(feature = "_externalize_tests")]
+pub fn test_funding_output_registered_with_filter() {
+	// Test that the funding output of outbound and inbound channels is registered with the
+	// `ChannelManager`'s `chain::Filter` as soon as the funding transaction is known, before the
+	// `ChannelMonitor` is handed to the `chain::Watch`, and remains pending until it confirms.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let filter_a = Arc::new(test_utils::TestChainSource::new(Network::Testnet));
+	let filter_b = Arc::new(test_utils::TestChainSource::new(Network::Testnet));
+	nodes[0].node.set_chain_filter(Some(Arc::clone(&filter_a) as _));
+	nodes[1].node.set_chain_filter(Some(Arc::clone(&filter_b) as _));
+
+	nodes[0].node.create_channel(node_b_id, 100_000, 0, 42, None, None).unwrap();
+	let open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+	nodes[1].node.handle_open_channel(node_a_id, &open_channel);
+	let accept_channel = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a_id);
+	nodes[0].node.handle_accept_channel(node_b_id, &accept_channel);
+
+	let (temporary_channel_id, tx, funding_outpoint) =
+		create_funding_transaction(&nodes[0], &node_b_id, 100_000, 42);
+	let funding_script = tx.output[funding_outpoint.index as usize].script_pubkey.clone();
+	assert!(nodes[0].node.list_pending_funding_outputs().is_empty());
+	assert!(filter_a.watched_txn.lock().unwrap().is_empty());
+
+	// The outbound channel's funding output is registered once the funding transaction is
+	// provided, before any `ChannelMonitor` exists.
+	nodes[0]
+		.node
+		.funding_transaction_generated(temporary_channel_id, node_b_id, tx.clone())
+		.unwrap();
+	check_added_monitors(&nodes[0], 0);
+	let expected_txn = [(funding_outpoint.txid, funding_script.clone())];
+	let expected_outputs = [(funding_outpoint, funding_script.clone())];
+	assert_eq!(*filter_a.watched_txn.lock().unwrap(), hash_set_from_iter(expected_txn.clone()));
+	assert_eq!(
+		*filter_a.watched_outputs.lock().unwrap(),
+		hash_set_from_iter(expected_outputs.clone())
+	);
+	let pending_outputs = nodes[0].node.list_pending_funding_outputs();
+	assert_eq!(pending_outputs.len(), 1);
+	assert_eq!(pending_outputs[0].outpoint, funding_outpoint);
+	assert_eq!(pending_outputs[0].script_pubkey, funding_script);
+	assert!(filter_b.watched_txn.lock().unwrap().is_empty());
+
+	// The inbound channel's funding output is registered once `funding_created` is received.
+	let funding_created = get_event_msg!(nodes[0], MessageSendEvent::SendFundingCreated, node_b_id);
+	nodes[1].node.handle_funding_created(node_a_id, &funding_created);
+	check_added_monitors(&nodes[1], 1);
+	expect_channel_pending_event(&nodes[1], &node_a_id);
+	assert_eq!(*filter_b.watched_txn.lock().unwrap(), hash_set_from_iter(expected_txn));
+	assert_eq!(*filter_b.watched_outputs.lock().unwrap(), hash_set_from_iter(expected_outputs));
+	assert!(nodes[1].node.list_pending_funding_outputs() == pending_outputs);
+
+	let funding_signed = get_event_msg!(nodes[1], MessageSendEvent::SendFundingSigned, node_a_id);
+	nodes[0].node.handle_funding_signed(node_b_id, &funding_signed);
+	check_added_monitors(&nodes[0], 1);
+	expect_channel_pending_event(&nodes[0], &node_b_id);
+	assert!(nodes[0].node.list_pending_funding_outputs() == pending_outputs);
+	let broadcast_txn = nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+	assert_eq!(broadcast_txn, vec![tx.clone()]);
+
+	// Once the funding transaction confirms, the funding outputs are no longer pending.
+	let (channel_ready, _) =
+		create_chan_between_nodes_with_value_confirm(&nodes[0], &nodes[1], &tx);
+	create_chan_between_nodes_with_value_b(&nodes[0], &nodes[1], &channel_ready);
+	assert!(nodes[0].node.list_pending_funding_outputs().is_empty());
+	assert!(nodes[1].node.list_pending_funding_outputs().is_empty());
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_zero_conf_funding_output_registered_with_filter() {
+	// Test that the funding output of an inbound zero-conf channel is registered with the
+	// `chain::Filter` on `funding_created` and remains pending while the channel is already usable.
+	let mut manually_accept_config = test_default_channel_config();
+	manually_accept_config.manually_accept_inbound_channels = true;
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, Some(manually_accept_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let filter = Arc::new(test_utils::TestChainSource::new(Network::Testnet));
+	nodes[1].node.set_chain_filter(Some(Arc::clone(&filter) as _));
+
+	let (tx, _) = open_zero_conf_channel(&nodes[0], &nodes[1], None);
+	let funding_output = tx
+		.output
+		.iter()
+		.enumerate()
+		.find(|(_, output)| output.value == Amount::from_sat(100_000))
+		.unwrap();
+	let funding_outpoint = OutPoint { txid: tx.compute_txid(), index: funding_output.0 as u16 };
+	let funding_script = funding_output.1.script_pubkey.clone();
+	assert_eq!(
+		*filter.watched_txn.lock().unwrap(),
+		hash_set_from_iter([(funding_outpoint.txid, funding_script.clone())])
+	);
+	assert_eq!(
+		*filter.watched_outputs.lock().unwrap(),
+		hash_set_from_iter([(funding_outpoint, funding_script)])
+	);
+
+	// The channel is usable but its funding transaction has yet to confirm.
+	assert_eq!(nodes[1].node.list_usable_channels().len(), 1);
+	let pending_outputs = nodes[1].node.list_pending_funding_outputs();
+	assert_eq!(pending_outputs.len(), 1);
+	assert_eq!(pending_outputs[0].outpoint, funding_outpoint);
+
+	mine_transaction(&nodes[1], &tx);
+	assert!(nodes[1].node.list_pending_funding_outputs().is_empty());
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_abandoned_funding_output_no_longer_pending() {
+	// Test that the funding output of a channel which is abandoned before its funding transaction
+	// is broadcast is no longer returned as pending.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let filter = Arc::new(test_utils::TestChainSource::new(Network::Testnet));
+	nodes[0].node.set_chain_filter(Some(Arc::clone(&filter) as _));
+
+	nodes[0].node.create_channel(node_b_id, 100_000, 0, 42, None, None).unwrap();
+	let open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+	nodes[1].node.handle_open_channel(node_a_id, &open_channel);
+	let accept_channel = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a_id);
+	nodes[0].node.handle_accept_channel(node_b_id, &accept_channel);
+
+	let (temporary_channel_id, tx, funding_outpoint) =
+		create_funding_transaction(&nodes[0], &node_b_id, 100_000, 42);
+	nodes[0].node.funding_transaction_generated(temporary_channel_id, node_b_id, tx).unwrap();
+	get_event_msg!(nodes[0], MessageSendEvent::SendFundingCreated, node_b_id);
+	assert_eq!(filter.watched_outputs.lock().unwrap().len(), 1);
+	let pending_outputs = nodes[0].node.list_pending_funding_outputs();
+	assert_eq!(pending_outputs.len(), 1);
+	assert_eq!(pending_outputs[0].outpoint, funding_outpoint);
+
+	// The peer disconnects before `funding_signed` is received and the channel is abandoned.
+	nodes[0].node.peer_disconnected(node_b_id);
+	nodes[1].node.peer_disconnected(node_a_id);
+	for _ in 0..UNFUNDED_CHANNEL_AGE_LIMIT_TICKS {
+		nodes[0].node.timer_tick_occurred();
+	}
+	check_closed_event!(&nodes[0], 2, ClosureReason::DisconnectedPeer, true, [node_b_id], 100000);
+	check_closed_event!(&nodes[1], 1, ClosureReason::DisconnectedPeer, false, [node_a_id], 100000);
+	assert!(nodes[0].node.list_pending_funding_outputs().is_empty());
+}
+
 #[xtest(feature = "_externalize_tests")]
 pub fn test_simple_peer_disconnect() {
 	// Test that we can reconnect when there are no lost messages
@@ -3872,6 +4341,117 @@ pub fn test_claim_sizeable_push_msat() {
 	assert_eq!(spend_txn[0].input[0].sequence.0, BREAKDOWN_TIMEOUT as u32);
 }
 
+#[xtest(feature = "_externalize_tests")]
+pub fn test_funding_from_spendable_outputs() {
+	// Tests funding a new channel directly from the to_local output of a force-closed channel,
+	// which may only be spent once its CSV delay expired.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	let chan = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+	let message = "Channel force-closed".to_owned();
+	nodes[0]
+		.node
+		.force_close_broadcasting_latest_txn(&chan.2, &node_b_id, message.clone())
+		.unwrap();
+	check_closed_broadcast!(nodes[0], true);
+	check_added_monitors(&nodes[0], 1);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 100000);
+
+	let commitment_tx = nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+	assert_eq!(commitment_tx.len(), 1);
+	check_spends!(commitment_tx[0], chan.3);
+	mine_transaction(&nodes[0], &commitment_tx[0]);
+	let confirmation_height = nodes[0].best_block_info().1;
+	connect_blocks(&nodes[0], BREAKDOWN_TIMEOUT as u32 - 1);
+
+	let mut events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	let descriptor = match events.pop().unwrap() {
+		Event::SpendableOutputs { mut outputs, .. } => {
+			assert_eq!(outputs.len(), 1);
+			outputs.pop().unwrap()
+		},
+		_ => panic!("Unexpected event"),
+	};
+	assert!(matches!(descriptor, SpendableOutputDescriptor::DelayedPaymentOutput(_)));
+	let maturity_height = descriptor.maturity_height(confirmation_height);
+	assert_eq!(maturity_height, confirmation_height + BREAKDOWN_TIMEOUT as u32);
+	assert_eq!(maturity_height, nodes[0].best_block_info().1 + 1);
+
+	nodes[0].node.create_channel(node_c_id, 50_000, 0, 42, None, None).unwrap();
+	let open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_c_id);
+	nodes[2].node.handle_open_channel(node_a_id, &open_channel);
+	let accept_channel = get_event_msg!(nodes[2], MessageSendEvent::SendAcceptChannel, node_a_id);
+	nodes[0].node.handle_accept_channel(node_c_id, &accept_channel);
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	let (temporary_channel_id, output_script) = match &events[0] {
+		Event::FundingGenerationReady { temporary_channel_id, output_script, .. } => {
+			(*temporary_channel_id, output_script.clone())
+		},
+		_ => panic!("Unexpected event"),
+	};
+
+	// Had the commitment transaction confirmed in the latest block, the output would not be mature
+	// yet and funding is rejected without affecting the channel.
+	let best_block_height = nodes[0].best_block_info().1;
+	let change_script = Builder::new().push_opcode(opcodes::all::OP_RETURN).into_script();
+	let res = nodes[0].node.funding_transaction_generated_from_spendable_outputs(
+		temporary_channel_id,
+		node_c_id,
+		&[(descriptor.clone(), best_block_height)],
+		&nodes[0].keys_manager.backing,
+		change_script.clone(),
+		253,
+	);
+	let err = format!(
+		"Spendable output {} is not mature until block height {}",
+		descriptor.spendable_outpoint(),
+		best_block_height + BREAKDOWN_TIMEOUT as u32
+	);
+	assert_eq!(res, Err(APIError::APIMisuseError { err }));
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+	nodes[0]
+		.node
+		.funding_transaction_generated_from_spendable_outputs(
+			temporary_channel_id,
+			node_c_id,
+			&[(descriptor, confirmation_height)],
+			&nodes[0].keys_manager.backing,
+			change_script,
+			253,
+		)
+		.unwrap();
+	let funding_created = get_event_msg!(nodes[0], MessageSendEvent::SendFundingCreated, node_c_id);
+	nodes[2].node.handle_funding_created(node_a_id, &funding_created);
+	check_added_monitors(&nodes[2], 1);
+	expect_channel_pending_event(&nodes[2], &node_a_id);
+	let funding_signed = get_event_msg!(nodes[2], MessageSendEvent::SendFundingSigned, node_a_id);
+	nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().clear();
+	nodes[0].node.handle_funding_signed(node_c_id, &funding_signed);
+	check_added_monitors(&nodes[0], 1);
+	expect_channel_pending_event(&nodes[0], &node_c_id);
+
+	let funding_tx = {
+		let mut txn = nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap();
+		assert_eq!(txn.len(), 1);
+		txn.pop().unwrap()
+	};
+	check_spends!(funding_tx, commitment_tx[0]);
+	assert_eq!(funding_tx.input[0].sequence.0, BREAKDOWN_TIMEOUT as u32);
+	let funding_output = TxOut { value: Amount::from_sat(50_000), script_pubkey: output_script };
+	assert!(funding_tx.output.contains(&funding_output));
+}
+
 #[xtest(feature = "_externalize_tests")]
 pub fn test_claim_on_remote_sizeable_push_msat() {
 	// Same test as previous, just test on remote commitment tx, as per_commitment_point registration changes following you're funder/fundee and
@@ -5752,42 +6332,444 @@ pub fn test_free_and_fail_holding_cell_htlcs() {
 		},
 		_ => panic!("Unexpected event"),
 	}
-	match &events[1] {
-		&Event::PaymentFailed { ref payment_hash, .. } => {
-			assert_eq!(Some(payment_hash_2), *payment_hash);
-		},
-		_ => panic!("Unexpected event"),
+	match &events[1] {
+		&Event::PaymentFailed { ref payment_hash, .. } => {
+			assert_eq!(Some(payment_hash_2), *payment_hash);
+		},
+		_ => panic!("Unexpected event"),
+	}
+
+	// Complete the first payment and the RAA from the fee update.
+	let (payment_event, send_raa_event) = {
+		let mut msgs = nodes[0].node.get_and_clear_pending_msg_events();
+		assert_eq!(msgs.len(), 2);
+		(SendEvent::from_event(msgs.remove(0)), msgs.remove(0))
+	};
+	let raa = match send_raa_event {
+		MessageSendEvent::SendRevokeAndACK { msg, .. } => msg,
+		_ => panic!("Unexpected event"),
+	};
+	nodes[1].node.handle_revoke_and_ack(node_a_id, &raa);
+	check_added_monitors(&nodes[1], 1);
+	nodes[1].node.handle_update_add_htlc(node_a_id, &payment_event.msgs[0]);
+	commitment_signed_dance!(nodes[1], nodes[0], payment_event.commitment_msg, false);
+	nodes[1].node.process_pending_htlc_forwards();
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match events[0] {
+		Event::PaymentClaimable { .. } => {},
+		_ => panic!("Unexpected event"),
+	}
+	nodes[1].node.claim_funds(payment_preimage_1);
+	check_added_monitors(&nodes[1], 1);
+	expect_payment_claimed!(nodes[1], payment_hash_1, amt_1);
+
+	let mut update_msgs = get_htlc_update_msgs!(nodes[1], node_a_id);
+	nodes[0].node.handle_update_fulfill_htlc(node_b_id, update_msgs.update_fulfill_htlcs.remove(0));
+	commitment_signed_dance!(nodes[0], nodes[1], update_msgs.commitment_signed, false, true);
+	expect_payment_sent!(nodes[0], payment_preimage_1);
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_holding_cell_freed_in_batches() {
+	// Test that hundreds of fulfills and fails queued in the holding cell during a disconnection
+	// are released in batches of at most `max_holding_cell_updates_per_commitment` updates, in
+	// the order they were queued, with each batch waiting on the revocation of the previous one.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut config = test_default_channel_config();
+	config.channel_handshake_config.our_max_accepted_htlcs = 483;
+	config.channel_handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel = 100;
+	config.channel_config.max_holding_cell_updates_per_commitment = Some(100);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(config.clone()), Some(config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_id = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 10_000_000, 0).2;
+
+	const NUM_PAYMENTS: usize = 300;
+	let payments: Vec<_> = (0..NUM_PAYMENTS)
+		.map(|_| {
+			let (payment_preimage, payment_hash, ..) =
+				route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+			(payment_preimage, payment_hash)
+		})
+		.collect();
+
+	nodes[0].node.peer_disconnected(node_b_id);
+	nodes[1].node.peer_disconnected(node_a_id);
+
+	// Claim the first half of the payments and fail the second half while disconnected, queueing
+	// all fulfills ahead of the fails in the holding cell.
+	for (payment_preimage, payment_hash) in payments.iter().take(NUM_PAYMENTS / 2) {
+		nodes[1].node.claim_funds(*payment_preimage);
+		check_added_monitors(&nodes[1], 1);
+		expect_payment_claimed!(nodes[1], *payment_hash, 1_000_000);
+	}
+	let mut fail_types = Vec::new();
+	for (_, payment_hash) in payments.iter().skip(NUM_PAYMENTS / 2) {
+		nodes[1].node.fail_htlc_backwards(payment_hash);
+		fail_types.push(HTLCHandlingFailureType::Receive { payment_hash: *payment_hash });
+	}
+	expect_and_process_pending_htlcs_and_htlc_handling_failed(&nodes[1], &fail_types);
+	check_added_monitors(&nodes[1], 0);
+
+	let holding_cell_sizes = nodes[1].node.list_holding_cell_sizes();
+	assert_eq!(holding_cell_sizes.len(), 1);
+	assert_eq!(holding_cell_sizes[0].channel_id, chan_id);
+	assert_eq!(holding_cell_sizes[0].counterparty_node_id, node_a_id);
+	assert_eq!(holding_cell_sizes[0].num_pending_adds, 0);
+	assert_eq!(holding_cell_sizes[0].num_pending_fulfills, NUM_PAYMENTS / 2);
+	assert_eq!(holding_cell_sizes[0].num_pending_fails, NUM_PAYMENTS / 2);
+	assert!(!holding_cell_sizes[0].has_pending_fee_update);
+
+	// Upon reconnection, only the first batch of updates is released.
+	connect_nodes(&nodes[0], &nodes[1]);
+	let as_reestablish = get_chan_reestablish_msgs!(nodes[0], nodes[1]);
+	let bs_reestablish = get_chan_reestablish_msgs!(nodes[1], nodes[0]);
+	nodes[0].node.handle_channel_reestablish(node_b_id, &bs_reestablish[0]);
+	let (_, as_raa, as_update, _) = handle_chan_reestablish_msgs!(nodes[0], nodes[1]);
+	assert!(as_raa.is_none() && as_update.is_none());
+	check_added_monitors(&nodes[0], 0);
+	nodes[1].node.handle_channel_reestablish(node_a_id, &as_reestablish[0]);
+	let (_, bs_raa, bs_update, _) = handle_chan_reestablish_msgs!(nodes[1], nodes[0]);
+	assert!(bs_raa.is_none());
+	check_added_monitors(&nodes[1], 1);
+
+	let mut commitment_update = bs_update.unwrap();
+	let mut bs_raa_opt = None;
+	let mut released_fulfills = 0;
+	let mut released_fails = 0;
+	loop {
+		assert!(commitment_update.update_add_htlcs.is_empty());
+		assert!(commitment_update.update_fail_malformed_htlcs.is_empty());
+		let num_fulfills = commitment_update.update_fulfill_htlcs.len();
+		let num_fails = commitment_update.update_fail_htlcs.len();
+		assert_eq!(num_fulfills + num_fails, 100);
+		// No fail is released before all fulfills have been.
+		assert!(num_fails == 0 || released_fulfills + num_fulfills == NUM_PAYMENTS / 2);
+		released_fulfills += num_fulfills;
+		released_fails += num_fails;
+
+		let holding_cell_sizes = nodes[1].node.list_holding_cell_sizes();
+		assert_eq!(
+			holding_cell_sizes[0].num_pending_fulfills,
+			NUM_PAYMENTS / 2 - released_fulfills
+		);
+		assert_eq!(holding_cell_sizes[0].num_pending_fails, NUM_PAYMENTS / 2 - released_fails);
+
+		for update_fulfill in commitment_update.update_fulfill_htlcs {
+			nodes[0].node.handle_update_fulfill_htlc(node_b_id, update_fulfill);
+		}
+		for update_fail in commitment_update.update_fail_htlcs.iter() {
+			nodes[0].node.handle_update_fail_htlc(node_b_id, update_fail);
+		}
+		nodes[0]
+			.node
+			.handle_commitment_signed_batch_test(node_b_id, &commitment_update.commitment_signed);
+		check_added_monitors(&nodes[0], 1);
+		let (as_raa, as_cs) = if let Some(bs_raa) = bs_raa_opt.take() {
+			// We're still waiting on the revocation of our previous commitment, which we have to
+			// receive before we can sign the next one.
+			let as_raa = get_event_msg!(nodes[0], MessageSendEvent::SendRevokeAndACK, node_b_id);
+			nodes[0].node.handle_revoke_and_ack(node_b_id, &bs_raa);
+			check_added_monitors(&nodes[0], 1);
+			(as_raa, get_htlc_update_msgs!(nodes[0], node_b_id).commitment_signed)
+		} else {
+			get_revoke_commit_msgs!(nodes[0], node_b_id)
+		};
+
+		// The revocation frees the next batch, if any.
+		nodes[1].node.handle_revoke_and_ack(node_a_id, &as_raa);
+		check_added_monitors(&nodes[1], 1);
+		nodes[1].node.handle_commitment_signed_batch_test(node_a_id, &as_cs);
+		check_added_monitors(&nodes[1], 1);
+
+		let mut bs_raa = None;
+		let mut next_commitment_update = None;
+		for event in nodes[1].node.get_and_clear_pending_msg_events() {
+			match event {
+				MessageSendEvent::SendRevokeAndACK { msg, .. } => bs_raa = Some(msg),
+				MessageSendEvent::UpdateHTLCs { updates, .. } => {
+					next_commitment_update = Some(updates)
+				},
+				_ => panic!("Unexpected event {:?}", event),
+			}
+		}
+		match next_commitment_update {
+			Some(updates) => {
+				commitment_update = updates;
+				bs_raa_opt = bs_raa;
+			},
+			None => {
+				nodes[0].node.handle_revoke_and_ack(node_b_id, &bs_raa.unwrap());
+				check_added_monitors(&nodes[0], 1);
+				break;
+			},
+		}
+	}
+	assert_eq!(released_fulfills, NUM_PAYMENTS / 2);
+	assert_eq!(released_fails, NUM_PAYMENTS / 2);
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+	let holding_cell_sizes = nodes[1].node.list_holding_cell_sizes();
+	assert_eq!(holding_cell_sizes[0].num_pending_fulfills, 0);
+	assert_eq!(holding_cell_sizes[0].num_pending_fails, 0);
+
+	let events = nodes[0].node.get_and_clear_pending_events();
+	let num_sent = events.iter().filter(|ev| matches!(ev, Event::PaymentSent { .. })).count();
+	let num_path_failed =
+		events.iter().filter(|ev| matches!(ev, Event::PaymentPathFailed { .. })).count();
+	assert_eq!(num_sent, NUM_PAYMENTS / 2);
+	assert_eq!(num_path_failed, NUM_PAYMENTS / 2);
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_receivable_summary() {
+	// Tests that `ChannelManager::get_receivable_summary` accounts for the counterparty's balance
+	// and commitment fee, our reserve and our inbound HTLC limits, and reports which one bounds
+	// the amount we can receive.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut config = test_default_channel_config();
+	config.channel_handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel = 100;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, Some(config.clone())]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let channel_value_sat = 100_000;
+	let chan_id =
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, channel_value_sat, 0).2;
+
+	let feerate = get_feerate!(nodes[0], nodes[1], chan_id);
+	let channel_type_features = get_channel_type_features!(nodes[0], nodes[1], chan_id);
+	let anchors_msat = if channel_type_features.supports_anchors_zero_fee_htlc_tx() {
+		2 * ANCHOR_OUTPUT_VALUE_SATOSHI * 1000
+	} else {
+		0
+	};
+	let reserve_msat =
+		get_holder_selected_channel_reserve_satoshis(channel_value_sat, &config) * 1000;
+	let commit_tx_fee_msat = commit_tx_fee_msat(feerate, 1, &channel_type_features);
+
+	// The funder has nothing to send us yet, so node B's receivable amount is bounded by the
+	// funder's balance less the fee for the HTLC's output and the reserve we require of them. A
+	// single HTLC is further bounded by the largest HTLC the channel can carry.
+	let summary = nodes[1].node.get_receivable_summary();
+	assert_eq!(summary.channels.len(), 1);
+	let receivable = &summary.channels[0];
+	assert_eq!(receivable.channel_id, chan_id);
+	assert_eq!(receivable.counterparty_node_id, node_a_id);
+	let expected_total_msat =
+		channel_value_sat * 1000 - anchors_msat - commit_tx_fee_msat - reserve_msat;
+	assert_eq!(receivable.max_total_msat, expected_total_msat);
+	assert_eq!(receivable.total_constraint, ReceiveConstraint::CounterpartyBalance);
+	assert_eq!(receivable.max_single_htlc_msat, channel_value_sat * 1000 - 2 * reserve_msat);
+	assert_eq!(receivable.single_htlc_constraint, ReceiveConstraint::MaxHtlcValue);
+	assert_eq!(summary.max_total_msat, expected_total_msat);
+	assert_eq!(summary.max_single_htlc_msat, receivable.max_single_htlc_msat);
+	assert!(summary.can_receive(expected_total_msat));
+	assert!(!summary.can_receive(expected_total_msat + 1));
+
+	// Unlike the summary, the channel's inbound capacity doesn't account for the commitment fee.
+	let details = &nodes[1].node.list_channels()[0];
+	assert!(details.inbound_capacity_msat > receivable.max_total_msat);
+
+	// Node A has no counterparty balance to receive from.
+	let summary = nodes[0].node.get_receivable_summary();
+	assert_eq!(summary.channels[0].max_total_msat, 0);
+	assert_eq!(summary.channels[0].total_constraint, ReceiveConstraint::CounterpartyBalance);
+	assert!(!summary.can_receive(1));
+
+	// Once node B has a balance, node A is bounded by its default in-flight limit of 10% of the
+	// channel value.
+	send_payment(&nodes[0], &[&nodes[1]], 50_000_000);
+	let summary = nodes[0].node.get_receivable_summary();
+	let receivable = &summary.channels[0];
+	assert_eq!(receivable.max_total_msat, channel_value_sat * 1000 / 10);
+	assert_eq!(receivable.total_constraint, ReceiveConstraint::MaxHtlcValueInFlight);
+	assert_eq!(receivable.max_single_htlc_msat, channel_value_sat * 1000 / 10);
+	assert_eq!(receivable.single_htlc_constraint, ReceiveConstraint::MaxHtlcValueInFlight);
+
+	let summary = nodes[1].node.get_receivable_summary();
+	let receivable = &summary.channels[0];
+	assert_eq!(receivable.max_total_msat, expected_total_msat - 50_000_000);
+	assert_eq!(receivable.total_constraint, ReceiveConstraint::CounterpartyBalance);
+
+	// Nothing can be received over a channel whose peer is disconnected.
+	nodes[0].node.peer_disconnected(node_b_id);
+	nodes[1].node.peer_disconnected(node_a_id);
+	let summary = nodes[1].node.get_receivable_summary();
+	assert_eq!(summary.channels[0].max_total_msat, 0);
+	assert_eq!(summary.channels[0].total_constraint, ReceiveConstraint::ChannelNotUsable);
+	assert_eq!(summary.channels[0].single_htlc_constraint, ReceiveConstraint::ChannelNotUsable);
+	assert!(!summary.can_receive(1));
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_liquidity_threshold_alerts() {
+	// Tests that `Event::ChannelLiquidityThresholdCrossed` is generated once our balance crosses a
+	// configured threshold, but not while it moves within the hysteresis margin.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+
+	let channel_value_sat = 100_000;
+	let chan_id =
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, channel_value_sat, 0).2;
+
+	// Thresholds which don't delimit three bands are rejected.
+	let invalid =
+		LiquidityAlertThresholds { low_percent: 50, high_percent: 40, hysteresis_percent: 0 };
+	let update = ChannelConfigUpdate {
+		liquidity_alert_thresholds: Some(Some(invalid)),
+		..ChannelConfigUpdate::default()
+	};
+	let err = nodes[1].node.update_partial_channel_config(&node_a_id, &[chan_id], &update);
+	assert!(matches!(err, Err(APIError::APIMisuseError { .. })));
+
+	let thresholds =
+		LiquidityAlertThresholds { low_percent: 10, high_percent: 90, hysteresis_percent: 2 };
+	let update = ChannelConfigUpdate {
+		liquidity_alert_thresholds: Some(Some(thresholds)),
+		..ChannelConfigUpdate::default()
+	};
+	nodes[1].node.update_partial_channel_config(&node_a_id, &[chan_id], &update).unwrap();
+
+	// Node B starts out with an empty balance, which it records as low without generating an
+	// event, and 11% isn't enough to leave the low band given the hysteresis.
+	send_payment(&nodes[0], &[&nodes[1]], 11_000_000);
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+
+	send_payment(&nodes[0], &[&nodes[1]], 2_000_000);
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match &events[0] {
+		Event::ChannelLiquidityThresholdCrossed {
+			channel_id,
+			counterparty_node_id,
+			direction,
+			band,
+			local_balance_msat,
+			channel_value_satoshis,
+			..
+		} => {
+			assert_eq!(*channel_id, chan_id);
+			assert_eq!(*counterparty_node_id, node_a_id);
+			assert_eq!(*direction, LiquidityThresholdDirection::Increasing);
+			assert_eq!(*band, LiquidityBand::Normal);
+			assert_eq!(*local_balance_msat, 13_000_000);
+			assert_eq!(*channel_value_satoshis, channel_value_sat);
+		},
+		_ => panic!("Unexpected event"),
+	}
+
+	// Dropping back to 11% stays in the normal band until the low threshold itself is crossed. As
+	// node B is now the sender, its events also contain the payment's `PaymentSent` and
+	// `PaymentPathSuccessful`.
+	let liquidity_events = || {
+		let events = nodes[1].node.get_and_clear_pending_events();
+		assert!(events.iter().any(|ev| matches!(ev, Event::PaymentSent { .. })));
+		events
+			.into_iter()
+			.filter(|ev| matches!(ev, Event::ChannelLiquidityThresholdCrossed { .. }))
+			.collect::<Vec<_>>()
+	};
+	let payment_preimage = route_payment(&nodes[1], &[&nodes[0]], 2_000_000).0;
+	do_claim_payment_along_route(ClaimAlongRouteArgs::new(
+		&nodes[1],
+		&[&[&nodes[0]]],
+		payment_preimage,
+	));
+	assert!(liquidity_events().is_empty());
+
+	let payment_preimage = route_payment(&nodes[1], &[&nodes[0]], 2_000_000).0;
+	do_claim_payment_along_route(ClaimAlongRouteArgs::new(
+		&nodes[1],
+		&[&[&nodes[0]]],
+		payment_preimage,
+	));
+	let events = liquidity_events();
+	assert_eq!(events.len(), 1);
+	match &events[0] {
+		Event::ChannelLiquidityThresholdCrossed { direction, band, local_balance_msat, .. } => {
+			assert_eq!(*direction, LiquidityThresholdDirection::Decreasing);
+			assert_eq!(*band, LiquidityBand::Low);
+			assert_eq!(*local_balance_msat, 9_000_000);
+		},
+		_ => panic!("Unexpected event"),
+	}
+
+	// Node A never configured any thresholds.
+	assert!(nodes[0].node.get_and_clear_pending_events().is_empty());
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_max_inbound_htlcs_for_reserve() {
+	// Tests that inbound HTLCs beyond the limit set via
+	// `ChannelManager::set_max_inbound_htlcs_for_reserve` are failed back, while HTLCs which were
+	// already accepted are unaffected by the limit changing.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	let chan_id_1 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	let chan_id_2 = create_announced_chan_between_nodes(&nodes, 1, 2).2;
+
+	nodes[1].node.set_max_inbound_htlcs_for_reserve(&chan_id_1, &node_a_id, Some(4)).unwrap();
+
+	let mut payment_preimages = Vec::new();
+	for _ in 0..4 {
+		let payment_preimage = route_payment(&nodes[0], &[&nodes[1], &nodes[2]], 100_000).0;
+		payment_preimages.push(payment_preimage);
 	}
 
-	// Complete the first payment and the RAA from the fee update.
-	let (payment_event, send_raa_event) = {
-		let mut msgs = nodes[0].node.get_and_clear_pending_msg_events();
-		assert_eq!(msgs.len(), 2);
-		(SendEvent::from_event(msgs.remove(0)), msgs.remove(0))
-	};
-	let raa = match send_raa_event {
-		MessageSendEvent::SendRevokeAndACK { msg, .. } => msg,
-		_ => panic!("Unexpected event"),
-	};
-	nodes[1].node.handle_revoke_and_ack(node_a_id, &raa);
-	check_added_monitors(&nodes[1], 1);
+	// The fifth HTLC exceeds the limit and is failed back by node B.
+	let (route, payment_hash, _, payment_secret) =
+		get_route_and_payment_hash!(nodes[0], nodes[2], 100_000);
+	let onion = RecipientOnionFields::secret_only(payment_secret);
+	let id = PaymentId(payment_hash.0);
+	nodes[0].node.send_payment_with_route(route, payment_hash, onion, id).unwrap();
+	check_added_monitors(&nodes[0], 1);
+
+	let payment_event = SendEvent::from_node(&nodes[0]);
 	nodes[1].node.handle_update_add_htlc(node_a_id, &payment_event.msgs[0]);
 	commitment_signed_dance!(nodes[1], nodes[0], payment_event.commitment_msg, false);
-	nodes[1].node.process_pending_htlc_forwards();
-	let events = nodes[1].node.get_and_clear_pending_events();
-	assert_eq!(events.len(), 1);
-	match events[0] {
-		Event::PaymentClaimable { .. } => {},
-		_ => panic!("Unexpected event"),
-	}
-	nodes[1].node.claim_funds(payment_preimage_1);
+	expect_and_process_pending_htlcs(&nodes[1], false);
+	expect_htlc_handling_failed_destinations!(
+		nodes[1].node.get_and_clear_pending_events(),
+		&[HTLCHandlingFailureType::Forward { node_id: Some(node_c_id), channel_id: chan_id_2 }]
+	);
 	check_added_monitors(&nodes[1], 1);
-	expect_payment_claimed!(nodes[1], payment_hash_1, amt_1);
 
-	let mut update_msgs = get_htlc_update_msgs!(nodes[1], node_a_id);
-	nodes[0].node.handle_update_fulfill_htlc(node_b_id, update_msgs.update_fulfill_htlcs.remove(0));
-	commitment_signed_dance!(nodes[0], nodes[1], update_msgs.commitment_signed, false, true);
-	expect_payment_sent!(nodes[0], payment_preimage_1);
+	let updates = get_htlc_update_msgs!(nodes[1], node_a_id);
+	nodes[0].node.handle_update_fail_htlc(node_b_id, &updates.update_fail_htlcs[0]);
+	commitment_signed_dance!(nodes[0], nodes[1], updates.commitment_signed, false);
+	let reason = LocalHTLCFailureReason::TemporaryChannelFailure;
+	let conditions = PaymentFailedConditions::new().expected_htlc_error_data(reason, &[0; 2]);
+	expect_payment_failed_conditions(&nodes[0], payment_hash, false, conditions);
+
+	// Lowering the limit doesn't affect the HTLCs which were already accepted.
+	nodes[1].node.set_max_inbound_htlcs_for_reserve(&chan_id_1, &node_a_id, Some(2)).unwrap();
+	for payment_preimage in payment_preimages {
+		claim_payment(&nodes[0], &[&nodes[1], &nodes[2]], payment_preimage);
+	}
+
+	// Once the limit is removed, HTLCs are accepted again.
+	nodes[1].node.set_max_inbound_htlcs_for_reserve(&chan_id_1, &node_a_id, None).unwrap();
+	send_payment(&nodes[0], &[&nodes[1], &nodes[2]], 100_000);
 }
 
 // Test that if we fail to forward an HTLC that is being freed from the holding cell that the
@@ -7448,11 +8430,18 @@ pub fn test_manually_accept_inbound_channel_request() {
 			max_dust_htlc_exposure_msat: None,
 			force_close_avoidance_max_fee_satoshis: None,
 			accept_underpaying_htlcs: None,
+			dust_classification_feerate_multiplier_percent: None,
+			reveal_private_channel_identifiers: None,
+			max_holding_cell_updates_per_commitment: None,
+			liquidity_alert_thresholds: None,
+			max_inbound_htlcs_for_reserve: None,
 		}),
 	};
 	let events = nodes[1].node.get_and_clear_pending_events();
 	match events[0] {
-		Event::OpenChannelRequest { temporary_channel_id, .. } => {
+		Event::OpenChannelRequest { temporary_channel_id, estimated_reserve_required, .. } => {
+			// No on-chain reserve is required for channels without anchor outputs.
+			assert_eq!(estimated_reserve_required, None);
 			let config = Some(config_overrides);
 			nodes[1]
 				.node
@@ -10598,6 +11587,247 @@ fn test_nondust_htlc_fees_dust_exposure_delta() {
 	);
 }
 
+/// Opens a channel from `nodes[0]` to `nodes[1]` in which `nodes[0]` uses a dust limit of 546 sats,
+/// above the 354 sats used by `nodes[1]`.
+fn create_chan_with_distinct_dust_limits<'a, 'b, 'c>(nodes: &Vec<Node<'a, 'b, 'c>>) -> ChannelId {
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	nodes[0].node.create_channel(node_b_id, 1_000_000, 500_000_000, 42, None, None).unwrap();
+	let mut open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+	open_channel.common_fields.dust_limit_satoshis = 546;
+	nodes[1].node.handle_open_channel(node_a_id, &open_channel);
+	let accept_channel = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a_id);
+	nodes[0].node.handle_accept_channel(node_b_id, &accept_channel);
+
+	let (temp_chan_id, tx, _) = create_funding_transaction(&nodes[0], &node_b_id, 1_000_000, 42);
+	{
+		let mut per_peer_lock;
+		let mut peer_state_lock;
+		let channel =
+			get_channel_ref!(nodes[0], nodes[1], per_peer_lock, peer_state_lock, temp_chan_id);
+		if let Some(mut chan) = channel.as_unfunded_outbound_v1_mut() {
+			chan.context.holder_dust_limit_satoshis = 546;
+		} else {
+			panic!("Unexpected Channel phase");
+		}
+	}
+
+	nodes[0].node.funding_transaction_generated(temp_chan_id, node_b_id, tx.clone()).unwrap();
+	nodes[1].node.handle_funding_created(
+		node_a_id,
+		&get_event_msg!(nodes[0], MessageSendEvent::SendFundingCreated, node_b_id),
+	);
+	check_added_monitors(&nodes[1], 1);
+	expect_channel_pending_event(&nodes[1], &node_a_id);
+
+	nodes[0].node.handle_funding_signed(
+		node_b_id,
+		&get_event_msg!(nodes[1], MessageSendEvent::SendFundingSigned, node_a_id),
+	);
+	check_added_monitors(&nodes[0], 1);
+	expect_channel_pending_event(&nodes[0], &node_b_id);
+
+	let (channel_ready, chan_id) =
+		create_chan_between_nodes_with_value_confirm(&nodes[0], &nodes[1], &tx);
+	let (announcement, as_update, bs_update) =
+		create_chan_between_nodes_with_value_b(&nodes[0], &nodes[1], &channel_ready);
+	update_nodes_with_chan_announce(&nodes, 0, 1, &announcement, &as_update, &bs_update);
+	chan_id
+}
+
+/// The dust thresholds, in sats, of `node`'s non-anchor channel with `counterparty` at the given
+/// feerate, as `(holder_inbound, holder_outbound, counterparty_inbound, counterparty_outbound)`,
+/// where inbound and outbound are from `node`'s point of view.
+fn get_dust_thresholds_sat(
+	node: &Node, counterparty: &Node, chan_id: ChannelId, feerate_per_kw: u32,
+) -> (u64, u64, u64, u64) {
+	let get_holder_dust_limit_sat = |node: &Node, counterparty: &Node| {
+		let per_peer_state = node.node.per_peer_state.read().unwrap();
+		let chan_lock =
+			per_peer_state.get(&counterparty.node.get_our_node_id()).unwrap().lock().unwrap();
+		chan_lock.channel_by_id.get(&chan_id).unwrap().context().holder_dust_limit_satoshis
+	};
+	let holder_dust_limit_sat = get_holder_dust_limit_sat(node, counterparty);
+	let counterparty_dust_limit_sat = get_holder_dust_limit_sat(counterparty, node);
+	let (htlc_success_tx_fee_sat, htlc_timeout_tx_fee_sat) =
+		second_stage_tx_fees_sat(&ChannelTypeFeatures::only_static_remote_key(), feerate_per_kw);
+	(
+		htlc_success_tx_fee_sat + holder_dust_limit_sat,
+		htlc_timeout_tx_fee_sat + holder_dust_limit_sat,
+		htlc_timeout_tx_fee_sat + counterparty_dust_limit_sat,
+		htlc_success_tx_fee_sat + counterparty_dust_limit_sat,
+	)
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_project_dust_exposure() {
+	// Test that `ChannelManager::project_dust_exposure` classifies HTLCs which are not dust at the
+	// current feerate as dust on each commitment transaction once the projected feerate pushes
+	// them below that commitment's dust threshold.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut config = test_default_channel_config();
+	config.channel_config.max_dust_htlc_exposure = MaxDustHTLCExposure::FixedLimitMsat(5_000_000);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, Some(config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_id = create_chan_with_distinct_dust_limits(&nodes);
+
+	let feerate = get_feerate!(nodes[1], nodes[0], chan_id);
+	let dust_buffer_feerate = {
+		let per_peer_state = nodes[1].node.per_peer_state.read().unwrap();
+		let chan_lock = per_peer_state.get(&node_a_id).unwrap().lock().unwrap();
+		chan_lock.channel_by_id.get(&chan_id).unwrap().context().get_dust_buffer_feerate(None)
+	};
+	let projected_feerate = feerate * 20;
+
+	let (
+		holder_inbound_sat,
+		holder_outbound_sat,
+		counterparty_inbound_sat,
+		counterparty_outbound_sat,
+	) = get_dust_thresholds_sat(&nodes[1], &nodes[0], chan_id, projected_feerate);
+	// Due to the distinct dust limits, an inbound HTLC right at the counterparty's threshold is only
+	// dust on our commitment transaction, while an outbound HTLC right at our threshold is only dust
+	// on the counterparty's.
+	let inbound_htlc_sat = counterparty_inbound_sat;
+	assert!(inbound_htlc_sat < holder_inbound_sat);
+	let outbound_htlc_sat = holder_outbound_sat;
+	assert!(outbound_htlc_sat < counterparty_outbound_sat);
+
+	// Neither HTLC is dust at the current feerate, even including the dust buffer.
+	let (
+		holder_inbound_sat,
+		holder_outbound_sat,
+		counterparty_inbound_sat,
+		counterparty_outbound_sat,
+	) = get_dust_thresholds_sat(&nodes[1], &nodes[0], chan_id, dust_buffer_feerate);
+	assert!(inbound_htlc_sat >= holder_inbound_sat.max(counterparty_inbound_sat));
+	assert!(outbound_htlc_sat >= holder_outbound_sat.max(counterparty_outbound_sat));
+
+	route_payment(&nodes[0], &[&nodes[1]], inbound_htlc_sat * 1000);
+	route_payment(&nodes[1], &[&nodes[0]], outbound_htlc_sat * 1000);
+
+	let projection = nodes[1].node.project_dust_exposure(&chan_id, &node_a_id, feerate).unwrap();
+	assert_eq!(projection.feerate_sat_per_1000_weight, feerate);
+	assert_eq!(projection.holder_commitment_dust_exposure_msat, 0);
+	assert_eq!(projection.counterparty_commitment_dust_exposure_msat, 0);
+	assert_eq!(projection.max_dust_htlc_exposure_msat, 5_000_000);
+
+	let projection =
+		nodes[1].node.project_dust_exposure(&chan_id, &node_a_id, projected_feerate).unwrap();
+	assert_eq!(projection.feerate_sat_per_1000_weight, projected_feerate);
+	assert_eq!(projection.holder_commitment_dust_exposure_msat, inbound_htlc_sat * 1000);
+	assert_eq!(projection.counterparty_commitment_dust_exposure_msat, outbound_htlc_sat * 1000);
+
+	// The counterparty's projection mirrors ours, as its commitment transaction is our
+	// counterparty's commitment transaction.
+	let projection =
+		nodes[0].node.project_dust_exposure(&chan_id, &node_b_id, projected_feerate).unwrap();
+	assert_eq!(projection.holder_commitment_dust_exposure_msat, outbound_htlc_sat * 1000);
+	assert_eq!(projection.counterparty_commitment_dust_exposure_msat, inbound_htlc_sat * 1000);
+
+	assert!(nodes[1].node.project_dust_exposure(&chan_id, &node_b_id, feerate).is_err());
+}
+
+fn do_test_dust_classification_feerate_multiplier(on_holder_tx: bool) {
+	// Test that with `dust_classification_feerate_multiplier_percent` set, we fail inbound HTLCs
+	// which would only push our dust exposure over the limit once the feerate increases by more
+	// than the fixed dust buffer accounts for.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut config = test_default_channel_config();
+	config.channel_config.max_dust_htlc_exposure = MaxDustHTLCExposure::FixedLimitMsat(5_000_000);
+	config.channel_config.dust_classification_feerate_multiplier_percent = Some(2000);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, Some(config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_id = create_chan_with_distinct_dust_limits(&nodes);
+
+	let feerate = get_feerate!(nodes[1], nodes[0], chan_id);
+	let projected_feerate = feerate * 20;
+	let projection =
+		nodes[1].node.project_dust_exposure(&chan_id, &node_a_id, projected_feerate).unwrap();
+	let max_dust_htlc_exposure_msat = projection.max_dust_htlc_exposure_msat;
+	let (holder_inbound_sat, holder_outbound_sat, counterparty_inbound_sat, _) =
+		get_dust_thresholds_sat(&nodes[1], &nodes[0], chan_id, projected_feerate);
+
+	let (amount_msat, expected_exposure_msat) = if on_holder_tx {
+		// An inbound HTLC right at the counterparty's threshold is only dust on our commitment
+		// transaction at the projected feerate, so the first one is still accepted.
+		let amount_msat = counterparty_inbound_sat * 1000;
+		assert!(amount_msat / 1000 < holder_inbound_sat);
+		assert!(amount_msat <= max_dust_htlc_exposure_msat);
+		assert!(amount_msat * 2 > max_dust_htlc_exposure_msat);
+		route_payment(&nodes[0], &[&nodes[1]], amount_msat);
+		(amount_msat, amount_msat * 2)
+	} else {
+		// An outbound HTLC right at our threshold is only dust on the counterparty's commitment
+		// transaction at the projected feerate, and isn't subject to the stricter classification.
+		let outbound_amount_msat = holder_outbound_sat * 1000;
+		route_payment(&nodes[1], &[&nodes[0]], outbound_amount_msat);
+		let dust_buffer_feerate = {
+			let per_peer_state = nodes[1].node.per_peer_state.read().unwrap();
+			let chan_lock = per_peer_state.get(&node_a_id).unwrap().lock().unwrap();
+			chan_lock.channel_by_id.get(&chan_id).unwrap().context().get_dust_buffer_feerate(None)
+		};
+		let (inbound_buffer_sat, _, counterparty_inbound_buffer_sat, _) =
+			get_dust_thresholds_sat(&nodes[1], &nodes[0], chan_id, dust_buffer_feerate);
+		// An inbound HTLC which is not dust on either commitment transaction at the current
+		// feerate, but dust on both at the projected feerate.
+		let amount_msat = inbound_buffer_sat.max(counterparty_inbound_buffer_sat) * 1000;
+		assert!(amount_msat / 1000 < holder_inbound_sat.min(counterparty_inbound_sat));
+		assert!(amount_msat <= max_dust_htlc_exposure_msat);
+		assert!(outbound_amount_msat + amount_msat > max_dust_htlc_exposure_msat);
+		(amount_msat, outbound_amount_msat + amount_msat)
+	};
+
+	let (route, payment_hash, _, payment_secret) =
+		get_route_and_payment_hash!(nodes[0], nodes[1], amount_msat);
+	let onion = RecipientOnionFields::secret_only(payment_secret);
+	let id = PaymentId(payment_hash.0);
+	nodes[0].node.send_payment_with_route(route, payment_hash, onion, id).unwrap();
+	check_added_monitors(&nodes[0], 1);
+
+	let payment_event = SendEvent::from_node(&nodes[0]);
+	nodes[1].node.handle_update_add_htlc(node_a_id, &payment_event.msgs[0]);
+	commitment_signed_dance!(nodes[1], nodes[0], payment_event.commitment_msg, false);
+	expect_and_process_pending_htlcs(&nodes[1], false);
+	expect_htlc_handling_failed_destinations!(
+		nodes[1].node.get_and_clear_pending_events(),
+		&[HTLCHandlingFailureType::Receive { payment_hash }]
+	);
+	let commitment_tx = if on_holder_tx { "holder" } else { "counterparty" };
+	nodes[1].logger.assert_log(
+		"lightning::ln::channel",
+		format!(
+			"Cannot accept value that would put our exposure to dust HTLCs at {} over the limit {} on {} commitment tx at projected feerate {} sat/kW",
+			expected_exposure_msat, max_dust_htlc_exposure_msat, commitment_tx, projected_feerate
+		),
+		1,
+	);
+	check_added_monitors(&nodes[1], 1);
+
+	let updates = get_htlc_update_msgs!(nodes[1], node_a_id);
+	nodes[0].node.handle_update_fail_htlc(node_b_id, &updates.update_fail_htlcs[0]);
+	commitment_signed_dance!(nodes[0], nodes[1], updates.commitment_signed, false);
+	// Both dust limit failures are relayed as a `temporary_channel_failure`.
+	let reason = LocalHTLCFailureReason::TemporaryChannelFailure;
+	let conditions = PaymentFailedConditions::new().expected_htlc_error_data(reason, &[0; 2]);
+	expect_payment_failed_conditions(&nodes[0], payment_hash, false, conditions);
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_dust_classification_feerate_multiplier() {
+	do_test_dust_classification_feerate_multiplier(true);
+	do_test_dust_classification_feerate_multiplier(false);
+}
+
 #[xtest(feature = "_externalize_tests")]
 pub fn test_non_final_funding_tx() {
 	let chanmon_cfgs = create_chanmon_cfgs(2);
@@ -11717,3 +12947,190 @@ pub fn test_funding_signed_event() {
 	nodes[0].node.get_and_clear_pending_msg_events();
 	nodes[1].node.get_and_clear_pending_msg_events();
 }
+
+/// Adds an input spending a 2-of-2 multisig output of the given keys to the given funding
+/// transaction, returning an unsigned PSBT of it for the multisig's signers.
+fn add_multisig_funding_input(
+	funding_tx: &mut Transaction, signer_keys: &[SecretKey; 2],
+) -> bitcoin::Psbt {
+	let secp_ctx = Secp256k1::signing_only();
+	let pubkeys = signer_keys.map(|key| PublicKey::from_secret_key(&secp_ctx, &key));
+	let witness_script = chan_utils::make_funding_redeemscript(&pubkeys[0], &pubkeys[1]);
+	funding_tx.input.push(TxIn {
+		previous_output: BitcoinOutPoint { txid: bitcoin::Txid::all_zeros(), vout: 0 },
+		script_sig: ScriptBuf::new(),
+		sequence: Sequence::MAX,
+		witness: Witness::new(),
+	});
+
+	let mut psbt = bitcoin::Psbt::from_unsigned_tx(funding_tx.clone()).unwrap();
+	let spent_output =
+		TxOut { value: Amount::from_sat(200_000), script_pubkey: witness_script.to_p2wsh() };
+	psbt.inputs[0].witness_utxo = Some(spent_output);
+	psbt.inputs[0].witness_script = Some(witness_script);
+	psbt
+}
+
+/// Adds a signature of the given key to the multisig input of the given funding PSBT, as done by
+/// one of the multisig's signers.
+fn sign_multisig_funding_input(psbt: &mut bitcoin::Psbt, signer_key: &SecretKey) {
+	use bitcoin::sighash::{EcdsaSighashType, SighashCache};
+
+	let secp_ctx = Secp256k1::signing_only();
+	let witness_script = psbt.inputs[0].witness_script.clone().unwrap();
+	let value = psbt.inputs[0].witness_utxo.as_ref().unwrap().value;
+	let sighash = SighashCache::new(&psbt.unsigned_tx)
+		.p2wsh_signature_hash(0, &witness_script, value, EcdsaSighashType::All)
+		.unwrap();
+	let msg = bitcoin::secp256k1::Message::from_digest(sighash.to_byte_array());
+	let sig = bitcoin::ecdsa::Signature::sighash_all(secp_ctx.sign_ecdsa(&msg, signer_key));
+	let pubkey = bitcoin::PublicKey::new(PublicKey::from_secret_key(&secp_ctx, signer_key));
+	psbt.inputs[0].partial_sigs.insert(pubkey, sig);
+}
+
+/// Finalizes the multisig input of the given funding PSBT once both signers signed it.
+fn finalize_multisig_funding_input(psbt: &mut bitcoin::Psbt) {
+	let input = &mut psbt.inputs[0];
+	let mut sigs: Vec<_> = input.partial_sigs.iter().collect();
+	// The signatures have to be in the order of their keys in the script.
+	sigs.sort_by_key(|(pubkey, _)| pubkey.inner.serialize());
+	let mut witness = Witness::new();
+	witness.push(Vec::new());
+	for (_, sig) in sigs {
+		witness.push(sig.to_vec());
+	}
+	witness.push(input.witness_script.as_ref().unwrap().as_bytes());
+	input.final_script_witness = Some(witness);
+	input.partial_sigs.clear();
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_funding_psbt_signed_in_two_passes() {
+	// Fund a channel from an output locked by a 2-of-2 multisig, whose signers sign the funding
+	// transaction in turn.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	nodes[0].node.create_channel(node_b_id, 100_000, 0, 42, None, None).unwrap();
+	let open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+	nodes[1].node.handle_open_channel(node_a_id, &open_channel);
+	let accept_channel = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a_id);
+	nodes[0].node.handle_accept_channel(node_b_id, &accept_channel);
+
+	let (temp_channel_id, mut tx, _) =
+		create_funding_transaction(&nodes[0], &node_b_id, 100_000, 42);
+	let signer_keys =
+		[SecretKey::from_slice(&[1; 32]).unwrap(), SecretKey::from_slice(&[2; 32]).unwrap()];
+	let psbt = add_multisig_funding_input(&mut tx, &signer_keys);
+	nodes[0]
+		.node
+		.funding_transaction_generated_unsigned(temp_channel_id, node_b_id, tx.clone())
+		.unwrap();
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+	// A PSBT changing the funding output is rejected, leaving the channel pending.
+	let mut changed_tx = tx.clone();
+	changed_tx.output[0].value = Amount::from_sat(99_000);
+	let changed_psbt = bitcoin::Psbt::from_unsigned_tx(changed_tx).unwrap();
+	let res = nodes[0].node.funding_psbt_updated(temp_channel_id, changed_psbt);
+	assert!(matches!(res, Err(APIError::APIMisuseError { .. })));
+
+	// Once the first signer signed, the funding transaction is still incomplete.
+	let mut first_pass = psbt.clone();
+	sign_multisig_funding_input(&mut first_pass, &signer_keys[0]);
+	let res = nodes[0].node.funding_psbt_updated(temp_channel_id, first_pass.clone());
+	assert_eq!(res, Ok(false));
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+	// The second signer adds its signature and finalizes the input, completing the transaction.
+	let mut second_pass = first_pass;
+	sign_multisig_funding_input(&mut second_pass, &signer_keys[1]);
+	finalize_multisig_funding_input(&mut second_pass);
+	let signed_tx = second_pass.clone().extract_tx_unchecked_fee_rate();
+	assert_eq!(signed_tx.compute_txid(), tx.compute_txid());
+	assert_eq!(nodes[0].node.funding_psbt_updated(temp_channel_id, second_pass), Ok(true));
+	check_added_monitors(&nodes[0], 0);
+
+	let funding_created = get_event_msg!(nodes[0], MessageSendEvent::SendFundingCreated, node_b_id);
+	nodes[1].node.handle_funding_created(node_a_id, &funding_created);
+	check_added_monitors(&nodes[1], 1);
+	expect_channel_pending_event(&nodes[1], &node_a_id);
+
+	let funding_signed = get_event_msg!(nodes[1], MessageSendEvent::SendFundingSigned, node_a_id);
+	nodes[0].node.handle_funding_signed(node_b_id, &funding_signed);
+	check_added_monitors(&nodes[0], 1);
+	expect_channel_pending_event(&nodes[0], &node_b_id);
+
+	// The fully signed funding transaction is broadcast, after which no PSBTs are accepted.
+	assert_eq!(*nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap(), vec![signed_tx]);
+	let res = nodes[0].node.funding_psbt_updated(temp_channel_id, psbt);
+	assert!(matches!(res, Err(APIError::ChannelUnavailable { .. })));
+}
+
+#[xtest(feature = "_externalize_tests")]
+pub fn test_funding_psbt_not_signed_in_time() {
+	// If the funding transaction isn't fully signed before the pending channel times out, the
+	// channel is closed with an error sent to our counterparty, after which the PSBT is no longer
+	// accepted.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	nodes[0].node.create_channel(node_b_id, 100_000, 0, 42, None, None).unwrap();
+	let open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+	nodes[1].node.handle_open_channel(node_a_id, &open_channel);
+	let accept_channel = get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a_id);
+	nodes[0].node.handle_accept_channel(node_b_id, &accept_channel);
+
+	let (temp_channel_id, mut tx, _) =
+		create_funding_transaction(&nodes[0], &node_b_id, 100_000, 42);
+	let signer_keys =
+		[SecretKey::from_slice(&[1; 32]).unwrap(), SecretKey::from_slice(&[2; 32]).unwrap()];
+	let mut psbt = add_multisig_funding_input(&mut tx, &signer_keys);
+	nodes[0].node.funding_transaction_generated_unsigned(temp_channel_id, node_b_id, tx).unwrap();
+	sign_multisig_funding_input(&mut psbt, &signer_keys[0]);
+	assert_eq!(nodes[0].node.funding_psbt_updated(temp_channel_id, psbt.clone()), Ok(false));
+
+	for _ in 0..UNFUNDED_CHANNEL_AGE_LIMIT_TICKS {
+		nodes[0].node.timer_tick_occurred();
+	}
+	let msg_events = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(msg_events.len(), 1);
+	let err_msg = match msg_events[0] {
+		MessageSendEvent::HandleError {
+			action: ErrorAction::SendErrorMessage { ref msg },
+			node_id,
+		} => {
+			assert_eq!(node_id, node_b_id);
+			assert_eq!(msg.channel_id, temp_channel_id);
+			msg.clone()
+		},
+		_ => panic!("Unexpected event"),
+	};
+	let reason = ClosureReason::FundingTimedOut;
+	check_closed_event(&nodes[0], 1, reason, false, &[node_b_id], 100000);
+	assert!(nodes[0].node.list_channels().is_empty());
+
+	// Our counterparty forgets the channel once it receives the error.
+	nodes[1].node.handle_error(node_a_id, &err_msg);
+	let reason = ClosureReason::CounterpartyForceClosed { peer_msg: UntrustedString(err_msg.data) };
+	check_closed_event(&nodes[1], 1, reason, false, &[node_a_id], 100000);
+	assert!(nodes[1].node.list_channels().is_empty());
+
+	// Completing the signatures afterwards doesn't resurrect the channel.
+	sign_multisig_funding_input(&mut psbt, &signer_keys[1]);
+	finalize_multisig_funding_input(&mut psbt);
+	let res = nodes[0].node.funding_psbt_updated(temp_channel_id, psbt);
+	assert!(matches!(res, Err(APIError::ChannelUnavailable { .. })));
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+	assert!(nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+}
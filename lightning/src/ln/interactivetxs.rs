@@ -23,6 +23,7 @@ use crate::chain::chaininterface::fee_for_weight;
 use crate::events::bump_transaction::{BASE_INPUT_WEIGHT, EMPTY_SCRIPT_SIG_WEIGHT};
 use crate::ln::chan_utils::FUNDING_TRANSACTION_WITNESS_WEIGHT;
 use crate::ln::channel::{FundingNegotiationContext, TOTAL_BITCOIN_SUPPLY_SATOSHIS};
+use crate::ln::channel_state::InteractiveTxNegotiationProgress;
 use crate::ln::msgs;
 use crate::ln::msgs::{MessageSendEvent, SerialId, TxSignatures};
 use crate::ln::types::ChannelId;
@@ -1140,6 +1141,20 @@ impl Default for StateMachine {
 	}
 }
 
+impl StateMachine {
+	fn negotiation_context(&self) -> Option<&NegotiationContext> {
+		match self {
+			Self::SentChangeMsg(s) => Some(&s.0),
+			Self::ReceivedChangeMsg(s) => Some(&s.0),
+			Self::SentTxComplete(s) => Some(&s.0),
+			Self::ReceivedTxComplete(s) => Some(&s.0),
+			Self::Indeterminate | Self::NegotiationComplete(_) | Self::NegotiationAborted(_) => {
+				None
+			},
+		}
+	}
+}
+
 // The `StateMachine` internally executes the actual transition between two states and keeps
 // track of the current state. This macro defines _how_ those state transitions happen to
 // update the internal state.
@@ -1530,6 +1545,15 @@ pub(super) struct InteractiveTxConstructor {
 	channel_id: ChannelId,
 	inputs_to_contribute: Vec<(SerialId, InputOwned)>,
 	outputs_to_contribute: Vec<(SerialId, OutputOwned)>,
+	/// The outpoints of the non-shared inputs we contribute, to be released if the negotiation is
+	/// aborted.
+	contributed_inputs: Vec<OutPoint>,
+	/// The total value of [`Self::contributed_inputs`].
+	contributed_input_satoshis: u64,
+	/// The non-shared outputs we contribute.
+	contributed_outputs: Vec<TxOut>,
+	/// The number of timer ticks since we last received a message from the counterparty.
+	ticks_since_counterparty_message: u16,
 }
 
 #[allow(clippy::enum_variant_names)] // Clippy doesn't like the repeated `Tx` prefix here
@@ -1692,12 +1716,20 @@ impl InteractiveTxConstructor {
 		);
 
 		// Check for the existence of prevouts'
+		let mut contributed_input_satoshis = 0u64;
 		for (txin, tx) in inputs_to_contribute.iter() {
 			let vout = txin.previous_output.vout as usize;
-			if tx.as_transaction().output.get(vout).is_none() {
-				return Err(AbortReason::PrevTxOutInvalid);
+			match tx.as_transaction().output.get(vout) {
+				Some(prev_output) => {
+					contributed_input_satoshis =
+						contributed_input_satoshis.saturating_add(prev_output.value.to_sat());
+				},
+				None => return Err(AbortReason::PrevTxOutInvalid),
 			}
 		}
+		let contributed_inputs =
+			inputs_to_contribute.iter().map(|(txin, _)| txin.previous_output).collect();
+		let contributed_outputs = outputs_to_contribute.clone();
 		let mut inputs_to_contribute: Vec<(SerialId, InputOwned)> = inputs_to_contribute
 			.into_iter()
 			.map(|(txin, tx)| {
@@ -1745,6 +1777,10 @@ impl InteractiveTxConstructor {
 			channel_id,
 			inputs_to_contribute,
 			outputs_to_contribute,
+			contributed_inputs,
+			contributed_input_satoshis,
+			contributed_outputs,
+			ticks_since_counterparty_message: 0,
 		};
 		// We'll store the first message for the initiator.
 		if is_initiator {
@@ -1800,6 +1836,7 @@ impl InteractiveTxConstructor {
 	pub fn handle_tx_add_input(
 		&mut self, msg: &msgs::TxAddInput,
 	) -> Result<InteractiveTxMessageSend, AbortReason> {
+		self.ticks_since_counterparty_message = 0;
 		do_state_transition!(self, received_tx_add_input, msg)?;
 		self.maybe_send_message()
 	}
@@ -1807,6 +1844,7 @@ impl InteractiveTxConstructor {
 	pub fn handle_tx_remove_input(
 		&mut self, msg: &msgs::TxRemoveInput,
 	) -> Result<InteractiveTxMessageSend, AbortReason> {
+		self.ticks_since_counterparty_message = 0;
 		do_state_transition!(self, received_tx_remove_input, msg)?;
 		self.maybe_send_message()
 	}
@@ -1814,6 +1852,7 @@ impl InteractiveTxConstructor {
 	pub fn handle_tx_add_output(
 		&mut self, msg: &msgs::TxAddOutput,
 	) -> Result<InteractiveTxMessageSend, AbortReason> {
+		self.ticks_since_counterparty_message = 0;
 		do_state_transition!(self, received_tx_add_output, msg)?;
 		self.maybe_send_message()
 	}
@@ -1821,6 +1860,7 @@ impl InteractiveTxConstructor {
 	pub fn handle_tx_remove_output(
 		&mut self, msg: &msgs::TxRemoveOutput,
 	) -> Result<InteractiveTxMessageSend, AbortReason> {
+		self.ticks_since_counterparty_message = 0;
 		do_state_transition!(self, received_tx_remove_output, msg)?;
 		self.maybe_send_message()
 	}
@@ -1828,6 +1868,7 @@ impl InteractiveTxConstructor {
 	pub fn handle_tx_complete(
 		&mut self, msg: &msgs::TxComplete,
 	) -> Result<HandleTxCompleteValue, AbortReason> {
+		self.ticks_since_counterparty_message = 0;
 		do_state_transition!(self, received_tx_complete, msg)?;
 		match &self.state_machine {
 			StateMachine::ReceivedTxComplete(_) => {
@@ -1862,6 +1903,49 @@ impl InteractiveTxConstructor {
 			_ => panic!("Signing session is not ready yet"),
 		}
 	}
+
+	/// Returns the progress of the negotiation, or `None` if it has already completed or been
+	/// aborted.
+	pub fn progress(&self) -> Option<InteractiveTxNegotiationProgress> {
+		let context = self.state_machine.negotiation_context()?;
+		let is_holder_serial_id =
+			|serial_id: &SerialId| !context.is_serial_id_valid_for_counterparty(serial_id);
+		let holder_inputs_added =
+			context.inputs.keys().filter(|id| is_holder_serial_id(id)).count();
+		let holder_outputs_added =
+			context.outputs.keys().filter(|id| is_holder_serial_id(id)).count();
+		Some(InteractiveTxNegotiationProgress {
+			is_initiator: context.holder_is_initiator,
+			holder_inputs_added,
+			holder_outputs_added,
+			counterparty_inputs_added: context.inputs.len() - holder_inputs_added,
+			counterparty_outputs_added: context.outputs.len() - holder_outputs_added,
+			holder_inputs_pending: self.inputs_to_contribute.len(),
+			holder_outputs_pending: self.outputs_to_contribute.len(),
+			holder_contributed_input_satoshis: self.contributed_input_satoshis,
+			feerate_sat_per_1000_weight: context.feerate_sat_per_kw,
+			ticks_since_counterparty_message: self.ticks_since_counterparty_message,
+		})
+	}
+
+	/// Determines whether we should abort the negotiation due to the counterparty not having sent
+	/// us a message within `timeout_ticks` timer ticks.
+	///
+	/// This should be called on every [`super::channelmanager::ChannelManager::check_peer_timeouts`].
+	pub fn should_abort_stalled_negotiation(&mut self, timeout_ticks: u16) -> bool {
+		if self.state_machine.negotiation_context().is_none() {
+			return false;
+		}
+		self.ticks_since_counterparty_message =
+			self.ticks_since_counterparty_message.saturating_add(1);
+		self.ticks_since_counterparty_message >= timeout_ticks
+	}
+
+	/// Consumes the constructor of an aborted negotiation, returning the non-shared inputs and
+	/// outputs we contributed to it.
+	pub fn into_contribution(self) -> (Vec<OutPoint>, Vec<TxOut>) {
+		(self.contributed_inputs, self.contributed_outputs)
+	}
 }
 
 /// Determine whether a change output should be added, and if yes, of what size, considering our
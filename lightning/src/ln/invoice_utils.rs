@@ -589,6 +589,93 @@ fn prefer_current_channel(
 	current_channel > candidate_channel
 }
 
+/// A route hint for a just-in-time (JIT) channel through an intercept SCID, as provided by a
+/// Lightning Service Provider (LSP) or generated via [`ChannelManager::get_intercept_scid_hint`].
+///
+/// Intercept SCIDs are typically only accepted by the LSP for a limited time and within certain
+/// amount bounds, which are modeled here such that no invoices are handed out with hints the LSP
+/// would reject. See [`ChannelManager::create_bolt11_invoice_with_jit_channel_hint`].
+///
+/// [`ChannelManager::get_intercept_scid_hint`]: crate::ln::channelmanager::ChannelManager::get_intercept_scid_hint
+/// [`ChannelManager::create_bolt11_invoice_with_jit_channel_hint`]: crate::ln::channelmanager::ChannelManager::create_bolt11_invoice_with_jit_channel_hint
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JitChannelHint {
+	/// The intercept SCID over which HTLCs will be intercepted by the LSP.
+	pub intercept_scid: u64,
+	/// The node id of the LSP intercepting HTLCs over [`Self::intercept_scid`].
+	pub lsp_node_id: PublicKey,
+	/// The fees charged by the LSP for forwarding over [`Self::intercept_scid`].
+	pub fees: RoutingFees,
+	/// The CLTV expiry delta required by the LSP for forwarding over [`Self::intercept_scid`].
+	pub cltv_expiry_delta: u16,
+	/// The minimum amount the LSP accepts over [`Self::intercept_scid`], if any.
+	pub htlc_minimum_msat: Option<u64>,
+	/// The maximum amount the LSP accepts over [`Self::intercept_scid`], if any.
+	pub htlc_maximum_msat: Option<u64>,
+	/// The time, as a duration since the Unix epoch, after which the LSP will no longer accept
+	/// HTLCs over [`Self::intercept_scid`].
+	pub valid_until: Duration,
+}
+
+impl JitChannelHint {
+	/// Returns whether the hint is still valid at the given time, as a duration since the Unix
+	/// epoch.
+	pub fn is_valid_at(&self, duration_since_epoch: Duration) -> bool {
+		duration_since_epoch <= self.valid_until
+	}
+
+	/// Returns whether an invoice for `amount_msats` can be paid over the hint. Invoices without
+	/// an amount are always considered payable.
+	pub fn supports_amount(&self, amount_msats: Option<u64>) -> bool {
+		match amount_msats {
+			Some(amount_msats) => {
+				self.htlc_minimum_msat.map_or(true, |min| amount_msats >= min)
+					&& self.htlc_maximum_msat.map_or(true, |max| amount_msats <= max)
+			},
+			None => true,
+		}
+	}
+
+	/// Returns the [`RouteHint`] to include in an invoice.
+	pub fn route_hint(&self) -> RouteHint {
+		RouteHint(vec![RouteHintHop {
+			src_node_id: self.lsp_node_id,
+			short_channel_id: self.intercept_scid,
+			fees: self.fees,
+			cltv_expiry_delta: self.cltv_expiry_delta,
+			htlc_minimum_msat: self.htlc_minimum_msat,
+			htlc_maximum_msat: self.htlc_maximum_msat,
+		}])
+	}
+}
+
+/// Selects a [`JitChannelHint`] that is valid for an invoice expiring at `invoice_expires_at` and
+/// accepting `amount_msats`, calling `refresh_hint` at most once to request a fresh hint if `hint`
+/// isn't suitable.
+pub(super) fn select_jit_channel_hint<F>(
+	hint: JitChannelHint, refresh_hint: F, invoice_expires_at: Duration, amount_msats: Option<u64>,
+) -> Result<JitChannelHint, CreationError>
+where
+	F: FnOnce(&JitChannelHint) -> Option<JitChannelHint>,
+{
+	let check_hint = |hint: &JitChannelHint| {
+		if !hint.supports_amount(amount_msats) {
+			Err(CreationError::InvalidAmount)
+		} else if !hint.is_valid_at(invoice_expires_at) {
+			Err(CreationError::RouteHintExpired)
+		} else {
+			Ok(())
+		}
+	};
+	match check_hint(&hint) {
+		Ok(()) => Ok(hint),
+		Err(e) => match refresh_hint(&hint) {
+			Some(fresh_hint) => check_hint(&fresh_hint).map(|()| fresh_hint),
+			None => Err(e),
+		},
+	}
+}
+
 /// Adds relevant context to a [`Record`] before passing it to the wrapped [`Logger`].
 struct WithChannelDetails<'a, 'b, L: Deref>
 where
@@ -2099,4 +2186,83 @@ mod test {
 		let expected = vec!["a0", "a1", "b1"];
 		assert_eq!(expected, result);
 	}
+
+	fn make_jit_channel_hint(intercept_scid: u64, valid_until_secs: u64) -> JitChannelHint {
+		JitChannelHint {
+			intercept_scid,
+			lsp_node_id: PublicKey::from_slice(&[2; 33]).unwrap(),
+			fees: RoutingFees { base_msat: 1000, proportional_millionths: 100 },
+			cltv_expiry_delta: 144,
+			htlc_minimum_msat: Some(10_000),
+			htlc_maximum_msat: Some(1_000_000),
+			valid_until: Duration::from_secs(valid_until_secs),
+		}
+	}
+
+	#[test]
+	fn test_jit_channel_hint_route_hint() {
+		let hint = make_jit_channel_hint(42, 1000);
+		let route_hint = hint.route_hint();
+		assert_eq!(route_hint.0.len(), 1);
+		assert_eq!(route_hint.0[0].src_node_id, hint.lsp_node_id);
+		assert_eq!(route_hint.0[0].short_channel_id, 42);
+		assert_eq!(route_hint.0[0].fees, hint.fees);
+		assert_eq!(route_hint.0[0].cltv_expiry_delta, 144);
+		assert_eq!(route_hint.0[0].htlc_minimum_msat, Some(10_000));
+		assert_eq!(route_hint.0[0].htlc_maximum_msat, Some(1_000_000));
+	}
+
+	#[test]
+	fn test_select_jit_channel_hint() {
+		let no_refresh = |_: &JitChannelHint| -> Option<JitChannelHint> { panic!() };
+		let expires_at = Duration::from_secs(1000);
+
+		// A hint that outlives the invoice is used as-is.
+		let hint = make_jit_channel_hint(42, 1000);
+		assert_eq!(
+			select_jit_channel_hint(hint.clone(), no_refresh, expires_at, Some(100_000)),
+			Ok(hint)
+		);
+
+		// A hint that expires before the invoice is refreshed.
+		let stale_hint = make_jit_channel_hint(42, 999);
+		let fresh_hint = make_jit_channel_hint(43, 2000);
+		assert_eq!(
+			select_jit_channel_hint(
+				stale_hint.clone(),
+				|_| Some(fresh_hint.clone()),
+				expires_at,
+				None
+			),
+			Ok(fresh_hint)
+		);
+
+		// If no fresh hint can be obtained, the invoice can't be built.
+		assert_eq!(
+			select_jit_channel_hint(stale_hint.clone(), |_| None, expires_at, None),
+			Err(CreationError::RouteHintExpired)
+		);
+
+		// A refreshed hint must itself be valid.
+		assert_eq!(
+			select_jit_channel_hint(
+				stale_hint.clone(),
+				|hint| Some(hint.clone()),
+				expires_at,
+				None
+			),
+			Err(CreationError::RouteHintExpired)
+		);
+
+		// Amounts outside the bounds accepted by the LSP are rejected.
+		let hint = make_jit_channel_hint(42, 1000);
+		assert_eq!(
+			select_jit_channel_hint(hint.clone(), |_| None, expires_at, Some(1_000)),
+			Err(CreationError::InvalidAmount)
+		);
+		assert_eq!(
+			select_jit_channel_hint(hint, |_| None, expires_at, Some(1_000_001)),
+			Err(CreationError::InvalidAmount)
+		);
+	}
 }
@@ -15,6 +15,10 @@ pub mod functional_test_utils;
 
 pub mod chan_utils;
 pub mod channel_keys;
+pub mod channel_message_demux;
+pub mod channel_receipt;
+#[cfg(any(test, feature = "channel_state_replication"))]
+pub mod channel_replication;
 pub mod channel_state;
 pub mod channelmanager;
 mod features;
@@ -12,10 +12,11 @@
 //! Further functional tests which test blockchain reorganizations.
 
 use crate::sign::{ecdsa::EcdsaChannelSigner, OutputSpender, SignerProvider, SpendableOutputDescriptor};
-use crate::chain::channelmonitor::{ANTI_REORG_DELAY, ARCHIVAL_DELAY_BLOCKS,LATENCY_GRACE_PERIOD_BLOCKS, COUNTERPARTY_CLAIMABLE_WITHIN_BLOCKS_PINNABLE, Balance, BalanceSource, ChannelMonitorUpdateStep};
+use crate::chain::channelmonitor::{ANTI_REORG_DELAY, ARCHIVAL_DELAY_BLOCKS,LATENCY_GRACE_PERIOD_BLOCKS, COUNTERPARTY_CLAIMABLE_WITHIN_BLOCKS_PINNABLE, AnchorClaimStatus, Balance, BalanceCertainty, BalanceSource, ChannelMonitor, ChannelMonitorUpdateStep, DetailedBalance, ResolutionTimelineEntry};
 use crate::chain::transaction::OutPoint;
 use crate::chain::chaininterface::{ConfirmationTarget, LowerBoundedFeeEstimator, compute_feerate_sat_per_1000_weight};
-use crate::events::bump_transaction::{BumpTransactionEvent};
+use crate::events::bump_transaction::{BumpTransactionEvent, ClaimNotifier, HTLCDeadlineFeerateSchedule, PendingClaimSpend};
+use crate::events::bump_transaction::sync::{BumpTransactionEventHandlerSync, WalletSync};
 use crate::events::{Event, ClosureReason, HTLCHandlingFailureType};
 use crate::ln::channel;
 use crate::ln::types::ChannelId;
@@ -23,10 +24,15 @@ use crate::ln::chan_utils;
 use crate::ln::channelmanager::{BREAKDOWN_TIMEOUT, PaymentId, RecipientOnionFields};
 use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, MessageSendEvent};
 use crate::crypto::utils::sign;
-use crate::util::ser::Writeable;
+use crate::util::ser::{ReadableArgs, Writeable};
+use crate::util::test_channel_signer::TestChannelSigner;
 use crate::util::scid_utils::block_from_scid;
+use crate::util::anchor_channel_reserves::{ClaimWeightComponent, ForceCloseTransactionType, ReserveAuditLog, ReserveAuditLogger, validate_weight_constants};
+use crate::util::errors::APIError;
+use crate::util::persist::{KVStoreNamespace, KVStoreSync};
+use crate::util::test_utils::TestStore;
 
-use bitcoin::{Amount, PublicKey, ScriptBuf, Transaction, TxIn, TxOut, Witness};
+use bitcoin::{Amount, BlockHash, FeeRate, PublicKey, ScriptBuf, Transaction, TxIn, TxOut, Witness};
 use bitcoin::locktime::absolute::LockTime;
 use bitcoin::script::Builder;
 use bitcoin::opcodes;
@@ -36,6 +42,7 @@ use bitcoin::sighash::{SighashCache, EcdsaSighashType};
 use bitcoin::transaction::Version;
 
 use crate::prelude::*;
+use crate::sync::{Arc, Mutex};
 
 use crate::ln::functional_test_utils::*;
 
@@ -434,6 +441,16 @@ fn sorted_vec<T: Ord>(mut v: Vec<T>) -> Vec<T> {
 	v
 }
 
+/// Returns the fee paid by `tx`, looking up the values of its inputs in `parent_txs`.
+fn tx_fee(tx: &Transaction, parent_txs: &[&Transaction]) -> Amount {
+	let input_value: Amount = tx.input.iter().map(|input| {
+		let parent_tx = parent_txs.iter()
+			.find(|parent_tx| parent_tx.compute_txid() == input.previous_output.txid).unwrap();
+		parent_tx.output[input.previous_output.vout as usize].value
+	}).sum();
+	input_value - tx.output.iter().map(|output| output.value).sum::<Amount>()
+}
+
 /// Asserts that `a` and `b` are close, but maybe off by up to 5.
 /// This is useful when checking fees and weights on transactions as things may vary by a few based
 /// on signature size and signature size estimation being non-exact.
@@ -887,6 +904,19 @@ fn do_test_balances_on_local_commitment_htlcs(anchors: bool) {
 	let chan_feerate = get_feerate!(nodes[0], nodes[1], chan_id) as u64;
 	let channel_type_features = get_channel_type_features!(nodes[0], nodes[1], chan_id);
 
+	// Estimate the cost of force-closing before doing so, which includes an HTLC-timeout
+	// transaction for each of our outbound HTLCs.
+	let cost_estimate = nodes[0].node.estimate_force_close_cost(
+		&chan_id, &nodes[1].node.get_our_node_id(), FeeRate::from_sat_per_kwu(chan_feerate)
+	).unwrap();
+	assert_eq!(cost_estimate.fees_committed, !anchors);
+	let estimated_htlc_fees: Vec<_> = cost_estimate.transactions.iter()
+		.filter(|tx| tx.transaction_type == ForceCloseTransactionType::HtlcTimeout)
+		.map(|tx| tx.fee)
+		.collect();
+	assert_eq!(estimated_htlc_fees.len(), 2);
+	assert_eq!(cost_estimate.transactions.len(), if anchors { 4 } else { 3 });
+
 	// First confirm the commitment transaction on nodes[0], which should leave us with three
 	// claimable balances.
 	let message = "Channel force-closed".to_owned();
@@ -933,6 +963,11 @@ fn do_test_balances_on_local_commitment_htlcs(anchors: bool) {
 		}, htlc_balance_known_preimage.clone(), htlc_balance_unknown_preimage.clone()]),
 		sorted_vec(nodes[0].chain_monitor.chain_monitor.get_monitor(chan_id).unwrap().get_claimable_balances()));
 
+	// The fee committed to in the commitment transaction matches our estimate exactly.
+	assert_eq!(cost_estimate.transactions[0].transaction_type, ForceCloseTransactionType::Commitment);
+	assert_eq!(cost_estimate.transactions[0].fee, tx_fee(&commitment_tx, &[&funding_tx]));
+	assert_eq!(cost_estimate.transactions[0].fee.to_sat(), commitment_tx_fee);
+
 	// Get nodes[1]'s HTLC claim tx for the second HTLC
 	mine_transaction(&nodes[1], &commitment_tx);
 	check_added_monitors!(nodes[1], 1);
@@ -970,6 +1005,17 @@ fn do_test_balances_on_local_commitment_htlcs(anchors: bool) {
 		assert_eq!(timeout_htlc_txn[0].input[0].witness.last().unwrap().len(), chan_utils::OFFERED_HTLC_SCRIPT_WEIGHT);
 		assert_eq!(timeout_htlc_txn[1].input[0].witness.last().unwrap().len(), chan_utils::OFFERED_HTLC_SCRIPT_WEIGHT);
 	}
+	if anchors {
+		// We estimate each HTLC to be claimed in a separate transaction with its own wallet input,
+		// so the aggregated claim pays less.
+		let estimated_fee: Amount = estimated_htlc_fees.iter().copied().sum();
+		assert!(tx_fee(&timeout_htlc_txn[0], &[&commitment_tx, &coinbase_tx]) <= estimated_fee);
+	} else {
+		// The fees committed to in the HTLC-timeout transactions match our estimate exactly.
+		for htlc_tx in timeout_htlc_txn.iter() {
+			assert_eq!(tx_fee(htlc_tx, &[&commitment_tx]), estimated_htlc_fees[0]);
+		}
+	}
 
 	// Now confirm nodes[1]'s HTLC claim, giving nodes[0] the preimage. Note that the "maybe
 	// claimable" balance remains until we see ANTI_REORG_DELAY blocks.
@@ -1312,6 +1358,406 @@ fn test_no_preimage_inbound_htlc_balances() {
 	assert!(nodes[1].chain_monitor.chain_monitor.get_monitor(chan_id).unwrap().get_claimable_balances().is_empty());
 }
 
+#[test]
+fn test_detailed_balance_deadlines() {
+	// Tests that `get_detailed_claimable_balances` provides the height by which we have to claim
+	// each balance as well as the confirmations remaining, that these update as blocks connect,
+	// and that they survive a round-trip through monitor serialization.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+
+	let (_, _, chan_id, _) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+
+	let (payment_preimage, payment_hash, ..) = route_payment(&nodes[0], &[&nodes[1]], 10_000_000);
+	let htlc_cltv_timeout = nodes[1].best_block_info().1 + TEST_FINAL_CLTV + 1; // Note ChannelManager adds one to CLTV timeouts for safety
+
+	let get_detailed_balances = || {
+		let monitor = nodes[1].chain_monitor.chain_monitor.get_monitor(chan_id).unwrap();
+		let detailed_balances = monitor.get_detailed_claimable_balances();
+		// The detailed balances must always match the plain ones.
+		assert_eq!(detailed_balances.iter().map(|b| b.balance.clone()).collect::<Vec<_>>(),
+			monitor.get_claimable_balances());
+		// ...and must round-trip through serialization.
+		let (_, read_monitor) = <(BlockHash, ChannelMonitor<TestChannelSigner>)>::read(
+			&mut &monitor.encode()[..], (nodes[1].keys_manager, nodes[1].keys_manager)).unwrap();
+		assert_eq!(read_monitor.get_detailed_claimable_balances(), detailed_balances);
+		detailed_balances
+	};
+
+	// Before B has the preimage, the inbound HTLC has to be claimed by its expiry, but nothing in
+	// the balance we'd get on close is time-sensitive.
+	let b_received_htlc_balance = DetailedBalance {
+		balance: Balance::MaybePreimageClaimableHTLC {
+			amount_satoshis: 10_000,
+			expiry_height: htlc_cltv_timeout,
+			payment_hash,
+		},
+		deadline_height: Some(htlc_cltv_timeout),
+		confirmations_remaining: None,
+	};
+	let balances = get_detailed_balances();
+	assert_eq!(balances.len(), 2);
+	assert!(balances.contains(&b_received_htlc_balance));
+	assert!(balances.iter().any(|b| matches!(b.balance, Balance::ClaimableOnChannelClose { .. })
+		&& b.deadline_height.is_none() && b.confirmations_remaining.is_none()));
+
+	// Once B has the preimage, the HTLC is included in the on-close balance, which now has to be
+	// claimed by the HTLC's expiry.
+	nodes[1].node.claim_funds(payment_preimage);
+	check_added_monitors!(nodes[1], 1);
+	expect_payment_claimed!(nodes[1], payment_hash, 10_000_000);
+	let _ = get_htlc_update_msgs!(nodes[1], node_a_id);
+
+	let balances = get_detailed_balances();
+	assert_eq!(balances.len(), 1);
+	assert!(matches!(balances[0].balance, Balance::ClaimableOnChannelClose { .. }));
+	assert_eq!(balances[0].deadline_height, Some(htlc_cltv_timeout));
+	assert_eq!(balances[0].confirmations_remaining, None);
+
+	// Confirm A's commitment transaction. B's main output is now awaiting confirmations while the
+	// HTLC is contentious until B's preimage claim confirms.
+	let as_txn = get_local_commitment_txn!(nodes[0], chan_id);
+	mine_transaction(&nodes[1], &as_txn[0]);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_a_id], 1000000);
+	let bs_htlc_claim_txn = nodes[1].tx_broadcaster.txn_broadcast();
+	assert_eq!(bs_htlc_claim_txn.len(), 1);
+	check_spends!(bs_htlc_claim_txn[0], as_txn[0]);
+
+	let to_remote_conf_height = nodes[1].best_block_info().1 + ANTI_REORG_DELAY - 1;
+	let contentious_balance = DetailedBalance {
+		balance: Balance::ContentiousClaimable {
+			amount_satoshis: 10_000,
+			timeout_height: htlc_cltv_timeout,
+			payment_hash,
+			payment_preimage,
+		},
+		deadline_height: Some(htlc_cltv_timeout),
+		confirmations_remaining: None,
+	};
+	let to_remote_balance = |confirmations_remaining| DetailedBalance {
+		balance: Balance::ClaimableAwaitingConfirmations {
+			amount_satoshis: 500_000,
+			confirmation_height: to_remote_conf_height,
+			source: BalanceSource::CounterpartyForceClosed,
+		},
+		deadline_height: None,
+		confirmations_remaining: Some(confirmations_remaining),
+	};
+	let balances = get_detailed_balances();
+	assert_eq!(balances.len(), 2);
+	assert!(balances.contains(&contentious_balance));
+	assert!(balances.contains(&to_remote_balance(ANTI_REORG_DELAY - 1)));
+
+	// As blocks connect, the remaining confirmations count down while the deadline stays fixed.
+	connect_blocks(&nodes[1], 1);
+	let balances = get_detailed_balances();
+	assert!(balances.contains(&contentious_balance));
+	assert!(balances.contains(&to_remote_balance(ANTI_REORG_DELAY - 2)));
+
+	// Once B's preimage claim confirms, the HTLC balance awaits confirmations, but still carries
+	// the HTLC expiry as a deadline in case the claim is reorganized out of the chain.
+	mine_transaction(&nodes[1], &bs_htlc_claim_txn[0]);
+	let htlc_claim_conf_height = nodes[1].best_block_info().1 + ANTI_REORG_DELAY - 1;
+	let balances = get_detailed_balances();
+	assert_eq!(balances.len(), 2);
+	assert!(balances.contains(&to_remote_balance(ANTI_REORG_DELAY - 3)));
+	assert!(balances.contains(&DetailedBalance {
+		balance: Balance::ClaimableAwaitingConfirmations {
+			amount_satoshis: 10_000,
+			confirmation_height: htlc_claim_conf_height,
+			source: BalanceSource::Htlc,
+		},
+		deadline_height: Some(htlc_cltv_timeout),
+		confirmations_remaining: Some(ANTI_REORG_DELAY - 1),
+	}));
+}
+
+#[test]
+fn test_claimable_balances_cache() {
+	// Tests that the claimable balances cached by a `ChannelMonitor` always match those computed
+	// from scratch, across monitor updates, transaction confirmations and reorgs.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+
+	let (_, _, chan_id, _) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+
+	let check_balances = || {
+		let monitor = nodes[1].chain_monitor.chain_monitor.get_monitor(chan_id).unwrap();
+		let balances = monitor.get_claimable_balances();
+		assert_eq!(monitor.get_claimable_balances(), balances);
+		assert_eq!(monitor.recompute_claimable_balances(), balances);
+		// A monitor read from disk starts out without any cached balances.
+		let (_, read_monitor) = <(BlockHash, ChannelMonitor<TestChannelSigner>)>::read(
+			&mut &monitor.encode()[..], (nodes[1].keys_manager, nodes[1].keys_manager)).unwrap();
+		assert_eq!(read_monitor.get_claimable_balances(), balances);
+		sorted_vec(balances)
+	};
+	assert_eq!(check_balances().len(), 1);
+
+	// Monitor updates adding and claiming an HTLC change the balances.
+	let (payment_preimage, payment_hash, ..) = route_payment(&nodes[0], &[&nodes[1]], 10_000_000);
+	assert_eq!(check_balances().len(), 2);
+
+	nodes[1].node.claim_funds(payment_preimage);
+	check_added_monitors!(nodes[1], 1);
+	expect_payment_claimed!(nodes[1], payment_hash, 10_000_000);
+	let _ = get_htlc_update_msgs!(nodes[1], node_a_id);
+	assert_eq!(check_balances().len(), 1);
+
+	// As do confirmations of A's commitment transaction and B's preimage claim.
+	let as_txn = get_local_commitment_txn!(nodes[0], chan_id);
+	mine_transaction(&nodes[1], &as_txn[0]);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_a_id], 1000000);
+	let bs_htlc_claim_txn = nodes[1].tx_broadcaster.txn_broadcast();
+	assert_eq!(bs_htlc_claim_txn.len(), 1);
+	let balances = check_balances();
+	assert!(balances.iter().any(|balance| matches!(balance, Balance::ContentiousClaimable { .. })));
+
+	mine_transaction(&nodes[1], &bs_htlc_claim_txn[0]);
+	let claimed_balances = check_balances();
+	assert!(!claimed_balances.iter().any(|balance| matches!(balance, Balance::ContentiousClaimable { .. })));
+
+	// Reorging out B's preimage claim reverts the balances, even if no other blocks are connected.
+	disconnect_blocks(&nodes[1], 1);
+	nodes[1].tx_broadcaster.txn_broadcast();
+	assert_eq!(check_balances(), balances);
+
+	mine_transaction(&nodes[1], &bs_htlc_claim_txn[0]);
+	assert_eq!(check_balances(), claimed_balances);
+
+	// Once everything has reached enough confirmations, no balances remain.
+	connect_blocks(&nodes[1], ANTI_REORG_DELAY - 1);
+	let _ = nodes[1].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert!(check_balances().is_empty());
+}
+
+fn do_test_resolution_timeline(anchors: bool) {
+	// Tests that `get_resolution_timeline` provides the heights at which each balance can be
+	// claimed and becomes spendable, based on the CSV delay on our outputs and the HTLC expiry,
+	// and that the outpoints it provides match those handed over via `SpendableOutputs`.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut user_config = test_default_channel_config();
+	if anchors {
+		user_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		user_config.manually_accept_inbound_channels = true;
+	}
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(user_config.clone()), Some(user_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let coinbase_tx = provide_anchor_reserves(&nodes);
+
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+
+	let (_, payment_hash, ..) = route_payment(&nodes[0], &[&nodes[1]], 10_000_000);
+	let htlc_cltv_timeout = nodes[0].best_block_info().1 + TEST_FINAL_CLTV + 1; // Note ChannelManager adds one to CLTV timeouts for safety
+
+	let get_timeline = || {
+		let monitor = nodes[0].chain_monitor.chain_monitor.get_monitor(chan_id).unwrap();
+		let timeline = monitor.get_resolution_timeline(nodes[0].best_block_info().1);
+		// The timeline must cover exactly the claimable balances.
+		assert_eq!(sorted_vec(timeline.iter().map(|entry| entry.balance.clone()).collect()),
+			sorted_vec(monitor.get_claimable_balances()));
+		timeline
+	};
+
+	// Before the channel is closed, the timeline assumes we force-close now, after which our
+	// balance is delayed by our CSV delay, and the HTLC additionally by its expiry.
+	let cur_height = nodes[0].best_block_info().1;
+	let timeline = get_timeline();
+	assert_eq!(timeline.len(), 2);
+	assert!(matches!(timeline[0].balance, Balance::ClaimableOnChannelClose { .. }));
+	assert_eq!(timeline[0].claimable_height, cur_height);
+	assert_eq!(timeline[0].spendable_height, cur_height + BREAKDOWN_TIMEOUT as u32 + ANTI_REORG_DELAY);
+	assert_eq!(timeline[0].needs_exogenous_fees, anchors);
+	assert_eq!(timeline[0].spendable_outpoint, None);
+	let htlc_entry = ResolutionTimelineEntry {
+		balance: Balance::MaybeTimeoutClaimableHTLC {
+			amount_satoshis: 10_000,
+			claimable_height: htlc_cltv_timeout,
+			payment_hash,
+			outbound_payment: true,
+		},
+		claimable_height: htlc_cltv_timeout,
+		spendable_height: htlc_cltv_timeout + BREAKDOWN_TIMEOUT as u32 + ANTI_REORG_DELAY,
+		needs_exogenous_fees: anchors,
+		spendable_outpoint: None,
+	};
+	assert_eq!(timeline[1], htlc_entry);
+
+	// Force-close and confirm our commitment transaction. Our balance is now awaiting its CSV
+	// delay, and its output is known.
+	let message = "Channel force-closed".to_owned();
+	let node_a_commitment_claimable = nodes[0].best_block_info().1 + BREAKDOWN_TIMEOUT as u32;
+	nodes[0].node.force_close_broadcasting_latest_txn(&chan_id, &nodes[1].node.get_our_node_id(), message.clone()).unwrap();
+	check_added_monitors!(nodes[0], 1);
+	check_closed_broadcast!(nodes[0], true);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+	check_closed_event!(nodes[0], 1, reason, [nodes[1].node.get_our_node_id()], 1000000);
+	let commitment_tx = {
+		let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+		assert_eq!(txn.len(), 1);
+		let commitment_tx = txn.pop().unwrap();
+		check_spends!(commitment_tx, funding_tx);
+		commitment_tx
+	};
+	mine_transaction(&nodes[0], &commitment_tx);
+	if nodes[0].connect_style.borrow().updates_best_block_first() {
+		let mut txn = nodes[0].tx_broadcaster.txn_broadcast();
+		assert_eq!(txn.len(), 1);
+		assert_eq!(txn[0].compute_txid(), commitment_tx.compute_txid());
+	}
+
+	let timeline = get_timeline();
+	assert_eq!(timeline.len(), 2);
+	assert!(matches!(timeline[0].balance, Balance::ClaimableAwaitingConfirmations {
+		confirmation_height, source: BalanceSource::HolderForceClosed, ..
+	} if confirmation_height == node_a_commitment_claimable));
+	assert_eq!(timeline[0].claimable_height, node_a_commitment_claimable);
+	assert_eq!(timeline[0].spendable_height, node_a_commitment_claimable + ANTI_REORG_DELAY);
+	assert!(!timeline[0].needs_exogenous_fees);
+	let to_self_outpoint = timeline[0].spendable_outpoint.unwrap();
+	assert_eq!(to_self_outpoint.txid, commitment_tx.compute_txid());
+	assert_eq!(timeline[1], htlc_entry);
+
+	// Once the HTLC expires, we claim it back with an HTLC-Timeout transaction, whose output is
+	// again subject to our CSV delay.
+	connect_blocks(&nodes[0], TEST_FINAL_CLTV);
+	assert_eq!(nodes[0].best_block_info().1, htlc_cltv_timeout);
+	if anchors {
+		handle_bump_htlc_event(&nodes[0], 1);
+	}
+	let timeout_htlc_txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+	assert_eq!(timeout_htlc_txn.len(), 1);
+	if anchors {
+		check_spends!(timeout_htlc_txn[0], commitment_tx, coinbase_tx);
+	} else {
+		check_spends!(timeout_htlc_txn[0], commitment_tx);
+	}
+
+	let node_a_htlc_claimable = nodes[0].best_block_info().1 + BREAKDOWN_TIMEOUT as u32;
+	mine_transaction(&nodes[0], &timeout_htlc_txn[0]);
+	let timeline = get_timeline();
+	assert_eq!(timeline.len(), 2);
+	assert_eq!(timeline[0].spendable_outpoint, Some(to_self_outpoint));
+	// With anchors, the HTLC-Timeout transaction's output carries the full HTLC value, allowing
+	// it to be matched. Otherwise, the transaction's fee is paid from the HTLC value.
+	let htlc_outpoint = OutPoint { txid: timeout_htlc_txn[0].compute_txid(), index: 0 };
+	assert_eq!(timeline[1], ResolutionTimelineEntry {
+		balance: Balance::ClaimableAwaitingConfirmations {
+			amount_satoshis: 10_000,
+			confirmation_height: node_a_htlc_claimable,
+			source: BalanceSource::Htlc,
+		},
+		claimable_height: node_a_htlc_claimable,
+		spendable_height: node_a_htlc_claimable + ANTI_REORG_DELAY,
+		needs_exogenous_fees: false,
+		spendable_outpoint: if anchors { Some(htlc_outpoint) } else { None },
+	});
+
+	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+	expect_payment_failed!(nodes[0], payment_hash, false);
+
+	// The outpoints match those of the outputs handed over once the CSV delays expire.
+	connect_blocks(&nodes[0], node_a_commitment_claimable - nodes[0].best_block_info().1);
+	let to_self_spendable_output = test_spendable_output(&nodes[0], &commitment_tx, false);
+	assert_eq!(to_self_spendable_output[0].spendable_outpoint(), to_self_outpoint);
+	assert_eq!(get_timeline().len(), 1);
+
+	connect_blocks(&nodes[0], node_a_htlc_claimable - nodes[0].best_block_info().1);
+	let htlc_spendable_output = test_spendable_output(&nodes[0], &timeout_htlc_txn[0], false);
+	if anchors {
+		assert_eq!(htlc_spendable_output[0].spendable_outpoint(), htlc_outpoint);
+	}
+	assert!(get_timeline().is_empty());
+}
+
+#[test]
+fn test_resolution_timeline() {
+	do_test_resolution_timeline(false);
+	do_test_resolution_timeline(true);
+}
+
+#[test]
+fn test_balance_outcome_certainty() {
+	// Tests that `Balance::outcome_certainty` classifies the balances we see across the lifetime
+	// of HTLCs and channel closures by whether they are ours unconditionally or depend on an
+	// action by us or our counterparty.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	let (_, _, chan_ab, _) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+	let (_, _, chan_bc, _) = create_announced_chan_between_nodes_with_value(&nodes, 1, 2, 1_000_000, 500_000_000);
+
+	let certainties = |node: &Node, chan_id| {
+		let balances = node.chain_monitor.chain_monitor.get_monitor(chan_id).unwrap().get_claimable_balances();
+		sorted_vec(balances.iter().map(|balance| balance.outcome_certainty()).collect())
+	};
+
+	// While the channel is open, our balance on close is ours unconditionally, while the HTLC
+	// depends on whether B learns the preimage.
+	let (payment_preimage, payment_hash, ..) = route_payment(&nodes[0], &[&nodes[1]], 10_000_000);
+	assert_eq!(certainties(&nodes[0], chan_ab), vec![
+		BalanceCertainty::ClaimableByUsUnconditionally,
+		BalanceCertainty::CounterpartyCanClaimBeforeTimeout,
+	]);
+	assert_eq!(certainties(&nodes[1], chan_ab), vec![
+		BalanceCertainty::ClaimableByUsUnconditionally,
+		BalanceCertainty::ClaimableByUsIfPreimageKnown,
+	]);
+
+	// Once B knows the preimage, the HTLC is included in its balance on close.
+	nodes[1].node.claim_funds(payment_preimage);
+	check_added_monitors!(nodes[1], 1);
+	expect_payment_claimed!(nodes[1], payment_hash, 10_000_000);
+	let _ = get_htlc_update_msgs!(nodes[1], node_a_id);
+	assert_eq!(certainties(&nodes[1], chan_ab), vec![BalanceCertainty::ClaimableByUsUnconditionally]);
+
+	// After A's commitment transaction confirms, B's main output awaits confirmations, while B
+	// has to claim the HTLC before it times out.
+	let as_txn = get_local_commitment_txn!(nodes[0], chan_ab);
+	mine_transaction(&nodes[1], &as_txn[0]);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_a_id], 1000000);
+	let balances = nodes[1].chain_monitor.chain_monitor.get_monitor(chan_ab).unwrap().get_claimable_balances();
+	assert!(balances.iter().any(|balance| matches!(balance, Balance::ContentiousClaimable { .. })));
+	assert_eq!(certainties(&nodes[1], chan_ab), vec![
+		BalanceCertainty::ClaimableByUsUnconditionally,
+		BalanceCertainty::ClaimableByUsBeforeTimeout,
+	]);
+	nodes[1].tx_broadcaster.txn_broadcast();
+
+	// If C broadcasts a revoked commitment transaction, B has to claim C's output before C's
+	// timelock on it expires.
+	let revoked_cs_txn = get_local_commitment_txn!(nodes[2], chan_bc);
+	send_payment(&nodes[1], &[&nodes[2]], 1_000_000);
+	mine_transaction(&nodes[1], &revoked_cs_txn[0]);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_c_id], 1000000);
+	let balances = nodes[1].chain_monitor.chain_monitor.get_monitor(chan_bc).unwrap().get_claimable_balances();
+	assert!(balances.iter().any(|balance| matches!(balance, Balance::CounterpartyRevokedOutputClaimable { .. })));
+	assert_eq!(certainties(&nodes[1], chan_bc), vec![
+		BalanceCertainty::ClaimableByUsUnconditionally,
+		BalanceCertainty::ClaimableByUsBeforeTimeout,
+	]);
+}
+
 fn do_test_revoked_counterparty_commitment_balances(anchors: bool, confirm_htlc_spend_first: bool) {
 	// Tests `get_claimable_balances` for revoked counterparty commitment transactions.
 	let mut chanmon_cfgs = create_chanmon_cfgs(2);
@@ -2470,6 +2916,13 @@ fn do_test_yield_anchors_events(have_htlcs: bool) {
 	assert!(nodes[0].node.get_and_clear_pending_events().is_empty());
 	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
 
+	let cost_estimate = nodes[0].node.estimate_force_close_cost(
+		&chan_id, &nodes[1].node.get_our_node_id(), FeeRate::from_sat_per_kwu(500)
+	).unwrap();
+	assert!(!cost_estimate.fees_committed);
+	assert_eq!(cost_estimate.transactions.len(), if have_htlcs { 4 } else { 2 });
+	assert_eq!(cost_estimate.transactions[1].transaction_type, ForceCloseTransactionType::AnchorSpend);
+
 	// Note that if we use the wrong target, we will immediately broadcast the commitment
 	// transaction as no bump is required.
 	if have_htlcs {
@@ -2540,6 +2993,15 @@ fn do_test_yield_anchors_events(have_htlcs: bool) {
 	if have_htlcs {
 		assert_eq!(commitment_tx.output[2].value.to_sat(), 1_000); // HTLC A -> B
 		assert_eq!(commitment_tx.output[3].value.to_sat(), 2_000); // HTLC B -> A
+
+		// The commitment transaction was bumped to the same feerate we estimated the cost at, so
+		// the fees of the package should roughly match our estimate.
+		let commitment_tx_fee = tx_fee(&commitment_tx, &[&funding_tx]);
+		assert_eq!(cost_estimate.transactions[0].fee, commitment_tx_fee);
+		let package_fee = commitment_tx_fee + tx_fee(&anchor_tx, &[&commitment_tx, &coinbase_tx]);
+		let estimated_package_fee = cost_estimate.transactions[0].fee + cost_estimate.transactions[1].fee;
+		assert!(package_fee.to_sat() * 9 / 10 <= estimated_package_fee.to_sat());
+		assert!(estimated_package_fee.to_sat() <= package_fee.to_sat() * 11 / 10);
 	}
 
 	mine_transactions(&nodes[0], &[&commitment_tx, &anchor_tx]);
@@ -2593,6 +3055,16 @@ fn do_test_yield_anchors_events(have_htlcs: bool) {
 		}
 	}
 
+	// The weights the anchor channel reserve is based on cover those of our signed claims. Our test
+	// wallet spends P2PKH inputs, which are thus left unclassified.
+	let report = validate_weight_constants(&[anchor_tx.clone(), htlc_txs[0].clone(), htlc_txs[1].clone()]);
+	assert!(report.underestimated().is_empty(), "{}", report);
+	let components: Vec<_> = report.checks.iter().map(|check| check.component).collect();
+	assert_eq!(components, vec![
+		ClaimWeightComponent::KeyedAnchorInput, ClaimWeightComponent::HtlcSuccess,
+		ClaimWeightComponent::HtlcTimeout,
+	]);
+
 	mine_transactions(&nodes[0], &[&htlc_txs[0], &htlc_txs[1]]);
 	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
 
@@ -2617,6 +3089,663 @@ fn test_yield_anchors_events() {
 	do_test_yield_anchors_events(false);
 }
 
+#[test]
+fn test_onchain_resolution_stats() {
+	// Tests that the fees paid to resolve an anchor channel on-chain are accounted for in the
+	// `OnchainResolutionStats` of its monitor, once the fee bumps recorded by a
+	// `BumpTransactionEventHandler` are provided and the claims have been resolved.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let coinbase_tx = provide_anchor_reserves(&nodes);
+	let (_, _, chan_id, _) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+	let (_, payment_hash, ..) = route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+
+	let audit_log = Arc::new(ReserveAuditLog::new(10));
+	let bump_tx_handler = BumpTransactionEventHandlerSync::new(
+		nodes[0].tx_broadcaster, Arc::new(WalletSync::new(Arc::clone(&nodes[0].wallet_source), nodes[0].logger)),
+		nodes[0].keys_manager, nodes[0].logger, HTLCDeadlineFeerateSchedule::disabled(),
+	).with_audit_logger(Arc::clone(&audit_log) as Arc<dyn ReserveAuditLogger + Send + Sync>);
+	let provide_fee_bumps = || {
+		for entry in audit_log.entries() {
+			nodes[0].chain_monitor.chain_monitor.provide_claim_fee_bump(&entry);
+		}
+	};
+
+	// Let the HTLC expire such that we force close, requiring fees to be attached to both our
+	// commitment transaction and the HTLC-timeout transaction.
+	nodes[0].fee_estimator.target_override.lock().unwrap().insert(ConfirmationTarget::UrgentOnChainSweep, 500);
+	connect_blocks(&nodes[0], TEST_FINAL_CLTV + LATENCY_GRACE_PERIOD_BLOCKS + 1);
+	check_closed_broadcast(&nodes[0], 1, true);
+	check_added_monitors(&nodes[0], 1);
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert!(events.iter().any(|event| matches!(event, Event::ChannelClosed { .. })));
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_onchain_resolution_stats().num_confirmed_claims, 0);
+
+	let mut holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	let (commitment_tx, anchor_tx) = match holder_events.pop().unwrap() {
+		Event::BumpTransaction(event) => {
+			bump_tx_handler.handle_event(&event);
+			let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+			assert_eq!(txn.len(), 2);
+			let anchor_tx = txn.pop().unwrap();
+			let commitment_tx = txn.pop().unwrap();
+			check_spends!(anchor_tx, coinbase_tx, commitment_tx);
+			(commitment_tx, anchor_tx)
+		},
+		_ => panic!("Unexpected event"),
+	};
+	provide_fee_bumps();
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_exogenous_fees_paid(), Amount::ZERO);
+	mine_transactions(&nodes[0], &[&commitment_tx, &anchor_tx]);
+
+	// The fees of the anchor transaction count as soon as it confirms along with the commitment
+	// transaction.
+	let anchor_fees = audit_log.entries()[0].fees_paid;
+	assert!(anchor_fees > Amount::ZERO);
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_exogenous_fees_paid(), anchor_fees);
+
+	let mut htlc_tx = None;
+	for event in nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events() {
+		match event {
+			Event::BumpTransaction(event @ BumpTransactionEvent::HTLCResolution { .. }) => {
+				bump_tx_handler.handle_event(&event);
+				let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+				assert_eq!(txn.len(), 1);
+				htlc_tx = txn.pop();
+			},
+			// Certain block `ConnectStyle`s cause an extra `ChannelClose` event to be emitted
+			// since the best block is updated before the confirmed transactions are notified.
+			Event::BumpTransaction(BumpTransactionEvent::ChannelClose { .. }) => {},
+			_ => panic!("Unexpected event"),
+		}
+	}
+	let htlc_tx = htlc_tx.unwrap();
+	check_spends!(htlc_tx, commitment_tx, anchor_tx);
+	provide_fee_bumps();
+	mine_transaction(&nodes[0], &htlc_tx);
+	let htlc_fees = audit_log.entries()[1].fees_paid;
+	assert!(htlc_fees > Amount::ZERO);
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_exogenous_fees_paid(), anchor_fees + htlc_fees);
+
+	// Once the HTLC-timeout transaction is reorged out, its fees no longer count until it
+	// confirms again.
+	disconnect_blocks(&nodes[0], 1);
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_exogenous_fees_paid(), anchor_fees);
+	mine_transaction(&nodes[0], &htlc_tx);
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_exogenous_fees_paid(), anchor_fees + htlc_fees);
+	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+	expect_payment_failed!(nodes[0], payment_hash, false);
+
+	// Both the anchor and HTLC-timeout transactions are accounted for, with the fees they paid
+	// out of our wallet's inputs.
+	let entries = audit_log.entries();
+	assert_eq!(entries.len(), 2);
+	let stats = get_monitor!(nodes[0], chan_id).get_onchain_resolution_stats();
+	assert!(stats.total_claim_fees > Amount::ZERO);
+	assert_eq!(stats.total_claim_fees, entries[0].fees_paid + entries[1].fees_paid);
+	assert_eq!(stats.total_external_inputs_value, entries[0].utxos_value + entries[1].utxos_value);
+	assert_eq!(stats.num_confirmed_claims, 2);
+	assert_eq!(stats.num_htlc_outputs_claimed, 1);
+	assert!(stats.max_claim_feerate_sat_per_1000_weight >= 500);
+	assert!(stats.max_pending_claims >= 1);
+	assert_eq!(stats.total_exogenous_fees, anchor_fees + htlc_fees);
+	assert_eq!(get_monitor!(nodes[0], chan_id).get_exogenous_fees_paid(), anchor_fees + htlc_fees);
+
+	// The statistics survive a round-trip through the monitor's serialization.
+	let monitor = get_monitor!(nodes[0], chan_id).encode();
+	let (_, read_monitor) = <(BlockHash, ChannelMonitor<TestChannelSigner>)>::read(
+		&mut &monitor[..], (nodes[0].keys_manager, nodes[0].keys_manager)
+	).unwrap();
+	assert_eq!(read_monitor.get_onchain_resolution_stats(), stats);
+	assert_eq!(read_monitor.get_exogenous_fees_paid(), anchor_fees + htlc_fees);
+}
+
+#[test]
+fn test_bump_transaction_claims_persist_across_restarts() {
+	// Tests that a `BumpTransactionEventHandler` persisting its claims reloads them upon restart,
+	// such that the anchor transaction it produces for a claim replaces the one broadcast before
+	// the restart, rather than conflicting with it at a lower feerate, and that the claim is pruned
+	// once it confirms.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let coinbase_tx = provide_anchor_reserves(&nodes);
+	create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+	route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+
+	let store = Arc::new(TestStore::new(false));
+	let new_bump_tx_handler = || BumpTransactionEventHandlerSync::new(
+		nodes[0].tx_broadcaster, Arc::new(WalletSync::new(Arc::clone(&nodes[0].wallet_source), nodes[0].logger)),
+		nodes[0].keys_manager, nodes[0].logger, HTLCDeadlineFeerateSchedule::disabled(),
+	);
+	let namespace = KVStoreNamespace::BUMP_TRANSACTION_CLAIMS;
+	let persisted_claims = || store.list(namespace.primary_namespace, namespace.secondary_namespace).unwrap();
+	let spent_outpoints = |tx: &Transaction| tx.input.iter().map(|input| input.previous_output).collect::<Vec<_>>();
+
+	// Let the HTLC expire such that we force close, requiring fees to be attached to our
+	// commitment transaction.
+	nodes[0].fee_estimator.target_override.lock().unwrap().insert(ConfirmationTarget::UrgentOnChainSweep, 2_000);
+	connect_blocks(&nodes[0], TEST_FINAL_CLTV + LATENCY_GRACE_PERIOD_BLOCKS + 1);
+	check_closed_broadcast(&nodes[0], 1, true);
+	check_added_monitors(&nodes[0], 1);
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert!(events.iter().any(|event| matches!(event, Event::ChannelClosed { .. })));
+
+	let mut holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	let event = match holder_events.pop().unwrap() {
+		Event::BumpTransaction(event @ BumpTransactionEvent::ChannelClose { .. }) => event,
+		_ => panic!("Unexpected event"),
+	};
+	let (commitment_tx, anchor_tx) = {
+		let bump_tx_handler = new_bump_tx_handler()
+			.with_claim_persistence(Arc::clone(&store) as Arc<dyn KVStoreSync + Send + Sync>).unwrap();
+		bump_tx_handler.handle_event(&event);
+		let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+		assert_eq!(txn.len(), 2);
+		let anchor_tx = txn.pop().unwrap();
+		let commitment_tx = txn.pop().unwrap();
+		check_spends!(anchor_tx, coinbase_tx, commitment_tx);
+		(commitment_tx, anchor_tx)
+	};
+	assert_eq!(persisted_claims().len(), 1);
+
+	// After a restart, the claim is regenerated at a lower feerate.
+	let mut lower_feerate_event = event.clone();
+	if let BumpTransactionEvent::ChannelClose { ref mut package_target_feerate_sat_per_1000_weight, .. } = lower_feerate_event {
+		*package_target_feerate_sat_per_1000_weight = 500;
+	}
+
+	// Without the persisted claim, we'd produce a conflicting anchor transaction spending the
+	// same inputs at a lower fee, which would fail to replace the first one.
+	new_bump_tx_handler().handle_event(&lower_feerate_event);
+	let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+	assert_eq!(txn.len(), 2);
+	let conflicting_anchor_tx = txn.pop().unwrap();
+	assert_eq!(spent_outpoints(&conflicting_anchor_tx), spent_outpoints(&anchor_tx));
+	assert!(conflicting_anchor_tx.output[0].value > anchor_tx.output[0].value);
+
+	// With the persisted claim reloaded, we target at least the feerate of the first attempt.
+	let bump_tx_handler = new_bump_tx_handler()
+		.with_claim_persistence(Arc::clone(&store) as Arc<dyn KVStoreSync + Send + Sync>).unwrap();
+	bump_tx_handler.handle_event(&lower_feerate_event);
+	let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+	assert_eq!(txn.len(), 2);
+	let replacement_anchor_tx = txn.pop().unwrap();
+	assert_eq!(txn.pop().unwrap(), commitment_tx);
+	assert_eq!(spent_outpoints(&replacement_anchor_tx), spent_outpoints(&anchor_tx));
+	assert!(replacement_anchor_tx.output[0].value <= anchor_tx.output[0].value);
+	assert_eq!(persisted_claims().len(), 1);
+
+	// Once the claim confirms, it is pruned from the store after `ANTI_REORG_DELAY` blocks.
+	mine_transactions(&nodes[0], &[&commitment_tx, &replacement_anchor_tx]);
+	let height = nodes[0].best_block_info().1;
+	assert!(bump_tx_handler.transactions_confirmed(&[&commitment_tx, &replacement_anchor_tx], height).is_empty());
+	assert_eq!(persisted_claims().len(), 1);
+	bump_tx_handler.best_block_updated(height + ANTI_REORG_DELAY - 2);
+	assert_eq!(persisted_claims().len(), 1);
+	bump_tx_handler.best_block_updated(height + ANTI_REORG_DELAY - 1);
+	assert!(persisted_claims().is_empty());
+	nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+}
+
+#[test]
+fn test_bump_transaction_claims_rebroadcast_after_reorg() {
+	// Tests that a `BumpTransactionEventHandler` persisting its claims reverts their resolution once
+	// the block confirming them is reorged out, notifying the wallet of the spend of its UTXOs anew,
+	// rebroadcasting the claim and reporting its UTXOs as in limbo until it confirms again.
+	struct RecordingClaimNotifier(Mutex<Vec<PendingClaimSpend>>);
+	impl ClaimNotifier for RecordingClaimNotifier {
+		fn claim_spend_pending(&self, pending_spend: &PendingClaimSpend) -> Result<(), ()> {
+			self.0.lock().unwrap().push(pending_spend.clone());
+			Ok(())
+		}
+	}
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let coinbase_tx = provide_anchor_reserves(&nodes);
+	create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+	route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+
+	let store = Arc::new(TestStore::new(false));
+	let claim_notifier = Arc::new(RecordingClaimNotifier(Mutex::new(Vec::new())));
+	let bump_tx_handler = BumpTransactionEventHandlerSync::new(
+		nodes[0].tx_broadcaster, Arc::new(WalletSync::new(Arc::clone(&nodes[0].wallet_source), nodes[0].logger)),
+		nodes[0].keys_manager, nodes[0].logger, HTLCDeadlineFeerateSchedule::disabled(),
+	)
+		.with_claim_notifier(Arc::clone(&claim_notifier) as Arc<dyn ClaimNotifier + Send + Sync>)
+		.with_claim_persistence(Arc::clone(&store) as Arc<dyn KVStoreSync + Send + Sync>).unwrap();
+	let namespace = KVStoreNamespace::BUMP_TRANSACTION_CLAIMS;
+	let persisted_claims = || store.list(namespace.primary_namespace, namespace.secondary_namespace).unwrap();
+
+	nodes[0].fee_estimator.target_override.lock().unwrap().insert(ConfirmationTarget::UrgentOnChainSweep, 2_000);
+	connect_blocks(&nodes[0], TEST_FINAL_CLTV + LATENCY_GRACE_PERIOD_BLOCKS + 1);
+	check_closed_broadcast(&nodes[0], 1, true);
+	check_added_monitors(&nodes[0], 1);
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert!(events.iter().any(|event| matches!(event, Event::ChannelClosed { .. })));
+
+	let mut holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	match holder_events.pop().unwrap() {
+		Event::BumpTransaction(event @ BumpTransactionEvent::ChannelClose { .. }) => bump_tx_handler.handle_event(&event),
+		_ => panic!("Unexpected event"),
+	}
+	let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+	assert_eq!(txn.len(), 2);
+	let anchor_tx = txn.pop().unwrap();
+	let commitment_tx = txn.pop().unwrap();
+	check_spends!(anchor_tx, coinbase_tx, commitment_tx);
+	let wallet_utxos = vec![anchor_tx.input[1].previous_output];
+	let pending_spend = {
+		let mut pending_spends = claim_notifier.0.lock().unwrap();
+		assert_eq!(pending_spends.len(), 1);
+		pending_spends.pop().unwrap()
+	};
+	assert_eq!(pending_spend.wallet_utxos, wallet_utxos);
+
+	mine_transactions(&nodes[0], &[&commitment_tx, &anchor_tx]);
+	let height = nodes[0].best_block_info().1;
+	assert!(bump_tx_handler.transactions_confirmed(&[&commitment_tx, &anchor_tx], height).is_empty());
+	assert!(bump_tx_handler.reorged_claim_utxos().is_empty());
+	nodes[0].tx_broadcaster.txn_broadcast();
+
+	// Once the block confirming the claim is reorged out, the wallet is notified of the spend of
+	// its UTXOs anew and the claim is rebroadcast.
+	disconnect_blocks(&nodes[0], 1);
+	bump_tx_handler.blocks_disconnected(nodes[0].best_block_info().1);
+	assert!(nodes[0].tx_broadcaster.txn_broadcast().contains(&anchor_tx));
+	let renotified_spend = {
+		let mut pending_spends = claim_notifier.0.lock().unwrap();
+		assert_eq!(pending_spends.len(), 1);
+		pending_spends.pop().unwrap()
+	};
+	assert_eq!(renotified_spend.claim_id, pending_spend.claim_id);
+	assert_eq!(renotified_spend.wallet_utxos, wallet_utxos);
+	assert_eq!(renotified_spend.change_output, pending_spend.change_output);
+	assert_eq!(bump_tx_handler.reorged_claim_utxos(), wallet_utxos);
+	assert_eq!(persisted_claims().len(), 1);
+
+	// Disconnecting blocks below the claim's confirmation doesn't rebroadcast it again.
+	bump_tx_handler.blocks_disconnected(nodes[0].best_block_info().1);
+	assert!(!nodes[0].tx_broadcaster.txn_broadcast().contains(&anchor_tx));
+	assert!(claim_notifier.0.lock().unwrap().is_empty());
+
+	// Once the claim confirms again, its UTXOs are no longer in limbo and it is eventually pruned.
+	mine_transactions(&nodes[0], &[&commitment_tx, &anchor_tx]);
+	let height = nodes[0].best_block_info().1;
+	assert!(bump_tx_handler.transactions_confirmed(&[&commitment_tx, &anchor_tx], height).is_empty());
+	assert!(bump_tx_handler.reorged_claim_utxos().is_empty());
+	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+	bump_tx_handler.best_block_updated(nodes[0].best_block_info().1);
+	assert!(persisted_claims().is_empty());
+	nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+}
+
+#[test]
+fn test_anchor_claim_skipped_when_uneconomical_or_confirmed() {
+	// Tests that we only spend the anchor output of our commitment transaction when it is stuck
+	// below the configured feerate, skip it when spending it costs more than it's worth and the
+	// commitment transaction already pays enough, and never spend it once the commitment
+	// transaction has confirmed. The decision is exposed via `get_holder_anchor_output`.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	provide_anchor_reserves(&nodes);
+
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes_with_value(
+		&nodes, 0, 1, 1_000_000, 500_000_000
+	);
+	assert!(get_monitor!(nodes[0], chan_id).get_holder_anchor_output().is_none());
+
+	// Claiming the anchor output at 5000 sat/kW costs more than the anchor output is worth, but as
+	// our commitment transaction only pays 253 sat/kW, it is stuck and we must claim it anyway.
+	nodes[0].fee_estimator.target_override.lock().unwrap().insert(ConfirmationTarget::OutputSpendingFee, 5000);
+	get_monitor!(nodes[0], chan_id).set_min_feerate_to_skip_uneconomical_anchor_claim(Some(1000));
+
+	let message = "Channel force-closed".to_owned();
+	nodes[0].node.force_close_broadcasting_latest_txn(&chan_id, &nodes[1].node.get_our_node_id(), message.clone()).unwrap();
+	check_added_monitors!(nodes[0], 1);
+	check_closed_broadcast!(nodes[0], true);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+	check_closed_event!(nodes[0], 1, reason, [nodes[1].node.get_our_node_id()], 1_000_000);
+
+	let mut holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	let commitment_tx = match holder_events.pop().unwrap() {
+		Event::BumpTransaction(BumpTransactionEvent::ChannelClose { commitment_tx, .. }) => commitment_tx,
+		_ => panic!("Unexpected event"),
+	};
+	check_spends!(commitment_tx, funding_tx);
+	let anchor_output = get_monitor!(nodes[0], chan_id).get_holder_anchor_output().unwrap();
+	assert_eq!(anchor_output.commitment_txid, commitment_tx.compute_txid());
+	assert_eq!(anchor_output.amount_satoshis, channel::ANCHOR_OUTPUT_VALUE_SATOSHI);
+	assert_eq!(anchor_output.claim_status, AnchorClaimStatus::Claiming);
+
+	// Once our commitment transaction pays enough, we skip the uneconomical anchor claim and simply
+	// rebroadcast it.
+	get_monitor!(nodes[0], chan_id).set_min_feerate_to_skip_uneconomical_anchor_claim(Some(200));
+	nodes[0].chain_monitor.chain_monitor.rebroadcast_pending_claims();
+	assert!(nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events().is_empty());
+	let txn = nodes[0].tx_broadcaster.txn_broadcast();
+	assert_eq!(txn.len(), 1);
+	assert_eq!(txn[0], commitment_tx);
+	let anchor_output = get_monitor!(nodes[0], chan_id).get_holder_anchor_output().unwrap();
+	match anchor_output.claim_status {
+		AnchorClaimStatus::Uneconomical { commitment_feerate_sat_per_1000_weight, claim_fee_satoshis } => {
+			assert!(commitment_feerate_sat_per_1000_weight >= 200);
+			assert!(claim_fee_satoshis > channel::ANCHOR_OUTPUT_VALUE_SATOSHI);
+		},
+		_ => panic!("Unexpected claim status"),
+	}
+
+	// Even if our commitment transaction is stuck again, we never claim the anchor output once the
+	// commitment transaction has confirmed.
+	get_monitor!(nodes[0], chan_id).set_min_feerate_to_skip_uneconomical_anchor_claim(None);
+	mine_transaction(&nodes[0], &commitment_tx);
+	nodes[0].chain_monitor.chain_monitor.rebroadcast_pending_claims();
+	assert!(nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events().is_empty());
+	let anchor_output = get_monitor!(nodes[0], chan_id).get_holder_anchor_output().unwrap();
+	assert_eq!(anchor_output.commitment_txid, commitment_tx.compute_txid());
+	assert_eq!(anchor_output.claim_status, AnchorClaimStatus::CommitmentConfirmed);
+}
+
+#[test]
+fn test_force_close_claim_deferred_when_uneconomical() {
+	// Tests that we defer bumping our commitment transaction to the target feerate when doing so
+	// would burn more than the configured fraction of our balance and no HTLCs are at risk,
+	// targeting the lowest feerate instead until claiming it becomes economical again. Claims of
+	// commitment transactions with HTLCs pending are never deferred.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	provide_anchor_reserves(&nodes);
+
+	let (_, _, htlc_chan_id, _) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+	route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+	for channel_id in [chan_id, htlc_chan_id] {
+		// We're willing to spend up to 10% of our balance to claim our commitment transaction.
+		get_monitor!(nodes[0], channel_id).set_max_force_close_fee_proportional_millionths(Some(100_000));
+	}
+
+	// At 25,000 sat/kW, spending the anchor output costs far more than 10% of our balance, while
+	// our commitment transaction already pays the lowest feerate, so we simply broadcast it.
+	{
+		let mut target_override = nodes[0].fee_estimator.target_override.lock().unwrap();
+		target_override.insert(ConfirmationTarget::OutputSpendingFee, 25_000);
+		target_override.insert(ConfirmationTarget::UrgentOnChainSweep, 25_000);
+		target_override.insert(ConfirmationTarget::MinAllowedAnchorChannelRemoteFee, 200);
+	}
+	let message = "Channel force-closed".to_owned();
+	nodes[0].node.force_close_broadcasting_latest_txn(&chan_id, &node_b_id, message.clone()).unwrap();
+	check_added_monitors!(nodes[0], 1);
+	check_closed_broadcast!(nodes[0], true);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message: message.clone() };
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 100_000);
+
+	let txn = nodes[0].tx_broadcaster.txn_broadcast();
+	assert_eq!(txn.len(), 1);
+	let commitment_tx = txn[0].clone();
+	check_spends!(commitment_tx, funding_tx);
+	let holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	let (claim_fee, balance) = match holder_events[0] {
+		Event::ForceCloseClaimDeferred {
+			channel_id, counterparty_node_id, commitment_txid, target_feerate_sat_per_1000_weight,
+			claim_fee_satoshis, balance_satoshis,
+		} => {
+			assert_eq!(channel_id, chan_id);
+			assert_eq!(counterparty_node_id, node_b_id);
+			assert_eq!(commitment_txid, commitment_tx.compute_txid());
+			assert_eq!(target_feerate_sat_per_1000_weight, 253);
+			assert!(claim_fee_satoshis * 10 > balance_satoshis);
+			(claim_fee_satoshis, balance_satoshis)
+		},
+		_ => panic!("Unexpected event"),
+	};
+	let anchor_output = get_monitor!(nodes[0], chan_id).get_holder_anchor_output().unwrap();
+	match anchor_output.claim_status {
+		AnchorClaimStatus::Deferred {
+			commitment_feerate_sat_per_1000_weight, target_feerate_sat_per_1000_weight,
+			claim_fee_satoshis, balance_satoshis,
+		} => {
+			assert!(commitment_feerate_sat_per_1000_weight >= target_feerate_sat_per_1000_weight);
+			assert_eq!((claim_fee_satoshis, balance_satoshis), (claim_fee, balance));
+		},
+		_ => panic!("Unexpected claim status"),
+	}
+
+	// If our commitment transaction doesn't pay the lowest feerate, we only bump it to the lowest
+	// feerate, without generating another event for the same deferral.
+	nodes[0].fee_estimator.target_override.lock().unwrap()
+		.insert(ConfirmationTarget::MinAllowedAnchorChannelRemoteFee, 1_000);
+	nodes[0].chain_monitor.chain_monitor.rebroadcast_pending_claims();
+	let holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	match holder_events[0] {
+		Event::BumpTransaction(BumpTransactionEvent::ChannelClose {
+			ref commitment_tx, package_target_feerate_sat_per_1000_weight, ..
+		}) => {
+			assert_eq!(commitment_tx.compute_txid(), anchor_output.commitment_txid);
+			assert_eq!(package_target_feerate_sat_per_1000_weight, 1_000);
+		},
+		_ => panic!("Unexpected event"),
+	}
+	let anchor_output = get_monitor!(nodes[0], chan_id).get_holder_anchor_output().unwrap();
+	assert!(matches!(anchor_output.claim_status, AnchorClaimStatus::Deferred { .. }));
+
+	// Once feerates drop, we claim our commitment transaction as usual.
+	nodes[0].fee_estimator.target_override.lock().unwrap()
+		.insert(ConfirmationTarget::OutputSpendingFee, 2_000);
+	nodes[0].chain_monitor.chain_monitor.rebroadcast_pending_claims();
+	let holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	match holder_events[0] {
+		Event::BumpTransaction(BumpTransactionEvent::ChannelClose { package_target_feerate_sat_per_1000_weight, .. }) => {
+			assert_eq!(package_target_feerate_sat_per_1000_weight, 2_000);
+		},
+		_ => panic!("Unexpected event"),
+	}
+	let anchor_output = get_monitor!(nodes[0], chan_id).get_holder_anchor_output().unwrap();
+	assert_eq!(anchor_output.claim_status, AnchorClaimStatus::Claiming);
+
+	// With an HTLC pending, we always claim our commitment transaction at the urgent feerate, even
+	// though it costs more than 10% of our balance.
+	nodes[0].node.force_close_broadcasting_latest_txn(&htlc_chan_id, &node_b_id, message.clone()).unwrap();
+	check_added_monitors!(nodes[0], 1);
+	check_closed_broadcast!(nodes[0], true);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 100_000);
+
+	let holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 1);
+	match holder_events[0] {
+		Event::BumpTransaction(BumpTransactionEvent::ChannelClose { channel_id, package_target_feerate_sat_per_1000_weight, .. }) => {
+			assert_eq!(channel_id, htlc_chan_id);
+			assert_eq!(package_target_feerate_sat_per_1000_weight, 25_000);
+		},
+		_ => panic!("Unexpected event"),
+	}
+	let anchor_output = get_monitor!(nodes[0], htlc_chan_id).get_holder_anchor_output().unwrap();
+	assert_eq!(anchor_output.claim_status, AnchorClaimStatus::Claiming);
+}
+
+fn get_anchor_resolution_started_events(node: &Node) -> Vec<Event> {
+	node.chain_monitor.chain_monitor.get_and_clear_pending_events().into_iter()
+		.filter(|event| matches!(event, Event::AnchorChannelResolutionStarted { .. }))
+		.collect()
+}
+
+#[test]
+fn test_anchor_channel_resolution_started_event() {
+	// Tests that we generate an `Event::AnchorChannelResolutionStarted` exactly once when an anchor
+	// channel starts being resolved on-chain, both when we broadcast our own commitment
+	// transaction and when we see the counterparty's confirm.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	provide_anchor_reserves(&nodes);
+
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+	route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+	for node in nodes.iter() {
+		node.chain_monitor.chain_monitor.set_anchor_resolution_events_enabled(true);
+	}
+	assert!(get_anchor_resolution_started_events(&nodes[0]).is_empty());
+	assert!(get_anchor_resolution_started_events(&nodes[1]).is_empty());
+
+	// Force-closing the channel generates the event along with the request to bump our
+	// commitment transaction.
+	let message = "Channel force-closed".to_owned();
+	nodes[0].node.force_close_broadcasting_latest_txn(&chan_id, &node_b_id, message.clone()).unwrap();
+	check_added_monitors!(nodes[0], 1);
+	check_closed_broadcast!(nodes[0], true);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 1_000_000);
+
+	let holder_events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(holder_events.len(), 2);
+	let commitment_tx = holder_events.iter().find_map(|event| match event {
+		Event::BumpTransaction(BumpTransactionEvent::ChannelClose { commitment_tx, .. }) => Some(commitment_tx.clone()),
+		_ => None,
+	}).unwrap();
+	check_spends!(commitment_tx, funding_tx);
+	let resolution_event = holder_events.iter()
+		.find(|event| matches!(event, Event::AnchorChannelResolutionStarted { .. }))
+		.unwrap();
+	assert_eq!(*resolution_event, Event::AnchorChannelResolutionStarted {
+		channel_id: chan_id,
+		counterparty_node_id: node_b_id,
+		commitment_txid: commitment_tx.compute_txid(),
+		num_pending_htlc_outputs: 1,
+		holder_force_closed: true,
+	});
+
+	// Neither rebroadcasting nor confirming our commitment transaction generates it again.
+	nodes[0].chain_monitor.chain_monitor.rebroadcast_pending_claims();
+	assert!(get_anchor_resolution_started_events(&nodes[0]).is_empty());
+	mine_transaction(&nodes[0], &commitment_tx);
+	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+	assert!(get_anchor_resolution_started_events(&nodes[0]).is_empty());
+
+	// The counterparty generates the event once our commitment transaction confirms.
+	mine_transaction(&nodes[1], &commitment_tx);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_a_id], 1_000_000);
+
+	let counterparty_events = get_anchor_resolution_started_events(&nodes[1]);
+	assert_eq!(counterparty_events, vec![Event::AnchorChannelResolutionStarted {
+		channel_id: chan_id,
+		counterparty_node_id: node_a_id,
+		commitment_txid: commitment_tx.compute_txid(),
+		num_pending_htlc_outputs: 1,
+		holder_force_closed: false,
+	}]);
+	connect_blocks(&nodes[1], ANTI_REORG_DELAY - 1);
+	assert!(get_anchor_resolution_started_events(&nodes[1]).is_empty());
+}
+
+#[test]
+fn test_anchor_channel_resolution_started_event_after_reload() {
+	// Tests that an `Event::AnchorChannelResolutionStarted` which was not handled before a restart
+	// is provided after it, and that it is not generated again once handled.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let (persister_a, persister_b);
+	let (chain_monitor_a, chain_monitor_b);
+	let mut anchors_config = test_default_channel_config();
+	anchors_config.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_config.manually_accept_inbound_channels = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_config.clone()), Some(anchors_config.clone())]);
+	let (node_deserialized_a, node_deserialized_b);
+	let mut nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	provide_anchor_reserves(&nodes);
+
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000);
+
+	let message = "Channel force-closed".to_owned();
+	nodes[0].node.force_close_broadcasting_latest_txn(&chan_id, &node_b_id, message.clone()).unwrap();
+	check_added_monitors!(nodes[0], 1);
+	check_closed_broadcast!(nodes[0], true);
+	let reason = ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 1_000_000);
+
+	let commitment_tx = {
+		let mut txn = nodes[0].tx_broadcaster.unique_txn_broadcast();
+		assert_eq!(txn.len(), 1);
+		check_spends!(txn[0], funding_tx);
+		txn.pop().unwrap()
+	};
+
+	// Restart the counterparty after the commitment transaction confirms, but before the event is
+	// handled.
+	mine_transaction(&nodes[1], &commitment_tx);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_a_id], 1_000_000);
+
+	let serialized_monitor = get_monitor!(nodes[1], chan_id).encode();
+	reload_node!(nodes[1], anchors_config.clone(), &nodes[1].node.encode(), &[&serialized_monitor], persister_a, chain_monitor_a, node_deserialized_a);
+	nodes[1].chain_monitor.chain_monitor.set_anchor_resolution_events_enabled(true);
+	assert_eq!(get_anchor_resolution_started_events(&nodes[1]), vec![Event::AnchorChannelResolutionStarted {
+		channel_id: chan_id,
+		counterparty_node_id: node_a_id,
+		commitment_txid: commitment_tx.compute_txid(),
+		num_pending_htlc_outputs: 0,
+		holder_force_closed: false,
+	}]);
+
+	// Once handled, the event is neither replayed after another restart nor generated again.
+	let serialized_monitor = get_monitor!(nodes[1], chan_id).encode();
+	reload_node!(nodes[1], anchors_config, &nodes[1].node.encode(), &[&serialized_monitor], persister_b, chain_monitor_b, node_deserialized_b);
+	nodes[1].chain_monitor.chain_monitor.set_anchor_resolution_events_enabled(true);
+	assert!(get_anchor_resolution_started_events(&nodes[1]).is_empty());
+	connect_blocks(&nodes[1], ANTI_REORG_DELAY);
+	assert!(get_anchor_resolution_started_events(&nodes[1]).is_empty());
+}
+
 #[test]
 fn test_anchors_aggregated_revoked_htlc_tx() {
 	// Test that `ChannelMonitor`s can properly detect and claim funds from a counterparty claiming
@@ -3211,3 +4340,73 @@ fn test_update_replay_panics() {
 	monitor.update_monitor(&updates[2], &nodes[1].tx_broadcaster, &nodes[1].fee_estimator, &nodes[1].logger).unwrap();
 	monitor.update_monitor(&updates[3], &nodes[1].tx_broadcaster, &nodes[1].fee_estimator, &nodes[1].logger).unwrap();
 }
+
+#[test]
+fn test_emergency_broadcast() {
+	// Tests that emergency broadcasts are only executed once enabled and for the latest commitment
+	// transaction, and that a still-running `ChannelManager` closes the channel once it processes
+	// the resulting monitor event.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+	route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+
+	let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+	let token = get_monitor!(nodes[0], chan_id).prepare_emergency_broadcast();
+	let summary = token.summary().clone();
+	assert_eq!(summary.channel_id, chan_id);
+	assert_eq!(summary.counterparty_node_id, node_b_id);
+	assert_eq!(summary.htlcs.len(), 1);
+	assert!(summary.htlcs[0].offered);
+	assert_eq!(summary.htlcs[0].amount_msat, 1_000_000);
+	assert!(!summary.requires_exogenous_fees);
+	assert!(summary.to_string().contains(&summary.commitment_txid.to_string()));
+
+	// Emergency broadcasts are refused until they are enabled.
+	let res = chain_monitor.execute_emergency_broadcast(token);
+	assert!(matches!(res, Err(APIError::APIMisuseError { .. })));
+	assert!(nodes[0].tx_broadcaster.txn_broadcast().is_empty());
+	chain_monitor.set_emergency_broadcast_enabled(true);
+
+	// Once the channel advances, a previously prepared token would broadcast a revoked commitment
+	// transaction and is refused.
+	let stale_token = get_monitor!(nodes[0], chan_id).prepare_emergency_broadcast();
+	send_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+	let res = chain_monitor.execute_emergency_broadcast(stale_token);
+	assert!(matches!(res, Err(APIError::APIMisuseError { .. })));
+	assert!(nodes[0].tx_broadcaster.txn_broadcast().is_empty());
+
+	let token = get_monitor!(nodes[0], chan_id).prepare_emergency_broadcast();
+	assert_ne!(token.summary().commitment_number, summary.commitment_number);
+	let commitment_txid = token.summary().commitment_txid;
+	chain_monitor.execute_emergency_broadcast(token).unwrap();
+	let commitment_tx = {
+		let mut txn = nodes[0].tx_broadcaster.txn_broadcast();
+		assert_eq!(txn.len(), 1);
+		check_spends!(txn[0], funding_tx);
+		assert_eq!(txn[0].compute_txid(), commitment_txid);
+		txn.pop().unwrap()
+	};
+
+	// The `ChannelManager` closes the channel the next time it processes monitor events.
+	assert_eq!(nodes[0].node.list_channels().len(), 1);
+	check_closed_broadcast!(nodes[0], true);
+	check_added_monitors!(nodes[0], 1);
+	let reason = ClosureReason::HolderForceClosed {
+		broadcasted_latest_txn: Some(true),
+		message: "Emergency broadcast of the latest commitment transaction".to_owned(),
+	};
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 1_000_000);
+	assert!(nodes[0].node.list_channels().is_empty());
+
+	mine_transaction(&nodes[1], &commitment_tx);
+	check_added_monitors!(nodes[1], 1);
+	check_closed_broadcast!(nodes[1], true);
+	check_closed_event!(nodes[1], 1, ClosureReason::CommitmentTxConfirmed, [node_a_id], 1_000_000);
+}
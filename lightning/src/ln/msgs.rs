@@ -2084,6 +2084,16 @@ pub trait ChannelMessageHandler: BaseMessageHandler {
 	/// Note: Since this function is called frequently, it should be as
 	/// efficient as possible for its intended purpose.
 	fn message_received(&self);
+
+	/// Returns whether we have HTLCs pending with the given peer, i.e., HTLCs which have not yet
+	/// been irrevocably resolved in any of our channels with it.
+	///
+	/// Used by the [`PeerManager`] to prioritize writes to such peers when sending data is
+	/// congested. While congested, this is called for each connected peer every time the
+	/// [`PeerManager`] processes events, so it should be efficient.
+	///
+	/// [`PeerManager`]: crate::ln::peer_handler::PeerManager
+	fn peer_has_pending_htlcs(&self, their_node_id: PublicKey) -> bool;
 }
 
 /// A trait to describe an object which can receive routing messages.
@@ -2287,6 +2287,7 @@ fn do_test_fail_htlc_backwards_with_reason(failure_code: FailureCode) {
 
 	let mut events = nodes[0].node.get_and_clear_pending_msg_events();
 	let mut payment_event = SendEvent::from_event(events.pop().unwrap());
+	let htlc_cltv_expiry = payment_event.msgs[0].cltv_expiry;
 	nodes[1].node.handle_update_add_htlc(nodes[0].node.get_our_node_id(), &payment_event.msgs[0]);
 	commitment_signed_dance!(nodes[1], nodes[0], payment_event.commitment_msg, false);
 
@@ -2331,7 +2332,9 @@ fn do_test_fail_htlc_backwards_with_reason(failure_code: FailureCode) {
 
 	let failure_data = match failure_code {
 		FailureCode::TemporaryNodeFailure => vec![],
+		FailureCode::PermanentNodeFailure => vec![],
 		FailureCode::RequiredNodeFeatureMissing => vec![],
+		FailureCode::MppTimeout => vec![],
 		FailureCode::IncorrectOrUnknownPaymentDetails => {
 			let mut htlc_msat_height_data = (payment_amount as u64).to_be_bytes().to_vec();
 			htlc_msat_height_data.extend_from_slice(&CHAN_CONFIRM_DEPTH.to_be_bytes());
@@ -2341,6 +2344,8 @@ fn do_test_fail_htlc_backwards_with_reason(failure_code: FailureCode) {
 			Some((typ, offset)) => [BigSize(typ).encode(), offset.encode()].concat(),
 			None => Vec::new(),
 		},
+		FailureCode::FinalIncorrectCltvExpiry => htlc_cltv_expiry.to_be_bytes().to_vec(),
+		FailureCode::FinalIncorrectHtlcAmount => (payment_amount as u64).to_be_bytes().to_vec(),
 	};
 
 	let failure_code = failure_code.into();
@@ -2360,6 +2365,46 @@ fn test_fail_htlc_backwards_with_reason() {
 	do_test_fail_htlc_backwards_with_reason(FailureCode::IncorrectOrUnknownPaymentDetails);
 	do_test_fail_htlc_backwards_with_reason(FailureCode::InvalidOnionPayload(Some((1 << 16, 42))));
 	do_test_fail_htlc_backwards_with_reason(FailureCode::InvalidOnionPayload(None));
+	do_test_fail_htlc_backwards_with_reason(FailureCode::PermanentNodeFailure);
+	do_test_fail_htlc_backwards_with_reason(FailureCode::FinalIncorrectCltvExpiry);
+	do_test_fail_htlc_backwards_with_reason(FailureCode::FinalIncorrectHtlcAmount);
+	do_test_fail_htlc_backwards_with_reason(FailureCode::MppTimeout);
+}
+
+#[test]
+fn test_failure_code_from_local_failure_reason() {
+	// Failure reasons which may be sent by the final node convert to a `FailureCode`.
+	assert_eq!(
+		FailureCode::try_from(LocalHTLCFailureReason::MPPTimeout),
+		Ok(FailureCode::MppTimeout)
+	);
+	assert_eq!(
+		FailureCode::try_from(LocalHTLCFailureReason::FinalIncorrectCLTVExpiry),
+		Ok(FailureCode::FinalIncorrectCltvExpiry)
+	);
+	assert_eq!(
+		FailureCode::try_from(LocalHTLCFailureReason::PaymentClaimBuffer),
+		Ok(FailureCode::IncorrectOrUnknownPaymentDetails)
+	);
+	for code in [
+		FailureCode::TemporaryNodeFailure,
+		FailureCode::PermanentNodeFailure,
+		FailureCode::RequiredNodeFeatureMissing,
+		FailureCode::IncorrectOrUnknownPaymentDetails,
+		FailureCode::InvalidOnionPayload(None),
+		FailureCode::FinalIncorrectCltvExpiry,
+		FailureCode::FinalIncorrectHtlcAmount,
+		FailureCode::MppTimeout,
+	] {
+		let reason: LocalHTLCFailureReason = code.into();
+		assert_eq!(FailureCode::try_from(reason), Ok(code));
+	}
+
+	// Failure reasons which only intermediate nodes may send are rejected.
+	assert!(FailureCode::try_from(LocalHTLCFailureReason::TemporaryChannelFailure).is_err());
+	assert!(FailureCode::try_from(LocalHTLCFailureReason::UnknownNextPeer).is_err());
+	assert!(FailureCode::try_from(LocalHTLCFailureReason::FeeInsufficient).is_err());
+	assert!(FailureCode::try_from(LocalHTLCFailureReason::InvalidOnionBlinding).is_err());
 }
 
 macro_rules! get_phantom_route {
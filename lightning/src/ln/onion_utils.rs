@@ -1678,6 +1678,18 @@ pub enum LocalHTLCFailureReason {
 	HTLCMaximum,
 	/// The HTLC was failed because our remote peer is offline.
 	PeerOffline,
+	/// The HTLC was failed because accepting it would exceed the number of inbound HTLCs we allow
+	/// in-flight on the channel given our on-chain reserve, as set by
+	/// [`ChannelConfig::max_inbound_htlcs_for_reserve`].
+	///
+	/// [`ChannelConfig::max_inbound_htlcs_for_reserve`]: crate::util::config::ChannelConfig::max_inbound_htlcs_for_reserve
+	InboundHTLCLimitForReserve,
+	/// The HTLC was failed because accepting it would exceed the number of parts of inbound
+	/// payments we allow to be pending on the channel given our on-chain reserve, as set by
+	/// [`UserConfig::max_inbound_payment_parts_for_reserve`].
+	///
+	/// [`UserConfig::max_inbound_payment_parts_for_reserve`]: crate::util::config::UserConfig::max_inbound_payment_parts_for_reserve
+	InboundPaymentPartLimitForReserve,
 }
 
 impl LocalHTLCFailureReason {
@@ -1697,7 +1709,9 @@ impl LocalHTLCFailureReason {
 			| Self::ZeroAmount
 			| Self::HTLCMinimum
 			| Self::HTLCMaximum
-			| Self::PeerOffline => UPDATE | 7,
+			| Self::PeerOffline
+			| Self::InboundHTLCLimitForReserve
+			| Self::InboundPaymentPartLimitForReserve => UPDATE | 7,
 			Self::PermanentChannelFailure | Self::ChannelClosed | Self::OnChainTimeout => PERM | 8,
 			Self::RequiredChannelFeature => PERM | 9,
 			Self::UnknownNextPeer
@@ -1852,6 +1866,8 @@ impl_writeable_tlv_based_enum!(LocalHTLCFailureReason,
 	(79, HTLCMinimum) => {},
 	(81, HTLCMaximum) => {},
 	(83, PeerOffline) => {},
+	(85, InboundHTLCLimitForReserve) => {},
+	(87, InboundPaymentPartLimitForReserve) => {},
 );
 
 impl From<&HTLCFailReason> for HTLCHandlingFailureReason {
@@ -1967,7 +1983,9 @@ impl HTLCFailReason {
 			| LocalHTLCFailureReason::ZeroAmount
 			| LocalHTLCFailureReason::HTLCMinimum
 			| LocalHTLCFailureReason::HTLCMaximum
-			| LocalHTLCFailureReason::PeerOffline => {
+			| LocalHTLCFailureReason::PeerOffline
+			| LocalHTLCFailureReason::InboundHTLCLimitForReserve
+			| LocalHTLCFailureReason::InboundPaymentPartLimitForReserve => {
 				debug_assert_eq!(
 					data.len() - 2,
 					u16::from_be_bytes(data[0..2].try_into().unwrap()) as usize
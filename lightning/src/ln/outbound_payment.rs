@@ -25,7 +25,7 @@ use crate::offers::invoice_request::InvoiceRequest;
 use crate::offers::nonce::Nonce;
 use crate::offers::static_invoice::StaticInvoice;
 use crate::routing::router::{
-	BlindedTail, InFlightHtlcs, Path, PaymentParameters, Route, RouteParameters,
+	BlindedTail, InFlightHtlcs, Path, Payee, PaymentParameters, Route, RouteParameters,
 	RouteParametersConfig, Router,
 };
 use crate::sign::{EntropySource, NodeSigner, Recipient};
@@ -48,21 +48,12 @@ use core::time::Duration;
 use crate::prelude::*;
 use crate::sync::Mutex;
 
-/// The number of ticks of [`ChannelManager::timer_tick_occurred`] until we time-out the idempotency
-/// of payments by [`PaymentId`]. See [`OutboundPayments::remove_stale_payments`].
+/// The number of ticks of [`ChannelManager::remove_stale_outbound_payments`] until we time-out the
+/// idempotency of payments by [`PaymentId`]. See [`OutboundPayments::remove_stale_payments`].
 ///
-/// [`ChannelManager::timer_tick_occurred`]: crate::ln::channelmanager::ChannelManager::timer_tick_occurred
+/// [`ChannelManager::remove_stale_outbound_payments`]: crate::ln::channelmanager::ChannelManager::remove_stale_outbound_payments
 pub(crate) const IDEMPOTENCY_TIMEOUT_TICKS: u8 = 7;
 
-#[cfg(async_payments)]
-/// The default relative expiration to wait for a pending outbound HTLC to a often-offline
-/// payee to fulfill.
-const ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY: Duration = Duration::from_secs(60 * 60 * 24 * 7);
-
-#[cfg(all(async_payments, test))]
-pub(crate) const TEST_ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY: Duration =
-	ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY;
-
 /// Stores the session_priv for each part of a payment that is still pending. For versions 0.0.102
 /// and later, also stores information for retrying the payment.
 pub(crate) enum PendingOutboundPayment {
@@ -110,7 +101,7 @@ pub(crate) enum PendingOutboundPayment {
 		// The deadline as duration since the Unix epoch for the async recipient to come online,
 		// after which we'll fail the payment.
 		//
-		// Defaults to creation time + [`ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY`].
+		// Defaults to creation time + [`UserConfig::async_payment_hold_timeout_secs`].
 		expiry_time: Duration,
 	},
 	Retryable {
@@ -454,6 +445,52 @@ pub(super) fn has_expired(route_params: &RouteParameters) -> bool {
 	false
 }
 
+/// Checks whether a payment with the given [`RouteParameters`] can possibly succeed, without
+/// attempting to find a route.
+///
+/// Routing fees are not included in the required outbound capacity, as the fees paid may be
+/// anywhere up to [`RouteParameters::max_total_routing_fee_msat`], including zero when paying a
+/// peer directly.
+pub(super) fn check_payment_feasibility<R: Deref>(
+	route_params: &RouteParameters, first_hops: &[ChannelDetails], router: &R,
+	duration_since_epoch: Duration,
+) -> Result<(), InfeasiblePaymentReason>
+where
+	R::Target: Router,
+{
+	let payment_params = &route_params.payment_params;
+	if let Some(expiry_time) = payment_params.expiry_time {
+		if duration_since_epoch > Duration::from_secs(expiry_time) {
+			return Err(InfeasiblePaymentReason::PaymentExpired);
+		}
+	}
+
+	let outbound_capacity_msat = first_hops
+		.iter()
+		.map(|channel| channel.next_outbound_htlc_limit_msat)
+		.fold(0u64, |total, limit| total.saturating_add(limit));
+	if outbound_capacity_msat < route_params.final_value_msat {
+		return Err(InfeasiblePaymentReason::InsufficientOutboundCapacity {
+			outbound_capacity_msat,
+			required_msat: route_params.final_value_msat,
+		});
+	}
+
+	let reachable = match &payment_params.payee {
+		Payee::Blinded { route_hints, .. } => !route_hints.is_empty(),
+		Payee::Clear { node_id, route_hints, .. } => {
+			!route_hints.is_empty()
+				|| first_hops.iter().any(|channel| channel.counterparty.node_id == *node_id)
+				|| router.has_known_channels(node_id) != Some(false)
+		},
+	};
+	if !reachable {
+		return Err(InfeasiblePaymentReason::UnreachableDestination);
+	}
+
+	Ok(())
+}
+
 /// Storing minimal payment attempts information required for determining if a outbound payment can
 /// be retried.
 pub(crate) struct PaymentAttempts {
@@ -541,6 +578,33 @@ pub enum RetryableSendFailure {
 	///
 	/// [`BlindedPaymentPath`]: crate::blinded_path::payment::BlindedPaymentPath
 	OnionPacketSizeExceeded,
+	/// The payment was determined to be infeasible before attempting to find a route, see
+	/// [`ChannelManager::estimate_payment_feasibility`].
+	///
+	/// [`ChannelManager::estimate_payment_feasibility`]: crate::ln::channelmanager::ChannelManager::estimate_payment_feasibility
+	PaymentInfeasible(InfeasiblePaymentReason),
+}
+
+/// The reason a payment cannot succeed, as determined by
+/// [`ChannelManager::estimate_payment_feasibility`] without attempting to find a route.
+///
+/// [`ChannelManager::estimate_payment_feasibility`]: crate::ln::channelmanager::ChannelManager::estimate_payment_feasibility
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InfeasiblePaymentReason {
+	/// The provided [`PaymentParameters::expiry_time`] indicated that the payment has expired.
+	///
+	/// [`PaymentParameters::expiry_time`]: crate::routing::router::PaymentParameters::expiry_time
+	PaymentExpired,
+	/// The total outbound capacity of our usable channels is less than the amount to send.
+	InsufficientOutboundCapacity {
+		/// The sum of [`ChannelDetails::next_outbound_htlc_limit_msat`] across our usable channels.
+		outbound_capacity_msat: u64,
+		/// The amount required to be sent, excluding any routing fees.
+		required_msat: u64,
+	},
+	/// No channels of the destination are known and neither route hints nor blinded paths were
+	/// provided to reach it.
+	UnreachableDestination,
 }
 
 /// If a payment fails to send to a route, it can be in one of several states. This enum is returned
@@ -1057,6 +1121,7 @@ impl OutboundPayments {
 					RetryableSendFailure::RouteNotFound => PaymentFailureReason::RouteNotFound,
 					RetryableSendFailure::DuplicatePayment => PaymentFailureReason::UnexpectedError,
 					RetryableSendFailure::OnionPacketSizeExceeded => PaymentFailureReason::UnexpectedError,
+					RetryableSendFailure::PaymentInfeasible(_) => PaymentFailureReason::RouteNotFound,
 				};
 				self.abandon_payment(payment_id, reason, pending_events);
 				return Err(Bolt12PaymentError::SendingFailed(e));
@@ -1115,7 +1180,8 @@ impl OutboundPayments {
 	#[rustfmt::skip]
 	pub(super) fn static_invoice_received<ES: Deref>(
 		&self, invoice: &StaticInvoice, payment_id: PaymentId, features: Bolt12InvoiceFeatures,
-		best_block_height: u32, duration_since_epoch: Duration, entropy_source: ES,
+		best_block_height: u32, duration_since_epoch: Duration, hold_timeout: Duration,
+		entropy_source: ES,
 		pending_events: &Mutex<VecDeque<(events::Event, Option<EventCompletionAction>)>>
 	) -> Result<(), Bolt12PaymentError> where ES::Target: EntropySource {
 		macro_rules! abandon_with_entry {
@@ -1178,7 +1244,7 @@ impl OutboundPayments {
 						abandon_with_entry!(entry, PaymentFailureReason::RouteNotFound);
 						return Err(Bolt12PaymentError::SendingFailed(RetryableSendFailure::OnionPacketSizeExceeded))
 					}
-					let absolute_expiry = duration_since_epoch.saturating_add(ASYNC_PAYMENT_TIMEOUT_RELATIVE_EXPIRY);
+					let absolute_expiry = duration_since_epoch.saturating_add(hold_timeout);
 
 					*entry.into_mut() = PendingOutboundPayment::StaticInvoiceReceived {
 						payment_hash,
@@ -16,8 +16,8 @@ use crate::chain::channelmonitor::{
 };
 use crate::chain::{ChannelMonitorUpdateStatus, Confirm, Listen};
 use crate::events::{
-	ClosureReason, Event, HTLCHandlingFailureType, PathFailure, PaymentFailureReason,
-	PaymentPurpose,
+	ClosureReason, Event, HTLCHandlingFailureType, PartialPaymentFailureReason, PathFailure,
+	PaymentFailureReason, PaymentPurpose,
 };
 use crate::ln::chan_utils;
 use crate::ln::channel::{
@@ -25,14 +25,16 @@ use crate::ln::channel::{
 	EXPIRE_PREV_CONFIG_TICKS,
 };
 use crate::ln::channelmanager::{
-	HTLCForwardInfo, PaymentId, PendingAddHTLCInfo, PendingHTLCRouting, RecentPaymentDetails,
-	RecipientOnionFields, BREAKDOWN_TIMEOUT, MIN_CLTV_EXPIRY_DELTA, MPP_TIMEOUT_TICKS,
+	HTLCForwardInfo, InboundStateStats, PaymentId, PendingAddHTLCInfo, PendingHTLCRouting,
+	RecentPaymentDetails, RecipientOnionFields, BREAKDOWN_TIMEOUT, INTERCEPTED_HTLC_TIMEOUT_TICKS,
+	MIN_CLTV_EXPIRY_DELTA, MPP_TIMEOUT_TICKS,
 };
 use crate::ln::msgs;
 use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, MessageSendEvent};
 use crate::ln::onion_utils::{self, LocalHTLCFailureReason};
 use crate::ln::outbound_payment::{
-	ProbeSendFailure, Retry, RetryableSendFailure, IDEMPOTENCY_TIMEOUT_TICKS,
+	InfeasiblePaymentReason, ProbeSendFailure, Retry, RetryableSendFailure,
+	IDEMPOTENCY_TIMEOUT_TICKS,
 };
 use crate::ln::types::ChannelId;
 use crate::routing::gossip::{EffectiveCapacity, RoutingFees};
@@ -52,7 +54,7 @@ use crate::util::test_utils;
 use bitcoin::hashes::sha256::Hash as Sha256;
 use bitcoin::hashes::Hash;
 use bitcoin::network::Network;
-use bitcoin::secp256k1::{Secp256k1, SecretKey};
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
 
 use crate::prelude::*;
 
@@ -426,6 +428,222 @@ fn mpp_receive_timeout() {
 	do_mpp_receive_timeout(false);
 }
 
+fn do_mpp_receive_configured_timeout(second_part_before_deadline: bool) {
+	// Tests that the parts of an incomplete MPP are held for `UserConfig::mpp_timeout_secs`, and
+	// that the recipient is notified of the partial payment and its failure.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut config = test_default_channel_config();
+	// A timeout of three minutes is enforced on the fourth call to
+	// `expire_stale_inbound_payments`, as the first call may happen right after the first part.
+	config.mpp_timeout_secs = 180;
+	config.notify_partial_mpp_payments = true;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, Some(config)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_1_scid = create_announced_chan_between_nodes(&nodes, 0, 1).0.contents.short_channel_id;
+	let chan_2_scid = create_announced_chan_between_nodes(&nodes, 0, 1).0.contents.short_channel_id;
+
+	let (mut route, hash, payment_preimage, payment_secret) =
+		get_route_and_payment_hash!(nodes[0], nodes[1], 100_000);
+	let path = route.paths[0].clone();
+	route.paths.push(path);
+	route.paths[0].hops[0].short_channel_id = chan_1_scid;
+	route.paths[1].hops[0].short_channel_id = chan_2_scid;
+
+	let start_secs = SystemTime::UNIX_EPOCH.elapsed().unwrap().as_secs();
+	let onion = RecipientOnionFields::secret_only(payment_secret);
+	nodes[0].node.send_payment_with_route(route, hash, onion, PaymentId(hash.0)).unwrap();
+	check_added_monitors(&nodes[0], 2);
+	let mut events = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(events.len(), 2);
+
+	let pass_partial_path = |ev| {
+		let args = PassAlongPathArgs::new(&nodes[0], &[&nodes[1]], 200_000, hash, ev)
+			.with_payment_secret(payment_secret)
+			.without_claimable_event()
+			.without_clearing_recipient_events();
+		do_pass_along_path(args);
+
+		let events = nodes[1].node.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		match events[0] {
+			Event::PaymentPartiallyReceived {
+				payment_hash,
+				received_msat,
+				expected_msat,
+				deadline,
+			} => {
+				assert_eq!(payment_hash, hash);
+				assert_eq!(received_msat, 100_000);
+				assert_eq!(expected_msat, 200_000);
+				assert!(deadline >= start_secs + 180);
+			},
+			_ => panic!("Unexpected event"),
+		}
+	};
+	let time_out_partial_payment = |mpp_parts_remain: bool| {
+		nodes[1].node.expire_stale_inbound_payments();
+		let mut events = nodes[1].node.get_and_clear_pending_events();
+		assert_eq!(events.len(), 2);
+		match events[0] {
+			Event::PartialPaymentFailed { payment_hash, received_msat, expected_msat, reason } => {
+				assert_eq!(payment_hash, hash);
+				assert_eq!(received_msat, 100_000);
+				assert_eq!(expected_msat, 200_000);
+				assert_eq!(reason, PartialPaymentFailureReason::TimedOutAwaitingParts);
+			},
+			_ => panic!("Unexpected event"),
+		}
+		let fail = HTLCHandlingFailureType::Receive { payment_hash: hash };
+		expect_htlc_failure_conditions(events.split_off(1), &[fail]);
+		expect_and_process_pending_htlcs(&nodes[1], false);
+
+		let updates = get_htlc_update_msgs!(nodes[1], node_a_id);
+		assert_eq!(updates.update_fail_htlcs.len(), 1);
+		nodes[0].node.handle_update_fail_htlc(node_b_id, &updates.update_fail_htlcs[0]);
+		check_added_monitors(&nodes[1], 1);
+		commitment_signed_dance!(nodes[0], nodes[1], updates.commitment_signed, false);
+
+		let mut conditions = PaymentFailedConditions::new()
+			.expected_htlc_error_data(LocalHTLCFailureReason::MPPTimeout, &[][..]);
+		if mpp_parts_remain {
+			conditions = conditions.mpp_parts_remain();
+		}
+		expect_payment_failed_conditions(&nodes[0], hash, false, conditions);
+	};
+
+	pass_partial_path(events.remove(0));
+
+	// Just before the deadline, the parts received so far are still held.
+	for _ in 0..3 {
+		nodes[1].node.expire_stale_inbound_payments();
+	}
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+
+	if second_part_before_deadline {
+		let ev = events.remove(0);
+		let payment_secret = Some(payment_secret);
+		pass_along_path(&nodes[0], &[&nodes[1]], 200_000, hash, payment_secret, ev, true, None);
+		let full_path: &[&[_]] = &[&[&nodes[1]], &[&nodes[1]]];
+		claim_payment_along_route(ClaimAlongRouteArgs::new(&nodes[0], full_path, payment_preimage));
+	} else {
+		time_out_partial_payment(true);
+
+		// A part arriving after the deadline starts a new partial payment, which times out again.
+		pass_partial_path(events.remove(0));
+		for _ in 0..3 {
+			nodes[1].node.expire_stale_inbound_payments();
+		}
+		time_out_partial_payment(false);
+	}
+}
+
+#[test]
+fn mpp_receive_configured_timeout() {
+	do_mpp_receive_configured_timeout(true);
+	do_mpp_receive_configured_timeout(false);
+}
+
+#[test]
+fn mpp_receive_part_limit_for_reserve() {
+	// Tests that the parts of an inbound MPP exceeding
+	// `UserConfig::max_inbound_payment_parts_for_reserve` on a channel are failed back with a
+	// retriable error, such that the payment still completes once the sender retries them over
+	// another channel.
+	let chanmon_cfgs = create_chanmon_cfgs(4);
+	let node_cfgs = create_node_cfgs(4, &chanmon_cfgs);
+	let mut config = test_default_channel_config();
+	config.max_inbound_payment_parts_for_reserve = Some(1);
+	let node_chanmgrs = create_node_chanmgrs(4, &node_cfgs, &[None, None, None, Some(config)]);
+	let nodes = create_network(4, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_d_id = nodes[3].node.get_our_node_id();
+
+	let chan_1_scid = create_announced_chan_between_nodes(&nodes, 0, 1).0.contents.short_channel_id;
+	let chan_2_scid = create_announced_chan_between_nodes(&nodes, 0, 1).0.contents.short_channel_id;
+	create_announced_chan_between_nodes(&nodes, 0, 2);
+	let chan_3_scid = create_announced_chan_between_nodes(&nodes, 1, 3).0.contents.short_channel_id;
+	create_announced_chan_between_nodes(&nodes, 2, 3);
+
+	// Route both parts over the channel between node B and node D, exceeding the limit.
+	let amt_msat = 1_000_000;
+	let payment_params = PaymentParameters::from_node_id(node_d_id, TEST_FINAL_CLTV)
+		.with_bolt11_features(nodes[3].node.bolt11_invoice_features())
+		.unwrap();
+	let (mut route, hash, preimage, pay_secret) =
+		get_route_and_payment_hash!(nodes[0], nodes[3], payment_params, amt_msat);
+	let path = route.paths[0].clone();
+	route.paths.push(path);
+	route.paths[0].hops[0].pubkey = node_b_id;
+	route.paths[0].hops[0].short_channel_id = chan_1_scid;
+	route.paths[0].hops[1].short_channel_id = chan_3_scid;
+	route.paths[1].hops[0].pubkey = node_b_id;
+	route.paths[1].hops[0].short_channel_id = chan_2_scid;
+	route.paths[1].hops[1].short_channel_id = chan_3_scid;
+
+	let id = PaymentId(hash.0);
+	let route_params = route.route_params.clone().unwrap();
+	nodes[0].router.expect_find_route(route_params.clone(), Ok(route));
+	let onion = RecipientOnionFields::secret_only(pay_secret);
+	nodes[0].node.send_payment(hash, onion, id, route_params, Retry::Attempts(1)).unwrap();
+	check_added_monitors(&nodes[0], 2);
+	let events = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(events.len(), 2);
+	for event in events {
+		let payment_event = SendEvent::from_event(event);
+		nodes[1].node.handle_update_add_htlc(node_a_id, &payment_event.msgs[0]);
+		commitment_signed_dance!(nodes[1], nodes[0], payment_event.commitment_msg, false);
+	}
+	expect_and_process_pending_htlcs(&nodes[1], false);
+	check_added_monitors(&nodes[1], 1);
+
+	let payment_event = SendEvent::from_node(&nodes[1]);
+	assert_eq!(payment_event.msgs.len(), 2);
+	nodes[3].node.handle_update_add_htlc(node_b_id, &payment_event.msgs[0]);
+	nodes[3].node.handle_update_add_htlc(node_b_id, &payment_event.msgs[1]);
+	commitment_signed_dance!(nodes[3], nodes[1], payment_event.commitment_msg, false);
+
+	// Node D holds on to the first part, but fails back the second one.
+	expect_and_process_pending_htlcs(&nodes[3], false);
+	let events = nodes[3].node.get_and_clear_pending_events();
+	let fail = HTLCHandlingFailureType::Receive { payment_hash: hash };
+	expect_htlc_failure_conditions(events, &[fail]);
+	check_added_monitors(&nodes[3], 1);
+
+	let updates = get_htlc_update_msgs!(nodes[3], node_b_id);
+	assert_eq!(updates.update_fail_htlcs.len(), 1);
+	nodes[1].node.handle_update_fail_htlc(node_d_id, &updates.update_fail_htlcs[0]);
+	commitment_signed_dance!(nodes[1], nodes[3], updates.commitment_signed, true);
+	let updates = get_htlc_update_msgs!(nodes[1], node_a_id);
+	nodes[0].node.handle_update_fail_htlc(node_b_id, &updates.update_fail_htlcs[0]);
+	commitment_signed_dance!(nodes[0], nodes[1], updates.commitment_signed, false);
+
+	let events = nodes[0].node.get_and_clear_pending_events();
+	let reason = LocalHTLCFailureReason::TemporaryChannelFailure;
+	let conditions =
+		PaymentFailedConditions::new().mpp_parts_remain().expected_htlc_error_data(reason, &[0; 2]);
+	expect_payment_failed_conditions_event(events, hash, false, conditions);
+
+	// The retry avoids the channel the part was failed on, so it's routed via node C instead and
+	// completes the payment.
+	expect_and_process_pending_htlcs(&nodes[0], false);
+	check_added_monitors(&nodes[0], 1);
+	let mut events = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(events.len(), 1);
+	let event = events.pop().unwrap();
+	let retry_path = &[&nodes[2], &nodes[3]];
+	pass_along_path(&nodes[0], retry_path, 2_000_000, hash, Some(pay_secret), event, true, None);
+
+	let claim_paths: &[&[_]] = &[&[&nodes[1], &nodes[3]], &[&nodes[2], &nodes[3]]];
+	claim_payment_along_route(ClaimAlongRouteArgs::new(&nodes[0], claim_paths, preimage));
+}
+
 #[test]
 fn test_keysend_payments() {
 	do_test_keysend_payments(false);
@@ -1952,6 +2170,8 @@ enum InterceptTest {
 	Forward,
 	Fail,
 	Timeout,
+	ReleaseScid,
+	TimerTimeout,
 }
 
 #[test]
@@ -2149,6 +2369,10 @@ fn intercepted_payment() {
 	do_test_intercepted_payment(InterceptTest::Fail);
 	// Make sure that intercepted payments will be automatically failed back if too many blocks pass.
 	do_test_intercepted_payment(InterceptTest::Timeout);
+	// Check that intercepted payments can be failed back by releasing their intercept scid, and are
+	// automatically failed back if they are held for too many timer ticks.
+	do_test_intercepted_payment(InterceptTest::ReleaseScid);
+	do_test_intercepted_payment(InterceptTest::TimerTimeout);
 }
 
 fn do_test_intercepted_payment(test: InterceptTest) {
@@ -2250,9 +2474,44 @@ fn do_test_intercepted_payment(test: InterceptTest) {
 	);
 	assert_eq!(unknown_chan_id_err, Err(APIError::ChannelUnavailable { err }));
 
-	if test == InterceptTest::Fail {
-		// Ensure we can fail the intercepted payment back.
-		nodes[1].node.fail_intercepted_htlc(intercept_id).unwrap();
+	let stats = nodes[1].node.inbound_state_stats();
+	assert_eq!(stats.intercepted_htlcs, 1);
+	assert_eq!(stats.intercept_scids, 1);
+	assert_eq!(stats.claimable_payments, 0);
+	assert!(stats.approximate_memory_bytes > 0);
+	let encoded_len_before = nodes[1].node.encode().len();
+
+	if test == InterceptTest::Fail
+		|| test == InterceptTest::ReleaseScid
+		|| test == InterceptTest::TimerTimeout
+	{
+		if test == InterceptTest::Fail {
+			// Ensure we can fail the intercepted payment back.
+			nodes[1].node.fail_intercepted_htlc(intercept_id).unwrap();
+		} else if test == InterceptTest::ReleaseScid {
+			// Releasing an unrelated scid leaves the intercepted payment alone.
+			assert_eq!(nodes[1].node.release_intercept_scid(intercept_scid + 1), 0);
+			assert_eq!(nodes[1].node.inbound_state_stats().intercepted_htlcs, 1);
+			assert_eq!(nodes[1].node.release_intercept_scid(intercept_scid), 1);
+		} else {
+			for _ in 0..INTERCEPTED_HTLC_TIMEOUT_TICKS - 1 {
+				nodes[1].node.timer_tick_occurred();
+			}
+			assert_eq!(nodes[1].node.inbound_state_stats().intercepted_htlcs, 1);
+			assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+			nodes[1].node.timer_tick_occurred();
+		}
+		let empty_stats = InboundStateStats {
+			claimable_payments: 0,
+			claimable_htlcs: 0,
+			claiming_payments: 0,
+			intercepted_htlcs: 0,
+			intercept_scids: 0,
+			approximate_memory_bytes: 0,
+		};
+		assert_eq!(nodes[1].node.inbound_state_stats(), empty_stats);
+		assert!(nodes[1].node.encode().len() < encoded_len_before);
+
 		let fail =
 			HTLCHandlingFailureType::InvalidForward { requested_forward_scid: intercept_scid };
 		expect_htlc_failure_conditions(nodes[1].node.get_and_clear_pending_events(), &[fail]);
@@ -5318,3 +5577,186 @@ fn max_out_mpp_path() {
 	check_added_monitors(&nodes[0], 2); // one monitor update per MPP part
 	nodes[0].node.get_and_clear_pending_msg_events();
 }
+
+#[test]
+fn fails_fast_on_infeasible_payments() {
+	// Payments which cannot possibly succeed should fail immediately, without marking the payment
+	// as pending, unless `UserConfig::fail_fast_infeasible_payments` is disabled.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let mut no_fail_fast_config = test_default_channel_config();
+	no_fail_fast_config.fail_fast_infeasible_payments = false;
+	let configs = [None, Some(no_fail_fast_config), None];
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &configs);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	create_announced_chan_between_nodes(&nodes, 0, 1);
+	create_announced_chan_between_nodes(&nodes, 1, 2);
+
+	let amt_msat = 10_000;
+	let (preimage, hash, payment_secret) = get_payment_preimage_hash!(nodes[2]);
+	let id = PaymentId(hash.0);
+	let payment_params = PaymentParameters::from_node_id(node_c_id, TEST_FINAL_CLTV);
+	let route_params =
+		RouteParameters::from_payment_params_and_value(payment_params.clone(), amt_msat);
+	assert_eq!(nodes[0].node.estimate_payment_feasibility(&route_params), Ok(()));
+
+	let send_infeasible_payment = |route_params: RouteParameters| {
+		let onion = RecipientOnionFields::secret_only(payment_secret);
+		let res = nodes[0].node.send_payment(hash, onion, id, route_params, Retry::Attempts(1));
+		assert!(nodes[0].node.list_recent_payments().is_empty());
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+		match res {
+			Err(RetryableSendFailure::PaymentInfeasible(reason)) => reason,
+			_ => panic!("Unexpected result: {:?}", res),
+		}
+	};
+
+	// An expired payment.
+	let expired_params = payment_params.clone().with_expiry_time(1);
+	let expired_route_params =
+		RouteParameters::from_payment_params_and_value(expired_params, amt_msat);
+	assert_eq!(
+		send_infeasible_payment(expired_route_params),
+		InfeasiblePaymentReason::PaymentExpired
+	);
+
+	// A payment exceeding our outbound capacity.
+	let outbound_capacity_msat =
+		nodes[0].node.list_usable_channels()[0].next_outbound_htlc_limit_msat;
+	let required_msat = outbound_capacity_msat + 1;
+	let large_route_params =
+		RouteParameters::from_payment_params_and_value(payment_params.clone(), required_msat);
+	assert_eq!(
+		send_infeasible_payment(large_route_params),
+		InfeasiblePaymentReason::InsufficientOutboundCapacity {
+			outbound_capacity_msat,
+			required_msat
+		}
+	);
+
+	// A payment to a node without any known channels, unless route hints are provided.
+	let secp_ctx = Secp256k1::new();
+	let unknown_node_id =
+		PublicKey::from_secret_key(&secp_ctx, &SecretKey::from_slice(&[42; 32]).unwrap());
+	let unknown_params = PaymentParameters::from_node_id(unknown_node_id, TEST_FINAL_CLTV);
+	let unknown_route_params =
+		RouteParameters::from_payment_params_and_value(unknown_params.clone(), amt_msat);
+	assert_eq!(
+		send_infeasible_payment(unknown_route_params.clone()),
+		InfeasiblePaymentReason::UnreachableDestination
+	);
+
+	let route_hint = RouteHint(vec![RouteHintHop {
+		src_node_id: node_c_id,
+		short_channel_id: 42,
+		fees: RoutingFees { base_msat: 0, proportional_millionths: 0 },
+		cltv_expiry_delta: MIN_CLTV_EXPIRY_DELTA,
+		htlc_minimum_msat: None,
+		htlc_maximum_msat: None,
+	}]);
+	let hinted_params = unknown_params.with_route_hints(vec![route_hint]).unwrap();
+	let hinted_route_params =
+		RouteParameters::from_payment_params_and_value(hinted_params, amt_msat);
+	assert_eq!(nodes[0].node.estimate_payment_feasibility(&hinted_route_params), Ok(()));
+
+	// With the checks disabled, we still attempt to find a route.
+	assert_eq!(
+		nodes[1].node.estimate_payment_feasibility(&unknown_route_params),
+		Err(InfeasiblePaymentReason::UnreachableDestination)
+	);
+	let onion = RecipientOnionFields::secret_only(payment_secret);
+	let res = nodes[1].node.send_payment(hash, onion, id, unknown_route_params, Retry::Attempts(1));
+	assert_eq!(res, Err(RetryableSendFailure::RouteNotFound));
+
+	// The `PaymentId` of the infeasible payments may be reused.
+	let onion = RecipientOnionFields::secret_only(payment_secret);
+	nodes[0].node.send_payment(hash, onion, id, route_params, Retry::Attempts(1)).unwrap();
+	check_added_monitors(&nodes[0], 1);
+
+	let mut events = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(events.len(), 1);
+	let ev = remove_first_msg_event_to_node(&node_b_id, &mut events);
+	let path = &[&nodes[1], &nodes[2]];
+	pass_along_path(&nodes[0], path, amt_msat, hash, Some(payment_secret), ev, true, None);
+	claim_payment(&nodes[0], path, preimage);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn fails_forwards_while_chain_sync_stale() {
+	// Tests that once no new best block has been seen for longer than
+	// `UserConfig::chain_sync_stale_threshold_secs`, an `Event::ChainSyncStale` is generated and,
+	// if `UserConfig::fail_htlc_forwards_while_chain_sync_stale` is set, forwards are failed until
+	// the next block is connected.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let mut stale_cfg = test_default_channel_config();
+	stale_cfg.fail_htlc_forwards_while_chain_sync_stale = true;
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, Some(stale_cfg.clone()), None]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let node_c_id = nodes[2].node.get_our_node_id();
+
+	create_announced_chan_between_nodes(&nodes, 0, 1);
+	let chan_id_2 = create_announced_chan_between_nodes(&nodes, 1, 2).2;
+
+	// Up to the threshold, our view of the chain is not considered stale.
+	let threshold_secs = stale_cfg.chain_sync_stale_threshold_secs;
+	TestTime::advance(Duration::from_secs(threshold_secs));
+	nodes[1].node.timer_tick_occurred();
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+
+	TestTime::advance(Duration::from_secs(1));
+	nodes[1].node.timer_tick_occurred();
+	let events = nodes[1].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match events[0] {
+		Event::ChainSyncStale { last_block_hash, last_update_age_secs } => {
+			assert_eq!(last_block_hash, nodes[1].best_block_hash());
+			assert_eq!(last_update_age_secs, threshold_secs + 1);
+		},
+		_ => panic!("Unexpected event"),
+	}
+
+	// The event is only generated once while our view of the chain remains stale.
+	nodes[1].node.timer_tick_occurred();
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+
+	// Forwards are failed back with a `temporary_node_failure`.
+	let (route, payment_hash, _, payment_secret) =
+		get_route_and_payment_hash!(nodes[0], nodes[2], 10_000);
+	let onion = RecipientOnionFields::secret_only(payment_secret);
+	let id = PaymentId(payment_hash.0);
+	nodes[0].node.send_payment_with_route(route, payment_hash, onion, id).unwrap();
+	check_added_monitors(&nodes[0], 1);
+	let payment_event = SendEvent::from_node(&nodes[0]);
+	nodes[1].node.handle_update_add_htlc(node_a_id, &payment_event.msgs[0]);
+	commitment_signed_dance!(nodes[1], nodes[0], payment_event.commitment_msg, false, true);
+	expect_and_process_pending_htlcs(&nodes[1], false);
+	expect_htlc_handling_failed_destinations!(
+		nodes[1].node.get_and_clear_pending_events(),
+		&[HTLCHandlingFailureType::Forward { node_id: Some(node_c_id), channel_id: chan_id_2 }]
+	);
+	check_added_monitors(&nodes[1], 1);
+
+	let htlc_fail = get_htlc_update_msgs!(nodes[1], node_a_id);
+	assert_eq!(htlc_fail.update_fail_htlcs.len(), 1);
+	nodes[0].node.handle_update_fail_htlc(node_b_id, &htlc_fail.update_fail_htlcs[0]);
+	commitment_signed_dance!(nodes[0], nodes[1], htlc_fail.commitment_signed, false);
+	let reason = LocalHTLCFailureReason::TemporaryNodeFailure;
+	expect_payment_failed!(nodes[0], payment_hash, false, reason, []);
+
+	// Once a new block is seen, forwards are accepted again.
+	connect_blocks(&nodes[0], 1);
+	connect_blocks(&nodes[1], 1);
+	connect_blocks(&nodes[2], 1);
+	nodes[1].node.timer_tick_occurred();
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	send_payment(&nodes[0], &[&nodes[1], &nodes[2]], 10_000);
+}
@@ -656,6 +656,12 @@ impl MessageBuf {
 		res[16 + 2..].copy_from_slice(&encoded_msg);
 		Self(res)
 	}
+
+	/// Returns the length of the buffer, i.e., of the encoded message plus its length header and
+	/// the MAC of the header.
+	pub(crate) fn len(&self) -> usize {
+		self.0.len()
+	}
 }
 
 #[cfg(test)]
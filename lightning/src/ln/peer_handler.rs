@@ -567,6 +567,10 @@ impl ChannelMessageHandler for ErroringMessageHandler {
 	}
 
 	fn message_received(&self) {}
+
+	fn peer_has_pending_htlcs(&self, _their_node_id: PublicKey) -> bool {
+		false
+	}
 }
 
 impl Deref for ErroringMessageHandler {
@@ -670,6 +674,57 @@ pub struct PeerDetails {
 	///
 	/// Will be `true` for inbound connections, and `false` for outbound connections.
 	pub is_inbound_connection: bool,
+	/// The number of bytes currently buffered to be sent to the peer.
+	pub outbound_buffered_bytes: usize,
+	/// The class the peer is placed in when scheduling writes to peers while sending data is
+	/// congested.
+	///
+	/// See [`OutboundSchedulingConfig`] for more details.
+	pub scheduling_class: PeerSchedulingClass,
+}
+
+/// The class a peer is placed in when scheduling writes to peers while sending data is congested.
+///
+/// See [`OutboundSchedulingConfig`] for more details.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PeerSchedulingClass {
+	/// We have HTLCs pending with the peer, so writes to it are never deferred.
+	PendingHtlcs,
+	/// We have no HTLCs pending with the peer, so writes to it may be deferred in favor of peers
+	/// in [`PeerSchedulingClass::PendingHtlcs`].
+	Standard,
+}
+
+/// Configures how the [`PeerManager`] schedules writes to peers when the total amount of data
+/// buffered to be sent to peers grows large, e.g., because our uplink is saturated.
+///
+/// While sending data is congested, writes to peers in [`PeerSchedulingClass::PendingHtlcs`] are
+/// prioritized by deferring writes to all other peers, such that HTLCs can still be resolved in a
+/// timely manner. To ensure other peers still make progress, writes to a peer are only deferred a
+/// limited number of times in a row.
+///
+/// Writes to peers which were deferred are retried on the next call to
+/// [`PeerManager::process_events`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutboundSchedulingConfig {
+	/// The total number of bytes buffered to be sent across all peers above which we consider
+	/// sending data to be congested.
+	///
+	/// Default value: 262144 (256 KiB).
+	pub congestion_threshold_bytes: usize,
+	/// The maximum number of times in a row writes to a peer in [`PeerSchedulingClass::Standard`]
+	/// are deferred while sending data is congested, after which we write to the peer anyway.
+	///
+	/// Setting this to 0 disables prioritizing writes to peers.
+	///
+	/// Default value: 4.
+	pub max_consecutive_deferred_writes: u8,
+}
+
+impl Default for OutboundSchedulingConfig {
+	fn default() -> Self {
+		Self { congestion_threshold_bytes: 256 * 1024, max_consecutive_deferred_writes: 4 }
+	}
 }
 
 /// Error for PeerManager errors. If you get one of these, you must disconnect the socket and
@@ -804,6 +859,13 @@ struct Peer {
 	inbound_connection: bool,
 
 	message_batch: Option<MessageBatch>,
+
+	/// The class of the peer as of the last time we scheduled writes while sending data was
+	/// congested.
+	scheduling_class: PeerSchedulingClass,
+	/// The number of times in a row we've deferred writing to this peer while sending data was
+	/// congested.
+	consecutive_deferred_writes: u8,
 }
 
 impl Peer {
@@ -903,6 +965,29 @@ impl Peer {
 	fn set_their_node_id(&mut self, node_id: PublicKey) {
 		self.their_node_id = Some((node_id, NodeId::from_pubkey(&node_id)));
 	}
+
+	/// Returns the number of bytes buffered to be sent to this peer, including gossip broadcasts
+	/// we have yet to encrypt.
+	fn outbound_buffered_bytes(&self) -> usize {
+		let pending_bytes: usize = self.pending_outbound_buffer.iter().map(|buf| buf.len()).sum();
+		let gossip_bytes: usize = self.gossip_broadcast_buffer.iter().map(|buf| buf.len()).sum();
+		pending_bytes - self.pending_outbound_buffer_first_msg_offset + gossip_bytes
+	}
+
+	/// Returns whether we should defer writing to this peer while sending data is congested,
+	/// tracking how many times in a row we've done so to bound how long the peer is starved.
+	fn should_defer_write(&mut self, max_consecutive_deferred_writes: u8) -> bool {
+		// If we're awaiting a write event, attempting to write is a no-op anyway.
+		let defer = self.scheduling_class == PeerSchedulingClass::Standard
+			&& !self.awaiting_write_event
+			&& self.consecutive_deferred_writes < max_consecutive_deferred_writes;
+		if defer {
+			self.consecutive_deferred_writes += 1;
+		} else {
+			self.consecutive_deferred_writes = 0;
+		}
+		defer
+	}
 }
 
 /// SimpleArcPeerManager is useful when you need a PeerManager with a static lifetime, e.g.
@@ -1100,6 +1185,11 @@ pub struct PeerManager<
 	gossip_processing_backlogged: AtomicBool,
 	gossip_processing_backlog_lifted: AtomicBool,
 
+	outbound_scheduling_config: Mutex<OutboundSchedulingConfig>,
+	/// Set when the total number of bytes buffered to be sent across all peers exceeded
+	/// [`OutboundSchedulingConfig::congestion_threshold_bytes`] the last time we processed events.
+	outbound_congested: AtomicBool,
+
 	node_signer: NS,
 
 	logger: L,
@@ -1324,6 +1414,8 @@ where
 			peer_counter: AtomicCounter::new(),
 			gossip_processing_backlogged: AtomicBool::new(false),
 			gossip_processing_backlog_lifted: AtomicBool::new(false),
+			outbound_scheduling_config: Mutex::new(OutboundSchedulingConfig::default()),
+			outbound_congested: AtomicBool::new(false),
 			last_node_announcement_serial: AtomicU32::new(current_time),
 			logger,
 			node_signer,
@@ -1340,15 +1432,18 @@ where
 			if !p.handshake_complete() {
 				return None;
 			}
+			// unwrap safety: their_node_id is guaranteed to be `Some` after the handshake
+			// completed.
+			let counterparty_node_id = p.their_node_id.unwrap().0;
 			let details = PeerDetails {
-				// unwrap safety: their_node_id is guaranteed to be `Some` after the handshake
-				// completed.
-				counterparty_node_id: p.their_node_id.unwrap().0,
+				counterparty_node_id,
 				socket_address: p.their_socket_address.clone(),
 				// unwrap safety: their_features is guaranteed to be `Some` after the handshake
 				// completed.
 				init_features: p.their_features.clone().unwrap(),
 				is_inbound_connection: p.inbound_connection,
+				outbound_buffered_bytes: p.outbound_buffered_bytes(),
+				scheduling_class: self.scheduling_class(counterparty_node_id),
 			};
 			Some(details)
 		};
@@ -1381,11 +1476,27 @@ where
 				// completed.
 				init_features: p.their_features.clone().unwrap(),
 				is_inbound_connection: p.inbound_connection,
+				outbound_buffered_bytes: p.outbound_buffered_bytes(),
+				scheduling_class: self.scheduling_class(counterparty_node_id),
 			};
 			Some(details)
 		})
 	}
 
+	/// Sets the [`OutboundSchedulingConfig`] used to schedule writes to peers while sending data is
+	/// congested.
+	pub fn set_outbound_scheduling_config(&self, config: OutboundSchedulingConfig) {
+		*self.outbound_scheduling_config.lock().unwrap() = config;
+	}
+
+	fn scheduling_class(&self, their_node_id: PublicKey) -> PeerSchedulingClass {
+		if self.message_handler.chan_handler.peer_has_pending_htlcs(their_node_id) {
+			PeerSchedulingClass::PendingHtlcs
+		} else {
+			PeerSchedulingClass::Standard
+		}
+	}
+
 	fn get_ephemeral_key(&self) -> SecretKey {
 		let mut ephemeral_hash = self.ephemeral_key_midstate.clone();
 		let counter = self.peer_counter.next();
@@ -1459,6 +1570,9 @@ where
 					inbound_connection: false,
 
 					message_batch: None,
+
+					scheduling_class: PeerSchedulingClass::Standard,
+					consecutive_deferred_writes: 0,
 				}));
 				Ok(res)
 			},
@@ -1519,6 +1633,9 @@ where
 					inbound_connection: true,
 
 					message_batch: None,
+
+					scheduling_class: PeerSchedulingClass::Standard,
+					consecutive_deferred_writes: 0,
 				}));
 				Ok(())
 			},
@@ -1665,6 +1782,13 @@ where
 			Some(peer_mutex) => {
 				let mut peer = peer_mutex.lock().unwrap();
 				peer.awaiting_write_event = false;
+				if self.outbound_congested.load(Ordering::Relaxed) {
+					let config = self.outbound_scheduling_config.lock().unwrap();
+					if peer.should_defer_write(config.max_consecutive_deferred_writes) {
+						// We'll write to the peer on a later call to `process_events`.
+						return Ok(());
+					}
+				}
 				self.do_attempt_write_data(descriptor, &mut peer, false);
 			},
 		};
@@ -3343,11 +3467,42 @@ where
 					self.enqueue_message(&mut peer, &msg);
 				}
 
+				let config = *self.outbound_scheduling_config.lock().unwrap();
+				let total_buffered_bytes: usize = peers
+					.values()
+					.map(|peer_mutex| peer_mutex.lock().unwrap().outbound_buffered_bytes())
+					.sum();
+				let congested = total_buffered_bytes > config.congestion_threshold_bytes;
+				self.outbound_congested.store(congested, Ordering::Relaxed);
+
+				// While congested, write to peers we have HTLCs pending with before any others,
+				// deferring writes to the others up to `max_consecutive_deferred_writes` times.
+				let mut standard_peers = Vec::new();
 				for (descriptor, peer_mutex) in peers.iter() {
 					let mut peer = peer_mutex.lock().unwrap();
 					if flush_read_disabled {
 						peer.received_channel_announce_since_backlogged = false;
 					}
+					if congested {
+						if let Some((node_id, _)) = peer.their_node_id {
+							peer.scheduling_class = self.scheduling_class(node_id);
+						}
+						if peer.scheduling_class == PeerSchedulingClass::Standard {
+							standard_peers.push((descriptor, peer_mutex));
+							continue;
+						}
+					}
+					self.do_attempt_write_data(
+						&mut (*descriptor).clone(),
+						&mut *peer,
+						flush_read_disabled,
+					);
+				}
+				for (descriptor, peer_mutex) in standard_peers {
+					let mut peer = peer_mutex.lock().unwrap();
+					if peer.should_defer_write(config.max_consecutive_deferred_writes) {
+						continue;
+					}
 					self.do_attempt_write_data(
 						&mut (*descriptor).clone(),
 						&mut *peer,
@@ -3720,6 +3875,8 @@ mod tests {
 	struct FileDescriptor {
 		fd: u16,
 		hang_writes: Arc<AtomicBool>,
+		/// The maximum number of bytes `outbound_data` may hold, simulating a socket send buffer.
+		send_buffer_size: Arc<AtomicUsize>,
 		outbound_data: Arc<Mutex<Vec<u8>>>,
 		disconnect: Arc<AtomicBool>,
 	}
@@ -3740,8 +3897,12 @@ mod tests {
 			if self.hang_writes.load(Ordering::Acquire) {
 				0
 			} else {
-				self.outbound_data.lock().unwrap().extend_from_slice(data);
-				data.len()
+				let mut outbound_data = self.outbound_data.lock().unwrap();
+				let send_buffer_size = self.send_buffer_size.load(Ordering::Acquire);
+				let space = send_buffer_size.saturating_sub(outbound_data.len());
+				let sent = cmp::min(space, data.len());
+				outbound_data.extend_from_slice(&data[..sent]);
+				sent
 			}
 		}
 
@@ -3755,6 +3916,7 @@ mod tests {
 			Self {
 				fd,
 				hang_writes: Arc::new(AtomicBool::new(false)),
+				send_buffer_size: Arc::new(AtomicUsize::new(usize::MAX)),
 				outbound_data: Arc::new(Mutex::new(Vec::new())),
 				disconnect: Arc::new(AtomicBool::new(false)),
 			}
@@ -4682,6 +4844,90 @@ mod tests {
 		);
 	}
 
+	#[test]
+	fn test_outbound_scheduling_prioritizes_peers_with_pending_htlcs() {
+		// Tests that once the data buffered to be sent to peers exceeds the congestion threshold,
+		// we prioritize writes to peers we have HTLCs pending with, while still writing to other
+		// peers once we've deferred writing to them `max_consecutive_deferred_writes` times.
+		let cfgs = create_peermgr_cfgs(3);
+		let peers = create_network(3, &cfgs);
+		let (mut fd_a_b, mut fd_b) = establish_connection(&peers[0], &peers[1]);
+		let (mut fd_a_c, mut fd_c) = establish_connection(&peers[0], &peers[2]);
+		let node_b_id = peers[1].node_signer.get_node_id(Recipient::Node).unwrap();
+		let node_c_id = peers[2].node_signer.get_node_id(Recipient::Node).unwrap();
+
+		cfgs[0].chan_handler.peers_with_pending_htlcs.lock().unwrap().insert(node_b_id);
+		peers[0].set_outbound_scheduling_config(OutboundSchedulingConfig {
+			congestion_threshold_bytes: 4_000,
+			max_consecutive_deferred_writes: 2,
+		});
+
+		// Without congestion, all peers are in the same class as far as writes are concerned.
+		let peer_b = peers[0].peer_by_node_id(&node_b_id).unwrap();
+		assert_eq!(peer_b.scheduling_class, PeerSchedulingClass::PendingHtlcs);
+		assert_eq!(peer_b.outbound_buffered_bytes, 0);
+		let peer_c = peers[0].peer_by_node_id(&node_c_id).unwrap();
+		assert_eq!(peer_c.scheduling_class, PeerSchedulingClass::Standard);
+		assert_eq!(peer_c.outbound_buffered_bytes, 0);
+
+		// Give our sockets tiny send buffers and queue more messages than the congestion threshold.
+		fd_a_b.send_buffer_size.store(100, Ordering::Release);
+		fd_a_c.send_buffer_size.store(100, Ordering::Release);
+		for node_id in [node_b_id, node_c_id] {
+			for _ in 0..10 {
+				let msg = msgs::Shutdown {
+					channel_id: ChannelId::from_bytes([42; 32]),
+					scriptpubkey: bitcoin::ScriptBuf::from_bytes(vec![0; 500]),
+				};
+				let ev = MessageSendEvent::SendShutdown { node_id, msg };
+				cfgs[0].chan_handler.pending_events.lock().unwrap().push(ev);
+			}
+		}
+
+		// Only the peer we have HTLCs pending with is written to until we've deferred writing to
+		// the other peer twice.
+		for _ in 0..2 {
+			peers[0].process_events();
+			assert_eq!(fd_a_b.outbound_data.lock().unwrap().len(), 100);
+			assert!(fd_a_c.outbound_data.lock().unwrap().is_empty());
+
+			let data = fd_a_b.outbound_data.lock().unwrap().split_off(0);
+			peers[1].read_event(&mut fd_b, &data).unwrap();
+			peers[0].write_buffer_space_avail(&mut fd_a_b).unwrap();
+		}
+		let peer_c = peers[0].peer_by_node_id(&node_c_id).unwrap();
+		assert!(peer_c.outbound_buffered_bytes > 5_000);
+		let peer_b = peers[0].peer_by_node_id(&node_b_id).unwrap();
+		assert!(peer_b.outbound_buffered_bytes < peer_c.outbound_buffered_bytes);
+
+		peers[0].process_events();
+		assert_eq!(fd_a_c.outbound_data.lock().unwrap().len(), 100);
+
+		// Once we drain all buffers, we're no longer congested and write to all peers again.
+		let mut rounds = 0;
+		loop {
+			peers[0].process_events();
+			let data_b = fd_a_b.outbound_data.lock().unwrap().split_off(0);
+			if !data_b.is_empty() {
+				peers[1].read_event(&mut fd_b, &data_b).unwrap();
+				peers[0].write_buffer_space_avail(&mut fd_a_b).unwrap();
+			}
+			let data_c = fd_a_c.outbound_data.lock().unwrap().split_off(0);
+			if !data_c.is_empty() {
+				peers[2].read_event(&mut fd_c, &data_c).unwrap();
+				peers[0].write_buffer_space_avail(&mut fd_a_c).unwrap();
+			}
+			let buffered_bytes: usize =
+				peers[0].list_peers().iter().map(|peer| peer.outbound_buffered_bytes).sum();
+			if buffered_bytes == 0 && data_b.is_empty() && data_c.is_empty() {
+				break;
+			}
+			rounds += 1;
+			assert!(rounds < 1_000);
+		}
+		assert!(!peers[0].outbound_congested.load(Ordering::Relaxed));
+	}
+
 	#[test]
 	fn test_filter_addresses() {
 		// Tests the filter_addresses function.
@@ -23,11 +23,13 @@ use crate::ln::types::ChannelId;
 use crate::routing::gossip::RoutingFees;
 use crate::routing::router::{PaymentParameters, RouteHint, RouteHintHop};
 use crate::types::features::ChannelTypeFeatures;
-use crate::util::config::{MaxDustHTLCExposure, UserConfig};
+use crate::util::config::{ChannelConfigUpdate, MaxDustHTLCExposure, UserConfig};
 use crate::util::ser::Writeable;
 
 use crate::prelude::*;
 
+use bitcoin::secp256k1::ecdsa::Signature;
+
 use crate::ln::functional_test_utils::*;
 
 #[test]
@@ -1351,3 +1353,71 @@ fn test_0conf_ann_sigs_racing_conf() {
 	let as_announcement = nodes[0].node.get_and_clear_pending_msg_events();
 	assert_eq!(as_announcement.len(), 1);
 }
+
+#[test]
+fn test_private_channel_identifiers_not_revealed() {
+	// Unless configured otherwise, we should refer to unannounced channels by their alias rather
+	// than their real SCID in messages to our counterparty, and avoid including the funding txid
+	// in error messages.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let (_, _, chan_id, funding_tx) =
+		create_unannounced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+	let chan = nodes[0].node.list_channels()[0].clone();
+	let real_scid = chan.short_channel_id.unwrap();
+	let alias_scid = chan.inbound_scid_alias.unwrap();
+	assert_ne!(real_scid, alias_scid);
+
+	// The channel_update sent to our counterparty carries the alias they know the channel by.
+	let config_update =
+		ChannelConfigUpdate { forwarding_fee_base_msat: Some(1_234), ..Default::default() };
+	nodes[0].node.update_partial_channel_config(&node_b_id, &[chan_id], &config_update).unwrap();
+	let update = get_event_msg!(nodes[0], MessageSendEvent::SendChannelUpdate, node_b_id);
+	assert_eq!(update.contents.short_channel_id, alias_scid);
+	nodes[1].node.handle_channel_update(node_a_id, &update);
+	let forwarding_info = nodes[1].node.list_channels()[0].counterparty.forwarding_info.clone();
+	assert_eq!(forwarding_info.unwrap().fee_base_msat, 1_234);
+
+	// Once allowed to, we use the real SCID.
+	let config_update = ChannelConfigUpdate {
+		forwarding_fee_base_msat: Some(2_345),
+		reveal_private_channel_identifiers: Some(true),
+		..Default::default()
+	};
+	nodes[0].node.update_partial_channel_config(&node_b_id, &[chan_id], &config_update).unwrap();
+	let update = get_event_msg!(nodes[0], MessageSendEvent::SendChannelUpdate, node_b_id);
+	assert_eq!(update.contents.short_channel_id, real_scid);
+
+	// nodes[1] still uses the default config, so doesn't reveal the funding txid when failing
+	// the channel due to a malformed commitment_signed batch.
+	let funding_txid = funding_tx.compute_txid();
+	let commitment_signed = msgs::CommitmentSigned {
+		channel_id: chan_id,
+		signature: Signature::from_compact(&[1; 64]).unwrap(),
+		htlc_signatures: vec![],
+		funding_txid: Some(funding_txid),
+		#[cfg(taproot)]
+		partial_signature_with_nonce: None,
+	};
+	let batch = vec![commitment_signed.clone(), commitment_signed];
+	nodes[1].node.handle_commitment_signed_batch(node_a_id, chan_id, batch);
+	check_added_monitors(&nodes[1], 1);
+
+	let msg_events = nodes[1].node.get_and_clear_pending_msg_events();
+	assert_eq!(msg_events.len(), 1);
+	let err_msg = match &msg_events[0] {
+		MessageSendEvent::HandleError { action: ErrorAction::SendErrorMessage { msg }, .. } => {
+			msg.data.clone()
+		},
+		_ => panic!("Unexpected event {:?}", msg_events[0]),
+	};
+	assert!(err_msg.contains("(omitted)"));
+	assert!(!err_msg.contains(&funding_txid.to_string()));
+	let reason = ClosureReason::ProcessingError { err: err_msg };
+	check_closed_event!(nodes[1], 1, reason, [node_a_id], 100_000);
+}
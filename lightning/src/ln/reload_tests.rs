@@ -13,9 +13,9 @@
 
 use crate::chain::{ChannelMonitorUpdateStatus, Watch};
 use crate::chain::chaininterface::LowerBoundedFeeEstimator;
-use crate::chain::channelmonitor::{ChannelMonitor, ChannelMonitorUpdateStep};
+use crate::chain::channelmonitor::{ANTI_REORG_DELAY, ChannelMonitor, ChannelMonitorUpdateStep};
 use crate::routing::router::{PaymentParameters, RouteParameters};
-use crate::sign::EntropySource;
+use crate::sign::{EntropySource, SpendableOutputDescriptor};
 use crate::chain::transaction::OutPoint;
 use crate::events::{ClosureReason, Event, HTLCHandlingFailureType};
 use crate::ln::channelmanager::{ChannelManager, ChannelManagerReadArgs, PaymentId, RecipientOnionFields, RAACommitmentOrder};
@@ -714,6 +714,159 @@ fn test_data_loss_protect() {
 	do_test_data_loss_protect(false, false, false);
 }
 
+#[test]
+fn test_data_loss_protect_manual_acknowledgement() {
+	use crate::types::string::UntrustedString;
+	// When `manually_acknowledge_data_loss` is set, instead of panicking upon a channel_reestablish
+	// proving we're behind, we generate an event and stall the channel by disconnecting the peer
+	// with a warning upon each reconnection until the data loss is acknowledged. Once it is, we
+	// close the channel without broadcasting and recover our funds once the peer broadcasts.
+	let mut chanmon_cfgs = create_chanmon_cfgs(2);
+	// We broadcast during Drop because chanmon is out of sync with chanmgr, which would cause a panic
+	// during signing due to revoked tx
+	chanmon_cfgs[0].keys_manager.disable_revocation_policy_check = true;
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let persister;
+	let new_chain_monitor;
+
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes_0_deserialized;
+
+	let mut nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan = create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1000000, 1000000);
+
+	// Cache node A state before any channel update
+	let previous_node_state = nodes[0].node.encode();
+	let previous_chain_monitor_state = get_monitor!(nodes[0], chan.2).encode();
+
+	send_payment(&nodes[0], &[&nodes[1]], 8000000);
+	send_payment(&nodes[0], &[&nodes[1]], 8000000);
+
+	nodes[0].node.peer_disconnected(node_b_id);
+	nodes[1].node.peer_disconnected(node_a_id);
+
+	let mut config = test_default_channel_config();
+	config.manually_acknowledge_data_loss = true;
+	reload_node!(nodes[0], config, previous_node_state, &[&previous_chain_monitor_state], persister, new_chain_monitor, nodes_0_deserialized);
+
+	// Data loss can only be acknowledged once detected.
+	match nodes[0].node.acknowledge_data_loss(&chan.2, &node_b_id) {
+		Err(APIError::APIMisuseError { .. }) => {},
+		res => panic!("Unexpected result: {:?}", res),
+	}
+
+	for reconnect in 0..2 {
+		nodes[0].node.peer_connected(node_b_id, &msgs::Init {
+			features: nodes[1].node.init_features(), networks: None, remote_network_address: None
+		}, true).unwrap();
+		nodes[1].node.peer_connected(node_a_id, &msgs::Init {
+			features: nodes[0].node.init_features(), networks: None, remote_network_address: None
+		}, false).unwrap();
+
+		let reestablish_a = get_chan_reestablish_msgs!(nodes[0], nodes[1]);
+		nodes[1].node.handle_channel_reestablish(node_a_id, &reestablish_a[0]);
+		let msg_events = nodes[1].node.get_and_clear_pending_msg_events();
+		assert_eq!(msg_events.len(), 2);
+		let reestablish_b = match &msg_events[0] {
+			MessageSendEvent::SendChannelReestablish { msg, .. } => msg.clone(),
+			_ => panic!("Unexpected events: {:?}", msg_events),
+		};
+
+		// Rather than panicking, A disconnects B with a warning without touching the channel.
+		nodes[0].node.handle_channel_reestablish(node_b_id, &reestablish_b);
+		let msg_events = nodes[0].node.get_and_clear_pending_msg_events();
+		assert_eq!(msg_events.len(), 1);
+		match &msg_events[0] {
+			MessageSendEvent::HandleError { action: ErrorAction::DisconnectPeerWithWarning { msg }, node_id } => {
+				assert_eq!(*node_id, node_b_id);
+				assert_eq!(msg.data, "Refusing to reestablish channel with stale local state until data loss is acknowledged");
+			},
+			_ => panic!("Unexpected events: {:?}", msg_events),
+		}
+
+		// The event is only generated upon first detecting the data loss.
+		let events = nodes[0].node.get_and_clear_pending_events();
+		if reconnect == 0 {
+			assert_eq!(events.len(), 1);
+			match &events[0] {
+				Event::LocalDataLossDetected { channel_id, counterparty_node_id, our_commitment_number, their_claimed_number } => {
+					assert_eq!(*channel_id, chan.2);
+					assert_eq!(*counterparty_node_id, node_b_id);
+					assert_eq!(*our_commitment_number, 0);
+					assert_eq!(*their_claimed_number, 4);
+				},
+				_ => panic!("Unexpected event: {:?}", events),
+			}
+		} else {
+			assert!(events.is_empty());
+		}
+		check_added_monitors(&nodes[0], 0);
+		assert!(nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+		assert_eq!(nodes[0].node.list_channels().len(), 1);
+		assert!(nodes[0].node.list_usable_channels().is_empty());
+
+		nodes[0].node.peer_disconnected(node_b_id);
+		nodes[1].node.peer_disconnected(node_a_id);
+	}
+
+	// Once acknowledged, A closes the channel without broadcasting its revoked commitment.
+	nodes[0].node.acknowledge_data_loss(&chan.2, &node_b_id).unwrap();
+	check_added_monitors(&nodes[0], 1);
+	let reason = ClosureReason::HolderForceClosed {
+		broadcasted_latest_txn: Some(false), message: "Local data loss acknowledged".to_owned()
+	};
+	check_closed_event!(nodes[0], 1, reason, [node_b_id], 1000000);
+	assert!(nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+	for msg in nodes[0].node.get_and_clear_pending_msg_events() {
+		match msg {
+			MessageSendEvent::BroadcastChannelUpdate { .. } => {},
+			MessageSendEvent::HandleError { action: ErrorAction::SendErrorMessage { .. }, .. } => {},
+			_ => panic!("Unexpected event {:?}", msg),
+		}
+	}
+
+	// Upon reconnecting, A no longer knows about the channel and gets B to force-close it.
+	nodes[0].node.peer_connected(node_b_id, &msgs::Init {
+		features: nodes[1].node.init_features(), networks: None, remote_network_address: None
+	}, true).unwrap();
+	nodes[1].node.peer_connected(node_a_id, &msgs::Init {
+		features: nodes[0].node.init_features(), networks: None, remote_network_address: None
+	}, false).unwrap();
+	assert!(get_chan_reestablish_msgs!(nodes[0], nodes[1]).is_empty());
+	let reestablish_b = get_chan_reestablish_msgs!(nodes[1], nodes[0]);
+	nodes[0].node.handle_channel_reestablish(node_b_id, &reestablish_b[0]);
+	let msg_events = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(msg_events.len(), 2);
+	let err_msg = match &msg_events[1] {
+		MessageSendEvent::HandleError { action: ErrorAction::SendErrorMessage { msg }, .. } => msg.clone(),
+		_ => panic!("Unexpected events: {:?}", msg_events),
+	};
+	nodes[1].node.handle_error(node_a_id, &err_msg);
+	check_added_monitors(&nodes[1], 1);
+	check_closed_event!(nodes[1], 1, ClosureReason::CounterpartyForceClosed { peer_msg: UntrustedString(err_msg.data.clone()) }, [node_a_id], 1000000);
+	check_closed_broadcast!(nodes[1], false);
+	let node_b_txn = nodes[1].tx_broadcaster.txn_broadcasted.lock().unwrap().split_off(0);
+	assert_eq!(node_b_txn.len(), 1);
+	check_spends!(node_b_txn[0], chan.3);
+
+	// Once B's commitment transaction confirms, A can claim its balance.
+	mine_transaction(&nodes[0], &node_b_txn[0]);
+	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+	let events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match &events[0] {
+		Event::SpendableOutputs { outputs, channel_id } => {
+			assert_eq!(*channel_id, Some(chan.2));
+			assert_eq!(outputs.len(), 1);
+			assert!(matches!(outputs[0], SpendableOutputDescriptor::StaticPaymentOutput(_)));
+		},
+		_ => panic!("Unexpected event: {:?}", events),
+	}
+}
+
 fn do_test_partial_claim_before_restart(persist_both_monitors: bool, double_restart: bool) {
 	// Test what happens if a node receives an MPP payment, claims it, but crashes before
 	// persisting the ChannelManager. If `persist_both_monitors` is false, also crash after only
@@ -1305,3 +1458,75 @@ fn test_htlc_localremoved_persistence() {
 	let htlc_fail_msg_after_reload = msgs.2.unwrap().update_fail_htlcs[0].clone();
 	assert_eq!(htlc_fail_msg, htlc_fail_msg_after_reload);
 }
+
+#[cfg(feature = "std")]
+fn do_test_reload_pending_channel_negotiation(reconnect: bool) {
+	// Unfunded channels are not persisted, but a negotiation in progress while the
+	// `ChannelManager` was persisted is still listed (and counted towards the anchor reserve)
+	// after reloading until the counterparty reconnects or the negotiation times out.
+	use crate::util::anchor_channel_reserves::{AnchorChannelReserveCheck, AnchorChannelReserveContext};
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let persister;
+	let new_chain_monitor;
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_cfg.manually_accept_inbound_channels = true;
+	anchors_cfg.pending_channel_negotiation_timeout_ticks = 2;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg.clone())]);
+	let nodes_0_deserialized;
+	let mut nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let context = AnchorChannelReserveContext::default();
+	let num_anchor_channels = |node: &Node| {
+		AnchorChannelReserveCheck::new(&context, &[], node.node, &node.chain_monitor.chain_monitor).num_anchor_channels()
+	};
+
+	// Start opening a channel, but crash before our counterparty received the `open_channel`.
+	let temporary_channel_id = nodes[0].node.create_channel(node_b_id, 100_000, 0, 42, None, None).unwrap();
+	get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+	let negotiations = nodes[0].node.list_pending_channel_negotiations();
+	assert_eq!(negotiations.len(), 1);
+	assert_eq!(negotiations[0].temporary_channel_id, temporary_channel_id);
+	assert_eq!(negotiations[0].counterparty_node_id, node_b_id);
+	assert!(negotiations[0].is_outbound);
+	assert!(negotiations[0].channel_type.supports_anchors_zero_fee_htlc_tx());
+	assert!(!negotiations[0].is_restored);
+	assert_eq!(num_anchor_channels(&nodes[0]), 1);
+
+	let node_encoded = nodes[0].node.encode();
+	nodes[1].node.peer_disconnected(node_a_id);
+	reload_node!(nodes[0], anchors_cfg, node_encoded, &[], persister, new_chain_monitor, nodes_0_deserialized);
+
+	let restored_negotiations = nodes[0].node.list_pending_channel_negotiations();
+	assert_eq!(restored_negotiations.len(), 1);
+	assert!(restored_negotiations[0].is_restored);
+	assert_eq!(restored_negotiations[0].temporary_channel_id, temporary_channel_id);
+	assert_eq!(num_anchor_channels(&nodes[0]), 1);
+
+	nodes[0].node.timer_tick_occurred();
+	assert_eq!(nodes[0].node.list_pending_channel_negotiations().len(), 1);
+
+	if reconnect {
+		nodes[0].node.peer_connected(node_b_id, &msgs::Init {
+			features: nodes[1].node.init_features(), networks: None, remote_network_address: None
+		}, true).unwrap();
+		nodes[1].node.peer_connected(node_a_id, &msgs::Init {
+			features: nodes[0].node.init_features(), networks: None, remote_network_address: None
+		}, false).unwrap();
+	} else {
+		nodes[0].node.timer_tick_occurred();
+	}
+	assert!(nodes[0].node.list_pending_channel_negotiations().is_empty());
+	assert_eq!(num_anchor_channels(&nodes[0]), 0);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_reload_pending_channel_negotiation() {
+	do_test_reload_pending_channel_negotiation(true);
+	do_test_reload_pending_channel_negotiation(false);
+}
@@ -5,7 +5,7 @@ use bitcoin::hashes::Hash;
 use bitcoin::opcodes::all::{OP_PUSHBYTES_0 as SEGWIT_V0, OP_RETURN};
 use bitcoin::script::{PushBytes, Script, ScriptBuf};
 use bitcoin::secp256k1::PublicKey;
-use bitcoin::{WPubkeyHash, WScriptHash, WitnessProgram};
+use bitcoin::{Amount, TxOut, WPubkeyHash, WScriptHash, WitnessProgram};
 
 use crate::ln::channelmanager;
 use crate::ln::msgs::DecodeError;
@@ -33,6 +33,55 @@ pub struct InvalidShutdownScript {
 	pub script: ScriptBuf,
 }
 
+/// The reason a script does not meet the shutdown script requirements from [BOLT #2] for a given
+/// set of counterparty features, as returned by [`ShutdownScript::validate_for_features`].
+///
+/// [BOLT #2]: https://github.com/lightning/bolts/blob/master/02-peer-protocol.md
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ShutdownScriptError {
+	/// The script is a version 0 witness program which is neither P2WPKH (20 bytes) nor P2WSH
+	/// (32 bytes).
+	InvalidWitnessV0ProgramLength {
+		/// The length of the witness program, in bytes.
+		len: usize,
+	},
+	/// The script is a witness program of a version other than 0, which requires the
+	/// counterparty to support `option_shutdown_anysegwit`.
+	AnySegwitNotSupported {
+		/// The version of the witness program.
+		version: u8,
+	},
+	/// The script is an `OP_RETURN` script, which requires the counterparty to support
+	/// `option_simple_close`.
+	SimpleCloseNotSupported,
+	/// The script is an `OP_RETURN` script which is not followed by a single push of 6 to 80
+	/// bytes.
+	InvalidOpReturnData,
+	/// The script is not of any type allowed as a shutdown script, i.e., P2PKH, P2SH, a witness
+	/// program with a 2 to 40 byte program, or `OP_RETURN`.
+	UnsupportedScriptType,
+}
+
+impl core::fmt::Display for ShutdownScriptError {
+	fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+		match self {
+			Self::InvalidWitnessV0ProgramLength { len } => {
+				write!(f, "version 0 witness programs must be 20 or 32 bytes, not {}", len)
+			},
+			Self::AnySegwitNotSupported { version } => {
+				write!(f, "version {} witness programs require option_shutdown_anysegwit", version)
+			},
+			Self::SimpleCloseNotSupported => {
+				f.write_str("OP_RETURN scripts require option_simple_close")
+			},
+			Self::InvalidOpReturnData => {
+				f.write_str("OP_RETURN scripts must push between 6 and 80 bytes")
+			},
+			Self::UnsupportedScriptType => f.write_str("unsupported script type"),
+		}
+	}
+}
+
 #[derive(Clone, PartialEq, Eq)]
 enum ShutdownScriptImpl {
 	/// [`PublicKey`] used to form a P2WPKH script pubkey. Used to support backward-compatible
@@ -122,9 +171,21 @@ impl ShutdownScript {
 	/// Specifically, checks for compliance with feature `option_shutdown_anysegwit` and/or
 	/// `option_simple_close`.
 	pub fn is_compatible(&self, features: &InitFeatures) -> bool {
+		self.validate_for_features(features).is_ok()
+	}
+
+	/// Checks whether the shutdown script is compatible with the features as defined by BOLT #2,
+	/// like [`Self::is_compatible`], but returns the reason if it is not.
+	///
+	/// To validate a script before constructing a [`ShutdownScript`] from it, e.g., when taken as
+	/// user input, use [`validate_shutdown_script`]. The same rules are applied when the script is
+	/// later used for a channel with a counterparty with the given features.
+	pub fn validate_for_features(
+		&self, features: &InitFeatures,
+	) -> Result<(), ShutdownScriptError> {
 		match &self.0 {
-			ShutdownScriptImpl::Legacy(_) => true,
-			ShutdownScriptImpl::Bolt2(script) => is_bolt2_compliant(script, features),
+			ShutdownScriptImpl::Legacy(_) => Ok(()),
+			ShutdownScriptImpl::Bolt2(script) => validate_shutdown_script(script, features),
 		}
 	}
 }
@@ -132,52 +193,91 @@ impl ShutdownScript {
 /// Check if a given script is compliant with BOLT 2's shutdown script requirements for the given
 /// counterparty features.
 pub(crate) fn is_bolt2_compliant(script: &Script, features: &InitFeatures) -> bool {
+	validate_shutdown_script(script, features).is_ok()
+}
+
+/// Checks whether the given script is compliant with the shutdown script requirements of
+/// [BOLT #2] for a counterparty with the given features, returning the reason if it is not.
+///
+/// This applies the same rules as used when constructing a [`ShutdownScript`] and when
+/// negotiating a cooperative close with the counterparty.
+///
+/// [BOLT #2]: https://github.com/lightning/bolts/blob/master/02-peer-protocol.md
+pub fn validate_shutdown_script(
+	script: &Script, features: &InitFeatures,
+) -> Result<(), ShutdownScriptError> {
 	// BOLT2:
 	// 1. `OP_0` `20` 20-bytes (version 0 pay to witness pubkey hash), OR
 	// 2. `OP_0` `32` 32-bytes (version 0 pay to witness script hash), OR
 	if script.is_p2pkh() || script.is_p2sh() || script.is_p2wpkh() || script.is_p2wsh() {
-		true
-	} else if features.supports_shutdown_anysegwit() && script.is_witness_program() {
+		Ok(())
+	} else if script.is_witness_program() {
+		if script.as_bytes()[0] == SEGWIT_V0.to_u8() {
+			return Err(ShutdownScriptError::InvalidWitnessV0ProgramLength {
+				len: script.len() - 2,
+			});
+		}
 		// 3. if (and only if) `option_shutdown_anysegwit` is negotiated:
 		//    * `OP_1` through `OP_16` inclusive, followed by a single push of 2 to 40 bytes
 		//     (witness program versions 1 through 16)
-		script.as_bytes()[0] != SEGWIT_V0.to_u8()
-	} else if features.supports_simple_close() && script.is_op_return() {
+		if features.supports_shutdown_anysegwit() {
+			Ok(())
+		} else {
+			let version = script.witness_version().map_or(0, |version| version.to_num());
+			Err(ShutdownScriptError::AnySegwitNotSupported { version })
+		}
+	} else if script.is_op_return() {
 		// 4. if (and only if) `option_simple_close` is negotiated:
+		if !features.supports_simple_close() {
+			return Err(ShutdownScriptError::SimpleCloseNotSupported);
+		}
 		let mut instruction_iter = script.instructions();
 		if let Some(Ok(Instruction::Op(opcode))) = instruction_iter.next() {
 			// * `OP_RETURN` followed by one of:
 			if opcode != OP_RETURN {
-				return false;
+				return Err(ShutdownScriptError::InvalidOpReturnData);
 			}
 
 			match instruction_iter.next() {
 				Some(Ok(Instruction::PushBytes(bytes))) => {
 					// * `6` to `75` inclusive followed by exactly that many bytes
-					if (6..=75).contains(&bytes.len()) {
-						return instruction_iter.next().is_none();
-					}
-
+					//
 					// `rust-bitcoin` interprets `OP_PUSHDATA1` as `Instruction::PushBytes`, having
 					// us land here in this case, too.
 					//
 					// * `76` followed by `76` to `80` followed by exactly that many bytes
-					if (76..=80).contains(&bytes.len()) {
-						return instruction_iter.next().is_none();
+					if (6..=80).contains(&bytes.len()) && instruction_iter.next().is_none() {
+						return Ok(());
 					}
 
-					false
+					Err(ShutdownScriptError::InvalidOpReturnData)
 				},
-				_ => false,
+				_ => Err(ShutdownScriptError::InvalidOpReturnData),
 			}
 		} else {
-			false
+			Err(ShutdownScriptError::InvalidOpReturnData)
 		}
 	} else {
-		false
+		Err(ShutdownScriptError::UnsupportedScriptType)
 	}
 }
 
+/// Returns the weight an output paying to the given script adds to a transaction, excluding any
+/// change to the size of the output count.
+///
+/// This is the weight accounted for when [`KeysManager`]'s
+/// [`OutputSpender::spend_spendable_outputs`] (and thus the [`OutputSweeper`]) adds an output
+/// paying to a destination script. Note that such an output is only added if its value is at least
+/// [`Script::minimal_non_dust`]. Any script is otherwise accepted as a destination, though
+/// non-standard scripts will keep the resulting transaction from being relayed.
+///
+/// [`KeysManager`]: crate::sign::KeysManager
+/// [`OutputSpender::spend_spendable_outputs`]: crate::sign::OutputSpender::spend_spendable_outputs
+/// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
+pub fn destination_script_output_weight(script: &Script) -> u64 {
+	TxOut { value: Amount::ZERO, script_pubkey: script.to_owned() }.weight().to_wu()
+}
+
 // Note that this is only for our own shutdown scripts. Counterparties are still allowed to send us
 // non-witness shutdown scripts which this rejects.
 impl TryFrom<ScriptBuf> for ShutdownScript {
@@ -225,7 +325,10 @@ impl core::fmt::Display for ShutdownScript {
 
 #[cfg(test)]
 mod shutdown_script_tests {
-	use super::ShutdownScript;
+	use super::{
+		destination_script_output_weight, validate_shutdown_script, ShutdownScript,
+		ShutdownScriptError,
+	};
 
 	use bitcoin::opcodes;
 	use bitcoin::script::{Builder, PushBytes, ScriptBuf};
@@ -376,4 +479,69 @@ mod shutdown_script_tests {
 		let push_bytes: &PushBytes = big_buffer.try_into().unwrap();
 		assert!(ShutdownScript::new_op_return(&push_bytes).is_err());
 	}
+
+	#[test]
+	fn validates_scripts_for_features() {
+		let pubkey_hash = pubkey().wpubkey_hash().unwrap();
+		let p2wpkh_script = ScriptBuf::new_p2wpkh(&pubkey_hash);
+		assert_eq!(validate_shutdown_script(&p2wpkh_script, &InitFeatures::empty()), Ok(()));
+
+		let witness_program = WitnessProgram::new(WitnessVersion::V1, &[0; 32]).unwrap();
+		let taproot_script = ScriptBuf::new_witness_program(&witness_program);
+		assert_eq!(
+			validate_shutdown_script(&taproot_script, &InitFeatures::empty()),
+			Err(ShutdownScriptError::AnySegwitNotSupported { version: 1 })
+		);
+		assert_eq!(validate_shutdown_script(&taproot_script, &any_segwit_features()), Ok(()));
+		let shutdown_script = ShutdownScript::new_witness_program(&witness_program).unwrap();
+		assert_eq!(
+			shutdown_script.validate_for_features(&InitFeatures::empty()),
+			Err(ShutdownScriptError::AnySegwitNotSupported { version: 1 })
+		);
+		assert_eq!(shutdown_script.validate_for_features(&any_segwit_features()), Ok(()));
+
+		// Version 0 witness programs must be P2WPKH or P2WSH, even with anysegwit.
+		let v0_script = Builder::new().push_int(0).push_slice(&[0; 25]).into_script();
+		assert_eq!(
+			validate_shutdown_script(&v0_script, &any_segwit_features()),
+			Err(ShutdownScriptError::InvalidWitnessV0ProgramLength { len: 25 })
+		);
+
+		// Witness programs must be 2 to 40 bytes long.
+		let long_program_script = Builder::new().push_int(1).push_slice(&[0; 41]).into_script();
+		assert_eq!(
+			validate_shutdown_script(&long_program_script, &any_segwit_features()),
+			Err(ShutdownScriptError::UnsupportedScriptType)
+		);
+
+		let op_return_script = ScriptBuf::new_op_return(&[6; 6]);
+		assert_eq!(
+			validate_shutdown_script(&op_return_script, &InitFeatures::empty()),
+			Err(ShutdownScriptError::SimpleCloseNotSupported)
+		);
+	}
+
+	#[cfg(simple_close)]
+	#[test]
+	fn validates_op_return_data() {
+		let short_op_return_script = ScriptBuf::new_op_return(&[5; 5]);
+		assert_eq!(
+			validate_shutdown_script(&short_op_return_script, &simple_close_features()),
+			Err(ShutdownScriptError::InvalidOpReturnData)
+		);
+		let op_return_script = ScriptBuf::new_op_return(&[6; 6]);
+		assert_eq!(validate_shutdown_script(&op_return_script, &simple_close_features()), Ok(()));
+	}
+
+	#[test]
+	fn computes_destination_script_output_weight() {
+		// 8 bytes of value, 1 byte of script length and the script itself, times 4.
+		let pubkey_hash = pubkey().wpubkey_hash().unwrap();
+		let p2wpkh_script = ScriptBuf::new_p2wpkh(&pubkey_hash);
+		assert_eq!(destination_script_output_weight(&p2wpkh_script), (8 + 1 + 22) * 4);
+
+		let witness_program = WitnessProgram::new(WitnessVersion::V1, &[0; 32]).unwrap();
+		let taproot_script = ScriptBuf::new_witness_program(&witness_program);
+		assert_eq!(destination_script_output_weight(&taproot_script), (8 + 1 + 34) * 4);
+	}
 }
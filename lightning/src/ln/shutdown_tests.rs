@@ -10,11 +10,17 @@
 //! Tests of our shutdown and closing_signed negotiation logic as well as some assorted force-close
 //! handling tests.
 
+use crate::chain::channelmonitor::ANTI_REORG_DELAY;
 use crate::chain::transaction::OutPoint;
 use crate::chain::ChannelMonitorUpdateStatus;
-use crate::events::{ClosureReason, Event, HTLCHandlingFailureType};
+use crate::events::{
+	BatchedChannelCloseOutcome, BatchedChannelCloseResult, ClosureReason, Event,
+	HTLCHandlingFailureType,
+};
 use crate::ln::channel_state::{ChannelDetails, ChannelShutdownState};
-use crate::ln::channelmanager::{self, PaymentId, RecipientOnionFields, Retry};
+use crate::ln::channelmanager::{
+	self, CloseAuthorizer, PaymentId, RecipientOnionFields, Retry, BATCHED_CLOSE_TIMEOUT_TICKS,
+};
 use crate::ln::msgs;
 use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, ErrorAction, MessageSendEvent};
 use crate::ln::onion_utils::LocalHTLCFailureReason;
@@ -22,10 +28,12 @@ use crate::ln::script::ShutdownScript;
 use crate::ln::types::ChannelId;
 use crate::prelude::*;
 use crate::routing::router::{get_route, PaymentParameters, RouteParameters};
-use crate::sign::{EntropySource, SignerProvider};
+use crate::sign::{EntropySource, OutputSpender, SignerProvider, SpendableOutputDescriptor};
 use crate::types::string::UntrustedString;
 use crate::util::config::UserConfig;
 use crate::util::errors::APIError;
+use crate::util::message_signing::{sign_close_authorization, CloseAuthorization};
+use crate::util::ser::Writeable;
 use crate::util::test_utils;
 use crate::util::test_utils::OnGetShutdownScriptpubkey;
 
@@ -34,8 +42,9 @@ use bitcoin::locktime::absolute::LockTime;
 use bitcoin::network::Network;
 use bitcoin::opcodes;
 use bitcoin::script::Builder;
+use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
 use bitcoin::transaction::Version;
-use bitcoin::{Transaction, TxOut, WitnessProgram, WitnessVersion};
+use bitcoin::{FeeRate, Transaction, TxOut, WitnessProgram, WitnessVersion};
 
 use crate::ln::functional_test_utils::*;
 
@@ -1825,3 +1834,432 @@ fn test_force_closure_on_low_stale_fee() {
 	};
 	check_closed_events(&nodes[1], &[ExpectedCloseEvent::from_id_reason(chan_id, false, reason)]);
 }
+
+#[test]
+fn test_close_channels_with_peer() {
+	// Test that closing all channels with a peer as a batch generates a single summary event once
+	// all of them have closed cooperatively.
+	let chanmon_cfgs = create_chanmon_cfgs(3);
+	let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+	let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_id_1 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	let chan_id_2 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	let chan_id_3 = create_announced_chan_between_nodes(&nodes, 0, 2).2;
+
+	// If any of the channels is unknown, shutdown isn't initiated on any of them.
+	let unknown_chan_ids = [ChannelId::from_bytes([42; 32]), chan_id_3];
+	for unknown_chan_id in unknown_chan_ids {
+		let chan_ids = Some(vec![chan_id_1, unknown_chan_id]);
+		let res = nodes[0].node.close_channels_with_peer(&node_b_id, chan_ids, None, None);
+		assert!(matches!(res, Err(APIError::ChannelUnavailable { .. })));
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+	}
+
+	let max_fee_satoshis = 500;
+	nodes[0].node.close_channels_with_peer(&node_b_id, None, None, Some(max_fee_satoshis)).unwrap();
+	let shutdowns = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(shutdowns.len(), 2);
+
+	let mut closed_chan_ids = Vec::new();
+	for shutdown in shutdowns {
+		let shutdown_a = match shutdown {
+			MessageSendEvent::SendShutdown { node_id, msg } => {
+				assert_eq!(node_id, node_b_id);
+				msg
+			},
+			_ => panic!("Unexpected event"),
+		};
+		nodes[1].node.handle_shutdown(node_a_id, &shutdown_a);
+		let shutdown_b = get_event_msg!(nodes[1], MessageSendEvent::SendShutdown, node_a_id);
+		nodes[0].node.handle_shutdown(node_b_id, &shutdown_b);
+
+		// The fee we're willing to pay is capped by the batch's maximum.
+		let closing_signed_a =
+			get_event_msg!(nodes[0], MessageSendEvent::SendClosingSigned, node_b_id);
+		let fee_range = closing_signed_a.fee_range.as_ref().unwrap();
+		assert_eq!(fee_range.max_fee_satoshis, max_fee_satoshis);
+		nodes[1].node.handle_closing_signed(node_a_id, &closing_signed_a);
+		let (_, closing_signed_b) = get_closing_signed_broadcast!(nodes[1].node, node_a_id);
+		nodes[0].node.handle_closing_signed(node_b_id, &closing_signed_b.unwrap());
+		let (_, none_a) = get_closing_signed_broadcast!(nodes[0].node, node_b_id);
+		assert!(none_a.is_none());
+		let reason_b = ClosureReason::CounterpartyInitiatedCooperativeClosure;
+		check_closed_event!(nodes[1], 1, reason_b, [node_a_id], 100000);
+		closed_chan_ids.push(shutdown_a.channel_id);
+
+		// The summary event is only generated after the last channel has closed.
+		let events = nodes[0].node.get_and_clear_pending_events();
+		assert_eq!(events.len(), closed_chan_ids.len());
+		match &events[0] {
+			Event::ChannelClosed { channel_id, reason, .. } => {
+				assert_eq!(*channel_id, shutdown_a.channel_id);
+				assert_eq!(*reason, ClosureReason::LocallyInitiatedCooperativeClosure);
+			},
+			_ => panic!("Unexpected event"),
+		}
+		if closed_chan_ids.len() == 2 {
+			match &events[1] {
+				Event::BatchedChannelCloseCompleted { counterparty_node_id, results } => {
+					assert_eq!(*counterparty_node_id, node_b_id);
+					assert_eq!(results.len(), 2);
+					for result in results {
+						assert!(closed_chan_ids.contains(&result.channel_id));
+						assert_eq!(result.outcome, BatchedChannelCloseOutcome::CooperativelyClosed);
+					}
+				},
+				_ => panic!("Unexpected event"),
+			}
+		}
+	}
+
+	closed_chan_ids.sort();
+	let mut expected_chan_ids = vec![chan_id_1, chan_id_2];
+	expected_chan_ids.sort();
+	assert_eq!(closed_chan_ids, expected_chan_ids);
+}
+
+#[test]
+fn test_close_channels_with_peer_timeout() {
+	// Test that channels of a batched close which don't close in time are force-closed once the
+	// batch times out, unless they still have HTLCs pending.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_id_1 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	let chan_id_2 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	route_payment(&nodes[0], &[&nodes[1]], 100_000);
+	let htlc_chan_id = nodes[0]
+		.node
+		.list_channels()
+		.into_iter()
+		.find(|chan| !chan.pending_outbound_htlcs.is_empty())
+		.unwrap()
+		.channel_id;
+	let stalled_chan_id = if htlc_chan_id == chan_id_1 { chan_id_2 } else { chan_id_1 };
+
+	let chan_ids = Some(vec![chan_id_1, chan_id_2]);
+	nodes[0].node.close_channels_with_peer(&node_b_id, chan_ids, None, None).unwrap();
+	let shutdowns = nodes[0].node.get_and_clear_pending_msg_events();
+	assert_eq!(shutdowns.len(), 2);
+
+	// Only the channel with the pending HTLC makes progress, as we never deliver the shutdown of
+	// the other one.
+	for shutdown in shutdowns {
+		match shutdown {
+			MessageSendEvent::SendShutdown { msg, .. } if msg.channel_id == htlc_chan_id => {
+				nodes[1].node.handle_shutdown(node_a_id, &msg);
+			},
+			MessageSendEvent::SendShutdown { .. } => {},
+			_ => panic!("Unexpected event"),
+		}
+	}
+	let shutdown_b = get_event_msg!(nodes[1], MessageSendEvent::SendShutdown, node_a_id);
+	nodes[0].node.handle_shutdown(node_b_id, &shutdown_b);
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+	for _ in 0..BATCHED_CLOSE_TIMEOUT_TICKS - 1 {
+		nodes[0].node.timer_tick_occurred();
+	}
+	assert!(nodes[0].node.get_and_clear_pending_events().is_empty());
+	// Both channels have been disabled in the meantime.
+	nodes[0].node.get_and_clear_pending_msg_events();
+
+	// Once the batch times out, the stalled channel is force-closed while the other one remains
+	// pending until its HTLC is resolved.
+	nodes[0].node.timer_tick_occurred();
+	check_closed_broadcast!(nodes[0], true);
+	check_added_monitors!(nodes[0], 1);
+
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 2);
+	match &events[0] {
+		Event::ChannelClosed { channel_id, reason, .. } => {
+			assert_eq!(*channel_id, stalled_chan_id);
+			assert!(matches!(reason, ClosureReason::HolderForceClosed { .. }));
+		},
+		_ => panic!("Unexpected event"),
+	}
+	let outcome = |chan_id| {
+		if chan_id == stalled_chan_id {
+			BatchedChannelCloseOutcome::ForceClosed
+		} else {
+			BatchedChannelCloseOutcome::Pending
+		}
+	};
+	match &events[1] {
+		Event::BatchedChannelCloseCompleted { counterparty_node_id, results } => {
+			assert_eq!(*counterparty_node_id, node_b_id);
+			let expected_results = vec![
+				BatchedChannelCloseResult { channel_id: chan_id_1, outcome: outcome(chan_id_1) },
+				BatchedChannelCloseResult { channel_id: chan_id_2, outcome: outcome(chan_id_2) },
+			];
+			assert_eq!(*results, expected_results);
+		},
+		_ => panic!("Unexpected event"),
+	}
+
+	// The batch is resolved, so no further summary is generated.
+	nodes[0].node.timer_tick_occurred();
+	assert!(nodes[0].node.get_and_clear_pending_events().is_empty());
+	assert!(nodes[0].node.list_channels().iter().any(|chan| chan.channel_id == htlc_chan_id));
+}
+
+#[test]
+fn test_bump_closing_transaction_fee() {
+	// Tests that the fee of a cooperative closing transaction can be bumped by having its output
+	// paying to us swept right away, and that this is possible until the closing transaction has
+	// confirmed.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan = create_announced_chan_between_nodes(&nodes, 0, 1);
+	let chan_id = chan.2;
+
+	let res = nodes[0].node.bump_closing_transaction_fee(&chan_id, &node_b_id, 100_000);
+	assert!(matches!(res, Err(APIError::ChannelUnavailable { .. })));
+
+	let closing_tx = close_channel(&nodes[0], &nodes[1], &chan_id, chan.3, false).2;
+	let reason_a = ClosureReason::LocallyInitiatedCooperativeClosure;
+	check_closed_event!(nodes[0], 1, reason_a, [node_b_id], 100000);
+	let reason_b = ClosureReason::CounterpartyInitiatedCooperativeClosure;
+	check_closed_event!(nodes[1], 1, reason_b, [node_a_id], 100000);
+
+	// The closing transaction already pays more than 1 sat/kW, and nodes[1] only has a dust
+	// balance and thus no output on it to bump its fee with.
+	let res = nodes[0].node.bump_closing_transaction_fee(&chan_id, &node_b_id, 1);
+	assert!(matches!(res, Err(APIError::APIMisuseError { .. })));
+	let res = nodes[1].node.bump_closing_transaction_fee(&chan_id, &node_a_id, 100_000);
+	assert!(matches!(res, Err(APIError::APIMisuseError { .. })));
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+
+	nodes[0].node.bump_closing_transaction_fee(&chan_id, &node_b_id, 100_000).unwrap();
+	assert_eq!(nodes[0].tx_broadcaster.txn_broadcast(), vec![closing_tx.clone()]);
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	let outputs = match &events[0] {
+		Event::SpendableOutputs { outputs, channel_id } => {
+			assert_eq!(*channel_id, Some(chan_id));
+			outputs.clone()
+		},
+		_ => panic!("Unexpected event"),
+	};
+	assert_eq!(outputs.len(), 1);
+	assert!(matches!(outputs[0], SpendableOutputDescriptor::StaticOutput { .. }));
+	let spend_tx = nodes[0]
+		.keys_manager
+		.backing
+		.spend_spendable_outputs(
+			&[&outputs[0]],
+			Vec::new(),
+			Builder::new().push_opcode(opcodes::all::OP_RETURN).into_script(),
+			253,
+			None,
+			&Secp256k1::new(),
+		)
+		.unwrap();
+	check_spends!(spend_tx, closing_tx);
+
+	// Once the closing transaction confirms, its fee can no longer be bumped. Once it's
+	// irrevocably confirmed, the ChannelMonitor hands us the same output, which an
+	// `OutputSweeper` would ignore as it's already tracking it.
+	mine_transaction(&nodes[0], &closing_tx);
+	let res = nodes[0].node.bump_closing_transaction_fee(&chan_id, &node_b_id, 100_000);
+	assert!(matches!(res, Err(APIError::APIMisuseError { .. })));
+
+	connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+	let res = nodes[0].node.bump_closing_transaction_fee(&chan_id, &node_b_id, 100_000);
+	assert!(matches!(res, Err(APIError::ChannelUnavailable { .. })));
+	let events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match &events[0] {
+		Event::SpendableOutputs { outputs: monitor_outputs, .. } => {
+			assert_eq!(*monitor_outputs, outputs);
+		},
+		_ => panic!("Unexpected event"),
+	}
+}
+
+#[test]
+fn test_estimate_cooperative_close_fee() {
+	// The estimated cooperative closing transaction matches the one we negotiate, omitting outputs
+	// which would be dust.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+	let (_, _, chan_id, funding_tx) = create_announced_chan_between_nodes(&nodes, 0, 1);
+	let feerate = FeeRate::from_sat_per_kwu(253);
+
+	// Our counterparty's balance is initially dust, so its output would be omitted.
+	let estimate = nodes[0].node.estimate_cooperative_close_fee(&chan_id, &node_b_id, feerate);
+	let estimate = estimate.unwrap();
+	assert!(estimate.includes_holder_output);
+	assert!(!estimate.includes_counterparty_output);
+	assert_eq!(estimate.fee, feerate.fee_wu(estimate.weight).unwrap());
+	let counterparty_estimate =
+		nodes[1].node.estimate_cooperative_close_fee(&chan_id, &node_a_id, feerate).unwrap();
+	assert!(!counterparty_estimate.includes_holder_output);
+	assert!(counterparty_estimate.includes_counterparty_output);
+	assert_eq!(counterparty_estimate.weight, estimate.weight);
+
+	send_payment(&nodes[0], &[&nodes[1]], 5_000_000);
+	let estimate =
+		nodes[0].node.estimate_cooperative_close_fee(&chan_id, &node_b_id, feerate).unwrap();
+	assert!(estimate.includes_holder_output);
+	assert!(estimate.includes_counterparty_output);
+
+	let res =
+		nodes[0].node.estimate_cooperative_close_fee(&ChannelId([42; 32]), &node_b_id, feerate);
+	assert!(matches!(res, Err(APIError::ChannelUnavailable { .. })));
+
+	// The estimate only differs from the negotiated closing transaction by the signature lengths
+	// and by rounding the fee up.
+	let (_, _, closing_tx) =
+		close_channel(&nodes[0], &nodes[1], &chan_id, funding_tx.clone(), false);
+	assert_eq!(closing_tx.output.len(), 2);
+	assert!(closing_tx.weight() <= estimate.weight);
+	assert!(estimate.weight - closing_tx.weight() <= bitcoin::Weight::from_wu(4));
+	let closing_fee = funding_tx.output[0].value
+		- closing_tx.output.iter().map(|output| output.value).sum::<Amount>();
+	assert!(closing_fee <= estimate.fee);
+	assert!(estimate.fee - closing_fee <= Amount::ONE_SAT);
+}
+
+#[test]
+fn test_close_channel_authorization() {
+	// Test that cooperatively closing channels above the authorizer's threshold requires a signed
+	// authorization, whose nonce can't be replayed, even after a restart.
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let persister;
+	let new_chain_monitor;
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes_0_deserialized;
+	let mut nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_a_id = nodes[0].node.get_our_node_id();
+	let node_b_id = nodes[1].node.get_our_node_id();
+
+	let chan_id_1 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+	let chan_id_2 = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+
+	let secp_ctx = Secp256k1::new();
+	let authorizer_sk = SecretKey::from_slice(&[42; 32]).unwrap();
+	let other_sk = SecretKey::from_slice(&[43; 32]).unwrap();
+	let authorizer = CloseAuthorizer {
+		authorizer_pubkey: PublicKey::from_secret_key(&secp_ctx, &authorizer_sk),
+		threshold_satoshis: 10_000,
+	};
+	nodes[0].node.set_close_authorizer(Some(authorizer));
+
+	// Without an authorization, neither a single channel nor a batch of channels may be closed.
+	let res = nodes[0].node.close_channel(&chan_id_1, &node_b_id);
+	assert!(matches!(res, Err(APIError::AuthorizationRequired { .. })));
+	let res = nodes[0].node.close_channels_with_peer(&node_b_id, None, None, None);
+	assert!(matches!(res, Err(APIError::AuthorizationRequired { .. })));
+
+	// Authorizations which aren't signed by the authorizer or are for another channel are rejected.
+	let max_fee_satoshis = 1_000;
+	let authorization = CloseAuthorization { channel_id: chan_id_1, nonce: 5, max_fee_satoshis };
+	let signature = sign_close_authorization(&authorization, &other_sk);
+	let res = nodes[0].node.close_channel_with_authorization(
+		&chan_id_1,
+		&node_b_id,
+		None,
+		None,
+		&authorization,
+		&signature,
+	);
+	assert!(matches!(res, Err(APIError::AuthorizationRequired { .. })));
+	let signature = sign_close_authorization(&authorization, &authorizer_sk);
+	let res = nodes[0].node.close_channel_with_authorization(
+		&chan_id_2,
+		&node_b_id,
+		None,
+		None,
+		&authorization,
+		&signature,
+	);
+	assert!(matches!(res, Err(APIError::AuthorizationRequired { .. })));
+	assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+	// A valid authorization initiates the close, capping the fee we're willing to pay.
+	nodes[0]
+		.node
+		.close_channel_with_authorization(
+			&chan_id_1,
+			&node_b_id,
+			None,
+			None,
+			&authorization,
+			&signature,
+		)
+		.unwrap();
+	let shutdown_a = get_event_msg!(nodes[0], MessageSendEvent::SendShutdown, node_b_id);
+	nodes[1].node.handle_shutdown(node_a_id, &shutdown_a);
+	let shutdown_b = get_event_msg!(nodes[1], MessageSendEvent::SendShutdown, node_a_id);
+	nodes[0].node.handle_shutdown(node_b_id, &shutdown_b);
+	let closing_signed_a = get_event_msg!(nodes[0], MessageSendEvent::SendClosingSigned, node_b_id);
+	assert_eq!(closing_signed_a.fee_range.as_ref().unwrap().max_fee_satoshis, max_fee_satoshis);
+	nodes[1].node.handle_closing_signed(node_a_id, &closing_signed_a);
+	let (_, closing_signed_b) = get_closing_signed_broadcast!(nodes[1].node, node_a_id);
+	nodes[0].node.handle_closing_signed(node_b_id, &closing_signed_b.unwrap());
+	let (_, none_a) = get_closing_signed_broadcast!(nodes[0].node, node_b_id);
+	assert!(none_a.is_none());
+	let reason_a = ClosureReason::LocallyInitiatedCooperativeClosure;
+	check_closed_event!(nodes[0], 1, reason_a, [node_b_id], 100000);
+	let reason_b = ClosureReason::CounterpartyInitiatedCooperativeClosure;
+	check_closed_event!(nodes[1], 1, reason_b, [node_a_id], 100000);
+
+	// The authorization's nonce is used up, also across restarts.
+	let replayed = CloseAuthorization { channel_id: chan_id_2, nonce: 5, max_fee_satoshis };
+	let signature = sign_close_authorization(&replayed, &authorizer_sk);
+	let res = nodes[0].node.close_channel_with_authorization(
+		&chan_id_2, &node_b_id, None, None, &replayed, &signature,
+	);
+	assert!(matches!(res, Err(APIError::AuthorizationRequired { .. })));
+
+	nodes[1].node.peer_disconnected(node_a_id);
+	let mon_1_ser = get_monitor!(nodes[0], chan_id_1).encode();
+	let mon_2_ser = get_monitor!(nodes[0], chan_id_2).encode();
+	let node_ser = nodes[0].node.encode();
+	let mons = &[&mon_1_ser[..], &mon_2_ser[..]];
+	reload_node!(nodes[0], &node_ser, mons, persister, new_chain_monitor, nodes_0_deserialized);
+	reconnect_nodes(ReconnectArgs::new(&nodes[0], &nodes[1]));
+
+	// The authorizer isn't persisted, so it needs to be set again.
+	nodes[0].node.set_close_authorizer(Some(authorizer));
+	let res = nodes[0].node.close_channel_with_authorization(
+		&chan_id_2, &node_b_id, None, None, &replayed, &signature,
+	);
+	assert!(matches!(res, Err(APIError::AuthorizationRequired { .. })));
+
+	let authorization = CloseAuthorization { channel_id: chan_id_2, nonce: 6, max_fee_satoshis };
+	let signature = sign_close_authorization(&authorization, &authorizer_sk);
+	nodes[0]
+		.node
+		.close_channel_with_authorization(
+			&chan_id_2,
+			&node_b_id,
+			None,
+			None,
+			&authorization,
+			&signature,
+		)
+		.unwrap();
+	let shutdown_a = get_event_msg!(nodes[0], MessageSendEvent::SendShutdown, node_b_id);
+	assert_eq!(shutdown_a.channel_id, chan_id_2);
+}
@@ -7,8 +7,10 @@
 // You may not use this file except in accordance with one or both of these
 // licenses.
 
+use crate::events::{Event, FundingInfo};
 use crate::ln::functional_test_utils::*;
 use crate::ln::msgs::{BaseMessageHandler, ChannelMessageHandler, MessageSendEvent};
+use crate::util::config::UserConfig;
 use crate::util::errors::APIError;
 
 /// Splicing test, simple splice-in flow. Starts with opening a V1 channel first.
@@ -334,3 +336,175 @@ fn test_v1_splice_in_negative_insufficient_inputs() {
 		_ => panic!("Wrong error {:?}", res.err().unwrap()),
 	}
 }
+
+fn do_test_splice_negotiation_timeout(initiator_times_out: bool) {
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_id_0 = nodes[0].node.get_our_node_id();
+	let node_id_1 = nodes[1].node.get_our_node_id();
+
+	let (_, _, channel_id, _) =
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+
+	let splice_in_sats = 20_000;
+	let extra_splice_funding_input_sats = 35_000;
+	let funding_feerate_per_kw = 1024;
+	let funding_inputs =
+		create_dual_funding_utxos_with_prev_txs(&nodes[0], &[extra_splice_funding_input_sats]);
+	let funding_input_outpoint = funding_inputs[0].0.previous_output;
+
+	nodes[0]
+		.node
+		.splice_channel(
+			&channel_id,
+			&node_id_1,
+			splice_in_sats as i64,
+			funding_inputs,
+			None, // change_script
+			funding_feerate_per_kw,
+			None, // locktime
+		)
+		.unwrap();
+	let splice_init_msg = get_event_msg!(nodes[0], MessageSendEvent::SendSpliceInit, node_id_1);
+	nodes[1].node.handle_splice_init(node_id_0, &splice_init_msg);
+	let splice_ack_msg = get_event_msg!(nodes[1], MessageSendEvent::SendSpliceAck, node_id_0);
+	nodes[0].node.handle_splice_ack(node_id_1, &splice_ack_msg);
+
+	// The initiator adds its first input, which the acceptor acknowledges with `tx_complete`.
+	let tx_add_input_msg = get_event_msg!(nodes[0], MessageSendEvent::SendTxAddInput, node_id_1);
+	nodes[1].node.handle_tx_add_input(node_id_0, &tx_add_input_msg);
+	let tx_complete_msg = get_event_msg!(nodes[1], MessageSendEvent::SendTxComplete, node_id_0);
+
+	let initiator_progress =
+		nodes[0].node.interactive_tx_negotiation_progress(&channel_id, &node_id_1).unwrap();
+	assert!(initiator_progress.is_initiator);
+	assert_eq!(initiator_progress.holder_inputs_added, 1);
+	assert_eq!(initiator_progress.holder_inputs_pending, 1);
+	assert_eq!(initiator_progress.counterparty_inputs_added, 0);
+	assert_eq!(
+		initiator_progress.holder_contributed_input_satoshis,
+		extra_splice_funding_input_sats
+	);
+	assert_eq!(initiator_progress.feerate_sat_per_1000_weight, funding_feerate_per_kw);
+	assert_eq!(initiator_progress.ticks_since_counterparty_message, 0);
+
+	let acceptor_progress =
+		nodes[1].node.interactive_tx_negotiation_progress(&channel_id, &node_id_0).unwrap();
+	assert!(!acceptor_progress.is_initiator);
+	assert_eq!(acceptor_progress.holder_inputs_added, 0);
+	assert_eq!(acceptor_progress.counterparty_inputs_added, 1);
+	assert_eq!(acceptor_progress.holder_contributed_input_satoshis, 0);
+
+	// Only one side stalls: the timing out node never receives the other's next message.
+	let (timing_out_idx, other_idx) = if initiator_times_out { (0, 1) } else { (1, 0) };
+	let timing_out_node_id = nodes[timing_out_idx].node.get_our_node_id();
+	let other_node_id = nodes[other_idx].node.get_our_node_id();
+	if !initiator_times_out {
+		// Deliver the acceptor's `tx_complete` so that it awaits the initiator's next message.
+		nodes[0].node.handle_tx_complete(node_id_1, &tx_complete_msg);
+		let _ = get_event_msg!(nodes[0], MessageSendEvent::SendTxAddInput, node_id_1);
+	}
+
+	let timeout_ticks = UserConfig::default().interactive_tx_negotiation_timeout_ticks;
+	for _ in 0..timeout_ticks - 1 {
+		nodes[timing_out_idx].node.timer_tick_occurred();
+	}
+	assert!(nodes[timing_out_idx].node.get_and_clear_pending_msg_events().is_empty());
+	let progress = nodes[timing_out_idx]
+		.node
+		.interactive_tx_negotiation_progress(&channel_id, &other_node_id)
+		.unwrap();
+	assert_eq!(progress.ticks_since_counterparty_message, timeout_ticks - 1);
+
+	nodes[timing_out_idx].node.timer_tick_occurred();
+	let tx_abort_msg =
+		get_event_msg!(nodes[timing_out_idx], MessageSendEvent::SendTxAbort, other_node_id);
+	assert!(nodes[timing_out_idx]
+		.node
+		.interactive_tx_negotiation_progress(&channel_id, &other_node_id)
+		.is_none());
+
+	// The other node echoes back the `tx_abort`, which the timing out node does not respond to.
+	nodes[other_idx].node.handle_tx_abort(timing_out_node_id, &tx_abort_msg);
+	let tx_abort_echo_msg =
+		get_event_msg!(nodes[other_idx], MessageSendEvent::SendTxAbort, timing_out_node_id);
+	assert!(nodes[other_idx]
+		.node
+		.interactive_tx_negotiation_progress(&channel_id, &timing_out_node_id)
+		.is_none());
+	nodes[timing_out_idx].node.handle_tx_abort(other_node_id, &tx_abort_echo_msg);
+	assert!(nodes[timing_out_idx].node.get_and_clear_pending_msg_events().is_empty());
+
+	// Only the initiator contributed inputs, which it may now spend elsewhere.
+	assert!(nodes[1].node.get_and_clear_pending_events().is_empty());
+	let events = nodes[0].node.get_and_clear_pending_events();
+	assert_eq!(events.len(), 1);
+	match &events[0] {
+		Event::DiscardFunding {
+			channel_id: discarded_channel_id,
+			funding_info: FundingInfo::Contribution { inputs, .. },
+		} => {
+			assert_eq!(*discarded_channel_id, channel_id);
+			assert_eq!(*inputs, vec![funding_input_outpoint]);
+		},
+		_ => panic!("Unexpected event {:?}", events[0]),
+	}
+
+	// The channel remains usable.
+	assert!(nodes[0].node.list_channels()[0].is_usable);
+	send_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+}
+
+#[test]
+fn test_splice_negotiation_timeout() {
+	do_test_splice_negotiation_timeout(true);
+	do_test_splice_negotiation_timeout(false);
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn test_anchor_channel_reserve_counts_during_splice() {
+	// A channel with a splice in progress has both its current and its pending funding outpoint,
+	// but must only be counted once towards the anchor channel reserve.
+	use crate::util::anchor_channel_reserves::{
+		AnchorChannelReserveCheck, AnchorChannelReserveContext,
+	};
+
+	let chanmon_cfgs = create_chanmon_cfgs(2);
+	let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+	let mut anchors_cfg = test_default_channel_config();
+	anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+	anchors_cfg.manually_accept_inbound_channels = true;
+	let node_chanmgrs =
+		create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+	let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+	let node_id_0 = nodes[0].node.get_our_node_id();
+	let node_id_1 = nodes[1].node.get_our_node_id();
+
+	let (_, _, channel_id, _) =
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 100_000, 0);
+
+	let context = AnchorChannelReserveContext::default();
+	let num_anchor_channels = |node: &Node| {
+		let chain_monitor = &node.chain_monitor.chain_monitor;
+		AnchorChannelReserveCheck::new(&context, &[], node.node, chain_monitor)
+			.num_anchor_channels()
+	};
+	assert_eq!(num_anchor_channels(&nodes[0]), 1);
+	assert_eq!(num_anchor_channels(&nodes[1]), 1);
+
+	let funding_inputs = create_dual_funding_utxos_with_prev_txs(&nodes[0], &[35_000]);
+	nodes[0]
+		.node
+		.splice_channel(&channel_id, &node_id_1, 20_000, funding_inputs, None, 1024, None)
+		.unwrap();
+	let splice_init = get_event_msg!(nodes[0], MessageSendEvent::SendSpliceInit, node_id_1);
+	nodes[1].node.handle_splice_init(node_id_0, &splice_init);
+	let splice_ack = get_event_msg!(nodes[1], MessageSendEvent::SendSpliceAck, node_id_0);
+	nodes[0].node.handle_splice_ack(node_id_1, &splice_ack);
+
+	assert_eq!(num_anchor_channels(&nodes[0]), 1);
+	assert_eq!(num_anchor_channels(&nodes[1]), 1);
+}
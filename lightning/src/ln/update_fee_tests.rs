@@ -1088,7 +1088,7 @@ pub fn do_cannot_afford_on_holding_cell_release(
 
 	// Put the update fee into the holding cell of node 0
 
-	nodes[0].node.maybe_update_chan_fees();
+	nodes[0].node.process_stale_feerate_updates();
 
 	// While the update_fee is in the holding cell, add an inbound HTLC
 
@@ -0,0 +1,236 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Utilities for checking the BOLT 12 encoding of offers against other implementations.
+//!
+//! [`encode_offer`] produces the canonical encoding of an [`Offer`], while [`dump_offer`] produces
+//! a normalized, line-based dump of an encoded offer suitable for diffing against the output of
+//! other implementations.
+
+use core::fmt::Write;
+
+use bitcoin::hex::DisplayHex;
+
+use crate::offers::merkle::TlvStream;
+use crate::offers::offer::{Amount, Offer, Quantity};
+use crate::offers::parse::Bolt12ParseError;
+
+use crate::prelude::*;
+
+/// Returns the canonical BOLT 12 encoding of the given offer.
+///
+/// An [`Offer`] may be described using an [`OfferBuilder`].
+///
+/// [`OfferBuilder`]: crate::offers::offer::OfferBuilder
+pub fn encode_offer(offer: &Offer) -> String {
+	offer.to_string()
+}
+
+/// Parses the given BOLT 12 offer and returns a normalized dump of its structure.
+///
+/// The dump consists of `key=value` lines giving the semantics of the offer as interpreted by LDK:
+/// - `chains` as the comma-separated list of chain hashes, including the implied bitcoin chain,
+/// - `metadata`, `description` and `issuer` as lowercase hex, or `none` if not set,
+/// - `amount` as `none`, `bitcoin:amount_msats`, or `currency:iso4217_code:amount`,
+/// - `features` as the comma-separated list of set feature bits in ascending order,
+/// - `absolute_expiry` as seconds since the Unix epoch, or `none` if not set,
+/// - `paths` as the number of blinded paths,
+/// - `supported_quantity` as `one`, `unbounded`, or `bounded:max`,
+/// - `issuer_signing_pubkey` as lowercase hex, or `none` if not set.
+///
+/// These are followed by one `record` line per TLV record in encoding order giving its type,
+/// length and value in lowercase hex, and finally whether the input used the `canonical`
+/// encoding. Since the records cover every byte of the offer, two implementations producing the
+/// same dump for an offer agree on both its encoding and its semantics.
+pub fn dump_offer(encoded: &str) -> Result<String, Bolt12ParseError> {
+	let offer = encoded.parse::<Offer>()?;
+
+	let mut dump = String::new();
+	let chains = offer
+		.chains()
+		.iter()
+		.map(|chain| chain.as_bytes().as_hex().to_string())
+		.collect::<Vec<_>>();
+	writeln!(dump, "chains={}", chains.join(",")).unwrap();
+	match offer.metadata() {
+		Some(metadata) => writeln!(dump, "metadata={}", metadata.as_hex()).unwrap(),
+		None => writeln!(dump, "metadata=none").unwrap(),
+	}
+	match offer.amount() {
+		None => writeln!(dump, "amount=none").unwrap(),
+		Some(Amount::Bitcoin { amount_msats }) => {
+			writeln!(dump, "amount=bitcoin:{}", amount_msats).unwrap()
+		},
+		Some(Amount::Currency { iso4217_code, amount }) => {
+			writeln!(dump, "amount=currency:{}:{}", iso4217_code, amount).unwrap()
+		},
+	}
+	match offer.description() {
+		Some(description) => {
+			writeln!(dump, "description={}", description.0.as_bytes().as_hex()).unwrap()
+		},
+		None => writeln!(dump, "description=none").unwrap(),
+	}
+	let features = offer
+		.offer_features()
+		.le_flags()
+		.iter()
+		.enumerate()
+		.flat_map(|(idx, byte)| {
+			(0..8).filter(move |bit| byte & (1 << bit) != 0).map(move |bit| idx * 8 + bit)
+		})
+		.map(|bit| bit.to_string())
+		.collect::<Vec<_>>();
+	writeln!(dump, "features={}", features.join(",")).unwrap();
+	match offer.absolute_expiry() {
+		Some(expiry) => writeln!(dump, "absolute_expiry={}", expiry.as_secs()).unwrap(),
+		None => writeln!(dump, "absolute_expiry=none").unwrap(),
+	}
+	writeln!(dump, "paths={}", offer.paths().len()).unwrap();
+	match offer.issuer() {
+		Some(issuer) => writeln!(dump, "issuer={}", issuer.0.as_bytes().as_hex()).unwrap(),
+		None => writeln!(dump, "issuer=none").unwrap(),
+	}
+	match offer.supported_quantity() {
+		Quantity::One => writeln!(dump, "supported_quantity=one").unwrap(),
+		Quantity::Unbounded => writeln!(dump, "supported_quantity=unbounded").unwrap(),
+		Quantity::Bounded(max) => writeln!(dump, "supported_quantity=bounded:{}", max).unwrap(),
+	}
+	match offer.issuer_signing_pubkey() {
+		Some(pubkey) => writeln!(dump, "issuer_signing_pubkey={}", pubkey).unwrap(),
+		None => writeln!(dump, "issuer_signing_pubkey=none").unwrap(),
+	}
+
+	for record in TlvStream::new(offer.as_ref()) {
+		writeln!(
+			dump,
+			"record={} len={} value={}",
+			record.r#type,
+			record.value_bytes.len(),
+			record.value_bytes.as_hex()
+		)
+		.unwrap();
+	}
+	writeln!(dump, "canonical={}", offer.to_string() == encoded.to_lowercase()).unwrap();
+
+	Ok(dump)
+}
+
+#[cfg(all(test, not(c_bindings)))]
+mod tests {
+	use super::{dump_offer, encode_offer};
+
+	use crate::ln::msgs::MAX_VALUE_MSAT;
+	use crate::offers::offer::{Amount, CurrencyCode, Offer, OfferBuilder, Quantity};
+	use crate::offers::test_utils::*;
+	use bitcoin::network::Network;
+	use core::num::NonZeroU64;
+	use core::time::Duration;
+
+	use crate::prelude::*;
+
+	/// Returns the golden vectors as tuples of an offer's structured description, its canonical
+	/// encoding and its normalized dump.
+	fn get_golden_vectors() -> Vec<(Offer, &'static str, &'static str)> {
+		vec![
+			// An amount in a currency other than bitcoin with a non-ASCII issuer.
+			(
+				OfferBuilder::new(pubkey(42))
+					.amount(Amount::Currency {
+						iso4217_code: CurrencyCode::new(*b"USD").unwrap(),
+						amount: 1000,
+					})
+					.description("Coffee".to_string())
+					.issuer("Satoshi's Café".to_string())
+					.supported_quantity(Quantity::Unbounded)
+					.build_unchecked(),
+				"lno1qcp4256ypqpq86q2qepk7enxv4j3yr6nv96x7umgdynhxgzrv9nv82g5qqtzzq6muh550qsfva9fdes0ruph7ctk2s8aqq06r4jxj3msc448wzwy9s",
+				concat!(
+					"chains=6fe28c0ab6f1b372c1a6a246ae63f74f931e8365e15a089c68d6190000000000\n",
+					"metadata=none\n",
+					"amount=currency:USD:1000\n",
+					"description=436f66666565\n",
+					"features=\n",
+					"absolute_expiry=none\n",
+					"paths=0\n",
+					"issuer=5361746f736869277320436166c3a9\n",
+					"supported_quantity=unbounded\n",
+					"issuer_signing_pubkey=035be5e9478209674a96e60f1f037f6176540fd001fa1d64694770c56a7709c42c\n",
+					"record=6 len=3 value=555344\n",
+					"record=8 len=2 value=03e8\n",
+					"record=10 len=6 value=436f66666565\n",
+					"record=18 len=15 value=5361746f736869277320436166c3a9\n",
+					"record=20 len=0 value=\n",
+					"record=22 len=33 value=035be5e9478209674a96e60f1f037f6176540fd001fa1d64694770c56a7709c42c\n",
+					"canonical=true\n",
+				),
+			),
+			// The maximum bitcoin amount on another chain with metadata, an expiry and a bounded
+			// quantity.
+			(
+				OfferBuilder::new(pubkey(42))
+					.chain(Network::Testnet)
+					.metadata(vec![42; 16])
+					.unwrap()
+					.amount_msats(MAX_VALUE_MSAT)
+					.description("Max".to_string())
+					.absolute_expiry(Duration::from_secs(1_700_000_000))
+					.supported_quantity(Quantity::Bounded(NonZeroU64::new(10).unwrap()))
+					.build()
+					.unwrap(),
+				"lno1qgsyxjtl6luzd9t3pr62xr7eemp6awnejusgf6gw45q75vcfqqqqqqqyzq4z52329g4z52329g4z52329g4qszqayjedltzjqqqq5q6dv9uqupr920csq9qppgtzzq6muh550qsfva9fdes0ruph7ctk2s8aqq06r4jxj3msc448wzwy9s",
+				concat!(
+					"chains=43497fd7f826957108f4a30fd9cec3aeba79972084e90ead01ea330900000000\n",
+					"metadata=2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\n",
+					"amount=bitcoin:2100000000000000000\n",
+					"description=4d6178\n",
+					"features=\n",
+					"absolute_expiry=1700000000\n",
+					"paths=0\n",
+					"issuer=none\n",
+					"supported_quantity=bounded:10\n",
+					"issuer_signing_pubkey=035be5e9478209674a96e60f1f037f6176540fd001fa1d64694770c56a7709c42c\n",
+					"record=2 len=32 value=43497fd7f826957108f4a30fd9cec3aeba79972084e90ead01ea330900000000\n",
+					"record=4 len=16 value=2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a2a\n",
+					"record=8 len=8 value=1d24b2dfac520000\n",
+					"record=10 len=3 value=4d6178\n",
+					"record=14 len=4 value=6553f100\n",
+					"record=20 len=1 value=0a\n",
+					"record=22 len=33 value=035be5e9478209674a96e60f1f037f6176540fd001fa1d64694770c56a7709c42c\n",
+					"canonical=true\n",
+				),
+			),
+		]
+	}
+
+	#[test]
+	fn encodes_golden_vectors() {
+		for (offer, encoded, _) in get_golden_vectors() {
+			assert_eq!(encode_offer(&offer), encoded);
+		}
+	}
+
+	#[test]
+	fn dumps_golden_vectors() {
+		for (offer, encoded, dump) in get_golden_vectors() {
+			assert_eq!(dump_offer(encoded).unwrap(), dump);
+			assert_eq!(encoded.parse::<Offer>().unwrap(), offer);
+		}
+	}
+
+	#[test]
+	fn dumps_non_canonical_encoding() {
+		for (_, encoded, dump) in get_golden_vectors() {
+			let (head, tail) = encoded.split_at(encoded.len() / 2);
+			let continued = format!("{}+\n{}", head.to_uppercase(), tail.to_uppercase());
+			let expected = dump.replace("canonical=true", "canonical=false");
+			assert_eq!(dump_offer(&continued).unwrap(), expected);
+		}
+	}
+}
@@ -238,6 +238,7 @@ impl<'a> TlvStream<'a> {
 pub(super) struct TlvRecord<'a> {
 	pub(super) r#type: u64,
 	type_bytes: &'a [u8],
+	pub(super) value_bytes: &'a [u8],
 	// The entire TLV record.
 	pub(super) record_bytes: &'a [u8],
 	pub(super) end: usize,
@@ -258,12 +259,12 @@ impl<'a> Iterator for TlvStream<'a> {
 			let offset = self.data.position();
 			let end = offset + length;
 
-			let _value = &self.data.get_ref()[offset as usize..end as usize];
+			let value_bytes = &self.data.get_ref()[offset as usize..end as usize];
 			let record_bytes = &self.data.get_ref()[start as usize..end as usize];
 
 			self.data.set_position(end);
 
-			Some(TlvRecord { r#type, type_bytes, record_bytes, end: end as usize })
+			Some(TlvRecord { r#type, type_bytes, value_bytes, record_bytes, end: end as usize })
 		} else {
 			None
 		}
@@ -21,6 +21,7 @@ use bitcoin::hashes::sha256d::Hash as Sha256dHash;
 use bitcoin::hashes::Hash;
 use bitcoin::network::Network;
 
+use crate::events::{Event, EventHandler, EventsProvider, ReplayEvent};
 use crate::ln::msgs;
 use crate::ln::msgs::{
 	BaseMessageHandler, ChannelAnnouncement, ChannelUpdate, GossipTimestampFilter, NodeAnnouncement,
@@ -37,6 +38,9 @@ use crate::ln::types::ChannelId;
 use crate::routing::utxo::{self, UtxoLookup, UtxoResolver};
 use crate::types::features::{ChannelFeatures, InitFeatures, NodeFeatures};
 use crate::types::string::PrintableString;
+use crate::util::framed_ser::{
+	FramedDataDamage, FramedReader, FramedType, FramedWriter, LossyReadReport,
+};
 use crate::util::indexed_map::{Entry as IndexedMapEntry, IndexedMap};
 use crate::util::logger::{Level, Logger};
 use crate::util::scid_utils::{block_from_scid, scid_from_parts, MAX_SCID_BLOCK};
@@ -215,6 +219,13 @@ where
 	removed_nodes: Mutex<HashMap<NodeId, Option<u64>>>,
 	/// Announcement messages which are awaiting an on-chain lookup to be processed.
 	pub(super) pending_checks: utxo::PendingChecks,
+	/// Nodes whose `node_announcement` address changes generate an
+	/// [`Event::ChannelPartnerAnnouncementChanged`], each mapped to the timestamp of the last
+	/// announcement an event was generated for.
+	///
+	/// This is a leaf lock which may be taken while holding `nodes`.
+	watched_nodes: Mutex<HashMap<NodeId, Option<u32>>>,
+	pending_events: Mutex<Vec<Event>>,
 }
 
 /// A read-only view of [`NetworkGraph`].
@@ -314,6 +325,92 @@ impl MaybeReadable for NetworkUpdate {
 	}
 }
 
+/// Counts of the gossip messages received from a peer since it connected, as returned by
+/// [`P2PGossipSync::peer_gossip_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PeerGossipStats {
+	/// The number of gossip messages which updated our [`NetworkGraph`] with new information.
+	pub accepted_messages: u64,
+	/// The number of gossip messages which carried no new information, i.e., which we already had
+	/// or which only bumped the timestamp of a `channel_update` we already had.
+	pub redundant_messages: u64,
+	/// The number of gossip messages which failed validation.
+	pub invalid_messages: u64,
+	/// The number of gossip messages which were dropped unprocessed as the peer was deprioritized
+	/// and had exceeded [`GossipSpamPolicy::deprioritized_messages_per_tick`].
+	pub rate_limited_messages: u64,
+	/// The total serialized length of all gossip messages received, in bytes.
+	pub bytes_received: u64,
+	/// Whether the peer is currently deprioritized per the [`GossipSpamPolicy`].
+	pub deprioritized: bool,
+}
+
+/// A policy for deprioritizing peers which send us excessive redundant or invalid gossip, set via
+/// [`P2PGossipSync::set_gossip_spam_policy`].
+///
+/// Once a peer has sent more than [`Self::max_redundant_messages`] redundant or
+/// [`Self::max_invalid_messages`] invalid gossip messages, we stop requesting gossip from it and
+/// only process up to [`Self::deprioritized_messages_per_tick`] of the messages it sends us
+/// unsolicited between calls to [`P2PGossipSync::timer_tick_occurred`]. The peer is not
+/// disconnected.
+///
+/// The peer is reprioritized once it reconnects or after [`Self::cooldown_ticks`] calls to
+/// [`P2PGossipSync::timer_tick_occurred`], at which point we request gossip from it again.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GossipSpamPolicy {
+	/// The number of redundant gossip messages a peer may send before being deprioritized.
+	///
+	/// Default value: `1000`
+	pub max_redundant_messages: u64,
+	/// The number of invalid gossip messages a peer may send before being deprioritized.
+	///
+	/// Default value: `100`
+	pub max_invalid_messages: u64,
+	/// The number of gossip messages we process from a deprioritized peer per timer tick. Any
+	/// further messages until the next tick are dropped.
+	///
+	/// Default value: `10`
+	pub deprioritized_messages_per_tick: u32,
+	/// The number of timer ticks after which a deprioritized peer is reprioritized.
+	///
+	/// Default value: `60` (one hour if the timer ticks every minute)
+	pub cooldown_ticks: u32,
+}
+
+impl Default for GossipSpamPolicy {
+	fn default() -> Self {
+		Self {
+			max_redundant_messages: 1000,
+			max_invalid_messages: 100,
+			deprioritized_messages_per_tick: 10,
+			cooldown_ticks: 60,
+		}
+	}
+}
+
+/// The gossip accounting and [`GossipSpamPolicy`] state we keep for each connected peer.
+#[derive(Default)]
+struct PeerGossipState {
+	stats: PeerGossipStats,
+	/// Whether the peer supports `gossip_queries`, i.e., whether we request gossip from it.
+	supports_gossip_queries: bool,
+	/// The number of redundant and invalid messages counted towards the [`GossipSpamPolicy`]
+	/// thresholds since the peer connected or was last reprioritized.
+	policy_redundant_messages: u64,
+	policy_invalid_messages: u64,
+	/// The number of ticks until a deprioritized peer is reprioritized.
+	cooldown_ticks_remaining: u32,
+	/// The number of messages processed from a deprioritized peer since the last tick.
+	messages_this_tick: u32,
+}
+
+/// The outcome of processing a gossip message, for the purpose of [`PeerGossipStats`].
+enum GossipMessageOutcome {
+	Accepted,
+	Redundant,
+	Invalid,
+}
+
 /// Receives and validates network updates from peers,
 /// stores authentic and relevant data as a network graph.
 /// This network graph is then used for routing payments.
@@ -328,6 +425,8 @@ where
 	utxo_lookup: RwLock<Option<U>>,
 	full_syncs_requested: AtomicUsize,
 	pending_events: Mutex<Vec<MessageSendEvent>>,
+	gossip_spam_policy: Mutex<Option<GossipSpamPolicy>>,
+	peer_gossip_state: Mutex<HashMap<PublicKey, PeerGossipState>>,
 	logger: L,
 }
 
@@ -346,6 +445,8 @@ where
 			full_syncs_requested: AtomicUsize::new(0),
 			utxo_lookup: RwLock::new(utxo_lookup),
 			pending_events: Mutex::new(vec![]),
+			gossip_spam_policy: Mutex::new(None),
+			peer_gossip_state: Mutex::new(new_hash_map()),
 			logger,
 		}
 	}
@@ -365,6 +466,188 @@ where
 		&self.network_graph
 	}
 
+	/// Sets the [`GossipSpamPolicy`] used to deprioritize peers sending us excessive redundant or
+	/// invalid gossip, or disables deprioritization if `None`, which is the default.
+	///
+	/// Gossip is accounted for in [`PeerGossipStats`] regardless of the policy.
+	pub fn set_gossip_spam_policy(&self, policy: Option<GossipSpamPolicy>) {
+		*self.gossip_spam_policy.lock().unwrap() = policy;
+	}
+
+	/// Gets the [`PeerGossipStats`] for the given connected peer, if any.
+	pub fn peer_gossip_stats(&self, their_node_id: &PublicKey) -> Option<PeerGossipStats> {
+		self.peer_gossip_state.lock().unwrap().get(their_node_id).map(|state| state.stats)
+	}
+
+	/// Reprioritizes deprioritized peers whose [`GossipSpamPolicy::cooldown_ticks`] have passed
+	/// and resets the per-tick message allowance of those still deprioritized.
+	///
+	/// Should be called on a regular interval, e.g., once a minute. This is done automatically by
+	/// the `lightning-background-processor` crate.
+	pub fn timer_tick_occurred(&self) {
+		let mut reprioritized_peers = Vec::new();
+		{
+			let mut peer_gossip_state = self.peer_gossip_state.lock().unwrap();
+			for (their_node_id, state) in peer_gossip_state.iter_mut() {
+				if !state.stats.deprioritized {
+					continue;
+				}
+				state.messages_this_tick = 0;
+				state.cooldown_ticks_remaining = state.cooldown_ticks_remaining.saturating_sub(1);
+				if state.cooldown_ticks_remaining == 0 {
+					state.stats.deprioritized = false;
+					state.policy_redundant_messages = 0;
+					state.policy_invalid_messages = 0;
+					if state.supports_gossip_queries {
+						reprioritized_peers.push(*their_node_id);
+					}
+				}
+			}
+		}
+		for their_node_id in reprioritized_peers {
+			log_debug!(
+				self.logger,
+				"Reprioritizing gossip from peer {} after cooldown",
+				log_pubkey!(their_node_id)
+			);
+			// Only ask for recent gossip as we've already done any initial sync on connection.
+			let gossip_start_time = Self::gossip_start_time(false);
+			self.push_gossip_timestamp_filter(their_node_id, gossip_start_time, u32::max_value());
+		}
+	}
+
+	/// Returns whether a gossip message received from the given peer should be dropped
+	/// unprocessed as the peer is deprioritized and has exhausted its allowance for this tick,
+	/// accounting for its size otherwise.
+	fn should_rate_limit_gossip<M: Writeable>(&self, their_node_id: &PublicKey, msg: &M) -> bool {
+		let policy = *self.gossip_spam_policy.lock().unwrap();
+		let mut peer_gossip_state = self.peer_gossip_state.lock().unwrap();
+		let state = peer_gossip_state.entry(*their_node_id).or_default();
+		state.stats.bytes_received += msg.serialized_length() as u64;
+		let policy = match policy {
+			Some(policy) if state.stats.deprioritized => policy,
+			_ => return false,
+		};
+		if state.messages_this_tick >= policy.deprioritized_messages_per_tick {
+			state.stats.rate_limited_messages += 1;
+			return true;
+		}
+		state.messages_this_tick += 1;
+		false
+	}
+
+	/// Accounts for the outcome of processing a gossip message from the given peer, deprioritizing
+	/// the peer if it exceeds the [`GossipSpamPolicy`] thresholds.
+	fn record_gossip_outcome(&self, their_node_id: &PublicKey, outcome: GossipMessageOutcome) {
+		let policy = *self.gossip_spam_policy.lock().unwrap();
+		let should_deprioritize = {
+			let mut peer_gossip_state = self.peer_gossip_state.lock().unwrap();
+			let state = peer_gossip_state.entry(*their_node_id).or_default();
+			match outcome {
+				GossipMessageOutcome::Accepted => state.stats.accepted_messages += 1,
+				GossipMessageOutcome::Redundant => {
+					state.stats.redundant_messages += 1;
+					state.policy_redundant_messages += 1;
+				},
+				GossipMessageOutcome::Invalid => {
+					state.stats.invalid_messages += 1;
+					state.policy_invalid_messages += 1;
+				},
+			}
+			match policy {
+				Some(policy) if !state.stats.deprioritized => {
+					if state.policy_redundant_messages > policy.max_redundant_messages
+						|| state.policy_invalid_messages > policy.max_invalid_messages
+					{
+						state.stats.deprioritized = true;
+						state.cooldown_ticks_remaining = policy.cooldown_ticks;
+						state.messages_this_tick = 0;
+						state.supports_gossip_queries
+					} else {
+						false
+					}
+				},
+				_ => false,
+			}
+		};
+		if should_deprioritize {
+			log_debug!(
+				self.logger,
+				"Deprioritizing gossip from peer {} after excessive redundant or invalid gossip",
+				log_pubkey!(their_node_id)
+			);
+			// Ask the peer to stop sending us gossip entirely. Anything it sends regardless is
+			// rate-limited.
+			self.push_gossip_timestamp_filter(*their_node_id, u32::max_value() as u64, 0);
+		}
+	}
+
+	/// Handles a gossip message from the given peer (if any) with `handle_msg`, accounting for
+	/// the outcome in the peer's [`PeerGossipStats`].
+	fn handle_gossip_msg<M: Writeable, F: FnOnce() -> Result<bool, LightningError>>(
+		&self, their_node_id: Option<PublicKey>, msg: &M, is_redundant: bool, handle_msg: F,
+	) -> Result<bool, LightningError> {
+		let their_node_id = match their_node_id {
+			Some(their_node_id) => their_node_id,
+			None => return handle_msg(),
+		};
+		if self.should_rate_limit_gossip(&their_node_id, msg) {
+			return Err(LightningError {
+				err: "Rate-limiting gossip from deprioritized peer".to_owned(),
+				action: ErrorAction::IgnoreAndLog(Level::Gossip),
+			});
+		}
+		let res = handle_msg();
+		let outcome = match &res {
+			Ok(_) if is_redundant => GossipMessageOutcome::Redundant,
+			Ok(_) => GossipMessageOutcome::Accepted,
+			Err(LightningError { action: ErrorAction::IgnoreDuplicateGossip, .. }) => {
+				GossipMessageOutcome::Redundant
+			},
+			Err(_) => GossipMessageOutcome::Invalid,
+		};
+		self.record_gossip_outcome(&their_node_id, outcome);
+		res
+	}
+
+	/// Returns the `first_timestamp` to use in the `gossip_timestamp_filter` we send a peer,
+	/// covering the last two weeks if we want a full sync or the last hour otherwise.
+	///
+	/// For non-`std` builds, this is always zero, i.e., a full sync.
+	fn gossip_start_time(full_sync: bool) -> u64 {
+		#[cfg(feature = "std")]
+		{
+			let now = SystemTime::now()
+				.duration_since(UNIX_EPOCH)
+				.expect("Time must be > 1970")
+				.as_secs();
+			if full_sync {
+				now - 60 * 60 * 24 * 7 * 2 // 2 weeks ago
+			} else {
+				now - 60 * 60 // an hour ago
+			}
+		}
+		#[cfg(not(feature = "std"))]
+		{
+			let _ = full_sync;
+			0
+		}
+	}
+
+	fn push_gossip_timestamp_filter(
+		&self, their_node_id: PublicKey, first_timestamp: u64, timestamp_range: u32,
+	) {
+		let mut pending_events = self.pending_events.lock().unwrap();
+		pending_events.push(MessageSendEvent::SendGossipTimestampFilter {
+			node_id: their_node_id,
+			msg: GossipTimestampFilter {
+				chain_hash: self.network_graph.chain_hash,
+				first_timestamp: first_timestamp as u32, // 2106 issue!
+				timestamp_range,
+			},
+		});
+	}
+
 	/// Returns true when a full routing table sync should be performed with a peer.
 	fn should_request_full_sync(&self) -> bool {
 		const FULL_SYNCS_TO_REQUEST: usize = 5;
@@ -535,28 +818,36 @@ where
 	L::Target: Logger,
 {
 	fn handle_node_announcement(
-		&self, _their_node_id: Option<PublicKey>, msg: &msgs::NodeAnnouncement,
+		&self, their_node_id: Option<PublicKey>, msg: &msgs::NodeAnnouncement,
 	) -> Result<bool, LightningError> {
-		self.network_graph.update_node_from_announcement(msg)?;
-		Ok(msg.contents.excess_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY
-			&& msg.contents.excess_address_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY
-			&& msg.contents.excess_data.len() + msg.contents.excess_address_data.len()
-				<= MAX_EXCESS_BYTES_FOR_RELAY)
+		self.handle_gossip_msg(their_node_id, msg, false, || {
+			self.network_graph.update_node_from_announcement(msg)?;
+			Ok(msg.contents.excess_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY
+				&& msg.contents.excess_address_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY
+				&& msg.contents.excess_data.len() + msg.contents.excess_address_data.len()
+					<= MAX_EXCESS_BYTES_FOR_RELAY)
+		})
 	}
 
 	fn handle_channel_announcement(
-		&self, _their_node_id: Option<PublicKey>, msg: &msgs::ChannelAnnouncement,
+		&self, their_node_id: Option<PublicKey>, msg: &msgs::ChannelAnnouncement,
 	) -> Result<bool, LightningError> {
-		self.network_graph
-			.update_channel_from_announcement(msg, &*self.utxo_lookup.read().unwrap())?;
-		Ok(msg.contents.excess_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY)
+		self.handle_gossip_msg(their_node_id, msg, false, || {
+			self.network_graph
+				.update_channel_from_announcement(msg, &*self.utxo_lookup.read().unwrap())?;
+			Ok(msg.contents.excess_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY)
+		})
 	}
 
 	fn handle_channel_update(
-		&self, _their_node_id: Option<PublicKey>, msg: &msgs::ChannelUpdate,
+		&self, their_node_id: Option<PublicKey>, msg: &msgs::ChannelUpdate,
 	) -> Result<bool, LightningError> {
-		self.network_graph.update_channel(msg)?;
-		Ok(msg.contents.excess_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY)
+		let is_redundant = their_node_id.is_some()
+			&& self.network_graph.is_channel_update_content_redundant(&msg.contents);
+		self.handle_gossip_msg(their_node_id, msg, is_redundant, || {
+			self.network_graph.update_channel(msg)?;
+			Ok(msg.contents.excess_data.len() <= MAX_EXCESS_BYTES_FOR_RELAY)
+		})
 	}
 
 	fn get_next_channel_announcement(
@@ -778,8 +1069,15 @@ where
 	fn peer_connected(
 		&self, their_node_id: PublicKey, init_msg: &Init, _inbound: bool,
 	) -> Result<(), ()> {
+		let supports_gossip_queries = init_msg.features.supports_gossip_queries();
+		// Any gossip accounting and deprioritization is reset on reconnection.
+		self.peer_gossip_state.lock().unwrap().insert(
+			their_node_id,
+			PeerGossipState { supports_gossip_queries, ..Default::default() },
+		);
+
 		// We will only perform a sync with peers that support gossip_queries.
-		if !init_msg.features.supports_gossip_queries() {
+		if !supports_gossip_queries {
 			// Don't disconnect peers for not supporting gossip queries. We may wish to have
 			// channels with peers even without being able to exchange gossip.
 			return Ok(());
@@ -835,36 +1133,15 @@ where
 		// `gossip_timestamp_filter`, with the filter time set either two weeks ago or an hour ago.
 		//
 		// For non-`std` builds, we bury our head in the sand and do a full sync on each connection.
-		#[allow(unused_mut, unused_assignments)]
-		let mut gossip_start_time = 0;
-		#[allow(unused)]
 		let should_sync = self.should_request_full_sync();
-		#[cfg(feature = "std")]
-		{
-			gossip_start_time = SystemTime::now()
-				.duration_since(UNIX_EPOCH)
-				.expect("Time must be > 1970")
-				.as_secs();
-			if should_sync {
-				gossip_start_time -= 60 * 60 * 24 * 7 * 2; // 2 weeks ago
-			} else {
-				gossip_start_time -= 60 * 60; // an hour ago
-			}
-		}
-
-		let mut pending_events = self.pending_events.lock().unwrap();
-		pending_events.push(MessageSendEvent::SendGossipTimestampFilter {
-			node_id: their_node_id.clone(),
-			msg: GossipTimestampFilter {
-				chain_hash: self.network_graph.chain_hash,
-				first_timestamp: gossip_start_time as u32, // 2106 issue!
-				timestamp_range: u32::max_value(),
-			},
-		});
+		let gossip_start_time = Self::gossip_start_time(should_sync);
+		self.push_gossip_timestamp_filter(their_node_id, gossip_start_time, u32::max_value());
 		Ok(())
 	}
 
-	fn peer_disconnected(&self, _their_node_id: PublicKey) {}
+	fn peer_disconnected(&self, their_node_id: PublicKey) {
+		self.peer_gossip_state.lock().unwrap().remove(&their_node_id);
+	}
 
 	fn provided_node_features(&self) -> NodeFeatures {
 		let mut features = NodeFeatures::empty();
@@ -1699,19 +1976,187 @@ where
 			(1, last_rapid_gossip_sync_timestamp, option),
 		});
 
-		Ok(NetworkGraph {
+		Ok(NetworkGraph::from_read_parts(
+			chain_hash,
+			logger,
+			channels,
+			nodes,
+			last_rapid_gossip_sync_timestamp,
+		))
+	}
+}
+
+/// The first record of a [`NetworkGraph`] written by [`NetworkGraph::write_framed`].
+struct FramedGraphHeader {
+	chain_hash: ChainHash,
+	last_rapid_gossip_sync_timestamp: Option<u32>,
+}
+
+impl_writeable_tlv_based!(FramedGraphHeader, {
+	(0, chain_hash, required),
+	(1, last_rapid_gossip_sync_timestamp, option),
+});
+
+const FRAMED_NODE_RECORD: u8 = 0;
+const FRAMED_CHANNEL_RECORD: u8 = 1;
+
+/// A record following the [`FramedGraphHeader`], written by [`NetworkGraph::write_framed`].
+enum FramedGraphRecord {
+	Node(NodeId, NodeInfo),
+	Channel(u64, ChannelInfo),
+}
+
+impl Readable for FramedGraphRecord {
+	fn read<R: io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+		let record_type: u8 = Readable::read(reader)?;
+		match record_type {
+			FRAMED_NODE_RECORD => {
+				Ok(FramedGraphRecord::Node(Readable::read(reader)?, Readable::read(reader)?))
+			},
+			FRAMED_CHANNEL_RECORD => {
+				Ok(FramedGraphRecord::Channel(Readable::read(reader)?, Readable::read(reader)?))
+			},
+			_ => Err(DecodeError::UnknownRequiredFeature),
+		}
+	}
+}
+
+impl<L: Deref> NetworkGraph<L>
+where
+	L::Target: Logger,
+{
+	fn from_read_parts(
+		chain_hash: ChainHash, logger: L, channels: IndexedMap<u64, ChannelInfo>,
+		nodes: IndexedMap<NodeId, NodeInfo>, last_rapid_gossip_sync_timestamp: Option<u32>,
+	) -> Self {
+		let nodes_count = nodes.len();
+		NetworkGraph {
 			secp_ctx: Secp256k1::verification_only(),
 			chain_hash,
 			logger,
 			channels: RwLock::new(channels),
 			nodes: RwLock::new(nodes),
 			removed_node_counters: Mutex::new(Vec::new()),
-			next_node_counter: AtomicUsize::new(nodes_count as usize),
+			next_node_counter: AtomicUsize::new(nodes_count),
 			last_rapid_gossip_sync_timestamp: Mutex::new(last_rapid_gossip_sync_timestamp),
 			removed_nodes: Mutex::new(new_hash_map()),
 			removed_channels: Mutex::new(new_hash_map()),
 			pending_checks: utxo::PendingChecks::new(),
-		})
+			watched_nodes: Mutex::new(new_hash_map()),
+			pending_events: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Writes the graph in a framed format which, unlike the format written by
+	/// [`Writeable::write`], can still be partially read by [`Self::read_lossy`] after the data
+	/// was truncated or corrupted.
+	///
+	/// Each node and channel is written as a separate record with its own checksum, followed by an
+	/// integrity footer. See [`framed_ser`] for details.
+	///
+	/// [`framed_ser`]: crate::util::framed_ser
+	pub fn write_framed<W: Writer>(&self, writer: &mut W) -> Result<(), io::Error> {
+		self.test_node_counter_consistency();
+
+		let mut framed_writer = FramedWriter::new(writer, FramedType::NetworkGraph)?;
+		framed_writer.write_record(&FramedGraphHeader {
+			chain_hash: self.chain_hash,
+			last_rapid_gossip_sync_timestamp: self.get_last_rapid_gossip_sync_timestamp(),
+		})?;
+		// Nodes are written before channels, such that any recovered channel can be checked for
+		// its nodes having been recovered as well.
+		let nodes = self.nodes.read().unwrap();
+		for (node_id, node_info) in nodes.unordered_iter() {
+			framed_writer.write_record(&(FRAMED_NODE_RECORD, node_id, node_info))?;
+		}
+		let channels = self.channels.read().unwrap();
+		for (short_channel_id, channel_info) in channels.unordered_iter() {
+			framed_writer.write_record(&(FRAMED_CHANNEL_RECORD, short_channel_id, channel_info))?;
+		}
+		framed_writer.finish()
+	}
+
+	/// Reads a graph written by [`Self::write_framed`], recovering as much of it as possible if the
+	/// data was truncated or corrupted.
+	///
+	/// All records preceding the first damaged one are recovered, except for channels whose nodes
+	/// were lost and nodes whose channels were all lost, which are dropped to keep the graph
+	/// consistent. The returned [`LossyReadReport`] describes how much data was lost. If the data
+	/// was damaged, the remainder of `reader` is consumed.
+	///
+	/// Fails only if the data is not a framed graph or its first record, containing the chain the
+	/// graph belongs to, was lost.
+	pub fn read_lossy<R: io::Read>(
+		reader: &mut R, logger: L,
+	) -> Result<(Self, LossyReadReport), DecodeError> {
+		let mut framed_reader = FramedReader::new(reader, FramedType::NetworkGraph)?;
+		let header: FramedGraphHeader = match framed_reader.read_record(|r| Readable::read(r)) {
+			Some(header) => header,
+			None => {
+				let report = framed_reader.finish();
+				return match report.damage {
+					None | Some(FramedDataDamage::Truncated) => Err(DecodeError::ShortRead),
+					Some(_) => Err(DecodeError::InvalidValue),
+				};
+			},
+		};
+
+		let mut recovered_nodes = new_hash_map();
+		let mut recovered_channels = IndexedMap::with_capacity(CHAN_COUNT_ESTIMATE);
+		let mut records_dropped = 0;
+		while let Some(record) = framed_reader.read_record(|r| FramedGraphRecord::read(r)) {
+			match record {
+				FramedGraphRecord::Node(node_id, node_info) => {
+					recovered_nodes.insert(node_id, node_info);
+				},
+				FramedGraphRecord::Channel(short_channel_id, channel_info) => {
+					if recovered_nodes.contains_key(&channel_info.node_one)
+						&& recovered_nodes.contains_key(&channel_info.node_two)
+					{
+						recovered_channels.insert(short_channel_id, channel_info);
+					} else {
+						records_dropped += 1;
+					}
+				},
+			}
+		}
+		let mut report = framed_reader.finish();
+
+		// Nodes are only tracked as long as they have channels, so drop any whose channels were
+		// all lost.
+		let mut channel_nodes = new_hash_set();
+		for (_, chan) in recovered_channels.unordered_iter() {
+			channel_nodes.insert(chan.node_one);
+			channel_nodes.insert(chan.node_two);
+		}
+		let mut nodes = IndexedMap::with_capacity(NODE_COUNT_ESTIMATE);
+		for (node_id, mut node_info) in recovered_nodes.drain() {
+			if !channel_nodes.contains(&node_id) {
+				records_dropped += 1;
+				continue;
+			}
+			node_info.channels.retain(|scid| recovered_channels.contains_key(scid));
+			node_info.node_counter = nodes.len() as u32;
+			nodes.insert(node_id, node_info);
+		}
+		for (_, chan) in recovered_channels.unordered_iter_mut() {
+			if let Some(node_one) = nodes.get(&chan.node_one) {
+				chan.node_one_counter = node_one.node_counter;
+			}
+			if let Some(node_two) = nodes.get(&chan.node_two) {
+				chan.node_two_counter = node_two.node_counter;
+			}
+		}
+		report.records_dropped = records_dropped;
+
+		let graph = NetworkGraph::from_read_parts(
+			header.chain_hash,
+			logger,
+			recovered_channels,
+			nodes,
+			header.last_rapid_gossip_sync_timestamp,
+		);
+		Ok((graph, report))
 	}
 }
 
@@ -1732,6 +2177,26 @@ where
 	}
 }
 
+impl<L: Deref> EventsProvider for NetworkGraph<L>
+where
+	L::Target: Logger,
+{
+	/// Processes any [`Event::ChannelPartnerAnnouncementChanged`]s generated since the last call.
+	fn process_pending_events<H: Deref>(&self, handler: H)
+	where
+		H::Target: EventHandler,
+	{
+		let pending_events = core::mem::take(&mut *self.pending_events.lock().unwrap());
+		let mut failed_events = Vec::new();
+		for event in pending_events {
+			if handler.handle_event(event.clone()).is_err() {
+				failed_events.push(event);
+			}
+		}
+		self.pending_events.lock().unwrap().extend(failed_events);
+	}
+}
+
 impl<L: Deref> Eq for NetworkGraph<L> where L::Target: Logger {}
 impl<L: Deref> PartialEq for NetworkGraph<L>
 where
@@ -1782,6 +2247,8 @@ where
 			removed_channels: Mutex::new(new_hash_map()),
 			removed_nodes: Mutex::new(new_hash_map()),
 			pending_checks: utxo::PendingChecks::new(),
+			watched_nodes: Mutex::new(new_hash_map()),
+			pending_events: Mutex::new(Vec::new()),
 		}
 	}
 
@@ -1852,6 +2319,81 @@ where
 			.replace(last_rapid_gossip_sync_timestamp);
 	}
 
+	/// Starts watching the `node_announcement`s of the given node, generating an
+	/// [`Event::ChannelPartnerAnnouncementChanged`] whenever it announces a new set of addresses.
+	///
+	/// This is typically called for each channel partner, e.g., upon [`Event::ChannelPending`], but
+	/// may be used to watch arbitrary nodes. Watched nodes are not persisted and need to be
+	/// registered again upon restart.
+	///
+	/// Events are only generated for announcements received after this call, and need to be
+	/// processed via the [`EventsProvider`] implementation of the [`NetworkGraph`].
+	pub fn watch_node_announcements(&self, node_id: NodeId) {
+		self.watched_nodes.lock().unwrap().entry(node_id).or_insert(None);
+	}
+
+	/// Stops watching the `node_announcement`s of the given node, as previously registered via
+	/// [`Self::watch_node_announcements`].
+	pub fn unwatch_node_announcements(&self, node_id: &NodeId) {
+		self.watched_nodes.lock().unwrap().remove(node_id);
+	}
+
+	/// Returns the nodes whose `node_announcement`s are currently watched.
+	pub fn list_watched_nodes(&self) -> Vec<NodeId> {
+		self.watched_nodes.lock().unwrap().keys().cloned().collect()
+	}
+
+	/// Processes any [`Event::ChannelPartnerAnnouncementChanged`]s generated since the last call.
+	///
+	/// An async variant of [`EventsProvider::process_pending_events`].
+	pub async fn process_pending_events_async<
+		Future: core::future::Future<Output = Result<(), ReplayEvent>>,
+		H: Fn(Event) -> Future,
+	>(
+		&self, handler: H,
+	) {
+		let pending_events = core::mem::take(&mut *self.pending_events.lock().unwrap());
+		let mut failed_events = Vec::new();
+		for event in pending_events {
+			if handler(event.clone()).await.is_err() {
+				failed_events.push(event);
+			}
+		}
+		self.pending_events.lock().unwrap().extend(failed_events);
+	}
+
+	fn maybe_push_address_change_event(
+		&self, msg: &msgs::UnsignedNodeAnnouncement, previous_addresses: Option<&[SocketAddress]>,
+	) {
+		let mut watched_nodes = self.watched_nodes.lock().unwrap();
+		let last_notified_timestamp = match watched_nodes.get_mut(&msg.node_id) {
+			Some(last_notified_timestamp) => last_notified_timestamp,
+			None => return,
+		};
+		if last_notified_timestamp.map_or(false, |timestamp| timestamp >= msg.timestamp) {
+			return;
+		}
+		let previous_addresses = previous_addresses.unwrap_or(&[]);
+		let addresses_changed = previous_addresses.len() != msg.addresses.len()
+			|| msg.addresses.iter().any(|address| !previous_addresses.contains(address));
+		if !addresses_changed {
+			return;
+		}
+		let node_id = match msg.node_id.as_pubkey() {
+			Ok(node_id) => node_id,
+			Err(_) => return,
+		};
+		*last_notified_timestamp = Some(msg.timestamp);
+		core::mem::drop(watched_nodes);
+
+		log_debug!(self.logger, "Watched node {} announced new addresses", log_pubkey!(node_id));
+		self.pending_events.lock().unwrap().push(Event::ChannelPartnerAnnouncementChanged {
+			node_id,
+			addresses: msg.addresses.clone(),
+			alias: msg.alias,
+		});
+	}
+
 	/// Clears the `NodeAnnouncementInfo` field for all nodes in the `NetworkGraph` for testing
 	/// purposes.
 	#[cfg(test)]
@@ -1933,6 +2475,11 @@ where
 					&& msg.excess_data.len() + msg.excess_address_data.len()
 						<= MAX_EXCESS_BYTES_FOR_RELAY;
 
+				self.maybe_push_address_change_event(
+					msg,
+					node.announcement_info.as_ref().map(|info| info.addresses()),
+				);
+
 				node.announcement_info =
 					if let (Some(signed_announcement), true) = (full_msg, should_relay) {
 						Some(NodeAnnouncementInfo::Relayed(signed_announcement.clone()))
@@ -2444,6 +2991,31 @@ where
 		self.update_channel_internal(msg, None, None, false)
 	}
 
+	/// Returns whether the given `channel_update` is newer than, but otherwise identical to, the
+	/// latest update we have for the same direction of the channel.
+	fn is_channel_update_content_redundant(&self, msg: &msgs::UnsignedChannelUpdate) -> bool {
+		let channels = self.channels.read().unwrap();
+		let channel = match channels.get(&msg.short_channel_id) {
+			Some(channel) => channel,
+			None => return false,
+		};
+		let existing_update =
+			if msg.channel_flags & 1 == 1 { &channel.two_to_one } else { &channel.one_to_two };
+		match existing_update {
+			Some(existing_update) => {
+				existing_update.last_update < msg.timestamp
+					&& existing_update.enabled == (msg.channel_flags & (1 << 1) != (1 << 1))
+					&& existing_update.cltv_expiry_delta == msg.cltv_expiry_delta
+					&& existing_update.htlc_minimum_msat == msg.htlc_minimum_msat
+					&& existing_update.htlc_maximum_msat == msg.htlc_maximum_msat
+					&& existing_update.fees.base_msat == msg.fee_base_msat
+					&& existing_update.fees.proportional_millionths
+						== msg.fee_proportional_millionths
+			},
+			None => false,
+		}
+	}
+
 	/// For an already known (from announcement) channel, verify the given [`ChannelUpdate`].
 	///
 	/// This checks whether the update currently is applicable by [`Self::update_channel`].
@@ -2711,9 +3283,10 @@ impl ReadOnlyNetworkGraph<'_> {
 
 #[cfg(test)]
 pub(crate) mod tests {
+	use crate::events::Event;
 	use crate::ln::chan_utils::make_funding_redeemscript;
 	use crate::ln::channelmanager;
-	use crate::ln::msgs::{BaseMessageHandler, MessageSendEvent, SocketAddress};
+	use crate::ln::msgs::{BaseMessageHandler, Init, MessageSendEvent, SocketAddress};
 	use crate::ln::msgs::{
 		ChannelAnnouncement, ChannelUpdate, NodeAnnouncement, QueryChannelRange,
 		QueryShortChannelIds, ReplyChannelRange, RoutingMessageHandler,
@@ -2721,17 +3294,20 @@ pub(crate) mod tests {
 		MAX_VALUE_MSAT,
 	};
 	use crate::routing::gossip::{
-		ChannelInfo, ChannelUpdateInfo, NetworkGraph, NetworkUpdate, NodeAlias,
-		NodeAnnouncementInfo, NodeId, NodeInfo, P2PGossipSync, RoutingFees,
+		ChannelInfo, ChannelUpdateInfo, GossipSpamPolicy, NetworkGraph, NetworkUpdate, NodeAlias,
+		NodeAnnouncementInfo, NodeId, NodeInfo, P2PGossipSync, PeerGossipStats, RoutingFees,
 		MAX_EXCESS_BYTES_FOR_RELAY,
 	};
+	use crate::routing::test_utils::build_graph;
 	use crate::routing::utxo::{UtxoLookupError, UtxoResult};
-	#[cfg(feature = "std")]
 	use crate::types::features::InitFeatures;
 	use crate::util::config::UserConfig;
+	use crate::util::framed_ser::FramedDataDamage;
+	use crate::util::logger::Logger;
 	use crate::util::scid_utils::scid_from_parts;
 	use crate::util::ser::{Hostname, LengthReadable, Readable, ReadableArgs, Writeable};
 	use crate::util::test_utils;
+	use core::ops::Deref;
 
 	use super::STALE_CHANNEL_UPDATE_AGE_LIMIT_SECS;
 	use crate::routing::gossip::REMOVED_ENTRIES_TRACKING_AGE_LIMIT_SECS;
@@ -2951,6 +3527,70 @@ pub(crate) mod tests {
 		};
 	}
 
+	#[test]
+	fn watched_node_announcement_address_changes() {
+		let network_graph = create_network_graph();
+		let (secp_ctx, gossip_sync) = create_gossip_sync(&network_graph);
+
+		let node_1_privkey = &SecretKey::from_slice(&[42; 32]).unwrap();
+		let node_1_pubkey = PublicKey::from_secret_key(&secp_ctx, node_1_privkey);
+		let node_1_id = NodeId::from_pubkey(&node_1_pubkey);
+		let node_2_privkey = &SecretKey::from_slice(&[41; 32]).unwrap();
+		let take_events = || core::mem::take(&mut *network_graph.pending_events.lock().unwrap());
+
+		let channel_announcement =
+			get_signed_channel_announcement(|_| {}, node_1_privkey, node_2_privkey, &secp_ctx);
+		assert!(gossip_sync
+			.handle_channel_announcement(Some(node_1_pubkey), &channel_announcement)
+			.unwrap());
+
+		let address = SocketAddress::TcpIpV4 { addr: [127, 0, 0, 1], port: 9735 };
+		let announce = |timestamp: u32, addresses: Vec<SocketAddress>| {
+			let announcement = get_signed_node_announcement(
+				|msg| {
+					msg.timestamp = timestamp;
+					msg.addresses = addresses.clone();
+				},
+				node_1_privkey,
+				&secp_ctx,
+			);
+			gossip_sync.handle_node_announcement(Some(node_1_pubkey), &announcement)
+		};
+
+		// Announcements of nodes which are not watched don't generate events.
+		assert!(announce(100, vec![address.clone()]).unwrap());
+		assert!(take_events().is_empty());
+
+		network_graph.watch_node_announcements(node_1_id);
+		assert_eq!(network_graph.list_watched_nodes(), vec![node_1_id]);
+
+		// An announcement with the same set of addresses doesn't generate an event.
+		assert!(announce(200, vec![address.clone()]).unwrap());
+		assert!(take_events().is_empty());
+
+		// A changed set of addresses generates an event.
+		let new_address = SocketAddress::TcpIpV4 { addr: [127, 0, 0, 2], port: 9735 };
+		assert!(announce(300, vec![address.clone(), new_address.clone()]).unwrap());
+		match &take_events()[..] {
+			[Event::ChannelPartnerAnnouncementChanged { node_id, addresses, alias }] => {
+				assert_eq!(*node_id, node_1_pubkey);
+				assert_eq!(*addresses, vec![address.clone(), new_address.clone()]);
+				assert_eq!(*alias, NodeAlias([0; 32]));
+			},
+			events => panic!("Unexpected events: {:?}", events),
+		}
+
+		// Duplicate announcements are rejected without generating another event.
+		assert!(announce(300, vec![new_address.clone()]).is_err());
+		assert!(take_events().is_empty());
+
+		// Once unwatched, address changes don't generate events anymore.
+		network_graph.unwatch_node_announcements(&node_1_id);
+		assert!(network_graph.list_watched_nodes().is_empty());
+		assert!(announce(400, vec![new_address]).unwrap());
+		assert!(take_events().is_empty());
+	}
+
 	#[test]
 	fn handling_channel_announcements() {
 		let secp_ctx = Secp256k1::new();
@@ -3791,10 +4431,101 @@ pub(crate) mod tests {
 		assert_eq!(reassembled_network_graph.get_last_rapid_gossip_sync_timestamp().unwrap(), 42);
 	}
 
+	#[test]
+	fn network_graph_framed_serialization() {
+		let (_, network_graph, _, _, logger) = build_graph();
+		network_graph.set_last_rapid_gossip_sync_timestamp(42);
+
+		let mut w = test_utils::TestVecWriter(Vec::new());
+		network_graph.write_framed(&mut w).unwrap();
+
+		let (read_graph, report) =
+			NetworkGraph::read_lossy(&mut &w.0[..], Arc::clone(&logger)).unwrap();
+		assert!(read_graph == *network_graph);
+		assert_eq!(read_graph.get_last_rapid_gossip_sync_timestamp(), Some(42));
+		assert!(report.is_complete());
+		let num_nodes = network_graph.read_only().nodes().len();
+		let num_channels = network_graph.read_only().channels().len();
+		assert_eq!(report.records_recovered, (1 + num_nodes + num_channels) as u64);
+		assert_eq!(report.bytes_discarded, 0);
+
+		// The unframed format is not accepted.
+		let mut w = test_utils::TestVecWriter(Vec::new());
+		network_graph.write(&mut w).unwrap();
+		assert!(NetworkGraph::read_lossy(&mut &w.0[..], logger).is_err());
+	}
+
+	fn assert_consistent_subgraph<L: Deref>(graph: &NetworkGraph<L>, original: &NetworkGraph<L>)
+	where
+		L::Target: Logger,
+	{
+		// Writing checks the consistency of node counters.
+		graph.write_framed(&mut test_utils::TestVecWriter(Vec::new())).unwrap();
+
+		let (graph, original) = (graph.read_only(), original.read_only());
+		for (scid, channel) in graph.channels().unordered_iter() {
+			assert_eq!(original.channel(*scid), Some(channel));
+			assert!(graph.node(&channel.node_one).is_some());
+			assert!(graph.node(&channel.node_two).is_some());
+		}
+		for (node_id, node) in graph.nodes().unordered_iter() {
+			let original_node = original.node(node_id).unwrap();
+			assert_eq!(node.announcement_info, original_node.announcement_info);
+			assert!(!node.channels.is_empty());
+			for scid in node.channels.iter() {
+				assert!(graph.channel(*scid).is_some());
+			}
+		}
+	}
+
+	#[test]
+	fn network_graph_read_lossy_recovers_damaged_data() {
+		let (_, network_graph, _, _, logger) = build_graph();
+		let mut w = test_utils::TestVecWriter(Vec::new());
+		network_graph.write_framed(&mut w).unwrap();
+		let data = w.0;
+
+		// Truncation at any point past the first record recovers a consistent subgraph, with more
+		// records recovered the more data remains.
+		let mut prev_records_recovered = 0;
+		for len in 0..data.len() {
+			match NetworkGraph::read_lossy(&mut &data[..len], Arc::clone(&logger)) {
+				Ok((graph, report)) => {
+					assert_eq!(report.damage, Some(FramedDataDamage::Truncated));
+					assert!(report.records_recovered >= prev_records_recovered);
+					prev_records_recovered = report.records_recovered;
+					assert_consistent_subgraph(&graph, &network_graph);
+				},
+				Err(_) => assert_eq!(prev_records_recovered, 0),
+			}
+		}
+		assert!(prev_records_recovered > 0);
+
+		// Flipping bits at pseudo-random positions either fails to read the header or recovers a
+		// consistent subgraph, discarding the rest of the data if any damage was detected.
+		let mut state = 0x2545_f491_4f6c_dd1d_u64;
+		for _ in 0..1000 {
+			state ^= state << 13;
+			state ^= state >> 7;
+			state ^= state << 17;
+			let mut corrupted = data.clone();
+			let pos = (state % data.len() as u64) as usize;
+			corrupted[pos] ^= 1 << ((state >> 32) % 8);
+			let mut reader = &corrupted[..];
+			if let Ok((graph, report)) = NetworkGraph::read_lossy(&mut reader, Arc::clone(&logger))
+			{
+				assert_consistent_subgraph(&graph, &network_graph);
+				if report.damage.is_some() {
+					assert!(report.bytes_discarded > 0);
+					assert!(reader.is_empty());
+				}
+			}
+		}
+	}
+
 	#[test]
 	#[cfg(feature = "std")]
 	fn calling_sync_routing_table() {
-		use crate::ln::msgs::Init;
 		use std::time::{SystemTime, UNIX_EPOCH};
 
 		let network_graph = create_network_graph();
@@ -3846,6 +4577,170 @@ pub(crate) mod tests {
 		}
 	}
 
+	#[test]
+	fn deprioritizes_peer_sending_redundant_gossip() {
+		let network_graph = create_network_graph();
+		let (secp_ctx, gossip_sync) = create_gossip_sync(&network_graph);
+		let node_1_privkey = &SecretKey::from_slice(&[42; 32]).unwrap();
+		let node_2_privkey = &SecretKey::from_slice(&[41; 32]).unwrap();
+		let peer_privkey = &SecretKey::from_slice(&[43; 32]).unwrap();
+		let peer = PublicKey::from_secret_key(&secp_ctx, peer_privkey);
+
+		let mut features = InitFeatures::empty();
+		features.set_gossip_queries_optional();
+		let init_msg = Init { features, networks: None, remote_network_address: None };
+		gossip_sync.peer_connected(peer, &init_msg, true).unwrap();
+		assert_eq!(gossip_sync.get_and_clear_pending_msg_events().len(), 1);
+		assert_eq!(gossip_sync.peer_gossip_stats(&peer), Some(PeerGossipStats::default()));
+
+		gossip_sync.set_gossip_spam_policy(Some(GossipSpamPolicy {
+			max_redundant_messages: 3,
+			max_invalid_messages: 100,
+			deprioritized_messages_per_tick: 1,
+			cooldown_ticks: 2,
+		}));
+
+		let mut bytes_received = 0;
+		let channel_announcement =
+			get_signed_channel_announcement(|_| {}, node_1_privkey, node_2_privkey, &secp_ctx);
+		bytes_received += channel_announcement.serialized_length() as u64;
+		gossip_sync.handle_channel_announcement(Some(peer), &channel_announcement).unwrap();
+		let channel_update = get_signed_channel_update(|_| {}, node_1_privkey, &secp_ctx);
+		bytes_received += channel_update.serialized_length() as u64;
+		gossip_sync.handle_channel_update(Some(peer), &channel_update).unwrap();
+
+		// Updates which only bump the timestamp are redundant, as are those we've already seen.
+		for timestamp in 101..104 {
+			let channel_update = get_signed_channel_update(
+				|update| update.timestamp = timestamp,
+				node_1_privkey,
+				&secp_ctx,
+			);
+			bytes_received += channel_update.serialized_length() as u64;
+			gossip_sync.handle_channel_update(Some(peer), &channel_update).unwrap();
+		}
+		let stats = gossip_sync.peer_gossip_stats(&peer).unwrap();
+		assert_eq!(stats.accepted_messages, 2);
+		assert_eq!(stats.redundant_messages, 3);
+		assert!(!stats.deprioritized);
+		assert!(gossip_sync.get_and_clear_pending_msg_events().is_empty());
+
+		bytes_received += channel_update.serialized_length() as u64;
+		assert!(gossip_sync.handle_channel_update(Some(peer), &channel_update).is_err());
+		let stats = gossip_sync.peer_gossip_stats(&peer).unwrap();
+		assert_eq!(stats.redundant_messages, 4);
+		assert!(stats.deprioritized);
+
+		// We ask the peer to stop sending us gossip.
+		let events = gossip_sync.get_and_clear_pending_msg_events();
+		assert_eq!(events.len(), 1);
+		match &events[0] {
+			MessageSendEvent::SendGossipTimestampFilter { node_id, msg } => {
+				assert_eq!(*node_id, peer);
+				assert_eq!(msg.first_timestamp, u32::max_value());
+				assert_eq!(msg.timestamp_range, 0);
+			},
+			_ => panic!("Expected MessageSendEvent::SendGossipTimestampFilter"),
+		}
+
+		// Only one message is processed per tick while deprioritized.
+		let new_fee_update = |timestamp: u32| {
+			get_signed_channel_update(
+				|update| {
+					update.timestamp = timestamp;
+					update.fee_base_msat = timestamp;
+				},
+				node_1_privkey,
+				&secp_ctx,
+			)
+		};
+		bytes_received += 2 * new_fee_update(200).serialized_length() as u64;
+		gossip_sync.handle_channel_update(Some(peer), &new_fee_update(200)).unwrap();
+		assert!(gossip_sync.handle_channel_update(Some(peer), &new_fee_update(201)).is_err());
+		assert_eq!(
+			network_graph
+				.read_only()
+				.channels()
+				.get(&0)
+				.unwrap()
+				.one_to_two
+				.as_ref()
+				.unwrap()
+				.fees
+				.base_msat,
+			200
+		);
+
+		gossip_sync.timer_tick_occurred();
+		bytes_received += new_fee_update(202).serialized_length() as u64;
+		gossip_sync.handle_channel_update(Some(peer), &new_fee_update(202)).unwrap();
+		assert!(gossip_sync.get_and_clear_pending_msg_events().is_empty());
+
+		let stats = gossip_sync.peer_gossip_stats(&peer).unwrap();
+		let expected_stats = PeerGossipStats {
+			accepted_messages: 4,
+			redundant_messages: 4,
+			invalid_messages: 0,
+			rate_limited_messages: 1,
+			bytes_received,
+			deprioritized: true,
+		};
+		assert_eq!(stats, expected_stats);
+
+		// After the cooldown, we request gossip from the peer again.
+		gossip_sync.timer_tick_occurred();
+		assert!(!gossip_sync.peer_gossip_stats(&peer).unwrap().deprioritized);
+		let events = gossip_sync.get_and_clear_pending_msg_events();
+		assert_eq!(events.len(), 1);
+		match &events[0] {
+			MessageSendEvent::SendGossipTimestampFilter { node_id, msg } => {
+				assert_eq!(*node_id, peer);
+				assert_eq!(msg.timestamp_range, u32::max_value());
+			},
+			_ => panic!("Expected MessageSendEvent::SendGossipTimestampFilter"),
+		}
+		gossip_sync.handle_channel_update(Some(peer), &new_fee_update(203)).unwrap();
+		gossip_sync.handle_channel_update(Some(peer), &new_fee_update(204)).unwrap();
+	}
+
+	#[test]
+	fn resets_gossip_spam_policy_on_reconnect() {
+		let network_graph = create_network_graph();
+		let (secp_ctx, gossip_sync) = create_gossip_sync(&network_graph);
+		let node_1_privkey = &SecretKey::from_slice(&[42; 32]).unwrap();
+		let peer_privkey = &SecretKey::from_slice(&[43; 32]).unwrap();
+		let peer = PublicKey::from_secret_key(&secp_ctx, peer_privkey);
+
+		let init_msg =
+			Init { features: InitFeatures::empty(), networks: None, remote_network_address: None };
+		gossip_sync.peer_connected(peer, &init_msg, true).unwrap();
+		gossip_sync.set_gossip_spam_policy(Some(GossipSpamPolicy {
+			max_invalid_messages: 1,
+			..Default::default()
+		}));
+
+		// Updates for channels we don't know about are invalid.
+		for timestamp in 100..102 {
+			let channel_update = get_signed_channel_update(
+				|update| update.timestamp = timestamp,
+				node_1_privkey,
+				&secp_ctx,
+			);
+			assert!(gossip_sync.handle_channel_update(Some(peer), &channel_update).is_err());
+		}
+		let stats = gossip_sync.peer_gossip_stats(&peer).unwrap();
+		assert_eq!(stats.invalid_messages, 2);
+		assert!(stats.deprioritized);
+
+		// We don't request gossip from peers without `gossip_queries`, so there's nothing to stop.
+		assert!(gossip_sync.get_and_clear_pending_msg_events().is_empty());
+
+		gossip_sync.peer_disconnected(peer);
+		assert!(gossip_sync.peer_gossip_stats(&peer).is_none());
+		gossip_sync.peer_connected(peer, &init_msg, true).unwrap();
+		assert_eq!(gossip_sync.peer_gossip_stats(&peer), Some(PeerGossipStats::default()));
+	}
+
 	#[test]
 	fn handling_query_channel_range() {
 		let network_graph = create_network_graph();
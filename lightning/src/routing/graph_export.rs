@@ -0,0 +1,785 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Utilities for exporting a [`NetworkGraph`] to standard formats for analysis.
+//!
+//! [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+
+use crate::io;
+use crate::prelude::*;
+use crate::routing::gossip::{ChannelUpdateInfo, NodeId, ReadOnlyNetworkGraph};
+use crate::util::ser::{VecWriter, Writer};
+
+use alloc::collections::VecDeque;
+use core::fmt::Write;
+
+/// The format [`ReadOnlyNetworkGraph::export`] writes the graph in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum GraphExportFormat {
+	/// An undirected graph in the [DOT] language, as understood by Graphviz and most graph
+	/// analysis tools.
+	///
+	/// Each node is identified by its hex-encoded public key and labeled with its alias, if known.
+	/// Each channel is written as an edge between its two nodes, with the following attributes:
+	///  * `scid`: the short channel id,
+	///  * `capacity_sats`: the channel capacity, omitted if unknown.
+	///
+	/// [DOT]: https://graphviz.org/doc/info/lang.html
+	Dot,
+	/// A JSON document of the following schema:
+	///
+	/// ```text
+	/// {
+	///   "nodes": [{
+	///     "node_id": string,            // Hex-encoded public key
+	///     "alias": string | null,       // null if no node announcement was received
+	///     "addresses": [string],        // Empty if no node announcement was received
+	///     "last_update": number | null, // Timestamp of the latest node announcement
+	///     "num_channels": number        // Number of channels of the node in the export
+	///   }],
+	///   "channels": [{
+	///     "short_channel_id": number,
+	///     "node_one": string,           // Hex-encoded public key, always in "nodes"
+	///     "node_two": string,           // Hex-encoded public key, always in "nodes"
+	///     "capacity_sats": number | null,
+	///     "one_to_two": direction | null,
+	///     "two_to_one": direction | null
+	///   }]
+	/// }
+	///
+	/// direction: {
+	///   "enabled": bool,
+	///   "cltv_expiry_delta": number,
+	///   "htlc_minimum_msat": number,
+	///   "htlc_maximum_msat": number,
+	///   "fee_base_msat": number,
+	///   "fee_proportional_millionths": number,
+	///   "last_update": number
+	/// }
+	/// ```
+	///
+	/// Nodes are sorted by `node_id`, channels by `short_channel_id`.
+	Json,
+}
+
+/// Filters which nodes and channels [`ReadOnlyNetworkGraph::export`] includes.
+///
+/// The filters are applied in the order of the fields below, each only considering the nodes and
+/// channels which passed the previous filters. Only channels between two exported nodes are
+/// exported. The default filter exports the whole graph.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GraphExportFilter {
+	/// If set, only channels of at least the given capacity are exported, as well as only nodes
+	/// with at least one such channel. Channels of unknown capacity are excluded.
+	pub min_capacity_sats: Option<u64>,
+	/// If set, only nodes within the given number of hops of the given node are exported.
+	pub neighborhood: Option<(NodeId, u8)>,
+	/// If set, at most the given number of nodes are exported, preferring those with the most
+	/// channels. Ties are broken by the node id.
+	pub max_nodes: Option<usize>,
+}
+
+impl ReadOnlyNetworkGraph<'_> {
+	/// Exports the nodes and channels passing the given `filter` in the given `format`.
+	///
+	/// See [`Self::export_to`] to stream the export to a [`Writer`] instead, which avoids holding
+	/// the whole export in memory for large graphs.
+	pub fn export(&self, format: GraphExportFormat, filter: GraphExportFilter) -> Vec<u8> {
+		let mut writer = VecWriter(Vec::new());
+		self.export_to(&mut writer, format, filter).expect("Writing to a Vec cannot fail");
+		writer.0
+	}
+
+	/// Exports the nodes and channels passing the given `filter` in the given `format`, writing
+	/// them to `writer` one node or channel at a time.
+	pub fn export_to<W: Writer>(
+		&self, writer: &mut W, format: GraphExportFormat, filter: GraphExportFilter,
+	) -> Result<(), io::Error> {
+		let (node_ids, scids) = self.filter_for_export(&filter);
+		match format {
+			GraphExportFormat::Dot => self.write_dot(writer, &node_ids, &scids),
+			GraphExportFormat::Json => self.write_json(writer, &node_ids, &scids),
+		}
+	}
+
+	/// Returns the sorted node ids and short channel ids passing the given filter.
+	fn filter_for_export(&self, filter: &GraphExportFilter) -> (Vec<NodeId>, Vec<u64>) {
+		let channels = self.channels();
+		let mut scids: Vec<u64> = channels
+			.unordered_iter()
+			.filter(|(_, channel)| match filter.min_capacity_sats {
+				Some(min_capacity_sats) => {
+					channel.capacity_sats.map_or(false, |capacity| capacity >= min_capacity_sats)
+				},
+				None => true,
+			})
+			.map(|(scid, _)| *scid)
+			.collect();
+
+		let mut node_ids: HashSet<NodeId> = new_hash_set();
+		if filter.min_capacity_sats.is_some() {
+			for scid in scids.iter() {
+				let channel = channels.get(scid).expect("We just looked it up");
+				node_ids.insert(channel.node_one);
+				node_ids.insert(channel.node_two);
+			}
+		} else {
+			node_ids.extend(self.nodes().unordered_keys().copied());
+		}
+
+		if let Some((center, max_hops)) = filter.neighborhood {
+			let mut reached = new_hash_set();
+			let mut queue = VecDeque::new();
+			if node_ids.contains(&center) {
+				reached.insert(center);
+				queue.push_back((center, 0));
+			}
+			while let Some((node_id, hops)) = queue.pop_front() {
+				if hops == max_hops {
+					continue;
+				}
+				let node = match self.node(&node_id) {
+					Some(node) => node,
+					None => continue,
+				};
+				for channel in node.channels.iter().filter_map(|scid| channels.get(scid)) {
+					if let Some(min_capacity_sats) = filter.min_capacity_sats {
+						if channel
+							.capacity_sats
+							.map_or(true, |capacity| capacity < min_capacity_sats)
+						{
+							continue;
+						}
+					}
+					let peer = if channel.node_one == node_id {
+						channel.node_two
+					} else {
+						channel.node_one
+					};
+					if node_ids.contains(&peer) && reached.insert(peer) {
+						queue.push_back((peer, hops + 1));
+					}
+				}
+			}
+			node_ids = reached;
+		}
+
+		if let Some(max_nodes) = filter.max_nodes {
+			if node_ids.len() > max_nodes {
+				let mut degrees: HashMap<NodeId, usize> = new_hash_map();
+				degrees.extend(node_ids.iter().map(|node_id| (*node_id, 0)));
+				for channel in scids.iter().filter_map(|scid| channels.get(scid)) {
+					if node_ids.contains(&channel.node_one) && node_ids.contains(&channel.node_two)
+					{
+						*degrees.get_mut(&channel.node_one).expect("Inserted above") += 1;
+						*degrees.get_mut(&channel.node_two).expect("Inserted above") += 1;
+					}
+				}
+				let mut by_degree: Vec<(NodeId, usize)> = degrees.into_iter().collect();
+				by_degree.sort_unstable_by(|(a_id, a_degree), (b_id, b_degree)| {
+					b_degree.cmp(a_degree).then_with(|| a_id.cmp(b_id))
+				});
+				node_ids.clear();
+				node_ids.extend(by_degree.into_iter().take(max_nodes).map(|(node_id, _)| node_id));
+			}
+		}
+
+		scids.retain(|scid| {
+			let channel = channels.get(scid).expect("We just looked it up");
+			node_ids.contains(&channel.node_one) && node_ids.contains(&channel.node_two)
+		});
+		scids.sort_unstable();
+		let mut node_ids: Vec<NodeId> = node_ids.into_iter().collect();
+		node_ids.sort_unstable();
+		(node_ids, scids)
+	}
+
+	fn write_dot<W: Writer>(
+		&self, writer: &mut W, node_ids: &[NodeId], scids: &[u64],
+	) -> Result<(), io::Error> {
+		writer.write_all(b"graph lightning {\n")?;
+		let mut line = String::new();
+		for node_id in node_ids {
+			line.clear();
+			let _ = write!(line, "\t\"{}\"", node_id);
+			let alias = self
+				.node(node_id)
+				.and_then(|node| node.announcement_info.as_ref())
+				.map(|info| info.alias().to_string());
+			if let Some(alias) = alias {
+				line.push_str(" [label=\"");
+				push_escaped(&mut line, &alias);
+				line.push_str("\"]");
+			}
+			line.push_str(";\n");
+			writer.write_all(line.as_bytes())?;
+		}
+		for (scid, channel) in scids.iter().filter_map(|scid| Some((scid, self.channel(*scid)?))) {
+			line.clear();
+			let _ = write!(
+				line,
+				"\t\"{}\" -- \"{}\" [scid=\"{}\"",
+				channel.node_one, channel.node_two, scid
+			);
+			if let Some(capacity_sats) = channel.capacity_sats {
+				let _ = write!(line, ", capacity_sats={}", capacity_sats);
+			}
+			line.push_str("];\n");
+			writer.write_all(line.as_bytes())?;
+		}
+		writer.write_all(b"}\n")
+	}
+
+	fn write_json<W: Writer>(
+		&self, writer: &mut W, node_ids: &[NodeId], scids: &[u64],
+	) -> Result<(), io::Error> {
+		let mut num_channels: HashMap<NodeId, usize> = new_hash_map();
+		for channel in scids.iter().filter_map(|scid| self.channel(*scid)) {
+			*num_channels.entry(channel.node_one).or_insert(0) += 1;
+			*num_channels.entry(channel.node_two).or_insert(0) += 1;
+		}
+
+		writer.write_all(b"{\"nodes\":[")?;
+		let mut line = String::new();
+		for (idx, node_id) in node_ids.iter().enumerate() {
+			line.clear();
+			if idx != 0 {
+				line.push(',');
+			}
+			let _ = write!(line, "\n{{\"node_id\":\"{}\",\"alias\":", node_id);
+			let announcement_info =
+				self.node(node_id).and_then(|node| node.announcement_info.as_ref());
+			match announcement_info {
+				Some(info) => {
+					line.push('"');
+					push_escaped(&mut line, &info.alias().to_string());
+					line.push_str("\",\"addresses\":[");
+					for (addr_idx, address) in info.addresses().iter().enumerate() {
+						if addr_idx != 0 {
+							line.push(',');
+						}
+						line.push('"');
+						push_escaped(&mut line, &address.to_string());
+						line.push('"');
+					}
+					let _ = write!(line, "],\"last_update\":{}", info.last_update());
+				},
+				None => line.push_str("null,\"addresses\":[],\"last_update\":null"),
+			}
+			let _ = write!(
+				line,
+				",\"num_channels\":{}}}",
+				num_channels.get(node_id).copied().unwrap_or(0)
+			);
+			writer.write_all(line.as_bytes())?;
+		}
+
+		writer.write_all(b"\n],\"channels\":[")?;
+		for (idx, (scid, channel)) in
+			scids.iter().filter_map(|scid| Some((scid, self.channel(*scid)?))).enumerate()
+		{
+			line.clear();
+			if idx != 0 {
+				line.push(',');
+			}
+			let _ = write!(
+				line,
+				"\n{{\"short_channel_id\":{},\"node_one\":\"{}\",\"node_two\":\"{}\",\"capacity_sats\":",
+				scid, channel.node_one, channel.node_two
+			);
+			match channel.capacity_sats {
+				Some(capacity_sats) => {
+					let _ = write!(line, "{}", capacity_sats);
+				},
+				None => line.push_str("null"),
+			}
+			line.push_str(",\"one_to_two\":");
+			push_json_direction(&mut line, channel.one_to_two.as_ref());
+			line.push_str(",\"two_to_one\":");
+			push_json_direction(&mut line, channel.two_to_one.as_ref());
+			line.push('}');
+			writer.write_all(line.as_bytes())?;
+		}
+		writer.write_all(b"\n]}\n")
+	}
+}
+
+fn push_json_direction(line: &mut String, update: Option<&ChannelUpdateInfo>) {
+	match update {
+		Some(update) => {
+			let _ = write!(
+				line,
+				"{{\"enabled\":{},\"cltv_expiry_delta\":{},\"htlc_minimum_msat\":{},\
+				\"htlc_maximum_msat\":{},\"fee_base_msat\":{},\"fee_proportional_millionths\":{},\
+				\"last_update\":{}}}",
+				update.enabled,
+				update.cltv_expiry_delta,
+				update.htlc_minimum_msat,
+				update.htlc_maximum_msat,
+				update.fees.base_msat,
+				update.fees.proportional_millionths,
+				update.last_update
+			);
+		},
+		None => line.push_str("null"),
+	}
+}
+
+/// Escapes `s` for use within a double-quoted JSON or DOT string.
+fn push_escaped(line: &mut String, s: &str) {
+	for c in s.chars() {
+		match c {
+			'"' => line.push_str("\\\""),
+			'\\' => line.push_str("\\\\"),
+			c if c.is_control() => {
+				let _ = write!(line, "\\u{:04x}", c as u32);
+			},
+			c => line.push(c),
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{GraphExportFilter, GraphExportFormat};
+	use crate::ln::msgs::{SocketAddress, UnsignedNodeAnnouncement};
+	use crate::routing::gossip::{NetworkGraph, NodeAlias, NodeId};
+	use crate::types::features::{ChannelFeatures, NodeFeatures};
+	use crate::util::test_utils::TestLogger;
+
+	use bitcoin::hex::FromHex;
+	use bitcoin::network::Network;
+	use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+	use crate::prelude::*;
+	use crate::sync::Arc;
+
+	#[derive(Debug, PartialEq)]
+	enum JsonValue {
+		Null,
+		Bool(bool),
+		Number(u64),
+		String(String),
+		Array(Vec<JsonValue>),
+		Object(Vec<(String, JsonValue)>),
+	}
+
+	impl JsonValue {
+		fn get(&self, key: &str) -> &JsonValue {
+			match self {
+				JsonValue::Object(fields) => {
+					&fields.iter().find(|(k, _)| k == key).expect("Missing key").1
+				},
+				_ => panic!("Not an object"),
+			}
+		}
+
+		fn keys(&self) -> Vec<&str> {
+			match self {
+				JsonValue::Object(fields) => fields.iter().map(|(k, _)| k.as_str()).collect(),
+				_ => panic!("Not an object"),
+			}
+		}
+
+		fn as_array(&self) -> &Vec<JsonValue> {
+			match self {
+				JsonValue::Array(values) => values,
+				_ => panic!("Not an array"),
+			}
+		}
+
+		fn as_str(&self) -> &str {
+			match self {
+				JsonValue::String(s) => s,
+				_ => panic!("Not a string"),
+			}
+		}
+	}
+
+	/// A minimal JSON parser, only supporting the non-negative integers used by the export.
+	struct JsonParser<'a> {
+		input: &'a [u8],
+		pos: usize,
+	}
+
+	impl<'a> JsonParser<'a> {
+		fn parse(input: &'a [u8]) -> JsonValue {
+			let mut parser = JsonParser { input, pos: 0 };
+			let value = parser.value();
+			parser.skip_whitespace();
+			assert_eq!(parser.pos, input.len(), "Trailing data");
+			value
+		}
+
+		fn skip_whitespace(&mut self) {
+			while self.pos < self.input.len() && self.input[self.pos].is_ascii_whitespace() {
+				self.pos += 1;
+			}
+		}
+
+		fn expect(&mut self, token: &[u8]) {
+			self.skip_whitespace();
+			assert!(self.input[self.pos..].starts_with(token), "Unexpected token");
+			self.pos += token.len();
+		}
+
+		fn peek(&mut self) -> u8 {
+			self.skip_whitespace();
+			self.input[self.pos]
+		}
+
+		fn value(&mut self) -> JsonValue {
+			match self.peek() {
+				b'n' => {
+					self.expect(b"null");
+					JsonValue::Null
+				},
+				b't' => {
+					self.expect(b"true");
+					JsonValue::Bool(true)
+				},
+				b'f' => {
+					self.expect(b"false");
+					JsonValue::Bool(false)
+				},
+				b'"' => JsonValue::String(self.string()),
+				b'[' => {
+					self.expect(b"[");
+					let mut values = Vec::new();
+					if self.peek() != b']' {
+						loop {
+							values.push(self.value());
+							if self.peek() == b']' {
+								break;
+							}
+							self.expect(b",");
+						}
+					}
+					self.expect(b"]");
+					JsonValue::Array(values)
+				},
+				b'{' => {
+					self.expect(b"{");
+					let mut fields = Vec::new();
+					if self.peek() != b'}' {
+						loop {
+							self.skip_whitespace();
+							let key = self.string();
+							self.expect(b":");
+							fields.push((key, self.value()));
+							if self.peek() == b'}' {
+								break;
+							}
+							self.expect(b",");
+						}
+					}
+					self.expect(b"}");
+					JsonValue::Object(fields)
+				},
+				c if c.is_ascii_digit() => {
+					let start = self.pos;
+					while self.pos < self.input.len() && self.input[self.pos].is_ascii_digit() {
+						self.pos += 1;
+					}
+					let digits = core::str::from_utf8(&self.input[start..self.pos]).unwrap();
+					JsonValue::Number(digits.parse().unwrap())
+				},
+				_ => panic!("Unexpected character"),
+			}
+		}
+
+		fn string(&mut self) -> String {
+			assert_eq!(self.input[self.pos], b'"');
+			self.pos += 1;
+			let mut bytes = Vec::new();
+			loop {
+				match self.input[self.pos] {
+					b'"' => break,
+					b'\\' => {
+						self.pos += 1;
+						match self.input[self.pos] {
+							b'"' => bytes.push(b'"'),
+							b'\\' => bytes.push(b'\\'),
+							b'u' => {
+								let hex =
+									core::str::from_utf8(&self.input[self.pos + 1..self.pos + 5]);
+								let c = u32::from_str_radix(hex.unwrap(), 16).unwrap();
+								let mut buf = [0; 4];
+								let c = core::char::from_u32(c).unwrap();
+								bytes.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+								self.pos += 4;
+							},
+							_ => panic!("Unexpected escape"),
+						}
+					},
+					b => {
+						assert!(b >= 0x20, "Unescaped control character");
+						bytes.push(b);
+					},
+				}
+				self.pos += 1;
+			}
+			self.pos += 1;
+			String::from_utf8(bytes).unwrap()
+		}
+	}
+
+	fn assert_matches_schema(json: &JsonValue) {
+		assert_eq!(json.keys(), vec!["nodes", "channels"]);
+		let nodes = json.get("nodes").as_array();
+		let node_ids: Vec<&str> = nodes.iter().map(|node| node.get("node_id").as_str()).collect();
+		for node in nodes {
+			assert_eq!(
+				node.keys(),
+				vec!["node_id", "alias", "addresses", "last_update", "num_channels"]
+			);
+			assert_eq!(node.get("node_id").as_str().len(), 66);
+			match (node.get("alias"), node.get("last_update")) {
+				(JsonValue::String(_), JsonValue::Number(_)) => {},
+				(JsonValue::Null, JsonValue::Null) => {
+					assert!(node.get("addresses").as_array().is_empty())
+				},
+				_ => panic!("Unexpected alias or last_update"),
+			}
+			assert!(node.get("addresses").as_array().iter().all(|a| !a.as_str().is_empty()));
+			assert!(matches!(node.get("num_channels"), JsonValue::Number(_)));
+		}
+		for channel in json.get("channels").as_array() {
+			assert_eq!(
+				channel.keys(),
+				vec![
+					"short_channel_id",
+					"node_one",
+					"node_two",
+					"capacity_sats",
+					"one_to_two",
+					"two_to_one"
+				]
+			);
+			assert!(matches!(channel.get("short_channel_id"), JsonValue::Number(_)));
+			assert!(node_ids.contains(&channel.get("node_one").as_str()));
+			assert!(node_ids.contains(&channel.get("node_two").as_str()));
+			assert!(matches!(channel.get("capacity_sats"), JsonValue::Number(_) | JsonValue::Null));
+			for direction in [channel.get("one_to_two"), channel.get("two_to_one")] {
+				if *direction == JsonValue::Null {
+					continue;
+				}
+				assert_eq!(
+					direction.keys(),
+					vec![
+						"enabled",
+						"cltv_expiry_delta",
+						"htlc_minimum_msat",
+						"htlc_maximum_msat",
+						"fee_base_msat",
+						"fee_proportional_millionths",
+						"last_update"
+					]
+				);
+				assert!(matches!(direction.get("enabled"), JsonValue::Bool(_)));
+			}
+		}
+	}
+
+	/// Checks that `dot` is a well-formed undirected DOT graph in the shape written by the export,
+	/// returning its node ids and edges' short channel ids.
+	fn parse_dot(dot: &str) -> (Vec<String>, Vec<u64>) {
+		let node_regex =
+			regex::Regex::new(r#"^\t"([0-9a-f]{66})"( \[label="(?:[^"\\]|\\.)*"\])?;$"#).unwrap();
+		let edge_regex = regex::Regex::new(
+			r#"^\t"([0-9a-f]{66})" -- "([0-9a-f]{66})" \[scid="([0-9]+)"(, capacity_sats=[0-9]+)?\];$"#,
+		)
+		.unwrap();
+		let mut lines = dot.lines();
+		assert_eq!(lines.next(), Some("graph lightning {"));
+		assert_eq!(lines.next_back(), Some("}"));
+		let mut node_ids = Vec::new();
+		let mut scids = Vec::new();
+		for line in lines {
+			if let Some(captures) = node_regex.captures(line) {
+				assert!(scids.is_empty(), "Nodes must precede edges");
+				node_ids.push(captures[1].to_owned());
+			} else if let Some(captures) = edge_regex.captures(line) {
+				assert!(node_ids.iter().any(|node_id| *node_id == captures[1]));
+				assert!(node_ids.iter().any(|node_id| *node_id == captures[2]));
+				scids.push(captures[3].parse().unwrap());
+			} else {
+				panic!("Unexpected line {}", line);
+			}
+		}
+		(node_ids, scids)
+	}
+
+	fn node_id(idx: u8) -> NodeId {
+		let secp_ctx = Secp256k1::new();
+		let secret_key = SecretKey::from_slice(&[idx; 32]).unwrap();
+		NodeId::from_pubkey(&PublicKey::from_secret_key(&secp_ctx, &secret_key))
+	}
+
+	// Builds the following graph, with channel capacities in sats:
+	//
+	//   1 -(100k)- 2 -(200k)- 3 -(300k)- 4 -(unknown)- 5
+	//              |          |
+	//           (50k)      (500k)
+	//              |          |
+	//              6 -(150k)--+
+	fn build_graph() -> NetworkGraph<Arc<TestLogger>> {
+		let logger = Arc::new(TestLogger::new());
+		let network_graph = NetworkGraph::new(Network::Testnet, logger);
+		let channels = [
+			(1, 1, 2, Some(100_000)),
+			(2, 2, 3, Some(200_000)),
+			(3, 3, 4, Some(300_000)),
+			(4, 4, 5, None),
+			(5, 2, 6, Some(50_000)),
+			(6, 3, 6, Some(500_000)),
+			(7, 6, 3, Some(150_000)),
+		];
+		for (scid, node_one, node_two, capacity_sats) in channels {
+			network_graph
+				.add_channel_from_partial_announcement(
+					scid,
+					capacity_sats,
+					0,
+					ChannelFeatures::empty(),
+					node_id(node_one),
+					node_id(node_two),
+				)
+				.unwrap();
+		}
+		let mut alias = [0; 32];
+		alias[..11].copy_from_slice(b"say \"hi\"\\\n!");
+		network_graph
+			.update_node_from_unsigned_announcement(&UnsignedNodeAnnouncement {
+				features: NodeFeatures::empty(),
+				timestamp: 42,
+				node_id: node_id(2),
+				rgb: [0; 3],
+				alias: NodeAlias(alias),
+				addresses: vec![SocketAddress::TcpIpV4 { addr: [127, 0, 0, 1], port: 9735 }],
+				excess_address_data: Vec::new(),
+				excess_data: Vec::new(),
+			})
+			.unwrap();
+		network_graph
+	}
+
+	fn exported_node_ids(
+		graph: &NetworkGraph<Arc<TestLogger>>, filter: GraphExportFilter,
+	) -> Vec<NodeId> {
+		let dot = graph.read_only().export(GraphExportFormat::Dot, filter);
+		let (node_ids, _) = parse_dot(&String::from_utf8(dot).unwrap());
+		node_ids
+			.iter()
+			.map(|node_id| NodeId::from_slice(&<Vec<u8>>::from_hex(node_id).unwrap()).unwrap())
+			.collect()
+	}
+
+	#[test]
+	fn exports_valid_json() {
+		let network_graph = build_graph();
+		let export =
+			network_graph.read_only().export(GraphExportFormat::Json, GraphExportFilter::default());
+		let json = JsonParser::parse(&export);
+		assert_matches_schema(&json);
+
+		let nodes = json.get("nodes").as_array();
+		assert_eq!(nodes.len(), 6);
+		let node_two = nodes
+			.iter()
+			.find(|node| node.get("node_id").as_str() == node_id(2).to_string())
+			.unwrap();
+		assert_eq!(node_two.get("alias").as_str(), "say \"hi\"\\\u{fffd}!");
+		assert_eq!(node_two.get("addresses").as_array()[0].as_str(), "127.0.0.1:9735");
+		assert_eq!(*node_two.get("last_update"), JsonValue::Number(42));
+		assert_eq!(*node_two.get("num_channels"), JsonValue::Number(3));
+
+		let channels = json.get("channels").as_array();
+		assert_eq!(channels.len(), 7);
+		assert_eq!(*channels[3].get("short_channel_id"), JsonValue::Number(4));
+		assert_eq!(*channels[3].get("capacity_sats"), JsonValue::Null);
+		assert_eq!(*channels[3].get("one_to_two"), JsonValue::Null);
+	}
+
+	#[test]
+	fn exports_valid_dot() {
+		let network_graph = build_graph();
+		let export =
+			network_graph.read_only().export(GraphExportFormat::Dot, GraphExportFilter::default());
+		let dot = String::from_utf8(export).unwrap();
+		let (node_ids, scids) = parse_dot(&dot);
+		assert_eq!(node_ids.len(), 6);
+		assert_eq!(scids, vec![1, 2, 3, 4, 5, 6, 7]);
+		assert!(dot.contains("[label=\"say \\\"hi\\\"\\\\\u{fffd}!\"]"));
+	}
+
+	#[test]
+	fn export_streams_to_writer() {
+		let network_graph = build_graph();
+		let read_only = network_graph.read_only();
+		for format in [GraphExportFormat::Dot, GraphExportFormat::Json] {
+			let mut writer = crate::util::ser::VecWriter(Vec::new());
+			read_only.export_to(&mut writer, format, GraphExportFilter::default()).unwrap();
+			assert_eq!(writer.0, read_only.export(format, GraphExportFilter::default()));
+		}
+	}
+
+	#[test]
+	fn export_filters() {
+		let network_graph = build_graph();
+
+		// Channels below 150k sats (and of unknown capacity) are dropped, as is node 5 which no
+		// longer has any channel.
+		let filter =
+			GraphExportFilter { min_capacity_sats: Some(150_000), ..GraphExportFilter::default() };
+		let export = network_graph.read_only().export(GraphExportFormat::Json, filter.clone());
+		let json = JsonParser::parse(&export);
+		assert_matches_schema(&json);
+		let scids: Vec<&JsonValue> =
+			json.get("channels").as_array().iter().map(|c| c.get("short_channel_id")).collect();
+		assert_eq!(
+			scids,
+			vec![
+				&JsonValue::Number(2),
+				&JsonValue::Number(3),
+				&JsonValue::Number(6),
+				&JsonValue::Number(7)
+			]
+		);
+		let mut expected = vec![node_id(2), node_id(3), node_id(4), node_id(6)];
+		expected.sort_unstable();
+		assert_eq!(exported_node_ids(&network_graph, filter), expected);
+
+		// The neighborhood of node 1 within two hops.
+		let filter = GraphExportFilter {
+			neighborhood: Some((node_id(1), 2)),
+			..GraphExportFilter::default()
+		};
+		let mut expected = vec![node_id(1), node_id(2), node_id(3), node_id(6)];
+		expected.sort_unstable();
+		assert_eq!(exported_node_ids(&network_graph, filter), expected);
+
+		// The neighborhood is only walked over channels passing the capacity filter.
+		let filter = GraphExportFilter {
+			min_capacity_sats: Some(150_000),
+			neighborhood: Some((node_id(1), 2)),
+			..GraphExportFilter::default()
+		};
+		assert!(exported_node_ids(&network_graph, filter).is_empty());
+
+		// Nodes 3 and 6 have the most channels, followed by node 2.
+		let filter = GraphExportFilter { max_nodes: Some(3), ..GraphExportFilter::default() };
+		let mut expected = vec![node_id(2), node_id(3), node_id(6)];
+		expected.sort_unstable();
+		assert_eq!(exported_node_ids(&network_graph, filter.clone()), expected);
+		let export = network_graph.read_only().export(GraphExportFormat::Dot, filter);
+		let (_, scids) = parse_dot(&String::from_utf8(export).unwrap());
+		assert_eq!(scids, vec![2, 5, 6, 7]);
+	}
+}
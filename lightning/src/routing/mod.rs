@@ -10,7 +10,9 @@
 //! Structs and impls for receiving messages about the network and storing the topology live here.
 
 pub mod gossip;
+pub mod graph_export;
 mod log_approx;
+pub mod privacy;
 pub mod router;
 pub mod scoring;
 #[cfg(test)]
@@ -0,0 +1,401 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Utilities to audit how much a [`Route`] may reveal about the sender of a payment.
+//!
+//! [`audit_route`] reports characteristics of each [`Path`] which may allow intermediate nodes to
+//! infer the sender or recipient of a payment, as well as a heuristic score summarizing them. The
+//! [`DefaultRouter`] may be configured to act on low-scoring routes via
+//! [`DefaultRouter::with_privacy_policy`].
+//!
+//! [`DefaultRouter`]: crate::routing::router::DefaultRouter
+//! [`DefaultRouter::with_privacy_policy`]: crate::routing::router::DefaultRouter::with_privacy_policy
+
+use crate::ln::channelmanager::MIN_FINAL_CLTV_EXPIRY_DELTA;
+use crate::routing::gossip::{NetworkGraph, NodeId, ReadOnlyNetworkGraph};
+use crate::routing::router::{Path, Route};
+use crate::util::logger::Logger;
+
+use crate::prelude::*;
+use core::ops::Deref;
+
+/// The maximum privacy score of a [`Path`] or [`Route`].
+pub const MAX_PRIVACY_SCORE: u8 = 100;
+
+const SINGLE_HOP_PENALTY: u8 = 40;
+const IDENTIFYING_FIRST_HOP_PENALTY: u8 = 30;
+const SURVEILLANCE_HOP_PENALTY: u8 = 25;
+const COMMON_FINAL_CLTV_EXPIRY_DELTA_PENALTY: u8 = 15;
+const ROUND_AMOUNT_PENALTY: u8 = 10;
+
+/// Configuration for [`audit_route`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PrivacyAuditConfig {
+	/// Nodes known or suspected to be collecting payment data, e.g., large routing nodes operated
+	/// by chain analysis companies.
+	pub surveillance_nodes: HashSet<NodeId>,
+	/// Final CLTV expiry deltas which are commonly used as defaults by recipients and thus allow
+	/// intermediate nodes to guess that the next hop is the recipient.
+	///
+	/// Defaults to the BOLT 11 default of 18 blocks, [`MIN_FINAL_CLTV_EXPIRY_DELTA`], and other
+	/// values commonly used by implementations.
+	pub common_final_cltv_expiry_deltas: Vec<u32>,
+	/// Payment amounts which are a multiple of this value are considered round, and thus more
+	/// likely to be recognized across hops or correlated with an invoice.
+	///
+	/// Defaults to 1,000,000 msat, i.e., 1,000 sats. Set to 0 to never consider amounts round.
+	pub round_amount_msat: u64,
+}
+
+impl Default for PrivacyAuditConfig {
+	fn default() -> Self {
+		Self {
+			surveillance_nodes: new_hash_set(),
+			common_final_cltv_expiry_deltas: vec![
+				18,
+				MIN_FINAL_CLTV_EXPIRY_DELTA as u32,
+				40,
+				80,
+				144,
+			],
+			round_amount_msat: 1_000_000,
+		}
+	}
+}
+
+/// The privacy-relevant characteristics of a single [`Path`], as reported by [`audit_route`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PathAudit {
+	/// The number of hops in the path, including any hops in a blinded path.
+	pub hop_count: usize,
+	/// The intermediate nodes of the path which are in
+	/// [`PrivacyAuditConfig::surveillance_nodes`].
+	///
+	/// The recipient is not included as it learns about the payment regardless.
+	pub surveillance_hops: Vec<NodeId>,
+	/// Whether the amount received by the recipient is a multiple of
+	/// [`PrivacyAuditConfig::round_amount_msat`].
+	pub round_amount: bool,
+	/// The final hop's CLTV expiry delta, including any "shadow" offset, or `None` for paths to a
+	/// blinded path, where it is not known to us.
+	pub final_cltv_expiry_delta: Option<u32>,
+	/// Whether [`Self::final_cltv_expiry_delta`] is one of
+	/// [`PrivacyAuditConfig::common_final_cltv_expiry_deltas`].
+	pub common_final_cltv_expiry_delta: bool,
+	/// Whether the first hop's channel allows it to infer that we are the sender, i.e., because the
+	/// channel is unannounced or is our only announced channel.
+	pub identifying_first_hop: bool,
+}
+
+impl PathAudit {
+	/// Returns a heuristic score between 0 and [`MAX_PRIVACY_SCORE`] for the path, where higher
+	/// is better.
+	///
+	/// The score starts at [`MAX_PRIVACY_SCORE`] and is reduced for each characteristic which may
+	/// allow intermediate nodes to infer the sender or recipient, with single-hop paths and
+	/// identifying first hops weighing the most.
+	pub fn privacy_score(&self) -> u8 {
+		let mut score = MAX_PRIVACY_SCORE;
+		if self.hop_count <= 1 {
+			score = score.saturating_sub(SINGLE_HOP_PENALTY);
+		}
+		if self.identifying_first_hop {
+			score = score.saturating_sub(IDENTIFYING_FIRST_HOP_PENALTY);
+		}
+		for _ in self.surveillance_hops.iter() {
+			score = score.saturating_sub(SURVEILLANCE_HOP_PENALTY);
+		}
+		if self.common_final_cltv_expiry_delta {
+			score = score.saturating_sub(COMMON_FINAL_CLTV_EXPIRY_DELTA_PENALTY);
+		}
+		if self.round_amount {
+			score = score.saturating_sub(ROUND_AMOUNT_PENALTY);
+		}
+		score
+	}
+}
+
+/// The privacy-relevant characteristics of a [`Route`], as reported by [`audit_route`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RouteAudit {
+	/// The audits of each of the route's [`Route::paths`], in the same order.
+	pub paths: Vec<PathAudit>,
+}
+
+impl RouteAudit {
+	/// Returns the lowest [`PathAudit::privacy_score`] of the route's paths, as the least private
+	/// path determines what may be learned about the payment.
+	pub fn privacy_score(&self) -> u8 {
+		self.paths.iter().map(|path| path.privacy_score()).min().unwrap_or(MAX_PRIVACY_SCORE)
+	}
+}
+
+/// Reports the privacy-relevant characteristics of each path of the given [`Route`], as found via
+/// [`find_route`] from our node, using the given [`NetworkGraph`] to determine whether the first
+/// hops identify us.
+///
+/// Note that the audit is heuristic and cannot account for all information revealed by a payment,
+/// e.g., timing or fee rates.
+///
+/// [`find_route`]: crate::routing::router::find_route
+pub fn audit_route<L: Deref>(
+	route: &Route, network_graph: &NetworkGraph<L>, config: &PrivacyAuditConfig,
+) -> RouteAudit
+where
+	L::Target: Logger,
+{
+	audit_route_internal(route, &network_graph.read_only(), config)
+}
+
+pub(crate) fn audit_route_internal(
+	route: &Route, network_graph: &ReadOnlyNetworkGraph, config: &PrivacyAuditConfig,
+) -> RouteAudit {
+	let paths = route.paths.iter().map(|path| audit_path(path, network_graph, config)).collect();
+	RouteAudit { paths }
+}
+
+fn audit_path(
+	path: &Path, network_graph: &ReadOnlyNetworkGraph, config: &PrivacyAuditConfig,
+) -> PathAudit {
+	// The introduction node of a blinded path is the last unblinded hop and also the first
+	// blinded hop.
+	let hop_count = match &path.blinded_tail {
+		Some(tail) => path.hops.len() + tail.hops.len().saturating_sub(1),
+		None => path.hops.len(),
+	};
+
+	let intermediate_hops = match &path.blinded_tail {
+		Some(_) => &path.hops[..],
+		None => path.hops.split_last().map_or(&[][..], |(_, hops)| hops),
+	};
+	let surveillance_hops = intermediate_hops
+		.iter()
+		.map(|hop| NodeId::from_pubkey(&hop.pubkey))
+		.filter(|node_id| config.surveillance_nodes.contains(node_id))
+		.collect();
+
+	let round_amount =
+		config.round_amount_msat != 0 && path.final_value_msat() % config.round_amount_msat == 0;
+
+	let final_cltv_expiry_delta = path.final_cltv_expiry_delta();
+	let common_final_cltv_expiry_delta = final_cltv_expiry_delta
+		.map_or(false, |delta| config.common_final_cltv_expiry_deltas.contains(&delta));
+
+	let identifying_first_hop = match path.hops.first() {
+		Some(first_hop) => {
+			let first_hop_node_id = NodeId::from_pubkey(&first_hop.pubkey);
+			network_graph
+				.channel(first_hop.short_channel_id)
+				.and_then(|channel| {
+					if channel.node_one == first_hop_node_id {
+						Some(channel.node_two)
+					} else if channel.node_two == first_hop_node_id {
+						Some(channel.node_one)
+					} else {
+						None
+					}
+				})
+				.and_then(|our_node_id| network_graph.node(&our_node_id))
+				.map_or(true, |our_node| our_node.channels.len() <= 1)
+		},
+		None => false,
+	};
+
+	PathAudit {
+		hop_count,
+		surveillance_hops,
+		round_amount,
+		final_cltv_expiry_delta,
+		common_final_cltv_expiry_delta,
+		identifying_first_hop,
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::routing::router::{
+		DefaultRouter, InFlightHtlcs, LowPrivacyRouteAction, PaymentParameters, RouteHop,
+		RouteParameters, RoutePrivacyPolicy, Router,
+	};
+	use crate::routing::test_utils::{build_graph, build_line_graph, get_nodes};
+	use crate::sign::RandomBytes;
+	use crate::sync::{Arc, RwLock};
+	use crate::types::features::{ChannelFeatures, NodeFeatures};
+	use crate::util::test_utils::TestScorer;
+
+	use bitcoin::secp256k1::PublicKey;
+
+	fn route_hop(pubkey: PublicKey, short_channel_id: u64, fee_msat: u64, cltv: u32) -> RouteHop {
+		RouteHop {
+			pubkey,
+			node_features: NodeFeatures::empty(),
+			short_channel_id,
+			channel_features: ChannelFeatures::empty(),
+			fee_msat,
+			cltv_expiry_delta: cltv,
+			maybe_announced_channel: true,
+		}
+	}
+
+	fn route(hops: Vec<RouteHop>) -> Route {
+		Route { paths: vec![Path { hops, blinded_tail: None }], route_params: None }
+	}
+
+	#[test]
+	fn test_audit_single_hop_route() {
+		// Our only channel is the one with node0 in the line graph.
+		let (secp_ctx, network_graph, ..) = build_line_graph();
+		let (_, _, _, nodes) = get_nodes(&secp_ctx);
+		let config = PrivacyAuditConfig::default();
+
+		let route = route(vec![route_hop(nodes[0], 1, 5_000_000, 40)]);
+		let audit = audit_route(&route, &network_graph, &config);
+		assert_eq!(
+			audit.paths,
+			vec![PathAudit {
+				hop_count: 1,
+				surveillance_hops: vec![],
+				round_amount: true,
+				final_cltv_expiry_delta: Some(40),
+				common_final_cltv_expiry_delta: true,
+				identifying_first_hop: true,
+			}]
+		);
+		assert_eq!(audit.privacy_score(), 5);
+	}
+
+	#[test]
+	fn test_audit_multi_hop_route() {
+		// Our node has three announced channels in the graph, so the first hop doesn't identify us.
+		let (secp_ctx, network_graph, ..) = build_graph();
+		let (_, _, _, nodes) = get_nodes(&secp_ctx);
+		let mut config = PrivacyAuditConfig::default();
+
+		let route = route(vec![
+			route_hop(nodes[1], 2, 100, 144),
+			route_hop(nodes[2], 4, 0, 40),
+			route_hop(nodes[4], 6, 123_456, 77),
+		]);
+		let audit = audit_route(&route, &network_graph, &config);
+		assert_eq!(
+			audit.paths,
+			vec![PathAudit {
+				hop_count: 3,
+				surveillance_hops: vec![],
+				round_amount: false,
+				final_cltv_expiry_delta: Some(77),
+				common_final_cltv_expiry_delta: false,
+				identifying_first_hop: false,
+			}]
+		);
+		assert_eq!(audit.privacy_score(), MAX_PRIVACY_SCORE);
+
+		// Only intermediate hops are reported as surveillance hops.
+		config.surveillance_nodes.insert(NodeId::from_pubkey(&nodes[2]));
+		config.surveillance_nodes.insert(NodeId::from_pubkey(&nodes[4]));
+		let audit = audit_route(&route, &network_graph, &config);
+		assert_eq!(audit.paths[0].surveillance_hops, vec![NodeId::from_pubkey(&nodes[2])]);
+		assert_eq!(audit.privacy_score(), MAX_PRIVACY_SCORE - SURVEILLANCE_HOP_PENALTY);
+
+		// Disabling round amounts and adding 77 to the common deltas flags the final CLTV delta.
+		config.round_amount_msat = 0;
+		config.common_final_cltv_expiry_deltas.push(77);
+		let audit = audit_route(&route, &network_graph, &config);
+		assert!(!audit.paths[0].round_amount);
+		assert!(audit.paths[0].common_final_cltv_expiry_delta);
+	}
+
+	#[test]
+	fn test_audit_route_scores_worst_path() {
+		let (secp_ctx, network_graph, ..) = build_graph();
+		let (_, _, _, nodes) = get_nodes(&secp_ctx);
+		let config = PrivacyAuditConfig::default();
+
+		// A first hop over a channel unknown to the graph identifies us as the sender.
+		let route = Route {
+			paths: vec![
+				Path {
+					hops: vec![route_hop(nodes[0], 1, 0, 144), route_hop(nodes[2], 3, 250_000, 77)],
+					blinded_tail: None,
+				},
+				Path {
+					hops: vec![
+						route_hop(nodes[7], 42, 0, 144),
+						route_hop(nodes[2], 13, 750_000, 77),
+					],
+					blinded_tail: None,
+				},
+			],
+			route_params: None,
+		};
+		let audit = audit_route(&route, &network_graph, &config);
+		assert!(!audit.paths[0].identifying_first_hop);
+		assert!(audit.paths[1].identifying_first_hop);
+		assert_eq!(audit.paths[0].privacy_score(), MAX_PRIVACY_SCORE);
+		assert_eq!(audit.privacy_score(), MAX_PRIVACY_SCORE - IDENTIFYING_FIRST_HOP_PENALTY);
+
+		assert_eq!(RouteAudit { paths: vec![] }.privacy_score(), MAX_PRIVACY_SCORE);
+	}
+
+	#[test]
+	fn test_default_router_privacy_policy() {
+		let (secp_ctx, network_graph, _, _, logger) = build_line_graph();
+		let (_, our_id, _, nodes) = get_nodes(&secp_ctx);
+		let scorer = RwLock::new(TestScorer::new());
+		let entropy_source = Arc::new(RandomBytes::new([42; 32]));
+		let new_router = || {
+			DefaultRouter::new(
+				Arc::clone(&network_graph),
+				Arc::clone(&logger),
+				Arc::clone(&entropy_source),
+				&scorer,
+				(),
+			)
+			.with_max_shadow_cltv_expiry_delta_offset(0)
+		};
+
+		// Paying node2 goes through our only channel with a round amount and a common final CLTV
+		// expiry delta, which isn't offset.
+		let payment_params = PaymentParameters::from_node_id(nodes[2], 40);
+		let route_params =
+			RouteParameters::from_payment_params_and_value(payment_params, 1_000_000);
+		let route =
+			new_router().find_route(&our_id, &route_params, None, InFlightHtlcs::new()).unwrap();
+		assert_eq!(route.paths[0].hops.len(), 3);
+		assert_eq!(route.paths[0].final_cltv_expiry_delta(), Some(40));
+		let audit = audit_route(&route, &network_graph, &PrivacyAuditConfig::default());
+		assert_eq!(audit.privacy_score(), 45);
+
+		let policy = |min_privacy_score, action| RoutePrivacyPolicy {
+			audit_config: PrivacyAuditConfig::default(),
+			min_privacy_score,
+			action,
+		};
+		let router = new_router().with_privacy_policy(policy(45, LowPrivacyRouteAction::Reject));
+		assert_eq!(
+			router.find_route(&our_id, &route_params, None, InFlightHtlcs::new()),
+			Ok(route)
+		);
+
+		let router = new_router().with_privacy_policy(policy(46, LowPrivacyRouteAction::Reject));
+		assert_eq!(
+			router.find_route(&our_id, &route_params, None, InFlightHtlcs::new()),
+			Err("Route did not meet the minimum privacy score")
+		);
+
+		// Without any shadow offset, re-randomizing can't improve the score.
+		let action = LowPrivacyRouteAction::Rerandomize { max_attempts: 3 };
+		let router = new_router().with_privacy_policy(policy(46, action));
+		assert_eq!(
+			router.find_route(&our_id, &route_params, None, InFlightHtlcs::new()),
+			Err("Route did not meet the minimum privacy score")
+		);
+	}
+}
@@ -22,19 +22,22 @@ use crate::ln::channel_state::ChannelDetails;
 use crate::ln::channelmanager::{PaymentId, RecipientOnionFields, MIN_FINAL_CLTV_EXPIRY_DELTA};
 use crate::ln::msgs::{DecodeError, MAX_VALUE_MSAT};
 use crate::ln::onion_utils;
+use crate::ln::types::ChannelId;
 use crate::offers::invoice::Bolt12Invoice;
 #[cfg(async_payments)]
 use crate::offers::static_invoice::StaticInvoice;
 use crate::routing::gossip::{
 	DirectedChannelInfo, EffectiveCapacity, NetworkGraph, NodeId, ReadOnlyNetworkGraph,
 };
+use crate::routing::privacy::{audit_route_internal, PrivacyAuditConfig};
 use crate::routing::scoring::{ChannelUsage, LockableScore, ScoreLookUp};
 use crate::sign::EntropySource;
-use crate::sync::Mutex;
+use crate::sync::{Arc, Mutex};
 use crate::types::features::{
 	BlindedHopFeatures, Bolt11InvoiceFeatures, Bolt12InvoiceFeatures, ChannelFeatures, NodeFeatures,
 };
 use crate::types::payment::{PaymentHash, PaymentPreimage};
+use crate::util::anchor_channel_reserves::ChannelReserveHealth;
 use crate::util::logger::Logger;
 use crate::util::ser::{Readable, ReadableArgs, Writeable, Writer};
 
@@ -73,6 +76,10 @@ pub struct DefaultRouter<
 	entropy_source: ES,
 	scorer: S,
 	score_params: SP,
+	max_shadow_cltv_expiry_delta_offset: u32,
+	privacy_policy: Option<RoutePrivacyPolicy>,
+	reserve_health:
+		Option<(Arc<dyn ChannelReserveHealthSource + Send + Sync>, ReserveHealthPenalties)>,
 }
 
 impl<
@@ -92,7 +99,48 @@ where
 	pub fn new(
 		network_graph: G, logger: L, entropy_source: ES, scorer: S, score_params: SP,
 	) -> Self {
-		Self { network_graph, logger, entropy_source, scorer, score_params }
+		Self {
+			network_graph,
+			logger,
+			entropy_source,
+			scorer,
+			score_params,
+			max_shadow_cltv_expiry_delta_offset: DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET,
+			privacy_policy: None,
+			reserve_health: None,
+		}
+	}
+
+	/// Sets the maximum "shadow" CLTV expiry delta offset added to the final hop of each path to
+	/// deter intermediate nodes from inferring their position in the path, defaulting to
+	/// [`DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET`].
+	///
+	/// Larger offsets improve privacy at the cost of locking up liquidity for longer in the worst
+	/// case. Setting this to 0 disables shadow offsets entirely.
+	pub fn with_max_shadow_cltv_expiry_delta_offset(mut self, max_offset: u32) -> Self {
+		self.max_shadow_cltv_expiry_delta_offset = max_offset;
+		self
+	}
+
+	/// Sets a [`RoutePrivacyPolicy`] which each found route is audited against, see
+	/// [`audit_route`].
+	///
+	/// [`audit_route`]: crate::routing::privacy::audit_route
+	pub fn with_privacy_policy(mut self, privacy_policy: RoutePrivacyPolicy) -> Self {
+		self.privacy_policy = Some(privacy_policy);
+		self
+	}
+
+	/// Sets a [`ChannelReserveHealthSource`] which is consulted for the anchor reserve health of
+	/// our first hops, penalizing those with an unhealthy reserve as configured in `penalties`.
+	///
+	/// See [`ScorerAccountingForReserveHealth`] for details.
+	pub fn with_reserve_health_source(
+		mut self, source: Arc<dyn ChannelReserveHealthSource + Send + Sync>,
+		penalties: ReserveHealthPenalties,
+	) -> Self {
+		self.reserve_health = Some((source, penalties));
+		self
 	}
 }
 
@@ -117,13 +165,49 @@ where
 		first_hops: Option<&[&ChannelDetails]>,
 		inflight_htlcs: InFlightHtlcs
 	) -> Result<Route, &'static str> {
-		let random_seed_bytes = self.entropy_source.get_secure_random_bytes();
-		find_route(
-			payer, params, &self.network_graph, first_hops, &*self.logger,
-			&ScorerAccountingForInFlightHtlcs::new(self.scorer.read_lock(), &inflight_htlcs),
-			&self.score_params,
-			&random_seed_bytes
-		)
+		let mut random_seed_bytes = self.entropy_source.get_secure_random_bytes();
+		let scorer = ScorerAccountingForInFlightHtlcs::new(self.scorer.read_lock(), &inflight_htlcs);
+		let graph_lock = self.network_graph.read_only();
+		let route = match &self.reserve_health {
+			Some((source, penalties)) => {
+				let scorer = ScorerAccountingForReserveHealth::new(&scorer, &**source, *penalties);
+				get_route(
+					payer, params, &graph_lock, first_hops, &*self.logger, &scorer,
+					&self.score_params, &random_seed_bytes
+				)?
+			},
+			None => get_route(
+				payer, params, &graph_lock, first_hops, &*self.logger, &scorer, &self.score_params,
+				&random_seed_bytes
+			)?,
+		};
+
+		let mut attempts = 0;
+		loop {
+			let mut offset_route = route.clone();
+			add_random_cltv_offset(&mut offset_route, &params.payment_params, &graph_lock,
+				&random_seed_bytes, self.max_shadow_cltv_expiry_delta_offset);
+
+			let policy = match &self.privacy_policy {
+				Some(policy) => policy,
+				None => return Ok(offset_route),
+			};
+			let privacy_score = audit_route_internal(&offset_route, &graph_lock, &policy.audit_config)
+				.privacy_score();
+			if privacy_score >= policy.min_privacy_score {
+				return Ok(offset_route);
+			}
+
+			log_debug!(self.logger, "Found route with privacy score {} below the minimum of {}",
+				privacy_score, policy.min_privacy_score);
+			match policy.action {
+				LowPrivacyRouteAction::Rerandomize { max_attempts } if attempts < max_attempts => {
+					attempts += 1;
+					random_seed_bytes = self.entropy_source.get_secure_random_bytes();
+				},
+				_ => return Err("Route did not meet the minimum privacy score"),
+			}
+		}
 	}
 
 	#[rustfmt::skip]
@@ -219,6 +303,50 @@ where
 			},
 		}
 	}
+
+	fn has_known_channels(&self, node_id: &PublicKey) -> Option<bool> {
+		let network_graph = self.network_graph.read_only();
+		let node = network_graph.node(&NodeId::from_pubkey(node_id));
+		Some(node.map_or(false, |node| !node.channels.is_empty()))
+	}
+}
+
+/// The default maximum "shadow" CLTV expiry delta offset added to the final hop of each path, see
+/// [`DefaultRouter::with_max_shadow_cltv_expiry_delta_offset`].
+pub const DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET: u32 = 3 * 144;
+
+/// A policy applied by [`DefaultRouter`] to routes which don't meet a minimum privacy score as
+/// reported by [`audit_route`].
+///
+/// [`audit_route`]: crate::routing::privacy::audit_route
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RoutePrivacyPolicy {
+	/// The configuration used to audit found routes.
+	pub audit_config: PrivacyAuditConfig,
+	/// The minimum [`RouteAudit::privacy_score`] a route must have to be returned as-is.
+	///
+	/// [`RouteAudit::privacy_score`]: crate::routing::privacy::RouteAudit::privacy_score
+	pub min_privacy_score: u8,
+	/// What to do with routes scoring below [`Self::min_privacy_score`].
+	pub action: LowPrivacyRouteAction,
+}
+
+/// The action taken by [`DefaultRouter`] when a found route doesn't meet
+/// [`RoutePrivacyPolicy::min_privacy_score`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LowPrivacyRouteAction {
+	/// Fail to find a route.
+	Reject,
+	/// Re-draw the route's "shadow" CLTV expiry delta offsets using fresh randomness, up to
+	/// `max_attempts` times, failing to find a route if none of the attempts meet the minimum
+	/// score.
+	///
+	/// Note that this only changes the final CLTV expiry delta of each path and thus cannot
+	/// improve scores penalized for other characteristics, such as the number of hops.
+	Rerandomize {
+		/// The maximum number of times the offsets are re-drawn.
+		max_attempts: u8,
+	},
 }
 
 /// A `Router` that returns a fixed route one time, erroring otherwise. Useful for
@@ -287,6 +415,17 @@ pub trait Router {
 		&self, recipient: PublicKey, first_hops: Vec<ChannelDetails>, tlvs: ReceiveTlvs,
 		amount_msats: Option<u64>, secp_ctx: &Secp256k1<T>,
 	) -> Result<Vec<BlindedPaymentPath>, ()>;
+
+	/// Returns whether any channels of the node with the given `node_id` are known, or `None` if
+	/// this [`Router`] has no such knowledge.
+	///
+	/// Used by [`ChannelManager::estimate_payment_feasibility`] to fail payments to unreachable
+	/// destinations without attempting to find a route.
+	///
+	/// [`ChannelManager::estimate_payment_feasibility`]: crate::ln::channelmanager::ChannelManager::estimate_payment_feasibility
+	fn has_known_channels(&self, _node_id: &PublicKey) -> Option<bool> {
+		None
+	}
 }
 
 /// [`ScoreLookUp`] implementation that factors in in-flight HTLC liquidity.
@@ -348,6 +487,104 @@ where
 	}
 }
 
+/// Provides the anchor reserve health of our channels to [`ScorerAccountingForReserveHealth`].
+///
+/// The health of a channel can be determined with [`get_channel_reserve_health`]. As it is looked
+/// up for each first hop considered during pathfinding, implementations should return cached
+/// values rather than recomputing them.
+///
+/// [`get_channel_reserve_health`]: crate::util::anchor_channel_reserves::get_channel_reserve_health
+pub trait ChannelReserveHealthSource {
+	/// Returns the anchor reserve health of our channel described by `channel`, or `None` if it is
+	/// unknown, e.g., as the channel does not support anchor outputs.
+	fn channel_reserve_health(&self, channel: &ChannelDetails) -> Option<ChannelReserveHealth>;
+}
+
+impl ChannelReserveHealthSource for HashMap<ChannelId, ChannelReserveHealth> {
+	fn channel_reserve_health(&self, channel: &ChannelDetails) -> Option<ChannelReserveHealth> {
+		self.get(&channel.channel_id).copied()
+	}
+}
+
+/// The penalties applied by [`ScorerAccountingForReserveHealth`] to first hops whose anchor
+/// reserve is not [`ChannelReserveHealth::Healthy`].
+///
+/// A penalty of `u64::MAX` excludes such first hops from routes entirely.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ReserveHealthPenalties {
+	/// The penalty applied to first hops whose reserve is [`ChannelReserveHealth::Degraded`].
+	///
+	/// Default value: 50,000 msat
+	pub degraded_penalty_msat: u64,
+	/// The penalty applied to first hops whose reserve is [`ChannelReserveHealth::Critical`].
+	///
+	/// Default value: `u64::MAX`, i.e., such first hops are not used
+	pub critical_penalty_msat: u64,
+}
+
+impl Default for ReserveHealthPenalties {
+	fn default() -> Self {
+		Self { degraded_penalty_msat: 50_000, critical_penalty_msat: u64::MAX }
+	}
+}
+
+/// [`ScoreLookUp`] implementation that penalizes first hops whose anchor reserve is unhealthy, as
+/// reported by a [`ChannelReserveHealthSource`], in addition to the penalty of the wrapped scorer.
+///
+/// Sending a payment over a channel adds HTLCs which have to be resolved on-chain if the channel
+/// is force-closed. If the channel's reserve already can't cover the pending HTLCs, these
+/// additional HTLCs increase the funds at risk, so other first hops are preferred when available.
+///
+/// Useful for custom [`Router`] implementations to wrap their [`ScoreLookUp`] on-the-fly when calling
+/// [`find_route`]. [`DefaultRouter`] does so if configured via
+/// [`DefaultRouter::with_reserve_health_source`].
+pub struct ScorerAccountingForReserveHealth<S: Deref, H: Deref>
+where
+	S::Target: ScoreLookUp,
+	H::Target: ChannelReserveHealthSource,
+{
+	scorer: S,
+	health_source: H,
+	penalties: ReserveHealthPenalties,
+}
+
+impl<S: Deref, H: Deref> ScorerAccountingForReserveHealth<S, H>
+where
+	S::Target: ScoreLookUp,
+	H::Target: ChannelReserveHealthSource,
+{
+	/// Initialize a new `ScorerAccountingForReserveHealth`.
+	pub fn new(scorer: S, health_source: H, penalties: ReserveHealthPenalties) -> Self {
+		ScorerAccountingForReserveHealth { scorer, health_source, penalties }
+	}
+}
+
+impl<S: Deref, H: Deref> ScoreLookUp for ScorerAccountingForReserveHealth<S, H>
+where
+	S::Target: ScoreLookUp,
+	H::Target: ChannelReserveHealthSource,
+{
+	type ScoreParams = <S::Target as ScoreLookUp>::ScoreParams;
+	fn channel_penalty_msat(
+		&self, candidate: &CandidateRouteHop, usage: ChannelUsage, score_params: &Self::ScoreParams,
+	) -> u64 {
+		let penalty = self.scorer.channel_penalty_msat(candidate, usage, score_params);
+		let details = match candidate {
+			CandidateRouteHop::FirstHop(hop) => hop.details,
+			_ => return penalty,
+		};
+		match self.health_source.channel_reserve_health(details) {
+			Some(ChannelReserveHealth::Degraded { .. }) => {
+				penalty.saturating_add(self.penalties.degraded_penalty_msat)
+			},
+			Some(ChannelReserveHealth::Critical { .. }) => {
+				penalty.saturating_add(self.penalties.critical_penalty_msat)
+			},
+			Some(ChannelReserveHealth::Healthy) | None => penalty,
+		}
+	}
+}
+
 /// A data structure for tracking in-flight HTLCs. May be used during pathfinding to account for
 /// in-use channel liquidity.
 #[derive(Clone)]
@@ -2428,7 +2665,8 @@ where L::Target: Logger, GL::Target: Logger {
 	let graph_lock = network_graph.read_only();
 	let mut route = get_route(our_node_pubkey, &route_params, &graph_lock, first_hops, logger,
 		scorer, score_params, random_seed_bytes)?;
-	add_random_cltv_offset(&mut route, &route_params.payment_params, &graph_lock, random_seed_bytes);
+	add_random_cltv_offset(&mut route, &route_params.payment_params, &graph_lock, random_seed_bytes,
+		DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
 	Ok(route)
 }
 
@@ -3733,7 +3971,7 @@ where L::Target: Logger {
 // payment path by adding a randomized 'shadow route' offset to the final hop.
 #[rustfmt::skip]
 fn add_random_cltv_offset(route: &mut Route, payment_params: &PaymentParameters,
-	network_graph: &ReadOnlyNetworkGraph, random_seed_bytes: &[u8; 32]
+	network_graph: &ReadOnlyNetworkGraph, random_seed_bytes: &[u8; 32], max_offset: u32
 ) {
 	let network_channels = network_graph.channels();
 	let network_nodes = network_graph.nodes();
@@ -3794,8 +4032,7 @@ fn add_random_cltv_offset(route: &mut Route, payment_params: &PaymentParameters,
 		}
 
 		// Limit the total offset to reduce the worst-case locked liquidity timevalue
-		const MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET: u32 = 3*144;
-		shadow_ctlv_expiry_delta_offset = cmp::min(shadow_ctlv_expiry_delta_offset, MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
+		shadow_ctlv_expiry_delta_offset = cmp::min(shadow_ctlv_expiry_delta_offset, max_offset);
 
 		// Limit the offset so we never exceed the max_total_cltv_expiry_delta. To improve plausibility,
 		// we choose the limit to be the largest possible multiple of MEDIAN_HOP_CLTV_EXPIRY_DELTA.
@@ -3831,7 +4068,8 @@ where L::Target: Logger, GL::Target: Logger {
 	let graph_lock = network_graph.read_only();
 	let mut route = build_route_from_hops_internal(our_node_pubkey, hops, &route_params,
 		&graph_lock, logger, random_seed_bytes)?;
-	add_random_cltv_offset(&mut route, &route_params.payment_params, &graph_lock, random_seed_bytes);
+	add_random_cltv_offset(&mut route, &route_params.payment_params, &graph_lock, random_seed_bytes,
+		DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
 	Ok(route)
 }
 
@@ -3903,7 +4141,8 @@ mod tests {
 	use crate::routing::router::{
 		add_random_cltv_offset, build_route_from_hops_internal, default_node_features, get_route,
 		BlindedTail, CandidateRouteHop, InFlightHtlcs, Path, PaymentParameters, PublicHopCandidate,
-		Route, RouteHint, RouteHintHop, RouteHop, RouteParameters, RoutingFees,
+		ReserveHealthPenalties, Route, RouteHint, RouteHintHop, RouteHop, RouteParameters,
+		RoutingFees, ScorerAccountingForReserveHealth, DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET,
 		DEFAULT_MAX_TOTAL_CLTV_EXPIRY_DELTA, MAX_PATH_LENGTH_ESTIMATE,
 	};
 	use crate::routing::scoring::{
@@ -3916,6 +4155,7 @@ mod tests {
 	};
 	use crate::routing::utxo::UtxoResult;
 	use crate::types::features::{BlindedHopFeatures, ChannelFeatures, InitFeatures, NodeFeatures};
+	use crate::util::anchor_channel_reserves::ChannelReserveHealth;
 	use crate::util::config::UserConfig;
 	#[cfg(c_bindings)]
 	use crate::util::ser::Writer;
@@ -3975,6 +4215,7 @@ mod tests {
 			channel_shutdown_state: Some(ChannelShutdownState::NotShuttingDown),
 			pending_inbound_htlcs: Vec::new(),
 			pending_outbound_htlcs: Vec::new(),
+			exogenous_fee_reserve_recommended_sat: None,
 		}
 	}
 
@@ -7175,6 +7416,83 @@ mod tests {
 		}
 	}
 
+	#[test]
+	fn avoids_first_hops_with_unhealthy_reserve() {
+		let secp_ctx = Secp256k1::new();
+		let (_, our_id, _, nodes) = get_nodes(&secp_ctx);
+		let logger = Arc::new(ln_test_utils::TestLogger::new());
+		let network_graph = NetworkGraph::new(Network::Testnet, Arc::clone(&logger));
+		let scorer = ln_test_utils::TestScorer::new();
+		let config = UserConfig::default();
+		let payment_params = PaymentParameters::from_node_id(nodes[0], 42)
+			.with_bolt11_features(channelmanager::provided_bolt11_invoice_features(&config))
+			.unwrap();
+		let route_params = RouteParameters::from_payment_params_and_value(payment_params, 100_000);
+		let random_seed_bytes = [42; 32];
+
+		let features = channelmanager::provided_init_features(&config);
+		let mut preferred_hop = get_channel_details(Some(6), nodes[0], features.clone(), 300_000);
+		preferred_hop.channel_id = ChannelId::from_bytes([6; 32]);
+		let mut alternative_hop = get_channel_details(Some(4), nodes[0], features, 1_000_000);
+		alternative_hop.channel_id = ChannelId::from_bytes([4; 32]);
+		let first_hops = [&preferred_hop, &alternative_hop];
+
+		let route_first_hop_scid = |health: Option<ChannelReserveHealth>, penalties| {
+			let mut health_source = new_hash_map();
+			if let Some(health) = health {
+				health_source.insert(preferred_hop.channel_id, health);
+			}
+			let scorer = ScorerAccountingForReserveHealth::new(&scorer, &health_source, penalties);
+			let graph = network_graph.read_only();
+			let route = get_route(
+				&our_id,
+				&route_params,
+				&graph,
+				Some(&first_hops),
+				Arc::clone(&logger),
+				&scorer,
+				&Default::default(),
+				&random_seed_bytes,
+			)
+			.unwrap();
+			assert_eq!(route.paths.len(), 1);
+			route.paths[0].hops[0].short_channel_id
+		};
+		let penalties = ReserveHealthPenalties::default();
+		let degraded = ChannelReserveHealth::Degraded { shortfall: Amount::from_sat(1_000) };
+		let critical =
+			ChannelReserveHealth::Critical { unfunded_htlc_value: Amount::from_sat(1_000) };
+
+		// Without any unhealthy reserve, the smaller channel sufficient for the payment is used.
+		assert_eq!(route_first_hop_scid(None, penalties), 6);
+		assert_eq!(route_first_hop_scid(Some(ChannelReserveHealth::Healthy), penalties), 6);
+
+		// Once its reserve is degraded or critical, the alternative first hop is used instead.
+		assert_eq!(route_first_hop_scid(Some(degraded), penalties), 4);
+		assert_eq!(route_first_hop_scid(Some(critical), penalties), 4);
+
+		// Unless the reserve health is not penalized.
+		let no_penalties =
+			ReserveHealthPenalties { degraded_penalty_msat: 0, critical_penalty_msat: 0 };
+		assert_eq!(route_first_hop_scid(Some(critical), no_penalties), 6);
+
+		// Without an alternative, a first hop with a critical reserve is excluded by default.
+		let mut health_source = new_hash_map();
+		health_source.insert(preferred_hop.channel_id, critical);
+		let scorer = ScorerAccountingForReserveHealth::new(&scorer, &health_source, penalties);
+		let route = get_route(
+			&our_id,
+			&route_params,
+			&network_graph.read_only(),
+			Some(&[&preferred_hop]),
+			Arc::clone(&logger),
+			&scorer,
+			&Default::default(),
+			&random_seed_bytes,
+		);
+		assert!(route.is_err());
+	}
+
 	#[test]
 	#[rustfmt::skip]
 	fn prefers_shorter_route_with_higher_fees() {
@@ -7483,7 +7801,7 @@ mod tests {
 
 		// Check whether the offset added to the last hop by default is in [1 .. DEFAULT_MAX_TOTAL_CLTV_EXPIRY_DELTA]
 		let mut route_default = route.clone();
-		add_random_cltv_offset(&mut route_default, &payment_params, &network_graph.read_only(), &random_seed_bytes);
+		add_random_cltv_offset(&mut route_default, &payment_params, &network_graph.read_only(), &random_seed_bytes, DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
 		let cltv_expiry_deltas_default = route_default.paths[0].hops.iter().map(|h| h.cltv_expiry_delta).collect::<Vec<u32>>();
 		assert_eq!(cltv_expiry_deltas_before.split_last().unwrap().1, cltv_expiry_deltas_default.split_last().unwrap().1);
 		assert!(cltv_expiry_deltas_default.last() > cltv_expiry_deltas_before.last());
@@ -7493,7 +7811,7 @@ mod tests {
 		let mut route_limited = route.clone();
 		let limited_max_total_cltv_expiry_delta = cltv_expiry_deltas_before.iter().sum();
 		let limited_payment_params = payment_params.with_max_total_cltv_expiry_delta(limited_max_total_cltv_expiry_delta);
-		add_random_cltv_offset(&mut route_limited, &limited_payment_params, &network_graph.read_only(), &random_seed_bytes);
+		add_random_cltv_offset(&mut route_limited, &limited_payment_params, &network_graph.read_only(), &random_seed_bytes, DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
 		let cltv_expiry_deltas_limited = route_limited.paths[0].hops.iter().map(|h| h.cltv_expiry_delta).collect::<Vec<u32>>();
 		assert_eq!(cltv_expiry_deltas_before, cltv_expiry_deltas_limited);
 	}
@@ -7514,7 +7832,7 @@ mod tests {
 			payment_params.clone(), 100);
 		let mut route = get_route(&our_id, &route_params, &network_graph, None,
 			Arc::clone(&logger), &scorer, &Default::default(), &random_seed_bytes).unwrap();
-		add_random_cltv_offset(&mut route, &payment_params, &network_graph, &random_seed_bytes);
+		add_random_cltv_offset(&mut route, &payment_params, &network_graph, &random_seed_bytes, DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
 
 		let mut path_plausibility = vec![];
 
@@ -8046,7 +8364,7 @@ mod tests {
 
 		let payment_params = PaymentParameters::from_node_id(ln_test_utils::pubkey(47), 18);
 		let (_, network_graph, _, _, _) = build_line_graph();
-		add_random_cltv_offset(&mut route, &payment_params, &network_graph.read_only(), &[0; 32]);
+		add_random_cltv_offset(&mut route, &payment_params, &network_graph.read_only(), &[0; 32], DEFAULT_MAX_SHADOW_CLTV_EXPIRY_DELTA_OFFSET);
 		assert_eq!(route.paths[0].blinded_tail.as_ref().unwrap().excess_final_cltv_expiry_delta, 40);
 		assert_eq!(route.paths[0].hops.last().unwrap().cltv_expiry_delta, 40);
 	}
@@ -9370,6 +9688,7 @@ pub(crate) mod bench_utils {
 			channel_shutdown_state: Some(ChannelShutdownState::NotShuttingDown),
 			pending_inbound_htlcs: Vec::new(),
 			pending_outbound_htlcs: Vec::new(),
+			exogenous_fee_reserve_recommended_sat: None,
 		}
 	}
 
@@ -59,6 +59,7 @@ use crate::routing::gossip::{DirectedChannelInfo, EffectiveCapacity, NetworkGrap
 use crate::routing::log_approx;
 use crate::routing::router::{CandidateRouteHop, Path, PublicHopCandidate};
 use crate::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+use crate::util::framed_ser::{FramedReader, FramedType, FramedWriter, LossyReadReport};
 use crate::util::logger::Logger;
 use crate::util::ser::{Readable, ReadableArgs, Writeable, Writer};
 use bucketed_history::{
@@ -534,6 +535,14 @@ impl ChannelLiquidities {
 		self.0.get(short_channel_id)
 	}
 
+	fn last_update_time(&self) -> Duration {
+		self.0
+			.values()
+			.map(|liquidity| liquidity.last_updated)
+			.max()
+			.unwrap_or(Duration::from_secs(0))
+	}
+
 	fn insert(
 		&mut self, short_channel_id: u64, liquidity: ChannelLiquidity,
 	) -> Option<ChannelLiquidity> {
@@ -2531,10 +2540,7 @@ where
 	) -> Result<Self, DecodeError> {
 		let (decay_params, network_graph, logger) = args;
 		let channel_liquidities = ChannelLiquidities::read(r)?;
-		let mut last_update_time = Duration::from_secs(0);
-		for (_, liq) in channel_liquidities.0.iter() {
-			last_update_time = cmp::max(last_update_time, liq.last_updated);
-		}
+		let last_update_time = channel_liquidities.last_update_time();
 		Ok(Self {
 			decay_params,
 			network_graph,
@@ -2545,6 +2551,51 @@ where
 	}
 }
 
+impl<G: Deref<Target = NetworkGraph<L>>, L: Deref> ProbabilisticScorer<G, L>
+where
+	L::Target: Logger,
+{
+	/// Writes the scorer in a framed format which, unlike the format written by
+	/// [`Writeable::write`], can still be partially read by [`Self::read_lossy`] after the data
+	/// was truncated or corrupted.
+	///
+	/// The learned liquidity of each channel is written as a separate record with its own
+	/// checksum, followed by an integrity footer. See [`framed_ser`] for details.
+	///
+	/// [`framed_ser`]: crate::util::framed_ser
+	pub fn write_framed<W: Writer>(&self, w: &mut W) -> Result<(), io::Error> {
+		let mut framed_writer = FramedWriter::new(w, FramedType::ProbabilisticScorer)?;
+		for (short_channel_id, liquidity) in self.channel_liquidities.iter() {
+			framed_writer.write_record(&(short_channel_id, liquidity))?;
+		}
+		framed_writer.finish()
+	}
+
+	/// Reads a scorer written by [`Self::write_framed`], recovering the learned liquidity of all
+	/// channels preceding the first damaged record if the data was truncated or corrupted.
+	///
+	/// The returned [`LossyReadReport`] describes how much data was lost. If the data was damaged,
+	/// the remainder of `r` is consumed.
+	///
+	/// Fails only if the data is not a framed scorer.
+	pub fn read_lossy<R: Read>(
+		r: &mut R, decay_params: ProbabilisticScoringDecayParameters, network_graph: G, logger: L,
+	) -> Result<(Self, LossyReadReport), DecodeError> {
+		let mut framed_reader = FramedReader::new(r, FramedType::ProbabilisticScorer)?;
+		let mut channel_liquidities = ChannelLiquidities::new();
+		while let Some((short_channel_id, liquidity)) =
+			framed_reader.read_record(|r| <(u64, ChannelLiquidity)>::read(r))
+		{
+			channel_liquidities.insert(short_channel_id, liquidity);
+		}
+		let report = framed_reader.finish();
+		let last_update_time = channel_liquidities.last_update_time();
+		let scorer =
+			Self { decay_params, network_graph, logger, channel_liquidities, last_update_time };
+		Ok((scorer, report))
+	}
+}
+
 impl Writeable for ChannelLiquidity {
 	#[inline]
 	fn write<W: Writer>(&self, w: &mut W) -> Result<(), io::Error> {
@@ -2635,6 +2686,7 @@ mod tests {
 	use crate::routing::scoring::{
 		ChannelLiquidities, ChannelUsage, CombinedScorer, ScoreLookUp, ScoreUpdate,
 	};
+	use crate::util::framed_ser::FramedDataDamage;
 	use crate::util::ser::{ReadableArgs, Writeable};
 	use crate::util::test_utils::{self, TestLogger};
 
@@ -3596,6 +3648,62 @@ mod tests {
 		do_decays_persisted_liquidity_bounds(true);
 	}
 
+	#[test]
+	fn recovers_framed_liquidity_bounds() {
+		let logger = TestLogger::new();
+		let network_graph = network_graph(&logger);
+		let decay_params = ProbabilisticScoringDecayParameters::default();
+		let mut scorer = ProbabilisticScorer::new(decay_params, &network_graph, &logger);
+		scorer.payment_path_failed(&payment_path_for_amount(500), 43, Duration::from_secs(10));
+		let num_channels = scorer.channel_liquidities.iter().count() as u64;
+		assert!(num_channels > 1);
+
+		let mut serialized_scorer = Vec::new();
+		scorer.write_framed(&mut serialized_scorer).unwrap();
+
+		let assert_recovered_liquidities = |recovered: &ProbabilisticScorer<_, _>| {
+			for (scid, liquidity) in recovered.channel_liquidities.iter() {
+				let original = scorer.channel_liquidities.get(scid).unwrap();
+				assert_eq!(liquidity.encode(), original.encode());
+			}
+		};
+
+		let (recovered, report) = ProbabilisticScorer::read_lossy(
+			&mut &serialized_scorer[..],
+			decay_params,
+			&network_graph,
+			&logger,
+		)
+		.unwrap();
+		assert!(report.is_complete());
+		assert_eq!(report.records_recovered, num_channels);
+		assert_eq!(recovered.channel_liquidities.iter().count() as u64, num_channels);
+		assert_eq!(recovered.last_update_time, scorer.last_update_time);
+		assert_recovered_liquidities(&recovered);
+
+		// A truncated scorer still provides the liquidity bounds of the channels preceding the
+		// damage.
+		let mut num_recovered = 0;
+		for len in 0..serialized_scorer.len() {
+			let mut truncated = &serialized_scorer[..len];
+			let (recovered, report) = match ProbabilisticScorer::read_lossy(
+				&mut truncated,
+				decay_params,
+				&network_graph,
+				&logger,
+			) {
+				Ok(result) => result,
+				Err(_) => continue,
+			};
+			assert_eq!(report.damage, Some(FramedDataDamage::Truncated));
+			assert!(report.records_recovered >= num_recovered);
+			num_recovered = report.records_recovered;
+			assert_eq!(recovered.channel_liquidities.iter().count() as u64, num_recovered);
+			assert_recovered_liquidities(&recovered);
+		}
+		assert_eq!(num_recovered, num_channels);
+	}
+
 	#[test]
 	#[rustfmt::skip]
 	fn scores_realistic_payments() {
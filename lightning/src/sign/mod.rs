@@ -58,6 +58,7 @@ use crate::ln::script::ShutdownScript;
 use crate::offers::invoice::UnsignedBolt12Invoice;
 use crate::types::payment::PaymentPreimage;
 use crate::util::async_poll::AsyncResult;
+use crate::util::message_signing::MessageSigningKeyId;
 use crate::util::ser::{ReadableArgs, Writeable};
 use crate::util::transaction_utils;
 
@@ -445,67 +446,18 @@ impl SpendableOutputDescriptor {
 		let mut witness_weight = 0;
 		let mut output_set = hash_set_with_capacity(descriptors.len());
 		for outp in descriptors {
-			match outp {
-				SpendableOutputDescriptor::StaticPaymentOutput(descriptor) => {
-					if !output_set.insert(descriptor.outpoint) {
-						return Err(());
-					}
-					let sequence = if descriptor.needs_csv_1_for_spend() {
-						Sequence::from_consensus(1)
-					} else {
-						Sequence::ZERO
-					};
-					input.push(TxIn {
-						previous_output: descriptor.outpoint.into_bitcoin_outpoint(),
-						script_sig: ScriptBuf::new(),
-						sequence,
-						witness: Witness::new(),
-					});
-					witness_weight += descriptor.max_witness_length();
-					#[cfg(feature = "grind_signatures")]
-					{
-						// Guarantees a low R signature
-						witness_weight -= 1;
-					}
-					input_value += descriptor.output.value;
-				},
-				SpendableOutputDescriptor::DelayedPaymentOutput(descriptor) => {
-					if !output_set.insert(descriptor.outpoint) {
-						return Err(());
-					}
-					input.push(TxIn {
-						previous_output: descriptor.outpoint.into_bitcoin_outpoint(),
-						script_sig: ScriptBuf::new(),
-						sequence: Sequence(descriptor.to_self_delay as u32),
-						witness: Witness::new(),
-					});
-					witness_weight += DelayedPaymentOutputDescriptor::MAX_WITNESS_LENGTH;
-					#[cfg(feature = "grind_signatures")]
-					{
-						// Guarantees a low R signature
-						witness_weight -= 1;
-					}
-					input_value += descriptor.output.value;
-				},
-				SpendableOutputDescriptor::StaticOutput { ref outpoint, ref output, .. } => {
-					if !output_set.insert(*outpoint) {
-						return Err(());
-					}
-					input.push(TxIn {
-						previous_output: outpoint.into_bitcoin_outpoint(),
-						script_sig: ScriptBuf::new(),
-						sequence: Sequence::ZERO,
-						witness: Witness::new(),
-					});
-					witness_weight += 1 + 73 + 34;
-					#[cfg(feature = "grind_signatures")]
-					{
-						// Guarantees a low R signature
-						witness_weight -= 1;
-					}
-					input_value += output.value;
-				},
+			let outpoint = outp.spendable_outpoint();
+			if !output_set.insert(outpoint) {
+				return Err(());
 			}
+			input.push(TxIn {
+				previous_output: outpoint.into_bitcoin_outpoint(),
+				script_sig: ScriptBuf::new(),
+				sequence: outp.spending_sequence(),
+				witness: Witness::new(),
+			});
+			witness_weight += outp.max_witness_weight();
+			input_value += outp.output().value;
 			if input_value > Amount::MAX_MONEY {
 				return Err(());
 			}
@@ -546,6 +498,56 @@ impl SpendableOutputDescriptor {
 			Self::DelayedPaymentOutput(descriptor) => descriptor.outpoint,
 		}
 	}
+
+	/// Returns the spendable output.
+	pub(crate) fn output(&self) -> &TxOut {
+		match self {
+			Self::StaticOutput { output, .. } => output,
+			Self::StaticPaymentOutput(descriptor) => &descriptor.output,
+			Self::DelayedPaymentOutput(descriptor) => &descriptor.output,
+		}
+	}
+
+	/// Returns the `nSequence` value an input spending the output must set to satisfy any relative
+	/// locktime of the output's script.
+	pub(crate) fn spending_sequence(&self) -> Sequence {
+		match self {
+			Self::StaticOutput { .. } => Sequence::ZERO,
+			Self::StaticPaymentOutput(descriptor) if descriptor.needs_csv_1_for_spend() => {
+				Sequence::from_consensus(1)
+			},
+			Self::StaticPaymentOutput(_) => Sequence::ZERO,
+			Self::DelayedPaymentOutput(descriptor) => Sequence(descriptor.to_self_delay as u32),
+		}
+	}
+
+	/// Returns the upper-bound weight of the witness of an input spending the output.
+	pub(crate) fn max_witness_weight(&self) -> u64 {
+		let witness_weight = match self {
+			Self::StaticOutput { .. } => 1 + 73 + 34,
+			Self::StaticPaymentOutput(descriptor) => descriptor.max_witness_length(),
+			Self::DelayedPaymentOutput(_) => DelayedPaymentOutputDescriptor::MAX_WITNESS_LENGTH,
+		};
+		// Guarantees a low R signature
+		#[cfg(feature = "grind_signatures")]
+		let witness_weight = witness_weight - 1;
+		witness_weight
+	}
+
+	/// Returns the height of the first block in which a transaction spending the output may be
+	/// included, given the height of the block in which the output was confirmed.
+	///
+	/// This accounts for the relative locktime the spending input must commit to, i.e., the
+	/// [`DelayedPaymentOutputDescriptor::to_self_delay`] of outputs to our own commitment
+	/// transactions or the one block delay of [`StaticPaymentOutputDescriptor`]s of anchor
+	/// channels.
+	pub fn maturity_height(&self, confirmation_height: u32) -> u32 {
+		let relative_locktime = match self.spending_sequence().to_relative_lock_time() {
+			Some(bitcoin::relative::LockTime::Blocks(height)) => height.value() as u32,
+			_ => 0,
+		};
+		confirmation_height.saturating_add(relative_locktime)
+	}
 }
 
 /// The parameters required to derive a channel signer via [`SignerProvider`].
@@ -941,6 +943,31 @@ pub trait NodeSigner {
 	/// An `Err` can be returned to signal that the signer is unavailable / cannot produce a valid
 	/// signature.
 	fn sign_message(&self, msg: &[u8]) -> Result<String, ()>;
+
+	/// Get the public key of the message signing key derived for the given `key_id`.
+	///
+	/// This method must return the same value each time it is called with a given `key_id`, such
+	/// that it can be shared with receivers of messages signed via
+	/// [`Self::sign_message_with_derived_key`].
+	///
+	/// Errors if deriving message signing keys, or the given `key_id`, is not supported by the
+	/// implementation.
+	fn get_message_signing_pubkey(&self, key_id: MessageSigningKeyId) -> Result<PublicKey, ()>;
+
+	/// Sign an arbitrary message with the message signing key derived for the given `key_id`,
+	/// rather than with the node's secret key.
+	///
+	/// The signature must have the same format as the ones created via [`Self::sign_message`] and
+	/// be created by the key returned by [`Self::get_message_signing_pubkey`]. See
+	/// [`message_signing::sign_with_derived_key`] for more details.
+	///
+	/// An `Err` can be returned to signal that the signer is unavailable / cannot produce a valid
+	/// signature.
+	///
+	/// [`message_signing::sign_with_derived_key`]: crate::util::message_signing::sign_with_derived_key
+	fn sign_message_with_derived_key(
+		&self, msg: &[u8], key_id: MessageSigningKeyId,
+	) -> Result<String, ()>;
 }
 
 /// A trait that describes a wallet capable of creating a spending [`Transaction`] from a set of
@@ -963,6 +990,15 @@ pub trait OutputSpender {
 		change_destination_script: ScriptBuf, feerate_sat_per_1000_weight: u32,
 		locktime: Option<LockTime>, secp_ctx: &Secp256k1<All>,
 	) -> Result<Transaction, ()>;
+
+	/// Signs the inputs of the given [`Psbt`] which spend the given descriptors, e.g., to spend
+	/// them along with inputs from an on-chain wallet. The resulting inputs will be finalized,
+	/// while any other inputs are left untouched.
+	///
+	/// Returns `Err(())` if the PSBT is missing a descriptor or if we fail to sign.
+	fn sign_spendable_outputs_psbt(
+		&self, descriptors: &[&SpendableOutputDescriptor], psbt: Psbt, secp_ctx: &Secp256k1<All>,
+	) -> Result<Psbt, ()>;
 }
 
 // Primarily needed in doctests because of https://github.com/rust-lang/rust/issues/67295
@@ -1859,6 +1895,7 @@ pub struct KeysManager {
 	shutdown_pubkey: PublicKey,
 	channel_master_key: Xpriv,
 	channel_child_index: AtomicUsize,
+	message_signing_master_key: Xpriv,
 	peer_storage_key: PeerStorageKey,
 	receive_auth_key: ReceiveAuthKey,
 
@@ -1899,6 +1936,8 @@ impl KeysManager {
 		const INBOUND_PAYMENT_KEY_INDEX: ChildNumber = ChildNumber::Hardened { index: 5 };
 		const PEER_STORAGE_KEY_INDEX: ChildNumber = ChildNumber::Hardened { index: 6 };
 		const RECEIVE_AUTH_KEY_INDEX: ChildNumber = ChildNumber::Hardened { index: 7 };
+		const MESSAGE_SIGNING_KEY_PATH: [ChildNumber; 2] =
+			[ChildNumber::Hardened { index: 1017 }, ChildNumber::Hardened { index: 0 }];
 
 		let secp_ctx = Secp256k1::new();
 		// Note that when we aren't serializing the key, network doesn't matter
@@ -1946,6 +1985,10 @@ impl KeysManager {
 					.expect("Your RNG is busted")
 					.private_key;
 
+				let message_signing_master_key = master_key
+					.derive_priv(&secp_ctx, &MESSAGE_SIGNING_KEY_PATH)
+					.expect("Your RNG is busted");
+
 				let mut rand_bytes_engine = Sha256::engine();
 				rand_bytes_engine.input(&starting_time_secs.to_be_bytes());
 				rand_bytes_engine.input(&starting_time_nanos.to_be_bytes());
@@ -1968,6 +2011,7 @@ impl KeysManager {
 
 					channel_master_key,
 					channel_child_index: AtomicUsize::new(0),
+					message_signing_master_key,
 
 					entropy_source: RandomBytes::new(rand_bytes_unique_start),
 
@@ -1988,6 +2032,44 @@ impl KeysManager {
 		self.node_secret
 	}
 
+	/// Derives the [LNURL-auth] linking key for the given `domain` from our seed and signs the
+	/// service's `k1` challenge with it.
+	///
+	/// The linking key is derived from the BIP 32 master key of our seed as specified in LUD-05,
+	/// under a path distinct from those of any other key derived by the [`KeysManager`], including
+	/// the node's secret. Thus, it is unique per domain, and is restored along with the seed.
+	///
+	/// Returns the linking public key and the hex-encoded DER signature, i.e., the `key` and `sig`
+	/// parameters of the callback to the service. See
+	/// [`message_signing::derive_lnurl_auth_linking_key`] for more details.
+	///
+	/// [LNURL-auth]: https://github.com/lnurl/luds/blob/luds/04.md
+	/// [`message_signing::derive_lnurl_auth_linking_key`]: crate::util::message_signing::derive_lnurl_auth_linking_key
+	pub fn sign_lnurl_auth_challenge(&self, domain: &str, k1: &[u8; 32]) -> (PublicKey, String) {
+		// Note that when we aren't serializing the key, network doesn't matter
+		let master_key =
+			Xpriv::new_master(Network::Testnet, &self.seed).expect("Your RNG is busted");
+		let linking_key = crate::util::message_signing::derive_lnurl_auth_linking_key(
+			&self.secp_ctx,
+			&master_key,
+			domain,
+		)
+		.expect("Your RNG is busted");
+		crate::util::message_signing::sign_lnurl_auth_challenge(&self.secp_ctx, k1, &linking_key)
+	}
+
+	/// Derives the message signing key for the given `key_id` at
+	/// `m/1017'/0'/{family}'/0/{index}` from our seed.
+	fn derive_message_signing_key(&self, key_id: MessageSigningKeyId) -> Result<SecretKey, ()> {
+		let path = [
+			ChildNumber::from_hardened_idx(key_id.family).map_err(|_| ())?,
+			ChildNumber::Normal { index: 0 },
+			ChildNumber::from_normal_idx(key_id.index).map_err(|_| ())?,
+		];
+		let key = self.message_signing_master_key.derive_priv(&self.secp_ctx, &path);
+		Ok(key.map_err(|_| ())?.private_key)
+	}
+
 	/// Derive an old [`EcdsaChannelSigner`] containing per-channel secrets based on a key derivation parameters.
 	pub fn derive_channel_keys(&self, params: &[u8; 32]) -> InMemorySigner {
 		let chan_id = u64::from_be_bytes(params[0..8].try_into().unwrap());
@@ -2224,6 +2306,18 @@ impl NodeSigner for KeysManager {
 	fn sign_message(&self, msg: &[u8]) -> Result<String, ()> {
 		Ok(crate::util::message_signing::sign(msg, &self.node_secret))
 	}
+
+	fn get_message_signing_pubkey(&self, key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		let key = self.derive_message_signing_key(key_id)?;
+		Ok(PublicKey::from_secret_key(&self.secp_ctx, &key))
+	}
+
+	fn sign_message_with_derived_key(
+		&self, msg: &[u8], key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		let key = self.derive_message_signing_key(key_id)?;
+		Ok(crate::util::message_signing::sign(msg, &key))
+	}
 }
 
 impl OutputSpender for KeysManager {
@@ -2263,6 +2357,14 @@ impl OutputSpender for KeysManager {
 
 		Ok(spend_tx)
 	}
+
+	/// See [`OutputSpender::sign_spendable_outputs_psbt`] and
+	/// [`KeysManager::sign_spendable_outputs_psbt`] for documentation on this method.
+	fn sign_spendable_outputs_psbt(
+		&self, descriptors: &[&SpendableOutputDescriptor], psbt: Psbt, secp_ctx: &Secp256k1<All>,
+	) -> Result<Psbt, ()> {
+		KeysManager::sign_spendable_outputs_psbt(self, descriptors, psbt, secp_ctx)
+	}
 }
 
 impl SignerProvider for KeysManager {
@@ -2393,6 +2495,16 @@ impl NodeSigner for PhantomKeysManager {
 	fn sign_message(&self, msg: &[u8]) -> Result<String, ()> {
 		self.inner.sign_message(msg)
 	}
+
+	fn get_message_signing_pubkey(&self, key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		self.inner.get_message_signing_pubkey(key_id)
+	}
+
+	fn sign_message_with_derived_key(
+		&self, msg: &[u8], key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		self.inner.sign_message_with_derived_key(msg, key_id)
+	}
 }
 
 impl OutputSpender for PhantomKeysManager {
@@ -2412,6 +2524,14 @@ impl OutputSpender for PhantomKeysManager {
 			secp_ctx,
 		)
 	}
+
+	/// See [`OutputSpender::sign_spendable_outputs_psbt`] and
+	/// [`KeysManager::sign_spendable_outputs_psbt`] for documentation on this method.
+	fn sign_spendable_outputs_psbt(
+		&self, descriptors: &[&SpendableOutputDescriptor], psbt: Psbt, secp_ctx: &Secp256k1<All>,
+	) -> Result<Psbt, ()> {
+		self.inner.sign_spendable_outputs_psbt(descriptors, psbt, secp_ctx)
+	}
 }
 
 impl SignerProvider for PhantomKeysManager {
@@ -19,30 +19,104 @@
 //! appropriately, it can lead to loss of funds of the in-flight HLTCs as mentioned above. Only
 //! partially satisfying UTXO requirements incurs the risk of not being able to resolve a subset of
 //! HTLCs.
-use crate::chain::chaininterface::BroadcasterInterface;
-use crate::chain::chaininterface::FeeEstimator;
-use crate::chain::chainmonitor::ChainMonitor;
-use crate::chain::chainmonitor::Persist;
-use crate::chain::Filter;
+//!
+//! HTLCs accepted from counterparties are at risk if they can't be claimed with their preimage
+//! before they expire, after which the counterparty can time them out instead. This is the risk
+//! the reserve covers by default, see [ReservePolicy]. HTLCs offered by us for our own payments are
+//! at risk the other way around: if our HTLC-timeout transaction does not confirm after expiry,
+//! the counterparty can still claim the HTLC if it learns the preimage late, even though the
+//! payment may have already been failed and retried. Reserving for these is optional, see
+//! [AnchorChannelReserveContext::include_outbound_htlcs].
+//!
+//! The reserve calculations only depend on the [AnchorChannelReserveContext] and the UTXOs
+//! provided. Helpers which count the anchor channels relying on the reserve by inspecting a
+//! [ChannelManager](crate::ln::channelmanager::ChannelManager) and a `ChainMonitor` are only
+//! available with the `std` feature. Without it, the number of anchor channels can be counted by
+//! the caller and provided to [AnchorChannelReserveCheck::with_num_anchor_channels] and
+//! [can_support_channel_batch_with_num_anchor_channels] instead.
+//!
+//! With the `serde` feature, [AnchorChannelReserveContext], [SupportableAnchorChannels],
+//! [ChannelReserveHealth], [UrgentFundingRequirement] and [Utxo] implement `Serialize` and
+//! `Deserialize`, e.g., to expose the reserve status via an API. Their field names are a stable
+//! compatibility surface: fields holding an [Amount], [FeeRate] or [Weight] are represented as
+//! integers with a unit suffix, such as `upper_bound_fee_rate_sat_per_kwu` or
+//! `satisfaction_weight_wu`, enum variants are represented in `snake_case`, and missing fields of
+//! an [AnchorChannelReserveContext] take their [Default] value. Outpoints and transaction outputs
+//! use the representation of the `bitcoin` crate.
+use crate::chain::chaininterface::{ConfirmationTarget, FeeEstimator, FEERATE_FLOOR_SATS_PER_KW};
+#[cfg(feature = "std")]
+use crate::chain::chainmonitor::MonitorSummary;
+use crate::chain::channelmonitor::{
+	AnchorClaimStatus, Balance, ChannelMonitor, OnchainResolutionStats,
+};
+use crate::chain::ClaimId;
+use crate::events::bump_transaction::sync::WalletSourceSync;
 use crate::events::bump_transaction::Utxo;
-use crate::ln::chan_utils::max_htlcs;
-use crate::ln::channelmanager::AChannelManager;
-use crate::prelude::new_hash_set;
+use crate::io;
+use crate::ln::chan_utils::{
+	commit_tx_fee_sat, commitment_tx_base_weight, htlc_success_tx_weight, htlc_timeout_tx_weight,
+	max_htlcs,
+};
+use crate::ln::channel::COINBASE_MATURITY;
+use crate::ln::channel_state::{ChannelDetails, ChannelShutdownState};
+use crate::ln::channelmanager::{
+	InboundChannelAcceptor, InboundChannelDecision, InboundChannelProposal, InflightHtlcStats,
+};
+use crate::ln::msgs::DecodeError;
+use crate::ln::types::ChannelId;
+use crate::prelude::*;
 use crate::sign::ecdsa::EcdsaChannelSigner;
-use crate::sign::EntropySource;
+use crate::sync::{Arc, Mutex};
 use crate::types::features::ChannelTypeFeatures;
-use crate::util::logger::Logger;
+use crate::util::config::{ChannelHandshakeConfig, UserConfig};
+#[cfg(feature = "std")]
+use crate::util::facade::{DynChainMonitor, DynChannelManager};
+use crate::util::ser::{Readable, Writeable, Writer};
 use bitcoin::constants::WITNESS_SCALE_FACTOR;
+use bitcoin::secp256k1::PublicKey;
 use bitcoin::Amount;
 use bitcoin::FeeRate;
+use bitcoin::Network;
+use bitcoin::OutPoint;
+use bitcoin::Script;
+use bitcoin::TxOut;
+use bitcoin::Txid;
 use bitcoin::Weight;
-use core::cmp::min;
-use core::ops::Deref;
+use core::cmp::{max, min};
+use core::ops::{Deref, RangeInclusive};
+use core::time::Duration;
 
 // Transaction weights based on:
 // https://github.com/lightning/bolts/blob/master/03-transactions.md#appendix-a-expected-weights
-const COMMITMENT_TRANSACTION_BASE_WEIGHT: u64 = 900 + 224;
-const COMMITMENT_TRANSACTION_PER_HTLC_WEIGHT: u64 = 172;
+//
+// A commitment transaction without any outputs consists of:
+// - 4 bytes for the version
+// - 1 byte for the number of inputs
+// - 41 bytes for the funding input
+//   - 36 bytes for the previous outpoint
+//   - 1 byte for the script sig length
+//   - 4 bytes for the sequence
+// - 1 byte for the number of outputs
+// - 4 bytes for the locktime
+// - 2 bytes for the witness header
+// - 222 bytes for the 2-of-2 multisig witness of the funding input
+const COMMITMENT_TRANSACTION_NO_OUTPUTS_WEIGHT: u64 =
+	(4 + 1 + 41 + 1 + 4) * WITNESS_SCALE_FACTOR as u64 + 2 + 222;
+// A P2WSH output consists of:
+// - 8 bytes for the output amount
+// - 1 byte for the script length
+// - 34 bytes for the script (OP_0 OP_PUSH32 32 byte script hash)
+const P2WSH_OUTPUT_WEIGHT: u64 = (8 + 1 + 34) * WITNESS_SCALE_FACTOR as u64;
+// The spec's base weight of a commitment transaction with keyed anchor outputs, 1124, exceeds the
+// sum of its parts by 8. As the fee of commitment transactions is based on the spec's weight, the
+// difference is attributed to the two P2WSH anchor outputs.
+const KEYED_ANCHOR_OUTPUT_WEIGHT: u64 = P2WSH_OUTPUT_WEIGHT + 4;
+// A shared P2A anchor output consists of:
+// - 8 bytes for the output amount
+// - 1 byte for the script length
+// - 4 bytes for the script (OP_1 OP_PUSHBYTES_2 0x4e73)
+const P2A_ANCHOR_OUTPUT_WEIGHT: u64 = (8 + 1 + 4) * WITNESS_SCALE_FACTOR as u64;
+const COMMITMENT_TRANSACTION_PER_HTLC_WEIGHT: u64 = P2WSH_OUTPUT_WEIGHT;
 const PER_HTLC_TIMEOUT_WEIGHT: u64 = 666;
 const PER_HTLC_SUCCESS_WEIGHT: u64 = 706;
 
@@ -110,38 +184,561 @@ const P2TR_OUTPUT_WEIGHT: u64 = (8 + 1 + 34) * WITNESS_SCALE_FACTOR as u64;
 //     - 6 1-byte opcodes
 const ANCHOR_INPUT_WEIGHT: u64 = (36 + 4 + 1) * WITNESS_SCALE_FACTOR as u64 + (1 + 1 + 72 + 1 + 40);
 
-fn htlc_success_transaction_weight(context: &AnchorChannelReserveContext) -> u64 {
-	PER_HTLC_SUCCESS_WEIGHT
-		+ if context.taproot_wallet {
-			P2TR_KEYPATH_INPUT_WEIGHT + P2TR_OUTPUT_WEIGHT
-		} else {
-			P2WPKH_INPUT_WEIGHT + P2WPKH_OUTPUT_WEIGHT
+// A P2A anchor input consists of:
+// - 36 bytes for the previous outpoint:
+//   - 32 bytes transaction hash
+//   - 4 bytes index
+// - 4 bytes for the sequence
+// - 1 byte for the script sig length
+// - the witness:
+//   - 1 byte for witness item count, as the output is keyless
+const P2A_ANCHOR_INPUT_WEIGHT: u64 = (36 + 4 + 1) * WITNESS_SCALE_FACTOR as u64 + 1;
+
+// A revoked `to_local` input of a counterparty commitment transaction, claimed via the revocation
+// path, consists of:
+// - 36 bytes for the previous outpoint:
+//   - 32 bytes transaction hash
+//   - 4 bytes index
+// - 4 bytes for the sequence
+// - 1 byte for the script sig length
+// - the witness:
+//   - 1 byte for witness item count
+//   - 1 byte for signature length
+//   - 73 bytes signature
+//   - 1 byte for the length of the revocation path selector
+//   - 1 byte revocation path selector
+//   - 1 byte for script length
+//   - 77 byte script
+//     OP_IF <revocationpubkey> OP_ELSE <to_self_delay> OP_CHECKSEQUENCEVERIFY OP_DROP
+//     <local_delayedpubkey> OP_ENDIF OP_CHECKSIG
+//     - 2 33 byte pubkeys with 1 byte OP_PUSHBYTES_33.
+//     - 2 byte to_self_delay with 1 byte OP_PUSHBYTES_2.
+//     - 6 1-byte opcodes
+const REVOKED_TO_LOCAL_INPUT_WEIGHT: u64 =
+	(36 + 4 + 1) * WITNESS_SCALE_FACTOR as u64 + (1 + 1 + 73 + 1 + 1 + 1 + 77);
+
+// A P2WSH `to_remote` input of a counterparty commitment transaction consists of:
+// - 36 bytes for the previous outpoint:
+//   - 32 bytes transaction hash
+//   - 4 bytes index
+// - 4 bytes for the sequence
+// - 1 byte for the script sig length
+// - the witness:
+//   - 1 byte for witness item count
+//   - 1 byte for signature length
+//   - 73 bytes signature
+//   - 1 byte for script length
+//   - 37 byte script
+//     <remotepubkey> OP_CHECKSIGVERIFY OP_1 OP_CHECKSEQUENCEVERIFY
+//     - 33 byte pubkey with 1 byte OP_PUSHBYTES_33.
+//     - 3 1-byte opcodes
+// This exceeds the weight of a P2WPKH `to_remote` input.
+const TO_REMOTE_INPUT_WEIGHT: u64 =
+	(36 + 4 + 1) * WITNESS_SCALE_FACTOR as u64 + (1 + 1 + 73 + 1 + 37);
+
+// A revoked HTLC input of a counterparty commitment transaction, claimed via the revocation path,
+// consists of:
+// - 36 bytes for the previous outpoint:
+//   - 32 bytes transaction hash
+//   - 4 bytes index
+// - 4 bytes for the sequence
+// - 1 byte for the script sig length
+// - the witness:
+//   - 1 byte for witness item count
+//   - 1 byte for signature length
+//   - 73 bytes signature
+//   - 1 byte for public key length
+//   - 33 bytes revocation public key
+//   - 1 byte for script length
+//   - 142 byte script of an accepted HTLC on anchor channels, which exceeds the 136 byte script
+//     of an offered HTLC. See
+//     https://github.com/lightning/bolts/blob/master/03-transactions.md#received-htlc-outputs
+const REVOKED_HTLC_INPUT_WEIGHT: u64 =
+	(36 + 4 + 1) * WITNESS_SCALE_FACTOR as u64 + (1 + 1 + 73 + 1 + 33 + 1 + 142);
+
+/// Returns the weight of a commitment transaction of the given [AnchorChannelType] with `num_htlcs`
+/// non-dust HTLC outputs.
+///
+/// This is the weight LDK assumes when reserving for and estimating the cost of a unilateral
+/// closure, and includes the anchor outputs of the channel type. The `to_local` and `to_remote`
+/// outputs are assumed as per [CommitmentWeightParams::for_channel_type].
+pub fn commitment_transaction_weight(num_htlcs: u64, channel_type: AnchorChannelType) -> Weight {
+	CommitmentWeightParams::for_channel_type(channel_type)
+		.commitment_transaction_weight(num_htlcs, channel_type)
+}
+
+/// The outputs of a commitment transaction determining its weight besides its HTLC outputs, as
+/// used for [AnchorChannelReserveContext::commitment_weight_params].
+///
+/// The weight of the `to_local` and `to_remote` outputs depends on their script types, e.g., a
+/// P2TR `to_remote` output weighs more than a P2WPKH one. As each HTLC output adds 172 weight
+/// units, these only matter for commitment transactions with few HTLCs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct CommitmentWeightParams {
+	/// The number of anchor outputs, each of the type of the [AnchorChannelType] the weight is
+	/// calculated for, i.e., a P2WSH output for [AnchorChannelType::AnchorsZeroFeeHtlcTx] and a
+	/// P2A output for [AnchorChannelType::ZeroFeeCommitments].
+	pub num_anchor_outputs: u8,
+	/// The weight of the `to_local` output, including its amount and script length.
+	#[cfg_attr(
+		feature = "serde",
+		serde(
+			rename = "to_local_output_weight_wu",
+			with = "crate::util::serde_helpers::weight_wu"
+		)
+	)]
+	pub to_local_output_weight: Weight,
+	/// The weight of the `to_remote` output, including its amount and script length.
+	#[cfg_attr(
+		feature = "serde",
+		serde(
+			rename = "to_remote_output_weight_wu",
+			with = "crate::util::serde_helpers::weight_wu"
+		)
+	)]
+	pub to_remote_output_weight: Weight,
+}
+
+impl_writeable_tlv_based!(CommitmentWeightParams, {
+	(0, num_anchor_outputs, required),
+	(2, to_local_output_weight, required),
+	(4, to_remote_output_weight, required),
+});
+
+impl CommitmentWeightParams {
+	/// Returns the outputs of commitment transactions of the given `channel_type`:
+	/// - [AnchorChannelType::AnchorsZeroFeeHtlcTx]: two anchor outputs, as well as P2WSH
+	///   `to_local` and `to_remote` outputs, matching the spec's base weight of 1124 weight units.
+	/// - [AnchorChannelType::ZeroFeeCommitments]: a single shared anchor output, a P2WSH
+	///   `to_local` output and a P2WPKH `to_remote` output, for a base weight of 776 weight units.
+	pub fn for_channel_type(channel_type: AnchorChannelType) -> Self {
+		match channel_type {
+			AnchorChannelType::AnchorsZeroFeeHtlcTx => CommitmentWeightParams {
+				num_anchor_outputs: 2,
+				to_local_output_weight: Weight::from_wu(P2WSH_OUTPUT_WEIGHT),
+				to_remote_output_weight: Weight::from_wu(P2WSH_OUTPUT_WEIGHT),
+			},
+			AnchorChannelType::ZeroFeeCommitments => CommitmentWeightParams {
+				num_anchor_outputs: 1,
+				to_local_output_weight: Weight::from_wu(P2WSH_OUTPUT_WEIGHT),
+				to_remote_output_weight: Weight::from_wu(P2WPKH_OUTPUT_WEIGHT),
+			},
 		}
+	}
+
+	/// Returns the weight of a commitment transaction with these outputs, anchor outputs of the
+	/// given [AnchorChannelType], and `num_htlcs` non-dust HTLC outputs.
+	///
+	/// The weight saturates at [Weight::MAX].
+	pub fn commitment_transaction_weight(
+		&self, num_htlcs: u64, channel_type: AnchorChannelType,
+	) -> Weight {
+		let anchor_output_weight = match channel_type {
+			AnchorChannelType::AnchorsZeroFeeHtlcTx => KEYED_ANCHOR_OUTPUT_WEIGHT,
+			AnchorChannelType::ZeroFeeCommitments => P2A_ANCHOR_OUTPUT_WEIGHT,
+		};
+		let weight = COMMITMENT_TRANSACTION_NO_OUTPUTS_WEIGHT
+			.saturating_add(self.num_anchor_outputs as u64 * anchor_output_weight)
+			.saturating_add(self.to_local_output_weight.to_wu())
+			.saturating_add(self.to_remote_output_weight.to_wu())
+			.saturating_add(num_htlcs.saturating_mul(COMMITMENT_TRANSACTION_PER_HTLC_WEIGHT));
+		Weight::from_wu(weight)
+	}
+}
+
+/// The [CommitmentWeightParams] for commitment transactions of two anchor outputs, and P2WSH
+/// `to_local` and `to_remote` outputs, i.e., those of
+/// [AnchorChannelType::AnchorsZeroFeeHtlcTx] as per
+/// [CommitmentWeightParams::for_channel_type].
+impl Default for CommitmentWeightParams {
+	fn default() -> Self {
+		CommitmentWeightParams::for_channel_type(AnchorChannelType::AnchorsZeroFeeHtlcTx)
+	}
+}
+
+fn anchor_input_weight(context: &AnchorChannelReserveContext) -> u64 {
+	match context.channel_type {
+		AnchorChannelType::AnchorsZeroFeeHtlcTx => ANCHOR_INPUT_WEIGHT,
+		AnchorChannelType::ZeroFeeCommitments => P2A_ANCHOR_INPUT_WEIGHT,
+	}
+}
+
+/// The type of a wallet input spent to provide fees from the anchor channel reserve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WalletInputType {
+	/// A Segwit P2WPKH input.
+	P2wpkh,
+	/// A Taproot P2TR input spent via the key path.
+	P2trKeyPath,
 }
 
-fn htlc_timeout_transaction_weight(context: &AnchorChannelReserveContext) -> u64 {
-	PER_HTLC_TIMEOUT_WEIGHT
-		+ if context.taproot_wallet {
-			P2TR_KEYPATH_INPUT_WEIGHT + P2TR_OUTPUT_WEIGHT
+impl WalletInputType {
+	/// Classifies the input spending `utxo` by the type of its `script_pubkey`.
+	///
+	/// Returns `None` for other script types, for which the type indicated by
+	/// [AnchorChannelReserveContext::taproot_wallet] is assumed when estimating the weight of the
+	/// transactions funded by the UTXO.
+	pub fn from_utxo(utxo: &Utxo) -> Option<Self> {
+		if utxo.output.script_pubkey.is_p2wpkh() {
+			Some(WalletInputType::P2wpkh)
+		} else if utxo.output.script_pubkey.is_p2tr() {
+			Some(WalletInputType::P2trKeyPath)
+		} else {
+			None
+		}
+	}
+
+	fn from_context(context: &AnchorChannelReserveContext) -> Self {
+		if context.taproot_wallet {
+			WalletInputType::P2trKeyPath
 		} else {
-			P2WPKH_INPUT_WEIGHT + P2WPKH_OUTPUT_WEIGHT
+			WalletInputType::P2wpkh
+		}
+	}
+
+	/// Returns the weight of an input of this type, including its witness.
+	pub fn input_weight(&self) -> Weight {
+		match self {
+			WalletInputType::P2wpkh => Weight::from_wu(P2WPKH_INPUT_WEIGHT),
+			WalletInputType::P2trKeyPath => Weight::from_wu(P2TR_KEYPATH_INPUT_WEIGHT),
 		}
+	}
+}
+
+fn wallet_output_weight(context: &AnchorChannelReserveContext) -> u64 {
+	if context.taproot_wallet {
+		P2TR_OUTPUT_WEIGHT
+	} else {
+		P2WPKH_OUTPUT_WEIGHT
+	}
+}
+
+/// Returns the weight of a change output paying to `script_pubkey`.
+///
+/// This is the weight the [Wallet] pays fees for when adding a change output to a claim
+/// transaction, and matches the change output weight assumed by the estimates in this module for
+/// P2WPKH and P2TR scripts, as indicated by [AnchorChannelReserveContext::taproot_wallet].
+///
+/// [Wallet]: crate::events::bump_transaction::Wallet
+pub fn change_output_weight(script_pubkey: &Script) -> Weight {
+	TxOut { value: Amount::ZERO, script_pubkey: script_pubkey.to_owned() }.weight()
+}
+
+/// Returns the weight of an HTLC-Success transaction on an anchor channel, including a wallet
+/// input of type `input_type` to provide fees and a change output of the type indicated by
+/// [AnchorChannelReserveContext::taproot_wallet].
+///
+/// Can be used to estimate the fees of second-stage HTLC transactions before they are built.
+pub fn htlc_success_transaction_weight(
+	context: &AnchorChannelReserveContext, input_type: WalletInputType,
+) -> Weight {
+	Weight::from_wu(
+		PER_HTLC_SUCCESS_WEIGHT + input_type.input_weight().to_wu() + wallet_output_weight(context),
+	)
 }
 
-fn anchor_output_spend_transaction_weight(
+/// Returns the weight of an HTLC-Timeout transaction on an anchor channel, including a wallet
+/// input of type `input_type` to provide fees and a change output of the type indicated by
+/// [AnchorChannelReserveContext::taproot_wallet].
+///
+/// Can be used to estimate the fees of second-stage HTLC transactions before they are built.
+pub fn htlc_timeout_transaction_weight(
+	context: &AnchorChannelReserveContext, input_type: WalletInputType,
+) -> Weight {
+	Weight::from_wu(
+		PER_HTLC_TIMEOUT_WEIGHT + input_type.input_weight().to_wu() + wallet_output_weight(context),
+	)
+}
+
+/// Returns the weight of a transaction spending the anchor output of a commitment transaction of
+/// the [AnchorChannelReserveContext::channel_type], together with wallet inputs of a total weight
+/// of `input_weight` and a change output of the type indicated by
+/// [AnchorChannelReserveContext::taproot_wallet].
+///
+/// The weight of a single wallet input can be obtained through [WalletInputType::input_weight].
+pub fn anchor_output_spend_transaction_weight(
 	context: &AnchorChannelReserveContext, input_weight: Weight,
-) -> u64 {
-	TRANSACTION_BASE_WEIGHT
-		+ ANCHOR_INPUT_WEIGHT
-		+ input_weight.to_wu()
-		+ if context.taproot_wallet { P2TR_OUTPUT_WEIGHT } else { P2WPKH_OUTPUT_WEIGHT }
+) -> Weight {
+	Weight::from_wu(
+		TRANSACTION_BASE_WEIGHT
+			+ anchor_input_weight(context)
+			+ input_weight.to_wu()
+			+ wallet_output_weight(context),
+	)
+}
+
+/// Returns the weight of a justice transaction claiming the `to_local`, `to_remote` and
+/// `num_htlcs` HTLC outputs of a revoked counterparty commitment transaction via their revocation
+/// paths, with a single output of the type indicated by
+/// [AnchorChannelReserveContext::taproot_wallet].
+///
+/// See [AnchorChannelReserveContext::include_justice_path].
+fn justice_transaction_weight(context: &AnchorChannelReserveContext, num_htlcs: u64) -> Weight {
+	Weight::from_wu(
+		TRANSACTION_BASE_WEIGHT
+			+ REVOKED_TO_LOCAL_INPUT_WEIGHT
+			+ TO_REMOTE_INPUT_WEIGHT
+			+ num_htlcs * REVOKED_HTLC_INPUT_WEIGHT
+			+ wallet_output_weight(context),
+	)
+}
+
+/// A part of a claim transaction whose weight is estimated by a constant in this module, as
+/// classified by [validate_weight_constants].
+#[cfg(any(test, feature = "_test_utils"))]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum ClaimWeightComponent {
+	/// An input spending a keyed anchor output of an [AnchorChannelType::AnchorsZeroFeeHtlcTx]
+	/// commitment transaction.
+	KeyedAnchorInput,
+	/// An input spending the keyless P2A anchor output of an
+	/// [AnchorChannelType::ZeroFeeCommitments] commitment transaction.
+	P2aAnchorInput,
+	/// An HTLC input claimed with its preimage, together with the output it is paired with via
+	/// `SIGHASH_SINGLE` and the base weight of the transaction, as covered by
+	/// [htlc_success_transaction_weight].
+	HtlcSuccess,
+	/// An HTLC input claimed after its expiry, together with the output it is paired with via
+	/// `SIGHASH_SINGLE` and the base weight of the transaction, as covered by
+	/// [htlc_timeout_transaction_weight].
+	HtlcTimeout,
+	/// A wallet input of type [WalletInputType::P2wpkh].
+	P2wpkhInput,
+	/// A wallet input of type [WalletInputType::P2trKeyPath].
+	P2trKeyPathInput,
+	/// A P2WPKH wallet output, e.g., for change.
+	P2wpkhOutput,
+	/// A P2TR wallet output, e.g., for change.
+	P2trOutput,
+}
+
+#[cfg(any(test, feature = "_test_utils"))]
+impl ClaimWeightComponent {
+	/// Returns the weight estimated for this component by the calculations in this module.
+	pub fn estimated_weight(&self) -> Weight {
+		Weight::from_wu(match self {
+			ClaimWeightComponent::KeyedAnchorInput => ANCHOR_INPUT_WEIGHT,
+			ClaimWeightComponent::P2aAnchorInput => P2A_ANCHOR_INPUT_WEIGHT,
+			ClaimWeightComponent::HtlcSuccess => PER_HTLC_SUCCESS_WEIGHT,
+			ClaimWeightComponent::HtlcTimeout => PER_HTLC_TIMEOUT_WEIGHT,
+			ClaimWeightComponent::P2wpkhInput => P2WPKH_INPUT_WEIGHT,
+			ClaimWeightComponent::P2trKeyPathInput => P2TR_KEYPATH_INPUT_WEIGHT,
+			ClaimWeightComponent::P2wpkhOutput => P2WPKH_OUTPUT_WEIGHT,
+			ClaimWeightComponent::P2trOutput => P2TR_OUTPUT_WEIGHT,
+		})
+	}
+
+	fn name(&self) -> &'static str {
+		match self {
+			ClaimWeightComponent::KeyedAnchorInput => "keyed_anchor_input",
+			ClaimWeightComponent::P2aAnchorInput => "p2a_anchor_input",
+			ClaimWeightComponent::HtlcSuccess => "htlc_success",
+			ClaimWeightComponent::HtlcTimeout => "htlc_timeout",
+			ClaimWeightComponent::P2wpkhInput => "p2wpkh_input",
+			ClaimWeightComponent::P2trKeyPathInput => "p2tr_key_path_input",
+			ClaimWeightComponent::P2wpkhOutput => "p2wpkh_output",
+			ClaimWeightComponent::P2trOutput => "p2tr_output",
+		}
+	}
+
+	/// Classifies `input` by its witness, as the previous outputs of claim transactions are
+	/// generally not at hand.
+	fn from_input(input: &bitcoin::TxIn) -> Option<Self> {
+		let witness = &input.witness;
+		if !input.script_sig.is_empty() {
+			return None;
+		}
+		match witness.len() {
+			0 => Some(ClaimWeightComponent::P2aAnchorInput),
+			1 if matches!(witness[0].len(), 64 | 65) => {
+				Some(ClaimWeightComponent::P2trKeyPathInput)
+			},
+			2 if witness[1].len() == 33 => Some(ClaimWeightComponent::P2wpkhInput),
+			2 if witness[1].len() == 40 => {
+				let funding_pubkey = PublicKey::from_slice(&witness[1][1..34]).ok()?;
+				let anchor_script =
+					crate::ln::chan_utils::get_keyed_anchor_redeemscript(&funding_pubkey);
+				if anchor_script.as_bytes() == &witness[1][..] {
+					Some(ClaimWeightComponent::KeyedAnchorInput)
+				} else {
+					None
+				}
+			},
+			5 if witness[0].is_empty() && witness[3].len() == 32 => {
+				Some(ClaimWeightComponent::HtlcSuccess)
+			},
+			5 if witness[0].is_empty() && witness[3].is_empty() => {
+				Some(ClaimWeightComponent::HtlcTimeout)
+			},
+			_ => None,
+		}
+	}
+}
+
+/// The weights of a [ClaimWeightComponent] observed by [validate_weight_constants].
+#[cfg(any(test, feature = "_test_utils"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightConstantCheck {
+	/// The component whose weight was observed.
+	pub component: ClaimWeightComponent,
+	/// The number of occurrences of the component in the validated transactions.
+	pub num_occurrences: usize,
+	/// The highest weight of any occurrence of the component.
+	pub max_actual_weight: Weight,
+}
+
+#[cfg(any(test, feature = "_test_utils"))]
+impl WeightConstantCheck {
+	/// Returns the weight estimated for the component less its [Self::max_actual_weight], in
+	/// weight units. A negative delta means the estimate is too low.
+	pub fn delta_wu(&self) -> i64 {
+		self.component.estimated_weight().to_wu() as i64 - self.max_actual_weight.to_wu() as i64
+	}
+
+	/// Returns whether the weight estimated for the component is lower than the weight of any of
+	/// its occurrences, such that the reserve would be undersized.
+	pub fn underestimates(&self) -> bool {
+		self.delta_wu() < 0
+	}
+}
+
+/// The result of [validate_weight_constants], listing a [WeightConstantCheck] for each observed
+/// [ClaimWeightComponent] in the order of the latter.
+///
+/// Its [Display] implementation renders one line per component:
+/// ```text
+/// component=<name> occurrences=<count> estimated_wu=<wu> max_actual_wu=<wu> delta_wu=<wu>
+/// ```
+/// followed by ` UNDERESTIMATED` if [WeightConstantCheck::underestimates].
+///
+/// [Display]: core::fmt::Display
+#[cfg(any(test, feature = "_test_utils"))]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeightConstantReport {
+	/// The checks of each observed component.
+	pub checks: Vec<WeightConstantCheck>,
+	/// The number of inputs which could not be classified, e.g., as they spend a commitment
+	/// transaction output via a path not covered by the constants.
+	pub num_unclassified_inputs: usize,
+}
+
+#[cfg(any(test, feature = "_test_utils"))]
+impl WeightConstantReport {
+	/// Returns the checks of the components whose weight is underestimated.
+	pub fn underestimated(&self) -> Vec<&WeightConstantCheck> {
+		self.checks.iter().filter(|check| check.underestimates()).collect()
+	}
+}
+
+#[cfg(any(test, feature = "_test_utils"))]
+impl core::fmt::Display for WeightConstantReport {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		for check in self.checks.iter() {
+			write!(
+				f,
+				"component={} occurrences={} estimated_wu={} max_actual_wu={} delta_wu={}",
+				check.component.name(),
+				check.num_occurrences,
+				check.component.estimated_weight().to_wu(),
+				check.max_actual_weight.to_wu(),
+				check.delta_wu()
+			)?;
+			if check.underestimates() {
+				write!(f, " UNDERESTIMATED")?;
+			}
+			writeln!(f)?;
+		}
+		Ok(())
+	}
+}
+
+/// Cross-validates the weight constants the reserve calculations rely on against the given
+/// signed claim transactions, e.g., anchor output spends and HTLC transactions broadcast by our
+/// node.
+///
+/// Each input is classified by its witness, and each P2WPKH or P2TR output as a wallet output,
+/// except for the outputs paired with HTLC inputs, which are accounted for as part of the HTLC
+/// component. The weight of each occurrence is then compared to the constant estimating it, such
+/// that changes to the commitment format or the wallet's signatures which increase the actual
+/// weights are caught.
+#[cfg(any(test, feature = "_test_utils"))]
+pub fn validate_weight_constants(transactions: &[bitcoin::Transaction]) -> WeightConstantReport {
+	let mut checks: Vec<WeightConstantCheck> = Vec::new();
+	let mut record = |component: ClaimWeightComponent, weight: Weight| {
+		let check = checks.iter_mut().find(|check| check.component == component);
+		if let Some(check) = check {
+			check.num_occurrences += 1;
+			check.max_actual_weight = max(check.max_actual_weight, weight);
+		} else {
+			checks.push(WeightConstantCheck {
+				component,
+				num_occurrences: 1,
+				max_actual_weight: weight,
+			});
+		}
+	};
+	let mut num_unclassified_inputs = 0;
+	for transaction in transactions {
+		let mut htlc_output_indices = Vec::new();
+		for (idx, input) in transaction.input.iter().enumerate() {
+			match ClaimWeightComponent::from_input(input) {
+				Some(
+					component @ (ClaimWeightComponent::HtlcSuccess
+					| ClaimWeightComponent::HtlcTimeout),
+				) => {
+					let paired_output_weight =
+						transaction.output.get(idx).map_or(Weight::ZERO, |output| output.weight());
+					htlc_output_indices.push(idx);
+					let weight = Weight::from_wu(TRANSACTION_BASE_WEIGHT)
+						+ input.segwit_weight()
+						+ paired_output_weight;
+					record(component, weight);
+				},
+				Some(component) => record(component, input.segwit_weight()),
+				None => num_unclassified_inputs += 1,
+			}
+		}
+		for (idx, output) in transaction.output.iter().enumerate() {
+			if htlc_output_indices.contains(&idx) {
+				continue;
+			}
+			if output.script_pubkey.is_p2wpkh() {
+				record(ClaimWeightComponent::P2wpkhOutput, output.weight());
+			} else if output.script_pubkey.is_p2tr() {
+				record(ClaimWeightComponent::P2trOutput, output.weight());
+			}
+		}
+	}
+	checks.sort_unstable_by_key(|check| check.component);
+	WeightConstantReport { checks, num_unclassified_inputs }
 }
 
 /// Parameters defining the context around the anchor channel reserve requirement calculation.
+///
+/// The context is not persisted as part of the
+/// [ChannelManager](crate::ln::channelmanager::ChannelManager). Custom parameters may be persisted
+/// separately, e.g., under [ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY] in a
+/// [KVStore](crate::util::persist::KVStore), to survive restarts and be shared between processes.
+///
+/// [ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY]: crate::util::persist::ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY
 #[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct AnchorChannelReserveContext {
 	/// An upper bound fee rate estimate used to calculate the anchor channel reserve that is
 	/// sufficient to provide fees for all required transactions.
+	///
+	/// A [FeeRateHistory] can be used to derive this from a percentile of observed fee rates.
+	///
+	/// [FeeRateHistory]: crate::util::fee_rate_history::FeeRateHistory
+	#[cfg_attr(
+		feature = "serde",
+		serde(
+			rename = "upper_bound_fee_rate_sat_per_kwu",
+			with = "crate::util::serde_helpers::fee_rate_sat_per_kwu"
+		)
+	)]
 	pub upper_bound_fee_rate: FeeRate,
 	/// The expected number of accepted in-flight HTLCs per channel.
 	///
@@ -158,8 +755,158 @@ pub struct AnchorChannelReserveContext {
 	/// Whether the wallet handling anchor channel reserves creates Taproot P2TR outputs for any new
 	/// outputs, or Segwit P2WPKH outputs otherwise.
 	pub taproot_wallet: bool,
+	/// Whether the node forwards HTLCs, determining which transactions need to be provided with
+	/// fees per accepted HTLC. See [ReservePolicy] for the risk model of each policy.
+	pub reserve_policy: ReservePolicy,
+	/// The type of anchor channels the reserve is calculated for, determining the weight of the
+	/// commitment transaction and of the anchor output spend.
+	pub channel_type: AnchorChannelType,
+	/// A multiplier applied to the reserve per channel, in parts per million, to maintain a safety
+	/// margin on top of the calculated reserve. E.g., a value of 1_500_000 requires 1.5x the
+	/// calculated reserve.
+	///
+	/// The multiplied reserve is rounded up and saturates at [Amount::MAX]. Values below 1_000_000
+	/// are treated as 1_000_000, i.e., the reserve is never lowered below the calculated one.
+	///
+	/// The margin is respected by all checks based on [get_reserve_per_channel], such as
+	/// [get_supportable_anchor_channels] and [can_support_additional_anchor_channel].
+	pub safety_margin_ppm: u32,
+	/// The value below which HTLCs are assumed to be abandoned rather than claimed on-chain, as the
+	/// fee of the HTLC transaction claiming them would exceed their value, or `None` to assume that
+	/// all HTLCs are claimed.
+	///
+	/// As the number of accepted HTLCs does not indicate their value, this only reduces the number
+	/// of HTLC transactions reserved for in combination with
+	/// [Self::max_htlc_value_in_flight_msat], which bounds the number of HTLCs at or above this
+	/// value. [get_min_economical_htlc_value_sat] derives a threshold from the
+	/// [Self::upper_bound_fee_rate].
+	pub min_economical_htlc_value_sat: Option<u64>,
+	/// The maximum total value of in-flight HTLCs in each direction per channel, e.g., the
+	/// `max_htlc_value_in_flight_msat` advertised by counterparties, or `None` if it is not bounded
+	/// beyond the channel value.
+	///
+	/// Only used together with [Self::min_economical_htlc_value_sat].
+	pub max_htlc_value_in_flight_msat: Option<u64>,
+	/// Whether to also reserve for HTLCs offered by us for our own payments, as opposed to
+	/// outbound HTLCs forwarding accepted HTLCs, which are covered by [ReservePolicy::Forwarding].
+	///
+	/// If set, the reserve includes an HTLC-timeout transaction, and an HTLC output on the
+	/// commitment transaction, for each of [Self::expected_offered_htlcs]. Otherwise, offered
+	/// HTLCs which can't be timed out on-chain in time may be claimed by the counterparty with a
+	/// preimage learned late, after the payment was considered failed, e.g., and retried.
+	pub include_outbound_htlcs: bool,
+	/// The expected number of in-flight HTLCs offered by us for our own payments per channel.
+	///
+	/// Only used if [Self::include_outbound_htlcs] is set.
+	pub expected_offered_htlcs: u16,
+	/// The minimum number of confirmations of a UTXO for it to count towards the reserve. Zero also
+	/// counts unconfirmed UTXOs, e.g., change of our own transactions, which may never confirm if
+	/// they are replaced or evicted from the mempool.
+	///
+	/// Only applies to UTXOs whose [UtxoMaturity] is provided to
+	/// [get_supportable_anchor_channels_with_maturity].
+	pub min_confirmations: u32,
+	/// Whether coinbase UTXOs are excluded from the reserve until they can be spent, i.e., until
+	/// they have 100 confirmations.
+	///
+	/// Only applies to UTXOs whose [UtxoMaturity] is provided to
+	/// [get_supportable_anchor_channels_with_maturity].
+	pub exclude_immature_coinbase: bool,
+	/// The outputs assumed on commitment transactions besides their HTLC outputs, e.g., to account
+	/// for counterparties with P2TR `to_remote` outputs, or `None` to assume those of
+	/// [CommitmentWeightParams::for_channel_type] for the [Self::channel_type].
+	pub commitment_weight_params: Option<CommitmentWeightParams>,
+	/// Whether to also reserve for a justice transaction claiming all outputs of a revoked
+	/// commitment transaction broadcast by the counterparty, i.e., its `to_local` and `to_remote`
+	/// outputs, as well as an HTLC output for each HTLC assumed on the commitment transaction as
+	/// per [Self::reserve_policy] and [Self::include_outbound_htlcs].
+	///
+	/// Revocation paths have heavier witnesses than the HTLC-success and HTLC-timeout transactions,
+	/// e.g., each HTLC output is claimed with the witness of the larger accepted HTLC script,
+	/// regardless of [Self::min_economical_htlc_value_sat].
+	///
+	/// This is a very conservative upper bound. Justice transactions can pay their fees from the
+	/// claimed outputs, and a revoked commitment transaction confirms instead of, not in addition
+	/// to, the commitment transaction otherwise reserved for.
+	pub include_justice_path: bool,
+}
+
+/// The maturity of a [Utxo], determining whether it counts towards the anchor channel reserve as
+/// configured by [AnchorChannelReserveContext::min_confirmations] and
+/// [AnchorChannelReserveContext::exclude_immature_coinbase].
+///
+/// See [get_supportable_anchor_channels_with_maturity].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UtxoMaturity {
+	/// The number of confirmations of the UTXO, or zero if it is unconfirmed.
+	pub confirmations: u32,
+	/// Whether the UTXO is an output of a coinbase transaction.
+	pub is_coinbase: bool,
+}
+
+impl UtxoMaturity {
+	fn counts_towards_reserve(&self, context: &AnchorChannelReserveContext) -> bool {
+		// A coinbase output can be spent by a transaction in the block after its 100th
+		// confirmation, which is the earliest a force-close could require it.
+		let immature_coinbase = self.is_coinbase && self.confirmations < COINBASE_MATURITY;
+		self.confirmations >= context.min_confirmations
+			&& !(context.exclude_immature_coinbase && immature_coinbase)
+	}
+}
+
+/// The type of anchor channel for which the anchor channel reserve is calculated.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AnchorChannelType {
+	/// Channels negotiated with `option_anchors_zero_fee_htlc_tx`, whose commitment transactions
+	/// carry a P2WSH anchor output for each party.
+	AnchorsZeroFeeHtlcTx,
+	/// Channels negotiated with `option_zero_fee_commitments`, whose zero-fee commitment
+	/// transactions carry a single, keyless P2A anchor output with a cheaper spend.
+	///
+	/// Such commitment transactions are limited in size by TRUC policy, lowering the maximum number
+	/// of HTLCs per channel.
+	ZeroFeeCommitments,
+}
+
+impl_writeable_tlv_based_enum!(AnchorChannelType,
+	(0, AnchorsZeroFeeHtlcTx) => {},
+	(2, ZeroFeeCommitments) => {},
+);
+
+/// The policy used to determine which transactions the anchor channel reserve needs to cover for
+/// each accepted HTLC.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum ReservePolicy {
+	/// Each accepted HTLC is assumed to be forwarded, resulting in an inbound and an outbound HTLC
+	/// on the commitment transactions. The reserve covers an HTLC-success transaction to claim the
+	/// inbound HTLC and an HTLC-timeout transaction to time out the outbound HTLC.
+	///
+	/// This is the policy to use for routing nodes.
+	Forwarding,
+	/// Accepted HTLCs are assumed to be payments to us, which are never forwarded. The reserve only
+	/// covers an HTLC-success transaction to claim each inbound HTLC with its preimage.
+	///
+	/// No reserve is allocated to time out outbound HTLCs for payments we send unless
+	/// [AnchorChannelReserveContext::include_outbound_htlcs] is set. If such an HTLC cannot be
+	/// timed out on-chain in time, the counterparty may still claim it with the preimage, in which
+	/// case the payment succeeds, so only the HTLC value we already intended to pay is at risk.
+	///
+	/// This policy must only be used by nodes which never forward HTLCs. If a node using it
+	/// forwards HTLCs, the reserve will be insufficient to time out the outbound HTLCs before the
+	/// corresponding inbound HTLCs expire, risking the loss of the forwarded amounts.
+	ReceiveOnly,
 }
 
+impl_writeable_tlv_based_enum!(ReservePolicy,
+	(0, Forwarding) => {},
+	(2, ReceiveOnly) => {},
+);
+
 /// A default for the [AnchorChannelReserveContext] parameters is provided as follows:
 /// - The upper bound fee rate is set to the 99th percentile of the median block fee rate since 2019:
 ///   ~50 sats/vbyte.
@@ -167,35 +914,344 @@ pub struct AnchorChannelReserveContext {
 ///   above the number seen for a large routing node over a month (average <1, maximum 10
 ///   accepted in-flight HTLCS aggregated across all channels).
 /// - The wallet is assumed to be a Segwit wallet.
+/// - The node is assumed to forward HTLCs, i.e., to use [ReservePolicy::Forwarding].
+/// - Channels are assumed to use [AnchorChannelType::AnchorsZeroFeeHtlcTx].
+/// - No safety margin is applied to the reserve.
+/// - All HTLCs are assumed to be claimed on-chain, regardless of their value.
+/// - HTLCs offered by us for our own payments are not reserved for. If they are included, 10
+///   offered in-flight HTLCs per channel are expected.
+/// - UTXOs with a known [UtxoMaturity] need at least one confirmation, and coinbase UTXOs need to
+///   be mature, to count towards the reserve.
+/// - Commitment transactions are assumed to have the outputs given by
+///   [CommitmentWeightParams::for_channel_type].
+/// - Justice transactions for revoked commitment transactions are not reserved for.
 impl Default for AnchorChannelReserveContext {
 	fn default() -> Self {
 		AnchorChannelReserveContext {
 			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(50 * 250),
 			expected_accepted_htlcs: 10,
 			taproot_wallet: false,
+			reserve_policy: ReservePolicy::Forwarding,
+			channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+			safety_margin_ppm: 1_000_000,
+			min_economical_htlc_value_sat: None,
+			max_htlc_value_in_flight_msat: None,
+			include_outbound_htlcs: false,
+			expected_offered_htlcs: 10,
+			min_confirmations: 1,
+			exclude_immature_coinbase: true,
+			commitment_weight_params: None,
+			include_justice_path: false,
 		}
 	}
 }
 
-fn get_reserve_per_channel_with_input(
-	context: &AnchorChannelReserveContext, initial_input_weight: Weight,
-) -> Amount {
-	let max_max_htlcs = max_htlcs(&ChannelTypeFeatures::only_static_remote_key());
+impl AnchorChannelReserveContext {
+	/// Returns the default [AnchorChannelReserveContext] parameters for the given `network`.
+	///
+	/// The upper bound fee rate is tuned per network as follows, with all other parameters
+	/// matching the [Default] implementation:
+	/// - [Network::Bitcoin]: ~50 sats/vbyte, the 99th percentile of the median block fee rate since
+	///   2019, i.e., the [Default] values.
+	/// - [Network::Regtest]: 5 sats/vbyte, as fee rates are fully under the control of the operator
+	///   of the network, and reserves only need to cover the fee rates used in testing.
+	/// - Any other network, i.e., a test network such as testnet or signet: 5 sats/vbyte, as blocks
+	///   are rarely full and test coins are scarce, making a mainnet-sized reserve impractical.
+	pub fn for_network(network: Network) -> Self {
+		match network {
+			Network::Bitcoin => AnchorChannelReserveContext::default(),
+			_ => AnchorChannelReserveContext {
+				upper_bound_fee_rate: FeeRate::from_sat_per_kwu(5 * 250),
+				..AnchorChannelReserveContext::default()
+			},
+		}
+	}
+
+	/// Returns the default [AnchorChannelReserveContext] with its [Self::upper_bound_fee_rate] set
+	/// to the estimate of `fee_estimator` for [ConfirmationTarget::MaximumExogenousFeeReserve],
+	/// bounded below by the minimum relay fee rate.
+	///
+	/// As fee estimates change over time, the context should be derived anew periodically, e.g.,
+	/// when re-validating the reserve.
+	pub fn from_fee_estimator<F: Deref>(fee_estimator: &F) -> Self
+	where
+		F::Target: FeeEstimator,
+	{
+		let estimate = fee_estimator
+			.get_est_sat_per_1000_weight(ConfirmationTarget::MaximumExogenousFeeReserve);
+		let sat_per_kwu = max(estimate, FEERATE_FLOOR_SATS_PER_KW) as u64;
+		AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(sat_per_kwu),
+			..AnchorChannelReserveContext::default()
+		}
+	}
+
+	/// Derives a [ChannelHandshakeConfig] from `base_config` whose HTLC limits can be afforded by
+	/// the reserve provided by `utxos` across `planned_channels` anchor channels.
+	///
+	/// [ChannelHandshakeConfig::our_max_accepted_htlcs] is clamped to the
+	/// [max_supportable_accepted_htlcs] for `planned_channels`, such that a context with
+	/// [Self::expected_accepted_htlcs] set to it is supported by `utxos`, as determined by
+	/// [get_supportable_anchor_channels]. If it is clamped,
+	/// [ChannelHandshakeConfig::max_inbound_htlc_value_in_flight_percent_of_channel] is reduced
+	/// proportionally, down to 1 percent, such that the value of the HTLCs we accept isn't
+	/// concentrated in fewer of them. All other fields are taken from `base_config`.
+	///
+	/// As channels only apply the handshake config upon their creation, the config needs to be
+	/// derived anew as the reserve or the number of planned channels changes, and existing
+	/// channels may be limited via [ChannelManager::set_max_inbound_htlcs_for_reserve] instead.
+	///
+	/// Returns `None` if the reserve doesn't support `planned_channels` channels with a single
+	/// accepted HTLC each, as counterparties reject channels accepting no HTLCs at all.
+	///
+	/// [ChannelManager::set_max_inbound_htlcs_for_reserve]: crate::ln::channelmanager::ChannelManager::set_max_inbound_htlcs_for_reserve
+	pub fn derive_handshake_config(
+		&self, utxos: &[Utxo], planned_channels: u64, base_config: &ChannelHandshakeConfig,
+	) -> Option<ChannelHandshakeConfig> {
+		let max_accepted_htlcs = max_supportable_accepted_htlcs(self, utxos, planned_channels);
+		if max_accepted_htlcs == 0 {
+			return None;
+		}
+		let mut config = *base_config;
+		if config.our_max_accepted_htlcs > max_accepted_htlcs {
+			let percent = config.max_inbound_htlc_value_in_flight_percent_of_channel as u32
+				* max_accepted_htlcs as u32
+				/ config.our_max_accepted_htlcs as u32;
+			config.max_inbound_htlc_value_in_flight_percent_of_channel = max(percent, 1) as u8;
+			config.our_max_accepted_htlcs = max_accepted_htlcs;
+		}
+		Some(config)
+	}
+}
+
+impl AnchorChannelReserveContext {
+	fn commitment_transaction_weight(&self, num_htlcs: u64) -> Weight {
+		self.commitment_weight_params
+			.unwrap_or_else(|| CommitmentWeightParams::for_channel_type(self.channel_type))
+			.commitment_transaction_weight(num_htlcs, self.channel_type)
+	}
+
+	/// Returns the default context with its [Self::expected_accepted_htlcs] set to the given
+	/// `percentile` of the [InflightHtlcStats::max_accepted_htlcs] observed across `stats`, or
+	/// `None` if `stats` is empty.
+	///
+	/// The stats can be obtained via [ChannelManager::get_inflight_htlc_stats]. The percentile is
+	/// determined using the nearest-rank method, as in [FeeRateHistory::percentile]. Any
+	/// `percentile` above 100 is treated as 100, i.e., the highest watermark across all channels.
+	///
+	/// [ChannelManager::get_inflight_htlc_stats]: crate::ln::channelmanager::ChannelManager::get_inflight_htlc_stats
+	/// [FeeRateHistory::percentile]: crate::util::fee_rate_history::FeeRateHistory::percentile
+	pub fn from_observed_stats(stats: &[InflightHtlcStats], percentile: u8) -> Option<Self> {
+		if stats.is_empty() {
+			return None;
+		}
+		let mut max_accepted_htlcs: Vec<u64> =
+			stats.iter().map(|stats| stats.max_accepted_htlcs).collect();
+		max_accepted_htlcs.sort_unstable();
+		let percentile = percentile.min(100) as usize;
+		let rank = (percentile * max_accepted_htlcs.len() + 99) / 100;
+		let expected_accepted_htlcs = max_accepted_htlcs[rank.saturating_sub(1)];
+		Some(AnchorChannelReserveContext {
+			expected_accepted_htlcs: expected_accepted_htlcs.try_into().unwrap_or(u16::MAX),
+			..AnchorChannelReserveContext::default()
+		})
+	}
+}
+
+impl Writeable for AnchorChannelReserveContext {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), io::Error> {
+		let upper_bound_fee_rate_sat_per_kwu = self.upper_bound_fee_rate.to_sat_per_kwu();
+		write_tlv_fields!(writer, {
+			(0, upper_bound_fee_rate_sat_per_kwu, required),
+			(2, self.expected_accepted_htlcs, required),
+			(4, self.taproot_wallet, required),
+			(5, self.reserve_policy, required),
+			(7, self.channel_type, required),
+			(9, self.safety_margin_ppm, required),
+			(11, self.min_economical_htlc_value_sat, option),
+			(13, self.max_htlc_value_in_flight_msat, option),
+			(15, self.include_outbound_htlcs, required),
+			(17, self.expected_offered_htlcs, required),
+			(19, self.min_confirmations, required),
+			(21, self.exclude_immature_coinbase, required),
+			(23, self.commitment_weight_params, option),
+			(25, self.include_justice_path, required),
+		});
+		Ok(())
+	}
+}
+
+impl Readable for AnchorChannelReserveContext {
+	fn read<R: io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+		let mut upper_bound_fee_rate_sat_per_kwu: u64 = 0;
+		let mut expected_accepted_htlcs: u16 = 0;
+		let mut taproot_wallet = false;
+		let mut reserve_policy = None;
+		let mut channel_type = None;
+		let mut safety_margin_ppm = None;
+		let mut min_economical_htlc_value_sat = None;
+		let mut max_htlc_value_in_flight_msat = None;
+		let mut include_outbound_htlcs = None;
+		let mut expected_offered_htlcs = None;
+		let mut min_confirmations = None;
+		let mut exclude_immature_coinbase = None;
+		let mut commitment_weight_params = None;
+		let mut include_justice_path = None;
+		read_tlv_fields!(reader, {
+			(0, upper_bound_fee_rate_sat_per_kwu, required),
+			(2, expected_accepted_htlcs, required),
+			(4, taproot_wallet, required),
+			(5, reserve_policy, option),
+			(7, channel_type, option),
+			(9, safety_margin_ppm, option),
+			(11, min_economical_htlc_value_sat, option),
+			(13, max_htlc_value_in_flight_msat, option),
+			(15, include_outbound_htlcs, option),
+			(17, expected_offered_htlcs, option),
+			(19, min_confirmations, option),
+			(21, exclude_immature_coinbase, option),
+			(23, commitment_weight_params, option),
+			(25, include_justice_path, option),
+		});
+		Ok(Self {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(upper_bound_fee_rate_sat_per_kwu),
+			expected_accepted_htlcs,
+			taproot_wallet,
+			reserve_policy: reserve_policy.unwrap_or(ReservePolicy::Forwarding),
+			channel_type: channel_type.unwrap_or(AnchorChannelType::AnchorsZeroFeeHtlcTx),
+			safety_margin_ppm: safety_margin_ppm.unwrap_or(1_000_000),
+			min_economical_htlc_value_sat,
+			max_htlc_value_in_flight_msat,
+			include_outbound_htlcs: include_outbound_htlcs.unwrap_or(false),
+			expected_offered_htlcs: expected_offered_htlcs.unwrap_or(10),
+			min_confirmations: min_confirmations.unwrap_or(1),
+			exclude_immature_coinbase: exclude_immature_coinbase.unwrap_or(true),
+			commitment_weight_params,
+			include_justice_path: include_justice_path.unwrap_or(false),
+		})
+	}
+}
+
+/// An error returned by the checked reserve calculations, such as [try_get_reserve_per_channel]
+/// and [try_get_supportable_anchor_channels], indicating that the inputs were nonsensical rather
+/// than merely insufficient.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReserveCalculationError {
+	/// The fee for the reserved transactions, or for spending a UTXO, at
+	/// [AnchorChannelReserveContext::upper_bound_fee_rate] (including the
+	/// [AnchorChannelReserveContext::safety_margin_ppm]) does not fit in an [Amount].
+	FeeOverflow,
+	/// The weight of the reserved transactions, or of a transaction spending a UTXO with the
+	/// given [Utxo::satisfaction_weight], does not fit in a `u64`.
+	WeightOverflow,
+	/// The reserve per channel is zero, e.g. due to an
+	/// [AnchorChannelReserveContext::upper_bound_fee_rate] of zero, such that the number of
+	/// channels it supports is undefined.
+	ZeroReserve,
+}
+
+fn try_get_reserve_per_channel_with_input(
+	context: &AnchorChannelReserveContext, input_type: WalletInputType,
+	initial_input_weight: Weight,
+) -> Result<Amount, ReserveCalculationError> {
+	let channel_type_features = match context.channel_type {
+		AnchorChannelType::AnchorsZeroFeeHtlcTx => {
+			ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies()
+		},
+		AnchorChannelType::ZeroFeeCommitments => {
+			ChannelTypeFeatures::anchors_zero_fee_commitments()
+		},
+	};
+	let max_max_htlcs = max_htlcs(&channel_type_features);
 	let expected_accepted_htlcs = min(context.expected_accepted_htlcs, max_max_htlcs) as u64;
-	let weight = Weight::from_wu(
-		COMMITMENT_TRANSACTION_BASE_WEIGHT +
-		// Reserves are calculated in terms of accepted HTLCs, as their timeout defines the urgency of
-		// on-chain resolution. Each accepted HTLC is assumed to be forwarded to calculate an upper
-		// bound for the reserve, resulting in `expected_accepted_htlcs` inbound HTLCs and
+	let (htlcs_per_accepted_htlc, htlc_timeout_weight) = match context.reserve_policy {
+		// Reserves are calculated in terms of accepted HTLCs, as their timeout defines the urgency
+		// of on-chain resolution. Each accepted HTLC is assumed to be forwarded to calculate an
+		// upper bound for the reserve, resulting in `expected_accepted_htlcs` inbound HTLCs and
 		// `expected_accepted_htlcs` outbound HTLCs per channel in aggregate.
-		2 * expected_accepted_htlcs * COMMITMENT_TRANSACTION_PER_HTLC_WEIGHT +
-		anchor_output_spend_transaction_weight(context, initial_input_weight) +
-		// As an upper bound, it is assumed that each HTLC is resolved in a separate transaction.
-		// However, they might be aggregated when possible depending on timelocks and expiries.
-		htlc_success_transaction_weight(context) * expected_accepted_htlcs +
-		htlc_timeout_transaction_weight(context) * expected_accepted_htlcs,
-	);
-	context.upper_bound_fee_rate.fee_wu(weight).unwrap_or(Amount::MAX)
+		ReservePolicy::Forwarding => (2, htlc_timeout_transaction_weight(context, input_type)),
+		// Accepted HTLCs are never forwarded and only need to be claimed, while outbound HTLCs for
+		// our own payments are not reserved for.
+		ReservePolicy::ReceiveOnly => (1, Weight::ZERO),
+	};
+	// HTLCs offered for our own payments only need to be timed out, independently of the policy.
+	let expected_offered_htlcs = if context.include_outbound_htlcs {
+		min(context.expected_offered_htlcs, max_max_htlcs) as u64
+	} else {
+		0
+	};
+	// The number of HTLCs is bounded by `max_htlcs`, so only the initial input and the
+	// `commitment_weight_params` can overflow the weight in practice.
+	let num_htlc_outputs =
+		htlcs_per_accepted_htlc * expected_accepted_htlcs + expected_offered_htlcs;
+	let commitment_weight = context.commitment_transaction_weight(num_htlc_outputs);
+	// As an upper bound, it is assumed that each HTLC is resolved in a separate transaction.
+	// However, they might be aggregated when possible depending on timelocks and expiries.
+	// Uneconomical HTLCs remain on the commitment transaction, but are not claimed.
+	let htlc_weight = (htlc_success_transaction_weight(context, input_type) + htlc_timeout_weight)
+		* num_economical_htlcs(context, expected_accepted_htlcs)
+		+ htlc_timeout_transaction_weight(context, input_type)
+			* num_economical_htlcs(context, expected_offered_htlcs);
+	let anchor_spend_weight = anchor_output_spend_transaction_weight(context, Weight::ZERO);
+	// A revoked commitment transaction carries the same HTLC outputs, all of which are claimed.
+	let justice_weight = if context.include_justice_path {
+		justice_transaction_weight(context, num_htlc_outputs)
+	} else {
+		Weight::ZERO
+	};
+	let weight = (anchor_spend_weight + htlc_weight + justice_weight)
+		.checked_add(commitment_weight)
+		.and_then(|weight| weight.checked_add(initial_input_weight))
+		.ok_or(ReserveCalculationError::WeightOverflow)?;
+	let reserve =
+		context.upper_bound_fee_rate.fee_wu(weight).ok_or(ReserveCalculationError::FeeOverflow)?;
+	apply_safety_margin(reserve, context.safety_margin_ppm)
+		.ok_or(ReserveCalculationError::FeeOverflow)
+}
+
+/// Returns how many of `num_htlcs` accepted HTLCs are worth claiming on-chain, which is at most
+/// the number of HTLCs of [AnchorChannelReserveContext::min_economical_htlc_value_sat] fitting in
+/// [AnchorChannelReserveContext::max_htlc_value_in_flight_msat].
+fn num_economical_htlcs(context: &AnchorChannelReserveContext, num_htlcs: u64) -> u64 {
+	match (context.min_economical_htlc_value_sat, context.max_htlc_value_in_flight_msat) {
+		(Some(min_value_sat), Some(max_in_flight_msat)) => max_in_flight_msat
+			.checked_div(min_value_sat.saturating_mul(1000))
+			.map_or(num_htlcs, |max_economical_htlcs| min(num_htlcs, max_economical_htlcs)),
+		_ => num_htlcs,
+	}
+}
+
+/// Returns a threshold for [AnchorChannelReserveContext::min_economical_htlc_value_sat] below
+/// which HTLCs are not worth claiming on-chain at the
+/// [AnchorChannelReserveContext::upper_bound_fee_rate], as the fee of the HTLC transaction
+/// claiming them would exceed their value.
+///
+/// Under [ReservePolicy::Forwarding], or if [AnchorChannelReserveContext::include_outbound_htlcs]
+/// is set, the more expensive of the HTLC-Success and HTLC-Timeout transactions is assumed. The
+/// wallet input type is the one indicated by [AnchorChannelReserveContext::taproot_wallet]. The
+/// threshold saturates at `u64::MAX`.
+pub fn get_min_economical_htlc_value_sat(context: &AnchorChannelReserveContext) -> u64 {
+	let input_type = WalletInputType::from_context(context);
+	let success_weight = htlc_success_transaction_weight(context, input_type);
+	let weight = match context.reserve_policy {
+		ReservePolicy::ReceiveOnly if !context.include_outbound_htlcs => success_weight,
+		_ => max(success_weight, htlc_timeout_transaction_weight(context, input_type)),
+	};
+	context.upper_bound_fee_rate.fee_wu(weight).map_or(u64::MAX, |fee| fee.to_sat())
+}
+
+fn get_reserve_per_channel_with_input(
+	context: &AnchorChannelReserveContext, input_type: WalletInputType,
+	initial_input_weight: Weight,
+) -> Amount {
+	try_get_reserve_per_channel_with_input(context, input_type, initial_input_weight)
+		.unwrap_or(Amount::MAX)
+}
+
+fn apply_safety_margin(reserve: Amount, safety_margin_ppm: u32) -> Option<Amount> {
+	let safety_margin_ppm = max(safety_margin_ppm, 1_000_000) as u128;
+	let reserve_sat = (reserve.to_sat() as u128 * safety_margin_ppm + 999_999) / 1_000_000;
+	u64::try_from(reserve_sat).ok().map(Amount::from_sat)
 }
 
 /// Returns the amount that needs to be maintained as a reserve per anchor channel.
@@ -217,226 +1273,5488 @@ fn get_reserve_per_channel_with_input(
 /// [AnchorChannelReserveContext::taproot_wallet]. Larger sets of UTXOs with more complex witnesses
 /// will need to include the corresponding fee required to spend them.
 ///
+/// The calculation saturates at [Amount::MAX] if the reserve can't be represented. Use
+/// [try_get_reserve_per_channel] to detect this case.
+///
 /// [ConfirmationTarget::UrgentOnChainSweep]: crate::chain::chaininterface::ConfirmationTarget::UrgentOnChainSweep
 pub fn get_reserve_per_channel(context: &AnchorChannelReserveContext) -> Amount {
-	get_reserve_per_channel_with_input(
-		context,
-		if context.taproot_wallet {
-			Weight::from_wu(P2TR_KEYPATH_INPUT_WEIGHT)
-		} else {
-			Weight::from_wu(P2WPKH_INPUT_WEIGHT)
-		},
-	)
+	match try_get_reserve_per_channel(context) {
+		Ok(reserve) => reserve,
+		Err(ReserveCalculationError::ZeroReserve) => Amount::ZERO,
+		Err(_) => Amount::MAX,
+	}
 }
 
-/// Calculates the number of anchor channels that can be supported by the reserve provided
-/// by `utxos`.
-pub fn get_supportable_anchor_channels(
-	context: &AnchorChannelReserveContext, utxos: &[Utxo],
-) -> u64 {
-	// Get the reserve needed per channel, accounting for the actual satisfaction weight below.
-	let reserve_per_channel = get_reserve_per_channel_with_input(context, Weight::ZERO);
+/// Returns the amount that needs to be maintained as a reserve per anchor channel, as
+/// [get_reserve_per_channel] does, but fails instead of saturating on nonsensical inputs.
+///
+/// Returns [ReserveCalculationError::FeeOverflow] if the reserve does not fit in an [Amount],
+/// e.g. for an [AnchorChannelReserveContext::upper_bound_fee_rate] of [FeeRate::MAX], and
+/// [ReserveCalculationError::ZeroReserve] if the reserve is zero, which happens for an
+/// [AnchorChannelReserveContext::upper_bound_fee_rate] of zero.
+pub fn try_get_reserve_per_channel(
+	context: &AnchorChannelReserveContext,
+) -> Result<Amount, ReserveCalculationError> {
+	let input_type = WalletInputType::from_context(context);
+	let reserve =
+		try_get_reserve_per_channel_with_input(context, input_type, input_type.input_weight())?;
+	if reserve == Amount::ZERO {
+		return Err(ReserveCalculationError::ZeroReserve);
+	}
+	Ok(reserve)
+}
 
-	let mut total_fractional_amount = Amount::from_sat(0);
-	let mut num_whole_utxos = 0;
-	for utxo in utxos {
-		let satisfaction_fee = context
-			.upper_bound_fee_rate
-			.fee_wu(Weight::from_wu(utxo.satisfaction_weight))
-			.unwrap_or(Amount::MAX);
-		let amount = utxo.output.value.checked_sub(satisfaction_fee).unwrap_or(Amount::MIN);
-		if amount >= reserve_per_channel {
-			num_whole_utxos += 1;
+/// Returns the amount that needs to be maintained as a reserve for an anchor channel with a
+/// capacity of `channel_value_satoshis`, as [get_reserve_per_channel] does.
+///
+/// Instead of [AnchorChannelReserveContext::expected_accepted_htlcs], the number of accepted
+/// in-flight HTLCs is derived from the capacity, assuming HTLCs of `expected_htlc_amount_msat` on
+/// average, and capped at `max_accepted_htlcs`, the maximum number of HTLCs the channel accepts.
+///
+/// As a splice changes the capacity of a channel, this can be used to recalculate its reserve
+/// once the splice locks in, as indicated by [Event::ChannelReady].
+///
+/// [Event::ChannelReady]: crate::events::Event::ChannelReady
+pub fn get_reserve_per_channel_for_capacity(
+	context: &AnchorChannelReserveContext, channel_value_satoshis: u64,
+	expected_htlc_amount_msat: u64, max_accepted_htlcs: u16,
+) -> Amount {
+	let expected_accepted_htlcs = channel_value_satoshis
+		.saturating_mul(1000)
+		.checked_div(expected_htlc_amount_msat)
+		.map_or(max_accepted_htlcs, |num_htlcs| min(num_htlcs, max_accepted_htlcs as u64) as u16);
+	let context = AnchorChannelReserveContext { expected_accepted_htlcs, ..context.clone() };
+	get_reserve_per_channel(&context)
+}
+
+/// Returns the total reserve needed to support `num_channels` anchor channels.
+///
+/// If `fractional_utxos` is unset, the reserve is assumed to be provided by a UTXO per channel,
+/// each covering [get_reserve_per_channel] by itself. Otherwise, the reserve is assumed to be
+/// provided by UTXOs smaller than that, for which [get_supportable_anchor_channels] assumes a
+/// worst-case coin selection, demanding double the reserve of each channel. The fee to spend each
+/// of these smaller UTXOs at [AnchorChannelReserveContext::upper_bound_fee_rate] is not included,
+/// as it depends on the number of UTXOs, and needs to be provided on top.
+///
+/// The calculation saturates at [Amount::MAX] if the total reserve can't be represented. Use
+/// [try_total_reserve_required] to detect this case.
+pub fn total_reserve_required(
+	context: &AnchorChannelReserveContext, num_channels: u64, fractional_utxos: bool,
+) -> Amount {
+	try_total_reserve_required(context, num_channels, fractional_utxos).unwrap_or(Amount::MAX)
+}
+
+/// Returns the total reserve needed to support `num_channels` anchor channels, as
+/// [total_reserve_required] does, but fails instead of saturating on nonsensical inputs.
+///
+/// Returns [ReserveCalculationError::FeeOverflow] if the total reserve does not fit in an
+/// [Amount], and [ReserveCalculationError::WeightOverflow] if the weight of the reserved
+/// transactions does not fit in a `u64`. A zero reserve per channel is not an error, resulting in
+/// a zero total reserve.
+pub fn try_total_reserve_required(
+	context: &AnchorChannelReserveContext, num_channels: u64, fractional_utxos: bool,
+) -> Result<Amount, ReserveCalculationError> {
+	let input_type = WalletInputType::from_context(context);
+	// Fractional UTXOs pay for their own satisfaction out of their value, as counted by
+	// `supportable_anchor_channels`.
+	let (reserve_per_channel, reserves_per_channel) = if fractional_utxos {
+		(try_get_reserve_per_channel_with_input(context, input_type, Weight::ZERO)?, 2)
+	} else {
+		let input_weight = input_type.input_weight();
+		(try_get_reserve_per_channel_with_input(context, input_type, input_weight)?, 1)
+	};
+	(reserve_per_channel.to_sat() as u128)
+		.checked_mul(num_channels as u128 * reserves_per_channel)
+		.and_then(|total_sat| u64::try_from(total_sat).ok())
+		.map(Amount::from_sat)
+		.ok_or(ReserveCalculationError::FeeOverflow)
+}
+
+/// Returns the total value of the additional UTXOs needed for `existing_utxos` to support
+/// `target_channels` anchor channels, as counted by [get_supportable_anchor_channels], or zero if
+/// they already do.
+///
+/// The value is to be provided as one UTXO of [get_reserve_per_channel] per missing channel, of
+/// the type indicated by [AnchorChannelReserveContext::taproot_wallet], such that each covers the
+/// reserve of a channel by itself. The value saturates at [Amount::MAX].
+pub fn utxo_value_needed_for_channels(
+	context: &AnchorChannelReserveContext, existing_utxos: &[Utxo], target_channels: u64,
+) -> Amount {
+	let num_supportable_channels = get_supportable_anchor_channels(context, existing_utxos);
+	let num_missing_channels = target_channels.saturating_sub(num_supportable_channels);
+	total_reserve_required(context, num_missing_channels, false)
+}
+
+/// Calculates the number of anchor channels that can be supported by the reserve provided
+/// by `utxos`.
+///
+/// UTXOs whose value does not exceed the fee to spend them at
+/// [AnchorChannelReserveContext::upper_bound_fee_rate] are not counted towards the reserve. Use
+/// [get_supportable_anchor_channels_with_uneconomical_utxos] to also retrieve these UTXOs.
+///
+/// UTXOs are deduplicated by their outpoint, only counting the first occurrence, and UTXOs with a
+/// zero value are rejected. Use [get_supportable_anchor_channels_detailed] to retrieve the UTXOs
+/// that were skipped for these reasons.
+///
+/// A UTXO covering the reserve of a channel by itself is assumed to fund that channel's
+/// transactions with inputs of its own type, as classified by [WalletInputType::from_utxo].
+/// [AnchorChannelReserveContext::taproot_wallet] only determines the type assumed for sets of
+/// smaller UTXOs and for UTXOs of unknown type.
+///
+/// The calculation saturates on nonsensical inputs, supporting no channels if the reserve per
+/// channel can't be represented, and skipping UTXOs whose satisfaction can't be paid for as
+/// uneconomical. Use [try_get_supportable_anchor_channels] to detect these cases.
+pub fn get_supportable_anchor_channels(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo],
+) -> u64 {
+	get_supportable_anchor_channels_with_uneconomical_utxos(context, utxos).0
+}
+
+/// Calculates the number of anchor channels that can be supported by the reserve provided
+/// by `utxos`, as [get_supportable_anchor_channels] does, but fails instead of saturating on
+/// nonsensical inputs.
+///
+/// Returns [ReserveCalculationError::FeeOverflow] or [ReserveCalculationError::WeightOverflow] if
+/// the reserve per channel or the fee to spend one of the `utxos` can't be represented, e.g. for
+/// a [Utxo::satisfaction_weight] of [Weight::MAX], and [ReserveCalculationError::ZeroReserve] if the
+/// reserve per channel is zero, which happens for an
+/// [AnchorChannelReserveContext::upper_bound_fee_rate] of zero.
+pub fn try_get_supportable_anchor_channels(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo],
+) -> Result<u64, ReserveCalculationError> {
+	supportable_anchor_channels(context, utxos, &[], false)
+		.map(|supportable| supportable.num_channels)
+}
+
+/// Calculates the number of anchor channels that can be supported by the reserve provided
+/// by `utxos`, as [get_supportable_anchor_channels] does.
+///
+/// Additionally returns the outpoints of the UTXOs that were skipped as uneconomical, as their
+/// value does not exceed the fee to spend them at
+/// [AnchorChannelReserveContext::upper_bound_fee_rate]. Wallets may want to consolidate these
+/// UTXOs at a lower fee rate to make them count towards the reserve.
+pub fn get_supportable_anchor_channels_with_uneconomical_utxos(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo],
+) -> (u64, Vec<OutPoint>) {
+	let supportable = get_supportable_anchor_channels_detailed(context, utxos);
+	(supportable.num_channels, supportable.uneconomical_utxos)
+}
+
+/// The number of anchor channels supported by a set of UTXOs, along with the UTXOs that were not
+/// counted towards the reserve.
+///
+/// See [get_supportable_anchor_channels_detailed].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SupportableAnchorChannels {
+	/// The number of anchor channels that can be supported by the reserve.
+	pub num_channels: u64,
+	/// The outpoints of the UTXOs that were skipped as uneconomical, as their value does not exceed
+	/// the fee to spend them at [AnchorChannelReserveContext::upper_bound_fee_rate].
+	pub uneconomical_utxos: Vec<OutPoint>,
+	/// The outpoints of the UTXOs that were rejected for having a zero value.
+	pub zero_value_utxos: Vec<OutPoint>,
+	/// The number of UTXOs that were skipped as their outpoint was already provided by a previous
+	/// UTXO.
+	pub num_duplicate_utxos: usize,
+	/// The outpoints of the UTXOs that were excluded as they are not yet mature enough, as
+	/// determined by their [UtxoMaturity].
+	pub immature_utxos: Vec<OutPoint>,
+	/// The total value of the [Self::immature_utxos].
+	#[cfg_attr(
+		feature = "serde",
+		serde(rename = "immature_value_sat", with = "crate::util::serde_helpers::amount_sat")
+	)]
+	pub immature_value: Amount,
+}
+
+/// Calculates the number of anchor channels that can be supported by the reserve provided
+/// by `utxos`, as [get_supportable_anchor_channels] does.
+///
+/// Additionally returns the UTXOs that were not counted towards the reserve, including duplicates
+/// of previously provided outpoints, which likely indicate that wallet snapshots were merged
+/// incorrectly.
+pub fn get_supportable_anchor_channels_detailed(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo],
+) -> SupportableAnchorChannels {
+	supportable_anchor_channels(context, utxos, &[], true)
+		.expect("Saturating reserve calculations never fail")
+}
+
+/// Calculates the number of anchor channels that can be supported by the reserve provided
+/// by `utxos`, as [get_supportable_anchor_channels_detailed] does, but only counting UTXOs which
+/// are mature enough to be relied upon when a force-close happens.
+///
+/// `maturities[i]` describes the maturity of `utxos[i]`. UTXOs with fewer confirmations than
+/// [AnchorChannelReserveContext::min_confirmations], or immature coinbase UTXOs if
+/// [AnchorChannelReserveContext::exclude_immature_coinbase] is set, are reported in
+/// [SupportableAnchorChannels::immature_utxos]. UTXOs without a corresponding maturity are
+/// counted as by [get_supportable_anchor_channels].
+pub fn get_supportable_anchor_channels_with_maturity(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], maturities: &[UtxoMaturity],
+) -> SupportableAnchorChannels {
+	supportable_anchor_channels(context, utxos, maturities, true)
+		.expect("Saturating reserve calculations never fail")
+}
+
+fn supportable_anchor_channels(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], maturities: &[UtxoMaturity],
+	saturate: bool,
+) -> Result<SupportableAnchorChannels, ReserveCalculationError> {
+	// When saturating, a reserve that can't be represented is treated as unaffordable.
+	let saturated_reserve = |reserve: Result<Amount, ReserveCalculationError>| match reserve {
+		Err(_) if saturate => Ok(Amount::MAX),
+		reserve => reserve,
+	};
+	// Get the reserve needed per channel, accounting for the actual satisfaction weight below.
+	// Sets of fractional UTXOs may mix input types, for which the wallet's type is assumed.
+	let fractional_reserve_per_channel =
+		saturated_reserve(try_get_reserve_per_channel_with_input(
+			context,
+			WalletInputType::from_context(context),
+			Weight::ZERO,
+		))?;
+	if fractional_reserve_per_channel == Amount::ZERO && !saturate {
+		return Err(ReserveCalculationError::ZeroReserve);
+	}
+
+	let mut total_fractional_amount = Amount::from_sat(0);
+	let mut num_whole_utxos = 0;
+	let mut uneconomical_utxos = Vec::new();
+	let mut zero_value_utxos = Vec::new();
+	let mut num_duplicate_utxos = 0;
+	let mut immature_utxos = Vec::new();
+	let mut immature_value = Amount::ZERO;
+	let mut seen_outpoints = new_hash_set();
+	for (idx, utxo) in utxos.iter().enumerate() {
+		// Counting the same UTXO more than once would overstate the reserve.
+		if !seen_outpoints.insert(utxo.outpoint) {
+			num_duplicate_utxos += 1;
+			continue;
+		}
+		if utxo.output.value == Amount::ZERO {
+			zero_value_utxos.push(utxo.outpoint);
+			continue;
+		}
+		// UTXOs which may not be spendable when a force-close happens can't fund its claims.
+		if let Some(maturity) = maturities.get(idx) {
+			if !maturity.counts_towards_reserve(context) {
+				immature_utxos.push(utxo.outpoint);
+				immature_value =
+					immature_value.checked_add(utxo.output.value).unwrap_or(Amount::MAX);
+				continue;
+			}
+		}
+		// The UTXO's satisfaction is part of the transactions it funds, e.g. the anchor spend.
+		let satisfaction_fee = match anchor_output_spend_transaction_weight(context, Weight::ZERO)
+			.checked_add(utxo.satisfaction_weight)
+		{
+			Some(_) => context
+				.upper_bound_fee_rate
+				.fee_wu(utxo.satisfaction_weight)
+				.ok_or(ReserveCalculationError::FeeOverflow),
+			None => Err(ReserveCalculationError::WeightOverflow),
+		};
+		// When saturating, a UTXO whose satisfaction can't be paid for is uneconomical.
+		let amount = match satisfaction_fee {
+			Ok(fee) => utxo.output.value.checked_sub(fee),
+			Err(_) if saturate => None,
+			Err(e) => return Err(e),
+		};
+		let amount = match amount {
+			Some(amount) if amount > Amount::ZERO => amount,
+			_ => {
+				uneconomical_utxos.push(utxo.outpoint);
+				continue;
+			},
+		};
+		// A whole UTXO funds all transactions of a channel, which thus spend inputs of its type.
+		let input_type =
+			WalletInputType::from_utxo(utxo).unwrap_or(WalletInputType::from_context(context));
+		let whole_reserve_per_channel = saturated_reserve(try_get_reserve_per_channel_with_input(
+			context,
+			input_type,
+			Weight::ZERO,
+		))?;
+		if amount >= whole_reserve_per_channel {
+			num_whole_utxos += 1;
 		} else {
 			total_fractional_amount =
 				total_fractional_amount.checked_add(amount).unwrap_or(Amount::MAX);
 		}
 	}
-	// We require disjoint sets of UTXOs for the reserve of each channel,
-	// as claims are currently only aggregated per channel.
-	//
-	// A worst-case coin selection is assumed for fractional UTXOs, selecting up to double the
-	// required amount.
-	num_whole_utxos + total_fractional_amount.to_sat() / reserve_per_channel.to_sat() / 2
-}
+	// We require disjoint sets of UTXOs for the reserve of each channel,
+	// as claims are currently only aggregated per channel.
+	//
+	// A worst-case coin selection is assumed for fractional UTXOs, selecting up to double the
+	// required amount. With a zero reserve, e.g. at a zero fee rate, every economical UTXO covers
+	// the reserve of a channel by itself, so there is no fractional amount to divide.
+	let num_fractional_channels = total_fractional_amount
+		.to_sat()
+		.checked_div(fractional_reserve_per_channel.to_sat())
+		.unwrap_or(0)
+		/ 2;
+	Ok(SupportableAnchorChannels {
+		num_channels: num_whole_utxos + num_fractional_channels,
+		uneconomical_utxos,
+		zero_value_utxos,
+		num_duplicate_utxos,
+		immature_utxos,
+		immature_value,
+	})
+}
+
+/// The number of anchor channels supported by the reserves of multiple wallets, each with its own
+/// [AnchorChannelReserveContext].
+///
+/// See [get_supportable_anchor_channels_multi].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiWalletSupportableAnchorChannels {
+	/// The total number of anchor channels that can be supported across all wallets.
+	pub num_channels: u64,
+	/// The number of anchor channels that can be supported by each wallet, in the order the
+	/// wallets were provided.
+	pub num_channels_per_wallet: Vec<u64>,
+}
+
+impl MultiWalletSupportableAnchorChannels {
+	/// Returns the index of the wallet which would fund the reserve of an additional anchor channel
+	/// if `num_anchor_channels` already rely on the reserves, or `None` if they are insufficient.
+	///
+	/// Channels are assigned to wallets in the order the wallets were provided, i.e., a wallet's
+	/// reserve is only relied upon once those of all previous wallets are exhausted.
+	pub fn next_channel_wallet(&self, num_anchor_channels: u64) -> Option<usize> {
+		let mut num_assigned_channels: u64 = 0;
+		for (wallet_index, num_channels) in self.num_channels_per_wallet.iter().enumerate() {
+			num_assigned_channels = num_assigned_channels.saturating_add(*num_channels);
+			if num_assigned_channels > num_anchor_channels {
+				return Some(wallet_index);
+			}
+		}
+		None
+	}
+}
+
+/// Calculates the number of anchor channels that can be supported by the reserves of multiple
+/// wallets, each provided as its [AnchorChannelReserveContext] and UTXOs, e.g., a P2WPKH hot
+/// wallet and a Taproot wallet with their own fee policies.
+///
+/// The number of channels supported by each wallet is calculated as with
+/// [get_supportable_anchor_channels], and summed. UTXOs of different wallets are not combined to
+/// support a channel, as the claims of a channel are funded by a single wallet, to which their
+/// change is returned as well.
+///
+/// UTXOs are deduplicated by their outpoint across wallets, only counting them towards the first
+/// wallet providing them.
+pub fn get_supportable_anchor_channels_multi(
+	wallets: &[(AnchorChannelReserveContext, &[Utxo])],
+) -> MultiWalletSupportableAnchorChannels {
+	let mut seen_outpoints = new_hash_set();
+	let num_channels_per_wallet: Vec<u64> = wallets
+		.iter()
+		.map(|(context, utxos)| {
+			// Counting the same UTXO towards multiple wallets would overstate the reserve.
+			let new_utxos: Vec<Utxo> = utxos
+				.iter()
+				.filter(|utxo| !seen_outpoints.contains(&utxo.outpoint))
+				.cloned()
+				.collect();
+			seen_outpoints.extend(new_utxos.iter().map(|utxo| utxo.outpoint));
+			get_supportable_anchor_channels(context, &new_utxos)
+		})
+		.collect();
+	let num_channels =
+		num_channels_per_wallet.iter().fold(0u64, |total, num| total.saturating_add(*num));
+	MultiWalletSupportableAnchorChannels { num_channels, num_channels_per_wallet }
+}
+
+/// Calculates the maximum [AnchorChannelReserveContext::expected_accepted_htlcs] for which the
+/// reserve provided by `utxos` supports `num_channels` anchor channels, as determined by
+/// [get_supportable_anchor_channels].
+///
+/// The [AnchorChannelReserveContext::expected_accepted_htlcs] of `context` is ignored. The result
+/// saturates at the maximum number of HTLCs on a commitment transaction, and is zero if the
+/// reserve does not support `num_channels` channels even without any accepted HTLCs.
+///
+/// The result may be used to limit inbound HTLCs per channel with
+/// [ChannelManager::set_max_inbound_htlcs_for_reserve], or the parts of inbound multi-part
+/// payments per channel with [UserConfig::max_inbound_payment_parts_for_reserve].
+///
+/// [ChannelManager::set_max_inbound_htlcs_for_reserve]: crate::ln::channelmanager::ChannelManager::set_max_inbound_htlcs_for_reserve
+/// [UserConfig::max_inbound_payment_parts_for_reserve]: crate::util::config::UserConfig::max_inbound_payment_parts_for_reserve
+pub fn max_supportable_accepted_htlcs(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], num_channels: u64,
+) -> u16 {
+	let supports_channels = |expected_accepted_htlcs| {
+		let context = AnchorChannelReserveContext { expected_accepted_htlcs, ..context.clone() };
+		get_supportable_anchor_channels(&context, utxos) >= num_channels
+	};
+	// The reserve per channel increases with the number of HTLCs, so the number of supportable
+	// channels is non-increasing in it. As the reserve of fractional UTXOs does not scale linearly
+	// due to the worst-case coin selection, we binary search for the maximum.
+	let (mut low, mut high) = (0, max_htlcs(&ChannelTypeFeatures::only_static_remote_key()));
+	if !supports_channels(low) {
+		return 0;
+	}
+	while low < high {
+		let mid = low + (high - low + 1) / 2;
+		if supports_channels(mid) {
+			low = mid;
+		} else {
+			high = mid - 1;
+		}
+	}
+	low
+}
+
+/// Suggests an [AnchorChannelReserveContext] calibrated from the [OnchainResolutionStats] of
+/// channels resolved in the past, as returned by [ChannelMonitor::get_onchain_resolution_stats].
+///
+/// The [AnchorChannelReserveContext::expected_accepted_htlcs] is set to the highest number of HTLC
+/// outputs claimed in any resolution, and the [AnchorChannelReserveContext::upper_bound_fee_rate]
+/// to the highest fee rate paid by any claim. All other fields are taken from `base_context`, which
+/// is returned as-is if none of the `historical_stats` include any confirmed claims.
+///
+/// Note that the HTLCs claimed on-chain include offered ones, and that past resolutions do not
+/// bound future fee rates, so a safety margin should still be maintained on top.
+pub fn suggest_reserve_context(
+	historical_stats: &[OnchainResolutionStats], base_context: &AnchorChannelReserveContext,
+) -> AnchorChannelReserveContext {
+	let mut resolutions =
+		historical_stats.iter().filter(|stats| stats.num_confirmed_claims > 0).peekable();
+	if resolutions.peek().is_none() {
+		return base_context.clone();
+	}
+	let mut max_htlc_outputs_claimed = 0;
+	let mut max_feerate_sat_per_1000_weight = 0;
+	for stats in resolutions {
+		max_htlc_outputs_claimed = max(max_htlc_outputs_claimed, stats.num_htlc_outputs_claimed);
+		max_feerate_sat_per_1000_weight =
+			max(max_feerate_sat_per_1000_weight, stats.max_claim_feerate_sat_per_1000_weight);
+	}
+	AnchorChannelReserveContext {
+		expected_accepted_htlcs: max_htlc_outputs_claimed.try_into().unwrap_or(u16::MAX),
+		upper_bound_fee_rate: FeeRate::from_sat_per_kwu(max_feerate_sat_per_1000_weight as u64),
+		..base_context.clone()
+	}
+}
+
+pub(crate) fn is_anchor_channel_type(channel_type: &ChannelTypeFeatures) -> bool {
+	channel_type.supports_anchors_zero_fee_htlc_tx()
+		|| channel_type.supports_anchor_zero_fee_commitments()
+}
+
+/// Returns the reserve recommended for a channel of type `channel_type`, as
+/// [get_reserve_per_channel] does, or `None` if it is not an anchor channel.
+///
+/// The expected numbers of accepted and offered HTLCs of `context` are capped at the limits
+/// negotiated for the channel, and its channel type replaces [AnchorChannelReserveContext::channel_type].
+pub(crate) fn get_reserve_for_channel(
+	context: &AnchorChannelReserveContext, channel_type: &ChannelTypeFeatures,
+	holder_max_accepted_htlcs: u16, counterparty_max_accepted_htlcs: u16,
+) -> Option<Amount> {
+	if !is_anchor_channel_type(channel_type) {
+		return None;
+	}
+	let context = AnchorChannelReserveContext {
+		expected_accepted_htlcs: min(context.expected_accepted_htlcs, holder_max_accepted_htlcs),
+		expected_offered_htlcs: min(
+			context.expected_offered_htlcs,
+			counterparty_max_accepted_htlcs,
+		),
+		channel_type: if channel_type.supports_anchor_zero_fee_commitments() {
+			AnchorChannelType::ZeroFeeCommitments
+		} else {
+			AnchorChannelType::AnchorsZeroFeeHtlcTx
+		},
+		..context.clone()
+	};
+	Some(get_reserve_per_channel(&context))
+}
+
+/// The type of a transaction broadcast to resolve a channel on-chain after a unilateral closure.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ForceCloseTransactionType {
+	/// Our commitment transaction, spending the funding output.
+	Commitment,
+	/// The child transaction spending our anchor output to bump the fee of the commitment
+	/// transaction. Only broadcast for anchor channels.
+	AnchorSpend,
+	/// An HTLC-success transaction, claiming an inbound HTLC with its preimage.
+	HtlcSuccess,
+	/// An HTLC-timeout transaction, claiming an outbound HTLC back after its expiry.
+	HtlcTimeout,
+}
+
+/// The estimated weight and fee of a single transaction in a [ForceCloseCostEstimate].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForceCloseTransactionCost {
+	/// The type of the transaction.
+	pub transaction_type: ForceCloseTransactionType,
+	/// The estimated weight of the transaction, including any wallet input and change output
+	/// added to provide fees.
+	pub weight: Weight,
+	/// The estimated fee paid by the transaction.
+	pub fee: Amount,
+}
+
+/// An estimate of the on-chain fees paid to resolve a channel after a unilateral closure, as
+/// returned by [estimate_force_close_cost].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForceCloseCostEstimate {
+	/// The transactions we would broadcast, with one HTLC transaction per pending non-dust HTLC.
+	pub transactions: Vec<ForceCloseTransactionCost>,
+	/// Whether the fees were committed to when the transactions were signed, i.e., whether the
+	/// channel does not support anchor outputs and the fees can't be changed at broadcast time.
+	pub fees_committed: bool,
+	/// The sum of the fees of [Self::transactions].
+	pub total_fee: Amount,
+}
+
+/// Estimates the worst-case on-chain fees paid to resolve a channel with the given `channel_type`
+/// after broadcasting our commitment transaction.
+///
+/// `num_accepted_htlcs` and `num_offered_htlcs` are the number of inbound and outbound HTLCs which
+/// are not dust on our commitment transaction at `commitment_feerate_sat_per_1000_weight`. As an
+/// upper bound, each of them is assumed to be resolved by us in a separate HTLC transaction, even
+/// though the counterparty may claim some of them, and we may aggregate claims when possible.
+///
+/// For anchor channels, the fees are provided at broadcast time, so the commitment transaction is
+/// assumed to be bumped to `fee_rate` with an [ForceCloseTransactionType::AnchorSpend] child
+/// transaction, and HTLC transactions to pay `fee_rate` by spending a wallet input of the type
+/// indicated by `taproot_wallet`. Otherwise, the fees committed to in the commitment and HTLC
+/// transactions at `commitment_feerate_sat_per_1000_weight` are reported and `fee_rate` is
+/// ignored.
+pub fn estimate_force_close_cost(
+	channel_type: &ChannelTypeFeatures, commitment_feerate_sat_per_1000_weight: u32,
+	num_accepted_htlcs: usize, num_offered_htlcs: usize, fee_rate: FeeRate, taproot_wallet: bool,
+) -> ForceCloseCostEstimate {
+	let num_htlcs = num_accepted_htlcs + num_offered_htlcs;
+	let committed_commitment_fee = Amount::from_sat(commit_tx_fee_sat(
+		commitment_feerate_sat_per_1000_weight,
+		num_htlcs,
+		channel_type,
+	));
+	let mut transactions = Vec::with_capacity(2 + num_htlcs);
+	let fees_committed = !is_anchor_channel_type(channel_type);
+	let (htlc_success_weight, htlc_timeout_weight) = if fees_committed {
+		transactions.push(ForceCloseTransactionCost {
+			transaction_type: ForceCloseTransactionType::Commitment,
+			weight: Weight::from_wu(
+				commitment_tx_base_weight(channel_type)
+					+ num_htlcs as u64 * COMMITMENT_TRANSACTION_PER_HTLC_WEIGHT,
+			),
+			fee: committed_commitment_fee,
+		});
+		(
+			Weight::from_wu(htlc_success_tx_weight(channel_type)),
+			Weight::from_wu(htlc_timeout_tx_weight(channel_type)),
+		)
+	} else {
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: fee_rate,
+			taproot_wallet,
+			channel_type: if channel_type.supports_anchor_zero_fee_commitments() {
+				AnchorChannelType::ZeroFeeCommitments
+			} else {
+				AnchorChannelType::AnchorsZeroFeeHtlcTx
+			},
+			..Default::default()
+		};
+		let input_type = WalletInputType::from_context(&context);
+		let commitment_weight =
+			commitment_transaction_weight(num_htlcs as u64, context.channel_type);
+		let anchor_spend_weight =
+			anchor_output_spend_transaction_weight(&context, input_type.input_weight());
+		// The anchor spend pays for the package of both transactions, net of the fee already
+		// committed to in the commitment transaction.
+		let package_fee = fee_rate.fee_wu(commitment_weight + anchor_spend_weight);
+		transactions.push(ForceCloseTransactionCost {
+			transaction_type: ForceCloseTransactionType::Commitment,
+			weight: commitment_weight,
+			fee: committed_commitment_fee,
+		});
+		transactions.push(ForceCloseTransactionCost {
+			transaction_type: ForceCloseTransactionType::AnchorSpend,
+			weight: anchor_spend_weight,
+			fee: package_fee
+				.unwrap_or(Amount::MAX)
+				.checked_sub(committed_commitment_fee)
+				.unwrap_or(Amount::ZERO),
+		});
+		(
+			htlc_success_transaction_weight(&context, input_type),
+			htlc_timeout_transaction_weight(&context, input_type),
+		)
+	};
+	let htlc_transaction_cost = |transaction_type, weight: Weight| {
+		let fee = if fees_committed {
+			// Committed fees are rounded down, as for the commitment transaction.
+			Amount::from_sat(commitment_feerate_sat_per_1000_weight as u64 * weight.to_wu() / 1000)
+		} else {
+			fee_rate.fee_wu(weight).unwrap_or(Amount::MAX)
+		};
+		ForceCloseTransactionCost { transaction_type, weight, fee }
+	};
+	for _ in 0..num_accepted_htlcs {
+		transactions.push(htlc_transaction_cost(
+			ForceCloseTransactionType::HtlcSuccess,
+			htlc_success_weight,
+		));
+	}
+	for _ in 0..num_offered_htlcs {
+		transactions.push(htlc_transaction_cost(
+			ForceCloseTransactionType::HtlcTimeout,
+			htlc_timeout_weight,
+		));
+	}
+	let total_fee = transactions
+		.iter()
+		.try_fold(Amount::ZERO, |total, transaction| total.checked_add(transaction.fee))
+		.unwrap_or(Amount::MAX);
+	ForceCloseCostEstimate { transactions, fees_committed, total_fee }
+}
+
+#[cfg(feature = "std")]
+fn get_num_anchor_channels(
+	channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	sponsored_channels: &[ChannelId],
+) -> u64 {
+	get_anchor_channel_ids(channel_manager, chain_monitor, sponsored_channels).len() as u64
+}
+
+#[cfg(feature = "std")]
+fn get_anchor_channel_ids(
+	channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	sponsored_channels: &[ChannelId],
+) -> HashSet<ChannelId> {
+	get_anchor_channel_ids_inspecting_monitors(
+		channel_manager,
+		chain_monitor,
+		sponsored_channels,
+		&mut |_| {},
+	)
+}
+
+/// Lists the anchor channels relying on the reserve as [get_anchor_channel_ids] does, additionally
+/// calling `inspect` with the summary of each unresolved monitor within the same pass over the
+/// monitors.
+#[cfg(feature = "std")]
+fn get_anchor_channel_ids_inspecting_monitors(
+	channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	sponsored_channels: &[ChannelId], inspect: &mut dyn FnMut(&MonitorSummary),
+) -> HashSet<ChannelId> {
+	// Channels are identified by their `ChannelId`, which a splice does not change. Thus, a channel
+	// with both its current and a pending splice funding outpoint is only counted once.
+	let mut anchor_channels = new_hash_set();
+	// Calculate the number of in-progress anchor channels by inspecting ChannelMonitors which may
+	// still require fees to resolve. This includes channels that are in the process of being
+	// resolved on-chain, but not those whose remaining balances are only awaiting confirmations,
+	// unless a claim we bumped with external funds may still be reorged.
+	// Monitors without claimable balances never require fees, so resolved ones are skipped.
+	// The summaries are read from the monitor set itself rather than by looking up each monitor,
+	// so no monitor can be missed due to a failed lookup, which would undercount the reserve.
+	chain_monitor.for_each_monitor_summary(false, &mut |summary| {
+		if is_anchor_channel_type(&summary.channel_type_features)
+			&& summary.needs_exogenous_fee_reserve
+		{
+			anchor_channels.insert(summary.channel_id);
+		}
+		inspect(summary);
+	});
+	// Also include anchor channels that don't have a ChannelMonitor yet, in any state before their
+	// funding transaction confirms. These are listed in a single pass over the `ChannelManager`, as
+	// combining `list_channels` and `list_pending_channel_negotiations` could miss a channel whose
+	// funding completes in between, changing its channel ID. As the monitors are inspected first, a
+	// channel funded in the meantime is still listed by the `ChannelManager`.
+	anchor_channels.extend(channel_manager.list_channels_requiring_anchor_reserves());
+	// Sponsored channels are fee-bumped with a third party's UTXOs rather than our reserve.
+	anchor_channels.retain(|channel_id| !sponsored_channels.contains(channel_id));
+	anchor_channels
+}
+
+/// A closed anchor channel whose claims no longer require fees paid from outside of the channel,
+/// but which still relies on the anchor channel reserve until they can't be reorged anymore, as
+/// returned by [get_pending_reserve_releases].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct PendingReserveRelease {
+	/// The channel ID of the closed channel.
+	pub channel_id: ChannelId,
+	/// The block height at which the channel no longer relies on the reserve, as returned by
+	/// [ChannelMonitor::reserve_release_height].
+	pub release_height: u32,
+}
+
+impl_writeable_tlv_based!(PendingReserveRelease, {
+	(0, channel_id, required),
+	(2, release_height, required),
+});
+
+/// Lists the closed anchor channels tracked by `chain_monitor` which still rely on the anchor
+/// channel reserve but will release it at a known height, e.g., to display a countdown until the
+/// number of supportable anchor channels increases.
+///
+/// The release heights are updated as blocks are connected and disconnected. Channels whose claims
+/// may still require fees, e.g., as HTLCs remain unresolved, are not included as their release
+/// height is not yet known.
+#[cfg(feature = "std")]
+pub fn get_pending_reserve_releases(
+	chain_monitor: &DynChainMonitor<'_>,
+) -> Vec<PendingReserveRelease> {
+	let mut pending_releases = Vec::new();
+	chain_monitor.for_each_monitor_summary(false, &mut |summary| {
+		if !is_anchor_channel_type(&summary.channel_type_features)
+			|| !summary.needs_exogenous_fee_reserve
+		{
+			return;
+		}
+		if let Some(release_height) = summary.reserve_release_height {
+			let channel_id = summary.channel_id;
+			pending_releases.push(PendingReserveRelease { channel_id, release_height });
+		}
+	});
+	pending_releases
+}
+
+/// Verifies whether the anchor channel reserve provided by `utxos` is sufficient to support
+/// an additional anchor channel.
+///
+/// This should be verified:
+/// - Before opening a new outbound anchor channel with [ChannelManager::create_channel].
+/// - Before accepting a new inbound anchor channel while handling [Event::OpenChannelRequest].
+///
+/// As with [get_supportable_anchor_channels], duplicate and zero-value `utxos` are not counted
+/// towards the reserve.
+///
+/// Without the `std` feature, [AnchorChannelReserveCheck::with_num_anchor_channels] can be used
+/// instead.
+///
+/// [ChannelManager::create_channel]: crate::ln::channelmanager::ChannelManager::create_channel
+/// [Event::OpenChannelRequest]: crate::events::Event::OpenChannelRequest
+#[cfg(feature = "std")]
+pub fn can_support_additional_anchor_channel(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], channel_manager: &DynChannelManager<'_>,
+	chain_monitor: &DynChainMonitor<'_>,
+) -> bool {
+	AnchorChannelReserveCheck::new(context, utxos, channel_manager, chain_monitor)
+		.can_support_additional_anchor_channel()
+}
+
+/// Verifies whether the anchor channel reserves of multiple wallets are sufficient to support an
+/// additional anchor channel, as [can_support_additional_anchor_channel] does for a single wallet.
+///
+/// The reserves are combined as with [get_supportable_anchor_channels_multi]. Use
+/// [MultiWalletSupportableAnchorChannels::next_channel_wallet] to determine which wallet would
+/// fund the additional channel.
+#[cfg(feature = "std")]
+pub fn can_support_additional_anchor_channel_multi(
+	wallets: &[(AnchorChannelReserveContext, &[Utxo])], channel_manager: &DynChannelManager<'_>,
+	chain_monitor: &DynChainMonitor<'_>,
+) -> bool {
+	AnchorChannelReserveCheck::new_multi(wallets, channel_manager, chain_monitor)
+		.can_support_additional_anchor_channel()
+}
+
+/// The share of the anchor channel reserve a channel with a counterparty consumes, as configured
+/// per counterparty via [AnchorChannelReservePolicies].
+///
+/// Channels with trusted counterparties, e.g., our LSP, are unlikely to be force-closed with
+/// unresolved HTLCs and thus may rely on a reduced share of the reserve.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "snake_case"))]
+pub enum AnchorChannelReservePolicy {
+	/// The channel consumes the full reserve per channel.
+	Full,
+	/// The channel consumes the given fraction of the reserve per channel, in parts per million.
+	///
+	/// Fractions above 1_000_000 are treated as [Self::Full].
+	Fraction {
+		/// The fraction of the reserve per channel, in parts per million.
+		reserve_ppm: u32,
+	},
+	/// The channel doesn't consume any reserve, i.e., the counterparty is trusted not to force-close
+	/// the channel while claims require fees.
+	None,
+}
+
+impl AnchorChannelReservePolicy {
+	fn reserve_share_ppm(&self) -> u64 {
+		match self {
+			AnchorChannelReservePolicy::Full => 1_000_000,
+			AnchorChannelReservePolicy::Fraction { reserve_ppm } => {
+				min(*reserve_ppm as u64, 1_000_000)
+			},
+			AnchorChannelReservePolicy::None => 0,
+		}
+	}
+}
+
+impl_writeable_tlv_based_enum!(AnchorChannelReservePolicy,
+	(0, Full) => {},
+	(2, Fraction) => {
+		(0, reserve_ppm, required),
+	},
+	(4, None) => {},
+);
+
+/// The [AnchorChannelReservePolicy] of each counterparty, weighting the anchor channels with them
+/// when checking whether the reserve is sufficient, e.g., via
+/// [can_support_additional_anchor_channel_with_policies] or an [AnchorReserveChannelAcceptor]
+/// configured via [AnchorReserveChannelAcceptor::with_reserve_policies].
+///
+/// Counterparties without a policy use the default policy, which is [AnchorChannelReservePolicy::Full]
+/// unless created via [Self::with_default_policy].
+///
+/// Closed channels which are still being resolved on-chain always consume the full reserve, as
+/// their resolution no longer depends on the counterparty's cooperation.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorChannelReservePolicies {
+	default_policy: AnchorChannelReservePolicy,
+	counterparty_policies: HashMap<PublicKey, AnchorChannelReservePolicy>,
+}
+
+impl_writeable_tlv_based!(AnchorChannelReservePolicies, {
+	(0, default_policy, required),
+	(2, counterparty_policies, required),
+});
+
+impl Default for AnchorChannelReservePolicies {
+	fn default() -> Self {
+		Self::with_default_policy(AnchorChannelReservePolicy::Full)
+	}
+}
+
+impl AnchorChannelReservePolicies {
+	/// Creates a set of policies applying `default_policy` to all counterparties.
+	pub fn with_default_policy(default_policy: AnchorChannelReservePolicy) -> Self {
+		Self { default_policy, counterparty_policies: new_hash_map() }
+	}
+
+	/// Sets the policy for channels with the counterparty with the given `node_id`.
+	pub fn set_counterparty_policy(
+		&mut self, node_id: PublicKey, policy: AnchorChannelReservePolicy,
+	) {
+		self.counterparty_policies.insert(node_id, policy);
+	}
+
+	/// Removes the policy for channels with the counterparty with the given `node_id`, such that
+	/// the default policy applies to them again.
+	pub fn remove_counterparty_policy(
+		&mut self, node_id: &PublicKey,
+	) -> Option<AnchorChannelReservePolicy> {
+		self.counterparty_policies.remove(node_id)
+	}
+
+	/// Returns the policy for channels with the counterparty with the given `node_id`.
+	pub fn policy_for(&self, node_id: &PublicKey) -> AnchorChannelReservePolicy {
+		self.counterparty_policies.get(node_id).copied().unwrap_or(self.default_policy)
+	}
+
+	/// Returns the share of the reserve per channel consumed by channels with the given
+	/// counterparty, in parts per million, or the full reserve if the counterparty is unknown.
+	fn reserve_share_ppm(&self, node_id: Option<&PublicKey>) -> u64 {
+		node_id.map_or(1_000_000, |node_id| self.policy_for(node_id).reserve_share_ppm())
+	}
+}
+
+/// Returns whether a reserve supporting `num_supportable_channels` anchor channels is sufficient
+/// for anchor channels consuming `reserve_share_ppm` in total.
+fn supports_reserve_share(num_supportable_channels: u64, reserve_share_ppm: u64) -> bool {
+	num_supportable_channels.saturating_mul(1_000_000) >= reserve_share_ppm
+}
+
+/// Returns the number of full anchor channels consuming `reserve_share_ppm` in total, rounded up.
+fn num_weighted_channels(reserve_share_ppm: u64) -> u64 {
+	(reserve_share_ppm + 999_999) / 1_000_000
+}
+
+/// Returns the total share of the reserve per channel, in parts per million, consumed by the
+/// anchor channels currently tracked by `channel_manager` and `chain_monitor`, weighted by the
+/// policies of their counterparties.
+#[cfg(feature = "std")]
+fn get_anchor_channel_reserve_share(
+	channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	policies: &AnchorChannelReservePolicies,
+) -> u64 {
+	let anchor_channels = get_anchor_channel_ids(channel_manager, chain_monitor, &[]);
+	let mut counterparties = new_hash_map();
+	for channel in channel_manager.list_channels() {
+		counterparties.insert(channel.channel_id, channel.counterparty.node_id);
+	}
+	for negotiation in channel_manager.list_pending_channel_negotiations() {
+		counterparties.insert(negotiation.temporary_channel_id, negotiation.counterparty_node_id);
+	}
+	anchor_channels.iter().fold(0u64, |total, channel_id| {
+		total.saturating_add(policies.reserve_share_ppm(counterparties.get(channel_id)))
+	})
+}
+
+/// Verifies whether the anchor channel reserve provided by `utxos` is sufficient to support an
+/// additional anchor channel with the counterparty with the given `counterparty_node_id`, as
+/// [can_support_additional_anchor_channel] does, but weighting each anchor channel by the
+/// [AnchorChannelReservePolicy] of its counterparty.
+///
+/// Channels with counterparties whose policy is [AnchorChannelReservePolicy::None] thus don't
+/// consume any of the reserve, and can always be supported.
+#[cfg(feature = "std")]
+pub fn can_support_additional_anchor_channel_with_policies(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], channel_manager: &DynChannelManager<'_>,
+	chain_monitor: &DynChainMonitor<'_>, policies: &AnchorChannelReservePolicies,
+	counterparty_node_id: &PublicKey,
+) -> bool {
+	let reserve_share = get_anchor_channel_reserve_share(channel_manager, chain_monitor, policies)
+		.saturating_add(policies.reserve_share_ppm(Some(counterparty_node_id)));
+	supports_reserve_share(get_supportable_anchor_channels(context, utxos), reserve_share)
+}
+
+/// A snapshot of the anchor channel reserve provided by a set of UTXOs against the anchor channels
+/// that currently rely on it.
+///
+/// This can be provided to [ChannelManager::preflight_open_channel] to verify the reserve is
+/// sufficient before opening a new outbound anchor channel.
+///
+/// [ChannelManager::preflight_open_channel]: crate::ln::channelmanager::ChannelManager::preflight_open_channel
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct AnchorChannelReserveCheck {
+	num_supportable_channels: u64,
+	num_anchor_channels: u64,
+}
+
+impl_writeable_tlv_based!(AnchorChannelReserveCheck, {
+	(0, num_supportable_channels, required),
+	(2, num_anchor_channels, required),
+});
+
+impl AnchorChannelReserveCheck {
+	/// Takes a snapshot of the anchor channel reserve provided by `utxos` and the anchor channels
+	/// currently tracked by `channel_manager` and `chain_monitor`.
+	#[cfg(feature = "std")]
+	pub fn new(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo],
+		channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	) -> Self {
+		Self::new_with_sponsored_channels(context, utxos, channel_manager, chain_monitor, &[])
+	}
+
+	/// Takes a snapshot of the anchor channel reserve provided by `utxos` and the anchor channels
+	/// currently tracked by `channel_manager` and `chain_monitor`, as [Self::new] does, but
+	/// excluding the channels in `sponsored_channels`.
+	///
+	/// This should be used for channels whose claims are fee-bumped with UTXOs of a third party,
+	/// e.g., as registered via [BumpTransactionEventHandler::register_fee_sponsor], as they don't
+	/// rely on the reserve.
+	///
+	/// [BumpTransactionEventHandler::register_fee_sponsor]: crate::events::bump_transaction::BumpTransactionEventHandler::register_fee_sponsor
+	#[cfg(feature = "std")]
+	pub fn new_with_sponsored_channels(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo],
+		channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+		sponsored_channels: &[ChannelId],
+	) -> Self {
+		let num_anchor_channels =
+			get_num_anchor_channels(channel_manager, chain_monitor, sponsored_channels);
+		Self::with_num_anchor_channels(context, utxos, num_anchor_channels)
+	}
+
+	/// Takes a snapshot of the anchor channel reserve provided by `utxos` and the anchor channels
+	/// currently tracked by `channel_manager` and `chain_monitor`, as [Self::new] does, but
+	/// weighting each anchor channel by the [AnchorChannelReservePolicy] of its counterparty.
+	///
+	/// [Self::num_anchor_channels] is the number of channels consuming the full reserve per channel
+	/// the weighted channels amount to, rounded up. Use
+	/// [can_support_additional_anchor_channel_with_policies] to account for the policy of the
+	/// counterparty of an additional channel.
+	#[cfg(feature = "std")]
+	pub fn new_with_policies(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo],
+		channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+		policies: &AnchorChannelReservePolicies,
+	) -> Self {
+		let reserve_share =
+			get_anchor_channel_reserve_share(channel_manager, chain_monitor, policies);
+		Self::with_num_anchor_channels(context, utxos, num_weighted_channels(reserve_share))
+	}
+
+	/// Takes a snapshot of the anchor channel reserve provided by `utxos` against the given number
+	/// of anchor channels currently relying on it.
+	///
+	/// `num_anchor_channels` should include all anchor channels listed by
+	/// [ChannelManager::list_channels] or [ChannelManager::list_pending_channel_negotiations], as
+	/// well as closed anchor channels for which [ChannelMonitor::needs_exogenous_fee_reserve] returns
+	/// true, as they may still need to be resolved on-chain.
+	///
+	/// [ChannelManager::list_channels]: crate::ln::channelmanager::ChannelManager::list_channels
+	/// [ChannelManager::list_pending_channel_negotiations]: crate::ln::channelmanager::ChannelManager::list_pending_channel_negotiations
+	pub fn with_num_anchor_channels(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo], num_anchor_channels: u64,
+	) -> Self {
+		AnchorChannelReserveCheck {
+			num_supportable_channels: get_supportable_anchor_channels(context, utxos),
+			num_anchor_channels,
+		}
+	}
+
+	/// Takes a snapshot of the anchor channel reserves of multiple wallets, combined as with
+	/// [get_supportable_anchor_channels_multi], and the anchor channels currently tracked by
+	/// `channel_manager` and `chain_monitor`.
+	#[cfg(feature = "std")]
+	pub fn new_multi(
+		wallets: &[(AnchorChannelReserveContext, &[Utxo])],
+		channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	) -> Self {
+		let num_anchor_channels = get_num_anchor_channels(channel_manager, chain_monitor, &[]);
+		Self::with_num_anchor_channels_multi(wallets, num_anchor_channels)
+	}
+
+	/// Takes a snapshot of the anchor channel reserves of multiple wallets, combined as with
+	/// [get_supportable_anchor_channels_multi], against the given number of anchor channels
+	/// currently relying on them.
+	///
+	/// See [Self::with_num_anchor_channels] for which channels to include in
+	/// `num_anchor_channels`.
+	pub fn with_num_anchor_channels_multi(
+		wallets: &[(AnchorChannelReserveContext, &[Utxo])], num_anchor_channels: u64,
+	) -> Self {
+		AnchorChannelReserveCheck {
+			num_supportable_channels: get_supportable_anchor_channels_multi(wallets).num_channels,
+			num_anchor_channels,
+		}
+	}
+
+	/// Returns whether the reserve is sufficient to support an additional anchor channel.
+	pub fn can_support_additional_anchor_channel(&self) -> bool {
+		self.num_supportable_channels > self.num_anchor_channels
+	}
+
+	/// Returns the number of anchor channels the reserve is sufficient to support.
+	pub fn num_supportable_channels(&self) -> u64 {
+		self.num_supportable_channels
+	}
+
+	/// Returns the number of anchor channels relying on the reserve.
+	pub fn num_anchor_channels(&self) -> u64 {
+		self.num_anchor_channels
+	}
+
+	/// Returns whether the reserve is sufficient to support an additional anchor channel, as
+	/// [Self::can_support_additional_anchor_channel] does, recording the decision for the channel
+	/// with the given `channel_id` to `audit_logger`.
+	///
+	/// `context` and `utxos` should be the ones this check was created with.
+	pub fn audit_additional_anchor_channel(
+		&self, context: &AnchorChannelReserveContext, utxos: &[Utxo], channel_id: ChannelId,
+		audit_logger: &dyn ReserveAuditLogger,
+	) -> bool {
+		let num_anchor_channels = self.num_anchor_channels;
+		let num_supportable_channels = self.num_supportable_channels;
+		let can_support = self.can_support_additional_anchor_channel();
+		let decision = if can_support {
+			ReserveAuditDecision::ChannelAllowed { num_anchor_channels, num_supportable_channels }
+		} else {
+			ReserveAuditDecision::ChannelDenied { num_anchor_channels, num_supportable_channels }
+		};
+		audit_logger.log_entry(ReserveAuditEntry::for_channel_decision(
+			context, utxos, channel_id, decision,
+		));
+		can_support
+	}
+}
+
+/// The maximum number of channels an [AnchorReserveReport] lists individually. The remaining
+/// channels are only counted, such that the report remains bounded for nodes with thousands of
+/// channels.
+pub const MAX_REPORTED_CHANNELS: usize = 10;
+
+/// A class of UTXOs provided for the anchor channel reserve, as listed by an
+/// [AnchorReserveReport].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportedUtxoClass {
+	/// P2WPKH UTXOs counted towards the reserve.
+	P2wpkh,
+	/// P2TR UTXOs counted towards the reserve.
+	P2tr,
+	/// UTXOs of other script types counted towards the reserve, assuming the input type indicated
+	/// by [AnchorChannelReserveContext::taproot_wallet].
+	Other,
+	/// UTXOs not counted as their value does not exceed the fee to spend them, see
+	/// [SupportableAnchorChannels::uneconomical_utxos].
+	Uneconomical,
+	/// UTXOs not counted as they have a zero value.
+	ZeroValue,
+	/// UTXOs not counted as their outpoint was already provided by a previous UTXO.
+	Duplicate,
+}
+
+impl ReportedUtxoClass {
+	const ALL: [ReportedUtxoClass; 6] = [
+		ReportedUtxoClass::P2wpkh,
+		ReportedUtxoClass::P2tr,
+		ReportedUtxoClass::Other,
+		ReportedUtxoClass::Uneconomical,
+		ReportedUtxoClass::ZeroValue,
+		ReportedUtxoClass::Duplicate,
+	];
+
+	fn name(&self) -> &'static str {
+		match self {
+			ReportedUtxoClass::P2wpkh => "p2wpkh",
+			ReportedUtxoClass::P2tr => "p2tr",
+			ReportedUtxoClass::Other => "other",
+			ReportedUtxoClass::Uneconomical => "uneconomical",
+			ReportedUtxoClass::ZeroValue => "zero_value",
+			ReportedUtxoClass::Duplicate => "duplicate",
+		}
+	}
+}
+
+/// A summary of the anchor channel reserve for operators, whose [Display] implementation renders a
+/// stable, line-oriented report, e.g., to be logged or returned by a CLI command.
+///
+/// Each line consists of `key=value` pairs. The report lists the reserve per channel, the total
+/// reserve required by the anchor channels relying on it, the number of anchor channels the reserve
+/// can support, the number and total value of the provided UTXOs by [ReportedUtxoClass], the
+/// reserve of up to [MAX_REPORTED_CHANNELS] channels requiring the most, and the blockers to
+/// supporting an additional anchor channel, if any:
+///
+/// ```text
+/// reserve_per_channel_sat=<sat>
+/// num_anchor_channels=<count>
+/// total_reserve_required_sat=<sat>
+/// num_supportable_channels=<count>
+/// utxos class=<class> count=<count> value_sat=<sat>
+/// channel channel_id=<id> reserve_sat=<sat>
+/// channels_omitted=<count>
+/// blocker reason=<reason> count=<count> value_sat=<sat>
+/// ```
+///
+/// A `utxos` line is rendered for each [ReportedUtxoClass], while `channels_omitted` is only
+/// rendered if channels were omitted, and `blocker` lines only if an additional anchor channel
+/// can't be supported or UTXOs weren't counted. The `shortfall` blocker provides the additional
+/// UTXO value needed to support an additional anchor channel, as returned by
+/// [utxo_value_needed_for_channels].
+///
+/// [Display]: core::fmt::Display
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AnchorReserveReport {
+	reserve_per_channel: Amount,
+	num_anchor_channels: u64,
+	total_reserve_required: Amount,
+	num_supportable_channels: u64,
+	utxos_by_class: [(ReportedUtxoClass, usize, Amount); 6],
+	channels: Vec<(ChannelId, Amount)>,
+	num_omitted_channels: usize,
+	shortfall: Amount,
+}
+
+impl AnchorReserveReport {
+	/// Summarizes the anchor channel reserve provided by `utxos` for the anchor channels currently
+	/// tracked by `channel_manager` and `chain_monitor`, as counted by
+	/// [AnchorChannelReserveCheck::new].
+	///
+	/// The reserve of each channel is the one recommended via
+	/// [ChannelDetails::exogenous_fee_reserve_recommended_sat], or [get_reserve_per_channel] for
+	/// channels without one, e.g., closed channels.
+	#[cfg(feature = "std")]
+	pub fn new(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo],
+		channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	) -> Self {
+		let reserve_per_channel = get_reserve_per_channel(context);
+		let mut anchor_channels = get_anchor_channel_ids(channel_manager, chain_monitor, &[]);
+		let mut channel_reserves = Vec::with_capacity(anchor_channels.len());
+		for channel in channel_manager.list_channels() {
+			if anchor_channels.remove(&channel.channel_id) {
+				let reserve = channel
+					.exogenous_fee_reserve_recommended_sat
+					.map_or(reserve_per_channel, Amount::from_sat);
+				channel_reserves.push((channel.channel_id, reserve));
+			}
+		}
+		channel_reserves.extend(anchor_channels.into_iter().map(|id| (id, reserve_per_channel)));
+		Self::with_channel_reserves(context, utxos, channel_reserves)
+	}
+
+	/// Summarizes the anchor channel reserve provided by `utxos` for the anchor channels relying on
+	/// it, given as their channel IDs along with their individual reserves.
+	///
+	/// `channel_reserves` should include the channels counted towards `num_anchor_channels` by
+	/// [AnchorChannelReserveCheck::with_num_anchor_channels].
+	pub fn with_channel_reserves(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo],
+		mut channel_reserves: Vec<(ChannelId, Amount)>,
+	) -> Self {
+		let reserve_per_channel = get_reserve_per_channel(context);
+		let num_anchor_channels = channel_reserves.len() as u64;
+		let supportable = get_supportable_anchor_channels_detailed(context, utxos);
+
+		let mut utxos_by_class = ReportedUtxoClass::ALL.map(|class| (class, 0, Amount::ZERO));
+		let mut seen_outpoints = new_hash_set();
+		for utxo in utxos {
+			let class = if !seen_outpoints.insert(utxo.outpoint) {
+				ReportedUtxoClass::Duplicate
+			} else if supportable.zero_value_utxos.contains(&utxo.outpoint) {
+				ReportedUtxoClass::ZeroValue
+			} else if supportable.uneconomical_utxos.contains(&utxo.outpoint) {
+				ReportedUtxoClass::Uneconomical
+			} else {
+				match WalletInputType::from_utxo(utxo) {
+					Some(WalletInputType::P2wpkh) => ReportedUtxoClass::P2wpkh,
+					Some(WalletInputType::P2trKeyPath) => ReportedUtxoClass::P2tr,
+					None => ReportedUtxoClass::Other,
+				}
+			};
+			let entry = utxos_by_class.iter_mut().find(|(c, _, _)| *c == class).unwrap();
+			entry.1 += 1;
+			entry.2 = entry.2.checked_add(utxo.output.value).unwrap_or(Amount::MAX);
+		}
+
+		// List the channels requiring the most reserve first, breaking ties by channel ID such that
+		// the report is deterministic.
+		channel_reserves.sort_unstable_by(|(id_a, reserve_a), (id_b, reserve_b)| {
+			reserve_b.cmp(reserve_a).then_with(|| id_a.0.cmp(&id_b.0))
+		});
+		let num_omitted_channels = channel_reserves.len().saturating_sub(MAX_REPORTED_CHANNELS);
+		channel_reserves.truncate(MAX_REPORTED_CHANNELS);
+		channel_reserves.shrink_to_fit();
+
+		Self {
+			reserve_per_channel,
+			num_anchor_channels,
+			total_reserve_required: total_reserve_required(context, num_anchor_channels, false),
+			num_supportable_channels: supportable.num_channels,
+			utxos_by_class,
+			channels: channel_reserves,
+			num_omitted_channels,
+			shortfall: utxo_value_needed_for_channels(
+				context,
+				utxos,
+				num_anchor_channels.saturating_add(1),
+			),
+		}
+	}
+
+	/// Returns the number of anchor channels the reserve is sufficient to support.
+	pub fn num_supportable_channels(&self) -> u64 {
+		self.num_supportable_channels
+	}
+
+	/// Returns the number of anchor channels relying on the reserve.
+	pub fn num_anchor_channels(&self) -> u64 {
+		self.num_anchor_channels
+	}
+}
+
+impl core::fmt::Display for AnchorReserveReport {
+	fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+		writeln!(f, "reserve_per_channel_sat={}", self.reserve_per_channel.to_sat())?;
+		writeln!(f, "num_anchor_channels={}", self.num_anchor_channels)?;
+		writeln!(f, "total_reserve_required_sat={}", self.total_reserve_required.to_sat())?;
+		writeln!(f, "num_supportable_channels={}", self.num_supportable_channels)?;
+		for (class, count, value) in self.utxos_by_class.iter() {
+			writeln!(
+				f,
+				"utxos class={} count={} value_sat={}",
+				class.name(),
+				count,
+				value.to_sat()
+			)?;
+		}
+		for (channel_id, reserve) in self.channels.iter() {
+			writeln!(f, "channel channel_id={} reserve_sat={}", channel_id, reserve.to_sat())?;
+		}
+		if self.num_omitted_channels > 0 {
+			writeln!(f, "channels_omitted={}", self.num_omitted_channels)?;
+		}
+		if self.shortfall > Amount::ZERO {
+			writeln!(f, "blocker reason=shortfall count=1 value_sat={}", self.shortfall.to_sat())?;
+		}
+		let uncounted_classes = [
+			(ReportedUtxoClass::Uneconomical, "uneconomical_utxos"),
+			(ReportedUtxoClass::ZeroValue, "zero_value_utxos"),
+			(ReportedUtxoClass::Duplicate, "duplicate_utxos"),
+		];
+		for (class, reason) in uncounted_classes {
+			let (_, count, value) =
+				self.utxos_by_class.iter().find(|(c, _, _)| *c == class).unwrap();
+			if *count > 0 {
+				writeln!(
+					f,
+					"blocker reason={} count={} value_sat={}",
+					reason,
+					count,
+					value.to_sat()
+				)?;
+			}
+		}
+		Ok(())
+	}
+}
+
+/// Numeric metrics on the anchor channel reserve, e.g., to be exported to a monitoring system
+/// without parsing an [AnchorReserveReport], as gathered by [ReserveMetrics::collect].
+///
+/// The metrics are consistent with each other: if [Self::total_reserve_available_sat] is at least
+/// [Self::total_reserve_required_sat], then [Self::supportable_channels] is at least
+/// [Self::active_anchor_channels], and [Self::channels_in_onchain_resolution] never exceeds
+/// [Self::active_anchor_channels].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReserveMetrics {
+	/// The reserve required by the [Self::active_anchor_channels], as returned by
+	/// [total_reserve_required].
+	///
+	/// This is as current as [Self::active_anchor_channels].
+	pub total_reserve_required_sat: u64,
+	/// The reserve provided by the UTXOs, i.e., the reserve required by the
+	/// [Self::supportable_channels]. UTXOs which are not counted towards the reserve and value in
+	/// excess of the reserve of the channels the UTXOs support are not included, so this may be
+	/// lower than the total value of the UTXOs.
+	///
+	/// This is only as current as the UTXOs provided to [Self::collect].
+	pub total_reserve_available_sat: u64,
+	/// The number of anchor channels the UTXOs can support, as returned by
+	/// [get_supportable_anchor_channels].
+	///
+	/// This is only as current as the UTXOs provided to [Self::collect].
+	pub supportable_channels: u64,
+	/// The number of anchor channels relying on the reserve, as counted by
+	/// [AnchorChannelReserveCheck::new], including those being resolved on-chain.
+	///
+	/// This is never stale, as it is counted from the current state of the `ChannelManager` and
+	/// `ChainMonitor`, though channels opened or closed while collecting may or may not be counted.
+	pub active_anchor_channels: u64,
+	/// The number of [Self::active_anchor_channels] whose funding output has been spent on-chain,
+	/// but whose claims may still require fees paid from the reserve.
+	///
+	/// This can be stale, as it is only updated once the `ChainMonitor` processes the blocks
+	/// spending the funding outputs.
+	pub channels_in_onchain_resolution: u64,
+	/// The total value of the wallet UTXOs spent by the most recent transactions of pending claims,
+	/// which are thus unavailable until the claims confirm, as provided via
+	/// [ChainMonitor::provide_claim_fee_bump].
+	///
+	/// This can be stale, as fee bumps are only accounted for once provided, e.g., by a
+	/// [ReserveAuditLogger], and is zero if they are never provided.
+	///
+	/// [ChainMonitor::provide_claim_fee_bump]: crate::chain::chainmonitor::ChainMonitor::provide_claim_fee_bump
+	pub pending_claim_external_value_sat: u64,
+}
+
+impl ReserveMetrics {
+	/// Gathers the metrics on the anchor channel reserve provided by `utxos` for the anchor
+	/// channels currently tracked by `channel_manager` and `chain_monitor`.
+	///
+	/// The monitors are inspected in a single pass, under a single lock of the `ChainMonitor`'s
+	/// monitor set, such that this is cheap enough to be called on every scrape.
+	#[cfg(feature = "std")]
+	pub fn collect(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo],
+		channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+	) -> Self {
+		let mut resolving_channels = new_hash_set();
+		let mut pending_claim_external_value = Amount::ZERO;
+		let anchor_channels = get_anchor_channel_ids_inspecting_monitors(
+			channel_manager,
+			chain_monitor,
+			&[],
+			&mut |summary| {
+				if summary.funding_spend_seen {
+					resolving_channels.insert(summary.channel_id);
+				}
+				pending_claim_external_value = pending_claim_external_value
+					.checked_add(summary.pending_claim_external_inputs_value)
+					.unwrap_or(Amount::MAX);
+			},
+		);
+		let active_anchor_channels = anchor_channels.len() as u64;
+		let supportable_channels = get_supportable_anchor_channels(context, utxos);
+		// Channels being resolved on-chain are only counted if they still rely on the reserve.
+		let channels_in_onchain_resolution =
+			resolving_channels.intersection(&anchor_channels).count() as u64;
+		// The available reserve is expressed in the same terms as the required one, such that
+		// comparing them is equivalent to comparing the channel counts.
+		let required_reserve = total_reserve_required(context, active_anchor_channels, false);
+		let available_reserve = total_reserve_required(context, supportable_channels, false);
+		Self {
+			total_reserve_required_sat: required_reserve.to_sat(),
+			total_reserve_available_sat: available_reserve.to_sat(),
+			supportable_channels,
+			active_anchor_channels,
+			channels_in_onchain_resolution,
+			pending_claim_external_value_sat: pending_claim_external_value.to_sat(),
+		}
+	}
+}
+
+/// Builds the reason sent to a counterparty when rejecting an inbound anchor channel because the
+/// anchor channel reserve is insufficient, such as "insufficient on-chain reserves: need 12345 sat".
+///
+/// The amount is the additional reserve required to support the channel on top of the
+/// `num_anchor_channels` already relying on the reserve, given that only `num_supportable_channels`
+/// can be supported, as returned by [AnchorChannelReserveCheck::num_anchor_channels] and
+/// [AnchorChannelReserveCheck::num_supportable_channels]. Each anchor channel which can't be
+/// supported is assumed to require the reserve returned by [get_reserve_per_channel].
+///
+/// This is used by [AnchorReserveChannelAcceptor] and can be passed to
+/// [ChannelManager::force_close_broadcasting_latest_txn] when declining an
+/// [Event::OpenChannelRequest].
+///
+/// [ChannelManager::force_close_broadcasting_latest_txn]: crate::ln::channelmanager::ChannelManager::force_close_broadcasting_latest_txn
+/// [Event::OpenChannelRequest]: crate::events::Event::OpenChannelRequest
+pub fn insufficient_reserve_error_message(
+	context: &AnchorChannelReserveContext, num_anchor_channels: u64, num_supportable_channels: u64,
+) -> String {
+	let num_unsupported_channels =
+		(num_anchor_channels + 1).saturating_sub(num_supportable_channels);
+	let shortfall = get_reserve_per_channel(context)
+		.checked_mul(num_unsupported_channels)
+		.unwrap_or(Amount::MAX);
+	format!("insufficient on-chain reserves: need {} sat", shortfall.to_sat())
+}
+
+/// An [`InboundChannelAcceptor`] which accepts inbound anchor channels only if the reserve provided
+/// by the confirmed UTXOs of a wallet is sufficient to support an additional anchor channel.
+///
+/// Channels without anchor outputs are always accepted. Channels with anchor outputs are rejected
+/// with a reason built by [`insufficient_reserve_error_message`] if the reserve is insufficient,
+/// or deferred to an [`Event::OpenChannelRequest`] if configured via
+/// [`Self::with_deferral_on_insufficient_reserve`] or if the wallet fails to list its UTXOs.
+///
+/// Note that, unlike [`can_support_additional_anchor_channel`], only the channels listed by the
+/// [`ChannelManager`] are considered to rely on the reserve, i.e., closed channels which are still
+/// being resolved on-chain are not accounted for.
+///
+/// [`Event::OpenChannelRequest`]: crate::events::Event::OpenChannelRequest
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+pub struct AnchorReserveChannelAcceptor<W: Deref>
+where
+	W::Target: WalletSourceSync,
+{
+	context: AnchorChannelReserveContext,
+	wallet: W,
+	defer_on_insufficient_reserve: bool,
+	audit_logger: Option<Arc<dyn ReserveAuditLogger + Send + Sync>>,
+	reserve_policies: Mutex<AnchorChannelReservePolicies>,
+}
+
+impl<W: Deref> AnchorReserveChannelAcceptor<W>
+where
+	W::Target: WalletSourceSync,
+{
+	/// Creates an acceptor checking the reserve provided by the UTXOs of `wallet` against the
+	/// requirements given by `context`.
+	pub fn new(context: AnchorChannelReserveContext, wallet: W) -> Self {
+		Self {
+			context,
+			wallet,
+			defer_on_insufficient_reserve: false,
+			audit_logger: None,
+			reserve_policies: Mutex::new(AnchorChannelReservePolicies::default()),
+		}
+	}
+
+	/// Defers anchor channels to manual acceptance instead of rejecting them when the reserve is
+	/// insufficient, e.g., to allow funding the wallet before accepting them.
+	pub fn with_deferral_on_insufficient_reserve(self) -> Self {
+		Self { defer_on_insufficient_reserve: true, ..self }
+	}
+
+	/// Records each decision on an inbound anchor channel to `audit_logger`.
+	///
+	/// No entry is recorded for channels without anchor outputs, nor for channels deferred to
+	/// manual acceptance because the wallet failed to list its UTXOs.
+	pub fn with_audit_logger(
+		self, audit_logger: Arc<dyn ReserveAuditLogger + Send + Sync>,
+	) -> Self {
+		Self { audit_logger: Some(audit_logger), ..self }
+	}
+
+	/// Weights the anchor channels with each counterparty by its [AnchorChannelReservePolicy],
+	/// including the channel being decided on.
+	///
+	/// The policies may be updated at runtime via [Self::set_reserve_policies] and
+	/// [Self::set_counterparty_reserve_policy].
+	pub fn with_reserve_policies(self, reserve_policies: AnchorChannelReservePolicies) -> Self {
+		Self { reserve_policies: Mutex::new(reserve_policies), ..self }
+	}
+
+	/// Replaces the [AnchorChannelReservePolicies] applied to subsequent decisions.
+	pub fn set_reserve_policies(&self, reserve_policies: AnchorChannelReservePolicies) {
+		*self.reserve_policies.lock().unwrap() = reserve_policies;
+	}
+
+	/// Sets the [AnchorChannelReservePolicy] applied to subsequent decisions on channels with the
+	/// counterparty with the given `node_id`.
+	pub fn set_counterparty_reserve_policy(
+		&self, node_id: PublicKey, policy: AnchorChannelReservePolicy,
+	) {
+		self.reserve_policies.lock().unwrap().set_counterparty_policy(node_id, policy);
+	}
+
+	/// Returns the [AnchorChannelReservePolicies] currently applied, e.g., to persist them.
+	pub fn reserve_policies(&self) -> AnchorChannelReservePolicies {
+		self.reserve_policies.lock().unwrap().clone()
+	}
+}
+
+impl<W: Deref> InboundChannelAcceptor for AnchorReserveChannelAcceptor<W>
+where
+	W::Target: WalletSourceSync,
+{
+	fn decide_inbound_channel(
+		&self, proposal: &InboundChannelProposal, existing_channels: &[ChannelDetails],
+	) -> InboundChannelDecision {
+		if !is_anchor_channel_type(&proposal.channel_type) {
+			return InboundChannelDecision::Accept;
+		}
+		let utxos = match self.wallet.list_confirmed_utxos() {
+			Ok(utxos) => utxos,
+			Err(()) => return InboundChannelDecision::DeferToManual,
+		};
+		let (existing_reserve_share, proposal_reserve_share) = {
+			let policies = self.reserve_policies.lock().unwrap();
+			let existing_reserve_share = existing_channels
+				.iter()
+				.filter(|channel| {
+					channel.channel_type.as_ref().map_or(true, is_anchor_channel_type)
+				})
+				.map(|channel| policies.reserve_share_ppm(Some(&channel.counterparty.node_id)))
+				.fold(0u64, |total, share| total.saturating_add(share));
+			(
+				existing_reserve_share,
+				policies.reserve_share_ppm(Some(&proposal.counterparty_node_id)),
+			)
+		};
+		let reserve_share = existing_reserve_share.saturating_add(proposal_reserve_share);
+		let num_anchor_channels = num_weighted_channels(existing_reserve_share);
+		let num_supportable_channels = get_supportable_anchor_channels(&self.context, &utxos);
+		let can_support = supports_reserve_share(num_supportable_channels, reserve_share);
+		let (decision, audit_decision) = if can_support {
+			let audit_decision = ReserveAuditDecision::ChannelAllowed {
+				num_anchor_channels,
+				num_supportable_channels,
+			};
+			(InboundChannelDecision::Accept, audit_decision)
+		} else if self.defer_on_insufficient_reserve {
+			let audit_decision = ReserveAuditDecision::ChannelDeferred {
+				num_anchor_channels,
+				num_supportable_channels,
+			};
+			(InboundChannelDecision::DeferToManual, audit_decision)
+		} else {
+			let decision = InboundChannelDecision::Reject(insufficient_reserve_error_message(
+				&self.context,
+				num_anchor_channels,
+				num_supportable_channels,
+			));
+			let audit_decision = ReserveAuditDecision::ChannelDenied {
+				num_anchor_channels,
+				num_supportable_channels,
+			};
+			(decision, audit_decision)
+		};
+		if let Some(audit_logger) = &self.audit_logger {
+			audit_logger.log_entry(ReserveAuditEntry::for_channel_decision(
+				&self.context,
+				&utxos,
+				proposal.temporary_channel_id,
+				audit_decision,
+			));
+		}
+		decision
+	}
+
+	fn anchor_channel_reserve_context(&self) -> Option<&AnchorChannelReserveContext> {
+		Some(&self.context)
+	}
+}
+
+/// The number of anchor channels supported by a set of UTXOs across a range of fee rates, as
+/// returned by [stress_test_reserves].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReserveStressTest {
+	/// The number of anchor channels supported at each fee rate of the range, in ascending order
+	/// of fee rate, e.g., for plotting.
+	pub supportable_channels: Vec<(FeeRate, u64)>,
+	/// The lowest fee rate at which fewer than the given number of anchor channels are supported,
+	/// regardless of whether it is within the range, or `None` if no channels were given.
+	pub breakeven_fee_rate: Option<FeeRate>,
+}
+
+/// Simulates the adequacy of the reserve provided by `utxos` for `num_channels` anchor channels
+/// across the fee rates in `fee_rate_range`, answering at which fee rate the UTXOs stop covering
+/// the channels.
+///
+/// The [AnchorChannelReserveContext::upper_bound_fee_rate] of `base_context` is replaced by each
+/// fee rate from the start of `fee_rate_range` up to its end, in increments of `step`. A zero
+/// `step` only evaluates the start of the range.
+///
+/// As the reserve per channel grows linearly with the fee rate, while the value UTXOs contribute
+/// shrinks, the number of supportable channels never increases with the fee rate. The breakeven
+/// fee rate is thus found by a binary search over all fee rates rather than by evaluating each of
+/// them. Without any UTXOs, it is zero for any non-zero `num_channels`.
+pub fn stress_test_reserves(
+	base_context: &AnchorChannelReserveContext, utxos: &[Utxo], num_channels: u64,
+	fee_rate_range: RangeInclusive<FeeRate>, step: FeeRate,
+) -> ReserveStressTest {
+	let supportable_at = |fee_rate: FeeRate| {
+		let context =
+			AnchorChannelReserveContext { upper_bound_fee_rate: fee_rate, ..base_context.clone() };
+		get_supportable_anchor_channels(&context, utxos)
+	};
+
+	let mut supportable_channels = Vec::new();
+	let (start, end) =
+		(fee_rate_range.start().to_sat_per_kwu(), fee_rate_range.end().to_sat_per_kwu());
+	let mut sat_per_kwu = start;
+	while sat_per_kwu <= end {
+		let fee_rate = FeeRate::from_sat_per_kwu(sat_per_kwu);
+		supportable_channels.push((fee_rate, supportable_at(fee_rate)));
+		match sat_per_kwu.checked_add(step.to_sat_per_kwu()) {
+			Some(next) if next > sat_per_kwu => sat_per_kwu = next,
+			_ => break,
+		}
+	}
+
+	let breakeven_fee_rate = if num_channels == 0 {
+		None
+	} else if supportable_at(FeeRate::MAX) >= num_channels {
+		// Only reachable for fee rates at which the calculations saturate.
+		None
+	} else {
+		// Invariant: the channels are supported below `low` and not supported at `high`.
+		let (mut low, mut high) = (0, FeeRate::MAX.to_sat_per_kwu());
+		while low < high {
+			let mid = low + (high - low) / 2;
+			if supportable_at(FeeRate::from_sat_per_kwu(mid)) < num_channels {
+				high = mid;
+			} else {
+				low = mid + 1;
+			}
+		}
+		Some(FeeRate::from_sat_per_kwu(high))
+	};
+
+	ReserveStressTest { supportable_channels, breakeven_fee_rate }
+}
+
+/// Suggests anchor channels to cooperatively close, e.g., via
+/// [ChannelManager::close_channel], such that the reserve provided by `utxos` supports the
+/// remaining anchor channels in `channels`.
+///
+/// Returns the [ChannelId]s of the channels to close in order of preference, which is empty if the
+/// reserve is sufficient. Exactly as many channels are suggested as the reserve falls short of, so
+/// every suggestion is necessary. Channels are preferred if they:
+/// 1. have no pending HTLCs, which would be at risk if the counterparty force-closed instead,
+/// 2. are not usable, e.g., as our counterparty is disconnected, indicating recent inactivity,
+/// 3. have a low local balance, i.e., [ChannelDetails::outbound_capacity_msat].
+///
+/// Channels without anchor outputs don't rely on the reserve and are never suggested. Channels
+/// which are already shutting down are assumed to close cooperatively, and neither count towards
+/// the anchor channels relying on the reserve nor are suggested again.
+///
+/// As this does not modify any state, the suggestions can be shown to the user for confirmation
+/// before closing the channels.
+///
+/// [ChannelManager::close_channel]: crate::ln::channelmanager::ChannelManager::close_channel
+pub fn suggest_channels_to_close(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], channels: &[ChannelDetails],
+) -> Vec<ChannelId> {
+	let mut candidates: Vec<&ChannelDetails> = channels
+		.iter()
+		.filter(|channel| channel.channel_type.as_ref().map_or(true, is_anchor_channel_type))
+		.filter(|channel| {
+			channel
+				.channel_shutdown_state
+				.map_or(true, |state| state == ChannelShutdownState::NotShuttingDown)
+		})
+		.collect();
+	let num_supportable_channels = get_supportable_anchor_channels(context, utxos);
+	let num_excess_channels = (candidates.len() as u64).saturating_sub(num_supportable_channels);
+	candidates.sort_by_key(|channel| {
+		let has_pending_htlcs =
+			!channel.pending_inbound_htlcs.is_empty() || !channel.pending_outbound_htlcs.is_empty();
+		(has_pending_htlcs, channel.is_usable, channel.outbound_capacity_msat, channel.channel_id)
+	});
+	candidates
+		.into_iter()
+		.take(num_excess_channels as usize)
+		.map(|channel| channel.channel_id)
+		.collect()
+}
+
+/// A way in which an [AnchorChannelReserveContext] contradicts the node's configuration, as
+/// returned by [validate_against_config].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReserveConfigWarning {
+	/// [AnchorChannelReserveContext::expected_accepted_htlcs] is below the number of HTLCs we
+	/// accept per channel, i.e., [ChannelHandshakeConfig::our_max_accepted_htlcs] as limited by
+	/// [ChannelConfig::max_inbound_htlcs_for_reserve], undersizing the reserve if channels are
+	/// filled up with HTLCs.
+	///
+	/// [ChannelHandshakeConfig::our_max_accepted_htlcs]: crate::util::config::ChannelHandshakeConfig::our_max_accepted_htlcs
+	/// [ChannelConfig::max_inbound_htlcs_for_reserve]: crate::util::config::ChannelConfig::max_inbound_htlcs_for_reserve
+	ExpectedAcceptedHtlcsBelowMaxAccepted {
+		/// The [AnchorChannelReserveContext::expected_accepted_htlcs].
+		expected_accepted_htlcs: u16,
+		/// The number of HTLCs we accept per channel.
+		max_accepted_htlcs: u16,
+	},
+	/// [AnchorChannelReserveContext::upper_bound_fee_rate] is below the current estimate of the
+	/// [FeeEstimator] for [ConfirmationTarget::MaximumFeeEstimate], such that the reserve may not
+	/// cover the fees of a unilateral closure at the current fee rates already.
+	UpperBoundFeeRateBelowMaximumFeeEstimate {
+		/// The [AnchorChannelReserveContext::upper_bound_fee_rate].
+		upper_bound_fee_rate: FeeRate,
+		/// The current estimate for [ConfirmationTarget::MaximumFeeEstimate].
+		maximum_fee_estimate: FeeRate,
+	},
+	/// An [InboundChannelAcceptor] enforcing the reserve is registered, but
+	/// [UserConfig::manually_accept_inbound_channels] is set, such that it is never consulted and
+	/// inbound anchor channels are accepted without checking the reserve.
+	InboundChannelAcceptorBypassed,
+}
+
+/// Checks whether `context` contradicts the node's `config` or the current fee rate estimates of
+/// `fee_estimator`, returning all [ReserveConfigWarning]s that apply.
+///
+/// `inbound_channel_acceptor_registered` should indicate whether the reserve is enforced on
+/// inbound channels by an [InboundChannelAcceptor], such as an [AnchorReserveChannelAcceptor],
+/// registered via [ChannelManager::set_inbound_channel_acceptor]. The [ChannelManager] runs this
+/// check and logs any warnings whenever an acceptor is registered.
+///
+/// [ChannelManager]: crate::ln::channelmanager::ChannelManager
+/// [ChannelManager::set_inbound_channel_acceptor]: crate::ln::channelmanager::ChannelManager::set_inbound_channel_acceptor
+pub fn validate_against_config<F: Deref>(
+	context: &AnchorChannelReserveContext, config: &UserConfig, fee_estimator: &F,
+	inbound_channel_acceptor_registered: bool,
+) -> Result<(), Vec<ReserveConfigWarning>>
+where
+	F::Target: FeeEstimator,
+{
+	let mut warnings = Vec::new();
+	let max_accepted_htlcs = match config.channel_config.max_inbound_htlcs_for_reserve {
+		Some(max_inbound_htlcs) => {
+			min(config.channel_handshake_config.our_max_accepted_htlcs, max_inbound_htlcs)
+		},
+		None => config.channel_handshake_config.our_max_accepted_htlcs,
+	};
+	if context.expected_accepted_htlcs < max_accepted_htlcs {
+		warnings.push(ReserveConfigWarning::ExpectedAcceptedHtlcsBelowMaxAccepted {
+			expected_accepted_htlcs: context.expected_accepted_htlcs,
+			max_accepted_htlcs,
+		});
+	}
+	let maximum_fee_estimate = FeeRate::from_sat_per_kwu(
+		fee_estimator.get_est_sat_per_1000_weight(ConfirmationTarget::MaximumFeeEstimate) as u64,
+	);
+	if context.upper_bound_fee_rate < maximum_fee_estimate {
+		warnings.push(ReserveConfigWarning::UpperBoundFeeRateBelowMaximumFeeEstimate {
+			upper_bound_fee_rate: context.upper_bound_fee_rate,
+			maximum_fee_estimate,
+		});
+	}
+	if inbound_channel_acceptor_registered && config.manually_accept_inbound_channels {
+		warnings.push(ReserveConfigWarning::InboundChannelAcceptorBypassed);
+	}
+	if warnings.is_empty() {
+		Ok(())
+	} else {
+		Err(warnings)
+	}
+}
+
+/// Verifies whether the anchor channel reserve is sufficient to support a batch of new anchor
+/// channels in addition to the given number of anchor channels currently relying on it, as
+/// [can_support_channel_batch] does.
+///
+/// See [AnchorChannelReserveCheck::with_num_anchor_channels] for the anchor channels which should
+/// be counted in `num_anchor_channels`.
+pub fn can_support_channel_batch_with_num_anchor_channels(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], spent_outpoints: &[OutPoint],
+	num_anchor_channels: u64, num_new_channels: usize,
+) -> Vec<usize> {
+	let remaining_utxos: Vec<Utxo> =
+		utxos.iter().filter(|utxo| !spent_outpoints.contains(&utxo.outpoint)).cloned().collect();
+	let num_available_channels = get_supportable_anchor_channels(context, &remaining_utxos)
+		.saturating_sub(num_anchor_channels);
+	(0..num_new_channels).filter(|idx| *idx as u64 >= num_available_channels).collect()
+}
+
+/// Verifies whether the anchor channel reserve is sufficient to support a batch of new anchor
+/// channels funded by a single funding transaction, as done with
+/// [ChannelManager::batch_funding_transaction_generated].
+///
+/// The UTXOs in `utxos` that are spent by the batch funding transaction should be provided in
+/// `spent_outpoints`, and will not be considered part of the reserve. Any change output of the
+/// funding transaction can be included in `utxos` to count towards the reserve.
+///
+/// Returns the indices in `0..num_new_channels` of the proposed channels that would not be
+/// supported by the remaining reserve. Channels are assumed to be supported in order, so an empty
+/// result indicates the whole batch is supported.
+///
+/// Without the `std` feature, [can_support_channel_batch_with_num_anchor_channels] can be used
+/// instead.
+///
+/// [ChannelManager::batch_funding_transaction_generated]: crate::ln::channelmanager::ChannelManager::batch_funding_transaction_generated
+#[cfg(feature = "std")]
+pub fn can_support_channel_batch(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], spent_outpoints: &[OutPoint],
+	num_new_channels: usize, channel_manager: &DynChannelManager<'_>,
+	chain_monitor: &DynChainMonitor<'_>,
+) -> Vec<usize> {
+	let num_anchor_channels = get_num_anchor_channels(channel_manager, chain_monitor, &[]);
+	can_support_channel_batch_with_num_anchor_channels(
+		context,
+		utxos,
+		spent_outpoints,
+		num_anchor_channels,
+		num_new_channels,
+	)
+}
+
+/// The health of the anchor channel reserve allocated to a single channel, given the HTLCs
+/// currently pending in it.
+///
+/// See [get_channel_reserve_health].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "status", rename_all = "snake_case"))]
+pub enum ChannelReserveHealth {
+	/// The allocated UTXOs are sufficient to confirm the commitment transaction and resolve all
+	/// pending HTLCs on-chain at the upper bound fee rate.
+	Healthy,
+	/// The allocated UTXOs are sufficient to confirm the commitment transaction, but not to
+	/// resolve all pending HTLCs on-chain at the upper bound fee rate.
+	Degraded {
+		/// The additional amount needed to resolve all pending HTLCs.
+		#[cfg_attr(
+			feature = "serde",
+			serde(rename = "shortfall_sat", with = "crate::util::serde_helpers::amount_sat")
+		)]
+		shortfall: Amount,
+	},
+	/// The allocated UTXOs are not sufficient to confirm the commitment transaction at the upper
+	/// bound fee rate, putting the value of all pending HTLCs at risk.
+	Critical {
+		/// The value of the pending HTLCs which can not be resolved on-chain in time.
+		#[cfg_attr(
+			feature = "serde",
+			serde(
+				rename = "unfunded_htlc_value_sat",
+				with = "crate::util::serde_helpers::amount_sat"
+			)
+		)]
+		unfunded_htlc_value: Amount,
+	},
+}
+
+fn get_reserve_health(
+	context: &AnchorChannelReserveContext, utxos: &[Utxo], pending_htlc_amounts_msat: &[u64],
+) -> ChannelReserveHealth {
+	let mut available = Amount::from_sat(0);
+	for utxo in utxos {
+		let satisfaction_fee =
+			context.upper_bound_fee_rate.fee_wu(utxo.satisfaction_weight).unwrap_or(Amount::MAX);
+		let amount = utxo.output.value.checked_sub(satisfaction_fee).unwrap_or(Amount::MIN);
+		available = available.checked_add(amount).unwrap_or(Amount::MAX);
+	}
+
+	let num_pending_htlcs = pending_htlc_amounts_msat.len().try_into().unwrap_or(u16::MAX);
+	let required = get_reserve_per_channel_with_input(
+		&AnchorChannelReserveContext {
+			expected_accepted_htlcs: num_pending_htlcs,
+			..context.clone()
+		},
+		WalletInputType::from_context(context),
+		Weight::ZERO,
+	);
+	if available >= required {
+		return ChannelReserveHealth::Healthy;
+	}
+
+	let required_without_htlcs = get_reserve_per_channel_with_input(
+		&AnchorChannelReserveContext { expected_accepted_htlcs: 0, ..context.clone() },
+		WalletInputType::from_context(context),
+		Weight::ZERO,
+	);
+	if available < required_without_htlcs && !pending_htlc_amounts_msat.is_empty() {
+		let pending_htlc_value_msat: u64 = pending_htlc_amounts_msat.iter().sum();
+		ChannelReserveHealth::Critical {
+			unfunded_htlc_value: Amount::from_sat(pending_htlc_value_msat / 1000),
+		}
+	} else {
+		ChannelReserveHealth::Degraded { shortfall: required - available }
+	}
+}
+
+/// Determines the health of the anchor channel reserve provided by `utxos_allocated` to the
+/// channel tracked by `channel_monitor`.
+///
+/// Rather than relying on [AnchorChannelReserveContext::expected_accepted_htlcs], this uses the
+/// number and value of the inbound HTLCs currently pending in the channel.
+pub fn get_channel_reserve_health<ChannelSigner: EcdsaChannelSigner>(
+	context: &AnchorChannelReserveContext, utxos_allocated: &[Utxo],
+	channel_monitor: &ChannelMonitor<ChannelSigner>,
+) -> ChannelReserveHealth {
+	let pending_htlc_amounts_msat = channel_monitor.get_pending_inbound_htlc_amounts_msat();
+	get_reserve_health(context, utxos_allocated, &pending_htlc_amounts_msat)
+}
+
+/// The wallet funds required to claim the HTLCs of a force-closed anchor channel before they
+/// expire, as returned by [get_urgent_funding_requirement].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UrgentFundingRequirement {
+	/// The fees required for the second-stage HTLC transactions claiming the HTLCs at risk.
+	#[cfg_attr(
+		feature = "serde",
+		serde(rename = "required_funds_sat", with = "crate::util::serde_helpers::amount_sat")
+	)]
+	pub required_funds: Amount,
+	/// The earliest height by which one of the HTLC transactions has to be broadcast.
+	pub deadline_height: u32,
+	/// The number of blocks left until [Self::deadline_height], or zero if it has been reached.
+	pub blocks_until_deadline: u32,
+	/// The total value of the HTLCs which may be lost if their HTLC transactions can't be funded.
+	#[cfg_attr(
+		feature = "serde",
+		serde(rename = "amount_at_risk_sat", with = "crate::util::serde_helpers::amount_sat")
+	)]
+	pub amount_at_risk: Amount,
+}
+
+/// Gets the wallet funds required to claim the HTLCs of the anchor channel tracked by
+/// `channel_monitor` once our commitment transaction has confirmed, e.g., to ask the user to
+/// deposit at least [UrgentFundingRequirement::required_funds] within
+/// [UrgentFundingRequirement::blocks_until_deadline] blocks when coin selection keeps failing.
+///
+/// This walks the pending [Balance::ContentiousClaimable] and [Balance::MaybeTimeoutClaimableHTLC]
+/// balances, which are claimed by HTLC-Success and HTLC-Timeout transactions respectively. As an
+/// upper bound, each HTLC is assumed to be claimed by a separate transaction paying `fee_rate` by
+/// spending a wallet input and producing a change output of the type indicated by
+/// `taproot_wallet`. Note that HTLCs remain included until their HTLC transaction confirms.
+///
+/// Returns `None` if our commitment transaction has not confirmed or no HTLCs are pending, as
+/// HTLCs on our counterparty's commitment transaction are claimed without wallet funds.
+pub fn get_urgent_funding_requirement<ChannelSigner: EcdsaChannelSigner>(
+	channel_monitor: &ChannelMonitor<ChannelSigner>, current_height: u32, fee_rate: FeeRate,
+	taproot_wallet: bool,
+) -> Option<UrgentFundingRequirement> {
+	let holder_commitment_confirmed =
+		channel_monitor.get_holder_anchor_output().map_or(false, |anchor_output| {
+			anchor_output.claim_status == AnchorClaimStatus::CommitmentConfirmed
+		});
+	if !holder_commitment_confirmed {
+		return None;
+	}
+	let context = AnchorChannelReserveContext {
+		upper_bound_fee_rate: fee_rate,
+		taproot_wallet,
+		..Default::default()
+	};
+	let input_type = WalletInputType::from_context(&context);
+	let mut requirement: Option<UrgentFundingRequirement> = None;
+	for balance in channel_monitor.get_claimable_balances() {
+		let (amount_satoshis, deadline_height, weight) = match balance {
+			Balance::ContentiousClaimable { amount_satoshis, timeout_height, .. } => (
+				amount_satoshis,
+				timeout_height,
+				htlc_success_transaction_weight(&context, input_type),
+			),
+			Balance::MaybeTimeoutClaimableHTLC { amount_satoshis, claimable_height, .. } => (
+				amount_satoshis,
+				claimable_height,
+				htlc_timeout_transaction_weight(&context, input_type),
+			),
+			_ => continue,
+		};
+		let fee = fee_rate.fee_wu(weight).unwrap_or(Amount::MAX);
+		let requirement = requirement.get_or_insert(UrgentFundingRequirement {
+			required_funds: Amount::ZERO,
+			deadline_height,
+			blocks_until_deadline: 0,
+			amount_at_risk: Amount::ZERO,
+		});
+		requirement.required_funds =
+			requirement.required_funds.checked_add(fee).unwrap_or(Amount::MAX);
+		requirement.deadline_height = min(requirement.deadline_height, deadline_height);
+		requirement.amount_at_risk += Amount::from_sat(amount_satoshis);
+	}
+	requirement.map(|requirement| UrgentFundingRequirement {
+		blocks_until_deadline: requirement.deadline_height.saturating_sub(current_height),
+		..requirement
+	})
+}
+
+/// The decision recorded in a [ReserveAuditEntry].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ReserveAuditDecision {
+	/// A new anchor channel was allowed as the reserve is sufficient to support it in addition to
+	/// the anchor channels already relying on it.
+	ChannelAllowed {
+		/// The number of anchor channels relying on the reserve before the new channel.
+		num_anchor_channels: u64,
+		/// The number of anchor channels the reserve is sufficient to support.
+		num_supportable_channels: u64,
+	},
+	/// A new anchor channel was rejected as the reserve is insufficient to support it.
+	ChannelDenied {
+		/// The number of anchor channels relying on the reserve before the new channel.
+		num_anchor_channels: u64,
+		/// The number of anchor channels the reserve is sufficient to support.
+		num_supportable_channels: u64,
+	},
+	/// The decision on a new inbound anchor channel was deferred to an
+	/// [Event::OpenChannelRequest] as the reserve is insufficient to support it.
+	///
+	/// [Event::OpenChannelRequest]: crate::events::Event::OpenChannelRequest
+	ChannelDeferred {
+		/// The number of anchor channels relying on the reserve before the new channel.
+		num_anchor_channels: u64,
+		/// The number of anchor channels the reserve is sufficient to support.
+		num_supportable_channels: u64,
+	},
+	/// The fee of a commitment transaction was bumped by spending its anchor output in a
+	/// Child-Pays-For-Parent transaction.
+	CommitmentFeeBumped {
+		/// The txid of the commitment transaction.
+		commitment_txid: Txid,
+		/// The txid of the transaction spending the anchor output.
+		anchor_txid: Txid,
+		/// The fee already paid by the commitment transaction itself, which is not included in
+		/// [ReserveAuditEntry::fees_paid].
+		commitment_tx_fee: Amount,
+		/// The fee rate targeted for the package of the commitment and anchor transactions.
+		target_feerate_sat_per_1000_weight: u32,
+		/// The [ClaimId] of the fee-bumped claim.
+		///
+		/// This is `None` for entries recorded with LDK versions prior to 0.3.
+		claim_id: Option<ClaimId>,
+		/// The weight of the signed transaction spending the anchor output, excluding the
+		/// commitment transaction. Together with [ReserveAuditEntry::fees_paid], this allows
+		/// reconciling the fee paid with the change output actually produced by the wallet.
+		///
+		/// This is `None` for entries recorded with LDK versions prior to 0.3.
+		anchor_tx_weight: Option<Weight>,
+	},
+	/// The fee of an HTLC transaction was bumped by attaching wallet inputs to it.
+	///
+	/// HTLC claims of several channels may be aggregated into a single transaction, in which case
+	/// an entry is recorded for each channel, all sharing the same `htlc_txid` and fees.
+	HtlcFeeBumped {
+		/// The txid of the HTLC transaction.
+		htlc_txid: Txid,
+		/// The fee rate targeted for the HTLC transaction.
+		target_feerate_sat_per_1000_weight: u32,
+		/// The [ClaimId] of the fee-bumped claim. For aggregated HTLC claims, this is the claim of
+		/// the channel the entry is recorded for.
+		///
+		/// This is `None` for entries recorded with LDK versions prior to 0.3.
+		claim_id: Option<ClaimId>,
+		/// The weight of the signed HTLC transaction. Together with [ReserveAuditEntry::fees_paid],
+		/// this allows reconciling the fee paid with the change output actually produced by the
+		/// wallet.
+		///
+		/// This is `None` for entries recorded with LDK versions prior to 0.3.
+		htlc_tx_weight: Option<Weight>,
+	},
+}
+
+impl_writeable_tlv_based_enum!(ReserveAuditDecision,
+	(0, ChannelAllowed) => {
+		(0, num_anchor_channels, required),
+		(2, num_supportable_channels, required),
+	},
+	(2, ChannelDenied) => {
+		(0, num_anchor_channels, required),
+		(2, num_supportable_channels, required),
+	},
+	(4, ChannelDeferred) => {
+		(0, num_anchor_channels, required),
+		(2, num_supportable_channels, required),
+	},
+	(6, CommitmentFeeBumped) => {
+		(0, commitment_txid, required),
+		(2, anchor_txid, required),
+		(4, commitment_tx_fee, required),
+		(6, target_feerate_sat_per_1000_weight, required),
+		(7, claim_id, option),
+		(9, anchor_tx_weight, option),
+	},
+	(8, HtlcFeeBumped) => {
+		(0, htlc_txid, required),
+		(2, target_feerate_sat_per_1000_weight, required),
+		(3, claim_id, option),
+		(5, htlc_tx_weight, option),
+	},
+);
+
+/// A decision made based on the anchor channel reserve, or a fee spent from it, as recorded to a
+/// [ReserveAuditLogger].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ReserveAuditEntry {
+	/// The time since the UNIX epoch at which the entry was recorded. This is only available with
+	/// the `std` feature.
+	pub timestamp: Option<Duration>,
+	/// The channel the entry relates to. For decisions on new channels, this is the temporary
+	/// channel ID, if the channel ID is not yet known.
+	pub channel_id: ChannelId,
+	/// The decision made.
+	pub decision: ReserveAuditDecision,
+	/// The reserve required per anchor channel, as computed by [get_reserve_per_channel]. This is
+	/// only set for decisions on new channels.
+	pub reserve_per_channel: Option<Amount>,
+	/// For decisions on new channels, the UTXOs providing the reserve. For fee bumps, the wallet
+	/// UTXOs spent to pay the fees.
+	pub utxos_considered: Vec<OutPoint>,
+	/// The total value of [Self::utxos_considered].
+	pub utxos_value: Amount,
+	/// The fees paid from the reserve, which is zero for decisions on new channels.
+	pub fees_paid: Amount,
+}
+
+impl_writeable_tlv_based!(ReserveAuditEntry, {
+	(0, timestamp, option),
+	(2, channel_id, required),
+	(4, decision, required),
+	(6, reserve_per_channel, option),
+	(8, utxos_considered, required_vec),
+	(10, utxos_value, required),
+	(12, fees_paid, required),
+});
+
+impl ReserveAuditEntry {
+	pub(crate) fn new(
+		channel_id: ChannelId, decision: ReserveAuditDecision, reserve_per_channel: Option<Amount>,
+		utxos_considered: Vec<OutPoint>, utxos_value: Amount, fees_paid: Amount,
+	) -> Self {
+		#[cfg(feature = "std")]
+		let timestamp = Some(
+			std::time::SystemTime::now()
+				.duration_since(std::time::UNIX_EPOCH)
+				.expect("Time must be > 1970"),
+		);
+		#[cfg(not(feature = "std"))]
+		let timestamp = None;
+		Self {
+			timestamp,
+			channel_id,
+			decision,
+			reserve_per_channel,
+			utxos_considered,
+			utxos_value,
+			fees_paid,
+		}
+	}
+
+	fn for_channel_decision(
+		context: &AnchorChannelReserveContext, utxos: &[Utxo], channel_id: ChannelId,
+		decision: ReserveAuditDecision,
+	) -> Self {
+		Self::new(
+			channel_id,
+			decision,
+			Some(get_reserve_per_channel(context)),
+			utxos.iter().map(|utxo| utxo.outpoint).collect(),
+			utxos.iter().map(|utxo| utxo.output.value).sum(),
+			Amount::ZERO,
+		)
+	}
+}
+
+/// A recipient of [ReserveAuditEntry]s, recording the decisions made based on the anchor channel
+/// reserve and the fees spent from it, e.g., for compliance purposes.
+///
+/// Entries are recorded by an [AnchorReserveChannelAcceptor] configured via
+/// [AnchorReserveChannelAcceptor::with_audit_logger], by
+/// [AnchorChannelReserveCheck::audit_additional_anchor_channel], and by a
+/// [BumpTransactionEventHandler] configured via [BumpTransactionEventHandler::with_audit_logger].
+/// No entries are recorded unless a logger is configured.
+///
+/// See [ReserveAuditLog] for a simple in-memory implementation.
+///
+/// [BumpTransactionEventHandler]: crate::events::bump_transaction::BumpTransactionEventHandler
+/// [BumpTransactionEventHandler::with_audit_logger]: crate::events::bump_transaction::BumpTransactionEventHandler::with_audit_logger
+pub trait ReserveAuditLogger {
+	/// Records the given entry. This should not block.
+	fn log_entry(&self, entry: ReserveAuditEntry);
+}
+
+/// A [ReserveAuditLogger] keeping the most recent entries in memory, up to a maximum number.
+///
+/// Once full, the oldest entry is dropped for each new entry. The retained entries can be persisted
+/// via its [Writeable] implementation, e.g., through a [KVStore].
+///
+/// [KVStore]: crate::util::persist::KVStore
+pub struct ReserveAuditLog {
+	max_entries: usize,
+	entries: Mutex<VecDeque<ReserveAuditEntry>>,
+}
+
+impl ReserveAuditLog {
+	/// Creates an empty log retaining up to `max_entries` entries.
+	pub fn new(max_entries: usize) -> Self {
+		Self { max_entries, entries: Mutex::new(VecDeque::new()) }
+	}
+
+	/// Returns the retained entries, from oldest to newest.
+	pub fn entries(&self) -> Vec<ReserveAuditEntry> {
+		self.entries.lock().unwrap().iter().cloned().collect()
+	}
+}
+
+impl ReserveAuditLogger for ReserveAuditLog {
+	fn log_entry(&self, entry: ReserveAuditEntry) {
+		let mut entries = self.entries.lock().unwrap();
+		entries.push_back(entry);
+		while entries.len() > self.max_entries {
+			entries.pop_front();
+		}
+	}
+}
+
+impl Writeable for ReserveAuditLog {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), io::Error> {
+		let max_entries = self.max_entries as u64;
+		let entries = self.entries();
+		write_tlv_fields!(writer, {
+			(0, max_entries, required),
+			(2, entries, required_vec),
+		});
+		Ok(())
+	}
+}
+
+impl Readable for ReserveAuditLog {
+	fn read<R: io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+		let mut max_entries: u64 = 0;
+		let mut entries: Vec<ReserveAuditEntry> = Vec::new();
+		read_tlv_fields!(reader, {
+			(0, max_entries, required),
+			(2, entries, required_vec),
+		});
+		let max_entries = usize::try_from(max_entries).map_err(|_| DecodeError::InvalidValue)?;
+		let skip = entries.len().saturating_sub(max_entries);
+		let entries = entries.into_iter().skip(skip).collect();
+		Ok(Self { max_entries, entries: Mutex::new(entries) })
+	}
+}
+
+/// A source of the UTXOs earmarked for the anchor channel reserve.
+///
+/// Once registered via [ChainMonitor::register_anchor_reserve_provider], the reserve UTXOs are
+/// re-validated periodically and an [Event::AnchorReserveUtxosSpent] is generated if any of them
+/// disappear for reasons other than being spent by LDK's own claims.
+///
+/// This is implemented for [WalletSync], which excludes the UTXOs it selected as inputs to claims
+/// built by a [BumpTransactionEventHandlerSync].
+///
+/// [ChainMonitor::register_anchor_reserve_provider]: crate::chain::chainmonitor::ChainMonitor::register_anchor_reserve_provider
+/// [Event::AnchorReserveUtxosSpent]: crate::events::Event::AnchorReserveUtxosSpent
+/// [WalletSync]: crate::events::bump_transaction::sync::WalletSync
+/// [BumpTransactionEventHandlerSync]: crate::events::bump_transaction::sync::BumpTransactionEventHandlerSync
+pub trait AnchorReserveProvider {
+	/// Returns the confirmed UTXOs currently earmarked for the anchor channel reserve.
+	fn list_reserve_utxos(&self) -> Result<Vec<Utxo>, ()>;
+	/// Returns the outpoints of UTXOs which were selected as inputs to LDK's own claims, e.g., to
+	/// fee-bump a commitment or HTLC transaction. These are not reported as spent externally once
+	/// they are no longer returned by [Self::list_reserve_utxos].
+	fn list_claim_input_outpoints(&self) -> Vec<OutPoint>;
+}
+
+/// Tracks the UTXOs provided by an [AnchorReserveProvider] to detect ones spent externally.
+pub(crate) struct AnchorReserveUtxoTracker {
+	provider: Arc<dyn AnchorReserveProvider + Send + Sync>,
+	context: AnchorChannelReserveContext,
+	check_interval_blocks: u32,
+	/// The outpoints of the reserve UTXOs seen on the last successful check, if any.
+	reserve_outpoints: Option<Vec<OutPoint>>,
+	last_check_height: Option<u32>,
+	utxos_changed: bool,
+}
+
+impl AnchorReserveUtxoTracker {
+	pub(crate) fn new(
+		provider: Arc<dyn AnchorReserveProvider + Send + Sync>,
+		context: AnchorChannelReserveContext, check_interval_blocks: u32,
+	) -> Self {
+		Self {
+			provider,
+			context,
+			check_interval_blocks,
+			reserve_outpoints: None,
+			last_check_height: None,
+			utxos_changed: false,
+		}
+	}
+
+	pub(crate) fn utxos_changed(&mut self) {
+		self.utxos_changed = true;
+	}
+
+	pub(crate) fn is_check_due(&self, best_block_height: u32) -> bool {
+		self.utxos_changed
+			|| self.last_check_height.map_or(true, |last_check_height| {
+				best_block_height >= last_check_height.saturating_add(self.check_interval_blocks)
+			})
+	}
+
+	/// Re-validates the reserve UTXOs, returning the outpoints of the reserve UTXOs seen on the
+	/// previous check which have since been spent externally, along with the number of the given
+	/// anchor channels which the remaining reserve can no longer support.
+	///
+	/// Returns `Err` if the [AnchorReserveProvider] failed to list its UTXOs.
+	pub(crate) fn check(
+		&mut self, best_block_height: u32, num_anchor_channels: u64,
+	) -> Result<Option<(Vec<OutPoint>, u64)>, ()> {
+		self.last_check_height = Some(best_block_height);
+		self.utxos_changed = false;
+		let utxos = self.provider.list_reserve_utxos()?;
+		let reserve_outpoints: Vec<OutPoint> = utxos.iter().map(|utxo| utxo.outpoint).collect();
+		let previous_outpoints = match self.reserve_outpoints.replace(reserve_outpoints) {
+			Some(previous_outpoints) => previous_outpoints,
+			None => return Ok(None),
+		};
+		let current_outpoints = self.reserve_outpoints.as_ref().unwrap();
+		let claim_input_outpoints = self.provider.list_claim_input_outpoints();
+		let spent_outpoints: Vec<OutPoint> = previous_outpoints
+			.into_iter()
+			.filter(|outpoint| !current_outpoints.contains(outpoint))
+			.filter(|outpoint| !claim_input_outpoints.contains(outpoint))
+			.collect();
+		if spent_outpoints.is_empty() {
+			return Ok(None);
+		}
+		let num_supportable_channels = get_supportable_anchor_channels(&self.context, &utxos);
+		let num_affected_channels = num_anchor_channels.saturating_sub(num_supportable_channels);
+		Ok(Some((spent_outpoints, num_affected_channels)))
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::ln::chan_utils::{
+		commitment_tx_base_weight, get_keyed_anchor_redeemscript, shared_anchor_script_pubkey,
+		COMMITMENT_TX_WEIGHT_PER_HTLC,
+	};
+	use crate::ln::channel_state::InboundHTLCDetails;
+	use crate::types::payment::PaymentHash;
+	use crate::util::test_utils::TestFeeEstimator;
+	use bitcoin::hashes::Hash;
+	use bitcoin::key::TweakedPublicKey;
+	use bitcoin::secp256k1::PublicKey;
+	use bitcoin::{
+		ScriptBuf, Sequence, Transaction, TxIn, TxOut, Txid, WPubkeyHash, WScriptHash, Witness,
+	};
+	use std::str::FromStr;
+
+	#[test]
+	fn test_get_reserve_per_channel() {
+		// At a 1000 sats/kw, with 4 expected transactions at ~1kw (commitment transaction, anchor
+		// output spend transaction, 2 HTLC transactions), we expect the reserve to be around 4k sats.
+		assert_eq!(
+			get_reserve_per_channel(&AnchorChannelReserveContext {
+				upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+				expected_accepted_htlcs: 1,
+				taproot_wallet: false,
+				reserve_policy: ReservePolicy::Forwarding,
+				channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+				safety_margin_ppm: 1_000_000,
+				min_economical_htlc_value_sat: None,
+				max_htlc_value_in_flight_msat: None,
+				include_outbound_htlcs: false,
+				expected_offered_htlcs: 0,
+				min_confirmations: 1,
+				exclude_immature_coinbase: true,
+				commitment_weight_params: None,
+				include_justice_path: false,
+			}),
+			Amount::from_sat(4349)
+		);
+	}
+
+	#[test]
+	fn test_get_reserve_per_channel_safety_margin() {
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			expected_accepted_htlcs: 1,
+			..AnchorChannelReserveContext::default()
+		};
+		let with_margin = |safety_margin_ppm| AnchorChannelReserveContext {
+			safety_margin_ppm,
+			..context.clone()
+		};
+
+		// A multiplier of 1.0 leaves the reserve unchanged, as does any lower multiplier.
+		assert_eq!(get_reserve_per_channel(&with_margin(1_000_000)), Amount::from_sat(4349));
+		assert_eq!(get_reserve_per_channel(&with_margin(500_000)), Amount::from_sat(4349));
+		assert_eq!(get_reserve_per_channel(&with_margin(0)), Amount::from_sat(4349));
+
+		// A multiplier of 1.5 rounds the reserve up.
+		assert_eq!(get_reserve_per_channel(&with_margin(1_500_000)), Amount::from_sat(6524));
+
+		// Huge multipliers saturate rather than overflow, both for an already saturated reserve and
+		// for a reserve whose multiplication exceeds the range of `Amount`.
+		assert_eq!(get_reserve_per_channel(&with_margin(u32::MAX)), Amount::from_sat(18_678_813));
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1_000_000_000_000_000),
+			..with_margin(u32::MAX)
+		};
+		assert_eq!(get_reserve_per_channel(&context), Amount::MAX);
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::MAX,
+			expected_accepted_htlcs: u16::MAX,
+			..with_margin(u32::MAX)
+		};
+		assert_eq!(get_reserve_per_channel(&context), Amount::MAX);
+	}
+
+	#[test]
+	fn test_get_reserve_per_channel_min_economical_htlc_value() {
+		let context = AnchorChannelReserveContext {
+			expected_accepted_htlcs: 10,
+			max_htlc_value_in_flight_msat: Some(100_000_000),
+			..AnchorChannelReserveContext::default()
+		};
+		let with_threshold = |min_economical_htlc_value_sat| AnchorChannelReserveContext {
+			min_economical_htlc_value_sat,
+			..context.clone()
+		};
+		let reserve = get_reserve_per_channel(&context);
+
+		// At most 10 HTLCs of 10k sats fit in the maximum value in flight, so all expected HTLCs
+		// may be worth claiming.
+		assert_eq!(get_reserve_per_channel(&with_threshold(Some(10_000))), reserve);
+
+		// The reserve shrinks as the threshold rises, as fewer HTLCs fit in the maximum value in
+		// flight, until no HTLC transactions are reserved for at all.
+		let mut previous_reserve = reserve;
+		for threshold_sat in [20_000, 50_000, 100_000, 200_000] {
+			let reserve = get_reserve_per_channel(&with_threshold(Some(threshold_sat)));
+			assert!(reserve < previous_reserve);
+			previous_reserve = reserve;
+		}
+
+		// Only the HTLC transactions are skipped, while the HTLC outputs remain on the commitment
+		// transaction.
+		let input_type = WalletInputType::P2wpkh;
+		let htlc_transactions_fee = context
+			.upper_bound_fee_rate
+			.fee_wu(
+				(htlc_success_transaction_weight(&context, input_type)
+					+ htlc_timeout_transaction_weight(&context, input_type))
+					* 10,
+			)
+			.unwrap();
+		assert_eq!(previous_reserve, reserve - htlc_transactions_fee);
+
+		// Without a bound on the value in flight, any number of HTLCs may be worth claiming.
+		let unbounded_context =
+			AnchorChannelReserveContext { max_htlc_value_in_flight_msat: None, ..context.clone() };
+		let unbounded_context = AnchorChannelReserveContext {
+			min_economical_htlc_value_sat: Some(200_000),
+			..unbounded_context
+		};
+		assert_eq!(get_reserve_per_channel(&unbounded_context), reserve);
+	}
+
+	#[test]
+	fn test_get_min_economical_htlc_value_sat() {
+		let context = AnchorChannelReserveContext::default();
+		let input_type = WalletInputType::P2wpkh;
+		let success_fee = context
+			.upper_bound_fee_rate
+			.fee_wu(htlc_success_transaction_weight(&context, input_type))
+			.unwrap();
+		let timeout_fee = context
+			.upper_bound_fee_rate
+			.fee_wu(htlc_timeout_transaction_weight(&context, input_type))
+			.unwrap();
+		assert_eq!(
+			get_min_economical_htlc_value_sat(&context),
+			max(success_fee, timeout_fee).to_sat()
+		);
+		let receive_only_context =
+			AnchorChannelReserveContext { reserve_policy: ReservePolicy::ReceiveOnly, ..context };
+		assert_eq!(get_min_economical_htlc_value_sat(&receive_only_context), success_fee.to_sat());
+
+		// The threshold rises with the fee rate and saturates.
+		let low_fee_context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(250),
+			..AnchorChannelReserveContext::default()
+		};
+		assert!(
+			get_min_economical_htlc_value_sat(&low_fee_context)
+				< get_min_economical_htlc_value_sat(&AnchorChannelReserveContext::default())
+		);
+		let max_fee_context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::MAX,
+			..AnchorChannelReserveContext::default()
+		};
+		assert_eq!(get_min_economical_htlc_value_sat(&max_fee_context), u64::MAX);
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_safety_margin() {
+		let context = AnchorChannelReserveContext::default();
+		let margin_context =
+			AnchorChannelReserveContext { safety_margin_ppm: 1_500_000, ..context.clone() };
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		assert_eq!(
+			get_reserve_per_channel(&margin_context),
+			Amount::from_sat((reserve_per_channel.to_sat() * 3 + 1) / 2)
+		);
+
+		// UTXOs covering the calculated reserve no longer cover the reserve including the margin.
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 6 / 5),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 6 / 5),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 3);
+		assert_eq!(get_supportable_anchor_channels(&margin_context, &utxos), 1);
+
+		let check = AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 2);
+		assert!(check.can_support_additional_anchor_channel());
+		let check = AnchorChannelReserveCheck::with_num_anchor_channels(&margin_context, &utxos, 2);
+		assert!(!check.can_support_additional_anchor_channel());
+	}
+
+	#[test]
+	fn test_get_reserve_per_channel_receive_only() {
+		let forwarding_context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			expected_accepted_htlcs: 1,
+			taproot_wallet: false,
+			reserve_policy: ReservePolicy::Forwarding,
+			channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+			safety_margin_ppm: 1_000_000,
+			min_economical_htlc_value_sat: None,
+			max_htlc_value_in_flight_msat: None,
+			include_outbound_htlcs: false,
+			expected_offered_htlcs: 0,
+			min_confirmations: 1,
+			exclude_immature_coinbase: true,
+			commitment_weight_params: None,
+
+			include_justice_path: false,
+		};
+		let receive_only_context = AnchorChannelReserveContext {
+			reserve_policy: ReservePolicy::ReceiveOnly,
+			..forwarding_context.clone()
+		};
+
+		// At 1 sat/wu, we save the weight of the outbound HTLC on the commitment transaction and of
+		// the HTLC-timeout transaction for each accepted HTLC.
+		assert_eq!(
+			get_reserve_per_channel(&forwarding_context)
+				- get_reserve_per_channel(&receive_only_context),
+			Amount::from_sat(172 + 1062)
+		);
+
+		// As the number of HTLCs grows, the reserve of a receive-only node is roughly half of that
+		// of a forwarding node.
+		for expected_accepted_htlcs in [10, 50, 100] {
+			let forwarding_reserve = get_reserve_per_channel(&AnchorChannelReserveContext {
+				expected_accepted_htlcs,
+				..forwarding_context.clone()
+			});
+			let receive_only_reserve = get_reserve_per_channel(&AnchorChannelReserveContext {
+				expected_accepted_htlcs,
+				..receive_only_context.clone()
+			});
+			assert!(receive_only_reserve * 2 > forwarding_reserve * 9 / 10);
+			assert!(receive_only_reserve * 2 < forwarding_reserve * 11 / 10);
+		}
+	}
+
+	#[test]
+	fn test_get_reserve_per_channel_outbound_htlcs() {
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			expected_accepted_htlcs: 1,
+			taproot_wallet: false,
+			reserve_policy: ReservePolicy::ReceiveOnly,
+			channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+			safety_margin_ppm: 1_000_000,
+			min_economical_htlc_value_sat: None,
+			max_htlc_value_in_flight_msat: None,
+			include_outbound_htlcs: false,
+			expected_offered_htlcs: 2,
+			min_confirmations: 1,
+			exclude_immature_coinbase: true,
+			commitment_weight_params: None,
+
+			include_justice_path: false,
+		};
+		let outbound_context =
+			AnchorChannelReserveContext { include_outbound_htlcs: true, ..context.clone() };
+
+		// At 1 sat/wu, each offered HTLC adds its output on the commitment transaction and an
+		// HTLC-timeout transaction.
+		assert_eq!(
+			get_reserve_per_channel(&outbound_context) - get_reserve_per_channel(&context),
+			Amount::from_sat(2 * (172 + 1062))
+		);
+
+		// Offered HTLCs are reserved for even if no accepted HTLCs are expected.
+		let offered_only_context =
+			AnchorChannelReserveContext { expected_accepted_htlcs: 0, ..outbound_context.clone() };
+		let no_htlcs_context = AnchorChannelReserveContext {
+			include_outbound_htlcs: false,
+			..offered_only_context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&offered_only_context)
+				- get_reserve_per_channel(&no_htlcs_context),
+			Amount::from_sat(2 * (172 + 1062))
+		);
+
+		// Offered HTLCs are independent of the reserve policy, as they only need to be timed out.
+		let forwarding_context = |include_outbound_htlcs| AnchorChannelReserveContext {
+			reserve_policy: ReservePolicy::Forwarding,
+			include_outbound_htlcs,
+			..context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&forwarding_context(true))
+				- get_reserve_per_channel(&forwarding_context(false)),
+			Amount::from_sat(2 * (172 + 1062))
+		);
+
+		// The number of offered HTLCs is capped by the channel's maximum.
+		let max_htlcs = max_htlcs(&ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies());
+		let context_with_offered_htlcs = |expected_offered_htlcs| AnchorChannelReserveContext {
+			expected_offered_htlcs,
+			..outbound_context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&context_with_offered_htlcs(u16::MAX)),
+			get_reserve_per_channel(&context_with_offered_htlcs(max_htlcs))
+		);
+	}
+
+	#[test]
+	fn test_get_reserve_per_channel_justice_path() {
+		// The witnesses of the revocation paths, including their signatures with a sighash flag.
+		assert_eq!(REVOKED_TO_LOCAL_INPUT_WEIGHT, 164 + 155);
+		assert_eq!(TO_REMOTE_INPUT_WEIGHT, 164 + 113);
+		assert_eq!(REVOKED_HTLC_INPUT_WEIGHT, 164 + 252);
+
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			expected_accepted_htlcs: 1,
+			..AnchorChannelReserveContext::default()
+		};
+		let justice_context =
+			AnchorChannelReserveContext { include_justice_path: true, ..context.clone() };
+
+		// At 1 sat/wu, a forwarded HTLC results in two HTLC outputs claimed by the justice
+		// transaction, on top of its base weight, the `to_local` and `to_remote` inputs and a
+		// P2WPKH output.
+		assert_eq!(
+			justice_transaction_weight(&justice_context, 2),
+			Weight::from_wu(42 + 319 + 277 + 2 * 416 + 124)
+		);
+		assert_eq!(get_reserve_per_channel(&context), Amount::from_sat(4349));
+		assert_eq!(get_reserve_per_channel(&justice_context), Amount::from_sat(4349 + 1594));
+
+		// Only the accepted HTLC itself is on the commitment transaction if it is not forwarded.
+		let receive_only = |include_justice_path| AnchorChannelReserveContext {
+			reserve_policy: ReservePolicy::ReceiveOnly,
+			include_justice_path,
+			..context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&receive_only(true))
+				- get_reserve_per_channel(&receive_only(false)),
+			Amount::from_sat(42 + 319 + 277 + 416 + 124)
+		);
+
+		// Offered HTLCs are claimed as well if they are reserved for.
+		let outbound_context = AnchorChannelReserveContext {
+			include_outbound_htlcs: true,
+			expected_offered_htlcs: 2,
+			..justice_context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&outbound_context)
+				- get_reserve_per_channel(&AnchorChannelReserveContext {
+					include_justice_path: false,
+					..outbound_context.clone()
+				}),
+			Amount::from_sat(1594 + 2 * 416)
+		);
+
+		// With a Taproot wallet, the justice transaction pays to a P2TR output.
+		let taproot_context =
+			AnchorChannelReserveContext { taproot_wallet: true, ..justice_context.clone() };
+		assert_eq!(
+			justice_transaction_weight(&taproot_context, 0),
+			Weight::from_wu(42 + 319 + 277 + 172)
+		);
+	}
+
+	#[test]
+	fn test_zero_fee_commitment_weights() {
+		// The base weight of a commitment transaction with anchor outputs, as per the spec.
+		let anchors_base_weight =
+			commitment_transaction_weight(0, AnchorChannelType::AnchorsZeroFeeHtlcTx);
+		assert_eq!(anchors_base_weight.to_wu(), 1124);
+		// A zero-fee commitment transaction replaces the two keyed anchor outputs of the former
+		// with a single P2A output on top of the spec's 724 weight units without anchors.
+		let non_anchor_base_weight =
+			commitment_tx_base_weight(&ChannelTypeFeatures::only_static_remote_key());
+		assert_eq!(non_anchor_base_weight, 724);
+		let p2a_output =
+			TxOut { value: Amount::ZERO, script_pubkey: shared_anchor_script_pubkey() };
+		assert_eq!(p2a_output.weight().to_wu(), 52);
+		let zero_fee_base_weight =
+			commitment_transaction_weight(0, AnchorChannelType::ZeroFeeCommitments);
+		assert_eq!(zero_fee_base_weight.to_wu(), non_anchor_base_weight + 52);
+		assert_eq!(zero_fee_base_weight.to_wu(), 776);
+
+		// A P2A output is spent with an empty witness, unlike the P2WSH anchor output.
+		let p2a_input = TxIn {
+			previous_output: OutPoint::null(),
+			script_sig: ScriptBuf::new(),
+			sequence: Sequence::ZERO,
+			witness: Witness::new(),
+		};
+		assert_eq!(p2a_input.segwit_weight().to_wu(), P2A_ANCHOR_INPUT_WEIGHT);
+		assert_eq!(P2A_ANCHOR_INPUT_WEIGHT, 165);
+		assert_eq!(ANCHOR_INPUT_WEIGHT, 279);
+	}
+
+	#[test]
+	fn test_commitment_weight_params() {
+		let output_weight = |script_pubkey| TxOut { value: Amount::ZERO, script_pubkey }.weight();
+		let p2wsh_weight = output_weight(ScriptBuf::new_p2wsh(&WScriptHash::all_zeros()));
+		let p2wpkh_weight = output_weight(ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()));
+		let p2tr_weight =
+			output_weight(ScriptBuf::new_p2tr_tweaked(TweakedPublicKey::dangerous_assume_tweaked(
+				PublicKey::from_slice(&[2; 33]).unwrap().x_only_public_key().0,
+			)));
+		assert_eq!(p2wsh_weight.to_wu(), P2WSH_OUTPUT_WEIGHT);
+		assert_eq!(p2wpkh_weight.to_wu(), P2WPKH_OUTPUT_WEIGHT);
+		assert_eq!(p2tr_weight.to_wu(), P2TR_OUTPUT_WEIGHT);
+
+		// The spec's expected weights without anchors, with a P2WSH `to_local` and a P2WPKH
+		// `to_remote` output, don't depend on the type of anchors.
+		let no_anchors = CommitmentWeightParams {
+			num_anchor_outputs: 0,
+			to_local_output_weight: p2wsh_weight,
+			to_remote_output_weight: p2wpkh_weight,
+		};
+		let non_anchor_channel_type = ChannelTypeFeatures::only_static_remote_key();
+		for channel_type in
+			[AnchorChannelType::AnchorsZeroFeeHtlcTx, AnchorChannelType::ZeroFeeCommitments]
+		{
+			for num_htlcs in [0, 1, 483] {
+				assert_eq!(
+					no_anchors.commitment_transaction_weight(num_htlcs, channel_type).to_wu(),
+					commitment_tx_base_weight(&non_anchor_channel_type)
+						+ num_htlcs * COMMITMENT_TX_WEIGHT_PER_HTLC
+				);
+			}
+		}
+		assert_eq!(
+			no_anchors.commitment_transaction_weight(0, AnchorChannelType::AnchorsZeroFeeHtlcTx),
+			Weight::from_wu(724)
+		);
+
+		// The spec's expected weights with anchors, with P2WSH `to_local` and `to_remote` outputs.
+		let anchors = CommitmentWeightParams::default();
+		assert_eq!(
+			anchors,
+			CommitmentWeightParams {
+				num_anchor_outputs: 2,
+				to_local_output_weight: p2wsh_weight,
+				to_remote_output_weight: p2wsh_weight,
+			}
+		);
+		let anchor_channel_type = ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		for num_htlcs in [0, 1, 483] {
+			assert_eq!(
+				anchors
+					.commitment_transaction_weight(
+						num_htlcs,
+						AnchorChannelType::AnchorsZeroFeeHtlcTx
+					)
+					.to_wu(),
+				commitment_tx_base_weight(&anchor_channel_type)
+					+ num_htlcs * COMMITMENT_TX_WEIGHT_PER_HTLC
+			);
+		}
+		assert_eq!(
+			anchors.commitment_transaction_weight(0, AnchorChannelType::AnchorsZeroFeeHtlcTx),
+			Weight::from_wu(1124)
+		);
+
+		// A P2TR `to_remote` output weighs as much as a P2WSH one, unlike a P2WPKH one.
+		let p2tr_to_remote =
+			CommitmentWeightParams { to_remote_output_weight: p2tr_weight, ..anchors };
+		let p2wpkh_to_remote =
+			CommitmentWeightParams { to_remote_output_weight: p2wpkh_weight, ..anchors };
+		let weight = |params: CommitmentWeightParams| {
+			params.commitment_transaction_weight(0, AnchorChannelType::AnchorsZeroFeeHtlcTx)
+		};
+		assert_eq!(weight(p2tr_to_remote), Weight::from_wu(1124));
+		assert_eq!(weight(p2wpkh_to_remote), Weight::from_wu(1124 - 48));
+
+		// The reserve accounts for the configured outputs.
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			..Default::default()
+		};
+		let p2wpkh_context = AnchorChannelReserveContext {
+			commitment_weight_params: Some(p2wpkh_to_remote),
+			..context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&context) - get_reserve_per_channel(&p2wpkh_context),
+			Amount::from_sat(48)
+		);
+		let default_params_context = AnchorChannelReserveContext {
+			commitment_weight_params: Some(CommitmentWeightParams::default()),
+			..context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&default_params_context),
+			get_reserve_per_channel(&context)
+		);
+
+		// Absurd weights are reported rather than overflowing.
+		let absurd_context = AnchorChannelReserveContext {
+			commitment_weight_params: Some(CommitmentWeightParams {
+				to_local_output_weight: Weight::MAX,
+				..anchors
+			}),
+			..context
+		};
+		assert_eq!(
+			try_get_reserve_per_channel(&absurd_context),
+			Err(ReserveCalculationError::WeightOverflow)
+		);
+	}
+
+	#[test]
+	fn test_get_reserve_per_channel_zero_fee_commitments() {
+		let anchors_context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			expected_accepted_htlcs: 1,
+			taproot_wallet: false,
+			reserve_policy: ReservePolicy::Forwarding,
+			channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+			safety_margin_ppm: 1_000_000,
+			min_economical_htlc_value_sat: None,
+			max_htlc_value_in_flight_msat: None,
+			include_outbound_htlcs: false,
+			expected_offered_htlcs: 0,
+			min_confirmations: 1,
+			exclude_immature_coinbase: true,
+			commitment_weight_params: None,
+
+			include_justice_path: false,
+		};
+		let zero_fee_commitments_context = AnchorChannelReserveContext {
+			channel_type: AnchorChannelType::ZeroFeeCommitments,
+			..anchors_context.clone()
+		};
+
+		// At 1 sat/wu, we save the weight of the keyed anchor outputs on the commitment transaction
+		// and of the P2WSH anchor input on the anchor output spend transaction.
+		assert_eq!(
+			get_reserve_per_channel(&anchors_context)
+				- get_reserve_per_channel(&zero_fee_commitments_context),
+			Amount::from_sat((1124 - 776) + (279 - 165))
+		);
+
+		// The number of HTLCs is capped by the maximum for zero-fee commitment channels.
+		let max_htlcs = max_htlcs(&ChannelTypeFeatures::anchors_zero_fee_commitments());
+		assert_eq!(max_htlcs, 114);
+		let context_with_max_htlcs = |expected_accepted_htlcs| AnchorChannelReserveContext {
+			expected_accepted_htlcs,
+			..zero_fee_commitments_context.clone()
+		};
+		assert_eq!(
+			get_reserve_per_channel(&context_with_max_htlcs(u16::MAX)),
+			get_reserve_per_channel(&context_with_max_htlcs(max_htlcs))
+		);
+		assert!(
+			get_reserve_per_channel(&context_with_max_htlcs(max_htlcs))
+				> get_reserve_per_channel(&context_with_max_htlcs(max_htlcs - 1))
+		);
+	}
+
+	#[test]
+	fn test_is_anchor_channel_type() {
+		assert!(is_anchor_channel_type(
+			&ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies()
+		));
+		assert!(is_anchor_channel_type(&ChannelTypeFeatures::anchors_zero_fee_commitments()));
+		assert!(!is_anchor_channel_type(&ChannelTypeFeatures::only_static_remote_key()));
+	}
+
+	#[test]
+	fn test_anchor_channel_reserve_context_for_network() {
+		// Changes to these values should be deliberate, as they affect the reserve of every user
+		// relying on the per-network defaults.
+		let mainnet = AnchorChannelReserveContext::for_network(Network::Bitcoin);
+		assert_eq!(mainnet, AnchorChannelReserveContext::default());
+		assert_eq!(mainnet.upper_bound_fee_rate, FeeRate::from_sat_per_vb(50).unwrap());
+
+		for network in [Network::Testnet, Network::Signet, Network::Regtest] {
+			let context = AnchorChannelReserveContext::for_network(network);
+			assert_eq!(context.upper_bound_fee_rate, FeeRate::from_sat_per_vb(5).unwrap());
+			assert_eq!(
+				context,
+				AnchorChannelReserveContext {
+					upper_bound_fee_rate: FeeRate::from_sat_per_vb(5).unwrap(),
+					..AnchorChannelReserveContext::default()
+				}
+			);
+		}
+	}
+
+	#[test]
+	fn test_anchor_channel_reserve_context_from_fee_estimator() {
+		// Only the dedicated reserve target determines the upper bound, not the feerates we
+		// currently pay.
+		let fee_estimator = TestFeeEstimator::new(253);
+		fee_estimator
+			.target_override
+			.lock()
+			.unwrap()
+			.insert(ConfirmationTarget::MaximumExogenousFeeReserve, 20 * 250);
+		let context = AnchorChannelReserveContext::from_fee_estimator(&&fee_estimator);
+		assert_eq!(
+			context,
+			AnchorChannelReserveContext {
+				upper_bound_fee_rate: FeeRate::from_sat_per_vb(20).unwrap(),
+				..AnchorChannelReserveContext::default()
+			}
+		);
+
+		// Estimates below the minimum relay fee rate are raised to it.
+		fee_estimator
+			.target_override
+			.lock()
+			.unwrap()
+			.insert(ConfirmationTarget::MaximumExogenousFeeReserve, 100);
+		let context = AnchorChannelReserveContext::from_fee_estimator(&&fee_estimator);
+		assert_eq!(context.upper_bound_fee_rate, FeeRate::from_sat_per_kwu(253));
+	}
+
+	#[test]
+	fn test_anchor_channel_reserve_context_serialization() {
+		let contexts = [
+			AnchorChannelReserveContext::default(),
+			AnchorChannelReserveContext {
+				upper_bound_fee_rate: FeeRate::ZERO,
+				expected_accepted_htlcs: 0,
+				taproot_wallet: true,
+				reserve_policy: ReservePolicy::ReceiveOnly,
+				channel_type: AnchorChannelType::ZeroFeeCommitments,
+				safety_margin_ppm: 0,
+				min_economical_htlc_value_sat: Some(0),
+				max_htlc_value_in_flight_msat: None,
+				include_outbound_htlcs: false,
+				expected_offered_htlcs: 0,
+				min_confirmations: 0,
+				exclude_immature_coinbase: false,
+				commitment_weight_params: None,
+				include_justice_path: false,
+			},
+			AnchorChannelReserveContext {
+				upper_bound_fee_rate: FeeRate::MAX,
+				expected_accepted_htlcs: u16::MAX,
+				taproot_wallet: false,
+				reserve_policy: ReservePolicy::Forwarding,
+				channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+				safety_margin_ppm: u32::MAX,
+				min_economical_htlc_value_sat: Some(u64::MAX),
+				max_htlc_value_in_flight_msat: Some(u64::MAX),
+				include_outbound_htlcs: true,
+				expected_offered_htlcs: u16::MAX,
+				min_confirmations: u32::MAX,
+				exclude_immature_coinbase: true,
+				commitment_weight_params: Some(CommitmentWeightParams {
+					num_anchor_outputs: u8::MAX,
+					to_local_output_weight: Weight::MAX,
+					to_remote_output_weight: Weight::ZERO,
+				}),
+				include_justice_path: true,
+			},
+		];
+		for context in contexts {
+			let encoded = context.encode();
+			let decoded: AnchorChannelReserveContext = Readable::read(&mut &encoded[..]).unwrap();
+			assert_eq!(decoded, context);
+
+			// Unknown odd TLV records written by newer versions are ignored.
+			let mut encoded_with_odd_type = encoded.clone();
+			encoded_with_odd_type[0] += 3;
+			encoded_with_odd_type.extend_from_slice(&[27, 1, 42]);
+			let decoded: AnchorChannelReserveContext =
+				Readable::read(&mut &encoded_with_odd_type[..]).unwrap();
+			assert_eq!(decoded, context);
+
+			// Unknown even TLV records are required to be understood.
+			let mut encoded_with_even_type = encoded.clone();
+			encoded_with_even_type[0] += 3;
+			encoded_with_even_type.extend_from_slice(&[26, 1, 42]);
+			let res: Result<AnchorChannelReserveContext, _> =
+				Readable::read(&mut &encoded_with_even_type[..]);
+			assert_eq!(res, Err(DecodeError::UnknownRequiredFeature));
+		}
+	}
+
+	#[test]
+	fn test_reserve_status_serialization() {
+		let context = AnchorChannelReserveContext::default();
+		let utxos = [make_p2wpkh_utxo(Amount::ONE_BTC)];
+		let reserve_check =
+			AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 3);
+		let encoded = reserve_check.encode();
+		let decoded: AnchorChannelReserveCheck = Readable::read(&mut &encoded[..]).unwrap();
+		assert_eq!(decoded, reserve_check);
+
+		// Unknown odd TLV records written by newer versions are ignored.
+		let mut encoded_with_odd_type = encoded.clone();
+		encoded_with_odd_type[0] += 3;
+		encoded_with_odd_type.extend_from_slice(&[27, 1, 42]);
+		let decoded: AnchorChannelReserveCheck =
+			Readable::read(&mut &encoded_with_odd_type[..]).unwrap();
+		assert_eq!(decoded, reserve_check);
+
+		let release =
+			PendingReserveRelease { channel_id: ChannelId([42; 32]), release_height: 1000 };
+		let decoded: PendingReserveRelease = Readable::read(&mut &release.encode()[..]).unwrap();
+		assert_eq!(decoded, release);
+	}
+
+	#[test]
+	fn test_anchor_reserve_report() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = [
+			make_classified_utxo(0, WalletInputType::P2wpkh, Amount::ONE_BTC),
+			make_classified_utxo(1, WalletInputType::P2trKeyPath, Amount::ONE_BTC),
+			make_p2wpkh_utxo_at(2, reserve_per_channel),
+			make_p2wpkh_utxo_at(3, Amount::ZERO),
+			make_p2wpkh_utxo_at(4, Amount::from_sat(1)),
+			make_classified_utxo(0, WalletInputType::P2wpkh, Amount::ONE_BTC),
+		];
+		let channel_reserves = vec![
+			(ChannelId([2; 32]), reserve_per_channel),
+			(ChannelId([3; 32]), reserve_per_channel * 2),
+			(ChannelId([1; 32]), reserve_per_channel),
+		];
+		let report = AnchorReserveReport::with_channel_reserves(&context, &utxos, channel_reserves);
+		let num_supportable_channels =
+			get_supportable_anchor_channels_detailed(&context, &utxos).num_channels;
+		assert_eq!(report.num_anchor_channels(), 3);
+		assert_eq!(report.num_supportable_channels(), num_supportable_channels);
+
+		// Channels are listed by descending reserve, then by channel ID.
+		let reserve_sat = reserve_per_channel.to_sat();
+		let expected = format!(
+			"reserve_per_channel_sat={}\n\
+			num_anchor_channels=3\n\
+			total_reserve_required_sat={}\n\
+			num_supportable_channels={}\n\
+			utxos class=p2wpkh count=1 value_sat=100000000\n\
+			utxos class=p2tr count=1 value_sat=100000000\n\
+			utxos class=other count=1 value_sat={}\n\
+			utxos class=uneconomical count=1 value_sat=1\n\
+			utxos class=zero_value count=1 value_sat=0\n\
+			utxos class=duplicate count=1 value_sat=100000000\n\
+			channel channel_id={} reserve_sat={}\n\
+			channel channel_id={} reserve_sat={}\n\
+			channel channel_id={} reserve_sat={}\n\
+			blocker reason=uneconomical_utxos count=1 value_sat=1\n\
+			blocker reason=zero_value_utxos count=1 value_sat=0\n\
+			blocker reason=duplicate_utxos count=1 value_sat=100000000\n",
+			reserve_sat,
+			total_reserve_required(&context, 3, false).to_sat(),
+			num_supportable_channels,
+			reserve_sat,
+			ChannelId([3; 32]),
+			reserve_sat * 2,
+			ChannelId([1; 32]),
+			reserve_sat,
+			ChannelId([2; 32]),
+			reserve_sat,
+		);
+		assert_eq!(report.to_string(), expected);
+	}
+
+	#[test]
+	fn test_anchor_reserve_report_caps_listed_channels() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = [make_p2wpkh_utxo_at(0, reserve_per_channel * 3)];
+		let channel_reserves =
+			(0..25u8).map(|i| (ChannelId([i; 32]), reserve_per_channel)).collect();
+		let report = AnchorReserveReport::with_channel_reserves(&context, &utxos, channel_reserves);
+
+		let report = report.to_string();
+		let channel_lines: Vec<&str> =
+			report.lines().filter(|line| line.starts_with("channel ")).collect();
+		assert_eq!(channel_lines.len(), MAX_REPORTED_CHANNELS);
+		assert_eq!(
+			channel_lines[0],
+			format!(
+				"channel channel_id={} reserve_sat={}",
+				ChannelId([0; 32]),
+				reserve_per_channel.to_sat()
+			)
+		);
+		assert!(report.contains("\nchannels_omitted=15\n"));
+
+		// The reserve only supports a fraction of the channels, so supporting another one is blocked
+		// by the value still needed.
+		let shortfall = utxo_value_needed_for_channels(&context, &utxos, 26);
+		assert!(shortfall > Amount::ZERO);
+		assert!(report.ends_with(&format!(
+			"blocker reason=shortfall count=1 value_sat={}\n",
+			shortfall.to_sat()
+		)));
+	}
+
+	fn make_p2wpkh_utxo(amount: Amount) -> Utxo {
+		Utxo {
+			outpoint: OutPoint {
+				txid: Txid::from_str(
+					"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
+				)
+				.unwrap(),
+				vout: 0,
+			},
+			output: TxOut { value: amount, script_pubkey: ScriptBuf::new() },
+			satisfaction_weight: Weight::from_wu(1 * 4 + (1 + 1 + 72 + 1 + 33)),
+		}
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		// Only 3 disjoint sets with a value greater than the required reserve can be created.
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 99 / 100),
+			make_p2wpkh_utxo_at(3, reserve_per_channel * 99 / 100),
+			make_p2wpkh_utxo_at(4, reserve_per_channel * 20 / 100),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, utxos.as_slice()), 3);
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_with_uneconomical_utxos() {
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(100 * 250),
+			..AnchorChannelReserveContext::default()
+		};
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let satisfaction_fee = context
+			.upper_bound_fee_rate
+			.fee_wu(make_p2wpkh_utxo(Amount::ZERO).satisfaction_weight)
+			.unwrap();
+		// One whole UTXO and a set of fractional UTXOs support 2 channels.
+		let mut utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(3, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(4, reserve_per_channel * 60 / 100),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 2);
+
+		// Dust UTXOs that are not worth spending at the upper bound fee rate are skipped entirely.
+		let dust_utxos = vec![
+			make_p2wpkh_utxo_at(5, Amount::ONE_SAT),
+			make_p2wpkh_utxo_at(6, satisfaction_fee / 2),
+			make_p2wpkh_utxo_at(7, satisfaction_fee),
+		];
+		utxos.extend(dust_utxos.iter().cloned());
+		let dust_outpoints: Vec<OutPoint> = dust_utxos.iter().map(|utxo| utxo.outpoint).collect();
+		assert_eq!(
+			get_supportable_anchor_channels_with_uneconomical_utxos(&context, &utxos),
+			(2, dust_outpoints.clone())
+		);
+
+		// A UTXO just above its satisfaction fee still contributes to the reserve.
+		utxos.push(make_p2wpkh_utxo_at(8, satisfaction_fee + Amount::ONE_SAT));
+		assert_eq!(
+			get_supportable_anchor_channels_with_uneconomical_utxos(&context, &utxos),
+			(2, dust_outpoints)
+		);
+
+		// At a lower fee rate, the same UTXOs are no longer uneconomical.
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1),
+			..context
+		};
+		let uneconomical_utxos =
+			get_supportable_anchor_channels_with_uneconomical_utxos(&context, &utxos).1;
+		assert_eq!(uneconomical_utxos, vec![utxos[5].outpoint]);
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_duplicate_and_zero_value_utxos() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 60 / 100),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 1);
+
+		// Passing the same UTXOs twice, e.g. after merging wallet snapshots, does not double-count
+		// them, even if their values were reported differently.
+		let mut duplicated_utxos = utxos.clone();
+		duplicated_utxos.extend(utxos.iter().cloned());
+		duplicated_utxos.push(make_p2wpkh_utxo_at(1, reserve_per_channel * 2));
+		assert_eq!(get_supportable_anchor_channels(&context, &duplicated_utxos), 1);
+		assert_eq!(try_get_supportable_anchor_channels(&context, &duplicated_utxos), Ok(1));
+		let check =
+			AnchorChannelReserveCheck::with_num_anchor_channels(&context, &duplicated_utxos, 1);
+		assert!(!check.can_support_additional_anchor_channel());
+
+		// Zero-value UTXOs are rejected rather than reported as uneconomical.
+		duplicated_utxos.push(make_p2wpkh_utxo_at(3, Amount::ZERO));
+		assert_eq!(
+			get_supportable_anchor_channels_detailed(&context, &duplicated_utxos),
+			SupportableAnchorChannels {
+				num_channels: 1,
+				uneconomical_utxos: Vec::new(),
+				zero_value_utxos: vec![duplicated_utxos[7].outpoint],
+				num_duplicate_utxos: 4,
+				immature_utxos: Vec::new(),
+				immature_value: Amount::ZERO,
+			}
+		);
+		assert_eq!(
+			get_supportable_anchor_channels_with_uneconomical_utxos(&context, &duplicated_utxos),
+			(1, Vec::new())
+		);
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_with_maturity() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos: Vec<Utxo> =
+			(0..5).map(|vout| make_p2wpkh_utxo_at(vout, reserve_per_channel)).collect();
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 5);
+
+		// The last UTXO has no known maturity and is counted regardless.
+		let maturities = [
+			UtxoMaturity { confirmations: 6, is_coinbase: false },
+			UtxoMaturity { confirmations: 0, is_coinbase: false },
+			UtxoMaturity { confirmations: 99, is_coinbase: true },
+			UtxoMaturity { confirmations: 100, is_coinbase: true },
+		];
+		let supportable =
+			get_supportable_anchor_channels_with_maturity(&context, &utxos, &maturities);
+		assert_eq!(supportable.num_channels, 3);
+		assert_eq!(supportable.immature_utxos, vec![utxos[1].outpoint, utxos[2].outpoint]);
+		assert_eq!(supportable.immature_value, reserve_per_channel * 2);
+
+		// Unconfirmed UTXOs may be allowed, while immature coinbase UTXOs are still excluded.
+		let unconfirmed_context =
+			AnchorChannelReserveContext { min_confirmations: 0, ..context.clone() };
+		let supportable = get_supportable_anchor_channels_with_maturity(
+			&unconfirmed_context,
+			&utxos,
+			&maturities,
+		);
+		assert_eq!(supportable.num_channels, 4);
+		assert_eq!(supportable.immature_utxos, vec![utxos[2].outpoint]);
+		assert_eq!(supportable.immature_value, reserve_per_channel);
+
+		let permissive_context =
+			AnchorChannelReserveContext { exclude_immature_coinbase: false, ..unconfirmed_context };
+		let supportable =
+			get_supportable_anchor_channels_with_maturity(&permissive_context, &utxos, &maturities);
+		assert_eq!(supportable, get_supportable_anchor_channels_detailed(&context, &utxos));
+		assert_eq!(supportable.num_channels, 5);
+		assert_eq!(supportable.immature_value, Amount::ZERO);
+
+		// Requiring more confirmations excludes the confirmed UTXO, but not the mature coinbase.
+		let deep_context = AnchorChannelReserveContext { min_confirmations: 7, ..context };
+		let supportable =
+			get_supportable_anchor_channels_with_maturity(&deep_context, &utxos, &maturities);
+		assert_eq!(supportable.num_channels, 2);
+		assert_eq!(
+			supportable.immature_utxos,
+			vec![utxos[0].outpoint, utxos[1].outpoint, utxos[2].outpoint]
+		);
+		assert_eq!(supportable.immature_value, reserve_per_channel * 3);
+	}
+
+	#[test]
+	fn test_context_from_observed_stats() {
+		assert_eq!(AnchorChannelReserveContext::from_observed_stats(&[], 50), None);
+
+		let secp_ctx = bitcoin::secp256k1::Secp256k1::signing_only();
+		let secret_key = bitcoin::secp256k1::SecretKey::from_slice(&[42; 32]).unwrap();
+		let counterparty_node_id = PublicKey::from_secret_key(&secp_ctx, &secret_key);
+		let stats: Vec<InflightHtlcStats> = (1..=10)
+			.map(|max_accepted_htlcs| InflightHtlcStats {
+				channel_id: ChannelId::from_bytes([max_accepted_htlcs as u8; 32]),
+				counterparty_node_id,
+				accepted_htlcs: 0,
+				accepted_htlcs_value_msat: 0,
+				max_accepted_htlcs,
+				max_accepted_htlcs_value_msat: max_accepted_htlcs * 1_000_000,
+			})
+			.collect();
+		let expected_accepted_htlcs = |percentile| {
+			AnchorChannelReserveContext::from_observed_stats(&stats, percentile)
+				.unwrap()
+				.expected_accepted_htlcs
+		};
+		assert_eq!(expected_accepted_htlcs(0), 1);
+		assert_eq!(expected_accepted_htlcs(50), 5);
+		assert_eq!(expected_accepted_htlcs(90), 9);
+		assert_eq!(expected_accepted_htlcs(95), 10);
+		assert_eq!(expected_accepted_htlcs(255), 10);
+
+		let context = AnchorChannelReserveContext::from_observed_stats(&stats, 50).unwrap();
+		let expected_context = AnchorChannelReserveContext {
+			expected_accepted_htlcs: 5,
+			..AnchorChannelReserveContext::default()
+		};
+		assert_eq!(context, expected_context);
+	}
+
+	#[test]
+	fn test_try_reserve_calculations() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		assert_eq!(try_get_reserve_per_channel(&context), Ok(reserve_per_channel));
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(3, Amount::ZERO),
+		];
+		assert_eq!(try_get_supportable_anchor_channels(&context, &utxos), Ok(1));
+
+		// A reserve which doesn't fit in an `Amount` supports no channels.
+		let max_fee_context =
+			AnchorChannelReserveContext { upper_bound_fee_rate: FeeRate::MAX, ..context.clone() };
+		assert_eq!(
+			try_get_reserve_per_channel(&max_fee_context),
+			Err(ReserveCalculationError::FeeOverflow)
+		);
+		assert_eq!(get_reserve_per_channel(&max_fee_context), Amount::MAX);
+		assert_eq!(
+			try_get_supportable_anchor_channels(&max_fee_context, &utxos),
+			Err(ReserveCalculationError::FeeOverflow)
+		);
+		assert_eq!(get_supportable_anchor_channels(&max_fee_context, &utxos), 0);
+
+		// A UTXO whose satisfaction weight doesn't fit in a transaction is uneconomical.
+		let mut absurd_utxos = utxos.clone();
+		let mut absurd_utxo = make_p2wpkh_utxo_at(4, reserve_per_channel * 2);
+		absurd_utxo.satisfaction_weight = Weight::MAX;
+		absurd_utxos.push(absurd_utxo);
+		assert_eq!(
+			try_get_supportable_anchor_channels(&context, &absurd_utxos),
+			Err(ReserveCalculationError::WeightOverflow)
+		);
+		assert_eq!(
+			get_supportable_anchor_channels_with_uneconomical_utxos(&context, &absurd_utxos),
+			(1, vec![utxos[3].outpoint, absurd_utxos[4].outpoint])
+		);
+
+		// At a zero fee rate, the reserve is zero and every UTXO with a non-zero value covers the
+		// reserve of a channel by itself, rather than dividing by the zero reserve.
+		let zero_fee_context =
+			AnchorChannelReserveContext { upper_bound_fee_rate: FeeRate::ZERO, ..context };
+		assert_eq!(
+			try_get_reserve_per_channel(&zero_fee_context),
+			Err(ReserveCalculationError::ZeroReserve)
+		);
+		assert_eq!(get_reserve_per_channel(&zero_fee_context), Amount::ZERO);
+		assert_eq!(
+			try_get_supportable_anchor_channels(&zero_fee_context, &utxos),
+			Err(ReserveCalculationError::ZeroReserve)
+		);
+		assert_eq!(get_supportable_anchor_channels(&zero_fee_context, &utxos), 3);
+		assert_eq!(get_supportable_anchor_channels(&zero_fee_context, &[]), 0);
+	}
+
+	#[test]
+	fn test_total_reserve_required() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		assert_eq!(total_reserve_required(&context, 0, false), Amount::ZERO);
+		assert_eq!(total_reserve_required(&context, 1, false), reserve_per_channel);
+		assert_eq!(total_reserve_required(&context, 10, false), reserve_per_channel * 10);
+
+		// The total reserve of whole UTXOs is exactly what is needed to support the channels.
+		let utxos =
+			(0..10).map(|idx| make_p2wpkh_utxo_at(idx, reserve_per_channel)).collect::<Vec<_>>();
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 10);
+
+		// Fractional UTXOs need to cover double the reserve, not including their own satisfaction.
+		let fractional_reserve = total_reserve_required(&context, 3, true);
+		assert!(fractional_reserve < reserve_per_channel * 6);
+		let fee_per_utxo = context
+			.upper_bound_fee_rate
+			.fee_wu(make_p2wpkh_utxo(Amount::ZERO).satisfaction_weight)
+			.unwrap();
+		let utxo_value = reserve_per_channel * 90 / 100;
+		let utxo_amount = (utxo_value - fee_per_utxo).to_sat();
+		let num_utxos = (fractional_reserve.to_sat() + utxo_amount - 1) / utxo_amount;
+		let utxos = (0..num_utxos as u32)
+			.map(|idx| make_p2wpkh_utxo_at(idx, utxo_value))
+			.collect::<Vec<_>>();
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 3);
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos[1..]), 2);
+
+		// Totals exceeding the range of `Amount` saturate, or fail when checked, even where the
+		// product would overflow a `u64` many times over.
+		let max_channels = u64::MAX / reserve_per_channel.to_sat();
+		assert_eq!(
+			try_total_reserve_required(&context, max_channels, false),
+			Ok(reserve_per_channel * max_channels)
+		);
+		assert_eq!(
+			try_total_reserve_required(&context, max_channels + 1, false),
+			Err(ReserveCalculationError::FeeOverflow)
+		);
+		assert_eq!(total_reserve_required(&context, max_channels + 1, false), Amount::MAX);
+		let max_fractional_channels = u64::MAX / total_reserve_required(&context, 1, true).to_sat();
+		assert!(try_total_reserve_required(&context, max_fractional_channels, true).is_ok());
+		assert_eq!(
+			try_total_reserve_required(&context, max_fractional_channels + 1, true),
+			Err(ReserveCalculationError::FeeOverflow)
+		);
+		assert_eq!(total_reserve_required(&context, u64::MAX, false), Amount::MAX);
+		assert_eq!(total_reserve_required(&context, u64::MAX, true), Amount::MAX);
+
+		// A reserve per channel which can't be represented fails as well.
+		let max_fee_context =
+			AnchorChannelReserveContext { upper_bound_fee_rate: FeeRate::MAX, ..context.clone() };
+		assert_eq!(
+			try_total_reserve_required(&max_fee_context, 1, false),
+			Err(ReserveCalculationError::FeeOverflow)
+		);
+		assert_eq!(total_reserve_required(&max_fee_context, 1, true), Amount::MAX);
+
+		// A zero reserve per channel results in a zero total reserve.
+		let zero_fee_context =
+			AnchorChannelReserveContext { upper_bound_fee_rate: FeeRate::ZERO, ..context };
+		assert_eq!(try_total_reserve_required(&zero_fee_context, u64::MAX, true), Ok(Amount::ZERO));
+	}
+
+	#[test]
+	fn test_utxo_value_needed_for_channels() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		assert_eq!(utxo_value_needed_for_channels(&context, &[], 0), Amount::ZERO);
+		assert_eq!(utxo_value_needed_for_channels(&context, &[], 2), reserve_per_channel * 2);
+
+		// Existing UTXOs are accounted for, while a fractional one too small to support a channel
+		// by itself doesn't reduce the value needed.
+		let mut utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 2),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 60 / 100),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 2);
+		assert_eq!(utxo_value_needed_for_channels(&context, &utxos, 1), Amount::ZERO);
+		assert_eq!(utxo_value_needed_for_channels(&context, &utxos, 2), Amount::ZERO);
+		let needed = utxo_value_needed_for_channels(&context, &utxos, 5);
+		assert_eq!(needed, reserve_per_channel * 3);
+
+		// Providing the value as whole-reserve UTXOs supports the target number of channels.
+		for idx in 3..6 {
+			utxos.push(make_p2wpkh_utxo_at(idx, reserve_per_channel));
+		}
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 5);
+		assert_eq!(utxo_value_needed_for_channels(&context, &utxos, 5), Amount::ZERO);
+
+		// The value saturates rather than overflowing.
+		assert_eq!(utxo_value_needed_for_channels(&context, &utxos, u64::MAX), Amount::MAX);
+	}
+
+	#[test]
+	fn test_suggest_reserve_context() {
+		let base_context = AnchorChannelReserveContext {
+			taproot_wallet: true,
+			..AnchorChannelReserveContext::default()
+		};
+		// Without any confirmed claims, there is nothing to calibrate from.
+		assert_eq!(suggest_reserve_context(&[], &base_context), base_context);
+		let cooperative_close = OnchainResolutionStats::default();
+		assert_eq!(
+			suggest_reserve_context(&[cooperative_close.clone()], &base_context),
+			base_context
+		);
+
+		let force_close = |num_htlc_outputs_claimed, max_claim_feerate_sat_per_1000_weight| {
+			OnchainResolutionStats {
+				total_claim_fees: Amount::from_sat(5_000),
+				total_external_inputs_value: Amount::from_sat(100_000),
+				num_confirmed_claims: 1 + num_htlc_outputs_claimed,
+				num_htlc_outputs_claimed,
+				max_claim_feerate_sat_per_1000_weight,
+				max_pending_claims: 1,
+				total_exogenous_fees: Amount::from_sat(5_000),
+			}
+		};
+		let historical_stats = [force_close(3, 2_000), cooperative_close, force_close(1, 5_000)];
+		assert_eq!(
+			suggest_reserve_context(&historical_stats, &base_context),
+			AnchorChannelReserveContext {
+				expected_accepted_htlcs: 3,
+				upper_bound_fee_rate: FeeRate::from_sat_per_kwu(5_000),
+				..base_context
+			}
+		);
+	}
+
+	#[test]
+	fn test_max_supportable_accepted_htlcs() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		// Two whole UTXOs and a set of fractional UTXOs support 4 channels at the default number
+		// of HTLCs.
+		let mut utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+		];
+		for index in 2..10 {
+			utxos.push(make_p2wpkh_utxo_at(index, reserve_per_channel * 60 / 100));
+		}
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 4);
+
+		let with_htlcs = |expected_accepted_htlcs| AnchorChannelReserveContext {
+			expected_accepted_htlcs,
+			..context.clone()
+		};
+		for num_channels in 1..=4 {
+			let max_accepted_htlcs = max_supportable_accepted_htlcs(&context, &utxos, num_channels);
+			assert!(max_accepted_htlcs >= context.expected_accepted_htlcs);
+			let supportable_channels =
+				get_supportable_anchor_channels(&with_htlcs(max_accepted_htlcs), &utxos);
+			assert!(supportable_channels >= num_channels);
+			let supportable_channels =
+				get_supportable_anchor_channels(&with_htlcs(max_accepted_htlcs + 1), &utxos);
+			assert!(supportable_channels < num_channels);
+		}
+		// Fewer channels can each support more HTLCs.
+		assert!(
+			max_supportable_accepted_htlcs(&context, &utxos, 2)
+				> max_supportable_accepted_htlcs(&context, &utxos, 4)
+		);
+
+		// Without HTLCs, every UTXO covers the reserve of a channel, but not more.
+		assert_eq!(get_supportable_anchor_channels(&with_htlcs(0), &utxos), 10);
+		assert_eq!(max_supportable_accepted_htlcs(&context, &utxos, 11), 0);
+
+		// The result saturates at the maximum number of HTLCs per commitment transaction.
+		let large_utxos = vec![make_p2wpkh_utxo(Amount::from_sat(100_000_000))];
+		assert_eq!(max_supportable_accepted_htlcs(&context, &large_utxos, 1), 483);
+		assert_eq!(max_supportable_accepted_htlcs(&context, &[], 0), 483);
+	}
+
+	#[test]
+	fn test_derive_handshake_config() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let mut utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+		];
+		for index in 2..10 {
+			utxos.push(make_p2wpkh_utxo_at(index, reserve_per_channel * 60 / 100));
+		}
+		let base_config = ChannelHandshakeConfig {
+			our_max_accepted_htlcs: 483,
+			max_inbound_htlc_value_in_flight_percent_of_channel: 100,
+			..Default::default()
+		};
+
+		for planned_channels in 1..=4 {
+			let config =
+				context.derive_handshake_config(&utxos, planned_channels, &base_config).unwrap();
+			let max_accepted_htlcs =
+				max_supportable_accepted_htlcs(&context, &utxos, planned_channels);
+			assert!(max_accepted_htlcs < 483);
+			assert_eq!(config.our_max_accepted_htlcs, max_accepted_htlcs);
+			assert_eq!(
+				config.max_inbound_htlc_value_in_flight_percent_of_channel as u16,
+				max(100 * max_accepted_htlcs / 483, 1)
+			);
+			assert_eq!(config.minimum_depth, base_config.minimum_depth);
+
+			// Reserves sized for the derived config are supportable.
+			let derived_context = AnchorChannelReserveContext {
+				expected_accepted_htlcs: config.our_max_accepted_htlcs,
+				..context.clone()
+			};
+			assert!(get_supportable_anchor_channels(&derived_context, &utxos) >= planned_channels);
+			let mut user_config = UserConfig::default();
+			user_config.channel_handshake_config = config;
+			let fee_estimator = TestFeeEstimator::new(253);
+			assert_eq!(
+				validate_against_config(&derived_context, &user_config, &&fee_estimator, true),
+				Ok(())
+			);
+		}
+
+		// A config accepting fewer HTLCs than supportable is left as is.
+		let small_config =
+			ChannelHandshakeConfig { our_max_accepted_htlcs: 1, ..Default::default() };
+		let config = context.derive_handshake_config(&utxos, 1, &small_config).unwrap();
+		assert_eq!(config.our_max_accepted_htlcs, 1);
+		assert_eq!(
+			config.max_inbound_htlc_value_in_flight_percent_of_channel,
+			small_config.max_inbound_htlc_value_in_flight_percent_of_channel
+		);
+
+		// Without enough reserve for a single HTLC per channel, no config is derived.
+		assert!(context.derive_handshake_config(&utxos, 11, &base_config).is_none());
+		assert!(context.derive_handshake_config(&[], 1, &base_config).is_none());
+	}
+
+	#[test]
+	fn test_amount_overflow() {
+		// Calculations over amounts close to the total supply saturate rather than overflow,
+		// independently of the limits of any particular chain.
+		let context = AnchorChannelReserveContext::default();
+		let utxos = vec![make_p2wpkh_utxo_at(0, Amount::MAX), make_p2wpkh_utxo_at(1, Amount::MAX)];
+		assert_eq!(try_get_supportable_anchor_channels(&context, &utxos), Ok(2));
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &[u64::MAX, u64::MAX]),
+			ChannelReserveHealth::Healthy
+		);
+
+		// At a fee rate for which the fee of a single HTLC transaction exceeds the total supply,
+		// its fee and the total fee saturate.
+		let estimate = estimate_force_close_cost(
+			&ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies(),
+			253,
+			1,
+			1,
+			FeeRate::MAX,
+			false,
+		);
+		assert!(estimate.transactions.iter().skip(2).all(|tx| tx.fee == Amount::MAX));
+		assert_eq!(estimate.total_fee, Amount::MAX);
+
+		// The number of HTLCs derived from a capacity is capped before the reserve is calculated.
+		let reserve = get_reserve_per_channel_for_capacity(&context, u64::MAX, 1, 483);
+		let max_htlcs_context =
+			AnchorChannelReserveContext { expected_accepted_htlcs: 483, ..context };
+		assert_eq!(reserve, get_reserve_per_channel(&max_htlcs_context));
+	}
+
+	#[test]
+	fn test_anchor_output_spend_transaction_weight() {
+		// Example with smaller signatures:
+		// https://mempool.space/tx/188b0f9f26999a48611dba4e2a88507251eba31f3695d005023de3514cba34bd
+		// DER-encoded ECDSA signatures vary in size and can be 71-73 bytes.
+		assert_eq!(
+			anchor_output_spend_transaction_weight(
+				&AnchorChannelReserveContext { taproot_wallet: false, ..Default::default() },
+				Weight::from_wu(P2WPKH_INPUT_WEIGHT),
+			),
+			Weight::from_wu(717)
+		);
+
+		// Example:
+		// https://mempool.space/tx/9c493177e395ec77d9e725e1cfd465c5f06d4a5816dd0274c3a8c2442d854a85
+		assert_eq!(
+			anchor_output_spend_transaction_weight(
+				&AnchorChannelReserveContext { taproot_wallet: true, ..Default::default() },
+				Weight::from_wu(P2TR_KEYPATH_INPUT_WEIGHT),
+			),
+			Weight::from_wu(723)
+		);
+	}
+
+	#[test]
+	fn test_htlc_success_transaction_weight() {
+		assert_eq!(
+			htlc_success_transaction_weight(
+				&AnchorChannelReserveContext { taproot_wallet: false, ..Default::default() },
+				WalletInputType::P2wpkh,
+			),
+			Weight::from_wu(1102)
+		);
+
+		assert_eq!(
+			htlc_success_transaction_weight(
+				&AnchorChannelReserveContext { taproot_wallet: true, ..Default::default() },
+				WalletInputType::P2trKeyPath,
+			),
+			Weight::from_wu(1108)
+		);
+	}
+
+	#[test]
+	fn test_commitment_transaction_weight() {
+		assert_eq!(
+			commitment_transaction_weight(0, AnchorChannelType::AnchorsZeroFeeHtlcTx),
+			Weight::from_wu(1124)
+		);
+		assert_eq!(
+			commitment_transaction_weight(0, AnchorChannelType::ZeroFeeCommitments),
+			Weight::from_wu(776)
+		);
+		assert_eq!(
+			commitment_transaction_weight(3, AnchorChannelType::AnchorsZeroFeeHtlcTx),
+			Weight::from_wu(1124 + 3 * 172)
+		);
+		assert_eq!(
+			commitment_transaction_weight(3, AnchorChannelType::ZeroFeeCommitments),
+			Weight::from_wu(776 + 3 * 172)
+		);
+
+		// The weight agrees with the one used for the committed fee of anchor channels.
+		let channel_type = ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		assert_eq!(
+			commitment_transaction_weight(5, AnchorChannelType::AnchorsZeroFeeHtlcTx),
+			Weight::from_wu(
+				commitment_tx_base_weight(&channel_type) + 5 * COMMITMENT_TX_WEIGHT_PER_HTLC
+			)
+		);
+	}
+
+	#[test]
+	fn test_wallet_input_weight() {
+		assert_eq!(WalletInputType::P2wpkh.input_weight(), Weight::from_wu(272));
+		assert_eq!(WalletInputType::P2trKeyPath.input_weight(), Weight::from_wu(230));
+	}
+
+	#[test]
+	fn test_change_output_weight() {
+		// The change outputs assumed by the estimates match those accounted for by the wallet.
+		let p2wpkh_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros());
+		assert_eq!(change_output_weight(&p2wpkh_script), Weight::from_wu(P2WPKH_OUTPUT_WEIGHT));
+		let p2tr_script = bitcoin::script::Builder::new()
+			.push_opcode(bitcoin::opcodes::all::OP_PUSHNUM_1)
+			.push_slice([0; 32])
+			.into_script();
+		assert!(p2tr_script.is_p2tr());
+		assert_eq!(change_output_weight(&p2tr_script), Weight::from_wu(P2TR_OUTPUT_WEIGHT));
+	}
+
+	#[test]
+	fn test_htlc_timeout_transaction_weight() {
+		// Example with smaller signatures:
+		// https://mempool.space/tx/37185342f9f088bd12376599b245dbc02eb0bb6c4b99568b75a8cd775ddfd1f4
+		assert_eq!(
+			htlc_timeout_transaction_weight(
+				&AnchorChannelReserveContext { taproot_wallet: false, ..Default::default() },
+				WalletInputType::P2wpkh,
+			),
+			Weight::from_wu(1062)
+		);
+
+		assert_eq!(
+			htlc_timeout_transaction_weight(
+				&AnchorChannelReserveContext { taproot_wallet: true, ..Default::default() },
+				WalletInputType::P2trKeyPath,
+			),
+			Weight::from_wu(1068)
+		);
+	}
+
+	fn make_witness_input(witness: &[&[u8]]) -> TxIn {
+		TxIn { witness: Witness::from_slice(witness), ..Default::default() }
+	}
+
+	#[test]
+	fn test_validate_weight_constants() {
+		let funding_pubkey = PublicKey::from_slice(&[2; 33]).unwrap();
+		let anchor_script = get_keyed_anchor_redeemscript(&funding_pubkey);
+		let wallet_input = make_witness_input(&[&[0x30; 72], &[2; 33]]);
+		let change_output = TxOut {
+			value: Amount::from_sat(10_000),
+			script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+		};
+		let anchor_tx = Transaction {
+			version: bitcoin::transaction::Version::TWO,
+			lock_time: bitcoin::absolute::LockTime::ZERO,
+			input: vec![
+				make_witness_input(&[&[0x30; 72], anchor_script.as_bytes()]),
+				wallet_input.clone(),
+			],
+			output: vec![change_output.clone()],
+		};
+		// With signatures of the size assumed by the constants, the components add up to the
+		// estimated weight of the transaction.
+		let context = AnchorChannelReserveContext { taproot_wallet: false, ..Default::default() };
+		assert_eq!(
+			anchor_tx.weight(),
+			anchor_output_spend_transaction_weight(
+				&context,
+				WalletInputType::P2wpkh.input_weight()
+			)
+		);
+
+		let htlc_output = TxOut {
+			value: Amount::from_sat(1_000),
+			script_pubkey: ScriptBuf::new_p2wsh(&WScriptHash::all_zeros()),
+		};
+		let htlc_tx = Transaction {
+			version: bitcoin::transaction::Version::TWO,
+			lock_time: bitcoin::absolute::LockTime::ZERO,
+			input: vec![
+				make_witness_input(&[&[], &[0x30; 72], &[0x30; 72], &[0x42; 32], &[0x63; 142]]),
+				make_witness_input(&[&[], &[0x30; 72], &[0x30; 72], &[], &[0x63; 136]]),
+				wallet_input,
+			],
+			output: vec![htlc_output.clone(), htlc_output, change_output],
+		};
+
+		let report = validate_weight_constants(&[anchor_tx.clone(), htlc_tx]);
+		assert_eq!(report.num_unclassified_inputs, 0);
+		assert!(report.underestimated().is_empty());
+		let check = |component, num_occurrences, max_actual_weight| WeightConstantCheck {
+			component,
+			num_occurrences,
+			max_actual_weight: Weight::from_wu(max_actual_weight),
+		};
+		assert_eq!(
+			report.checks,
+			vec![
+				check(ClaimWeightComponent::KeyedAnchorInput, 1, ANCHOR_INPUT_WEIGHT),
+				check(ClaimWeightComponent::HtlcSuccess, 1, 702),
+				check(ClaimWeightComponent::HtlcTimeout, 1, 664),
+				check(ClaimWeightComponent::P2wpkhInput, 2, P2WPKH_INPUT_WEIGHT),
+				check(ClaimWeightComponent::P2wpkhOutput, 2, P2WPKH_OUTPUT_WEIGHT),
+			]
+		);
+		assert_eq!(report.checks[1].delta_wu(), 4);
+
+		// A Taproot signature with an explicit sighash type exceeds the estimate, as does a
+		// high-R ECDSA signature.
+		let wallet_inputs_tx = Transaction {
+			input: vec![
+				make_witness_input(&[&[0x42; 65]]),
+				make_witness_input(&[&[0x30; 73], &[2; 33]]),
+			],
+			output: Vec::new(),
+			..anchor_tx
+		};
+		let report = validate_weight_constants(&[wallet_inputs_tx]);
+		let underestimated: Vec<_> = report
+			.underestimated()
+			.iter()
+			.map(|check| (check.component, check.delta_wu()))
+			.collect();
+		assert_eq!(
+			underestimated,
+			vec![
+				(ClaimWeightComponent::P2wpkhInput, -1),
+				(ClaimWeightComponent::P2trKeyPathInput, -1),
+			]
+		);
+		assert_eq!(
+			report.to_string(),
+			"component=p2wpkh_input occurrences=1 estimated_wu=272 max_actual_wu=273 delta_wu=-1 UNDERESTIMATED\n\
+			component=p2tr_key_path_input occurrences=1 estimated_wu=230 max_actual_wu=231 delta_wu=-1 UNDERESTIMATED\n"
+		);
+
+		// Inputs spending other outputs, e.g., legacy wallet inputs, are not classified.
+		let legacy_input =
+			TxIn { script_sig: ScriptBuf::from_bytes(vec![0; 107]), ..Default::default() };
+		let legacy_tx = Transaction {
+			version: bitcoin::transaction::Version::TWO,
+			lock_time: bitcoin::absolute::LockTime::ZERO,
+			input: vec![legacy_input],
+			output: Vec::new(),
+		};
+		let report = validate_weight_constants(&[legacy_tx]);
+		assert_eq!(report.num_unclassified_inputs, 1);
+		assert!(report.checks.is_empty());
+	}
+
+	fn make_p2wpkh_utxo_at(vout: u32, amount: Amount) -> Utxo {
+		let mut utxo = make_p2wpkh_utxo(amount);
+		utxo.outpoint.vout = vout;
+		utxo
+	}
+
+	fn make_classified_utxo(vout: u32, input_type: WalletInputType, amount: Amount) -> Utxo {
+		let script_pubkey = match input_type {
+			WalletInputType::P2wpkh => ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+			WalletInputType::P2trKeyPath => {
+				let mut script_bytes = vec![0x51, 0x20];
+				script_bytes.extend_from_slice(&[0x42; 32]);
+				ScriptBuf::from_bytes(script_bytes)
+			},
+		};
+		let outpoint = make_p2wpkh_utxo_at(vout, amount).outpoint;
+		Utxo::try_from_txout(outpoint, TxOut { value: amount, script_pubkey }).unwrap()
+	}
+
+	#[test]
+	fn test_wallet_input_type_from_utxo() {
+		for input_type in [WalletInputType::P2wpkh, WalletInputType::P2trKeyPath] {
+			let utxo = make_classified_utxo(0, input_type, Amount::ONE_BTC);
+			assert_eq!(WalletInputType::from_utxo(&utxo), Some(input_type));
+			// The satisfaction weight of the UTXO matches that of its input type, except for the
+			// P2WPKH signature, which the reserve assumes to be low-R and thus a byte shorter than
+			// the upper bound used for UTXOs.
+			let low_r_savings = match input_type {
+				WalletInputType::P2wpkh => Weight::from_wu(1),
+				WalletInputType::P2trKeyPath => Weight::ZERO,
+			};
+			assert_eq!(
+				utxo.satisfaction_weight + Weight::from_wu(40 * 4),
+				input_type.input_weight() + low_r_savings
+			);
+		}
+		assert_eq!(WalletInputType::from_utxo(&make_p2wpkh_utxo(Amount::ONE_BTC)), None);
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_mixed_wallet() {
+		let context = AnchorChannelReserveContext {
+			taproot_wallet: true,
+			..AnchorChannelReserveContext::default()
+		};
+		let p2tr_reserve = get_reserve_per_channel_with_input(
+			&context,
+			WalletInputType::P2trKeyPath,
+			Weight::ZERO,
+		);
+		let p2wpkh_reserve =
+			get_reserve_per_channel_with_input(&context, WalletInputType::P2wpkh, Weight::ZERO);
+		assert!(p2wpkh_reserve > p2tr_reserve);
+
+		let utxos_with_net_amount = |input_type, amount: Amount| {
+			(0..2)
+				.map(|vout| {
+					let utxo = make_classified_utxo(vout, input_type, Amount::ZERO);
+					let satisfaction_fee =
+						context.upper_bound_fee_rate.fee_wu(utxo.satisfaction_weight).unwrap();
+					make_classified_utxo(vout, input_type, amount + satisfaction_fee)
+				})
+				.collect::<Vec<_>>()
+		};
+
+		// P2TR UTXOs covering the reserve with P2TR inputs each support a channel.
+		let p2tr_utxos = utxos_with_net_amount(WalletInputType::P2trKeyPath, p2tr_reserve);
+		assert_eq!(get_supportable_anchor_channels(&context, &p2tr_utxos), 2);
+
+		// Previously, P2WPKH UTXOs of the same value were assumed to be spent as P2TR inputs, as
+		// indicated by the context, and supported a channel each as well. Their HTLC transactions
+		// need heavier P2WPKH inputs though, so they only count as fractional UTXOs.
+		let p2wpkh_utxos = utxos_with_net_amount(WalletInputType::P2wpkh, p2tr_reserve);
+		assert_eq!(get_supportable_anchor_channels(&context, &p2wpkh_utxos), 1);
+		let p2wpkh_utxos = utxos_with_net_amount(WalletInputType::P2wpkh, p2wpkh_reserve);
+		assert_eq!(get_supportable_anchor_channels(&context, &p2wpkh_utxos), 2);
+
+		// In a mixed wallet, each UTXO is checked against the reserve of its own input type.
+		let mixed_utxos = vec![
+			make_classified_utxo(0, WalletInputType::P2trKeyPath, p2tr_reserve * 2),
+			make_classified_utxo(1, WalletInputType::P2wpkh, p2wpkh_reserve * 2),
+			make_classified_utxo(2, WalletInputType::P2wpkh, p2tr_reserve),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, &mixed_utxos), 2);
+
+		// UTXOs of unknown type keep being assumed to be of the wallet's type.
+		let unknown_utxos = vec![make_p2wpkh_utxo_at(0, p2tr_reserve * 2)];
+		assert_eq!(get_supportable_anchor_channels(&context, &unknown_utxos), 1);
+	}
+
+	fn make_classified_utxo_with_net_amount(
+		context: &AnchorChannelReserveContext, vout: u32, input_type: WalletInputType,
+		amount: Amount,
+	) -> Utxo {
+		let utxo = make_classified_utxo(vout, input_type, Amount::ZERO);
+		let satisfaction_fee =
+			context.upper_bound_fee_rate.fee_wu(utxo.satisfaction_weight).unwrap();
+		make_classified_utxo(vout, input_type, amount + satisfaction_fee)
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_multi() {
+		let hot_context = AnchorChannelReserveContext::default();
+		let treasury_context = AnchorChannelReserveContext {
+			taproot_wallet: true,
+			safety_margin_ppm: 1_500_000,
+			..AnchorChannelReserveContext::default()
+		};
+		let hot_reserve =
+			get_reserve_per_channel_with_input(&hot_context, WalletInputType::P2wpkh, Weight::ZERO);
+		let treasury_reserve = get_reserve_per_channel_with_input(
+			&treasury_context,
+			WalletInputType::P2trKeyPath,
+			Weight::ZERO,
+		);
+
+		// A rich treasury wallet supports a channel with each of its UTXOs, while a poor hot wallet
+		// doesn't support any.
+		let rich_utxos = (0..3)
+			.map(|vout| {
+				make_classified_utxo_with_net_amount(
+					&treasury_context,
+					vout,
+					WalletInputType::P2trKeyPath,
+					treasury_reserve,
+				)
+			})
+			.collect::<Vec<_>>();
+		let poor_utxos = vec![make_classified_utxo_with_net_amount(
+			&hot_context,
+			10,
+			WalletInputType::P2wpkh,
+			hot_reserve / 2,
+		)];
+		let supportable = get_supportable_anchor_channels_multi(&[
+			(hot_context.clone(), &poor_utxos[..]),
+			(treasury_context.clone(), &rich_utxos[..]),
+		]);
+		assert_eq!(
+			supportable,
+			MultiWalletSupportableAnchorChannels {
+				num_channels: 3,
+				num_channels_per_wallet: vec![0, 3],
+			}
+		);
+		// All channels are funded by the treasury wallet.
+		assert_eq!(supportable.next_channel_wallet(0), Some(1));
+		assert_eq!(supportable.next_channel_wallet(2), Some(1));
+		assert_eq!(supportable.next_channel_wallet(3), None);
+
+		// Channels are assigned to wallets in order, so the treasury wallet funds the first ones.
+		let supportable = get_supportable_anchor_channels_multi(&[
+			(treasury_context.clone(), &rich_utxos[..]),
+			(hot_context.clone(), &rich_utxos[..1]),
+		]);
+		// UTXOs provided by multiple wallets are only counted towards the first.
+		assert_eq!(supportable.num_channels_per_wallet, vec![3, 0]);
+
+		let hot_utxos = vec![make_classified_utxo_with_net_amount(
+			&hot_context,
+			11,
+			WalletInputType::P2wpkh,
+			hot_reserve,
+		)];
+		let supportable = get_supportable_anchor_channels_multi(&[
+			(treasury_context.clone(), &rich_utxos[..]),
+			(hot_context.clone(), &hot_utxos[..]),
+		]);
+		assert_eq!(supportable.num_channels, 4);
+		assert_eq!(supportable.next_channel_wallet(2), Some(0));
+		assert_eq!(supportable.next_channel_wallet(3), Some(1));
+		assert_eq!(supportable.next_channel_wallet(4), None);
+
+		let check = AnchorChannelReserveCheck::with_num_anchor_channels_multi(
+			&[(treasury_context.clone(), &rich_utxos[..]), (hot_context.clone(), &hot_utxos[..])],
+			3,
+		);
+		assert!(check.can_support_additional_anchor_channel());
+		let check = AnchorChannelReserveCheck::with_num_anchor_channels_multi(
+			&[(treasury_context, &rich_utxos[..]), (hot_context, &hot_utxos[..])],
+			4,
+		);
+		assert!(!check.can_support_additional_anchor_channel());
+	}
+
+	#[test]
+	fn test_get_supportable_anchor_channels_multi_fractional() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve =
+			get_reserve_per_channel_with_input(&context, WalletInputType::P2wpkh, Weight::ZERO);
+		let fractional_utxos = |first_vout| {
+			(first_vout..first_vout + 2)
+				.map(|vout| {
+					make_classified_utxo_with_net_amount(
+						&context,
+						vout,
+						WalletInputType::P2wpkh,
+						reserve * 3 / 4,
+					)
+				})
+				.collect::<Vec<_>>()
+		};
+		let first_utxos = fractional_utxos(0);
+		let second_utxos = fractional_utxos(2);
+
+		// Combined, the fractional UTXOs would cover the worst-case coin selection of a channel.
+		let all_utxos = [first_utxos.clone(), second_utxos.clone()].concat();
+		assert_eq!(get_supportable_anchor_channels(&context, &all_utxos), 1);
+
+		// Split across two wallets, neither covers it, as a channel is funded by a single wallet.
+		let supportable = get_supportable_anchor_channels_multi(&[
+			(context.clone(), &first_utxos[..]),
+			(context.clone(), &second_utxos[..]),
+		]);
+		assert_eq!(
+			supportable,
+			MultiWalletSupportableAnchorChannels {
+				num_channels: 0,
+				num_channels_per_wallet: vec![0, 0],
+			}
+		);
+		assert_eq!(supportable.next_channel_wallet(0), None);
+	}
+
+	#[test]
+	fn test_can_support_channel_batch_with_num_anchor_channels() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 10),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 3 / 2),
+		];
+		// All UTXOs remain available, so 3 channels can be supported in total.
+		assert_eq!(
+			can_support_channel_batch_with_num_anchor_channels(&context, &utxos, &[], 1, 2),
+			Vec::<usize>::new()
+		);
+		assert_eq!(
+			can_support_channel_batch_with_num_anchor_channels(&context, &utxos, &[], 1, 3),
+			vec![2]
+		);
+
+		// Spending the large UTXO in the funding transaction leaves 2 reserve UTXOs.
+		let spent_outpoints = [utxos[0].outpoint];
+		assert_eq!(
+			can_support_channel_batch_with_num_anchor_channels(
+				&context,
+				&utxos,
+				&spent_outpoints,
+				1,
+				3
+			),
+			vec![1, 2]
+		);
+	}
+
+	#[test]
+	fn test_can_support_channel_batch_with_funding_change() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let spent_utxo = make_p2wpkh_utxo_at(0, reserve_per_channel * 100);
+		let spent_outpoints = [spent_utxo.outpoint];
+		// Without the change output of the funding transaction, no channels can be supported.
+		let utxos = vec![spent_utxo.clone()];
+		assert_eq!(
+			can_support_channel_batch_with_num_anchor_channels(
+				&context,
+				&utxos,
+				&spent_outpoints,
+				0,
+				2
+			),
+			vec![0, 1]
+		);
+
+		// The change output of the funding transaction becomes part of the reserve.
+		let change_utxo = Utxo {
+			outpoint: OutPoint {
+				txid: Txid::from_str(
+					"f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+				)
+				.unwrap(),
+				vout: 2,
+			},
+			..make_p2wpkh_utxo(reserve_per_channel * 2)
+		};
+		let utxos = vec![spent_utxo, change_utxo];
+		assert_eq!(
+			can_support_channel_batch_with_num_anchor_channels(
+				&context,
+				&utxos,
+				&spent_outpoints,
+				0,
+				2
+			),
+			vec![1]
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_anchor_channel_reserve_check_counts_anchor_channels() {
+		// The anchor channels counted from the `ChannelManager` match those provided explicitly.
+		use crate::ln::functional_test_utils::*;
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut anchors_cfg = test_default_channel_config();
+		anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		anchors_cfg.manually_accept_inbound_channels = true;
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![make_p2wpkh_utxo(reserve_per_channel)];
+		let reserve_check =
+			AnchorChannelReserveCheck::new(&context, &utxos, nodes[0].node, chain_monitor);
+		assert_eq!(
+			reserve_check,
+			AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 1)
+		);
+		assert!(!reserve_check.can_support_additional_anchor_channel());
+		assert!(!can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			nodes[0].node,
+			chain_monitor
+		));
+
+		let utxos = vec![
+			make_p2wpkh_utxo(reserve_per_channel),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+		];
+		assert!(can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			nodes[0].node,
+			chain_monitor
+		));
+		assert_eq!(
+			can_support_channel_batch(&context, &utxos, &[], 2, nodes[0].node, chain_monitor),
+			can_support_channel_batch_with_num_anchor_channels(&context, &utxos, &[], 1, 2),
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_anchor_channel_reserve_check_counts_unconfirmed_channels() {
+		// Anchor channels opened back-to-back are counted exactly once in every state before their
+		// funding transaction confirms, such that the reserve isn't over-committed.
+		use crate::events::Event;
+		use crate::ln::channelmanager::OpenChannelBlocker;
+		use crate::ln::functional_test_utils::*;
+		use crate::ln::msgs::{ChannelMessageHandler, MessageSendEvent};
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut anchors_cfg = test_default_channel_config();
+		anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		anchors_cfg.manually_accept_inbound_channels = true;
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_a_id = nodes[0].node.get_our_node_id();
+		let node_b_id = nodes[1].node.get_our_node_id();
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+
+		let context = AnchorChannelReserveContext::default();
+		let utxos = vec![make_p2wpkh_utxo(get_reserve_per_channel(&context) * 2)];
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 2);
+		let reserve_check =
+			|| AnchorChannelReserveCheck::new(&context, &utxos, nodes[0].node, chain_monitor);
+		let assert_num_anchor_channels = |num_anchor_channels| {
+			assert_eq!(reserve_check().num_anchor_channels(), num_anchor_channels);
+			assert_eq!(
+				nodes[0].node.count_channels_requiring_anchor_reserves(),
+				num_anchor_channels
+			);
+		};
+
+		// The first channel is counted once its funding transaction is broadcast, awaiting
+		// confirmation.
+		assert!(reserve_check().can_support_additional_anchor_channel());
+		create_chan_between_nodes_with_value_init(&nodes[0], &nodes[1], 100_000, 0);
+		assert_num_anchor_channels(1);
+		*nodes[0].network_chan_count.borrow_mut() += 1;
+
+		// The second channel is counted while awaiting the counterparty's acceptance, and after
+		// generating its funding transaction, awaiting `funding_signed`.
+		assert!(reserve_check().can_support_additional_anchor_channel());
+		nodes[0].node.create_channel(node_b_id, 100_000, 0, 42, None, None).unwrap();
+		let open_channel = get_event_msg!(nodes[0], MessageSendEvent::SendOpenChannel, node_b_id);
+		assert_num_anchor_channels(2);
+		nodes[1].node.handle_open_channel(node_a_id, &open_channel);
+		let events = nodes[1].node.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		match &events[0] {
+			Event::OpenChannelRequest { temporary_channel_id, .. } => nodes[1]
+				.node
+				.accept_inbound_channel(temporary_channel_id, &node_a_id, 42, None)
+				.unwrap(),
+			_ => panic!("Unexpected event"),
+		}
+		let accept_channel =
+			get_event_msg!(nodes[1], MessageSendEvent::SendAcceptChannel, node_a_id);
+		nodes[0].node.handle_accept_channel(node_b_id, &accept_channel);
+		assert_num_anchor_channels(2);
+		let (temporary_channel_id, funding_tx, _) =
+			create_funding_transaction(&nodes[0], &node_b_id, 100_000, 42);
+		nodes[0]
+			.node
+			.funding_transaction_generated(temporary_channel_id, node_b_id, funding_tx)
+			.unwrap();
+		get_event_msg!(nodes[0], MessageSendEvent::SendFundingCreated, node_b_id);
+		assert_num_anchor_channels(2);
+
+		// As the reserve only covers two channels, the third is refused.
+		let reserve_check = reserve_check();
+		assert!(!reserve_check.can_support_additional_anchor_channel());
+		assert_eq!(
+			nodes[0].node.preflight_open_channel(
+				node_b_id,
+				100_000,
+				0,
+				None,
+				None,
+				Some(&reserve_check),
+				None
+			),
+			Err(vec![OpenChannelBlocker::InsufficientAnchorReserve {
+				num_anchor_channels: 2,
+				num_supportable_channels: 2,
+			}])
+		);
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_anchor_channel_reserve_check_excludes_cooperatively_closed_channels() {
+		// Once the closing transaction of a cooperatively closed anchor channel appears on-chain, the
+		// channel no longer relies on the reserve, even before the transaction is final.
+		use crate::chain::channelmonitor::ANTI_REORG_DELAY;
+		use crate::events::{ClosureReason, Event};
+		use crate::ln::functional_test_utils::*;
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut anchors_cfg = test_default_channel_config();
+		anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		anchors_cfg.manually_accept_inbound_channels = true;
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_a_id = nodes[0].node.get_our_node_id();
+		let node_b_id = nodes[1].node.get_our_node_id();
+		let (_, _, channel_id, funding_tx) =
+			create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			make_p2wpkh_utxo(reserve_per_channel),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+		];
+		let has_claimable_balances =
+			|| !chain_monitor.get_monitor(channel_id).unwrap().get_claimable_balances().is_empty();
+		let num_anchor_channels = || {
+			AnchorChannelReserveCheck::new(&context, &utxos, nodes[0].node, chain_monitor)
+				.num_anchor_channels()
+		};
+		assert_eq!(num_anchor_channels(), 2);
+		assert!(!can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			nodes[0].node,
+			chain_monitor
+		));
+
+		// Until the closing transaction appears on-chain, the channel may still have to be
+		// force-closed.
+		let closing_tx = close_channel(&nodes[0], &nodes[1], &channel_id, funding_tx, false).2;
+		let reason = ClosureReason::LocallyInitiatedCooperativeClosure;
+		check_closed_event(&nodes[0], 1, reason, false, &[node_b_id], 1_000_000);
+		let reason = ClosureReason::CounterpartyInitiatedCooperativeClosure;
+		check_closed_event(&nodes[1], 1, reason, false, &[node_a_id], 1_000_000);
+		assert_eq!(num_anchor_channels(), 2);
+
+		mine_transaction(&nodes[0], &closing_tx);
+		assert!(has_claimable_balances());
+		assert_eq!(num_anchor_channels(), 1);
+		assert!(can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			nodes[0].node,
+			chain_monitor
+		));
+
+		connect_blocks(&nodes[0], ANTI_REORG_DELAY - 1);
+		assert!(!has_claimable_balances());
+		assert_eq!(num_anchor_channels(), 1);
+		let events = chain_monitor.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		assert!(matches!(events[0], Event::SpendableOutputs { .. }));
+	}
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_can_support_additional_anchor_channel() {
+		use crate::chain::channelmonitor::{Balance, BalanceSource};
+		use crate::ln::channel_state::PendingChannelNegotiation;
+		use crate::types::payment::PaymentHash;
+		use crate::util::test_utils::{
+			test_nested_p2wpkh_utxo, test_p2pkh_utxo, test_p2tr_utxo, test_p2wpkh_utxo,
+			TestChainMonitorFacade, TestChannelManagerFacade,
+		};
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			test_p2pkh_utxo(0, reserve_per_channel * 2),
+			test_nested_p2wpkh_utxo(1, reserve_per_channel * 2),
+			test_p2wpkh_utxo(2, reserve_per_channel * 2),
+			test_p2tr_utxo(3, reserve_per_channel * 2),
+		];
+		let num_supportable_channels = get_supportable_anchor_channels(&context, &utxos);
+		assert!(num_supportable_channels >= 4);
+
+		let anchors = ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		let node_id = PublicKey::from_slice(&[2; 33]).unwrap();
+		let channel_manager = TestChannelManagerFacade::new(node_id);
+		let mut chain_monitor = TestChainMonitorFacade::new();
+		for i in 0..num_supportable_channels - 1 {
+			let channel_id = ChannelId([i as u8; 32]);
+			chain_monitor =
+				chain_monitor.with_open_channel_monitor(channel_id, anchors.clone(), 100_000);
+		}
+		assert!(can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor
+		));
+
+		// Neither non-anchor channels nor channels which can't require further fees are counted.
+		let resolved_balance = Balance::ClaimableAwaitingConfirmations {
+			amount_satoshis: 100_000,
+			confirmation_height: 42,
+			source: BalanceSource::HolderForceClosed,
+		};
+		let chain_monitor = chain_monitor
+			.with_open_channel_monitor(
+				ChannelId([100; 32]),
+				ChannelTypeFeatures::only_static_remote_key(),
+				100_000,
+			)
+			.with_monitor(ChannelId([101; 32]), anchors.clone(), vec![resolved_balance])
+			.with_monitor(ChannelId([102; 32]), anchors.clone(), Vec::new());
+		assert!(can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor
+		));
+		assert!(!can_support_additional_anchor_channel(
+			&context,
+			&[],
+			&channel_manager,
+			&chain_monitor
+		));
+
+		// Pending negotiations of anchor channels are counted, exhausting the reserve.
+		let channel_manager =
+			channel_manager.with_pending_channel_negotiation(PendingChannelNegotiation {
+				temporary_channel_id: ChannelId([103; 32]),
+				counterparty_node_id: node_id,
+				is_outbound: true,
+				channel_type: anchors.clone(),
+				is_restored: false,
+			});
+		assert!(!can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor
+		));
+
+		// As do unresolved HTLCs of a closed channel.
+		let htlc_balance = Balance::MaybeTimeoutClaimableHTLC {
+			amount_satoshis: 10_000,
+			claimable_height: 42,
+			payment_hash: PaymentHash([1; 32]),
+			outbound_payment: true,
+		};
+		let channel_manager = TestChannelManagerFacade::new(node_id);
+		let chain_monitor =
+			chain_monitor.with_monitor(ChannelId([104; 32]), anchors, vec![htlc_balance]);
+		assert!(!can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor
+		));
+	}
 
-/// Verifies whether the anchor channel reserve provided by `utxos` is sufficient to support
-/// an additional anchor channel.
-///
-/// This should be verified:
-/// - Before opening a new outbound anchor channel with [ChannelManager::create_channel].
-/// - Before accepting a new inbound anchor channel while handling [Event::OpenChannelRequest].
-///
-/// [ChannelManager::create_channel]: crate::ln::channelmanager::ChannelManager::create_channel
-/// [Event::OpenChannelRequest]: crate::events::Event::OpenChannelRequest
-pub fn can_support_additional_anchor_channel<
-	AChannelManagerRef: Deref,
-	ChannelSigner: EcdsaChannelSigner,
-	FilterRef: Deref,
-	BroadcasterRef: Deref,
-	EstimatorRef: Deref,
-	LoggerRef: Deref,
-	PersistRef: Deref,
-	EntropySourceRef: Deref,
-	ChainMonitorRef: Deref<
-		Target = ChainMonitor<
-			ChannelSigner,
-			FilterRef,
-			BroadcasterRef,
-			EstimatorRef,
-			LoggerRef,
-			PersistRef,
-			EntropySourceRef,
-		>,
-	>,
->(
-	context: &AnchorChannelReserveContext, utxos: &[Utxo], a_channel_manager: &AChannelManagerRef,
-	chain_monitor: &ChainMonitorRef,
-) -> bool
-where
-	AChannelManagerRef::Target: AChannelManager,
-	FilterRef::Target: Filter,
-	BroadcasterRef::Target: BroadcasterInterface,
-	EstimatorRef::Target: FeeEstimator,
-	LoggerRef::Target: Logger,
-	PersistRef::Target: Persist<ChannelSigner>,
-	EntropySourceRef::Target: EntropySource,
-{
-	let mut anchor_channels = new_hash_set();
-	// Calculate the number of in-progress anchor channels by inspecting ChannelMonitors with balance.
-	// This includes channels that are in the process of being resolved on-chain.
-	for channel_id in chain_monitor.list_monitors() {
-		let channel_monitor = if let Ok(channel_monitor) = chain_monitor.get_monitor(channel_id) {
-			channel_monitor
-		} else {
-			continue;
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_reserve_metrics() {
+		use crate::chain::channelmonitor::{Balance, BalanceSource};
+		use crate::types::payment::PaymentHash;
+		use crate::util::test_utils::{
+			test_p2wpkh_utxo, TestChainMonitorFacade, TestChannelManagerFacade,
 		};
-		if channel_monitor.channel_type_features().supports_anchors_zero_fee_htlc_tx()
-			&& !channel_monitor.get_claimable_balances().is_empty()
-		{
-			anchor_channels.insert(channel_id);
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let anchors = ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		let node_id = PublicKey::from_slice(&[2; 33]).unwrap();
+		let channel_manager = TestChannelManagerFacade::new(node_id);
+
+		// Two open anchor channels, one being resolved on-chain whose claim was bumped with wallet
+		// funds, one whose resolution no longer requires fees, and a non-anchor channel.
+		let htlc_balance = Balance::MaybeTimeoutClaimableHTLC {
+			amount_satoshis: 10_000,
+			claimable_height: 42,
+			payment_hash: PaymentHash([1; 32]),
+			outbound_payment: true,
+		};
+		let resolved_balance = Balance::ClaimableAwaitingConfirmations {
+			amount_satoshis: 100_000,
+			confirmation_height: 42,
+			source: BalanceSource::HolderForceClosed,
+		};
+		let chain_monitor = TestChainMonitorFacade::new()
+			.with_open_channel_monitor(ChannelId([0; 32]), anchors.clone(), 100_000)
+			.with_open_channel_monitor(ChannelId([1; 32]), anchors.clone(), 100_000)
+			.with_monitor(ChannelId([2; 32]), anchors.clone(), vec![htlc_balance])
+			.with_pending_claim_external_inputs_value(Amount::from_sat(20_000))
+			.with_monitor(ChannelId([3; 32]), anchors, vec![resolved_balance])
+			.with_open_channel_monitor(
+				ChannelId([4; 32]),
+				ChannelTypeFeatures::only_static_remote_key(),
+				100_000,
+			);
+
+		let whole_utxos =
+			(0..5).map(|vout| test_p2wpkh_utxo(vout, reserve_per_channel)).collect::<Vec<_>>();
+		let fractional_utxos =
+			(0..12).map(|vout| test_p2wpkh_utxo(vout, reserve_per_channel / 3)).collect::<Vec<_>>();
+		for utxos in [&[][..], &whole_utxos[..2], &whole_utxos[..], &fractional_utxos[..]] {
+			let metrics =
+				ReserveMetrics::collect(&context, utxos, &channel_manager, &chain_monitor);
+			assert_eq!(metrics.active_anchor_channels, 3);
+			assert_eq!(metrics.channels_in_onchain_resolution, 1);
+			assert_eq!(metrics.pending_claim_external_value_sat, 20_000);
+			assert_eq!(
+				metrics.supportable_channels,
+				get_supportable_anchor_channels(&context, utxos)
+			);
+			assert_eq!(
+				metrics.total_reserve_required_sat,
+				total_reserve_required(&context, 3, false).to_sat()
+			);
+
+			// The metrics are consistent with each other.
+			let sufficient =
+				metrics.total_reserve_available_sat >= metrics.total_reserve_required_sat;
+			assert_eq!(sufficient, metrics.supportable_channels >= metrics.active_anchor_channels);
+			assert!(metrics.channels_in_onchain_resolution <= metrics.active_anchor_channels);
 		}
 	}
-	// Also include channels that are in the middle of negotiation or anchor channels that don't have
-	// a ChannelMonitor yet.
-	for channel in a_channel_manager.get_cm().list_channels() {
-		if channel.channel_type.map_or(true, |ct| ct.supports_anchors_zero_fee_htlc_tx()) {
-			anchor_channels.insert(channel.channel_id);
+
+	#[test]
+	#[cfg(feature = "std")]
+	fn test_can_support_additional_anchor_channel_with_policies() {
+		use crate::ln::channel_state::PendingChannelNegotiation;
+		use crate::util::test_utils::{TestChainMonitorFacade, TestChannelManagerFacade};
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos: Vec<Utxo> =
+			(0..3).map(|vout| make_p2wpkh_utxo_at(vout, reserve_per_channel * 2)).collect();
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 3);
+
+		let anchors = ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies();
+		let untrusted_node_id = PublicKey::from_slice(&[2; 33]).unwrap();
+		let lsp_node_id = PublicKey::from_slice(&[3; 33]).unwrap();
+		let trusted_node_id = PublicKey::from_slice(&[4; 33]).unwrap();
+		let mut channel_manager = TestChannelManagerFacade::new(untrusted_node_id);
+		let counterparties = [untrusted_node_id, untrusted_node_id, lsp_node_id, lsp_node_id];
+		for (i, counterparty_node_id) in counterparties.into_iter().enumerate() {
+			channel_manager =
+				channel_manager.with_pending_channel_negotiation(PendingChannelNegotiation {
+					temporary_channel_id: ChannelId([i as u8; 32]),
+					counterparty_node_id,
+					is_outbound: true,
+					channel_type: anchors.clone(),
+					is_restored: false,
+				});
 		}
+		let chain_monitor = TestChainMonitorFacade::new();
+
+		// Without policies, the 4 existing channels already exceed the reserve.
+		let mut policies = AnchorChannelReservePolicies::default();
+		let check = AnchorChannelReserveCheck::new_with_policies(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor,
+			&policies,
+		);
+		assert_eq!(check.num_anchor_channels(), 4);
+		assert!(!can_support_additional_anchor_channel_with_policies(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor,
+			&policies,
+			&trusted_node_id,
+		));
+
+		// Channels with the LSP consume half of the reserve per channel each, such that the existing
+		// channels consume exactly the reserve of 3 channels.
+		policies.set_counterparty_policy(
+			lsp_node_id,
+			AnchorChannelReservePolicy::Fraction { reserve_ppm: 500_000 },
+		);
+		policies.set_counterparty_policy(trusted_node_id, AnchorChannelReservePolicy::None);
+		let check = AnchorChannelReserveCheck::new_with_policies(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor,
+			&policies,
+		);
+		assert_eq!(check.num_anchor_channels(), 3);
+		assert_eq!(check.num_supportable_channels(), 3);
+		let can_support_with = |policies: &AnchorChannelReservePolicies, node_id| {
+			can_support_additional_anchor_channel_with_policies(
+				&context,
+				&utxos,
+				&channel_manager,
+				&chain_monitor,
+				policies,
+				node_id,
+			)
+		};
+		assert!(!can_support_with(&policies, &untrusted_node_id));
+		assert!(!can_support_with(&policies, &lsp_node_id));
+		assert!(can_support_with(&policies, &trusted_node_id));
+
+		// Once the LSP is fully trusted, its channels no longer consume any reserve.
+		policies.set_counterparty_policy(lsp_node_id, AnchorChannelReservePolicy::None);
+		let check = AnchorChannelReserveCheck::new_with_policies(
+			&context,
+			&utxos,
+			&channel_manager,
+			&chain_monitor,
+			&policies,
+		);
+		assert_eq!(check.num_anchor_channels(), 2);
+		assert!(can_support_with(&policies, &untrusted_node_id));
+
+		// Removing a policy falls back to the default policy.
+		assert_eq!(
+			policies.remove_counterparty_policy(&lsp_node_id),
+			Some(AnchorChannelReservePolicy::None)
+		);
+		assert_eq!(policies.policy_for(&lsp_node_id), AnchorChannelReservePolicy::Full);
+		assert!(!can_support_with(&policies, &untrusted_node_id));
+		let policies = AnchorChannelReservePolicies::with_default_policy(
+			AnchorChannelReservePolicy::Fraction { reserve_ppm: 2_000_000 },
+		);
+		assert_eq!(
+			AnchorChannelReserveCheck::new_with_policies(
+				&context,
+				&utxos,
+				&channel_manager,
+				&chain_monitor,
+				&policies,
+			)
+			.num_anchor_channels(),
+			4
+		);
 	}
-	get_supportable_anchor_channels(context, utxos) > anchor_channels.len() as u64
-}
 
-#[cfg(test)]
-mod test {
-	use super::*;
-	use bitcoin::{OutPoint, ScriptBuf, TxOut, Txid};
-	use std::str::FromStr;
+	#[test]
+	fn test_anchor_channel_reserve_policies_serialization() {
+		let mut policies = AnchorChannelReservePolicies::with_default_policy(
+			AnchorChannelReservePolicy::Fraction { reserve_ppm: 750_000 },
+		);
+		policies.set_counterparty_policy(
+			PublicKey::from_slice(&[2; 33]).unwrap(),
+			AnchorChannelReservePolicy::None,
+		);
+		policies.set_counterparty_policy(
+			PublicKey::from_slice(&[3; 33]).unwrap(),
+			AnchorChannelReservePolicy::Full,
+		);
+		let encoded = policies.encode();
+		let decoded: AnchorChannelReservePolicies = Readable::read(&mut &encoded[..]).unwrap();
+		assert_eq!(decoded, policies);
+	}
 
 	#[test]
-	fn test_get_reserve_per_channel() {
-		// At a 1000 sats/kw, with 4 expected transactions at ~1kw (commitment transaction, anchor
-		// output spend transaction, 2 HTLC transactions), we expect the reserve to be around 4k sats.
+	#[cfg(feature = "std")]
+	fn test_anchor_channel_reserve_check_excludes_swept_channels() {
+		// Once our commitment transaction of a force-closed anchor channel without HTLCs confirms,
+		// the remaining balance is only awaiting confirmations and can't require any further fees,
+		// even though the monitor still reports claimable balances. The reserve is released once the
+		// commitment transaction reaches ANTI_REORG_DELAY confirmations.
+		use crate::chain::channelmonitor::{Balance, ANTI_REORG_DELAY};
+		use crate::events::bump_transaction::BumpTransactionEvent;
+		use crate::events::{ClosureReason, Event};
+		use crate::ln::functional_test_utils::*;
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut anchors_cfg = test_default_channel_config();
+		anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		anchors_cfg.manually_accept_inbound_channels = true;
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+		let channel_id =
+			create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0).2;
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 0);
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			make_p2wpkh_utxo(reserve_per_channel),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+		];
+		let num_anchor_channels = || {
+			AnchorChannelReserveCheck::new(&context, &utxos, nodes[0].node, chain_monitor)
+				.num_anchor_channels()
+		};
+		assert_eq!(num_anchor_channels(), 2);
+
+		let message = "Channel force-closed".to_owned();
+		nodes[0]
+			.node
+			.force_close_broadcasting_latest_txn(&channel_id, &node_b_id, message.clone())
+			.unwrap();
+		check_added_monitors(&nodes[0], 1);
+		check_closed_broadcast(&nodes[0], 1, true);
+		let reason =
+			ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+		check_closed_event(&nodes[0], 1, reason, false, &[node_b_id], 1_000_000);
+		let mut events = chain_monitor.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		let commitment_tx = match events.pop().unwrap() {
+			Event::BumpTransaction(BumpTransactionEvent::ChannelClose {
+				commitment_tx, ..
+			}) => commitment_tx,
+			_ => panic!("Unexpected event"),
+		};
+
+		// Until the commitment transaction confirms, it may have to be bumped via its anchor.
+		assert!(chain_monitor.get_monitor(channel_id).unwrap().needs_exogenous_fee_reserve());
+		assert_eq!(num_anchor_channels(), 2);
+
+		assert_eq!(chain_monitor.get_monitor(channel_id).unwrap().reserve_release_height(), None);
+		assert!(get_pending_reserve_releases(chain_monitor).is_empty());
+
+		// Once it confirms, the commitment transaction no longer has to be bumped, but the reserve is
+		// only released once it can't be reorged anymore.
+		mine_transaction(&nodes[0], &commitment_tx);
+		let confirmation_height = nodes[0].best_block_info().1;
+		let release_height = confirmation_height + ANTI_REORG_DELAY - 1;
+		let monitor = chain_monitor.get_monitor(channel_id).unwrap();
+		let balances = monitor.get_claimable_balances();
+		assert!(!balances.is_empty());
+		assert!(balances
+			.iter()
+			.all(|balance| matches!(balance, Balance::ClaimableAwaitingConfirmations { .. })));
+		assert_eq!(monitor.reserve_release_height(), Some(release_height));
+		assert!(monitor.needs_exogenous_fee_reserve());
+		drop(monitor);
 		assert_eq!(
-			get_reserve_per_channel(&AnchorChannelReserveContext {
-				upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
-				expected_accepted_htlcs: 1,
-				taproot_wallet: false,
-			}),
-			Amount::from_sat(4349)
+			get_pending_reserve_releases(chain_monitor),
+			vec![PendingReserveRelease { channel_id, release_height }]
 		);
-	}
+		assert_eq!(num_anchor_channels(), 2);
+		assert!(!can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			nodes[0].node,
+			chain_monitor
+		));
 
-	fn make_p2wpkh_utxo(amount: Amount) -> Utxo {
-		Utxo {
-			outpoint: OutPoint {
-				txid: Txid::from_str(
-					"4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b",
-				)
-				.unwrap(),
-				vout: 0,
-			},
-			output: TxOut { value: amount, script_pubkey: ScriptBuf::new() },
-			satisfaction_weight: 1 * 4 + (1 + 1 + 72 + 1 + 33),
+		// If the commitment transaction is reorged out of the chain, the release height is unknown
+		// again until it confirms anew.
+		disconnect_blocks(&nodes[0], 1);
+		let monitor = chain_monitor.get_monitor(channel_id).unwrap();
+		assert_eq!(monitor.reserve_release_height(), None);
+		assert!(monitor.needs_exogenous_fee_reserve());
+		drop(monitor);
+		assert!(get_pending_reserve_releases(chain_monitor).is_empty());
+		assert_eq!(num_anchor_channels(), 2);
+
+		mine_transaction(&nodes[0], &commitment_tx);
+		chain_monitor.get_and_clear_pending_events();
+		assert_eq!(nodes[0].best_block_info().1, confirmation_height);
+
+		// The release height counts down as blocks connect, until the reserve is released.
+		for _ in 0..ANTI_REORG_DELAY - 2 {
+			connect_blocks(&nodes[0], 1);
+			let monitor = chain_monitor.get_monitor(channel_id).unwrap();
+			assert_eq!(monitor.reserve_release_height(), Some(release_height));
+			assert!(monitor.needs_exogenous_fee_reserve());
+			drop(monitor);
+			assert!(nodes[0].best_block_info().1 < release_height);
+			assert_eq!(num_anchor_channels(), 2);
 		}
+
+		connect_blocks(&nodes[0], 1);
+		assert_eq!(nodes[0].best_block_info().1, release_height);
+		let monitor = chain_monitor.get_monitor(channel_id).unwrap();
+		assert_eq!(monitor.reserve_release_height(), Some(release_height));
+		assert!(!monitor.needs_exogenous_fee_reserve());
+		drop(monitor);
+		assert!(get_pending_reserve_releases(chain_monitor).is_empty());
+		assert_eq!(num_anchor_channels(), 1);
+		assert!(can_support_additional_anchor_channel(
+			&context,
+			&utxos,
+			nodes[0].node,
+			chain_monitor
+		));
 	}
 
 	#[test]
-	fn test_get_supportable_anchor_channels() {
+	fn test_get_urgent_funding_requirement() {
+		// Once our commitment transaction confirms, the fees of the HTLC transactions required to
+		// claim its HTLCs are reported along with the earliest deadline and the value at risk.
+		use crate::events::bump_transaction::BumpTransactionEvent;
+		use crate::events::{ClosureReason, Event};
+		use crate::ln::functional_test_utils::*;
+		use crate::{expect_payment_claimed, get_monitor};
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let mut anchors_cfg = test_default_channel_config();
+		anchors_cfg.channel_handshake_config.negotiate_anchors_zero_fee_htlc_tx = true;
+		anchors_cfg.manually_accept_inbound_channels = true;
+		let node_chanmgrs =
+			create_node_chanmgrs(2, &node_cfgs, &[Some(anchors_cfg.clone()), Some(anchors_cfg)]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+		let chan_id =
+			create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 500_000_000).2;
+
+		// An inbound HTLC we'll claim with its preimage and an outbound HTLC expiring later.
+		let (preimage, payment_hash, ..) = route_payment(&nodes[1], &[&nodes[0]], 1_000_000);
+		connect_blocks(&nodes[0], 10);
+		connect_blocks(&nodes[1], 10);
+		route_payment(&nodes[0], &[&nodes[1]], 2_000_000);
+		let channel = nodes[0].node.list_channels().pop().unwrap();
+		let inbound_expiry = channel.pending_inbound_htlcs[0].cltv_expiry;
+		let outbound_expiry = channel.pending_outbound_htlcs[0].cltv_expiry;
+		assert_eq!(outbound_expiry, inbound_expiry + 10);
+
+		let fee_rate = FeeRate::from_sat_per_kwu(5000);
+		let requirement = || {
+			let monitor = get_monitor!(nodes[0], chan_id);
+			let current_height = nodes[0].best_block_info().1;
+			get_urgent_funding_requirement(&*monitor, current_height, fee_rate, false)
+		};
+		assert!(requirement().is_none());
+
+		let message = "Channel force-closed".to_owned();
+		nodes[0]
+			.node
+			.force_close_broadcasting_latest_txn(&chan_id, &node_b_id, message.clone())
+			.unwrap();
+		check_added_monitors(&nodes[0], 1);
+		check_closed_broadcast(&nodes[0], 1, true);
+		let reason =
+			ClosureReason::HolderForceClosed { broadcasted_latest_txn: Some(true), message };
+		check_closed_event(&nodes[0], 1, reason, false, &[node_b_id], 1_000_000);
+		let mut events = nodes[0].chain_monitor.chain_monitor.get_and_clear_pending_events();
+		assert_eq!(events.len(), 1);
+		let commitment_tx = match events.pop().unwrap() {
+			Event::BumpTransaction(BumpTransactionEvent::ChannelClose {
+				commitment_tx, ..
+			}) => commitment_tx,
+			_ => panic!("Unexpected event"),
+		};
+		nodes[0].node.claim_funds(preimage);
+		check_added_monitors(&nodes[0], 1);
+		expect_payment_claimed!(nodes[0], payment_hash, 1_000_000);
+
+		// Nothing is required until our commitment transaction confirms.
+		assert!(requirement().is_none());
+
+		mine_transaction(&nodes[0], &commitment_tx);
+		let context =
+			AnchorChannelReserveContext { upper_bound_fee_rate: fee_rate, ..Default::default() };
+		let success_fee = fee_rate
+			.fee_wu(htlc_success_transaction_weight(&context, WalletInputType::P2wpkh))
+			.unwrap();
+		let timeout_fee = fee_rate
+			.fee_wu(htlc_timeout_transaction_weight(&context, WalletInputType::P2wpkh))
+			.unwrap();
+		let current_height = nodes[0].best_block_info().1;
+		assert_eq!(
+			requirement(),
+			Some(UrgentFundingRequirement {
+				required_funds: success_fee + timeout_fee,
+				deadline_height: inbound_expiry,
+				blocks_until_deadline: inbound_expiry - current_height,
+				amount_at_risk: Amount::from_sat(3_000),
+			})
+		);
+	}
+
+	#[test]
+	fn test_reserve_audit_log() {
 		let context = AnchorChannelReserveContext::default();
 		let reserve_per_channel = get_reserve_per_channel(&context);
-		// Only 3 disjoint sets with a value greater than the required reserve can be created.
 		let utxos = vec![
-			make_p2wpkh_utxo(reserve_per_channel * 3 / 2),
 			make_p2wpkh_utxo(reserve_per_channel),
-			make_p2wpkh_utxo(reserve_per_channel * 99 / 100),
-			make_p2wpkh_utxo(reserve_per_channel * 99 / 100),
-			make_p2wpkh_utxo(reserve_per_channel * 20 / 100),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
 		];
-		assert_eq!(get_supportable_anchor_channels(&context, utxos.as_slice()), 3);
+		let audit_log = ReserveAuditLog::new(2);
+
+		let reserve_check =
+			AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 1);
+		assert!(reserve_check.audit_additional_anchor_channel(
+			&context,
+			&utxos,
+			ChannelId([1; 32]),
+			&audit_log
+		));
+		let reserve_check =
+			AnchorChannelReserveCheck::with_num_anchor_channels(&context, &utxos, 2);
+		assert!(!reserve_check.audit_additional_anchor_channel(
+			&context,
+			&utxos,
+			ChannelId([2; 32]),
+			&audit_log
+		));
+
+		let entries = audit_log.entries();
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].channel_id, ChannelId([1; 32]));
+		assert_eq!(
+			entries[0].decision,
+			ReserveAuditDecision::ChannelAllowed {
+				num_anchor_channels: 1,
+				num_supportable_channels: 2
+			}
+		);
+		assert_eq!(entries[0].reserve_per_channel, Some(reserve_per_channel));
+		assert_eq!(
+			entries[0].utxos_considered,
+			utxos.iter().map(|utxo| utxo.outpoint).collect::<Vec<_>>()
+		);
+		assert_eq!(entries[0].utxos_value, reserve_per_channel * 2);
+		assert_eq!(entries[0].fees_paid, Amount::ZERO);
+		assert_eq!(entries[0].timestamp.is_some(), cfg!(feature = "std"));
+		assert_eq!(entries[1].channel_id, ChannelId([2; 32]));
+		assert_eq!(
+			entries[1].decision,
+			ReserveAuditDecision::ChannelDenied {
+				num_anchor_channels: 2,
+				num_supportable_channels: 2
+			}
+		);
+
+		// Once full, the oldest entry is dropped.
+		let mut entry = entries[1].clone();
+		entry.channel_id = ChannelId([3; 32]);
+		audit_log.log_entry(entry);
+		let entries = audit_log.entries();
+		assert_eq!(entries.len(), 2);
+		assert_eq!(entries[0].channel_id, ChannelId([2; 32]));
+		assert_eq!(entries[1].channel_id, ChannelId([3; 32]));
+
+		let serialized = audit_log.encode();
+		let read_audit_log = ReserveAuditLog::read(&mut &serialized[..]).unwrap();
+		assert_eq!(read_audit_log.entries(), entries);
+		assert_eq!(read_audit_log.max_entries, 2);
 	}
 
 	#[test]
-	fn test_anchor_output_spend_transaction_weight() {
-		// Example with smaller signatures:
-		// https://mempool.space/tx/188b0f9f26999a48611dba4e2a88507251eba31f3695d005023de3514cba34bd
-		// DER-encoded ECDSA signatures vary in size and can be 71-73 bytes.
+	fn test_get_reserve_per_channel_for_capacity() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_for_htlcs = |expected_accepted_htlcs| {
+			get_reserve_per_channel(&AnchorChannelReserveContext {
+				expected_accepted_htlcs,
+				..context.clone()
+			})
+		};
+
+		// HTLCs of 10_000 sats on average are expected in proportion to the capacity.
+		let reserve = get_reserve_per_channel_for_capacity(&context, 100_000, 10_000_000, 50);
+		assert_eq!(reserve, reserve_for_htlcs(10));
+		let spliced_in_reserve =
+			get_reserve_per_channel_for_capacity(&context, 200_000, 10_000_000, 50);
+		assert_eq!(spliced_in_reserve, reserve_for_htlcs(20));
+		assert!(spliced_in_reserve > reserve);
+
+		// The expected HTLCs are capped at the maximum the channel accepts.
 		assert_eq!(
-			anchor_output_spend_transaction_weight(
-				&AnchorChannelReserveContext { taproot_wallet: false, ..Default::default() },
-				Weight::from_wu(P2WPKH_INPUT_WEIGHT),
-			),
-			717
+			get_reserve_per_channel_for_capacity(&context, 10_000_000, 10_000_000, 50),
+			reserve_for_htlcs(50)
+		);
+		assert_eq!(
+			get_reserve_per_channel_for_capacity(&context, 100_000, 0, 50),
+			reserve_for_htlcs(50)
 		);
+	}
 
-		// Example:
-		// https://mempool.space/tx/9c493177e395ec77d9e725e1cfd465c5f06d4a5816dd0274c3a8c2442d854a85
+	fn reserve_with_htlcs(context: &AnchorChannelReserveContext, num_htlcs: u16) -> Amount {
+		get_reserve_per_channel_with_input(
+			&AnchorChannelReserveContext { expected_accepted_htlcs: num_htlcs, ..context.clone() },
+			WalletInputType::from_context(context),
+			Weight::ZERO,
+		)
+	}
+
+	fn make_p2wpkh_utxo_with_net_amount(
+		context: &AnchorChannelReserveContext, amount: Amount,
+	) -> Utxo {
+		let satisfaction_weight = make_p2wpkh_utxo(amount).satisfaction_weight;
+		let satisfaction_fee = context.upper_bound_fee_rate.fee_wu(satisfaction_weight).unwrap();
+		make_p2wpkh_utxo(amount + satisfaction_fee)
+	}
+
+	#[test]
+	fn test_stress_test_reserves() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel),
+			make_p2wpkh_utxo_at(2, reserve_per_channel * 60 / 100),
+			make_p2wpkh_utxo_at(3, reserve_per_channel * 60 / 100),
+		];
+		let supportable_at = |sat_per_kwu: u64| {
+			let context = AnchorChannelReserveContext {
+				upper_bound_fee_rate: FeeRate::from_sat_per_kwu(sat_per_kwu),
+				..context.clone()
+			};
+			get_supportable_anchor_channels(&context, &utxos)
+		};
+
+		let start = FeeRate::from_sat_per_kwu(1_000);
+		let end = FeeRate::from_sat_per_kwu(30_000);
+		let step = FeeRate::from_sat_per_kwu(2_500);
+		for num_channels in 1..=3 {
+			let stress_test =
+				stress_test_reserves(&context, &utxos, num_channels, start..=end, step);
+
+			// The table covers the range in steps, including its start and, if reached, its end.
+			assert_eq!(stress_test.supportable_channels.len(), 12);
+			for (idx, (fee_rate, supportable)) in
+				stress_test.supportable_channels.iter().enumerate()
+			{
+				let sat_per_kwu = 1_000 + 2_500 * idx as u64;
+				assert_eq!(*fee_rate, FeeRate::from_sat_per_kwu(sat_per_kwu));
+				assert_eq!(*supportable, supportable_at(sat_per_kwu));
+			}
+
+			// The breakeven matches a manual binary search over fee rates, below which the channels
+			// are supported.
+			let (mut low, mut high) = (0, 1_000_000);
+			assert!(supportable_at(high) < num_channels);
+			while high - low > 1 {
+				let mid = (low + high) / 2;
+				if supportable_at(mid) < num_channels {
+					high = mid;
+				} else {
+					low = mid;
+				}
+			}
+			let breakeven = stress_test.breakeven_fee_rate.unwrap();
+			assert_eq!(breakeven, FeeRate::from_sat_per_kwu(high));
+			assert!(supportable_at(high) < num_channels);
+			assert!(supportable_at(high - 1) >= num_channels);
+		}
+		// With more channels than ever supported, the breakeven is zero.
+		let stress_test = stress_test_reserves(&context, &utxos, 5, start..=end, step);
+		assert_eq!(stress_test.breakeven_fee_rate, Some(FeeRate::ZERO));
+
+		// Without UTXOs, no channels are supported at any fee rate.
+		let stress_test = stress_test_reserves(&context, &[], 1, start..=end, step);
+		assert!(stress_test.supportable_channels.iter().all(|(_, supportable)| *supportable == 0));
+		assert_eq!(stress_test.breakeven_fee_rate, Some(FeeRate::ZERO));
+
+		// Without channels, there is no breakeven.
+		let stress_test = stress_test_reserves(&context, &utxos, 0, start..=end, step);
+		assert_eq!(stress_test.breakeven_fee_rate, None);
+
+		// A zero step, an empty range and a range ending at the maximum fee rate terminate.
+		let zero_step = stress_test_reserves(&context, &utxos, 1, start..=end, FeeRate::ZERO);
+		assert_eq!(zero_step.supportable_channels, vec![(start, supportable_at(1_000))]);
+		let empty = stress_test_reserves(&context, &utxos, 1, end..=start, step);
+		assert!(empty.supportable_channels.is_empty());
+		let max = stress_test_reserves(&context, &utxos, 1, FeeRate::MAX..=FeeRate::MAX, step);
+		assert_eq!(max.supportable_channels, vec![(FeeRate::MAX, 0)]);
+	}
+
+	fn make_anchor_channel(
+		id: u8, outbound_capacity_msat: u64, is_usable: bool, num_pending_htlcs: usize,
+	) -> ChannelDetails {
+		let mut channel = crate::routing::router::bench_utils::first_hop(
+			PublicKey::from_slice(&[2; 33]).unwrap(),
+		);
+		channel.channel_id = ChannelId::from_bytes([id; 32]);
+		channel.channel_type = Some(ChannelTypeFeatures::anchors_zero_htlc_fee_and_dependencies());
+		channel.outbound_capacity_msat = outbound_capacity_msat;
+		channel.is_usable = is_usable;
+		channel.pending_inbound_htlcs = (0..num_pending_htlcs as u64)
+			.map(|htlc_id| InboundHTLCDetails {
+				htlc_id,
+				amount_msat: 1_000_000,
+				cltv_expiry: 500,
+				payment_hash: PaymentHash([0; 32]),
+				state: None,
+				is_dust: false,
+			})
+			.collect();
+		channel
+	}
+
+	#[test]
+	fn test_suggest_channels_to_close() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve_per_channel = get_reserve_per_channel(&context);
+		let utxos = vec![
+			make_p2wpkh_utxo_at(0, reserve_per_channel * 3 / 2),
+			make_p2wpkh_utxo_at(1, reserve_per_channel * 3 / 2),
+		];
+		assert_eq!(get_supportable_anchor_channels(&context, &utxos), 2);
+
+		let busy_channel = make_anchor_channel(1, 1_000_000, true, 2);
+		let rich_channel = make_anchor_channel(2, 900_000_000, true, 0);
+		let poor_channel = make_anchor_channel(3, 1_000, true, 0);
+		let inactive_channel = make_anchor_channel(4, 800_000_000, false, 0);
+		let mut non_anchor_channel = make_anchor_channel(5, 0, false, 0);
+		non_anchor_channel.channel_type = Some(ChannelTypeFeatures::only_static_remote_key());
+		let mut closing_channel = make_anchor_channel(6, 0, false, 0);
+		closing_channel.channel_shutdown_state = Some(ChannelShutdownState::ShutdownInitiated);
+
+		// The reserve supports two anchor channels, so nothing needs to be closed.
+		let channels = vec![busy_channel.clone(), rich_channel.clone(), non_anchor_channel.clone()];
+		assert!(suggest_channels_to_close(&context, &utxos, &channels).is_empty());
+		assert!(suggest_channels_to_close(&context, &utxos, &[]).is_empty());
+
+		// With one anchor channel too many, only the least valuable one without pending HTLCs is
+		// suggested, ignoring channels without anchors and channels already shutting down.
+		let channels = vec![
+			busy_channel.clone(),
+			rich_channel.clone(),
+			poor_channel.clone(),
+			non_anchor_channel.clone(),
+			closing_channel.clone(),
+		];
 		assert_eq!(
-			anchor_output_spend_transaction_weight(
-				&AnchorChannelReserveContext { taproot_wallet: true, ..Default::default() },
-				Weight::from_wu(P2TR_KEYPATH_INPUT_WEIGHT),
-			),
-			723
+			suggest_channels_to_close(&context, &utxos, &channels),
+			vec![poor_channel.channel_id]
+		);
+
+		// Inactive channels are preferred over channels with a lower balance.
+		let channels = vec![
+			busy_channel.clone(),
+			rich_channel.clone(),
+			poor_channel.clone(),
+			inactive_channel.clone(),
+		];
+		assert_eq!(
+			suggest_channels_to_close(&context, &utxos, &channels),
+			vec![inactive_channel.channel_id, poor_channel.channel_id]
+		);
+
+		// Channels with pending HTLCs are only suggested once all others are.
+		assert_eq!(
+			suggest_channels_to_close(&context, &[], &channels),
+			vec![
+				inactive_channel.channel_id,
+				poor_channel.channel_id,
+				rich_channel.channel_id,
+				busy_channel.channel_id,
+			]
 		);
+
+		// Closing the suggested channels, and no fewer, leaves a supportable set of channels.
+		for num_utxos in 0..=utxos.len() {
+			let utxos = &utxos[..num_utxos];
+			let suggested = suggest_channels_to_close(&context, utxos, &channels);
+			let remaining =
+				channels.iter().filter(|channel| !suggested.contains(&channel.channel_id)).count()
+					as u64;
+			let num_supportable = get_supportable_anchor_channels(&context, utxos);
+			assert_eq!(remaining, min(num_supportable, channels.len() as u64));
+		}
+	}
+
+	#[cfg(feature = "serde")]
+	fn assert_json_snapshot<T>(value: &T, expected: serde_json::Value)
+	where
+		T: serde::Serialize + serde::de::DeserializeOwned + PartialEq + core::fmt::Debug,
+	{
+		assert_eq!(serde_json::to_value(value).unwrap(), expected);
+		assert_eq!(&serde_json::from_value::<T>(expected).unwrap(), value);
 	}
 
 	#[test]
-	fn test_htlc_success_transaction_weight() {
+	#[cfg(feature = "serde")]
+	fn test_serde_snapshots() {
+		// The JSON representations are a compatibility surface, so any change to these snapshots,
+		// e.g. renaming a field, needs to be deliberate.
+		use serde_json::json;
+
+		let context = AnchorChannelReserveContext {
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(12_500),
+			expected_accepted_htlcs: 10,
+			taproot_wallet: true,
+			reserve_policy: ReservePolicy::ReceiveOnly,
+			channel_type: AnchorChannelType::ZeroFeeCommitments,
+			safety_margin_ppm: 1_500_000,
+			min_economical_htlc_value_sat: Some(1_000),
+			max_htlc_value_in_flight_msat: None,
+			include_outbound_htlcs: true,
+			expected_offered_htlcs: 5,
+			min_confirmations: 6,
+			exclude_immature_coinbase: false,
+			commitment_weight_params: Some(CommitmentWeightParams {
+				num_anchor_outputs: 1,
+				to_local_output_weight: Weight::from_wu(172),
+				to_remote_output_weight: Weight::from_wu(124),
+			}),
+			include_justice_path: true,
+		};
+		assert_json_snapshot(
+			&context,
+			json!({
+				"upper_bound_fee_rate_sat_per_kwu": 12_500,
+				"expected_accepted_htlcs": 10,
+				"taproot_wallet": true,
+				"reserve_policy": "receive_only",
+				"channel_type": "zero_fee_commitments",
+				"safety_margin_ppm": 1_500_000,
+				"min_economical_htlc_value_sat": 1_000,
+				"max_htlc_value_in_flight_msat": null,
+				"include_outbound_htlcs": true,
+				"expected_offered_htlcs": 5,
+				"min_confirmations": 6,
+				"exclude_immature_coinbase": false,
+				"commitment_weight_params": {
+					"num_anchor_outputs": 1,
+					"to_local_output_weight_wu": 172,
+					"to_remote_output_weight_wu": 124,
+				},
+				"include_justice_path": true,
+			}),
+		);
+		// Missing fields take their default value.
+		let partial: AnchorChannelReserveContext =
+			serde_json::from_value(json!({ "expected_accepted_htlcs": 20 })).unwrap();
 		assert_eq!(
-			htlc_success_transaction_weight(&AnchorChannelReserveContext {
-				taproot_wallet: false,
-				..Default::default()
+			partial,
+			AnchorChannelReserveContext { expected_accepted_htlcs: 20, ..Default::default() }
+		);
+
+		let txid =
+			Txid::from_str("c1b9f40a0d8fd20e2e4d8d5d3b6f0a5c5c4d6e1f5e5c2e7a2b5e8d3c4f1a2b3c")
+				.unwrap();
+		let outpoint = OutPoint { txid, vout: 1 };
+		let utxo = Utxo {
+			outpoint,
+			output: TxOut {
+				value: Amount::from_sat(50_000),
+				script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+			},
+			satisfaction_weight: Weight::from_wu(272),
+		};
+		assert_json_snapshot(
+			&utxo,
+			json!({
+				"outpoint": format!("{}:1", txid),
+				"output": {
+					"value": 50_000,
+					"script_pubkey": "00140000000000000000000000000000000000000000",
+				},
+				"satisfaction_weight_wu": 272,
 			}),
-			1102
 		);
 
-		assert_eq!(
-			htlc_success_transaction_weight(&AnchorChannelReserveContext {
-				taproot_wallet: true,
-				..Default::default()
+		let supportable = SupportableAnchorChannels {
+			num_channels: 3,
+			uneconomical_utxos: vec![outpoint],
+			zero_value_utxos: Vec::new(),
+			num_duplicate_utxos: 1,
+			immature_utxos: Vec::new(),
+			immature_value: Amount::from_sat(0),
+		};
+		assert_json_snapshot(
+			&supportable,
+			json!({
+				"num_channels": 3,
+				"uneconomical_utxos": [format!("{}:1", txid)],
+				"zero_value_utxos": [],
+				"num_duplicate_utxos": 1,
+				"immature_utxos": [],
+				"immature_value_sat": 0,
+			}),
+		);
+
+		assert_json_snapshot(&ChannelReserveHealth::Healthy, json!({ "status": "healthy" }));
+		assert_json_snapshot(
+			&ChannelReserveHealth::Degraded { shortfall: Amount::from_sat(1_234) },
+			json!({ "status": "degraded", "shortfall_sat": 1_234 }),
+		);
+		assert_json_snapshot(
+			&ChannelReserveHealth::Critical { unfunded_htlc_value: Amount::from_sat(5_678) },
+			json!({ "status": "critical", "unfunded_htlc_value_sat": 5_678 }),
+		);
+
+		let requirement = UrgentFundingRequirement {
+			required_funds: Amount::from_sat(10_000),
+			deadline_height: 800_000,
+			blocks_until_deadline: 12,
+			amount_at_risk: Amount::from_sat(250_000),
+		};
+		assert_json_snapshot(
+			&requirement,
+			json!({
+				"required_funds_sat": 10_000,
+				"deadline_height": 800_000,
+				"blocks_until_deadline": 12,
+				"amount_at_risk_sat": 250_000,
 			}),
-			1108
 		);
 	}
 
 	#[test]
-	fn test_htlc_timeout_transaction_weight() {
-		// Example with smaller signatures:
-		// https://mempool.space/tx/37185342f9f088bd12376599b245dbc02eb0bb6c4b99568b75a8cd775ddfd1f4
+	fn test_validate_against_config() {
+		let fee_estimator = TestFeeEstimator::new(253);
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.our_max_accepted_htlcs = 50;
+		let context = AnchorChannelReserveContext {
+			expected_accepted_htlcs: 50,
+			upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+			..Default::default()
+		};
+		assert_eq!(validate_against_config(&context, &config, &&fee_estimator, true), Ok(()));
+
+		// Sizing the reserve for fewer HTLCs than we accept undersizes it.
+		let few_htlcs_context =
+			AnchorChannelReserveContext { expected_accepted_htlcs: 10, ..context.clone() };
 		assert_eq!(
-			htlc_timeout_transaction_weight(&AnchorChannelReserveContext {
-				taproot_wallet: false,
-				..Default::default()
-			}),
-			1062
+			validate_against_config(&few_htlcs_context, &config, &&fee_estimator, true),
+			Err(vec![ReserveConfigWarning::ExpectedAcceptedHtlcsBelowMaxAccepted {
+				expected_accepted_htlcs: 10,
+				max_accepted_htlcs: 50,
+			}])
+		);
+		// Unless the number of inbound HTLCs is limited according to the reserve.
+		let mut limited_config = config.clone();
+		limited_config.channel_config.max_inbound_htlcs_for_reserve = Some(10);
+		assert_eq!(
+			validate_against_config(&few_htlcs_context, &limited_config, &&fee_estimator, true),
+			Ok(())
 		);
 
+		// An upper bound below the current maximum fee estimate is flagged.
+		fee_estimator
+			.target_override
+			.lock()
+			.unwrap()
+			.insert(ConfirmationTarget::MaximumFeeEstimate, 1001);
 		assert_eq!(
-			htlc_timeout_transaction_weight(&AnchorChannelReserveContext {
-				taproot_wallet: true,
-				..Default::default()
-			}),
-			1068
+			validate_against_config(&context, &config, &&fee_estimator, true),
+			Err(vec![ReserveConfigWarning::UpperBoundFeeRateBelowMaximumFeeEstimate {
+				upper_bound_fee_rate: FeeRate::from_sat_per_kwu(1000),
+				maximum_fee_estimate: FeeRate::from_sat_per_kwu(1001),
+			}])
+		);
+		fee_estimator.target_override.lock().unwrap().clear();
+
+		// Manually accepting inbound channels bypasses a registered acceptor.
+		let mut manual_config = config.clone();
+		manual_config.manually_accept_inbound_channels = true;
+		assert_eq!(
+			validate_against_config(&context, &manual_config, &&fee_estimator, true),
+			Err(vec![ReserveConfigWarning::InboundChannelAcceptorBypassed])
+		);
+		assert_eq!(
+			validate_against_config(&context, &manual_config, &&fee_estimator, false),
+			Ok(())
+		);
+	}
+
+	#[test]
+	fn test_get_reserve_health_no_pending_htlcs() {
+		let context = AnchorChannelReserveContext::default();
+		let reserve = reserve_with_htlcs(&context, 0);
+		let utxos = vec![make_p2wpkh_utxo_with_net_amount(&context, reserve)];
+		assert_eq!(get_reserve_health(&context, &utxos, &[]), ChannelReserveHealth::Healthy);
+
+		// Without pending HTLCs, no HTLC value is at risk even if the commitment transaction can't
+		// be confirmed.
+		let utxos = vec![make_p2wpkh_utxo_with_net_amount(&context, reserve / 2)];
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &[]),
+			ChannelReserveHealth::Degraded { shortfall: reserve - reserve / 2 }
+		);
+		assert_eq!(
+			get_reserve_health(&context, &[], &[]),
+			ChannelReserveHealth::Degraded { shortfall: reserve }
+		);
+	}
+
+	#[test]
+	fn test_get_reserve_health_some_pending_htlcs() {
+		let context = AnchorChannelReserveContext::default();
+		let pending_htlcs = [1_000_000; 5];
+		let reserve = reserve_with_htlcs(&context, 5);
+		let utxos = vec![make_p2wpkh_utxo_with_net_amount(&context, reserve)];
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &pending_htlcs),
+			ChannelReserveHealth::Healthy
+		);
+
+		// The real number of pending HTLCs is used rather than the expected number, which would
+		// require a larger reserve.
+		assert!(get_reserve_per_channel(&context) > reserve);
+
+		// Enough to confirm the commitment transaction, but not to resolve all HTLCs.
+		let available = reserve_with_htlcs(&context, 2);
+		let utxos = vec![make_p2wpkh_utxo_with_net_amount(&context, available)];
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &pending_htlcs),
+			ChannelReserveHealth::Degraded { shortfall: reserve - available }
+		);
+	}
+
+	#[test]
+	fn test_get_reserve_health_many_pending_htlcs() {
+		let context = AnchorChannelReserveContext::default();
+		let pending_htlcs = [2_000_500; 50];
+		let reserve = reserve_with_htlcs(&context, 50);
+		let utxos = vec![
+			make_p2wpkh_utxo_with_net_amount(&context, reserve / 2),
+			make_p2wpkh_utxo_with_net_amount(&context, reserve - reserve / 2),
+		];
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &pending_htlcs),
+			ChannelReserveHealth::Healthy
+		);
+
+		// A reserve sufficient for the expected number of HTLCs falls short with 50 pending.
+		let available = get_reserve_per_channel(&context);
+		let utxos = vec![make_p2wpkh_utxo_with_net_amount(&context, available)];
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &pending_htlcs),
+			ChannelReserveHealth::Degraded { shortfall: reserve - available }
+		);
+
+		// Not even the commitment transaction can be confirmed, so all HTLCs are at risk.
+		let available = reserve_with_htlcs(&context, 0) / 2;
+		let utxos = vec![make_p2wpkh_utxo_with_net_amount(&context, available)];
+		assert_eq!(
+			get_reserve_health(&context, &utxos, &pending_htlcs),
+			ChannelReserveHealth::Critical { unfunded_htlc_value: Amount::from_sat(100_025) }
 		);
 	}
 }
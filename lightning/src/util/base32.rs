@@ -99,6 +99,22 @@ impl Alphabet {
 		Ok(Self::decode_data(data, alphabet)?)
 	}
 
+	/// Returns the byte offset of the first character of `data` which is not part of the alphabet,
+	/// or `None` if all characters are, in which case [`Self::decode`] can still fail due to the
+	/// length of `data` or its trailing bits.
+	pub(crate) fn find_invalid_char(&self, data: &str) -> Option<usize> {
+		let alphabet = match self {
+			Self::RFC4648 { .. } => RFC4648_INV_ALPHABET,
+			Self::ZBase32 => ZBASE_INV_ALPHABET,
+		};
+		data.bytes().position(|c| {
+			matches!(
+				alphabet.get(c.to_ascii_uppercase().wrapping_sub(b'0') as usize),
+				Some(&-1) | None
+			)
+		})
+	}
+
 	/// Encode a byte slice into a base32 string.
 	fn encode_data(data: &[u8], alphabet: &'static [u8]) -> Vec<u8> {
 		// cap is calculated as follows:
@@ -0,0 +1,117 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Base64 encoding and decoding as specified in RFC 4648, using the standard alphabet with
+//! padding.
+
+#[allow(unused)]
+use crate::prelude::*;
+
+/// RFC4648 encoding table
+const ALPHABET: &'static [u8] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Encodes bytes into a padded base64 string.
+pub fn encode(data: &[u8]) -> String {
+	let mut ret = String::with_capacity((data.len() + 2) / 3 * 4);
+	for chunk in data.chunks(3) {
+		let bytes = [chunk[0], *chunk.get(1).unwrap_or(&0), *chunk.get(2).unwrap_or(&0)];
+		let bits = (bytes[0] as u32) << 16 | (bytes[1] as u32) << 8 | bytes[2] as u32;
+		for i in 0..4 {
+			if i <= chunk.len() {
+				ret.push(ALPHABET[(bits >> (18 - 6 * i)) as usize & 0x3f] as char);
+			} else {
+				ret.push('=');
+			}
+		}
+	}
+	ret
+}
+
+/// Decodes a padded base64 string into a byte vector.
+///
+/// Returns `Err` for invalid characters or padding, as well as for non-canonical encodings, i.e.,
+/// ones with non-zero bits following the last encoded byte.
+pub fn decode(data: &str) -> Result<Vec<u8>, ()> {
+	let data = data.as_bytes();
+	if data.len() % 4 != 0 {
+		return Err(());
+	}
+
+	let num_chunks = data.len() / 4;
+	let mut ret = Vec::with_capacity(num_chunks * 3);
+	for (chunk_idx, chunk) in data.chunks(4).enumerate() {
+		let padding = chunk.iter().rev().take_while(|c| **c == b'=').count();
+		if padding > 2 || (padding > 0 && chunk_idx != num_chunks - 1) {
+			return Err(());
+		}
+
+		let mut bits = 0u32;
+		for c in &chunk[..4 - padding] {
+			let value = match c {
+				b'A'..=b'Z' => c - b'A',
+				b'a'..=b'z' => c - b'a' + 26,
+				b'0'..=b'9' => c - b'0' + 52,
+				b'+' => 62,
+				b'/' => 63,
+				_ => return Err(()),
+			};
+			bits = bits << 6 | value as u32;
+		}
+		bits <<= 6 * padding as u32;
+
+		let bytes = [(bits >> 16) as u8, (bits >> 8) as u8, bits as u8];
+		if bytes[3 - padding..].iter().any(|b| *b != 0) {
+			return Err(());
+		}
+		ret.extend_from_slice(&bytes[..3 - padding]);
+	}
+	Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::{decode, encode};
+
+	const RFC4648_TEST_VECTORS: &[(&[u8], &str)] = &[
+		(b"", ""),
+		(b"f", "Zg=="),
+		(b"fo", "Zm8="),
+		(b"foo", "Zm9v"),
+		(b"foob", "Zm9vYg=="),
+		(b"fooba", "Zm9vYmE="),
+		(b"foobar", "Zm9vYmFy"),
+		(&[0xfb, 0xff, 0xbf], "+/+/"),
+	];
+
+	#[test]
+	fn test_encode() {
+		for (input, encoded) in RFC4648_TEST_VECTORS {
+			assert_eq!(&encode(input), encoded);
+		}
+	}
+
+	#[test]
+	fn test_decode() {
+		for (input, encoded) in RFC4648_TEST_VECTORS {
+			assert_eq!(&decode(encoded).unwrap()[..], &input[..]);
+		}
+	}
+
+	#[test]
+	fn test_decode_errors() {
+		assert!(decode("Zg").is_err()); // Missing padding
+		assert!(decode("Zg=").is_err()); // Invalid length
+		assert!(decode("Z===").is_err()); // Too much padding
+		assert!(decode("Zg==Zm8=").is_err()); // Padding before the last chunk
+		assert!(decode("Z=g=").is_err()); // Invalid char
+		assert!(decode("Zm9-").is_err()); // Invalid char
+		assert!(decode("Zh==").is_err()); // Non-zero trailing bits
+		assert!(decode("Zm9=").is_err()); // Non-zero trailing bits
+	}
+}
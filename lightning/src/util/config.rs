@@ -10,8 +10,15 @@
 //! Various user-configurable channel limits and settings which ChannelManager
 //! applies for you.
 
-use crate::ln::channel::MAX_FUNDING_SATOSHIS_NO_WUMBO;
-use crate::ln::channelmanager::{BREAKDOWN_TIMEOUT, MAX_LOCAL_BREAKDOWN_TIMEOUT};
+use crate::events::LiquidityBand;
+use crate::ln::channel::{MAX_FUNDING_SATOSHIS_NO_WUMBO, MIN_CHAN_DUST_LIMIT_SATOSHIS};
+use crate::ln::channelmanager::{
+	BREAKDOWN_TIMEOUT, MAX_LOCAL_BREAKDOWN_TIMEOUT, MIN_CLTV_EXPIRY_DELTA,
+};
+
+use crate::prelude::*;
+
+use core::fmt;
 
 #[cfg(fuzzing)]
 use crate::util::ser::Readable;
@@ -464,6 +471,76 @@ impl_writeable_tlv_based_enum_legacy!(MaxDustHTLCExposure, ;
 	(3, FeeRateMultiplier),
 );
 
+/// Thresholds on a channel's local balance, as a percentage of the channel's capacity, delimiting
+/// the [`LiquidityBand`]s reported in [`Event::ChannelLiquidityThresholdCrossed`]. See
+/// [`ChannelConfig::liquidity_alert_thresholds`] for details.
+///
+/// [`Event::ChannelLiquidityThresholdCrossed`]: crate::events::Event::ChannelLiquidityThresholdCrossed
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct LiquidityAlertThresholds {
+	/// The percentage of the channel's capacity below which the local balance is in
+	/// [`LiquidityBand::Low`].
+	///
+	/// Default value: `10`
+	pub low_percent: u8,
+	/// The percentage of the channel's capacity above which the local balance is in
+	/// [`LiquidityBand::High`].
+	///
+	/// Default value: `90`
+	pub high_percent: u8,
+	/// The number of percentage points by which the local balance has to move back past a
+	/// threshold before the band it entered is left again.
+	///
+	/// This avoids generating an event on every HTLC while the balance oscillates around a
+	/// threshold. For example, with the default values, a channel whose local balance dropped below
+	/// 10% only re-enters [`LiquidityBand::Normal`] once its local balance reaches 12%.
+	///
+	/// Default value: `2`
+	pub hysteresis_percent: u8,
+}
+
+impl LiquidityAlertThresholds {
+	/// Returns whether the thresholds delimit three non-empty bands, i.e., whether
+	/// `low_percent < high_percent <= 100`.
+	pub fn is_valid(&self) -> bool {
+		self.low_percent < self.high_percent && self.high_percent <= 100
+	}
+
+	/// Returns the band of a local balance of `local_balance_msat` in a channel with a capacity of
+	/// `channel_value_msat`, given the band it was previously in, if any.
+	pub(crate) fn band_for_balance(
+		&self, current_band: Option<LiquidityBand>, local_balance_msat: u64,
+		channel_value_msat: u64,
+	) -> LiquidityBand {
+		// Compare `local_balance_msat / channel_value_msat` against the percentages without
+		// rounding.
+		let balance = local_balance_msat as u128 * 100;
+		let below = |percent: u8| balance < channel_value_msat as u128 * percent as u128;
+		let above = |percent: u8| balance > channel_value_msat as u128 * percent as u128;
+		let low_exit = self.low_percent.saturating_add(self.hysteresis_percent);
+		let high_exit = self.high_percent.saturating_sub(self.hysteresis_percent);
+		match current_band {
+			Some(LiquidityBand::Low) if below(low_exit) => LiquidityBand::Low,
+			Some(LiquidityBand::High) if above(high_exit) => LiquidityBand::High,
+			_ if below(self.low_percent) => LiquidityBand::Low,
+			_ if above(self.high_percent) => LiquidityBand::High,
+			_ => LiquidityBand::Normal,
+		}
+	}
+}
+
+impl Default for LiquidityAlertThresholds {
+	fn default() -> Self {
+		Self { low_percent: 10, high_percent: 90, hysteresis_percent: 2 }
+	}
+}
+
+impl_writeable_tlv_based!(LiquidityAlertThresholds, {
+	(0, low_percent, required),
+	(2, high_percent, required),
+	(4, hysteresis_percent, required),
+});
+
 /// Options which apply on a per-channel basis and may change at runtime or based on negotiation
 /// with our counterparty.
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
@@ -625,6 +702,93 @@ pub struct ChannelConfig {
 	/// [`PaymentClaimable::counterparty_skimmed_fee_msat`]: crate::events::Event::PaymentClaimable::counterparty_skimmed_fee_msat
 	//  TODO: link to bLIP when it's merged
 	pub accept_underpaying_htlcs: bool,
+	/// If set, inbound HTLCs are additionally only accepted if our dust exposure would remain
+	/// within [`Self::max_dust_htlc_exposure`] after the channel's feerate increased to this
+	/// percentage of its current value, i.e., with pending HTLCs classified as dust at the
+	/// projected feerate.
+	///
+	/// This guards against the exposure we'd have after the next feerate increase reclassifies
+	/// HTLCs which are just above the dust threshold as dust. It applies on top of the fixed
+	/// buffer of 25% or 10 sat/vB we always assume the feerate may increase by, so only
+	/// multipliers projecting a larger increase, e.g., `300` for a tripling of the feerate, make
+	/// acceptance stricter. Outbound HTLCs and feerate updates are not affected.
+	///
+	/// As the fees of second-stage HTLC transactions are zero for anchor channels, this has no
+	/// effect on them. [`ChannelManager::project_dust_exposure`] can be used to inspect the
+	/// projected exposure at any feerate.
+	///
+	/// Default value: `None`
+	///
+	/// [`ChannelManager::project_dust_exposure`]: crate::ln::channelmanager::ChannelManager::project_dust_exposure
+	pub dust_classification_feerate_multiplier_percent: Option<u16>,
+	/// If set to `false`, the real short channel id and funding outpoint of an unannounced channel
+	/// are kept out of the messages we send to our counterparty where possible, as they link the
+	/// channel to its funding transaction for anyone able to read the counterparty's logs.
+	///
+	/// In that case, `channel_update`s for the channel carry the SCID alias provided by our
+	/// counterparty rather than the real SCID (if the counterparty provided one), and the error
+	/// messages we send it omit the funding transaction id. Note that HTLC failure onions never
+	/// include a `channel_update` and thus never reveal the SCID of the channel.
+	///
+	/// This has no effect on channels which are announced, as their SCID is public anyway.
+	///
+	/// Default value: `false`
+	pub reveal_private_channel_identifiers: bool,
+	/// If set, limits the number of HTLC additions, fulfills and fails released from the channel's
+	/// holding cell in a single `commitment_signed`.
+	///
+	/// Updates which cannot be sent immediately, e.g., while our counterparty is disconnected or
+	/// we're waiting on a `revoke_and_ack`, are queued in the holding cell. After a long
+	/// disconnection, this backlog may be large enough for the resulting commitment update to be
+	/// rejected by some counterparties. If set, updates are instead released in batches of at most
+	/// this many, each in its own commitment update sent once the counterparty has revoked the
+	/// previous one. Updates are always released in the order in which they were queued.
+	///
+	/// A value of `Some(0)` is treated as `Some(1)`. The current size of a channel's holding cell
+	/// can be queried via [`ChannelManager::list_holding_cell_sizes`].
+	///
+	/// Default value: `None`
+	///
+	/// [`ChannelManager::list_holding_cell_sizes`]: crate::ln::channelmanager::ChannelManager::list_holding_cell_sizes
+	pub max_holding_cell_updates_per_commitment: Option<u16>,
+	/// If set, an [`Event::ChannelLiquidityThresholdCrossed`] is generated whenever the channel's
+	/// local balance moves into a different [`LiquidityBand`], e.g., to wake up a rebalancer once
+	/// the outbound liquidity of a channel is depleted, without polling
+	/// [`ChannelManager::list_channels`].
+	///
+	/// The local balance is evaluated whenever an update to the channel's commitment transactions
+	/// completes, e.g., once an HTLC has been resolved. The band of the channel is first determined
+	/// on the first such evaluation after the thresholds were set, which does not generate an event.
+	///
+	/// Setting this in [`UserConfig::channel_config`] applies the thresholds to all new channels,
+	/// while [`ChannelManager::update_partial_channel_config`] may be used to set them for
+	/// individual channels.
+	///
+	/// Default value: `None`
+	///
+	/// [`Event::ChannelLiquidityThresholdCrossed`]: crate::events::Event::ChannelLiquidityThresholdCrossed
+	/// [`ChannelManager::list_channels`]: crate::ln::channelmanager::ChannelManager::list_channels
+	/// [`ChannelManager::update_partial_channel_config`]: crate::ln::channelmanager::ChannelManager::update_partial_channel_config
+	pub liquidity_alert_thresholds: Option<LiquidityAlertThresholds>,
+	/// If set, limits the number of inbound HTLCs we accept to have in-flight on the channel at
+	/// the same time, failing back any additional ones with a `temporary_channel_failure` error.
+	///
+	/// Claiming or timing out an HTLC on-chain after a force-close of an anchor channel requires
+	/// spending wallet UTXOs to pay fees, so if our on-chain reserve only covers a limited number
+	/// of HTLCs per channel, the excess HTLCs are those we may fail to resolve. Unlike
+	/// [`ChannelHandshakeConfig::our_max_accepted_htlcs`], which is fixed when the channel is
+	/// opened, this limit may be adjusted at any time as the reserve changes, e.g., via
+	/// [`ChannelManager::set_max_inbound_htlcs_for_reserve`], with a limit computed by
+	/// [`max_supportable_accepted_htlcs`].
+	///
+	/// The limit is only applied when an inbound HTLC is first accepted for forwarding or
+	/// receiving, so lowering it does not fail back any HTLCs which were already accepted.
+	///
+	/// Default value: `None`
+	///
+	/// [`ChannelManager::set_max_inbound_htlcs_for_reserve`]: crate::ln::channelmanager::ChannelManager::set_max_inbound_htlcs_for_reserve
+	/// [`max_supportable_accepted_htlcs`]: crate::util::anchor_channel_reserves::max_supportable_accepted_htlcs
+	pub max_inbound_htlcs_for_reserve: Option<u16>,
 }
 
 impl ChannelConfig {
@@ -652,6 +816,27 @@ impl ChannelConfig {
 		if let Some(accept_underpaying_htlcs) = update.accept_underpaying_htlcs {
 			self.accept_underpaying_htlcs = accept_underpaying_htlcs;
 		}
+		if let Some(dust_classification_feerate_multiplier_percent) =
+			update.dust_classification_feerate_multiplier_percent
+		{
+			self.dust_classification_feerate_multiplier_percent =
+				dust_classification_feerate_multiplier_percent;
+		}
+		if let Some(reveal_private_channel_identifiers) = update.reveal_private_channel_identifiers
+		{
+			self.reveal_private_channel_identifiers = reveal_private_channel_identifiers;
+		}
+		if let Some(max_holding_cell_updates_per_commitment) =
+			update.max_holding_cell_updates_per_commitment
+		{
+			self.max_holding_cell_updates_per_commitment = max_holding_cell_updates_per_commitment;
+		}
+		if let Some(liquidity_alert_thresholds) = update.liquidity_alert_thresholds {
+			self.liquidity_alert_thresholds = liquidity_alert_thresholds;
+		}
+		if let Some(max_inbound_htlcs_for_reserve) = update.max_inbound_htlcs_for_reserve {
+			self.max_inbound_htlcs_for_reserve = max_inbound_htlcs_for_reserve;
+		}
 	}
 }
 
@@ -665,6 +850,11 @@ impl Default for ChannelConfig {
 			max_dust_htlc_exposure: MaxDustHTLCExposure::FeeRateMultiplier(10000),
 			force_close_avoidance_max_fee_satoshis: 1000,
 			accept_underpaying_htlcs: false,
+			dust_classification_feerate_multiplier_percent: None,
+			reveal_private_channel_identifiers: false,
+			max_holding_cell_updates_per_commitment: None,
+			liquidity_alert_thresholds: None,
+			max_inbound_htlcs_for_reserve: None,
 		}
 	}
 }
@@ -686,6 +876,11 @@ impl crate::util::ser::Writeable for ChannelConfig {
 			// LegacyChannelConfig. To make sure that serialization is not compatible with this one, we use
 			// the next required type of 10, which if seen by the old serialization will always fail.
 			(10, self.force_close_avoidance_max_fee_satoshis, required),
+			(11, self.dust_classification_feerate_multiplier_percent, option),
+			(13, self.reveal_private_channel_identifiers, (default_value, false)),
+			(15, self.max_holding_cell_updates_per_commitment, option),
+			(17, self.liquidity_alert_thresholds, option),
+			(19, self.max_inbound_htlcs_for_reserve, option),
 		});
 		Ok(())
 	}
@@ -700,6 +895,11 @@ impl crate::util::ser::Readable for ChannelConfig {
 		let mut max_dust_htlc_exposure_msat = None;
 		let mut max_dust_htlc_exposure_enum = None;
 		let mut force_close_avoidance_max_fee_satoshis = 1000;
+		let mut dust_classification_feerate_multiplier_percent = None;
+		let mut reveal_private_channel_identifiers = false;
+		let mut max_holding_cell_updates_per_commitment = None;
+		let mut liquidity_alert_thresholds = None;
+		let mut max_inbound_htlcs_for_reserve = None;
 		read_tlv_fields!(reader, {
 			(0, forwarding_fee_proportional_millionths, required),
 			(1, accept_underpaying_htlcs, (default_value, false)),
@@ -709,6 +909,11 @@ impl crate::util::ser::Readable for ChannelConfig {
 			// Has always been written, but became optionally read in 0.0.116
 			(6, max_dust_htlc_exposure_msat, option),
 			(10, force_close_avoidance_max_fee_satoshis, required),
+			(11, dust_classification_feerate_multiplier_percent, option),
+			(13, reveal_private_channel_identifiers, (default_value, false)),
+			(15, max_holding_cell_updates_per_commitment, option),
+			(17, liquidity_alert_thresholds, option),
+			(19, max_inbound_htlcs_for_reserve, option),
 		});
 		let max_dust_htlc_fixed_limit = max_dust_htlc_exposure_msat.unwrap_or(5_000_000);
 		let max_dust_htlc_exposure_msat = max_dust_htlc_exposure_enum
@@ -720,6 +925,11 @@ impl crate::util::ser::Readable for ChannelConfig {
 			cltv_expiry_delta,
 			max_dust_htlc_exposure: max_dust_htlc_exposure_msat,
 			force_close_avoidance_max_fee_satoshis,
+			dust_classification_feerate_multiplier_percent,
+			reveal_private_channel_identifiers,
+			max_holding_cell_updates_per_commitment,
+			liquidity_alert_thresholds,
+			max_inbound_htlcs_for_reserve,
 		})
 	}
 }
@@ -749,6 +959,28 @@ pub struct ChannelConfigUpdate {
 	/// If set, allows this channel's counterparty to skim an additional fee off this node's inbound HTLCs. See
 	/// [`ChannelConfig::accept_underpaying_htlcs`].
 	pub accept_underpaying_htlcs: Option<bool>,
+
+	/// The percentage of the channel's current feerate at which inbound HTLCs are additionally
+	/// classified as dust before accepting them. See
+	/// [`ChannelConfig::dust_classification_feerate_multiplier_percent`].
+	pub dust_classification_feerate_multiplier_percent: Option<Option<u16>>,
+
+	/// Whether the real short channel id and funding outpoint of an unannounced channel may be
+	/// included in messages sent to our counterparty. See
+	/// [`ChannelConfig::reveal_private_channel_identifiers`].
+	pub reveal_private_channel_identifiers: Option<bool>,
+
+	/// The maximum number of holding cell updates released in a single commitment update. See
+	/// [`ChannelConfig::max_holding_cell_updates_per_commitment`].
+	pub max_holding_cell_updates_per_commitment: Option<Option<u16>>,
+
+	/// The thresholds on the channel's local balance for which events are generated. See
+	/// [`ChannelConfig::liquidity_alert_thresholds`].
+	pub liquidity_alert_thresholds: Option<Option<LiquidityAlertThresholds>>,
+
+	/// The maximum number of inbound HTLCs we accept to have in-flight given our on-chain reserve.
+	/// See [`ChannelConfig::max_inbound_htlcs_for_reserve`].
+	pub max_inbound_htlcs_for_reserve: Option<Option<u16>>,
 }
 
 impl From<ChannelConfig> for ChannelConfigUpdate {
@@ -764,6 +996,15 @@ impl From<ChannelConfig> for ChannelConfigUpdate {
 				config.force_close_avoidance_max_fee_satoshis,
 			),
 			accept_underpaying_htlcs: Some(config.accept_underpaying_htlcs),
+			dust_classification_feerate_multiplier_percent: Some(
+				config.dust_classification_feerate_multiplier_percent,
+			),
+			reveal_private_channel_identifiers: Some(config.reveal_private_channel_identifiers),
+			max_holding_cell_updates_per_commitment: Some(
+				config.max_holding_cell_updates_per_commitment,
+			),
+			liquidity_alert_thresholds: Some(config.liquidity_alert_thresholds),
+			max_inbound_htlcs_for_reserve: Some(config.max_inbound_htlcs_for_reserve),
 		}
 	}
 }
@@ -808,6 +1049,11 @@ impl crate::util::ser::Writeable for LegacyChannelConfig {
 			(5, self.options.max_dust_htlc_exposure, required),
 			(6, self.commit_upfront_shutdown_pubkey, required),
 			(8, self.options.forwarding_fee_base_msat, required),
+			(9, self.options.dust_classification_feerate_multiplier_percent, option),
+			(11, self.options.reveal_private_channel_identifiers, (default_value, false)),
+			(13, self.options.max_holding_cell_updates_per_commitment, option),
+			(15, self.options.liquidity_alert_thresholds, option),
+			(17, self.options.max_inbound_htlcs_for_reserve, option),
 		});
 		Ok(())
 	}
@@ -823,6 +1069,11 @@ impl crate::util::ser::Readable for LegacyChannelConfig {
 		let mut commit_upfront_shutdown_pubkey = false;
 		let mut forwarding_fee_base_msat = 0;
 		let mut max_dust_htlc_exposure_enum = None;
+		let mut dust_classification_feerate_multiplier_percent = None;
+		let mut reveal_private_channel_identifiers = false;
+		let mut max_holding_cell_updates_per_commitment = None;
+		let mut liquidity_alert_thresholds = None;
+		let mut max_inbound_htlcs_for_reserve = None;
 		read_tlv_fields!(reader, {
 			(0, forwarding_fee_proportional_millionths, required),
 			// Has always been written, but became optionally read in 0.0.116
@@ -833,6 +1084,11 @@ impl crate::util::ser::Readable for LegacyChannelConfig {
 			(5, max_dust_htlc_exposure_enum, option),
 			(6, commit_upfront_shutdown_pubkey, required),
 			(8, forwarding_fee_base_msat, required),
+			(9, dust_classification_feerate_multiplier_percent, option),
+			(11, reveal_private_channel_identifiers, (default_value, false)),
+			(13, max_holding_cell_updates_per_commitment, option),
+			(15, liquidity_alert_thresholds, option),
+			(17, max_inbound_htlcs_for_reserve, option),
 		});
 		let max_dust_htlc_exposure_msat_fixed_limit =
 			max_dust_htlc_exposure_msat_fixed_limit.unwrap_or(5_000_000);
@@ -847,6 +1103,11 @@ impl crate::util::ser::Readable for LegacyChannelConfig {
 				force_close_avoidance_max_fee_satoshis,
 				forwarding_fee_base_msat,
 				accept_underpaying_htlcs: false,
+				dust_classification_feerate_multiplier_percent,
+				reveal_private_channel_identifiers,
+				max_holding_cell_updates_per_commitment,
+				liquidity_alert_thresholds,
+				max_inbound_htlcs_for_reserve,
 			},
 			announce_for_forwarding,
 			commit_upfront_shutdown_pubkey,
@@ -935,6 +1196,208 @@ pub struct UserConfig {
 	///
 	/// Default value: `false`
 	pub enable_dual_funded_channels: bool,
+	/// The maximum duration, in seconds, for which we'll hold an outbound async payment to an
+	/// often-offline recipient while waiting for it to come online and release the payment.
+	///
+	/// Once the duration elapses without the recipient releasing the payment, the payment is failed
+	/// with [`PaymentFailureReason::PaymentExpired`]. As no HTLCs are sent before the payment is
+	/// released, no funds are locked up while the payment is held.
+	///
+	/// Default value: `604800` (1 week)
+	///
+	/// [`PaymentFailureReason::PaymentExpired`]: crate::events::PaymentFailureReason::PaymentExpired
+	pub async_payment_hold_timeout_secs: u64,
+	/// The maximum number of balance samples to retain per channel, as returned by
+	/// [`ChannelManager::get_channel_balance_history`].
+	///
+	/// If non-zero, a sample of a channel's outbound and inbound capacity is recorded whenever it
+	/// changes as HTLCs are added or resolved, dropping the oldest sample once this limit is
+	/// reached. Each sample uses 32 bytes of memory. If zero, no samples are recorded.
+	///
+	/// Default value: `0`
+	///
+	/// [`ChannelManager::get_channel_balance_history`]: crate::ln::channelmanager::ChannelManager::get_channel_balance_history
+	pub channel_balance_history_limit: u16,
+	/// If this is set to `true`, [`ChannelManager::send_payment`] will check whether a payment can
+	/// possibly succeed before attempting to find a route, failing immediately with
+	/// [`RetryableSendFailure::PaymentInfeasible`] otherwise.
+	///
+	/// See [`ChannelManager::estimate_payment_feasibility`] for the checks performed. Set this to
+	/// `false` to always attempt to find a route instead.
+	///
+	/// Default value: `true`
+	///
+	/// [`ChannelManager::send_payment`]: crate::ln::channelmanager::ChannelManager::send_payment
+	/// [`RetryableSendFailure::PaymentInfeasible`]: crate::ln::channelmanager::RetryableSendFailure::PaymentInfeasible
+	/// [`ChannelManager::estimate_payment_feasibility`]: crate::ln::channelmanager::ChannelManager::estimate_payment_feasibility
+	pub fail_fast_infeasible_payments: bool,
+	/// The number of calls to [`ChannelManager::timer_tick_occurred`] without receiving a message
+	/// from our counterparty after which we abort an interactive funding transaction negotiation
+	/// for a dual-funded channel or a splice.
+	///
+	/// Upon aborting, an [`Event::DiscardFunding`] is generated for any inputs we contributed to
+	/// the negotiation so that they can be spent elsewhere. The progress of a negotiation can be
+	/// queried via [`ChannelManager::interactive_tx_negotiation_progress`].
+	///
+	/// Default value: `5`
+	///
+	/// [`ChannelManager::timer_tick_occurred`]: crate::ln::channelmanager::ChannelManager::timer_tick_occurred
+	/// [`Event::DiscardFunding`]: crate::events::Event::DiscardFunding
+	/// [`ChannelManager::interactive_tx_negotiation_progress`]: crate::ln::channelmanager::ChannelManager::interactive_tx_negotiation_progress
+	pub interactive_tx_negotiation_timeout_ticks: u16,
+	/// If this is set to `true`, the user needs to manually acknowledge that a channel's state was
+	/// lost before LDK proceeds with recovering the channel's funds.
+	///
+	/// Upon receiving a `channel_reestablish` proving that our channel state is stale, e.g.,
+	/// because we restarted from an old backup, LDK will by default panic as doing so violates the
+	/// [`chain::Watch`] requirements. When set to `true`, [`Event::LocalDataLossDetected`] is
+	/// generated instead and the peer is disconnected with a warning on every reconnection until
+	/// [`ChannelManager::acknowledge_data_loss`] is called, giving the operator the opportunity to
+	/// check backups and ensure the peer is not lying about our state. No other messages for the
+	/// channel are processed in the meantime.
+	///
+	/// Note that our stale [`ChannelMonitor`] may still broadcast our (revoked) commitment
+	/// transaction while waiting if HTLCs are about to expire, so this should not be left
+	/// unacknowledged for long.
+	///
+	/// Default value: `false`
+	///
+	/// [`chain::Watch`]: crate::chain::Watch
+	/// [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+	/// [`Event::LocalDataLossDetected`]: crate::events::Event::LocalDataLossDetected
+	/// [`ChannelManager::acknowledge_data_loss`]: crate::ln::channelmanager::ChannelManager::acknowledge_data_loss
+	pub manually_acknowledge_data_loss: bool,
+	/// The duration, in seconds, for which we wait for the remaining parts of an inbound
+	/// multi-part payment after receiving its first part, before failing back the parts received
+	/// so far with an `mpp_timeout` error.
+	///
+	/// The timeout is enforced by [`ChannelManager::expire_stale_inbound_payments`] in units of
+	/// its calls, assuming it is called roughly once per minute. Thus, parts may be held up to a
+	/// minute longer than configured.
+	///
+	/// Default value: `120`
+	///
+	/// Minimum value: [`MIN_MPP_TIMEOUT_SECS`], as recommended by BOLT 4 (Any values less than
+	///                this will be treated as [`MIN_MPP_TIMEOUT_SECS`] instead.)
+	///
+	/// Maximum value: [`MAX_MPP_TIMEOUT_SECS`] (Any values greater than this will be treated as
+	///                [`MAX_MPP_TIMEOUT_SECS`] instead.)
+	///
+	/// [`ChannelManager::expire_stale_inbound_payments`]: crate::ln::channelmanager::ChannelManager::expire_stale_inbound_payments
+	/// [`MIN_MPP_TIMEOUT_SECS`]: crate::ln::channelmanager::MIN_MPP_TIMEOUT_SECS
+	/// [`MAX_MPP_TIMEOUT_SECS`]: crate::ln::channelmanager::MAX_MPP_TIMEOUT_SECS
+	pub mpp_timeout_secs: u64,
+	/// If this is set to `true`, [`Event::PaymentPartiallyReceived`] will be generated once the
+	/// first part of an inbound multi-part payment is received, and
+	/// [`Event::PartialPaymentFailed`] once the parts received so far are failed back before the
+	/// payment became claimable.
+	///
+	/// Default value: `false`
+	///
+	/// [`Event::PaymentPartiallyReceived`]: crate::events::Event::PaymentPartiallyReceived
+	/// [`Event::PartialPaymentFailed`]: crate::events::Event::PartialPaymentFailed
+	pub notify_partial_mpp_payments: bool,
+	/// If set, limits the number of HTLCs per channel which are pending as parts of payments we
+	/// receive, failing back any additional parts with a `temporary_channel_failure` error, such
+	/// that senders retry them along other channels or split them differently.
+	///
+	/// The parts of a multi-part payment are held until all of them arrived, so a payment split
+	/// into many small parts may briefly occupy many HTLC slots on an anchor channel, all of which
+	/// we'd need to claim on-chain if the channel force-closed meanwhile. Unlike
+	/// [`ChannelConfig::max_inbound_htlcs_for_reserve`], which limits all inbound HTLCs including
+	/// those we forward, this limit only applies to HTLCs we are the final recipient of, and it is
+	/// enforced when an HTLC is first received.
+	///
+	/// A suitable limit may be derived from the [`AnchorChannelReserveContext`] used to size the
+	/// on-chain reserve via [`max_supportable_accepted_htlcs`], leaving room for the inbound HTLCs
+	/// of any forwards.
+	///
+	/// Default value: `None`
+	///
+	/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+	/// [`max_supportable_accepted_htlcs`]: crate::util::anchor_channel_reserves::max_supportable_accepted_htlcs
+	pub max_inbound_payment_parts_for_reserve: Option<u16>,
+	/// The number of events pending handling by the [`ChannelManager`] above which its event queue
+	/// is considered backed up, e.g., because the event handler stalls.
+	///
+	/// Whenever events are processed or [`ChannelManager::timer_tick_occurred`] is called while
+	/// more events are pending, a warning is logged and, if
+	/// [`Self::drop_informational_events_on_overflow`] is set, the oldest informational events are
+	/// dropped. The number of pending events can be monitored via
+	/// [`ChannelManager::get_pending_event_stats`]. If zero, no limit is enforced.
+	///
+	/// Default value: `10000`
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	/// [`ChannelManager::timer_tick_occurred`]: crate::ln::channelmanager::ChannelManager::timer_tick_occurred
+	/// [`ChannelManager::get_pending_event_stats`]: crate::ln::channelmanager::ChannelManager::get_pending_event_stats
+	pub pending_events_soft_limit: u32,
+	/// If this is set to `true`, the oldest informational events are dropped once more than
+	/// [`Self::pending_events_soft_limit`] events are pending, until the limit is met again.
+	///
+	/// Only events which require no action are dropped, i.e., [`Event::PaymentForwarded`],
+	/// [`Event::PaymentPathSuccessful`], [`Event::ProbeSuccessful`], [`Event::ProbeFailed`],
+	/// [`Event::ChannelPending`], [`Event::ChannelReady`], [`Event::HTLCHandlingFailed`],
+	/// [`Event::ChannelPartnerAnnouncementChanged`] and [`Event::ChannelLiquidityThresholdCrossed`],
+	/// and only if no channel update awaits their handling. All other events, e.g.,
+	/// [`Event::PaymentClaimable`], [`Event::FundingGenerationReady`] or
+	/// [`Event::SpendableOutputs`], are never dropped, so more events than the limit may remain
+	/// pending.
+	///
+	/// Default value: `false`
+	///
+	/// [`Event::PaymentForwarded`]: crate::events::Event::PaymentForwarded
+	/// [`Event::PaymentPathSuccessful`]: crate::events::Event::PaymentPathSuccessful
+	/// [`Event::ProbeSuccessful`]: crate::events::Event::ProbeSuccessful
+	/// [`Event::ProbeFailed`]: crate::events::Event::ProbeFailed
+	/// [`Event::ChannelPending`]: crate::events::Event::ChannelPending
+	/// [`Event::ChannelReady`]: crate::events::Event::ChannelReady
+	/// [`Event::HTLCHandlingFailed`]: crate::events::Event::HTLCHandlingFailed
+	/// [`Event::ChannelPartnerAnnouncementChanged`]: crate::events::Event::ChannelPartnerAnnouncementChanged
+	/// [`Event::ChannelLiquidityThresholdCrossed`]: crate::events::Event::ChannelLiquidityThresholdCrossed
+	/// [`Event::PaymentClaimable`]: crate::events::Event::PaymentClaimable
+	/// [`Event::FundingGenerationReady`]: crate::events::Event::FundingGenerationReady
+	/// [`Event::SpendableOutputs`]: crate::events::Event::SpendableOutputs
+	pub drop_informational_events_on_overflow: bool,
+	/// The duration, in seconds, after which our view of the chain is considered stale if no new
+	/// best block has been seen, e.g., because our chain source stopped working.
+	///
+	/// Once exceeded, an [`Event::ChainSyncStale`] is generated by
+	/// [`ChannelManager::check_chain_sync_staleness`]. Note that, as blocks are found at random
+	/// intervals of ten minutes on average, the default value is still exceeded by chance a few
+	/// times a year.
+	///
+	/// Staleness is only detected with the `std` feature enabled, as it requires a local time
+	/// source.
+	///
+	/// Default value: `5400` (90 minutes)
+	///
+	/// [`Event::ChainSyncStale`]: crate::events::Event::ChainSyncStale
+	/// [`ChannelManager::check_chain_sync_staleness`]: crate::ln::channelmanager::ChannelManager::check_chain_sync_staleness
+	pub chain_sync_stale_threshold_secs: u64,
+	/// If this is set to `true`, HTLCs we are asked to forward are failed back with a
+	/// `temporary_node_failure` error while our view of the chain is stale, see
+	/// [`Self::chain_sync_stale_threshold_secs`]. Forwarding resumes as soon as a new best block is
+	/// seen.
+	///
+	/// With a stale view of the chain, we may fail to act on HTLC timeouts in time, so accepting
+	/// new forwards only adds to the funds at risk.
+	///
+	/// Default value: `false`
+	pub fail_htlc_forwards_while_chain_sync_stale: bool,
+	/// The number of calls to [`ChannelManager::timer_tick_occurred`] after which a channel
+	/// negotiation which was in progress when the [`ChannelManager`] was last persisted is
+	/// forgotten, unless the counterparty reconnected before.
+	///
+	/// Such negotiations are listed by [`ChannelManager::list_pending_channel_negotiations`] and
+	/// thus still counted towards the anchor channel reserve until then.
+	///
+	/// Default value: `60` (roughly one hour)
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	/// [`ChannelManager::timer_tick_occurred`]: crate::ln::channelmanager::ChannelManager::timer_tick_occurred
+	/// [`ChannelManager::list_pending_channel_negotiations`]: crate::ln::channelmanager::ChannelManager::list_pending_channel_negotiations
+	pub pending_channel_negotiation_timeout_ticks: u16,
 }
 
 impl Default for UserConfig {
@@ -949,6 +1412,19 @@ impl Default for UserConfig {
 			accept_intercept_htlcs: false,
 			manually_handle_bolt12_invoices: false,
 			enable_dual_funded_channels: false,
+			async_payment_hold_timeout_secs: 60 * 60 * 24 * 7,
+			channel_balance_history_limit: 0,
+			fail_fast_infeasible_payments: true,
+			interactive_tx_negotiation_timeout_ticks: 5,
+			manually_acknowledge_data_loss: false,
+			mpp_timeout_secs: 120,
+			notify_partial_mpp_payments: false,
+			max_inbound_payment_parts_for_reserve: None,
+			pending_events_soft_limit: 10_000,
+			drop_informational_events_on_overflow: false,
+			chain_sync_stale_threshold_secs: 90 * 60,
+			fail_htlc_forwards_while_chain_sync_stale: false,
+			pending_channel_negotiation_timeout_ticks: 60,
 		}
 	}
 }
@@ -969,6 +1445,19 @@ impl Readable for UserConfig {
 			accept_intercept_htlcs: Readable::read(reader)?,
 			manually_handle_bolt12_invoices: Readable::read(reader)?,
 			enable_dual_funded_channels: Readable::read(reader)?,
+			async_payment_hold_timeout_secs: Readable::read(reader)?,
+			channel_balance_history_limit: Readable::read(reader)?,
+			fail_fast_infeasible_payments: Readable::read(reader)?,
+			interactive_tx_negotiation_timeout_ticks: Readable::read(reader)?,
+			manually_acknowledge_data_loss: Readable::read(reader)?,
+			mpp_timeout_secs: Readable::read(reader)?,
+			notify_partial_mpp_payments: Readable::read(reader)?,
+			max_inbound_payment_parts_for_reserve: Readable::read(reader)?,
+			pending_events_soft_limit: Readable::read(reader)?,
+			drop_informational_events_on_overflow: Readable::read(reader)?,
+			chain_sync_stale_threshold_secs: Readable::read(reader)?,
+			fail_htlc_forwards_while_chain_sync_stale: Readable::read(reader)?,
+			pending_channel_negotiation_timeout_ticks: Readable::read(reader)?,
 		})
 	}
 }
@@ -983,6 +1472,149 @@ pub struct ChannelConfigOverrides {
 	pub update_overrides: Option<ChannelConfigUpdate>,
 }
 
+/// An inconsistency in a [`UserConfig`], as returned by [`UserConfig::validate`].
+///
+/// Most of these describe values which LDK silently clamps or which only cause some channels to be
+/// rejected, and are thus only logged when constructing a [`ChannelManager`]. Those for which
+/// [`ConfigError::is_fatal`] returns `true` violate protocol minimums, so that no channel could
+/// ever be opened with the configuration, and cause [`ChannelManager::new`] to panic.
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+/// [`ChannelManager::new`]: crate::ln::channelmanager::ChannelManager::new
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ConfigError {
+	/// [`ChannelHandshakeLimits::min_funding_satoshis`] is greater than
+	/// [`ChannelHandshakeLimits::max_funding_satoshis`], so all inbound channels will be rejected.
+	FundingRangeEmpty {
+		/// The configured [`ChannelHandshakeLimits::min_funding_satoshis`].
+		min_funding_satoshis: u64,
+		/// The configured [`ChannelHandshakeLimits::max_funding_satoshis`].
+		max_funding_satoshis: u64,
+	},
+	/// [`ChannelHandshakeConfig::max_inbound_htlc_value_in_flight_percent_of_channel`] is not
+	/// between `1` and `100`, and will be clamped to that range.
+	MaxInFlightPercentOutOfRange {
+		/// The configured percentage.
+		percent: u8,
+	},
+	/// [`ChannelHandshakeConfig::our_htlc_minimum_msat`] exceeds the total value of inbound HTLCs
+	/// we allow in-flight on a channel of [`ChannelHandshakeLimits::min_funding_satoshis`], so no
+	/// HTLC could ever be received over the smallest inbound channels we accept.
+	HtlcMinimumExceedsMaxInFlight {
+		/// The configured [`ChannelHandshakeConfig::our_htlc_minimum_msat`].
+		our_htlc_minimum_msat: u64,
+		/// The total value of inbound HTLCs we allow in-flight on the smallest inbound channel.
+		max_inbound_htlc_value_in_flight_msat: u64,
+	},
+	/// [`ChannelHandshakeConfig::our_to_self_delay`] is below [`BREAKDOWN_TIMEOUT`], so opening
+	/// channels with this configuration will fail.
+	ToSelfDelayBelowMinimum {
+		/// The configured [`ChannelHandshakeConfig::our_to_self_delay`].
+		our_to_self_delay: u16,
+	},
+	/// [`ChannelHandshakeConfig::our_max_accepted_htlcs`] is `0`, which our counterparties are
+	/// required to reject, so no channel could ever be opened.
+	ZeroMaxAcceptedHtlcs,
+	/// [`ChannelHandshakeConfig::our_max_accepted_htlcs`] is above the protocol maximum of `483`,
+	/// and will be reduced to the maximum.
+	MaxAcceptedHtlcsAboveMaximum {
+		/// The configured [`ChannelHandshakeConfig::our_max_accepted_htlcs`].
+		our_max_accepted_htlcs: u16,
+	},
+	/// [`ChannelHandshakeConfig::their_channel_reserve_proportional_millionths`] is above one
+	/// million, i.e., the reserve would exceed the channel value, so no channel could ever be
+	/// opened.
+	ChannelReserveExceedsChannelValue {
+		/// The configured [`ChannelHandshakeConfig::their_channel_reserve_proportional_millionths`].
+		their_channel_reserve_proportional_millionths: u32,
+	},
+	/// [`ChannelConfig::cltv_expiry_delta`] is below [`MIN_CLTV_EXPIRY_DELTA`], and will be raised
+	/// to the minimum.
+	CltvExpiryDeltaBelowMinimum {
+		/// The configured [`ChannelConfig::cltv_expiry_delta`].
+		cltv_expiry_delta: u16,
+	},
+	/// [`ChannelConfig::max_dust_htlc_exposure`] is a [`MaxDustHTLCExposure::FixedLimitMsat`] below
+	/// the smallest dust limit a channel may have, so even a single dust HTLC may be refused.
+	DustExposureBelowDustLimit {
+		/// The configured limit, in millisatoshis.
+		max_dust_htlc_exposure_msat: u64,
+	},
+	/// [`ChannelConfig::liquidity_alert_thresholds`] do not delimit three bands, see
+	/// [`LiquidityAlertThresholds::is_valid`].
+	InvalidLiquidityAlertThresholds(LiquidityAlertThresholds),
+}
+
+impl ConfigError {
+	/// Returns whether the configuration violates a protocol minimum, such that no channel could
+	/// ever be opened with it.
+	pub fn is_fatal(&self) -> bool {
+		matches!(
+			self,
+			ConfigError::ZeroMaxAcceptedHtlcs
+				| ConfigError::ChannelReserveExceedsChannelValue { .. }
+		)
+	}
+}
+
+impl fmt::Display for ConfigError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			ConfigError::FundingRangeEmpty { min_funding_satoshis, max_funding_satoshis } => write!(
+				f,
+				"min_funding_satoshis ({}) is greater than max_funding_satoshis ({})",
+				min_funding_satoshis, max_funding_satoshis
+			),
+			ConfigError::MaxInFlightPercentOutOfRange { percent } => write!(
+				f,
+				"max_inbound_htlc_value_in_flight_percent_of_channel ({}) is not between 1 and 100",
+				percent
+			),
+			ConfigError::HtlcMinimumExceedsMaxInFlight {
+				our_htlc_minimum_msat,
+				max_inbound_htlc_value_in_flight_msat,
+			} => write!(
+				f,
+				"our_htlc_minimum_msat ({}) exceeds the inbound in-flight limit of the smallest accepted channel ({} msat)",
+				our_htlc_minimum_msat, max_inbound_htlc_value_in_flight_msat
+			),
+			ConfigError::ToSelfDelayBelowMinimum { our_to_self_delay } => write!(
+				f,
+				"our_to_self_delay ({}) is below the minimum of {}",
+				our_to_self_delay, BREAKDOWN_TIMEOUT
+			),
+			ConfigError::ZeroMaxAcceptedHtlcs => f.write_str("our_max_accepted_htlcs is 0"),
+			ConfigError::MaxAcceptedHtlcsAboveMaximum { our_max_accepted_htlcs } => write!(
+				f,
+				"our_max_accepted_htlcs ({}) is above the maximum of 483",
+				our_max_accepted_htlcs
+			),
+			ConfigError::ChannelReserveExceedsChannelValue {
+				their_channel_reserve_proportional_millionths,
+			} => write!(
+				f,
+				"their_channel_reserve_proportional_millionths ({}) exceeds the channel value",
+				their_channel_reserve_proportional_millionths
+			),
+			ConfigError::CltvExpiryDeltaBelowMinimum { cltv_expiry_delta } => write!(
+				f,
+				"cltv_expiry_delta ({}) is below the minimum of {}",
+				cltv_expiry_delta, MIN_CLTV_EXPIRY_DELTA
+			),
+			ConfigError::DustExposureBelowDustLimit { max_dust_htlc_exposure_msat } => write!(
+				f,
+				"max_dust_htlc_exposure ({} msat) is below the minimum dust limit of {} sat",
+				max_dust_htlc_exposure_msat, MIN_CHAN_DUST_LIMIT_SATOSHIS
+			),
+			ConfigError::InvalidLiquidityAlertThresholds(thresholds) => write!(
+				f,
+				"liquidity_alert_thresholds {:?} do not delimit three bands",
+				thresholds
+			),
+		}
+	}
+}
+
 impl UserConfig {
 	/// Applies given channel config overrides to the user config.
 	pub fn apply(&mut self, config: &ChannelConfigOverrides) {
@@ -994,6 +1626,92 @@ impl UserConfig {
 			self.channel_config.apply(&update_overrides);
 		}
 	}
+
+	/// Checks the configuration for values which are out of range or inconsistent with each
+	/// other, returning all [`ConfigError`]s found.
+	///
+	/// This is called when constructing a [`ChannelManager`], which logs any errors and panics on
+	/// [fatal] ones, but may also be called by applications whenever the configuration is edited.
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	/// [fatal]: ConfigError::is_fatal
+	pub fn validate(&self) -> Result<(), Vec<ConfigError>> {
+		let handshake_config = &self.channel_handshake_config;
+		let handshake_limits = &self.channel_handshake_limits;
+		let mut errors = Vec::new();
+
+		if handshake_limits.min_funding_satoshis > handshake_limits.max_funding_satoshis {
+			errors.push(ConfigError::FundingRangeEmpty {
+				min_funding_satoshis: handshake_limits.min_funding_satoshis,
+				max_funding_satoshis: handshake_limits.max_funding_satoshis,
+			});
+		}
+
+		let max_in_flight_percent =
+			handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel;
+		if !(1..=100).contains(&max_in_flight_percent) {
+			errors
+				.push(ConfigError::MaxInFlightPercentOutOfRange { percent: max_in_flight_percent });
+		}
+		let max_inbound_htlc_value_in_flight_msat = handshake_limits
+			.min_funding_satoshis
+			.saturating_mul(10 * max_in_flight_percent.clamp(1, 100) as u64);
+		if handshake_config.our_htlc_minimum_msat > max_inbound_htlc_value_in_flight_msat {
+			errors.push(ConfigError::HtlcMinimumExceedsMaxInFlight {
+				our_htlc_minimum_msat: handshake_config.our_htlc_minimum_msat,
+				max_inbound_htlc_value_in_flight_msat,
+			});
+		}
+
+		if handshake_config.our_to_self_delay < BREAKDOWN_TIMEOUT {
+			errors.push(ConfigError::ToSelfDelayBelowMinimum {
+				our_to_self_delay: handshake_config.our_to_self_delay,
+			});
+		}
+
+		if handshake_config.our_max_accepted_htlcs == 0 {
+			errors.push(ConfigError::ZeroMaxAcceptedHtlcs);
+		} else if handshake_config.our_max_accepted_htlcs > 483 {
+			errors.push(ConfigError::MaxAcceptedHtlcsAboveMaximum {
+				our_max_accepted_htlcs: handshake_config.our_max_accepted_htlcs,
+			});
+		}
+
+		if handshake_config.their_channel_reserve_proportional_millionths > 1_000_000 {
+			errors.push(ConfigError::ChannelReserveExceedsChannelValue {
+				their_channel_reserve_proportional_millionths: handshake_config
+					.their_channel_reserve_proportional_millionths,
+			});
+		}
+
+		if self.channel_config.cltv_expiry_delta < MIN_CLTV_EXPIRY_DELTA {
+			errors.push(ConfigError::CltvExpiryDeltaBelowMinimum {
+				cltv_expiry_delta: self.channel_config.cltv_expiry_delta,
+			});
+		}
+
+		if let MaxDustHTLCExposure::FixedLimitMsat(limit_msat) =
+			self.channel_config.max_dust_htlc_exposure
+		{
+			if limit_msat < MIN_CHAN_DUST_LIMIT_SATOSHIS * 1000 {
+				errors.push(ConfigError::DustExposureBelowDustLimit {
+					max_dust_htlc_exposure_msat: limit_msat,
+				});
+			}
+		}
+
+		if let Some(thresholds) = self.channel_config.liquidity_alert_thresholds {
+			if !thresholds.is_valid() {
+				errors.push(ConfigError::InvalidLiquidityAlertThresholds(thresholds));
+			}
+		}
+
+		if errors.is_empty() {
+			Ok(())
+		} else {
+			Err(errors)
+		}
+	}
 }
 
 /// Config structure for overriding channel handshake parameters.
@@ -1053,3 +1771,183 @@ impl ChannelHandshakeConfig {
 		}
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::{ConfigError, LiquidityAlertThresholds, MaxDustHTLCExposure, UserConfig};
+	use crate::ln::channelmanager::{BREAKDOWN_TIMEOUT, MIN_CLTV_EXPIRY_DELTA};
+
+	#[test]
+	fn test_default_config_is_valid() {
+		assert_eq!(UserConfig::default().validate(), Ok(()));
+	}
+
+	#[test]
+	fn test_validate_funding_range() {
+		let mut config = UserConfig::default();
+		config.channel_handshake_limits.min_funding_satoshis = 100_000;
+		config.channel_handshake_limits.max_funding_satoshis = 100_000;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_handshake_limits.min_funding_satoshis = 100_001;
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::FundingRangeEmpty {
+				min_funding_satoshis: 100_001,
+				max_funding_satoshis: 100_000,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_validate_max_in_flight_percent() {
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel = 1;
+		assert_eq!(config.validate(), Ok(()));
+		config.channel_handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel = 100;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel = 0;
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::MaxInFlightPercentOutOfRange { percent: 0 }])
+		);
+		config.channel_handshake_config.max_inbound_htlc_value_in_flight_percent_of_channel = 101;
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::MaxInFlightPercentOutOfRange { percent: 101 }])
+		);
+	}
+
+	#[test]
+	fn test_validate_htlc_minimum_against_max_in_flight() {
+		// With the default 1000 sat minimum funding and 10% in-flight limit, at most 100_000 msat
+		// may be in-flight on the smallest inbound channel.
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.our_htlc_minimum_msat = 100_000;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_handshake_config.our_htlc_minimum_msat = 100_001;
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::HtlcMinimumExceedsMaxInFlight {
+				our_htlc_minimum_msat: 100_001,
+				max_inbound_htlc_value_in_flight_msat: 100_000,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_validate_to_self_delay() {
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.our_to_self_delay = BREAKDOWN_TIMEOUT;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_handshake_config.our_to_self_delay = BREAKDOWN_TIMEOUT - 1;
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::ToSelfDelayBelowMinimum {
+				our_to_self_delay: BREAKDOWN_TIMEOUT - 1,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_validate_max_accepted_htlcs() {
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.our_max_accepted_htlcs = 1;
+		assert_eq!(config.validate(), Ok(()));
+		config.channel_handshake_config.our_max_accepted_htlcs = 483;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_handshake_config.our_max_accepted_htlcs = 0;
+		let errors = config.validate().unwrap_err();
+		assert_eq!(errors, vec![ConfigError::ZeroMaxAcceptedHtlcs]);
+		assert!(errors[0].is_fatal());
+
+		config.channel_handshake_config.our_max_accepted_htlcs = 484;
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors,
+			vec![ConfigError::MaxAcceptedHtlcsAboveMaximum { our_max_accepted_htlcs: 484 }]
+		);
+		assert!(!errors[0].is_fatal());
+	}
+
+	#[test]
+	fn test_validate_channel_reserve() {
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.their_channel_reserve_proportional_millionths = 1_000_000;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_handshake_config.their_channel_reserve_proportional_millionths = 1_000_001;
+		let errors = config.validate().unwrap_err();
+		assert_eq!(
+			errors,
+			vec![ConfigError::ChannelReserveExceedsChannelValue {
+				their_channel_reserve_proportional_millionths: 1_000_001,
+			}]
+		);
+		assert!(errors[0].is_fatal());
+	}
+
+	#[test]
+	fn test_validate_cltv_expiry_delta() {
+		let mut config = UserConfig::default();
+		config.channel_config.cltv_expiry_delta = MIN_CLTV_EXPIRY_DELTA;
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_config.cltv_expiry_delta = MIN_CLTV_EXPIRY_DELTA - 1;
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::CltvExpiryDeltaBelowMinimum {
+				cltv_expiry_delta: MIN_CLTV_EXPIRY_DELTA - 1,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_validate_dust_exposure() {
+		let mut config = UserConfig::default();
+		config.channel_config.max_dust_htlc_exposure = MaxDustHTLCExposure::FixedLimitMsat(354_000);
+		assert_eq!(config.validate(), Ok(()));
+
+		config.channel_config.max_dust_htlc_exposure = MaxDustHTLCExposure::FixedLimitMsat(353_999);
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::DustExposureBelowDustLimit {
+				max_dust_htlc_exposure_msat: 353_999,
+			}])
+		);
+	}
+
+	#[test]
+	fn test_validate_liquidity_alert_thresholds() {
+		let mut config = UserConfig::default();
+		let mut thresholds =
+			LiquidityAlertThresholds { low_percent: 99, high_percent: 100, hysteresis_percent: 0 };
+		config.channel_config.liquidity_alert_thresholds = Some(thresholds);
+		assert_eq!(config.validate(), Ok(()));
+
+		thresholds.low_percent = 100;
+		config.channel_config.liquidity_alert_thresholds = Some(thresholds);
+		assert_eq!(
+			config.validate(),
+			Err(vec![ConfigError::InvalidLiquidityAlertThresholds(thresholds)])
+		);
+	}
+
+	#[test]
+	fn test_validate_reports_all_errors() {
+		let mut config = UserConfig::default();
+		config.channel_handshake_config.our_max_accepted_htlcs = 0;
+		config.channel_config.cltv_expiry_delta = 0;
+		assert_eq!(
+			config.validate(),
+			Err(vec![
+				ConfigError::ZeroMaxAcceptedHtlcs,
+				ConfigError::CltvExpiryDeltaBelowMinimum { cltv_expiry_delta: 0 },
+			])
+		);
+	}
+}
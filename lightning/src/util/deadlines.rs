@@ -0,0 +1,116 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Utilities for exporting the block heights at which time-sensitive actions on HTLCs and channels
+//! are due, e.g., to alert an external watchdog before a deadline is missed.
+//!
+//! The heights are derived from the same checks the [`ChannelManager`] and [`ChannelMonitor`] use
+//! to act on them as blocks are connected, so they remain consistent with the node's behavior.
+//!
+//! [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+//! [`ChannelMonitor`]: crate::chain::channelmonitor::ChannelMonitor
+
+use crate::ln::types::ChannelId;
+use crate::prelude::*;
+use crate::util::facade::{DynChainMonitor, DynChannelManager};
+
+use bitcoin::Amount;
+
+/// The kind of a time-sensitive deadline, as returned by [`get_htlc_deadlines`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum DeadlineKind {
+	/// An inbound HTLC paying us has to be claimed by this height, after which it is failed back to
+	/// avoid racing our counterparty on-chain.
+	InboundHtlcClaim,
+	/// An HTLC we have yet to forward, e.g., as it is held in our holding cell or was intercepted,
+	/// will be failed back at this height.
+	OutboundHtlcFailBack,
+	/// The channel will be force-closed at this height to resolve an HTLC on-chain, unless it is
+	/// resolved off-chain before.
+	ChannelForceClose,
+	/// Our counterparty may claim an HTLC output of a confirmed commitment transaction at this
+	/// height if we have not yet claimed it with the preimage.
+	CounterpartyClaimTimeout,
+}
+
+/// Gets every time-sensitive deadline the [`ChannelManager`] and [`ChainMonitor`] currently track,
+/// as the height at which it is due, its kind, the channel it relates to and the amount at stake,
+/// sorted by height.
+///
+/// Amounts of HTLCs are rounded down to the nearest satoshi.
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+/// [`ChainMonitor`]: crate::chain::chainmonitor::ChainMonitor
+pub fn get_htlc_deadlines(
+	channel_manager: &DynChannelManager<'_>, chain_monitor: &DynChainMonitor<'_>,
+) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+	let mut deadlines = channel_manager.get_htlc_deadlines();
+	deadlines.append(&mut chain_monitor.get_htlc_deadlines());
+	deadlines.sort_by_key(|(height, ..)| *height);
+	deadlines
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::chain::channelmonitor::{HTLC_FAIL_BACK_BUFFER, LATENCY_GRACE_PERIOD_BLOCKS};
+	use crate::check_closed_broadcast;
+	use crate::events::ClosureReason;
+	use crate::ln::functional_test_utils::*;
+
+	#[test]
+	fn test_get_htlc_deadlines() {
+		// The exported deadlines match the heights at which the node actually acts.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+		let chan_id = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+		let deadlines =
+			|node: &Node| get_htlc_deadlines(node.node, &node.chain_monitor.chain_monitor);
+
+		assert!(deadlines(&nodes[0]).is_empty());
+		assert!(deadlines(&nodes[1]).is_empty());
+
+		route_payment(&nodes[0], &[&nodes[1]], 1_000_000);
+		let amount = Amount::from_sat(1_000);
+		let senders_deadlines = deadlines(&nodes[0]);
+		assert_eq!(senders_deadlines.len(), 1);
+		let (force_close_height, kind, channel_id, htlc_amount) = senders_deadlines[0];
+		assert_eq!(
+			(kind, channel_id, htlc_amount),
+			(DeadlineKind::ChannelForceClose, chan_id, amount)
+		);
+		let recipients_deadlines = deadlines(&nodes[1]);
+		assert_eq!(recipients_deadlines.len(), 1);
+		let (claim_height, kind, channel_id, htlc_amount) = recipients_deadlines[0];
+		assert_eq!(
+			(kind, channel_id, htlc_amount),
+			(DeadlineKind::InboundHtlcClaim, chan_id, amount)
+		);
+		assert_eq!(
+			force_close_height - LATENCY_GRACE_PERIOD_BLOCKS,
+			claim_height + HTLC_FAIL_BACK_BUFFER
+		);
+
+		// The sender only force-closes once the deadline is reached.
+		let current_height = nodes[0].best_block_info().1;
+		connect_blocks(&nodes[0], force_close_height - current_height - 1);
+		assert!(nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().is_empty());
+		assert_eq!(deadlines(&nodes[0])[0].0, force_close_height);
+
+		connect_blocks(&nodes[0], 1);
+		check_closed_broadcast!(nodes[0], true);
+		check_added_monitors(&nodes[0], 1);
+		let reason = ClosureReason::HTLCsTimedOut;
+		check_closed_event(&nodes[0], 1, reason, false, &[node_b_id], 100000);
+		nodes[0].tx_broadcaster.txn_broadcasted.lock().unwrap().clear();
+	}
+}
@@ -20,8 +20,10 @@ use crate::sign::{EntropySource, HTLCDescriptor, OutputSpender, PhantomKeysManag
 use crate::sign::{
 	NodeSigner, PeerStorageKey, Recipient, SignerProvider, SpendableOutputDescriptor,
 };
+use crate::util::message_signing::MessageSigningKeyId;
 use bitcoin;
 use bitcoin::absolute::LockTime;
+use bitcoin::psbt::Psbt;
 use bitcoin::secp256k1::All;
 use bitcoin::{secp256k1, ScriptBuf, Transaction, TxOut, Txid};
 use lightning_invoice::RawBolt11Invoice;
@@ -212,6 +214,8 @@ inner,
 	fn get_node_id(, recipient: Recipient) -> Result<PublicKey, ()>,
 	fn sign_gossip_message(, msg: UnsignedGossipMessage) -> Result<Signature, ()>,
 	fn sign_message(, msg: &[u8]) -> Result<String, ()>,
+	fn get_message_signing_pubkey(, key_id: MessageSigningKeyId) -> Result<PublicKey, ()>,
+	fn sign_message_with_derived_key(, msg: &[u8], key_id: MessageSigningKeyId) -> Result<String, ()>,
 	fn ecdh(, recipient: Recipient, other_key: &PublicKey, tweak: Option<&Scalar>) -> Result<SharedSecret, ()>,
 	fn sign_invoice(, invoice: &RawBolt11Invoice, recipient: Recipient) -> Result<RecoverableSignature, ()>,
 	fn sign_bolt12_invoice(,
@@ -242,7 +246,10 @@ delegate!(DynKeysInterface, OutputSpender, inner,
 		descriptors: &[&SpendableOutputDescriptor], outputs: Vec<TxOut>,
 		change_destination_script: ScriptBuf, feerate_sat_per_1000_weight: u32,
 		locktime: Option<LockTime>, secp_ctx: &Secp256k1<All>
-	) -> Result<Transaction, ()>
+	) -> Result<Transaction, ()>,
+	fn sign_spendable_outputs_psbt(,
+		descriptors: &[&SpendableOutputDescriptor], psbt: Psbt, secp_ctx: &Secp256k1<All>
+	) -> Result<Psbt, ()>
 );
 #[cfg(not(taproot))]
 /// A supertrait for all the traits that a keys interface implements
@@ -280,6 +287,8 @@ delegate!(DynPhantomKeysInterface, NodeSigner,
 	fn get_node_id(, recipient: Recipient) -> Result<PublicKey, ()>,
 	fn sign_gossip_message(, msg: UnsignedGossipMessage) -> Result<Signature, ()>,
 	fn sign_message(, msg: &[u8]) -> Result<String, ()>,
+	fn get_message_signing_pubkey(, key_id: MessageSigningKeyId) -> Result<PublicKey, ()>,
+	fn sign_message_with_derived_key(, msg: &[u8], key_id: MessageSigningKeyId) -> Result<String, ()>,
 	fn ecdh(, recipient: Recipient, other_key: &PublicKey, tweak: Option<&Scalar>) -> Result<SharedSecret, ()>,
 	fn sign_invoice(, invoice: &RawBolt11Invoice, recipient: Recipient) -> Result<RecoverableSignature, ()>,
 	fn sign_bolt12_invoice(, invoice: &crate::offers::invoice::UnsignedBolt12Invoice
@@ -321,7 +330,10 @@ delegate!(DynPhantomKeysInterface, OutputSpender, inner,
 		descriptors: &[&SpendableOutputDescriptor], outputs: Vec<TxOut>,
 		change_destination_script: ScriptBuf, feerate_sat_per_1000_weight: u32,
 		locktime: Option<LockTime>, secp_ctx: &Secp256k1<All>
-	) -> Result<Transaction, ()>
+	) -> Result<Transaction, ()>,
+	fn sign_spendable_outputs_psbt(,
+		descriptors: &[&SpendableOutputDescriptor], psbt: Psbt, secp_ctx: &Secp256k1<All>
+	) -> Result<Psbt, ()>
 );
 
 impl DynKeysInterfaceTrait for DynPhantomKeysInterface {}
@@ -69,6 +69,32 @@ pub enum APIError {
 		/// The incompatible shutdown script.
 		script: ShutdownScript,
 	},
+	/// The anchor channel reserve is insufficient to support an additional anchor channel, e.g.,
+	/// when accepting a zero-conf channel via
+	/// [`ChannelManager::accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve`].
+	///
+	/// Depositing additional on-chain funds into the wallet backing the reserve should resolve
+	/// this issue.
+	///
+	/// [`ChannelManager::accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve`]: crate::ln::channelmanager::ChannelManager::accept_inbound_channel_from_trusted_peer_0conf_with_anchor_reserve
+	InsufficientAnchorReserve {
+		/// A human-readable error message
+		err: String,
+	},
+	/// Cooperatively closing the channel requires a valid authorization, as configured via
+	/// [`ChannelManager::set_close_authorizer`], but none was provided, it was not signed by the
+	/// authorizer, or its nonce was already used.
+	///
+	/// Obtaining a fresh authorization signed via [`sign_close_authorization`] and providing it to
+	/// [`ChannelManager::close_channel_with_authorization`] should resolve this issue.
+	///
+	/// [`ChannelManager::set_close_authorizer`]: crate::ln::channelmanager::ChannelManager::set_close_authorizer
+	/// [`ChannelManager::close_channel_with_authorization`]: crate::ln::channelmanager::ChannelManager::close_channel_with_authorization
+	/// [`sign_close_authorization`]: crate::util::message_signing::sign_close_authorization
+	AuthorizationRequired {
+		/// A human-readable error message
+		err: String,
+	},
 }
 
 impl fmt::Debug for APIError {
@@ -86,6 +112,12 @@ impl fmt::Debug for APIError {
 			APIError::IncompatibleShutdownScript { ref script } => {
 				write!(f, "Provided a scriptpubkey format not accepted by peer: {}", script)
 			},
+			APIError::InsufficientAnchorReserve { ref err } => {
+				write!(f, "Insufficient anchor channel reserve: {}", err)
+			},
+			APIError::AuthorizationRequired { ref err } => {
+				write!(f, "Authorization required: {}", err)
+			},
 		}
 	}
 }
@@ -100,4 +132,6 @@ impl_writeable_tlv_based_enum_upgradable!(APIError,
 	(6, ChannelUnavailable) => { (0, err, required), },
 	(8, MonitorUpdateInProgress) => {},
 	(10, IncompatibleShutdownScript) => { (0, script, required), },
+	(12, InsufficientAnchorReserve) => { (0, err, required), },
+	(14, AuthorizationRequired) => { (0, err, required), },
 );
@@ -0,0 +1,198 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Object-safe facades over the read-mostly APIs of [`ChannelManager`] and [`ChainMonitor`].
+//!
+//! Both are generic over all of their dependencies, so utilities which only need to list channels
+//! or monitors otherwise have to repeat those type parameters and their bounds. Instead, such
+//! utilities can take a [`DynChannelManager`] or [`DynChainMonitor`], to which a reference to any
+//! [`ChannelManager`] or [`ChainMonitor`] coerces.
+//!
+//! [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+
+use crate::chain::chaininterface::{BroadcasterInterface, FeeEstimator};
+use crate::chain::chainmonitor::{ChainMonitor, MonitorSummary, Persist};
+use crate::chain::channelmonitor::Balance;
+use crate::chain::Filter;
+use crate::events::{EventHandler, EventsProvider};
+use crate::ln::channel_state::{ChannelDetails, PendingChannelNegotiation};
+use crate::ln::channelmanager::AChannelManager;
+use crate::ln::types::ChannelId;
+use crate::prelude::*;
+use crate::sign::ecdsa::EcdsaChannelSigner;
+use crate::sign::EntropySource;
+use crate::util::deadlines::DeadlineKind;
+use crate::util::logger::Logger;
+
+use bitcoin::secp256k1::PublicKey;
+use bitcoin::Amount;
+
+use core::ops::Deref;
+
+/// The read-mostly APIs of a [`ChannelManager`], in an object-safe form.
+///
+/// This is implemented for any [`AChannelManager`], and is usually used via [`DynChannelManager`].
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+pub trait ChannelManagerFacade {
+	/// See [`ChannelManager::get_our_node_id`].
+	///
+	/// [`ChannelManager::get_our_node_id`]: crate::ln::channelmanager::ChannelManager::get_our_node_id
+	fn get_our_node_id(&self) -> PublicKey;
+	/// See [`ChannelManager::list_channels`].
+	///
+	/// [`ChannelManager::list_channels`]: crate::ln::channelmanager::ChannelManager::list_channels
+	fn list_channels(&self) -> Vec<ChannelDetails>;
+	/// See [`ChannelManager::list_usable_channels`].
+	///
+	/// [`ChannelManager::list_usable_channels`]: crate::ln::channelmanager::ChannelManager::list_usable_channels
+	fn list_usable_channels(&self) -> Vec<ChannelDetails>;
+	/// See [`ChannelManager::list_pending_channel_negotiations`].
+	///
+	/// [`ChannelManager::list_pending_channel_negotiations`]: crate::ln::channelmanager::ChannelManager::list_pending_channel_negotiations
+	fn list_pending_channel_negotiations(&self) -> Vec<PendingChannelNegotiation>;
+	/// See [`ChannelManager::list_channels_requiring_anchor_reserves`].
+	///
+	/// [`ChannelManager::list_channels_requiring_anchor_reserves`]: crate::ln::channelmanager::ChannelManager::list_channels_requiring_anchor_reserves
+	fn list_channels_requiring_anchor_reserves(&self) -> Vec<ChannelId>;
+	/// See [`ChannelManager::get_htlc_deadlines`].
+	///
+	/// [`ChannelManager::get_htlc_deadlines`]: crate::ln::channelmanager::ChannelManager::get_htlc_deadlines
+	fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)>;
+	/// See [`ChannelManager::is_anchor_negotiation_enabled`].
+	///
+	/// [`ChannelManager::is_anchor_negotiation_enabled`]: crate::ln::channelmanager::ChannelManager::is_anchor_negotiation_enabled
+	fn is_anchor_negotiation_enabled(&self) -> bool;
+	/// See [`ChannelManager::set_anchor_negotiation_enabled`].
+	///
+	/// [`ChannelManager::set_anchor_negotiation_enabled`]: crate::ln::channelmanager::ChannelManager::set_anchor_negotiation_enabled
+	fn set_anchor_negotiation_enabled(&self, enabled: bool);
+	/// Processes pending events of the [`ChannelManager`] with `handler`, as
+	/// [`EventsProvider::process_pending_events`] does.
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	fn process_pending_events(&self, handler: &dyn EventHandler);
+}
+
+impl<CM: AChannelManager> ChannelManagerFacade for CM {
+	fn get_our_node_id(&self) -> PublicKey {
+		self.get_cm().get_our_node_id()
+	}
+
+	fn list_channels(&self) -> Vec<ChannelDetails> {
+		self.get_cm().list_channels()
+	}
+
+	fn list_usable_channels(&self) -> Vec<ChannelDetails> {
+		self.get_cm().list_usable_channels()
+	}
+
+	fn list_pending_channel_negotiations(&self) -> Vec<PendingChannelNegotiation> {
+		self.get_cm().list_pending_channel_negotiations()
+	}
+
+	fn list_channels_requiring_anchor_reserves(&self) -> Vec<ChannelId> {
+		self.get_cm().list_channels_requiring_anchor_reserves()
+	}
+
+	fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+		self.get_cm().get_htlc_deadlines()
+	}
+
+	fn is_anchor_negotiation_enabled(&self) -> bool {
+		self.get_cm().is_anchor_negotiation_enabled()
+	}
+
+	fn set_anchor_negotiation_enabled(&self, enabled: bool) {
+		self.get_cm().set_anchor_negotiation_enabled(enabled)
+	}
+
+	fn process_pending_events(&self, handler: &dyn EventHandler) {
+		EventsProvider::process_pending_events(self.get_cm(), handler)
+	}
+}
+
+/// A type-erased [`ChannelManager`], covering the APIs of [`ChannelManagerFacade`].
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+pub type DynChannelManager<'a> = dyn ChannelManagerFacade + 'a;
+
+/// The read-mostly APIs of a [`ChainMonitor`], in an object-safe form.
+///
+/// This is implemented for any [`ChainMonitor`], and is usually used via [`DynChainMonitor`].
+pub trait ChainMonitorFacade {
+	/// See [`ChainMonitor::list_monitors`].
+	fn list_monitors(&self) -> Vec<ChannelId>;
+	/// See [`ChainMonitor::list_monitor_summaries`].
+	fn list_monitor_summaries(&self) -> Vec<MonitorSummary>;
+	/// See [`ChainMonitor::for_each_monitor_summary`].
+	fn for_each_monitor_summary(&self, include_resolved: bool, f: &mut dyn FnMut(&MonitorSummary));
+	/// See [`ChainMonitor::count_monitors_matching`].
+	fn count_monitors_matching(
+		&self, include_resolved: bool, predicate: &mut dyn FnMut(&MonitorSummary) -> bool,
+	) -> usize;
+	/// See [`ChainMonitor::get_claimable_balances`].
+	fn get_claimable_balances(&self, ignored_channels: &[&ChannelDetails]) -> Vec<Balance>;
+	/// See [`ChainMonitor::get_htlc_deadlines`].
+	fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)>;
+	/// Processes pending events of the [`ChainMonitor`] with `handler`, as
+	/// [`EventsProvider::process_pending_events`] does.
+	fn process_pending_events(&self, handler: &dyn EventHandler);
+}
+
+impl<
+		ChannelSigner: EcdsaChannelSigner,
+		C: Deref,
+		T: Deref,
+		F: Deref,
+		L: Deref,
+		P: Deref,
+		ES: Deref,
+	> ChainMonitorFacade for ChainMonitor<ChannelSigner, C, T, F, L, P, ES>
+where
+	C::Target: Filter,
+	T::Target: BroadcasterInterface,
+	F::Target: FeeEstimator,
+	L::Target: Logger,
+	P::Target: Persist<ChannelSigner>,
+	ES::Target: EntropySource,
+{
+	fn list_monitors(&self) -> Vec<ChannelId> {
+		ChainMonitor::list_monitors(self)
+	}
+
+	fn list_monitor_summaries(&self) -> Vec<MonitorSummary> {
+		ChainMonitor::list_monitor_summaries(self)
+	}
+
+	fn for_each_monitor_summary(&self, include_resolved: bool, f: &mut dyn FnMut(&MonitorSummary)) {
+		ChainMonitor::for_each_monitor_summary(self, include_resolved, f)
+	}
+
+	fn count_monitors_matching(
+		&self, include_resolved: bool, predicate: &mut dyn FnMut(&MonitorSummary) -> bool,
+	) -> usize {
+		ChainMonitor::count_monitors_matching(self, include_resolved, predicate)
+	}
+
+	fn get_claimable_balances(&self, ignored_channels: &[&ChannelDetails]) -> Vec<Balance> {
+		ChainMonitor::get_claimable_balances(self, ignored_channels)
+	}
+
+	fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+		ChainMonitor::get_htlc_deadlines(self)
+	}
+
+	fn process_pending_events(&self, handler: &dyn EventHandler) {
+		EventsProvider::process_pending_events(self, handler)
+	}
+}
+
+/// A type-erased [`ChainMonitor`], covering the APIs of [`ChainMonitorFacade`].
+pub type DynChainMonitor<'a> = dyn ChainMonitorFacade + 'a;
@@ -0,0 +1,237 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Tracks a bounded history of fee rates to derive fee rate percentiles.
+//!
+//! The default [`AnchorChannelReserveContext::upper_bound_fee_rate`] is based on the fee rates
+//! observed on mainnet since 2019, which may not be representative of a given deployment. A
+//! [`FeeRateHistory`] can instead be fed with fee rate samples, e.g., from periodic
+//! [`FeeEstimator`] polls or median block fee rates, to derive an upper bound fee rate from a
+//! percentile of the observed fee rates. Such a percentile is also what a [`FeeEstimator`] should
+//! return for [`ConfirmationTarget::MaximumExogenousFeeReserve`].
+//!
+//! [`FeeEstimator`]: crate::chain::chaininterface::FeeEstimator
+//! [`ConfirmationTarget::MaximumExogenousFeeReserve`]: crate::chain::chaininterface::ConfirmationTarget::MaximumExogenousFeeReserve
+
+use crate::io;
+use crate::ln::msgs::DecodeError;
+use crate::prelude::*;
+use crate::util::anchor_channel_reserves::AnchorChannelReserveContext;
+use crate::util::ser::{Readable, Writeable, Writer};
+
+use bitcoin::FeeRate;
+
+/// A bounded history of fee rate samples, from which percentiles can be derived.
+///
+/// Once [`Self::max_samples`] samples have been added, each new sample evicts the oldest one.
+///
+/// The history can be persisted via its [`Writeable`] implementation to survive restarts.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FeeRateHistory {
+	max_samples: usize,
+	samples: VecDeque<FeeRate>,
+}
+
+impl FeeRateHistory {
+	/// Creates an empty history retaining up to `max_samples` samples, or one sample if
+	/// `max_samples` is zero.
+	pub fn new(max_samples: usize) -> Self {
+		let max_samples = max_samples.max(1);
+		Self { max_samples, samples: VecDeque::with_capacity(max_samples) }
+	}
+
+	/// Adds a fee rate sample, evicting the oldest sample if the history is full.
+	pub fn add_sample(&mut self, fee_rate: FeeRate) {
+		if self.samples.len() == self.max_samples {
+			self.samples.pop_front();
+		}
+		self.samples.push_back(fee_rate);
+	}
+
+	/// Returns the maximum number of samples retained.
+	pub fn max_samples(&self) -> usize {
+		self.max_samples
+	}
+
+	/// Returns the number of samples currently retained.
+	pub fn len(&self) -> usize {
+		self.samples.len()
+	}
+
+	/// Returns whether no samples have been added yet.
+	pub fn is_empty(&self) -> bool {
+		self.samples.is_empty()
+	}
+
+	/// Returns the given percentile of the retained samples using the nearest-rank method, i.e.,
+	/// the smallest sample such that at least `percentile` percent of the samples are less than or
+	/// equal to it, or `None` if the history is empty.
+	///
+	/// A `percentile` of zero returns the lowest sample and any `percentile` above 100 is treated
+	/// as 100, returning the highest sample.
+	pub fn percentile(&self, percentile: u8) -> Option<FeeRate> {
+		if self.samples.is_empty() {
+			return None;
+		}
+		let mut sorted_samples: Vec<FeeRate> = self.samples.iter().copied().collect();
+		sorted_samples.sort_unstable();
+		let percentile = percentile.min(100) as usize;
+		let rank = (percentile * sorted_samples.len() + 99) / 100;
+		Some(sorted_samples[rank.saturating_sub(1)])
+	}
+
+	/// Returns an [`AnchorChannelReserveContext`] using the given `percentile` of the retained
+	/// samples as its [`AnchorChannelReserveContext::upper_bound_fee_rate`], or `None` if the
+	/// history is empty.
+	///
+	/// The default context uses the 99th percentile of the median block fee rate.
+	pub fn to_reserve_context(
+		&self, percentile: u8, expected_accepted_htlcs: u16, taproot_wallet: bool,
+	) -> Option<AnchorChannelReserveContext> {
+		self.percentile(percentile).map(|upper_bound_fee_rate| AnchorChannelReserveContext {
+			upper_bound_fee_rate,
+			expected_accepted_htlcs,
+			taproot_wallet,
+			..AnchorChannelReserveContext::default()
+		})
+	}
+}
+
+impl Writeable for FeeRateHistory {
+	fn write<W: Writer>(&self, writer: &mut W) -> Result<(), io::Error> {
+		let max_samples = self.max_samples as u64;
+		let samples_sat_per_kwu: Vec<u64> =
+			self.samples.iter().map(|fee_rate| fee_rate.to_sat_per_kwu()).collect();
+		write_tlv_fields!(writer, {
+			(0, max_samples, required),
+			(2, samples_sat_per_kwu, required_vec),
+		});
+		Ok(())
+	}
+}
+
+impl Readable for FeeRateHistory {
+	fn read<R: io::Read>(reader: &mut R) -> Result<Self, DecodeError> {
+		let mut max_samples: u64 = 0;
+		let mut samples_sat_per_kwu: Vec<u64> = Vec::new();
+		read_tlv_fields!(reader, {
+			(0, max_samples, required),
+			(2, samples_sat_per_kwu, required_vec),
+		});
+		if max_samples == 0 || samples_sat_per_kwu.len() as u64 > max_samples {
+			return Err(DecodeError::InvalidValue);
+		}
+		let max_samples: usize = max_samples.try_into().map_err(|_| DecodeError::InvalidValue)?;
+		let samples = samples_sat_per_kwu.into_iter().map(FeeRate::from_sat_per_kwu).collect();
+		Ok(Self { max_samples, samples })
+	}
+}
+
+#[cfg(test)]
+mod test {
+	use super::*;
+	use crate::util::anchor_channel_reserves::{AnchorChannelType, ReservePolicy};
+
+	fn sat_per_vb(sat_per_vb: u64) -> FeeRate {
+		FeeRate::from_sat_per_kwu(sat_per_vb * 250)
+	}
+
+	#[test]
+	fn test_percentile() {
+		let mut history = FeeRateHistory::new(100);
+		assert!(history.is_empty());
+		assert_eq!(history.percentile(50), None);
+
+		// Feed 1 to 100 sat/vB in an order other than sorted.
+		for sample in (1..=100).rev() {
+			history.add_sample(sat_per_vb(sample));
+		}
+		assert_eq!(history.len(), 100);
+		assert_eq!(history.percentile(0), Some(sat_per_vb(1)));
+		assert_eq!(history.percentile(1), Some(sat_per_vb(1)));
+		assert_eq!(history.percentile(50), Some(sat_per_vb(50)));
+		assert_eq!(history.percentile(90), Some(sat_per_vb(90)));
+		assert_eq!(history.percentile(99), Some(sat_per_vb(99)));
+		assert_eq!(history.percentile(100), Some(sat_per_vb(100)));
+		assert_eq!(history.percentile(u8::MAX), Some(sat_per_vb(100)));
+
+		// With a skewed distribution of 90 samples at 2 sat/vB and 10 samples at 200 sat/vB, only
+		// percentiles above 90 reflect the spikes.
+		for sample in 0..100 {
+			history.add_sample(sat_per_vb(if sample % 10 == 0 { 200 } else { 2 }));
+		}
+		assert_eq!(history.percentile(50), Some(sat_per_vb(2)));
+		assert_eq!(history.percentile(90), Some(sat_per_vb(2)));
+		assert_eq!(history.percentile(91), Some(sat_per_vb(200)));
+	}
+
+	#[test]
+	fn test_evicts_oldest_samples() {
+		let mut history = FeeRateHistory::new(10);
+		for sample in 1..=20 {
+			history.add_sample(sat_per_vb(sample));
+		}
+		assert_eq!(history.len(), 10);
+		assert_eq!(history.percentile(0), Some(sat_per_vb(11)));
+		assert_eq!(history.percentile(50), Some(sat_per_vb(15)));
+		assert_eq!(history.percentile(100), Some(sat_per_vb(20)));
+
+		let mut history = FeeRateHistory::new(0);
+		assert_eq!(history.max_samples(), 1);
+		history.add_sample(sat_per_vb(1));
+		history.add_sample(sat_per_vb(2));
+		assert_eq!(history.len(), 1);
+		assert_eq!(history.percentile(0), Some(sat_per_vb(2)));
+	}
+
+	#[test]
+	fn test_to_reserve_context() {
+		let mut history = FeeRateHistory::new(100);
+		assert_eq!(history.to_reserve_context(99, 10, false), None);
+		for sample in 1..=100 {
+			history.add_sample(sat_per_vb(sample));
+		}
+		assert_eq!(
+			history.to_reserve_context(99, 5, true),
+			Some(AnchorChannelReserveContext {
+				upper_bound_fee_rate: sat_per_vb(99),
+				expected_accepted_htlcs: 5,
+				taproot_wallet: true,
+				reserve_policy: ReservePolicy::Forwarding,
+				channel_type: AnchorChannelType::AnchorsZeroFeeHtlcTx,
+				safety_margin_ppm: 1_000_000,
+				min_economical_htlc_value_sat: None,
+				max_htlc_value_in_flight_msat: None,
+				include_outbound_htlcs: false,
+				expected_offered_htlcs: 10,
+				min_confirmations: 1,
+				exclude_immature_coinbase: true,
+				commitment_weight_params: None,
+				include_justice_path: false,
+			})
+		);
+	}
+
+	#[test]
+	fn test_fee_rate_history_serialization() {
+		let mut history = FeeRateHistory::new(10);
+		let encoded = history.encode();
+		let decoded: FeeRateHistory = Readable::read(&mut &encoded[..]).unwrap();
+		assert_eq!(decoded, history);
+
+		for sample in 1..=15 {
+			history.add_sample(sat_per_vb(sample));
+		}
+		history.add_sample(FeeRate::MAX);
+		let encoded = history.encode();
+		let decoded: FeeRateHistory = Readable::read(&mut &encoded[..]).unwrap();
+		assert_eq!(decoded, history);
+		assert_eq!(decoded.percentile(50), history.percentile(50));
+	}
+}
@@ -0,0 +1,390 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! A framed serialization format from which data can be partially recovered after truncation or
+//! corruption.
+//!
+//! Framed data starts with a header identifying the format, its version and the type of the framed
+//! object, followed by a sequence of records and an integrity footer. Each record is prefixed with
+//! its length and followed by a checksum of its contents, allowing readers to detect the first
+//! damaged record and recover all records preceding it. The footer commits to the number of
+//! records and their checksums, distinguishing complete data from data truncated at a record
+//! boundary.
+//!
+//! This format is used by [`NetworkGraph::write_framed`] and [`ProbabilisticScorer::write_framed`],
+//! which can be read back with [`NetworkGraph::read_lossy`] and
+//! [`ProbabilisticScorer::read_lossy`], respectively.
+//!
+//! [`NetworkGraph::write_framed`]: crate::routing::gossip::NetworkGraph::write_framed
+//! [`NetworkGraph::read_lossy`]: crate::routing::gossip::NetworkGraph::read_lossy
+//! [`ProbabilisticScorer::write_framed`]: crate::routing::scoring::ProbabilisticScorer::write_framed
+//! [`ProbabilisticScorer::read_lossy`]: crate::routing::scoring::ProbabilisticScorer::read_lossy
+
+use bitcoin::hashes::sha256::Hash as Sha256;
+use bitcoin::hashes::{Hash, HashEngine};
+
+use crate::io::{self, Read};
+use crate::io_extras::{copy, sink};
+use crate::ln::msgs::DecodeError;
+use crate::prelude::*;
+use crate::util::ser::{Readable, Writeable, Writer};
+
+/// The magic bytes at the start of framed data.
+const FRAMED_MAGIC: [u8; 4] = *b"LDKF";
+
+const SERIALIZATION_VERSION: u8 = 1;
+const MIN_SERIALIZATION_VERSION: u8 = 1;
+
+/// The length prefix marking the integrity footer instead of a record.
+const FOOTER_MARKER: u32 = u32::MAX;
+
+/// Records longer than this are considered corrupted, bounding the memory allocated for a record
+/// whose length prefix was damaged.
+const MAX_RECORD_LEN: u32 = 1 << 24;
+
+/// The type of an object serialized in the framed format.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum FramedType {
+	NetworkGraph = 1,
+	ProbabilisticScorer = 2,
+}
+
+impl FramedType {
+	/// Returns the type of the object framed in `data`, if it starts with a valid framed header.
+	pub(crate) fn sniff(data: &[u8]) -> Option<Self> {
+		if data.len() < 7 || data[..4] != FRAMED_MAGIC {
+			return None;
+		}
+		match data[6] {
+			1 => Some(FramedType::NetworkGraph),
+			2 => Some(FramedType::ProbabilisticScorer),
+			_ => None,
+		}
+	}
+}
+
+/// The damage which prevented a lossy read from recovering all records of framed data.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FramedDataDamage {
+	/// The data ended before the integrity footer, e.g., as a write was interrupted.
+	Truncated,
+	/// A record or the integrity footer did not match its checksum.
+	Corrupted,
+	/// A record matched its checksum, but could not be decoded.
+	UndecodableRecord,
+}
+
+/// A report of how much data was recovered by a lossy read, such as [`NetworkGraph::read_lossy`].
+///
+/// [`NetworkGraph::read_lossy`]: crate::routing::gossip::NetworkGraph::read_lossy
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LossyReadReport {
+	/// The number of records which were read successfully.
+	pub records_recovered: u64,
+	/// The number of recovered records which were dropped nonetheless, as they depend on records
+	/// which were lost, e.g., channels of a [`NetworkGraph`] whose nodes were lost.
+	///
+	/// [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+	pub records_dropped: u64,
+	/// The number of bytes which were discarded, starting at the first damaged record.
+	pub bytes_discarded: u64,
+	/// The damage which prevented all records from being read, or `None` if the data was complete.
+	pub damage: Option<FramedDataDamage>,
+}
+
+impl LossyReadReport {
+	/// Returns whether all records were read and no records were dropped.
+	pub fn is_complete(&self) -> bool {
+		self.damage.is_none() && self.records_dropped == 0
+	}
+}
+
+fn record_checksum(record: &[u8]) -> [u8; 4] {
+	let hash = Sha256::hash(record);
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+fn footer_checksum(engine: &mut <Sha256 as Hash>::Engine, num_records: u64) -> [u8; 4] {
+	engine.input(&num_records.to_be_bytes());
+	let hash = Sha256::from_engine(engine.clone());
+	[hash[0], hash[1], hash[2], hash[3]]
+}
+
+/// Writes an object in the framed format, one record at a time.
+///
+/// [`FramedWriter::finish`] must be called after the last record to write the integrity footer.
+pub(crate) struct FramedWriter<'a, W: Writer> {
+	writer: &'a mut W,
+	num_records: u64,
+	checksums: <Sha256 as Hash>::Engine,
+}
+
+impl<'a, W: Writer> FramedWriter<'a, W> {
+	/// Writes the header for an object of the given type.
+	pub(crate) fn new(writer: &'a mut W, framed_type: FramedType) -> Result<Self, io::Error> {
+		writer.write_all(&FRAMED_MAGIC)?;
+		write_ver_prefix!(writer, SERIALIZATION_VERSION, MIN_SERIALIZATION_VERSION);
+		(framed_type as u8).write(writer)?;
+		Ok(Self { writer, num_records: 0, checksums: Sha256::engine() })
+	}
+
+	/// Writes the given record, prefixed with its length and followed by its checksum.
+	pub(crate) fn write_record<T: Writeable>(&mut self, record: &T) -> Result<(), io::Error> {
+		let record = record.encode();
+		if record.len() >= MAX_RECORD_LEN as usize {
+			return Err(io::Error::new(io::ErrorKind::InvalidInput, "Record too long"));
+		}
+		let checksum = record_checksum(&record);
+		(record.len() as u32).write(self.writer)?;
+		self.writer.write_all(&record)?;
+		self.writer.write_all(&checksum)?;
+		self.checksums.input(&checksum);
+		self.num_records += 1;
+		Ok(())
+	}
+
+	/// Writes the integrity footer, committing to all records written.
+	pub(crate) fn finish(mut self) -> Result<(), io::Error> {
+		FOOTER_MARKER.write(self.writer)?;
+		self.num_records.write(self.writer)?;
+		self.writer.write_all(&footer_checksum(&mut self.checksums, self.num_records))
+	}
+}
+
+/// Reads the records of an object written by a [`FramedWriter`], stopping at the first damage.
+pub(crate) struct FramedReader<'a, R: Read> {
+	reader: &'a mut R,
+	bytes_read: u64,
+	valid_bytes: u64,
+	num_records: u64,
+	checksums: <Sha256 as Hash>::Engine,
+	damage: Option<FramedDataDamage>,
+	done: bool,
+}
+
+impl<'a, R: Read> FramedReader<'a, R> {
+	/// Reads the header, failing if it doesn't indicate an object of the given type in a version
+	/// we understand.
+	pub(crate) fn new(reader: &'a mut R, framed_type: FramedType) -> Result<Self, DecodeError> {
+		let magic: [u8; 4] = Readable::read(reader)?;
+		if magic != FRAMED_MAGIC {
+			return Err(DecodeError::InvalidValue);
+		}
+		let _ver = read_ver_prefix!(reader, SERIALIZATION_VERSION);
+		let read_type: u8 = Readable::read(reader)?;
+		if read_type != framed_type as u8 {
+			return Err(DecodeError::InvalidValue);
+		}
+		Ok(Self {
+			reader,
+			bytes_read: 0,
+			valid_bytes: 0,
+			num_records: 0,
+			checksums: Sha256::engine(),
+			damage: None,
+			done: false,
+		})
+	}
+
+	fn read_exact(&mut self, buf: &mut [u8]) -> Result<(), FramedDataDamage> {
+		let mut filled = 0;
+		while filled < buf.len() {
+			match self.reader.read(&mut buf[filled..]) {
+				Ok(0) => {
+					self.bytes_read += filled as u64;
+					return Err(FramedDataDamage::Truncated);
+				},
+				Ok(len) => filled += len,
+				Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+				Err(_) => {
+					self.bytes_read += filled as u64;
+					return Err(FramedDataDamage::Truncated);
+				},
+			}
+		}
+		self.bytes_read += filled as u64;
+		Ok(())
+	}
+
+	fn read_footer(&mut self) -> Result<(), FramedDataDamage> {
+		let mut num_records = [0; 8];
+		self.read_exact(&mut num_records)?;
+		let mut checksum = [0; 4];
+		self.read_exact(&mut checksum)?;
+		let num_records = u64::from_be_bytes(num_records);
+		if num_records != self.num_records
+			|| checksum != footer_checksum(&mut self.checksums, num_records)
+		{
+			return Err(FramedDataDamage::Corrupted);
+		}
+		Ok(())
+	}
+
+	fn read_payload(&mut self) -> Result<Option<Vec<u8>>, FramedDataDamage> {
+		let mut len = [0; 4];
+		self.read_exact(&mut len)?;
+		let len = u32::from_be_bytes(len);
+		if len == FOOTER_MARKER {
+			self.read_footer()?;
+			return Ok(None);
+		}
+		if len >= MAX_RECORD_LEN {
+			return Err(FramedDataDamage::Corrupted);
+		}
+		let mut record = vec![0; len as usize];
+		self.read_exact(&mut record)?;
+		let mut checksum = [0; 4];
+		self.read_exact(&mut checksum)?;
+		if checksum != record_checksum(&record) {
+			return Err(FramedDataDamage::Corrupted);
+		}
+		self.checksums.input(&checksum);
+		Ok(Some(record))
+	}
+
+	/// Reads the next record using `decode`, returning `None` once the footer was read or the
+	/// data turned out to be damaged.
+	pub(crate) fn read_record<T, F>(&mut self, decode: F) -> Option<T>
+	where
+		F: FnOnce(&mut &[u8]) -> Result<T, DecodeError>,
+	{
+		if self.done {
+			return None;
+		}
+		let result = self.read_payload().and_then(|record| match record {
+			Some(record) => {
+				decode(&mut &record[..]).map(Some).map_err(|_| FramedDataDamage::UndecodableRecord)
+			},
+			None => Ok(None),
+		});
+		match result {
+			Ok(Some(record)) => {
+				self.num_records += 1;
+				self.valid_bytes = self.bytes_read;
+				Some(record)
+			},
+			Ok(None) => {
+				self.done = true;
+				None
+			},
+			Err(damage) => {
+				self.done = true;
+				self.damage = Some(damage);
+				None
+			},
+		}
+	}
+
+	/// Finishes reading, consuming the remainder of the reader if the data was damaged.
+	pub(crate) fn finish(self) -> LossyReadReport {
+		let mut bytes_discarded = 0;
+		if self.damage.is_some() {
+			let bytes_remaining = copy(self.reader, &mut sink()).unwrap_or(0);
+			bytes_discarded = self.bytes_read - self.valid_bytes + bytes_remaining;
+		}
+		LossyReadReport {
+			records_recovered: self.num_records,
+			records_dropped: 0,
+			bytes_discarded,
+			damage: self.damage,
+		}
+	}
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+	use crate::util::ser::VecWriter;
+	use core::cmp;
+
+	fn write_records(records: &[u64]) -> Vec<u8> {
+		let mut writer = VecWriter(Vec::new());
+		let mut framed_writer =
+			FramedWriter::new(&mut writer, FramedType::ProbabilisticScorer).unwrap();
+		for record in records {
+			framed_writer.write_record(record).unwrap();
+		}
+		framed_writer.finish().unwrap();
+		writer.0
+	}
+
+	fn read_records(mut data: &[u8]) -> Result<(Vec<u64>, LossyReadReport), DecodeError> {
+		let mut reader = FramedReader::new(&mut data, FramedType::ProbabilisticScorer)?;
+		let mut records = Vec::new();
+		while let Some(record) = reader.read_record(|r| u64::read(r)) {
+			records.push(record);
+		}
+		Ok((records, reader.finish()))
+	}
+
+	#[test]
+	fn reads_complete_data() {
+		let records = [1, 2, 3];
+		let data = write_records(&records);
+		let (read, report) = read_records(&data).unwrap();
+		assert_eq!(read, records);
+		assert!(report.is_complete());
+		assert_eq!(report.records_recovered, 3);
+		assert_eq!(report.bytes_discarded, 0);
+
+		let (read, report) = read_records(&write_records(&[])).unwrap();
+		assert!(read.is_empty());
+		assert!(report.is_complete());
+	}
+
+	#[test]
+	fn rejects_other_types_and_versions() {
+		let data = write_records(&[1]);
+		let mut reader = &data[..];
+		assert!(FramedReader::new(&mut reader, FramedType::NetworkGraph).is_err());
+
+		let mut data = data;
+		data[5] = SERIALIZATION_VERSION + 1;
+		assert_eq!(read_records(&data).unwrap_err(), DecodeError::UnknownVersion);
+		data[0] ^= 1;
+		assert_eq!(read_records(&data).unwrap_err(), DecodeError::InvalidValue);
+	}
+
+	#[test]
+	fn recovers_records_before_truncation() {
+		let records = [1, 2, 3];
+		let data = write_records(&records);
+		let header_len = 7;
+		let record_len = 4 + 8 + 4;
+		for len in header_len..data.len() {
+			let (read, report) = read_records(&data[..len]).unwrap();
+			let expected_records = cmp::min((len - header_len) / record_len, records.len());
+			assert_eq!(read, records[..expected_records]);
+			assert_eq!(report.records_recovered, expected_records as u64);
+			assert_eq!(report.damage, Some(FramedDataDamage::Truncated));
+			let valid_len = header_len + expected_records * record_len;
+			assert_eq!(report.bytes_discarded, (len - valid_len) as u64);
+		}
+	}
+
+	#[test]
+	fn recovers_records_before_corruption() {
+		let records = [1, 2, 3];
+		let data = write_records(&records);
+		let header_len = 7;
+		let record_len = 4 + 8 + 4;
+		for pos in header_len..data.len() {
+			for bit in 0..8 {
+				let mut corrupted = data.clone();
+				corrupted[pos] ^= 1 << bit;
+				let (read, report) = read_records(&corrupted).unwrap();
+				let damaged_record = cmp::min((pos - header_len) / record_len, records.len());
+				assert_eq!(read, records[..damaged_record]);
+				assert!(report.damage.is_some());
+				let valid_len = header_len + damaged_record * record_len;
+				assert_eq!(report.bytes_discarded, (data.len() - valid_len) as u64);
+			}
+		}
+	}
+}
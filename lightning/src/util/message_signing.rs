@@ -19,67 +19,366 @@
 //!
 //! <https://lightning.readthedocs.io/lightning-signmessage.7.html>
 //! <https://api.lightning.community/#signmessage>
+//!
+//! Recovery accepts signatures in both the canonical low-S and the malleated high-S form. To only
+//! accept the former, e.g., for signatures which are stored and re-verified by other secp256k1
+//! backends, [`recover_pk_strict`] may be used instead of [`recover_pk`].
+//!
+//! To recover or verify many signatures, e.g., node proofs at the scale of the network graph, a
+//! [`MessageVerifier`] avoids creating a secp256k1 context per signature.
+//!
+//! Failures to decode a signature or to recover its signer are reported as a
+//! [`MessageSigningError`], distinguishing, e.g., truncated or mistyped signatures from signatures
+//! which are well-formed but invalid.
+//!
+//! To verify signatures against node ids which aren't available as a [`PublicKey`], e.g., node ids
+//! given as hex strings or [`NodeId`]s of the network graph, [`verify_against`] and
+//! [`recover_node_id`] may be used instead of [`verify`] and [`recover_pk`].
+//!
+//! Messages may also be signed with a dedicated key derived by a [`NodeSigner`] rather than the
+//! node's secret via [`sign_with_derived_key`], e.g., for watch-only setups which don't expose the
+//! node key to their signer. Such signatures are verified the same way.
+//!
+//! lnd additionally supports a variant of the above which hashes the prefixed message with a
+//! single SHA-256 rather than with sha256d (`lncli signmessage --single_hash`). Signatures of the
+//! two schemes are not interchangeable, thus such signatures must be created and verified with the
+//! dedicated [`sign_single_hash`], [`recover_pk_single_hash`] and [`verify_single_hash`]. If it's
+//! unknown which scheme a signature uses, [`verify_any`] tries both.
+//!
+//! Additionally, [`sign_bip322`] and [`verify_bip322`] can be used to prove control over an
+//! on-chain address, e.g., one of the scripts provided by a [`SignerProvider`], using the
+//! "simple" signature format of [BIP 322].
+//!
+//! Finally, [`sign_payment_attestation`] and [`verify_payment_attestation`] can be used to
+//! attest to third parties that the node paid for a given payment hash, proven by its preimage,
+//! and [`sign_node_metrics_snapshot`] and [`verify_node_metrics_snapshot`] to report basic channel
+//! metrics of the node, e.g., to an LSP.
+//!
+//! Signatures used as, e.g., login tokens can be bound to an expiry via [`sign_with_expiry`] or
+//! [`sign_with_expiry_with_node_signer`], such that [`verify_with_expiry`] rejects them once they
+//! expired rather than accepting replays indefinitely.
+//!
+//! Arbitrary structured data can be signed as a TLV stream via [`sign_tlv`] or
+//! [`sign_tlv_with_node_signer`] and verified via [`verify_tlv`]. As fields are always encoded in
+//! ascending type order, signatures don't depend on the order in which fields were provided,
+//! avoiding the canonicalization issues of signing, e.g., JSON.
+//!
+//! To prove that the node created a given BOLT 12 `invoice_request`, e.g., in a dispute with a
+//! merchant, [`sign_payer_proof`] binds the request's payer id to the node id, which can be
+//! verified against the request via [`verify_payer_proof`].
+//!
+//! Routing fee policies can be distributed to partners out-of-band via [`sign_fee_policy`], akin
+//! to a `channel_update`, and verified via [`verify_fee_policy`].
+//!
+//! Cooperative closes of large channels may be required to be authorized by an offline key via
+//! [`ChannelManager::set_close_authorizer`], in which case a [`CloseAuthorization`] signed via
+//! [`sign_close_authorization`] must be provided to
+//! [`ChannelManager::close_channel_with_authorization`].
+//!
+//! Backups exported to untrusted storage, e.g., serialized `ChannelMonitor`s, can be wrapped via
+//! [`sign_backup`] such that [`verify_backup`] detects whether they were tampered with or
+//! truncated, or were created by another node.
+//!
+//! To check a signing setup, e.g., a remote [`NodeSigner`], [`self_test`] signs and verifies a
+//! test message and verifies signatures created by other implementations.
+//!
+//! For [LNURL-auth], [`derive_lnurl_auth_linking_key`] derives the per-domain linking key and
+//! [`sign_lnurl_auth_challenge`] signs a service's `k1` challenge with it. [`KeysManager`] provides
+//! both via [`KeysManager::sign_lnurl_auth_challenge`].
+//!
+//! [`NodeSigner`]: crate::sign::NodeSigner
+//! [`ChannelManager::set_close_authorizer`]: crate::ln::channelmanager::ChannelManager::set_close_authorizer
+//! [`ChannelManager::close_channel_with_authorization`]: crate::ln::channelmanager::ChannelManager::close_channel_with_authorization
+//! [`SignerProvider`]: crate::sign::SignerProvider
+//! [BIP 322]: https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki
+//! [LNURL-auth]: https://github.com/lnurl/luds/blob/luds/04.md
+//! [`KeysManager`]: crate::sign::KeysManager
+//! [`KeysManager::sign_lnurl_auth_challenge`]: crate::sign::KeysManager::sign_lnurl_auth_challenge
 
+use crate::ln::channel_state::ChannelDetails;
+use crate::ln::msgs::DecodeError;
+use crate::ln::types::ChannelId;
+use crate::offers::invoice_request::InvoiceRequest;
 #[allow(unused)]
 use crate::prelude::*;
-use crate::util::base32;
-use bitcoin::hashes::{sha256d, Hash};
+use crate::routing::gossip::{NetworkGraph, NodeAnnouncementInfo, NodeId};
+use crate::sign::{NodeSigner, Recipient};
+use crate::types::payment::{PaymentHash, PaymentPreimage};
+use crate::util::facade::DynChannelManager;
+use crate::util::logger::Logger;
+use crate::util::ser::{BigSize, Readable, Writeable};
+use crate::util::{base32, base64};
+use bech32::primitives::decode::CheckedHrpstring;
+use bech32::Bech32;
+use bitcoin::absolute::LockTime;
+use bitcoin::bip32::{ChildNumber, Xpriv};
+use bitcoin::consensus::encode;
+use bitcoin::hashes::hmac::{Hmac, HmacEngine};
+use bitcoin::hashes::{sha256, sha256d, Hash, HashEngine};
+use bitcoin::hex::{DisplayHex, FromHex};
+use bitcoin::key::{CompressedPublicKey, TapTweak};
+use bitcoin::opcodes;
+use bitcoin::script::{Builder, Script, ScriptBuf};
 use bitcoin::secp256k1::ecdsa::{RecoverableSignature, RecoveryId};
-use bitcoin::secp256k1::{Error, Message, PublicKey, Secp256k1, SecretKey};
+use bitcoin::secp256k1::{
+	Error, Keypair, Message, PublicKey, Secp256k1, SecretKey, Signing, Verification, VerifyOnly,
+	XOnlyPublicKey,
+};
+use bitcoin::sighash::{EcdsaSighashType, Prevouts, SighashCache, TapSighashType};
+use bitcoin::transaction::Version;
+use bitcoin::{
+	ecdsa, taproot, Amount, OutPoint, Sequence, Transaction, TxIn, TxOut, Txid, Witness,
+};
+use core::fmt;
+use core::ops::Deref;
 
 static LN_MESSAGE_PREFIX: &[u8] = b"Lightning Signed Message:";
 
-fn sigrec_encode(sig_rec: RecoverableSignature) -> Vec<u8> {
+/// The maximum length of a zbase32-encoded signature accepted by [`recover_pk`] and [`verify`],
+/// i.e., the length of the encoding of a 65-byte recoverable signature.
+///
+/// Longer strings are rejected before being decoded, such that signatures provided by untrusted
+/// callers can't cause large allocations.
+pub const MAX_SIGNATURE_LENGTH: usize = 104;
+
+/// The tag of the tagged hash of messages signed via [`sign_bip322`].
+static BIP322_MESSAGE_TAG: &[u8] = b"BIP0322-signed-message";
+
+/// The prefix of the messages signed via [`sign_payment_attestation`], such that attestations
+/// can't be confused with other messages signed by the node.
+static PAYMENT_ATTESTATION_PREFIX: &[u8] = b"Lightning Payment Attestation:";
+
+/// The prefix of the messages signed via [`sign_node_metrics_snapshot`], such that snapshots
+/// can't be confused with other messages signed by the node.
+static NODE_METRICS_SNAPSHOT_PREFIX: &[u8] = b"Lightning Node Metrics Snapshot:";
+
+/// The prefix of the messages signed via [`sign_tlv`], such that TLV envelopes can't be confused
+/// with other messages signed by the node.
+static TLV_ENVELOPE_PREFIX: &[u8] = b"Lightning Signed TLV Envelope:";
+
+/// The prefix of the messages signed via [`sign_payer_proof`], such that payer proofs can't be
+/// confused with other messages signed by the node.
+static PAYER_PROOF_PREFIX: &[u8] = b"Lightning BOLT 12 Payer Proof:";
+
+/// The prefix of the messages signed via [`sign_fee_policy`], such that fee policies can't be
+/// confused with other messages signed by the node.
+static FEE_POLICY_PREFIX: &[u8] = b"Lightning Fee Policy Attestation:";
+
+/// The prefix of the messages signed via [`sign_close_authorization`], such that authorizations
+/// can't be confused with other messages signed by the same key.
+static CLOSE_AUTHORIZATION_PREFIX: &[u8] = b"Lightning Cooperative Close Authorization:";
+
+/// The prefix of the messages signed via [`sign_backup`], such that backups can't be confused
+/// with other messages signed by the node.
+static SIGNED_BACKUP_PREFIX: &[u8] = b"Lightning Signed Backup:";
+
+/// An error returned when decoding a message signature or recovering its signer, e.g., via
+/// [`recover_pk`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageSigningError {
+	/// The signature is not valid zbase32.
+	InvalidZbase32 {
+		/// The byte offset of the first character outside of the zbase32 alphabet, or of the last
+		/// character if it is superfluous or sets bits beyond the encoded data.
+		position: usize,
+	},
+	/// The signature does not consist of 65 bytes, i.e., the recovery prefix followed by the
+	/// 64-byte compact signature.
+	InvalidLength {
+		/// The number of bytes the signature consists of.
+		got: usize,
+	},
+	/// The recovery prefix of the signature is not in the range of 27 to 34.
+	InvalidRecoveryId,
+	/// The compact signature is invalid, no public key can be recovered from it, or it was
+	/// rejected for not being in low-S form by [`recover_pk_strict`].
+	InvalidSignature,
+	/// Any other error returned by secp256k1.
+	Secp(Error),
+}
+
+impl From<Error> for MessageSigningError {
+	fn from(e: Error) -> Self {
+		match e {
+			Error::InvalidRecoveryId => MessageSigningError::InvalidRecoveryId,
+			Error::InvalidSignature => MessageSigningError::InvalidSignature,
+			_ => MessageSigningError::Secp(e),
+		}
+	}
+}
+
+impl fmt::Display for MessageSigningError {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			MessageSigningError::InvalidZbase32 { position } => {
+				write!(f, "Invalid zbase32 encoding at position {}", position)
+			},
+			MessageSigningError::InvalidLength { got } => {
+				write!(f, "Invalid signature length: expected 65 bytes, got {}", got)
+			},
+			MessageSigningError::InvalidRecoveryId => f.write_str("Invalid recovery id"),
+			MessageSigningError::InvalidSignature => f.write_str("Invalid signature"),
+			MessageSigningError::Secp(e) => write!(f, "secp256k1 error: {}", e),
+		}
+	}
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MessageSigningError {}
+
+fn sigrec_encode(sig_rec: RecoverableSignature) -> [u8; 65] {
 	let (rid, rsig) = sig_rec.serialize_compact();
 	let prefix = rid.to_i32() as u8 + 31;
 
-	[&[prefix], &rsig[..]].concat()
+	let mut res = [0; 65];
+	res[0] = prefix;
+	res[1..].copy_from_slice(&rsig);
+	res
 }
 
-fn sigrec_decode(sig_rec: Vec<u8>) -> Result<RecoverableSignature, Error> {
-	// Signature must be 64 + 1 bytes long (compact signature + recovery id)
-	if sig_rec.len() != 65 {
-		return Err(Error::InvalidSignature);
-	}
+/// Parses a raw recoverable signature, i.e., the recovery prefix followed by the 64-byte compact
+/// signature, e.g., as returned by [`sign_to_bytes`].
+///
+/// Recovery prefixes of both 27 to 30 and 31 to 34 are accepted. Returns
+/// [`MessageSigningError::InvalidLength`] if `sig_rec` is not 65 bytes long.
+pub fn decode_recoverable_signature(
+	sig_rec: &[u8],
+) -> Result<RecoverableSignature, MessageSigningError> {
+	let sig_rec: &[u8; 65] = sig_rec
+		.try_into()
+		.map_err(|_| MessageSigningError::InvalidLength { got: sig_rec.len() })?;
+	sigrec_decode(sig_rec)
+}
 
+fn sigrec_decode(sig_rec: &[u8; 65]) -> Result<RecoverableSignature, MessageSigningError> {
 	let rsig = &sig_rec[1..];
-	let rid = sig_rec[0] as i32 - 31;
+	// The prefix is 27 + recovery id for signatures by uncompressed keys and 31 + recovery id for
+	// signatures by compressed keys. Like lnd, we accept both and always recover compressed keys.
+	let rid = match sig_rec[0] {
+		27..=30 => sig_rec[0] as i32 - 27,
+		31..=34 => sig_rec[0] as i32 - 31,
+		_ => return Err(MessageSigningError::InvalidRecoveryId),
+	};
 
-	match RecoveryId::from_i32(rid) {
-		Ok(x) => RecoverableSignature::from_compact(rsig, x),
-		Err(e) => Err(e),
-	}
+	let rid = RecoveryId::from_i32(rid).map_err(|_| MessageSigningError::InvalidRecoveryId)?;
+	Ok(RecoverableSignature::from_compact(rsig, rid)?)
 }
 
 /// Creates a digital signature of a message given a SecretKey, like the node's secret.
 /// A receiver knowing the PublicKey (e.g. the node's id) and the message can be sure that the signature was generated by the caller.
 /// Signatures are EC recoverable, meaning that given the message and the signature the PublicKey of the signer can be extracted.
+///
+/// The signature is always in canonical low-S form, as libsecp256k1 normalizes the signatures it
+/// creates, and is thus also accepted by [`recover_pk_strict`].
 pub fn sign(msg: &[u8], sk: &SecretKey) -> String {
-	let secp_ctx = Secp256k1::signing_only();
-	let msg_hash = sha256d::Hash::hash(&[LN_MESSAGE_PREFIX, msg].concat());
+	base32::Alphabet::ZBase32.encode(&sign_to_bytes(msg, sk))
+}
 
-	let sig = secp_ctx.sign_ecdsa_recoverable(&Message::from_digest(msg_hash.to_byte_array()), sk);
-	base32::Alphabet::ZBase32.encode(&sigrec_encode(sig))
+/// Creates a digital signature of a message given a SecretKey like [`sign`], but returns the raw
+/// 65-byte recoverable signature, i.e., the recovery prefix followed by the compact signature,
+/// rather than its zbase32 encoding.
+pub fn sign_to_bytes(msg: &[u8], sk: &SecretKey) -> [u8; 65] {
+	sign_digest(message_digest(msg), sk)
 }
 
-/// Recovers the PublicKey of the signer of the message given the message and the signature.
-pub fn recover_pk(msg: &[u8], sig: &str) -> Result<PublicKey, Error> {
-	let secp_ctx = Secp256k1::verification_only();
-	let msg_hash = sha256d::Hash::hash(&[LN_MESSAGE_PREFIX, msg].concat());
+/// Computes the sha256d of the prefixed message, streaming the prefix and the message into the
+/// hash engine rather than concatenating them, such that no allocation is required.
+fn message_digest(msg: &[u8]) -> [u8; 32] {
+	let mut engine = sha256d::Hash::engine();
+	engine.input(LN_MESSAGE_PREFIX);
+	engine.input(msg);
+	sha256d::Hash::from_engine(engine).to_byte_array()
+}
+
+fn sign_digest(msg_hash: [u8; 32], sk: &SecretKey) -> [u8; 65] {
+	let secp_ctx = Secp256k1::signing_only();
+	let sig = secp_ctx.sign_ecdsa_recoverable(&Message::from_digest(msg_hash), sk);
+	sigrec_encode(sig)
+}
 
+fn decode_zbase32_sig(sig: &str) -> Result<[u8; 65], MessageSigningError> {
+	if sig.len() > MAX_SIGNATURE_LENGTH {
+		// Report the number of bytes the signature would decode to without decoding it.
+		return Err(MessageSigningError::InvalidLength { got: sig.len() * 5 / 8 });
+	}
 	match base32::Alphabet::ZBase32.decode(&sig) {
-		Ok(sig_rec) => match sigrec_decode(sig_rec) {
-			Ok(sig) => {
-				secp_ctx.recover_ecdsa(&Message::from_digest(msg_hash.to_byte_array()), &sig)
-			},
-			Err(e) => Err(e),
+		// Signature must be 64 + 1 bytes long (compact signature + recovery id)
+		Ok(sig_rec) => {
+			let got = sig_rec.len();
+			sig_rec.try_into().map_err(|_| MessageSigningError::InvalidLength { got })
 		},
-		Err(_) => Err(Error::InvalidSignature),
+		// Strings consisting of valid characters only fail to decode due to their last character.
+		Err(()) => Err(MessageSigningError::InvalidZbase32 {
+			position: base32::Alphabet::ZBase32
+				.find_invalid_char(sig)
+				.unwrap_or(sig.len().saturating_sub(1)),
+		}),
+	}
+}
+
+/// Recovers the PublicKey of the signer of the message given the message and the signature.
+///
+/// This creates a new secp256k1 context on each call, which dominates the cost of recovery. When
+/// recovering or verifying many signatures, a [`MessageVerifier`] should be used instead.
+pub fn recover_pk(msg: &[u8], sig: &str) -> Result<PublicKey, MessageSigningError> {
+	recover_pk_with_secp_ctx(&Secp256k1::verification_only(), msg, sig)
+}
+
+/// Recovers the PublicKey of the signer of the message given the message and the signature, like
+/// [`recover_pk`], but using the given `secp_ctx` rather than creating a new one.
+///
+/// This avoids the cost of creating a context when recovering many signatures.
+pub fn recover_pk_with_secp_ctx<C: Verification>(
+	secp_ctx: &Secp256k1<C>, msg: &[u8], sig: &str,
+) -> Result<PublicKey, MessageSigningError> {
+	recover_pk_from_digest(secp_ctx, message_digest(msg), &decode_zbase32_sig(sig)?)
+}
+
+/// Recovers the PublicKey of the signer of the message given the message and the raw 65-byte
+/// recoverable signature, e.g., as returned by [`sign_to_bytes`] or hex-decoded from the output of
+/// other tools.
+///
+/// Recovery prefixes of both 27 to 30 and 31 to 34 are accepted, with the compressed key being
+/// recovered in either case.
+pub fn recover_pk_from_bytes(msg: &[u8], sig: &[u8; 65]) -> Result<PublicKey, MessageSigningError> {
+	recover_pk_from_digest(&Secp256k1::verification_only(), message_digest(msg), sig)
+}
+
+/// Recovers the PublicKey of the signer of the message given the message and the signature, like
+/// [`recover_pk`], but rejects signatures whose S value is not in the lower half of the curve
+/// order with [`MessageSigningError::InvalidSignature`].
+///
+/// Each signature has a malleated high-S counterpart recovering the same PublicKey, which
+/// backends enforcing the canonical low-S form reject. Accepting only low-S signatures ensures
+/// that signatures stored for later re-verification are accepted by any backend. Signatures
+/// created by [`sign`], lnd and CLN are always low-S.
+pub fn recover_pk_strict(msg: &[u8], sig: &str) -> Result<PublicKey, MessageSigningError> {
+	let sig = decode_zbase32_sig(sig)?;
+	if !is_low_s(&sigrec_decode(&sig)?) {
+		return Err(MessageSigningError::InvalidSignature);
 	}
+	recover_pk_from_digest(&Secp256k1::verification_only(), message_digest(msg), &sig)
+}
+
+fn is_low_s(sig: &RecoverableSignature) -> bool {
+	let sig = sig.to_standard();
+	let mut normalized_sig = sig;
+	normalized_sig.normalize_s();
+	normalized_sig == sig
+}
+
+fn recover_pk_from_digest<C: Verification>(
+	secp_ctx: &Secp256k1<C>, msg_hash: [u8; 32], sig: &[u8; 65],
+) -> Result<PublicKey, MessageSigningError> {
+	let sig = sigrec_decode(sig)?;
+	Ok(secp_ctx.recover_ecdsa(&Message::from_digest(msg_hash), &sig)?)
 }
 
 /// Verifies a message was signed by a PrivateKey that derives to a given PublicKey, given a message, a signature,
 /// and the PublicKey.
+///
+/// Like [`recover_pk`], this creates a new secp256k1 context on each call. When verifying many
+/// signatures, a [`MessageVerifier`] should be used instead.
 pub fn verify(msg: &[u8], sig: &str, pk: &PublicKey) -> bool {
 	match recover_pk(msg, sig) {
 		Ok(x) => x == *pk,
@@ -87,64 +386,2900 @@ pub fn verify(msg: &[u8], sig: &str, pk: &PublicKey) -> bool {
 	}
 }
 
-#[cfg(test)]
-mod test {
-	use crate::util::message_signing::{recover_pk, sign, verify};
-	use bitcoin::secp256k1::constants::ONE;
-	use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
-	use core::str::FromStr;
+/// Recovers and verifies message signatures like [`recover_pk`] and [`verify`], but reuses a
+/// single secp256k1 context across calls.
+///
+/// Creating a context is considerably more expensive than recovering a signature, thus this
+/// should be used for workloads verifying many signatures, e.g., crawlers verifying node proofs
+/// at the scale of the network graph. Its results are identical to those of the free functions.
+///
+/// The message digest is computed by streaming the message into the hash engine, such that no
+/// buffers are required for it, leaving the decoding of the zbase32 signature as the only
+/// allocation per signature.
+pub struct MessageVerifier {
+	secp_ctx: Secp256k1<VerifyOnly>,
+}
 
-	#[test]
-	fn test_sign() {
-		let message = "test message";
-		let one_key = SecretKey::from_slice(&ONE).unwrap();
-		let zbase32_sig = sign(message.as_bytes(), &one_key);
+impl MessageVerifier {
+	/// Creates a new verifier, including its secp256k1 context.
+	pub fn new() -> Self {
+		Self { secp_ctx: Secp256k1::verification_only() }
+	}
 
-		assert_eq!(zbase32_sig, "d9tibmnic9t5y41hg7hkakdcra94akas9ku3rmmj4ag9mritc8ok4p5qzefs78c9pqfhpuftqqzhydbdwfg7u6w6wdxcqpqn4sj4e73e")
+	/// Recovers the PublicKey of the signer of the message given the message and the signature,
+	/// like [`recover_pk`].
+	pub fn recover_pk(&self, msg: &[u8], sig: &str) -> Result<PublicKey, MessageSigningError> {
+		recover_pk_with_secp_ctx(&self.secp_ctx, msg, sig)
 	}
 
-	#[test]
-	fn test_recover_pk() {
-		let message = "test message";
-		let one_key = SecretKey::from_slice(&ONE).unwrap();
-		let sig = "d9tibmnic9t5y41hg7hkakdcra94akas9ku3rmmj4ag9mritc8ok4p5qzefs78c9pqfhpuftqqzhydbdwfg7u6w6wdxcqpqn4sj4e73e";
-		let pk = recover_pk(message.as_bytes(), sig);
+	/// Verifies a message was signed by the given PublicKey, like [`verify`].
+	pub fn verify(&self, msg: &[u8], sig: &str, pk: &PublicKey) -> bool {
+		match self.recover_pk(msg, sig) {
+			Ok(x) => x == *pk,
+			Err(_) => false,
+		}
+	}
 
-		assert_eq!(pk.unwrap(), PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key))
+	/// Verifies a batch of messages, signatures and PublicKeys, returning whether each of them
+	/// verified, in the order of `items`, like calling [`verify`] for each of them.
+	pub fn verify_batch(&self, items: &[(&[u8], &str, &PublicKey)]) -> Vec<bool> {
+		items.iter().map(|(msg, sig, pk)| self.verify(msg, sig, pk)).collect()
 	}
+}
 
-	#[test]
-	fn test_verify() {
-		let message = "another message";
-		let one_key = SecretKey::from_slice(&ONE).unwrap();
-		let sig = sign(message.as_bytes(), &one_key);
-		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+impl Default for MessageVerifier {
+	fn default() -> Self {
+		Self::new()
+	}
+}
 
-		assert!(verify(message.as_bytes(), &sig, &pk))
+/// A reference to a node id in one of the encodings accepted by [`verify_against`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeIdRef<'a> {
+	/// A node id given as a [`PublicKey`].
+	PublicKey(&'a PublicKey),
+	/// A node id given as a [`NodeId`], e.g., as found in the [`NetworkGraph`].
+	NodeId(&'a NodeId),
+	/// A node id given as a string, either hex-encoded or encoded as [BIP 173] bech32 with any
+	/// human-readable part.
+	///
+	/// [BIP 173]: https://github.com/bitcoin/bips/blob/master/bip-0173.mediawiki
+	Str(&'a str),
+}
+
+impl<'a> From<&'a PublicKey> for NodeIdRef<'a> {
+	fn from(pubkey: &'a PublicKey) -> Self {
+		NodeIdRef::PublicKey(pubkey)
 	}
+}
 
-	#[test]
-	fn test_verify_ground_truth_ish() {
-		// There are no standard tests vectors for Sign/Verify, using the same tests vectors as c-lightning to see if they are compatible.
-		// Taken from https://github.com/ElementsProject/lightning/blob/1275af6fbb02460c8eb2f00990bb0ef9179ce8f3/tests/test_misc.py#L1925-L1938
+impl<'a> From<&'a NodeId> for NodeIdRef<'a> {
+	fn from(node_id: &'a NodeId) -> Self {
+		NodeIdRef::NodeId(node_id)
+	}
+}
 
-		let corpus = [
-			["@bitconner",
-			"is this compatible?",
-			"rbgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
-			"02b80cabdf82638aac86948e4c06e82064f547768dcef977677b9ea931ea75bab5"],
-			["@duck1123",
-			"hi",
-			"rnrphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
-			"02de60d194e1ca5947b59fe8e2efd6aadeabfb67f2e89e13ae1a799c1e08e4a43b"],
-			["@jochemin",
-			"hi",
-			"ry8bbsopmduhxy3dr5d9ekfeabdpimfx95kagdem7914wtca79jwamtbw4rxh69hg7n6x9ty8cqk33knbxaqftgxsfsaeprxkn1k48p3",
-			"022b8ece90ee891cbcdac0c1cc6af46b73c47212d8defbce80265ac81a6b794931"],
-		];
+impl<'a> From<&'a str> for NodeIdRef<'a> {
+	fn from(node_id: &'a str) -> Self {
+		NodeIdRef::Str(node_id)
+	}
+}
 
-		for c in &corpus {
-			assert!(verify(c[1].as_bytes(), c[2], &PublicKey::from_str(c[3]).unwrap()))
+impl<'a> From<&'a String> for NodeIdRef<'a> {
+	fn from(node_id: &'a String) -> Self {
+		NodeIdRef::Str(node_id.as_str())
+	}
+}
+
+impl NodeIdRef<'_> {
+	/// Returns the referenced node id as a [`NodeId`], parsing it first if it was given as a
+	/// string.
+	pub fn to_node_id(self) -> Result<NodeId, NodeIdParseError> {
+		match self {
+			NodeIdRef::PublicKey(pubkey) => Ok(NodeId::from_pubkey(pubkey)),
+			NodeIdRef::NodeId(node_id) => Ok(*node_id),
+			NodeIdRef::Str(node_id) => {
+				let bytes: Vec<u8> = if let Ok(bytes) = Vec::<u8>::from_hex(node_id) {
+					bytes
+				} else if let Ok(parsed) = CheckedHrpstring::new::<Bech32>(node_id) {
+					parsed.byte_iter().collect()
+				} else {
+					return Err(NodeIdParseError::InvalidEncoding);
+				};
+				let node_id =
+					NodeId::from_slice(&bytes).map_err(|_| NodeIdParseError::InvalidLength)?;
+				node_id.as_pubkey().map_err(|_| NodeIdParseError::InvalidPublicKey)?;
+				Ok(node_id)
+			},
 		}
 	}
 }
+
+/// An error returned when parsing a node id given as a string via [`NodeIdRef::Str`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum NodeIdParseError {
+	/// The node id is neither valid hex nor valid bech32.
+	InvalidEncoding,
+	/// The node id doesn't encode 33 bytes, e.g., because its leading `02` or `03` byte is missing.
+	InvalidLength,
+	/// The node id encodes 33 bytes which aren't a valid compressed public key, e.g., because its
+	/// leading byte is neither `02` nor `03`.
+	InvalidPublicKey,
+}
+
+/// Verifies a message was signed by the node with the given node id, like [`verify`], but accepts
+/// the node id as a [`PublicKey`], a [`NodeId`], or a hex or bech32 string.
+///
+/// Returns `Err` if the node id is malformed and `Ok(false)` if the signature is invalid or was
+/// not created by the given node.
+pub fn verify_against<'a, N: Into<NodeIdRef<'a>>>(
+	msg: &[u8], sig: &str, node_id: N,
+) -> Result<bool, NodeIdParseError> {
+	let node_id = node_id.into().to_node_id()?;
+	match recover_node_id(msg, sig) {
+		Ok(signer_node_id) => Ok(signer_node_id == node_id),
+		Err(_) => Ok(false),
+	}
+}
+
+/// Recovers the node id of the signer of the message given the message and the signature, like
+/// [`recover_pk`], but returns it as a [`NodeId`], e.g., to look the node up in the
+/// [`NetworkGraph`].
+pub fn recover_node_id(msg: &[u8], sig: &str) -> Result<NodeId, MessageSigningError> {
+	recover_pk(msg, sig).map(|pk| NodeId::from_pubkey(&pk))
+}
+
+/// Creates a digital signature of a message given a SecretKey using lnd's single-hash scheme,
+/// i.e., signing the SHA-256 rather than the sha256d of the prefixed message, as done by
+/// `lncli signmessage --single_hash`.
+///
+/// The result is encoded like the signatures created via [`sign`], but can only be verified via
+/// [`recover_pk_single_hash`] and [`verify_single_hash`] (or [`verify_any`]). Unless a verifier
+/// specifically requires this scheme, use [`sign`] instead.
+pub fn sign_single_hash(msg: &[u8], sk: &SecretKey) -> String {
+	let msg_hash = sha256::Hash::hash(&[LN_MESSAGE_PREFIX, msg].concat());
+	base32::Alphabet::ZBase32.encode(&sign_digest(msg_hash.to_byte_array(), sk))
+}
+
+/// Recovers the PublicKey of the signer of the message given the message and a signature created
+/// using lnd's single-hash scheme, e.g., via [`sign_single_hash`].
+///
+/// Note that recovering a public key from a signature of the other scheme will generally succeed,
+/// but yield an unrelated public key.
+pub fn recover_pk_single_hash(msg: &[u8], sig: &str) -> Result<PublicKey, MessageSigningError> {
+	let msg_hash = sha256::Hash::hash(&[LN_MESSAGE_PREFIX, msg].concat());
+	let secp_ctx = Secp256k1::verification_only();
+	recover_pk_from_digest(&secp_ctx, msg_hash.to_byte_array(), &decode_zbase32_sig(sig)?)
+}
+
+/// Verifies a message was signed by a PrivateKey that derives to a given PublicKey using lnd's
+/// single-hash scheme, e.g., via [`sign_single_hash`].
+///
+/// Signatures created via [`sign`] will fail verification, see [`verify`] for those.
+pub fn verify_single_hash(msg: &[u8], sig: &str, pk: &PublicKey) -> bool {
+	match recover_pk_single_hash(msg, sig) {
+		Ok(x) => x == *pk,
+		Err(_) => false,
+	}
+}
+
+/// The scheme with which a message signature was created, as returned by [`verify_any`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub enum MessageSignatureScheme {
+	/// The default scheme signing the sha256d of the prefixed message, as created via [`sign`] and
+	/// verified via [`verify`].
+	DoubleHash,
+	/// lnd's single-hash scheme signing the SHA-256 of the prefixed message, as created via
+	/// [`sign_single_hash`] and verified via [`verify_single_hash`].
+	SingleHash,
+}
+
+/// Verifies a message was signed by a PrivateKey that derives to a given PublicKey using either
+/// the default scheme (see [`verify`]) or lnd's single-hash scheme (see [`verify_single_hash`]),
+/// returning the scheme which matched or `None` if the signature is invalid under both.
+///
+/// This should only be used if the scheme used by the signer is unknown.
+pub fn verify_any(msg: &[u8], sig: &str, pk: &PublicKey) -> Option<MessageSignatureScheme> {
+	if verify(msg, sig, pk) {
+		Some(MessageSignatureScheme::DoubleHash)
+	} else if verify_single_hash(msg, sig, pk) {
+		Some(MessageSignatureScheme::SingleHash)
+	} else {
+		None
+	}
+}
+
+/// The separator between the expiry and the signature of a token created via
+/// [`sign_with_expiry`].
+const EXPIRING_TOKEN_SEPARATOR: char = ':';
+
+fn expiring_msg(msg: &[u8], expiry_unix_secs: u64) -> Vec<u8> {
+	[&expiry_unix_secs.to_be_bytes()[..], msg].concat()
+}
+
+fn expiring_token(expiry_unix_secs: u64, sig: &str) -> String {
+	format!("{:016x}{}{}", expiry_unix_secs, EXPIRING_TOKEN_SEPARATOR, sig)
+}
+
+/// Creates a token for a message given a SecretKey, like the node's secret, which is only accepted
+/// by [`verify_with_expiry`] until `expiry_unix_secs`, in seconds since the UNIX epoch.
+///
+/// The token is of the form `<expiry>:<signature>`, where:
+/// - `<expiry>` is `expiry_unix_secs` as 16 lowercase hex characters, i.e., the hex encoding of
+///   its 8-byte big-endian representation, and
+/// - `<signature>` is the zbase32-encoded signature of the 8-byte big-endian expiry followed by
+///   the message, as created via [`sign`], i.e., over
+///   `sha256d("Lightning Signed Message:" || expiry || msg)`.
+///
+/// Other implementations can thus create compatible tokens via any lnd-compatible message signer.
+pub fn sign_with_expiry(msg: &[u8], expiry_unix_secs: u64, sk: &SecretKey) -> String {
+	let sig = sign(&expiring_msg(msg, expiry_unix_secs), sk);
+	expiring_token(expiry_unix_secs, &sig)
+}
+
+/// Creates a token for a message like [`sign_with_expiry`], but with the node's secret via
+/// [`NodeSigner::sign_message`].
+///
+/// Returns `Err` if the signer fails to sign.
+pub fn sign_with_expiry_with_node_signer<NS: Deref>(
+	msg: &[u8], expiry_unix_secs: u64, node_signer: NS,
+) -> Result<String, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let sig = node_signer.sign_message(&expiring_msg(msg, expiry_unix_secs))?;
+	Ok(expiring_token(expiry_unix_secs, &sig))
+}
+
+/// An error returned by [`verify_with_expiry`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExpiringSignatureError {
+	/// The token is not of the form described at [`sign_with_expiry`], e.g., as it lacks the
+	/// separator or its expiry is not 16 lowercase hex characters.
+	InvalidEncoding,
+	/// The signature is malformed or was not created by the expected key for the message and
+	/// expiry, e.g., as the expiry was tampered with.
+	InvalidSignature,
+	/// The token is authentic, but expired.
+	Expired {
+		/// The time at which the token expired, in seconds since the UNIX epoch.
+		expiry_unix_secs: u64,
+	},
+}
+
+/// Verifies a token created via [`sign_with_expiry`] or [`sign_with_expiry_with_node_signer`] was
+/// signed for the message by a PrivateKey that derives to the given PublicKey, and that it did
+/// not expire as of `now_unix_secs`, in seconds since the UNIX epoch.
+///
+/// Tokens are accepted up to and including their expiry. Returns the expiry of the token, as
+/// tokens far in the future may need to be rejected by the caller.
+pub fn verify_with_expiry(
+	msg: &[u8], token: &str, pk: &PublicKey, now_unix_secs: u64,
+) -> Result<u64, ExpiringSignatureError> {
+	let (expiry, sig) = token
+		.split_once(EXPIRING_TOKEN_SEPARATOR)
+		.ok_or(ExpiringSignatureError::InvalidEncoding)?;
+	// Only accept the canonical encoding of the expiry, such that each token has a single form.
+	if expiry.len() != 16 || expiry.bytes().any(|c| c.is_ascii_uppercase()) {
+		return Err(ExpiringSignatureError::InvalidEncoding);
+	}
+	let expiry_unix_secs = <[u8; 8]>::from_hex(expiry)
+		.map(u64::from_be_bytes)
+		.map_err(|_| ExpiringSignatureError::InvalidEncoding)?;
+	if !verify(&expiring_msg(msg, expiry_unix_secs), sig, pk) {
+		return Err(ExpiringSignatureError::InvalidSignature);
+	}
+	if now_unix_secs > expiry_unix_secs {
+		return Err(ExpiringSignatureError::Expired { expiry_unix_secs });
+	}
+	Ok(expiry_unix_secs)
+}
+
+/// Identifies a message signing key derived by a [`NodeSigner`], as used by
+/// [`sign_with_derived_key`].
+///
+/// [`KeysManager`] derives the key at `m/1017'/0'/{family}'/0/{index}` from its seed, following
+/// the key derivation scheme of lnd.
+///
+/// [`KeysManager`]: crate::sign::KeysManager
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct MessageSigningKeyId {
+	/// The family of the key, which must be lower than 2^31.
+	pub family: u32,
+	/// The index of the key within its family, which must be lower than 2^31.
+	pub index: u32,
+}
+
+/// Creates a digital signature of a message with the key derived by the given [`NodeSigner`] for
+/// the given `key_id`, rather than with the node's secret.
+///
+/// The signature has the same format as the ones created via [`sign`], thus the signer's public
+/// key can be recovered via [`recover_pk`]. Verifiers should be told which public key to expect,
+/// as provided by [`NodeSigner::get_message_signing_pubkey`].
+///
+/// Returns `Err` if the signer is unavailable, doesn't support deriving the key, or produced a
+/// signature which doesn't match the derived public key.
+pub fn sign_with_derived_key<NS: Deref>(
+	msg: &[u8], key_id: MessageSigningKeyId, node_signer: NS,
+) -> Result<String, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let pubkey = node_signer.get_message_signing_pubkey(key_id)?;
+	let sig = node_signer.sign_message_with_derived_key(msg, key_id)?;
+	if !verify(msg, &sig, &pubkey) {
+		return Err(());
+	}
+	Ok(sig)
+}
+
+/// The purpose of the BIP 32 paths under which LNURL-auth keys are derived, as per LUD-05.
+const LNURL_AUTH_PURPOSE: ChildNumber = ChildNumber::Hardened { index: 138 };
+
+/// Derives the [LNURL-auth] linking key for the given `domain` from a BIP 32 `master_key`, as
+/// specified in [LUD-05].
+///
+/// The hashing key at `m/138'/0` is used to compute an HMAC-SHA256 over the `domain`, the first 16
+/// bytes of which determine the path `m/138'/<long1>/<long2>/<long3>/<long4>` of the linking key.
+/// The linking key is thus deterministic for a given master key and domain, and unique per domain,
+/// such that services can't correlate a user across domains. `domain` should be the full domain
+/// name of the service, e.g., `site.com`.
+///
+/// Returns `Err` in the astronomically unlikely case of a derived key being invalid.
+///
+/// [LNURL-auth]: https://github.com/lnurl/luds/blob/luds/04.md
+/// [LUD-05]: https://github.com/lnurl/luds/blob/luds/05.md
+pub fn derive_lnurl_auth_linking_key<C: Signing>(
+	secp_ctx: &Secp256k1<C>, master_key: &Xpriv, domain: &str,
+) -> Result<SecretKey, ()> {
+	let purpose_key = master_key.derive_priv(secp_ctx, &[LNURL_AUTH_PURPOSE]).map_err(|_| ())?;
+	let hashing_key = purpose_key
+		.derive_priv(secp_ctx, &[ChildNumber::Normal { index: 0 }])
+		.map_err(|_| ())?
+		.private_key;
+
+	let mut hmac = HmacEngine::<sha256::Hash>::new(&hashing_key.secret_bytes());
+	hmac.input(domain.as_bytes());
+	let derivation_material = Hmac::from_engine(hmac).to_byte_array();
+	// Each long is interpreted as a BIP 32 child number, i.e., hardened if its top bit is set.
+	let path: Vec<ChildNumber> = derivation_material[..16]
+		.chunks_exact(4)
+		.map(|long| ChildNumber::from(u32::from_be_bytes(long.try_into().unwrap())))
+		.collect();
+	Ok(purpose_key.derive_priv(secp_ctx, &path).map_err(|_| ())?.private_key)
+}
+
+/// Signs the `k1` challenge of an [LNURL-auth] service with the given `linking_key`, as derived
+/// via [`derive_lnurl_auth_linking_key`].
+///
+/// Returns the linking public key and the hex-encoded DER signature, i.e., the `key` and `sig`
+/// parameters of the callback to the service.
+///
+/// [LNURL-auth]: https://github.com/lnurl/luds/blob/luds/04.md
+pub fn sign_lnurl_auth_challenge<C: Signing>(
+	secp_ctx: &Secp256k1<C>, k1: &[u8; 32], linking_key: &SecretKey,
+) -> (PublicKey, String) {
+	let linking_pubkey = PublicKey::from_secret_key(secp_ctx, linking_key);
+	let sig = secp_ctx.sign_ecdsa(&Message::from_digest(*k1), linking_key);
+	(linking_pubkey, sig.serialize_der().to_lower_hex_string())
+}
+
+/// Returns the virtual `to_spend` transaction of BIP 322, whose only output pays to the given
+/// `script_pubkey` and whose only input commits to the given message.
+fn bip322_to_spend_tx(msg: &[u8], script_pubkey: &Script) -> Transaction {
+	let tag_hash = sha256::Hash::hash(BIP322_MESSAGE_TAG);
+	let mut engine = sha256::Hash::engine();
+	engine.input(tag_hash.as_ref());
+	engine.input(tag_hash.as_ref());
+	engine.input(msg);
+	let msg_hash = sha256::Hash::from_engine(engine);
+
+	Transaction {
+		version: Version(0),
+		lock_time: LockTime::ZERO,
+		input: vec![TxIn {
+			previous_output: OutPoint::null(),
+			script_sig: Builder::new()
+				.push_opcode(opcodes::OP_0)
+				.push_slice(msg_hash.to_byte_array())
+				.into_script(),
+			sequence: Sequence::ZERO,
+			witness: Witness::new(),
+		}],
+		output: vec![TxOut { value: Amount::ZERO, script_pubkey: script_pubkey.to_owned() }],
+	}
+}
+
+/// Returns the virtual `to_sign` transaction of BIP 322, spending the only output of the
+/// `to_spend` transaction with the given txid.
+fn bip322_to_sign_tx(to_spend_txid: Txid, witness: Witness) -> Transaction {
+	Transaction {
+		version: Version(0),
+		lock_time: LockTime::ZERO,
+		input: vec![TxIn {
+			previous_output: OutPoint { txid: to_spend_txid, vout: 0 },
+			script_sig: ScriptBuf::new(),
+			sequence: Sequence::ZERO,
+			witness,
+		}],
+		output: vec![TxOut {
+			value: Amount::ZERO,
+			script_pubkey: Builder::new().push_opcode(opcodes::all::OP_RETURN).into_script(),
+		}],
+	}
+}
+
+/// Creates a [BIP 322] "simple" signature of a message, proving control over the given
+/// `script_pubkey`, e.g., the script of an on-chain address.
+///
+/// Only P2WPKH scripts paying to the public key of `sk` and P2TR scripts with `sk` as their
+/// internal key and no script path are supported. Returns `Err` for any other script.
+///
+/// The signature is the base64 encoding of the witness of the virtual `to_sign` transaction, as
+/// expected by other BIP 322 implementations.
+///
+/// [BIP 322]: https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki
+pub fn sign_bip322(msg: &[u8], script_pubkey: &Script, sk: &SecretKey) -> Result<String, ()> {
+	let secp_ctx = Secp256k1::new();
+	let to_spend = bip322_to_spend_tx(msg, script_pubkey);
+	let to_sign = bip322_to_sign_tx(to_spend.compute_txid(), Witness::new());
+	let mut sighash_cache = SighashCache::new(&to_sign);
+
+	let witness = if script_pubkey.is_p2wpkh() {
+		let pk = CompressedPublicKey(PublicKey::from_secret_key(&secp_ctx, sk));
+		if ScriptBuf::new_p2wpkh(&pk.wpubkey_hash()) != *script_pubkey {
+			return Err(());
+		}
+		let sighash = sighash_cache
+			.p2wpkh_signature_hash(0, script_pubkey, Amount::ZERO, EcdsaSighashType::All)
+			.map_err(|_| ())?;
+		let msg = Message::from_digest(sighash.to_byte_array());
+		let sig = ecdsa::Signature::sighash_all(secp_ctx.sign_ecdsa_low_r(&msg, sk));
+		Witness::from_slice(&[&sig.serialize()[..], &pk.to_bytes()[..]])
+	} else if script_pubkey.is_p2tr() {
+		let keypair = Keypair::from_secret_key(&secp_ctx, sk);
+		let (internal_key, _) = keypair.x_only_public_key();
+		if ScriptBuf::new_p2tr(&secp_ctx, internal_key, None) != *script_pubkey {
+			return Err(());
+		}
+		let sighash = sighash_cache
+			.taproot_key_spend_signature_hash(
+				0,
+				&Prevouts::All(&to_spend.output),
+				TapSighashType::Default,
+			)
+			.map_err(|_| ())?;
+		let msg = Message::from_digest(sighash.to_byte_array());
+		let tweaked_keypair = keypair.tap_tweak(&secp_ctx, None).to_inner();
+		let sig = secp_ctx.sign_schnorr_no_aux_rand(&msg, &tweaked_keypair);
+		Witness::from_slice(&[&sig.serialize()[..]])
+	} else {
+		return Err(());
+	};
+
+	Ok(base64::encode(&encode::serialize(&witness)))
+}
+
+/// Verifies a [BIP 322] "simple" signature of a message, proving control over the given
+/// `script_pubkey`, as created by [`sign_bip322`] or other BIP 322 implementations.
+///
+/// Only P2WPKH and single-key P2TR scripts are supported. Returns `false` for any other script.
+///
+/// As the virtual `to_sign` transaction is reconstructed from the message, a signature is only
+/// valid if it commits to the exact message.
+///
+/// [BIP 322]: https://github.com/bitcoin/bips/blob/master/bip-0322.mediawiki
+pub fn verify_bip322(msg: &[u8], sig: &str, script_pubkey: &Script) -> bool {
+	let witness: Witness = match base64::decode(sig).map(|bytes| encode::deserialize(&bytes)) {
+		Ok(Ok(witness)) => witness,
+		_ => return false,
+	};
+	let secp_ctx = Secp256k1::verification_only();
+	let to_spend = bip322_to_spend_tx(msg, script_pubkey);
+	let to_sign = bip322_to_sign_tx(to_spend.compute_txid(), witness.clone());
+	let mut sighash_cache = SighashCache::new(&to_sign);
+
+	if script_pubkey.is_p2wpkh() {
+		if witness.len() != 2 {
+			return false;
+		}
+		let (sig, pk) = match (
+			ecdsa::Signature::from_slice(&witness[0]),
+			CompressedPublicKey::from_slice(&witness[1]),
+		) {
+			(Ok(sig), Ok(pk)) => (sig, pk),
+			_ => return false,
+		};
+		if ScriptBuf::new_p2wpkh(&pk.wpubkey_hash()) != *script_pubkey {
+			return false;
+		}
+		match sighash_cache.p2wpkh_signature_hash(0, script_pubkey, Amount::ZERO, sig.sighash_type)
+		{
+			Ok(sighash) => {
+				let msg = Message::from_digest(sighash.to_byte_array());
+				secp_ctx.verify_ecdsa(&msg, &sig.signature, &pk.0).is_ok()
+			},
+			Err(_) => false,
+		}
+	} else if script_pubkey.is_p2tr() {
+		if witness.len() != 1 {
+			return false;
+		}
+		let (sig, output_key) = match (
+			taproot::Signature::from_slice(&witness[0]),
+			XOnlyPublicKey::from_slice(&script_pubkey.as_bytes()[2..]),
+		) {
+			(Ok(sig), Ok(output_key)) => (sig, output_key),
+			_ => return false,
+		};
+		let prevouts = Prevouts::All(&to_spend.output);
+		match sighash_cache.taproot_key_spend_signature_hash(0, &prevouts, sig.sighash_type) {
+			Ok(sighash) => {
+				let msg = Message::from_digest(sighash.to_byte_array());
+				secp_ctx.verify_schnorr(&sig.signature, &msg, &output_key).is_ok()
+			},
+			Err(_) => false,
+		}
+	} else {
+		false
+	}
+}
+
+/// An error returned by [`verify_node_announcement_signature`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeSignatureError {
+	/// The signature is malformed or was not created by the claimed node.
+	InvalidSignature,
+	/// The signature was created by the claimed node, but the node is not in the network graph.
+	UnknownNode,
+	/// The signature was created by the claimed node, which is in the network graph, but we have
+	/// not yet received a node announcement for it.
+	MissingNodeAnnouncement,
+}
+
+/// Verifies a message was signed by the node with the given `node_id` and looks up the node in the
+/// given [`NetworkGraph`], returning the information from its latest node announcement (e.g., its
+/// alias and addresses).
+///
+/// The `node_id` may be given either as a [`NodeId`] or as a [`PublicKey`]. Note that the alias
+/// of a node is not unique and is chosen by the node itself, so it should only be displayed
+/// alongside the verified `node_id`.
+pub fn verify_node_announcement_signature<N: Into<NodeId>, L: Deref>(
+	msg: &[u8], sig: &str, node_id: N, network_graph: &NetworkGraph<L>,
+) -> Result<NodeAnnouncementInfo, NodeSignatureError>
+where
+	L::Target: Logger,
+{
+	let node_id = node_id.into();
+	match recover_pk(msg, sig) {
+		Ok(pk) if NodeId::from_pubkey(&pk) == node_id => {},
+		_ => return Err(NodeSignatureError::InvalidSignature),
+	}
+
+	match network_graph.read_only().node(&node_id) {
+		Some(node_info) => {
+			node_info.announcement_info.clone().ok_or(NodeSignatureError::MissingNodeAnnouncement)
+		},
+		None => Err(NodeSignatureError::UnknownNode),
+	}
+}
+
+/// A node's claim to have paid for a payment hash, e.g., of an invoice, as signed via
+/// [`sign_payment_attestation`] and returned by [`verify_payment_attestation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct PaymentAttestation {
+	/// The hash of the paid payment.
+	pub payment_hash: PaymentHash,
+	/// The preimage of [`Self::payment_hash`], as learned by the payer once the payment succeeded,
+	/// e.g., via [`Event::PaymentSent`].
+	///
+	/// [`Event::PaymentSent`]: crate::events::Event::PaymentSent
+	pub payment_preimage: PaymentPreimage,
+	/// An arbitrary note of the payer, e.g., the description of the paid invoice.
+	pub memo: Option<String>,
+}
+
+impl_writeable_tlv_based!(PaymentAttestation, {
+	(0, payment_hash, required),
+	(2, payment_preimage, required),
+	(4, memo, option),
+});
+
+/// A [`PaymentAttestation`] along with the zbase32-encoded signature of the payer.
+struct SignedPaymentAttestation {
+	attestation: PaymentAttestation,
+	signature: String,
+}
+
+impl_writeable_tlv_based!(SignedPaymentAttestation, {
+	(0, attestation, required),
+	(2, signature, required),
+});
+
+fn payment_attestation_msg(attestation: &PaymentAttestation) -> Vec<u8> {
+	[PAYMENT_ATTESTATION_PREFIX, &attestation.encode()].concat()
+}
+
+fn is_preimage_of(payment_preimage: &PaymentPreimage, payment_hash: &PaymentHash) -> bool {
+	sha256::Hash::hash(&payment_preimage.0).to_byte_array() == payment_hash.0
+}
+
+/// Creates an attestation that our node paid for the given `payment_hash`, signed with the node's
+/// secret via [`NodeSigner::sign_message`] and optionally including a `memo`.
+///
+/// The returned blob can be handed to third parties, who can verify it via
+/// [`verify_payment_attestation`] given our node id.
+///
+/// Returns `Err` if the `payment_preimage` doesn't hash to the `payment_hash` or if the signer
+/// fails to sign.
+pub fn sign_payment_attestation<NS: Deref>(
+	payment_hash: PaymentHash, payment_preimage: PaymentPreimage, memo: Option<String>,
+	node_signer: NS,
+) -> Result<Vec<u8>, ()>
+where
+	NS::Target: NodeSigner,
+{
+	if !is_preimage_of(&payment_preimage, &payment_hash) {
+		return Err(());
+	}
+	let attestation = PaymentAttestation { payment_hash, payment_preimage, memo };
+	let signature = node_signer.sign_message(&payment_attestation_msg(&attestation))?;
+	Ok(SignedPaymentAttestation { attestation, signature }.encode())
+}
+
+/// An error returned by [`verify_payment_attestation`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PaymentAttestationError {
+	/// The attestation could not be decoded.
+	InvalidEncoding,
+	/// The attested preimage doesn't hash to the attested payment hash, regardless of whether the
+	/// attestation was signed by the claimed payer.
+	PreimageMismatch,
+	/// The signature is malformed or was not created by the claimed payer.
+	InvalidSignature,
+}
+
+/// Verifies an attestation created via [`sign_payment_attestation`], checking that its preimage
+/// hashes to its payment hash and that it was signed by the node with the given
+/// `payer_node_id`, and returns the attested payment.
+///
+/// Note that this only proves the payer learned the preimage, not from whom. Verifiers should
+/// additionally check that the payment hash matches the invoice in question.
+pub fn verify_payment_attestation(
+	blob: &[u8], payer_node_id: &PublicKey,
+) -> Result<PaymentAttestation, PaymentAttestationError> {
+	let mut reader = blob;
+	let signed: SignedPaymentAttestation =
+		Readable::read(&mut reader).map_err(|_| PaymentAttestationError::InvalidEncoding)?;
+	if !reader.is_empty() {
+		return Err(PaymentAttestationError::InvalidEncoding);
+	}
+	let SignedPaymentAttestation { attestation, signature } = signed;
+	if !is_preimage_of(&attestation.payment_preimage, &attestation.payment_hash) {
+		return Err(PaymentAttestationError::PreimageMismatch);
+	}
+	if !verify(&payment_attestation_msg(&attestation), &signature, payer_node_id) {
+		return Err(PaymentAttestationError::InvalidSignature);
+	}
+	Ok(attestation)
+}
+
+/// A snapshot of basic metrics of a node's channels, as signed via [`sign_node_metrics_snapshot`]
+/// and returned by [`verify_node_metrics_snapshot`].
+///
+/// The metrics only aggregate over the node's channels, so a snapshot does not depend on the order
+/// in which channels were listed.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct NodeMetricsSnapshot {
+	/// The time at which the snapshot was taken, in seconds since the UNIX epoch.
+	pub timestamp: u64,
+	/// The number of channels, including those which are not yet or no longer usable.
+	pub num_channels: u64,
+	/// The number of channels which are currently usable, see [`ChannelDetails::is_usable`].
+	pub num_usable_channels: u64,
+	/// The total capacity of the channels, in satoshis.
+	pub total_capacity_sat: u64,
+	/// The total inbound capacity of the channels, see [`ChannelDetails::inbound_capacity_msat`].
+	pub total_inbound_capacity_msat: u64,
+	/// The total outbound capacity of the channels, see
+	/// [`ChannelDetails::outbound_capacity_msat`].
+	pub total_outbound_capacity_msat: u64,
+}
+
+impl_writeable_tlv_based!(NodeMetricsSnapshot, {
+	(0, timestamp, required),
+	(2, num_channels, required),
+	(4, num_usable_channels, required),
+	(6, total_capacity_sat, required),
+	(8, total_inbound_capacity_msat, required),
+	(10, total_outbound_capacity_msat, required),
+});
+
+impl NodeMetricsSnapshot {
+	/// Takes a snapshot of the given `channels`, e.g., as returned by
+	/// [`ChannelManager::list_channels`], at the given `timestamp` in seconds since the UNIX epoch.
+	///
+	/// [`ChannelManager::list_channels`]: crate::ln::channelmanager::ChannelManager::list_channels
+	pub fn from_channels(channels: &[ChannelDetails], timestamp: u64) -> Self {
+		let mut snapshot = NodeMetricsSnapshot {
+			timestamp,
+			num_channels: channels.len() as u64,
+			num_usable_channels: 0,
+			total_capacity_sat: 0,
+			total_inbound_capacity_msat: 0,
+			total_outbound_capacity_msat: 0,
+		};
+		// Saturating additions of non-negative values yield the same result in any order.
+		for channel in channels {
+			snapshot.num_usable_channels += channel.is_usable as u64;
+			snapshot.total_capacity_sat =
+				snapshot.total_capacity_sat.saturating_add(channel.channel_value_satoshis);
+			snapshot.total_inbound_capacity_msat =
+				snapshot.total_inbound_capacity_msat.saturating_add(channel.inbound_capacity_msat);
+			snapshot.total_outbound_capacity_msat = snapshot
+				.total_outbound_capacity_msat
+				.saturating_add(channel.outbound_capacity_msat);
+		}
+		snapshot
+	}
+}
+
+/// A [`NodeMetricsSnapshot`] along with the zbase32-encoded signature of the node.
+struct SignedNodeMetricsSnapshot {
+	snapshot: NodeMetricsSnapshot,
+	signature: String,
+}
+
+impl_writeable_tlv_based!(SignedNodeMetricsSnapshot, {
+	(0, snapshot, required),
+	(2, signature, required),
+});
+
+fn node_metrics_snapshot_msg(snapshot: &NodeMetricsSnapshot) -> Vec<u8> {
+	[NODE_METRICS_SNAPSHOT_PREFIX, &snapshot.encode()].concat()
+}
+
+/// Creates a snapshot of the metrics of the given `channels`, e.g., as returned by
+/// [`ChannelManager::list_channels`], signed with the node's secret via
+/// [`NodeSigner::sign_message`].
+///
+/// The returned blob can be handed to third parties, who can verify it and recover our node id
+/// via [`verify_node_metrics_snapshot`]. The blob only depends on the set of `channels`, not their
+/// order.
+///
+/// Returns `Err` if the signer fails to sign.
+///
+/// [`ChannelManager::list_channels`]: crate::ln::channelmanager::ChannelManager::list_channels
+pub fn sign_node_metrics_snapshot<NS: Deref>(
+	channels: &[ChannelDetails], timestamp: u64, node_signer: NS,
+) -> Result<Vec<u8>, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let snapshot = NodeMetricsSnapshot::from_channels(channels, timestamp);
+	let signature = node_signer.sign_message(&node_metrics_snapshot_msg(&snapshot))?;
+	Ok(SignedNodeMetricsSnapshot { snapshot, signature }.encode())
+}
+
+/// An error returned by [`verify_node_metrics_snapshot`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum NodeMetricsSnapshotError {
+	/// The snapshot could not be decoded, or was not canonically encoded.
+	InvalidEncoding,
+	/// The signature is malformed.
+	InvalidSignature,
+}
+
+/// Verifies a snapshot created via [`sign_node_metrics_snapshot`], returning the snapshot along
+/// with the node id of the node which signed it.
+///
+/// Verifiers should check that the returned node id is the one they expect, as well as the
+/// snapshot's [`NodeMetricsSnapshot::timestamp`] to prevent replays of old snapshots.
+pub fn verify_node_metrics_snapshot(
+	blob: &[u8],
+) -> Result<(NodeMetricsSnapshot, PublicKey), NodeMetricsSnapshotError> {
+	let mut reader = blob;
+	let signed: SignedNodeMetricsSnapshot =
+		Readable::read(&mut reader).map_err(|_| NodeMetricsSnapshotError::InvalidEncoding)?;
+	// Snapshots must be canonically encoded, e.g., without unknown fields which are not signed.
+	if !reader.is_empty() || signed.encode() != blob {
+		return Err(NodeMetricsSnapshotError::InvalidEncoding);
+	}
+	let SignedNodeMetricsSnapshot { snapshot, signature } = signed;
+	let node_id = recover_pk(&node_metrics_snapshot_msg(&snapshot), &signature)
+		.map_err(|_| NodeMetricsSnapshotError::InvalidSignature)?;
+	Ok((snapshot, node_id))
+}
+
+/// Encodes the given fields as a TLV stream in ascending type order, failing on duplicate types.
+fn encode_tlv_fields(fields: &[(u64, &[u8])]) -> Result<Vec<u8>, ()> {
+	let mut sorted_fields = fields.to_vec();
+	sorted_fields.sort_unstable_by_key(|(typ, _)| *typ);
+	if sorted_fields.windows(2).any(|pair| pair[0].0 == pair[1].0) {
+		return Err(());
+	}
+	let mut stream = Vec::new();
+	for (typ, value) in sorted_fields {
+		stream.extend_from_slice(&BigSize(typ).encode());
+		stream.extend_from_slice(&BigSize(value.len() as u64).encode());
+		stream.extend_from_slice(value);
+	}
+	Ok(stream)
+}
+
+fn tlv_envelope_msg(stream: &[u8]) -> Vec<u8> {
+	[TLV_ENVELOPE_PREFIX, stream].concat()
+}
+
+/// Signs the given `(type, value)` fields with the given SecretKey, e.g., the node's secret.
+///
+/// The returned envelope consists of the 65-byte recoverable signature, as returned by
+/// [`sign_to_bytes`], followed by the fields encoded as a TLV stream in ascending type order. Thus,
+/// the envelope doesn't depend on the order of `fields`. It can be verified via [`verify_tlv`].
+///
+/// Returns `Err` if `fields` contains a type more than once.
+pub fn sign_tlv(fields: &[(u64, &[u8])], sk: &SecretKey) -> Result<Vec<u8>, ()> {
+	let stream = encode_tlv_fields(fields)?;
+	let signature = sign_to_bytes(&tlv_envelope_msg(&stream), sk);
+	Ok([&signature[..], &stream].concat())
+}
+
+/// Signs the given `(type, value)` fields like [`sign_tlv`], but with the node's secret via
+/// [`NodeSigner::sign_message`].
+///
+/// Returns `Err` if `fields` contains a type more than once or if the signer fails to sign.
+pub fn sign_tlv_with_node_signer<NS: Deref>(
+	fields: &[(u64, &[u8])], node_signer: NS,
+) -> Result<Vec<u8>, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let stream = encode_tlv_fields(fields)?;
+	let signature = node_signer.sign_message(&tlv_envelope_msg(&stream))?;
+	let signature = decode_zbase32_sig(&signature).map_err(|_| ())?;
+	Ok([&signature[..], &stream].concat())
+}
+
+/// An error returned by [`verify_tlv`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TlvEnvelopeError {
+	/// The envelope is truncated or a field's length exceeds the remaining data.
+	InvalidEncoding,
+	/// The TLV stream is not canonically encoded, i.e., its types are not strictly ascending or
+	/// are not minimally encoded.
+	NonCanonicalEncoding,
+	/// The signature is malformed.
+	InvalidSignature,
+}
+
+fn read_tlv_envelope_bigsize(reader: &mut &[u8]) -> Result<u64, TlvEnvelopeError> {
+	match BigSize::read(reader) {
+		Ok(value) => Ok(value.0),
+		// Non-minimally encoded values are rejected by `BigSize::read`.
+		Err(DecodeError::InvalidValue) => Err(TlvEnvelopeError::NonCanonicalEncoding),
+		Err(_) => Err(TlvEnvelopeError::InvalidEncoding),
+	}
+}
+
+/// Verifies an envelope created via [`sign_tlv`] or [`sign_tlv_with_node_signer`], returning its
+/// `(type, value)` fields in ascending type order along with the public key of the signer.
+///
+/// Verifiers should check that the returned public key is the one they expect, as any change to
+/// the fields results in a different key being recovered rather than an error.
+pub fn verify_tlv(envelope: &[u8]) -> Result<(Vec<(u64, Vec<u8>)>, PublicKey), TlvEnvelopeError> {
+	if envelope.len() < 65 {
+		return Err(TlvEnvelopeError::InvalidEncoding);
+	}
+	let (signature, stream) = envelope.split_at(65);
+	let mut reader = stream;
+	let mut fields: Vec<(u64, Vec<u8>)> = Vec::new();
+	while !reader.is_empty() {
+		let typ = read_tlv_envelope_bigsize(&mut reader)?;
+		if fields.last().map_or(false, |(last_typ, _)| typ <= *last_typ) {
+			return Err(TlvEnvelopeError::NonCanonicalEncoding);
+		}
+		let len = read_tlv_envelope_bigsize(&mut reader)?;
+		if len > reader.len() as u64 {
+			return Err(TlvEnvelopeError::InvalidEncoding);
+		}
+		let (value, rest) = reader.split_at(len as usize);
+		fields.push((typ, value.to_vec()));
+		reader = rest;
+	}
+	let signature: &[u8; 65] = signature.try_into().expect("Split off 65 bytes above");
+	let pubkey = recover_pk_from_bytes(&tlv_envelope_msg(stream), signature)
+		.map_err(|_| TlvEnvelopeError::InvalidSignature)?;
+	Ok((fields, pubkey))
+}
+
+/// A payer id along with the zbase32-encoded signature of the node which created it.
+struct SignedPayerProof {
+	payer_signing_pubkey: PublicKey,
+	signature: String,
+}
+
+impl_writeable_tlv_based!(SignedPayerProof, {
+	(0, payer_signing_pubkey, required),
+	(2, signature, required),
+});
+
+fn payer_proof_msg(payer_signing_pubkey: &PublicKey) -> Vec<u8> {
+	[PAYER_PROOF_PREFIX, &payer_signing_pubkey.serialize()[..]].concat()
+}
+
+/// Creates a proof that our node created the BOLT 12 `invoice_request` with the given payer id,
+/// i.e., [`InvoiceRequest::payer_signing_pubkey`], signed with the node's secret via
+/// [`NodeSigner::sign_message`].
+///
+/// Only the payer id is signed, so the proof doesn't reveal the secret from which the payer id
+/// and the request's payer metadata were derived. The returned blob can be handed to third
+/// parties, who can verify it against the `invoice_request` via [`verify_payer_proof`] given our
+/// node id.
+///
+/// Returns `Err` if the signer fails to sign.
+pub fn sign_payer_proof<NS: Deref>(
+	payer_signing_pubkey: PublicKey, node_signer: NS,
+) -> Result<Vec<u8>, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let signature = node_signer.sign_message(&payer_proof_msg(&payer_signing_pubkey))?;
+	Ok(SignedPayerProof { payer_signing_pubkey, signature }.encode())
+}
+
+/// An error returned by [`verify_payer_proof`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PayerProofError {
+	/// The proof could not be decoded.
+	InvalidEncoding,
+	/// The `invoice_request` could not be parsed or its signature is invalid.
+	InvalidInvoiceRequest,
+	/// The payer id of the proof doesn't match the one of the `invoice_request`.
+	PayerIdMismatch,
+	/// The signature is malformed or was not created by the claimed payer node.
+	InvalidSignature,
+}
+
+/// Verifies a proof created via [`sign_payer_proof`] against the serialized `invoice_request`,
+/// checking that the payer id of the proof matches the request's payer id and that the proof was
+/// signed by the node with the given `payer_node_id`, and returns the parsed request.
+///
+/// As parsing the `invoice_request` checks its signature by the payer id, a valid proof shows that
+/// the request was created by the payer node.
+pub fn verify_payer_proof(
+	blob: &[u8], invoice_request: &[u8], payer_node_id: &PublicKey,
+) -> Result<InvoiceRequest, PayerProofError> {
+	let mut reader = blob;
+	let signed: SignedPayerProof =
+		Readable::read(&mut reader).map_err(|_| PayerProofError::InvalidEncoding)?;
+	if !reader.is_empty() {
+		return Err(PayerProofError::InvalidEncoding);
+	}
+	let invoice_request = InvoiceRequest::try_from(invoice_request.to_vec())
+		.map_err(|_| PayerProofError::InvalidInvoiceRequest)?;
+	let SignedPayerProof { payer_signing_pubkey, signature } = signed;
+	if invoice_request.payer_signing_pubkey() != payer_signing_pubkey {
+		return Err(PayerProofError::PayerIdMismatch);
+	}
+	if !verify(&payer_proof_msg(&payer_signing_pubkey), &signature, payer_node_id) {
+		return Err(PayerProofError::InvalidSignature);
+	}
+	Ok(invoice_request)
+}
+
+/// The routing fee policy of one of our channels, akin to the fields of a `channel_update`, as
+/// signed via [`sign_fee_policy`] and returned by [`verify_fee_policy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChannelFeePolicy {
+	/// The short channel id of the channel the policy applies to.
+	pub short_channel_id: u64,
+	/// The base fee charged for forwarding an HTLC over the channel, in millisatoshis.
+	pub fee_base_msat: u32,
+	/// The fee charged per millionth of the amount forwarded over the channel.
+	pub fee_proportional_millionths: u32,
+	/// The difference in the CLTV expiry required between incoming and outgoing HTLCs.
+	pub cltv_expiry_delta: u16,
+	/// The smallest HTLC which can be forwarded over the channel, in millisatoshis.
+	pub htlc_minimum_msat: u64,
+	/// The largest HTLC which can be forwarded over the channel, in millisatoshis.
+	pub htlc_maximum_msat: u64,
+}
+
+impl_writeable_tlv_based!(ChannelFeePolicy, {
+	(0, short_channel_id, required),
+	(2, fee_base_msat, required),
+	(4, fee_proportional_millionths, required),
+	(6, cltv_expiry_delta, required),
+	(8, htlc_minimum_msat, required),
+	(10, htlc_maximum_msat, required),
+});
+
+impl ChannelFeePolicy {
+	/// Builds the current fee policy of the channel with the given `channel_id` from its
+	/// [`ChannelDetails::config`] and the HTLC limits of its counterparty.
+	///
+	/// Returns `None` if the channel doesn't exist, has no short channel id yet, or its config or
+	/// HTLC limits are not yet known.
+	pub fn for_channel(
+		channel_manager: &DynChannelManager<'_>, channel_id: &ChannelId,
+	) -> Option<Self> {
+		let channel = channel_manager
+			.list_channels()
+			.into_iter()
+			.find(|channel| channel.channel_id == *channel_id)?;
+		let config = channel.config?;
+		Some(ChannelFeePolicy {
+			short_channel_id: channel.short_channel_id?,
+			fee_base_msat: config.forwarding_fee_base_msat,
+			fee_proportional_millionths: config.forwarding_fee_proportional_millionths,
+			cltv_expiry_delta: config.cltv_expiry_delta,
+			htlc_minimum_msat: channel.counterparty.outbound_htlc_minimum_msat?,
+			htlc_maximum_msat: channel.counterparty.outbound_htlc_maximum_msat?,
+		})
+	}
+}
+
+/// A [`ChannelFeePolicy`] along with the zbase32-encoded signature of the node.
+struct SignedChannelFeePolicy {
+	policy: ChannelFeePolicy,
+	signature: String,
+}
+
+impl_writeable_tlv_based!(SignedChannelFeePolicy, {
+	(0, policy, required),
+	(2, signature, required),
+});
+
+fn fee_policy_msg(policy: &ChannelFeePolicy) -> Vec<u8> {
+	[FEE_POLICY_PREFIX, &policy.encode()].concat()
+}
+
+/// Signs the given fee `policy`, e.g., as built via [`ChannelFeePolicy::for_channel`], with the
+/// node's secret via [`NodeSigner::sign_message`].
+///
+/// The returned blob can be distributed to partners out-of-band, who can verify it and recover our
+/// node id via [`verify_fee_policy`].
+///
+/// Returns `Err` if the signer fails to sign.
+pub fn sign_fee_policy<NS: Deref>(policy: ChannelFeePolicy, node_signer: NS) -> Result<Vec<u8>, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let signature = node_signer.sign_message(&fee_policy_msg(&policy))?;
+	Ok(SignedChannelFeePolicy { policy, signature }.encode())
+}
+
+/// An error returned by [`verify_fee_policy`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FeePolicyError {
+	/// The policy could not be decoded, or was not canonically encoded.
+	InvalidEncoding,
+	/// The signature is malformed.
+	InvalidSignature,
+	/// The policy's short channel id is not in the given [`NetworkGraph`].
+	UnknownChannel,
+	/// The policy was signed by a node which is not a party to the policy's channel.
+	NotChannelParty,
+}
+
+/// Verifies a fee policy created via [`sign_fee_policy`], returning the policy along with the
+/// node id of the node which signed it.
+///
+/// If a [`NetworkGraph`] is given, the policy is only accepted if its channel is in the graph and
+/// the signing node is one of its parties. Otherwise, verifiers should check that the returned
+/// node id is the one they expect.
+pub fn verify_fee_policy<L: Deref>(
+	blob: &[u8], network_graph: Option<&NetworkGraph<L>>,
+) -> Result<(ChannelFeePolicy, PublicKey), FeePolicyError>
+where
+	L::Target: Logger,
+{
+	let mut reader = blob;
+	let signed: SignedChannelFeePolicy =
+		Readable::read(&mut reader).map_err(|_| FeePolicyError::InvalidEncoding)?;
+	// Policies must be canonically encoded, e.g., without unknown fields which are not signed.
+	if !reader.is_empty() || signed.encode() != blob {
+		return Err(FeePolicyError::InvalidEncoding);
+	}
+	let SignedChannelFeePolicy { policy, signature } = signed;
+	let node_id = recover_pk(&fee_policy_msg(&policy), &signature)
+		.map_err(|_| FeePolicyError::InvalidSignature)?;
+
+	if let Some(network_graph) = network_graph {
+		let read_only_graph = network_graph.read_only();
+		let channel = read_only_graph
+			.channel(policy.short_channel_id)
+			.ok_or(FeePolicyError::UnknownChannel)?;
+		let signer = NodeId::from_pubkey(&node_id);
+		if channel.node_one != signer && channel.node_two != signer {
+			return Err(FeePolicyError::NotChannelParty);
+		}
+	}
+	Ok((policy, node_id))
+}
+
+/// An authorization to cooperatively close a channel, signed via [`sign_close_authorization`] and
+/// provided to [`ChannelManager::close_channel_with_authorization`].
+///
+/// [`ChannelManager::close_channel_with_authorization`]: crate::ln::channelmanager::ChannelManager::close_channel_with_authorization
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CloseAuthorization {
+	/// The channel ID of the channel which may be closed.
+	pub channel_id: ChannelId,
+	/// A nonce which must be greater than that of any authorization previously used, such that
+	/// authorizations can't be replayed.
+	pub nonce: u64,
+	/// The maximum fee we may pay on the closing transaction.
+	pub max_fee_satoshis: u64,
+}
+
+impl_writeable_tlv_based!(CloseAuthorization, {
+	(0, channel_id, required),
+	(2, nonce, required),
+	(4, max_fee_satoshis, required),
+});
+
+fn close_authorization_msg(authorization: &CloseAuthorization) -> Vec<u8> {
+	[CLOSE_AUTHORIZATION_PREFIX, &authorization.encode()].concat()
+}
+
+/// Signs the given `authorization` with the authorizer's secret key `sk`, returning the
+/// zbase32-encoded signature as [`sign`] does.
+pub fn sign_close_authorization(authorization: &CloseAuthorization, sk: &SecretKey) -> String {
+	sign(&close_authorization_msg(authorization), sk)
+}
+
+/// Verifies that `sig` is a signature of the given `authorization` by the authorizer with the
+/// public key `pk`, as created via [`sign_close_authorization`].
+///
+/// The nonce of the authorization is not checked, which is left to the caller.
+pub fn verify_close_authorization(
+	authorization: &CloseAuthorization, sig: &str, pk: &PublicKey,
+) -> bool {
+	verify(&close_authorization_msg(authorization), sig, pk)
+}
+
+/// The version of the header written by [`sign_backup`].
+pub const SIGNED_BACKUP_VERSION: u8 = 1;
+
+/// The length of the fields of a signed backup's header which are covered by its signature, i.e.,
+/// its version, timestamp, node id and payload length.
+const SIGNED_BACKUP_FIELDS_LEN: usize = 1 + 8 + 33 + 8;
+
+/// The length of a signed backup's header, i.e., of its signed fields followed by the signature.
+const SIGNED_BACKUP_HEADER_LEN: usize = SIGNED_BACKUP_FIELDS_LEN + 65;
+
+fn signed_backup_msg(signed_fields: &[u8], payload: &[u8]) -> Vec<u8> {
+	let payload_hash = sha256d::Hash::hash(payload);
+	[SIGNED_BACKUP_PREFIX, signed_fields, payload_hash.as_byte_array()].concat()
+}
+
+/// Wraps the given backup `payload`, e.g., a list of serialized `ChannelMonitor`s, with a header
+/// binding it to our node id, signed with the node's secret via [`NodeSigner::sign_message`].
+///
+/// The header consists of the [`SIGNED_BACKUP_VERSION`], the given `timestamp` in seconds since
+/// the UNIX epoch and our node id, followed by the length of the payload and the 65-byte
+/// recoverable signature of these fields along with the sha256d hash of the payload. The payload
+/// follows the header as-is, so it should be encrypted beforehand if it is to be kept private.
+///
+/// The returned blob can be verified and unwrapped via [`verify_backup`].
+///
+/// Returns `Err` if the signer fails to provide our node id or to sign.
+pub fn sign_backup<NS: Deref>(
+	payload: &[u8], timestamp: u64, node_signer: NS,
+) -> Result<Vec<u8>, ()>
+where
+	NS::Target: NodeSigner,
+{
+	let node_id = node_signer.get_node_id(Recipient::Node)?;
+	let mut blob = Vec::with_capacity(SIGNED_BACKUP_HEADER_LEN + payload.len());
+	blob.push(SIGNED_BACKUP_VERSION);
+	blob.extend_from_slice(&timestamp.to_be_bytes());
+	blob.extend_from_slice(&node_id.serialize());
+	blob.extend_from_slice(&(payload.len() as u64).to_be_bytes());
+	let signature = node_signer.sign_message(&signed_backup_msg(&blob, payload))?;
+	let signature = decode_zbase32_sig(&signature).map_err(|_| ())?;
+	blob.extend_from_slice(&signature);
+	blob.extend_from_slice(payload);
+	Ok(blob)
+}
+
+/// An error returned by [`verify_backup`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SignedBackupError {
+	/// The blob is shorter than its header or than the payload length it declares, e.g., as it was
+	/// only partially written or downloaded.
+	Truncated,
+	/// The blob's header has a version other than [`SIGNED_BACKUP_VERSION`].
+	UnsupportedVersion(u8),
+	/// The blob's node id is not a valid public key, or the blob is longer than its header and the
+	/// payload length it declares.
+	InvalidEncoding,
+	/// The blob was created by a node other than the expected one.
+	NodeIdMismatch,
+	/// The signature is malformed or was not created by the expected node over the blob's header
+	/// and payload, i.e., the blob was tampered with.
+	InvalidSignature,
+}
+
+/// Verifies a blob created via [`sign_backup`] by the node with the given `expected_node_id`,
+/// returning its timestamp along with the backup payload.
+///
+/// Callers may want to check the returned timestamp to detect that an outdated backup was
+/// restored, e.g., by the storage provider.
+pub fn verify_backup<'a>(
+	blob: &'a [u8], expected_node_id: &PublicKey,
+) -> Result<(u64, &'a [u8]), SignedBackupError> {
+	if blob.is_empty() {
+		return Err(SignedBackupError::Truncated);
+	}
+	// Check the version first, such that future headers of a different length aren't reported as
+	// truncated.
+	if blob[0] != SIGNED_BACKUP_VERSION {
+		return Err(SignedBackupError::UnsupportedVersion(blob[0]));
+	}
+	if blob.len() < SIGNED_BACKUP_HEADER_LEN {
+		return Err(SignedBackupError::Truncated);
+	}
+	let (header, payload) = blob.split_at(SIGNED_BACKUP_HEADER_LEN);
+	let (signed_fields, signature) = header.split_at(SIGNED_BACKUP_FIELDS_LEN);
+	let timestamp = u64::from_be_bytes(signed_fields[1..9].try_into().unwrap());
+	let node_id = PublicKey::from_slice(&signed_fields[9..42])
+		.map_err(|_| SignedBackupError::InvalidEncoding)?;
+	let payload_len = u64::from_be_bytes(signed_fields[42..50].try_into().unwrap());
+	if (payload.len() as u64) < payload_len {
+		return Err(SignedBackupError::Truncated);
+	} else if payload.len() as u64 > payload_len {
+		return Err(SignedBackupError::InvalidEncoding);
+	}
+	if node_id != *expected_node_id {
+		return Err(SignedBackupError::NodeIdMismatch);
+	}
+	let signature: &[u8; 65] = signature.try_into().expect("Split off 65 bytes above");
+	match recover_pk_from_bytes(&signed_backup_msg(signed_fields, payload), signature) {
+		Ok(signer) if signer == node_id => Ok((timestamp, payload)),
+		_ => Err(SignedBackupError::InvalidSignature),
+	}
+}
+
+/// The message signed via [`NodeSigner::sign_message`] by [`self_test`].
+static SELF_TEST_MESSAGE: &[u8] = b"LDK message signing self-test";
+
+/// A message signature created by another implementation, which is verified by [`self_test`].
+struct InteropVector {
+	implementation: &'static str,
+	message: &'static str,
+	signature: &'static str,
+	node_id: &'static str,
+}
+
+/// The signatures verified by [`self_test`], as used by c-lightning's tests to check compatibility
+/// of its message signatures with other implementations.
+///
+/// Taken from <https://github.com/ElementsProject/lightning/blob/1275af6fbb02460c8eb2f00990bb0ef9179ce8f3/tests/test_misc.py#L1925-L1938>
+const INTEROP_VECTORS: [InteropVector; 3] = [
+	InteropVector {
+		implementation: "c-lightning",
+		message: "is this compatible?",
+		signature: "rbgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
+		node_id: "02b80cabdf82638aac86948e4c06e82064f547768dcef977677b9ea931ea75bab5",
+	},
+	InteropVector {
+		implementation: "c-lightning",
+		message: "hi",
+		signature: "rnrphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
+		node_id: "02de60d194e1ca5947b59fe8e2efd6aadeabfb67f2e89e13ae1a799c1e08e4a43b",
+	},
+	InteropVector {
+		implementation: "c-lightning",
+		message: "hi",
+		signature: "ry8bbsopmduhxy3dr5d9ekfeabdpimfx95kagdem7914wtca79jwamtbw4rxh69hg7n6x9ty8cqk33knbxaqftgxsfsaeprxkn1k48p3",
+		node_id: "022b8ece90ee891cbcdac0c1cc6af46b73c47212d8defbce80265ac81a6b794931",
+	},
+];
+
+/// A check performed by [`self_test`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SelfTestCheck {
+	/// The signer provided our node id and signed the test message.
+	Sign,
+	/// The public key recovered from the signature of the test message is our node id.
+	RecoverNodeId,
+	/// The signature verifies against our node id for the test message, but not for any other.
+	Verify,
+	/// The signature is in canonical low-S form, and is thus also accepted by [`recover_pk_strict`]
+	/// and by verifiers of other implementations which reject high-S signatures.
+	LowS,
+	/// A signature created by another implementation verifies.
+	InteropVector {
+		/// The implementation whose signature was verified.
+		implementation: &'static str,
+		/// The index of the signature among those of the implementation.
+		index: usize,
+	},
+}
+
+impl fmt::Display for SelfTestCheck {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		match self {
+			SelfTestCheck::Sign => f.write_str("sign"),
+			SelfTestCheck::RecoverNodeId => f.write_str("recover node id"),
+			SelfTestCheck::Verify => f.write_str("verify"),
+			SelfTestCheck::LowS => f.write_str("low-S signature"),
+			SelfTestCheck::InteropVector { implementation, index } => {
+				write!(f, "{} vector #{}", implementation, index)
+			},
+		}
+	}
+}
+
+/// The results of the checks performed by [`self_test`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SelfTestReport {
+	/// Each check performed, along with whether it passed.
+	pub results: Vec<(SelfTestCheck, bool)>,
+}
+
+impl SelfTestReport {
+	/// Returns whether all checks passed.
+	pub fn passed(&self) -> bool {
+		self.results.iter().all(|(_, passed)| *passed)
+	}
+
+	/// Returns the checks which failed.
+	pub fn failed_checks(&self) -> impl Iterator<Item = &SelfTestCheck> {
+		self.results.iter().filter(|(_, passed)| !passed).map(|(check, _)| check)
+	}
+}
+
+impl fmt::Display for SelfTestReport {
+	fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+		for (check, passed) in self.results.iter() {
+			writeln!(f, "{}: {}", check, if *passed { "passed" } else { "FAILED" })?;
+		}
+		Ok(())
+	}
+}
+
+/// Checks that message signatures created by the given [`NodeSigner`] can be verified against its
+/// node id, and that signatures created by other implementations can be verified, e.g., after
+/// swapping in a remote signer.
+///
+/// A fixed message is signed via [`NodeSigner::sign_message`] and the signature is checked to
+/// recover to, and verify against, the node id provided by [`NodeSigner::get_node_id`]. Further,
+/// a set of signatures created by other implementations, which is embedded in LDK, is verified.
+/// No network access is required.
+///
+/// Returns an error if the signer's signature of the test message can't be decoded or no public
+/// key can be recovered from it. Otherwise, the returned [`SelfTestReport`] indicates which
+/// checks passed.
+pub fn self_test<NS: Deref>(node_signer: NS) -> Result<SelfTestReport, MessageSigningError>
+where
+	NS::Target: NodeSigner,
+{
+	let verifier = MessageVerifier::new();
+	let mut results = Vec::with_capacity(4 + INTEROP_VECTORS.len());
+
+	let node_id = node_signer.get_node_id(Recipient::Node).ok();
+	let sig = node_signer.sign_message(SELF_TEST_MESSAGE).ok();
+	results.push((SelfTestCheck::Sign, node_id.is_some() && sig.is_some()));
+	match (node_id, sig) {
+		(Some(node_id), Some(sig)) => {
+			let recovered_pk = verifier.recover_pk(SELF_TEST_MESSAGE, &sig)?;
+			results.push((SelfTestCheck::RecoverNodeId, recovered_pk == node_id));
+			let other_msg = [SELF_TEST_MESSAGE, b"!"].concat();
+			let verified = verifier.verify(SELF_TEST_MESSAGE, &sig, &node_id)
+				&& !verifier.verify(&other_msg, &sig, &node_id);
+			results.push((SelfTestCheck::Verify, verified));
+			let low_s = recover_pk_strict(SELF_TEST_MESSAGE, &sig).is_ok();
+			results.push((SelfTestCheck::LowS, low_s));
+		},
+		_ => {
+			results.push((SelfTestCheck::RecoverNodeId, false));
+			results.push((SelfTestCheck::Verify, false));
+			results.push((SelfTestCheck::LowS, false));
+		},
+	}
+
+	for (idx, vector) in INTEROP_VECTORS.iter().enumerate() {
+		let index = INTEROP_VECTORS[..idx]
+			.iter()
+			.filter(|other| other.implementation == vector.implementation)
+			.count();
+		let passed = <[u8; 33]>::from_hex(vector.node_id)
+			.ok()
+			.and_then(|node_id| PublicKey::from_slice(&node_id).ok())
+			.map_or(false, |pk| verifier.verify(vector.message.as_bytes(), vector.signature, &pk));
+		let check = SelfTestCheck::InteropVector { implementation: vector.implementation, index };
+		results.push((check, passed));
+	}
+
+	Ok(SelfTestReport { results })
+}
+
+#[cfg(test)]
+mod test {
+	use crate::ln::inbound_payment::ExpandedKey;
+	use crate::ln::msgs::UnsignedGossipMessage;
+	use crate::ln::types::ChannelId;
+	use crate::offers::invoice::UnsignedBolt12Invoice;
+	use crate::routing::gossip::{NetworkGraph, NodeId, P2PGossipSync};
+	use crate::routing::test_utils::{add_channel, add_or_update_node};
+	use crate::sign::{KeysManager, NodeSigner, PeerStorageKey, ReceiveAuthKey, Recipient};
+	use crate::sync::Arc;
+	use crate::types::features::{ChannelFeatures, NodeFeatures};
+	use crate::types::payment::{PaymentHash, PaymentPreimage};
+	use crate::util::base32;
+	use crate::util::message_signing::{
+		bip322_to_sign_tx, bip322_to_spend_tx, node_metrics_snapshot_msg, payment_attestation_msg,
+		recover_pk, recover_pk_from_bytes, recover_pk_single_hash, sign, sign_bip322,
+		sign_node_metrics_snapshot, sign_payment_attestation, sign_single_hash, sign_to_bytes,
+		sign_with_derived_key, verify, verify_any, verify_bip322,
+		verify_node_announcement_signature, verify_node_metrics_snapshot,
+		verify_payment_attestation, verify_single_hash, MessageSignatureScheme,
+		MessageSigningKeyId, NodeMetricsSnapshot, NodeMetricsSnapshotError, NodeSignatureError,
+		PaymentAttestation, PaymentAttestationError, SignedNodeMetricsSnapshot,
+		SignedPaymentAttestation,
+	};
+	use crate::util::message_signing::{
+		decode_recoverable_signature, recover_pk_with_secp_ctx, MessageSigningError,
+		MessageVerifier, MAX_SIGNATURE_LENGTH,
+	};
+	use crate::util::message_signing::{
+		derive_lnurl_auth_linking_key, sign_lnurl_auth_challenge, NodeIdParseError,
+	};
+	use crate::util::message_signing::{is_low_s, recover_pk_strict};
+	use crate::util::message_signing::{recover_node_id, verify_against};
+	use crate::util::message_signing::{self_test, SelfTestCheck, INTEROP_VECTORS};
+	use crate::util::message_signing::{
+		sign_backup, verify_backup, SignedBackupError, SIGNED_BACKUP_VERSION,
+	};
+	use crate::util::message_signing::{
+		sign_close_authorization, verify_close_authorization, CloseAuthorization,
+	};
+	use crate::util::message_signing::{
+		sign_fee_policy, verify_fee_policy, ChannelFeePolicy, FeePolicyError,
+	};
+	use crate::util::message_signing::{sign_payer_proof, verify_payer_proof, PayerProofError};
+	use crate::util::message_signing::{
+		sign_tlv, sign_tlv_with_node_signer, tlv_envelope_msg, verify_tlv, TlvEnvelopeError,
+	};
+	use crate::util::message_signing::{
+		sign_with_expiry, sign_with_expiry_with_node_signer, verify_with_expiry,
+		ExpiringSignatureError,
+	};
+	use crate::util::ser::{Readable, Writeable};
+	use crate::util::test_utils;
+	use bitcoin::bip32::Xpriv;
+	use bitcoin::hashes::{sha256, Hash};
+	use bitcoin::hex::FromHex;
+	use bitcoin::network::Network;
+	use bitcoin::secp256k1::constants::ONE;
+	use bitcoin::secp256k1::ecdh::SharedSecret;
+	use bitcoin::secp256k1::ecdsa::{RecoverableSignature, Signature};
+	use bitcoin::secp256k1::{schnorr, Error, PublicKey, Scalar, Secp256k1, SecretKey};
+	use bitcoin::{Address, PrivateKey, ScriptBuf, Txid, Witness};
+	use core::str::FromStr;
+	use lightning_invoice::RawBolt11Invoice;
+
+	#[test]
+	fn test_sign() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let zbase32_sig = sign(message.as_bytes(), &one_key);
+
+		assert_eq!(zbase32_sig, "d9tibmnic9t5y41hg7hkakdcra94akas9ku3rmmj4ag9mritc8ok4p5qzefs78c9pqfhpuftqqzhydbdwfg7u6w6wdxcqpqn4sj4e73e")
+	}
+
+	#[test]
+	fn test_recover_pk() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let sig = "d9tibmnic9t5y41hg7hkakdcra94akas9ku3rmmj4ag9mritc8ok4p5qzefs78c9pqfhpuftqqzhydbdwfg7u6w6wdxcqpqn4sj4e73e";
+		let pk = recover_pk(message.as_bytes(), sig);
+
+		assert_eq!(pk.unwrap(), PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key))
+	}
+
+	#[test]
+	fn test_recover_pk_with_secp_ctx() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let sig = sign(message.as_bytes(), &one_key);
+		let secp_ctx = Secp256k1::verification_only();
+
+		assert_eq!(
+			recover_pk_with_secp_ctx(&secp_ctx, message.as_bytes(), &sig),
+			recover_pk(message.as_bytes(), &sig)
+		);
+	}
+
+	#[test]
+	fn test_malformed_signature_lengths() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let sig = sign(message.as_bytes(), &one_key);
+		assert_eq!(sig.len(), MAX_SIGNATURE_LENGTH);
+
+		// Signatures which are too short or too long are rejected rather than panicking. Truncated
+		// signatures either decode to too few bytes, or end in a character which can't be decoded.
+		let recover_truncated = |len| recover_pk(message.as_bytes(), &sig[..len]);
+		assert_eq!(recover_truncated(0), Err(MessageSigningError::InvalidLength { got: 0 }));
+		assert_eq!(recover_truncated(1), Err(MessageSigningError::InvalidZbase32 { position: 0 }));
+		assert_eq!(recover_truncated(2), Err(MessageSigningError::InvalidZbase32 { position: 1 }));
+		assert_eq!(recover_truncated(64), Err(MessageSigningError::InvalidLength { got: 40 }));
+		let long_sig = sig.repeat(1000);
+		assert_eq!(
+			recover_pk(message.as_bytes(), &long_sig),
+			Err(MessageSigningError::InvalidLength { got: 65_000 })
+		);
+		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+		assert_eq!(verify_any(message.as_bytes(), &long_sig, &pk), None);
+
+		let sig_rec = sign_to_bytes(message.as_bytes(), &one_key);
+		assert!(decode_recoverable_signature(&sig_rec).is_ok());
+		let invalid_length = |got| Err(MessageSigningError::InvalidLength { got });
+		assert_eq!(decode_recoverable_signature(&[]), invalid_length(0));
+		assert_eq!(decode_recoverable_signature(&sig_rec[..1]), invalid_length(1));
+		assert_eq!(decode_recoverable_signature(&[sig_rec[0]; 66]), invalid_length(66));
+	}
+
+	#[test]
+	fn test_malformed_signature_errors() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let sig = sign(message.as_bytes(), &one_key);
+
+		// Characters outside of the zbase32 alphabet are reported at their position.
+		let mut typo_sig = sig.clone();
+		typo_sig.replace_range(10..11, "l");
+		assert_eq!(
+			recover_pk(message.as_bytes(), &typo_sig),
+			Err(MessageSigningError::InvalidZbase32 { position: 10 })
+		);
+		assert_eq!(
+			recover_pk(message.as_bytes(), "invalid"),
+			Err(MessageSigningError::InvalidZbase32 { position: 2 })
+		);
+
+		// Recovery prefixes outside of 27 to 34 are rejected.
+		let mut sig_rec = sign_to_bytes(message.as_bytes(), &one_key);
+		for prefix in [0, 26, 35, 255] {
+			sig_rec[0] = prefix;
+			assert_eq!(
+				recover_pk_from_bytes(message.as_bytes(), &sig_rec),
+				Err(MessageSigningError::InvalidRecoveryId)
+			);
+			let zbase32_sig = base32::Alphabet::ZBase32.encode(&sig_rec);
+			assert_eq!(
+				recover_pk(message.as_bytes(), &zbase32_sig),
+				Err(MessageSigningError::InvalidRecoveryId)
+			);
+		}
+
+		// Compact signatures whose R value exceeds the field size are rejected.
+		let mut invalid_sig_rec = [0xff; 65];
+		invalid_sig_rec[0] = 31;
+		assert_eq!(
+			recover_pk_from_bytes(message.as_bytes(), &invalid_sig_rec),
+			Err(MessageSigningError::InvalidSignature)
+		);
+
+		// Other secp256k1 errors are passed through.
+		assert_eq!(
+			MessageSigningError::from(Error::InvalidSecretKey),
+			MessageSigningError::Secp(Error::InvalidSecretKey)
+		);
+		assert_eq!(
+			MessageSigningError::InvalidZbase32 { position: 10 }.to_string(),
+			"Invalid zbase32 encoding at position 10"
+		);
+		assert_eq!(
+			MessageSigningError::InvalidLength { got: 40 }.to_string(),
+			"Invalid signature length: expected 65 bytes, got 40"
+		);
+	}
+
+	#[test]
+	fn test_verify() {
+		let message = "another message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let sig = sign(message.as_bytes(), &one_key);
+		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+
+		assert!(verify(message.as_bytes(), &sig, &pk))
+	}
+
+	#[test]
+	fn test_verify_against_node_id_encodings() {
+		let message = b"another message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let sig = sign(message, &one_key);
+		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+		let node_id = NodeId::from_pubkey(&pk);
+		let hex = pk.to_string();
+
+		assert_eq!(verify_against(message, &sig, &pk), Ok(true));
+		assert_eq!(verify_against(message, &sig, &node_id), Ok(true));
+		assert_eq!(verify_against(message, &sig, &hex), Ok(true));
+		assert_eq!(verify_against(message, &sig, hex.to_uppercase().as_str()), Ok(true));
+
+		let mut bech32 = String::new();
+		let hrp = bech32::Hrp::parse("ln").unwrap();
+		bech32::encode_to_fmt::<bech32::Bech32, _>(&mut bech32, hrp, &pk.serialize()).unwrap();
+		assert_eq!(verify_against(message, &sig, &bech32), Ok(true));
+		let mut bech32m = String::new();
+		bech32::encode_to_fmt::<bech32::Bech32m, _>(&mut bech32m, hrp, &pk.serialize()).unwrap();
+		assert_eq!(verify_against(message, &sig, &bech32m), Err(NodeIdParseError::InvalidEncoding));
+
+		// Signatures which are invalid or by other nodes fail verification, but aren't errors.
+		assert_eq!(verify_against(b"other message", &sig, &hex), Ok(false));
+		assert_eq!(verify_against(message, "invalid", &hex), Ok(false));
+
+		// Typos in the leading byte of hex node ids are rejected, unless they result in the node id
+		// of another node.
+		assert_eq!(verify_against(message, &sig, &hex[2..]), Err(NodeIdParseError::InvalidLength));
+		assert_eq!(
+			verify_against(message, &sig, &hex[1..]),
+			Err(NodeIdParseError::InvalidEncoding)
+		);
+		let prefixed = format!("0x{}", hex);
+		assert_eq!(
+			verify_against(message, &sig, &prefixed),
+			Err(NodeIdParseError::InvalidEncoding)
+		);
+		let uncompressed_prefix = format!("04{}", &hex[2..]);
+		assert_eq!(
+			verify_against(message, &sig, &uncompressed_prefix),
+			Err(NodeIdParseError::InvalidPublicKey)
+		);
+		let flipped_parity =
+			format!("{}{}", if &hex[..2] == "02" { "03" } else { "02" }, &hex[2..]);
+		assert_eq!(verify_against(message, &sig, &flipped_parity), Ok(false));
+
+		// The recovered node id round-trips via its hex encoding and the public key.
+		let recovered_node_id = recover_node_id(message, &sig).unwrap();
+		assert_eq!(recovered_node_id, node_id);
+		assert_eq!(recovered_node_id.as_pubkey().unwrap(), pk);
+		assert_eq!(NodeId::from_str(&recovered_node_id.to_string()).unwrap(), node_id);
+		assert_eq!(verify_against(message, &sig, &recovered_node_id.to_string()), Ok(true));
+		assert!(recover_node_id(message, "invalid").is_err());
+	}
+
+	#[test]
+	fn test_verify_ground_truth_ish() {
+		// There are no standard tests vectors for Sign/Verify, using the same tests vectors as c-lightning to see if they are compatible.
+		// Taken from https://github.com/ElementsProject/lightning/blob/1275af6fbb02460c8eb2f00990bb0ef9179ce8f3/tests/test_misc.py#L1925-L1938
+
+		let corpus = [
+			["@bitconner",
+			"is this compatible?",
+			"rbgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
+			"02b80cabdf82638aac86948e4c06e82064f547768dcef977677b9ea931ea75bab5"],
+			["@duck1123",
+			"hi",
+			"rnrphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
+			"02de60d194e1ca5947b59fe8e2efd6aadeabfb67f2e89e13ae1a799c1e08e4a43b"],
+			["@jochemin",
+			"hi",
+			"ry8bbsopmduhxy3dr5d9ekfeabdpimfx95kagdem7914wtca79jwamtbw4rxh69hg7n6x9ty8cqk33knbxaqftgxsfsaeprxkn1k48p3",
+			"022b8ece90ee891cbcdac0c1cc6af46b73c47212d8defbce80265ac81a6b794931"],
+		];
+
+		for c in &corpus {
+			let pk = PublicKey::from_str(c[3]).unwrap();
+			assert!(verify(c[1].as_bytes(), c[2], &pk));
+			// Signatures by lnd and CLN are low-S, and thus also accepted in strict mode.
+			assert_eq!(recover_pk_strict(c[1].as_bytes(), c[2]).unwrap(), pk);
+		}
+	}
+
+	#[test]
+	fn test_message_verifier() {
+		// The corpus of `test_verify_ground_truth_ish`, along with signatures using the recovery
+		// prefixes for uncompressed keys, signatures by other keys and malformed signatures.
+		let messages = ["is this compatible?", "hi", "another message"];
+		let sigs = [
+			"rbgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
+			"rnrphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
+			"ry8bbsopmduhxy3dr5d9ekfeabdpimfx95kagdem7914wtca79jwamtbw4rxh69hg7n6x9ty8cqk33knbxaqftgxsfsaeprxkn1k48p3",
+			"dtgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
+			"d1rphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
+			"invalid",
+			"",
+		];
+		let pks = [
+			"02b80cabdf82638aac86948e4c06e82064f547768dcef977677b9ea931ea75bab5",
+			"02de60d194e1ca5947b59fe8e2efd6aadeabfb67f2e89e13ae1a799c1e08e4a43b",
+			"022b8ece90ee891cbcdac0c1cc6af46b73c47212d8defbce80265ac81a6b794931",
+		]
+		.map(|pk| PublicKey::from_str(pk).unwrap());
+
+		let verifier = MessageVerifier::new();
+		let mut items = Vec::new();
+		for msg in messages.iter() {
+			for sig in sigs.iter() {
+				assert_eq!(
+					verifier.recover_pk(msg.as_bytes(), sig),
+					recover_pk(msg.as_bytes(), sig)
+				);
+				for pk in pks.iter() {
+					items.push((msg.as_bytes(), *sig, pk));
+				}
+			}
+		}
+		let results = verifier.verify_batch(&items);
+		assert_eq!(results.len(), items.len());
+		for ((msg, sig, pk), result) in items.iter().zip(results.iter()) {
+			assert_eq!(*result, verify(msg, sig, pk));
+			assert_eq!(*result, verifier.verify(msg, sig, pk));
+		}
+		// Each of the five valid signatures verifies against its signer only.
+		assert_eq!(results.iter().filter(|result| **result).count(), 5);
+		assert!(verifier.verify_batch(&[]).is_empty());
+	}
+
+	#[test]
+	fn test_sign_low_s() {
+		for i in 1..=32u8 {
+			let sk = SecretKey::from_slice(&[i; 32]).unwrap();
+			let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &sk);
+			let message = [i; 16];
+			let sig_bytes = sign_to_bytes(&message, &sk);
+			assert!(is_low_s(&decode_recoverable_signature(&sig_bytes).unwrap()));
+			assert_eq!(recover_pk_strict(&message, &sign(&message, &sk)).unwrap(), pk);
+		}
+	}
+
+	#[test]
+	fn test_recover_pk_strict_rejects_high_s() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+		let sig_bytes = sign_to_bytes(message.as_bytes(), &one_key);
+
+		// Malleate the signature by negating S modulo the curve order, flipping the parity of the
+		// recovery id accordingly, such that it still recovers the same key.
+		let mut high_s_sig_bytes = sig_bytes;
+		high_s_sig_bytes[0] = 31 + ((sig_bytes[0] - 31) ^ 1);
+		let s = SecretKey::from_slice(&sig_bytes[33..]).unwrap().negate();
+		high_s_sig_bytes[33..].copy_from_slice(&s.secret_bytes());
+		assert!(!is_low_s(&decode_recoverable_signature(&high_s_sig_bytes).unwrap()));
+
+		let high_s_sig = base32::Alphabet::ZBase32.encode(&high_s_sig_bytes);
+		assert_eq!(recover_pk(message.as_bytes(), &high_s_sig).unwrap(), pk);
+		assert_eq!(
+			recover_pk_strict(message.as_bytes(), &high_s_sig),
+			Err(MessageSigningError::InvalidSignature)
+		);
+
+		let low_s_sig = sign(message.as_bytes(), &one_key);
+		assert_eq!(recover_pk_strict(message.as_bytes(), &low_s_sig).unwrap(), pk);
+		assert_eq!(
+			recover_pk_strict(message.as_bytes(), "invalid"),
+			Err(MessageSigningError::InvalidZbase32 { position: 2 })
+		);
+	}
+
+	#[test]
+	fn test_recover_pk_uncompressed_prefix() {
+		// The signatures of `test_verify_ground_truth_ish`, using the recovery prefixes of 27 to 30
+		// rather than 31 to 34, as emitted by lnd and other tools following the convention for
+		// uncompressed keys. Both the zbase32 and the hex encoding of the raw signature are given.
+		let corpus = [
+			["is this compatible?",
+			"dtgfioj114mh48d8egqx8o9qxqw4fmhe8jbeeabdioxnjk8z3t1ma1hu1fiswpakgucwwzwo6ofycffbsqusqdimugbh41n1g698hr9t",
+			"1c4c5ac1329697cd1c67419cf3c3ee7ba9a2af883a42846023ac1e24a8f7cc64bc4b93916b6a370a34d94a5e90f40a0614a1b3a7670eab9983cd485237be7e13f1",
+			"02b80cabdf82638aac86948e4c06e82064f547768dcef977677b9ea931ea75bab5"],
+			["hi",
+			"d1rphcjswusbacjnmmmrynh9pqip7sy5cx695h6mfu64iac6qmcmsd8xnsyczwmpqp9shqkth3h4jmkgyqu5z47jfn1q7gpxtaqpx4xg",
+			"1c88de3136a4ec1c31225ad6400b9f6baaded81b63fdfdf3cb2cfdaae19e72d8bb0cef1580cbd16d737f6e3951e679a4ad4603a7bbeba928a4ee99af8e1cd7e9e6",
+			"02de60d194e1ca5947b59fe8e2efd6aadeabfb67f2e89e13ae1a799c1e08e4a43b"],
+			["hi",
+			"do8bbsopmduhxy3dr5d9ekfeabdpimfx95kagdem7914wtca79jwamtbw4rxh69hg7n6x9ty8cqk33knbxaqftgxsfsaeprxkn1k48p3",
+			"1c0e10da0d58e7c7832326c7f428a8c046daacaffed5830d0befe5aa4598efd34c2e21a688fe7bfc3745e7fe203b1cace5420bf0e2c4cfb16d84348f50a4ad1db9",
+			"022b8ece90ee891cbcdac0c1cc6af46b73c47212d8defbce80265ac81a6b794931"],
+		];
+
+		for c in &corpus {
+			let pk = PublicKey::from_str(c[3]).unwrap();
+			assert!(verify(c[0].as_bytes(), c[1], &pk));
+			assert_eq!(recover_pk(c[0].as_bytes(), c[1]).unwrap(), pk);
+
+			let sig_bytes: [u8; 65] = <Vec<u8>>::from_hex(c[2]).unwrap().try_into().unwrap();
+			assert_eq!(recover_pk_from_bytes(c[0].as_bytes(), &sig_bytes).unwrap(), pk);
+		}
+	}
+
+	#[test]
+	fn test_single_hash() {
+		// Signatures of lnd's single-hash scheme for the key 0x4242..42. These were computed with
+		// RFC 6979 nonces and low-S normalization as used by both lnd and libsecp256k1, thus match
+		// what `lncli signmessage --single_hash` outputs for the same key.
+		let corpus = [
+			["is this compatible?",
+			"rdwa3kdgkbom3wg6zhrx8xah8brcfpypmjxu8xh17ca39mtxtn8r46bwkaszzjjd73szuwjuhmr71q1ty6qnfnwicqjsj8oj4x6chrqk"],
+			["hi",
+			"rbnaccoi3zmaiexuwtjrtcoesqcepmy4q5chuek7mhkbxfm9cy3ny81oj4k6oicw9ju4sj984jk3r7ocyc518am1g57qni1rsqnyx9so"],
+			["",
+			"rbcceb7nchjf87766oj3bs7bap53tnpfqgkyx9qbm1cxgtssxkjzq4eir6xqwt1tseb936weif1aeq3zqueyhtw5jb9yee5urq6wtuof"],
+		];
+		let sk = SecretKey::from_slice(&[0x42; 32]).unwrap();
+		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &sk);
+		assert_eq!(
+			pk,
+			PublicKey::from_str(
+				"0324653eac434488002cc06bbfb7f10fe18991e35f9fe4302dbea6d2353dc0ab1c"
+			)
+			.unwrap()
+		);
+
+		for c in &corpus {
+			let msg = c[0].as_bytes();
+			assert_eq!(sign_single_hash(msg, &sk), c[1]);
+			assert_eq!(recover_pk_single_hash(msg, c[1]).unwrap(), pk);
+			assert!(verify_single_hash(msg, c[1], &pk));
+			assert_eq!(verify_any(msg, c[1], &pk), Some(MessageSignatureScheme::SingleHash));
+
+			// The schemes aren't interchangeable.
+			assert!(!verify(msg, c[1], &pk));
+			let double_hash_sig = sign(msg, &sk);
+			assert!(!verify_single_hash(msg, &double_hash_sig, &pk));
+			assert_eq!(
+				verify_any(msg, &double_hash_sig, &pk),
+				Some(MessageSignatureScheme::DoubleHash)
+			);
+		}
+
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let one_pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+		let sig = "d7mds8n7thrtgqt9fuuh4q6h9c9b1gyopehjkrmp6qqnxp3pkntg178pmz3ya4w3ra3um8mwhk87zuxifz3gwyrwc5t8qxmfig3zn75e";
+		assert_eq!(sign_single_hash("test message".as_bytes(), &one_key), sig);
+		assert_eq!(verify_any("test message".as_bytes(), sig, &pk), None);
+		assert_eq!(verify_any("other message".as_bytes(), sig, &one_pk), None);
+		assert!(recover_pk_single_hash("test message".as_bytes(), "invalid").is_err());
+	}
+
+	#[test]
+	fn test_sign_to_bytes() {
+		let message = "test message";
+		let one_key = SecretKey::from_slice(&ONE).unwrap();
+		let pk = PublicKey::from_secret_key(&Secp256k1::signing_only(), &one_key);
+		let mut sig_bytes = sign_to_bytes(message.as_bytes(), &one_key);
+
+		assert!((31..=34).contains(&sig_bytes[0]));
+		assert_eq!(recover_pk_from_bytes(message.as_bytes(), &sig_bytes).unwrap(), pk);
+		assert_eq!(
+			recover_pk(message.as_bytes(), &sign(message.as_bytes(), &one_key)).unwrap(),
+			pk
+		);
+
+		// The same signature is accepted with the prefix for uncompressed keys.
+		sig_bytes[0] -= 4;
+		assert_eq!(recover_pk_from_bytes(message.as_bytes(), &sig_bytes).unwrap(), pk);
+
+		// Any other prefix is rejected.
+		for prefix in [0, 26, 35, 255] {
+			sig_bytes[0] = prefix;
+			assert!(recover_pk_from_bytes(message.as_bytes(), &sig_bytes).is_err());
+		}
+	}
+
+	#[test]
+	fn test_sign_with_derived_key() {
+		// Test vectors for keys derived at m/1017'/0'/{family}'/0/{index} from a fixed seed.
+		let vectors = [
+			(
+				6,
+				0,
+				"032a30bdc67f618be95531a577f4e52b24aa59130a7b411cb79971b285c0d8cf60",
+				"rycmweo8d7mb5dd9ai9s9gaebqa1htatr44owhw61wp8a95u97xiea6g44aefcntx51ph3fu3ymmprsp4b9am9o6yasdsunss4jfopo6",
+			),
+			(
+				6,
+				1,
+				"0330eb0087379c053fe3c6bbef2750a760e57ea77537b1a56d5e705b14df4fdd85",
+				"d9prixdq1scgrane8zkiw71a5iyr7th1mck1nou45huinpmhba54y9pf55jumfmd9oji7tz4obuo5tndix1b4zgas1pbobsk99xo594j",
+			),
+			(
+				0,
+				0,
+				"031989cfd6fb0174c2b712b14053eca43c8dc054baa1dc7f66df4634a988da0bda",
+				"d9tx91p85rkyibjmi4a3mg8mgipaz9ifatqwt94xkdzxbktjoika6ixrzdtay6dt7nh73dtrynafy1fhzpfnrrdo811efp6rxf4jo9m8",
+			),
+		];
+
+		let message = "test message";
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		// The same seed always derives the same keys, regardless of the starting time.
+		let restarted_keys_manager = KeysManager::new(&[42; 32], 43, 43);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+
+		for (family, index, pubkey, sig) in vectors {
+			let key_id = MessageSigningKeyId { family, index };
+			let expected_pubkey = PublicKey::from_str(pubkey).unwrap();
+			assert_eq!(keys_manager.get_message_signing_pubkey(key_id), Ok(expected_pubkey));
+			assert_eq!(
+				restarted_keys_manager.get_message_signing_pubkey(key_id),
+				Ok(expected_pubkey)
+			);
+			assert_ne!(expected_pubkey, node_id);
+
+			assert_eq!(
+				sign_with_derived_key(message.as_bytes(), key_id, &keys_manager).unwrap(),
+				sig
+			);
+			assert_eq!(
+				sign_with_derived_key(message.as_bytes(), key_id, &restarted_keys_manager).unwrap(),
+				sig
+			);
+			assert_eq!(recover_pk(message.as_bytes(), sig).unwrap(), expected_pubkey);
+			assert!(verify(message.as_bytes(), sig, &expected_pubkey));
+			assert!(!verify(message.as_bytes(), sig, &node_id));
+		}
+
+		// Hardened or otherwise out-of-range indices are rejected.
+		let key_id = MessageSigningKeyId { family: 1 << 31, index: 0 };
+		assert!(keys_manager.get_message_signing_pubkey(key_id).is_err());
+		assert!(sign_with_derived_key(message.as_bytes(), key_id, &keys_manager).is_err());
+		let key_id = MessageSigningKeyId { family: 6, index: 1 << 31 };
+		assert!(keys_manager.get_message_signing_pubkey(key_id).is_err());
+		assert!(sign_with_derived_key(message.as_bytes(), key_id, &keys_manager).is_err());
+	}
+
+	// The private key of the BIP 322 test vectors.
+	const BIP322_PRIVATE_KEY: &str = "L3VFeEujGtevx9w18HD1fhRbCH67Az2dpCymeRE1SoPK6XQtaN2k";
+	// The P2WPKH and P2TR addresses of the BIP 322 test vectors, both controlled by the key above.
+	const BIP322_P2WPKH_ADDRESS: &str = "bc1q9vza2e8x573nczrlzms0wvx3gsqjx7vavgkx0l";
+	const BIP322_P2TR_ADDRESS: &str =
+		"bc1ppv609nr0vr25u07u95waq5lucwfm6tde4nydujnu8npg4q75mr5sxq8lt3";
+
+	fn address_script(address: &str) -> ScriptBuf {
+		Address::from_str(address).unwrap().assume_checked().script_pubkey()
+	}
+
+	#[test]
+	fn test_bip322_virtual_transactions() {
+		// Test vectors from BIP 322.
+		let script_pubkey = address_script(BIP322_P2WPKH_ADDRESS);
+		let vectors = [
+			(
+				"",
+				"c5680aa69bb8d860bf82d4e9cd3504b55dde018de765a91bb566283c545a99a7",
+				"1e9654e951a5ba44c8604c4de6c67fd78a27e81dcadcfe1edf638ba3aaebaed6",
+			),
+			(
+				"Hello World",
+				"b79d196740ad5217771c1098fc4a4b51e0535c32236c71f1ea4d61a2d603352b",
+				"88737ae86f2077145f93cc4b153ae9a1cb8d56afa511988c149c5c8c9d93bddf",
+			),
+		];
+		for (message, to_spend_txid, to_sign_txid) in vectors {
+			let to_spend = bip322_to_spend_tx(message.as_bytes(), &script_pubkey);
+			assert_eq!(to_spend.compute_txid(), Txid::from_str(to_spend_txid).unwrap());
+			let to_sign = bip322_to_sign_tx(to_spend.compute_txid(), Witness::new());
+			assert_eq!(to_sign.compute_txid(), Txid::from_str(to_sign_txid).unwrap());
+		}
+	}
+
+	#[test]
+	fn test_bip322_p2wpkh() {
+		// Test vectors from BIP 322.
+		let sk = PrivateKey::from_wif(BIP322_PRIVATE_KEY).unwrap().inner;
+		let script_pubkey = address_script(BIP322_P2WPKH_ADDRESS);
+		let vectors = [
+			("", "AkcwRAIgM2gBAQqvZX15ZiysmKmQpDrG83avLIT492QBzLnQIxYCIBaTpOaD20qRlEylyxFSeEA2ba9YOixpX8z46TSDtS40ASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="),
+			("Hello World", "AkcwRAIgZRfIY3p7/DoVTty6YZbWS71bc5Vct9p9Fia83eRmw2QCICK/ENGfwLtptFluMGs2KsqoNSk89pO7F29zJLUx9a/sASECx/EgAxlkQpQ9hYjgGu6EBCPMVPwVIVJqO4XCsMvViHI="),
+		];
+		for (message, sig) in vectors {
+			assert_eq!(sign_bip322(message.as_bytes(), &script_pubkey, &sk).unwrap(), sig);
+			assert!(verify_bip322(message.as_bytes(), sig, &script_pubkey));
+		}
+
+		// Signatures without a low R value, as produced by other signers, are valid as well.
+		let sig = "AkgwRQIhAOzyynlqt93lOKJr+wmmxIens//zPzl9tqIOua93wO6MAiBi5n5EyAcPScOjf1lAqIUIQtr3zKNeavYabHyR8eGhowEhAsfxIAMZZEKUPYWI4BruhAQjzFT8FSFSajuFwrDL1Yhy";
+		assert!(verify_bip322(b"Hello World", sig, &script_pubkey));
+
+		// The signature commits to the exact message and script.
+		assert!(!verify_bip322(b"Hello World!", sig, &script_pubkey));
+		assert!(!verify_bip322(b"", sig, &script_pubkey));
+		let other_sk = SecretKey::from_slice(&ONE).unwrap();
+		let other_pk = bitcoin::CompressedPublicKey(other_sk.public_key(&Secp256k1::new()));
+		let other_script_pubkey = ScriptBuf::new_p2wpkh(&other_pk.wpubkey_hash());
+		assert!(!verify_bip322(b"Hello World", sig, &other_script_pubkey));
+
+		// We can't sign for scripts which don't pay to our key.
+		assert!(sign_bip322(b"Hello World", &other_script_pubkey, &sk).is_err());
+	}
+
+	#[test]
+	fn test_bip322_p2tr() {
+		// Test vector from BIP 322.
+		let sk = PrivateKey::from_wif(BIP322_PRIVATE_KEY).unwrap().inner;
+		let script_pubkey = address_script(BIP322_P2TR_ADDRESS);
+		let sig = "AUHd69PrJQEv+oKTfZ8l+WROBHuy9HKrbFCJu7U1iK2iiEy1vMU5EfMtjc+VSHM7aU0SDbak5IUZRVno2P5mjSafAQ==";
+		assert!(verify_bip322(b"Hello World", sig, &script_pubkey));
+		assert!(!verify_bip322(b"Hello World!", sig, &script_pubkey));
+		assert!(!verify_bip322(b"Hello World", sig, &address_script(BIP322_P2WPKH_ADDRESS)));
+
+		// Our signatures use the default sighash type and can be verified.
+		let sig = sign_bip322(b"Hello World", &script_pubkey, &sk).unwrap();
+		assert!(verify_bip322(b"Hello World", &sig, &script_pubkey));
+		assert!(!verify_bip322(b"", &sig, &script_pubkey));
+
+		let other_sk = SecretKey::from_slice(&ONE).unwrap();
+		assert!(sign_bip322(b"Hello World", &script_pubkey, &other_sk).is_err());
+	}
+
+	#[test]
+	fn test_bip322_unsupported_scripts() {
+		let sk = PrivateKey::from_wif(BIP322_PRIVATE_KEY).unwrap().inner;
+		let pk = bitcoin::PublicKey::new(sk.public_key(&Secp256k1::new()));
+		let p2pkh_script = ScriptBuf::new_p2pkh(&pk.pubkey_hash());
+		assert!(sign_bip322(b"Hello World", &p2pkh_script, &sk).is_err());
+
+		// Malformed signatures are rejected rather than panicking.
+		let script_pubkey = address_script(BIP322_P2WPKH_ADDRESS);
+		let sig = sign_bip322(b"Hello World", &script_pubkey, &sk).unwrap();
+		assert!(!verify_bip322(b"Hello World", &sig, &p2pkh_script));
+		assert!(!verify_bip322(b"Hello World", "", &script_pubkey));
+		assert!(!verify_bip322(b"Hello World", "not base64", &script_pubkey));
+		assert!(!verify_bip322(b"Hello World", &sig[4..], &script_pubkey));
+	}
+
+	#[test]
+	fn test_verify_node_announcement_signature() {
+		let secp_ctx = Secp256k1::new();
+		let logger = Arc::new(test_utils::TestLogger::new());
+		let network_graph = Arc::new(NetworkGraph::new(Network::Testnet, Arc::clone(&logger)));
+		let gossip_sync = P2PGossipSync::new(Arc::clone(&network_graph), None, Arc::clone(&logger));
+
+		// Only the first node has sent a node announcement, while the third is not in the graph.
+		let privkeys: Vec<SecretKey> =
+			(1..4).map(|i| SecretKey::from_slice(&[i; 32]).unwrap()).collect();
+		let pubkeys: Vec<PublicKey> =
+			privkeys.iter().map(|privkey| PublicKey::from_secret_key(&secp_ctx, privkey)).collect();
+		add_channel(
+			&gossip_sync,
+			&secp_ctx,
+			&privkeys[0],
+			&privkeys[1],
+			ChannelFeatures::empty(),
+			1,
+		);
+		add_or_update_node(&gossip_sync, &secp_ctx, &privkeys[0], NodeFeatures::empty(), 100);
+
+		let node_ids: Vec<NodeId> = pubkeys.iter().map(NodeId::from_pubkey).collect();
+		let verify_node = |msg: &str, sig: &str, node_id: NodeId| {
+			verify_node_announcement_signature(msg.as_bytes(), sig, node_id, &network_graph)
+		};
+
+		let message = "I am node 0";
+		let sig = sign(message.as_bytes(), &privkeys[0]);
+		let node_info = network_graph.read_only().node(&node_ids[0]).cloned().unwrap();
+		let announcement_info = node_info.announcement_info.unwrap();
+		assert_eq!(verify_node(message, &sig, node_ids[0]), Ok(announcement_info.clone()));
+
+		// The `PublicKey` form of the node id is accepted as well.
+		assert_eq!(
+			verify_node_announcement_signature(
+				message.as_bytes(),
+				&sig,
+				pubkeys[0],
+				&network_graph
+			),
+			Ok(announcement_info)
+		);
+
+		// The signature is not valid for another node, nor for another message.
+		let err = Err(NodeSignatureError::InvalidSignature);
+		assert_eq!(verify_node(message, &sig, node_ids[1]), err);
+		assert_eq!(verify_node("I am node 1", &sig, node_ids[0]), err);
+		assert_eq!(verify_node(message, "invalid", node_ids[0]), err);
+
+		// Valid signatures from nodes without a node announcement or not in the graph are
+		// distinguished.
+		let sig = sign(message.as_bytes(), &privkeys[1]);
+		let err = Err(NodeSignatureError::MissingNodeAnnouncement);
+		assert_eq!(verify_node(message, &sig, node_ids[1]), err);
+		let sig = sign(message.as_bytes(), &privkeys[2]);
+		assert_eq!(verify_node(message, &sig, node_ids[2]), Err(NodeSignatureError::UnknownNode));
+	}
+
+	#[test]
+	fn test_payment_attestation() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let payment_preimage = PaymentPreimage([1; 32]);
+		let payment_hash = PaymentHash(sha256::Hash::hash(&payment_preimage.0).to_byte_array());
+		let memo = Some("1 cup of coffee".to_owned());
+
+		let blob =
+			sign_payment_attestation(payment_hash, payment_preimage, memo.clone(), &keys_manager)
+				.unwrap();
+		let expected = PaymentAttestation { payment_hash, payment_preimage, memo };
+		assert_eq!(verify_payment_attestation(&blob, &node_id), Ok(expected.clone()));
+
+		let blob =
+			sign_payment_attestation(payment_hash, payment_preimage, None, &keys_manager).unwrap();
+		let attestation = verify_payment_attestation(&blob, &node_id).unwrap();
+		assert_eq!(attestation, PaymentAttestation { memo: None, ..expected });
+
+		// Attestations for a preimage which doesn't match the payment hash aren't signed.
+		let other_preimage = PaymentPreimage([2; 32]);
+		assert!(
+			sign_payment_attestation(payment_hash, other_preimage, None, &keys_manager).is_err()
+		);
+	}
+
+	#[test]
+	fn test_payment_attestation_tampering() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let payment_preimage = PaymentPreimage([1; 32]);
+		let payment_hash = PaymentHash(sha256::Hash::hash(&payment_preimage.0).to_byte_array());
+		let memo = Some("1 cup of coffee".to_owned());
+		let blob =
+			sign_payment_attestation(payment_hash, payment_preimage, memo, &keys_manager).unwrap();
+		let signed: SignedPaymentAttestation = Readable::read(&mut &blob[..]).unwrap();
+
+		// Only the signing node is accepted as the payer.
+		let other_node_id =
+			PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&ONE).unwrap());
+		let err = Err(PaymentAttestationError::InvalidSignature);
+		assert_eq!(verify_payment_attestation(&blob, &other_node_id), err);
+
+		// Changing the memo invalidates the signature.
+		let mut tampered = SignedPaymentAttestation {
+			attestation: signed.attestation.clone(),
+			signature: signed.signature.clone(),
+		};
+		tampered.attestation.memo = Some("2 cups of coffee".to_owned());
+		assert_eq!(verify_payment_attestation(&tampered.encode(), &node_id), err);
+		tampered.attestation.memo = None;
+		assert_eq!(verify_payment_attestation(&tampered.encode(), &node_id), err);
+
+		// A preimage not matching the payment hash is rejected, even if validly signed.
+		let attestation = PaymentAttestation {
+			payment_hash,
+			payment_preimage: PaymentPreimage([2; 32]),
+			memo: None,
+		};
+		let signature =
+			sign(&payment_attestation_msg(&attestation), &keys_manager.get_node_secret_key());
+		let tampered = SignedPaymentAttestation { attestation, signature };
+		assert_eq!(
+			verify_payment_attestation(&tampered.encode(), &node_id),
+			Err(PaymentAttestationError::PreimageMismatch)
+		);
+
+		// Truncated or extended blobs are rejected.
+		let err = Err(PaymentAttestationError::InvalidEncoding);
+		assert_eq!(verify_payment_attestation(&blob[..blob.len() - 1], &node_id), err);
+		let mut extended = blob.clone();
+		extended.push(0);
+		assert_eq!(verify_payment_attestation(&extended, &node_id), err);
+		assert_eq!(verify_payment_attestation(&[], &node_id), err);
+	}
+
+	#[test]
+	fn test_node_metrics_snapshot() {
+		use crate::ln::functional_test_utils::*;
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		create_announced_chan_between_nodes_with_value(&nodes, 0, 1, 1_000_000, 400_000_000);
+		create_announced_chan_between_nodes_with_value(&nodes, 1, 2, 500_000, 0);
+		let node_id = nodes[1].node.get_our_node_id();
+		let channels = nodes[1].node.list_channels();
+		assert_eq!(channels.len(), 2);
+
+		let blob = sign_node_metrics_snapshot(&channels, 42, nodes[1].keys_manager).unwrap();
+		let (snapshot, signer_node_id) = verify_node_metrics_snapshot(&blob).unwrap();
+		assert_eq!(signer_node_id, node_id);
+		assert_eq!(snapshot, NodeMetricsSnapshot::from_channels(&channels, 42));
+		assert_eq!(snapshot.timestamp, 42);
+		assert_eq!(snapshot.num_channels, 2);
+		assert_eq!(snapshot.num_usable_channels, 2);
+		assert_eq!(snapshot.total_capacity_sat, 1_500_000);
+		assert_eq!(
+			snapshot.total_inbound_capacity_msat,
+			channels.iter().map(|channel| channel.inbound_capacity_msat).sum::<u64>()
+		);
+		assert_eq!(
+			snapshot.total_outbound_capacity_msat,
+			channels.iter().map(|channel| channel.outbound_capacity_msat).sum::<u64>()
+		);
+
+		// The blob, including its signature, doesn't depend on the order of the channels.
+		let reordered_channels: Vec<_> = channels.iter().rev().cloned().collect();
+		assert_ne!(reordered_channels[0].channel_id, channels[0].channel_id);
+		let reordered_blob =
+			sign_node_metrics_snapshot(&reordered_channels, 42, nodes[1].keys_manager).unwrap();
+		assert_eq!(reordered_blob, blob);
+
+		// A snapshot taken at a different time is signed differently.
+		let later_blob = sign_node_metrics_snapshot(&channels, 43, nodes[1].keys_manager).unwrap();
+		assert_ne!(later_blob, blob);
+		assert_eq!(verify_node_metrics_snapshot(&later_blob).unwrap().1, node_id);
+	}
+
+	#[test]
+	fn test_node_metrics_snapshot_tampering() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let blob = sign_node_metrics_snapshot(&[], 42, &keys_manager).unwrap();
+		let (snapshot, signer_node_id) = verify_node_metrics_snapshot(&blob).unwrap();
+		assert_eq!(signer_node_id, node_id);
+		assert_eq!(snapshot, NodeMetricsSnapshot::from_channels(&[], 42));
+		let signed: SignedNodeMetricsSnapshot = Readable::read(&mut &blob[..]).unwrap();
+
+		// Changing the metrics changes the node id the signature recovers to.
+		let mut tampered = SignedNodeMetricsSnapshot {
+			snapshot: signed.snapshot.clone(),
+			signature: signed.signature.clone(),
+		};
+		tampered.snapshot.total_inbound_capacity_msat = 1_000_000_000;
+		let (_, tampered_node_id) = verify_node_metrics_snapshot(&tampered.encode()).unwrap();
+		assert_ne!(tampered_node_id, node_id);
+
+		// Messages signed without the snapshot prefix aren't accepted as snapshots.
+		let signature = sign(&signed.snapshot.encode(), &keys_manager.get_node_secret_key());
+		let unprefixed = SignedNodeMetricsSnapshot { snapshot: signed.snapshot.clone(), signature };
+		let (_, unprefixed_node_id) = verify_node_metrics_snapshot(&unprefixed.encode()).unwrap();
+		assert_ne!(unprefixed_node_id, node_id);
+		let signature =
+			sign(&node_metrics_snapshot_msg(&signed.snapshot), &keys_manager.get_node_secret_key());
+		assert_eq!(signature, signed.signature);
+
+		// Trailing data and malformed signatures are rejected.
+		let mut trailing = blob.clone();
+		trailing.push(0);
+		let err = Err(NodeMetricsSnapshotError::InvalidEncoding);
+		assert_eq!(verify_node_metrics_snapshot(&trailing), err);
+		assert_eq!(verify_node_metrics_snapshot(&blob[..blob.len() - 1]), err);
+		let malformed =
+			SignedNodeMetricsSnapshot { snapshot: signed.snapshot, signature: "abc".to_owned() };
+		assert_eq!(
+			verify_node_metrics_snapshot(&malformed.encode()),
+			Err(NodeMetricsSnapshotError::InvalidSignature)
+		);
+	}
+
+	#[test]
+	fn test_sign_with_expiry() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let sk = keys_manager.get_node_secret_key();
+		let message = "Log me in";
+		let expiry: u64 = 1_700_000_000;
+
+		// The token consists of the hex-encoded expiry and the signature of the expiry followed by
+		// the message.
+		let token = sign_with_expiry(message.as_bytes(), expiry, &sk);
+		let signed_msg = [&expiry.to_be_bytes()[..], message.as_bytes()].concat();
+		assert_eq!(token, format!("000000006553f100:{}", sign(&signed_msg, &sk)));
+		assert_eq!(
+			sign_with_expiry_with_node_signer(message.as_bytes(), expiry, &keys_manager).unwrap(),
+			token
+		);
+
+		// The token is accepted up to and including its expiry.
+		assert_eq!(verify_with_expiry(message.as_bytes(), &token, &node_id, 0), Ok(expiry));
+		assert_eq!(verify_with_expiry(message.as_bytes(), &token, &node_id, expiry), Ok(expiry));
+		assert_eq!(
+			verify_with_expiry(message.as_bytes(), &token, &node_id, expiry + 1),
+			Err(ExpiringSignatureError::Expired { expiry_unix_secs: expiry })
+		);
+		assert_eq!(
+			verify_with_expiry(message.as_bytes(), &token, &node_id, u64::MAX),
+			Err(ExpiringSignatureError::Expired { expiry_unix_secs: expiry })
+		);
+
+		// Tokens dated far in the future are accepted, leaving it to the caller to bound their
+		// lifetime via the returned expiry.
+		let future_token = sign_with_expiry(message.as_bytes(), u64::MAX, &sk);
+		assert!(future_token.starts_with("ffffffffffffffff:"));
+		assert_eq!(
+			verify_with_expiry(message.as_bytes(), &future_token, &node_id, expiry),
+			Ok(u64::MAX)
+		);
+
+		// An expired token for a different message or key is rejected for its signature.
+		let other_sk = SecretKey::from_slice(&ONE).unwrap();
+		let other_token = sign_with_expiry(message.as_bytes(), expiry, &other_sk);
+		assert_eq!(
+			verify_with_expiry(message.as_bytes(), &other_token, &node_id, u64::MAX),
+			Err(ExpiringSignatureError::InvalidSignature)
+		);
+		assert_eq!(
+			verify_with_expiry(b"Log me out", &token, &node_id, 0),
+			Err(ExpiringSignatureError::InvalidSignature)
+		);
+
+		// The signature doesn't verify as a plain signature of the message.
+		let (_, sig) = token.split_once(':').unwrap();
+		assert!(!verify(message.as_bytes(), sig, &node_id));
+	}
+
+	#[test]
+	fn test_sign_with_expiry_tampering() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let sk = keys_manager.get_node_secret_key();
+		let message = b"Log me in";
+		let expiry: u64 = 1_700_000_000;
+		let token = sign_with_expiry(message, expiry, &sk);
+		let (_, sig) = token.split_once(':').unwrap();
+
+		// Extending the expiry invalidates the signature, rather than the token being expired.
+		let tampered = format!("{:016x}:{}", expiry + 3600, sig);
+		assert_eq!(
+			verify_with_expiry(message, &tampered, &node_id, expiry + 1),
+			Err(ExpiringSignatureError::InvalidSignature)
+		);
+		assert_eq!(
+			verify_with_expiry(message, &tampered, &node_id, 0),
+			Err(ExpiringSignatureError::InvalidSignature)
+		);
+
+		// Malformed composites are rejected before verifying the signature.
+		let malformed = [
+			sig.to_owned(),
+			format!("{:x}:{}", expiry, sig),
+			format!("{:016X}:{}", 0xabcdefu64, sig),
+			format!("{:016x}{}", expiry, sig),
+			format!("{:016x};{}", expiry, sig),
+			format!("0x{:014x}:{}", expiry, sig),
+			format!("{:017x}:{}", expiry, sig),
+			String::new(),
+		];
+		for token in malformed.iter() {
+			assert_eq!(
+				verify_with_expiry(message, token, &node_id, 0),
+				Err(ExpiringSignatureError::InvalidEncoding),
+				"{}",
+				token
+			);
+		}
+
+		// A well-formed composite with a malformed signature is rejected for its signature.
+		for sig in ["", "abc", &sig[1..]] {
+			assert_eq!(
+				verify_with_expiry(message, &format!("{:016x}:{}", expiry, sig), &node_id, 0),
+				Err(ExpiringSignatureError::InvalidSignature)
+			);
+		}
+	}
+
+	#[test]
+	fn test_tlv_envelope() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let sk = keys_manager.get_node_secret_key();
+		let large_value = [0xab; 300];
+		let fields: [(u64, &[u8]); 4] =
+			[(1, b"alice"), (4, b""), (253, &large_value), (0x10000, b"bob")];
+
+		let envelope = sign_tlv(&fields, &sk).unwrap();
+		let (parsed_fields, signer) = verify_tlv(&envelope).unwrap();
+		assert_eq!(signer, node_id);
+		let expected_fields: Vec<(u64, Vec<u8>)> =
+			fields.iter().map(|(typ, value)| (*typ, value.to_vec())).collect();
+		assert_eq!(parsed_fields, expected_fields);
+
+		// The fields are encoded in ascending type order, with the signature over the prefixed
+		// stream preceding them.
+		let stream = &envelope[65..];
+		assert_eq!(&stream[..7], &[1, 5, b'a', b'l', b'i', b'c', b'e']);
+		assert_eq!(&envelope[..65], &sign_to_bytes(&tlv_envelope_msg(stream), &sk)[..]);
+
+		// Reordering the fields yields the same envelope, including its signature.
+		let mut reversed_fields = fields;
+		reversed_fields.reverse();
+		assert_eq!(sign_tlv(&reversed_fields, &sk).unwrap(), envelope);
+		let shuffled_fields = [fields[2], fields[0], fields[3], fields[1]];
+		assert_eq!(sign_tlv(&shuffled_fields, &sk).unwrap(), envelope);
+		assert_eq!(sign_tlv_with_node_signer(&shuffled_fields, &keys_manager).unwrap(), envelope);
+
+		// Empty envelopes are valid, but duplicate types are rejected.
+		let empty_envelope = sign_tlv(&[], &sk).unwrap();
+		assert_eq!(verify_tlv(&empty_envelope).unwrap(), (Vec::new(), node_id));
+		let duplicate_fields: [(u64, &[u8]); 2] = [(1, b"alice"), (1, b"bob")];
+		assert!(sign_tlv(&duplicate_fields, &sk).is_err());
+		assert!(sign_tlv_with_node_signer(&duplicate_fields, &keys_manager).is_err());
+	}
+
+	#[test]
+	fn test_tlv_envelope_tampering() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let sk = keys_manager.get_node_secret_key();
+		let fields: [(u64, &[u8]); 3] = [(0, b"alice"), (2, b"bob"), (42, b"carol")];
+		let envelope = sign_tlv(&fields, &sk).unwrap();
+
+		// Tampering with any byte, whether of the signature, a type, a length or a value, either
+		// fails to decode or recovers a different node id.
+		for idx in 0..envelope.len() {
+			let mut tampered = envelope.clone();
+			tampered[idx] ^= 1;
+			match verify_tlv(&tampered) {
+				Ok((_, signer)) => assert_ne!(signer, node_id),
+				Err(_) => {},
+			}
+		}
+
+		// Messages signed without the envelope prefix aren't accepted as envelopes.
+		let stream = &envelope[65..];
+		let unprefixed = [&sign_to_bytes(stream, &sk)[..], stream].concat();
+		assert_ne!(verify_tlv(&unprefixed).unwrap().1, node_id);
+
+		// Duplicate and out-of-order types as well as non-minimally encoded types are rejected.
+		let signature = &envelope[..65];
+		let non_canonical = Err(TlvEnvelopeError::NonCanonicalEncoding);
+		let out_of_order = [signature, &[2, 1, b'a', 1, 1, b'b']].concat();
+		assert_eq!(verify_tlv(&out_of_order), non_canonical);
+		let duplicate = [signature, &[1, 1, b'a', 1, 1, b'b']].concat();
+		assert_eq!(verify_tlv(&duplicate), non_canonical);
+		let non_minimal = [signature, &[0xfd, 0, 1, 1, b'a']].concat();
+		assert_eq!(verify_tlv(&non_minimal), non_canonical);
+
+		// Truncated envelopes are rejected.
+		let invalid_encoding = Err(TlvEnvelopeError::InvalidEncoding);
+		assert_eq!(verify_tlv(&envelope[..envelope.len() - 1]), invalid_encoding);
+		assert_eq!(verify_tlv(&[signature, &[1]].concat()), invalid_encoding);
+		assert_eq!(verify_tlv(&envelope[..64]), invalid_encoding);
+	}
+
+	#[test]
+	fn test_payer_proof() {
+		use crate::ln::channelmanager::PaymentId;
+		use crate::ln::inbound_payment::ExpandedKey;
+		use crate::offers::nonce::Nonce;
+		use crate::offers::offer::OfferBuilder;
+		use crate::offers::test_utils::recipient_pubkey;
+
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let expanded_key = ExpandedKey::new([42; 32]);
+		let secp_ctx = Secp256k1::new();
+		let payment_id = PaymentId([1; 32]);
+		let offer = OfferBuilder::new(recipient_pubkey()).amount_msats(1000).build().unwrap();
+		let invoice_request = offer
+			.request_invoice(&expanded_key, Nonce([1; Nonce::LENGTH]), &secp_ctx, payment_id)
+			.unwrap()
+			.build_and_sign()
+			.unwrap();
+		let other_invoice_request = offer
+			.request_invoice(&expanded_key, Nonce([2; Nonce::LENGTH]), &secp_ctx, payment_id)
+			.unwrap()
+			.build_and_sign()
+			.unwrap();
+		assert_ne!(
+			invoice_request.payer_signing_pubkey(),
+			other_invoice_request.payer_signing_pubkey()
+		);
+		let bytes = invoice_request.encode();
+		let other_bytes = other_invoice_request.encode();
+
+		let payer_id = invoice_request.payer_signing_pubkey();
+		let blob = sign_payer_proof(payer_id, &keys_manager).unwrap();
+		let verified = verify_payer_proof(&blob, &bytes, &node_id).unwrap();
+		assert_eq!(verified.payer_signing_pubkey(), payer_id);
+
+		// The proof doesn't apply to requests with a different payer id.
+		let err = verify_payer_proof(&blob, &other_bytes, &node_id).unwrap_err();
+		assert_eq!(err, PayerProofError::PayerIdMismatch);
+
+		// Only the signing node is accepted as the payer node.
+		let other_node_id =
+			PublicKey::from_secret_key(&Secp256k1::new(), &SecretKey::from_slice(&ONE).unwrap());
+		let err = verify_payer_proof(&blob, &bytes, &other_node_id).unwrap_err();
+		assert_eq!(err, PayerProofError::InvalidSignature);
+
+		// Malformed proofs and requests are rejected.
+		let err = verify_payer_proof(&blob[..blob.len() - 1], &bytes, &node_id).unwrap_err();
+		assert_eq!(err, PayerProofError::InvalidEncoding);
+		let err = verify_payer_proof(&blob, &bytes[..bytes.len() - 1], &node_id).unwrap_err();
+		assert_eq!(err, PayerProofError::InvalidInvoiceRequest);
+	}
+
+	#[test]
+	fn test_fee_policy() {
+		use crate::ln::functional_test_utils::*;
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let (_, _, channel_id, _) = create_announced_chan_between_nodes(&nodes, 0, 1);
+		let node_id = nodes[0].node.get_our_node_id();
+
+		let channel = nodes[0].node.list_channels().pop().unwrap();
+		let config = channel.config.unwrap();
+		let policy = ChannelFeePolicy::for_channel(nodes[0].node, &channel_id).unwrap();
+		assert_eq!(policy.short_channel_id, channel.short_channel_id.unwrap());
+		assert_eq!(policy.fee_base_msat, config.forwarding_fee_base_msat);
+		assert_eq!(
+			policy.fee_proportional_millionths,
+			config.forwarding_fee_proportional_millionths
+		);
+		assert_eq!(policy.cltv_expiry_delta, config.cltv_expiry_delta);
+		assert_eq!(
+			policy.htlc_minimum_msat,
+			channel.counterparty.outbound_htlc_minimum_msat.unwrap()
+		);
+		assert_eq!(
+			policy.htlc_maximum_msat,
+			channel.counterparty.outbound_htlc_maximum_msat.unwrap()
+		);
+		assert!(ChannelFeePolicy::for_channel(nodes[0].node, &ChannelId([42; 32])).is_none());
+
+		// The policy round-trips, both with and without checking it against the network graph.
+		let blob = sign_fee_policy(policy.clone(), nodes[0].keys_manager).unwrap();
+		let no_graph: Option<&NetworkGraph<&test_utils::TestLogger>> = None;
+		assert_eq!(verify_fee_policy(&blob, no_graph), Ok((policy.clone(), node_id)));
+		assert_eq!(
+			verify_fee_policy(&blob, Some(nodes[1].network_graph)),
+			Ok((policy.clone(), node_id))
+		);
+
+		// Trailing data and malformed blobs are rejected.
+		let mut trailing = blob.clone();
+		trailing.push(0);
+		let err = Err(FeePolicyError::InvalidEncoding);
+		assert_eq!(verify_fee_policy(&trailing, no_graph), err);
+		assert_eq!(verify_fee_policy(&blob[..blob.len() - 1], no_graph), err);
+	}
+
+	#[test]
+	fn test_fee_policy_graph_mismatch() {
+		use crate::ln::functional_test_utils::*;
+		let chanmon_cfgs = create_chanmon_cfgs(3);
+		let node_cfgs = create_node_cfgs(3, &chanmon_cfgs);
+		let node_chanmgrs = create_node_chanmgrs(3, &node_cfgs, &[None, None, None]);
+		let nodes = create_network(3, &node_cfgs, &node_chanmgrs);
+		let (_, _, channel_id, _) = create_announced_chan_between_nodes(&nodes, 0, 1);
+		let policy = ChannelFeePolicy::for_channel(nodes[0].node, &channel_id).unwrap();
+		let network_graph = Some(nodes[1].network_graph);
+
+		// A node which is not a party to the channel can't sign a policy for it.
+		let blob = sign_fee_policy(policy.clone(), nodes[2].keys_manager).unwrap();
+		let (_, signer_node_id) =
+			verify_fee_policy::<&test_utils::TestLogger>(&blob, None).unwrap();
+		assert_eq!(signer_node_id, nodes[2].node.get_our_node_id());
+		assert_eq!(verify_fee_policy(&blob, network_graph), Err(FeePolicyError::NotChannelParty));
+
+		// Either party of the channel may sign a policy for it.
+		let blob = sign_fee_policy(policy.clone(), nodes[1].keys_manager).unwrap();
+		let (_, signer_node_id) = verify_fee_policy(&blob, network_graph).unwrap();
+		assert_eq!(signer_node_id, nodes[1].node.get_our_node_id());
+
+		// Policies for channels which aren't in the graph are rejected.
+		let mut unknown_policy = policy;
+		unknown_policy.short_channel_id += 1;
+		let blob = sign_fee_policy(unknown_policy, nodes[0].keys_manager).unwrap();
+		assert_eq!(verify_fee_policy(&blob, network_graph), Err(FeePolicyError::UnknownChannel));
+	}
+
+	#[test]
+	fn test_lnurl_auth() {
+		let vectors = [
+			("site.com", "02c5c27b1498003f6475182f63220420effa0522a531a55569c0632a20afc16743"),
+			("example.com", "02441aeca593a18aa287bca5c5b3493a0733eb1c9e04c11f12dcf902fca0806862"),
+		];
+
+		let secp_ctx = Secp256k1::new();
+		let k1 = [0x11; 32];
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		// The same seed always derives the same linking keys, regardless of the starting time.
+		let restarted_keys_manager = KeysManager::new(&[42; 32], 43, 43);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		// The network of the master key doesn't affect the derived keys.
+		let master_key = Xpriv::new_master(Network::Bitcoin, &[42; 32]).unwrap();
+
+		for (domain, linking_pubkey) in vectors {
+			let expected_pubkey = PublicKey::from_str(linking_pubkey).unwrap();
+			let linking_key =
+				derive_lnurl_auth_linking_key(&secp_ctx, &master_key, domain).unwrap();
+			assert_eq!(PublicKey::from_secret_key(&secp_ctx, &linking_key), expected_pubkey);
+			assert_ne!(expected_pubkey, node_id);
+
+			let (pubkey, sig) = keys_manager.sign_lnurl_auth_challenge(domain, &k1);
+			assert_eq!(pubkey, expected_pubkey);
+			assert_eq!(
+				restarted_keys_manager.sign_lnurl_auth_challenge(domain, &k1),
+				(pubkey, sig.clone())
+			);
+			assert_eq!(
+				sign_lnurl_auth_challenge(&secp_ctx, &k1, &linking_key),
+				(pubkey, sig.clone())
+			);
+
+			// The signature is a hex-encoded DER signature of `k1` by the linking key.
+			let sig = Signature::from_der(&Vec::<u8>::from_hex(&sig).unwrap()).unwrap();
+			let msg = bitcoin::secp256k1::Message::from_digest(k1);
+			assert!(secp_ctx.verify_ecdsa(&msg, &sig, &pubkey).is_ok());
+			assert!(secp_ctx.verify_ecdsa(&msg, &sig, &node_id).is_err());
+		}
+	}
+
+	#[test]
+	fn test_close_authorization() {
+		let secp_ctx = Secp256k1::new();
+		let authorizer_key = SecretKey::from_slice(&[42; 32]).unwrap();
+		let authorizer_pk = PublicKey::from_secret_key(&secp_ctx, &authorizer_key);
+		let other_pk =
+			PublicKey::from_secret_key(&secp_ctx, &SecretKey::from_slice(&[43; 32]).unwrap());
+		let authorization = CloseAuthorization {
+			channel_id: ChannelId([1; 32]),
+			nonce: 7,
+			max_fee_satoshis: 1_000,
+		};
+		let sig = sign_close_authorization(&authorization, &authorizer_key);
+		assert!(verify_close_authorization(&authorization, &sig, &authorizer_pk));
+		assert!(!verify_close_authorization(&authorization, &sig, &other_pk));
+
+		// The signature commits to each field of the authorization.
+		let other_channel = CloseAuthorization { channel_id: ChannelId([2; 32]), ..authorization };
+		assert!(!verify_close_authorization(&other_channel, &sig, &authorizer_pk));
+		let other_nonce = CloseAuthorization { nonce: 8, ..authorization };
+		assert!(!verify_close_authorization(&other_nonce, &sig, &authorizer_pk));
+		let other_fee = CloseAuthorization { max_fee_satoshis: 1_001, ..authorization };
+		assert!(!verify_close_authorization(&other_fee, &sig, &authorizer_pk));
+
+		// An authorization can't be confused with a plain message signed by the same key.
+		assert!(!verify(&authorization.encode(), &sig, &authorizer_pk));
+		let plain_sig = sign(&authorization.encode(), &authorizer_key);
+		assert!(!verify_close_authorization(&authorization, &plain_sig, &authorizer_pk));
+	}
+
+	#[test]
+	fn test_signed_backup() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let other_node_id =
+			KeysManager::new(&[43; 32], 42, 42).get_node_id(Recipient::Node).unwrap();
+
+		for payload in [&[][..], b"channel monitors", &[0xab; 10_000][..]] {
+			let blob = sign_backup(payload, 42, &keys_manager).unwrap();
+			assert_eq!(blob.len(), 115 + payload.len());
+			assert_eq!(blob[0], SIGNED_BACKUP_VERSION);
+			assert_eq!(&blob[115..], payload);
+			assert_eq!(verify_backup(&blob, &node_id), Ok((42, payload)));
+			assert_eq!(
+				verify_backup(&blob, &other_node_id),
+				Err(SignedBackupError::NodeIdMismatch)
+			);
+		}
+
+		// The signature commits to the timestamp.
+		let blob = sign_backup(b"channel monitors", 42, &keys_manager).unwrap();
+		let later_blob = sign_backup(b"channel monitors", 43, &keys_manager).unwrap();
+		assert_ne!(blob[50..115], later_blob[50..115]);
+		assert_eq!(verify_backup(&later_blob, &node_id), Ok((43, &b"channel monitors"[..])));
+		let mut replaced_timestamp = blob.clone();
+		replaced_timestamp[1..9].copy_from_slice(&later_blob[1..9]);
+		assert_eq!(
+			verify_backup(&replaced_timestamp, &node_id),
+			Err(SignedBackupError::InvalidSignature)
+		);
+	}
+
+	#[test]
+	fn test_signed_backup_corruption() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let node_id = keys_manager.get_node_id(Recipient::Node).unwrap();
+		let payload = [0xab; 100];
+		let blob = sign_backup(&payload, 42, &keys_manager).unwrap();
+
+		// Corrupting any byte, whether of the header or the payload, is detected.
+		for idx in 0..blob.len() {
+			let mut corrupted = blob.clone();
+			corrupted[idx] ^= 1;
+			assert!(verify_backup(&corrupted, &node_id).is_err());
+		}
+		let mut corrupted = blob.clone();
+		corrupted[0] = 2;
+		assert_eq!(
+			verify_backup(&corrupted, &node_id),
+			Err(SignedBackupError::UnsupportedVersion(2))
+		);
+		let mut corrupted = blob.clone();
+		*corrupted.last_mut().unwrap() ^= 1;
+		assert_eq!(verify_backup(&corrupted, &node_id), Err(SignedBackupError::InvalidSignature));
+
+		// Partial blobs are rejected cleanly, as is trailing data.
+		for len in 1..blob.len() {
+			assert_eq!(verify_backup(&blob[..len], &node_id), Err(SignedBackupError::Truncated));
+		}
+		assert_eq!(verify_backup(&[], &node_id), Err(SignedBackupError::Truncated));
+		let mut trailing = blob.clone();
+		trailing.push(0);
+		assert_eq!(verify_backup(&trailing, &node_id), Err(SignedBackupError::InvalidEncoding));
+	}
+
+	/// The ways in which a [`BrokenMessageSigner`] signs messages.
+	enum BrokenSignerMode {
+		Correct,
+		WrongKey,
+		IgnoresMessage,
+		HighS,
+		Garbage,
+		Unavailable,
+	}
+
+	/// A [`NodeSigner`] which signs messages incorrectly in the given way.
+	struct BrokenMessageSigner {
+		node_secret: SecretKey,
+		mode: BrokenSignerMode,
+	}
+
+	impl NodeSigner for BrokenMessageSigner {
+		fn get_node_id(&self, _recipient: Recipient) -> Result<PublicKey, ()> {
+			Ok(PublicKey::from_secret_key(&Secp256k1::signing_only(), &self.node_secret))
+		}
+		fn sign_message(&self, msg: &[u8]) -> Result<String, ()> {
+			match self.mode {
+				BrokenSignerMode::Correct => Ok(sign(msg, &self.node_secret)),
+				BrokenSignerMode::WrongKey => Ok(sign(msg, &SecretKey::from_slice(&ONE).unwrap())),
+				BrokenSignerMode::IgnoresMessage => Ok(sign(b"another message", &self.node_secret)),
+				BrokenSignerMode::HighS => {
+					// Malleate the signature as in `test_recover_pk_strict_rejects_high_s`.
+					let sig_bytes = sign_to_bytes(msg, &self.node_secret);
+					let mut high_s_sig_bytes = sig_bytes;
+					high_s_sig_bytes[0] = 31 + ((sig_bytes[0] - 31) ^ 1);
+					let s = SecretKey::from_slice(&sig_bytes[33..]).unwrap().negate();
+					high_s_sig_bytes[33..].copy_from_slice(&s.secret_bytes());
+					Ok(base32::Alphabet::ZBase32.encode(&high_s_sig_bytes))
+				},
+				BrokenSignerMode::Garbage => Ok("garbage".to_owned()),
+				BrokenSignerMode::Unavailable => Err(()),
+			}
+		}
+		fn ecdh(
+			&self, _recipient: Recipient, _other_key: &PublicKey, _tweak: Option<&Scalar>,
+		) -> Result<SharedSecret, ()> {
+			unreachable!()
+		}
+		fn get_inbound_payment_key(&self) -> ExpandedKey {
+			unreachable!()
+		}
+		fn get_peer_storage_key(&self) -> PeerStorageKey {
+			unreachable!()
+		}
+		fn get_receive_auth_key(&self) -> ReceiveAuthKey {
+			unreachable!()
+		}
+		fn sign_invoice(
+			&self, _invoice: &RawBolt11Invoice, _recipient: Recipient,
+		) -> Result<RecoverableSignature, ()> {
+			unreachable!()
+		}
+		fn sign_bolt12_invoice(
+			&self, _invoice: &UnsignedBolt12Invoice,
+		) -> Result<schnorr::Signature, ()> {
+			unreachable!()
+		}
+		fn sign_gossip_message(&self, _msg: UnsignedGossipMessage) -> Result<Signature, ()> {
+			unreachable!()
+		}
+		fn get_message_signing_pubkey(
+			&self, _key_id: MessageSigningKeyId,
+		) -> Result<PublicKey, ()> {
+			unreachable!()
+		}
+		fn sign_message_with_derived_key(
+			&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+		) -> Result<String, ()> {
+			unreachable!()
+		}
+	}
+
+	#[test]
+	fn test_self_test() {
+		let keys_manager = KeysManager::new(&[42; 32], 42, 42);
+		let report = self_test(&keys_manager).unwrap();
+		assert!(report.passed());
+		assert_eq!(report.failed_checks().count(), 0);
+		assert_eq!(report.results.len(), 4 + INTEROP_VECTORS.len());
+		assert_eq!(report.results[0], (SelfTestCheck::Sign, true));
+		assert_eq!(
+			report.results[4],
+			(SelfTestCheck::InteropVector { implementation: "c-lightning", index: 0 }, true)
+		);
+		assert_eq!(
+			report.results.last().unwrap().0,
+			SelfTestCheck::InteropVector { implementation: "c-lightning", index: 2 }
+		);
+		assert!(report.to_string().starts_with("sign: passed\nrecover node id: passed\n"));
+
+		let node_secret = SecretKey::from_slice(&[42; 32]).unwrap();
+		let signer = BrokenMessageSigner { node_secret, mode: BrokenSignerMode::Correct };
+		assert!(self_test(&signer).unwrap().passed());
+	}
+
+	#[test]
+	fn test_self_test_broken_signer() {
+		let node_secret = SecretKey::from_slice(&[42; 32]).unwrap();
+		let failed_checks = |mode| {
+			let signer = BrokenMessageSigner { node_secret, mode };
+			let report = self_test(&signer).unwrap();
+			// The embedded vectors don't depend on the signer.
+			assert!(report
+				.failed_checks()
+				.all(|check| !matches!(check, SelfTestCheck::InteropVector { .. })));
+			report.failed_checks().cloned().collect::<Vec<_>>()
+		};
+
+		assert_eq!(
+			failed_checks(BrokenSignerMode::WrongKey),
+			vec![SelfTestCheck::RecoverNodeId, SelfTestCheck::Verify]
+		);
+		assert_eq!(
+			failed_checks(BrokenSignerMode::IgnoresMessage),
+			vec![SelfTestCheck::RecoverNodeId, SelfTestCheck::Verify]
+		);
+		assert_eq!(failed_checks(BrokenSignerMode::HighS), vec![SelfTestCheck::LowS]);
+		assert_eq!(
+			failed_checks(BrokenSignerMode::Unavailable),
+			vec![
+				SelfTestCheck::Sign,
+				SelfTestCheck::RecoverNodeId,
+				SelfTestCheck::Verify,
+				SelfTestCheck::LowS
+			]
+		);
+
+		// Signatures which can't be decoded at all are reported as an error.
+		let signer = BrokenMessageSigner { node_secret, mode: BrokenSignerMode::Garbage };
+		assert!(matches!(self_test(&signer), Err(MessageSigningError::InvalidZbase32 { .. })));
+	}
+
+	#[test]
+	fn test_lnurl_auth_linking_keys_differ_per_domain() {
+		let secp_ctx = Secp256k1::new();
+		let master_key = Xpriv::new_master(Network::Testnet, &[42; 32]).unwrap();
+		let linking_key = |domain| derive_lnurl_auth_linking_key(&secp_ctx, &master_key, domain);
+
+		assert_eq!(linking_key("site.com"), linking_key("site.com"));
+		assert_ne!(linking_key("site.com"), linking_key("example.com"));
+		assert_ne!(linking_key("site.com"), linking_key("login.site.com"));
+
+		// A different seed derives a different linking key for the same domain.
+		let other_master_key = Xpriv::new_master(Network::Testnet, &[43; 32]).unwrap();
+		assert_ne!(
+			linking_key("site.com"),
+			derive_lnurl_auth_linking_key(&secp_ctx, &other_master_key, "site.com")
+		);
+	}
+}
+
+#[cfg(ldk_bench)]
+pub mod benches {
+	use super::{sign, verify, MessageVerifier};
+	use bitcoin::secp256k1::{PublicKey, Secp256k1, SecretKey};
+
+	use criterion::Criterion;
+
+	pub fn bench_verify_message_signatures(bench: &mut Criterion) {
+		let secp_ctx = Secp256k1::signing_only();
+		let mut signed_messages = Vec::new();
+		for i in 1..=100u8 {
+			let sk = SecretKey::from_slice(&[i; 32]).unwrap();
+			let msg = [i; 32];
+			let sig = sign(&msg, &sk);
+			signed_messages.push((msg, sig, PublicKey::from_secret_key(&secp_ctx, &sk)));
+		}
+		let items: Vec<(&[u8], &str, &PublicKey)> =
+			signed_messages.iter().map(|(msg, sig, pk)| (&msg[..], sig.as_str(), pk)).collect();
+
+		bench.bench_function("verify_message_signatures", |b| {
+			b.iter(|| items.iter().all(|(msg, sig, pk)| verify(msg, sig, pk)))
+		});
+		let verifier = MessageVerifier::new();
+		bench.bench_function("verify_message_signatures_with_verifier", |b| {
+			b.iter(|| verifier.verify_batch(&items))
+		});
+	}
+}
@@ -19,16 +19,23 @@ pub mod ser_macros;
 pub mod mut_global;
 
 pub mod anchor_channel_reserves;
+pub mod fee_rate_history;
 
 #[cfg(fuzzing)]
 pub mod base32;
 #[cfg(not(fuzzing))]
 pub(crate) mod base32;
+pub(crate) mod base64;
+pub mod deadlines;
 pub mod errors;
+pub mod facade;
+pub mod framed_ser;
 pub mod message_signing;
 pub mod persist;
 pub mod scid_utils;
 pub mod ser;
+#[cfg(feature = "serde")]
+pub(crate) mod serde_helpers;
 pub mod sweep;
 pub mod wakers;
 
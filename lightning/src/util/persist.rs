@@ -12,6 +12,7 @@
 //! [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
 
 use bitcoin::hashes::hex::FromHex;
+use bitcoin::secp256k1::PublicKey;
 use bitcoin::{BlockHash, Txid};
 use core::cmp;
 use core::future::Future;
@@ -20,15 +21,19 @@ use core::pin::Pin;
 use core::str::FromStr;
 
 use crate::prelude::*;
-use crate::{io, log_error};
+use crate::{io, log_error, log_info};
 
 use crate::chain;
 use crate::chain::chaininterface::{BroadcasterInterface, FeeEstimator};
-use crate::chain::chainmonitor::Persist;
+use crate::chain::chainmonitor::{ChainMonitor, Persist};
 use crate::chain::channelmonitor::{ChannelMonitor, ChannelMonitorUpdate};
 use crate::chain::transaction::OutPoint;
+use crate::chain::ChannelMonitorUpdateStatus;
+use crate::events::{Event, EventHandler, EventsProvider};
 use crate::ln::types::ChannelId;
 use crate::sign::{ecdsa::EcdsaChannelSigner, EntropySource, SignerProvider};
+use crate::sync::Mutex;
+use crate::util::framed_ser::FramedType;
 use crate::util::logger::Logger;
 use crate::util::ser::{Readable, ReadableArgs, Writeable};
 
@@ -104,6 +109,117 @@ pub const OUTPUT_SWEEPER_PERSISTENCE_SECONDARY_NAMESPACE: &str = "";
 /// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
 pub const OUTPUT_SWEEPER_PERSISTENCE_KEY: &str = "output_sweeper";
 
+/// The primary namespace under which an [`AnchorChannelReserveContext`] may be persisted.
+///
+/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+pub const ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_PRIMARY_NAMESPACE: &str = "";
+/// The secondary namespace under which an [`AnchorChannelReserveContext`] may be persisted.
+///
+/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+pub const ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_SECONDARY_NAMESPACE: &str = "";
+/// The key under which an [`AnchorChannelReserveContext`] may be persisted.
+///
+/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+pub const ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY: &str = "anchor_channel_reserve_context";
+
+/// The primary namespace under which a [`BumpTransactionEventHandler`] may persist the state of
+/// its claims, each stored under the hex-encoded [`ClaimId`].
+///
+/// [`BumpTransactionEventHandler`]: crate::events::bump_transaction::BumpTransactionEventHandler
+/// [`ClaimId`]: crate::chain::ClaimId
+pub const BUMP_TRANSACTION_CLAIMS_PERSISTENCE_PRIMARY_NAMESPACE: &str = "bump_transaction_claims";
+/// The secondary namespace under which a [`BumpTransactionEventHandler`] may persist the state of
+/// its claims.
+///
+/// [`BumpTransactionEventHandler`]: crate::events::bump_transaction::BumpTransactionEventHandler
+pub const BUMP_TRANSACTION_CLAIMS_PERSISTENCE_SECONDARY_NAMESPACE: &str = "";
+
+/// A pair of primary and secondary namespaces of a [`KVStore`] or [`KVStoreSync`].
+///
+/// The namespaces LDK writes to are available as associated constants, which should be used
+/// rather than raw strings to avoid reading from or writing to the wrong namespace. Applications
+/// may still use raw strings for their own namespaces, or define them via [`Self::new`].
+#[derive(Clone, Copy, Debug, Hash, PartialEq, Eq)]
+pub struct KVStoreNamespace {
+	/// The primary namespace.
+	pub primary_namespace: &'static str,
+	/// The secondary namespace.
+	pub secondary_namespace: &'static str,
+}
+
+impl KVStoreNamespace {
+	/// The namespace of the [`ChannelManager`], stored under [`CHANNEL_MANAGER_PERSISTENCE_KEY`].
+	///
+	/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+	pub const CHANNEL_MANAGER: Self = Self::new(
+		CHANNEL_MANAGER_PERSISTENCE_PRIMARY_NAMESPACE,
+		CHANNEL_MANAGER_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+	/// The namespace of [`ChannelMonitor`]s, each stored under its [`MonitorName`].
+	pub const CHANNEL_MONITORS: Self = Self::new(
+		CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+		CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+	/// The namespace of archived [`ChannelMonitor`]s, each stored under its [`MonitorName`].
+	pub const ARCHIVED_CHANNEL_MONITORS: Self = Self::new(
+		ARCHIVED_CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
+		ARCHIVED_CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+	/// The primary namespace of [`ChannelMonitorUpdate`]s persisted by the
+	/// [`MonitorUpdatingPersister`].
+	///
+	/// Updates are stored under their [`UpdateName`] in a secondary namespace named after the
+	/// [`MonitorName`] of their monitor, thus only the primary namespace of this constant applies.
+	pub const CHANNEL_MONITOR_UPDATES: Self =
+		Self::new(CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE, "");
+	/// The namespace of the [`NetworkGraph`], stored under [`NETWORK_GRAPH_PERSISTENCE_KEY`].
+	///
+	/// [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+	pub const NETWORK_GRAPH: Self = Self::new(
+		NETWORK_GRAPH_PERSISTENCE_PRIMARY_NAMESPACE,
+		NETWORK_GRAPH_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+	/// The namespace of the [`WriteableScore`], stored under [`SCORER_PERSISTENCE_KEY`].
+	///
+	/// [`WriteableScore`]: crate::routing::scoring::WriteableScore
+	pub const SCORER: Self =
+		Self::new(SCORER_PERSISTENCE_PRIMARY_NAMESPACE, SCORER_PERSISTENCE_SECONDARY_NAMESPACE);
+	/// The namespace of [`OutputSweeper`] state, stored under [`OUTPUT_SWEEPER_PERSISTENCE_KEY`].
+	///
+	/// [`OutputSweeper`]: crate::util::sweep::OutputSweeper
+	pub const OUTPUT_SWEEPER: Self = Self::new(
+		OUTPUT_SWEEPER_PERSISTENCE_PRIMARY_NAMESPACE,
+		OUTPUT_SWEEPER_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+	/// The namespace of an [`AnchorChannelReserveContext`], stored under
+	/// [`ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY`].
+	///
+	/// [`AnchorChannelReserveContext`]: crate::util::anchor_channel_reserves::AnchorChannelReserveContext
+	pub const ANCHOR_CHANNEL_RESERVE_CONTEXT: Self = Self::new(
+		ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_PRIMARY_NAMESPACE,
+		ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+	/// The namespace of the claims of a [`BumpTransactionEventHandler`], each stored under its
+	/// hex-encoded [`ClaimId`].
+	///
+	/// [`BumpTransactionEventHandler`]: crate::events::bump_transaction::BumpTransactionEventHandler
+	/// [`ClaimId`]: crate::chain::ClaimId
+	pub const BUMP_TRANSACTION_CLAIMS: Self = Self::new(
+		BUMP_TRANSACTION_CLAIMS_PERSISTENCE_PRIMARY_NAMESPACE,
+		BUMP_TRANSACTION_CLAIMS_PERSISTENCE_SECONDARY_NAMESPACE,
+	);
+
+	/// Constructs a namespace, e.g., for data of an application.
+	pub const fn new(primary_namespace: &'static str, secondary_namespace: &'static str) -> Self {
+		Self { primary_namespace, secondary_namespace }
+	}
+
+	fn contains(&self, primary_namespace: &str, secondary_namespace: &str) -> bool {
+		self.primary_namespace == primary_namespace
+			&& self.secondary_namespace == secondary_namespace
+	}
+}
+
 /// A sentinel value to be prepended to monitors persisted by the [`MonitorUpdatingPersister`].
 ///
 /// This serves to prevent someone from accidentally loading such monitors (which may need
@@ -297,6 +413,159 @@ pub fn migrate_kv_store_data<S: MigratableKVStore, T: MigratableKVStore>(
 	Ok(())
 }
 
+/// Moves all keys from the `old` to the `new` namespace of the given store, e.g., to recover data
+/// which was written to the wrong namespace, returning the number of keys moved.
+///
+/// Each key is written to the `new` namespace before it is removed from the `old` one, thus an
+/// interrupted migration can be resumed by calling this again. Keys which already exist in the
+/// `new` namespace are not overwritten, but an error is returned if their data differs.
+pub fn migrate_namespace<K: KVStoreSync + ?Sized>(
+	store: &K, old: KVStoreNamespace, new: KVStoreNamespace,
+) -> Result<usize, io::Error> {
+	if old == new {
+		return Ok(0);
+	}
+	let existing_keys = store.list(new.primary_namespace, new.secondary_namespace)?;
+	let keys = store.list(old.primary_namespace, old.secondary_namespace)?;
+	for key in keys.iter() {
+		let data = store.read(old.primary_namespace, old.secondary_namespace, key)?;
+		if existing_keys.contains(key) {
+			if store.read(new.primary_namespace, new.secondary_namespace, key)? != data {
+				return Err(io::Error::new(
+					io::ErrorKind::AlreadyExists,
+					"Key exists with different data in the new namespace",
+				));
+			}
+		} else {
+			store.write(new.primary_namespace, new.secondary_namespace, key, data)?;
+		}
+		store.remove(old.primary_namespace, old.secondary_namespace, key, false)?;
+	}
+	Ok(keys.len())
+}
+
+/// An issue with the layout of a store, as found by [`validate_store_layout`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum StoreLayoutIssue {
+	/// A key in a namespace owned by LDK which LDK doesn't write.
+	UnknownKey {
+		/// The primary namespace of the key.
+		primary_namespace: String,
+		/// The secondary namespace of the key.
+		secondary_namespace: String,
+		/// The key.
+		key: String,
+	},
+	/// Data whose serialization identifies it as data LDK writes elsewhere.
+	MisplacedData {
+		/// The primary namespace of the key the data is stored under.
+		primary_namespace: String,
+		/// The secondary namespace of the key the data is stored under.
+		secondary_namespace: String,
+		/// The key the data is stored under.
+		key: String,
+		/// The namespace the data is expected in.
+		expected_namespace: KVStoreNamespace,
+		/// The key the data is expected under, if it is stored under a fixed key.
+		expected_key: Option<&'static str>,
+	},
+}
+
+/// Returns whether LDK writes the given key, or `None` if the namespace is not owned by LDK.
+fn is_known_key(primary_namespace: &str, secondary_namespace: &str, key: &str) -> Option<bool> {
+	if KVStoreNamespace::CHANNEL_MANAGER.contains(primary_namespace, secondary_namespace) {
+		// All of LDK's singletons share the same namespace.
+		Some(
+			[
+				CHANNEL_MANAGER_PERSISTENCE_KEY,
+				NETWORK_GRAPH_PERSISTENCE_KEY,
+				SCORER_PERSISTENCE_KEY,
+				OUTPUT_SWEEPER_PERSISTENCE_KEY,
+				ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY,
+			]
+			.contains(&key),
+		)
+	} else if primary_namespace == KVStoreNamespace::CHANNEL_MONITORS.primary_namespace
+		|| primary_namespace == KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS.primary_namespace
+	{
+		Some(secondary_namespace.is_empty() && MonitorName::from_str(key).is_ok())
+	} else if primary_namespace == KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace {
+		Some(
+			MonitorName::from_str(secondary_namespace).is_ok()
+				&& UpdateName::new(key.to_owned()).is_ok(),
+		)
+	} else if KVStoreNamespace::BUMP_TRANSACTION_CLAIMS
+		.contains(primary_namespace, secondary_namespace)
+	{
+		Some(key.len() == 64 && <[u8; 32]>::from_hex(key).is_ok())
+	} else {
+		None
+	}
+}
+
+/// Returns the namespace and key LDK writes the given data to, if its serialization identifies it.
+fn sniff_location(data: &[u8]) -> Option<(KVStoreNamespace, Option<&'static str>)> {
+	if data.starts_with(MONITOR_UPDATING_PERSISTER_PREPEND_SENTINEL) {
+		return Some((KVStoreNamespace::CHANNEL_MONITORS, None));
+	}
+	match FramedType::sniff(data)? {
+		FramedType::NetworkGraph => {
+			Some((KVStoreNamespace::NETWORK_GRAPH, Some(NETWORK_GRAPH_PERSISTENCE_KEY)))
+		},
+		FramedType::ProbabilisticScorer => {
+			Some((KVStoreNamespace::SCORER, Some(SCORER_PERSISTENCE_KEY)))
+		},
+	}
+}
+
+/// Checks the layout of the given store for keys in namespaces owned by LDK which LDK doesn't
+/// write, e.g., as an application wrote to the wrong namespace, and for data which is identified
+/// by its serialization as data LDK writes to another namespace or key.
+///
+/// Only data starting with a recognizable header, i.e., [`ChannelMonitor`]s written by the
+/// [`MonitorUpdatingPersister`] and framed [`NetworkGraph`]s and scorers, can be identified.
+/// Custom namespaces of applications are only checked for such data.
+///
+/// Note that this reads all data in the store.
+///
+/// [`NetworkGraph`]: crate::routing::gossip::NetworkGraph
+pub fn validate_store_layout<K: MigratableKVStore + ?Sized>(
+	store: &K,
+) -> Result<Vec<StoreLayoutIssue>, io::Error> {
+	let mut issues = Vec::new();
+	for (primary_namespace, secondary_namespace, key) in store.list_all_keys()? {
+		let data = store.read(&primary_namespace, &secondary_namespace, &key)?;
+		let misplaced = sniff_location(&data).filter(|(expected_namespace, expected_key)| {
+			let in_namespace =
+				expected_namespace.contains(&primary_namespace, &secondary_namespace);
+			match expected_key {
+				Some(expected_key) => !in_namespace || key != *expected_key,
+				// Monitors may also have been archived.
+				None => {
+					let archived = KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS;
+					!in_namespace && !archived.contains(&primary_namespace, &secondary_namespace)
+				},
+			}
+		});
+		if let Some((expected_namespace, expected_key)) = misplaced {
+			issues.push(StoreLayoutIssue::MisplacedData {
+				primary_namespace,
+				secondary_namespace,
+				key,
+				expected_namespace,
+				expected_key,
+			});
+		} else if is_known_key(&primary_namespace, &secondary_namespace, &key) == Some(false) {
+			issues.push(StoreLayoutIssue::UnknownKey {
+				primary_namespace,
+				secondary_namespace,
+				key,
+			});
+		}
+	}
+	Ok(issues)
+}
+
 impl<ChannelSigner: EcdsaChannelSigner, K: KVStoreSync + ?Sized> Persist<ChannelSigner> for K {
 	// TODO: We really need a way for the persister to inform the user that its time to crash/shut
 	// down once these start returning failure.
@@ -307,8 +576,8 @@ impl<ChannelSigner: EcdsaChannelSigner, K: KVStoreSync + ?Sized> Persist<Channel
 		&self, monitor_name: MonitorName, monitor: &ChannelMonitor<ChannelSigner>,
 	) -> chain::ChannelMonitorUpdateStatus {
 		match self.write(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			&monitor_name.to_string(),
 			monitor.encode(),
 		) {
@@ -322,8 +591,8 @@ impl<ChannelSigner: EcdsaChannelSigner, K: KVStoreSync + ?Sized> Persist<Channel
 		monitor: &ChannelMonitor<ChannelSigner>,
 	) -> chain::ChannelMonitorUpdateStatus {
 		match self.write(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			&monitor_name.to_string(),
 			monitor.encode(),
 		) {
@@ -335,16 +604,16 @@ impl<ChannelSigner: EcdsaChannelSigner, K: KVStoreSync + ?Sized> Persist<Channel
 	fn archive_persisted_channel(&self, monitor_name: MonitorName) {
 		let monitor_key = monitor_name.to_string();
 		let monitor = match self.read(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			monitor_key.as_str(),
 		) {
 			Ok(monitor) => monitor,
 			Err(_) => return,
 		};
 		match self.write(
-			ARCHIVED_CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			ARCHIVED_CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS.secondary_namespace,
 			monitor_key.as_str(),
 			monitor,
 		) {
@@ -352,8 +621,8 @@ impl<ChannelSigner: EcdsaChannelSigner, K: KVStoreSync + ?Sized> Persist<Channel
 			Err(_e) => return,
 		};
 		let _ = self.remove(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			monitor_key.as_str(),
 			true,
 		);
@@ -372,13 +641,13 @@ where
 	let mut res = Vec::new();
 
 	for stored_key in kv_store.list(
-		CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-		CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+		KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+		KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 	)? {
 		match <(BlockHash, ChannelMonitor<<SP::Target as SignerProvider>::EcdsaSigner>)>::read(
 			&mut io::Cursor::new(kv_store.read(
-				CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-				CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+				KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+				KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 				&stored_key,
 			)?),
 			(&*entropy_source, &*signer_provider),
@@ -561,8 +830,8 @@ where
 		io::Error,
 	> {
 		let monitor_list = self.kv_store.list(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 		)?;
 		let mut res = Vec::with_capacity(monitor_list.len());
 		for monitor_key in monitor_list {
@@ -634,8 +903,8 @@ where
 	) -> Result<(BlockHash, ChannelMonitor<<SP::Target as SignerProvider>::EcdsaSigner>), io::Error>
 	{
 		let mut monitor_cursor = io::Cursor::new(self.kv_store.read(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			monitor_key,
 		)?);
 		// Discard the sentinel bytes if found.
@@ -678,7 +947,7 @@ where
 		&self, monitor_key: &str, update_name: &UpdateName,
 	) -> Result<ChannelMonitorUpdate, io::Error> {
 		let update_bytes = self.kv_store.read(
-			CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 			monitor_key,
 			update_name.as_str(),
 		)?;
@@ -686,7 +955,7 @@ where
 			log_error!(
 				self.logger,
 				"Failed to read ChannelMonitorUpdate {}/{}/{}, reason: {}",
-				CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+				KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 				monitor_key,
 				update_name.as_str(),
 				e,
@@ -703,21 +972,22 @@ where
 	/// be passed to [`KVStoreSync::remove`].
 	pub fn cleanup_stale_updates(&self, lazy: bool) -> Result<(), io::Error> {
 		let monitor_keys = self.kv_store.list(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 		)?;
 		for monitor_key in monitor_keys {
 			let monitor_name = MonitorName::from_str(&monitor_key)?;
 			let (_, current_monitor) = self.read_monitor(&monitor_name, &monitor_key)?;
-			let updates = self
-				.kv_store
-				.list(CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE, monitor_key.as_str())?;
+			let updates = self.kv_store.list(
+				KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
+				monitor_key.as_str(),
+			)?;
 			for update in updates {
 				let update_name = UpdateName::new(update)?;
 				// if the update_id is lower than the stored monitor, delete
 				if update_name.0 <= current_monitor.get_latest_update_id() {
 					self.kv_store.remove(
-						CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+						KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 						monitor_key.as_str(),
 						update_name.as_str(),
 						lazy,
@@ -760,8 +1030,8 @@ where
 		monitor_bytes.extend_from_slice(MONITOR_UPDATING_PERSISTER_PREPEND_SENTINEL);
 		monitor.write(&mut monitor_bytes).unwrap();
 		match self.kv_store.write(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			monitor_key.as_str(),
 			monitor_bytes,
 		) {
@@ -770,8 +1040,8 @@ where
 				log_error!(
 					self.logger,
 					"Failed to write ChannelMonitor {}/{}/{} reason: {}",
-					CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-					CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+					KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+					KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 					monitor_key.as_str(),
 					e
 				);
@@ -801,7 +1071,7 @@ where
 				let monitor_key = monitor_name.to_string();
 				let update_name = UpdateName::from(update.update_id);
 				match self.kv_store.write(
-					CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+					KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 					monitor_key.as_str(),
 					update_name.as_str(),
 					update.encode(),
@@ -811,7 +1081,7 @@ where
 						log_error!(
 							self.logger,
 							"Failed to write ChannelMonitorUpdate {}/{}/{} reason: {}",
-							CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+							KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 							monitor_key.as_str(),
 							update_name.as_str(),
 							e
@@ -873,8 +1143,8 @@ where
 			Err(_) => return,
 		};
 		match self.kv_store.write(
-			ARCHIVED_CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			ARCHIVED_CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS.secondary_namespace,
 			monitor_key.as_str(),
 			monitor.encode(),
 		) {
@@ -882,8 +1152,8 @@ where
 			Err(_e) => return,
 		};
 		let _ = self.kv_store.remove(
-			CHANNEL_MONITOR_PERSISTENCE_PRIMARY_NAMESPACE,
-			CHANNEL_MONITOR_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::CHANNEL_MONITORS.primary_namespace,
+			KVStoreNamespace::CHANNEL_MONITORS.secondary_namespace,
 			monitor_key.as_str(),
 			true,
 		);
@@ -906,7 +1176,7 @@ where
 		for update_id in start..=end {
 			let update_name = UpdateName::from(update_id);
 			if let Err(e) = self.kv_store.remove(
-				CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+				KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 				monitor_key.as_str(),
 				update_name.as_str(),
 				true,
@@ -922,6 +1192,433 @@ where
 	}
 }
 
+/// The maximum number of [`RetryingPersister::timer_tick_occurred`] calls between retries of
+/// buffered persistence operations for a channel.
+const MAX_RETRY_BACKOFF_TICKS: u32 = 8;
+
+/// Configuration for a [`RetryingPersister`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RetryingPersisterConfig {
+	/// The maximum number of persistence operations buffered across all channels. Once reached,
+	/// failures of the wrapped [`Persist`] implementation are returned as-is.
+	///
+	/// Default value: 1000
+	pub max_buffered_updates: usize,
+	/// The maximum total serialized size, in bytes, of the [`ChannelMonitorUpdate`]s buffered
+	/// across all channels. Once reached, failures of the wrapped [`Persist`] implementation are
+	/// returned as-is.
+	///
+	/// Default value: 16 MiB
+	pub max_buffered_bytes: usize,
+	/// The number of [`RetryingPersister::timer_tick_occurred`] calls after a channel's first
+	/// buffered operation after which we stop retrying and consider the channel's persistence
+	/// permanently failed.
+	///
+	/// Default value: 60
+	pub failure_deadline_ticks: u32,
+}
+
+impl Default for RetryingPersisterConfig {
+	fn default() -> Self {
+		RetryingPersisterConfig {
+			max_buffered_updates: 1000,
+			max_buffered_bytes: 16 * 1024 * 1024,
+			failure_deadline_ticks: 60,
+		}
+	}
+}
+
+#[derive(Clone)]
+enum BufferedPersist {
+	NewChannel { update_id: u64 },
+	Update(ChannelMonitorUpdate),
+	FullMonitor,
+}
+
+impl BufferedPersist {
+	fn serialized_length(&self) -> usize {
+		match self {
+			BufferedPersist::Update(update) => update.serialized_length(),
+			BufferedPersist::NewChannel { .. } | BufferedPersist::FullMonitor => 0,
+		}
+	}
+
+	fn completed_update_id(&self) -> Option<u64> {
+		match self {
+			BufferedPersist::NewChannel { update_id } => Some(*update_id),
+			BufferedPersist::Update(update) => Some(update.update_id),
+			BufferedPersist::FullMonitor => None,
+		}
+	}
+}
+
+struct BufferedChannel {
+	monitor_name: MonitorName,
+	counterparty_node_id: PublicKey,
+	/// Operations that have yet to be persisted by the wrapped [`Persist`] implementation, in the
+	/// order they were received.
+	pending: VecDeque<BufferedPersist>,
+	ticks_since_first_failure: u32,
+	ticks_until_retry: u32,
+	failed_retries: u32,
+	permanently_failed: bool,
+}
+
+struct BufferedPersists {
+	channels: HashMap<ChannelId, BufferedChannel>,
+	num_updates: usize,
+	num_bytes: usize,
+}
+
+/// A [`Persist`] implementation wrapping another which buffers failed persistence operations in
+/// memory and retries them, allowing channels to keep operating through short outages of the
+/// underlying storage.
+///
+/// Whenever the wrapped persister returns [`ChannelMonitorUpdateStatus::UnrecoverableError`], the
+/// operation is buffered and [`ChannelMonitorUpdateStatus::InProgress`] is returned instead. Any
+/// later operations for the same channel are buffered behind it, ensuring they are delivered to
+/// the wrapped persister in order. Buffered operations are retried with an exponential backoff on
+/// calls to [`RetryingPersister::timer_tick_occurred`], and once they succeed, completion is
+/// signaled via [`ChainMonitor::channel_monitor_updated`].
+///
+/// If a channel's buffered operations can't be persisted within
+/// [`RetryingPersisterConfig::failure_deadline_ticks`], we give up retrying and generate an
+/// [`Event::MonitorPersistenceFailed`], retrieved via [`EventsProvider::process_pending_events`].
+/// Any further operations for that channel return [`ChannelMonitorUpdateStatus::UnrecoverableError`].
+/// Similarly, if the buffer limits in [`RetryingPersisterConfig`] are reached, failures are
+/// returned as-is.
+///
+/// # Crash Semantics
+///
+/// Buffered operations are only held in memory and are lost if the node crashes. As their
+/// persistence was reported as [`ChannelMonitorUpdateStatus::InProgress`], the [`ChannelManager`]
+/// will not have released any messages or funds depending on them. If the [`ChannelManager`] was
+/// persisted after the lost [`ChannelMonitorUpdate`]s were generated, it will replay them on
+/// startup. Otherwise, the [`ChannelManager`] and persisted [`ChannelMonitor`] will both reflect
+/// the channel state prior to them. Either way, the lost operations will not need to be
+/// re-derived manually.
+///
+/// [`ChannelManager`]: crate::ln::channelmanager::ChannelManager
+pub struct RetryingPersister<P: Deref, L: Deref>
+where
+	L::Target: Logger,
+{
+	persister: P,
+	logger: L,
+	config: RetryingPersisterConfig,
+	buffered: Mutex<BufferedPersists>,
+	retry_lock: Mutex<()>,
+	pending_events: Mutex<Vec<Event>>,
+}
+
+impl<P: Deref, L: Deref> RetryingPersister<P, L>
+where
+	L::Target: Logger,
+{
+	/// Constructs a new [`RetryingPersister`] wrapping `persister`.
+	pub fn new(persister: P, logger: L, config: RetryingPersisterConfig) -> Self {
+		RetryingPersister {
+			persister,
+			logger,
+			config,
+			buffered: Mutex::new(BufferedPersists {
+				channels: new_hash_map(),
+				num_updates: 0,
+				num_bytes: 0,
+			}),
+			retry_lock: Mutex::new(()),
+			pending_events: Mutex::new(Vec::new()),
+		}
+	}
+
+	/// Returns the number of persistence operations currently buffered across all channels.
+	pub fn buffered_update_count(&self) -> usize {
+		self.buffered.lock().unwrap().num_updates
+	}
+
+	/// Retries buffered persistence operations which are due and checks for channels which have
+	/// exceeded [`RetryingPersisterConfig::failure_deadline_ticks`].
+	///
+	/// `chain_monitor` must be the [`ChainMonitor`] this persister is used by. This should be
+	/// called regularly, with the tick frequency determining both how quickly buffered operations
+	/// are retried and the real-time duration of the failure deadline.
+	pub fn timer_tick_occurred<
+		ChannelSigner: EcdsaChannelSigner,
+		C: Deref,
+		T: Deref,
+		F: Deref,
+		CML: Deref,
+		CMP: Deref,
+		ES: Deref,
+	>(
+		&self, chain_monitor: &ChainMonitor<ChannelSigner, C, T, F, CML, CMP, ES>,
+	) where
+		P::Target: Persist<ChannelSigner>,
+		C::Target: chain::Filter,
+		T::Target: BroadcasterInterface,
+		F::Target: FeeEstimator,
+		CML::Target: Logger,
+		CMP::Target: Persist<ChannelSigner>,
+		ES::Target: EntropySource,
+	{
+		let _retry_lock = self.retry_lock.lock().unwrap();
+		let mut channels_to_retry = Vec::new();
+		{
+			let mut buffered = self.buffered.lock().unwrap();
+			let BufferedPersists { channels, num_updates, num_bytes } = &mut *buffered;
+			for (channel_id, channel) in channels.iter_mut() {
+				if channel.permanently_failed {
+					continue;
+				}
+				channel.ticks_since_first_failure += 1;
+				if channel.ticks_since_first_failure >= self.config.failure_deadline_ticks {
+					log_error!(
+						self.logger,
+						"Failed to persist {} monitor update(s) for channel {} within {} ticks, giving up",
+						channel.pending.len(),
+						channel_id,
+						self.config.failure_deadline_ticks
+					);
+					channel.permanently_failed = true;
+					let pending_update_ids = channel
+						.pending
+						.iter()
+						.filter_map(BufferedPersist::completed_update_id)
+						.collect();
+					for op in channel.pending.drain(..) {
+						*num_updates -= 1;
+						*num_bytes -= op.serialized_length();
+					}
+					self.pending_events.lock().unwrap().push(Event::MonitorPersistenceFailed {
+						channel_id: *channel_id,
+						counterparty_node_id: channel.counterparty_node_id,
+						pending_update_ids,
+					});
+					continue;
+				}
+				channel.ticks_until_retry = channel.ticks_until_retry.saturating_sub(1);
+				if channel.ticks_until_retry == 0 {
+					channels_to_retry.push(*channel_id);
+				}
+			}
+		}
+		for channel_id in channels_to_retry {
+			self.retry_channel(chain_monitor, channel_id);
+		}
+	}
+
+	fn retry_channel<
+		ChannelSigner: EcdsaChannelSigner,
+		C: Deref,
+		T: Deref,
+		F: Deref,
+		CML: Deref,
+		CMP: Deref,
+		ES: Deref,
+	>(
+		&self, chain_monitor: &ChainMonitor<ChannelSigner, C, T, F, CML, CMP, ES>,
+		channel_id: ChannelId,
+	) where
+		P::Target: Persist<ChannelSigner>,
+		C::Target: chain::Filter,
+		T::Target: BroadcasterInterface,
+		F::Target: FeeEstimator,
+		CML::Target: Logger,
+		CMP::Target: Persist<ChannelSigner>,
+		ES::Target: EntropySource,
+	{
+		loop {
+			let (monitor_name, op) = {
+				let buffered = self.buffered.lock().unwrap();
+				match buffered.channels.get(&channel_id) {
+					Some(channel) if !channel.permanently_failed => match channel.pending.front() {
+						Some(op) => (channel.monitor_name, op.clone()),
+						None => return,
+					},
+					_ => return,
+				}
+			};
+
+			let status = match chain_monitor.get_monitor(channel_id) {
+				Ok(monitor) => match &op {
+					BufferedPersist::NewChannel { .. } => {
+						self.persister.persist_new_channel(monitor_name, &monitor)
+					},
+					BufferedPersist::Update(update) => self.persister.update_persisted_channel(
+						monitor_name,
+						Some(update),
+						&monitor,
+					),
+					BufferedPersist::FullMonitor => {
+						self.persister.update_persisted_channel(monitor_name, None, &monitor)
+					},
+				},
+				Err(()) => {
+					// The monitor is no longer tracked, so there is nothing left to persist.
+					let mut buffered = self.buffered.lock().unwrap();
+					let BufferedPersists { channels, num_updates, num_bytes } = &mut *buffered;
+					if let Some(channel) = channels.remove(&channel_id) {
+						for op in channel.pending {
+							*num_updates -= 1;
+							*num_bytes -= op.serialized_length();
+						}
+					}
+					return;
+				},
+			};
+
+			let mut buffered = self.buffered.lock().unwrap();
+			let BufferedPersists { channels, num_updates, num_bytes } = &mut *buffered;
+			let channel = match channels.get_mut(&channel_id) {
+				Some(channel) => channel,
+				None => return,
+			};
+			if status == ChannelMonitorUpdateStatus::UnrecoverableError {
+				channel.failed_retries += 1;
+				channel.ticks_until_retry =
+					cmp::min(1u32 << cmp::min(channel.failed_retries, 31), MAX_RETRY_BACKOFF_TICKS);
+				return;
+			}
+			channel.pending.pop_front();
+			*num_updates -= 1;
+			*num_bytes -= op.serialized_length();
+			if channel.pending.is_empty() {
+				channels.remove(&channel_id);
+			}
+			core::mem::drop(buffered);
+
+			log_info!(self.logger, "Persisted buffered monitor update for channel {}", channel_id);
+			// If the wrapped persister returned `InProgress`, it is now responsible for signaling
+			// completion.
+			if status == ChannelMonitorUpdateStatus::Completed {
+				if let Some(update_id) = op.completed_update_id() {
+					let _ = chain_monitor.channel_monitor_updated(channel_id, update_id);
+				}
+			}
+		}
+	}
+
+	fn persist_or_buffer<PF: FnOnce() -> ChannelMonitorUpdateStatus>(
+		&self, monitor_name: MonitorName, channel_id: ChannelId, counterparty_node_id: PublicKey,
+		op: BufferedPersist, persist: PF,
+	) -> ChannelMonitorUpdateStatus {
+		let mut buffered = self.buffered.lock().unwrap();
+		match buffered.channels.get(&channel_id) {
+			Some(channel) if channel.permanently_failed => {
+				return ChannelMonitorUpdateStatus::UnrecoverableError;
+			},
+			// Buffer behind any pending operations to ensure they are persisted in order.
+			Some(_) => {},
+			None => {
+				let status = persist();
+				if status != ChannelMonitorUpdateStatus::UnrecoverableError {
+					return status;
+				}
+			},
+		}
+
+		let op_bytes = op.serialized_length();
+		if buffered.num_updates >= self.config.max_buffered_updates
+			|| buffered.num_bytes + op_bytes > self.config.max_buffered_bytes
+		{
+			log_error!(
+				self.logger,
+				"Unable to buffer monitor update for channel {} as the buffer is full",
+				channel_id
+			);
+			return ChannelMonitorUpdateStatus::UnrecoverableError;
+		}
+		log_info!(self.logger, "Buffering monitor update for channel {} to retry", channel_id);
+		buffered.num_updates += 1;
+		buffered.num_bytes += op_bytes;
+		let channel = buffered.channels.entry(channel_id).or_insert_with(|| BufferedChannel {
+			monitor_name,
+			counterparty_node_id,
+			pending: VecDeque::new(),
+			ticks_since_first_failure: 0,
+			ticks_until_retry: 1,
+			failed_retries: 0,
+			permanently_failed: false,
+		});
+		channel.pending.push_back(op);
+		ChannelMonitorUpdateStatus::InProgress
+	}
+}
+
+impl<ChannelSigner: EcdsaChannelSigner, P: Deref, L: Deref> Persist<ChannelSigner>
+	for RetryingPersister<P, L>
+where
+	P::Target: Persist<ChannelSigner>,
+	L::Target: Logger,
+{
+	fn persist_new_channel(
+		&self, monitor_name: MonitorName, monitor: &ChannelMonitor<ChannelSigner>,
+	) -> ChannelMonitorUpdateStatus {
+		let op = BufferedPersist::NewChannel { update_id: monitor.get_latest_update_id() };
+		self.persist_or_buffer(
+			monitor_name,
+			monitor.channel_id(),
+			monitor.get_counterparty_node_id(),
+			op,
+			|| self.persister.persist_new_channel(monitor_name, monitor),
+		)
+	}
+
+	fn update_persisted_channel(
+		&self, monitor_name: MonitorName, monitor_update: Option<&ChannelMonitorUpdate>,
+		monitor: &ChannelMonitor<ChannelSigner>,
+	) -> ChannelMonitorUpdateStatus {
+		let op = match monitor_update {
+			Some(update) => BufferedPersist::Update(update.clone()),
+			None => BufferedPersist::FullMonitor,
+		};
+		self.persist_or_buffer(
+			monitor_name,
+			monitor.channel_id(),
+			monitor.get_counterparty_node_id(),
+			op,
+			|| self.persister.update_persisted_channel(monitor_name, monitor_update, monitor),
+		)
+	}
+
+	fn archive_persisted_channel(&self, monitor_name: MonitorName) {
+		let mut buffered = self.buffered.lock().unwrap();
+		let BufferedPersists { channels, num_updates, num_bytes } = &mut *buffered;
+		channels.retain(|_, channel| {
+			if channel.monitor_name != monitor_name {
+				return true;
+			}
+			for op in channel.pending.iter() {
+				*num_updates -= 1;
+				*num_bytes -= op.serialized_length();
+			}
+			false
+		});
+		core::mem::drop(buffered);
+		self.persister.archive_persisted_channel(monitor_name);
+	}
+}
+
+impl<P: Deref, L: Deref> EventsProvider for RetryingPersister<P, L>
+where
+	L::Target: Logger,
+{
+	/// Processes any [`Event::MonitorPersistenceFailed`]s generated since the last call.
+	fn process_pending_events<H: Deref>(&self, handler: H)
+	where
+		H::Target: EventHandler,
+	{
+		let pending_events = core::mem::take(&mut *self.pending_events.lock().unwrap());
+		let mut failed_events = Vec::new();
+		for event in pending_events {
+			if handler.handle_event(event.clone()).is_err() {
+				failed_events.push(event);
+			}
+		}
+		self.pending_events.lock().unwrap().extend(failed_events);
+	}
+}
+
 /// A struct representing a name for a channel monitor.
 ///
 /// `MonitorName` is primarily used within the [`MonitorUpdatingPersister`]
@@ -1107,13 +1804,17 @@ mod tests {
 	use super::*;
 	use crate::chain::ChannelMonitorUpdateStatus;
 	use crate::events::ClosureReason;
+	use crate::ln::channelmanager::{PaymentId, RecipientOnionFields};
 	use crate::ln::functional_test_utils::*;
 	use crate::ln::msgs::BaseMessageHandler;
+	use crate::routing::gossip::NetworkGraph;
 	use crate::sync::Arc;
 	use crate::util::test_channel_signer::TestChannelSigner;
 	use crate::util::test_utils::{self, TestLogger, TestStore};
-	use crate::{check_added_monitors, check_closed_broadcast};
+	use crate::{check_added_monitors, check_closed_broadcast, get_route_and_payment_hash};
 	use bitcoin::hashes::hex::FromHex;
+	use bitcoin::network::Network;
+	use core::cell::RefCell;
 
 	const EXPECTED_UPDATES_PER_PAYMENT: u64 = 5;
 
@@ -1260,7 +1961,7 @@ mod tests {
 						persister_0
 							.kv_store
 							.list(
-								CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+								KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 								&monitor_name.to_string()
 							)
 							.unwrap()
@@ -1279,7 +1980,7 @@ mod tests {
 						persister_1
 							.kv_store
 							.list(
-								CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+								KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 								&monitor_name.to_string()
 							)
 							.unwrap()
@@ -1420,6 +2121,113 @@ mod tests {
 		nodes[1].node.get_and_clear_pending_msg_events();
 	}
 
+	#[test]
+	fn retrying_persister_retries_buffered_updates() {
+		// Test that updates which fail to persist are buffered, retried with backoff, and
+		// completed in the `ChainMonitor` once persisted.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let retrying_persister = RetryingPersister::new(
+			&chanmon_cfgs[0].persister,
+			&chanmon_cfgs[0].logger,
+			RetryingPersisterConfig::default(),
+		);
+		let mut node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		node_cfgs[0].chain_monitor = test_utils::TestChainMonitor::new(
+			Some(&chanmon_cfgs[0].chain_source),
+			&chanmon_cfgs[0].tx_broadcaster,
+			&chanmon_cfgs[0].logger,
+			&chanmon_cfgs[0].fee_estimator,
+			&retrying_persister,
+			&chanmon_cfgs[0].keys_manager,
+		);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+		create_announced_chan_between_nodes(&nodes, 0, 1);
+
+		// Fail the initial attempt to persist the update for a new payment as well as the first
+		// retry.
+		chanmon_cfgs[0].persister.set_update_ret(ChannelMonitorUpdateStatus::UnrecoverableError);
+		chanmon_cfgs[0].persister.set_update_ret(ChannelMonitorUpdateStatus::UnrecoverableError);
+		let (route, payment_hash, _, payment_secret) =
+			get_route_and_payment_hash!(nodes[0], nodes[1], 1_000_000);
+		let onion = RecipientOnionFields::secret_only(payment_secret);
+		let id = PaymentId(payment_hash.0);
+		nodes[0].node.send_payment_with_route(route, payment_hash, onion, id).unwrap();
+		check_added_monitors!(nodes[0], 1);
+		assert_eq!(retrying_persister.buffered_update_count(), 1);
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+		// The first retry fails, after which we back off for two ticks.
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+		retrying_persister.timer_tick_occurred(chain_monitor);
+		assert_eq!(retrying_persister.buffered_update_count(), 1);
+		retrying_persister.timer_tick_occurred(chain_monitor);
+		assert_eq!(retrying_persister.buffered_update_count(), 1);
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+
+		// Once the retry succeeds, the HTLC is released to the counterparty.
+		retrying_persister.timer_tick_occurred(chain_monitor);
+		assert_eq!(retrying_persister.buffered_update_count(), 0);
+		let mut events = nodes[0].node.get_and_clear_pending_msg_events();
+		assert_eq!(events.len(), 1);
+		let payment_event = SendEvent::from_event(events.pop().unwrap());
+		assert_eq!(payment_event.node_id, node_b_id);
+	}
+
+	#[test]
+	fn retrying_persister_fails_after_deadline() {
+		// Test that we give up retrying once the deadline is reached, generating an event.
+		let chanmon_cfgs = create_chanmon_cfgs(2);
+		let config = RetryingPersisterConfig { failure_deadline_ticks: 3, ..Default::default() };
+		let retrying_persister =
+			RetryingPersister::new(&chanmon_cfgs[0].persister, &chanmon_cfgs[0].logger, config);
+		let mut node_cfgs = create_node_cfgs(2, &chanmon_cfgs);
+		node_cfgs[0].chain_monitor = test_utils::TestChainMonitor::new(
+			Some(&chanmon_cfgs[0].chain_source),
+			&chanmon_cfgs[0].tx_broadcaster,
+			&chanmon_cfgs[0].logger,
+			&chanmon_cfgs[0].fee_estimator,
+			&retrying_persister,
+			&chanmon_cfgs[0].keys_manager,
+		);
+		let node_chanmgrs = create_node_chanmgrs(2, &node_cfgs, &[None, None]);
+		let nodes = create_network(2, &node_cfgs, &node_chanmgrs);
+		let node_b_id = nodes[1].node.get_our_node_id();
+		let channel_id = create_announced_chan_between_nodes(&nodes, 0, 1).2;
+
+		chanmon_cfgs[0].persister.set_update_ret(ChannelMonitorUpdateStatus::UnrecoverableError);
+		chanmon_cfgs[0].persister.set_update_ret(ChannelMonitorUpdateStatus::UnrecoverableError);
+		let (route, payment_hash, _, payment_secret) =
+			get_route_and_payment_hash!(nodes[0], nodes[1], 1_000_000);
+		let onion = RecipientOnionFields::secret_only(payment_secret);
+		let id = PaymentId(payment_hash.0);
+		nodes[0].node.send_payment_with_route(route, payment_hash, onion, id).unwrap();
+		check_added_monitors!(nodes[0], 1);
+
+		let chain_monitor = &nodes[0].chain_monitor.chain_monitor;
+		let latest_update_id =
+			chain_monitor.get_monitor(channel_id).unwrap().get_latest_update_id();
+		let events = RefCell::new(Vec::new());
+		for _ in 0..2 {
+			retrying_persister.timer_tick_occurred(chain_monitor);
+			retrying_persister.process_pending_events(&|e| Ok(events.borrow_mut().push(e)));
+			assert!(events.borrow().is_empty());
+		}
+		retrying_persister.timer_tick_occurred(chain_monitor);
+		assert_eq!(retrying_persister.buffered_update_count(), 0);
+		retrying_persister.process_pending_events(&|e| Ok(events.borrow_mut().push(e)));
+		assert_eq!(
+			events.into_inner(),
+			vec![Event::MonitorPersistenceFailed {
+				channel_id,
+				counterparty_node_id: node_b_id,
+				pending_update_ids: vec![latest_update_id],
+			}]
+		);
+		assert!(nodes[0].node.get_and_clear_pending_msg_events().is_empty());
+	}
+
 	// Confirm that the `clean_stale_updates` function finds and deletes stale updates.
 	#[test]
 	fn clean_stale_updates_works() {
@@ -1484,7 +2292,7 @@ mod tests {
 		persister_0
 			.kv_store
 			.write(
-				CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+				KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 				&monitor_name.to_string(),
 				UpdateName::from(1).as_str(),
 				vec![0u8; 1],
@@ -1498,7 +2306,7 @@ mod tests {
 		assert!(persister_0
 			.kv_store
 			.read(
-				CHANNEL_MONITOR_UPDATE_PERSISTENCE_PRIMARY_NAMESPACE,
+				KVStoreNamespace::CHANNEL_MONITOR_UPDATES.primary_namespace,
 				&monitor_name.to_string(),
 				UpdateName::from(1).as_str()
 			)
@@ -1517,4 +2325,115 @@ mod tests {
 		let store: Arc<dyn KVStoreSync + Send + Sync> = Arc::new(TestStore::new(false));
 		assert!(persist_fn::<_, TestChannelSigner>(Arc::clone(&store)));
 	}
+
+	#[test]
+	fn kvstore_namespaces_are_stable() {
+		// Renaming any of LDK's namespaces or keys would make existing data unreadable.
+		let namespaces = [
+			(KVStoreNamespace::CHANNEL_MANAGER, "", ""),
+			(KVStoreNamespace::CHANNEL_MONITORS, "monitors", ""),
+			(KVStoreNamespace::ARCHIVED_CHANNEL_MONITORS, "archived_monitors", ""),
+			(KVStoreNamespace::CHANNEL_MONITOR_UPDATES, "monitor_updates", ""),
+			(KVStoreNamespace::NETWORK_GRAPH, "", ""),
+			(KVStoreNamespace::SCORER, "", ""),
+			(KVStoreNamespace::OUTPUT_SWEEPER, "", ""),
+			(KVStoreNamespace::ANCHOR_CHANNEL_RESERVE_CONTEXT, "", ""),
+			(KVStoreNamespace::BUMP_TRANSACTION_CLAIMS, "bump_transaction_claims", ""),
+		];
+		for (namespace, primary_namespace, secondary_namespace) in namespaces {
+			assert_eq!(namespace, KVStoreNamespace::new(primary_namespace, secondary_namespace));
+		}
+		assert_eq!(CHANNEL_MANAGER_PERSISTENCE_KEY, "manager");
+		assert_eq!(NETWORK_GRAPH_PERSISTENCE_KEY, "network_graph");
+		assert_eq!(SCORER_PERSISTENCE_KEY, "scorer");
+		assert_eq!(OUTPUT_SWEEPER_PERSISTENCE_KEY, "output_sweeper");
+		assert_eq!(
+			ANCHOR_CHANNEL_RESERVE_CONTEXT_PERSISTENCE_KEY,
+			"anchor_channel_reserve_context"
+		);
+	}
+
+	#[test]
+	fn migrates_namespace() {
+		let store = TestStore::new(false);
+		let misplaced = KVStoreNamespace::new("monitor", "");
+		let monitors = KVStoreNamespace::CHANNEL_MONITORS;
+		store.write("monitor", "", "a", vec![1]).unwrap();
+		store.write("monitor", "", "b", vec![2]).unwrap();
+		// Keys which were already migrated by an interrupted migration are only removed.
+		store.write("monitors", "", "b", vec![2]).unwrap();
+		assert_eq!(migrate_namespace(&store, misplaced, monitors).unwrap(), 2);
+		assert!(store.list("monitor", "").unwrap().is_empty());
+		assert_eq!(store.read("monitors", "", "a").unwrap(), vec![1]);
+		assert_eq!(store.read("monitors", "", "b").unwrap(), vec![2]);
+		assert_eq!(migrate_namespace(&store, misplaced, monitors).unwrap(), 0);
+
+		// Keys with different data in the new namespace aren't overwritten.
+		store.write("monitor", "", "a", vec![3]).unwrap();
+		assert!(migrate_namespace(&store, misplaced, monitors).is_err());
+		assert_eq!(store.read("monitors", "", "a").unwrap(), vec![1]);
+		assert_eq!(store.read("monitor", "", "a").unwrap(), vec![3]);
+	}
+
+	#[test]
+	fn validates_store_layout() {
+		let store = TestStore::new(false);
+		let monitor_name = MonitorName::V2Channel(ChannelId([1; 32])).to_string();
+		let mut monitor = MONITOR_UPDATING_PERSISTER_PREPEND_SENTINEL.to_vec();
+		monitor.extend_from_slice(&[1, 1]);
+		let logger = TestLogger::new();
+		let mut graph = Vec::new();
+		NetworkGraph::new(Network::Testnet, &logger).write_framed(&mut graph).unwrap();
+
+		store.write("", "", CHANNEL_MANAGER_PERSISTENCE_KEY, vec![1, 1]).unwrap();
+		store.write("", "", NETWORK_GRAPH_PERSISTENCE_KEY, graph.clone()).unwrap();
+		store.write("monitors", "", &monitor_name, monitor.clone()).unwrap();
+		store.write("archived_monitors", "", &monitor_name, monitor.clone()).unwrap();
+		store.write("monitor_updates", &monitor_name, "1", vec![1, 1]).unwrap();
+		store.write("app", "", "settings", vec![1, 1]).unwrap();
+		assert!(validate_store_layout(&store).unwrap().is_empty());
+
+		store.write("app", "", &monitor_name, monitor.clone()).unwrap();
+		store.write("", "", SCORER_PERSISTENCE_KEY, graph).unwrap();
+		store.write("", "", "settings", vec![1, 1]).unwrap();
+		store.write("monitors", "", "settings", vec![1, 1]).unwrap();
+		store.write("monitor_updates", &monitor_name, "settings", vec![1, 1]).unwrap();
+
+		let issues = validate_store_layout(&store).unwrap();
+		let expected_issues = [
+			StoreLayoutIssue::MisplacedData {
+				primary_namespace: "app".to_owned(),
+				secondary_namespace: String::new(),
+				key: monitor_name.clone(),
+				expected_namespace: KVStoreNamespace::CHANNEL_MONITORS,
+				expected_key: None,
+			},
+			StoreLayoutIssue::MisplacedData {
+				primary_namespace: String::new(),
+				secondary_namespace: String::new(),
+				key: SCORER_PERSISTENCE_KEY.to_owned(),
+				expected_namespace: KVStoreNamespace::NETWORK_GRAPH,
+				expected_key: Some(NETWORK_GRAPH_PERSISTENCE_KEY),
+			},
+			StoreLayoutIssue::UnknownKey {
+				primary_namespace: String::new(),
+				secondary_namespace: String::new(),
+				key: "settings".to_owned(),
+			},
+			StoreLayoutIssue::UnknownKey {
+				primary_namespace: "monitors".to_owned(),
+				secondary_namespace: String::new(),
+				key: "settings".to_owned(),
+			},
+			StoreLayoutIssue::UnknownKey {
+				primary_namespace: "monitor_updates".to_owned(),
+				secondary_namespace: monitor_name,
+				key: "settings".to_owned(),
+			},
+		];
+		assert_eq!(issues.len(), expected_issues.len());
+		for issue in expected_issues.iter() {
+			assert!(issues.contains(issue));
+		}
+	}
 }
@@ -0,0 +1,57 @@
+// This file is Copyright its original authors, visible in version control
+// history.
+//
+// This file is licensed under the Apache License, Version 2.0 <LICENSE-APACHE
+// or http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your option.
+// You may not use this file except in accordance with one or both of these
+// licenses.
+
+//! Helpers to (de)serialize `bitcoin` types with `serde` as plain integers of a fixed
+//! denomination, for use with `#[serde(with = "...")]`.
+//!
+//! Unlike the `serde` implementations of `bitcoin`, whose representation of e.g. a [`FeeRate`]
+//! isn't denominated in its field name, these keep JSON representations self-describing when
+//! combined with a unit suffix on the field name, such as `_sat` or `_sat_per_kwu`.
+
+use bitcoin::{Amount, FeeRate, Weight};
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// (De)serializes an [`Amount`] as an integer number of satoshis.
+pub(crate) mod amount_sat {
+	use super::*;
+
+	pub(crate) fn serialize<S: Serializer>(amount: &Amount, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_u64(amount.to_sat())
+	}
+
+	pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Amount, D::Error> {
+		u64::deserialize(d).map(Amount::from_sat)
+	}
+}
+
+/// (De)serializes a [`FeeRate`] as an integer number of satoshis per 1000 weight units.
+pub(crate) mod fee_rate_sat_per_kwu {
+	use super::*;
+
+	pub(crate) fn serialize<S: Serializer>(fee_rate: &FeeRate, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_u64(fee_rate.to_sat_per_kwu())
+	}
+
+	pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<FeeRate, D::Error> {
+		u64::deserialize(d).map(FeeRate::from_sat_per_kwu)
+	}
+}
+
+/// (De)serializes a [`Weight`] as an integer number of weight units.
+pub(crate) mod weight_wu {
+	use super::*;
+
+	pub(crate) fn serialize<S: Serializer>(weight: &Weight, s: S) -> Result<S::Ok, S::Error> {
+		s.serialize_u64(weight.to_wu())
+	}
+
+	pub(crate) fn deserialize<'de, D: Deserializer<'de>>(d: D) -> Result<Weight, D::Error> {
+		u64::deserialize(d).map(Weight::from_wu)
+	}
+}
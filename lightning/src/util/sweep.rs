@@ -10,6 +10,7 @@
 
 use crate::chain::chaininterface::{BroadcasterInterface, ConfirmationTarget, FeeEstimator};
 use crate::chain::channelmonitor::{ANTI_REORG_DELAY, ARCHIVAL_DELAY_BLOCKS};
+use crate::chain::transaction::OutPoint;
 use crate::chain::{self, BestBlock, Confirm, Filter, Listen, WatchedOutput};
 use crate::io;
 use crate::ln::msgs::DecodeError;
@@ -21,10 +22,10 @@ use crate::sign::{
 };
 use crate::sync::Arc;
 use crate::sync::Mutex;
+use crate::util::anchor_channel_reserves::{get_reserve_per_channel, AnchorChannelReserveContext};
 use crate::util::logger::Logger;
 use crate::util::persist::{
-	KVStore, KVStoreSync, KVStoreSyncWrapper, OUTPUT_SWEEPER_PERSISTENCE_KEY,
-	OUTPUT_SWEEPER_PERSISTENCE_PRIMARY_NAMESPACE, OUTPUT_SWEEPER_PERSISTENCE_SECONDARY_NAMESPACE,
+	KVStore, KVStoreNamespace, KVStoreSync, KVStoreSyncWrapper, OUTPUT_SWEEPER_PERSISTENCE_KEY,
 };
 use crate::util::ser::{Readable, ReadableArgs, Writeable};
 use crate::{impl_writeable_tlv_based, log_debug, log_error};
@@ -32,8 +33,9 @@ use crate::{impl_writeable_tlv_based, log_debug, log_error};
 use bitcoin::block::Header;
 use bitcoin::locktime::absolute::LockTime;
 use bitcoin::secp256k1::Secp256k1;
-use bitcoin::{BlockHash, ScriptBuf, Transaction, Txid};
+use bitcoin::{Amount, BlockHash, ScriptBuf, Transaction, TxOut, Txid};
 
+use core::cmp::min;
 use core::future::Future;
 use core::ops::Deref;
 use core::pin::Pin;
@@ -328,6 +330,69 @@ impl_writeable_tlv_based_enum!(OutputSpendStatus,
 	},
 );
 
+/// Configures an [`OutputSweeper`] to split swept funds into outputs sized to provide the anchor
+/// channel reserve of a channel each, such that they can be earmarked as reserve UTXOs by the
+/// wallet, as set via [`OutputSweeper::set_reserve_split`].
+///
+/// Each reserve output is worth [`get_reserve_per_channel`] for the given [`Self::context`] and
+/// pays to the change destination script, along with the remainder of the swept funds. The fees
+/// for the reserve outputs are paid at the fee rate of the sweep, reducing the remainder.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SweepReserveSplit {
+	/// The context determining the value of each reserve output.
+	pub context: AnchorChannelReserveContext,
+	/// The number of reserve outputs to create per sweep, i.e., the number of channels whose
+	/// reserve should be replenished by a sweep if the swept value suffices.
+	pub target_channels: u16,
+	/// The maximum number of reserve outputs to create per sweep, bounding the size of the
+	/// sweeping transaction regardless of [`Self::target_channels`].
+	pub max_outputs: u16,
+	/// The minimum value of the remainder sent to the change destination script as a separate
+	/// output. Fewer reserve outputs are created rather than leaving a smaller remainder, which
+	/// would be uneconomical to spend or would be added to the fee if below the dust limit.
+	pub dust_floor: Amount,
+}
+
+/// Returns the reserve outputs to add to a sweep of the given `descriptors` as configured by
+/// `reserve_split`, or none if the swept value doesn't suffice for a single reserve output along
+/// with a remainder of at least [`SweepReserveSplit::dust_floor`].
+fn reserve_split_outputs(
+	reserve_split: &SweepReserveSplit, descriptors: &[&SpendableOutputDescriptor],
+	change_destination_script: &ScriptBuf, feerate_sat_per_1000_weight: u32,
+	locktime: Option<LockTime>,
+) -> Vec<TxOut> {
+	let reserve_per_channel = get_reserve_per_channel(&reserve_split.context);
+	if reserve_per_channel < change_destination_script.minimal_non_dust() {
+		return Vec::new();
+	}
+	let swept_value: Amount = descriptors.iter().map(|descriptor| descriptor.output().value).sum();
+	let max_outputs = min(reserve_split.target_channels, reserve_split.max_outputs) as u64;
+	let max_outputs = min(max_outputs, swept_value.to_sat() / reserve_per_channel.to_sat());
+	let reserve_output =
+		TxOut { value: reserve_per_channel, script_pubkey: change_destination_script.clone() };
+	let secp_ctx = Secp256k1::signing_only();
+	// The fees for the reserve outputs are only known once the transaction is built, so we build it
+	// with fewer outputs until the remainder suffices.
+	for num_outputs in (1..=max_outputs as usize).rev() {
+		let outputs = vec![reserve_output.clone(); num_outputs];
+		let psbt = SpendableOutputDescriptor::create_spendable_outputs_psbt(
+			&secp_ctx,
+			descriptors,
+			outputs.clone(),
+			change_destination_script.clone(),
+			feerate_sat_per_1000_weight,
+			locktime,
+		);
+		if let Ok((psbt, _)) = psbt {
+			let remainder = psbt.unsigned_tx.output.get(num_outputs);
+			if remainder.map_or(false, |output| output.value >= reserve_split.dust_floor) {
+				return outputs;
+			}
+		}
+	}
+	Vec::new()
+}
+
 /// A utility that keeps track of [`SpendableOutputDescriptor`]s, persists them in a given
 /// [`KVStoreSync`] and regularly retries sweeping them based on a callback given to the constructor
 /// methods.
@@ -361,6 +426,7 @@ where
 	change_destination_source: D,
 	kv_store: K,
 	logger: L,
+	reserve_split: Mutex<Option<SweepReserveSplit>>,
 }
 
 impl<B: Deref, D: Deref, E: Deref, F: Deref, K: Deref, L: Deref, O: Deref>
@@ -435,6 +501,7 @@ where
 			change_destination_source,
 			kv_store,
 			logger,
+			reserve_split: Mutex::new(None),
 		}
 	}
 
@@ -502,12 +569,32 @@ where
 		self.sweeper_state.lock().unwrap().outputs.clone()
 	}
 
+	/// Returns the currently tracked spendable output with the given outpoint, as given by
+	/// [`SpendableOutputDescriptor::spendable_outpoint`], if any.
+	///
+	/// This allows looking up the sweep status of the output of a
+	/// [`ResolutionTimelineEntry::spendable_outpoint`] once it has been handed over.
+	///
+	/// [`ResolutionTimelineEntry::spendable_outpoint`]: crate::chain::channelmonitor::ResolutionTimelineEntry::spendable_outpoint
+	pub fn tracked_spendable_output(&self, outpoint: &OutPoint) -> Option<TrackedSpendableOutput> {
+		let state_lock = self.sweeper_state.lock().unwrap();
+		state_lock.outputs.iter().find(|o| o.descriptor.spendable_outpoint() == *outpoint).cloned()
+	}
+
 	/// Gets the latest best block which was connected either via the [`Listen`] or
 	/// [`Confirm`] interfaces.
 	pub fn current_best_block(&self) -> BestBlock {
 		self.sweeper_state.lock().unwrap().best_block
 	}
 
+	/// Sets the [`SweepReserveSplit`] used to split swept funds into anchor channel reserve outputs,
+	/// or disables the split if `None`, which is the default.
+	///
+	/// This is not persisted and needs to be set again after reloading the [`OutputSweeper`].
+	pub fn set_reserve_split(&self, reserve_split: Option<SweepReserveSplit>) {
+		*self.reserve_split.lock().unwrap() = reserve_split;
+	}
+
 	/// Regenerates and broadcasts the spending transaction for any outputs that are pending. This method will be a
 	/// no-op if a sweep is already pending.
 	pub async fn regenerate_and_broadcast_spend_if_necessary(&self) -> Result<(), ()> {
@@ -656,8 +743,8 @@ where
 		let encoded = sweeper_state.encode();
 
 		self.kv_store.write(
-			OUTPUT_SWEEPER_PERSISTENCE_PRIMARY_NAMESPACE,
-			OUTPUT_SWEEPER_PERSISTENCE_SECONDARY_NAMESPACE,
+			KVStoreNamespace::OUTPUT_SWEEPER.primary_namespace,
+			KVStoreNamespace::OUTPUT_SWEEPER.secondary_namespace,
 			OUTPUT_SWEEPER_PERSISTENCE_KEY,
 			encoded,
 		)
@@ -699,9 +786,19 @@ where
 			self.fee_estimator.get_est_sat_per_1000_weight(ConfirmationTarget::OutputSpendingFee);
 		let cur_height = sweeper_state.best_block.height;
 		let locktime = Some(LockTime::from_height(cur_height).unwrap_or(LockTime::ZERO));
+		let outputs = match self.reserve_split.lock().unwrap().as_ref() {
+			Some(reserve_split) => reserve_split_outputs(
+				reserve_split,
+				descriptors,
+				&change_destination_script,
+				tx_feerate,
+				locktime,
+			),
+			None => Vec::new(),
+		};
 		self.output_spender.spend_spendable_outputs(
 			descriptors,
-			Vec::new(),
+			outputs,
 			change_destination_script,
 			tx_feerate,
 			locktime,
@@ -924,6 +1021,7 @@ where
 			change_destination_source,
 			kv_store,
 			logger,
+			reserve_split: Mutex::new(None),
 		})
 	}
 }
@@ -975,6 +1073,7 @@ where
 				change_destination_source,
 				kv_store,
 				logger,
+				reserve_split: Mutex::new(None),
 			},
 		))
 	}
@@ -1080,6 +1179,17 @@ where
 		self.sweeper.tracked_spendable_outputs()
 	}
 
+	/// Returns the currently tracked spendable output with the given outpoint, if any. Wraps
+	/// [`OutputSweeper::tracked_spendable_output`].
+	pub fn tracked_spendable_output(&self, outpoint: &OutPoint) -> Option<TrackedSpendableOutput> {
+		self.sweeper.tracked_spendable_output(outpoint)
+	}
+
+	/// Wrapper around [`OutputSweeper::set_reserve_split`].
+	pub fn set_reserve_split(&self, reserve_split: Option<SweepReserveSplit>) {
+		self.sweeper.set_reserve_split(reserve_split)
+	}
+
 	/// Returns the inner async sweeper for testing purposes.
 	#[cfg(any(test, feature = "_test_utils"))]
 	pub fn sweeper_async(
@@ -1128,3 +1238,117 @@ where
 		self.sweeper.get_relevant_txids()
 	}
 }
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	use crate::chain::transaction::OutPoint;
+
+	use bitcoin::hashes::Hash;
+	use bitcoin::WPubkeyHash;
+
+	fn static_output(value: Amount) -> SpendableOutputDescriptor {
+		SpendableOutputDescriptor::StaticOutput {
+			outpoint: OutPoint { txid: Txid::all_zeros(), index: 0 },
+			output: TxOut {
+				value,
+				script_pubkey: ScriptBuf::new_p2wpkh(&WPubkeyHash::all_zeros()),
+			},
+			channel_keys_id: None,
+		}
+	}
+
+	#[test]
+	fn test_reserve_split_outputs() {
+		let reserve_split = SweepReserveSplit {
+			context: AnchorChannelReserveContext::default(),
+			target_channels: 3,
+			max_outputs: 5,
+			dust_floor: Amount::from_sat(1_000),
+		};
+		let reserve_per_channel = get_reserve_per_channel(&reserve_split.context);
+		let change_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([1; 20]));
+		let feerate = 2_000;
+		let num_reserve_outputs = |swept_value: Amount, reserve_split: &SweepReserveSplit| {
+			let descriptor = static_output(swept_value);
+			let outputs =
+				reserve_split_outputs(reserve_split, &[&descriptor], &change_script, feerate, None);
+			for output in outputs.iter() {
+				assert_eq!(output.value, reserve_per_channel);
+				assert_eq!(output.script_pubkey, change_script);
+			}
+			outputs.len()
+		};
+
+		// Less than a single reserve output is swept as usual.
+		assert_eq!(num_reserve_outputs(reserve_per_channel, &reserve_split), 0);
+		assert_eq!(num_reserve_outputs(reserve_per_channel / 2, &reserve_split), 0);
+		// The remainder has to cover the fees along with the dust floor.
+		let value = reserve_per_channel + Amount::from_sat(1_000);
+		assert_eq!(num_reserve_outputs(value, &reserve_split), 0);
+		let value = reserve_per_channel + Amount::from_sat(10_000);
+		assert_eq!(num_reserve_outputs(value, &reserve_split), 1);
+		let value = reserve_per_channel * 2 + Amount::from_sat(1_000);
+		assert_eq!(num_reserve_outputs(value, &reserve_split), 1);
+		let value = reserve_per_channel * 2 + Amount::from_sat(10_000);
+		assert_eq!(num_reserve_outputs(value, &reserve_split), 2);
+		// The number of outputs is capped by the target and the maximum number of outputs.
+		let value = reserve_per_channel * 10;
+		assert_eq!(num_reserve_outputs(value, &reserve_split), 3);
+		let capped_split = SweepReserveSplit { max_outputs: 2, ..reserve_split.clone() };
+		assert_eq!(num_reserve_outputs(value, &capped_split), 2);
+		let disabled_split = SweepReserveSplit { target_channels: 0, ..reserve_split.clone() };
+		assert_eq!(num_reserve_outputs(value, &disabled_split), 0);
+	}
+
+	#[test]
+	fn test_reserve_split_fees() {
+		// The fees for the reserve outputs are paid at the sweep's fee rate, out of the remainder.
+		let reserve_split = SweepReserveSplit {
+			context: AnchorChannelReserveContext::default(),
+			target_channels: 3,
+			max_outputs: 3,
+			dust_floor: Amount::from_sat(1_000),
+		};
+		let reserve_per_channel = get_reserve_per_channel(&reserve_split.context);
+		let change_script = ScriptBuf::new_p2wpkh(&WPubkeyHash::from_byte_array([1; 20]));
+		let feerate = 2_000;
+		let swept_value = reserve_per_channel * 5;
+		let descriptor = static_output(swept_value);
+		let outputs =
+			reserve_split_outputs(&reserve_split, &[&descriptor], &change_script, feerate, None);
+		assert_eq!(outputs.len(), 3);
+
+		let secp_ctx = Secp256k1::new();
+		let (plain_psbt, plain_weight) = SpendableOutputDescriptor::create_spendable_outputs_psbt(
+			&secp_ctx,
+			&[&descriptor],
+			Vec::new(),
+			change_script.clone(),
+			feerate,
+			None,
+		)
+		.unwrap();
+		let (split_psbt, split_weight) = SpendableOutputDescriptor::create_spendable_outputs_psbt(
+			&secp_ctx,
+			&[&descriptor],
+			outputs,
+			change_script.clone(),
+			feerate,
+			None,
+		)
+		.unwrap();
+		assert_eq!(split_psbt.unsigned_tx.output.len(), 4);
+		assert!(split_weight > plain_weight);
+
+		let fee = |tx: &Transaction| {
+			swept_value - tx.output.iter().map(|output| output.value).sum::<Amount>()
+		};
+		let plain_fee = fee(&plain_psbt.unsigned_tx);
+		let split_fee = fee(&split_psbt.unsigned_tx);
+		assert!(split_fee.to_sat() >= split_weight * feerate as u64 / 1000);
+		let extra_weight = split_weight - plain_weight;
+		assert!(split_fee - plain_fee >= Amount::from_sat(extra_weight * feerate as u64 / 1000));
+	}
+}
@@ -15,17 +15,18 @@ use crate::chain::chaininterface;
 use crate::chain::chaininterface::ConfirmationTarget;
 #[cfg(any(test, feature = "_externalize_tests"))]
 use crate::chain::chaininterface::FEERATE_FLOOR_SATS_PER_KW;
-use crate::chain::chainmonitor::{ChainMonitor, Persist};
+use crate::chain::chainmonitor::{ChainMonitor, MonitorSummary, Persist};
 use crate::chain::channelmonitor::{
-	ChannelMonitor, ChannelMonitorUpdate, ChannelMonitorUpdateStep, MonitorEvent,
+	Balance, ChannelMonitor, ChannelMonitorUpdate, ChannelMonitorUpdateStep, MonitorEvent,
 };
 use crate::chain::transaction::OutPoint;
 use crate::chain::WatchedOutput;
 use crate::events::bump_transaction::sync::WalletSourceSync;
 use crate::events::bump_transaction::Utxo;
+use crate::events::EventHandler;
 #[cfg(any(test, feature = "_externalize_tests"))]
 use crate::ln::chan_utils::CommitmentTransaction;
-use crate::ln::channel_state::ChannelDetails;
+use crate::ln::channel_state::{ChannelDetails, PendingChannelNegotiation};
 use crate::ln::channelmanager;
 use crate::ln::inbound_payment::ExpandedKey;
 use crate::ln::msgs::{BaseMessageHandler, MessageSendEvent};
@@ -49,15 +50,19 @@ use crate::routing::utxo::{UtxoLookup, UtxoLookupError, UtxoResult};
 use crate::sign::{self, ReceiveAuthKey};
 use crate::sign::{ChannelSigner, PeerStorageKey};
 use crate::sync::RwLock;
-use crate::types::features::{ChannelFeatures, InitFeatures, NodeFeatures};
+use crate::types::features::{ChannelFeatures, ChannelTypeFeatures, InitFeatures, NodeFeatures};
+use crate::util::anchor_channel_reserves::is_anchor_channel_type;
 use crate::util::config::UserConfig;
+use crate::util::deadlines::DeadlineKind;
 use crate::util::dyn_signer::{
 	DynKeysInterface, DynKeysInterfaceTrait, DynPhantomKeysInterface, DynSigner,
 };
+use crate::util::facade::{ChainMonitorFacade, ChannelManagerFacade};
 use crate::util::logger::{Logger, Record};
+use crate::util::message_signing::MessageSigningKeyId;
 #[cfg(feature = "std")]
 use crate::util::mut_global::MutGlobal;
-use crate::util::persist::{KVStoreSync, MonitorName};
+use crate::util::persist::{KVStoreSync, MigratableKVStore, MonitorName};
 use crate::util::ser::{Readable, ReadableArgs, Writeable, Writer};
 use crate::util::test_channel_signer::{EnforcementState, TestChannelSigner};
 
@@ -67,6 +72,7 @@ use bitcoin::constants::genesis_block;
 use bitcoin::constants::ChainHash;
 use bitcoin::hash_types::{BlockHash, Txid};
 use bitcoin::hashes::Hash;
+use bitcoin::key::TweakedPublicKey;
 use bitcoin::network::Network;
 use bitcoin::opcodes;
 use bitcoin::script::{Builder, Script, ScriptBuf};
@@ -90,6 +96,7 @@ use core::time::Duration;
 
 use bitcoin::psbt::Psbt;
 use bitcoin::Sequence;
+use bitcoin::{PubkeyHash, WPubkeyHash};
 
 use super::test_channel_signer::SignerOp;
 
@@ -300,6 +307,15 @@ impl<'a> Router for TestRouter<'a> {
 			Ok(core::mem::take(&mut *expected_paths))
 		}
 	}
+
+	fn has_known_channels(&self, node_id: &PublicKey) -> Option<bool> {
+		// Expected routes may use channels that aren't in the network graph.
+		if self.next_routes.lock().unwrap().is_empty() {
+			self.router.has_known_channels(node_id)
+		} else {
+			None
+		}
+	}
 }
 
 impl<'a> Drop for TestRouter<'a> {
@@ -919,6 +935,22 @@ impl KVStoreSync for TestStore {
 	}
 }
 
+impl MigratableKVStore for TestStore {
+	fn list_all_keys(&self) -> io::Result<Vec<(String, String, String)>> {
+		let persisted_lock = self.persisted_bytes.lock().unwrap();
+		let mut keys = Vec::new();
+		for (prefixed, outer_ref) in persisted_lock.iter() {
+			let mut namespaces = prefixed.splitn(2, '/');
+			let primary_namespace = namespaces.next().unwrap_or("").to_string();
+			let secondary_namespace = namespaces.next().unwrap_or("").to_string();
+			for key in outer_ref.keys() {
+				keys.push((primary_namespace.clone(), secondary_namespace.clone(), key.clone()));
+			}
+		}
+		Ok(keys)
+	}
+}
+
 unsafe impl Sync for TestStore {}
 unsafe impl Send for TestStore {}
 
@@ -1013,6 +1045,7 @@ pub struct TestChannelMessageHandler {
 	pub pending_events: Mutex<Vec<MessageSendEvent>>,
 	expected_recv_msgs: Mutex<Option<Vec<wire::Message<()>>>>,
 	pub conn_tracker: ConnectionTracker,
+	pub peers_with_pending_htlcs: Mutex<HashSet<PublicKey>>,
 	chain_hash: ChainHash,
 }
 
@@ -1028,6 +1061,7 @@ impl TestChannelMessageHandler {
 			pending_events: Mutex::new(Vec::new()),
 			expected_recv_msgs: Mutex::new(None),
 			conn_tracker: ConnectionTracker::new(),
+			peers_with_pending_htlcs: Mutex::new(new_hash_set()),
 			chain_hash,
 		}
 	}
@@ -1215,6 +1249,10 @@ impl msgs::ChannelMessageHandler for TestChannelMessageHandler {
 	}
 
 	fn message_received(&self) {}
+
+	fn peer_has_pending_htlcs(&self, their_node_id: PublicKey) -> bool {
+		self.peers_with_pending_htlcs.lock().unwrap().contains(&their_node_id)
+	}
 }
 
 impl msgs::BaseMessageHandler for TestChannelMessageHandler {
@@ -1601,6 +1639,16 @@ impl NodeSigner for TestNodeSigner {
 	fn sign_message(&self, msg: &[u8]) -> Result<String, ()> {
 		Ok(crate::util::message_signing::sign(msg, &self.node_secret))
 	}
+
+	fn get_message_signing_pubkey(&self, _key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		unreachable!()
+	}
+
+	fn sign_message_with_derived_key(
+		&self, _msg: &[u8], _key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		unreachable!()
+	}
 }
 
 pub struct TestKeysInterface {
@@ -1667,6 +1715,16 @@ impl NodeSigner for TestKeysInterface {
 	fn sign_message(&self, msg: &[u8]) -> Result<String, ()> {
 		self.backing.sign_message(msg)
 	}
+
+	fn get_message_signing_pubkey(&self, key_id: MessageSigningKeyId) -> Result<PublicKey, ()> {
+		self.backing.get_message_signing_pubkey(key_id)
+	}
+
+	fn sign_message_with_derived_key(
+		&self, msg: &[u8], key_id: MessageSigningKeyId,
+	) -> Result<String, ()> {
+		self.backing.sign_message_with_derived_key(msg, key_id)
+	}
 }
 
 impl SignerProvider for TestKeysInterface {
@@ -2028,3 +2086,220 @@ impl WalletSourceSync for TestWalletSource {
 		Ok(tx)
 	}
 }
+
+fn test_utxo_outpoint(vout: u32) -> bitcoin::OutPoint {
+	bitcoin::OutPoint { txid: Txid::from_byte_array([42; 32]), vout }
+}
+
+/// Returns a P2PKH [`Utxo`] of the given `value`, e.g., to provide an anchor channel reserve.
+pub fn test_p2pkh_utxo(vout: u32, value: Amount) -> Utxo {
+	Utxo::new_p2pkh(test_utxo_outpoint(vout), value, &PubkeyHash::all_zeros())
+}
+
+/// Returns a P2WPKH nested in P2SH [`Utxo`] of the given `value`.
+pub fn test_nested_p2wpkh_utxo(vout: u32, value: Amount) -> Utxo {
+	Utxo::new_nested_p2wpkh(test_utxo_outpoint(vout), value, &WPubkeyHash::all_zeros())
+}
+
+/// Returns a P2WPKH [`Utxo`] of the given `value`.
+pub fn test_p2wpkh_utxo(vout: u32, value: Amount) -> Utxo {
+	Utxo::new_p2wpkh(test_utxo_outpoint(vout), value, &WPubkeyHash::all_zeros())
+}
+
+/// Returns a P2TR [`Utxo`] of the given `value`, spent via its key path.
+pub fn test_p2tr_utxo(vout: u32, value: Amount) -> Utxo {
+	let secp_ctx = Secp256k1::signing_only();
+	let secret_key = SecretKey::from_slice(&[42; 32]).unwrap();
+	let (output_key, _) = PublicKey::from_secret_key(&secp_ctx, &secret_key).x_only_public_key();
+	let output_key = TweakedPublicKey::dangerous_assume_tweaked(output_key);
+	Utxo::new_p2tr(test_utxo_outpoint(vout), value, output_key)
+}
+
+/// A [`ChainMonitorFacade`] over synthetic monitors with a chosen channel type and claimable
+/// balances, allowing to test logic relying on monitor summaries, such as the anchor channel
+/// reserve checks, without resolving channels on-chain.
+pub struct TestChainMonitorFacade {
+	monitors: Vec<(MonitorSummary, Vec<Balance>)>,
+}
+
+impl TestChainMonitorFacade {
+	pub fn new() -> Self {
+		Self { monitors: Vec::new() }
+	}
+
+	/// Adds a monitor of the channel with the given `channel_id` and `channel_type_features`,
+	/// whose summary is derived from its claimable `balances`.
+	///
+	/// The monitor of an open channel has a single [`Balance::ClaimableOnChannelClose`], while
+	/// the monitor of a fully resolved channel has none. Any other balance indicates that the
+	/// channel is being resolved on-chain.
+	pub fn with_monitor(
+		mut self, channel_id: ChannelId, channel_type_features: ChannelTypeFeatures,
+		balances: Vec<Balance>,
+	) -> Self {
+		let summary = MonitorSummary {
+			channel_id,
+			channel_type_features,
+			has_claimable_balances: !balances.is_empty(),
+			needs_exogenous_fee_reserve: balances
+				.iter()
+				.any(|balance| balance.needs_exogenous_fee_reserve()),
+			reserve_release_height: None,
+			funding_spend_seen: balances
+				.iter()
+				.any(|balance| !matches!(balance, Balance::ClaimableOnChannelClose { .. })),
+			pending_claim_external_inputs_value: Amount::ZERO,
+		};
+		self.monitors.push((summary, balances));
+		self
+	}
+
+	/// Sets the value of the external inputs attached to the pending claims of the monitor added
+	/// last.
+	pub fn with_pending_claim_external_inputs_value(mut self, value: Amount) -> Self {
+		let (summary, _) = self.monitors.last_mut().expect("No monitor added");
+		summary.pending_claim_external_inputs_value = value;
+		self
+	}
+
+	/// Adds a monitor of an open channel with the given `channel_type_features`, whose balance of
+	/// `amount_satoshis` is claimable on channel close.
+	pub fn with_open_channel_monitor(
+		self, channel_id: ChannelId, channel_type_features: ChannelTypeFeatures,
+		amount_satoshis: u64,
+	) -> Self {
+		let balance = Balance::ClaimableOnChannelClose {
+			amount_satoshis,
+			transaction_fee_satoshis: 0,
+			outbound_payment_htlc_rounded_msat: 0,
+			outbound_forwarded_htlc_rounded_msat: 0,
+			inbound_claiming_htlc_rounded_msat: 0,
+			inbound_htlc_rounded_msat: 0,
+		};
+		self.with_monitor(channel_id, channel_type_features, vec![balance])
+	}
+}
+
+impl ChainMonitorFacade for TestChainMonitorFacade {
+	fn list_monitors(&self) -> Vec<ChannelId> {
+		self.monitors.iter().map(|(summary, _)| summary.channel_id).collect()
+	}
+
+	fn list_monitor_summaries(&self) -> Vec<MonitorSummary> {
+		self.monitors.iter().map(|(summary, _)| summary.clone()).collect()
+	}
+
+	fn for_each_monitor_summary(&self, include_resolved: bool, f: &mut dyn FnMut(&MonitorSummary)) {
+		for (summary, _) in self.monitors.iter() {
+			if include_resolved || summary.has_claimable_balances {
+				f(summary);
+			}
+		}
+	}
+
+	fn count_monitors_matching(
+		&self, include_resolved: bool, predicate: &mut dyn FnMut(&MonitorSummary) -> bool,
+	) -> usize {
+		let mut count = 0;
+		self.for_each_monitor_summary(include_resolved, &mut |summary| {
+			if predicate(summary) {
+				count += 1;
+			}
+		});
+		count
+	}
+
+	fn get_claimable_balances(&self, ignored_channels: &[&ChannelDetails]) -> Vec<Balance> {
+		self.monitors
+			.iter()
+			.filter(|(summary, _)| {
+				!ignored_channels.iter().any(|chan| chan.channel_id == summary.channel_id)
+			})
+			.flat_map(|(_, balances)| balances.iter().cloned())
+			.collect()
+	}
+
+	fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+		Vec::new()
+	}
+
+	fn process_pending_events(&self, _handler: &dyn EventHandler) {}
+}
+
+/// A [`ChannelManagerFacade`] listing the given channels and pending channel negotiations, to be
+/// used along with a [`TestChainMonitorFacade`].
+pub struct TestChannelManagerFacade {
+	node_id: PublicKey,
+	channels: Vec<ChannelDetails>,
+	pending_negotiations: Vec<PendingChannelNegotiation>,
+	anchor_negotiation_enabled: AtomicBool,
+}
+
+impl TestChannelManagerFacade {
+	pub fn new(node_id: PublicKey) -> Self {
+		Self {
+			node_id,
+			channels: Vec::new(),
+			pending_negotiations: Vec::new(),
+			anchor_negotiation_enabled: AtomicBool::new(true),
+		}
+	}
+
+	pub fn with_channel(mut self, channel: ChannelDetails) -> Self {
+		self.channels.push(channel);
+		self
+	}
+
+	pub fn with_pending_channel_negotiation(
+		mut self, negotiation: PendingChannelNegotiation,
+	) -> Self {
+		self.pending_negotiations.push(negotiation);
+		self
+	}
+}
+
+impl ChannelManagerFacade for TestChannelManagerFacade {
+	fn get_our_node_id(&self) -> PublicKey {
+		self.node_id
+	}
+
+	fn list_channels(&self) -> Vec<ChannelDetails> {
+		self.channels.clone()
+	}
+
+	fn list_usable_channels(&self) -> Vec<ChannelDetails> {
+		self.channels.iter().filter(|channel| channel.is_usable).cloned().collect()
+	}
+
+	fn list_pending_channel_negotiations(&self) -> Vec<PendingChannelNegotiation> {
+		self.pending_negotiations.clone()
+	}
+
+	fn list_channels_requiring_anchor_reserves(&self) -> Vec<ChannelId> {
+		let channels = self
+			.channels
+			.iter()
+			.filter(|channel| channel.channel_type.as_ref().map_or(false, is_anchor_channel_type))
+			.map(|channel| channel.channel_id);
+		let negotiations = self
+			.pending_negotiations
+			.iter()
+			.filter(|negotiation| is_anchor_channel_type(&negotiation.channel_type))
+			.map(|negotiation| negotiation.temporary_channel_id);
+		channels.chain(negotiations).collect()
+	}
+
+	fn get_htlc_deadlines(&self) -> Vec<(u32, DeadlineKind, ChannelId, Amount)> {
+		Vec::new()
+	}
+
+	fn is_anchor_negotiation_enabled(&self) -> bool {
+		self.anchor_negotiation_enabled.load(Ordering::Acquire)
+	}
+
+	fn set_anchor_negotiation_enabled(&self, enabled: bool) {
+		self.anchor_negotiation_enabled.store(enabled, Ordering::Release);
+	}
+
+	fn process_pending_events(&self, _handler: &dyn EventHandler) {}
+}
@@ -9,14 +9,13 @@
 
 use bitcoin::amount::Amount;
 use bitcoin::consensus::encode::VarInt;
-use bitcoin::consensus::Encodable;
 use bitcoin::script::ScriptBuf;
 use bitcoin::transaction::{Transaction, TxOut};
 
 #[allow(unused_imports)]
 use crate::prelude::*;
 
-use crate::io_extras::sink;
+use crate::ln::script::destination_script_output_weight;
 use core::cmp::Ordering;
 
 pub fn sort_outputs<T, C: Fn(&T, &T) -> Ordering>(outputs: &mut Vec<(TxOut, T)>, tie_breaker: C) {
@@ -51,11 +50,11 @@ pub(crate) fn maybe_add_change_output(
 	}
 
 	let dust_value = change_destination_script.minimal_non_dust();
+	let change_weight = destination_script_output_weight(&change_destination_script);
 	let mut change_output = TxOut { script_pubkey: change_destination_script, value: Amount::ZERO };
-	let change_len = change_output.consensus_encode(&mut sink()).unwrap();
 	let starting_weight = tx.weight().to_wu() + WITNESS_FLAG_BYTES + witness_max_weight as u64;
 	let starting_fees = (starting_weight as i64) * feerate_sat_per_1000_weight as i64 / 1000;
-	let mut weight_with_change: i64 = starting_weight as i64 + change_len as i64 * 4;
+	let mut weight_with_change: i64 = starting_weight as i64 + change_weight as i64;
 	// Include any extra bytes required to push an extra output.
 	let num_outputs = tx.output.len() as u64;
 	weight_with_change += (VarInt(num_outputs + 1).size() - VarInt(num_outputs).size()) as i64 * 4;